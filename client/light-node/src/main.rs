@@ -0,0 +1,88 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::{bail, Result};
+use diem_client::{BlockingClient, FileStorage, LightClient};
+use diem_types::{account_address::AccountAddress, waypoint::Waypoint};
+use std::{path::PathBuf, thread, time::Duration};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "diem-light-node",
+    about = "Follow the chain as a light client, syncing only ledger infos and epoch changes from \
+             a full node, and verify payments against them."
+)]
+struct Args {
+    #[structopt(long)]
+    /// JSON-RPC endpoint of the full node to sync from
+    url: String,
+    #[structopt(long, parse(from_os_str))]
+    /// Directory the trusted state is kept in, to carry on from it on restart
+    data_dir: PathBuf,
+    #[structopt(long)]
+    /// Waypoint to trust, required unless a trusted state ahead of it is kept in the data dir
+    waypoint: Option<Waypoint>,
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Keep syncing to the latest ledger info of the full node
+    Follow {
+        #[structopt(long, default_value = "10")]
+        /// Seconds between syncs
+        interval_secs: u64,
+    },
+    /// Print the transaction sent by an account with a sequence number, verified to be committed
+    VerifyPayment {
+        #[structopt(long)]
+        sender: AccountAddress,
+        #[structopt(long)]
+        sequence_number: u64,
+    },
+}
+
+fn main() -> Result<()> {
+    let args = Args::from_args();
+
+    let client = BlockingClient::new(args.url);
+    let storage = FileStorage::new(args.data_dir)?;
+    let mut light_client = match args.waypoint {
+        Some(waypoint) => LightClient::new_with_waypoint(client, waypoint, storage),
+        None => LightClient::new(client, storage)?,
+    };
+
+    match args.command {
+        Command::Follow { interval_secs } => loop {
+            let version = light_client.version();
+            match light_client.sync() {
+                Ok(()) if light_client.version() != version => println!(
+                    "Synced to version {}, waypoint {}",
+                    light_client.version(),
+                    light_client.waypoint()
+                ),
+                Ok(()) => (),
+                // Verification failures are reported, the trusted state stays as it was
+                Err(error) => eprintln!("Failed to sync: {}", error),
+            }
+            thread::sleep(Duration::from_secs(interval_secs));
+        },
+        Command::VerifyPayment {
+            sender,
+            sequence_number,
+        } => match light_client.verify_payment(sender, sequence_number)? {
+            Some(txn) => println!("{}", serde_json::to_string_pretty(&txn)?),
+            None => bail!(
+                "Transaction {}:{} isn't committed at version {}.",
+                sender,
+                sequence_number,
+                light_client.version()
+            ),
+        },
+    }
+    Ok(())
+}
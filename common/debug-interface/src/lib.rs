@@ -72,6 +72,17 @@ impl NodeDebugClient {
             .collect()
     }
 
+    /// Retrieves the stats a node component serves under `name`, e.g. `rocksdb`.
+    pub fn get_stats(&mut self, name: &str) -> Result<serde_json::Value> {
+        let response = self
+            .client
+            .get(&format!("{}/stats/{}", self.addr, name))
+            .send()?
+            .error_for_status()?;
+
+        Ok(response.json()?)
+    }
+
     pub fn get_events(&mut self) -> Result<Vec<JsonLogEntry>> {
         let response = self.client.get(&format!("{}/events", self.addr)).send()?;
 
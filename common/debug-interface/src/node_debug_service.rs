@@ -3,14 +3,34 @@
 
 //! Debug interface to access information in a specific node.
 
+use anyhow::Result;
 use diem_logger::{info, json_log, Filter, Logger};
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    collections::HashMap,
+    fmt,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+};
 use tokio::runtime::{Builder, Runtime};
-use warp::Filter as _;
+use warp::{http::StatusCode, Filter as _};
+
+type StatsProvider = Box<dyn Fn() -> Result<serde_json::Value> + Send + Sync>;
 
-#[derive(Debug)]
 pub struct NodeDebugService {
     runtime: Runtime,
+    stats_providers: Arc<RwLock<HashMap<String, StatsProvider>>>,
+}
+
+impl fmt::Debug for NodeDebugService {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NodeDebugService")
+            .field("runtime", &self.runtime)
+            .field(
+                "stats_providers",
+                &self.stats_providers.read().unwrap().keys(),
+            )
+            .finish()
+    }
 }
 
 impl NodeDebugService {
@@ -28,6 +48,30 @@ impl NodeDebugService {
         // GET /events
         let events = warp::path("events").map(|| warp::reply::json(&json_log::pop_last_entries()));
 
+        // GET /stats/<name>
+        let stats_providers: Arc<RwLock<HashMap<String, StatsProvider>>> = Arc::default();
+        let providers = Arc::clone(&stats_providers);
+        let stats = warp::path!("stats" / String).map(move |name: String| {
+            let stats = providers
+                .read()
+                .unwrap()
+                .get(&name)
+                .map(|provider| provider());
+            match stats {
+                Some(Ok(stats)) => {
+                    warp::reply::with_status(warp::reply::json(&stats), StatusCode::OK)
+                }
+                Some(Err(e)) => warp::reply::with_status(
+                    warp::reply::json(&e.to_string()),
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                ),
+                None => warp::reply::with_status(
+                    warp::reply::json(&format!("No stats named {}.", name)),
+                    StatusCode::NOT_FOUND,
+                ),
+            }
+        });
+
         // Post /log/filter
         let local_filter = {
             let logger = logger.clone();
@@ -65,16 +109,32 @@ impl NodeDebugService {
             .and(warp::path("log"))
             .and(local_filter.or(remote_filter));
 
-        let routes = log.or(warp::get().and(metrics.or(events)));
+        let routes = log.or(warp::get().and(metrics.or(events).or(stats)));
 
         runtime
             .handle()
             .spawn(async move { warp::serve(routes).bind(address).await });
 
-        Self { runtime }
+        Self {
+            runtime,
+            stats_providers,
+        }
     }
 
     pub fn runtime(&self) -> &Runtime {
         &self.runtime
     }
+
+    /// Serves what `provider` returns at `GET /stats/<name>`, for components created after the
+    /// service. Replaces any provider added under the same name.
+    pub fn add_stats_provider(
+        &self,
+        name: &str,
+        provider: impl Fn() -> Result<serde_json::Value> + Send + Sync + 'static,
+    ) {
+        self.stats_providers
+            .write()
+            .unwrap()
+            .insert(name.to_string(), Box::new(provider));
+    }
 }
@@ -171,6 +171,19 @@ impl<Key: Eq + Hash + Clone + Debug> TokenBucketRateLimiter<Key> {
         }
         remove
     }
+
+    /// Garbage collects every bucket not in use that has refilled completely, for keys that come
+    /// and go without notice.  A new bucket only starts full if `new_bucket_start_percentage` is 100
+    pub fn garbage_collect_full_buckets(&self) {
+        self.buckets.write().retain(|_, bucket| {
+            if Arc::strong_count(bucket) > 1 {
+                return true;
+            }
+            let mut bucket = bucket.lock();
+            bucket.refill();
+            bucket.tokens < bucket.size
+        });
+    }
 }
 
 /// A token bucket object that keeps track of everything related to a key
@@ -546,4 +559,26 @@ mod tests {
         assert!(!rate_limiter.try_garbage_collect_key(&key_to_keep));
         assert_num_keys(&rate_limiter, 1);
     }
+
+    #[test]
+    fn test_garbage_collect_full_buckets() {
+        let rate_limiter = TokenBucketRateLimiter::test(2, 1);
+        rate_limiter
+            .bucket("full")
+            .lock()
+            .acquire_all_tokens(0)
+            .unwrap();
+        rate_limiter
+            .bucket("drained")
+            .lock()
+            .acquire_all_tokens(1)
+            .unwrap();
+        let _in_use = rate_limiter.bucket("in use");
+        assert_num_keys(&rate_limiter, 3);
+
+        // Only the full bucket nobody holds is dropped
+        rate_limiter.garbage_collect_full_buckets();
+        assert_num_keys(&rate_limiter, 2);
+        assert!(rate_limiter.buckets.read().contains_key("drained"));
+    }
 }
@@ -10,11 +10,16 @@ use channel::{
     diem_channel::{self, Receiver, Sender},
     message_queues::QueueStyle,
 };
+use diem_infallible::Mutex;
 use diem_types::{
+    account_config::NewEpochEvent,
     event::EventKey,
-    on_chain_config::{ConfigID, OnChainConfigPayload},
+    on_chain_config::{ConfigID, OnChainConfigPayload, ON_CHAIN_CONFIG_REGISTRY},
 };
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
+
+#[cfg(test)]
+mod tests;
 
 pub struct SubscriptionService<T, U> {
     pub name: String,
@@ -79,3 +84,90 @@ impl ReconfigSubscription {
         Self::subscribe(name, bundle)
     }
 }
+
+/// The bus state sync notifies the components of the node, and any plugin, of reconfigurations
+/// on. A subscription is sent the latest on-chain configs as soon as it's made, or as soon as state
+/// sync reads them at startup, then the new ones whenever a config or an event it subscribed to
+/// changes or is emitted. Only the latest notification is kept for a subscriber that lags behind.
+#[derive(Clone, Default)]
+pub struct ReconfigNotificationBus {
+    inner: Arc<Mutex<ReconfigNotificationBusInner>>,
+}
+
+#[derive(Default)]
+struct ReconfigNotificationBusInner {
+    subscriptions: Vec<ReconfigSubscription>,
+    latest_payload: Option<OnChainConfigPayload>,
+}
+
+impl ReconfigNotificationBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes `name` to the changes of `configs` and the emissions of `events`.
+    pub fn subscribe(
+        &self,
+        name: &str,
+        configs: Vec<ConfigID>,
+        events: Vec<EventKey>,
+    ) -> Receiver<(), OnChainConfigPayload> {
+        let (subscription, receiver) = ReconfigSubscription::subscribe_all(name, configs, events);
+        self.register(subscription)
+            .expect("The receiver of a new subscription can't be dropped.");
+        receiver
+    }
+
+    /// Subscribes `name` to every epoch change and to the changes of any on-chain config. The
+    /// state of the new epoch is given by `OnChainConfigPayload::epoch_state`.
+    pub fn subscribe_to_epoch_changes(&self, name: &str) -> Receiver<(), OnChainConfigPayload> {
+        self.subscribe(
+            name,
+            ON_CHAIN_CONFIG_REGISTRY.to_vec(),
+            vec![NewEpochEvent::event_key()],
+        )
+    }
+
+    /// Adds a subscription made separately, failing if its receiver is already dropped.
+    pub fn register(&self, mut subscription: ReconfigSubscription) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if let Some(payload) = &inner.latest_payload {
+            subscription.publish(payload.clone())?;
+        }
+        inner.subscriptions.push(subscription);
+        Ok(())
+    }
+
+    /// Publishes `payload` to every subscription, failing if any of them can't receive it.
+    pub fn publish_to_all(&self, payload: OnChainConfigPayload) -> Result<()> {
+        let mut inner = self.inner.lock();
+        for subscription in inner.subscriptions.iter_mut() {
+            subscription.publish(payload.clone())?;
+        }
+        inner.latest_payload = Some(payload);
+        Ok(())
+    }
+
+    /// Publishes `payload` to the subscriptions to any of `changed_configs` or `event_keys`,
+    /// returning the name of each subscription notified along with the outcome.
+    pub fn publish(
+        &self,
+        payload: OnChainConfigPayload,
+        changed_configs: &HashSet<ConfigID>,
+        event_keys: &HashSet<EventKey>,
+    ) -> Vec<(String, Result<()>)> {
+        let mut inner = self.inner.lock();
+        let mut outcomes = vec![];
+        for subscription in inner.subscriptions.iter_mut() {
+            let subscribed_items = subscription.subscribed_items();
+            if !changed_configs.is_disjoint(&subscribed_items.configs)
+                || !event_keys.is_disjoint(&subscribed_items.events)
+            {
+                let outcome = subscription.publish(payload.clone());
+                outcomes.push((subscription.name.clone(), outcome));
+            }
+        }
+        inner.latest_payload = Some(payload);
+        outcomes
+    }
+}
@@ -0,0 +1,52 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use diem_types::on_chain_config::{DiemVersion, OnChainConfig, VMConfig};
+use futures::{FutureExt, StreamExt};
+use std::collections::HashMap;
+
+fn payload(epoch: u64) -> OnChainConfigPayload {
+    OnChainConfigPayload::new(epoch, Arc::new(HashMap::new()))
+}
+
+fn next_epoch(receiver: &mut Receiver<(), OnChainConfigPayload>) -> Option<u64> {
+    receiver
+        .select_next_some()
+        .now_or_never()
+        .map(|payload| payload.epoch())
+}
+
+#[test]
+fn test_reconfig_notification_bus() {
+    let bus = ReconfigNotificationBus::new();
+    let mut early = bus.subscribe("early", vec![VMConfig::CONFIG_ID], vec![]);
+    assert_eq!(next_epoch(&mut early), None);
+
+    bus.publish_to_all(payload(1)).unwrap();
+    assert_eq!(next_epoch(&mut early), Some(1));
+
+    // A subscription made once the node runs gets the latest configs right away.
+    let mut late = bus.subscribe_to_epoch_changes("late");
+    assert_eq!(next_epoch(&mut late), Some(1));
+
+    // Only the subscriptions to what changed are notified.
+    let changed = vec![DiemVersion::CONFIG_ID].into_iter().collect();
+    let outcomes = bus.publish(payload(2), &changed, &HashSet::new());
+    assert_eq!(outcomes.len(), 1);
+    assert_eq!(outcomes[0].0, "late");
+    assert!(outcomes[0].1.is_ok());
+    assert_eq!(next_epoch(&mut early), None);
+    assert_eq!(next_epoch(&mut late), Some(2));
+
+    let events = vec![NewEpochEvent::event_key()].into_iter().collect();
+    bus.publish(payload(3), &HashSet::new(), &events);
+    bus.publish(payload(4), &HashSet::new(), &events);
+    // A subscriber lagging behind only gets the latest payload.
+    assert_eq!(next_epoch(&mut late), Some(4));
+    assert_eq!(next_epoch(&mut late), None);
+
+    // Publishing fails for a subscription whose receiver is dropped.
+    drop(early);
+    assert!(bus.publish_to_all(payload(5)).is_err());
+}
@@ -18,7 +18,7 @@ use diem_types::{
     chain_id::ChainId,
     network_address::{
         encrypted::{Key, KeyVersion, KEY_LEN},
-        NetworkAddress,
+        IpPreference, NetworkAddress,
     },
     PeerId,
 };
@@ -292,7 +292,7 @@ async fn check_endpoint_inner(
 ) -> Result<String, Error> {
     // Connect to the address, this should handle DNS resolution
     let fut_socket = async {
-        resolve_and_connect(address.clone())
+        resolve_and_connect(address.clone(), IpPreference::Any)
             .await
             .map(TcpSocket::new)
     };
@@ -325,7 +325,7 @@ async fn check_endpoint_inner(
 const INVALID_NOISE_HEADER: &[u8; 152] = &[7; 152];
 
 async fn check_endpoint_inner_no_handshake(address: NetworkAddress) -> Result<String, Error> {
-    let mut socket = resolve_and_connect(address.clone())
+    let mut socket = resolve_and_connect(address.clone(), IpPreference::Any)
         .await
         .map(TcpSocket::new)
         .map_err(|error| {
@@ -2,7 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::config::SafetyRulesConfig;
-use diem_types::{account_address::AccountAddress, block_info::Round};
+use diem_types::{
+    account_address::AccountAddress,
+    block_info::Round,
+    on_chain_config::{LeaderReputationParams, ReputationHeuristicParams},
+};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, path::PathBuf};
 
@@ -11,19 +15,35 @@ use std::{collections::HashMap, path::PathBuf};
 pub struct ConsensusConfig {
     pub contiguous_rounds: u32,
     pub max_block_size: u64,
+    // Adapts the size of the blocks proposed, up to max_block_size, to the mempool backlog and
+    // how long blocks take to execute. None always proposes blocks of up to max_block_size.
+    pub adaptive_block_size: Option<AdaptiveBlockSizeConfig>,
     pub max_pruned_blocks_in_mem: usize,
     // Timeout for consensus to get an ack from mempool for executed transactions (in milliseconds)
     pub mempool_executed_txn_timeout_ms: u64,
     // Timeout for consensus to pull transactions from mempool and get a response (in milliseconds)
     pub mempool_txn_pull_timeout_ms: u64,
     pub round_initial_timeout_ms: u64,
+    // How round timeouts grow from round_initial_timeout_ms as rounds go by without a commit
+    pub round_timeout_policy: RoundTimeoutPolicy,
     pub proposer_type: ConsensusProposerType,
     pub safety_rules: SafetyRulesConfig,
     // Only sync committed transactions but not vote for any pending blocks. This is useful when
     // validators coordinate on the latest version to apply a manual transaction.
     pub sync_only: bool,
+    // Follow consensus as a non-voting observer: execute the proposals and commit the blocks as
+    // soon as the votes certifying them arrive, with lower latency than state sync, but never
+    // vote or propose. The node runs with the validator role, so that state sync leaves
+    // committing to consensus, and follows the validators over its VFN network, which needs to
+    // connect to theirs. The validators need to list the node's peer id on it in their observers.
+    pub observer: bool,
+    // Peer ids of the observers, on the VFN network, this validator sends its proposals, votes
+    // and timeouts to over it. It serves their block and epoch retrievals too.
+    pub observers: Vec<AccountAddress>,
     // how many times to wait for txns from mempool when propose
     pub mempool_poll_count: u64,
+    // Limits on the messages received from each peer, checked before verifying any signature
+    pub message_limits: ConsensusMessageLimitsConfig,
 }
 
 impl Default for ConsensusConfig {
@@ -31,17 +51,21 @@ impl Default for ConsensusConfig {
         ConsensusConfig {
             contiguous_rounds: 2,
             max_block_size: 1000,
+            adaptive_block_size: None,
             max_pruned_blocks_in_mem: 100,
             mempool_txn_pull_timeout_ms: 1000,
             mempool_executed_txn_timeout_ms: 1000,
             round_initial_timeout_ms: 1000,
-            proposer_type: ConsensusProposerType::LeaderReputation(LeaderReputationConfig {
-                active_weights: 99,
-                inactive_weights: 1,
-            }),
+            round_timeout_policy: RoundTimeoutPolicy::default(),
+            proposer_type: ConsensusProposerType::LeaderReputation(
+                LeaderReputationConfig::default(),
+            ),
             safety_rules: SafetyRulesConfig::default(),
             sync_only: false,
+            observer: false,
+            observers: vec![],
             mempool_poll_count: 1,
+            message_limits: ConsensusMessageLimitsConfig::default(),
         }
     }
 }
@@ -52,6 +76,78 @@ impl ConsensusConfig {
     }
 }
 
+/// Each peer can send `messages_per_sec` messages a second, with bursts of up to `burst` messages,
+/// and proposals of up to `max_proposal_txns` transactions.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsensusMessageLimitsConfig {
+    pub messages_per_sec: usize,
+    pub burst: usize,
+    pub max_proposal_txns: u64,
+}
+
+impl Default for ConsensusMessageLimitsConfig {
+    fn default() -> Self {
+        Self {
+            messages_per_sec: 100,
+            burst: 500,
+            max_proposal_txns: 10_000,
+        }
+    }
+}
+
+/// The block size grows by `increase_percent` after each proposal that mempool filled, and
+/// shrinks by `decrease_percent` after each one while blocks take longer than
+/// `target_execution_ms` to execute.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdaptiveBlockSizeConfig {
+    pub min_block_size: u64,
+    pub target_execution_ms: u64,
+    pub increase_percent: u64,
+    pub decrease_percent: u64,
+}
+
+impl Default for AdaptiveBlockSizeConfig {
+    fn default() -> Self {
+        Self {
+            min_block_size: 10,
+            target_execution_ms: 500,
+            increase_percent: 10,
+            decrease_percent: 25,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum RoundTimeoutPolicy {
+    /// The timeout is multiplied by `multiplier` for each round since the last commit, up to
+    /// `max_exponent` times.
+    Exponential {
+        multiplier: f64,
+        max_exponent: usize,
+    },
+    /// Like `Exponential`, up to `max_timeout_ms`, except that the rounds that time out keep the
+    /// timeout they reached after the next commit, until `stable_rounds` rounds in a row end with
+    /// a QC. Suits networks slower than `round_initial_timeout_ms` at times, e.g. spread far apart.
+    Adaptive {
+        multiplier: f64,
+        max_timeout_ms: u64,
+        stable_rounds: u64,
+    },
+}
+
+impl Default for RoundTimeoutPolicy {
+    fn default() -> Self {
+        // 1.2^6 ~= 3, the timeout goes from initial_timeout to initial_timeout*3 in 6 steps
+        RoundTimeoutPolicy::Exponential {
+            multiplier: 1.2,
+            max_exponent: 6,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum ConsensusProposerType {
@@ -67,9 +163,82 @@ pub enum ConsensusProposerType {
     RoundProposer(HashMap<Round, AccountAddress>),
 }
 
+/// Validators elect the same leaders only if they all use the same values. Once the on-chain
+/// consensus config sets leader reputation parameters, they are used instead of these.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
-#[serde(deny_unknown_fields)]
+#[serde(default, deny_unknown_fields)]
 pub struct LeaderReputationConfig {
+    /// Weight of the validators that proposed or voted in the window.
     pub active_weights: u64,
+    /// Weight of the validators that didn't, e.g. ones that are down or just joined. 0 excludes
+    /// them from being elected, unless no validator is active.
     pub inactive_weights: u64,
+    /// Number of committed blocks the reputation is computed over. 0 means as many as there are
+    /// validators.
+    pub window_size: usize,
+    /// How many rounds before the one elected for the window ends, so that validators lagging
+    /// a little behind still agree on it.
+    pub round_gap: u64,
+    pub heuristic: ReputationHeuristicConfig,
+}
+
+impl Default for LeaderReputationConfig {
+    fn default() -> Self {
+        Self {
+            active_weights: 99,
+            inactive_weights: 1,
+            window_size: 0,
+            round_gap: 4,
+            heuristic: ReputationHeuristicConfig::ActiveInactive,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum ReputationHeuristicConfig {
+    /// Validators are weighted by whether they are active in the window only.
+    ActiveInactive,
+    /// Active validators that fail too many rounds, or whose proposals are chronically slow, are
+    /// weighted down to `failed_weights`.
+    ProposerAndVoter {
+        failed_weights: u64,
+        /// Share of the blocks in the window a validator may miss the vote of before it's
+        /// considered failing.
+        failure_threshold_percent: u64,
+        /// Average time, from the block before, above which the proposals of a validator are
+        /// considered slow. Only blocks proposed in the round after the one before count, since
+        /// the time of the others includes timeouts.
+        slow_proposal_threshold_ms: u64,
+    },
+}
+
+impl Default for ReputationHeuristicConfig {
+    fn default() -> Self {
+        ReputationHeuristicConfig::ActiveInactive
+    }
+}
+
+impl From<&LeaderReputationParams> for LeaderReputationConfig {
+    fn from(params: &LeaderReputationParams) -> Self {
+        let heuristic = match params.heuristic {
+            ReputationHeuristicParams::ActiveInactive => ReputationHeuristicConfig::ActiveInactive,
+            ReputationHeuristicParams::ProposerAndVoter {
+                failed_weights,
+                failure_threshold_percent,
+                slow_proposal_threshold_ms,
+            } => ReputationHeuristicConfig::ProposerAndVoter {
+                failed_weights,
+                failure_threshold_percent,
+                slow_proposal_threshold_ms,
+            },
+        };
+        Self {
+            active_weights: params.active_weights,
+            inactive_weights: params.inactive_weights,
+            window_size: params.window_size as usize,
+            round_gap: params.round_gap,
+            heuristic,
+        }
+    }
 }
@@ -0,0 +1,76 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Publishing of committed transactions and their events to a message broker, for indexers.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EventPublisherConfig {
+    /// The broker to publish to, None disables the publisher
+    pub sink: Option<EventSinkConfig>,
+    /// File recording the next version to publish, relative to the data directory
+    pub cursor_path: PathBuf,
+    /// The version to publish from when there is no cursor file yet
+    pub start_version: u64,
+    /// Number of transactions read from storage and published at once
+    pub batch_size: u64,
+    /// Interval at which storage is checked for new transactions once caught up, in milliseconds
+    pub poll_interval_ms: u64,
+    #[serde(skip)]
+    data_dir: PathBuf,
+}
+
+impl Default for EventPublisherConfig {
+    fn default() -> EventPublisherConfig {
+        EventPublisherConfig {
+            sink: None,
+            cursor_path: PathBuf::from("event_publisher_cursor"),
+            start_version: 0,
+            batch_size: 100,
+            poll_interval_ms: 100,
+            data_dir: PathBuf::from("/opt/diem/data"),
+        }
+    }
+}
+
+impl EventPublisherConfig {
+    pub fn cursor_path(&self) -> PathBuf {
+        if self.cursor_path.is_relative() {
+            self.data_dir.join(&self.cursor_path)
+        } else {
+            self.cursor_path.clone()
+        }
+    }
+
+    pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        self.data_dir = data_dir;
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum EventSinkConfig {
+    Kafka(KafkaSinkConfig),
+    Nats(NatsSinkConfig),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct KafkaSinkConfig {
+    /// Comma separated `host:port` list of the brokers to bootstrap from
+    pub brokers: String,
+    /// The topic transactions are published to, keyed by version
+    pub topic: String,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct NatsSinkConfig {
+    /// URL of the NATS server
+    pub url: String,
+    /// The JetStream subject transactions are published to. A stream must capture it, as
+    /// publishing waits for the stream to acknowledge each message.
+    pub subject: String,
+}
@@ -23,6 +23,14 @@ pub struct ExecutionConfig {
     pub service: ExecutionCorrectnessService,
     pub backend: SecureBackend,
     pub network_timeout_ms: u64,
+    /// Lets the storage commit of a block overlap the execution of the next ones, with
+    /// `commit_blocks` returning once the commit is started rather than done. Commits ending an
+    /// epoch are still waited for. State Sync, which reads the committed state from storage, is
+    /// notified of a commit once it has landed, so one commit later.
+    pub pipelined_commit: bool,
+    /// Number of threads the transactions of a block are executed on, optimistically, executing
+    /// again those conflicting with the others. Blocks are executed sequentially with 1.
+    pub concurrency_level: usize,
 }
 
 impl std::fmt::Debug for ExecutionConfig {
@@ -57,6 +65,8 @@ impl Default for ExecutionConfig {
             sign_vote_proposal: true,
             // Default value of 30 seconds for the network timeout.
             network_timeout_ms: 30_000,
+            pipelined_commit: false,
+            concurrency_level: 1,
         }
     }
 }
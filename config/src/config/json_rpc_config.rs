@@ -12,14 +12,30 @@ pub struct JsonRpcConfig {
     pub batch_size_limit: u16,
     pub page_size_limit: u16,
     pub content_length_limit: usize,
+    // Longest time a submit_and_wait call waits for its transaction, in milliseconds; calls may
+    // ask for less
+    pub submit_and_wait_timeout_ms: u64,
     pub tls_cert_path: Option<String>,
     pub tls_key_path: Option<String>,
+    // CA certificates verifying client certificates, if specified, only clients presenting a
+    // certificate signed by one of them can connect. Requires tls_cert_path and tls_key_path.
+    pub tls_client_ca_path: Option<String>,
+    // Cross-origin requests accepted from web pages
+    pub cors: JsonRpcCorsConfig,
+    // Per-client rate limiting configuration, if not specified, no rate limiting
+    pub rate_limit: Option<JsonRpcRateLimitConfig>,
+    // Address of the gRPC transaction stream service, if not specified, the service is not started
+    pub stream_address: Option<SocketAddr>,
+    // Address of the admin routes, such as the mempool dump, if not specified, they are not served.
+    // They are meant for operators only: this address should not be reachable from outside.
+    pub admin_address: Option<SocketAddr>,
 }
 
 pub const DEFAULT_JSON_RPC_ADDRESS: &str = "127.0.0.1";
 pub const DEFAULT_JSON_RPC_PORT: u16 = 8080;
 pub const DEFAULT_BATCH_SIZE_LIMIT: u16 = 20;
 pub const DEFAULT_PAGE_SIZE_LIMIT: u16 = 1000;
+pub const DEFAULT_SUBMIT_AND_WAIT_TIMEOUT_MS: u64 = 30_000;
 /// 0L increased the json-RPC length so we can include stdlib in upgrade oracle.
 pub const DEFAULT_CONTENT_LENGTH_LIMIT: usize = 4096 * 1024; //////// 0L ////////
 
@@ -32,8 +48,14 @@ impl Default for JsonRpcConfig {
             batch_size_limit: DEFAULT_BATCH_SIZE_LIMIT,
             page_size_limit: DEFAULT_PAGE_SIZE_LIMIT,
             content_length_limit: DEFAULT_CONTENT_LENGTH_LIMIT,
+            submit_and_wait_timeout_ms: DEFAULT_SUBMIT_AND_WAIT_TIMEOUT_MS,
             tls_cert_path: None,
             tls_key_path: None,
+            tls_client_ca_path: None,
+            cors: JsonRpcCorsConfig::default(),
+            rate_limit: None,
+            stream_address: None,
+            admin_address: None,
         }
     }
 }
@@ -41,5 +63,64 @@ impl Default for JsonRpcConfig {
 impl JsonRpcConfig {
     pub fn randomize_ports(&mut self) {
         self.address.set_port(utils::get_available_port());
+        if let Some(stream_address) = self.stream_address.as_mut() {
+            stream_address.set_port(utils::get_available_port());
+        }
+        if let Some(admin_address) = self.admin_address.as_mut() {
+            admin_address.set_port(utils::get_available_port());
+        }
+    }
+}
+
+/// CORS policy of the JSON-RPC server. Requests may always carry `content-type`, on top of the
+/// headers listed here.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JsonRpcCorsConfig {
+    /// Origins web pages may call the server from, such as `https://explorer.example.com`; any
+    /// origin if not specified
+    pub allowed_origins: Option<Vec<String>>,
+    /// Additional request headers web pages may send, such as the rate limit API key header
+    pub allowed_headers: Vec<String>,
+    /// How long browsers may cache the answer to a preflight request
+    pub max_age_secs: Option<u64>,
+}
+
+pub const DEFAULT_READ_CALLS_PER_SEC: usize = 100;
+pub const DEFAULT_READ_BURST: usize = 200;
+pub const DEFAULT_SUBMIT_CALLS_PER_SEC: usize = 10;
+pub const DEFAULT_SUBMIT_BURST: usize = 20;
+
+/// Token bucket limits applied to each client of the JSON-RPC server. Every call in a request
+/// takes one token, from the submit bucket for `submit` calls and from the read bucket otherwise.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JsonRpcRateLimitConfig {
+    /// Header carrying an API key; clients sending one of `api_keys` are limited by key, others
+    /// by IP address
+    pub api_key_header: Option<String>,
+    /// API keys issued to clients. Any other value of the header is ignored, so that clients
+    /// cannot get fresh limits by making up keys
+    pub api_keys: Vec<String>,
+    /// Number of read calls/s for a client
+    pub read_calls_per_sec: usize,
+    /// Maximum burst of read calls for a client
+    pub read_burst: usize,
+    /// Number of `submit` calls/s for a client
+    pub submit_calls_per_sec: usize,
+    /// Maximum burst of `submit` calls for a client
+    pub submit_burst: usize,
+}
+
+impl Default for JsonRpcRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            api_key_header: None,
+            api_keys: Vec::new(),
+            read_calls_per_sec: DEFAULT_READ_CALLS_PER_SEC,
+            read_burst: DEFAULT_READ_BURST,
+            submit_calls_per_sec: DEFAULT_SUBMIT_CALLS_PER_SEC,
+            submit_burst: DEFAULT_SUBMIT_BURST,
+        }
     }
 }
@@ -7,18 +7,52 @@ use serde::{Deserialize, Serialize};
 #[serde(default, deny_unknown_fields)]
 pub struct MempoolConfig {
     pub capacity: usize,
+    // total size in bytes of the raw transactions mempool holds
+    pub capacity_bytes: usize,
     pub capacity_per_user: usize,
+    // how long clients are told to wait before submitting again when mempool is full or the
+    // sender has too many transactions in it
+    pub backpressure_retry_after_ms: u64,
+    // how much higher, in percent, the gas unit price of a transaction must be to replace the
+    // pending one with the same sequence number
+    pub gas_price_bump_percent: u64,
     // number of failovers to broadcast to when the primary network is alive
     pub default_failovers: usize,
     pub max_broadcasts_per_peer: usize,
+    // share, in percent, of recent broadcasts to an upstream peer that timed out or failed to be
+    // sent above which the peer is demoted behind the healthy ones
+    pub peer_demotion_failure_percent: u64,
+    // how long an upstream peer stays demoted before it gets another chance
+    pub peer_demotion_duration_ms: u64,
     pub mempool_snapshot_interval_secs: u64,
     pub shared_mempool_ack_timeout_ms: u64,
     pub shared_mempool_backoff_interval_ms: u64,
     pub shared_mempool_batch_size: usize,
     pub shared_mempool_max_concurrent_inbound_syncs: usize,
     pub shared_mempool_tick_interval_ms: u64,
+    // how long a transaction stays in mempool at most, whatever its expiration time
     pub system_transaction_timeout_secs: u64,
     pub system_transaction_gc_interval_ms: u64,
+    // how often transactions past their expiration time are removed, besides when a block is
+    // pulled or committed
+    pub expiration_gc_interval_ms: u64,
+    // how long a transaction stays in mempool at least before garbage collection can remove it
+    pub min_time_in_mempool_before_gc_secs: u64,
+    // how many recently removed transactions are remembered along with why they were removed,
+    // and for how long
+    pub removed_transactions_cache_capacity: usize,
+    pub removed_transactions_cache_ttl_secs: u64,
+    // how many recent VM validation results are kept, and for how long, so that a transaction
+    // submitted again as is doesn't get its signature verified and its prologue run again
+    pub validation_cache_capacity: usize,
+    pub validation_cache_ttl_secs: u64,
+    // which parked transaction to drop when mempool is at capacity
+    pub eviction_policy: MempoolEvictionPolicy,
+    // how many parked transactions a sender may hold before the `per_sender_cap` eviction policy
+    // drops theirs first
+    pub parking_lot_capacity_per_user: usize,
+    // how ready transactions are ordered when pulled into a block
+    pub ordering_policy: MempoolOrderingPolicy,
 }
 
 impl Default for MempoolConfig {
@@ -32,10 +66,50 @@ impl Default for MempoolConfig {
             max_broadcasts_per_peer: 1,
             mempool_snapshot_interval_secs: 180,
             capacity: 1_000_000,
+            capacity_bytes: 2 * 1024 * 1024 * 1024,
             capacity_per_user: 100,
+            backpressure_retry_after_ms: 1_000,
+            gas_price_bump_percent: 10,
             default_failovers: 3,
+            peer_demotion_failure_percent: 50,
+            peer_demotion_duration_ms: 60_000,
             system_transaction_timeout_secs: 600,
             system_transaction_gc_interval_ms: 60_000,
+            expiration_gc_interval_ms: 60_000,
+            min_time_in_mempool_before_gc_secs: 0,
+            removed_transactions_cache_capacity: 100_000,
+            removed_transactions_cache_ttl_secs: 600,
+            validation_cache_capacity: 10_000,
+            validation_cache_ttl_secs: 10,
+            eviction_policy: MempoolEvictionPolicy::Random,
+            parking_lot_capacity_per_user: 10,
+            ordering_policy: MempoolOrderingPolicy::GasPrice,
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MempoolEvictionPolicy {
+    // Evict the highest parked transaction of a randomly chosen account
+    Random,
+    // Evict the parked transaction that entered mempool first
+    OldestFirst,
+    // Evict the parked transaction paying the lowest gas unit price
+    LowestGasPriceFirst,
+    // Evict from the account holding the most transactions in mempool
+    LargestAccountFootprintFirst,
+    // Evict from the account parking the most transactions above `parking_lot_capacity_per_user`,
+    // then the parked transaction that entered mempool first
+    PerSenderCap,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MempoolOrderingPolicy {
+    // Highest ranking score first, i.e. the gas unit price normalized across currencies, then the
+    // transaction expiring from mempool first
+    GasPrice,
+    // The transaction that entered mempool first, whatever it pays
+    Fifo,
+}
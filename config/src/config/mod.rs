@@ -17,6 +17,8 @@ mod consensus_config;
 pub use consensus_config::*;
 mod debug_interface_config;
 pub use debug_interface_config::*;
+mod event_publisher_config;
+pub use event_publisher_config::*;
 mod error;
 pub use error::*;
 mod execution_config;
@@ -63,6 +65,8 @@ pub struct NodeConfig {
     #[serde(default)]
     pub debug_interface: DebugInterfaceConfig,
     #[serde(default)]
+    pub event_publisher: EventPublisherConfig,
+    #[serde(default)]
     pub execution: ExecutionConfig,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub full_node_networks: Vec<NetworkConfig>,
@@ -94,6 +98,10 @@ pub struct BaseConfig {
     data_dir: PathBuf,
     pub role: RoleType,
     pub waypoint: WaypointConfig,
+    /// An archival node is a full node which never prunes, keeping all the historical
+    /// transactions, events and account states, and maintains all the optional indices, whatever
+    /// the storage config says. It advertises it through the JSON-RPC API.
+    pub archival: bool,
 }
 
 impl Default for BaseConfig {
@@ -102,6 +110,7 @@ impl Default for BaseConfig {
             data_dir: PathBuf::from("/opt/diem/data"),
             role: RoleType::Validator,
             waypoint: WaypointConfig::None,
+            archival: false,
         }
     }
 }
@@ -218,6 +227,7 @@ impl NodeConfig {
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
         self.base.data_dir = data_dir.clone();
         self.consensus.set_data_dir(data_dir.clone());
+        self.event_publisher.set_data_dir(data_dir.clone());
         self.execution.set_data_dir(data_dir.clone());
         self.metrics.set_data_dir(data_dir.clone());
         self.storage.set_data_dir(data_dir);
@@ -232,11 +242,37 @@ impl NodeConfig {
         let input_dir = RootPath::new(input_path);
         config.execution.load(&input_dir)?;
 
+        config.storage.rocksdb_config.validate()?;
+
         let mut config = config.validate_network_configs()?;
+        config.apply_archival_mode()?;
         config.set_data_dir(config.data_dir().to_path_buf());
         Ok(config)
     }
 
+    /// Makes an archival node keep all its history and maintain all the optional indices
+    fn apply_archival_mode(&mut self) -> Result<(), Error> {
+        if !self.base.archival {
+            return Ok(());
+        }
+        invariant(
+            !self.base.role.is_validator(),
+            "An archival node must be a full node".into(),
+        )?;
+        // A node downloading a state snapshot holds no history before the snapshot version
+        invariant(
+            self.state_sync.bootstrapping_mode == BootstrappingMode::ExecuteTransactions,
+            "An archival node must bootstrap by executing transactions".into(),
+        )?;
+
+        self.storage.prune_window = None;
+        self.storage.prune_window_secs = None;
+        self.storage.prune_ledger_history = false;
+        self.storage.event_type_index = true;
+        self.storage.event_indexer = true;
+        Ok(())
+    }
+
     /// Checks `NetworkConfig` setups so that they exist on proper networks
     /// Additionally, handles any strange missing default cases
     fn validate_network_configs(mut self) -> Result<NodeConfig, Error> {
@@ -448,6 +484,48 @@ mod test {
         }
     }
 
+    #[test]
+    fn verify_archival_mode() {
+        let mut config = NodeConfig::default_for_public_full_node();
+        config.base.archival = true;
+        config.storage.prune_window_secs = Some(3600);
+        config.apply_archival_mode().unwrap();
+        assert_eq!(config.storage.prune_window, None);
+        assert_eq!(config.storage.prune_window_secs, None);
+        assert!(config.storage.event_type_index && config.storage.event_indexer);
+
+        let mut config = NodeConfig::default_for_public_full_node();
+        config.base.archival = true;
+        config.state_sync.bootstrapping_mode = BootstrappingMode::DownloadStateSnapshot;
+        assert!(config.apply_archival_mode().is_err());
+
+        let mut config = NodeConfig::default_for_validator();
+        config.base.archival = true;
+        assert!(config.apply_archival_mode().is_err());
+
+        // Other nodes are left as configured
+        let mut config = NodeConfig::default_for_public_full_node();
+        let storage = config.storage.clone();
+        config.apply_archival_mode().unwrap();
+        assert_eq!(config.storage, storage);
+    }
+
+    #[test]
+    fn verify_dns_seeds_rejected_on_mutually_authenticated_networks() {
+        let mut config = NodeConfig::default_for_public_full_node();
+        let network = &mut config.full_node_networks[0];
+        network.dns_seed_config.names = vec!["seeds.example.com".to_string()];
+        assert!(network.load_fullnode_network().is_ok());
+
+        network.mutual_authentication = true;
+        assert!(network.load_fullnode_network().is_err());
+
+        let mut config = NodeConfig::default_for_validator();
+        let network = config.validator_network.as_mut().unwrap();
+        network.dns_seed_config.names = vec!["seeds.example.com".to_string()];
+        assert!(network.load_validator_network().is_err());
+    }
+
     #[test]
     fn verify_configs() {
         NodeConfig::default_for_public_full_node();
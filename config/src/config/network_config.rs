@@ -11,7 +11,9 @@ use diem_crypto::{x25519, Uniform};
 use diem_network_address_encryption::Encryptor;
 use diem_secure_storage::{CryptoStorage, KVStorage, Storage};
 use diem_types::{
-    network_address::NetworkAddress, transaction::authenticator::AuthenticationKey, PeerId,
+    network_address::{IpPreference, NetworkAddress},
+    transaction::authenticator::AuthenticationKey,
+    PeerId,
 };
 use rand::{
     rngs::{OsRng, StdRng},
@@ -43,6 +45,21 @@ pub const MAX_FRAME_SIZE: usize = 8 * 1024 * 1024; /* 8 MiB */
 pub const CONNECTION_BACKOFF_BASE: u64 = 2;
 pub const IP_BYTE_BUCKET_RATE: usize = 102400 /* 100 KiB */;
 pub const IP_BYTE_BUCKET_SIZE: usize = IP_BYTE_BUCKET_RATE;
+pub const BAN_SCORE: u8 = 20;
+pub const BAN_DURATION_SECS: u64 = 600; /* 10 minutes */
+pub const SCORE_HALF_LIFE_SECS: u64 = 300; /* 5 minutes */
+pub const PORT_MAPPING_LEASE_SECS: u32 = 3600; /* 1 hour */
+pub const PORT_MAPPING_TIMEOUT_MS: u64 = 3000;
+pub const CONSENSUS_QOS_WEIGHT: u32 = 8;
+pub const CONTROL_QOS_WEIGHT: u32 = 4;
+pub const BULK_QOS_WEIGHT: u32 = 1;
+pub const DNS_SEED_REFRESH_INTERVAL_SECS: u64 = 300; /* 5 minutes */
+pub const DNS_SEED_TIMEOUT_MS: u64 = 5000;
+pub const COMPRESSION_THRESHOLD_BYTES: usize = 4 * 1024; /* 4 KiB */
+pub const MAX_ADMITTED_INBOUND_CONNECTIONS: usize = 1024;
+pub const MAX_INBOUND_CONNECTIONS_PER_IP: usize = 16;
+pub const MAX_INBOUND_HANDSHAKES_PER_SEC: u32 = 50;
+pub const INBOUND_HANDSHAKE_BURST: u32 = 100;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -63,8 +80,11 @@ pub struct NetworkConfig {
     pub discovery_method: DiscoveryMethod,
     pub identity: Identity,
     // TODO: Add support for multiple listen/advertised addresses in config.
-    // The address that this node is listening on for new connections.
+    // The address that this node is listening on for new connections. Listening on `/ip6/::`
+    // accepts connections over both IPv4 and IPv6.
     pub listen_address: NetworkAddress,
+    // Which IP family to dial first, when a peer has addresses of both or a name resolving to both
+    pub ip_preference: IpPreference,
     // Select this to enforce that both peers should authenticate each other, otherwise
     // authentication only occurs for outgoing connections.
     pub mutual_authentication: bool,
@@ -77,6 +97,9 @@ pub struct NetworkConfig {
     pub seed_addrs: HashMap<PeerId, Vec<NetworkAddress>>,
     // The initial peers to connect to prior to onchain discovery
     pub seeds: PeerSet,
+    // DNS names seed peers are published under, re-resolved periodically, so that seeds can
+    // change without editing the config. Not allowed with `mutual_authentication`
+    pub dns_seed_config: DnsSeedConfig,
     // The maximum size of an inbound or outbound request frame
     pub max_frame_size: usize,
     // Enables proxy protocol on incoming connections to get original source addresses
@@ -91,10 +114,33 @@ pub struct NetworkConfig {
     pub max_outbound_connections: usize,
     // Maximum number of outbound connections, limited by PeerManager
     pub max_inbound_connections: usize,
+    // Caps on the inbound connections of any peer, checked before their Noise handshake, so that
+    // a flood of connections is rejected before it costs any key exchange
+    pub inbound_admission_config: InboundAdmissionConfig,
     // Inbound rate limiting configuration, if not specified, no rate limiting
     pub inbound_rate_limit_config: Option<RateLimitConfig>,
     // Outbound rate limiting configuration, if not specified, no rate limiting
     pub outbound_rate_limit_config: Option<RateLimitConfig>,
+    // Inbound rate limiting configuration of each peer, on top of the one of its IP, if not
+    // specified, no rate limiting
+    pub peer_inbound_rate_limit_config: Option<RateLimitConfig>,
+    // Outbound rate limiting configuration of each peer, on top of the one of its IP, if not
+    // specified, no rate limiting
+    pub peer_outbound_rate_limit_config: Option<RateLimitConfig>,
+    // Rate limiting configuration of the messages of each peer by protocol name, applied in each
+    // direction. Messages over the limit are dropped. Protocols not listed aren't rate limited
+    pub protocol_rate_limit_configs: HashMap<String, RateLimitConfig>,
+    // Scoring of peers by their misbehaviors, and banning of those scoring too low
+    pub peer_reputation_config: PeerReputationConfig,
+    // Mapping of the listen port on the router of the node with UPnP or NAT-PMP, for nodes
+    // behind a NAT to accept inbound connections
+    pub nat_traversal_config: NatTraversalConfig,
+    // Weights of the QoS classes of the messages written to each peer, for consensus messages to
+    // go ahead of bulk traffic when a connection backs up
+    pub outbound_qos_config: OutboundQosConfig,
+    // Compression of large messages, with the algorithms negotiated with each peer in the
+    // handshake
+    pub compression_config: CompressionConfig,
 }
 
 impl Default for NetworkConfig {
@@ -109,11 +155,13 @@ impl NetworkConfig {
             discovery_method: DiscoveryMethod::None,
             identity: Identity::None,
             listen_address: "/ip4/0.0.0.0/tcp/6180".parse().unwrap(),
+            ip_preference: IpPreference::default(),
             mutual_authentication: false,
             network_address_key_backend: None,
             network_id,
             seed_addrs: HashMap::new(),
             seeds: PeerSet::default(),
+            dns_seed_config: DnsSeedConfig::default(),
             max_frame_size: MAX_FRAME_SIZE,
             enable_proxy_protocol: false,
             max_connection_delay_ms: MAX_CONNECTION_DELAY_MS,
@@ -126,8 +174,16 @@ impl NetworkConfig {
             ping_failures_tolerated: PING_FAILURES_TOLERATED,
             max_outbound_connections: MAX_FULLNODE_OUTBOUND_CONNECTIONS,
             max_inbound_connections: MAX_INBOUND_CONNECTIONS,
+            inbound_admission_config: InboundAdmissionConfig::default(),
             inbound_rate_limit_config: None,
             outbound_rate_limit_config: None,
+            peer_inbound_rate_limit_config: None,
+            peer_outbound_rate_limit_config: None,
+            protocol_rate_limit_configs: HashMap::new(),
+            peer_reputation_config: PeerReputationConfig::default(),
+            nat_traversal_config: NatTraversalConfig::default(),
+            outbound_qos_config: OutboundQosConfig::default(),
+            compression_config: CompressionConfig::default(),
         };
         config.prepare_identity();
         config
@@ -186,6 +242,15 @@ impl NetworkConfig {
     }
 
     fn load(&mut self) -> Result<(), Error> {
+        // Nothing authenticates what DNS answers, so the keys of DNS seeds may only tell whom to
+        // dial, never whom to trust, as they would on a mutually authenticated network
+        if self.mutual_authentication && !self.dns_seed_config.names.is_empty() {
+            return Err(Error::InvariantViolation(format!(
+                "DNS seeds are not allowed on the mutually authenticated {} network",
+                self.network_id
+            )));
+        }
+
         if self.listen_address.to_string().is_empty() {
             self.listen_address = utils::get_local_ip()
                 .ok_or_else(|| Error::InvariantViolation("No local IP".to_string()))?;
@@ -331,9 +396,9 @@ pub struct IdentityFromStorage {
 
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct RateLimitConfig {
-    /// Maximum number of bytes/s for an IP
+    /// Maximum number of bytes/s for an IP, or a peer when rate limiting peers
     pub ip_byte_bucket_rate: usize,
-    /// Maximum burst of bytes for an IP
+    /// Maximum burst of bytes for an IP, or a peer when rate limiting peers
     pub ip_byte_bucket_size: usize,
     /// Initial amount of tokens initially in the bucket
     pub initial_bucket_fill_percentage: u8,
@@ -352,6 +417,157 @@ impl Default for RateLimitConfig {
     }
 }
 
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PeerReputationConfig {
+    /// Score, out of 100, under which a peer is banned
+    pub ban_score: u8,
+    /// How long a peer stays banned
+    pub ban_duration_secs: u64,
+    /// Time for the score of a peer to recover half of what it's lost
+    pub score_half_life_secs: u64,
+    /// Allow for disabling bans, peers are still scored
+    pub enabled: bool,
+}
+
+impl Default for PeerReputationConfig {
+    fn default() -> Self {
+        Self {
+            ban_score: BAN_SCORE,
+            ban_duration_secs: BAN_DURATION_SECS,
+            score_half_life_secs: SCORE_HALF_LIFE_SECS,
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NatTraversalConfig {
+    /// Lifetime of the port mapping requested from the router, renewed halfway through
+    pub lease_duration_secs: u32,
+    /// Time to wait for each answer of the router
+    pub timeout_ms: u64,
+    /// Allow for enabling the port mapping, disabled by default as only nodes behind a NAT need it
+    pub enabled: bool,
+}
+
+impl Default for NatTraversalConfig {
+    fn default() -> Self {
+        Self {
+            lease_duration_secs: PORT_MAPPING_LEASE_SECS,
+            timeout_ms: PORT_MAPPING_TIMEOUT_MS,
+            enabled: false,
+        }
+    }
+}
+
+/// Admission control of inbound connections, as soon as they're accepted. Connections count from
+/// then until they're closed, whether their handshake succeeds or not, and are counted by the IP
+/// they come from, which is that of the proxy with `enable_proxy_protocol`.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InboundAdmissionConfig {
+    /// Maximum inbound connections, handshaking or established
+    pub max_connections: usize,
+    /// Maximum inbound connections from a single IP
+    pub max_connections_per_ip: usize,
+    /// Rate at which inbound connections may start their handshake
+    pub max_handshakes_per_sec: u32,
+    /// Handshakes which may start in a single burst, faster than `max_handshakes_per_sec`
+    pub handshake_burst: u32,
+    /// Allow for enabling admission control, disabled by default as only public nodes need it
+    pub enabled: bool,
+}
+
+impl Default for InboundAdmissionConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: MAX_ADMITTED_INBOUND_CONNECTIONS,
+            max_connections_per_ip: MAX_INBOUND_CONNECTIONS_PER_IP,
+            max_handshakes_per_sec: MAX_INBOUND_HANDSHAKES_PER_SEC,
+            handshake_burst: INBOUND_HANDSHAKE_BURST,
+            enabled: false,
+        }
+    }
+}
+
+/// Weights of the QoS classes of outbound messages. When a connection backs up, each class gets as
+/// many messages written as its weight in each round.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutboundQosConfig {
+    /// Weight of consensus messages
+    pub consensus_weight: u32,
+    /// Weight of health checks, discovery and other small control messages
+    pub control_weight: u32,
+    /// Weight of mempool broadcasts and state sync chunks
+    pub bulk_weight: u32,
+}
+
+impl Default for OutboundQosConfig {
+    fn default() -> Self {
+        Self {
+            consensus_weight: CONSENSUS_QOS_WEIGHT,
+            control_weight: CONTROL_QOS_WEIGHT,
+            bulk_weight: BULK_QOS_WEIGHT,
+        }
+    }
+}
+
+/// Seed peers published in DNS, as TXT records holding their network addresses, or SRV records
+/// pointing at hosts whose TXT records hold their network public keys as `noise-ik=<pubkey>`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DnsSeedConfig {
+    /// Names the seeds are published under, none by default
+    pub names: Vec<String>,
+    /// How often the names are resolved again
+    pub refresh_interval_secs: u64,
+    /// Time to wait for each answer of the nameserver
+    pub timeout_ms: u64,
+}
+
+impl Default for DnsSeedConfig {
+    fn default() -> Self {
+        Self {
+            names: Vec::new(),
+            refresh_interval_secs: DNS_SEED_REFRESH_INTERVAL_SECS,
+            timeout_ms: DNS_SEED_TIMEOUT_MS,
+        }
+    }
+}
+
+/// Compression of the messages exchanged with peers. Each peer is sent messages compressed with the
+/// first of `algorithms` it also supports, and only if their payload is at least `threshold_bytes`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompressionConfig {
+    /// Algorithms offered to peers in the handshake, in order of preference. Compression is
+    /// disabled when there are none, which is the default
+    pub algorithms: Vec<CompressionAlgorithm>,
+    /// Size of the smallest payload worth compressing
+    pub threshold_bytes: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            algorithms: Vec::new(),
+            threshold_bytes: COMPRESSION_THRESHOLD_BYTES,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionAlgorithm {
+    /// Fast, for links where CPU time matters as much as bandwidth
+    Lz4,
+    /// Smaller output than LZ4, for slow links
+    Zstd,
+}
+
 pub type PeerSet = HashMap<PeerId, Peer>;
 
 // TODO: Combine with RoleType?
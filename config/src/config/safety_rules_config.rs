@@ -5,7 +5,7 @@ use crate::{
     config::{LoggerConfig, SecureBackend},
     keys::ConfigKey,
 };
-use diem_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+use diem_crypto::{ed25519::Ed25519PrivateKey, x25519, Uniform};
 use diem_types::{network_address::NetworkAddress, waypoint::Waypoint, PeerId};
 use rand::rngs::StdRng;
 use serde::{Deserialize, Serialize};
@@ -71,9 +71,45 @@ pub enum SafetyRulesService {
 #[serde(deny_unknown_fields)]
 pub struct RemoteService {
     pub server_address: NetworkAddress,
+    /// Authenticates both ends of the connection and encrypts it, if set. The service and
+    /// consensus must both set it, each with their own key and the public key of the other.
+    #[serde(default)]
+    pub secure_transport: Option<SecureTransportConfig>,
+    /// Number of times consensus retries a request the service didn't answer before failing it.
+    #[serde(default = "RemoteService::default_max_retries")]
+    pub max_retries: usize,
+    /// Delay in milliseconds between two attempts of a request.
+    #[serde(default = "RemoteService::default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+    /// Consensus checks that the service still answers when it made no request to it for this
+    /// long, in milliseconds. Zero disables health checks.
+    #[serde(default = "RemoteService::default_health_check_interval_ms")]
+    pub health_check_interval_ms: u64,
 }
 
 impl RemoteService {
+    pub fn new(server_address: NetworkAddress) -> Self {
+        Self {
+            server_address,
+            secure_transport: None,
+            max_retries: Self::default_max_retries(),
+            retry_delay_ms: Self::default_retry_delay_ms(),
+            health_check_interval_ms: Self::default_health_check_interval_ms(),
+        }
+    }
+
+    fn default_max_retries() -> usize {
+        10
+    }
+
+    fn default_retry_delay_ms() -> u64 {
+        500
+    }
+
+    fn default_health_check_interval_ms() -> u64 {
+        10_000
+    }
+
     pub fn server_address(&self) -> SocketAddr {
         self.server_address
             .to_socket_addrs()
@@ -83,6 +119,26 @@ impl RemoteService {
     }
 }
 
+/// The keys with which the service and consensus authenticate each other with the Noise IK
+/// handshake.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SecureTransportConfig {
+    /// The key of this end of the connection.
+    pub private_key: ConfigKey<x25519::PrivateKey>,
+    /// The public key the other end must authenticate with.
+    pub remote_public_key: x25519::PublicKey,
+}
+
+impl SecureTransportConfig {
+    pub fn new(private_key: x25519::PrivateKey, remote_public_key: x25519::PublicKey) -> Self {
+        Self {
+            private_key: ConfigKey::new(private_key),
+            remote_public_key,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SafetyRulesTestConfig {
     pub author: PeerId,
@@ -1,19 +1,31 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use diem_types::PeerId;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct StateSyncConfig {
+    // How a node behind its waypoint gets to the waypoint version
+    pub bootstrapping_mode: BootstrappingMode,
     // Size of chunk to request for state synchronization
     pub chunk_limit: u64,
     // The timeout of the state sync client to process a commit notification (in milliseconds)
     pub client_commit_timeout_ms: u64,
+    // How the ledger infos carried by chunks are verified while catching up
+    pub epoch_change_verification: EpochChangeVerification,
+    // Caps on the throughput of the transactions executed by state sync, so that a node catching
+    // up leaves IO to the services on the same machine (e.g., JSON-RPC)
+    pub execution_throttle: ExecutionThrottleConfig,
     // default timeout used for long polling to remote peer
     pub long_poll_timeout_ms: u64,
     // valid maximum chunk limit for sanity check
     pub max_chunk_limit: u64,
+    // Maximum number of chunk requests in flight, including the one for the next chunk to apply.
+    // Above 1, the chunks after the next one (up to the sync target) are requested from other
+    // peers in parallel, and applied in order once received.
+    pub max_parallel_chunk_requests: u64,
     // valid maximum timeout limit for sanity check
     pub max_timeout_ms: u64,
     // The timeout of the state sync coordinator to receive a commit ack from mempool (in milliseconds)
@@ -22,6 +34,11 @@ pub struct StateSyncConfig {
     // if no progress is made by sending chunk requests to a number of networks,
     // the next sync request will be multicasted, i.e. sent to more networks
     pub multicast_timeout_ms: u64,
+    // Upstream peers which chunks are requested from before the other peers, e.g., the validators
+    // of the operator of a full node. The other peers are a fallback.
+    pub preferred_peers: Vec<PreferredPeerConfig>,
+    // Number of accounts to request in each chunk of a state snapshot
+    pub snapshot_chunk_limit: u64,
     // The timeout for ensuring sync requests are making progress (i.e., the maximum time between
     // commits when processing a sync request).
     pub sync_request_timeout_ms: u64,
@@ -32,15 +49,86 @@ pub struct StateSyncConfig {
 impl Default for StateSyncConfig {
     fn default() -> Self {
         Self {
+            bootstrapping_mode: BootstrappingMode::ExecuteTransactions,
             chunk_limit: 1000,
             client_commit_timeout_ms: 5_000,
+            epoch_change_verification: EpochChangeVerification::Batch,
+            execution_throttle: ExecutionThrottleConfig::default(),
             long_poll_timeout_ms: 10_000,
             max_chunk_limit: 1000,
+            max_parallel_chunk_requests: 1,
             max_timeout_ms: 120_000,
             mempool_commit_timeout_ms: 5_000,
             multicast_timeout_ms: 30_000,
+            preferred_peers: vec![],
+            snapshot_chunk_limit: 1000,
             sync_request_timeout_ms: 60_000,
             tick_interval_ms: 100,
         }
     }
 }
+
+/// How a node behind its waypoint gets to the waypoint version.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BootstrappingMode {
+    /// Executes all the transactions up to the waypoint version.
+    ExecuteTransactions,
+    /// Downloads the state at the waypoint version from peers, verified against the waypoint, and
+    /// only executes the transactions after it. The node then holds no transactions, events or
+    /// states before the waypoint version.
+    DownloadStateSnapshot,
+}
+
+/// How state sync verifies the ledger infos carried by chunks while catching up.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EpochChangeVerification {
+    /// Verifies the signatures of the ledger infos of every chunk.
+    Strict,
+    /// Verifies the signatures of a ledger info once, however many chunks carry it. Behind the
+    /// waypoint, the epoch ending ledger infos are committed as their chunks are executed, their
+    /// contents being verified against the waypoint, and their signatures are verified in a batch
+    /// once the waypoint is reached, or after a restart for the ones not verified yet.
+    Batch,
+}
+
+/// Caps on the throughput of the transactions executed by state sync. Chunks aren't requested
+/// while the chunks executed recently exceed a cap.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExecutionThrottleConfig {
+    /// Max number of transactions executed per second, if any.
+    pub max_versions_per_sec: Option<u64>,
+    /// Max size of the transactions (with their infos and events) written per second, in MB, if
+    /// any.
+    pub max_write_mb_per_sec: Option<u64>,
+    /// Lifts the caps until the node synced to its waypoint, e.g., on a machine dedicated to
+    /// bootstrapping the node. The caps apply once the node serves its clients.
+    pub burst_mode: bool,
+}
+
+/// An upstream peer which chunks are requested from before the other peers, on whichever network
+/// it is connected. The other peers are only requested from while no preferred peer is connected,
+/// or once the preferred peers made no progress for the multicast timeout, until a preferred peer
+/// sends a chunk again.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PreferredPeerConfig {
+    pub peer_id: PeerId,
+    /// The next chunk is requested from the connected preferred peers of the highest priority,
+    /// i.e., the lowest number.
+    #[serde(default)]
+    pub priority: u32,
+    /// Share of the chunk requests sent to the peer among the preferred peers of its priority,
+    /// relative to the weights of the others and scaled by how well the peer served the previous
+    /// requests. Must be positive.
+    #[serde(default = "PreferredPeerConfig::default_weight")]
+    pub weight: f64,
+}
+
+impl PreferredPeerConfig {
+    fn default_weight() -> f64 {
+        1.0
+    }
+}
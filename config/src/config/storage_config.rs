@@ -1,9 +1,13 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::utils;
+use crate::{
+    config::{invariant, Error},
+    utils,
+};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::PathBuf,
 };
@@ -11,13 +15,139 @@ use std::{
 /// Port selected RocksDB options for tuning underlying rocksdb instance of DiemDB.
 /// see https://github.com/facebook/rocksdb/blob/master/include/rocksdb/options.h
 /// for detailed explanations.
-#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct RocksdbConfig {
     pub max_open_files: i32,
     pub max_total_wal_size: u64,
+    /// Size in bytes of the LRU block cache shared by all column families. 0 leaves each column
+    /// family with RocksDB's default cache of its own.
+    pub block_cache_size: u64,
+    /// Options for every column family not overridden in `column_families`.
+    pub default_cf: RocksdbCfConfig,
+    /// Per column family overrides of `default_cf`, keyed by column family name, e.g.
+    /// `jellyfish_merkle_node`.
+    pub column_families: BTreeMap<String, RocksdbCfOverrides>,
+    /// Collects RocksDB statistics, which the block cache hit and miss counts are exported from,
+    /// at the cost of a few percent of throughput.
+    pub enable_statistics: bool,
+    /// Number of RocksDB instances the nodes of the state Merkle tree are spread across, by the
+    /// first nibble of their path, and written to in parallel. 1 keeps them in the main instance.
+    /// Changing it on an existing DB requires migrating the state with `diem-storage-inspector
+    /// migrate-state-shards` first.
+    pub state_shards: usize,
+    /// Saves without a ledger info, e.g. those of state sync catching up, return without waiting
+    /// for their writes to be synced to disk, which are synced together once this many bytes are
+    /// unsynced, or `group_commit_max_latency_ms` after the oldest of them. A crash can lose them
+    /// until then, for state sync to fetch again. 0 syncs every save.
+    pub group_commit_max_bytes: u64,
+    pub group_commit_max_latency_ms: u64,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RocksdbCfConfig {
+    /// Size in bytes of a memtable before it's flushed.
+    pub write_buffer_size: u64,
+    /// Number of memtables kept in memory, including the ones being flushed. At least 2.
+    pub max_write_buffer_number: i32,
+    pub compaction_style: RocksdbCompactionStyle,
+}
+
+impl Default for RocksdbCfConfig {
+    fn default() -> Self {
+        // RocksDB defaults.
+        Self {
+            #[allow(clippy::integer_arithmetic)] // TODO: remove once clippy lint fixed
+            write_buffer_size: 64 << 20,
+            max_write_buffer_number: 2,
+            compaction_style: RocksdbCompactionStyle::Level,
+        }
+    }
+}
+
+/// Like `RocksdbCfConfig`, with fields left out taken from `RocksdbConfig::default_cf`.
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RocksdbCfOverrides {
+    pub write_buffer_size: Option<u64>,
+    pub max_write_buffer_number: Option<i32>,
+    pub compaction_style: Option<RocksdbCompactionStyle>,
+}
+
+/// Universal compaction trades space amplification for less write amplification, which can suit
+/// write heavy column families on slow disks. FIFO compaction is not offered since it drops data.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RocksdbCompactionStyle {
+    Level,
+    Universal,
+}
+
+impl RocksdbConfig {
+    /// The options for column family `cf_name`, with overrides applied.
+    pub fn cf_config(&self, cf_name: &str) -> RocksdbCfConfig {
+        let mut config = self.default_cf;
+        if let Some(overrides) = self.column_families.get(cf_name) {
+            if let Some(write_buffer_size) = overrides.write_buffer_size {
+                config.write_buffer_size = write_buffer_size;
+            }
+            if let Some(max_write_buffer_number) = overrides.max_write_buffer_number {
+                config.max_write_buffer_number = max_write_buffer_number;
+            }
+            if let Some(compaction_style) = overrides.compaction_style {
+                config.compaction_style = compaction_style;
+            }
+        }
+        config
+    }
+
+    /// Checks the values are in ranges RocksDB works with. Column family names are checked when
+    /// opening the DB, since only DiemDB knows them.
+    pub fn validate(&self) -> Result<(), Error> {
+        invariant(
+            self.max_open_files == -1 || self.max_open_files > 0,
+            format!(
+                "rocksdb max_open_files must be -1 (unlimited) or positive, got {}",
+                self.max_open_files
+            ),
+        )?;
+        invariant(
+            (1..=MAX_STATE_SHARDS).contains(&self.state_shards),
+            format!(
+                "rocksdb state_shards must be between 1 and {}, got {}",
+                MAX_STATE_SHARDS, self.state_shards
+            ),
+        )?;
+        for cf_name in
+            std::iter::once("default_cf").chain(self.column_families.keys().map(String::as_str))
+        {
+            let config = self.cf_config(cf_name);
+            invariant(
+                config.write_buffer_size >= MIN_WRITE_BUFFER_SIZE,
+                format!(
+                    "rocksdb write_buffer_size of {} must be at least {}, got {}",
+                    cf_name, MIN_WRITE_BUFFER_SIZE, config.write_buffer_size
+                ),
+            )?;
+            invariant(
+                config.max_write_buffer_number >= 2,
+                format!(
+                    "rocksdb max_write_buffer_number of {} must be at least 2, got {}",
+                    cf_name, config.max_write_buffer_number
+                ),
+            )?;
+        }
+        Ok(())
+    }
 }
 
+/// 1MB, well above RocksDB's own minimum, below which memtables would be flushed constantly.
+const MIN_WRITE_BUFFER_SIZE: u64 = 1_048_576;
+
+/// State nodes are sharded by the first nibble of their path, which takes 16 values.
+pub const MAX_STATE_SHARDS: usize = 16;
+
 impl Default for RocksdbConfig {
     fn default() -> Self {
         Self {
@@ -28,6 +158,15 @@ impl Default for RocksdbConfig {
             // families are updated at non-uniform frequencies.
             #[allow(clippy::integer_arithmetic)] // TODO: remove once clippy lint fixed
             max_total_wal_size: 1u64 << 30,
+            // The same total as RocksDB's default 8MB cache per column family, but shared.
+            #[allow(clippy::integer_arithmetic)] // TODO: remove once clippy lint fixed
+            block_cache_size: 128 << 20,
+            default_cf: RocksdbCfConfig::default(),
+            column_families: BTreeMap::new(),
+            enable_statistics: true,
+            state_shards: 1,
+            group_commit_max_bytes: 0,
+            group_commit_max_latency_ms: 100,
         }
     }
 }
@@ -39,15 +178,68 @@ pub struct StorageConfig {
     pub backup_service_address: SocketAddr,
     pub dir: PathBuf,
     pub grpc_max_receive_len: Option<i32>,
-    /// None disables pruning. The windows is in number of versions, consider system tps
-    /// (transaction per second) when calculating proper window.
+    /// None disables pruning (unless `prune_window_secs` is set). The windows is in number of
+    /// versions, consider system tps (transaction per second) when calculating proper window.
     pub prune_window: Option<u64>,
+    /// Keeps versions committed within this many seconds of the latest block readable, on top of
+    /// `prune_window`. None disables the wall-clock window.
+    pub prune_window_secs: Option<u64>,
+    /// Whether to prune transactions and events outside the prune windows as well, not only
+    /// account states.
+    pub prune_ledger_history: bool,
     #[serde(skip)]
     data_dir: PathBuf,
     /// Read, Write, Connect timeout for network operations in milliseconds
     pub timeout_ms: u64,
     /// Rocksdb-specific configurations
     pub rocksdb_config: RocksdbConfig,
+    /// Index events by Move type, to serve `get_events_by_type`. Only events committed while the
+    /// index is enabled are indexed.
+    pub event_type_index: bool,
+    /// Index events by the accounts and currencies they involve, to serve `get_events_by_account`
+    /// and `get_events_by_currency`. Only events committed while the indexer is enabled are
+    /// indexed.
+    pub event_indexer: bool,
+    /// Number of transaction accumulator range proofs, and as many epoch ending ledger infos,
+    /// cached for serving light clients and state sync. Zero disables the cache.
+    pub proof_cache_size: usize,
+    /// Moves old transactions and events out of the DB into archive files. None disables archiving.
+    pub archive: Option<ArchiveConfig>,
+}
+
+/// Configures moving transactions and events far behind the latest version out of DiemDB into
+/// archive files, which are read back transparently when those versions are queried. Transaction
+/// infos, indices and proofs stay in the DB.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArchiveConfig {
+    /// Transactions and events more than this many versions behind the latest are archived.
+    pub keep_versions: u64,
+    /// Number of versions in each archive file.
+    #[serde(default = "ArchiveConfig::default_chunk_size")]
+    pub chunk_size: u64,
+    pub storage: ArchiveStorageConfig,
+}
+
+impl ArchiveConfig {
+    fn default_chunk_size() -> u64 {
+        100_000
+    }
+}
+
+/// Where archive files are kept.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArchiveStorageConfig {
+    /// A local directory, e.g. a mounted network file system.
+    Local { dir: PathBuf },
+    /// Shell commands moving a file in and out of an object store. The file name is passed in
+    /// `$FILE_NAME`, the content is fed to `put` via stdin and read from `get` via stdout. E.g.
+    /// for S3, `aws s3 cp - "s3://bucket/archive/$FILE_NAME"` and
+    /// `aws s3 cp "s3://bucket/archive/$FILE_NAME" -`; for GCS,
+    /// `gsutil cp - "gs://bucket/archive/$FILE_NAME"` and
+    /// `gsutil cat "gs://bucket/archive/$FILE_NAME"`.
+    Command { put: String, get: String },
 }
 
 impl Default for StorageConfig {
@@ -66,10 +258,16 @@ impl Default for StorageConfig {
             //////// 0L ////////
             // ~50GB state tree history (about 1 day at 100 tps)
             prune_window: Some(10_000_000), 
+            prune_window_secs: None,
+            prune_ledger_history: false,
             data_dir: PathBuf::from("/opt/diem/data"),
             // Default read/write/connection timeout, in milliseconds
             timeout_ms: 30_000,
             rocksdb_config: RocksdbConfig::default(),
+            event_type_index: false,
+            event_indexer: false,
+            proof_cache_size: 1024,
+            archive: None,
         }
     }
 }
@@ -93,3 +291,67 @@ impl StorageConfig {
             .set_port(utils::get_available_port());
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rocksdb_cf_overrides() {
+        let config: RocksdbConfig = serde_yaml::from_str(
+            r#"
+            block_cache_size: 1073741824
+            default_cf:
+                write_buffer_size: 134217728
+            column_families:
+                jellyfish_merkle_node:
+                    compaction_style: universal
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.max_open_files,
+            RocksdbConfig::default().max_open_files
+        );
+
+        let node_cf = config.cf_config("jellyfish_merkle_node");
+        assert_eq!(node_cf.write_buffer_size, 134_217_728);
+        assert_eq!(node_cf.max_write_buffer_number, 2);
+        assert_eq!(node_cf.compaction_style, RocksdbCompactionStyle::Universal);
+        assert_eq!(config.cf_config("transaction"), config.default_cf);
+    }
+
+    #[test]
+    fn test_rocksdb_config_validation() {
+        assert!(RocksdbConfig::default().validate().is_ok());
+
+        let mut config = RocksdbConfig::default();
+        config.column_families.insert(
+            "transaction".to_string(),
+            RocksdbCfOverrides {
+                max_write_buffer_number: Some(1),
+                ..Default::default()
+            },
+        );
+        assert!(config.validate().is_err());
+
+        let mut config = RocksdbConfig::default();
+        config.default_cf.write_buffer_size = 1024;
+        assert!(config.validate().is_err());
+
+        let config = RocksdbConfig {
+            max_open_files: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        for state_shards in &[0, MAX_STATE_SHARDS + 1] {
+            let config = RocksdbConfig {
+                state_shards: *state_shards,
+                ..Default::default()
+            };
+            assert!(config.validate().is_err());
+        }
+    }
+}
@@ -0,0 +1,306 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Derives a waypoint from several independent sources and cross-checks them, so that an operator
+//! bootstrapping a node doesn't have to trust a single source or compute the waypoint by hand.
+//!
+//! A source knows the waypoints of some epoch endings:
+//! - a genesis blob, the waypoint at version 0, computed by executing it on a scratch DB;
+//! - an epoch ending backup, the waypoints of all the epochs in the backup;
+//! - a full node, the waypoints of all the epochs it synced, from its epoch change proof.
+//!
+//! The ledger infos of backups and full nodes are checked to be signed by the validators of the
+//! previous epoch, so a source agreeing with the others on an early waypoint can't make up later
+//! ones without the signatures of a quorum of validators.
+
+use anyhow::{bail, ensure, format_err, Context, Result};
+use backup_cli::{
+    backup_types::epoch_ending::restore::EpochHistoryRestoreController,
+    metadata::{self, cache::MetadataCacheOpt},
+    storage::{local_fs::LocalFs, BackupStorage},
+    utils::{GlobalRestoreOptions, RestoreRunMode},
+};
+use diem_client::BlockingClient;
+use diem_config::config::RocksdbConfig;
+use diem_temppath::TempPath;
+use diem_types::{
+    epoch_change::{EpochChangeProof, Verifier},
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{Transaction, Version},
+    waypoint::Waypoint,
+};
+use diem_vm::DiemVM;
+use diemdb::DiemDB;
+use executor::db_bootstrapper;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    path::PathBuf,
+    sync::Arc,
+};
+use storage_interface::DbReaderWriter;
+
+/// Where waypoints are read from.
+#[derive(Clone, Debug)]
+pub enum WaypointSource {
+    /// A BCS serialized genesis transaction.
+    Genesis(PathBuf),
+    /// The root dir of a backup in local storage, holding epoch ending backups.
+    Backup(PathBuf),
+    /// The JSON-RPC endpoint of a full node.
+    Node(String),
+}
+
+impl fmt::Display for WaypointSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WaypointSource::Genesis(path) => write!(f, "genesis {}", path.display()),
+            WaypointSource::Backup(path) => write!(f, "backup {}", path.display()),
+            WaypointSource::Node(url) => write!(f, "node {}", url),
+        }
+    }
+}
+
+impl WaypointSource {
+    /// Returns the waypoints known to the source, by version.
+    pub fn fetch_waypoints(&self) -> Result<BTreeMap<Version, Waypoint>> {
+        let waypoints = match self {
+            WaypointSource::Genesis(path) => vec![genesis_waypoint(path)?],
+            WaypointSource::Backup(dir) => backup_waypoints(dir.clone())?,
+            WaypointSource::Node(url) => node_waypoints(url)?,
+        };
+        Ok(waypoints
+            .into_iter()
+            .map(|waypoint| (waypoint.version(), waypoint))
+            .collect())
+    }
+}
+
+fn genesis_waypoint(path: &PathBuf) -> Result<Waypoint> {
+    let bytes = fs::read(path).with_context(|| format_err!("Failed to read {:?}.", path))?;
+    let genesis_txn: Transaction = bcs::from_bytes(&bytes)?;
+    ensure!(
+        matches!(genesis_txn, Transaction::GenesisTransaction(_)),
+        "Not a GenesisTransaction: {:?}",
+        path,
+    );
+
+    let db_dir = TempPath::new();
+    let db = DiemDB::open(&db_dir, false, None, RocksdbConfig::default())?;
+    db_bootstrapper::generate_waypoint::<DiemVM>(&DbReaderWriter::new(db), &genesis_txn)
+}
+
+fn backup_waypoints(dir: PathBuf) -> Result<Vec<Waypoint>> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    let epoch_endings = runtime.block_on(async {
+        let storage: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(dir));
+        // A cache of its own, as the cache is synced with the metadata of a single storage
+        let cache_dir = TempPath::new();
+        let metadata_view = metadata::cache::sync_and_load(
+            &MetadataCacheOpt::new(Some(cache_dir.path().to_path_buf())),
+            Arc::clone(&storage),
+            1, /* concurrent_downloads */
+        )
+        .await?;
+        let manifests = metadata_view
+            .select_epoch_ending_backups(Version::max_value())?
+            .into_iter()
+            .map(|backup| backup.manifest)
+            .collect();
+
+        // With no trusted waypoints, the ledger infos after the first one are verified with the
+        // validator sets of the epochs before them
+        let global_opt = GlobalRestoreOptions {
+            target_version: Version::max_value(),
+            trusted_waypoints: Arc::new(HashMap::new()),
+            run_mode: Arc::new(RestoreRunMode::Verify),
+            concurrent_downloads: 1,
+            concurrency: 1,
+        };
+        EpochHistoryRestoreController::new(manifests, global_opt, storage)
+            .run()
+            .await
+    })?;
+    epoch_endings
+        .epoch_endings
+        .iter()
+        .map(Waypoint::new_epoch_boundary)
+        .collect()
+}
+
+fn node_waypoints(url: &str) -> Result<Vec<Waypoint>> {
+    let client = BlockingClient::new(url);
+    let mut epoch_endings: Vec<LedgerInfoWithSignatures> = vec![];
+    loop {
+        // The proof starts at the epoch of the version, so the last epoch ending known is sent
+        // again and skipped
+        let known_version = epoch_endings
+            .last()
+            .map_or(0, |li| li.ledger_info().version());
+        let state_proof = client.get_state_proof(known_version)?.into_inner();
+        let proof: EpochChangeProof = bcs::from_bytes(state_proof.epoch_change_proof.inner())?;
+        let next_epoch = epoch_endings.len() as u64;
+        let num_epoch_endings = epoch_endings.len();
+        epoch_endings.extend(
+            proof
+                .ledger_info_with_sigs
+                .into_iter()
+                .filter(|li| li.ledger_info().epoch() >= next_epoch),
+        );
+        if !proof.more || epoch_endings.len() == num_epoch_endings {
+            break;
+        }
+    }
+    verify_epoch_endings(&epoch_endings)?;
+    epoch_endings
+        .iter()
+        .map(|li| Waypoint::new_epoch_boundary(li.ledger_info()))
+        .collect()
+}
+
+/// Checks that `epoch_endings` are the ledger infos ending epochs 0, 1, ... in turn, each signed
+/// by the validators of the epoch before it.
+fn verify_epoch_endings(epoch_endings: &[LedgerInfoWithSignatures]) -> Result<()> {
+    let mut previous_epoch_state = None;
+    for (epoch, li) in epoch_endings.iter().enumerate() {
+        ensure!(
+            li.ledger_info().epoch() == epoch as u64,
+            "LedgerInfo epoch not expected. Expected: {}, actual: {}.",
+            epoch,
+            li.ledger_info().epoch(),
+        );
+        if let Some(epoch_state) = previous_epoch_state {
+            epoch_state.verify(li)?;
+        }
+        previous_epoch_state =
+            Some(li.ledger_info().next_epoch_state().ok_or_else(|| {
+                format_err!("LedgerInfo at epoch {} doesn't end the epoch.", epoch)
+            })?);
+    }
+    Ok(())
+}
+
+/// Cross-checks the waypoints of `sources` and returns the waypoint at `version`, or at the
+/// highest version known to at least `min_sources` sources if it's `None`. Fails if any two
+/// sources disagree at some version, or if fewer than `min_sources` sources know the waypoint.
+pub fn cross_check(
+    sources: &[(String, BTreeMap<Version, Waypoint>)],
+    version: Option<Version>,
+    min_sources: usize,
+) -> Result<Waypoint> {
+    let mut by_version: BTreeMap<Version, Vec<(&str, &Waypoint)>> = BTreeMap::new();
+    for (source, waypoints) in sources {
+        for (version, waypoint) in waypoints {
+            by_version
+                .entry(*version)
+                .or_default()
+                .push((source, waypoint));
+        }
+    }
+
+    for (version, waypoints) in &by_version {
+        let (_, first) = waypoints[0];
+        if waypoints.iter().any(|(_, waypoint)| *waypoint != first) {
+            let listing = waypoints
+                .iter()
+                .map(|(source, waypoint)| format!("  {}: {}", source, waypoint))
+                .collect::<Vec<_>>()
+                .join("\n");
+            bail!(
+                "Sources disagree on the waypoint at version {}:\n{}",
+                version,
+                listing
+            );
+        }
+    }
+
+    let version = match version {
+        Some(version) => version,
+        None => {
+            *by_version
+                .iter()
+                .rev()
+                .find(|(_, waypoints)| waypoints.len() >= min_sources)
+                .ok_or_else(|| {
+                    format_err!("No waypoint is known to {} sources or more.", min_sources)
+                })?
+                .0
+        }
+    };
+    let waypoints = by_version
+        .get(&version)
+        .ok_or_else(|| format_err!("No source knows the waypoint at version {}.", version))?;
+    ensure!(
+        waypoints.len() >= min_sources,
+        "The waypoint at version {} is known to {} sources, {} required.",
+        version,
+        waypoints.len(),
+        min_sources,
+    );
+    Ok(*waypoints[0].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diem_crypto::HashValue;
+    use diem_types::{block_info::BlockInfo, ledger_info::LedgerInfo};
+
+    fn waypoint_at(version: Version) -> Waypoint {
+        let block_info = BlockInfo::new(
+            0,
+            0,
+            HashValue::random(),
+            HashValue::zero(),
+            version,
+            0,
+            None,
+        );
+        Waypoint::new_any(&LedgerInfo::new(block_info, HashValue::zero()))
+    }
+
+    fn source(name: &str, waypoints: &[Waypoint]) -> (String, BTreeMap<Version, Waypoint>) {
+        let waypoints = waypoints
+            .iter()
+            .map(|waypoint| (waypoint.version(), *waypoint))
+            .collect();
+        (name.to_string(), waypoints)
+    }
+
+    #[test]
+    fn test_cross_check() {
+        let (genesis, epoch_1, epoch_2) = (waypoint_at(0), waypoint_at(10), waypoint_at(20));
+        let sources = vec![
+            source("genesis", &[genesis]),
+            source("backup", &[genesis, epoch_1]),
+            source("node", &[genesis, epoch_1, epoch_2]),
+        ];
+
+        // The highest waypoint known to enough sources
+        assert_eq!(cross_check(&sources, None, 2).unwrap(), epoch_1);
+        assert_eq!(cross_check(&sources, None, 3).unwrap(), genesis);
+        assert_eq!(cross_check(&sources, None, 1).unwrap(), epoch_2);
+        assert!(cross_check(&sources, None, 4).is_err());
+
+        // A given version
+        assert_eq!(cross_check(&sources, Some(0), 3).unwrap(), genesis);
+        assert!(cross_check(&sources, Some(20), 2).is_err());
+        assert!(cross_check(&sources, Some(15), 1).is_err());
+    }
+
+    #[test]
+    fn test_cross_check_conflict() {
+        let (genesis, epoch_1) = (waypoint_at(0), waypoint_at(10));
+        let sources = vec![
+            source("backup", &[genesis, epoch_1]),
+            source("node", &[genesis, waypoint_at(10)]),
+        ];
+
+        // Any disagreement fails, even at another version than the one asked for
+        let error = cross_check(&sources, Some(0), 1).unwrap_err().to_string();
+        assert!(error.contains("version 10"));
+        assert!(error.contains("backup") && error.contains("node"));
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::{ensure, format_err, Context, Result};
+use diem_types::transaction::Version;
+use diem_waypoint::{cross_check, WaypointSource};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "diem-waypoint",
+    about = "Derive a waypoint from several independent sources, failing if any two disagree."
+)]
+struct Args {
+    #[structopt(long, parse(from_os_str))]
+    /// (multiple) BCS serialized genesis transaction, giving the waypoint at version 0
+    genesis_blob: Vec<PathBuf>,
+    #[structopt(long, parse(from_os_str))]
+    /// (multiple) Local backup dir, giving the waypoints of its epoch ending backups
+    backup_dir: Vec<PathBuf>,
+    #[structopt(long)]
+    /// (multiple) JSON-RPC endpoint of a full node, giving the waypoints of the epochs it synced
+    node: Vec<String>,
+    #[structopt(long)]
+    /// Version of the waypoint, defaulting to the highest one known to enough sources
+    version: Option<Version>,
+    #[structopt(long, default_value = "2")]
+    /// Number of sources which must know the waypoint
+    min_sources: usize,
+}
+
+fn main() -> Result<()> {
+    let args = Args::from_args();
+
+    let sources: Vec<_> = args
+        .genesis_blob
+        .into_iter()
+        .map(WaypointSource::Genesis)
+        .chain(args.backup_dir.into_iter().map(WaypointSource::Backup))
+        .chain(args.node.into_iter().map(WaypointSource::Node))
+        .collect();
+    ensure!(
+        sources.len() >= args.min_sources,
+        "{} sources given, {} required.",
+        sources.len(),
+        args.min_sources,
+    );
+
+    let mut waypoints = vec![];
+    for source in sources {
+        let source_waypoints = source
+            .fetch_waypoints()
+            .with_context(|| format_err!("Failed to fetch waypoints from {}.", source))?;
+        match source_waypoints.keys().next_back() {
+            Some(version) => eprintln!(
+                "{}: {} waypoints, up to version {}",
+                source,
+                source_waypoints.len(),
+                version
+            ),
+            None => eprintln!("{}: no waypoints", source),
+        }
+        waypoints.push((source.to_string(), source_waypoints));
+    }
+
+    let waypoint = cross_check(&waypoints, args.version, args.min_sources)?;
+    println!("{}", waypoint);
+    Ok(())
+}
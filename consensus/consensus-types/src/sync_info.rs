@@ -97,7 +97,8 @@ impl SyncInfo {
         std::cmp::max(self.highest_certified_round(), self.highest_timeout_round())
     }
 
-    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+    /// Checks the consistency of the certificates, without verifying their signatures.
+    pub fn verify_well_formed(&self) -> anyhow::Result<()> {
         let epoch = self.highest_quorum_cert.certified_block().epoch();
         ensure!(
             epoch == self.highest_commit_cert().certified_block().epoch(),
@@ -116,6 +117,11 @@ impl SyncInfo {
             *self.highest_commit_cert().commit_info() != BlockInfo::empty(),
             "HCC has no committed block"
         );
+        Ok(())
+    }
+
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        self.verify_well_formed()?;
         self.highest_quorum_cert
             .verify(validator)
             .and_then(|_| {
@@ -134,6 +134,16 @@ impl Vote {
         self.timeout_signature.is_some()
     }
 
+    /// Verifies that the consensus data hash of LedgerInfo corresponds to the vote info, and that
+    /// the vote info is consistent, without verifying the signature.
+    pub fn verify_well_formed(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.ledger_info.consensus_data_hash() == self.vote_data.hash(),
+            "Vote's hash mismatch with LedgerInfo"
+        );
+        self.vote_data().verify()
+    }
+
     /// Verifies that the consensus data hash of LedgerInfo corresponds to the vote info,
     /// and then verifies the signature.
     pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
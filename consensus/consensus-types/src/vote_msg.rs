@@ -49,6 +49,15 @@ impl VoteMsg {
         self.vote.vote_data().proposed().id()
     }
 
+    /// Checks the consistency of the vote, without verifying its signature.
+    pub fn verify_well_formed(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.vote().epoch() == self.sync_info.epoch(),
+            "VoteMsg has different epoch"
+        );
+        self.vote().verify_well_formed()
+    }
+
     pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
         ensure!(
             self.vote().epoch() == self.sync_info.epoch(),
@@ -0,0 +1,79 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::{ensure, Result};
+use consensus::forensics::ConsensusDump;
+use consensus_types::safety_data::SafetyData;
+use diem_global_constants::SAFETY_DATA;
+use diem_secure_storage::{KVStorage, OnDiskStorage};
+use std::{fs::File, io, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Inspects the consensus data of a stopped node after an incident")]
+enum Command {
+    /// Exports the blocks, QCs, last vote and highest timeout certificate of the ConsensusDB, the
+    /// safety data and the conflicts found in them as JSON.
+    #[structopt(name = "dump")]
+    Dump {
+        #[structopt(flatten)]
+        input: Input,
+        /// Defaults to stdout.
+        #[structopt(long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Prints the equivocations and safety rule violations found as JSON, exiting with 1 if any.
+    #[structopt(name = "analyze")]
+    Analyze {
+        #[structopt(flatten)]
+        input: Input,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+struct Input {
+    /// The data directory of the node, the one the `consensusdb` directory is in.
+    #[structopt(long, parse(from_os_str))]
+    db: PathBuf,
+    /// The on-disk secure storage of the safety rules of the node, to read its safety data from.
+    #[structopt(long, parse(from_os_str))]
+    safety_rules_storage: Option<PathBuf>,
+}
+
+impl Input {
+    fn load(&self) -> Result<ConsensusDump> {
+        ensure!(self.db.is_dir(), "Invalid directory {:?}", self.db);
+        let safety_data = match &self.safety_rules_storage {
+            Some(path) => {
+                // Opening an OnDiskStorage creates the file if it's missing.
+                ensure!(path.is_file(), "Invalid file {:?}", path);
+                let storage = OnDiskStorage::new(path.clone());
+                Some(storage.get::<SafetyData>(SAFETY_DATA)?.value)
+            }
+            None => None,
+        };
+        ConsensusDump::load(&self.db, safety_data)
+    }
+}
+
+fn main() -> Result<()> {
+    match Command::from_args() {
+        Command::Dump { input, output } => {
+            let dump = input.load()?;
+            match output {
+                Some(path) => serde_json::to_writer_pretty(File::create(path)?, &dump)?,
+                None => serde_json::to_writer_pretty(io::stdout(), &dump)?,
+            }
+        }
+        Command::Analyze { input } => {
+            let conflicts = input.load()?.conflicts;
+            println!("{}", serde_json::to_string_pretty(&conflicts)?);
+            if !conflicts.is_empty() {
+                std::process::exit(1);
+            }
+        }
+    }
+    Ok(())
+}
@@ -7,6 +7,9 @@ use diem_secure_push_metrics::{
 };
 use once_cell::sync::Lazy;
 
+/// The `STATE_GAUGE` field set to whether the remote service answered the last request.
+pub const REMOTE_SERVICE_HEALTHY: &str = "remote_service_healthy";
+
 pub static LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         "diem_safety_rules_latency",
@@ -3,12 +3,18 @@
 
 use crate::{
     persistent_safety_storage::PersistentSafetyStorage,
-    remote_service::{self, RemoteService},
+    remote_service::{self, RemoteClient},
     safety_rules_manager,
+    serializer::{SafetyRulesInput, SerializerClient, TSerializerClient},
+    Error,
 };
-use diem_config::config::{SafetyRulesConfig, SafetyRulesService};
-
-use std::net::SocketAddr;
+use diem_config::config::{
+    RemoteService as RemoteServiceConfig, SafetyRulesConfig, SafetyRulesService,
+    SecureTransportConfig,
+};
+use diem_infallible::Mutex;
+use diem_secure_net::NetworkClient;
+use std::{net::SocketAddr, sync::Arc, thread, time::Duration};
 
 pub struct Process {
     data: Option<ProcessData>,
@@ -25,10 +31,12 @@ impl Process {
             _ => panic!("Unexpected SafetyRules service: {:?}", config.service),
         };
         let server_addr = service.server_address();
+        let secure_transport = service.secure_transport.clone();
 
         Self {
             data: Some(ProcessData {
                 server_addr,
+                secure_transport,
                 storage,
                 verify_vote_proposal_signature,
                 export_consensus_key,
@@ -45,12 +53,14 @@ impl Process {
             data.verify_vote_proposal_signature,
             data.export_consensus_key,
             data.network_timeout,
+            data.secure_transport,
         );
     }
 }
 
 struct ProcessData {
     server_addr: SocketAddr,
+    secure_transport: Option<SecureTransportConfig>,
     storage: PersistentSafetyStorage,
     verify_vote_proposal_signature: bool,
     export_consensus_key: bool,
@@ -58,26 +68,49 @@ struct ProcessData {
     network_timeout: u64,
 }
 
+/// The client side of a SafetyRules process. As the process serves a single connection at a time,
+/// all the clients share the same one, which is health checked while idle.
 pub struct ProcessService {
-    server_addr: SocketAddr,
-    network_timeout_ms: u64,
+    remote_client: Arc<Mutex<RemoteClient>>,
 }
 
 impl ProcessService {
-    pub fn new(server_addr: SocketAddr, network_timeout: u64) -> Self {
-        Self {
-            server_addr,
-            network_timeout_ms: network_timeout,
+    pub fn new(config: &RemoteServiceConfig, network_timeout: u64) -> Self {
+        let mut network_client =
+            NetworkClient::new("safety-rules", config.server_address(), network_timeout);
+        if let Some(secure_transport) = &config.secure_transport {
+            network_client = network_client.with_noise(
+                secure_transport.private_key.private_key(),
+                secure_transport.remote_public_key,
+            );
         }
+        let remote_client = RemoteClient::new(network_client).with_retries(
+            config.max_retries,
+            Duration::from_millis(config.retry_delay_ms),
+        );
+        let remote_client = Arc::new(Mutex::new(remote_client));
+
+        if config.health_check_interval_ms > 0 {
+            let client = Arc::downgrade(&remote_client);
+            let interval = Duration::from_millis(config.health_check_interval_ms);
+            thread::Builder::new()
+                .name("safety-rules-health-check".into())
+                .spawn(move || remote_service::health_check(client, interval))
+                .expect("Failed to spawn the SafetyRules health check thread.");
+        }
+
+        Self { remote_client }
     }
-}
 
-impl RemoteService for ProcessService {
-    fn server_address(&self) -> SocketAddr {
-        self.server_addr
+    pub fn client(&self) -> SerializerClient {
+        SerializerClient::new_client(Box::new(SharedRemoteClient(self.remote_client.clone())))
     }
+}
+
+struct SharedRemoteClient(Arc<Mutex<RemoteClient>>);
 
-    fn network_timeout_ms(&self) -> u64 {
-        self.network_timeout_ms
+impl TSerializerClient for SharedRemoteClient {
+    fn request(&mut self, input: SafetyRulesInput) -> Result<Vec<u8>, Error> {
+        self.0.lock().request(input)
     }
 }
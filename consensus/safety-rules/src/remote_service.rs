@@ -2,13 +2,21 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    counters,
     persistent_safety_storage::PersistentSafetyStorage,
     serializer::{SafetyRulesInput, SerializerClient, SerializerService, TSerializerClient},
     Error, SafetyRules, TSafetyRules,
 };
-use diem_logger::warn;
+use diem_config::config::SecureTransportConfig;
+use diem_infallible::Mutex;
+use diem_logger::{error, warn};
 use diem_secure_net::{NetworkClient, NetworkServer};
-use std::net::SocketAddr;
+use std::{
+    net::SocketAddr,
+    sync::Weak,
+    thread,
+    time::{Duration, Instant},
+};
 
 pub trait RemoteService {
     fn client(&self) -> SerializerClient {
@@ -33,6 +41,7 @@ pub fn execute(
     verify_vote_proposal_signature: bool,
     export_consensus_key: bool,
     network_timeout_ms: u64,
+    secure_transport: Option<SecureTransportConfig>,
 ) {
     let mut safety_rules = SafetyRules::new(
         storage,
@@ -45,6 +54,12 @@ pub fn execute(
 
     let mut serializer_service = SerializerService::new(safety_rules);
     let mut network_server = NetworkServer::new("safety-rules", listen_addr, network_timeout_ms);
+    if let Some(secure_transport) = secure_transport {
+        network_server = network_server.with_noise(
+            secure_transport.private_key.private_key(),
+            secure_transport.remote_public_key,
+        );
+    }
 
     loop {
         if let Err(e) = process_one_message(&mut network_server, &mut serializer_service) {
@@ -63,27 +78,90 @@ fn process_one_message(
     Ok(())
 }
 
-struct RemoteClient {
+/// Checks that the service still answers whenever the client made no request to it for
+/// `interval`, until the client is dropped.
+pub(crate) fn health_check(client: Weak<Mutex<RemoteClient>>, interval: Duration) {
+    loop {
+        thread::sleep(interval);
+        let remote_client = match client.upgrade() {
+            Some(remote_client) => remote_client,
+            None => return,
+        };
+        let mut remote_client = remote_client.lock();
+        if remote_client.idle_time() < interval {
+            continue;
+        }
+        if let Err(err) = remote_client.health_check() {
+            error!("SafetyRules service failed its health check: {}", err);
+        }
+    }
+}
+
+pub(crate) struct RemoteClient {
     network_client: NetworkClient,
+    /// The number of times a request is retried before failing it, unbounded if not set.
+    max_retries: Option<usize>,
+    retry_delay: Duration,
+    last_answer: Instant,
 }
 
 impl RemoteClient {
     pub fn new(network_client: NetworkClient) -> Self {
-        Self { network_client }
+        Self {
+            network_client,
+            max_retries: None,
+            retry_delay: Duration::default(),
+            last_answer: Instant::now(),
+        }
+    }
+
+    pub fn with_retries(mut self, max_retries: usize, retry_delay: Duration) -> Self {
+        self.max_retries = Some(max_retries);
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// The time since the service last answered a request.
+    pub fn idle_time(&self) -> Duration {
+        self.last_answer.elapsed()
+    }
+
+    /// Requests the consensus state once, only to check that the service answers.
+    pub fn health_check(&mut self) -> Result<(), Error> {
+        let input_message = bcs::to_bytes(&SafetyRulesInput::ConsensusState)?;
+        self.process_one_message(&input_message).map(|_| ())
     }
 
     fn process_one_message(&mut self, input: &[u8]) -> Result<Vec<u8>, Error> {
-        self.network_client.write(&input)?;
-        self.network_client.read().map_err(|e| e.into())
+        let result = self
+            .network_client
+            .write(&input)
+            .and_then(|_| self.network_client.read());
+        counters::set_state(counters::REMOTE_SERVICE_HEALTHY, result.is_ok() as i64);
+        if result.is_ok() {
+            self.last_answer = Instant::now();
+        }
+        result.map_err(|e| e.into())
     }
 }
 
 impl TSerializerClient for RemoteClient {
     fn request(&mut self, input: SafetyRulesInput) -> Result<Vec<u8>, Error> {
         let input_message = bcs::to_bytes(&input)?;
+        let mut retries = 0;
         loop {
             match self.process_one_message(&input_message) {
-                Err(err) => warn!("Failed to communicate with SafetyRules service: {}", err),
+                Err(err) => {
+                    warn!("Failed to communicate with SafetyRules service: {}", err);
+                    if self
+                        .max_retries
+                        .map_or(false, |max_retries| retries >= max_retries)
+                    {
+                        return Err(err);
+                    }
+                    retries += 1;
+                    thread::sleep(self.retry_delay);
+                }
                 Ok(value) => return Ok(value),
             }
         }
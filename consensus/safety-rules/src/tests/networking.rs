@@ -1,8 +1,18 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{test_utils, SafetyRulesManager};
+use crate::{remote_service, test_utils, SafetyRulesManager};
+use diem_config::{
+    config::{RemoteService, SecureTransportConfig},
+    utils,
+};
+use diem_crypto::{x25519, Uniform};
 use diem_types::validator_signer::ValidatorSigner;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    thread,
+};
 
 #[test]
 fn test_reconnect() {
@@ -18,3 +28,36 @@ fn test_reconnect() {
     let state1 = safety_rules_manager.client().consensus_state().unwrap();
     assert_eq!(state0, state1);
 }
+
+#[test]
+fn test_secure_transport() {
+    let signer = ValidatorSigner::from_int(0);
+    let storage = test_utils::test_storage(&signer);
+    // test value for network timeout, in milliseconds.
+    let network_timeout = 5_000;
+    let server_key = x25519::PrivateKey::generate(&mut StdRng::from_seed([0; 32]));
+    let client_key = x25519::PrivateKey::generate(&mut StdRng::from_seed([1; 32]));
+    let (server_public_key, client_public_key) = (server_key.public_key(), client_key.public_key());
+
+    let server_port = utils::get_available_port();
+    let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+    thread::spawn(move || {
+        remote_service::execute(
+            storage,
+            server_addr,
+            false,
+            false,
+            network_timeout,
+            Some(SecureTransportConfig::new(server_key, client_public_key)),
+        )
+    });
+
+    let mut config = RemoteService::new(server_addr.into());
+    config.secure_transport = Some(SecureTransportConfig::new(client_key, server_public_key));
+    let safety_rules_manager = SafetyRulesManager::new_process(&config, network_timeout);
+
+    // All the clients share the authenticated connection
+    let state0 = safety_rules_manager.client().consensus_state().unwrap();
+    let state1 = safety_rules_manager.client().consensus_state().unwrap();
+    assert_eq!(state0, state1);
+}
@@ -43,6 +43,7 @@ impl ThreadService {
                 verify_vote_proposal_signature,
                 export_consensus_key,
                 timeout,
+                None,
             )
         });
 
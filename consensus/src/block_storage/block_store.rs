@@ -21,12 +21,16 @@ use consensus_types::{
     timeout_certificate::TimeoutCertificate,
 };
 use diem_crypto::HashValue;
-use diem_infallible::RwLock;
+use diem_infallible::{Mutex, RwLock};
 use diem_logger::prelude::*;
 use diem_types::{ledger_info::LedgerInfoWithSignatures, transaction::TransactionStatus};
 use executor_types::{Error, StateComputeResult};
 use short_hex_str::AsShortHexStr;
-use std::{collections::vec_deque::VecDeque, sync::Arc, time::Duration};
+use std::{
+    collections::vec_deque::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 #[cfg(test)]
 #[path = "block_store_test.rs"]
@@ -39,6 +43,9 @@ mod block_store_and_lec_recovery_test;
 #[path = "sync_manager.rs"]
 pub mod sync_manager;
 
+/// Weight of the latest block in the smoothed execution time.
+const EXECUTION_TIME_SMOOTHING: f64 = 0.2;
+
 fn update_counters_for_committed_blocks(blocks_to_commit: &[Arc<ExecutedBlock>]) {
     for block in blocks_to_commit {
         observe_block(block.block().timestamp_usecs(), BlockStage::COMMITTED);
@@ -94,6 +101,8 @@ pub struct BlockStore {
     storage: Arc<dyn PersistentLivenessStorage>,
     /// Used to ensure that any block stored will have a timestamp < the local time
     time_service: Arc<dyn TimeService>,
+    /// Exponentially smoothed time the blocks with transactions took to execute.
+    execution_time: Mutex<Option<Duration>>,
 }
 
 impl BlockStore {
@@ -176,6 +185,7 @@ impl BlockStore {
             state_computer,
             storage,
             time_service,
+            execution_time: Mutex::new(None),
         };
         for block in blocks {
             block_store
@@ -327,8 +337,17 @@ impl BlockStore {
     fn execute_block(&self, block: Block) -> anyhow::Result<ExecutedBlock, Error> {
         // Although NIL blocks don't have a payload, we still send a T::default() to compute
         // because we may inject a block prologue transaction.
+        let start = Instant::now();
         let state_compute_result = self.state_computer.compute(&block, block.parent_id())?;
         observe_block(block.timestamp_usecs(), BlockStage::EXECUTED);
+        if block.payload().map_or(false, |payload| !payload.is_empty()) {
+            let elapsed = start.elapsed();
+            let mut execution_time = self.execution_time.lock();
+            *execution_time = Some(execution_time.map_or(elapsed, |smoothed| {
+                smoothed.mul_f64(1.0 - EXECUTION_TIME_SMOOTHING)
+                    + elapsed.mul_f64(EXECUTION_TIME_SMOOTHING)
+            }));
+        }
 
         Ok(ExecutedBlock::new(block, state_compute_result))
     }
@@ -450,6 +469,10 @@ impl BlockReader for BlockStore {
             self.highest_timeout_cert().map(|tc| tc.as_ref().clone()),
         )
     }
+
+    fn recent_execution_time(&self) -> Option<Duration> {
+        *self.execution_time.lock()
+    }
 }
 
 #[cfg(any(test, feature = "fuzzing"))]
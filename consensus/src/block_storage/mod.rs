@@ -5,7 +5,7 @@ use consensus_types::{
     executed_block::ExecutedBlock, quorum_cert::QuorumCert, timeout_certificate::TimeoutCertificate,
 };
 use diem_crypto::HashValue;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 mod block_store;
 mod block_tree;
@@ -49,4 +49,7 @@ pub trait BlockReader: Send + Sync {
 
     /// Return the combination of highest quorum cert, timeout cert and commit cert.
     fn sync_info(&self) -> SyncInfo;
+
+    /// Smoothed time the recent blocks with transactions took to execute, None until one did.
+    fn recent_execution_time(&self) -> Option<Duration>;
 }
@@ -4,6 +4,7 @@
 use crate::{
     counters,
     epoch_manager::EpochManager,
+    message_admission::MessageAdmission,
     network::NetworkTask,
     network_interface::{ConsensusNetworkEvents, ConsensusNetworkSender},
     persistent_liveness_storage::StorageWriteProxy,
@@ -23,11 +24,13 @@ use std::sync::Arc;
 use storage_interface::DbReader;
 use tokio::runtime::{self, Runtime};
 
-/// Helper function to start consensus based on configuration and return the runtime
+/// Helper function to start consensus based on configuration and return the runtime. A validator
+/// with observers reaches them through `observer_network`.
 pub fn start_consensus(
     node_config: &NodeConfig,
     network_sender: ConsensusNetworkSender,
     network_events: ConsensusNetworkEvents,
+    observer_network: Option<(ConsensusNetworkSender, ConsensusNetworkEvents)>,
     state_sync_client: StateSyncClient,
     consensus_to_mempool_sender: mpsc::Sender<ConsensusRequest>,
     diem_db: Arc<dyn DbReader>,
@@ -46,16 +49,16 @@ pub fn start_consensus(
         node_config.consensus.mempool_executed_txn_timeout_ms,
     ));
     let execution_correctness_manager = ExecutionCorrectnessManager::new(node_config);
-    let state_computer = Arc::new(ExecutionProxy::new(
-        execution_correctness_manager.client(),
-        state_sync_client,
-    ));
+    let state_computer = Arc::new(
+        ExecutionProxy::new(execution_correctness_manager.client(), state_sync_client)
+            .with_pipelined_commit(node_config.execution.pipelined_commit),
+    );
     let time_service = Arc::new(ClockTimeService::new(runtime.handle().clone()));
 
     let (timeout_sender, timeout_receiver) = channel::new(1_024, &counters::PENDING_ROUND_TIMEOUTS);
     let (self_sender, self_receiver) = channel::new(1_024, &counters::PENDING_SELF_MESSAGES);
 
-    let epoch_mgr = EpochManager::new(
+    let mut epoch_mgr = EpochManager::new(
         node_config,
         time_service,
         self_sender,
@@ -67,7 +70,15 @@ pub fn start_consensus(
         reconfig_events,
     );
 
+    let author = node_config.validator_network.as_ref().unwrap().peer_id();
+    let message_admission = MessageAdmission::new(author, &node_config.consensus.message_limits);
     let (network_task, network_receiver) = NetworkTask::new(network_events, self_receiver);
+    let mut network_task = network_task.with_message_admission(message_admission);
+    if let Some((observer_sender, observer_events)) = observer_network {
+        epoch_mgr = epoch_mgr.with_observer_network(observer_sender);
+        network_task = network_task
+            .with_observer_events(observer_events, node_config.consensus.observers.clone());
+    }
 
     runtime.spawn(network_task.start());
     runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver));
@@ -51,6 +51,21 @@ impl ConsensusDB {
         Self { db }
     }
 
+    /// Opens the ConsensusDB under `db_root_path` for reading, which fails while a node has it
+    /// open.
+    pub fn new_readonly<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        let column_families = vec![
+            /* UNUSED CF = */ DEFAULT_CF_NAME,
+            BLOCK_CF_NAME,
+            QC_CF_NAME,
+            SINGLE_ENTRY_CF_NAME,
+        ];
+
+        let path = db_root_path.as_ref().join("consensusdb");
+        let db = DB::open_readonly(path, "consensus", column_families, &Options::default())?;
+        Ok(Self { db })
+    }
+
     pub fn get_data(
         &self,
     ) -> Result<(
@@ -198,6 +198,15 @@ pub static NUM_BLOCKS_IN_TREE: Lazy<IntGauge> = Lazy::new(|| {
 //     .unwrap()
 // });
 
+/// The max number of txns pulled for a proposal, which adapts to the load with adaptive block size.
+pub static PROPOSAL_BLOCK_SIZE_LIMIT: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_consensus_proposal_block_size_limit",
+        "Max number of txns pulled from mempool for a proposal."
+    )
+    .unwrap()
+});
+
 /// Histogram for the number of txns per (committed) blocks.
 pub static NUM_TXNS_PER_BLOCK: Lazy<Histogram> = Lazy::new(|| {
     register_histogram!(
@@ -253,6 +262,16 @@ pub static PENDING_CONSENSUS_NETWORK_EVENTS: Lazy<IntCounterVec> = Lazy::new(||
     .unwrap()
 });
 
+/// Counter of the messages from peers rejected before reaching consensus, by type and reason
+pub static REJECTED_CONSENSUS_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_consensus_rejected_msgs_count",
+        "Count of the messages from peers rejected before verifying their signatures",
+        &["msg_type", "reason"]
+    )
+    .unwrap()
+});
+
 /// Counters(queued,dequeued,dropped) related to consensus channel
 pub static CONSENSUS_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
@@ -6,12 +6,18 @@ use crate::{
     counters,
     error::{error_kind, DbError},
     liveness::{
-        leader_reputation::{ActiveInactiveHeuristic, DiemDBBackend, LeaderReputation},
+        leader_reputation::{
+            ActiveInactiveHeuristic, DiemDBBackend, LeaderReputation, ProposerAndVoterHeuristic,
+            ReputationHeuristic,
+        },
         proposal_generator::ProposalGenerator,
         proposer_election::ProposerElection,
         rotating_proposer_election::{choose_leader, RotatingProposer},
         round_proposer_election::RoundProposer,
-        round_state::{ExponentialTimeInterval, RoundState, RoundStateLogSchema},
+        round_state::{
+            AdaptiveTimeInterval, ExponentialTimeInterval, RoundState, RoundStateLogSchema,
+            RoundTimeInterval,
+        },
     },
     logging::{LogEvent, LogSchema},
     metrics_safety_rules::MetricsSafetyRules,
@@ -28,7 +34,10 @@ use consensus_types::{
     common::{Author, Round},
     epoch_retrieval::EpochRetrievalRequest,
 };
-use diem_config::config::{ConsensusConfig, ConsensusProposerType, NodeConfig};
+use diem_config::config::{
+    ConsensusConfig, ConsensusProposerType, LeaderReputationConfig, NodeConfig,
+    ReputationHeuristicConfig, RoundTimeoutPolicy,
+};
 use diem_infallible::duration_since_epoch;
 use diem_logger::prelude::*;
 use diem_metrics::monitor;
@@ -36,7 +45,7 @@ use diem_types::{
     account_address::AccountAddress,
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
-    on_chain_config::{OnChainConfigPayload, ValidatorSet},
+    on_chain_config::{OnChainConfig, OnChainConfigPayload, OnChainConsensusConfig},
 };
 use futures::{select, StreamExt};
 use network::protocols::network::Event;
@@ -82,6 +91,10 @@ pub struct EpochManager {
     safety_rules_manager: SafetyRulesManager,
     processor: Option<RoundProcessor>,
     reconfig_events: diem_channel::Receiver<(), OnChainConfigPayload>,
+    // The on-chain consensus config of the current epoch
+    onchain_config: OnChainConsensusConfig,
+    // Reaches the observers, which can't join the validator network
+    observer_network_sender: Option<ConsensusNetworkSender>,
 }
 
 impl EpochManager {
@@ -113,9 +126,20 @@ impl EpochManager {
             safety_rules_manager,
             processor: None,
             reconfig_events,
+            onchain_config: OnChainConsensusConfig::default(),
+            observer_network_sender: None,
         }
     }
 
+    /// Sends to the observers of the config through `observer_network_sender`.
+    pub fn with_observer_network(
+        mut self,
+        observer_network_sender: ConsensusNetworkSender,
+    ) -> Self {
+        self.observer_network_sender = Some(observer_network_sender);
+        self
+    }
+
     fn epoch_state(&self) -> &EpochState {
         match self
             .processor
@@ -136,13 +160,23 @@ impl EpochManager {
         time_service: Arc<dyn TimeService>,
         timeout_sender: channel::Sender<Round>,
     ) -> RoundState {
-        // 1.5^6 ~= 11
-        // Timeout goes from initial_timeout to initial_timeout*11 in 6 steps
-        let time_interval = Box::new(ExponentialTimeInterval::new(
-            Duration::from_millis(self.config.round_initial_timeout_ms),
-            1.2,
-            6,
-        ));
+        let base = Duration::from_millis(self.config.round_initial_timeout_ms);
+        let time_interval: Box<dyn RoundTimeInterval> = match self.config.round_timeout_policy {
+            RoundTimeoutPolicy::Exponential {
+                multiplier,
+                max_exponent,
+            } => Box::new(ExponentialTimeInterval::new(base, multiplier, max_exponent)),
+            RoundTimeoutPolicy::Adaptive {
+                multiplier,
+                max_timeout_ms,
+                stable_rounds,
+            } => Box::new(AdaptiveTimeInterval::new(
+                base,
+                multiplier,
+                Duration::from_millis(max_timeout_ms),
+                stable_rounds,
+            )),
+        };
         RoundState::new(time_interval, time_service, timeout_sender)
     }
 
@@ -168,14 +202,54 @@ impl EpochManager {
                     self.config.contiguous_rounds,
                 ))
             }
-            ConsensusProposerType::LeaderReputation(heuristic_config) => {
-                let backend = Box::new(DiemDBBackend::new(proposers.len(), self.storage.diem_db()));
-                let heuristic = Box::new(ActiveInactiveHeuristic::new(
-                    self.author,
-                    heuristic_config.active_weights,
-                    heuristic_config.inactive_weights,
-                ));
-                Box::new(LeaderReputation::new(proposers, backend, heuristic))
+            ConsensusProposerType::LeaderReputation(local_config) => {
+                // Validators must agree on the leaders, so the on-chain parameters win
+                let (config, source) = match self.onchain_config.leader_reputation() {
+                    Some(params) => (LeaderReputationConfig::from(params), "on-chain"),
+                    None => (*local_config, "local"),
+                };
+                info!(
+                    epoch = epoch_state.epoch,
+                    source = source,
+                    active_weights = config.active_weights,
+                    inactive_weights = config.inactive_weights,
+                    window_size = config.window_size,
+                    round_gap = config.round_gap,
+                    heuristic = ?config.heuristic,
+                    "Leader reputation config",
+                );
+                let window_size = match config.window_size {
+                    0 => proposers.len(),
+                    window_size => window_size,
+                };
+                let backend = Box::new(DiemDBBackend::new(window_size, self.storage.diem_db()));
+                let heuristic: Box<dyn ReputationHeuristic> = match config.heuristic {
+                    ReputationHeuristicConfig::ActiveInactive => {
+                        Box::new(ActiveInactiveHeuristic::new(
+                            self.author,
+                            config.active_weights,
+                            config.inactive_weights,
+                        ))
+                    }
+                    ReputationHeuristicConfig::ProposerAndVoter {
+                        failed_weights,
+                        failure_threshold_percent,
+                        slow_proposal_threshold_ms,
+                    } => Box::new(ProposerAndVoterHeuristic::new(
+                        self.author,
+                        config.active_weights,
+                        config.inactive_weights,
+                        failed_weights,
+                        failure_threshold_percent,
+                        slow_proposal_threshold_ms,
+                    )),
+                };
+                Box::new(LeaderReputation::new(
+                    proposers,
+                    backend,
+                    heuristic,
+                    config.round_gap,
+                ))
             }
             ConsensusProposerType::RoundProposer(round_proposers) => {
                 // Hardcoded to the first proposer
@@ -206,7 +280,11 @@ impl EpochManager {
             .map_err(DbError::from)
             .context("[EpochManager] Failed to get epoch proof")?;
         let msg = ConsensusMsg::EpochChangeProof(Box::new(proof));
-        self.network_sender.send_to(peer_id, msg).context(format!(
+        let network_sender = match &mut self.observer_network_sender {
+            Some(observer_sender) if self.config.observers.contains(&peer_id) => observer_sender,
+            _ => &mut self.network_sender,
+        };
+        network_sender.send_to(peer_id, msg).context(format!(
             "[EpochManager] Failed to send epoch proof to {}",
             peer_id
         ))
@@ -319,7 +397,8 @@ impl EpochManager {
             self.txn_manager.clone(),
             self.time_service.clone(),
             self.config.max_block_size,
-        );
+        )
+        .with_adaptive_block_size(self.config.adaptive_block_size);
 
         info!(epoch = epoch, "Create RoundState");
         let round_state =
@@ -327,12 +406,16 @@ impl EpochManager {
 
         info!(epoch = epoch, "Create ProposerElection");
         let proposer_election = self.create_proposer_election(&epoch_state);
-        let network_sender = NetworkSender::new(
+        let mut network_sender = NetworkSender::new(
             self.author,
             self.network_sender.clone(),
             self.self_sender.clone(),
             epoch_state.verifier.clone(),
         );
+        if let Some(observer_sender) = &self.observer_network_sender {
+            network_sender = network_sender
+                .with_observers(observer_sender.clone(), self.config.observers.clone());
+        }
 
         let mut processor = RoundManager::new(
             epoch_state,
@@ -345,7 +428,8 @@ impl EpochManager {
             self.txn_manager.clone(),
             self.storage.clone(),
             self.config.sync_only,
-        );
+        )
+        .with_observer(self.config.observer);
         processor.start(last_vote).await;
         self.processor = Some(RoundProcessor::Normal(processor));
         info!(epoch = epoch, "RoundManager started");
@@ -378,12 +462,24 @@ impl EpochManager {
     }
 
     async fn start_processor(&mut self, payload: OnChainConfigPayload) {
-        let validator_set: ValidatorSet = payload
-            .get()
+        let epoch_state = payload
+            .epoch_state()
             .expect("failed to get ValidatorSet from payload");
-        let epoch_state = EpochState {
-            epoch: payload.epoch(),
-            verifier: (&validator_set).into(),
+        // The config is left out of the payload until it's published
+        let published = payload
+            .configs()
+            .contains_key(&OnChainConsensusConfig::CONFIG_ID);
+        self.onchain_config = if published {
+            payload.get().unwrap_or_else(|error| {
+                error!(
+                    epoch = epoch_state.epoch,
+                    error = ?error,
+                    "Failed to read the on-chain consensus config, using the default one",
+                );
+                OnChainConsensusConfig::default()
+            })
+        } else {
+            OnChainConsensusConfig::default()
         };
 
         match self.storage.start() {
@@ -418,6 +514,13 @@ impl EpochManager {
                         error = ?err,
                         unverified_event = unverified_event
                     );
+                    if let Err(report_err) = self.network_sender.report_invalid_message(peer_id) {
+                        warn!(
+                            remote_peer = peer_id,
+                            error = ?report_err,
+                            "Failed to report invalid message"
+                        );
+                    }
                     err
                 })?;
 
@@ -0,0 +1,255 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Loads what a node persisted of consensus, the blocks and QCs of its ConsensusDB, its last vote
+//! and highest timeout certificate, along with the safety data of its safety rules, and looks for
+//! evidence of equivocation or of a violation of the safety rules in them.
+
+use crate::consensusdb::ConsensusDB;
+use anyhow::Result;
+use consensus_types::{
+    block::Block, common::Author, quorum_cert::QuorumCert, safety_data::SafetyData,
+    timeout_certificate::TimeoutCertificate, vote::Vote,
+};
+use diem_crypto::HashValue;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+#[cfg(test)]
+#[path = "forensics_test.rs"]
+mod forensics_test;
+
+/// A block along with its id, which isn't part of its serialization.
+#[derive(Serialize)]
+pub struct BlockWithId {
+    /// The id of the block.
+    pub id: HashValue,
+    /// The block.
+    #[serde(flatten)]
+    pub block: Block,
+}
+
+/// What a node persisted of consensus, ordered by round, and the conflicts found in it.
+#[derive(Serialize)]
+pub struct ConsensusDump {
+    /// The blocks of the ConsensusDB.
+    pub blocks: Vec<BlockWithId>,
+    /// The QCs of the ConsensusDB.
+    pub quorum_certs: Vec<QuorumCert>,
+    /// The last vote of the node.
+    pub last_vote: Option<Vote>,
+    /// The highest timeout certificate the node has seen.
+    pub highest_timeout_certificate: Option<TimeoutCertificate>,
+    /// The safety data of the safety rules of the node, if provided.
+    pub safety_data: Option<SafetyData>,
+    /// The conflicts found by `find_conflicts`.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl ConsensusDump {
+    /// Loads the ConsensusDB under the data directory `db_root_path` of a node, which mustn't be
+    /// running.
+    pub fn load<P: AsRef<Path>>(db_root_path: P, safety_data: Option<SafetyData>) -> Result<Self> {
+        let db = ConsensusDB::new_readonly(db_root_path)?;
+        let (last_vote, highest_timeout_certificate, mut blocks, mut quorum_certs) =
+            db.get_data()?;
+        let last_vote: Option<Vote> = last_vote.map(|v| bcs::from_bytes(&v)).transpose()?;
+        let highest_timeout_certificate = highest_timeout_certificate
+            .map(|tc| bcs::from_bytes(&tc))
+            .transpose()?;
+        blocks.sort_by_key(|block| (block.epoch(), block.round()));
+        quorum_certs.sort_by_key(|qc| (qc.certified_block().epoch(), qc.certified_block().round()));
+
+        let conflicts = find_conflicts(
+            &blocks,
+            &quorum_certs,
+            last_vote.as_ref(),
+            safety_data.as_ref(),
+        );
+        Ok(Self {
+            blocks: blocks
+                .into_iter()
+                .map(|block| BlockWithId {
+                    id: block.id(),
+                    block,
+                })
+                .collect(),
+            quorum_certs,
+            last_vote,
+            highest_timeout_certificate,
+            safety_data,
+            conflicts,
+        })
+    }
+}
+
+/// Evidence of a safety violation.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum Conflict {
+    /// An author proposed different blocks in the same round.
+    EquivocatingProposals {
+        /// The epoch of the blocks.
+        epoch: u64,
+        /// The round of the blocks.
+        round: u64,
+        /// The author of the blocks.
+        author: Author,
+        /// The ids of the blocks.
+        blocks: Vec<HashValue>,
+    },
+    /// An author voted for different blocks in the same round, as its signatures in QCs or a last
+    /// vote show.
+    EquivocatingVotes {
+        /// The epoch of the blocks.
+        epoch: u64,
+        /// The round of the blocks.
+        round: u64,
+        /// The author of the votes.
+        author: Author,
+        /// The ids of the blocks voted for.
+        blocks: Vec<HashValue>,
+    },
+    /// Different blocks of the same round got a QC.
+    ConflictingQuorumCerts {
+        /// The epoch of the blocks.
+        epoch: u64,
+        /// The round of the blocks.
+        round: u64,
+        /// The ids of the blocks certified.
+        blocks: Vec<HashValue>,
+    },
+    /// QCs commit different blocks of the same round.
+    ConflictingCommits {
+        /// The epoch of the blocks.
+        epoch: u64,
+        /// The round of the blocks.
+        round: u64,
+        /// The ids of the blocks committed.
+        blocks: Vec<HashValue>,
+    },
+    /// The last vote of the node is in a round above the last voted round of its safety data,
+    /// which lets its safety rules vote again in that round, e.g. once restored from a backup.
+    SafetyDataBehind {
+        /// The epoch of the safety data.
+        epoch: u64,
+        /// The round of the last vote.
+        vote_round: u64,
+        /// The last voted round of the safety data.
+        last_voted_round: u64,
+    },
+}
+
+/// Looks for the conflicts among the blocks, QCs and votes a node persisted, taking the QCs of the
+/// blocks into account as well.
+pub fn find_conflicts(
+    blocks: &[Block],
+    quorum_certs: &[QuorumCert],
+    last_vote: Option<&Vote>,
+    safety_data: Option<&SafetyData>,
+) -> Vec<Conflict> {
+    let mut proposals = BTreeMap::<_, BTreeSet<_>>::new();
+    for block in blocks {
+        if let Some(author) = block.author() {
+            proposals
+                .entry((block.epoch(), block.round(), author))
+                .or_default()
+                .insert(block.id());
+        }
+    }
+
+    let mut votes = BTreeMap::<_, BTreeSet<_>>::new();
+    let mut certified = BTreeMap::<_, BTreeSet<_>>::new();
+    let mut committed = BTreeMap::<_, BTreeSet<_>>::new();
+    for qc in quorum_certs
+        .iter()
+        .chain(blocks.iter().map(|block| block.quorum_cert()))
+    {
+        let block = qc.certified_block();
+        // The QC of genesis isn't voted for.
+        if block.round() == 0 {
+            continue;
+        }
+        certified
+            .entry((block.epoch(), block.round()))
+            .or_default()
+            .insert(block.id());
+        for author in qc.ledger_info().signatures().keys() {
+            votes
+                .entry((block.epoch(), block.round(), *author))
+                .or_default()
+                .insert(block.id());
+        }
+        let commit = qc.commit_info();
+        if commit.id() != HashValue::zero() {
+            committed
+                .entry((commit.epoch(), commit.round()))
+                .or_default()
+                .insert(commit.id());
+        }
+    }
+    for vote in last_vote
+        .into_iter()
+        .chain(safety_data.and_then(|data| data.last_vote.as_ref()))
+    {
+        let block = vote.vote_data().proposed();
+        votes
+            .entry((block.epoch(), block.round(), vote.author()))
+            .or_default()
+            .insert(block.id());
+    }
+
+    let mut conflicts = vec![];
+    for ((epoch, round, author), blocks) in proposals {
+        if blocks.len() > 1 {
+            conflicts.push(Conflict::EquivocatingProposals {
+                epoch,
+                round,
+                author,
+                blocks: blocks.into_iter().collect(),
+            });
+        }
+    }
+    for ((epoch, round, author), blocks) in votes {
+        if blocks.len() > 1 {
+            conflicts.push(Conflict::EquivocatingVotes {
+                epoch,
+                round,
+                author,
+                blocks: blocks.into_iter().collect(),
+            });
+        }
+    }
+    for ((epoch, round), blocks) in certified {
+        if blocks.len() > 1 {
+            conflicts.push(Conflict::ConflictingQuorumCerts {
+                epoch,
+                round,
+                blocks: blocks.into_iter().collect(),
+            });
+        }
+    }
+    for ((epoch, round), blocks) in committed {
+        if blocks.len() > 1 {
+            conflicts.push(Conflict::ConflictingCommits {
+                epoch,
+                round,
+                blocks: blocks.into_iter().collect(),
+            });
+        }
+    }
+    if let (Some(vote), Some(safety_data)) = (last_vote, safety_data) {
+        let vote_round = vote.vote_data().proposed().round();
+        if vote.epoch() == safety_data.epoch && vote_round > safety_data.last_voted_round {
+            conflicts.push(Conflict::SafetyDataBehind {
+                epoch: safety_data.epoch,
+                vote_round,
+                last_voted_round: safety_data.last_voted_round,
+            });
+        }
+    }
+    conflicts
+}
@@ -0,0 +1,88 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use consensus_types::{
+    block::block_test_utils::{certificate_for_genesis, gen_test_certificate},
+    vote_data::VoteData,
+};
+use diem_types::{ledger_info::LedgerInfo, validator_signer::ValidatorSigner};
+
+#[test]
+fn test_find_conflicts() {
+    let signers: Vec<_> = (0..4).map(ValidatorSigner::from_int).collect();
+    let genesis_qc = certificate_for_genesis();
+    let genesis = genesis_qc.certified_block().clone();
+    let a1 = Block::new_proposal(vec![], 1, 1, genesis_qc.clone(), &signers[0]);
+    let a1_info = a1.gen_block_info(HashValue::zero(), 0, None);
+    let qc_a1 = gen_test_certificate(
+        signers[..3].iter().collect(),
+        a1_info.clone(),
+        genesis.clone(),
+        None,
+    );
+    let a2 = Block::new_proposal(vec![], 2, 2, qc_a1.clone(), &signers[1]);
+    let vote_a2 = Vote::new(
+        VoteData::new(a2.gen_block_info(HashValue::zero(), 0, None), a1_info),
+        signers[3].author(),
+        LedgerInfo::new(genesis.clone(), HashValue::zero()),
+        &signers[3],
+    );
+    let safety_data = SafetyData::new(a2.epoch(), 2, 1, Some(vote_a2.clone()));
+
+    // A consistent history has no conflicts.
+    let blocks = vec![a1.clone(), a2.clone()];
+    let qcs = vec![qc_a1];
+    assert!(find_conflicts(&blocks, &qcs, Some(&vote_a2), Some(&safety_data)).is_empty());
+
+    // The same author proposes another block in round 1, which some voters vote for as well.
+    let b1 = Block::new_proposal(vec![], 1, 3, genesis_qc, &signers[0]);
+    let qc_b1 = gen_test_certificate(
+        signers[1..].iter().collect(),
+        b1.gen_block_info(HashValue::zero(), 0, None),
+        genesis,
+        None,
+    );
+    let (a1_id, b1_id) = (a1.id().min(b1.id()), a1.id().max(b1.id()));
+    let blocks = vec![a1, b1, a2.clone()];
+    let mut qcs = qcs;
+    qcs.push(qc_b1);
+    // The safety data was restored from before the last vote.
+    let safety_data = SafetyData::new(a2.epoch(), 1, 0, None);
+
+    let conflicts = find_conflicts(&blocks, &qcs, Some(&vote_a2), Some(&safety_data));
+    let epoch = a2.epoch();
+    assert_eq!(
+        conflicts,
+        vec![
+            Conflict::EquivocatingProposals {
+                epoch,
+                round: 1,
+                author: signers[0].author(),
+                blocks: vec![a1_id, b1_id],
+            },
+            Conflict::EquivocatingVotes {
+                epoch,
+                round: 1,
+                author: signers[1].author(),
+                blocks: vec![a1_id, b1_id],
+            },
+            Conflict::EquivocatingVotes {
+                epoch,
+                round: 1,
+                author: signers[2].author(),
+                blocks: vec![a1_id, b1_id],
+            },
+            Conflict::ConflictingQuorumCerts {
+                epoch,
+                round: 1,
+                blocks: vec![a1_id, b1_id],
+            },
+            Conflict::SafetyDataBehind {
+                epoch,
+                vote_round: 2,
+                last_voted_round: 1,
+            },
+        ]
+    );
+}
@@ -19,9 +19,10 @@ mod epoch_manager;
 mod error;
 mod liveness;
 mod logging;
+mod message_admission;
 mod metrics_safety_rules;
 mod network;
-#[cfg(test)]
+#[cfg(any(test, feature = "fuzzing"))]
 mod network_tests;
 mod pending_votes;
 mod persistent_liveness_storage;
@@ -30,15 +31,18 @@ mod state_computer;
 mod state_replication;
 #[cfg(any(test, feature = "fuzzing"))]
 mod test_utils;
-#[cfg(test)]
-mod twins;
 mod txn_manager;
 mod util;
 
 /// DiemBFT implementation
 pub mod consensus_provider;
+/// Inspection of the consensus data of a node after an incident.
+pub mod forensics;
 /// DiemNet interface.
 pub mod network_interface;
+/// Twins test harness to run Byzantine scenarios against consensus.
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod twins;
 
 #[cfg(feature = "fuzzing")]
 pub use round_manager::round_manager_fuzzing;
@@ -116,37 +116,128 @@ impl ActiveInactiveHeuristic {
 
 impl ReputationHeuristic for ActiveInactiveHeuristic {
     fn get_weights(&self, candidates: &[Author], history: &[NewBlockEvent]) -> Vec<u64> {
-        let mut committed_proposals: usize = 0;
-        let mut committed_votes: usize = 0;
+        let set = active_set(self.author, history);
 
-        let set = history.iter().fold(HashSet::new(), |mut set, meta| {
-            set.insert(meta.proposer());
-            for vote in meta.votes() {
-                set.insert(vote);
-                if vote == self.author {
-                    committed_votes = committed_votes
-                        .checked_add(1)
-                        .expect("Should not overflow the number of committed votes in a window");
+        candidates
+            .iter()
+            .map(|author| {
+                if set.contains(&author) {
+                    self.active_weight
+                } else {
+                    self.inactive_weight
                 }
-            }
-            if meta.proposer() == self.author {
-                committed_proposals = committed_proposals
+            })
+            .collect()
+    }
+}
+
+/// The validators that proposed or voted in the history, reporting how much `author` did.
+fn active_set(author: Author, history: &[NewBlockEvent]) -> HashSet<Author> {
+    let mut committed_proposals: usize = 0;
+    let mut committed_votes: usize = 0;
+
+    let set = history.iter().fold(HashSet::new(), |mut set, meta| {
+        set.insert(meta.proposer());
+        for vote in meta.votes() {
+            set.insert(vote);
+            if vote == author {
+                committed_votes = committed_votes
                     .checked_add(1)
-                    .expect("Should not overflow the number of committed proposals in a window");
+                    .expect("Should not overflow the number of committed votes in a window");
+            }
+        }
+        if meta.proposer() == author {
+            committed_proposals = committed_proposals
+                .checked_add(1)
+                .expect("Should not overflow the number of committed proposals in a window");
+        }
+        set
+    });
+
+    COMMITTED_PROPOSALS_IN_WINDOW.set(committed_proposals as i64);
+    COMMITTED_VOTES_IN_WINDOW.set(committed_votes as i64);
+    set
+}
+
+/// Like `ActiveInactiveHeuristic`, except that active candidates failing more than
+/// `failure_threshold_percent` of the rounds in the history, i.e. missing from the votes of that
+/// share of the blocks, or whose proposals take more than `slow_proposal_threshold_ms` on average
+/// after the block before, are assigned failed_weight.
+pub struct ProposerAndVoterHeuristic {
+    author: Author,
+    active_weight: u64,
+    inactive_weight: u64,
+    failed_weight: u64,
+    failure_threshold_percent: u64,
+    slow_proposal_threshold_ms: u64,
+}
+
+impl ProposerAndVoterHeuristic {
+    pub fn new(
+        author: Author,
+        active_weight: u64,
+        inactive_weight: u64,
+        failed_weight: u64,
+        failure_threshold_percent: u64,
+        slow_proposal_threshold_ms: u64,
+    ) -> Self {
+        Self {
+            author,
+            active_weight,
+            inactive_weight,
+            failed_weight,
+            failure_threshold_percent,
+            slow_proposal_threshold_ms,
+        }
+    }
+
+    fn is_failing(&self, missed_votes: u64, rounds: u64) -> bool {
+        missed_votes * 100 > rounds.saturating_mul(self.failure_threshold_percent)
+    }
+
+    fn is_slow(&self, (total_usecs, proposals): (u64, u64)) -> bool {
+        proposals > 0
+            && total_usecs / proposals > self.slow_proposal_threshold_ms.saturating_mul(1000)
+    }
+}
+
+impl ReputationHeuristic for ProposerAndVoterHeuristic {
+    fn get_weights(&self, candidates: &[Author], history: &[NewBlockEvent]) -> Vec<u64> {
+        let set = active_set(self.author, history);
+
+        let mut votes: HashMap<Author, u64> = HashMap::new();
+        for meta in history {
+            for vote in meta.votes() {
+                *votes.entry(vote).or_insert(0) += 1;
             }
-            set
-        });
+        }
 
-        COMMITTED_PROPOSALS_IN_WINDOW.set(committed_proposals as i64);
-        COMMITTED_VOTES_IN_WINDOW.set(committed_votes as i64);
+        // The time each proposer took after the block before, summed over its blocks proposed in
+        // the round right after, along with their number.
+        let mut by_round = history.iter().collect::<Vec<_>>();
+        by_round.sort_by_key(|meta| meta.round());
+        let mut proposal_times: HashMap<Author, (u64, u64)> = HashMap::new();
+        for pair in by_round.windows(2) {
+            let (prev, meta) = (pair[0], pair[1]);
+            if meta.round() == prev.round() + 1 {
+                let entry = proposal_times.entry(meta.proposer()).or_insert((0, 0));
+                entry.0 += meta.timestamp().saturating_sub(prev.timestamp());
+                entry.1 += 1;
+            }
+        }
 
+        let rounds = history.len() as u64;
         candidates
             .iter()
             .map(|author| {
-                if set.contains(&author) {
-                    self.active_weight
-                } else {
+                let missed_votes = rounds.saturating_sub(*votes.get(author).unwrap_or(&0));
+                let proposal_time = proposal_times.get(author).cloned().unwrap_or((0, 0));
+                if !set.contains(author) {
                     self.inactive_weight
+                } else if self.is_failing(missed_votes, rounds) || self.is_slow(proposal_time) {
+                    self.failed_weight
+                } else {
+                    self.active_weight
                 }
             })
             .collect()
@@ -159,6 +250,8 @@ pub struct LeaderReputation {
     proposers: Vec<Author>,
     backend: Box<dyn MetadataBackend>,
     heuristic: Box<dyn ReputationHeuristic>,
+    // How many rounds before the one elected the history ends.
+    round_gap: Round,
     already_proposed: Mutex<(Round, HashMap<Author, HashValue>)>,
 }
 
@@ -167,11 +260,13 @@ impl LeaderReputation {
         proposers: Vec<Author>,
         backend: Box<dyn MetadataBackend>,
         heuristic: Box<dyn ReputationHeuristic>,
+        round_gap: Round,
     ) -> Self {
         Self {
             proposers,
             backend,
             heuristic,
+            round_gap,
             already_proposed: Mutex::new((0, HashMap::new())),
         }
     }
@@ -179,11 +274,15 @@ impl LeaderReputation {
 
 impl ProposerElection for LeaderReputation {
     fn get_valid_proposer(&self, round: Round) -> Author {
-        // TODO: configure the round gap
-        let target_round = if round >= 4 { round - 4 } else { 0 };
+        let target_round = round.saturating_sub(self.round_gap);
         let sliding_window = self.backend.get_block_metadata(target_round);
         let mut weights = self.heuristic.get_weights(&self.proposers, &sliding_window);
         assert_eq!(weights.len(), self.proposers.len());
+        // With the inactive excluded and no one active, e.g. as the history is lost, fall back to
+        // choosing among all.
+        if weights.iter().all(|w| *w == 0) {
+            weights.iter_mut().for_each(|w| *w = 1);
+        }
         let mut total_weight = 0;
         for w in &mut weights {
             total_weight += *w;
@@ -3,7 +3,8 @@
 
 use crate::liveness::{
     leader_reputation::{
-        ActiveInactiveHeuristic, LeaderReputation, MetadataBackend, ReputationHeuristic,
+        ActiveInactiveHeuristic, LeaderReputation, MetadataBackend, ProposerAndVoterHeuristic,
+        ReputationHeuristic,
     },
     proposer_election::{next, ProposerElection},
 };
@@ -76,6 +77,101 @@ fn test_simple_heuristic() {
     }
 }
 
+fn create_timed_block(
+    round: Round,
+    timestamp_ms: u64,
+    proposer: Author,
+    voters: Vec<&ValidatorSigner>,
+) -> NewBlockEvent {
+    NewBlockEvent::new(
+        round,
+        proposer,
+        voters.iter().map(|v| v.author()).collect(),
+        timestamp_ms * 1000,
+    )
+}
+
+#[test]
+fn test_proposer_and_voter_heuristic() {
+    let (active_weight, inactive_weight, failed_weight) = (100, 1, 10);
+    let mut proposers = vec![];
+    let mut signers = vec![];
+    for i in 0..5 {
+        let signer = ValidatorSigner::random([i; 32]);
+        proposers.push(signer.author());
+        signers.push(signer);
+    }
+    let heuristic = ProposerAndVoterHeuristic::new(
+        proposers[0],
+        active_weight,
+        inactive_weight,
+        failed_weight,
+        50,  /* failure_threshold_percent */
+        500, /* slow_proposal_threshold_ms */
+    );
+    // 0 and 1 vote on every block, 2 on one out of four, 3 is absent and 4 only proposes, slowly.
+    // The time 0 took after the failed round 7 doesn't count.
+    let voters = vec![&signers[0], &signers[1]];
+    let history = vec![
+        create_timed_block(
+            5,
+            1000,
+            proposers[0],
+            vec![&signers[0], &signers[1], &signers[2]],
+        ),
+        create_timed_block(6, 1200, proposers[1], voters.clone()),
+        create_timed_block(8, 5000, proposers[0], voters.clone()),
+        create_timed_block(9, 6000, proposers[4], voters),
+    ];
+    assert_eq!(
+        heuristic.get_weights(&proposers, &history),
+        vec![
+            active_weight,
+            active_weight,
+            failed_weight,
+            inactive_weight,
+            failed_weight,
+        ]
+    );
+
+    // Being a little faster makes 4 active, but only if it doesn't miss the votes.
+    let mut history = history;
+    history[3] = create_timed_block(9, 5400, proposers[4], vec![&signers[0], &signers[1]]);
+    assert_eq!(
+        heuristic.get_weights(&proposers, &history)[4],
+        failed_weight
+    );
+    let all = signers.iter().collect::<Vec<_>>();
+    let history = vec![
+        create_timed_block(1, 0, proposers[0], all.clone()),
+        create_timed_block(2, 400, proposers[4], all),
+    ];
+    assert_eq!(
+        heuristic.get_weights(&proposers, &history)[4],
+        active_weight
+    );
+}
+
+#[test]
+fn test_no_active_proposer() {
+    let mut proposers = vec![];
+    let mut signers = vec![];
+    for i in 0..4 {
+        let signer = ValidatorSigner::random([i; 32]);
+        proposers.push(signer.author());
+        signers.push(signer);
+    }
+    // Excluding the inactive with no history at all still elects someone.
+    let leader_reputation = LeaderReputation::new(
+        proposers.clone(),
+        Box::new(MockHistory::new(4, vec![])),
+        Box::new(ActiveInactiveHeuristic::new(proposers[0], 1, 0)),
+        4, /* round_gap */
+    );
+    let proposer = leader_reputation.get_valid_proposer(42);
+    assert!(proposers.contains(&proposer));
+}
+
 #[test]
 fn test_api() {
     let active_weight = 9;
@@ -99,6 +195,7 @@ fn test_api() {
             active_weight,
             inactive_weight,
         )),
+        4, /* round_gap */
     );
     let round = 42u64;
     // first metadata is ignored because of window size 1
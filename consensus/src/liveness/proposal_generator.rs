@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    block_storage::BlockReader, state_replication::TxnManager, util::time_service::TimeService,
+    block_storage::BlockReader, counters, state_replication::TxnManager,
+    util::time_service::TimeService,
 };
 use anyhow::{bail, ensure, format_err, Context};
 use consensus_types::{
@@ -12,8 +13,9 @@ use consensus_types::{
     quorum_cert::QuorumCert,
 };
 
+use diem_config::config::AdaptiveBlockSizeConfig;
 use diem_infallible::Mutex;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 #[cfg(test)]
 #[path = "proposal_generator_test.rs"]
@@ -40,6 +42,8 @@ pub struct ProposalGenerator {
     time_service: Arc<dyn TimeService>,
     // Max number of transactions to be added to a proposed block.
     max_block_size: u64,
+    // Adapts the number of transactions pulled below max_block_size, if set.
+    adaptive_block_size: Option<AdaptiveBlockSize>,
     // Last round that a proposal was generated
     last_round_generated: Mutex<Round>,
 }
@@ -58,10 +62,19 @@ impl ProposalGenerator {
             txn_manager,
             time_service,
             max_block_size,
+            adaptive_block_size: None,
             last_round_generated: Mutex::new(0),
         }
     }
 
+    /// Adapts the size of the blocks proposed as `config` says instead of always pulling up to
+    /// `max_block_size` transactions.
+    pub fn with_adaptive_block_size(mut self, config: Option<AdaptiveBlockSizeConfig>) -> Self {
+        self.adaptive_block_size =
+            config.map(|config| AdaptiveBlockSize::new(config, self.max_block_size));
+        self
+    }
+
     pub fn author(&self) -> Author {
         self.author
     }
@@ -121,11 +134,21 @@ impl ProposalGenerator {
             // the local time exceeds it.
             let timestamp = self.time_service.get_current_timestamp();
 
+            let max_block_size = self
+                .adaptive_block_size
+                .as_ref()
+                .map_or(self.max_block_size, AdaptiveBlockSize::block_size);
             let payload = self
                 .txn_manager
-                .pull_txns(self.max_block_size, exclude_payload)
+                .pull_txns(max_block_size, exclude_payload)
                 .await
                 .context("Fail to retrieve txn")?;
+            if let Some(adaptive_block_size) = self.adaptive_block_size.as_mut() {
+                adaptive_block_size.update(
+                    payload.len() as u64 >= max_block_size,
+                    self.block_store.recent_execution_time(),
+                );
+            }
 
             (payload, timestamp.as_micros() as u64)
         };
@@ -156,3 +179,43 @@ impl ProposalGenerator {
         Ok(hqc)
     }
 }
+
+/// Tracks the size of the blocks to propose, growing it while mempool fills the blocks and
+/// shrinking it while they take too long to execute, which takes precedence.
+pub(crate) struct AdaptiveBlockSize {
+    config: AdaptiveBlockSizeConfig,
+    max_block_size: u64,
+    block_size: u64,
+}
+
+impl AdaptiveBlockSize {
+    pub fn new(config: AdaptiveBlockSizeConfig, max_block_size: u64) -> Self {
+        let min_block_size = config.min_block_size.max(1).min(max_block_size);
+        Self {
+            config: AdaptiveBlockSizeConfig {
+                min_block_size,
+                ..config
+            },
+            max_block_size,
+            block_size: max_block_size,
+        }
+    }
+
+    pub fn block_size(&self) -> u64 {
+        self.block_size
+    }
+
+    /// Updates the block size after a proposal, given whether mempool filled it and how long the
+    /// recent blocks took to execute.
+    pub fn update(&mut self, block_full: bool, execution_time: Option<Duration>) {
+        let target = Duration::from_millis(self.config.target_execution_ms);
+        if execution_time.map_or(false, |execution_time| execution_time > target) {
+            let decrease = self.block_size * self.config.decrease_percent.min(100) / 100;
+            self.block_size = (self.block_size - decrease).max(self.config.min_block_size);
+        } else if block_full {
+            let increase = (self.block_size * self.config.increase_percent / 100).max(1);
+            self.block_size = (self.block_size + increase).min(self.max_block_size);
+        }
+        counters::PROPOSAL_BLOCK_SIZE_LIMIT.set(self.block_size as i64);
+    }
+}
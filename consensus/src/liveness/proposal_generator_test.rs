@@ -3,13 +3,14 @@
 
 use crate::{
     block_storage::BlockReader,
-    liveness::proposal_generator::ProposalGenerator,
+    liveness::proposal_generator::{AdaptiveBlockSize, ProposalGenerator},
     test_utils::{build_empty_tree, MockTransactionManager, TreeInserter},
     util::mock_time_service::SimulatedTimeService,
 };
 use consensus_types::block::{block_test_utils::certificate_for_genesis, Block};
+use diem_config::config::AdaptiveBlockSizeConfig;
 use diem_types::validator_signer::ValidatorSigner;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 #[tokio::test]
 async fn test_proposal_generation_empty_tree() {
@@ -95,3 +96,37 @@ async fn test_old_proposal_generation() {
     let proposal_err = proposal_generator.generate_proposal(1).await.err();
     assert!(proposal_err.is_some());
 }
+
+#[test]
+fn test_adaptive_block_size() {
+    let config = AdaptiveBlockSizeConfig {
+        min_block_size: 10,
+        target_execution_ms: 500,
+        increase_percent: 10,
+        decrease_percent: 50,
+    };
+    let mut block_size = AdaptiveBlockSize::new(config, 100);
+    let fast = Some(Duration::from_millis(100));
+    let slow = Some(Duration::from_millis(1000));
+    assert_eq!(block_size.block_size(), 100);
+
+    // Slow execution shrinks the blocks down to the min, even when mempool fills them.
+    block_size.update(true, slow);
+    assert_eq!(block_size.block_size(), 50);
+    block_size.update(false, slow);
+    block_size.update(true, slow);
+    block_size.update(false, slow);
+    assert_eq!(block_size.block_size(), 10);
+
+    // Once execution keeps up, full blocks grow by at least one txn, up to the max.
+    block_size.update(false, fast);
+    assert_eq!(block_size.block_size(), 10);
+    block_size.update(true, fast);
+    assert_eq!(block_size.block_size(), 11);
+    block_size.update(true, None);
+    assert_eq!(block_size.block_size(), 12);
+    for _ in 0..100 {
+        block_size.update(true, fast);
+    }
+    assert_eq!(block_size.block_size(), 100);
+}
@@ -62,6 +62,10 @@ pub trait RoundTimeInterval: Send + Sync + 'static {
     /// to calculate the round duration of round 6 and the highest committed round is 3 (meaning
     /// the highest round to commit a block is round 5, then the round index is 0.
     fn get_round_duration(&self, round_index_after_committed_qc: usize) -> Duration;
+
+    /// Called when a round ends, with its index after the committed round as above and whether it
+    /// timed out, for intervals adapting to how rounds go.
+    fn on_round_end(&mut self, _round_index_after_committed_qc: usize, _timed_out: bool) {}
 }
 
 /// Round durations increase exponentially
@@ -113,6 +117,68 @@ impl RoundTimeInterval for ExponentialTimeInterval {
     }
 }
 
+/// Like `ExponentialTimeInterval`, capped at a maximum duration rather than exponent, and with the
+/// exponent reached by rounds timing out kept after the next commit, so that the rounds after it
+/// don't time out again if the network is slower than the base duration. It goes back to 0 once
+/// `stable_rounds` rounds in a row end with a QC.
+pub struct AdaptiveTimeInterval {
+    base_ms: u64,
+    multiplier: f64,
+    max_ms: u64,
+    stable_rounds: u64,
+    // The least exponent of the rounds, raised by the rounds timing out.
+    min_exponent: usize,
+    // How many rounds in a row ended with a QC.
+    qc_rounds_in_a_row: u64,
+}
+
+impl AdaptiveTimeInterval {
+    pub fn new(base: Duration, multiplier: f64, max: Duration, stable_rounds: u64) -> Self {
+        assert!(
+            multiplier >= 1.0,
+            "multiplier for AdaptiveTimeInterval should be at least 1"
+        );
+        assert!(
+            base <= max,
+            "base for AdaptiveTimeInterval should be <= max"
+        );
+        Self {
+            base_ms: base.as_millis() as u64,
+            multiplier,
+            max_ms: max.as_millis() as u64,
+            stable_rounds,
+            min_exponent: 0,
+            qc_rounds_in_a_row: 0,
+        }
+    }
+}
+
+impl RoundTimeInterval for AdaptiveTimeInterval {
+    fn get_round_duration(&self, round_index_after_committed_qc: usize) -> Duration {
+        let pow = round_index_after_committed_qc.max(self.min_exponent);
+        // Large powers saturate to infinity, which the max caps.
+        let duration_ms = (self.base_ms as f64) * self.multiplier.powf(pow as f64);
+        Duration::from_millis(duration_ms.min(self.max_ms as f64).ceil() as u64)
+    }
+
+    fn on_round_end(&mut self, round_index_after_committed_qc: usize, timed_out: bool) {
+        if timed_out {
+            self.qc_rounds_in_a_row = 0;
+            // Once at the max, there's no point going further.
+            if self.get_round_duration(self.min_exponent).as_millis() < self.max_ms as u128 {
+                self.min_exponent = self
+                    .min_exponent
+                    .max(round_index_after_committed_qc.saturating_add(1));
+            }
+        } else {
+            self.qc_rounds_in_a_row += 1;
+            if self.qc_rounds_in_a_row >= self.stable_rounds {
+                self.min_exponent = 0;
+            }
+        }
+    }
+}
+
 /// `RoundState` contains information about a specific round and moves forward when
 /// receives new certificates.
 ///
@@ -229,17 +295,23 @@ impl RoundState {
         }
         let new_round = sync_info.highest_round() + 1;
         if new_round > self.current_round {
-            // Start a new round.
-            self.current_round = new_round;
-            self.pending_votes = PendingVotes::new();
-            self.vote_sent = None;
-            let timeout = self.setup_timeout();
             // The new round reason is QCReady in case both QC and TC are equal
             let new_round_reason = if sync_info.highest_timeout_certificate().is_none() {
                 NewRoundReason::QCReady
             } else {
                 NewRoundReason::Timeout
             };
+            if self.current_round > 0 {
+                self.time_interval.on_round_end(
+                    self.round_index_after_committed_round(),
+                    new_round_reason == NewRoundReason::Timeout,
+                );
+            }
+            // Start a new round.
+            self.current_round = new_round;
+            self.pending_votes = PendingVotes::new();
+            self.vote_sent = None;
+            let timeout = self.setup_timeout();
             let new_round_event = NewRoundEvent {
                 round: self.current_round,
                 reason: new_round_reason,
@@ -290,22 +362,24 @@ impl RoundState {
         timeout
     }
 
+    /// The index of the current round after the committed round, see `RoundTimeInterval`.
+    fn round_index_after_committed_round(&self) -> usize {
+        (if self.highest_committed_round == 0 {
+            // Genesis doesn't require the 3-chain rule for commit, hence start the index at
+            // the round after genesis.
+            self.current_round - 1
+        } else if self.current_round < self.highest_committed_round + 3 {
+            0
+        } else {
+            self.current_round - self.highest_committed_round - 3
+        }) as usize
+    }
+
     /// Setup the current round deadline and return the duration of the current round
     fn setup_deadline(&mut self) -> Duration {
-        let round_index_after_committed_round = {
-            if self.highest_committed_round == 0 {
-                // Genesis doesn't require the 3-chain rule for commit, hence start the index at
-                // the round after genesis.
-                self.current_round - 1
-            } else if self.current_round < self.highest_committed_round + 3 {
-                0
-            } else {
-                self.current_round - self.highest_committed_round - 3
-            }
-        } as usize;
         let timeout = self
             .time_interval
-            .get_round_duration(round_index_after_committed_round);
+            .get_round_duration(self.round_index_after_committed_round());
         let now = self.time_service.get_current_timestamp();
         debug!(
             round = self.current_round,
@@ -3,7 +3,8 @@
 
 use crate::{
     liveness::round_state::{
-        ExponentialTimeInterval, NewRoundEvent, NewRoundReason, RoundState, RoundTimeInterval,
+        AdaptiveTimeInterval, ExponentialTimeInterval, NewRoundEvent, NewRoundReason, RoundState,
+        RoundTimeInterval,
     },
     util::mock_time_service::SimulatedTimeService,
 };
@@ -33,6 +34,37 @@ fn test_round_time_interval() {
     assert_eq!(6750, interval.get_round_duration(1000).as_millis());
 }
 
+#[test]
+fn test_adaptive_round_time_interval() {
+    let mut interval = AdaptiveTimeInterval::new(
+        Duration::from_millis(1000),
+        2.0,
+        Duration::from_millis(5000),
+        3,
+    );
+    assert_eq!(1000, interval.get_round_duration(0).as_millis());
+    assert_eq!(4000, interval.get_round_duration(2).as_millis());
+    // Capped at the max, without overflowing
+    assert_eq!(5000, interval.get_round_duration(3).as_millis());
+    assert_eq!(5000, interval.get_round_duration(1000).as_millis());
+
+    // Two rounds time out, then the next commits: the timeout stays where the timeouts took it.
+    interval.on_round_end(0, true);
+    interval.on_round_end(1, true);
+    interval.on_round_end(2, false);
+    assert_eq!(4000, interval.get_round_duration(0).as_millis());
+    assert_eq!(5000, interval.get_round_duration(3).as_millis());
+    // Until 3 rounds in a row end with a QC.
+    interval.on_round_end(0, false);
+    assert_eq!(4000, interval.get_round_duration(0).as_millis());
+    interval.on_round_end(0, true);
+    interval.on_round_end(0, false);
+    interval.on_round_end(0, false);
+    assert_eq!(4000, interval.get_round_duration(0).as_millis());
+    interval.on_round_end(0, false);
+    assert_eq!(1000, interval.get_round_duration(0).as_millis());
+}
+
 #[tokio::test]
 /// Verify that RoundState properly outputs local timeout events upon timeout
 async fn test_basic_timeout() {
@@ -0,0 +1,117 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Admission control of the messages peers send to consensus, so that a misbehaving peer can't
+//! exhaust the CPU with messages that consensus would only reject after verifying signatures.
+//!
+//! Each peer is rate limited with a token bucket, then every message goes through the checks that
+//! don't involve signatures: size limits and the consistency of the blocks, votes and
+//! certificates it carries.
+
+use crate::network_interface::ConsensusMsg;
+use anyhow::ensure;
+use consensus_types::common::Author;
+use diem_config::config::ConsensusMessageLimitsConfig;
+use diem_rate_limiter::rate_limit::TokenBucketRateLimiter;
+
+#[cfg(test)]
+#[path = "message_admission_test.rs"]
+mod message_admission_test;
+
+/// Why a message is rejected.
+#[derive(Debug)]
+pub enum Rejection {
+    RateLimited,
+    TooLarge,
+    Malformed(anyhow::Error),
+}
+
+impl Rejection {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Rejection::RateLimited => "rate_limited",
+            Rejection::TooLarge => "too_large",
+            Rejection::Malformed(_) => "malformed",
+        }
+    }
+}
+
+pub struct MessageAdmission {
+    author: Author,
+    max_proposal_txns: u64,
+    rate_limiter: TokenBucketRateLimiter<Author>,
+}
+
+impl MessageAdmission {
+    /// Limits the messages from the peers of `author` as `config` says. The messages of `author`
+    /// to itself are always admitted.
+    pub fn new(author: Author, config: &ConsensusMessageLimitsConfig) -> Self {
+        Self {
+            author,
+            max_proposal_txns: config.max_proposal_txns,
+            // New buckets start full, so that a peer reconnecting doesn't start throttled
+            rate_limiter: TokenBucketRateLimiter::new(
+                "consensus",
+                "consensus".to_string(),
+                100,
+                config.burst.max(config.messages_per_sec),
+                config.messages_per_sec,
+                None,
+            ),
+        }
+    }
+
+    pub fn admit(&self, peer: Author, msg: &ConsensusMsg) -> Result<(), Rejection> {
+        if peer == self.author {
+            return Ok(());
+        }
+        self.rate_limiter
+            .bucket(peer)
+            .lock()
+            .acquire_all_tokens(1)
+            .map_err(|_| Rejection::RateLimited)?;
+
+        match msg {
+            ConsensusMsg::ProposalMsg(proposal) => {
+                let txns = proposal.proposal().payload().map_or(0, |p| p.len()) as u64;
+                if txns > self.max_proposal_txns {
+                    return Err(Rejection::TooLarge);
+                }
+                proposal.verify_well_formed()
+            }
+            ConsensusMsg::VoteMsg(vote) => vote
+                .verify_well_formed()
+                .and_then(|_| vote.sync_info().verify_well_formed()),
+            ConsensusMsg::SyncInfo(sync_info) => sync_info.verify_well_formed(),
+            ConsensusMsg::EpochRetrievalRequest(request) => {
+                ensure!(
+                    request.start_epoch < request.end_epoch,
+                    "Empty epoch retrieval range"
+                );
+                Ok(())
+            }
+            ConsensusMsg::EpochChangeProof(proof) => {
+                ensure!(
+                    !proof.ledger_info_with_sigs.is_empty(),
+                    "Empty epoch change proof"
+                );
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+        .map_err(Rejection::Malformed)
+    }
+}
+
+/// The name of the type of `msg`, for metrics.
+pub fn msg_type(msg: &ConsensusMsg) -> &'static str {
+    match msg {
+        ConsensusMsg::BlockRetrievalRequest(_) => "block_retrieval_request",
+        ConsensusMsg::BlockRetrievalResponse(_) => "block_retrieval_response",
+        ConsensusMsg::EpochRetrievalRequest(_) => "epoch_retrieval_request",
+        ConsensusMsg::ProposalMsg(_) => "proposal",
+        ConsensusMsg::SyncInfo(_) => "sync_info",
+        ConsensusMsg::EpochChangeProof(_) => "epoch_change_proof",
+        ConsensusMsg::VoteMsg(_) => "vote",
+    }
+}
@@ -0,0 +1,64 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use consensus_types::{
+    block::{
+        block_test_utils::{certificate_for_genesis, random_payload},
+        Block,
+    },
+    proposal_msg::ProposalMsg,
+    sync_info::SyncInfo,
+};
+use diem_types::validator_signer::ValidatorSigner;
+
+fn proposal(signer: &ValidatorSigner, round: u64, txns: usize) -> ConsensusMsg {
+    let genesis_qc = certificate_for_genesis();
+    let block = Block::new_proposal(random_payload(txns), round, 1, genesis_qc.clone(), signer);
+    let sync_info = SyncInfo::new(genesis_qc.clone(), genesis_qc, None);
+    ConsensusMsg::ProposalMsg(Box::new(ProposalMsg::new(block, sync_info)))
+}
+
+#[test]
+fn test_message_admission() {
+    let (author, peer, other_peer) = (
+        ValidatorSigner::from_int(0),
+        ValidatorSigner::from_int(1),
+        ValidatorSigner::from_int(2),
+    );
+    let admission = MessageAdmission::new(
+        author.author(),
+        &ConsensusMessageLimitsConfig {
+            messages_per_sec: 1,
+            burst: 2,
+            max_proposal_txns: 1,
+        },
+    );
+
+    // The messages to self are never limited.
+    for _ in 0..5 {
+        assert!(admission
+            .admit(author.author(), &proposal(&author, 1, 5))
+            .is_ok());
+    }
+
+    assert!(admission
+        .admit(peer.author(), &proposal(&peer, 1, 1))
+        .is_ok());
+    assert!(matches!(
+        admission.admit(peer.author(), &proposal(&peer, 1, 2)),
+        Err(Rejection::TooLarge)
+    ));
+    // The burst of the peer is used up.
+    assert!(matches!(
+        admission.admit(peer.author(), &proposal(&peer, 1, 1)),
+        Err(Rejection::RateLimited)
+    ));
+
+    // A proposal two rounds after its QC, without a timeout certificate, is rejected before
+    // verifying any signature.
+    assert!(matches!(
+        admission.admit(other_peer.author(), &proposal(&other_peer, 2, 1)),
+        Err(Rejection::Malformed(_))
+    ));
+}
@@ -4,6 +4,7 @@
 use crate::{
     counters,
     logging::LogEvent,
+    message_admission::{self, MessageAdmission},
     network_interface::{ConsensusMsg, ConsensusNetworkEvents, ConsensusNetworkSender},
 };
 use anyhow::{anyhow, ensure};
@@ -21,7 +22,7 @@ use diem_types::{
     account_address::AccountAddress, epoch_change::EpochChangeProof,
     validator_verifier::ValidatorVerifier,
 };
-use futures::{channel::oneshot, stream::select, SinkExt, Stream, StreamExt};
+use futures::{channel::oneshot, future, stream::select, SinkExt, Stream, StreamExt};
 use network::protocols::{network::Event, rpc::error::RpcError};
 use std::{
     mem::{discriminant, Discriminant},
@@ -57,6 +58,10 @@ pub struct NetworkSender {
     // Note that we do not support self rpc requests as it might cause infinite recursive calls.
     self_sender: channel::Sender<Event<ConsensusMsg>>,
     validators: ValidatorVerifier,
+    // Non-voting followers, sent what's broadcast and the votes as well over the network they
+    // reach the validators on, as they can't join the validator network.
+    observer_sender: Option<ConsensusNetworkSender>,
+    observers: Vec<Author>,
 }
 
 impl NetworkSender {
@@ -71,9 +76,23 @@ impl NetworkSender {
             network_sender,
             self_sender,
             validators,
+            observer_sender: None,
+            observers: vec![],
         }
     }
 
+    /// Also sends what's broadcast and the votes to `observers` through `observer_sender`.
+    /// Observers follow consensus without taking part in it.
+    pub fn with_observers(
+        mut self,
+        observer_sender: ConsensusNetworkSender,
+        observers: Vec<Author>,
+    ) -> Self {
+        self.observer_sender = Some(observer_sender);
+        self.observers = observers;
+        self
+    }
+
     /// Tries to retrieve num of blocks backwards starting from id from the given peer: the function
     /// returns a future that is fulfilled with BlockRetrievalResponse.
     pub async fn request_block(
@@ -132,9 +151,13 @@ impl NetworkSender {
             .filter(|author| author != &self_author);
 
         // Broadcast message over direct-send to all other validators.
-        if let Err(err) = self.network_sender.send_to_many(other_validators, msg) {
+        if let Err(err) = self
+            .network_sender
+            .send_to_many(other_validators, msg.clone())
+        {
             error!(error = ?err, "Error broadcasting message");
         }
+        self.send_to_observers(msg);
     }
 
     /// Sends the vote to the chosen recipients (typically that would be the recipients that
@@ -164,6 +187,16 @@ impl NetworkSender {
                 );
             }
         }
+        self.send_to_observers(msg);
+    }
+
+    fn send_to_observers(&self, msg: ConsensusMsg) {
+        if let Some(observer_sender) = &self.observer_sender {
+            let mut observer_sender = observer_sender.clone();
+            if let Err(err) = observer_sender.send_to_many(self.observers.iter().cloned(), msg) {
+                error!(error = ?err, "Error sending message to observers");
+            }
+        }
     }
 
     /// Sends the given sync info to the given author.
@@ -201,6 +234,8 @@ pub struct NetworkTask {
     >,
     block_retrieval_tx: diem_channel::Sender<AccountAddress, IncomingBlockRetrievalRequest>,
     all_events: Box<dyn Stream<Item = Event<ConsensusMsg>> + Send + Unpin>,
+    // Checks the messages received before they are queued, if set.
+    admission: Option<MessageAdmission>,
 }
 
 impl NetworkTask {
@@ -222,6 +257,7 @@ impl NetworkTask {
                 consensus_messages_tx,
                 block_retrieval_tx,
                 all_events,
+                admission: None,
             },
             NetworkReceivers {
                 consensus_messages,
@@ -230,10 +266,53 @@ impl NetworkTask {
         )
     }
 
+    /// Also serves the block and epoch retrievals `observers` send over their network, the only
+    /// messages taken from it.
+    pub fn with_observer_events(
+        mut self,
+        observer_events: ConsensusNetworkEvents,
+        observers: Vec<Author>,
+    ) -> Self {
+        let retrievals = observer_events.filter(move |event| {
+            let is_retrieval = match event {
+                Event::RpcRequest(peer_id, ConsensusMsg::BlockRetrievalRequest(_), _)
+                | Event::Message(peer_id, ConsensusMsg::EpochRetrievalRequest(_)) => {
+                    observers.contains(peer_id)
+                }
+                _ => false,
+            };
+            future::ready(is_retrieval)
+        });
+        self.all_events = Box::new(select(self.all_events, retrievals));
+        self
+    }
+
+    /// Drops the messages `admission` rejects instead of queuing them for consensus.
+    pub fn with_message_admission(mut self, admission: MessageAdmission) -> Self {
+        self.admission = Some(admission);
+        self
+    }
+
     pub async fn start(mut self) {
         while let Some(message) = self.all_events.next().await {
             match message {
                 Event::Message(peer_id, msg) => {
+                    if let Some(admission) = &self.admission {
+                        if let Err(rejection) = admission.admit(peer_id, &msg) {
+                            let msg_type = message_admission::msg_type(&msg);
+                            counters::REJECTED_CONSENSUS_MSGS
+                                .with_label_values(&[msg_type, rejection.label()])
+                                .inc();
+                            sample!(
+                                SampleRate::Duration(Duration::from_secs(1)),
+                                warn!(
+                                    remote_peer = peer_id,
+                                    "Rejected {} msg: {:?}", msg_type, rejection
+                                )
+                            );
+                            continue;
+                        }
+                    }
                     if let Err(e) = self
                         .consensus_messages_tx
                         .push((peer_id, discriminant(&msg)), (peer_id, msg))
@@ -17,7 +17,7 @@ use diem_types::{epoch_change::EpochChangeProof, PeerId};
 use network::{
     constants::NETWORK_CHANNEL_SIZE,
     error::NetworkError,
-    peer_manager::{ConnectionRequestSender, PeerManagerRequestSender},
+    peer_manager::{reputation::Misbehavior, ConnectionRequestSender, PeerManagerRequestSender},
     protocols::{
         network::{NetworkEvents, NetworkSender, NewNetworkSender},
         rpc::error::RpcError,
@@ -136,4 +136,10 @@ impl ConsensusNetworkSender {
             .send_rpc(recipient, protocol, message, timeout)
             .await
     }
+
+    /// Report that the peer sent a message which failed verification.
+    pub fn report_invalid_message(&mut self, peer: PeerId) -> Result<(), NetworkError> {
+        self.network_sender
+            .report_peer(peer, Misbehavior::InvalidMessage)
+    }
 }
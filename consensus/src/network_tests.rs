@@ -1,39 +1,20 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{
-    network::{NetworkReceivers, NetworkSender},
-    network_interface::{ConsensusMsg, ConsensusNetworkEvents, ConsensusNetworkSender},
-    test_utils::{self, consensus_runtime, placeholder_ledger_info, timed_block_on},
-};
-use channel::{self, diem_channel, message_queues::QueueStyle};
-use consensus_types::{
-    block::{block_test_utils::certificate_for_genesis, Block},
-    common::Author,
-    proposal_msg::ProposalMsg,
-    sync_info::SyncInfo,
-    vote::Vote,
-    vote_data::VoteData,
-    vote_msg::VoteMsg,
-};
+use crate::network_interface::ConsensusMsg;
+use channel::{self, diem_channel};
+use consensus_types::common::Author;
 use diem_infallible::{Mutex, RwLock};
-use diem_types::{block_info::BlockInfo, PeerId};
+use diem_types::PeerId;
 use futures::{channel::mpsc, SinkExt, StreamExt};
 use network::{
-    peer_manager::{
-        conn_notifs_channel, ConnectionRequestSender, PeerManagerNotification, PeerManagerRequest,
-        PeerManagerRequestSender,
-    },
-    protocols::{
-        network::{NewNetworkEvents, NewNetworkSender},
-        rpc::InboundRpcRequest,
-    },
+    peer_manager::{PeerManagerNotification, PeerManagerRequest},
+    protocols::rpc::InboundRpcRequest,
     ProtocolId,
 };
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
-    time::Duration,
 };
 use tokio::runtime::Handle;
 
@@ -72,6 +53,8 @@ pub struct NetworkPlayground {
     drop_config: Arc<RwLock<DropConfig>>,
     /// Allow test code to drop direct-send messages between peers per round.
     drop_config_round: DropConfigRound,
+    /// Allow test code to drop the votes some peers send in a round.
+    withheld_votes_round: HashMap<u64, HashSet<TwinId>>,
     /// An executor for spawning node outbound network event handlers
     executor: Handle,
     /// Maps authors to twins IDs
@@ -80,6 +63,7 @@ pub struct NetworkPlayground {
 }
 
 impl NetworkPlayground {
+    /// Create a playground whose node outbound handlers are spawned on `executor`.
     pub fn new(executor: Handle) -> Self {
         let (outbound_msgs_tx, outbound_msgs_rx) = mpsc::channel(1_024);
 
@@ -89,6 +73,7 @@ impl NetworkPlayground {
             outbound_msgs_rx,
             drop_config: Arc::new(RwLock::new(DropConfig::default())),
             drop_config_round: DropConfigRound::default(),
+            withheld_votes_round: HashMap::new(),
             executor,
             author_to_twin_ids: Arc::new(RwLock::new(AuthorToTwinIds::default())),
         }
@@ -307,23 +292,28 @@ impl NetworkPlayground {
         matches!(&msg.1, ConsensusMsg::VoteMsg(_))
     }
 
+    /// Register `twin_id` as one of the nodes `author` messages are delivered to.
     pub fn extend_author_to_twin_ids(&mut self, author: Author, twin_id: TwinId) {
         self.author_to_twin_ids
             .write()
             .extend_author_to_twin_ids(author, twin_id);
     }
 
+    /// Return all the nodes running as `author`.
     pub fn get_twin_ids(&self, author: Author) -> Vec<TwinId> {
         self.author_to_twin_ids.read().get_twin_ids(author)
     }
 
     fn is_message_dropped(&self, src: &TwinId, dst: &TwinId, msg: ConsensusMsg) -> bool {
+        let is_vote = matches!(msg, ConsensusMsg::VoteMsg(_));
         self.drop_config.read().is_message_dropped(src, dst)
             || Self::get_message_round(msg).map_or(false, |r| {
                 self.drop_config_round.is_message_dropped(src, dst, r)
+                    || (is_vote && self.is_vote_withheld_round(src, r))
             })
     }
 
+    /// Drop all the messages between the two partitions, in every round.
     pub fn split_network(
         &self,
         partition_first: Vec<TwinId>,
@@ -339,6 +329,24 @@ impl NetworkPlayground {
         self.drop_config_round.is_message_dropped(src, dst, round)
     }
 
+    /// Check if the votes 'src_twin_id' sends in the given round are withheld
+    pub fn is_vote_withheld_round(&self, src: &TwinId, round: u64) -> bool {
+        self.withheld_votes_round
+            .get(&round)
+            .map_or(false, |voters| voters.contains(src))
+    }
+
+    /// Drops the votes sent by the given nodes in each round, as if they withheld them, while
+    /// their other messages are still delivered.
+    pub fn withhold_votes_round(&mut self, round_voters: &HashMap<u64, Vec<TwinId>>) {
+        for (round, voters) in round_voters.iter() {
+            self.withheld_votes_round
+                .entry(*round)
+                .or_insert_with(HashSet::new)
+                .extend(voters.iter().copied());
+        }
+    }
+
     /// Creates the given per round network partitions
     pub fn split_network_round(
         &mut self,
@@ -358,6 +366,8 @@ impl NetworkPlayground {
         ret
     }
 
+    /// Deliver the messages of all the nodes, except those configured to be dropped, until
+    /// they all shut down.
     pub async fn start(mut self) {
         // Take the next queued message
         while let Some((src_twin_id, net_req)) = self.outbound_msgs_rx.next().await {
@@ -464,15 +474,33 @@ impl DropConfigRound {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::network::NetworkTask;
+    use crate::{
+        network::{NetworkReceivers, NetworkSender, NetworkTask},
+        network_interface::{ConsensusNetworkEvents, ConsensusNetworkSender},
+        test_utils::{self, consensus_runtime, placeholder_ledger_info, timed_block_on},
+    };
     use bytes::Bytes;
-    use consensus_types::block_retrieval::{
-        BlockRetrievalRequest, BlockRetrievalResponse, BlockRetrievalStatus,
+    use channel::message_queues::QueueStyle;
+    use consensus_types::{
+        block::{block_test_utils::certificate_for_genesis, Block},
+        block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse, BlockRetrievalStatus},
+        proposal_msg::ProposalMsg,
+        sync_info::SyncInfo,
+        vote::Vote,
+        vote_data::VoteData,
+        vote_msg::VoteMsg,
     };
     use diem_crypto::HashValue;
-    use diem_types::validator_verifier::random_validator_verifier;
+    use diem_types::{block_info::BlockInfo, validator_verifier::random_validator_verifier};
     use futures::{channel::oneshot, future};
-    use network::protocols::direct_send::Message;
+    use network::{
+        peer_manager::{conn_notifs_channel, ConnectionRequestSender, PeerManagerRequestSender},
+        protocols::{
+            direct_send::Message,
+            network::{NewNetworkEvents, NewNetworkSender},
+        },
+    };
+    use std::time::Duration;
 
     #[test]
     fn test_split_network_round() {
@@ -522,6 +550,39 @@ mod tests {
         assert!(!playground.is_message_dropped_round(&nodes[0], &nodes[3], 2));
     }
 
+    #[test]
+    fn test_withhold_votes_round() {
+        let runtime = consensus_runtime();
+        let mut playground = NetworkPlayground::new(runtime.handle().clone());
+
+        let (signers, _validator_verifier) = random_validator_verifier(3, None, false);
+        let nodes: Vec<_> = signers
+            .iter()
+            .enumerate()
+            .map(|(i, signer)| TwinId {
+                id: i,
+                author: signer.author(),
+            })
+            .collect();
+
+        let mut round_voters: HashMap<u64, Vec<TwinId>> = HashMap::new();
+        // Round 1: node 0 withholds its vote
+        round_voters.insert(1, vec![nodes[0]]);
+        // Round 2: nodes 1 and 2 withhold their votes
+        round_voters.insert(2, vec![nodes[1], nodes[2]]);
+        playground.withhold_votes_round(&round_voters);
+
+        assert!(playground.is_vote_withheld_round(&nodes[0], 1));
+        assert!(!playground.is_vote_withheld_round(&nodes[1], 1));
+        assert!(!playground.is_vote_withheld_round(&nodes[0], 2));
+        assert!(playground.is_vote_withheld_round(&nodes[1], 2));
+        assert!(playground.is_vote_withheld_round(&nodes[2], 2));
+        // Votes are only withheld in the given rounds
+        assert!(!playground.is_vote_withheld_round(&nodes[0], 3));
+        // Withholding votes doesn't partition the network
+        assert!(!playground.is_message_dropped_round(&nodes[0], &nodes[1], 1));
+    }
+
     #[test]
     fn test_network_api() {
         let mut runtime = consensus_runtime();
@@ -190,6 +190,9 @@ pub struct RoundManager {
     txn_manager: Arc<dyn TxnManager>,
     storage: Arc<dyn PersistentLivenessStorage>,
     sync_only: bool,
+    // Follow the rounds, executing proposals and forming certificates from the votes received,
+    // without ever voting or proposing.
+    observer: bool,
 }
 
 impl RoundManager {
@@ -219,9 +222,18 @@ impl RoundManager {
             txn_manager,
             storage,
             sync_only,
+            observer: false,
         }
     }
 
+    /// Makes this node a consensus observer, which commits blocks as soon as it receives the
+    /// votes certifying them, without taking part in consensus.
+    pub fn with_observer(mut self, observer: bool) -> Self {
+        counters::OP_COUNTERS.gauge("observer").set(observer as i64);
+        self.observer = observer;
+        self
+    }
+
     fn create_block_retriever(&self, author: Author) -> BlockRetriever {
         BlockRetriever::new(self.network.clone(), author)
     }
@@ -256,9 +268,10 @@ impl RoundManager {
             self.new_log(LogEvent::NewRound),
             reason = new_round_event.reason
         );
-        if self
-            .proposer_election
-            .is_valid_proposer(self.proposal_generator.author(), new_round_event.round)
+        if !self.observer
+            && self
+                .proposer_election
+                .is_valid_proposer(self.proposal_generator.author(), new_round_event.round)
         {
             let proposal_msg =
                 ConsensusMsg::ProposalMsg(Box::new(self.generate_proposal(new_round_event).await?));
@@ -431,6 +444,11 @@ impl RoundManager {
             return Ok(());
         }
 
+        // Observers don't take part in timeouts, they move on as certificates come.
+        if self.observer {
+            return Ok(());
+        }
+
         if self.sync_only {
             self.network
                 .broadcast(ConsensusMsg::SyncInfo(Box::new(
@@ -470,7 +488,8 @@ impl RoundManager {
             self.block_store.sync_info(),
         )));
         self.network.broadcast(timeout_vote_msg).await;
-        warn!( //////// 0L ////////
+        warn!(
+            //////// 0L ////////
             round = round,
             remote_peer = self.proposer_election.get_valid_proposer(round),
             voted = use_last_vote,
@@ -524,6 +543,13 @@ impl RoundManager {
 
         observe_block(proposal.timestamp_usecs(), BlockStage::SYNCED);
 
+        if self.observer {
+            self.block_store
+                .execute_and_insert_block(proposal)
+                .context("[RoundManager] Failed to execute_and_insert the block")?;
+            return Ok(());
+        }
+
         let proposal_round = proposal.round();
         let vote = self
             .execute_and_vote(proposal)
@@ -639,7 +665,8 @@ impl RoundManager {
             vote_state = vote.vote_data().proposed().executed_state_id(),
         );
 
-        if !vote.is_timeout() {
+        // Observers aggregate the votes of all the rounds.
+        if !vote.is_timeout() && !self.observer {
             // Unlike timeout votes regular votes are sent to the leaders of the next round only.
             let next_round = round + 1;
             ensure!(
@@ -48,7 +48,7 @@ use futures::{
     channel::{mpsc, oneshot},
     executor::block_on,
     stream::select,
-    Stream, StreamExt,
+    FutureExt, Stream, StreamExt,
 };
 use network::{
     peer_manager::{conn_notifs_channel, ConnectionRequestSender, PeerManagerRequestSender},
@@ -70,6 +70,7 @@ pub struct NodeSetup {
     commit_cb_receiver: mpsc::UnboundedReceiver<LedgerInfoWithSignatures>,
     _state_sync_receiver: mpsc::UnboundedReceiver<Payload>,
     id: usize,
+    observer: bool,
 }
 
 impl NodeSetup {
@@ -89,14 +90,25 @@ impl NodeSetup {
         executor: Handle,
         num_nodes: usize,
     ) -> Vec<Self> {
-        let (signers, validators) = random_validator_verifier(num_nodes, None, false);
+        Self::create_nodes_and_observers(playground, executor, num_nodes, 0).0
+    }
+
+    /// Like `create_nodes`, along with `num_observers` observers of them.
+    fn create_nodes_and_observers(
+        playground: &mut NetworkPlayground,
+        executor: Handle,
+        num_nodes: usize,
+        num_observers: usize,
+    ) -> (Vec<Self>, Vec<Self>) {
+        let (mut signers, validators) = random_validator_verifier(num_nodes, None, false);
+        signers.extend((0..num_observers).map(|i| ValidatorSigner::random([100 + i as u8; 32])));
         let proposer_author = signers[0].author();
         let validator_set = (&validators).into();
         let waypoint =
             Waypoint::new_epoch_boundary(&LedgerInfo::mock_genesis(Some(validator_set))).unwrap();
 
         let mut nodes = vec![];
-        for (id, signer) in signers.iter().take(num_nodes).enumerate() {
+        for (id, signer) in signers.iter().enumerate() {
             let (initial_data, storage) = MockStorage::start_for_testing((&validators).into());
 
             let safety_storage = PersistentSafetyStorage::initialize(
@@ -118,9 +130,11 @@ impl NodeSetup {
                 initial_data,
                 safety_rules_manager,
                 id,
+                id >= num_nodes,
             ));
         }
-        nodes
+        let observers = nodes.split_off(num_nodes);
+        (nodes, observers)
     }
 
     fn new(
@@ -132,6 +146,7 @@ impl NodeSetup {
         initial_data: RecoveryData,
         safety_rules_manager: SafetyRulesManager,
         id: usize,
+        observer: bool,
     ) -> Self {
         let epoch_state = EpochState {
             epoch: 1,
@@ -189,7 +204,10 @@ impl NodeSetup {
         let proposer_election = Self::create_proposer_election(proposer_author);
         let mut safety_rules =
             MetricsSafetyRules::new(safety_rules_manager.client(), storage.clone());
-        safety_rules.perform_initialize().unwrap();
+        // Observers aren't in the validator set, so there's nothing for them to initialize.
+        if !observer {
+            safety_rules.perform_initialize().unwrap();
+        }
 
         let mut round_manager = RoundManager::new(
             epoch_state,
@@ -202,7 +220,8 @@ impl NodeSetup {
             Arc::new(MockTransactionManager::new(None)),
             storage.clone(),
             false,
-        );
+        )
+        .with_observer(observer);
         block_on(round_manager.start(last_vote_sent));
         Self {
             block_store,
@@ -215,6 +234,7 @@ impl NodeSetup {
             commit_cb_receiver,
             _state_sync_receiver,
             id,
+            observer,
         }
     }
 
@@ -232,6 +252,7 @@ impl NodeSetup {
             recover_data,
             self.safety_rules_manager,
             self.id,
+            self.observer,
         )
     }
 
@@ -336,6 +357,73 @@ fn vote_on_successful_proposal() {
     });
 }
 
+#[test]
+/// An observer executes the proposals and forms the QCs from the votes it receives, without
+/// voting on timeout or otherwise.
+fn observer_follows_without_voting() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let (mut nodes, mut observers) =
+        NodeSetup::create_nodes_and_observers(&mut playground, runtime.handle().clone(), 1, 1);
+    let node = &mut nodes[0];
+    let observer = &mut observers[0];
+    timed_block_on(&mut runtime, async {
+        let proposal_msg = node.next_proposal().await;
+        let b1_id = proposal_msg.proposal().id();
+        observer
+            .round_manager
+            .process_proposal_msg(proposal_msg.clone())
+            .await
+            .unwrap();
+        assert!(observer.block_store.get_block(b1_id).is_some());
+
+        node.round_manager
+            .process_proposal_msg(proposal_msg)
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+        observer
+            .round_manager
+            .process_vote_msg(vote_msg)
+            .await
+            .unwrap();
+        assert!(observer
+            .block_store
+            .get_quorum_cert_for_block(b1_id)
+            .is_some());
+        assert_eq!(observer.round_manager.round_state().current_round(), 2);
+
+        observer
+            .round_manager
+            .process_local_timeout(2)
+            .await
+            .unwrap();
+        // The observer sent nothing, not even to itself.
+        assert!(observer.all_events.next().now_or_never().is_none());
+    });
+}
+
+#[test]
+/// An observer checks proposals as the validators do before executing them.
+fn observer_rejects_proposal_from_the_future() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let (nodes, mut observers) =
+        NodeSetup::create_nodes_and_observers(&mut playground, runtime.handle().clone(), 1, 1);
+    let observer = &mut observers[0];
+    let genesis_qc = certificate_for_genesis();
+    timed_block_on(&mut runtime, async {
+        let proposal = Block::new_proposal(vec![], 1, u64::MAX, genesis_qc, &nodes[0].signer);
+        let proposal_id = proposal.id();
+        assert!(observer
+            .round_manager
+            .process_proposal(proposal)
+            .await
+            .is_err());
+        assert!(observer.block_store.get_block(proposal_id).is_none());
+    });
+}
+
 #[test]
 /// If the proposal does not pass voting rules,
 /// No votes are sent, but the block is still added to the block tree.
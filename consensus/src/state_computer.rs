@@ -8,18 +8,31 @@ use diem_crypto::HashValue;
 use diem_infallible::Mutex;
 use diem_logger::prelude::*;
 use diem_metrics::monitor;
-use diem_types::ledger_info::LedgerInfoWithSignatures;
+use diem_types::{
+    contract_event::ContractEvent, ledger_info::LedgerInfoWithSignatures, transaction::Transaction,
+};
 use execution_correctness::ExecutionCorrectness;
 use executor_types::{Error as ExecutionError, StateComputeResult};
 use fail::fail_point;
 use state_sync::client::StateSyncClient;
 use std::boxed::Box;
 
+#[cfg(test)]
+#[path = "state_computer_test.rs"]
+mod state_computer_test;
+
+/// The committed transactions and reconfiguration events State Sync is notified of.
+type CommitNotification = (Vec<Transaction>, Vec<ContractEvent>);
+
 /// Basic communication with the Execution module;
 /// implements StateComputer traits.
 pub struct ExecutionProxy {
     execution_correctness_client: Mutex<Box<dyn ExecutionCorrectness + Send + Sync>>,
     synchronizer: StateSyncClient,
+    // Whether the BlockExecutor returns from `commit_blocks` before the blocks are in Storage.
+    pipelined_commit: bool,
+    // The notification of the last commit, while it may still be in flight.
+    pending_notification: Mutex<Option<CommitNotification>>,
 }
 
 impl ExecutionProxy {
@@ -30,6 +43,28 @@ impl ExecutionProxy {
         Self {
             execution_correctness_client: Mutex::new(execution_correctness_client),
             synchronizer,
+            pipelined_commit: false,
+            pending_notification: Mutex::new(None),
+        }
+    }
+
+    /// Matches a BlockExecutor pipelining commits. State Sync reads the committed state from
+    /// Storage, so it is notified of a commit only once it has landed: after the next commit or
+    /// sync, which wait for it, or right away for commits ending an epoch, which are waited for.
+    pub fn with_pipelined_commit(mut self, pipelined_commit: bool) -> Self {
+        self.pipelined_commit = pipelined_commit;
+        self
+    }
+
+    async fn notify_state_sync(&self, notification: CommitNotification) {
+        let (committed_txns, reconfig_events) = notification;
+        if let Err(e) = monitor!(
+            "notify_state_sync",
+            self.synchronizer
+                .commit(committed_txns, reconfig_events)
+                .await
+        ) {
+            error!(error = ?e, "Failed to notify state synchronizer");
         }
     }
 }
@@ -69,19 +104,24 @@ impl StateComputer for ExecutionProxy {
         block_ids: Vec<HashValue>,
         finality_proof: LedgerInfoWithSignatures,
     ) -> Result<(), ExecutionError> {
-        let (committed_txns, reconfig_events) = monitor!(
+        let in_flight = self.pipelined_commit && !finality_proof.ledger_info().ends_epoch();
+        // The previous commit has landed once the BlockExecutor takes this one. If it fails
+        // instead, the previous commit may have failed too, and its notification is dropped
+        // rather than telling Mempool about transactions that are not in Storage.
+        let landed = self.pending_notification.lock().take();
+        let notification = monitor!(
             "commit_block",
             self.execution_correctness_client
                 .lock()
                 .commit_blocks(block_ids, finality_proof)?
         );
-        if let Err(e) = monitor!(
-            "notify_state_sync",
-            self.synchronizer
-                .commit(committed_txns, reconfig_events)
-                .await
-        ) {
-            error!(error = ?e, "Failed to notify state synchronizer");
+        if let Some(landed) = landed {
+            self.notify_state_sync(landed).await;
+        }
+        if in_flight {
+            *self.pending_notification.lock() = Some(notification);
+        } else {
+            self.notify_state_sync(notification).await;
         }
         Ok(())
     }
@@ -91,6 +131,13 @@ impl StateComputer for ExecutionProxy {
         fail_point!("consensus::sync_to", |_| {
             Err(anyhow::anyhow!("Injected error in sync_to").into())
         });
+        // A commit of the BlockExecutor may still be in flight if commits are pipelined, and
+        // must land before State Sync writes to Storage. Resetting waits for it.
+        let landed = self.pending_notification.lock().take();
+        self.execution_correctness_client.lock().reset()?;
+        if let Some(landed) = landed {
+            self.notify_state_sync(landed).await;
+        }
         // Here to start to do state synchronization where ChunkExecutor inside will
         // process chunks and commit to Storage. However, after block execution and
         // commitments, the the sync state of ChunkExecutor may be not up to date so
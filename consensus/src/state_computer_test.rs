@@ -0,0 +1,174 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{state_computer::ExecutionProxy, state_replication::StateComputer};
+use consensus_types::block::Block;
+use diem_crypto::HashValue;
+use diem_mempool::CommitResponse;
+use diem_types::{
+    account_address::AccountAddress,
+    block_info::BlockInfo,
+    block_metadata::BlockMetadata,
+    contract_event::ContractEvent,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    transaction::Transaction,
+};
+use execution_correctness::ExecutionCorrectness;
+use executor_types::{Error, StateComputeResult};
+use futures::{channel::mpsc, StreamExt};
+use state_sync::client::{CoordinatorMessage, StateSyncClient};
+use std::collections::BTreeMap;
+
+struct MockExecutionCorrectness;
+
+impl ExecutionCorrectness for MockExecutionCorrectness {
+    fn committed_block_id(&mut self) -> Result<HashValue, Error> {
+        Ok(HashValue::zero())
+    }
+
+    fn reset(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn execute_block(
+        &mut self,
+        _block: Block,
+        _parent_block_id: HashValue,
+    ) -> Result<StateComputeResult, Error> {
+        unimplemented!()
+    }
+
+    /// Commits one transaction per block
+    fn commit_blocks(
+        &mut self,
+        block_ids: Vec<HashValue>,
+        _ledger_info_with_sigs: LedgerInfoWithSignatures,
+    ) -> Result<(Vec<Transaction>, Vec<ContractEvent>), Error> {
+        Ok((block_ids.into_iter().map(block_txn).collect(), vec![]))
+    }
+}
+
+fn block_txn(block_id: HashValue) -> Transaction {
+    Transaction::BlockMetadata(BlockMetadata::new(
+        block_id,
+        0,
+        0,
+        vec![],
+        AccountAddress::ZERO,
+    ))
+}
+
+fn ledger_info(ends_epoch: bool) -> LedgerInfoWithSignatures {
+    let next_epoch_state = if ends_epoch {
+        Some(EpochState::empty())
+    } else {
+        None
+    };
+    let block_info = BlockInfo::new(
+        1,
+        1,
+        HashValue::zero(),
+        HashValue::zero(),
+        0,
+        0,
+        next_epoch_state,
+    );
+    LedgerInfoWithSignatures::new(
+        LedgerInfo::new(block_info, HashValue::zero()),
+        BTreeMap::new(),
+    )
+}
+
+/// Builds an `ExecutionProxy`, along with the transactions State Sync is notified of
+fn execution_proxy(
+    pipelined_commit: bool,
+) -> (ExecutionProxy, mpsc::UnboundedReceiver<Vec<Transaction>>) {
+    let (coordinator_sender, mut coordinator_receiver) = mpsc::unbounded();
+    let (notified_sender, notified_receiver) = mpsc::unbounded();
+    tokio::spawn(async move {
+        while let Some(message) = coordinator_receiver.next().await {
+            match message {
+                CoordinatorMessage::CommitNotification(notification) => {
+                    notified_sender
+                        .unbounded_send(notification.committed_transactions)
+                        .unwrap();
+                    let _ = notification.callback.send(Ok(CommitResponse::success()));
+                }
+                CoordinatorMessage::SyncRequest(request) => {
+                    let _ = request.callback.send(Ok(()));
+                }
+                _ => unreachable!(),
+            }
+        }
+    });
+    let proxy = ExecutionProxy::new(
+        Box::new(MockExecutionCorrectness),
+        StateSyncClient::new(coordinator_sender, 1_000),
+    )
+    .with_pipelined_commit(pipelined_commit);
+    (proxy, notified_receiver)
+}
+
+#[tokio::test]
+async fn test_commit_notifies_state_sync() {
+    let (proxy, mut notified) = execution_proxy(false);
+    let blocks = vec![HashValue::random(), HashValue::random()];
+
+    proxy
+        .commit(blocks.clone(), ledger_info(false))
+        .await
+        .unwrap();
+    let txns = blocks.into_iter().map(block_txn).collect::<Vec<_>>();
+    assert_eq!(notified.try_next().unwrap().unwrap(), txns);
+}
+
+#[tokio::test]
+async fn test_pipelined_commit_notifies_state_sync_once_landed() {
+    let (proxy, mut notified) = execution_proxy(true);
+    let blocks = (0..4).map(|_| HashValue::random()).collect::<Vec<_>>();
+
+    // The commit may still be in flight
+    proxy
+        .commit(vec![blocks[0]], ledger_info(false))
+        .await
+        .unwrap();
+    assert!(notified.try_next().is_err());
+
+    // The next commit waited for it
+    proxy
+        .commit(vec![blocks[1]], ledger_info(false))
+        .await
+        .unwrap();
+    assert_eq!(
+        notified.try_next().unwrap().unwrap(),
+        vec![block_txn(blocks[0])]
+    );
+    assert!(notified.try_next().is_err());
+
+    // So does a sync
+    proxy.sync_to(ledger_info(false)).await.unwrap();
+    assert_eq!(
+        notified.try_next().unwrap().unwrap(),
+        vec![block_txn(blocks[1])]
+    );
+
+    // A commit ending an epoch is waited for right away
+    proxy
+        .commit(vec![blocks[2]], ledger_info(false))
+        .await
+        .unwrap();
+    proxy
+        .commit(vec![blocks[3]], ledger_info(true))
+        .await
+        .unwrap();
+    assert_eq!(
+        notified.try_next().unwrap().unwrap(),
+        vec![block_txn(blocks[2])]
+    );
+    assert_eq!(
+        notified.try_next().unwrap().unwrap(),
+        vec![block_txn(blocks[3])]
+    );
+    assert!(notified.try_next().is_err());
+}
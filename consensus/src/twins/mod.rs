@@ -1,5 +1,21 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+//! Twins runs a network of validators, some of which have a twin sharing their keys, over a
+//! `NetworkPlayground` that controls the delivery of their messages. This lets integration tests
+//! script Byzantine scenarios against a consensus configuration:
+//! - duplicate leaders: twins of a proposer both propose in the same round, e.g. with a
+//!   `RoundProposer` picking a node that has a twin;
+//! - withheld votes: `NetworkPlayground::withhold_votes_round` drops the votes of some nodes in
+//!   some rounds;
+//! - partitioned rounds: `NetworkPlayground::split_network_round` drops the messages between
+//!   partitions in some rounds, and `NetworkPlayground::split_network` in all of them.
+//!
+//! Outside of this crate, the harness is available with the `fuzzing` feature.
+
+#[cfg(test)]
 mod basic_twins_test;
 mod twins_node;
+
+pub use crate::network_tests::{NetworkPlayground, TwinId};
+pub use twins_node::SMRNode;
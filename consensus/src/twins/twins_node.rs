@@ -14,6 +14,7 @@ use channel::{self, diem_channel, message_queues::QueueStyle};
 use consensus_types::common::{Author, Payload, Round};
 use diem_config::{
     config::{
+        ConsensusConfig,
         ConsensusProposerType::{self, RoundProposer},
         NodeConfig, WaypointConfig,
     },
@@ -36,8 +37,11 @@ use tokio::runtime::{Builder, Runtime};
 
 /// Auxiliary struct that is preparing SMR for the test
 pub struct SMRNode {
+    /// The id of the node in the `NetworkPlayground`.
     pub id: TwinId,
+    /// The storage of the node, e.g. to get its ledger info.
     pub storage: Arc<MockStorage>,
+    /// Receives the ledger info of every commit of the node.
     pub commit_cb_receiver: mpsc::UnboundedReceiver<LedgerInfoWithSignatures>,
     _runtime: Runtime,
     _shared_mempool: MockSharedMempool,
@@ -138,6 +142,31 @@ impl SMRNode {
         proposer_type: ConsensusProposerType,
         round_proposers_idx: Option<HashMap<Round, usize>>,
     ) -> Vec<Self> {
+        Self::start_num_nodes_with_twins_and_config(
+            num_nodes,
+            num_twins,
+            playground,
+            proposer_type,
+            round_proposers_idx,
+            |_| (),
+        )
+    }
+
+    /// Starts a given number of nodes and their twins, with the consensus config of every node
+    /// adjusted by `configure` once the test settings are applied.
+    /// The first `num_twins` nodes have a twin, and `round_proposers_idx` maps rounds to the
+    /// index of their proposer among the nodes sorted by author.
+    pub fn start_num_nodes_with_twins_and_config<F>(
+        num_nodes: usize,
+        num_twins: usize,
+        playground: &mut NetworkPlayground,
+        proposer_type: ConsensusProposerType,
+        round_proposers_idx: Option<HashMap<Round, usize>>,
+        configure: F,
+    ) -> Vec<Self>
+    where
+        F: Fn(&mut ConsensusConfig),
+    {
         assert!(num_nodes >= num_twins);
         let ValidatorSwarm {
             nodes: mut node_configs,
@@ -200,6 +229,7 @@ impl SMRNode {
             config.consensus.safety_rules.verify_vote_proposal_signature = false;
             // Disable timeout in twins test to avoid flakiness
             config.consensus.round_initial_timeout_ms = 2_000_000;
+            configure(&mut config.consensus);
 
             let author = author_from_config(&config);
 
@@ -2,18 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use channel::diem_channel::Receiver;
-use diem_types::{
-    account_config::NewEpochEvent,
-    on_chain_config::{OnChainConfigPayload, ON_CHAIN_CONFIG_REGISTRY},
-};
-use subscription_service::ReconfigSubscription;
+use diem_types::on_chain_config::OnChainConfigPayload;
+use subscription_service::ReconfigNotificationBus;
 
 /// Creates consensus's subscription to reconfiguration notification from state sync
 pub fn gen_consensus_reconfig_subscription(
-) -> (ReconfigSubscription, Receiver<(), OnChainConfigPayload>) {
-    ReconfigSubscription::subscribe_all(
-        "consensus",
-        ON_CHAIN_CONFIG_REGISTRY.to_vec(),
-        vec![NewEpochEvent::event_key()],
-    )
+    reconfig_bus: &ReconfigNotificationBus,
+) -> Receiver<(), OnChainConfigPayload> {
+    reconfig_bus.subscribe_to_epoch_changes("consensus")
 }
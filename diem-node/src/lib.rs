@@ -9,22 +9,32 @@ use diem_config::{
     network_id::NodeNetworkId,
     utils::get_genesis_txn,
 };
+use diem_event_publisher::bootstrap_from_config as bootstrap_event_publisher;
 use diem_json_rpc::bootstrap_from_config as bootstrap_rpc;
 use diem_logger::{prelude::*, Logger};
-use diem_mempool::gen_mempool_reconfig_subscription;
+use diem_mempool::{
+    gen_mempool_reconfig_subscription, MempoolReader, PendingTransaction, RemovedTransaction,
+};
 use diem_metrics::metric_server;
 use diem_time_service::TimeService;
 use diem_types::{
-    account_config::diem_root_address, account_state::AccountState, chain_id::ChainId,
-    move_resource::MoveStorage, PeerId,
+    account_address::AccountAddress,
+    account_config::diem_root_address,
+    account_state::AccountState,
+    chain_id::{ChainId, NamedChain},
+    move_resource::MoveStorage,
+    PeerId,
 };
 use diem_vm::DiemVM;
-use diemdb::DiemDB;
+use diemdb::{new_archive_storage, DiemDB, PrunerOptions};
 use executor::{db_bootstrapper::maybe_bootstrap, Executor};
 use executor_types::ChunkExecutor;
 use futures::{channel::mpsc::channel, executor::block_on};
 use network_builder::builder::NetworkBuilder;
-use state_sync::bootstrapper::StateSyncBootstrapper;
+use state_sync::{
+    bootstrapper::StateSyncBootstrapper,
+    sync_status::{SyncStatus, SyncStatusReader},
+};
 use std::{
     boxed::Box,
     convert::TryFrom,
@@ -35,10 +45,11 @@ use std::{
         Arc,
     },
     thread,
-    time::Instant,
+    time::{Duration, Instant},
 };
-use storage_interface::DbReaderWriter;
+use storage_interface::{DbReader, DbReaderWriter};
 use storage_service::start_storage_service_with_db;
+use subscription_service::ReconfigNotificationBus;
 use tokio::runtime::{Builder, Runtime};
 use tokio_stream::wrappers::IntervalStream;
 
@@ -54,6 +65,7 @@ pub struct DiemHandle {
     _consensus_runtime: Option<Runtime>,
     _debug: NodeDebugService,
     _backup: Runtime,
+    _event_publisher: Option<thread::JoinHandle<()>>,
 }
 
 pub fn start(config: &NodeConfig, log_file: Option<PathBuf>) {
@@ -103,6 +115,123 @@ pub fn start(config: &NodeConfig, log_file: Option<PathBuf>) {
     }
 }
 
+/// Serves JSON-RPC from a secondary instance of the DB of a node running in another process on the
+/// same data directory, so read traffic doesn't contend with the node. `secondary_dir` is where the
+/// secondary instance keeps its own files. New data becomes visible every `catch_up_interval`.
+///
+/// There's no mempool behind it, so transaction submission fails and no pending transactions are
+/// reported.
+pub fn start_read_only_json_rpc(
+    config: &NodeConfig,
+    secondary_dir: PathBuf,
+    catch_up_interval: Duration,
+) {
+    crash_handler::setup_panic_handler();
+
+    let mut logger = diem_logger::Logger::new();
+    logger
+        .channel_size(config.logger.chan_size)
+        .is_async(config.logger.is_async)
+        .level(config.logger.level)
+        .read_env();
+    logger.build();
+    info!(
+        config = config,
+        "Loaded DiemNode config, serving JSON-RPC read only"
+    );
+
+    let mut diem_db = DiemDB::open_as_secondary(
+        config.storage.dir(),
+        secondary_dir,
+        config.storage.rocksdb_config.clone(),
+    )
+    .expect("DB should open as secondary.")
+    .with_event_type_index(config.storage.event_type_index)
+    .with_indexer(config.storage.event_indexer)
+    .with_proof_cache(config.storage.proof_cache_size);
+    if let Some(archive_config) = &config.storage.archive {
+        diem_db = diem_db.with_archive_storage(
+            new_archive_storage(&archive_config.storage).expect("Archive storage should open."),
+        );
+    }
+    let (diem_db, db_rw) = DbReaderWriter::wrap(diem_db);
+    let chain_id = fetch_chain_id(&db_rw);
+
+    let catch_up_db = Arc::clone(&diem_db);
+    thread::Builder::new()
+        .name("db-catch-up".to_string())
+        .spawn(move || loop {
+            thread::sleep(catch_up_interval);
+            if let Err(e) = catch_up_db.try_catch_up_with_primary() {
+                warn!(error = ?e, "Failed to catch up with the primary DB.");
+            }
+        })
+        .expect("Failed to spawn DB catch up thread.");
+
+    // Nobody's receiving on the other end, submissions are rejected.
+    let (mp_client_sender, _) = channel(AC_SMP_CHANNEL_BUFFER_SIZE);
+    let sync_status = ReadOnlySyncStatus(diem_db.clone());
+    let _rpc = bootstrap_rpc(
+        config,
+        chain_id,
+        diem_db,
+        mp_client_sender,
+        Arc::new(NoMempool),
+        Arc::new(sync_status),
+    );
+
+    let term = Arc::new(AtomicBool::new(false));
+    while !term.load(Ordering::Acquire) {
+        std::thread::park();
+    }
+}
+
+/// Stands in for the mempool when serving JSON-RPC read only.
+struct NoMempool;
+
+impl MempoolReader for NoMempool {
+    fn get_account_transactions(&self, _account: &AccountAddress) -> Vec<PendingTransaction> {
+        vec![]
+    }
+
+    fn get_all_transactions(&self) -> Vec<PendingTransaction> {
+        vec![]
+    }
+
+    fn get_size_and_capacity(&self) -> (usize, usize) {
+        (0, 0)
+    }
+
+    fn get_removed_transaction(
+        &self,
+        _account: &AccountAddress,
+        _sequence_number: u64,
+    ) -> Option<RemovedTransaction> {
+        None
+    }
+}
+
+/// Stands in for state sync when serving JSON-RPC read only: the node follows the primary DB.
+struct ReadOnlySyncStatus(Arc<DiemDB>);
+
+impl SyncStatusReader for ReadOnlySyncStatus {
+    fn get_sync_status(&self) -> SyncStatus {
+        let synced_version = self.0.get_latest_version().unwrap_or(0);
+        let committed_version = self
+            .0
+            .get_latest_ledger_info()
+            .map_or(0, |li| li.ledger_info().version());
+        SyncStatus {
+            highest_known_version: synced_version,
+            synced_version,
+            committed_version,
+            initialized: true,
+            sync_rate: 0.0,
+            estimated_catch_up_time: Some(Duration::from_secs(0)),
+        }
+    }
+}
+
 fn setup_metrics(peer_id: PeerId, config: &NodeConfig) {
     diem_metrics::dump_all_metrics_to_file_periodically(
         &config.metrics.dir(),
@@ -263,21 +392,37 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
     });
 
     let mut instant = Instant::now();
-    let (diem_db, db_rw) = DbReaderWriter::wrap(
-        DiemDB::open(
-            &node_config.storage.dir(),
-            false, /* readonly */
-            node_config.storage.prune_window,
-            node_config.storage.rocksdb_config,
-        )
-        .expect("DB should open."),
-    );
+    let mut diem_db = DiemDB::open_with_pruner_options(
+        &node_config.storage.dir(),
+        false, /* readonly */
+        PrunerOptions {
+            historical_versions_to_keep: node_config.storage.prune_window,
+            window_secs: node_config.storage.prune_window_secs,
+            prune_ledger: node_config.storage.prune_ledger_history,
+        },
+        node_config.storage.rocksdb_config.clone(),
+    )
+    .expect("DB should open.")
+    .with_event_type_index(node_config.storage.event_type_index)
+    .with_indexer(node_config.storage.event_indexer)
+    .with_proof_cache(node_config.storage.proof_cache_size);
+    if let Some(archive_config) = &node_config.storage.archive {
+        diem_db = diem_db
+            .with_archive(archive_config)
+            .expect("Archive storage should open.");
+    }
+    let (diem_db, db_rw) = DbReaderWriter::wrap(diem_db);
+    let stats_db = Arc::clone(&diem_db);
+    debug_if.add_stats_provider("rocksdb", move || {
+        Ok(serde_json::to_value(stats_db.get_rocksdb_stats()?)?)
+    });
     let _simple_storage_service = start_storage_service_with_db(&node_config, Arc::clone(&diem_db));
     let backup_service = start_backup_service(
         node_config.storage.backup_service_address,
         Arc::clone(&diem_db),
     );
 
+    DiemVM::set_concurrency_level_once(node_config.execution.concurrency_level);
     let genesis_waypoint = node_config.base.waypoint.genesis_waypoint();
     // if there's genesis txn and waypoint, commit it if the result matches.
     if let Some(genesis) = get_genesis_txn(&node_config) {
@@ -299,21 +444,24 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
         instant.elapsed().as_millis()
     );
     let chain_id = fetch_chain_id(&db_rw);
+    // Custom natives are for local networks, whose nodes can all be made to register them.
+    assert!(
+        !DiemVM::has_custom_natives()
+            || !matches!(
+                NamedChain::from_chain_id(&chain_id),
+                Ok(NamedChain::MAINNET) | Ok(NamedChain::PREMAINNET)
+            ),
+        "Custom native functions can't be registered on {}",
+        chain_id
+    );
     let mut network_runtimes = vec![];
     let mut state_sync_network_handles = vec![];
     let mut mempool_network_handles = vec![];
     let mut consensus_network_handles = None;
-    let mut reconfig_subscriptions = vec![];
-
-    let (mempool_reconfig_subscription, mempool_reconfig_events) =
-        gen_mempool_reconfig_subscription();
-    reconfig_subscriptions.push(mempool_reconfig_subscription);
-    // consensus has to subscribe to ALL on-chain configs
-    let (consensus_reconfig_subscription, consensus_reconfig_events) =
-        gen_consensus_reconfig_subscription();
-    if node_config.base.role.is_validator() {
-        reconfig_subscriptions.push(consensus_reconfig_subscription);
-    }
+    let mut observer_network_handles = None;
+    let reconfig_bus = ReconfigNotificationBus::new();
+
+    let mempool_reconfig_events = gen_mempool_reconfig_subscription(&reconfig_bus);
 
     // Gather all network configs into a single vector.
     let mut network_configs: Vec<&NetworkConfig> = node_config.full_node_networks.iter().collect();
@@ -353,8 +501,19 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
             mempool_events,
         ));
 
+        // Observers follow consensus over the VFN network, as they aren't validators and can't
+        // join the validator network. It's also the network validators reach them on.
+        if network_id.is_vfn_network()
+            && (node_config.consensus.observer || !node_config.consensus.observers.is_empty())
+        {
+            observer_network_handles = Some(
+                network_builder
+                    .add_protocol_handler(consensus::network_interface::network_endpoint_config()),
+            );
+        }
+
         // Perform steps relevant specifically to Validator networks.
-        if network_id.is_validator_network() {
+        if network_id.is_validator_network() && !node_config.consensus.observer {
             // A valid config is allowed to have at most one ValidatorNetwork
             // TODO:  `expect_none` would be perfect here, once it is stable.
             if consensus_network_handles.is_some() {
@@ -367,7 +526,11 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
             );
         }
 
-        reconfig_subscriptions.append(network_builder.reconfig_subscriptions());
+        for subscription in network_builder.reconfig_subscriptions().drain(..) {
+            reconfig_bus
+                .register(subscription)
+                .expect("Failed to subscribe the network to reconfigurations");
+        }
 
         network_builders.push(network_builder);
     }
@@ -395,6 +558,78 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
         network_builder.start();
     }
 
+    // Serve the scores of the peers of every network, keyed by network id.
+    let peer_reputations: Vec<_> = network_builders
+        .iter()
+        .map(|network_builder| {
+            (
+                network_builder
+                    .network_context()
+                    .network_id()
+                    .as_str()
+                    .to_string(),
+                network_builder.peer_reputation(),
+            )
+        })
+        .collect();
+    debug_if.add_stats_provider("network_peers", move || {
+        let mut peers = serde_json::Map::new();
+        for (network_id, peer_reputation) in &peer_reputations {
+            peers.insert(
+                network_id.clone(),
+                serde_json::to_value(peer_reputation.read().peers())?,
+            );
+        }
+        Ok(serde_json::Value::Object(peers))
+    });
+
+    // Serve the traffic of every protocol of every network, keyed by network id.
+    let network_contexts: Vec<_> = network_builders
+        .iter()
+        .map(|network_builder| network_builder.network_context())
+        .collect();
+    debug_if.add_stats_provider("network_protocols", move || {
+        let mut traffic = serde_json::Map::new();
+        for network_context in &network_contexts {
+            traffic.insert(
+                network_context.network_id().as_str().to_string(),
+                serde_json::to_value(network::counters::protocol_traffic(network_context))?,
+            );
+        }
+        Ok(serde_json::Value::Object(traffic))
+    });
+
+    // Serve the addresses of every network, including the external one for nodes behind a NAT
+    // once their port is mapped, for operators to advertise.
+    let network_addresses: Vec<_> = network_builders
+        .iter()
+        .map(|network_builder| {
+            (
+                network_builder
+                    .network_context()
+                    .network_id()
+                    .as_str()
+                    .to_string(),
+                network_builder.listen_address().to_string(),
+                network_builder.external_address(),
+            )
+        })
+        .collect();
+    debug_if.add_stats_provider("network_addresses", move || {
+        let mut addresses = serde_json::Map::new();
+        for (network_id, listen_address, external_address) in &network_addresses {
+            let external_address = external_address.read().as_ref().map(ToString::to_string);
+            addresses.insert(
+                network_id.clone(),
+                serde_json::json!({
+                    "listen_address": listen_address,
+                    "external_address": external_address,
+                }),
+            );
+        }
+        Ok(serde_json::Value::Object(addresses))
+    });
+
     // TODO set up on-chain discovery network based on UpstreamConfig.fallback_network
     // and pass network handles to mempool/state sync
 
@@ -404,21 +639,19 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
     let state_sync_bootstrapper = StateSyncBootstrapper::bootstrap(
         state_sync_network_handles,
         state_sync_to_mempool_sender,
-        Arc::clone(&db_rw.reader),
+        db_rw.clone(),
         chunk_executor,
         node_config,
         genesis_waypoint,
-        reconfig_subscriptions,
+        reconfig_bus.clone(),
     );
     let (mp_client_sender, mp_client_events) = channel(AC_SMP_CHANNEL_BUFFER_SIZE);
 
-    let rpc_runtime = bootstrap_rpc(&node_config, chain_id, diem_db.clone(), mp_client_sender);
-
     let mut consensus_runtime = None;
     let (consensus_to_mempool_sender, consensus_requests) = channel(INTRA_NODE_CHANNEL_BUFFER_SIZE);
 
     instant = Instant::now();
-    let mempool = diem_mempool::bootstrap(
+    let (mempool, mempool_reader) = diem_mempool::bootstrap(
         node_config,
         Arc::clone(&db_rw.reader),
         mempool_network_handles,
@@ -429,9 +662,22 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
     );
     debug!("Mempool started in {} ms", instant.elapsed().as_millis());
 
+    let rpc_runtime = bootstrap_rpc(
+        &node_config,
+        chain_id,
+        diem_db.clone(),
+        mp_client_sender,
+        mempool_reader,
+        state_sync_bootstrapper.sync_status_reader(),
+    );
+    let event_publisher = bootstrap_event_publisher(&node_config, diem_db.clone());
+
     // StateSync should be instantiated and started before Consensus to avoid a cyclic dependency:
     // network provider -> consensus -> state synchronizer -> network provider.  This has resulted
     // in a deadlock as observed in GitHub issue #749.
+    if node_config.consensus.observer {
+        consensus_network_handles = observer_network_handles.take();
+    }
     if let Some((consensus_network_sender, consensus_network_events)) = consensus_network_handles {
         let state_sync_client =
             state_sync_bootstrapper.create_client(node_config.state_sync.client_commit_timeout_ms);
@@ -451,10 +697,13 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
             node_config,
             consensus_network_sender,
             consensus_network_events,
+            observer_network_handles,
             state_sync_client,
             consensus_to_mempool_sender,
             diem_db,
-            consensus_reconfig_events,
+            // consensus has to subscribe to ALL on-chain configs, which it's sent the latest of
+            // right away as state sync is already running
+            gen_consensus_reconfig_subscription(&reconfig_bus),
         ));
         debug!("Consensus started in {} ms", instant.elapsed().as_millis());
     }
@@ -473,5 +722,6 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
         _consensus_runtime: consensus_runtime,
         _debug: debug_if,
         _backup: backup_service,
+        _event_publisher: event_publisher,
     }
 }
@@ -4,7 +4,7 @@
 #![forbid(unsafe_code)]
 
 use diem_config::config::NodeConfig;
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -21,6 +21,20 @@ struct Args {
     test: bool,
     #[structopt(long, help = "Enabling random ports for testnet")]
     random_ports: bool,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        conflicts_with = "test",
+        help = "Serve JSON-RPC only, reading the DB of a node running on the same data directory \
+        as a RocksDB secondary instance, which keeps its own files in this directory"
+    )]
+    read_only_secondary: Option<PathBuf>,
+    #[structopt(
+        long,
+        default_value = "1000",
+        help = "How often the read only secondary DB catches up with the node, in milliseconds"
+    )]
+    catch_up_interval_ms: u64,
 }
 
 #[global_allocator]
@@ -32,6 +46,13 @@ fn main() {
     if args.test {
         println!("Entering test mode, this should never be used in production!");
         diem_node::load_test_environment(args.config, args.random_ports);
+    } else if let Some(secondary_dir) = args.read_only_secondary {
+        let config = NodeConfig::load(args.config.unwrap()).expect("Failed to load node config");
+        diem_node::start_read_only_json_rpc(
+            &config,
+            secondary_dir,
+            Duration::from_millis(args.catch_up_interval_ms),
+        );
     } else {
         let config = NodeConfig::load(args.config.unwrap()).expect("Failed to load node config");
         println!("Using node config {:?}", &config);
@@ -0,0 +1,32 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use diem_metrics::{register_int_counter, register_int_gauge, IntCounter, IntGauge};
+use once_cell::sync::Lazy;
+
+/// Number of transactions published, counting those published again after a failure
+pub static PUBLISHED_TRANSACTIONS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_event_publisher_published_transactions_count",
+        "Number of transactions published to the message broker"
+    )
+    .unwrap()
+});
+
+/// Number of failed attempts at reading or publishing a batch
+pub static PUBLISH_ERRORS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_event_publisher_errors_count",
+        "Number of failed attempts at publishing a batch of transactions"
+    )
+    .unwrap()
+});
+
+/// The next version to publish, as recorded in the cursor
+pub static NEXT_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_event_publisher_next_version",
+        "Next version to publish to the message broker"
+    )
+    .unwrap()
+});
@@ -0,0 +1,67 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use diem_types::transaction::Version;
+use std::{
+    fs::{self, File},
+    io::{ErrorKind, Write},
+    path::PathBuf,
+};
+
+/// File recording the next version to publish
+pub struct Cursor {
+    path: PathBuf,
+}
+
+impl Cursor {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The version recorded, `None` if nothing was recorded yet
+    pub fn load(&self) -> Result<Option<Version>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).context(format!("failed to read {:?}", self.path)),
+        };
+        let version = contents
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid cursor in {:?}", self.path))?;
+        Ok(Some(version))
+    }
+
+    /// Records `version`. The file is replaced atomically, so that a crash leaves either the
+    /// previous version or this one.
+    pub fn store(&self, version: Version) -> Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        let mut file = File::create(&tmp_path)?;
+        writeln!(file, "{}", version)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diem_temppath::TempPath;
+
+    #[test]
+    fn test_cursor() {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        let cursor = Cursor::new(dir.path().join("cursor"));
+        assert_eq!(cursor.load().unwrap(), None);
+
+        cursor.store(10).unwrap();
+        cursor.store(42).unwrap();
+        assert_eq!(cursor.load().unwrap(), Some(42));
+
+        fs::write(dir.path().join("cursor"), "not a version").unwrap();
+        assert!(cursor.load().is_err());
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Publishes committed transactions, with their decoded events, to a message broker, so that
+//! indexers can consume them instead of tailing storage themselves.
+//!
+//! A tailer reads transactions from storage in version order and hands them to an `EventSink` in
+//! batches. Once the sink acknowledges a batch, the version following it is recorded in a cursor
+//! file, from which publishing resumes after a restart. Delivery is at least once: a batch
+//! published but not recorded before a crash is published again, so consumers should deduplicate
+//! messages by version.
+//!
+//! The brokers supported are Kafka, with the `kafka` feature, and NATS JetStream, with the `nats`
+//! feature.
+
+mod counters;
+mod cursor;
+mod sink;
+mod tailer;
+
+pub use cursor::Cursor;
+pub use sink::{connect, EventSink, Message};
+pub use tailer::Tailer;
+
+use diem_config::config::NodeConfig;
+use diem_logger::prelude::*;
+use std::{sync::Arc, thread, time::Duration};
+use storage_interface::DbReader;
+
+/// Starts publishing on its own thread, if the config has a sink
+pub fn bootstrap_from_config(
+    config: &NodeConfig,
+    db: Arc<dyn DbReader>,
+) -> Option<thread::JoinHandle<()>> {
+    let config = config.event_publisher.clone();
+    let sink_config = config.sink.clone()?;
+    let handle = thread::Builder::new()
+        .name("event-publisher".to_string())
+        .spawn(move || {
+            let sink = tailer::retry(|| connect(&sink_config));
+            let cursor = Cursor::new(config.cursor_path());
+            let start_version = tailer::retry(|| cursor.load())
+                .unwrap_or(config.start_version);
+            info!(
+                "[event-publisher] publishing transactions from version {}",
+                start_version
+            );
+            Tailer::new(
+                db,
+                sink,
+                cursor,
+                start_version,
+                config.batch_size,
+                Duration::from_millis(config.poll_interval_ms),
+            )
+            .run()
+        })
+        .expect("[event-publisher] failed to spawn thread");
+    Some(handle)
+}
@@ -0,0 +1,39 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::sink::{EventSink, Message};
+use anyhow::{format_err, Result};
+use diem_config::config::NatsSinkConfig;
+use nats::jetstream::{JetStream, PublishOptions};
+
+/// Publishes to a NATS JetStream subject. Each message is sent with its version as message ID, so
+/// that the stream drops those published again within its duplicate window.
+pub struct NatsSink {
+    jetstream: JetStream,
+    subject: String,
+}
+
+impl NatsSink {
+    pub fn new(config: &NatsSinkConfig) -> Result<Self> {
+        let connection = nats::connect(&config.url)?;
+        Ok(Self {
+            jetstream: nats::jetstream::new(connection),
+            subject: config.subject.clone(),
+        })
+    }
+}
+
+impl EventSink for NatsSink {
+    fn publish(&mut self, messages: &[Message]) -> Result<()> {
+        for message in messages {
+            let options = PublishOptions {
+                id: Some(message.version.to_string()),
+                ..Default::default()
+            };
+            self.jetstream
+                .publish_with_options(&self.subject, &message.payload, &options)
+                .map_err(|e| format_err!("failed to publish version {}: {}", message.version, e))?;
+        }
+        Ok(())
+    }
+}
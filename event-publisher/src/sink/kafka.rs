@@ -0,0 +1,63 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::sink::{EventSink, Message};
+use anyhow::{format_err, Result};
+use diem_config::config::KafkaSinkConfig;
+use futures::{executor::block_on, future::join_all};
+use rdkafka::{
+    config::ClientConfig,
+    producer::{FutureProducer, FutureRecord},
+};
+
+// How long the producer keeps trying to deliver a message before failing it
+const MESSAGE_TIMEOUT_MS: &str = "30000";
+
+/// Publishes to a Kafka topic, keyed by version. Messages with different keys can land in
+/// different partitions, so the topic needs a single partition for consumers to read them in
+/// version order.
+pub struct KafkaSink {
+    producer: FutureProducer,
+    topic: String,
+}
+
+impl KafkaSink {
+    pub fn new(config: &KafkaSinkConfig) -> Result<Self> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("acks", "all")
+            .set("enable.idempotence", "true")
+            .set("message.timeout.ms", MESSAGE_TIMEOUT_MS)
+            .create()?;
+        Ok(Self {
+            producer,
+            topic: config.topic.clone(),
+        })
+    }
+}
+
+impl EventSink for KafkaSink {
+    fn publish(&mut self, messages: &[Message]) -> Result<()> {
+        let keys: Vec<_> = messages
+            .iter()
+            .map(|message| message.version.to_string())
+            .collect();
+        let mut deliveries = Vec::with_capacity(messages.len());
+        for (message, key) in messages.iter().zip(&keys) {
+            let record = FutureRecord::to(&self.topic)
+                .key(key)
+                .payload(&message.payload);
+            let delivery = self
+                .producer
+                .send_result(record)
+                .map_err(|(e, _)| format_err!("failed to enqueue version {}: {}", key, e))?;
+            deliveries.push(delivery);
+        }
+        for (delivery, key) in block_on(join_all(deliveries)).into_iter().zip(&keys) {
+            delivery
+                .map_err(|_| format_err!("delivery of version {} was canceled", key))?
+                .map_err(|(e, _)| format_err!("failed to deliver version {}: {}", key, e))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Result};
+use diem_config::config::EventSinkConfig;
+use diem_types::transaction::Version;
+
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "nats")]
+mod jetstream;
+
+/// A committed transaction and its events, as published
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    pub version: Version,
+    /// JSON of the transaction view served by JSON-RPC, events included
+    pub payload: Vec<u8>,
+}
+
+/// A message broker transactions are published to
+pub trait EventSink: Send {
+    /// Publishes `messages` in order, returning once the broker acknowledged all of them. On
+    /// error, some of them may have been published nonetheless.
+    fn publish(&mut self, messages: &[Message]) -> Result<()>;
+}
+
+/// Connects to the broker of `config`
+pub fn connect(config: &EventSinkConfig) -> Result<Box<dyn EventSink>> {
+    match config {
+        #[cfg(feature = "kafka")]
+        EventSinkConfig::Kafka(config) => Ok(Box::new(kafka::KafkaSink::new(config)?)),
+        #[cfg(feature = "nats")]
+        EventSinkConfig::Nats(config) => Ok(Box::new(jetstream::NatsSink::new(config)?)),
+        #[allow(unreachable_patterns)]
+        EventSinkConfig::Kafka(_) => bail!("built without the kafka feature"),
+        #[allow(unreachable_patterns)]
+        EventSinkConfig::Nats(_) => bail!("built without the nats feature"),
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters,
+    cursor::Cursor,
+    sink::{EventSink, Message},
+};
+use anyhow::Result;
+use diem_json_rpc_types::views::TransactionView;
+use diem_logger::prelude::*;
+use diem_types::transaction::{TransactionListWithProof, Version};
+use std::{cmp::min, sync::Arc, thread, time::Duration};
+use storage_interface::DbReader;
+
+// Bounds of the delay before retrying a failed read or publication, doubling at each failure
+const MIN_RETRY_DELAY: Duration = Duration::from_millis(100);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Tails storage from a version on and publishes each batch of transactions it reads, recording
+/// the progress in a cursor once the batch is acknowledged.
+pub struct Tailer {
+    db: Arc<dyn DbReader>,
+    sink: Box<dyn EventSink>,
+    cursor: Cursor,
+    next_version: Version,
+    batch_size: u64,
+    poll_interval: Duration,
+}
+
+impl Tailer {
+    pub fn new(
+        db: Arc<dyn DbReader>,
+        sink: Box<dyn EventSink>,
+        cursor: Cursor,
+        next_version: Version,
+        batch_size: u64,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            db,
+            sink,
+            cursor,
+            next_version,
+            batch_size,
+            poll_interval,
+        }
+    }
+
+    /// Publishes forever, retrying failed batches until they go through
+    pub fn run(mut self) {
+        loop {
+            if retry(|| self.publish_next_batch()) == 0 {
+                thread::sleep(self.poll_interval);
+            }
+        }
+    }
+
+    /// Publishes the next batch of committed transactions, returning how many there were
+    pub fn publish_next_batch(&mut self) -> Result<usize> {
+        let latest_version = self.db.get_latest_version()?;
+        if self.next_version > latest_version {
+            return Ok(0);
+        }
+        let txns =
+            self.db
+                .get_transactions(self.next_version, self.batch_size, latest_version, true)?;
+        let messages = messages(self.next_version, txns)?;
+        self.sink.publish(&messages)?;
+        counters::PUBLISHED_TRANSACTIONS.inc_by(messages.len() as u64);
+
+        self.next_version += messages.len() as u64;
+        self.cursor.store(self.next_version)?;
+        counters::NEXT_VERSION.set(self.next_version as i64);
+        Ok(messages.len())
+    }
+}
+
+/// Builds the messages of the transactions read from `start_version` on
+fn messages(start_version: Version, txns: TransactionListWithProof) -> Result<Vec<Message>> {
+    let mut events = txns.events.unwrap_or_default().into_iter();
+    txns.transactions
+        .into_iter()
+        .zip(txns.proof.transaction_infos)
+        .zip(start_version..)
+        .map(|((txn, info), version)| {
+            let events = events.next().unwrap_or_default();
+            let view = TransactionView::try_from_tx_and_events(version, txn, info, events)?;
+            Ok(Message {
+                version,
+                payload: serde_json::to_vec(&view)?,
+            })
+        })
+        .collect()
+}
+
+/// Calls `f` until it succeeds, backing off between failures
+pub(crate) fn retry<T>(mut f: impl FnMut() -> Result<T>) -> T {
+    let mut delay = MIN_RETRY_DELAY;
+    loop {
+        match f() {
+            Ok(value) => return value,
+            Err(e) => {
+                counters::PUBLISH_ERRORS.inc();
+                error!("[event-publisher] retrying in {:?}: {}", delay, e);
+                thread::sleep(delay);
+                delay = min(delay * 2, MAX_RETRY_DELAY);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::bail;
+    use diem_crypto::hash::CryptoHash;
+    use diem_proptest_helpers::ValueGenerator;
+    use diem_temppath::TempPath;
+    use diemdb::{test_helper::arb_blocks_to_commit, DiemDB};
+    use std::sync::Mutex;
+    use storage_interface::DbWriter;
+
+    /// Records the messages published, failing every other call to `publish`, from the first
+    #[derive(Clone, Default)]
+    struct FlakySink {
+        published: Arc<Mutex<Vec<Message>>>,
+        calls: usize,
+    }
+
+    impl EventSink for FlakySink {
+        fn publish(&mut self, messages: &[Message]) -> Result<()> {
+            self.calls += 1;
+            if self.calls % 2 == 1 {
+                bail!("broker unavailable");
+            }
+            self.published.lock().unwrap().extend_from_slice(messages);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_publish_resumes_from_cursor() {
+        let tmpdir = TempPath::new();
+        let db = Arc::new(DiemDB::new_for_test(&tmpdir));
+        let mut txns = vec![];
+        for (txns_to_commit, ledger_info_with_sigs) in
+            ValueGenerator::new().generate(arb_blocks_to_commit())
+        {
+            db.save_transactions(
+                &txns_to_commit,
+                txns.len() as u64,
+                Some(&ledger_info_with_sigs),
+            )
+            .unwrap();
+            txns.extend(
+                txns_to_commit
+                    .into_iter()
+                    .map(|txn| txn.transaction().clone()),
+            );
+        }
+        let cursor_path = tmpdir.path().join("cursor");
+
+        let sink = FlakySink::default();
+        let cursor = Cursor::new(cursor_path.clone());
+        let mut tailer = Tailer::new(
+            db.clone(),
+            Box::new(sink.clone()),
+            Cursor::new(cursor_path.clone()),
+            0,
+            2,
+            Duration::from_millis(1),
+        );
+        let mut failures = 0;
+        while cursor.load().unwrap().unwrap_or(0) < txns.len() as u64 {
+            let before = cursor.load().unwrap();
+            if tailer.publish_next_batch().is_err() {
+                failures += 1;
+                // failed batches are not recorded in the cursor
+                assert_eq!(cursor.load().unwrap(), before);
+            }
+        }
+        assert!(failures > 0);
+        assert_eq!(tailer.publish_next_batch().unwrap(), 0);
+
+        let published = sink.published.lock().unwrap().clone();
+        assert_eq!(published.len(), txns.len());
+        for (version, (message, txn)) in published.iter().zip(&txns).enumerate() {
+            assert_eq!(message.version, version as u64);
+            let view: TransactionView = serde_json::from_slice(&message.payload).unwrap();
+            assert_eq!(view.version, version as u64);
+            assert_eq!(view.hash, txn.hash());
+        }
+
+        // a tailer started from the cursor has nothing left to publish
+        let next_version = cursor.load().unwrap().unwrap();
+        let mut tailer = Tailer::new(
+            db,
+            Box::new(FlakySink::default()),
+            cursor,
+            next_version,
+            2,
+            Duration::from_millis(1),
+        );
+        assert_eq!(tailer.publish_next_batch().unwrap(), 0);
+    }
+}
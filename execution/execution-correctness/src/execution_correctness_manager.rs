@@ -67,16 +67,26 @@ impl ExecutionCorrectnessManager {
         let execution_prikey = extract_execution_prikey(config);
         let storage_address = config.storage.address;
         let timeout_ms = config.storage.timeout_ms;
+        let pipelined_commit = config.execution.pipelined_commit;
         match &config.execution.service {
-            ExecutionCorrectnessService::Local => {
-                Self::new_local(storage_address, execution_prikey, timeout_ms)
-            }
-            ExecutionCorrectnessService::Serializer => {
-                Self::new_serializer(storage_address, execution_prikey, timeout_ms)
-            }
-            ExecutionCorrectnessService::Thread => {
-                Self::new_thread(storage_address, execution_prikey, timeout_ms)
-            }
+            ExecutionCorrectnessService::Local => Self::new_local(
+                storage_address,
+                execution_prikey,
+                timeout_ms,
+                pipelined_commit,
+            ),
+            ExecutionCorrectnessService::Serializer => Self::new_serializer(
+                storage_address,
+                execution_prikey,
+                timeout_ms,
+                pipelined_commit,
+            ),
+            ExecutionCorrectnessService::Thread => Self::new_thread(
+                storage_address,
+                execution_prikey,
+                timeout_ms,
+                pipelined_commit,
+            ),
             _ => unreachable!(
                 "Unimplemented ExecutionCorrectnessService: {:?}",
                 config.execution.service
@@ -88,10 +98,12 @@ impl ExecutionCorrectnessManager {
         storage_address: SocketAddr,
         execution_prikey: Option<Ed25519PrivateKey>,
         timeout: u64,
+        pipelined_commit: bool,
     ) -> Self {
-        let block_executor = Box::new(Executor::<DiemVM>::new(
-            StorageClient::new(&storage_address, timeout).into(),
-        ));
+        let block_executor = Box::new(
+            Executor::<DiemVM>::new(StorageClient::new(&storage_address, timeout).into())
+                .with_pipelined_commit(pipelined_commit),
+        );
         Self {
             internal_execution_correctness: ExecutionCorrectnessWrapper::Local(Arc::new(
                 Mutex::new(LocalService::new(block_executor, execution_prikey)),
@@ -110,10 +122,12 @@ impl ExecutionCorrectnessManager {
         storage_address: SocketAddr,
         execution_prikey: Option<Ed25519PrivateKey>,
         timeout: u64,
+        pipelined_commit: bool,
     ) -> Self {
-        let block_executor = Box::new(Executor::<DiemVM>::new(
-            StorageClient::new(&storage_address, timeout).into(),
-        ));
+        let block_executor = Box::new(
+            Executor::<DiemVM>::new(StorageClient::new(&storage_address, timeout).into())
+                .with_pipelined_commit(pipelined_commit),
+        );
         let serializer_service = SerializerService::new(block_executor, execution_prikey);
         Self {
             internal_execution_correctness: ExecutionCorrectnessWrapper::Serializer(Arc::new(
@@ -126,8 +140,14 @@ impl ExecutionCorrectnessManager {
         storage_address: SocketAddr,
         execution_prikey: Option<Ed25519PrivateKey>,
         network_timeout: u64,
+        pipelined_commit: bool,
     ) -> Self {
-        let thread = ThreadService::new(storage_address, execution_prikey, network_timeout);
+        let thread = ThreadService::new(
+            storage_address,
+            execution_prikey,
+            network_timeout,
+            pipelined_commit,
+        );
         Self {
             internal_execution_correctness: ExecutionCorrectnessWrapper::Thread(thread),
         }
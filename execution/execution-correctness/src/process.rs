@@ -7,6 +7,7 @@ use crate::{
 };
 use diem_config::config::{ExecutionCorrectnessService, NodeConfig};
 use diem_crypto::ed25519::Ed25519PrivateKey;
+use diem_vm::DiemVM;
 use std::net::SocketAddr;
 
 pub struct Process {
@@ -34,11 +35,13 @@ impl Process {
             ExecutionCorrectnessService::Process(remote_service) => remote_service.server_address,
             _ => panic!("Unexpected ExecutionCorrectness service: {:?}", service),
         };
+        DiemVM::set_concurrency_level_once(self.config.execution.concurrency_level);
         remote_service::execute(
             self.config.storage.address,
             server_addr,
             self.prikey,
             self.network_timeout_ms,
+            self.config.execution.pipelined_commit,
         );
     }
 }
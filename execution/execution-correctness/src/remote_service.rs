@@ -30,10 +30,12 @@ pub fn execute(
     listen_addr: SocketAddr,
     prikey: Option<Ed25519PrivateKey>,
     network_timeout: u64,
+    pipelined_commit: bool,
 ) {
-    let block_executor = Box::new(Executor::<DiemVM>::new(
-        StorageClient::new(&storage_addr, network_timeout).into(),
-    ));
+    let block_executor = Box::new(
+        Executor::<DiemVM>::new(StorageClient::new(&storage_addr, network_timeout).into())
+            .with_pipelined_commit(pipelined_commit),
+    );
     let mut serializer_service = SerializerService::new(block_executor, prikey);
     let mut network_server = NetworkServer::new("execution", listen_addr, network_timeout);
 
@@ -29,7 +29,11 @@ fn execution_correctness(
     };
     // Timeout of 5s for network operations
     let timeout_ms = 5_000;
-    let execution_correctness_manager =
-        ExecutionCorrectnessManager::new_serializer(config.storage.address, prikey, timeout_ms);
+    let execution_correctness_manager = ExecutionCorrectnessManager::new_serializer(
+        config.storage.address,
+        prikey,
+        timeout_ms,
+        false,
+    );
     (execution_correctness_manager.client(), pubkey)
 }
@@ -30,7 +30,11 @@ fn execution_correctness(
     // Test value for network_timeout, in seconds.
     let network_timeout_ms = 5_000;
 
-    let execution_correctness_manager =
-        ExecutionCorrectnessManager::new_thread(config.storage.address, prikey, network_timeout_ms);
+    let execution_correctness_manager = ExecutionCorrectnessManager::new_thread(
+        config.storage.address,
+        prikey,
+        network_timeout_ms,
+        false,
+    );
     (execution_correctness_manager.client(), pubkey)
 }
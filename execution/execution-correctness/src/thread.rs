@@ -28,13 +28,20 @@ impl ThreadService {
         storage_addr: SocketAddr,
         prikey: Option<Ed25519PrivateKey>,
         network_timeout: u64,
+        pipelined_commit: bool,
     ) -> Self {
         let listen_port = utils::get_available_port();
         let listen_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listen_port);
         let server_addr = listen_addr;
 
         let child = thread::spawn(move || {
-            remote_service::execute(storage_addr, listen_addr, prikey, network_timeout)
+            remote_service::execute(
+                storage_addr,
+                listen_addr,
+                prikey,
+                network_timeout,
+                pipelined_commit,
+            )
         });
 
         Self {
@@ -0,0 +1,127 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `Committer`, which saves the transactions of committed blocks to storage on
+//! a thread of its own, so that with pipelined commit, the storage commit of blocks overlaps the VM
+//! execution and state root hashing of the next ones.
+//!
+//! At most one commit is in flight. Until it's done, the state trees of the blocks it commits are
+//! kept unpruned, and the state views of the blocks executed meanwhile are based on the version
+//! committed before it, so that the state being committed is read from memory rather than from
+//! the DB.
+
+use crate::metrics::{
+    DIEM_EXECUTOR_SAVE_TRANSACTIONS_SECONDS, DIEM_EXECUTOR_WAIT_FOR_COMMIT_SECONDS,
+};
+use anyhow::{format_err, Result};
+use diem_logger::prelude::*;
+use diem_types::{
+    account_state_blob::AccountStateBlob,
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{TransactionToCommit, Version},
+};
+use executor_types::ExecutedTrees;
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+};
+use storage_interface::DbWriter;
+
+type SparseMerkleTree = scratchpad::SparseMerkleTree<AccountStateBlob>;
+
+pub(crate) struct CommitRequest {
+    pub txns_to_commit: Vec<TransactionToCommit>,
+    pub first_version: Version,
+    pub ledger_info_with_sigs: LedgerInfoWithSignatures,
+}
+
+struct InFlightCommit {
+    /// The trees committed before this commit, the latest ones known to be in the DB.
+    persisted_trees: ExecutedTrees,
+    /// The state trees of the blocks committed, to prune once they are in the DB.
+    state_trees: Vec<Arc<SparseMerkleTree>>,
+}
+
+pub(crate) struct Committer {
+    request_tx: mpsc::SyncSender<CommitRequest>,
+    result_rx: mpsc::Receiver<Result<()>>,
+    in_flight: Option<InFlightCommit>,
+}
+
+impl Committer {
+    pub fn new(writer: Arc<dyn DbWriter>) -> Self {
+        let (request_tx, request_rx) = mpsc::sync_channel::<CommitRequest>(1);
+        let (result_tx, result_rx) = mpsc::channel();
+        // The thread ends once the `Committer` is dropped, and with it `request_tx`.
+        thread::Builder::new()
+            .name("executor-committer".into())
+            .spawn(move || {
+                for request in request_rx {
+                    let _timer = DIEM_EXECUTOR_SAVE_TRANSACTIONS_SECONDS.start_timer();
+                    let result = writer.save_transactions(
+                        &request.txns_to_commit,
+                        request.first_version,
+                        Some(&request.ledger_info_with_sigs),
+                    );
+                    if result_tx.send(result).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("Failed to spawn the executor committer thread.");
+        Self {
+            request_tx,
+            result_rx,
+            in_flight: None,
+        }
+    }
+
+    /// The latest trees known to be in the DB if a commit is in flight. Otherwise, those
+    /// committed are.
+    pub fn persisted_trees(&self) -> Option<&ExecutedTrees> {
+        self.in_flight
+            .as_ref()
+            .map(|in_flight| &in_flight.persisted_trees)
+    }
+
+    /// Starts a commit, once the one in flight, if any, is done. `persisted_trees` are the trees
+    /// committed before it, and `state_trees` those of the blocks it commits.
+    pub fn submit(
+        &mut self,
+        request: CommitRequest,
+        persisted_trees: ExecutedTrees,
+        state_trees: Vec<Arc<SparseMerkleTree>>,
+    ) -> Result<()> {
+        self.wait()?;
+        self.request_tx
+            .send(request)
+            .map_err(|_| format_err!("The executor committer thread is gone."))?;
+        self.in_flight = Some(InFlightCommit {
+            persisted_trees,
+            state_trees,
+        });
+        Ok(())
+    }
+
+    /// Waits for the commit in flight, if any, and prunes the state trees it persisted.
+    pub fn wait(&mut self) -> Result<()> {
+        if let Some(in_flight) = self.in_flight.take() {
+            let _timer = DIEM_EXECUTOR_WAIT_FOR_COMMIT_SECONDS.start_timer();
+            self.result_rx
+                .recv()
+                .map_err(|_| format_err!("The executor committer thread is gone."))??;
+            for state_tree in in_flight.state_trees {
+                state_tree.prune()
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Committer {
+    fn drop(&mut self) {
+        if let Err(err) = self.wait() {
+            error!("Failed to commit blocks: {}", err);
+        }
+    }
+}
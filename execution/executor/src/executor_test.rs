@@ -17,7 +17,7 @@ use diem_types::{
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     transaction::{Transaction, TransactionListWithProof, Version},
 };
-use diemdb::DiemDB;
+use diemdb::{DiemDB, PrunerOptions};
 use proptest::prelude::*;
 use rand::Rng;
 use std::collections::BTreeMap;
@@ -41,26 +41,19 @@ fn execute_and_commit_block(
 }
 
 struct TestExecutor {
-    _path: diem_temppath::TempPath,
     db: DbReaderWriter,
     executor: Executor<MockVM>,
 }
 
 impl TestExecutor {
     fn new() -> TestExecutor {
-        let path = diem_temppath::TempPath::new();
-        path.create_as_dir().unwrap();
-        let db = DbReaderWriter::new(DiemDB::new_for_test(path.path()));
+        let db = DbReaderWriter::new(DiemDB::new_in_memory(PrunerOptions::default()));
         let genesis = vm_genesis::test_genesis_transaction();
         let waypoint = generate_waypoint::<MockVM>(&db, &genesis).unwrap();
         maybe_bootstrap::<MockVM>(&db, &genesis, waypoint).unwrap();
         let executor = Executor::<MockVM>::new(db.clone());
 
-        TestExecutor {
-            _path: path,
-            db,
-            executor,
-        }
+        TestExecutor { db, executor }
     }
 }
 
@@ -222,6 +215,54 @@ fn test_executor_commit_twice() {
     assert_eq!(res, res_retry);
 }
 
+#[test]
+fn test_executor_pipelined_commit() {
+    let mut executor = TestExecutor::new();
+    let mut pipelined_executor = TestExecutor::new();
+    pipelined_executor.executor =
+        Executor::<MockVM>::new(pipelined_executor.db.clone()).with_pipelined_commit(true);
+    let mut parent_block_id = executor.committed_block_id();
+    assert_eq!(pipelined_executor.committed_block_id(), parent_block_id);
+
+    for i in 0..20 {
+        // Each block reads what the previous one wrote, which may still be being committed.
+        let txns = vec![
+            encode_mint_transaction(gen_address(i % 3), 100),
+            encode_transfer_transaction(gen_address(i % 3), gen_address((i + 1) % 3), 50),
+        ];
+        let block_id = gen_block_id(i + 1);
+        let output = executor
+            .execute_block((block_id, txns.clone()), parent_block_id)
+            .unwrap();
+        let pipelined_output = pipelined_executor
+            .execute_block((block_id, txns), parent_block_id)
+            .unwrap();
+        assert_eq!(pipelined_output, output);
+
+        let ledger_info = gen_ledger_info(output.version(), output.root_hash(), block_id, i + 1);
+        let res = executor
+            .commit_blocks(vec![block_id], ledger_info.clone())
+            .unwrap();
+        let pipelined_res = pipelined_executor
+            .commit_blocks(vec![block_id], ledger_info)
+            .unwrap();
+        assert_eq!(pipelined_res, res);
+        parent_block_id = block_id;
+    }
+
+    // Resetting waits for the last commit.
+    pipelined_executor.reset().unwrap();
+    assert_eq!(
+        pipelined_executor
+            .db
+            .reader
+            .get_latest_state_root()
+            .unwrap(),
+        executor.db.reader.get_latest_state_root().unwrap()
+    );
+    assert_eq!(pipelined_executor.committed_block_id(), parent_block_id);
+}
+
 #[test]
 fn test_executor_execute_same_block_multiple_times() {
     let mut executor = TestExecutor::new();
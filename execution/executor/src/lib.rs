@@ -3,6 +3,7 @@
 
 #![forbid(unsafe_code)]
 
+mod committer;
 #[cfg(test)]
 mod executor_test;
 #[cfg(any(test, feature = "fuzzing"))]
@@ -17,6 +18,7 @@ mod types;
 pub mod db_bootstrapper;
 
 use crate::{
+    committer::{CommitRequest, Committer},
     logging::{LogEntry, LogSchema},
     metrics::{
         DIEM_EXECUTOR_COMMIT_BLOCKS_SECONDS, DIEM_EXECUTOR_ERRORS,
@@ -69,6 +71,8 @@ type SparseMerkleProof = diem_types::proof::SparseMerkleProof<AccountStateBlob>;
 pub struct Executor<V> {
     db: DbReaderWriter,
     cache: SpeculationCache,
+    // Commits blocks in the background if commits are pipelined.
+    committer: Option<Committer>,
     phantom: PhantomData<V>,
 }
 
@@ -91,10 +95,24 @@ where
         Self {
             db,
             cache: SpeculationCache::new_with_startup_info(startup_info),
+            committer: None,
             phantom: PhantomData,
         }
     }
 
+    /// Makes `commit_blocks` return once the commit to storage is started, so that it overlaps
+    /// the execution of the next blocks. A commit is waited for before the next one, before
+    /// resetting, and right away if it ends an epoch. If it fails, the error is returned by the
+    /// call waiting for it.
+    pub fn with_pipelined_commit(mut self, pipelined_commit: bool) -> Self {
+        self.committer = if pipelined_commit {
+            Some(Committer::new(Arc::clone(&self.db.writer)))
+        } else {
+            None
+        };
+        self
+    }
+
     fn reset_cache(&mut self) -> Result<(), Error> {
         let startup_info = self
             .db
@@ -105,10 +123,23 @@ where
         Ok(())
     }
 
+    /// Waits for the commit in flight, if any. If it failed, the cache, which took the blocks as
+    /// committed, is reset to what's in the DB.
+    fn wait_for_commit(&mut self) -> Result<(), Error> {
+        if let Some(committer) = self.committer.as_mut() {
+            if let Err(err) = committer.wait() {
+                self.reset_cache()?;
+                return Err(err.into());
+            }
+        }
+        Ok(())
+    }
+
     pub fn new_on_unbootstrapped_db(db: DbReaderWriter, tree_state: TreeState) -> Self {
         Self {
             db,
             cache: SpeculationCache::new_for_db_bootstrapping(tree_state),
+            committer: None,
             phantom: PhantomData,
         }
     }
@@ -441,11 +472,17 @@ where
         id: StateViewId,
         executed_trees: &'a ExecutedTrees,
     ) -> VerifiedStateView<'a> {
+        // While a commit is in flight, what it commits is only in memory.
+        let persisted_trees = self
+            .committer
+            .as_ref()
+            .and_then(Committer::persisted_trees)
+            .unwrap_or_else(|| self.cache.committed_trees());
         VerifiedStateView::new(
             id,
             Arc::clone(&self.db.reader),
-            self.cache.committed_trees().version(),
-            self.cache.committed_trees().state_root(),
+            persisted_trees.version(),
+            persisted_trees.state_root(),
             executed_trees.state_tree(),
         )
     }
@@ -596,6 +633,7 @@ impl<V: VMExecutor> ChunkExecutor for Executor<V> {
     ) -> Result<Vec<ContractEvent>> {
         let _timer = DIEM_EXECUTOR_EXECUTE_AND_COMMIT_CHUNK_SECONDS.start_timer();
         // 1. Update the cache in executor to be consistent with latest synced state.
+        self.wait_for_commit()?;
         self.reset_cache()?;
 
         info!(
@@ -667,6 +705,7 @@ impl<V: VMExecutor> TransactionReplayer for Executor<V> {
         mut txns: Vec<Transaction>,
         mut txn_infos: Vec<TransactionInfo>,
     ) -> Result<()> {
+        self.wait_for_commit()?;
         ensure!(
             first_version == self.cache.synced_trees().txn_accumulator().num_leaves(),
             "Version not expected. Expected: {}, got: {}",
@@ -708,6 +747,7 @@ impl<V: VMExecutor> BlockExecutor for Executor<V> {
     }
 
     fn reset(&mut self) -> Result<(), Error> {
+        self.wait_for_commit()?;
         self.reset_cache()
     }
 
@@ -819,6 +859,7 @@ impl<V: VMExecutor> BlockExecutor for Executor<V> {
         ledger_info_with_sigs: LedgerInfoWithSignatures,
     ) -> Result<(Vec<Transaction>, Vec<ContractEvent>), Error> {
         let _timer = DIEM_EXECUTOR_COMMIT_BLOCKS_SECONDS.start_timer();
+        self.wait_for_commit()?;
         let block_id_to_commit = ledger_info_with_sigs.ledger_info().consensus_block_id();
 
         info!(
@@ -913,11 +954,26 @@ impl<V: VMExecutor> BlockExecutor for Executor<V> {
         }
 
         // Skip duplicate txns that are already persistent.
-        let txns_to_commit = &txns_to_keep[num_txns_to_skip as usize..];
+        let txns_to_commit = txns_to_keep.split_off(num_txns_to_skip as usize);
+
+        // Calculate committed transactions and reconfig events, returned once the commit has
+        // succeeded, or has started if commits are pipelined.
+        let mut committed_txns = vec![];
+        let mut reconfig_events = vec![];
+        for txn in txns_to_commit.iter() {
+            committed_txns.push(txn.transaction().clone());
+            reconfig_events.append(&mut Self::extract_reconfig_events(txn.events().to_vec()));
+        }
+
+        let state_trees = blocks
+            .iter()
+            .map(|block| Arc::clone(block.output().executed_trees().state_tree()))
+            .collect::<Vec<_>>();
+        // The blocks are locked again when pruning the cache.
+        drop(blocks);
 
         let num_txns_to_commit = txns_to_commit.len() as u64;
         {
-            let _timer = DIEM_EXECUTOR_SAVE_TRANSACTIONS_SECONDS.start_timer();
             DIEM_EXECUTOR_TRANSACTIONS_SAVED.observe(num_txns_to_commit as f64);
 
             assert_eq!(first_version_to_commit, num_txns_in_li - num_txns_to_commit);
@@ -926,23 +982,28 @@ impl<V: VMExecutor> BlockExecutor for Executor<V> {
                     "Injected error in commit_blocks"
                 )))
             });
-            self.db.writer.save_transactions(
-                txns_to_commit,
-                first_version_to_commit,
-                Some(&ledger_info_with_sigs),
-            )?;
-        }
-
-        // Calculate committed transactions and reconfig events now that commit has succeeded
-        let mut committed_txns = vec![];
-        let mut reconfig_events = vec![];
-        for txn in txns_to_commit.iter() {
-            committed_txns.push(txn.transaction().clone());
-            reconfig_events.append(&mut Self::extract_reconfig_events(txn.events().to_vec()));
-        }
-
-        for block in blocks {
-            block.output().executed_trees().state_tree().prune()
+            match self.committer.as_mut() {
+                Some(committer) => committer.submit(
+                    CommitRequest {
+                        txns_to_commit,
+                        first_version: first_version_to_commit,
+                        ledger_info_with_sigs: ledger_info_with_sigs.clone(),
+                    },
+                    self.cache.committed_trees().clone(),
+                    state_trees,
+                )?,
+                None => {
+                    let _timer = DIEM_EXECUTOR_SAVE_TRANSACTIONS_SECONDS.start_timer();
+                    self.db.writer.save_transactions(
+                        &txns_to_commit,
+                        first_version_to_commit,
+                        Some(&ledger_info_with_sigs),
+                    )?;
+                    for state_tree in state_trees {
+                        state_tree.prune()
+                    }
+                }
+            }
         }
 
         self.cache.prune(
@@ -951,7 +1012,13 @@ impl<V: VMExecutor> BlockExecutor for Executor<V> {
             reconfig_events.clone(),
         )?;
 
-        // Now that the blocks are persisted successfully, we can reply to consensus
+        // Those notified of a reconfiguration read the new configs from storage.
+        if ledger_info_with_sigs.ledger_info().ends_epoch() {
+            self.wait_for_commit()?;
+        }
+
+        // Now that the blocks are persisted successfully, or are being so if commits are
+        // pipelined, we can reply to consensus
         Ok((committed_txns, reconfig_events))
     }
 }
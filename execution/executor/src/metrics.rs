@@ -67,3 +67,13 @@ pub static DIEM_EXECUTOR_TRANSACTIONS_SAVED: Lazy<Histogram> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static DIEM_EXECUTOR_WAIT_FOR_COMMIT_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        // metric name
+        "diem_executor_wait_for_commit_seconds",
+        // metric description
+        "The time spent in seconds waiting for a pipelined commit to storage in Diem executor"
+    )
+    .unwrap()
+});
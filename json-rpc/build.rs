@@ -0,0 +1,6 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+fn main() {
+    tonic_build::compile_protos("proto/transaction_stream.proto").unwrap();
+}
@@ -0,0 +1,161 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Routes for node operators, served on their own address so that it can be kept private.
+//!
+//! `GET /-/mempool` dumps every transaction waiting in mempool, by sender and sequence number,
+//! along with whether it is ready or parked behind a missing sequence number.
+//!
+//! `GET /-/mempool/accounts` summarizes them by sender: how many are ready and parked, when the
+//! oldest one entered mempool and the spread of their gas unit prices.
+//!
+//! `GET /-/state-sync` reports the progress of state sync: the highest version known to be
+//! committed, the local synced and committed versions, the sync rate and how long catching up
+//! should take at that rate.
+
+use crate::data;
+use diem_mempool::MempoolReader;
+use state_sync::sync_status::SyncStatusReader;
+use std::{net::SocketAddr, sync::Arc};
+use tokio::runtime::Runtime;
+use warp::Filter;
+
+/// All admin routes
+fn routes(
+    mempool: Arc<dyn MempoolReader>,
+    sync_status: Arc<dyn SyncStatusReader>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let dump = {
+        let mempool = Arc::clone(&mempool);
+        warp::path!("-" / "mempool")
+            .map(move || warp::reply::json(&data::get_all_pending_transactions(mempool.as_ref())))
+    };
+    let accounts = warp::path!("-" / "mempool" / "accounts")
+        .map(move || warp::reply::json(&data::get_mempool_account_summaries(mempool.as_ref())));
+    let state_sync = warp::path!("-" / "state-sync")
+        .map(move || warp::reply::json(&data::get_sync_status(sync_status.as_ref())));
+    warp::get().and(dump.or(accounts).or(state_sync))
+}
+
+/// Serves the admin routes at `address` on `runtime`
+pub fn bootstrap(
+    runtime: &Runtime,
+    address: SocketAddr,
+    mempool: Arc<dyn MempoolReader>,
+    sync_status: Arc<dyn SyncStatusReader>,
+) {
+    // Bind within the runtime, so that the routes are served as soon as this returns
+    let _guard = runtime.enter();
+    let server = warp::serve(routes(mempool, sync_status)).bind(address);
+    runtime.handle().spawn(server);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tests::utils::{mock_db, mock_mempool, MockSyncStatusReader},
+        views::{MempoolAccountSummaryView, PendingTransactionView, SyncStatusView},
+    };
+    use diem_mempool::PendingTransactionStatus;
+    use state_sync::sync_status::SyncStatus;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_mempool_dump() {
+        let mempool = mock_mempool(&mock_db());
+        let expected = data::get_all_pending_transactions(&mempool);
+        assert!(!expected.is_empty());
+        let routes = routes(Arc::new(mempool), Arc::new(MockSyncStatusReader::default()));
+
+        let response = warp::test::request()
+            .path("/-/mempool")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), 200);
+        let dump: Vec<PendingTransactionView> = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(dump, expected);
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/-/mempool")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), 405);
+    }
+
+    #[tokio::test]
+    async fn test_mempool_account_summaries() {
+        let mempool = mock_mempool(&mock_db());
+        let txns = mempool.get_all_transactions();
+        let routes = routes(Arc::new(mempool), Arc::new(MockSyncStatusReader::default()));
+
+        let response = warp::test::request()
+            .path("/-/mempool/accounts")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), 200);
+        let summaries: Vec<MempoolAccountSummaryView> =
+            serde_json::from_slice(response.body()).unwrap();
+        assert!(summaries
+            .windows(2)
+            .all(|pair| pair[0].address < pair[1].address));
+        for summary in &summaries {
+            let account_txns: Vec<_> = txns
+                .iter()
+                .filter(|pending| pending.txn.sender() == summary.address)
+                .collect();
+            let parked = account_txns
+                .iter()
+                .filter(|pending| pending.status == PendingTransactionStatus::Parked)
+                .count() as u64;
+            assert_eq!(summary.parked, parked);
+            assert_eq!(summary.ready + summary.parked, account_txns.len() as u64);
+            let oldest = account_txns
+                .iter()
+                .map(|pending| pending.insertion_time)
+                .min()
+                .unwrap();
+            assert_eq!(
+                summary.oldest_insertion_timestamp_usecs,
+                oldest.as_micros() as u64
+            );
+            let gas_unit_price = &summary.gas_unit_price;
+            assert!(gas_unit_price.min <= gas_unit_price.median);
+            assert!(gas_unit_price.median <= gas_unit_price.max);
+        }
+        let total: u64 = summaries.iter().map(|s| s.ready + s.parked).sum();
+        assert_eq!(total, txns.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_sync_status() {
+        let sync_status = MockSyncStatusReader(SyncStatus {
+            highest_known_version: 1000,
+            synced_version: 400,
+            committed_version: 350,
+            initialized: true,
+            sync_rate: 20.0,
+            estimated_catch_up_time: Some(Duration::from_secs(30)),
+        });
+        let routes = routes(Arc::new(mock_mempool(&mock_db())), Arc::new(sync_status));
+
+        let response = warp::test::request()
+            .path("/-/state-sync")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), 200);
+        let status: SyncStatusView = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(
+            status,
+            SyncStatusView {
+                highest_known_version: 1000,
+                synced_version: 400,
+                committed_version: 350,
+                initialized: true,
+                sync_rate: 20.0,
+                estimated_catch_up_secs: Some(30),
+            }
+        );
+    }
+}
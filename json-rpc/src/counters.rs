@@ -82,3 +82,13 @@ pub static METHOD_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// Cumulative number of requests rejected by the per-client rate limits
+pub static RATE_LIMITED_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_client_service_rate_limited_requests_count",
+        "Cumulative number of requests rejected by the JSON RPC client service rate limits",
+        &["bucket"] // read / submit
+    )
+    .unwrap()
+});
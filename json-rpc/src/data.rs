@@ -4,18 +4,32 @@
 use crate::{
     errors::JsonRpcError,
     views::{
-        AccountStateWithProofView, AccountView, CurrencyInfoView, EventView, EventWithProofView,
-        MetadataView, StateProofView, TransactionListView, TransactionView,
-        TransactionsWithProofsView, TowerStateResourceView, OracleUpgradeStateView, WaypointView
+        AccountEntryView, AccountStateWithProofView, AccountView, BlockMetadataView,
+        CurrencyInfoView, EventDataView, EventView, EventWithProofView, GasUnitPriceDistributionView,
+        MempoolAccountSummaryView, MetadataView, MoveResourceView, OracleUpgradeStateView,
+        PendingTransactionStatusView, PendingTransactionView, RemovalReasonView,
+        RemovedTransactionView, StateProofView, SyncStatusView, TowerStateResourceView, TransactionListView, TransactionView,
+        TransactionsWithProofsView, WaypointView,
     },
 };
-use anyhow::{format_err, Result};
+use anyhow::Result;
 // use diem_client::views::WaypointView;
 // use diem_client::views::TowerStateResourceView;
 use diem_crypto::HashValue;
-use diem_types::{account_address::AccountAddress, account_config::{diem_root_address, resources::dual_attestation::Limit, AccountResource}, account_state::AccountState, chain_id::ChainId, event::EventKey, ledger_info::LedgerInfoWithSignatures, waypoint::Waypoint};
+use diem_mempool::{MempoolReader, PendingTransaction, PendingTransactionStatus, RemovalReason};
+use diem_types::{access_path::AccessPath, account_address::AccountAddress, account_config::{diem_root_address, resources::dual_attestation::Limit}, account_state::AccountState, chain_id::ChainId, contract_event::ContractEvent, event::EventKey, ledger_info::LedgerInfoWithSignatures, transaction::{TransactionListWithProof, TransactionWithProof}, waypoint::Waypoint};
+use move_binary_format::errors::{Location, PartialVMError, PartialVMResult, VMResult};
+use move_core_types::{
+    language_storage::{ModuleId, StructTag, TypeTag},
+    vm_status::StatusCode,
+};
+use move_vm_runtime::data_cache::MoveStorage;
+use resource_viewer::MoveValueAnnotator;
+use state_sync::sync_status::SyncStatusReader;
 use std::{
-    cmp::min,
+    cell::RefCell,
+    cmp::{max, min},
+    collections::{hash_map::Entry, BTreeMap, HashMap},
     convert::{TryFrom, TryInto},
 };
 use storage_interface::{DbReader, Order};
@@ -72,6 +86,7 @@ pub fn get_metadata(
         module_publishing_allowed,
         diem_version,
         dual_attestation_limit,
+        earliest_available_version: Some(db.get_earliest_available_version()?),
     })
 }
 
@@ -93,6 +108,110 @@ pub fn get_account(
     )?))
 }
 
+/// Returns the accounts at the given addresses, in the same order. Failing to read one account
+/// does not fail the others: its entry carries the error instead.
+pub fn get_accounts(
+    db: &dyn DbReader,
+    account_addresses: &[AccountAddress],
+    version: u64,
+) -> Vec<AccountEntryView> {
+    account_addresses
+        .iter()
+        .map(|&address| match get_account(db, address, version) {
+            Ok(account) => AccountEntryView {
+                address,
+                account,
+                error: None,
+            },
+            Err(error) => AccountEntryView {
+                address,
+                account: None,
+                error: Some(error),
+            },
+        })
+        .collect()
+}
+
+/// Returns the resource of type `struct_tag` published under an account, with its fields
+/// annotated using the modules published on chain as of the same version
+pub fn get_resource(
+    db: &dyn DbReader,
+    account_address: AccountAddress,
+    struct_tag: StructTag,
+    version: u64,
+) -> Result<Option<MoveResourceView>, JsonRpcError> {
+    let account_state = match get_account_state(db, account_address, version)? {
+        Some(val) => val,
+        None => return Ok(None),
+    };
+    let blob = match account_state.get(&AccessPath::resource_access_vec(struct_tag.clone())) {
+        Some(blob) => blob,
+        None => return Ok(None),
+    };
+
+    let storage = VersionedStorage::new(db, version);
+    let resource = MoveValueAnnotator::new_no_stdlib(&storage).view_resource(&struct_tag, blob)?;
+    Ok(Some(MoveResourceView {
+        type_: struct_tag.to_string(),
+        value: resource.to_json(),
+    }))
+}
+
+/// The modules and resources of all accounts as of a version, from which the resource viewer
+/// resolves the layout of structs
+struct VersionedStorage<'a> {
+    db: &'a dyn DbReader,
+    version: u64,
+    // The modules of a struct and of its fields are mostly published under the same account
+    account_states: RefCell<HashMap<AccountAddress, Option<AccountState>>>,
+}
+
+impl<'a> VersionedStorage<'a> {
+    fn new(db: &'a dyn DbReader, version: u64) -> Self {
+        Self {
+            db,
+            version,
+            account_states: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn get(&self, access_path: AccessPath) -> PartialVMResult<Option<Vec<u8>>> {
+        let mut account_states = self.account_states.borrow_mut();
+        let account_state = match account_states.entry(access_path.address) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let account_state = get_account_state(self.db, access_path.address, self.version)
+                    .map_err(|e| {
+                    PartialVMError::new(StatusCode::STORAGE_ERROR).with_message(e.to_string())
+                })?;
+                entry.insert(account_state)
+            }
+        };
+        Ok(account_state
+            .as_ref()
+            .and_then(|account_state| account_state.get(&access_path.path))
+            .cloned())
+    }
+}
+
+impl<'a> MoveStorage for VersionedStorage<'a> {
+    fn get_module(&self, module_id: &ModuleId) -> VMResult<Option<Vec<u8>>> {
+        self.get(AccessPath::from(module_id))
+            .map_err(|e| e.finish(Location::Undefined))
+    }
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> PartialVMResult<Option<Vec<u8>>> {
+        self.get(AccessPath::new(
+            *address,
+            AccessPath::resource_access_vec(tag.clone()),
+        ))
+    }
+}
+
 /// Returns transactions by range
 pub fn get_transactions(
     db: &dyn DbReader,
@@ -108,6 +227,20 @@ pub fn get_transactions(
     Ok(TransactionListView::try_from(txs)?)
 }
 
+/// Returns transactions by range, as stored
+pub fn get_raw_transactions(
+    db: &dyn DbReader,
+    ledger_version: u64,
+    start_version: u64,
+    limit: u64,
+    include_events: bool,
+) -> Result<TransactionListWithProof, JsonRpcError> {
+    if start_version > ledger_version || limit == 0 {
+        return Ok(TransactionListWithProof::new_empty());
+    }
+    Ok(db.get_transactions(start_version, limit, ledger_version, include_events)?)
+}
+
 /// Returns transactions by range with proofs
 pub fn get_transactions_with_proofs(
     db: &dyn DbReader,
@@ -145,7 +278,9 @@ pub fn get_account_transaction(
     }
 }
 
-/// Returns all account transactions
+/// Returns account transactions from sequence number `start` on, or, if `descending`, from `start`
+/// down to the first one. Descending, a `start` beyond the latest sequence number starts from the
+/// latest transaction, so that clients can page back from it without knowing its sequence number.
 pub fn get_account_transactions(
     db: &dyn DbReader,
     ledger_version: u64,
@@ -153,63 +288,205 @@ pub fn get_account_transactions(
     start: u64,
     limit: u64,
     include_events: bool,
+    descending: bool,
 ) -> Result<Vec<TransactionView>, JsonRpcError> {
-    let account_state = db.get_latest_account_state(account)?.ok_or_else(|| {
-        JsonRpcError::invalid_request_with_msg(format!(
-            "could not find account by address {}",
-            account
-        ))
-    })?;
-    let account_seq = AccountResource::try_from(&account_state)?.sequence_number();
-
-    if start >= account_seq {
-        return Ok(vec![]);
-    }
+    let txs = get_raw_account_transactions(
+        db,
+        ledger_version,
+        account,
+        start,
+        limit,
+        include_events,
+        descending,
+    )?;
 
-    let mut all_txs = vec![];
-    let end = min(
-        start
-            .checked_add(limit)
-            .ok_or_else(|| format_err!("overflow!"))?,
-        account_seq,
-    );
+    let views = txs
+        .into_iter()
+        .map(|tx| {
+            TransactionView::try_from_tx_and_events(
+                tx.version,
+                tx.transaction,
+                tx.proof.transaction_info,
+                tx.events.unwrap_or_default(),
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
 
-    for seq in start..end {
-        let tx = db
-            .get_txn_by_account(account, seq, ledger_version, include_events)?
-            .ok_or_else(|| format_err!("Can not find transaction for seq {}!", seq))?;
+    Ok(views)
+}
 
-        let tx_view = TransactionView::try_from_tx_and_events(
-            tx.version,
-            tx.transaction,
-            tx.proof.transaction_info,
-            tx.events.unwrap_or_default(),
-        )?;
-        all_txs.push(tx_view);
+/// Same as `get_account_transactions`, returning the transactions as stored
+pub fn get_raw_account_transactions(
+    db: &dyn DbReader,
+    ledger_version: u64,
+    account: AccountAddress,
+    start: u64,
+    limit: u64,
+    include_events: bool,
+    descending: bool,
+) -> Result<Vec<TransactionWithProof>, JsonRpcError> {
+    if db.get_latest_account_state(account)?.is_none() {
+        return Err(JsonRpcError::invalid_request_with_msg(format!(
+            "could not find account by address {}",
+            account
+        )));
     }
 
-    Ok(all_txs)
+    let order = if descending {
+        Order::Descending
+    } else {
+        Order::Ascending
+    };
+    Ok(db.get_account_transactions(account, start, order, limit, ledger_version, include_events)?)
+}
+
+/// Restricts the events returned by `get_events`.
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+    /// Only return events of this type.
+    pub type_tag: Option<TypeTag>,
+    /// Only return events emitted at or after this version.
+    pub start_version: Option<u64>,
+    /// Only return events emitted at or before this version.
+    pub end_version: Option<u64>,
 }
 
-/// Returns events by given access path
+/// Returns events by given access path, starting from sequence number `start` or from the first
+/// event emitted at `filter.start_version`, whichever comes later. Returning the events in sequence
+/// number order lets clients resume from the last sequence number they saw.
 pub fn get_events(
     db: &dyn DbReader,
     ledger_version: u64,
     event_key: EventKey,
     start: u64,
     limit: u64,
+    filter: &EventFilter,
+    decode_custom_events: bool,
 ) -> Result<Vec<EventView>, JsonRpcError> {
+    let events = get_raw_events(db, ledger_version, event_key, start, limit, filter)?;
+    event_views(db, ledger_version, events, decode_custom_events)
+}
+
+/// Same as `get_events`, returning the events as stored along with their transaction versions
+pub fn get_raw_events(
+    db: &dyn DbReader,
+    ledger_version: u64,
+    event_key: EventKey,
+    start: u64,
+    limit: u64,
+    filter: &EventFilter,
+) -> Result<Vec<(u64, ContractEvent)>, JsonRpcError> {
+    let end_version = filter
+        .end_version
+        .map_or(ledger_version, |version| min(version, ledger_version));
+    let start = match filter.start_version {
+        Some(start_version) if start_version > end_version => return Ok(vec![]),
+        Some(start_version) => {
+            match db
+                .get_first_event_sequence_number_at_or_after_version(&event_key, start_version)?
+            {
+                Some(seq) => max(start, seq),
+                None => return Ok(vec![]),
+            }
+        }
+        None => start,
+    };
     let events_raw = db.get_events(&event_key, start, Order::Ascending, limit)?;
 
     let events = events_raw
         .into_iter()
-        .filter(|(version, _event)| version <= &ledger_version)
-        .map(|event| event.try_into())
-        .collect::<Result<Vec<EventView>>>()?;
+        .filter(|(version, _event)| version <= &end_version)
+        .filter(|(_version, event)| {
+            filter
+                .type_tag
+                .as_ref()
+                .map_or(true, |type_tag| event.type_tag() == type_tag)
+        })
+        .collect();
 
     Ok(events)
 }
 
+/// Returns events of type `type_tag` across all event streams, from `start_version` on
+pub fn get_events_by_type(
+    db: &dyn DbReader,
+    ledger_version: u64,
+    type_tag: &TypeTag,
+    start_version: u64,
+    limit: u64,
+    decode_custom_events: bool,
+) -> Result<Vec<EventView>, JsonRpcError> {
+    let events = db.get_events_by_type(type_tag, start_version, limit, ledger_version)?;
+    event_views(db, ledger_version, events, decode_custom_events)
+}
+
+/// Returns events involving `account`, from `start_version` on
+pub fn get_events_by_account(
+    db: &dyn DbReader,
+    ledger_version: u64,
+    account: AccountAddress,
+    start_version: u64,
+    limit: u64,
+    decode_custom_events: bool,
+) -> Result<Vec<EventView>, JsonRpcError> {
+    let events = db.get_events_by_account(account, start_version, limit, ledger_version)?;
+    event_views(db, ledger_version, events, decode_custom_events)
+}
+
+/// Returns events involving the currency `currency_code`, from `start_version` on
+pub fn get_events_by_currency(
+    db: &dyn DbReader,
+    ledger_version: u64,
+    currency_code: &str,
+    start_version: u64,
+    limit: u64,
+    decode_custom_events: bool,
+) -> Result<Vec<EventView>, JsonRpcError> {
+    let events = db.get_events_by_currency(currency_code, start_version, limit, ledger_version)?;
+    event_views(db, ledger_version, events, decode_custom_events)
+}
+
+/// Converts events along with their transaction versions to views. If `decode_custom_events`,
+/// events with no view of their own are decoded with the modules published on chain as of
+/// `ledger_version` rather than returned as BCS bytes: modules can only be upgraded compatibly, so
+/// the layout of an event type doesn't change after events of it are emitted.
+fn event_views(
+    db: &dyn DbReader,
+    ledger_version: u64,
+    events: Vec<(u64, ContractEvent)>,
+    decode_custom_events: bool,
+) -> Result<Vec<EventView>, JsonRpcError> {
+    let storage = VersionedStorage::new(db, ledger_version);
+    let annotator = MoveValueAnnotator::new_no_stdlib(&storage);
+    let views = events
+        .into_iter()
+        .map(|(version, event)| {
+            let mut view = EventView::try_from((version, event.clone()))?;
+            if decode_custom_events && matches!(view.data, EventDataView::Unknown { .. }) {
+                // Events failing to decode, e.g. as their module isn't published, stay unknown
+                if let Ok(value) = annotator.view_contract_event(&event) {
+                    view.data = EventDataView::Custom {
+                        type_tag: event.type_tag().to_string(),
+                        value: value.to_annotated_json(),
+                        bytes: event.event_data().into(),
+                    };
+                }
+            }
+            Ok(view)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(views)
+}
+
+/// Returns the metadata of the block holding the transaction at `version`
+pub fn get_block_metadata(
+    db: &dyn DbReader,
+    version: u64,
+) -> Result<Option<BlockMetadataView>, JsonRpcError> {
+    Ok(db.get_block_metadata(version)?.map(BlockMetadataView::from))
+}
+
 /// Returns events by given access path along with their proofs
 pub fn get_events_with_proofs(
     db: &dyn DbReader,
@@ -256,6 +533,121 @@ pub fn get_network_status(_role: &str) -> Result<u64, JsonRpcError> {
     Ok(0)
 }
 
+/// Returns the transactions of `account` waiting in mempool, by sequence number
+pub fn get_pending_transactions(
+    mempool: &dyn MempoolReader,
+    account: &AccountAddress,
+) -> Vec<PendingTransactionView> {
+    mempool
+        .get_account_transactions(account)
+        .into_iter()
+        .map(pending_transaction_view)
+        .collect()
+}
+
+/// Returns all transactions waiting in mempool, by sender and sequence number
+pub fn get_all_pending_transactions(mempool: &dyn MempoolReader) -> Vec<PendingTransactionView> {
+    mempool
+        .get_all_transactions()
+        .into_iter()
+        .map(pending_transaction_view)
+        .collect()
+}
+
+/// Returns why the transaction of an account with a sequence number was removed from mempool, if
+/// it was recently
+pub fn get_removed_transaction(
+    mempool: &dyn MempoolReader,
+    account: &AccountAddress,
+    sequence_number: u64,
+) -> Option<RemovedTransactionView> {
+    let removed = mempool.get_removed_transaction(account, sequence_number)?;
+    let reason = match removed.reason {
+        RemovalReason::Committed => RemovalReasonView::Committed,
+        RemovalReason::Rejected => RemovalReasonView::Rejected,
+        RemovalReason::Expired => RemovalReasonView::Expired,
+        RemovalReason::SystemTtlExpired => RemovalReasonView::SystemTtlExpired,
+        RemovalReason::Evicted => RemovalReasonView::Evicted,
+        RemovalReason::Replaced => RemovalReasonView::Replaced,
+    };
+    Some(RemovedTransactionView {
+        reason,
+        removal_timestamp_usecs: removed.removal_time.as_micros() as u64,
+    })
+}
+
+/// Returns a summary of the transactions waiting in mempool for each sender, by sender
+pub fn get_mempool_account_summaries(
+    mempool: &dyn MempoolReader,
+) -> Vec<MempoolAccountSummaryView> {
+    let mut accounts: BTreeMap<AccountAddress, Vec<PendingTransaction>> = BTreeMap::new();
+    for pending in mempool.get_all_transactions() {
+        accounts
+            .entry(pending.txn.sender())
+            .or_default()
+            .push(pending);
+    }
+    accounts
+        .into_iter()
+        .map(|(address, txns)| mempool_account_summary_view(address, &txns))
+        .collect()
+}
+
+/// Returns the progress of state sync
+pub fn get_sync_status(state_sync: &dyn SyncStatusReader) -> SyncStatusView {
+    let status = state_sync.get_sync_status();
+    SyncStatusView {
+        highest_known_version: status.highest_known_version,
+        synced_version: status.synced_version,
+        committed_version: status.committed_version,
+        initialized: status.initialized,
+        sync_rate: status.sync_rate,
+        estimated_catch_up_secs: status
+            .estimated_catch_up_time
+            .map(|duration| duration.as_secs()),
+    }
+}
+
+// `txns` must not be empty
+fn mempool_account_summary_view(
+    address: AccountAddress,
+    txns: &[PendingTransaction],
+) -> MempoolAccountSummaryView {
+    let parked = txns
+        .iter()
+        .filter(|pending| pending.status == PendingTransactionStatus::Parked)
+        .count();
+    let oldest_insertion_time = txns
+        .iter()
+        .map(|pending| pending.insertion_time)
+        .min()
+        .unwrap_or_default();
+    let mut gas_unit_prices: Vec<_> = txns
+        .iter()
+        .map(|pending| pending.txn.gas_unit_price())
+        .collect();
+    gas_unit_prices.sort_unstable();
+    MempoolAccountSummaryView {
+        address,
+        ready: (txns.len() - parked) as u64,
+        parked: parked as u64,
+        oldest_insertion_timestamp_usecs: oldest_insertion_time.as_micros() as u64,
+        gas_unit_price: GasUnitPriceDistributionView {
+            min: gas_unit_prices[0],
+            median: gas_unit_prices[gas_unit_prices.len() / 2],
+            max: gas_unit_prices[gas_unit_prices.len() - 1],
+        },
+    }
+}
+
+fn pending_transaction_view(pending: PendingTransaction) -> PendingTransactionView {
+    let status = match pending.status {
+        PendingTransactionStatus::Ready => PendingTransactionStatusView::Ready,
+        PendingTransactionStatus::Parked => PendingTransactionStatusView::Parked,
+    };
+    PendingTransactionView::new(pending.txn, status)
+}
+
 /// Returns proof of new state relative to version known to client
 pub fn get_state_proof(
     db: &dyn DbReader,
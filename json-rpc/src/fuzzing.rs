@@ -1,8 +1,9 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{methods, runtime, tests};
+use crate::{methods, rate_limit, runtime, tests};
 use diem_config::config;
+use diem_json_rpc_types::ApiVersion;
 use diem_proptest_helpers::ValueGenerator;
 use diem_types::account_state_blob::AccountStateWithProof;
 use futures::{channel::mpsc::channel, StreamExt};
@@ -31,6 +32,17 @@ fn test_method_fuzzer() {
         &gen_request_params!(["000000000000000000000000000000dd"]),
         "get_account",
     );
+    method_fuzzer(
+        &gen_request_params!([["000000000000000000000000000000dd"]]),
+        "get_accounts",
+    );
+    method_fuzzer(
+        &gen_request_params!([
+            "000000000000000000000000000000dd",
+            "0x1::AccountFreezing::FreezingBit"
+        ]),
+        "get_resource",
+    );
     method_fuzzer(
         &gen_request_params!(["000000000000000000000000000000dd", 0, true]),
         "get_account_transaction",
@@ -45,6 +57,16 @@ fn test_method_fuzzer() {
         &gen_request_params!(["00000000000000000000000000000000000000000a550c18", 0, 10]),
         "get_events",
     );
+    method_fuzzer(
+        &gen_request_params!(["0x1::Diem::BurnEvent", 0, 10]),
+        "get_events_by_type",
+    );
+    method_fuzzer(
+        &gen_request_params!(["00000000000000000000000000000001", 0, 10]),
+        "get_events_by_account",
+    );
+    method_fuzzer(&gen_request_params!(["XUS", 0, 10]), "get_events_by_currency");
+    method_fuzzer(&gen_request_params!([0]), "get_block_metadata");
     method_fuzzer(&gen_request_params!([0]), "get_metadata");
     method_fuzzer(&gen_request_params!([]), "get_currencies");
     method_fuzzer(&gen_request_params!([1]), "get_state_proof");
@@ -53,6 +75,15 @@ fn test_method_fuzzer() {
         "get_account_state_with_proof",
     );
     method_fuzzer(&gen_request_params!([]), "get_network_status");
+    method_fuzzer(
+        &gen_request_params!(["000000000000000000000000000000dd"]),
+        "get_pending_transactions",
+    );
+    method_fuzzer(
+        &gen_request_params!(["000000000000000000000000000000dd", 0]),
+        "get_removed_transaction",
+    );
+    method_fuzzer(&gen_request_params!([]), "get_api_versions");
 }
 
 pub fn method_fuzzer(params_data: &[u8], method: &str) {
@@ -116,10 +147,13 @@ pub fn request_fuzzer(json_request: serde_json::Value) {
     let service = methods::JsonRpcService::new(
         Arc::new(db),
         mp_sender,
+        Arc::new(tests::utils::MockMempool::default()),
         config::RoleType::Validator,
+        false,
         diem_types::chain_id::ChainId::test(),
         config::DEFAULT_BATCH_SIZE_LIMIT,
         config::DEFAULT_PAGE_SIZE_LIMIT,
+        std::time::Duration::from_millis(config::DEFAULT_SUBMIT_AND_WAIT_TIMEOUT_MS),
     );
     let rt = tokio::runtime::Builder::new_current_thread()
         .enable_all()
@@ -138,9 +172,15 @@ pub fn request_fuzzer(json_request: serde_json::Value) {
         }
     });
     let body = rt.block_on(async {
-        let reply = runtime::rpc_endpoint(json_request, service, None)
-            .await
-            .unwrap();
+        let reply = runtime::rpc_endpoint(
+            ApiVersion::LATEST,
+            json_request,
+            service,
+            None,
+            rate_limit::Client::unlimited(),
+        )
+        .await
+        .unwrap();
 
         let resp = reply.into_response();
         let (_, body) = resp.into_parts();
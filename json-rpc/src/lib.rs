@@ -9,17 +9,25 @@
 //! Protocol specification: https://www.jsonrpc.org/specification
 //!
 //! Module organization:
+//! ├── admin.rs          # operator routes, served on a separate address
 //! ├── methods.rs        # contains all available JSON RPC method handlers
+//! ├── rate_limit.rs     # per-client rate limits of JSON RPC calls
+//! ├── rest.rs           # REST routes over the JSON RPC method handlers
 //! ├── runtime.rs        # implementation of JSON RPC protocol over HTTP
+//! ├── transaction_stream.rs # gRPC service streaming committed transactions
 //! ├── tests.rs          # tests
 
 #[macro_use]
 mod util;
 
+mod admin;
 mod counters;
 pub mod data;
 mod methods;
+mod rate_limit;
+mod rest;
 mod runtime;
+pub mod transaction_stream;
 
 pub use diem_json_rpc_types::{errors, response, views};
 
@@ -6,58 +6,89 @@ use crate::{
     data,
     errors::JsonRpcError,
     views::{
-        AccountStateWithProofView, AccountView, CurrencyInfoView, EventView, EventWithProofView,
-        MetadataView, TowerStateResourceView, OracleUpgradeStateView, StateProofView,
-        TransactionListView, TransactionView, TransactionsWithProofsView, WaypointView
+        AccountEntryView, AccountStateWithProofView, AccountView, ApiVersionsView,
+        BlockMetadataView, CurrencyInfoView, EventView, EventWithProofView, MetadataView,
+        MoveResourceView, OracleUpgradeStateView, PendingTransactionView, RemovedTransactionView,
+        StateProofView, TowerStateResourceView, TransactionListView, TransactionView, TransactionsWithProofsView,
+        WaypointView,
     },
 };
 use anyhow::Result;
 use diem_config::config::RoleType;
-use diem_json_rpc_types::request::{
-    GetAccountParams, GetAccountStateWithProofParams, GetAccountTransactionParams,
-    GetAccountTransactionsParams, GetCurrenciesParams, GetEventsParams, GetEventsWithProofsParams,
-    GetMetadataParams, GetTowerStateParams, GetNetworkStatusParams, GetStateProofParams,
-    GetTransactionsParams, GetTransactionsWithProofsParams, MethodRequest,
-    SubmitParams,
+use diem_crypto::hash::CryptoHash;
+use diem_json_rpc_types::{
+    request::{
+        GetAccountParams, GetAccountStateWithProofParams, GetAccountTransactionParams,
+        GetAccountTransactionsParams, GetAccountsParams, GetApiVersionsParams,
+        GetBlockMetadataParams, GetCurrenciesParams, GetEventsByAccountParams,
+        GetEventsByCurrencyParams, GetEventsByTypeParams, GetEventsParams,
+        GetEventsWithProofsParams, GetMetadataParams, GetNetworkStatusParams,
+        GetPendingTransactionsParams, GetRemovedTransactionParams, GetResourceParams,
+        GetStateProofParams, GetTowerStateParams, GetTransactionsParams,
+        GetTransactionsWithProofsParams, MethodRequest, SubmitAndWaitParams, SubmitParams,
+    },
+    ApiVersion, Method,
 };
-use diem_mempool::{MempoolClientSender, SubmissionStatus};
+use diem_mempool::{MempoolClientSender, MempoolReader, SubmissionStatus};
 use diem_types::{
     chain_id::ChainId,
     ledger_info::LedgerInfoWithSignatures, mempool_status::MempoolStatusCode,
-    transaction::SignedTransaction,
+    transaction::{SignedTransaction, Transaction},
 };
 use fail::fail_point;
 use futures::{channel::oneshot, SinkExt};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+    parser::parse_type_tag,
+};
 use serde_json::Value;
-use std::{borrow::Borrow, sync::Arc};
+use std::{
+    borrow::Borrow,
+    cmp::min,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use storage_interface::DbReader;
 
+// Interval at which `submit_and_wait` checks whether its transaction committed
+const SUBMIT_AND_WAIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(Clone)]
 pub(crate) struct JsonRpcService {
     db: Arc<dyn DbReader>,
     mempool_sender: MempoolClientSender,
+    mempool_reader: Arc<dyn MempoolReader>,
     role: RoleType,
+    archival: bool,
     chain_id: ChainId,
     batch_size_limit: u16,
     page_size_limit: u16,
+    submit_and_wait_timeout: Duration,
 }
 
 impl JsonRpcService {
     pub fn new(
         db: Arc<dyn DbReader>,
         mempool_sender: MempoolClientSender,
+        mempool_reader: Arc<dyn MempoolReader>,
         role: RoleType,
+        archival: bool,
         chain_id: ChainId,
         batch_size_limit: u16,
         page_size_limit: u16,
+        submit_and_wait_timeout: Duration,
     ) -> Self {
         Self {
             db,
             mempool_sender,
+            mempool_reader,
             role,
+            archival,
             chain_id,
             batch_size_limit,
             page_size_limit,
+            submit_and_wait_timeout,
         }
     }
 
@@ -85,6 +116,51 @@ impl JsonRpcService {
         self.db.get_latest_ledger_info()
     }
 
+    /// Looks up `txn`, submitted earlier: returns it once committed, `None` while it is pending,
+    /// and an error if it can no longer commit.
+    pub fn get_submitted_transaction(
+        &self,
+        txn: &SignedTransaction,
+    ) -> Result<Option<TransactionView>, JsonRpcError> {
+        // Mempool is read before storage, so that a transaction committed in between is found in
+        // storage rather than taken for one dropped from mempool.
+        let in_mempool = self
+            .mempool_reader
+            .get_account_transactions(&txn.sender())
+            .iter()
+            .any(|pending| pending.txn == *txn);
+        let ledger_info = self.get_latest_ledger_info()?;
+        let ledger_info = ledger_info.ledger_info();
+        let committed = data::get_account_transaction(
+            self.db.borrow(),
+            ledger_info.version(),
+            txn.sender(),
+            txn.sequence_number(),
+            true,
+        )?;
+
+        if let Some(committed) = committed {
+            if committed.hash != Transaction::UserTransaction(txn.clone()).hash() {
+                return Err(JsonRpcError::transaction_discarded(format!(
+                    "sequence number {} was used by transaction {}",
+                    txn.sequence_number(),
+                    committed.hash
+                )));
+            }
+            Ok(Some(committed))
+        } else if txn.expiration_timestamp_secs() <= ledger_info.timestamp_usecs() / 1_000_000 {
+            Err(JsonRpcError::transaction_discarded(
+                "transaction expired".to_string(),
+            ))
+        } else if !in_mempool {
+            Err(JsonRpcError::transaction_discarded(
+                "transaction is no longer in mempool".to_string(),
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
 
     pub fn chain_id(&self) -> ChainId {
         self.chain_id
@@ -113,13 +189,20 @@ impl JsonRpcService {
 pub(crate) struct Handler<'a> {
     service: &'a JsonRpcService,
     ledger_info: &'a LedgerInfoWithSignatures,
+    // Methods whose response schema changes between versions branch on it
+    api_version: ApiVersion,
 }
 
 impl<'a> Handler<'a> {
-    pub fn new(service: &'a JsonRpcService, ledger_info: &'a LedgerInfoWithSignatures) -> Self {
+    pub fn new(
+        service: &'a JsonRpcService,
+        ledger_info: &'a LedgerInfoWithSignatures,
+        api_version: ApiVersion,
+    ) -> Self {
         Self {
             service,
             ledger_info,
+            api_version,
         }
     }
 
@@ -127,6 +210,11 @@ impl<'a> Handler<'a> {
         self.ledger_info.ledger_info().version()
     }
 
+    /// Whether events with no view of their own are decoded, which v1 clients don't expect
+    fn decode_custom_events(&self) -> bool {
+        self.api_version >= ApiVersion::V2
+    }
+
     fn version_param(&self, version: Option<u64>, name: &str) -> Result<u64, JsonRpcError> {
         let version = if let Some(version) = version {
             if version > self.version() {
@@ -146,12 +234,21 @@ impl<'a> Handler<'a> {
     pub async fn handle(&self, method_request: MethodRequest) -> Result<Value, JsonRpcError> {
         let response: Value = match method_request {
             MethodRequest::Submit(params) => self.submit(params).await?.into(),
+            MethodRequest::SubmitAndWait(params) => {
+                serde_json::to_value(self.submit_and_wait(params).await?)?
+            }
             MethodRequest::GetMetadata(params) => {
                 serde_json::to_value(self.get_metadata(params).await?)?
             }
             MethodRequest::GetAccount(params) => {
                 serde_json::to_value(self.get_account(params).await?)?
             }
+            MethodRequest::GetAccounts(params) => {
+                serde_json::to_value(self.get_accounts(params).await?)?
+            }
+            MethodRequest::GetResource(params) => {
+                serde_json::to_value(self.get_resource(params).await?)?
+            }
             MethodRequest::GetTransactions(params) => {
                 serde_json::to_value(self.get_transactions(params).await?)?
             }
@@ -164,12 +261,33 @@ impl<'a> Handler<'a> {
             MethodRequest::GetEvents(params) => {
                 serde_json::to_value(self.get_events(params).await?)?
             }
+            MethodRequest::GetEventsByType(params) => {
+                serde_json::to_value(self.get_events_by_type(params).await?)?
+            }
+            MethodRequest::GetEventsByAccount(params) => {
+                serde_json::to_value(self.get_events_by_account(params).await?)?
+            }
+            MethodRequest::GetEventsByCurrency(params) => {
+                serde_json::to_value(self.get_events_by_currency(params).await?)?
+            }
+            MethodRequest::GetBlockMetadata(params) => {
+                serde_json::to_value(self.get_block_metadata(params).await?)?
+            }
             MethodRequest::GetCurrencies(params) => {
                 serde_json::to_value(self.get_currencies(params).await?)?
             }
             MethodRequest::GetNetworkStatus(params) => {
                 serde_json::to_value(self.get_network_status(params).await?)?
             }
+            MethodRequest::GetApiVersions(params) => {
+                serde_json::to_value(self.get_api_versions(params).await?)?
+            }
+            MethodRequest::GetPendingTransactions(params) => {
+                serde_json::to_value(self.get_pending_transactions(params).await?)?
+            }
+            MethodRequest::GetRemovedTransaction(params) => {
+                serde_json::to_value(self.get_removed_transaction(params).await?)?
+            }
             MethodRequest::GetStateProof(params) => {
                 serde_json::to_value(self.get_state_proof(params).await?)?
             }
@@ -197,6 +315,72 @@ impl<'a> Handler<'a> {
         Ok(response)
     }
 
+    /// Handles the read methods that can return the canonical types their views are built from,
+    /// BCS-serialized, instead of the views. `None` is a null result, such as a missing account.
+    pub async fn handle_bcs(
+        &self,
+        method_request: MethodRequest,
+    ) -> Result<Option<Vec<u8>>, JsonRpcError> {
+        let db: &dyn DbReader = self.service.db.borrow();
+        let bytes = match method_request {
+            MethodRequest::GetAccount(params) => {
+                let version = self.version_param(params.version, "version")?;
+                match db
+                    .get_account_state_with_proof_by_version(params.account, version)?
+                    .0
+                {
+                    Some(blob) => bcs::to_bytes(&blob)?,
+                    None => return Ok(None),
+                }
+            }
+            MethodRequest::GetTransactions(params) => {
+                self.service
+                    .validate_page_size_limit(params.limit as usize)?;
+                bcs::to_bytes(&data::get_raw_transactions(
+                    db,
+                    self.version(),
+                    params.start_version,
+                    params.limit,
+                    params.include_events,
+                )?)?
+            }
+            MethodRequest::GetAccountTransactions(params) => {
+                self.service
+                    .validate_page_size_limit(params.limit as usize)?;
+                bcs::to_bytes(&data::get_raw_account_transactions(
+                    db,
+                    self.version(),
+                    params.account,
+                    params.start,
+                    params.limit,
+                    params.include_events,
+                    params.descending,
+                )?)?
+            }
+            MethodRequest::GetEvents(params) => {
+                self.service
+                    .validate_page_size_limit(params.limit as usize)?;
+                let filter =
+                    event_filter(params.type_tag, params.start_version, params.end_version)?;
+                bcs::to_bytes(&data::get_raw_events(
+                    db,
+                    self.version(),
+                    params.key,
+                    params.start,
+                    params.limit,
+                    &filter,
+                )?)?
+            }
+            method_request => {
+                return Err(JsonRpcError::invalid_request_with_msg(format!(
+                    "{} does not support BCS responses",
+                    method_request.method().as_str()
+                )))
+            }
+        };
+        Ok(Some(bytes))
+    }
+
     async fn submit(&self, params: SubmitParams) -> Result<(), JsonRpcError> {
         let (mempool_status, vm_status_opt) = self.service.mempool_request(params.data).await?;
 
@@ -209,6 +393,31 @@ impl<'a> Handler<'a> {
         }
     }
 
+    /// Submits a transaction, then waits until it commits or can no longer commit, for up to the
+    /// timeout asked for, capped by the server's. Returns null if it is still pending by then.
+    async fn submit_and_wait(
+        &self,
+        params: SubmitAndWaitParams,
+    ) -> Result<Option<TransactionView>, JsonRpcError> {
+        let SubmitAndWaitParams { data, timeout_ms } = params;
+        let max_timeout = self.service.submit_and_wait_timeout;
+        let timeout = timeout_ms.map_or(max_timeout, |ms| {
+            min(Duration::from_millis(ms), max_timeout)
+        });
+
+        self.submit(SubmitParams { data: data.clone() }).await?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(committed) = self.service.get_submitted_transaction(&data)? {
+                return Ok(Some(committed));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            tokio::time::sleep(SUBMIT_AND_WAIT_POLL_INTERVAL).await;
+        }
+    }
+
     /// Returns the blockchain metadata for a specified version. If no version is specified, default to
     /// returning the current blockchain metadata
     /// Can be used to verify that target Full Node is up-to-date
@@ -228,6 +437,39 @@ impl<'a> Handler<'a> {
         data::get_account(self.service.db.borrow(), account_address, version)
     }
 
+    /// Returns the accounts at the given addresses, with an error entry for each one that could
+    /// not be read
+    async fn get_accounts(
+        &self,
+        params: GetAccountsParams,
+    ) -> Result<Vec<AccountEntryView>, JsonRpcError> {
+        let GetAccountsParams { accounts, version } = params;
+
+        self.service.validate_page_size_limit(accounts.len())?;
+        let version = self.version_param(version, "version")?;
+        Ok(data::get_accounts(
+            self.service.db.borrow(),
+            &accounts,
+            version,
+        ))
+    }
+
+    /// Returns a Move resource of an account, with its fields annotated by their names
+    async fn get_resource(
+        &self,
+        params: GetResourceParams,
+    ) -> Result<Option<MoveResourceView>, JsonRpcError> {
+        let GetResourceParams {
+            account,
+            struct_tag,
+            version,
+        } = params;
+
+        let version = self.version_param(version, "version")?;
+        let struct_tag = struct_tag_param(&struct_tag)?;
+        data::get_resource(self.service.db.borrow(), account, struct_tag, version)
+    }
+
     /// Returns transactions by range
     async fn get_transactions(
         &self,
@@ -258,13 +500,16 @@ impl<'a> Handler<'a> {
             start_version,
             limit,
             include_events,
+            ledger_version,
         } = params;
 
         // Notice limit is a u16 normally, but some APIs require u64 below
         self.service.validate_page_size_limit(limit as usize)?;
+        // Proofs are against the ledger version the client already trusts, if it gives one
+        let ledger_version = self.version_param(ledger_version, "ledger_version")?;
         data::get_transactions_with_proofs(
             self.service.db.borrow(),
-            self.version(),
+            ledger_version,
             start_version,
             limit,
             include_events,
@@ -300,6 +545,7 @@ impl<'a> Handler<'a> {
             start,
             limit,
             include_events,
+            descending,
         } = params;
 
         self.service.validate_page_size_limit(limit as usize)?;
@@ -310,15 +556,115 @@ impl<'a> Handler<'a> {
             start,
             limit,
             include_events,
+            descending,
         )
     }
 
     /// Returns events by given access path
     async fn get_events(&self, params: GetEventsParams) -> Result<Vec<EventView>, JsonRpcError> {
-        let GetEventsParams { key, start, limit } = params;
+        let GetEventsParams {
+            key,
+            start,
+            limit,
+            type_tag,
+            start_version,
+            end_version,
+        } = params;
 
         self.service.validate_page_size_limit(limit as usize)?;
-        data::get_events(self.service.db.borrow(), self.version(), key, start, limit)
+        let filter = event_filter(type_tag, start_version, end_version)?;
+        data::get_events(
+            self.service.db.borrow(),
+            self.version(),
+            key,
+            start,
+            limit,
+            &filter,
+            self.decode_custom_events(),
+        )
+    }
+
+    /// Returns events of a Move type across all event streams
+    async fn get_events_by_type(
+        &self,
+        params: GetEventsByTypeParams,
+    ) -> Result<Vec<EventView>, JsonRpcError> {
+        let GetEventsByTypeParams {
+            type_tag,
+            start_version,
+            limit,
+        } = params;
+
+        self.service.validate_page_size_limit(limit as usize)?;
+        let type_tag = type_tag_param(&type_tag)?;
+        data::get_events_by_type(
+            self.service.db.borrow(),
+            self.version(),
+            &type_tag,
+            start_version,
+            limit,
+            self.decode_custom_events(),
+        )
+    }
+
+    /// Returns events involving an account, on its event streams or as the other party of a
+    /// payment
+    async fn get_events_by_account(
+        &self,
+        params: GetEventsByAccountParams,
+    ) -> Result<Vec<EventView>, JsonRpcError> {
+        let GetEventsByAccountParams {
+            account,
+            start_version,
+            limit,
+        } = params;
+
+        self.service.validate_page_size_limit(limit as usize)?;
+        data::get_events_by_account(
+            self.service.db.borrow(),
+            self.version(),
+            account,
+            start_version,
+            limit,
+            self.decode_custom_events(),
+        )
+    }
+
+    /// Returns payment, mint, burn and exchange rate update events of a currency
+    async fn get_events_by_currency(
+        &self,
+        params: GetEventsByCurrencyParams,
+    ) -> Result<Vec<EventView>, JsonRpcError> {
+        let GetEventsByCurrencyParams {
+            currency,
+            start_version,
+            limit,
+        } = params;
+
+        self.service.validate_page_size_limit(limit as usize)?;
+        if !Identifier::is_valid(&currency) {
+            return Err(JsonRpcError::invalid_param(&format!(
+                "invalid currency: {}",
+                currency
+            )));
+        }
+        data::get_events_by_currency(
+            self.service.db.borrow(),
+            self.version(),
+            &currency,
+            start_version,
+            limit,
+            self.decode_custom_events(),
+        )
+    }
+
+    /// Returns the metadata of the block holding the transaction at the given version
+    async fn get_block_metadata(
+        &self,
+        params: GetBlockMetadataParams,
+    ) -> Result<Option<BlockMetadataView>, JsonRpcError> {
+        let version = self.version_param(Some(params.version), "version")?;
+        data::get_block_metadata(self.service.db.borrow(), version)
     }
 
     /// Returns events by given access path along with their proofs
@@ -348,6 +694,43 @@ impl<'a> Handler<'a> {
         data::get_network_status(self.service.role.as_str())
     }
 
+    /// Returns the API versions served, and the methods of the version called
+    async fn get_api_versions(
+        &self,
+        _params: GetApiVersionsParams,
+    ) -> Result<ApiVersionsView, JsonRpcError> {
+        Ok(ApiVersionsView {
+            version: self.api_version,
+            latest_version: ApiVersion::LATEST,
+            versions: ApiVersion::ALL.to_vec(),
+            methods: Method::ALL.to_vec(),
+            archival: self.service.archival,
+        })
+    }
+
+    /// Returns the transactions of an account waiting in the mempool of this node
+    async fn get_pending_transactions(
+        &self,
+        params: GetPendingTransactionsParams,
+    ) -> Result<Vec<PendingTransactionView>, JsonRpcError> {
+        Ok(data::get_pending_transactions(
+            self.service.mempool_reader.as_ref(),
+            &params.account,
+        ))
+    }
+
+    /// Returns why a transaction was recently removed from the mempool of this node
+    async fn get_removed_transaction(
+        &self,
+        params: GetRemovedTransactionParams,
+    ) -> Result<Option<RemovedTransactionView>, JsonRpcError> {
+        Ok(data::get_removed_transaction(
+            self.service.mempool_reader.as_ref(),
+            &params.account,
+            params.sequence_number,
+        ))
+    }
+
     /// Returns proof of new state relative to version known to client
     async fn get_state_proof(
         &self,
@@ -395,4 +778,34 @@ impl<'a> Handler<'a> {
     ) -> Result<WaypointView, JsonRpcError> {
         data::get_waypoint(self.ledger_info)
     }
-}
\ No newline at end of file
+}
+
+fn event_filter(
+    type_tag: Option<String>,
+    start_version: Option<u64>,
+    end_version: Option<u64>,
+) -> Result<data::EventFilter, JsonRpcError> {
+    let type_tag = type_tag
+        .map(|type_tag| type_tag_param(&type_tag))
+        .transpose()?;
+    Ok(data::EventFilter {
+        type_tag,
+        start_version,
+        end_version,
+    })
+}
+
+fn type_tag_param(type_tag: &str) -> Result<TypeTag, JsonRpcError> {
+    parse_type_tag(type_tag)
+        .map_err(|_| JsonRpcError::invalid_param(&format!("invalid type_tag: {}", type_tag)))
+}
+
+fn struct_tag_param(struct_tag: &str) -> Result<StructTag, JsonRpcError> {
+    match parse_type_tag(struct_tag) {
+        Ok(TypeTag::Struct(struct_tag)) => Ok(struct_tag),
+        _ => Err(JsonRpcError::invalid_param(&format!(
+            "invalid struct_tag: {}",
+            struct_tag
+        ))),
+    }
+}
@@ -0,0 +1,292 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-client token bucket rate limiting of JSON-RPC calls.
+//!
+//! Clients are identified by the API key header configured, when they send one of the configured
+//! keys, and by IP address otherwise. Each client has a bucket for submit calls and one for every other call; a request
+//! goes through only if both buckets hold a token for each of its calls.
+
+use crate::{counters, errors::JsonRpcError};
+use diem_config::config::JsonRpcRateLimitConfig;
+use diem_json_rpc_types::Method;
+use diem_rate_limiter::rate_limit::TokenBucketRateLimiter;
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Instant,
+};
+use warp::{
+    http::{header, HeaderMap},
+    Filter,
+};
+
+const LABEL_READ: &str = "read";
+const LABEL_SUBMIT: &str = "submit";
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum ClientKey {
+    ApiKey(String),
+    Ip(IpAddr),
+}
+
+pub(crate) struct RateLimiter {
+    api_key_header: Option<String>,
+    api_keys: HashSet<String>,
+    reads: TokenBucketRateLimiter<ClientKey>,
+    submits: TokenBucketRateLimiter<ClientKey>,
+}
+
+impl RateLimiter {
+    /// Builds a rate limiter letting everything through if `config` is `None`
+    pub fn new(config: Option<&JsonRpcRateLimitConfig>) -> Self {
+        match config {
+            Some(config) => Self {
+                api_key_header: config.api_key_header.clone(),
+                api_keys: config.api_keys.iter().cloned().collect(),
+                reads: Self::limiter(LABEL_READ, config.read_burst, config.read_calls_per_sec),
+                submits: Self::limiter(
+                    LABEL_SUBMIT,
+                    config.submit_burst,
+                    config.submit_calls_per_sec,
+                ),
+            },
+            None => Self {
+                api_key_header: None,
+                api_keys: HashSet::new(),
+                reads: TokenBucketRateLimiter::open(LABEL_READ),
+                submits: TokenBucketRateLimiter::open(LABEL_SUBMIT),
+            },
+        }
+    }
+
+    fn limiter(
+        label: &'static str,
+        burst: usize,
+        calls_per_sec: usize,
+    ) -> TokenBucketRateLimiter<ClientKey> {
+        // New buckets start full, so that dropping a full bucket does not change anything
+        TokenBucketRateLimiter::new(
+            label,
+            "json-rpc".to_string(),
+            100,
+            burst,
+            calls_per_sec,
+            None,
+        )
+    }
+
+    fn client_key(
+        &self,
+        remote_addr: Option<SocketAddr>,
+        headers: &HeaderMap,
+    ) -> Option<ClientKey> {
+        let api_key = self
+            .api_key_header
+            .as_ref()
+            .and_then(|name| headers.get(name.as_str()))
+            .and_then(|value| value.to_str().ok())
+            // unknown keys would each get a fresh bucket, so they count as no key
+            .filter(|api_key| self.api_keys.contains(*api_key));
+        match api_key {
+            Some(api_key) => Some(ClientKey::ApiKey(api_key.to_string())),
+            None => remote_addr.map(|addr| ClientKey::Ip(addr.ip())),
+        }
+    }
+
+    /// Drops the state of the clients that have not made calls recently
+    pub fn garbage_collect(&self) {
+        self.reads.garbage_collect_full_buckets();
+        self.submits.garbage_collect_full_buckets();
+    }
+}
+
+/// The rate limiter, bound to the client that sent a request
+#[derive(Clone)]
+pub(crate) struct Client {
+    limiter: Arc<RateLimiter>,
+    key: Option<ClientKey>,
+}
+
+impl Client {
+    /// A client that is never rate limited
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn unlimited() -> Self {
+        Self {
+            limiter: Arc::new(RateLimiter::new(None)),
+            key: None,
+        }
+    }
+
+    /// Takes the tokens for `reads` read calls and `submits` submit calls, all of them or none
+    pub fn acquire(&self, reads: usize, submits: usize) -> Result<(), RateLimited> {
+        let key = match &self.key {
+            Some(key) => key.clone(),
+            None => return Ok(()),
+        };
+        let read_bucket = self.limiter.reads.bucket(key.clone());
+        let mut read_bucket = read_bucket.lock();
+        read_bucket
+            .acquire_all_tokens(reads)
+            .map_err(|ready| RateLimited::new(LABEL_READ, ready))?;
+        let submit_bucket = self.limiter.submits.bucket(key);
+        let result = submit_bucket.lock().acquire_all_tokens(submits);
+        result.map_err(|ready| {
+            read_bucket.return_tokens(reads);
+            RateLimited::new(LABEL_SUBMIT, ready)
+        })
+    }
+}
+
+/// Extracts the `Client` that sent a request
+pub(crate) fn client(
+    limiter: Arc<RateLimiter>,
+) -> impl Filter<Extract = (Client,), Error = Infallible> + Clone {
+    warp::addr::remote()
+        .and(warp::header::headers_cloned())
+        .map(
+            move |remote_addr: Option<SocketAddr>, headers: HeaderMap| Client {
+                key: limiter.client_key(remote_addr, &headers),
+                limiter: limiter.clone(),
+            },
+        )
+}
+
+/// Returns the number of read calls and of submit calls, `submit` or `submit_and_wait`, in a
+/// JSON-RPC request or batch
+pub(crate) fn count_calls(request: &Value) -> (usize, usize) {
+    let is_submit = |request: &Value| {
+        request["method"] == Method::Submit.as_str()
+            || request["method"] == Method::SubmitAndWait.as_str()
+    };
+    match request {
+        Value::Array(requests) => {
+            let submits = requests.iter().filter(|request| is_submit(request)).count();
+            (requests.len() - submits, submits)
+        }
+        request if is_submit(request) => (0, 1),
+        _ => (1, 0),
+    }
+}
+
+/// A request rejected because its client ran out of tokens
+#[derive(Debug)]
+pub(crate) struct RateLimited {
+    retry_after_secs: Option<u64>,
+}
+
+impl RateLimited {
+    fn new(label: &str, ready: Option<Instant>) -> Self {
+        counters::RATE_LIMITED_REQUESTS
+            .with_label_values(&[label])
+            .inc();
+        // `None` means the request has more calls than the bucket holds, retrying cannot help
+        let retry_after_secs = ready.map(|ready| {
+            let wait = ready.saturating_duration_since(Instant::now());
+            wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
+        });
+        Self { retry_after_secs }
+    }
+
+    pub fn error(&self) -> JsonRpcError {
+        JsonRpcError::rate_limited(self.retry_after_secs)
+    }
+
+    /// Adds the `Retry-After` header, if retrying can succeed
+    pub fn insert_retry_after(&self, headers: &mut HeaderMap) {
+        if let Some(secs) = self.retry_after_secs {
+            headers.insert(header::RETRY_AFTER, secs.max(1).into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config() -> JsonRpcRateLimitConfig {
+        JsonRpcRateLimitConfig {
+            api_key_header: Some("x-api-key".to_string()),
+            api_keys: vec!["secret".to_string()],
+            read_calls_per_sec: 1,
+            read_burst: 3,
+            submit_calls_per_sec: 1,
+            submit_burst: 1,
+        }
+    }
+
+    #[test]
+    fn test_reads_and_submits_have_separate_limits() {
+        let client = Client {
+            limiter: Arc::new(RateLimiter::new(Some(&config()))),
+            key: Some(ClientKey::Ip([127, 0, 0, 1].into())),
+        };
+        client.acquire(2, 1).unwrap();
+
+        let limited = client.acquire(0, 1).unwrap_err();
+        assert_eq!(limited.retry_after_secs, Some(1));
+
+        // the reads of a request rejected for its submits are given back
+        client.acquire(1, 1).unwrap_err();
+        client.acquire(1, 0).unwrap();
+        client.acquire(1, 0).unwrap_err();
+
+        // a batch larger than the burst can never go through
+        let limited = client.acquire(4, 0).unwrap_err();
+        assert_eq!(limited.retry_after_secs, None);
+        let mut headers = HeaderMap::new();
+        limited.insert_retry_after(&mut headers);
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn test_clients_have_separate_limits() {
+        let limiter = Arc::new(RateLimiter::new(Some(&config())));
+        let ip: IpAddr = [127, 0, 0, 1].into();
+        let mut headers = HeaderMap::new();
+        let by_ip = limiter.client_key(Some((ip, 8080).into()), &headers);
+        assert_eq!(by_ip, Some(ClientKey::Ip(ip)));
+        headers.insert("x-api-key", "secret".parse().unwrap());
+        let by_key = limiter.client_key(Some((ip, 8080).into()), &headers);
+        assert_eq!(by_key, Some(ClientKey::ApiKey("secret".to_string())));
+        headers.insert("x-api-key", "made-up".parse().unwrap());
+        let unknown_key = limiter.client_key(Some((ip, 8080).into()), &headers);
+        assert_eq!(unknown_key, Some(ClientKey::Ip(ip)));
+
+        let clients: Vec<_> = vec![by_ip, by_key]
+            .into_iter()
+            .map(|key| Client {
+                limiter: limiter.clone(),
+                key,
+            })
+            .collect();
+        for client in &clients {
+            client.acquire(3, 0).unwrap();
+            client.acquire(1, 0).unwrap_err();
+        }
+
+        // without a config, nothing is limited
+        let open = Client {
+            limiter: Arc::new(RateLimiter::new(None)),
+            key: Some(ClientKey::Ip(ip)),
+        };
+        open.acquire(10_000, 10_000).unwrap();
+    }
+
+    #[test]
+    fn test_count_calls() {
+        let submit = json!({"jsonrpc": "2.0", "method": "submit", "params": ["00"], "id": 1});
+        let read = json!({"jsonrpc": "2.0", "method": "get_metadata", "params": [], "id": 2});
+        assert_eq!(count_calls(&submit), (0, 1));
+        assert_eq!(count_calls(&read), (1, 0));
+        assert_eq!(count_calls(&json!([submit, read, read])), (2, 1));
+        let submit_and_wait =
+            json!({"jsonrpc": "2.0", "method": "submit_and_wait", "params": ["00"], "id": 3});
+        assert_eq!(count_calls(&json!([submit, submit_and_wait, read])), (1, 2));
+        assert_eq!(count_calls(&json!("not a request")), (1, 0));
+    }
+}
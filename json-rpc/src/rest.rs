@@ -0,0 +1,217 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! REST routes over the JSON-RPC method handlers, for clients without a JSON-RPC library.
+//!
+//! Each route turns its path and query parameters into the params array of the equivalent
+//! JSON-RPC call and runs it through the same `Handler`, so both APIs share validation, limits and
+//! views. The routes are described by the OpenAPI document served at `/v1/openapi.json`.
+
+use crate::{
+    errors::{is_internal_error, JsonRpcError, ServerCode},
+    methods::{Handler, JsonRpcService},
+    rate_limit::{self, RateLimiter},
+    runtime::{insert_ledger_headers, DatabaseError},
+};
+use diem_json_rpc_types::{request::MethodRequest, ApiVersion, Method};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use warp::{
+    http::{header, StatusCode},
+    reject, Filter, Reply,
+};
+
+/// OpenAPI document describing the REST routes.
+pub const OPENAPI_SPEC: &str = include_str!("../docs/openapi.json");
+
+/// Media type of response bodies holding BCS-serialized canonical types instead of JSON views,
+/// sent by clients in the `Accept` header.
+pub const MIME_TYPE_BCS: &str = "application/x-bcs";
+
+#[derive(Deserialize)]
+struct VersionQuery {
+    version: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    start: u64,
+    limit: u64,
+    #[serde(default)]
+    include_events: bool,
+}
+
+#[derive(Deserialize)]
+struct AccountPageQuery {
+    start: u64,
+    limit: u64,
+    #[serde(default)]
+    include_events: bool,
+    #[serde(default)]
+    descending: bool,
+}
+
+/// All REST routes, rooted at `/v1`.
+pub(crate) fn routes(
+    service: JsonRpcService,
+    rate_limiter: Arc<RateLimiter>,
+) -> impl Filter<Extract = (warp::reply::Response,), Error = warp::Rejection> + Clone {
+    let service = warp::any().map(move || service.clone());
+
+    let account = warp::path!("v1" / "accounts" / String)
+        .and(warp::query::<VersionQuery>())
+        .map(|address: String, query: VersionQuery| {
+            (Method::GetAccount, json!([address, query.version]))
+        });
+    let account_transactions = warp::path!("v1" / "accounts" / String / "transactions")
+        .and(warp::query::<AccountPageQuery>())
+        .map(|address: String, query: AccountPageQuery| {
+            (
+                Method::GetAccountTransactions,
+                json!([
+                    address,
+                    query.start,
+                    query.limit,
+                    query.include_events,
+                    query.descending
+                ]),
+            )
+        });
+    let transactions = warp::path!("v1" / "transactions")
+        .and(warp::query::<PageQuery>())
+        .map(|query: PageQuery| {
+            (
+                Method::GetTransactions,
+                json!([query.start, query.limit, query.include_events]),
+            )
+        });
+    let events = warp::path!("v1" / "events" / String)
+        .and(warp::query::<PageQuery>())
+        .map(|key: String, query: PageQuery| {
+            (Method::GetEvents, json!([key, query.start, query.limit]))
+        });
+
+    let methods = account
+        .or(account_transactions)
+        .unify()
+        .or(transactions)
+        .unify()
+        .or(events)
+        .unify()
+        .and(service)
+        .and(rate_limit::client(rate_limiter))
+        .and(warp::header::optional::<String>("accept"))
+        .and_then(
+            |(method, params), service, client, accept: Option<String>| {
+                let bcs = accept.map_or(false, |accept| accepts_bcs(&accept));
+                rest_endpoint(method, params, service, client, bcs)
+            },
+        );
+
+    let openapi = warp::path!("v1" / "openapi.json").map(|| {
+        warp::reply::with_header(OPENAPI_SPEC, "content-type", "application/json").into_response()
+    });
+
+    warp::get().and(openapi.or(methods).unify())
+}
+
+/// Runs the JSON-RPC `method` with `params` and replies with its result as the body, or with its
+/// error and a matching status code. A null result, such as a missing account, is a 404. With
+/// `bcs`, the body of a result is the BCS serialization of the canonical type the JSON view of
+/// the result is built from; errors stay JSON.
+async fn rest_endpoint(
+    method: Method,
+    params: Value,
+    service: JsonRpcService,
+    client: rate_limit::Client,
+    bcs: bool,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let ledger_info = service
+        .get_latest_ledger_info()
+        .map_err(|_| reject::custom(DatabaseError))?;
+
+    let limited = client.acquire(1, 0).err();
+    let request = match (&limited, MethodRequest::from_value(method, params)) {
+        (Some(limited), _) => Err(limited.error()),
+        (None, Ok(request)) => Ok(request),
+        (None, Err(_)) => Err(JsonRpcError::invalid_params_from_method(method)),
+    };
+    let handler = Handler::new(&service, &ledger_info, ApiVersion::V1);
+    let mut response = match request {
+        Ok(request) if bcs => match handler.handle_bcs(request).await {
+            Ok(Some(bytes)) => {
+                warp::reply::with_header(bytes, header::CONTENT_TYPE, MIME_TYPE_BCS).into_response()
+            }
+            Ok(None) => StatusCode::NOT_FOUND.into_response(),
+            Err(error) => error_response(error),
+        },
+        Ok(request) => match handler.handle(request).await {
+            Ok(Value::Null) => {
+                warp::reply::with_status(warp::reply::json(&Value::Null), StatusCode::NOT_FOUND)
+                    .into_response()
+            }
+            Ok(value) => warp::reply::json(&value).into_response(),
+            Err(error) => error_response(error),
+        },
+        Err(error) => error_response(error),
+    };
+    if let Some(limited) = limited {
+        limited.insert_retry_after(response.headers_mut());
+    }
+    insert_ledger_headers(response.headers_mut(), service.chain_id(), &ledger_info);
+    Ok(response)
+}
+
+/// Whether an `Accept` header asks for BCS rather than JSON
+fn accepts_bcs(accept: &str) -> bool {
+    accept
+        .split(',')
+        .filter_map(|media_range| media_range.split(';').next())
+        .any(|media_type| media_type.trim().eq_ignore_ascii_case(MIME_TYPE_BCS))
+}
+
+fn error_response(error: JsonRpcError) -> warp::reply::Response {
+    let status = error_status(&error);
+    warp::reply::with_status(warp::reply::json(&error.serialize()), status).into_response()
+}
+
+fn error_status(error: &JsonRpcError) -> StatusCode {
+    if error.code == ServerCode::RateLimited as i16 {
+        StatusCode::TOO_MANY_REQUESTS
+    } else if is_internal_error(&error.code) {
+        StatusCode::INTERNAL_SERVER_ERROR
+    } else {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_spec_covers_routes() {
+        let spec: Value = serde_json::from_str(OPENAPI_SPEC).unwrap();
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+        let paths = spec["paths"].as_object().unwrap();
+        for path in &[
+            "/v1/accounts/{address}",
+            "/v1/accounts/{address}/transactions",
+            "/v1/transactions",
+            "/v1/events/{key}",
+            "/v1/openapi.json",
+        ] {
+            assert!(paths.contains_key(*path), "{} is not documented", path);
+        }
+    }
+
+    #[test]
+    fn test_accepts_bcs() {
+        assert!(accepts_bcs(MIME_TYPE_BCS));
+        assert!(accepts_bcs("application/json;q=0.5, Application/X-BCS"));
+        assert!(!accepts_bcs("application/json"));
+        assert!(!accepts_bcs("*/*"));
+        assert!(!accepts_bcs("application/x-bcs-foo"));
+    }
+}
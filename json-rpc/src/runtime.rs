@@ -2,21 +2,24 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    counters,
-    errors::is_internal_error,
+    admin, counters,
+    errors::{is_internal_error, ErrorCategory, ErrorCode, JsonRpcError},
     methods::{Handler, JsonRpcService},
+    rate_limit::{self, RateLimiter},
     response::{JsonRpcResponse, X_DIEM_CHAIN_ID, X_DIEM_TIMESTAMP_USEC_ID, X_DIEM_VERSION_ID},
+    rest, transaction_stream,
     util::{sdk_info_from_user_agent, SdkInfo},
 };
 use anyhow::{ensure, Result};
-use diem_config::config::{NodeConfig, RoleType};
-use diem_json_rpc_types::Method;
+use diem_config::config::{JsonRpcCorsConfig, JsonRpcRateLimitConfig, NodeConfig, RoleType};
+use diem_json_rpc_types::{ApiVersion, Method};
 use diem_logger::{debug, Schema};
-use diem_mempool::MempoolClientSender;
+use diem_mempool::{MempoolClientSender, MempoolReader};
 use diem_types::{chain_id::ChainId, ledger_info::LedgerInfoWithSignatures};
 use futures::future::{join_all, Either};
 use rand::{rngs::OsRng, RngCore};
 use serde_json::Value;
+use state_sync::sync_status::SyncStatusReader;
 use std::{
     net::SocketAddr,
     ops::Sub,
@@ -26,7 +29,7 @@ use std::{
 use storage_interface::DbReader;
 use tokio::runtime::{Builder, Runtime};
 use warp::{
-    http::header,
+    http::{header, StatusCode},
     reject::{self, Reject},
     Filter, Reply,
 };
@@ -37,6 +40,9 @@ const LABEL_SUCCESS: &str = "success";
 const LABEL_BATCH: &str = "batch";
 const LABEL_SINGLE: &str = "single";
 
+// Interval at which the rate limits of clients gone quiet are dropped
+const RATE_LIMIT_GC_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Schema)]
 struct HttpRequestLog<'a> {
     #[schema(display)]
@@ -74,6 +80,62 @@ struct HealthCheckParams {
     pub duration_secs: Option<u64>,
 }
 
+// ReadinessCheckParams is optional thresholds of the readiness check.
+#[derive(serde::Deserialize)]
+pub(crate) struct ReadinessCheckParams {
+    // Most versions the node may be behind the highest version it has seen from its peers,
+    // DEFAULT_MAX_SYNC_LAG if not provided
+    pub max_sync_lag: Option<u64>,
+    // Same as for the health check: when provided, the latest ledger info timestamp must be
+    // >= server current time timestamp - duration_secs
+    pub duration_secs: Option<u64>,
+}
+
+// Most versions a node may be behind its peers and still be ready
+pub(crate) const DEFAULT_MAX_SYNC_LAG: u64 = 10_000;
+
+// State sync gauge holding the highest version the node has seen from its peers
+const STATE_SYNC_VERSION_METRIC: &str = "diem_state_sync_version";
+const STATE_SYNC_HIGHEST_VERSION_LABEL: &str = "highest";
+
+/// Body of the readiness check response: the node is ready if all of its parts are.
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct Readiness {
+    pub ready: bool,
+    pub storage: StorageReadiness,
+    pub state_sync: StateSyncReadiness,
+    pub mempool: MempoolReadiness,
+}
+
+/// Storage is ready if it can be read, and its latest ledger info is recent enough when asked
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct StorageReadiness {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp_usecs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// State sync is ready if the node is not too far behind the highest version seen from its peers,
+/// which is unknown until state sync sees one
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct StateSyncReadiness {
+    pub ok: bool,
+    pub highest_known_version: Option<u64>,
+    pub lag: Option<u64>,
+}
+
+/// Mempool is ready if it is not full, so that submissions can succeed
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct MempoolReadiness {
+    pub ok: bool,
+    pub size: usize,
+    pub capacity: usize,
+}
+
 #[macro_export]
 macro_rules! log_response {
     ($trace_id: expr, $resp: expr, $is_batch: expr) => {
@@ -99,11 +161,17 @@ pub fn bootstrap(
     batch_size_limit: u16,
     page_size_limit: u16,
     content_len_limit: usize,
+    submit_and_wait_timeout_ms: u64,
     tls_cert_path: &Option<String>,
     tls_key_path: &Option<String>,
+    tls_client_ca_path: &Option<String>,
+    cors_config: &JsonRpcCorsConfig,
+    rate_limit_config: &Option<JsonRpcRateLimitConfig>,
     diem_db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
+    mempool_reader: Arc<dyn MempoolReader>,
     role: RoleType,
+    archival: bool,
     chain_id: ChainId,
 ) -> Runtime {
     let runtime = Builder::new_multi_thread()
@@ -115,53 +183,51 @@ pub fn bootstrap(
     let service = JsonRpcService::new(
         diem_db.clone(),
         mp_sender,
+        mempool_reader.clone(),
         role,
+        archival,
         chain_id,
         batch_size_limit,
         page_size_limit,
+        Duration::from_millis(submit_and_wait_timeout_ms),
     );
 
-    let base_route = warp::any()
+    let rate_limiter = Arc::new(RateLimiter::new(rate_limit_config.as_ref()));
+    if rate_limit_config.is_some() {
+        let rate_limiter = rate_limiter.clone();
+        runtime.spawn(async move {
+            let mut interval = tokio::time::interval(RATE_LIMIT_GC_INTERVAL);
+            loop {
+                interval.tick().await;
+                rate_limiter.garbage_collect();
+            }
+        });
+    }
+
+    let rest_route = rest::routes(service.clone(), rate_limiter.clone())
+        .with(warp::log::custom(log_request))
+        .with(cors(cors_config, "GET"));
+
+    // Each API version is served under its own path. For now we still allow user to use "/", served
+    // as "/v1", but user should start to move to a versioned path soon
+    let api_version = warp::path::end()
+        .map(|| ApiVersion::V1)
+        .or(warp::path::param::<ApiVersion>().and(warp::path::end()))
+        .unify();
+
+    let rpc_route = api_version
         .and(warp::post())
         .and(warp::header::exact("content-type", "application/json"))
         .and(warp::body::content_length_limit(content_len_limit as u64))
         .and(warp::body::json())
         .and(warp::any().map(move || service.clone()))
         .and(warp::filters::header::optional::<String>("user-agent"))
+        .and(rate_limit::client(rate_limiter))
         .and_then(rpc_endpoint)
-        .with(warp::log::custom(|info| {
-            debug!(HttpRequestLog {
-                remote_addr: info.remote_addr(),
-                method: info.method().to_string(),
-                path: info.path().to_string(),
-                status: info.status().as_u16(),
-                referer: info.referer(),
-                user_agent: info.user_agent(),
-                elapsed: info.elapsed(),
-                forwarded: info
-                    .request_headers()
-                    .get(header::FORWARDED)
-                    .and_then(|v| v.to_str().ok())
-            })
-        }))
-        // CORS is required for full node server to accept requests from different domain web pages.
-        // It needs to be configured for the json-rpc request accepting method and headers.
-        // Technically it's fine for any headers, but for simplicity we only set must have header
-        // content-type.
-        .with(
-            warp::cors()
-                .allow_any_origin()
-                .allow_methods(vec!["POST"])
-                .allow_headers(vec![header::CONTENT_TYPE]),
-        );
-
-    // For now we still allow user to use "/", but user should start to move to "/v1" soon
-    let route_root = warp::path::end().and(base_route.clone());
-
-    let route_v1 = warp::path::path("v1")
-        .and(warp::path::end())
-        .and(base_route);
+        .with(warp::log::custom(log_request))
+        .with(cors(cors_config, "POST"));
 
+    let ready_db = diem_db.clone();
     let health_route = warp::path!("-" / "healthy")
         .and(warp::path::end())
         .and(warp::query().map(move |params: HealthCheckParams| params))
@@ -169,7 +235,13 @@ pub fn bootstrap(
         .and(warp::any().map(SystemTime::now))
         .and_then(health_check);
 
-    let full_route = health_route.or(route_v1.or(route_root));
+    let ready_route = warp::path!("-" / "ready")
+        .and(warp::query().map(move |params: ReadinessCheckParams| params))
+        .and(warp::any().map(move || ready_db.clone()))
+        .and(warp::any().map(move || mempool_reader.clone()))
+        .map(readiness_check);
+
+    let full_route = health_route.or(ready_route).or(rest_route).or(rpc_route);
 
     // Ensure that we actually bind to the socket first before spawning the
     // server tasks. This helps in tests to prevent races where a client attempts
@@ -180,14 +252,27 @@ pub fn bootstrap(
     //       tokio TcpListener can only be bound inside a tokio context.
     let _guard = runtime.enter();
     let server = match tls_cert_path {
-        None => Either::Left(warp::serve(full_route).bind(address)),
-        Some(cert_path) => Either::Right(
-            warp::serve(full_route)
+        None => {
+            assert!(
+                tls_client_ca_path.is_none(),
+                "[json-rpc] tls_client_ca_path requires tls_cert_path"
+            );
+            Either::Left(warp::serve(full_route).bind(address))
+        }
+        Some(cert_path) => {
+            let key_path = tls_key_path
+                .as_ref()
+                .expect("[json-rpc] tls_cert_path requires tls_key_path");
+            let tls = warp::serve(full_route)
                 .tls()
                 .cert_path(cert_path)
-                .key_path(tls_key_path.as_ref().unwrap())
-                .bind(address),
-        ),
+                .key_path(key_path);
+            let tls = match tls_client_ca_path {
+                Some(ca_path) => tls.client_auth_required_path(ca_path),
+                None => tls,
+            };
+            Either::Right(tls.bind(address))
+        }
     };
     runtime.handle().spawn(server);
     runtime
@@ -199,19 +284,50 @@ pub fn bootstrap_from_config(
     chain_id: ChainId,
     diem_db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
+    mempool_reader: Arc<dyn MempoolReader>,
+    sync_status_reader: Arc<dyn SyncStatusReader>,
 ) -> Runtime {
-    bootstrap(
+    let runtime = bootstrap(
         config.json_rpc.address,
         config.json_rpc.batch_size_limit,
         config.json_rpc.page_size_limit,
         config.json_rpc.content_length_limit,
+        config.json_rpc.submit_and_wait_timeout_ms,
         &config.json_rpc.tls_cert_path,
         &config.json_rpc.tls_key_path,
-        diem_db,
+        &config.json_rpc.tls_client_ca_path,
+        &config.json_rpc.cors,
+        &config.json_rpc.rate_limit,
+        diem_db.clone(),
         mp_sender,
+        mempool_reader.clone(),
         config.base.role,
+        config.base.archival,
         chain_id,
-    )
+    );
+    if let Some(address) = config.json_rpc.stream_address {
+        transaction_stream::bootstrap(&runtime, address, diem_db);
+    }
+    if let Some(address) = config.json_rpc.admin_address {
+        admin::bootstrap(&runtime, address, mempool_reader, sync_status_reader);
+    }
+    runtime
+}
+
+fn log_request(info: warp::log::Info) {
+    debug!(HttpRequestLog {
+        remote_addr: info.remote_addr(),
+        method: info.method().to_string(),
+        path: info.path().to_string(),
+        status: info.status().as_u16(),
+        referer: info.referer(),
+        user_agent: info.user_agent(),
+        elapsed: info.elapsed(),
+        forwarded: info
+            .request_headers()
+            .get(header::FORWARDED)
+            .and_then(|v| v.to_str().ok())
+    })
 }
 
 async fn health_check(
@@ -231,6 +347,113 @@ async fn health_check(
     Ok(Box::new("diem-node:ok"))
 }
 
+/// CORS is required for full node server to accept requests from different domain web pages.
+/// It needs to be configured for the request accepting method and headers: requests may carry
+/// content-type, and the headers the operator allows, such as the rate limit API key header.
+fn cors(config: &JsonRpcCorsConfig, method: &str) -> warp::cors::Builder {
+    let cors = warp::cors()
+        .allow_methods(vec![method])
+        .allow_header(header::CONTENT_TYPE)
+        .allow_headers(config.allowed_headers.iter().map(String::as_str));
+    let cors = match &config.allowed_origins {
+        Some(origins) => cors.allow_origins(origins.iter().map(String::as_str)),
+        None => cors.allow_any_origin(),
+    };
+    match config.max_age_secs {
+        Some(max_age_secs) => cors.max_age(max_age_secs),
+        None => cors,
+    }
+}
+
+fn readiness_check(
+    params: ReadinessCheckParams,
+    db: Arc<dyn DbReader>,
+    mempool: Arc<dyn MempoolReader>,
+) -> impl warp::Reply {
+    let readiness = check_readiness(
+        &params,
+        db.as_ref(),
+        mempool.as_ref(),
+        state_sync_highest_known_version(),
+        SystemTime::now(),
+    );
+    let status = if readiness.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    warp::reply::with_status(warp::reply::json(&readiness), status)
+}
+
+pub(crate) fn check_readiness(
+    params: &ReadinessCheckParams,
+    db: &dyn DbReader,
+    mempool: &dyn MempoolReader,
+    highest_known_version: Option<u64>,
+    now: SystemTime,
+) -> Readiness {
+    let storage = match db.get_latest_ledger_info() {
+        Ok(ledger_info) => {
+            let version = ledger_info.ledger_info().version();
+            let timestamp = ledger_info.ledger_info().timestamp_usecs();
+            let ok = params.duration_secs.map_or(true, |duration| {
+                check_latest_ledger_info_timestamp(duration, timestamp, now).is_ok()
+            });
+            StorageReadiness {
+                ok,
+                version: Some(version),
+                timestamp_usecs: Some(timestamp),
+                error: None,
+            }
+        }
+        Err(e) => StorageReadiness {
+            ok: false,
+            version: None,
+            timestamp_usecs: None,
+            error: Some(e.to_string()),
+        },
+    };
+
+    let lag = highest_known_version
+        .zip(storage.version)
+        .map(|(highest, version)| highest.saturating_sub(version));
+    let max_lag = params.max_sync_lag.unwrap_or(DEFAULT_MAX_SYNC_LAG);
+    let state_sync = StateSyncReadiness {
+        ok: lag.map_or(true, |lag| lag <= max_lag),
+        highest_known_version,
+        lag,
+    };
+
+    let (size, capacity) = mempool.get_size_and_capacity();
+    let mempool = MempoolReadiness {
+        ok: size < capacity,
+        size,
+        capacity,
+    };
+
+    Readiness {
+        ready: storage.ok && state_sync.ok && mempool.ok,
+        storage,
+        state_sync,
+        mempool,
+    }
+}
+
+/// Highest version seen from peers, as published by state sync, which runs in the same process
+fn state_sync_highest_known_version() -> Option<u64> {
+    diem_metrics::gather_metrics()
+        .iter()
+        .filter(|family| family.get_name() == STATE_SYNC_VERSION_METRIC)
+        .flat_map(|family| family.get_metric())
+        .find(|metric| {
+            metric.get_label().iter().any(|label| {
+                label.get_name() == "type" && label.get_value() == STATE_SYNC_HIGHEST_VERSION_LABEL
+            })
+        })
+        .map(|metric| metric.get_gauge().get_value() as u64)
+        .filter(|version| *version > 0)
+}
+
 pub fn check_latest_ledger_info_timestamp(
     duration_sec: u64,
     timestamp_usecs: u64,
@@ -247,9 +470,11 @@ pub fn check_latest_ledger_info_timestamp(
 /// JSON RPC entry point
 /// Handles all incoming rpc requests
 pub(crate) async fn rpc_endpoint(
+    api_version: ApiVersion,
     data: Value,
     service: JsonRpcService,
     user_agent: Option<String>,
+    client: rate_limit::Client,
 ) -> Result<warp::reply::Response, warp::Rejection> {
     let label = match data {
         Value::Array(_) => LABEL_BATCH,
@@ -259,15 +484,19 @@ pub(crate) async fn rpc_endpoint(
     let timer = counters::RPC_REQUEST_LATENCY
         .with_label_values(&[label])
         .start_timer();
-    let ret = rpc_endpoint_without_metrics(data, service, user_agent.as_deref()).await;
+    let ret =
+        rpc_endpoint_without_metrics(api_version, data, service, user_agent.as_deref(), client)
+            .await;
     timer.stop_and_record();
     ret
 }
 
 async fn rpc_endpoint_without_metrics(
+    api_version: ApiVersion,
     data: Value,
     service: JsonRpcService,
     user_agent: Option<&str>,
+    client: rate_limit::Client,
 ) -> Result<warp::reply::Response, warp::Rejection> {
     // take snapshot of latest version of DB to be used across all requests, especially for batched requests
     let ledger_info = service
@@ -285,12 +514,37 @@ async fn rpc_endpoint_without_metrics(
     let latest_ledger_timestamp_usecs = ledger_info.ledger_info().timestamp_usecs();
     let sdk_info = sdk_info_from_user_agent(user_agent);
 
+    let (reads, submits) = rate_limit::count_calls(&data);
+    if let Err(limited) = client.acquire(reads, submits) {
+        let mut response = JsonRpcResponse::new(
+            chain_id,
+            latest_ledger_version,
+            latest_ledger_timestamp_usecs,
+        );
+        response.error = Some(versioned_error(limited.error(), api_version));
+        log_response!(&trace_id, &response, data.is_array());
+
+        let mut http_response =
+            warp::reply::with_status(warp::reply::json(&response), StatusCode::TOO_MANY_REQUESTS)
+                .into_response();
+        limited.insert_retry_after(http_response.headers_mut());
+        insert_ledger_headers(http_response.headers_mut(), chain_id, &ledger_info);
+        return Ok(http_response);
+    }
+
     let resp = if let Value::Array(requests) = data {
         match service.validate_batch_size_limit(requests.len()) {
             Ok(_) => {
                 // batch API call
                 let futures = requests.into_iter().map(|req| {
-                    rpc_request_handler(req, &service, &ledger_info, LABEL_BATCH, sdk_info)
+                    rpc_request_handler(
+                        req,
+                        api_version,
+                        &service,
+                        &ledger_info,
+                        LABEL_BATCH,
+                        sdk_info,
+                    )
                 });
                 let responses = join_all(futures).await;
                 for resp in &responses {
@@ -304,7 +558,7 @@ async fn rpc_endpoint_without_metrics(
                     latest_ledger_version,
                     latest_ledger_timestamp_usecs,
                 );
-                response.error = Some(err);
+                response.error = Some(versioned_error(err, api_version));
                 bump_counters(&response, LABEL_BATCH, None, sdk_info);
                 log_response!(&trace_id, &response, true);
 
@@ -313,39 +567,56 @@ async fn rpc_endpoint_without_metrics(
         }
     } else {
         // single API call
-        let resp = rpc_request_handler(data, &service, &ledger_info, LABEL_SINGLE, sdk_info).await;
+        let resp = rpc_request_handler(
+            data,
+            api_version,
+            &service,
+            &ledger_info,
+            LABEL_SINGLE,
+            sdk_info,
+        )
+        .await;
         log_response!(&trace_id, &resp, false);
 
         warp::reply::json(&resp)
     };
 
     let mut http_response = resp.into_response();
-    let headers = http_response.headers_mut();
+    insert_ledger_headers(http_response.headers_mut(), chain_id, &ledger_info);
 
+    Ok(http_response)
+}
+
+/// Adds the chain id and the version and timestamp of `ledger_info` to response headers
+pub(crate) fn insert_ledger_headers(
+    headers: &mut header::HeaderMap,
+    chain_id: ChainId,
+    ledger_info: &LedgerInfoWithSignatures,
+) {
     headers.insert(
         X_DIEM_CHAIN_ID,
         header::HeaderValue::from_str(&chain_id.id().to_string()).unwrap(),
     );
     headers.insert(
         X_DIEM_VERSION_ID,
-        header::HeaderValue::from_str(&latest_ledger_version.to_string()).unwrap(),
+        header::HeaderValue::from_str(&ledger_info.ledger_info().version().to_string()).unwrap(),
     );
     headers.insert(
         X_DIEM_TIMESTAMP_USEC_ID,
-        header::HeaderValue::from_str(&latest_ledger_timestamp_usecs.to_string()).unwrap(),
+        header::HeaderValue::from_str(&ledger_info.ledger_info().timestamp_usecs().to_string())
+            .unwrap(),
     );
-
-    Ok(http_response)
 }
 
 async fn rpc_request_handler(
     request: Value,
+    api_version: ApiVersion,
     service: &JsonRpcService,
     ledger_info: &LedgerInfoWithSignatures,
     request_type_label: &str,
     sdk_info: SdkInfo,
 ) -> JsonRpcResponse {
-    let handler = Handler::new(&service, &ledger_info);
+    let handler = Handler::new(&service, &ledger_info, api_version);
 
     let mut response = JsonRpcResponse::new(
         service.chain_id(),
@@ -363,14 +634,14 @@ async fn rpc_request_handler(
             response.id = Some(serde_json::to_value(&request.id).unwrap());
             match handler.handle(request.method_request).await {
                 Ok(ret) => response.result = Some(ret),
-                Err(e) => response.error = Some(e),
+                Err(e) => response.error = Some(versioned_error(e, api_version)),
             }
             timer.stop_and_record();
         }
         Err((e, m, id)) => {
             method = m;
             response.id = id.map(|id| serde_json::to_value(&id).unwrap());
-            response.error = Some(e);
+            response.error = Some(versioned_error(e, api_version));
         }
     }
 
@@ -379,6 +650,19 @@ async fn rpc_request_handler(
     response
 }
 
+/// Errors returned by API v2 and later carry their category and whether retrying can succeed, and
+/// mempool errors how full mempool is. v1 clients can't parse the latter, so it's left out for them.
+fn versioned_error(mut error: JsonRpcError, api_version: ApiVersion) -> JsonRpcError {
+    if api_version >= ApiVersion::V2 {
+        error.classified()
+    } else {
+        if error.as_mempool_capacity().is_some() {
+            error.data = None;
+        }
+        error
+    }
+}
+
 fn bump_counters(
     response: &JsonRpcResponse,
     request_type: &str,
@@ -398,11 +682,10 @@ fn bump_counters(
                 ])
                 .inc();
         } else {
-            let label = match error.code {
-                -32600 => "invalid_request",
-                -32601 => "method_not_found",
-                -32602 => "invalid_params",
-                -32604 => "invalid_format",
+            let label = match ErrorCode::of(error.code) {
+                Some(error_code) if error_code.category == ErrorCategory::InvalidRequest => {
+                    error_code.name
+                }
                 _ => "unexpected_code",
             };
             counters::INVALID_REQUESTS
@@ -435,7 +718,7 @@ fn bump_counters(
 
 /// Warp rejection types
 #[derive(Debug)]
-struct DatabaseError;
+pub(crate) struct DatabaseError;
 
 impl Reject for DatabaseError {}
 
@@ -2,34 +2,57 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    errors::ServerCode,
-    runtime::check_latest_ledger_info_timestamp,
+    data,
+    errors::{ErrorCategory, JsonRpcError, ServerCode},
+    response::X_DIEM_VERSION_ID,
+    rest,
+    runtime::{
+        bootstrap_from_config, check_latest_ledger_info_timestamp, check_readiness,
+        ReadinessCheckParams, DEFAULT_MAX_SYNC_LAG,
+    },
     tests::utils::{
-        create_database_client_and_runtime, create_db_and_runtime, mock_db, test_bootstrap,
-        MockDiemDB,
+        create_database_client_and_runtime, create_db_and_runtime, mock_db, mock_mempool,
+        payment_currency, test_bootstrap, test_bootstrap_with_mempool, MockDiemDB, MockMempool,
+        MockSyncStatusReader,
     },
     util::{sdk_info_from_user_agent, SdkInfo, SdkLang, SdkVersion},
-    views::VMStatusView,
+    views::{
+        ApiVersionsView, EventDataView, PendingTransactionStatusView, RemovalReasonView,
+        RemovedTransactionView, VMStatusView,
+    },
 };
 use diem_client::{views::TransactionDataView, BlockingClient, MethodRequest};
-use diem_config::{config::DEFAULT_CONTENT_LENGTH_LIMIT, utils};
+use diem_config::{
+    config::{JsonRpcCorsConfig, JsonRpcRateLimitConfig, NodeConfig, DEFAULT_CONTENT_LENGTH_LIMIT},
+    utils,
+};
 use diem_crypto::{ed25519::Ed25519PrivateKey, hash::CryptoHash, HashValue, PrivateKey, Uniform};
+use diem_json_rpc_types::{ApiVersion, Method};
+use diem_mempool::{
+    MempoolReader, PendingTransaction, PendingTransactionStatus, RemovalReason, RemovedTransaction,
+};
 use diem_metrics::get_all_metrics;
 use diem_types::{
+    access_path::AccessPath,
     account_address::AccountAddress,
-    account_config::AccountResource,
+    account_config::{AccountResource, CORE_CODE_ADDRESS},
     account_state::AccountState,
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
     chain_id::ChainId,
+    contract_event::ContractEvent,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
-    mempool_status::{MempoolStatus, MempoolStatusCode},
+    mempool_status::{MempoolCapacity, MempoolStatus, MempoolStatusCode},
     proof::{SparseMerkleProof, TransactionAccumulatorProof, TransactionInfoWithProof},
     test_helpers::transaction_test_helpers::get_test_signed_txn,
-    transaction::{Transaction, TransactionInfo, TransactionPayload},
+    transaction::{
+        Transaction, TransactionInfo, TransactionListWithProof, TransactionPayload,
+        TransactionWithProof,
+    },
     vm_status::StatusCode,
 };
 use futures::{channel::mpsc::channel, StreamExt};
+use move_core_types::language_storage::TypeTag;
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use std::{
     cmp::{max, min},
@@ -64,6 +87,55 @@ fn test_cors() {
     assert_eq!(cors_header, origin);
 }
 
+#[test]
+fn test_cors_config() {
+    let mut config = NodeConfig::default();
+    config.json_rpc.address = format!("127.0.0.1:{}", utils::get_available_port())
+        .parse()
+        .unwrap();
+    config.json_rpc.cors = JsonRpcCorsConfig {
+        allowed_origins: Some(vec!["https://explorer.example.com".to_string()]),
+        allowed_headers: vec!["x-api-key".to_string()],
+        max_age_secs: Some(600),
+    };
+    let (mp_sender, _mp_events) = channel(1);
+    let _runtime = bootstrap_from_config(
+        &config,
+        ChainId::test(),
+        Arc::new(mock_db()),
+        mp_sender,
+        Arc::new(MockMempool::default()),
+        Arc::new(MockSyncStatusReader::default()),
+    );
+    let url = format!("http://{}", config.json_rpc.address);
+
+    let client = reqwest::blocking::Client::new();
+    let preflight = |origin: &str, headers: &str| {
+        client
+            .request(reqwest::Method::OPTIONS, &url)
+            .header("origin", origin)
+            .header("access-control-request-method", "POST")
+            .header("access-control-request-headers", headers)
+            .send()
+            .unwrap()
+    };
+
+    let resp = preflight("https://explorer.example.com", "content-type, x-api-key");
+    assert_eq!(resp.status(), 200);
+    let headers = resp.headers();
+    assert_eq!(
+        headers["access-control-allow-origin"],
+        "https://explorer.example.com"
+    );
+    assert_eq!(headers["access-control-max-age"], "600");
+
+    // other origins and headers are refused
+    let resp = preflight("https://elsewhere.example.com", "content-type");
+    assert_eq!(resp.status(), 403);
+    let resp = preflight("https://explorer.example.com", "x-other");
+    assert_eq!(resp.status(), 403);
+}
+
 #[test]
 fn test_json_rpc_http_errors() {
     let (_mock_db, _runtime, url, _) = create_db_and_runtime();
@@ -83,10 +155,16 @@ fn test_json_rpc_http_errors() {
     let resp = client.post(&url).send().unwrap();
     assert_eq!(resp.status(), 400);
 
-    // For now /v1 and / are both supported
-    let url_v1 = format!("{}/v1", url);
-    let resp = client.post(&url_v1).send().unwrap();
-    assert_eq!(resp.status(), 400);
+    // For now /v1 and / are both supported, alongside each later version
+    for version in ApiVersion::ALL {
+        let resp = client.post(&format!("{}/{}", url, version)).send().unwrap();
+        assert_eq!(resp.status(), 400);
+    }
+
+    // unknown versions are not served
+    let url_v0 = format!("{}/v0", url);
+    let resp = client.post(&url_v0).send().unwrap();
+    assert_eq!(resp.status(), 404);
 
     let resp = client.post(&url).body("non json").send().unwrap();
     assert_eq!(resp.status(), 400);
@@ -254,6 +332,22 @@ fn test_json_rpc_protocol_invalid_requests() {
                 "diem_ledger_version": version
             }),
         ),
+        (
+            "get_accounts invalid arguments: version is too large",
+            json!({"jsonrpc": "2.0", "method": "get_accounts", "params": [["e1b3d22871989e9fd9dc6814b2f4fc41"], version+1], "id": 1}),
+            json!({
+                "error": {
+                    "code": -32602,
+                    "message": format!("Invalid param version should be <= known latest version {}", version),
+                    "data": null
+                },
+                "id": 1,
+                "jsonrpc": "2.0",
+                "diem_chain_id": ChainId::test().id(),
+                "diem_ledger_timestampusec": timestamp,
+                "diem_ledger_version": version
+            }),
+        ),
         (
             "get_account invalid param data type",
             json!({"jsonrpc": "2.0", "method": "get_account", "params": [false], "id": 1}),
@@ -438,6 +532,22 @@ fn test_json_rpc_protocol_invalid_requests() {
                 "diem_ledger_version": version
             }),
         ),
+        (
+            "get_transactions_with_proofs: ledger_version is too large",
+            json!({"jsonrpc": "2.0", "method": "get_transactions_with_proofs", "params": [1, 10, false, version+1], "id": 1}),
+            json!({
+                "error": {
+                    "code": -32602,
+                    "message": format!("Invalid param ledger_version should be <= known latest version {}", version),
+                    "data": null
+                },
+                "id": 1,
+                "jsonrpc": "2.0",
+                "diem_chain_id": ChainId::test().id(),
+                "diem_ledger_timestampusec": timestamp,
+                "diem_ledger_version": version
+            }),
+        ),
         (
             "get_events: invalid event_key type",
             json!({"jsonrpc": "2.0", "method": "get_events", "params": [false, 1, 10], "id": 1}),
@@ -514,6 +624,22 @@ fn test_json_rpc_protocol_invalid_requests() {
                 "diem_ledger_version": version
             }),
         ),
+        (
+            "get_events: invalid type_tag param",
+            json!({"jsonrpc": "2.0", "method": "get_events", "params": ["13000000000000000000000000000000000000000a550c18", 1, 10, "0x1::"], "id": 1}),
+            json!({
+                "error": {
+                    "code": -32602,
+                    "message": "Invalid param invalid type_tag: 0x1::",
+                    "data": null
+                },
+                "id": 1,
+                "jsonrpc": "2.0",
+                "diem_chain_id": ChainId::test().id(),
+                "diem_ledger_timestampusec": timestamp,
+                "diem_ledger_version": version
+            }),
+        ),
         (
             "get_events_with_proofs: invalid event_key type",
             json!({"jsonrpc": "2.0", "method": "get_events_with_proofs", "params": [false, 1, 10], "id": 1}),
@@ -1017,6 +1143,134 @@ fn test_transaction_submission() {
     assert_eq!(status_code, StatusCode::SENDING_ACCOUNT_DOES_NOT_EXIST);
 }
 
+#[test]
+fn test_transaction_submission_backpressure() {
+    let (mp_sender, mut mp_events) = channel(1);
+    let mock_db = mock_db();
+    let port = utils::get_available_port();
+    let address = format!("0.0.0.0:{}", port);
+    let runtime = test_bootstrap(address.parse().unwrap(), Arc::new(mock_db), mp_sender);
+
+    let capacity = MempoolCapacity {
+        size: 100,
+        capacity: 100,
+        size_bytes: 10_000,
+        capacity_bytes: 20_000,
+        sender_size: 2,
+        capacity_per_sender: 10,
+        retry_after_ms: 1_000,
+    };
+    // future that mocks a full shared mempool
+    runtime.spawn(async move {
+        while let Some((_txn, cb)) = mp_events.next().await {
+            let status =
+                MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_capacity(capacity);
+            cb.send(Ok((status, None))).unwrap();
+        }
+    });
+
+    let privkey = Ed25519PrivateKey::generate_for_testing();
+    let sender = AccountAddress::new([9; AccountAddress::LENGTH]);
+    let txn = get_test_signed_txn(sender, 0, &privkey, privkey.public_key(), None);
+
+    let client = BlockingClient::new(format!("http://127.0.0.1:{}/v2", port));
+    let error = client.submit(&txn).unwrap_err();
+    assert!(error.is_retriable());
+    assert_eq!(error.retry_after(), Some(Duration::from_secs(1)));
+    let error = error.json_rpc_error().unwrap();
+    assert_eq!(error.code, ServerCode::MempoolIsFull as i16);
+    assert_eq!(error.as_mempool_capacity(), Some(&capacity));
+
+    // v1 errors don't carry it
+    let client = BlockingClient::new(format!("http://127.0.0.1:{}/v1", port));
+    let error = client.submit(&txn).unwrap_err();
+    let error = error.json_rpc_error().unwrap();
+    assert_eq!(error.code, ServerCode::MempoolIsFull as i16);
+    assert!(error.data.is_none());
+}
+
+#[test]
+fn test_submit_and_wait() {
+    let mock_db = mock_db();
+    let committed = mock_db
+        .all_txns
+        .iter()
+        .find_map(|(txn, _)| txn.as_signed_user_txn().ok().cloned())
+        .unwrap();
+    let privkey = Ed25519PrivateKey::generate_for_testing();
+    let pending = get_test_signed_txn(
+        AccountAddress::random(),
+        0,
+        &privkey,
+        privkey.public_key(),
+        None,
+    );
+    let mempool = MockMempool {
+        txns: vec![PendingTransaction {
+            txn: pending.clone(),
+            status: PendingTransactionStatus::Ready,
+            insertion_time: Duration::from_secs(0),
+        }],
+        ..MockMempool::default()
+    };
+    let (mp_sender, mut mp_events) = channel(1);
+    let port = utils::get_available_port();
+    let address = format!("127.0.0.1:{}", port);
+    let runtime = test_bootstrap_with_mempool(
+        address.parse().unwrap(),
+        Arc::new(mock_db),
+        mp_sender,
+        Arc::new(mempool),
+    );
+    let client = BlockingClient::new(format!("http://{}/v1", address));
+
+    // future that mocks shared mempool accepting every transaction
+    runtime.spawn(async move {
+        while let Some((_txn, cb)) = mp_events.next().await {
+            let result = (MempoolStatus::new(MempoolStatusCode::Accepted), None);
+            cb.send(Ok(result)).unwrap();
+        }
+    });
+
+    // a committed transaction is returned
+    let txn = client
+        .submit_and_wait(&committed, None)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    assert_eq!(
+        txn.hash,
+        Transaction::UserTransaction(committed.clone()).hash()
+    );
+
+    // a transaction still in mempool once the timeout is up is null
+    let timeout = Some(Duration::from_millis(100));
+    let txn = client.submit_and_wait(&pending, timeout).unwrap();
+    assert!(txn.into_inner().is_none());
+
+    // a transaction neither committed nor in mempool was discarded, as is one whose sequence
+    // number was used by another transaction
+    let dropped = get_test_signed_txn(
+        AccountAddress::random(),
+        0,
+        &privkey,
+        privkey.public_key(),
+        None,
+    );
+    let replaced = get_test_signed_txn(
+        committed.sender(),
+        committed.sequence_number(),
+        &privkey,
+        privkey.public_key(),
+        None,
+    );
+    for txn in &[dropped, replaced] {
+        let error = client.submit_and_wait(txn, timeout).unwrap_err();
+        let error = error.json_rpc_error().unwrap();
+        assert_eq!(error.code, ServerCode::TransactionDiscarded as i16);
+    }
+}
+
 #[test]
 fn test_get_account() {
     let (mock_db, client, _runtime) = create_database_client_and_runtime();
@@ -1084,6 +1338,89 @@ fn test_get_account() {
     }
 }
 
+#[test]
+fn test_get_accounts() {
+    let (mock_db, client, _runtime) = create_database_client_and_runtime();
+
+    let mut addresses: Vec<_> = mock_db.all_accounts.keys().copied().collect();
+    let missing = AccountAddress::random();
+    addresses.insert(addresses.len() / 2, missing);
+
+    let entries = client.get_accounts(addresses.clone()).unwrap().into_inner();
+    assert_eq!(entries.len(), addresses.len());
+    for (entry, address) in entries.iter().zip(&addresses) {
+        assert_eq!(entry.address, *address);
+        assert!(entry.error.is_none());
+        let expected = client.get_account(*address).unwrap().into_inner();
+        assert_eq!(entry.account, expected);
+        assert_eq!(entry.account.is_none(), *address == missing);
+    }
+
+    // One entry per address, duplicates included
+    let first = addresses[0];
+    let entries = client
+        .get_accounts(vec![first, first])
+        .unwrap()
+        .into_inner();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0], entries[1]);
+
+    assert!(client.get_accounts(vec![]).unwrap().into_inner().is_empty());
+}
+
+#[test]
+fn test_get_resource() {
+    let mut mock_db = mock_db();
+    let account = *mock_db.all_accounts.keys().next().unwrap();
+
+    // Publish the module declaring the resource, for its fields to be named
+    let (blob, module) = diem_framework_releases::current_modules_with_blobs()
+        .find(|(_, module)| module.self_id().name().as_str() == "AccountFreezing")
+        .unwrap();
+    let mut code_account = AccountState::default();
+    code_account.insert(AccessPath::from(&module.self_id()).path, blob.clone());
+    mock_db.all_accounts.insert(
+        CORE_CODE_ADDRESS,
+        AccountStateBlob::try_from(&code_account).unwrap(),
+    );
+
+    let address = format!("127.0.0.1:{}", utils::get_available_port());
+    let (mp_sender, _mp_events) = channel(1);
+    let _runtime = test_bootstrap(address.parse().unwrap(), Arc::new(mock_db), mp_sender);
+    let client = BlockingClient::new(format!("http://{}/v1", address));
+
+    let struct_tag = "0x1::AccountFreezing::FreezingBit";
+    let resource = client
+        .get_resource(account, struct_tag)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    assert_eq!(resource.type_, struct_tag);
+    assert_eq!(resource.value, json!({ "is_frozen": false }));
+
+    // An account without the resource, and an account that does not exist
+    for address in &[CORE_CODE_ADDRESS, AccountAddress::random()] {
+        let resource = client.get_resource(*address, struct_tag).unwrap();
+        assert_eq!(resource.into_inner(), None);
+    }
+
+    // Only structs are resources
+    let error = client.get_resource(account, "u64").unwrap_err();
+    assert_eq!(error.json_rpc_error().unwrap().code, -32602);
+}
+
+#[test]
+fn test_get_accounts_page_limit() {
+    let (_, client, _runtime) = create_database_client_and_runtime();
+
+    let ret = client
+        .get_accounts(vec![AccountAddress::random(); 1001])
+        .unwrap_err();
+    let error = ret.json_rpc_error().unwrap();
+    let expected = "JsonRpcError { code: -32600, message: \"Invalid Request: page size = 1001, exceed limit 1000\", data: None, category: None, retriable: None }";
+    assert_eq!(format!("{:?}", error), expected)
+}
+
 #[test]
 fn test_get_metadata_latest() {
     let (mock_db, client, _runtime) = create_database_client_and_runtime();
@@ -1093,6 +1430,7 @@ fn test_get_metadata_latest() {
     let metadata = client.get_metadata().unwrap().into_inner();
     assert_eq!(metadata.version, actual_version);
     assert_eq!(metadata.timestamp, actual_timestamp);
+    assert_eq!(metadata.earliest_available_version, Some(0));
 }
 
 #[test]
@@ -1117,7 +1455,7 @@ fn test_limit_batch_size() {
     let ret = client.batch(batch).unwrap_err();
 
     let error = ret.json_rpc_error().unwrap();
-    let expected = "JsonRpcError { code: -32600, message: \"Invalid Request: batch size = 21, exceed limit 20\", data: None }";
+    let expected = "JsonRpcError { code: -32600, message: \"Invalid Request: batch size = 21, exceed limit 20\", data: None, category: None, retriable: None }";
     assert_eq!(format!("{:?}", error), expected)
 }
 
@@ -1134,7 +1472,7 @@ fn test_get_events_page_limit() {
         .unwrap_err();
 
     let error = ret.json_rpc_error().unwrap();
-    let expected = "JsonRpcError { code: -32600, message: \"Invalid Request: page size = 1001, exceed limit 1000\", data: None }";
+    let expected = "JsonRpcError { code: -32600, message: \"Invalid Request: page size = 1001, exceed limit 1000\", data: None, category: None, retriable: None }";
     assert_eq!(format!("{:?}", error), expected)
 }
 
@@ -1144,7 +1482,7 @@ fn test_get_transactions_page_limit() {
 
     let ret = client.get_transactions(0, 1001, false).unwrap_err();
     let error = ret.json_rpc_error().unwrap();
-    let expected = "JsonRpcError { code: -32600, message: \"Invalid Request: page size = 1001, exceed limit 1000\", data: None }";
+    let expected = "JsonRpcError { code: -32600, message: \"Invalid Request: page size = 1001, exceed limit 1000\", data: None, category: None, retriable: None }";
     assert_eq!(format!("{:?}", error), expected)
 }
 
@@ -1178,6 +1516,233 @@ fn test_get_events() {
     );
 }
 
+#[test]
+fn test_get_events_with_filter() {
+    let mut mock_db = mock_db();
+    let key = EventKey::new_from_address(&AccountAddress::random(), 0);
+    mock_db.events = [1, 1, 3, 6, 9]
+        .iter()
+        .enumerate()
+        .map(|(seq, version)| {
+            let event = ContractEvent::new(key, seq as u64, TypeTag::Bool, b"data".to_vec());
+            (*version, event)
+        })
+        .collect();
+    let get_events = |start, filter: &data::EventFilter| -> Vec<(u64, u64)> {
+        data::get_events(&mock_db, 10, key, start, 10, filter, true)
+            .unwrap()
+            .into_iter()
+            .map(|event| (event.sequence_number, event.transaction_version))
+            .collect()
+    };
+
+    let all = get_events(0, &data::EventFilter::default());
+    assert_eq!(all, vec![(0, 1), (1, 1), (2, 3), (3, 6), (4, 9)]);
+
+    let in_range = data::EventFilter {
+        start_version: Some(2),
+        end_version: Some(6),
+        ..data::EventFilter::default()
+    };
+    assert_eq!(get_events(0, &in_range), vec![(2, 3), (3, 6)]);
+    // `start` is the cursor for the next page within the same range.
+    assert_eq!(get_events(3, &in_range), vec![(3, 6)]);
+    assert!(get_events(4, &in_range).is_empty());
+
+    let past_the_end = data::EventFilter {
+        start_version: Some(10),
+        ..data::EventFilter::default()
+    };
+    assert!(get_events(0, &past_the_end).is_empty());
+
+    let empty_range = data::EventFilter {
+        start_version: Some(6),
+        end_version: Some(3),
+        ..data::EventFilter::default()
+    };
+    assert!(get_events(0, &empty_range).is_empty());
+
+    let same_type = data::EventFilter {
+        type_tag: Some(TypeTag::Bool),
+        ..data::EventFilter::default()
+    };
+    assert_eq!(get_events(0, &same_type), all);
+
+    let other_type = data::EventFilter {
+        type_tag: Some(TypeTag::U64),
+        ..data::EventFilter::default()
+    };
+    assert!(get_events(0, &other_type).is_empty());
+}
+
+#[test]
+fn test_get_events_decoding_custom_events() {
+    let mut mock_db = mock_db();
+    let key = EventKey::new_from_address(&AccountAddress::random(), 0);
+    let amount = 42u64.to_le_bytes().to_vec();
+    mock_db.events = vec![
+        (1, ContractEvent::new(key, 0, TypeTag::U64, amount.clone())),
+        // Not a valid u64, so left undecoded
+        (
+            2,
+            ContractEvent::new(key, 1, TypeTag::U64, b"data".to_vec()),
+        ),
+    ];
+    let get_events = |decode_custom_events| -> Vec<EventDataView> {
+        data::get_events(
+            &mock_db,
+            10,
+            key,
+            0,
+            10,
+            &data::EventFilter::default(),
+            decode_custom_events,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|event| event.data)
+        .collect()
+    };
+
+    assert_eq!(
+        get_events(true),
+        vec![
+            EventDataView::Custom {
+                type_tag: "U64".to_string(),
+                value: json!(42),
+                bytes: amount.clone().into(),
+            },
+            EventDataView::Unknown {
+                bytes: Some(b"data".to_vec().into()),
+            },
+        ]
+    );
+    assert_eq!(
+        get_events(false),
+        vec![
+            EventDataView::Unknown {
+                bytes: Some(amount.into()),
+            },
+            EventDataView::Unknown {
+                bytes: Some(b"data".to_vec().into()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_get_events_by_type() {
+    let (mock_db, client, _runtime) = create_database_client_and_runtime();
+
+    let type_tag = mock_db.events[0].1.type_tag();
+    let expected: Vec<_> = mock_db
+        .events
+        .iter()
+        .filter(|(version, event)| event.type_tag() == type_tag && *version <= mock_db.version)
+        .map(|(version, event)| (*version, event.sequence_number()))
+        .collect();
+    let events = client
+        .get_events_by_type(&type_tag.to_string(), 0, 1000)
+        .unwrap()
+        .into_inner();
+    let events: Vec<_> = events
+        .iter()
+        .map(|event| (event.transaction_version, event.sequence_number))
+        .collect();
+    assert_eq!(events, expected);
+
+    let ret = client.get_events_by_type("not a type", 0, 10).unwrap_err();
+    let error = ret.json_rpc_error().unwrap();
+    assert_eq!(error.code, -32602);
+}
+
+#[test]
+fn test_get_events_by_account() {
+    let (mock_db, client, _runtime) = create_database_client_and_runtime();
+
+    let account = mock_db.events[0].1.key().get_creator_address();
+    let expected: Vec<_> = mock_db
+        .events
+        .iter()
+        .filter(|(version, event)| {
+            event.key().get_creator_address() == account && *version <= mock_db.version
+        })
+        .map(|(version, event)| (*version, event.sequence_number()))
+        .collect();
+    let events = client
+        .get_events_by_account(account, 0, 1000)
+        .unwrap()
+        .into_inner();
+    let events: Vec<_> = events
+        .iter()
+        .map(|event| (event.transaction_version, event.sequence_number))
+        .collect();
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn test_get_events_by_currency() {
+    let (mock_db, client, _runtime) = create_database_client_and_runtime();
+
+    let expected: Vec<_> = mock_db
+        .events
+        .iter()
+        .filter(|(version, event)| {
+            payment_currency(event).as_deref() == Some("XUS") && *version <= mock_db.version
+        })
+        .map(|(version, event)| (*version, event.sequence_number()))
+        .collect();
+    let events = client
+        .get_events_by_currency("XUS", 0, 1000)
+        .unwrap()
+        .into_inner();
+    let events: Vec<_> = events
+        .iter()
+        .map(|event| (event.transaction_version, event.sequence_number))
+        .collect();
+    assert_eq!(events, expected);
+
+    let ret = client
+        .get_events_by_currency("not a currency", 0, 10)
+        .unwrap_err();
+    let error = ret.json_rpc_error().unwrap();
+    assert_eq!(error.code, -32602);
+}
+
+#[test]
+fn test_get_block_metadata() {
+    let (mock_db, client, _runtime) = create_database_client_and_runtime();
+
+    for version in 0..=mock_db.version {
+        let expected = mock_db
+            .all_txns
+            .iter()
+            .take(version as usize + 1)
+            .enumerate()
+            .rev()
+            .find_map(|(v, (t, _))| match t {
+                Transaction::BlockMetadata(block_metadata) => Some((v as u64, block_metadata)),
+                _ => None,
+            });
+        let view = client.get_block_metadata(version).unwrap().into_inner();
+        match expected {
+            Some((block_version, block_metadata)) => {
+                let view = view.unwrap();
+                assert_eq!(view.version, block_version);
+                assert_eq!(view.id, block_metadata.id());
+                assert_eq!(view.round, block_metadata.round());
+                assert_eq!(view.proposer, block_metadata.proposer());
+                assert_eq!(view.timestamp_usecs, block_metadata.timestamp_usec());
+            }
+            None => assert!(view.is_none()),
+        }
+    }
+
+    let ret = client.get_block_metadata(mock_db.version + 1).unwrap_err();
+    let error = ret.json_rpc_error().unwrap();
+    assert_eq!(error.code, -32602);
+}
+
 #[test]
 fn test_get_transactions() {
     let (mock_db, client, _runtime) = create_database_client_and_runtime();
@@ -1338,6 +1903,53 @@ fn test_get_account_transactions() {
         assert_eq!(tx_views.len() as u64, total);
     }
 }
+
+#[test]
+fn test_get_account_transactions_descending() {
+    let mock_db = mock_db();
+    let (account, blob) = mock_db
+        .all_accounts
+        .iter()
+        .max_by_key(|(_, blob)| AccountResource::try_from(*blob).unwrap().sequence_number())
+        .unwrap();
+    let total = AccountResource::try_from(blob).unwrap().sequence_number();
+    let get_sequence_numbers = |start, limit, descending| -> Vec<u64> {
+        data::get_account_transactions(
+            &mock_db,
+            mock_db.version,
+            *account,
+            start,
+            limit,
+            false,
+            descending,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|txn| match txn.transaction {
+            TransactionDataView::UserTransaction {
+                sequence_number, ..
+            } => sequence_number,
+            _ => panic!("not a user transaction"),
+        })
+        .collect()
+    };
+
+    // the latest transactions, without knowing the latest sequence number
+    let latest: Vec<_> = (0..total).rev().take(2).collect();
+    assert_eq!(get_sequence_numbers(u64::MAX, 2, true), latest);
+    // paging back from the last one seen
+    if let Some(last) = latest.last().filter(|last| **last > 0) {
+        assert_eq!(
+            get_sequence_numbers(last - 1, 1000, true),
+            (0..*last).rev().collect::<Vec<_>>()
+        );
+    }
+    assert_eq!(
+        get_sequence_numbers(0, 1000, false),
+        (0..total).collect::<Vec<_>>()
+    );
+    assert!(get_sequence_numbers(u64::MAX, 0, true).is_empty());
+}
 #[test]
 // Check that if version and ledger_version parameters are None, then the server returns the latest
 // known state.
@@ -1389,6 +2001,51 @@ fn test_get_account_state_with_proof() {
     assert_eq!(txn_info_with_proof, *expected_txn_info_with_proof);
 }
 
+#[test]
+fn test_get_transactions_with_proofs_by_version() {
+    let (mock_db, client, _runtime) = create_database_client_and_runtime();
+
+    // the proofs are against ledger version 2, so nothing past it is returned
+    let view = client
+        .get_transactions_with_proofs_by_version(0, 10, false, 2)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    let txn_list = view.try_into_txn_list_with_proof(0).unwrap();
+    let expected: Vec<_> = mock_db.all_txns[..3]
+        .iter()
+        .map(|(t, _)| t.clone())
+        .collect();
+    assert_eq!(txn_list.transactions, expected);
+    assert_eq!(txn_list.proof.transaction_infos.len(), 3);
+
+    let view = client
+        .get_transactions_with_proofs_by_version(3, 10, false, 2)
+        .unwrap()
+        .into_inner();
+    assert!(view.is_none());
+}
+
+#[test]
+fn test_get_transactions_with_proofs_params() {
+    let request = MethodRequest::get_transactions_with_proofs(1, 10, false);
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        json!({"method": "get_transactions_with_proofs", "params": [1, 10, false]})
+    );
+
+    let request = MethodRequest::get_transactions_with_proofs_by_version(1, 10, false, 2);
+    let value = serde_json::to_value(&request).unwrap();
+    assert_eq!(
+        value,
+        json!({"method": "get_transactions_with_proofs", "params": [1, 10, false, 2]})
+    );
+    match serde_json::from_value(value).unwrap() {
+        MethodRequest::GetTransactionsWithProofs(1, 10, false, Some(2)) => {}
+        other => panic!("unexpected request {:?}", other),
+    }
+}
+
 #[test]
 fn test_get_state_proof() {
     let (mock_db, client, _runtime) = create_database_client_and_runtime();
@@ -1408,6 +2065,149 @@ fn test_get_network_status() {
     assert_eq!(connected_peers, 0);
 }
 
+#[test]
+fn test_get_api_versions() {
+    let (_mock_db, _runtime, url, _) = create_db_and_runtime();
+    let client = reqwest::blocking::Client::new();
+    let request = json!({"jsonrpc": "2.0", "method": "get_api_versions", "params": [], "id": 1});
+
+    let paths = [
+        ("", ApiVersion::V1),
+        ("/v1", ApiVersion::V1),
+        ("/v2", ApiVersion::V2),
+    ];
+    for (path, version) in paths.iter() {
+        let resp: serde_json::Value = client
+            .post(&format!("{}{}", url, path))
+            .json(&request)
+            .send()
+            .unwrap()
+            .json()
+            .unwrap();
+        let view: ApiVersionsView = serde_json::from_value(resp["result"].clone()).unwrap();
+        assert_eq!(&view.version, version);
+        assert_eq!(view.latest_version, ApiVersion::LATEST);
+        assert_eq!(view.versions, ApiVersion::ALL.to_vec());
+        assert!(view.methods.contains(&Method::SubmitAndWait));
+        assert!(view.methods.contains(&Method::GetApiVersions));
+        assert!(!view.archival);
+    }
+}
+
+#[test]
+fn test_errors_classified_from_v2() {
+    let (_mock_db, _runtime, url, _) = create_db_and_runtime();
+    let client = reqwest::blocking::Client::new();
+    let request = json!({"jsonrpc": "2.0", "method": "get_account", "params": [], "id": 1});
+
+    let resp: serde_json::Value = client
+        .post(&format!("{}/v1", url))
+        .json(&request)
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    let error = resp["error"].as_object().unwrap();
+    assert_eq!(error["code"], -32602);
+    assert!(!error.contains_key("category"));
+    assert!(!error.contains_key("retriable"));
+
+    let resp: serde_json::Value = client
+        .post(&format!("{}/v2", url))
+        .json(&request)
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    let error: JsonRpcError = serde_json::from_value(resp["error"].clone()).unwrap();
+    assert_eq!(error.code, -32602);
+    assert_eq!(error.category, Some(ErrorCategory::InvalidRequest));
+    assert_eq!(error.retriable, Some(false));
+}
+
+#[test]
+fn test_get_pending_transactions() {
+    let mock_db = mock_db();
+    let mempool = mock_mempool(&mock_db);
+    let port = utils::get_available_port();
+    let address = format!("127.0.0.1:{}", port);
+    let (mp_sender, _mp_events) = channel(1);
+    let _runtime = test_bootstrap_with_mempool(
+        address.parse().unwrap(),
+        Arc::new(mock_db),
+        mp_sender,
+        Arc::new(mempool.clone()),
+    );
+    let client = BlockingClient::new(format!("http://{}/v1", address));
+
+    for pending in &mempool.txns {
+        let sender = pending.txn.sender();
+        let views = client
+            .get_pending_transactions(sender)
+            .unwrap()
+            .into_inner();
+        let expected = mempool.get_account_transactions(&sender);
+        assert_eq!(views.len(), expected.len());
+        for (view, expected) in views.iter().zip(expected) {
+            let txn = Transaction::UserTransaction(expected.txn);
+            assert_eq!(view.hash, txn.hash());
+            assert_eq!(view.transaction, TransactionDataView::from(txn));
+            let status = match expected.status {
+                PendingTransactionStatus::Ready => PendingTransactionStatusView::Ready,
+                PendingTransactionStatus::Parked => PendingTransactionStatusView::Parked,
+            };
+            assert_eq!(view.status, status);
+        }
+    }
+
+    let views = client
+        .get_pending_transactions(AccountAddress::random())
+        .unwrap()
+        .into_inner();
+    assert!(views.is_empty());
+}
+
+#[test]
+fn test_get_removed_transaction() {
+    let account = AccountAddress::random();
+    let mut mempool = MockMempool::default();
+    mempool.removed.insert(
+        (account, 3),
+        RemovedTransaction {
+            reason: RemovalReason::Evicted,
+            removal_time: Duration::from_secs(10),
+        },
+    );
+    let port = utils::get_available_port();
+    let address = format!("127.0.0.1:{}", port);
+    let (mp_sender, _mp_events) = channel(1);
+    let _runtime = test_bootstrap_with_mempool(
+        address.parse().unwrap(),
+        Arc::new(mock_db()),
+        mp_sender,
+        Arc::new(mempool),
+    );
+    let client = BlockingClient::new(format!("http://{}/v1", address));
+
+    let view = client
+        .get_removed_transaction(account, 3)
+        .unwrap()
+        .into_inner();
+    assert_eq!(
+        view,
+        Some(RemovedTransactionView {
+            reason: RemovalReasonView::Evicted,
+            removal_timestamp_usecs: 10_000_000,
+        })
+    );
+
+    let view = client
+        .get_removed_transaction(account, 4)
+        .unwrap()
+        .into_inner();
+    assert_eq!(view, None);
+}
+
 #[test]
 fn test_health_check() {
     let (_mock_db, _runtime, url, _) = create_db_and_runtime();
@@ -1429,6 +2229,279 @@ fn test_health_check() {
     assert_eq!(resp.status(), 200);
 }
 
+#[test]
+fn test_readiness_check() {
+    let mock_db = mock_db();
+    let version = mock_db.version;
+    let port = utils::get_available_port();
+    let address = format!("127.0.0.1:{}", port);
+    let (mp_sender, _mp_events) = channel(1);
+    let full_mempool = MockMempool {
+        txns: mock_mempool(&mock_db).txns,
+        capacity: 1,
+    };
+    let _runtime = test_bootstrap_with_mempool(
+        address.parse().unwrap(),
+        Arc::new(mock_db.clone()),
+        mp_sender,
+        Arc::new(full_mempool.clone()),
+    );
+
+    // a full mempool makes the node unready, but not unhealthy
+    let client = reqwest::blocking::Client::new();
+    let resp = client
+        .get(&format!("http://{}/-/ready", address))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 503);
+    let body: serde_json::Value = resp.json().unwrap();
+    assert_eq!(body["ready"], false);
+    assert_eq!(body["storage"]["ok"], true);
+    assert_eq!(body["storage"]["version"], version);
+    assert_eq!(body["mempool"]["ok"], false);
+    let resp = client
+        .get(&format!("http://{}/-/healthy", address))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let now = SystemTime::now();
+    let params = |max_sync_lag, duration_secs| ReadinessCheckParams {
+        max_sync_lag,
+        duration_secs,
+    };
+    let mempool = MockMempool::default();
+    let readiness = check_readiness(&params(None, None), &mock_db, &mempool, None, now);
+    assert!(readiness.ready);
+    assert_eq!(readiness.state_sync.lag, None);
+
+    // lagging behind peers
+    let highest = Some(version + DEFAULT_MAX_SYNC_LAG);
+    let readiness = check_readiness(&params(None, None), &mock_db, &mempool, highest, now);
+    assert!(readiness.ready);
+    assert_eq!(readiness.state_sync.lag, Some(DEFAULT_MAX_SYNC_LAG));
+    let highest = Some(version + 11);
+    let readiness = check_readiness(&params(Some(10), None), &mock_db, &mempool, highest, now);
+    assert!(!readiness.ready);
+    assert!(!readiness.state_sync.ok);
+
+    // ahead of what state sync saw last is not lagging
+    let highest = Some(version - 1);
+    let readiness = check_readiness(&params(Some(0), None), &mock_db, &mempool, highest, now);
+    assert_eq!(readiness.state_sync.lag, Some(0));
+    assert!(readiness.ready);
+
+    // stale ledger info
+    let later = now + Duration::from_secs(3600);
+    let readiness = check_readiness(&params(None, Some(60)), &mock_db, &mempool, None, later);
+    assert!(!readiness.ready);
+    assert!(!readiness.storage.ok);
+}
+
+#[test]
+fn test_rest_routes() {
+    let (mock_db, _runtime, url, _) = create_db_and_runtime();
+    let client = reqwest::blocking::Client::new();
+    let address = mock_db.all_accounts.keys().next().unwrap();
+
+    // the REST route returns the result of the equivalent JSON-RPC call
+    let resp = client
+        .get(&format!("{}/v1/accounts/{}", url, address))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers()[X_DIEM_VERSION_ID],
+        mock_db.version.to_string().as_str()
+    );
+    let account: serde_json::Value = resp.json().unwrap();
+    let request = json!({"jsonrpc": "2.0", "method": "get_account", "params": [address], "id": 1});
+    let resp: serde_json::Value = client
+        .post(&url)
+        .json(&request)
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(account, resp["result"]);
+
+    let resp = client
+        .get(&format!("{}/v1/accounts/{}", url, AccountAddress::random()))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+
+    let resp = client
+        .get(&format!("{}/v1/accounts/not_an_address", url))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+    let error: serde_json::Value = resp.json().unwrap();
+    assert_eq!(error["code"], -32602);
+
+    let resp = client
+        .get(&format!("{}/v1/transactions?start=0&limit=10", url))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.json::<Vec<serde_json::Value>>().unwrap().len(), 10);
+
+    // paging parameters are required
+    let resp = client
+        .get(&format!("{}/v1/transactions", url))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+
+    let resp = client
+        .get(&format!("{}/v1/openapi.json", url))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert!(resp.json::<serde_json::Value>().unwrap()["paths"].is_object());
+}
+
+#[test]
+fn test_rest_routes_bcs() {
+    let (mock_db, _runtime, url, _) = create_db_and_runtime();
+    let client = reqwest::blocking::Client::new();
+    let get_bcs = |path: &str| {
+        client
+            .get(&format!("{}{}", url, path))
+            .header("accept", "application/json;q=0.5, application/x-bcs")
+            .send()
+            .unwrap()
+    };
+
+    let (address, blob) = mock_db.all_accounts.iter().next().unwrap();
+    let resp = get_bcs(&format!("/v1/accounts/{}", address));
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], rest::MIME_TYPE_BCS);
+    assert_eq!(
+        resp.headers()[X_DIEM_VERSION_ID],
+        mock_db.version.to_string().as_str()
+    );
+    let account: AccountStateBlob = bcs::from_bytes(&resp.bytes().unwrap()).unwrap();
+    assert_eq!(&account, blob);
+
+    let resp = get_bcs(&format!("/v1/accounts/{}", AccountAddress::random()));
+    assert_eq!(resp.status(), 404);
+
+    let resp = get_bcs("/v1/transactions?start=0&limit=10&include_events=true");
+    assert_eq!(resp.status(), 200);
+    let txns: TransactionListWithProof = bcs::from_bytes(&resp.bytes().unwrap()).unwrap();
+    assert_eq!(txns.first_transaction_version, Some(0));
+    let expected: Vec<_> = mock_db.all_txns[..10]
+        .iter()
+        .map(|(txn, _)| txn.clone())
+        .collect();
+    assert_eq!(txns.transactions, expected);
+
+    let resp = get_bcs(&format!(
+        "/v1/accounts/{}/transactions?start=0&limit=10",
+        address
+    ));
+    assert_eq!(resp.status(), 200);
+    let txns: Vec<TransactionWithProof> = bcs::from_bytes(&resp.bytes().unwrap()).unwrap();
+    for txn in txns {
+        assert_eq!(
+            txn.transaction.as_signed_user_txn().unwrap().sender(),
+            *address
+        );
+    }
+
+    let (version, event) = &mock_db.events[0];
+    let resp = get_bcs(&format!(
+        "/v1/events/{}?start={}&limit=10",
+        event.key(),
+        event.sequence_number()
+    ));
+    assert_eq!(resp.status(), 200);
+    let events: Vec<(u64, ContractEvent)> = bcs::from_bytes(&resp.bytes().unwrap()).unwrap();
+    assert!(events.contains(&(*version, event.clone())));
+
+    // errors stay JSON
+    let resp = get_bcs("/v1/transactions?start=0&limit=100000");
+    assert_eq!(resp.status(), 400);
+    let error: serde_json::Value = resp.json().unwrap();
+    assert_eq!(error["code"], -32600);
+}
+
+#[test]
+fn test_rate_limit() {
+    let mut config = NodeConfig::default();
+    config.json_rpc.address = format!("127.0.0.1:{}", utils::get_available_port())
+        .parse()
+        .unwrap();
+    config.json_rpc.rate_limit = Some(JsonRpcRateLimitConfig {
+        api_key_header: Some("x-api-key".to_string()),
+        api_keys: vec!["key".to_string()],
+        read_calls_per_sec: 1,
+        read_burst: 2,
+        ..Default::default()
+    });
+    let (mp_sender, _mp_events) = channel(1);
+    let _runtime = bootstrap_from_config(
+        &config,
+        ChainId::test(),
+        Arc::new(mock_db()),
+        mp_sender,
+        Arc::new(MockMempool::default()),
+        Arc::new(MockSyncStatusReader::default()),
+    );
+    let url = format!("http://{}", config.json_rpc.address);
+
+    let client = reqwest::blocking::Client::new();
+    let request = json!({"jsonrpc": "2.0", "method": "get_currencies", "id": 1});
+    let resp = client.post(&url).json(&request).send().unwrap();
+    assert_eq!(resp.status(), 200);
+    let resp = client
+        .get(&format!("{}/v1/transactions?start=0&limit=1", url))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    // both routes draw from the same bucket, which is now empty
+    let resp = client.post(&url).json(&request).send().unwrap();
+    assert_eq!(resp.status(), 429);
+    assert!(resp.headers().contains_key("retry-after"));
+    let resp: serde_json::Value = resp.json().unwrap();
+    assert_eq!(resp["error"]["code"], ServerCode::RateLimited as i16);
+    let resp = client
+        .get(&format!("{}/v1/transactions?start=0&limit=1", url))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 429);
+
+    // a batch larger than the burst never goes through, so there is nothing to wait for
+    let resp = client
+        .post(&url)
+        .header("x-api-key", "key")
+        .json(&json!([request, request, request]))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 429);
+    assert!(!resp.headers().contains_key("retry-after"));
+
+    // an unknown API key is ignored, the client is still limited by IP address
+    let resp = client
+        .post(&url)
+        .header("x-api-key", "made-up")
+        .json(&request)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 429);
+
+    // clients sending a configured API key have their own bucket
+    let resp = client
+        .post(&url)
+        .header("x-api-key", "key")
+        .json(&json!([request, request]))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+}
+
 #[test]
 fn test_sdk_info_from_user_agent() {
     // Invalid user agents:
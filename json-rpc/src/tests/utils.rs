@@ -4,18 +4,23 @@
 use anyhow::{format_err, Error, Result};
 use diem_config::{
     config::{
-        RoleType, DEFAULT_BATCH_SIZE_LIMIT, DEFAULT_CONTENT_LENGTH_LIMIT, DEFAULT_PAGE_SIZE_LIMIT,
+        JsonRpcCorsConfig, MempoolConfig, RoleType, DEFAULT_BATCH_SIZE_LIMIT,
+        DEFAULT_CONTENT_LENGTH_LIMIT, DEFAULT_PAGE_SIZE_LIMIT, DEFAULT_SUBMIT_AND_WAIT_TIMEOUT_MS,
     },
     utils,
 };
 use diem_crypto::HashValue;
-use diem_mempool::{MempoolClientSender, SubmissionStatus};
+use diem_mempool::{
+    MempoolClientSender, MempoolReader, PendingTransaction, PendingTransactionStatus,
+    RemovedTransaction, SubmissionStatus,
+};
 
 use diem_types::{
     account_address::AccountAddress,
     account_state::AccountState,
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
     block_info::BlockInfo,
+    block_metadata::BlockMetadata,
     chain_id::ChainId,
     contract_event::{ContractEvent, EventWithProof},
     epoch_change::EpochChangeProof,
@@ -36,7 +41,7 @@ use diemdb::test_helper::arb_blocks_to_commit;
 use crate::tests::genesis::generate_genesis_state;
 use diem_client::BlockingClient;
 use diem_proptest_helpers::ValueGenerator;
-use diem_types::account_config::FreezingBit;
+use diem_types::account_config::{FreezingBit, ReceivedPaymentEvent, SentPaymentEvent};
 use futures::channel::{
     mpsc::{channel, Receiver},
     oneshot,
@@ -48,11 +53,13 @@ use move_core_types::{
 };
 use move_vm_types::values::{Struct, Value};
 use proptest::prelude::*;
+use state_sync::sync_status::{SyncStatus, SyncStatusReader};
 use std::{
     collections::{BTreeMap, HashMap},
     convert::TryFrom,
     net::SocketAddr,
     sync::Arc,
+    time::Duration,
 };
 use storage_interface::{DbReader, Order, StartupInfo, TreeState};
 use tokio::runtime::Runtime;
@@ -64,21 +71,112 @@ pub fn test_bootstrap(
     address: SocketAddr,
     diem_db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
+) -> Runtime {
+    test_bootstrap_with_mempool(
+        address,
+        diem_db,
+        mp_sender,
+        Arc::new(MockMempool::default()),
+    )
+}
+
+/// Creates JSON RPC server for a Validator node, reading pending transactions from `mempool`
+/// Should only be used for unit-tests
+#[allow(unused)]
+pub fn test_bootstrap_with_mempool(
+    address: SocketAddr,
+    diem_db: Arc<dyn DbReader>,
+    mp_sender: MempoolClientSender,
+    mempool: Arc<dyn MempoolReader>,
 ) -> Runtime {
     crate::bootstrap(
         address,
         DEFAULT_BATCH_SIZE_LIMIT,
         DEFAULT_PAGE_SIZE_LIMIT,
         DEFAULT_CONTENT_LENGTH_LIMIT,
+        DEFAULT_SUBMIT_AND_WAIT_TIMEOUT_MS,
         &None,
         &None,
+        &None,
+        &JsonRpcCorsConfig::default(),
+        &None,
         diem_db,
         mp_sender,
+        mempool,
         RoleType::Validator,
+        false,
         ChainId::test(),
     )
 }
 
+/// Lightweight mock of mempool
+#[derive(Clone)]
+#[allow(unused)]
+pub struct MockMempool {
+    pub txns: Vec<PendingTransaction>,
+    pub capacity: usize,
+    pub removed: HashMap<(AccountAddress, u64), RemovedTransaction>,
+}
+
+impl Default for MockMempool {
+    fn default() -> Self {
+        Self {
+            txns: vec![],
+            capacity: MempoolConfig::default().capacity,
+            removed: HashMap::new(),
+        }
+    }
+}
+
+impl MempoolReader for MockMempool {
+    fn get_account_transactions(&self, account: &AccountAddress) -> Vec<PendingTransaction> {
+        self.txns
+            .iter()
+            .filter(|pending| &pending.txn.sender() == account)
+            .cloned()
+            .collect()
+    }
+
+    fn get_all_transactions(&self) -> Vec<PendingTransaction> {
+        self.txns.clone()
+    }
+
+    fn get_size_and_capacity(&self) -> (usize, usize) {
+        (self.txns.len(), self.capacity)
+    }
+
+    fn get_removed_transaction(
+        &self,
+        account: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<RemovedTransaction> {
+        self.removed.get(&(*account, sequence_number)).copied()
+    }
+}
+
+/// Mock of state sync, reporting a fixed status
+#[allow(unused)]
+pub struct MockSyncStatusReader(pub SyncStatus);
+
+impl Default for MockSyncStatusReader {
+    fn default() -> Self {
+        Self(SyncStatus {
+            highest_known_version: 0,
+            synced_version: 0,
+            committed_version: 0,
+            initialized: true,
+            sync_rate: 0.0,
+            estimated_catch_up_time: Some(Duration::from_secs(0)),
+        })
+    }
+}
+
+impl SyncStatusReader for MockSyncStatusReader {
+    fn get_sync_status(&self) -> SyncStatus {
+        self.0.clone()
+    }
+}
+
 /// Lightweight mock of DiemDB
 #[derive(Clone)]
 #[allow(unused)]
@@ -249,6 +347,80 @@ impl DbReader for MockDiemDB {
         Ok(events)
     }
 
+    fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: u64,
+        limit: u64,
+        ledger_version: u64,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        let events = self
+            .events
+            .iter()
+            .filter(|(version, e)| {
+                e.type_tag() == type_tag && start_version <= *version && *version <= ledger_version
+            })
+            .take(limit as usize)
+            .cloned()
+            .collect();
+        Ok(events)
+    }
+
+    fn get_events_by_account(
+        &self,
+        address: AccountAddress,
+        start_version: u64,
+        limit: u64,
+        ledger_version: u64,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        let events = self
+            .events
+            .iter()
+            .filter(|(version, e)| {
+                e.key().get_creator_address() == address
+                    && start_version <= *version
+                    && *version <= ledger_version
+            })
+            .take(limit as usize)
+            .cloned()
+            .collect();
+        Ok(events)
+    }
+
+    fn get_events_by_currency(
+        &self,
+        currency_code: &str,
+        start_version: u64,
+        limit: u64,
+        ledger_version: u64,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        let events = self
+            .events
+            .iter()
+            .filter(|(version, e)| {
+                payment_currency(e).as_deref() == Some(currency_code)
+                    && start_version <= *version
+                    && *version <= ledger_version
+            })
+            .take(limit as usize)
+            .cloned()
+            .collect();
+        Ok(events)
+    }
+
+    fn get_first_event_sequence_number_at_or_after_version(
+        &self,
+        key: &EventKey,
+        version: u64,
+    ) -> Result<Option<u64>> {
+        Ok(self
+            .events
+            .iter()
+            .filter(|(v, e)| e.key() == key && *v >= version)
+            .map(|(_, e)| e.sequence_number())
+            .min())
+    }
+
     fn get_events_with_proofs(
         &self,
         _key: &EventKey,
@@ -277,6 +449,40 @@ impl DbReader for MockDiemDB {
         ))
     }
 
+    fn get_account_transactions(
+        &self,
+        address: AccountAddress,
+        start_seq_num: u64,
+        order: Order,
+        limit: u64,
+        ledger_version: u64,
+        fetch_events: bool,
+    ) -> Result<Vec<TransactionWithProof>, Error> {
+        let mut seq_nums: Vec<_> = self
+            .all_txns
+            .iter()
+            .filter_map(|(txn, _)| txn.as_signed_user_txn().ok())
+            .filter(|txn| txn.sender() == address)
+            .map(|txn| txn.sequence_number())
+            .filter(|seq_num| match order {
+                Order::Ascending => *seq_num >= start_seq_num,
+                Order::Descending => *seq_num <= start_seq_num,
+            })
+            .collect();
+        seq_nums.sort_unstable();
+        if order == Order::Descending {
+            seq_nums.reverse();
+        }
+        seq_nums
+            .into_iter()
+            .take(limit as usize)
+            .map(|seq_num| {
+                self.get_txn_by_account(address, seq_num, ledger_version, fetch_events)
+                    .map(Option::unwrap)
+            })
+            .collect()
+    }
+
     fn get_state_proof_with_ledger_info(
         &self,
         _known_version: u64,
@@ -339,6 +545,10 @@ impl DbReader for MockDiemDB {
         unimplemented!()
     }
 
+    fn get_earliest_available_version(&self) -> Result<Version> {
+        Ok(0)
+    }
+
     fn get_block_timestamp(&self, version: u64) -> Result<u64> {
         Ok(match self.timestamps.get(version as usize) {
             Some(t) => *t,
@@ -346,11 +556,65 @@ impl DbReader for MockDiemDB {
         })
     }
 
+    fn get_block_metadata(&self, version: u64) -> Result<Option<(u64, BlockMetadata)>> {
+        Ok(self
+            .all_txns
+            .iter()
+            .take(version as usize + 1)
+            .enumerate()
+            .rev()
+            .find_map(|(v, (t, _))| match t {
+                Transaction::BlockMetadata(block_metadata) => {
+                    Some((v as u64, block_metadata.clone()))
+                }
+                _ => None,
+            }))
+    }
+
     fn get_accumulator_root_hash(&self, _version: Version) -> Result<HashValue> {
         Ok(HashValue::zero())
     }
 }
 
+// returns the currency of a payment event
+#[allow(unused)]
+pub fn payment_currency(event: &ContractEvent) -> Option<String> {
+    if let Ok(sent) = SentPaymentEvent::try_from(event) {
+        Some(sent.currency_code().to_string())
+    } else if let Ok(received) = ReceivedPaymentEvent::try_from(event) {
+        Some(received.currency_code().to_string())
+    } else {
+        None
+    }
+}
+
+// returns MockMempool holding the user transactions of `db`, ready and parked in turn
+#[allow(unused)]
+pub fn mock_mempool(db: &MockDiemDB) -> MockMempool {
+    let txns = db
+        .all_txns
+        .iter()
+        .filter_map(|(txn, _)| match txn {
+            Transaction::UserTransaction(txn) => Some(txn.clone()),
+            _ => None,
+        })
+        .enumerate()
+        .map(|(i, txn)| PendingTransaction {
+            txn,
+            status: if i % 2 == 0 {
+                PendingTransactionStatus::Ready
+            } else {
+                PendingTransactionStatus::Parked
+            },
+            insertion_time: Duration::from_secs(i as u64),
+        })
+        .collect();
+    MockMempool {
+        txns,
+        ..MockMempool::default()
+    }
+}
+
 // returns MockDiemDB for unit-testing
 #[allow(unused)]
 pub fn mock_db() -> MockDiemDB {
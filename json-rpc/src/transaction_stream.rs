@@ -0,0 +1,191 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! gRPC service streaming committed transactions, defined in `proto/transaction_stream.proto`.
+//!
+//! Each subscription reads storage in batches from its start version until it catches up with the
+//! latest committed version, then polls storage for new transactions. A slow client slows down
+//! its own subscription only: reading stops while the stream buffer is full.
+
+use anyhow::Result;
+use diem_logger::{debug, error};
+use diem_types::transaction::Version;
+use futures::{channel::mpsc, SinkExt};
+use proto::transaction_stream_server::{TransactionStream, TransactionStreamServer};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use storage_interface::DbReader;
+use tokio::runtime::Runtime;
+use tonic::{transport::Server, Request, Response, Status};
+
+#[allow(clippy::all)]
+mod proto {
+    tonic::include_proto!("diem.transaction_stream.v1");
+}
+
+pub use proto::{
+    transaction_stream_client::TransactionStreamClient, CommittedTransaction,
+    SubscribeTransactionsRequest,
+};
+
+// Number of transactions read from storage at once
+const BATCH_SIZE: u64 = 100;
+// Number of transactions buffered for each subscription
+const STREAM_BUFFER_SIZE: usize = 1000;
+// Interval at which a subscription that caught up checks for new transactions
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+type TransactionSender = mpsc::Sender<Result<CommittedTransaction, Status>>;
+
+struct TransactionStreamService {
+    db: Arc<dyn DbReader>,
+}
+
+#[tonic::async_trait]
+impl TransactionStream for TransactionStreamService {
+    type SubscribeTransactionsStream = mpsc::Receiver<Result<CommittedTransaction, Status>>;
+
+    async fn subscribe_transactions(
+        &self,
+        request: Request<SubscribeTransactionsRequest>,
+    ) -> Result<Response<Self::SubscribeTransactionsStream>, Status> {
+        let SubscribeTransactionsRequest {
+            start_version,
+            include_events,
+        } = request.into_inner();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER_SIZE);
+        tokio::spawn(stream_transactions(
+            self.db.clone(),
+            start_version,
+            include_events,
+            sender,
+        ));
+        Ok(Response::new(receiver))
+    }
+}
+
+/// Sends the transactions from `version` on to `sender`, until the subscriber goes away
+async fn stream_transactions(
+    db: Arc<dyn DbReader>,
+    mut version: Version,
+    include_events: bool,
+    mut sender: TransactionSender,
+) {
+    loop {
+        let batch = match read_batch(db.as_ref(), version, include_events) {
+            Ok(batch) => batch,
+            Err(e) => {
+                let _ = sender.send(Err(Status::internal(e.to_string()))).await;
+                return;
+            }
+        };
+        if batch.is_empty() {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            continue;
+        }
+        version += batch.len() as u64;
+        for txn in batch {
+            if sender.send(Ok(txn)).await.is_err() {
+                debug!("[json-rpc] transaction stream subscriber went away");
+                return;
+            }
+        }
+    }
+}
+
+/// Reads up to `BATCH_SIZE` committed transactions from `start_version` on
+fn read_batch(
+    db: &dyn DbReader,
+    start_version: Version,
+    include_events: bool,
+) -> Result<Vec<CommittedTransaction>> {
+    let latest_version = db.get_latest_version()?;
+    if start_version > latest_version {
+        return Ok(vec![]);
+    }
+    let txns = db.get_transactions(start_version, BATCH_SIZE, latest_version, include_events)?;
+    let events = txns.events.unwrap_or_default();
+    txns.transactions
+        .iter()
+        .zip(&txns.proof.transaction_infos)
+        .enumerate()
+        .map(|(i, (txn, info))| {
+            Ok(CommittedTransaction {
+                version: start_version + i as u64,
+                transaction: bcs::to_bytes(txn)?,
+                info: bcs::to_bytes(info)?,
+                events: match events.get(i) {
+                    Some(events) => bcs::to_bytes(events)?,
+                    None => vec![],
+                },
+            })
+        })
+        .collect()
+}
+
+/// Serves the transaction stream service at `address` on `runtime`
+pub fn bootstrap(runtime: &Runtime, address: SocketAddr, db: Arc<dyn DbReader>) {
+    let service = TransactionStreamServer::new(TransactionStreamService { db });
+    runtime.spawn(async move {
+        if let Err(e) = Server::builder().add_service(service).serve(address).await {
+            error!("[json-rpc] transaction stream service failed: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::utils::mock_db;
+    use diem_config::utils;
+    use diem_types::{
+        contract_event::ContractEvent,
+        transaction::{Transaction, TransactionInfo},
+    };
+
+    #[test]
+    fn test_subscribe_transactions() {
+        let mock_db = mock_db();
+        let runtime = Runtime::new().unwrap();
+        let address: SocketAddr = format!("127.0.0.1:{}", utils::get_available_port())
+            .parse()
+            .unwrap();
+        bootstrap(&runtime, address, Arc::new(mock_db.clone()));
+
+        runtime.block_on(async {
+            let mut client = loop {
+                match TransactionStreamClient::connect(format!("http://{}", address)).await {
+                    Ok(client) => break client,
+                    Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                }
+            };
+            let request = SubscribeTransactionsRequest {
+                start_version: 1,
+                include_events: true,
+            };
+            let mut stream = client
+                .subscribe_transactions(request)
+                .await
+                .unwrap()
+                .into_inner();
+
+            // the mock serves the versions it has transactions for
+            for version in 1..mock_db.all_txns.len() as u64 {
+                let txn = stream.message().await.unwrap().unwrap();
+                assert_eq!(txn.version, version);
+                let (expected, status) = &mock_db.all_txns[version as usize];
+                let transaction: Transaction = bcs::from_bytes(&txn.transaction).unwrap();
+                assert_eq!(&transaction, expected);
+                let info: TransactionInfo = bcs::from_bytes(&txn.info).unwrap();
+                assert_eq!(info.status(), status);
+                let events: Vec<ContractEvent> = bcs::from_bytes(&txn.events).unwrap();
+                let expected_events = mock_db
+                    .events
+                    .iter()
+                    .filter(|(v, _)| *v == version)
+                    .map(|(_, e)| e.clone())
+                    .collect::<Vec<_>>();
+                assert_eq!(events, expected_events);
+            }
+        });
+    }
+}
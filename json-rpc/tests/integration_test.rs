@@ -120,6 +120,8 @@ fn create_test_cases() -> Vec<Test> {
                 assert_eq!(metadata["module_publishing_allowed"], false);
                 assert_eq!(metadata["diem_version"], 3);
                 assert_eq!(metadata["dual_attestation_limit"], 1000000000);
+                // nothing is pruned in the test network
+                assert_eq!(metadata["earliest_available_version"], 0);
                 assert_ne!(diem_ledger_timestampusec, 0);
                 assert_ne!(diem_ledger_version, 0);
 
@@ -3,11 +3,12 @@
 
 use anyhow::Result;
 use diem_types::{
-    mempool_status::{MempoolStatus, MempoolStatusCode},
+    mempool_status::{MempoolCapacity, MempoolStatus, MempoolStatusCode},
     vm_status::{StatusCode, StatusType},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::time::Duration;
 
 use crate::Method;
 
@@ -51,6 +52,12 @@ pub enum ServerCode {
     MempoolInvalidUpdate = -32010,
     MempoolVmError = -32011,
     MempoolUnknownError = -32012,
+
+    // Client exceeded its rate limit
+    RateLimited = -32013,
+
+    // Transaction submitted by `submit_and_wait` can no longer commit
+    TransactionDiscarded = -32014,
 }
 
 /// JSON RPC server error codes for invalid request
@@ -62,16 +69,189 @@ pub enum InvalidRequestCode {
     InvalidFormat = -32604,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// What an error code is about, for clients to branch on
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// The request is malformed or its params are invalid, sending it again fails the same way
+    InvalidRequest,
+    /// The client exceeded its rate limit
+    RateLimit,
+    /// Mempool did not accept a submitted transaction
+    Mempool,
+    /// The VM rejected a submitted transaction
+    Vm,
+    /// A submitted transaction will not be committed
+    Transaction,
+    /// The server failed to serve the request
+    Internal,
+}
+
+/// An entry of the error code registry
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ErrorCode {
+    pub code: i16,
+    /// Stable snake case name of the code, also used as metrics label
+    pub name: &'static str,
+    pub category: ErrorCategory,
+    /// Whether sending the same request again later can succeed
+    pub retriable: bool,
+}
+
+impl ErrorCode {
+    const fn new(code: i16, name: &'static str, category: ErrorCategory, retriable: bool) -> Self {
+        Self {
+            code,
+            name,
+            category,
+            retriable,
+        }
+    }
+
+    /// Returns the registry entry of `code`, if it is one the server returns
+    pub fn of(code: i16) -> Option<&'static ErrorCode> {
+        ERROR_CODES
+            .iter()
+            .find(|error_code| error_code.code == code)
+    }
+}
+
+/// All the error codes the server returns. Codes are never reused for another meaning, so that
+/// clients can rely on them across server versions.
+pub static ERROR_CODES: &[ErrorCode] = &[
+    ErrorCode::new(
+        InvalidRequestCode::InvalidRequest as i16,
+        "invalid_request",
+        ErrorCategory::InvalidRequest,
+        false,
+    ),
+    ErrorCode::new(
+        InvalidRequestCode::MethodNotFound as i16,
+        "method_not_found",
+        ErrorCategory::InvalidRequest,
+        false,
+    ),
+    ErrorCode::new(
+        InvalidRequestCode::InvalidParams as i16,
+        "invalid_params",
+        ErrorCategory::InvalidRequest,
+        false,
+    ),
+    ErrorCode::new(
+        InvalidRequestCode::InvalidFormat as i16,
+        "invalid_format",
+        ErrorCategory::InvalidRequest,
+        false,
+    ),
+    ErrorCode::new(
+        ServerCode::DefaultServerError as i16,
+        "server_error",
+        ErrorCategory::Internal,
+        false,
+    ),
+    ErrorCode::new(
+        ServerCode::VmValidationError as i16,
+        "vm_validation_error",
+        ErrorCategory::Vm,
+        false,
+    ),
+    ErrorCode::new(
+        ServerCode::VmVerificationError as i16,
+        "vm_verification_error",
+        ErrorCategory::Vm,
+        false,
+    ),
+    ErrorCode::new(
+        ServerCode::VmInvariantViolationError as i16,
+        "vm_invariant_violation_error",
+        ErrorCategory::Vm,
+        false,
+    ),
+    ErrorCode::new(
+        ServerCode::VmDeserializationError as i16,
+        "vm_deserialization_error",
+        ErrorCategory::Vm,
+        false,
+    ),
+    ErrorCode::new(
+        ServerCode::VmExecutionError as i16,
+        "vm_execution_error",
+        ErrorCategory::Vm,
+        false,
+    ),
+    ErrorCode::new(
+        ServerCode::VmUnknownError as i16,
+        "vm_unknown_error",
+        ErrorCategory::Vm,
+        false,
+    ),
+    ErrorCode::new(
+        ServerCode::MempoolInvalidSeqNumber as i16,
+        "mempool_invalid_seq_number",
+        ErrorCategory::Mempool,
+        false,
+    ),
+    ErrorCode::new(
+        ServerCode::MempoolIsFull as i16,
+        "mempool_is_full",
+        ErrorCategory::Mempool,
+        true,
+    ),
+    ErrorCode::new(
+        ServerCode::MempoolTooManyTransactions as i16,
+        "mempool_too_many_transactions",
+        ErrorCategory::Mempool,
+        true,
+    ),
+    ErrorCode::new(
+        ServerCode::MempoolInvalidUpdate as i16,
+        "mempool_invalid_update",
+        ErrorCategory::Mempool,
+        false,
+    ),
+    ErrorCode::new(
+        ServerCode::MempoolVmError as i16,
+        "mempool_vm_error",
+        ErrorCategory::Mempool,
+        false,
+    ),
+    ErrorCode::new(
+        ServerCode::MempoolUnknownError as i16,
+        "mempool_unknown_error",
+        ErrorCategory::Mempool,
+        false,
+    ),
+    ErrorCode::new(
+        ServerCode::RateLimited as i16,
+        "rate_limited",
+        ErrorCategory::RateLimit,
+        true,
+    ),
+    ErrorCode::new(
+        ServerCode::TransactionDiscarded as i16,
+        "transaction_discarded",
+        ErrorCategory::Transaction,
+        false,
+    ),
+];
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum ErrorData {
     StatusCode(StatusCode),
+    /// Set on mempool errors of API v2 and later when mempool or the sender is out of room
+    MempoolCapacity(MempoolCapacity),
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct JsonRpcError {
     pub code: i16,
     pub message: String,
     pub data: Option<ErrorData>,
+    /// Set in responses of API v2 and later, see `classified`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<ErrorCategory>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retriable: Option<bool>,
 }
 
 impl std::error::Error for JsonRpcError {}
@@ -101,84 +281,135 @@ impl From<bcs::Error> for JsonRpcError {
 }
 
 impl JsonRpcError {
+    fn new(code: i16, message: String, data: Option<ErrorData>) -> Self {
+        Self {
+            code,
+            message,
+            data,
+            category: None,
+            retriable: None,
+        }
+    }
+
     pub fn serialize(self) -> Value {
         serde_json::to_value(self).unwrap_or(Value::Null)
     }
 
+    /// Returns the error with its category and retriable flag set from the registry, for clients
+    /// that do not know the code
+    pub fn classified(self) -> Self {
+        Self {
+            category: Some(self.category()),
+            retriable: Some(self.is_retriable()),
+            ..self
+        }
+    }
+
+    /// The category of the error: the one it carries, else the one registered for its code.
+    /// Unknown codes are internal errors.
+    pub fn category(&self) -> ErrorCategory {
+        self.category
+            .or_else(|| ErrorCode::of(self.code).map(|error_code| error_code.category))
+            .unwrap_or(ErrorCategory::Internal)
+    }
+
+    /// Whether sending the same request again later can succeed
+    pub fn is_retriable(&self) -> bool {
+        self.retriable
+            .or_else(|| ErrorCode::of(self.code).map(|error_code| error_code.retriable))
+            .unwrap_or(false)
+    }
+
     pub fn invalid_request() -> Self {
         Self::invalid_request_with_data(Option::None)
     }
 
     pub fn invalid_request_with_data(data: Option<ErrorData>) -> Self {
-        Self {
-            code: InvalidRequestCode::InvalidRequest as i16,
-            message: "Invalid Request".to_string(),
+        Self::new(
+            InvalidRequestCode::InvalidRequest as i16,
+            "Invalid Request".to_string(),
             data,
-        }
+        )
     }
 
     pub fn invalid_request_with_msg(msg: String) -> Self {
-        Self {
-            code: InvalidRequestCode::InvalidRequest as i16,
-            message: format!("Invalid Request: {}", msg),
-            data: None,
-        }
+        Self::new(
+            InvalidRequestCode::InvalidRequest as i16,
+            format!("Invalid Request: {}", msg),
+            None,
+        )
     }
 
     pub fn invalid_format() -> Self {
-        Self {
-            code: InvalidRequestCode::InvalidFormat as i16,
-            message: "Invalid request format".to_string(),
-            data: None,
-        }
+        Self::new(
+            InvalidRequestCode::InvalidFormat as i16,
+            "Invalid request format".to_string(),
+            None,
+        )
     }
 
     pub fn invalid_params(method: Method) -> Self {
-        Self {
-            code: InvalidRequestCode::InvalidParams as i16,
-            message: format!("Invalid params for method '{}'", method.as_str()),
-            data: None,
-        }
+        Self::new(
+            InvalidRequestCode::InvalidParams as i16,
+            format!("Invalid params for method '{}'", method.as_str()),
+            None,
+        )
     }
 
     pub fn invalid_params_size(msg: String) -> Self {
-        Self {
-            code: InvalidRequestCode::InvalidParams as i16,
-            message: format!("Invalid params: {}", msg),
-            data: None,
-        }
+        Self::new(
+            InvalidRequestCode::InvalidParams as i16,
+            format!("Invalid params: {}", msg),
+            None,
+        )
     }
 
     pub fn invalid_param(msg: &str) -> Self {
-        Self {
-            code: InvalidRequestCode::InvalidParams as i16,
-            message: format!("Invalid param {}", msg),
-            data: None,
-        }
+        Self::new(
+            InvalidRequestCode::InvalidParams as i16,
+            format!("Invalid param {}", msg),
+            None,
+        )
     }
 
     pub fn invalid_params_from_method(method: Method) -> Self {
-        Self {
-            code: InvalidRequestCode::InvalidParams as i16,
-            message: format!("Invalid params for method '{}'", method.as_str()),
-            data: None,
-        }
+        Self::new(
+            InvalidRequestCode::InvalidParams as i16,
+            format!("Invalid params for method '{}'", method.as_str()),
+            None,
+        )
     }
 
     pub fn method_not_found() -> Self {
-        Self {
-            code: InvalidRequestCode::MethodNotFound as i16,
-            message: "Method not found".to_string(),
-            data: None,
-        }
+        Self::new(
+            InvalidRequestCode::MethodNotFound as i16,
+            "Method not found".to_string(),
+            None,
+        )
+    }
+
+    pub fn rate_limited(retry_after_secs: Option<u64>) -> Self {
+        let message = match retry_after_secs {
+            Some(secs) => format!("Rate limit exceeded, retry after {} seconds", secs.max(1)),
+            None => "Rate limit exceeded, request has more calls than the limit allows".to_string(),
+        };
+        Self::new(ServerCode::RateLimited as i16, message, None)
+    }
+
+    pub fn transaction_discarded(reason: String) -> Self {
+        Self::new(
+            ServerCode::TransactionDiscarded as i16,
+            format!("Server error: Transaction discarded: {}", reason),
+            None,
+        )
     }
 
     pub fn internal_error(message: String) -> Self {
-        Self {
-            code: ServerCode::DefaultServerError as i16,
-            message: format!("Server error: {}", message),
-            data: None,
-        }
+        Self::new(
+            ServerCode::DefaultServerError as i16,
+            format!("Server error: {}", message),
+            None,
+        )
     }
 
     pub fn mempool_error(error: MempoolStatus) -> Result<Self> {
@@ -196,14 +427,14 @@ impl JsonRpcError {
             }
         };
 
-        Ok(Self {
-            code: code as i16,
-            message: format!(
+        Ok(Self::new(
+            code as i16,
+            format!(
                 "Server error: Mempool submission error: {:?}",
                 error.message
             ),
-            data: None,
-        })
+            error.capacity.map(ErrorData::MempoolCapacity),
+        ))
     }
 
     pub fn vm_status(error: StatusCode) -> Self {
@@ -218,11 +449,11 @@ impl JsonRpcError {
             StatusType::Unknown => ServerCode::VmUnknownError,
         };
 
-        Self {
-            code: code as i16,
-            message: format!("Server error: VM {} error: {:?}", vm_status_type, error),
-            data: Some(ErrorData::StatusCode(error)),
-        }
+        Self::new(
+            code as i16,
+            format!("Server error: VM {} error: {:?}", vm_status_type, error),
+            Some(ErrorData::StatusCode(error)),
+        )
     }
 
     pub fn as_status_code(&self) -> Option<StatusCode> {
@@ -231,15 +462,35 @@ impl JsonRpcError {
         }
         None
     }
+
+    pub fn as_mempool_capacity(&self) -> Option<&MempoolCapacity> {
+        if let Some(ErrorData::MempoolCapacity(data)) = &self.data {
+            return Some(data);
+        }
+        None
+    }
+
+    /// How long the server suggests to wait before sending the request again
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.as_mempool_capacity()
+            .map(|capacity| capacity.retry_after())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::errors::{is_internal_error, JsonRpcError, ServerCode, INTERNAL_ERRORS};
+    use crate::{
+        errors::{
+            is_internal_error, ErrorCategory, ErrorCode, JsonRpcError, ServerCode, ERROR_CODES,
+            INTERNAL_ERRORS,
+        },
+        Method,
+    };
     use diem_types::{
-        mempool_status::{MempoolStatus, MempoolStatusCode},
+        mempool_status::{MempoolCapacity, MempoolStatus, MempoolStatusCode},
         vm_status::StatusCode,
     };
+    use std::time::Duration;
 
     #[test]
     fn test_vm_status() {
@@ -278,10 +529,9 @@ mod tests {
 
     #[test]
     fn test_mempool_error() {
-        let err = JsonRpcError::mempool_error(MempoolStatus {
-            code: MempoolStatusCode::Accepted,
-            message: "error msg".to_string(),
-        });
+        let err = JsonRpcError::mempool_error(
+            MempoolStatus::new(MempoolStatusCode::Accepted).with_message("error msg".to_string()),
+        );
         assert!(err.is_err());
 
         assert_map_code(
@@ -304,6 +554,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mempool_capacity() {
+        let capacity = MempoolCapacity {
+            size: 10,
+            capacity: 10,
+            size_bytes: 1_000,
+            capacity_bytes: 2_000,
+            sender_size: 1,
+            capacity_per_sender: 100,
+            retry_after_ms: 1_500,
+        };
+        let err = JsonRpcError::mempool_error(
+            MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_capacity(capacity),
+        )
+        .unwrap();
+        assert_eq!(err.as_mempool_capacity(), Some(&capacity));
+        assert_eq!(err.retry_after(), Some(Duration::from_millis(1_500)));
+        assert!(err.as_status_code().is_none());
+
+        let value = err.classified().serialize();
+        assert_eq!(value["data"]["MempoolCapacity"]["capacity_per_sender"], 100);
+        let err: JsonRpcError = serde_json::from_value(value).unwrap();
+        assert_eq!(err.as_mempool_capacity(), Some(&capacity));
+
+        let err =
+            JsonRpcError::mempool_error(MempoolStatus::new(MempoolStatusCode::InvalidSeqNumber))
+                .unwrap();
+        assert!(err.data.is_none());
+        assert!(err.retry_after().is_none());
+    }
+
     #[test]
     fn test_is_internal_error() {
         for code in INTERNAL_ERRORS {
@@ -315,11 +596,80 @@ mod tests {
         assert!(!is_internal_error(&(ServerCode::VmValidationError as i16)));
     }
 
+    #[test]
+    fn test_error_code_registry() {
+        for (i, error_code) in ERROR_CODES.iter().enumerate() {
+            for other in &ERROR_CODES[..i] {
+                assert_ne!(other.code, error_code.code);
+                assert_ne!(other.name, error_code.name);
+            }
+        }
+
+        // Every error the server returns is registered
+        let mut errors = vec![
+            JsonRpcError::invalid_request(),
+            JsonRpcError::invalid_format(),
+            JsonRpcError::invalid_params(Method::GetAccount),
+            JsonRpcError::method_not_found(),
+            JsonRpcError::rate_limited(None),
+            JsonRpcError::transaction_discarded("expired".to_string()),
+            JsonRpcError::internal_error("error msg".to_string()),
+        ];
+        for status_code in &[
+            StatusCode::UNKNOWN_VALIDATION_STATUS,
+            StatusCode::UNKNOWN_VERIFICATION_ERROR,
+            StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR,
+            StatusCode::UNKNOWN_BINARY_ERROR,
+            StatusCode::UNKNOWN_RUNTIME_STATUS,
+            StatusCode::UNKNOWN_STATUS,
+        ] {
+            errors.push(JsonRpcError::vm_status(*status_code));
+        }
+        for code in &[
+            MempoolStatusCode::InvalidSeqNumber,
+            MempoolStatusCode::MempoolIsFull,
+            MempoolStatusCode::TooManyTransactions,
+            MempoolStatusCode::InvalidUpdate,
+            MempoolStatusCode::VmError,
+            MempoolStatusCode::UnknownStatus,
+        ] {
+            let status = MempoolStatus::new(*code);
+            errors.push(JsonRpcError::mempool_error(status).unwrap());
+        }
+        for error in errors {
+            assert!(ErrorCode::of(error.code).is_some(), "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_classified() {
+        let error = JsonRpcError::rate_limited(Some(1));
+        assert_eq!(error.category(), ErrorCategory::RateLimit);
+        assert!(error.is_retriable());
+
+        // Only classified errors carry their category and retriable flag
+        let value = error.clone().serialize();
+        assert!(value.get("category").is_none());
+        assert!(value.get("retriable").is_none());
+        let value = error.classified().serialize();
+        assert_eq!(value["category"], "rate_limit");
+        assert_eq!(value["retriable"], true);
+
+        let error = JsonRpcError::invalid_params(Method::GetAccount).classified();
+        assert_eq!(error.category, Some(ErrorCategory::InvalidRequest));
+        assert_eq!(error.retriable, Some(false));
+
+        // Codes unknown to the client are internal errors
+        let mut error = JsonRpcError::internal_error("error msg".to_string());
+        error.code = -32099;
+        assert_eq!(error.category(), ErrorCategory::Internal);
+        assert!(!error.is_retriable());
+    }
+
     fn assert_map_code(from: MempoolStatusCode, to: ServerCode) {
-        let err = JsonRpcError::mempool_error(MempoolStatus {
-            code: from,
-            message: "error msg".to_string(),
-        })
+        let err = JsonRpcError::mempool_error(
+            MempoolStatus::new(from).with_message("error msg".to_string()),
+        )
         .unwrap();
         assert_eq!(err.code, to as i16);
     }
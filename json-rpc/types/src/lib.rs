@@ -7,6 +7,7 @@ pub mod response;
 pub mod views;
 
 use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 enum JsonRpcVersion {
@@ -24,18 +25,72 @@ pub enum Id {
     String(Box<str>),
 }
 
+/// Versions of the API, each served under its own path: `/v1`, `/v2`... Requests to `/` are served
+/// as `/v1`. A version keeps its response schemas; breaking changes go to a new version, so that
+/// clients move to it when they are ready.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    /// All the versions served, oldest first
+    pub const ALL: &'static [ApiVersion] = &[ApiVersion::V1, ApiVersion::V2];
+    pub const LATEST: ApiVersion = ApiVersion::V2;
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "v1",
+            ApiVersion::V2 => "v2",
+        }
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ApiVersion {
+    type Err = UnknownApiVersion;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ApiVersion::ALL
+            .iter()
+            .find(|version| version.as_str() == s)
+            .copied()
+            .ok_or(UnknownApiVersion)
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownApiVersion;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Method {
     Submit,
+    SubmitAndWait,
     GetMetadata,
     GetAccount,
+    GetAccounts,
+    GetResource,
     GetTransactions,
     GetAccountTransaction,
     GetAccountTransactions,
     GetEvents,
+    GetEventsByType,
+    GetEventsByAccount,
+    GetEventsByCurrency,
+    GetBlockMetadata,
     GetCurrencies,
     GetNetworkStatus,
+    GetPendingTransactions,
+    GetRemovedTransaction,
+    GetApiVersions,
 
     //
     // Experimental APIs
@@ -52,17 +107,57 @@ pub enum Method {
 }
 
 impl Method {
+    /// All the methods served
+    pub const ALL: &'static [Method] = &[
+        Method::Submit,
+        Method::SubmitAndWait,
+        Method::GetMetadata,
+        Method::GetAccount,
+        Method::GetAccounts,
+        Method::GetResource,
+        Method::GetTransactions,
+        Method::GetAccountTransaction,
+        Method::GetAccountTransactions,
+        Method::GetEvents,
+        Method::GetEventsByType,
+        Method::GetEventsByAccount,
+        Method::GetEventsByCurrency,
+        Method::GetBlockMetadata,
+        Method::GetCurrencies,
+        Method::GetNetworkStatus,
+        Method::GetPendingTransactions,
+        Method::GetRemovedTransaction,
+        Method::GetApiVersions,
+        Method::GetStateProof,
+        Method::GetAccountStateWithProof,
+        Method::GetTransactionsWithProofs,
+        Method::GetEventsWithProofs,
+        Method::GetTowerStateView,
+        Method::GetOracleUpgradeStateView,
+        Method::GetWaypointView,
+    ];
+
     pub fn as_str(&self) -> &str {
         match self {
             Method::Submit => "submit",
+            Method::SubmitAndWait => "submit_and_wait",
             Method::GetMetadata => "get_metadata",
             Method::GetAccount => "get_account",
+            Method::GetAccounts => "get_accounts",
+            Method::GetResource => "get_resource",
             Method::GetTransactions => "get_transactions",
             Method::GetAccountTransaction => "get_account_transaction",
             Method::GetAccountTransactions => "get_account_transactions",
             Method::GetEvents => "get_events",
+            Method::GetEventsByType => "get_events_by_type",
+            Method::GetEventsByAccount => "get_events_by_account",
+            Method::GetEventsByCurrency => "get_events_by_currency",
+            Method::GetBlockMetadata => "get_block_metadata",
             Method::GetCurrencies => "get_currencies",
             Method::GetNetworkStatus => "get_network_status",
+            Method::GetPendingTransactions => "get_pending_transactions",
+            Method::GetRemovedTransaction => "get_removed_transaction",
+            Method::GetApiVersions => "get_api_versions",
             Method::GetStateProof => "get_state_proof",
             Method::GetAccountStateWithProof => "get_account_state_with_proof",
             Method::GetTransactionsWithProofs => "get_transactions_with_proofs",
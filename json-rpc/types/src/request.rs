@@ -64,14 +64,24 @@ impl JsonRpcRequest {
 #[serde(tag = "method", content = "params")]
 pub enum MethodRequest {
     Submit(SubmitParams),
+    SubmitAndWait(SubmitAndWaitParams),
     GetMetadata(GetMetadataParams),
     GetAccount(GetAccountParams),
+    GetAccounts(GetAccountsParams),
+    GetResource(GetResourceParams),
     GetTransactions(GetTransactionsParams),
     GetAccountTransaction(GetAccountTransactionParams),
     GetAccountTransactions(GetAccountTransactionsParams),
     GetEvents(GetEventsParams),
+    GetEventsByType(GetEventsByTypeParams),
+    GetEventsByAccount(GetEventsByAccountParams),
+    GetEventsByCurrency(GetEventsByCurrencyParams),
+    GetBlockMetadata(GetBlockMetadataParams),
     GetCurrencies(GetCurrenciesParams),
     GetNetworkStatus(GetNetworkStatusParams),
+    GetApiVersions(GetApiVersionsParams),
+    GetPendingTransactions(GetPendingTransactionsParams),
+    GetRemovedTransaction(GetRemovedTransactionParams),
 
     //
     // Experimental APIs
@@ -91,8 +101,11 @@ impl MethodRequest {
     pub fn from_value(method: Method, value: serde_json::Value) -> Result<Self, serde_json::Error> {
         let method_request = match method {
             Method::Submit => MethodRequest::Submit(serde_json::from_value(value)?),
+            Method::SubmitAndWait => MethodRequest::SubmitAndWait(serde_json::from_value(value)?),
             Method::GetMetadata => MethodRequest::GetMetadata(serde_json::from_value(value)?),
             Method::GetAccount => MethodRequest::GetAccount(serde_json::from_value(value)?),
+            Method::GetAccounts => MethodRequest::GetAccounts(serde_json::from_value(value)?),
+            Method::GetResource => MethodRequest::GetResource(serde_json::from_value(value)?),
             Method::GetTransactions => {
                 MethodRequest::GetTransactions(serde_json::from_value(value)?)
             }
@@ -103,10 +116,29 @@ impl MethodRequest {
                 MethodRequest::GetAccountTransactions(serde_json::from_value(value)?)
             }
             Method::GetEvents => MethodRequest::GetEvents(serde_json::from_value(value)?),
+            Method::GetEventsByType => {
+                MethodRequest::GetEventsByType(serde_json::from_value(value)?)
+            }
+            Method::GetEventsByAccount => {
+                MethodRequest::GetEventsByAccount(serde_json::from_value(value)?)
+            }
+            Method::GetEventsByCurrency => {
+                MethodRequest::GetEventsByCurrency(serde_json::from_value(value)?)
+            }
+            Method::GetBlockMetadata => {
+                MethodRequest::GetBlockMetadata(serde_json::from_value(value)?)
+            }
             Method::GetCurrencies => MethodRequest::GetCurrencies(serde_json::from_value(value)?),
             Method::GetNetworkStatus => {
                 MethodRequest::GetNetworkStatus(serde_json::from_value(value)?)
             }
+            Method::GetApiVersions => MethodRequest::GetApiVersions(serde_json::from_value(value)?),
+            Method::GetPendingTransactions => {
+                MethodRequest::GetPendingTransactions(serde_json::from_value(value)?)
+            }
+            Method::GetRemovedTransaction => {
+                MethodRequest::GetRemovedTransaction(serde_json::from_value(value)?)
+            }
             Method::GetStateProof => MethodRequest::GetStateProof(serde_json::from_value(value)?),
             Method::GetAccountStateWithProof => {
                 MethodRequest::GetAccountStateWithProof(serde_json::from_value(value)?)
@@ -141,14 +173,24 @@ impl MethodRequest {
     pub fn method(&self) -> Method {
         match self {
             MethodRequest::Submit(_) => Method::Submit,
+            MethodRequest::SubmitAndWait(_) => Method::SubmitAndWait,
             MethodRequest::GetMetadata(_) => Method::GetMetadata,
             MethodRequest::GetAccount(_) => Method::GetAccount,
+            MethodRequest::GetAccounts(_) => Method::GetAccounts,
+            MethodRequest::GetResource(_) => Method::GetResource,
             MethodRequest::GetTransactions(_) => Method::GetTransactions,
             MethodRequest::GetAccountTransaction(_) => Method::GetAccountTransaction,
             MethodRequest::GetAccountTransactions(_) => Method::GetAccountTransactions,
             MethodRequest::GetEvents(_) => Method::GetEvents,
+            MethodRequest::GetEventsByType(_) => Method::GetEventsByType,
+            MethodRequest::GetEventsByAccount(_) => Method::GetEventsByAccount,
+            MethodRequest::GetEventsByCurrency(_) => Method::GetEventsByCurrency,
+            MethodRequest::GetBlockMetadata(_) => Method::GetBlockMetadata,
             MethodRequest::GetCurrencies(_) => Method::GetCurrencies,
             MethodRequest::GetNetworkStatus(_) => Method::GetNetworkStatus,
+            MethodRequest::GetApiVersions(_) => Method::GetApiVersions,
+            MethodRequest::GetPendingTransactions(_) => Method::GetPendingTransactions,
+            MethodRequest::GetRemovedTransaction(_) => Method::GetRemovedTransaction,
             MethodRequest::GetStateProof(_) => Method::GetStateProof,
             MethodRequest::GetAccountStateWithProof(_) => Method::GetAccountStateWithProof,
             MethodRequest::GetTransactionsWithProofs(_) => Method::GetTransactionsWithProofs,
@@ -168,6 +210,15 @@ pub struct SubmitParams {
     pub data: SignedTransaction,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SubmitAndWaitParams {
+    #[serde(serialize_with = "serialize_signed_transaction")]
+    #[serde(deserialize_with = "deserialize_signed_transaction")]
+    pub data: SignedTransaction,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
 fn serialize_signed_transaction<S>(
     txn: &SignedTransaction,
     serializer: S,
@@ -224,6 +275,21 @@ pub struct GetAccountParams {
     pub version: Option<u64>,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetAccountsParams {
+    pub accounts: Vec<AccountAddress>,
+    #[serde(default)]
+    pub version: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetResourceParams {
+    pub account: AccountAddress,
+    pub struct_tag: String,
+    #[serde(default)]
+    pub version: Option<u64>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct GetTransactionsParams {
     pub start_version: u64,
@@ -244,6 +310,8 @@ pub struct GetAccountTransactionsParams {
     pub start: u64,
     pub limit: u64,
     pub include_events: bool,
+    #[serde(default)]
+    pub descending: bool,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -251,6 +319,38 @@ pub struct GetEventsParams {
     pub key: EventKey,
     pub start: u64,
     pub limit: u64,
+    #[serde(default)]
+    pub type_tag: Option<String>,
+    #[serde(default)]
+    pub start_version: Option<u64>,
+    #[serde(default)]
+    pub end_version: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetEventsByTypeParams {
+    pub type_tag: String,
+    pub start_version: u64,
+    pub limit: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetEventsByAccountParams {
+    pub account: AccountAddress,
+    pub start_version: u64,
+    pub limit: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetEventsByCurrencyParams {
+    pub currency: String,
+    pub start_version: u64,
+    pub limit: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetBlockMetadataParams {
+    pub version: u64,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -281,6 +381,31 @@ impl<'de> Deserialize<'de> for GetNetworkStatusParams {
     }
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct GetApiVersionsParams;
+
+impl<'de> Deserialize<'de> for GetApiVersionsParams {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_option(NoParamsVisitor("get_api_versions params"))
+            .map(|_| GetApiVersionsParams)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetPendingTransactionsParams {
+    pub account: AccountAddress,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetRemovedTransactionParams {
+    pub account: AccountAddress,
+    pub sequence_number: u64,
+}
+
 /// A de::Visitor implementation for jsonrpc param structs without any parameters
 struct NoParamsVisitor(&'static str);
 impl<'de> de::Visitor<'de> for NoParamsVisitor {
@@ -345,6 +470,8 @@ pub struct GetTransactionsWithProofsParams {
     pub start_version: u64,
     pub limit: u64,
     pub include_events: bool,
+    #[serde(default)]
+    pub ledger_version: Option<u64>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -467,6 +594,85 @@ mod test {
         serde_json::from_value::<GetAccountParams>(value).unwrap();
     }
 
+    #[test]
+    fn get_accounts() {
+        let account = "1668f6be25668c1a17cd8caf6b8d2f25";
+
+        // Array without optional param
+        let value = serde_json::json!([[account, account]]);
+        let params = serde_json::from_value::<GetAccountsParams>(value).unwrap();
+        assert_eq!(params.accounts.len(), 2);
+
+        // Array with optional param
+        let value = serde_json::json!([[account], 10]);
+        serde_json::from_value::<GetAccountsParams>(value).unwrap();
+
+        // Array with an empty list of accounts
+        let value = serde_json::json!([[]]);
+        serde_json::from_value::<GetAccountsParams>(value).unwrap();
+
+        // Array with a single account instead of a list
+        let value = serde_json::json!([account]);
+        serde_json::from_value::<GetAccountsParams>(value).unwrap_err();
+
+        // Array with a wrong account
+        let value = serde_json::json!([[account, "foo"]]);
+        serde_json::from_value::<GetAccountsParams>(value).unwrap_err();
+
+        // Array with too many params
+        let value = serde_json::json!([[account], 10, 1]);
+        serde_json::from_value::<GetAccountsParams>(value).unwrap_err();
+
+        // Empty array without required params should fail
+        let value = serde_json::json!([]);
+        serde_json::from_value::<GetAccountsParams>(value).unwrap_err();
+
+        // Object params
+        let value = serde_json::json!({
+            "accounts": [account],
+            "version": 10,
+        });
+        serde_json::from_value::<GetAccountsParams>(value).unwrap();
+
+        // Object without required params should fail
+        let value = serde_json::json!({
+            "version": 10,
+        });
+        serde_json::from_value::<GetAccountsParams>(value).unwrap_err();
+    }
+
+    #[test]
+    fn get_resource() {
+        let account = "1668f6be25668c1a17cd8caf6b8d2f25";
+        let struct_tag = "0x1::DiemAccount::Balance<0x1::XUS::XUS>";
+
+        // Array without optional param
+        let value = serde_json::json!([account, struct_tag]);
+        let params = serde_json::from_value::<GetResourceParams>(value).unwrap();
+        assert_eq!(params.struct_tag, struct_tag);
+        assert_eq!(params.version, None);
+
+        // Array with optional param
+        let value = serde_json::json!([account, struct_tag, 10]);
+        let params = serde_json::from_value::<GetResourceParams>(value).unwrap();
+        assert_eq!(params.version, Some(10));
+
+        // Array without the struct tag
+        let value = serde_json::json!([account]);
+        serde_json::from_value::<GetResourceParams>(value).unwrap_err();
+
+        // Array with too many params
+        let value = serde_json::json!([account, struct_tag, 10, 11]);
+        serde_json::from_value::<GetResourceParams>(value).unwrap_err();
+
+        // Object params
+        let value = serde_json::json!({
+            "account": account,
+            "struct_tag": struct_tag,
+        });
+        serde_json::from_value::<GetResourceParams>(value).unwrap();
+    }
+
     #[test]
     fn get_transactions() {
         // Array with all params
@@ -579,9 +785,16 @@ mod test {
         let value = serde_json::json!([account, 10, 11, false]);
         serde_json::from_value::<GetAccountTransactionsParams>(value).unwrap();
 
+        // Array with optional param
+        let value = serde_json::json!([account, 10, 11, false, true]);
+        let params = serde_json::from_value::<GetAccountTransactionsParams>(value).unwrap();
+        assert!(params.descending);
+
         // Array with too many params
         let value = serde_json::json!([account, 10, 11, false, "foo"]);
         serde_json::from_value::<GetAccountTransactionsParams>(value).unwrap_err();
+        let value = serde_json::json!([account, 10, 11, false, true, "foo"]);
+        serde_json::from_value::<GetAccountTransactionsParams>(value).unwrap_err();
 
         // Array with wrong param
         let value = serde_json::json!(["foo", 10, 11, false]);
@@ -633,7 +846,17 @@ mod test {
         let value = serde_json::json!([key, 10, 11]);
         serde_json::from_value::<GetEventsParams>(value).unwrap();
 
+        // Array with optional params
+        let value = serde_json::json!([key, 10, 11, "0x1::DiemAccount::SentPaymentEvent", 5, 20]);
+        let params = serde_json::from_value::<GetEventsParams>(value).unwrap();
+        assert_eq!(params.start_version, Some(5));
+        assert_eq!(params.end_version, Some(20));
+
         // Array with too many params
+        let value = serde_json::json!([key, 10, 11, null, 5, 20, false]);
+        serde_json::from_value::<GetEventsParams>(value).unwrap_err();
+
+        // Array with wrong optional param
         let value = serde_json::json!([key, 10, 11, false]);
         serde_json::from_value::<GetEventsParams>(value).unwrap_err();
 
@@ -659,7 +882,21 @@ mod test {
             "start": 10,
             "limit": 11,
         });
-        serde_json::from_value::<GetEventsParams>(value).unwrap();
+        let params = serde_json::from_value::<GetEventsParams>(value).unwrap();
+        assert!(params.type_tag.is_none());
+        assert!(params.start_version.is_none());
+        assert!(params.end_version.is_none());
+
+        // Object with some optional params
+        let value = serde_json::json!({
+            "key": key,
+            "start": 10,
+            "limit": 11,
+            "end_version": 20,
+        });
+        let params = serde_json::from_value::<GetEventsParams>(value).unwrap();
+        assert!(params.start_version.is_none());
+        assert_eq!(params.end_version, Some(20));
 
         // Object without all params
         let value = serde_json::json!({
@@ -678,6 +915,114 @@ mod test {
         serde_json::from_value::<GetEventsParams>(value).unwrap();
     }
 
+    #[test]
+    fn get_events_by_type() {
+        let type_tag = "0x1::Diem::BurnEvent";
+
+        // Array with all params
+        let value = serde_json::json!([type_tag, 10, 11]);
+        serde_json::from_value::<GetEventsByTypeParams>(value).unwrap();
+
+        // Array with too many params
+        let value = serde_json::json!([type_tag, 10, 11, 12]);
+        serde_json::from_value::<GetEventsByTypeParams>(value).unwrap_err();
+
+        // Array with wrong param
+        let value = serde_json::json!([10, 11, 12]);
+        serde_json::from_value::<GetEventsByTypeParams>(value).unwrap_err();
+
+        // Array with too few params
+        let value = serde_json::json!([type_tag, 10]);
+        serde_json::from_value::<GetEventsByTypeParams>(value).unwrap_err();
+
+        // Object params
+        let value = serde_json::json!({
+            "type_tag": type_tag,
+            "start_version": 10,
+            "limit": 11,
+        });
+        serde_json::from_value::<GetEventsByTypeParams>(value).unwrap();
+
+        // Object without all params
+        let value = serde_json::json!({
+            "start_version": 10,
+            "limit": 11,
+        });
+        serde_json::from_value::<GetEventsByTypeParams>(value).unwrap_err();
+    }
+
+    #[test]
+    fn get_events_by_account() {
+        let account = "00000000000000000000000000000001";
+
+        // Array with all params
+        let value = serde_json::json!([account, 10, 11]);
+        serde_json::from_value::<GetEventsByAccountParams>(value).unwrap();
+
+        // Array with wrong param
+        let value = serde_json::json!(["not an account", 10, 11]);
+        serde_json::from_value::<GetEventsByAccountParams>(value).unwrap_err();
+
+        // Array with too few params
+        let value = serde_json::json!([account, 10]);
+        serde_json::from_value::<GetEventsByAccountParams>(value).unwrap_err();
+
+        // Object params
+        let value = serde_json::json!({
+            "account": account,
+            "start_version": 10,
+            "limit": 11,
+        });
+        serde_json::from_value::<GetEventsByAccountParams>(value).unwrap();
+    }
+
+    #[test]
+    fn get_events_by_currency() {
+        // Array with all params
+        let value = serde_json::json!(["XUS", 10, 11]);
+        serde_json::from_value::<GetEventsByCurrencyParams>(value).unwrap();
+
+        // Array with too many params
+        let value = serde_json::json!(["XUS", 10, 11, 12]);
+        serde_json::from_value::<GetEventsByCurrencyParams>(value).unwrap_err();
+
+        // Object params
+        let value = serde_json::json!({
+            "currency": "XUS",
+            "start_version": 10,
+            "limit": 11,
+        });
+        serde_json::from_value::<GetEventsByCurrencyParams>(value).unwrap();
+
+        // Object without all params
+        let value = serde_json::json!({
+            "currency": "XUS",
+            "limit": 11,
+        });
+        serde_json::from_value::<GetEventsByCurrencyParams>(value).unwrap_err();
+    }
+
+    #[test]
+    fn get_block_metadata() {
+        // Array with all params
+        let value = serde_json::json!([11]);
+        serde_json::from_value::<GetBlockMetadataParams>(value).unwrap();
+
+        // Array with wrong param
+        let value = serde_json::json!(["foo"]);
+        serde_json::from_value::<GetBlockMetadataParams>(value).unwrap_err();
+
+        // Empty array without required params should fail
+        let value = serde_json::json!([]);
+        serde_json::from_value::<GetBlockMetadataParams>(value).unwrap_err();
+
+        // Object params
+        let value = serde_json::json!({
+            "version": 10,
+        });
+        serde_json::from_value::<GetBlockMetadataParams>(value).unwrap();
+    }
+
     #[test]
     fn get_currencies() {
         let value = serde_json::json!([10]);
@@ -722,6 +1067,64 @@ mod test {
         serde_json::from_value::<JsonRpcRequest>(value).unwrap();
     }
 
+    #[test]
+    fn get_api_versions() {
+        let value = serde_json::json!([10]);
+        serde_json::from_value::<GetApiVersionsParams>(value).unwrap_err();
+
+        let value = serde_json::json!([]);
+        serde_json::from_value::<GetApiVersionsParams>(value).unwrap();
+
+        let value = serde_json::json! {{
+            "jsonrpc": "2.0",
+            "method": Method::GetApiVersions,
+            "id": 1,
+        }};
+        serde_json::from_value::<JsonRpcRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn get_pending_transactions() {
+        let account = "1668f6be25668c1a17cd8caf6b8d2f25";
+
+        let value = serde_json::json!([account]);
+        serde_json::from_value::<GetPendingTransactionsParams>(value).unwrap();
+
+        let value = serde_json::json!({ "account": account });
+        serde_json::from_value::<GetPendingTransactionsParams>(value).unwrap();
+
+        // Array with a wrong account
+        let value = serde_json::json!(["foo"]);
+        serde_json::from_value::<GetPendingTransactionsParams>(value).unwrap_err();
+
+        // Array with too many params
+        let value = serde_json::json!([account, 10]);
+        serde_json::from_value::<GetPendingTransactionsParams>(value).unwrap_err();
+
+        // Empty array without required params should fail
+        let value = serde_json::json!([]);
+        serde_json::from_value::<GetPendingTransactionsParams>(value).unwrap_err();
+    }
+
+    #[test]
+    fn get_removed_transaction() {
+        let account = "1668f6be25668c1a17cd8caf6b8d2f25";
+
+        let value = serde_json::json!([account, 3]);
+        serde_json::from_value::<GetRemovedTransactionParams>(value).unwrap();
+
+        let value = serde_json::json!({ "account": account, "sequence_number": 3 });
+        serde_json::from_value::<GetRemovedTransactionParams>(value).unwrap();
+
+        // Array with a wrong sequence number
+        let value = serde_json::json!([account, "foo"]);
+        serde_json::from_value::<GetRemovedTransactionParams>(value).unwrap_err();
+
+        // Array without the sequence number
+        let value = serde_json::json!([account]);
+        serde_json::from_value::<GetRemovedTransactionParams>(value).unwrap_err();
+    }
+
     #[test]
     fn get_state_proof() {
         // Array with all params
@@ -821,10 +1224,17 @@ mod test {
     #[test]
     fn get_transactions_with_proofs() {
         // Array with all params
+        let value = serde_json::json!([10, 11, true, 12]);
+        serde_json::from_value::<GetTransactionsWithProofsParams>(value).unwrap();
+
+        // Array without optional params
         let value = serde_json::json!([10, 11, true]);
         serde_json::from_value::<GetTransactionsWithProofsParams>(value).unwrap();
 
         // Array with too many params
+        let value = serde_json::json!([10, 11, true, 12, 13]);
+        serde_json::from_value::<GetTransactionsWithProofsParams>(value).unwrap_err();
+
         let value = serde_json::json!([10, 11, 42, false]);
         serde_json::from_value::<GetTransactionsWithProofsParams>(value).unwrap_err();
 
@@ -852,6 +1262,14 @@ mod test {
         });
         serde_json::from_value::<GetTransactionsWithProofsParams>(value).unwrap();
 
+        let value = serde_json::json!({
+            "start_version": 10,
+            "limit": 11,
+            "include_events": true,
+            "ledger_version": 12,
+        });
+        serde_json::from_value::<GetTransactionsWithProofsParams>(value).unwrap();
+
         // Object with more params
         let value = serde_json::json!({
             "start_version": 10,
@@ -1,6 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::{errors::JsonRpcError, ApiVersion, Method};
 use anyhow::{Error, Result, bail, ensure, format_err};
 use diem_crypto::hash::{CryptoHash, HashValue};
 use diem_transaction_builder::{error_explain, stdlib::ScriptCall};
@@ -10,12 +11,12 @@ use diem_types::{account_config::{
         CurrencyInfoResource, DesignatedDealerPreburns, DiemIdDomainEvent, FreezingBit, MintEvent,
         NewBlockEvent, NewEpochEvent, PreburnEvent, ReceivedMintEvent, ReceivedPaymentEvent,
         SentPaymentEvent, ToXDXExchangeRateUpdateEvent,
-    }, account_state::AccountState, account_state_blob::{AccountStateBlob, AccountStateWithProof}, contract_event::{ContractEvent, EventWithProof}, diem_id_identifier::DiemIdVaspDomainIdentifier, epoch_change::EpochChangeProof, event::EventKey, ledger_info::LedgerInfoWithSignatures, ol_oracle_upgrade_state::{OracleResource, UpgradeOracle}, proof::{
+    }, account_state::AccountState, account_state_blob::{AccountStateBlob, AccountStateWithProof}, block_metadata::BlockMetadata, contract_event::{ContractEvent, EventWithProof}, diem_id_identifier::DiemIdVaspDomainIdentifier, epoch_change::EpochChangeProof, event::EventKey, ledger_info::LedgerInfoWithSignatures, ol_oracle_upgrade_state::{OracleResource, UpgradeOracle}, proof::{
         AccountStateProof, AccumulatorConsistencyProof, SparseMerkleProof,
         TransactionAccumulatorProof, TransactionInfoWithProof, TransactionListProof,
     }, transaction::{
-        Script, ScriptFunction, Transaction, TransactionArgument, TransactionInfo,
-        TransactionListWithProof, TransactionPayload,
+        Script, ScriptFunction, SignedTransaction, Transaction, TransactionArgument,
+        TransactionInfo, TransactionListWithProof, TransactionPayload,
     }, vm_status::KeptVMStatus, waypoint::Waypoint};
 use hex::FromHex;
 use move_core_types::{
@@ -166,6 +167,27 @@ pub struct AccountView {
     pub version: Option<u64>,
 }
 
+/// An entry of the `get_accounts` result: the account at `address`, or why it could not be read.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AccountEntryView {
+    pub address: AccountAddress,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<AccountView>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+}
+
+/// A Move resource of an account, with its fields annotated by their names.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct MoveResourceView {
+    /// The struct tag of the resource, e.g. `0x1::DiemAccount::Balance<0x1::XUS::XUS>`
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// JSON object mapping each field name to its value: `u128` values and byte vectors are
+    /// rendered as strings, nested structs as objects
+    pub value: serde_json::Value,
+}
+
 impl AccountView {
     pub fn new(
         address: AccountAddress,
@@ -363,6 +385,16 @@ pub enum EventDataView {
         // On-chain account address
         address: AccountAddress,
     },
+    /// An event of a type with no view of its own, decoded with the modules published on chain
+    #[serde(rename = "custom")]
+    Custom {
+        /// The type of the event, e.g. `0x1::MyModule::MyEvent<0x1::GAS::GAS>`
+        type_tag: String,
+        /// JSON rendering of the event: each struct is an object holding its `type` and its
+        /// `fields`, `u128` values and byte vectors are rendered as strings
+        value: serde_json::Value,
+        bytes: BytesView,
+    },
     #[serde(rename = "unknown")]
     Unknown { bytes: Option<BytesView> },
 
@@ -514,6 +546,48 @@ pub struct MetadataView {
     pub module_publishing_allowed: Option<bool>,
     pub diem_version: Option<u64>,
     pub dual_attestation_limit: Option<u64>,
+    /// The earliest version not pruned by the node, absent from nodes not reporting it
+    pub earliest_available_version: Option<u64>,
+}
+
+/// The API versions a node serves, and what the version a request was made to offers
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ApiVersionsView {
+    /// The version the request was served under
+    pub version: ApiVersion,
+    pub latest_version: ApiVersion,
+    pub versions: Vec<ApiVersion>,
+    /// The methods served under `version`
+    pub methods: Vec<Method>,
+    /// Whether the node is an archival node, keeping all the historical transactions, events and
+    /// account states, and serving the methods depending on optional indices (e.g.,
+    /// `get_events_by_type`). Absent from nodes not reporting it.
+    #[serde(default)]
+    pub archival: bool,
+}
+
+/// The block a transaction was committed in, as recorded by the block metadata transaction
+/// starting it
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct BlockMetadataView {
+    /// The version of the block metadata transaction
+    pub version: u64,
+    pub id: HashValue,
+    pub round: u64,
+    pub proposer: AccountAddress,
+    pub timestamp_usecs: u64,
+}
+
+impl From<(u64, BlockMetadata)> for BlockMetadataView {
+    fn from((version, block_metadata): (u64, BlockMetadata)) -> Self {
+        Self {
+            version,
+            id: block_metadata.id(),
+            round: block_metadata.round(),
+            proposer: block_metadata.proposer(),
+            timestamp_usecs: block_metadata.timestamp_usec(),
+        }
+    }
 }
 
 /////// 0L /////////
@@ -751,6 +825,88 @@ impl TransactionView {
     }
 }
 
+/// A transaction waiting in the mempool of the node, not committed yet.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct PendingTransactionView {
+    pub hash: HashValue,
+    pub status: PendingTransactionStatusView,
+    pub transaction: TransactionDataView,
+}
+
+/// `ready` if the transaction can be included in the next block, `parked` while it waits for an
+/// earlier sequence number of its sender.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingTransactionStatusView {
+    Ready,
+    Parked,
+}
+
+impl PendingTransactionView {
+    pub fn new(txn: SignedTransaction, status: PendingTransactionStatusView) -> Self {
+        let txn = Transaction::UserTransaction(txn);
+        Self {
+            hash: txn.hash(),
+            status,
+            transaction: txn.into(),
+        }
+    }
+}
+
+/// Why a transaction was recently removed from the mempool of the node.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct RemovedTransactionView {
+    pub reason: RemovalReasonView,
+    pub removal_timestamp_usecs: u64,
+}
+
+/// `committed` once a transaction of the sender with the same or a later sequence number is
+/// committed, `rejected` when one is rejected, `expired` past the expiration time of the
+/// transaction, `system_ttl_expired` past the time the node keeps transactions at most, `evicted`
+/// to make room in a full mempool and `replaced` by a transaction paying a higher gas price.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalReasonView {
+    Committed,
+    Rejected,
+    Expired,
+    SystemTtlExpired,
+    Evicted,
+    Replaced,
+}
+
+/// The transactions of an account waiting in the mempool of the node, to find out why they aren't
+/// committed.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MempoolAccountSummaryView {
+    pub address: AccountAddress,
+    pub ready: u64,
+    pub parked: u64,
+    /// When the transaction waiting the longest entered mempool, in microseconds since the Unix
+    /// epoch.
+    pub oldest_insertion_timestamp_usecs: u64,
+    pub gas_unit_price: GasUnitPriceDistributionView,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct GasUnitPriceDistributionView {
+    pub min: u64,
+    pub median: u64,
+    pub max: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct SyncStatusView {
+    pub highest_known_version: u64,
+    pub synced_version: u64,
+    pub committed_version: u64,
+    pub initialized: bool,
+    /// Transactions synced per second, over the last minute
+    pub sync_rate: f64,
+    /// `None` if the node isn't syncing
+    pub estimated_catch_up_secs: Option<u64>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct TransactionListView(pub Vec<TransactionView>);
 
@@ -1411,6 +1567,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_serialize_custom_event_data() {
+        let data = EventDataView::Custom {
+            type_tag: "0x1::M::Paid".to_string(),
+            value: json!({"type": "0x1::M::Paid", "fields": {"amount": 1}}),
+            bytes: vec![1, 0, 0, 0, 0, 0, 0, 0].into(),
+        };
+        let value = serde_json::to_value(&data).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "type": "custom",
+                "type_tag": "0x1::M::Paid",
+                "value": {"type": "0x1::M::Paid", "fields": {"amount": 1}},
+                "bytes": "0100000000000000",
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<EventDataView>(value).unwrap(),
+            data
+        );
+    }
+
     #[test]
     fn test_serialize_preburn_with_metadata_view() {
         let view = PreburnWithMetadataView {
@@ -0,0 +1,64 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use bytecode_verifier::{verify_module_with_config, CodeUnitVerifier, VerifierConfig};
+use move_binary_format::file_format::{self, Bytecode};
+use move_core_types::vm_status::StatusCode;
+
+#[test]
+fn too_many_basic_blocks() {
+    let mut code: Vec<_> = (1..=20).map(Bytecode::Branch).collect();
+    code.push(Bytecode::Ret);
+    let module = file_format::dummy_procedure_module(code);
+
+    let config = VerifierConfig {
+        max_basic_blocks: Some(21),
+        ..VerifierConfig::default()
+    };
+    assert!(CodeUnitVerifier::verify_module_with_config(&module, &config).is_ok());
+
+    let config = VerifierConfig {
+        max_basic_blocks: Some(20),
+        ..VerifierConfig::default()
+    };
+    let result = CodeUnitVerifier::verify_module_with_config(&module, &config);
+    assert_eq!(
+        result.unwrap_err().major_status(),
+        StatusCode::PROGRAM_TOO_COMPLEX
+    );
+}
+
+#[test]
+fn too_many_meter_units() {
+    let mut code = vec![];
+    for _ in 0..100 {
+        code.push(Bytecode::LdTrue);
+        code.push(Bytecode::Pop);
+    }
+    code.push(Bytecode::Ret);
+    let module = file_format::dummy_procedure_module(code);
+    assert!(CodeUnitVerifier::verify_module(&module).is_ok());
+
+    let per_function = VerifierConfig {
+        max_per_function_meter_units: Some(100),
+        ..VerifierConfig::default()
+    };
+    let per_module = VerifierConfig {
+        max_per_module_meter_units: Some(100),
+        ..VerifierConfig::default()
+    };
+    for config in &[per_function, per_module] {
+        let result = CodeUnitVerifier::verify_module_with_config(&module, config);
+        assert_eq!(
+            result.unwrap_err().major_status(),
+            StatusCode::PROGRAM_TOO_COMPLEX
+        );
+    }
+}
+
+#[test]
+fn framework_within_production_bounds() {
+    for module in diem_framework_releases::current_modules() {
+        verify_module_with_config(module, &VerifierConfig::production()).unwrap();
+    }
+}
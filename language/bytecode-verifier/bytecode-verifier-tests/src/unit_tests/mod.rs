@@ -8,6 +8,7 @@ pub mod constants_tests;
 pub mod control_flow_tests;
 pub mod duplication_tests;
 pub mod generic_ops_tests;
+pub mod limit_tests;
 pub mod multi_pass_tests;
 pub mod negative_stack_size_tests;
 pub mod signature_tests;
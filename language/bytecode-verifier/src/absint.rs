@@ -4,8 +4,12 @@
 use crate::{
     binary_views::FunctionView,
     control_flow_graph::{BlockId, ControlFlowGraph},
+    meter::{Meter, BLOCK_UNITS, INSTRUCTION_UNITS, JOIN_UNITS},
+};
+use move_binary_format::{
+    errors::PartialVMResult,
+    file_format::{Bytecode, CodeOffset},
 };
-use move_binary_format::file_format::{Bytecode, CodeOffset};
 use std::collections::HashMap;
 
 /// Trait for finite-height abstract domains. Infinite height domains would require a more complex
@@ -72,11 +76,13 @@ pub(crate) trait TransferFunctions {
 
 pub(crate) trait AbstractInterpreter: TransferFunctions {
     /// Analyze procedure local@function_view starting from pre-state local@initial_state.
+    /// The work done is charged to local@meter, and the analysis stops once it runs out.
     fn analyze_function(
         &mut self,
         initial_state: Self::State,
         function_view: &FunctionView,
-    ) -> InvariantMap<Self::State, Self::AnalysisError> {
+        meter: &mut Meter,
+    ) -> PartialVMResult<InvariantMap<Self::State, Self::AnalysisError>> {
         let mut inv_map: InvariantMap<Self::State, Self::AnalysisError> = InvariantMap::new();
         let entry_block_id = function_view.cfg().entry_block_id();
         let mut work_list = vec![entry_block_id];
@@ -94,6 +100,13 @@ pub(crate) trait AbstractInterpreter: TransferFunctions {
                 None => unreachable!("Missing invariant for block {}", block_id),
             };
 
+            let cfg = function_view.cfg();
+            meter.add(BLOCK_UNITS)?;
+            meter.add_items(
+                INSTRUCTION_UNITS,
+                (cfg.block_end(block_id) - cfg.block_start(block_id)) as usize + 1,
+            )?;
+
             let pre_state = &block_invariant.pre;
             let post_state = match self.execute_block(block_id, pre_state, function_view) {
                 Err(e) => {
@@ -110,6 +123,7 @@ pub(crate) trait AbstractInterpreter: TransferFunctions {
             for next_block_id in function_view.cfg().successors(block_id) {
                 match inv_map.get_mut(next_block_id) {
                     Some(next_block_invariant) => {
+                        meter.add(JOIN_UNITS)?;
                         let join_result = {
                             let old_pre = &mut next_block_invariant.pre;
                             old_pre.join(&post_state)
@@ -141,7 +155,7 @@ pub(crate) trait AbstractInterpreter: TransferFunctions {
                 }
             }
         }
-        inv_map
+        Ok(inv_map)
     }
 
     fn execute_block(
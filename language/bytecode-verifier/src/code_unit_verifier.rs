@@ -7,19 +7,25 @@
 use crate::{
     acquires_list_verifier::AcquiresVerifier,
     binary_views::{BinaryIndexedView, FunctionView},
-    control_flow, locals_safety, reference_safety,
+    control_flow,
+    control_flow_graph::ControlFlowGraph,
+    locals_safety,
+    meter::Meter,
+    reference_safety,
     stack_usage_verifier::StackUsageVerifier,
     type_safety,
+    verifier::VerifierConfig,
 };
 use move_binary_format::{
     access::ModuleAccess,
-    errors::{Location, PartialVMResult, VMResult},
+    errors::{Location, PartialVMError, PartialVMResult, VMResult},
     file_format::{
         CompiledModule, CompiledScript, FunctionDefinition, FunctionDefinitionIndex,
         IdentifierIndex, TableIndex,
     },
     IndexKind,
 };
+use move_core_types::vm_status::StatusCode;
 use std::collections::HashMap;
 
 pub struct CodeUnitVerifier<'a> {
@@ -30,13 +36,26 @@ pub struct CodeUnitVerifier<'a> {
 
 impl<'a> CodeUnitVerifier<'a> {
     pub fn verify_module(module: &'a CompiledModule) -> VMResult<()> {
-        Self::verify_module_impl(module).map_err(|e| e.finish(Location::Module(module.self_id())))
+        Self::verify_module_with_config(module, &VerifierConfig::default())
     }
 
-    fn verify_module_impl(module: &'a CompiledModule) -> PartialVMResult<()> {
+    pub fn verify_module_with_config(
+        module: &'a CompiledModule,
+        config: &VerifierConfig,
+    ) -> VMResult<()> {
+        Self::verify_module_impl(module, config)
+            .map_err(|e| e.finish(Location::Module(module.self_id())))
+    }
+
+    fn verify_module_impl(
+        module: &'a CompiledModule,
+        config: &VerifierConfig,
+    ) -> PartialVMResult<()> {
+        let mut meter = Meter::new(config);
         for (idx, function_definition) in module.function_defs().iter().enumerate() {
             let index = FunctionDefinitionIndex(idx as TableIndex);
-            Self::verify_function(index, function_definition, module)
+            meter.enter_function();
+            Self::verify_function(index, function_definition, module, config, &mut meter)
                 .map_err(|err| err.at_index(IndexKind::FunctionDefinition, index.0))?
         }
         Ok(())
@@ -56,13 +75,16 @@ impl<'a> CodeUnitVerifier<'a> {
             function_view,
             name_def_map: HashMap::new(),
         };
-        code_unit_verifier.verify_common()
+        let config = VerifierConfig::default();
+        code_unit_verifier.verify_common(&config, &mut Meter::new(&config))
     }
 
     fn verify_function(
         index: FunctionDefinitionIndex,
         function_definition: &'a FunctionDefinition,
         module: &'a CompiledModule,
+        config: &VerifierConfig,
+        meter: &mut Meter,
     ) -> PartialVMResult<()> {
         // nothing to verify for native function
         let code = match &function_definition.code {
@@ -84,15 +106,30 @@ impl<'a> CodeUnitVerifier<'a> {
             function_view,
             name_def_map,
         };
-        code_unit_verifier.verify_common()?;
+        code_unit_verifier.verify_common(config, meter)?;
         AcquiresVerifier::verify(module, index, function_definition)
     }
 
-    fn verify_common(&self) -> PartialVMResult<()> {
+    fn verify_common(&self, config: &VerifierConfig, meter: &mut Meter) -> PartialVMResult<()> {
+        if let Some(max_basic_blocks) = config.max_basic_blocks {
+            if self.function_view.cfg().num_blocks() as usize > max_basic_blocks {
+                return Err(
+                    PartialVMError::new(StatusCode::PROGRAM_TOO_COMPLEX).with_message(format!(
+                        "the function has more than {} basic blocks",
+                        max_basic_blocks
+                    )),
+                );
+            }
+        }
         control_flow::verify(self.function_view.index(), self.function_view.code())?;
         StackUsageVerifier::verify(&self.resolver, &self.function_view)?;
         type_safety::verify(&self.resolver, &self.function_view)?;
-        locals_safety::verify(&self.resolver, &self.function_view)?;
-        reference_safety::verify(&self.resolver, &self.function_view, &self.name_def_map)
+        locals_safety::verify(&self.resolver, &self.function_view, meter)?;
+        reference_safety::verify(
+            &self.resolver,
+            &self.function_view,
+            &self.name_def_map,
+            meter,
+        )
     }
 }
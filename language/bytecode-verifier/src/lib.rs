@@ -27,12 +27,13 @@ pub use code_unit_verifier::CodeUnitVerifier;
 pub use instruction_consistency::InstructionConsistency;
 pub use signature::SignatureChecker;
 pub use struct_defs::RecursiveStructDefChecker;
-pub use verifier::{verify_module, verify_script};
+pub use verifier::{verify_module, verify_module_with_config, verify_script, VerifierConfig};
 
 mod absint;
 mod acquires_list_verifier;
 mod binary_views;
 mod locals_safety;
+mod meter;
 mod reference_safety;
 mod stack_usage_verifier;
 mod type_safety;
@@ -0,0 +1,70 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module meters the work the verifier does on function bodies, bounding how long verifying
+//! a module may take. Work is counted in units rather than measured in time, so that all the
+//! validators agree on which modules are too complex to verify.
+use crate::verifier::VerifierConfig;
+use move_binary_format::errors::{PartialVMError, PartialVMResult};
+use move_core_types::vm_status::StatusCode;
+
+/// Units charged for each instruction the abstract interpreter executes.
+pub(crate) const INSTRUCTION_UNITS: u64 = 1;
+/// Units charged for each basic block the abstract interpreter analyzes, covering the copy of the
+/// state the block starts from.
+pub(crate) const BLOCK_UNITS: u64 = 4;
+/// Units charged for each join of the states flowing into a basic block.
+pub(crate) const JOIN_UNITS: u64 = 16;
+
+pub(crate) struct Meter {
+    max_per_function_units: Option<u64>,
+    max_per_module_units: Option<u64>,
+    function_units: u64,
+    module_units: u64,
+}
+
+impl Meter {
+    pub(crate) fn new(config: &VerifierConfig) -> Self {
+        Self {
+            max_per_function_units: config.max_per_function_meter_units,
+            max_per_module_units: config.max_per_module_meter_units,
+            function_units: 0,
+            module_units: 0,
+        }
+    }
+
+    /// Starts metering the verification of another function of the module.
+    pub(crate) fn enter_function(&mut self) {
+        self.function_units = 0;
+    }
+
+    pub(crate) fn add(&mut self, units: u64) -> PartialVMResult<()> {
+        self.function_units = self.function_units.saturating_add(units);
+        self.module_units = self.module_units.saturating_add(units);
+        if let Some(max) = self.max_per_function_units {
+            if self.function_units > max {
+                return Err(
+                    PartialVMError::new(StatusCode::PROGRAM_TOO_COMPLEX).with_message(format!(
+                        "verifying the function takes more than {} units",
+                        max
+                    )),
+                );
+            }
+        }
+        if let Some(max) = self.max_per_module_units {
+            if self.module_units > max {
+                return Err(
+                    PartialVMError::new(StatusCode::PROGRAM_TOO_COMPLEX).with_message(format!(
+                        "verifying the module takes more than {} units",
+                        max
+                    )),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn add_items(&mut self, units_per_item: u64, items: usize) -> PartialVMResult<()> {
+        self.add(units_per_item.saturating_mul(items as u64))
+    }
+}
@@ -12,6 +12,7 @@ mod abstract_state;
 use crate::{
     absint::{AbstractInterpreter, BlockInvariant, BlockPostcondition, TransferFunctions},
     binary_views::{BinaryIndexedView, FunctionView},
+    meter::Meter,
 };
 use abstract_state::{AbstractState, AbstractValue};
 use mirai_annotations::*;
@@ -50,11 +51,12 @@ pub(crate) fn verify<'a>(
     resolver: &'a BinaryIndexedView<'a>,
     function_view: &FunctionView,
     name_def_map: &'a HashMap<IdentifierIndex, FunctionDefinitionIndex>,
+    meter: &mut Meter,
 ) -> PartialVMResult<()> {
     let initial_state = AbstractState::new(function_view);
 
     let mut verifier = ReferenceSafetyAnalysis::new(resolver, function_view, name_def_map);
-    let inv_map = verifier.analyze_function(initial_state, function_view);
+    let inv_map = verifier.analyze_function(initial_state, function_view, meter)?;
     // Report all the join failures
     for (_block_id, BlockInvariant { post, .. }) in inv_map {
         match post {
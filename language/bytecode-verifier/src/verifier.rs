@@ -13,6 +13,32 @@ use move_binary_format::{
     file_format::{CompiledModule, CompiledScript},
 };
 
+/// Bounds on the complexity of the modules the verifier accepts, for modules with pathological
+/// control flow graphs not to stall the validators verifying them as they are published. The work
+/// verifying a function is counted in meter units rather than measured in time, for all the
+/// validators to agree on the modules they accept. The default config has no bounds.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VerifierConfig {
+    /// The most basic blocks in the control flow graph of a function
+    pub max_basic_blocks: Option<usize>,
+    /// The most meter units verifying the body of a function may take
+    pub max_per_function_meter_units: Option<u64>,
+    /// The most meter units verifying the bodies of all the functions of a module may take
+    pub max_per_module_meter_units: Option<u64>,
+}
+
+impl VerifierConfig {
+    /// The bounds modules published on chain must fit within, far above what the modules of the
+    /// Diem Framework take.
+    pub fn production() -> Self {
+        Self {
+            max_basic_blocks: Some(1024),
+            max_per_function_meter_units: Some(1_000_000),
+            max_per_module_meter_units: Some(10_000_000),
+        }
+    }
+}
+
 /// Helper for a "canonical" verification of a module.
 ///
 /// Clients that rely on verification should call the proper passes
@@ -24,6 +50,11 @@ use move_binary_format::{
 /// minimize the code locations that need to be updated should a new checker
 /// is introduced.
 pub fn verify_module(module: &CompiledModule) -> VMResult<()> {
+    verify_module_with_config(module, &VerifierConfig::default())
+}
+
+/// Same as `verify_module`, also rejecting modules more complex than the bounds of `config`.
+pub fn verify_module_with_config(module: &CompiledModule, config: &VerifierConfig) -> VMResult<()> {
     DuplicationChecker::verify_module(&module)?;
     SignatureChecker::verify_module(&module)?;
     InstructionConsistency::verify_module(&module)?;
@@ -32,7 +63,7 @@ pub fn verify_module(module: &CompiledModule) -> VMResult<()> {
     ability_field_requirements::verify_module(&module)?;
     RecursiveStructDefChecker::verify_module(&module)?;
     InstantiationLoopChecker::verify_module(&module)?;
-    CodeUnitVerifier::verify_module(&module)
+    CodeUnitVerifier::verify_module_with_config(&module, config)
 }
 
 /// Helper for a "canonical" verification of a script.
@@ -38,6 +38,26 @@ pub static SYSTEM_TRANSACTIONS_EXECUTED: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count the number of transactions executed again by the parallel executor, as they conflicted
+/// with the transactions executed alongside them.
+pub static PARALLEL_EXECUTION_REEXECUTED_TRANSACTIONS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_vm_parallel_execution_reexecuted_transactions",
+        "Number of transactions executed again by the parallel executor after a conflict"
+    )
+    .unwrap()
+});
+
+/// Count the number of blocks the parallel executor finished sequentially, as too many of their
+/// transactions conflicted.
+pub static PARALLEL_EXECUTION_FALLBACKS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_vm_parallel_execution_fallbacks",
+        "Number of blocks finished sequentially by the parallel executor"
+    )
+    .unwrap()
+});
+
 pub static BLOCK_TRANSACTION_COUNT: Lazy<Histogram> = Lazy::new(|| {
     register_histogram!(
         "diem_vm_num_txns_per_block",
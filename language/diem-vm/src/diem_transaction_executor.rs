@@ -24,8 +24,9 @@ use diem_types::{
     block_metadata::BlockMetadata,
     on_chain_config::DIEM_VERSION_3,
     transaction::{
-        ChangeSet, Module, SignatureCheckedTransaction, Transaction, TransactionArgument,
-        TransactionOutput, TransactionPayload, TransactionStatus, WriteSetPayload,
+        ChangeSet, Module, SignatureCheckedTransaction, SignedTransaction, Transaction,
+        TransactionArgument, TransactionOutput, TransactionPayload, TransactionStatus,
+        WriteSetPayload,
     },
     vm_status::{KeptVMStatus, StatusCode, VMStatus},
     write_set::{WriteSet, WriteSetMut},
@@ -39,7 +40,7 @@ use move_core_types::{
     value::{serialize_values, MoveValue},
 };
 use move_vm_runtime::{data_cache::MoveStorage, logging::LogContext, session::Session};
-use move_vm_types::gas_schedule::GasStatus;
+use move_vm_types::{gas_profile::GasProfile, gas_schedule::GasStatus};
 use rayon::prelude::*;
 use std::{
     collections::HashSet,
@@ -294,11 +295,29 @@ impl DiemVM {
         txn: &SignatureCheckedTransaction,
         log_context: &impl LogContext,
     ) -> (VMStatus, TransactionOutput) {
+        let (vm_status, output, _) =
+            self.execute_user_transaction_impl(storage, txn, log_context, false);
+        (vm_status, output)
+    }
+
+    /// Executes a user transaction, profiling the gas it's charged if `profile_gas` is set. There's
+    /// no profile when the transaction is discarded before it's charged for.
+    fn execute_user_transaction_impl<S: MoveStorage>(
+        &self,
+        storage: &S,
+        txn: &SignatureCheckedTransaction,
+        log_context: &impl LogContext,
+        profile_gas: bool,
+    ) -> (VMStatus, TransactionOutput, Option<GasProfile>) {
+        let discard = |err| {
+            let (vm_status, output) = discard_error_vm_status(err);
+            (vm_status, output, None)
+        };
         macro_rules! unwrap_or_discard {
             ($res: expr) => {
                 match $res {
                     Ok(s) => s,
-                    Err(e) => return discard_error_vm_status(e),
+                    Err(e) => return discard(e),
                 }
             };
         }
@@ -315,13 +334,16 @@ impl DiemVM {
         ) {
             Ok((_, currency_code)) => currency_code,
             Err(err) => {
-                return discard_error_vm_status(err);
+                return discard(err);
             }
         };
 
         let gas_schedule = unwrap_or_discard!(self.0.get_gas_schedule(log_context));
         let txn_data = TransactionMetadata::new(txn);
         let mut gas_status = GasStatus::new(gas_schedule, txn_data.max_gas_amount());
+        if profile_gas {
+            gas_status.enable_profiling();
+        }
 
         let result = match txn.payload() {
             payload @ TransactionPayload::Script(_)
@@ -343,7 +365,7 @@ impl DiemVM {
                 log_context,
             ),
             TransactionPayload::WriteSet(_) => {
-                return discard_error_vm_status(VMStatus::Error(StatusCode::UNREACHABLE))
+                return discard(VMStatus::Error(StatusCode::UNREACHABLE))
             }
         };
 
@@ -353,7 +375,7 @@ impl DiemVM {
             .get();
         TXN_GAS_USAGE.observe(gas_usage as f64);
 
-        match result {
+        let (vm_status, output) = match result {
             Ok(output) => output,
             Err(err) => {
                 let txn_status = TransactionStatus::from(err.clone());
@@ -370,7 +392,8 @@ impl DiemVM {
                     )
                 }
             }
-        }
+        };
+        (vm_status, output, gas_status.take_profile())
     }
 
     fn execute_writeset<S: MoveStorage>(
@@ -853,7 +876,28 @@ impl DiemVM {
     ) -> Result<Vec<(VMStatus, TransactionOutput)>, VMStatus> {
         let mut state_view_cache = StateViewCache::new(state_view);
         let vm = DiemVM::new(&state_view_cache);
-        vm.execute_block_impl(transactions, &mut state_view_cache)
+        if Self::get_concurrency_level() > 1 {
+            vm.execute_block_parallel_impl(transactions, &mut state_view_cache)
+        } else {
+            vm.execute_block_impl(transactions, &mut state_view_cache)
+        }
+    }
+
+    /// Executes a user transaction on top of `state_view` without committing it, and profiles the
+    /// gas it's charged by call stack, instruction and native function. There's no profile when
+    /// the transaction is discarded before it's charged for.
+    pub fn execute_user_transaction_with_gas_profile(
+        txn: SignedTransaction,
+        state_view: &dyn StateView,
+    ) -> Result<(VMStatus, TransactionOutput, Option<GasProfile>), VMStatus> {
+        let state_view_cache = StateViewCache::new(state_view);
+        let vm = DiemVM::new(&state_view_cache);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let checked_txn = match txn.check_signature() {
+            Ok(checked_txn) => checked_txn,
+            Err(_) => return Err(VMStatus::Error(StatusCode::INVALID_SIGNATURE)),
+        };
+        Ok(vm.execute_user_transaction_impl(&state_view_cache, &checked_txn, &log_context, true))
     }
 }
 
@@ -880,7 +924,7 @@ pub(crate) fn preprocess_transaction(txn: Transaction) -> PreprocessedTransactio
     }
 }
 
-fn is_reconfiguration(vm_output: &TransactionOutput) -> bool {
+pub(crate) fn is_reconfiguration(vm_output: &TransactionOutput) -> bool {
     let new_epoch_event_key = diem_types::on_chain_config::new_epoch_event_key();
     vm_output
         .events()
@@ -8,7 +8,9 @@ use crate::{
     errors::{convert_epilogue_error, convert_prologue_error, expect_only_successful_execution},
     system_module_names::*,
     transaction_metadata::TransactionMetadata,
+    DiemVM,
 };
+use bytecode_verifier::VerifierConfig;
 use diem_crypto::HashValue;
 use diem_logger::prelude::*;
 use diem_state_view::StateView;
@@ -36,15 +38,46 @@ use move_core_types::{
     value::{serialize_values, MoveValue},
 };
 use move_vm_runtime::{
+    custom_natives::CustomNatives,
     data_cache::MoveStorage,
     logging::{expect_no_verification_errors, LogContext},
     move_vm::MoveVM,
     session::Session,
 };
 use move_vm_types::gas_schedule::{calculate_intrinsic_gas, GasStatus};
+use once_cell::sync::OnceCell;
 use std::{convert::TryFrom, sync::Arc};
 use diem_framework_releases::import_stdlib;
 
+/// Native functions the VMs support in addition to those of the Move VM, see
+/// `DiemVM::set_custom_natives_once`.
+static CUSTOM_NATIVES: OnceCell<CustomNatives> = OnceCell::new();
+
+impl DiemVM {
+    /// Registers native functions for the VMs to support in addition to those of the Move VM, for
+    /// local networks experimenting with new natives. They must be registered before any VM is
+    /// constructed, and only once.
+    pub fn set_custom_natives_once(natives: CustomNatives) -> anyhow::Result<()> {
+        CUSTOM_NATIVES
+            .set(natives)
+            .map_err(|_| anyhow::format_err!("Custom natives are already registered"))
+    }
+
+    /// Whether the VMs support native functions in addition to those of the Move VM.
+    pub fn has_custom_natives() -> bool {
+        CUSTOM_NATIVES
+            .get()
+            .map_or(false, |natives| !natives.is_empty())
+    }
+}
+
+fn new_move_vm() -> MoveVM {
+    MoveVM::new_with_config(
+        CUSTOM_NATIVES.get().cloned().unwrap_or_default(),
+        VerifierConfig::production(),
+    )
+}
+
 #[derive(Clone)]
 /// A wrapper to make VMRuntime standalone and thread safe.
 pub struct DiemVMImpl {
@@ -57,7 +90,7 @@ pub struct DiemVMImpl {
 impl DiemVMImpl {
     #[allow(clippy::new_without_default)]
     pub fn new<S: StateView>(state: &S) -> Self {
-        let inner = MoveVM::new();
+        let inner = new_move_vm();
         let mut vm = Self {
             move_vm: Arc::new(inner),
             on_chain_config: None,
@@ -73,7 +106,7 @@ impl DiemVMImpl {
         on_chain_config: VMConfig,
         publishing_option: VMPublishingOption,
     ) -> Self {
-        let inner = MoveVM::new();
+        let inner = new_move_vm();
         Self {
             move_vm: Arc::new(inner),
             on_chain_config: Some(on_chain_config),
@@ -672,7 +705,10 @@ pub(crate) fn charge_global_write_gas_usage<R: MoveStorage>(
             .mul(gas_status.cost_table().gas_constants.default_account_size)
             .get();
     gas_status
-        .deduct_gas(InternalGasUnits::new(total_cost))
+        .deduct_gas_for(
+            || "<global writes>".to_string(),
+            InternalGasUnits::new(total_cost),
+        )
         .map_err(|p_err| p_err.finish(Location::Undefined).into_vm_status())
 }
 
@@ -0,0 +1,401 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Optimistic parallel execution of the transactions of a block.
+//!
+//! User transactions are executed in batches. The transactions of a batch are first executed
+//! speculatively on several threads, all against the state at the start of the batch, recording
+//! the access paths they read. They're then committed in order: a transaction which read nothing
+//! written by the transactions committed before it in the batch saw the state a sequential
+//! execution would have shown it, so its output is kept as is. Any other transaction conflicts,
+//! and is executed again against the state committed so far. The outputs are thus the same as
+//! those of a sequential execution.
+//!
+//! Block prologues, write sets and transactions publishing modules are executed sequentially,
+//! between batches. When too many of the transactions of a block conflict, such as when most of
+//! them are sent by the same account, speculation costs more than it saves and the rest of the
+//! block is executed sequentially.
+
+use crate::{
+    counters::*,
+    data_cache::{RemoteStorage, StateViewCache},
+    diem_transaction_executor::{
+        is_reconfiguration, preprocess_transaction, PreprocessedTransaction,
+    },
+    logging::AdapterLogSchema,
+    DiemVM,
+};
+use diem_logger::prelude::*;
+use diem_state_view::{StateView, StateViewId};
+use diem_types::{
+    access_path::AccessPath,
+    transaction::{Transaction, TransactionOutput, TransactionPayload, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
+    write_set::WriteSet,
+};
+use move_binary_format::errors::{PartialVMResult, VMResult};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+};
+use move_vm_runtime::data_cache::MoveStorage;
+use once_cell::sync::{Lazy, OnceCell};
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+use std::{collections::HashSet, sync::Mutex};
+
+/// Number of transactions speculatively executed per thread in a batch.
+const BATCH_SIZE_PER_THREAD: usize = 4;
+
+/// Minimum number of transactions to speculatively execute in a block before falling back to
+/// sequential execution, so that a single unlucky batch doesn't.
+const MIN_SPECULATIVE_TRANSACTIONS: usize = 32;
+
+/// Percentage of speculatively executed transactions which may conflict before falling back to
+/// sequential execution for the rest of the block.
+const MAX_CONFLICT_PERCENTAGE: usize = 50;
+
+static CONCURRENCY_LEVEL: OnceCell<usize> = OnceCell::new();
+
+static EXECUTION_POOL: Lazy<ThreadPool> = Lazy::new(|| {
+    ThreadPoolBuilder::new()
+        .num_threads(DiemVM::get_concurrency_level())
+        .thread_name(|index| format!("parallel_exec_{}", index))
+        .build()
+        .expect("Failed to build the parallel execution thread pool")
+});
+
+impl DiemVM {
+    /// Sets the number of threads the transactions of a block are executed on, unless it was
+    /// already set. With the default of 1, blocks are executed sequentially.
+    pub fn set_concurrency_level_once(concurrency_level: usize) {
+        CONCURRENCY_LEVEL.get_or_init(|| concurrency_level.max(1));
+    }
+
+    /// The number of threads the transactions of a block are executed on.
+    pub fn get_concurrency_level() -> usize {
+        CONCURRENCY_LEVEL.get().copied().unwrap_or(1)
+    }
+
+    pub(crate) fn execute_block_parallel_impl(
+        &self,
+        transactions: Vec<Transaction>,
+        data_cache: &mut StateViewCache,
+    ) -> Result<Vec<(VMStatus, TransactionOutput)>, VMStatus> {
+        let count = transactions.len();
+        info!(
+            AdapterLogSchema::new(data_cache.id(), 0),
+            "Executing block in parallel, transaction count: {}", count
+        );
+
+        let signature_verified_block: Vec<PreprocessedTransaction> = transactions
+            .into_par_iter()
+            .map(preprocess_transaction)
+            .collect();
+
+        let batch_size = Self::get_concurrency_level() * BATCH_SIZE_PER_THREAD;
+        let mut committer = Committer::new(count);
+        let mut idx = 0;
+        while idx < count {
+            let batch_end = if committer.sequential {
+                idx
+            } else {
+                signature_verified_block[idx..]
+                    .iter()
+                    .take(batch_size)
+                    .take_while(|txn| is_speculative(txn))
+                    .count()
+                    + idx
+            };
+            if batch_end - idx < 2 {
+                let txn = &signature_verified_block[idx];
+                committer.execute_and_commit(self, txn, data_cache, &mut HashSet::new())?;
+                idx += 1;
+                continue;
+            }
+
+            let batch = &signature_verified_block[idx..batch_end];
+            let speculative_outputs: Vec<_> = {
+                let base_view: &StateViewCache = data_cache;
+                EXECUTION_POOL.install(|| {
+                    batch
+                        .par_iter()
+                        .enumerate()
+                        .map(|(offset, txn)| {
+                            let read_recorder = ReadRecorder::new(base_view);
+                            let log_context = AdapterLogSchema::new(base_view.id(), idx + offset);
+                            let (vm_status, output, _sender) = self
+                                .execute_single_transaction(txn, &read_recorder, &log_context)
+                                .ok()?;
+                            Some(SpeculativeOutput {
+                                vm_status,
+                                output,
+                                reads: read_recorder.into_reads(),
+                            })
+                        })
+                        .collect()
+                })
+            };
+
+            let mut written = HashSet::new();
+            for (txn, speculative_output) in batch.iter().zip(speculative_outputs) {
+                committer.commit_speculative(
+                    self,
+                    txn,
+                    speculative_output,
+                    data_cache,
+                    &mut written,
+                )?;
+            }
+            idx = batch_end;
+            committer.check_conflict_rate(data_cache.id());
+        }
+
+        BLOCK_TRANSACTION_COUNT.observe(count as f64);
+        Ok(committer.result)
+    }
+}
+
+/// Whether `txn` may be executed speculatively, alongside the transactions around it.
+fn is_speculative(txn: &PreprocessedTransaction) -> bool {
+    match txn {
+        // Modules are cached by the loader once loaded, so modules published by a transaction
+        // mustn't be seen by those executed alongside it.
+        PreprocessedTransaction::UserTransaction(txn) => {
+            !matches!(txn.payload(), TransactionPayload::Module(_))
+        }
+        PreprocessedTransaction::InvalidSignature => true,
+        PreprocessedTransaction::BlockMetadata(_)
+        | PreprocessedTransaction::WaypointWriteSet(_)
+        | PreprocessedTransaction::WriteSet(_) => false,
+    }
+}
+
+/// The output of a transaction executed speculatively, with the access paths it read.
+struct SpeculativeOutput {
+    vm_status: VMStatus,
+    output: TransactionOutput,
+    reads: HashSet<AccessPath>,
+}
+
+/// Commits the outputs of the transactions of a block, in order.
+struct Committer {
+    result: Vec<(VMStatus, TransactionOutput)>,
+    should_restart: bool,
+    /// Whether the rest of the block is executed sequentially
+    sequential: bool,
+    speculative_count: usize,
+    conflict_count: usize,
+}
+
+impl Committer {
+    fn new(count: usize) -> Self {
+        Self {
+            result: Vec::with_capacity(count),
+            should_restart: false,
+            sequential: false,
+            speculative_count: 0,
+            conflict_count: 0,
+        }
+    }
+
+    /// Executes `txn` against the state committed so far, and commits its output.
+    fn execute_and_commit(
+        &mut self,
+        vm: &DiemVM,
+        txn: &PreprocessedTransaction,
+        data_cache: &mut StateViewCache,
+        written: &mut HashSet<AccessPath>,
+    ) -> Result<(), VMStatus> {
+        if self.should_restart {
+            self.push_retry(data_cache.id());
+            return Ok(());
+        }
+        let log_context = AdapterLogSchema::new(data_cache.id(), self.result.len());
+        let (vm_status, output, _sender) =
+            vm.execute_single_transaction(txn, &*data_cache, &log_context)?;
+        self.commit(vm_status, output, data_cache, written);
+        Ok(())
+    }
+
+    /// Commits the output of `txn` executed speculatively, unless it read access paths in
+    /// `written`, written by the transactions committed since, in which case `txn` is executed
+    /// again. So is it when its speculative execution failed, as it may have read inconsistent
+    /// state.
+    fn commit_speculative(
+        &mut self,
+        vm: &DiemVM,
+        txn: &PreprocessedTransaction,
+        speculative_output: Option<SpeculativeOutput>,
+        data_cache: &mut StateViewCache,
+        written: &mut HashSet<AccessPath>,
+    ) -> Result<(), VMStatus> {
+        if self.should_restart {
+            self.push_retry(data_cache.id());
+            return Ok(());
+        }
+        self.speculative_count += 1;
+        match speculative_output {
+            Some(speculative_output) if speculative_output.reads.is_disjoint(written) => {
+                self.commit(
+                    speculative_output.vm_status,
+                    speculative_output.output,
+                    data_cache,
+                    written,
+                );
+                Ok(())
+            }
+            _ => {
+                self.conflict_count += 1;
+                PARALLEL_EXECUTION_REEXECUTED_TRANSACTIONS.inc();
+                self.execute_and_commit(vm, txn, data_cache, written)
+            }
+        }
+    }
+
+    fn commit(
+        &mut self,
+        vm_status: VMStatus,
+        output: TransactionOutput,
+        data_cache: &mut StateViewCache,
+        written: &mut HashSet<AccessPath>,
+    ) {
+        let log_context = AdapterLogSchema::new(data_cache.id(), self.result.len());
+        if !output.status().is_discarded() {
+            written.extend(output.write_set().iter().map(|(ap, _)| ap.clone()));
+            data_cache.push_write_set(output.write_set());
+        } else {
+            trace!(log_context, "Transaction discarded, error: {:?}", vm_status);
+        }
+        if is_reconfiguration(&output) {
+            info!(log_context, "Reconfiguration occurred: restart required");
+            self.should_restart = true;
+        }
+        self.result.push((vm_status, output));
+    }
+
+    fn push_retry(&mut self, id: StateViewId) {
+        let txn_output =
+            TransactionOutput::new(WriteSet::default(), vec![], 0, TransactionStatus::Retry);
+        self.result
+            .push((VMStatus::Error(StatusCode::UNKNOWN_STATUS), txn_output));
+        debug!(
+            AdapterLogSchema::new(id, self.result.len() - 1),
+            "Retry after reconfiguration"
+        );
+    }
+
+    /// Falls back to sequential execution if too many transactions conflicted so far.
+    fn check_conflict_rate(&mut self, id: StateViewId) {
+        if !self.sequential
+            && self.speculative_count >= MIN_SPECULATIVE_TRANSACTIONS
+            && self.conflict_count * 100 > self.speculative_count * MAX_CONFLICT_PERCENTAGE
+        {
+            info!(
+                AdapterLogSchema::new(id, self.result.len()),
+                "{} of {} transactions conflicted: executing the rest of the block sequentially",
+                self.conflict_count,
+                self.speculative_count
+            );
+            PARALLEL_EXECUTION_FALLBACKS.inc();
+            self.sequential = true;
+        }
+    }
+}
+
+/// A `StateView` recording the access paths read from it.
+struct ReadRecorder<'a> {
+    data_view: &'a dyn StateView,
+    reads: Mutex<HashSet<AccessPath>>,
+}
+
+impl<'a> ReadRecorder<'a> {
+    fn new(data_view: &'a dyn StateView) -> Self {
+        Self {
+            data_view,
+            reads: Mutex::new(HashSet::new()),
+        }
+    }
+
+    fn into_reads(self) -> HashSet<AccessPath> {
+        self.reads
+            .into_inner()
+            .expect("Read recorder mutex poisoned")
+    }
+}
+
+impl<'a> StateView for ReadRecorder<'a> {
+    fn get(&self, access_path: &AccessPath) -> anyhow::Result<Option<Vec<u8>>> {
+        self.reads
+            .lock()
+            .expect("Read recorder mutex poisoned")
+            .insert(access_path.clone());
+        self.data_view.get(access_path)
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.data_view.is_genesis()
+    }
+
+    fn id(&self) -> StateViewId {
+        self.data_view.id()
+    }
+}
+
+impl<'a> MoveStorage for ReadRecorder<'a> {
+    fn get_module(&self, module_id: &ModuleId) -> VMResult<Option<Vec<u8>>> {
+        RemoteStorage::new(self).get_module(module_id)
+    }
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> PartialVMResult<Option<Vec<u8>>> {
+        RemoteStorage::new(self).get_resource(address, tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diem_crypto::HashValue;
+    use diem_types::block_metadata::BlockMetadata;
+    use std::collections::BTreeMap;
+
+    struct MapView(BTreeMap<AccessPath, Vec<u8>>);
+
+    impl StateView for MapView {
+        fn get(&self, access_path: &AccessPath) -> anyhow::Result<Option<Vec<u8>>> {
+            Ok(self.0.get(access_path).cloned())
+        }
+
+        fn is_genesis(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_read_recorder() {
+        let present = AccessPath::new(AccountAddress::random(), vec![0]);
+        let missing = AccessPath::new(AccountAddress::random(), vec![1]);
+        let view = MapView(vec![(present.clone(), vec![42])].into_iter().collect());
+
+        let read_recorder = ReadRecorder::new(&view);
+        assert_eq!(read_recorder.get(&present).unwrap(), Some(vec![42]));
+        assert_eq!(read_recorder.get(&missing).unwrap(), None);
+        assert_eq!(read_recorder.get(&present).unwrap(), Some(vec![42]));
+        // Reading nothing there may conflict as much as reading something.
+        assert_eq!(
+            read_recorder.into_reads(),
+            vec![present, missing].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn test_is_speculative() {
+        assert!(is_speculative(&PreprocessedTransaction::InvalidSignature));
+        assert!(!is_speculative(&PreprocessedTransaction::BlockMetadata(
+            BlockMetadata::new(HashValue::zero(), 0, 0, vec![], AccountAddress::ZERO)
+        )));
+    }
+}
@@ -5,7 +5,8 @@ use crate::{
     file_format::{AbilitySet, Visibility},
     normalized::Module,
 };
-use std::collections::BTreeSet;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use std::{collections::BTreeSet, fmt};
 
 /// The result of a linking and layout compatibility check. Here is what the different combinations
 /// mean:
@@ -29,12 +30,25 @@ impl Compatibility {
 
     /// Return compatibility assessment for `new_module` relative to old module `old_module`.
     pub fn check(old_module: &Module, new_module: &Module) -> Compatibility {
-        let mut struct_and_function_linking = true;
-        let mut struct_layout = true;
+        let incompatibilities = Self::incompatibilities(old_module, new_module);
+        Compatibility {
+            struct_and_function_linking: !incompatibilities.iter().any(|i| i.breaks_linking()),
+            struct_layout: !incompatibilities.iter().any(|i| i.breaks_layout()),
+        }
+    }
+
+    /// Return the changes in `new_module` breaking its compatibility with the old module
+    /// `old_module`, so that they can be reported before the upgrade is attempted. There are none
+    /// when the modules are fully compatible.
+    pub fn incompatibilities(old_module: &Module, new_module: &Module) -> Vec<Incompatibility> {
+        let mut incompatibilities = vec![];
 
         // module's name and address are unchanged
         if old_module.address != new_module.address || old_module.name != new_module.name {
-            struct_and_function_linking = false;
+            incompatibilities.push(Incompatibility::ModuleRenamed {
+                old: old_module.module_id(),
+                new: new_module.module_id(),
+            });
         }
 
         // old module's structs are a subset of the new module's structs
@@ -43,7 +57,7 @@ impl Compatibility {
                 Some(new_struct) => new_struct,
                 None => {
                     // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
-                    struct_and_function_linking = false;
+                    incompatibilities.push(Incompatibility::StructRemoved(name.clone()));
                     // Note: we intentionally do *not* label this a layout compatibility violation.
                     // Existing modules can still successfully read previously published values of
                     // this struct `Parent::T`. That is, code like the function `foo` in
@@ -58,13 +72,14 @@ impl Compatibility {
                 }
             };
 
-            if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities)
-                || !type_parameters_compatibile(
-                    &old_struct.type_parameters,
-                    &new_struct.type_parameters,
-                )
-            {
-                struct_and_function_linking = false;
+            if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities) {
+                incompatibilities.push(Incompatibility::StructAbilitiesRemoved(name.clone()));
+            }
+            if !type_parameters_compatibile(
+                &old_struct.type_parameters,
+                &new_struct.type_parameters,
+            ) {
+                incompatibilities.push(Incompatibility::StructTypeParametersChanged(name.clone()));
             }
             if new_struct.fields != old_struct.fields {
                 // Fields changed. Code in this module will fail at runtime if it tries to
@@ -80,7 +95,7 @@ impl Compatibility {
                 //     B is struct B { some_name: bool }. TODO: does this affect clients? I
                 //     think not--the serialization of the same data with these two types
                 //     will be the same.
-                struct_layout = false
+                incompatibilities.push(Incompatibility::StructLayoutChanged(name.clone()));
             }
         }
 
@@ -102,7 +117,7 @@ impl Compatibility {
             let new_func = match new_module.exposed_functions.get(name) {
                 Some(new_func) => new_func,
                 None => {
-                    struct_and_function_linking = false;
+                    incompatibilities.push(Incompatibility::FunctionRemoved(name.clone()));
                     continue;
                 }
             };
@@ -116,15 +131,17 @@ impl Compatibility {
                 (Visibility::Friend, _) => false,
                 (Visibility::Private, _) => unreachable!("A private function can never be exposed"),
             };
-            if !is_vis_compatible
-                || old_func.parameters != new_func.parameters
+            if !is_vis_compatible {
+                incompatibilities.push(Incompatibility::FunctionVisibilityChanged(name.clone()));
+            }
+            if old_func.parameters != new_func.parameters
                 || old_func.return_ != new_func.return_
                 || !type_parameters_compatibile(
                     &old_func.type_parameters,
                     &new_func.type_parameters,
                 )
             {
-                struct_and_function_linking = false;
+                incompatibilities.push(Incompatibility::FunctionSignatureChanged(name.clone()));
             }
         }
 
@@ -138,13 +155,80 @@ impl Compatibility {
         // in this module.
         let old_friend_module_ids: BTreeSet<_> = old_module.friends.iter().cloned().collect();
         let new_friend_module_ids: BTreeSet<_> = new_module.friends.iter().cloned().collect();
-        if !old_friend_module_ids.is_subset(&new_friend_module_ids) {
-            struct_and_function_linking = false;
+        for friend in old_friend_module_ids.difference(&new_friend_module_ids) {
+            incompatibilities.push(Incompatibility::FriendRemoved(friend.clone()));
         }
 
-        Compatibility {
-            struct_and_function_linking,
-            struct_layout,
+        incompatibilities
+    }
+}
+
+/// A change breaking the compatibility of a module with its previous version.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Incompatibility {
+    /// The address or name of the module changed
+    ModuleRenamed {
+        old: ModuleId,
+        new: ModuleId,
+    },
+    StructRemoved(Identifier),
+    StructAbilitiesRemoved(Identifier),
+    /// Type parameters of the struct were added, removed, or constrained further
+    StructTypeParametersChanged(Identifier),
+    /// The fields of the struct changed, so that its published values can't be read anymore
+    StructLayoutChanged(Identifier),
+    /// A public, script or friend function was removed, or made private
+    FunctionRemoved(Identifier),
+    FunctionVisibilityChanged(Identifier),
+    /// The parameters, return types or type parameters of the function changed
+    FunctionSignatureChanged(Identifier),
+    FriendRemoved(ModuleId),
+}
+
+impl Incompatibility {
+    /// Whether dependent modules that reference functions or structs in the module may not link.
+    pub fn breaks_linking(&self) -> bool {
+        !self.breaks_layout()
+    }
+
+    /// Whether reading structs previously published by the module will fail at runtime.
+    pub fn breaks_layout(&self) -> bool {
+        matches!(self, Incompatibility::StructLayoutChanged(_))
+    }
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Incompatibility::ModuleRenamed { old, new } => {
+                write!(f, "module {} was renamed to {}", old, new)
+            }
+            Incompatibility::StructRemoved(name) => write!(f, "struct {} was removed", name),
+            Incompatibility::StructAbilitiesRemoved(name) => {
+                write!(f, "abilities of struct {} were removed", name)
+            }
+            Incompatibility::StructTypeParametersChanged(name) => write!(
+                f,
+                "type parameters of struct {} were changed or constrained further",
+                name
+            ),
+            Incompatibility::StructLayoutChanged(name) => write!(
+                f,
+                "fields of struct {} were changed, published values need a data migration",
+                name
+            ),
+            Incompatibility::FunctionRemoved(name) => {
+                write!(f, "function {} was removed or made private", name)
+            }
+            Incompatibility::FunctionVisibilityChanged(name) => {
+                write!(f, "visibility of function {} was restricted", name)
+            }
+            Incompatibility::FunctionSignatureChanged(name) => {
+                write!(f, "signature of function {} was changed", name)
+            }
+            Incompatibility::FriendRemoved(module_id) => {
+                write!(f, "friend {} was removed", module_id)
+            }
         }
     }
 }
@@ -0,0 +1,103 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    compatibility::{Compatibility, Incompatibility},
+    file_format::{AbilitySet, Visibility},
+    normalized::{Field, Function, Module, Struct, Type},
+};
+use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+
+fn ident(name: &str) -> Identifier {
+    Identifier::new(name).unwrap()
+}
+
+fn module() -> Module {
+    let mut module = Module {
+        address: AccountAddress::from_hex_literal("0x1").unwrap(),
+        name: ident("M"),
+        friends: vec![],
+        structs: Default::default(),
+        exposed_functions: Default::default(),
+    };
+    module.structs.insert(
+        ident("S"),
+        Struct {
+            abilities: AbilitySet::EMPTY,
+            type_parameters: vec![],
+            fields: vec![Field {
+                name: ident("f"),
+                type_: Type::U64,
+            }],
+        },
+    );
+    module.exposed_functions.insert(
+        ident("pay"),
+        Function {
+            visibility: Visibility::Public,
+            type_parameters: vec![],
+            parameters: vec![Type::Address, Type::U64],
+            return_: vec![],
+        },
+    );
+    module
+}
+
+#[test]
+fn fully_compatible_upgrade() {
+    let old = module();
+    let mut new = module();
+    new.exposed_functions.insert(
+        ident("new_function"),
+        Function {
+            visibility: Visibility::Script,
+            type_parameters: vec![],
+            parameters: vec![],
+            return_: vec![],
+        },
+    );
+
+    assert!(Compatibility::incompatibilities(&old, &new).is_empty());
+    assert!(Compatibility::check(&old, &new).is_fully_compatible());
+}
+
+#[test]
+fn layout_incompatible_upgrade() {
+    let old = module();
+    let mut new = module();
+    new.structs.get_mut(&ident("S")).unwrap().fields[0].type_ = Type::U128;
+
+    assert_eq!(
+        Compatibility::incompatibilities(&old, &new),
+        vec![Incompatibility::StructLayoutChanged(ident("S"))]
+    );
+    let compatibility = Compatibility::check(&old, &new);
+    assert!(compatibility.struct_and_function_linking);
+    assert!(!compatibility.struct_layout);
+}
+
+#[test]
+fn linking_incompatible_upgrade() {
+    let old = module();
+    let mut new = module();
+    new.exposed_functions
+        .get_mut(&ident("pay"))
+        .unwrap()
+        .parameters = vec![Type::Address];
+    new.structs.remove(&ident("S"));
+
+    assert_eq!(
+        Compatibility::incompatibilities(&old, &new),
+        vec![
+            Incompatibility::StructRemoved(ident("S")),
+            Incompatibility::FunctionSignatureChanged(ident("pay")),
+        ]
+    );
+    let compatibility = Compatibility::check(&old, &new);
+    assert!(!compatibility.struct_and_function_linking);
+    assert!(compatibility.struct_layout);
+    assert_eq!(
+        Incompatibility::FunctionSignatureChanged(ident("pay")).to_string(),
+        "signature of function pay was changed"
+    );
+}
@@ -573,6 +573,8 @@ pub enum StatusCode {
     INVALID_FRIEND_DECL_WITH_MODULES_IN_DEPENDENCIES = 1106,
     // The updated module introduces a cyclic friendship (i.e., A friends B and B also friends A)
     CYCLIC_MODULE_FRIENDSHIP = 1107,
+    // Verifying the module takes more work than the verifier is configured to do
+    PROGRAM_TOO_COMPLEX = 1108,
 
     // These are errors that the VM might raise if a violation of internal
     // invariants takes place.
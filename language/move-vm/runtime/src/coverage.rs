@@ -0,0 +1,43 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Collection of the bytecode instructions executed on the current thread, so that test runners
+//! can report how much of the modules they exercise is covered.
+//!
+//! Instructions are only counted by debug builds of the VM, which trace every instruction they
+//! execute; release builds collect no coverage. Only instructions of module functions are counted,
+//! not those of scripts.
+
+#[cfg(debug_assertions)]
+use crate::loader::Function;
+use move_binary_format::file_format::CodeOffset;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use std::{cell::RefCell, collections::BTreeMap};
+
+/// How many times each instruction of each function was executed, by its offset in the code.
+pub type ExecutionCounts = BTreeMap<(ModuleId, Identifier), BTreeMap<CodeOffset, u64>>;
+
+thread_local! {
+    static EXECUTION_COUNTS: RefCell<Option<ExecutionCounts>> = RefCell::new(None);
+}
+
+/// Starts counting the instructions executed on the current thread, from zero.
+pub fn start_collecting() {
+    EXECUTION_COUNTS.with(|counts| *counts.borrow_mut() = Some(ExecutionCounts::new()));
+}
+
+/// Stops counting the instructions executed on the current thread, returning those counted since
+/// collection started, if it did.
+pub fn stop_collecting() -> Option<ExecutionCounts> {
+    EXECUTION_COUNTS.with(|counts| counts.borrow_mut().take())
+}
+
+#[cfg(debug_assertions)]
+pub(crate) fn record(function: &Function, pc: CodeOffset) {
+    EXECUTION_COUNTS.with(|counts| {
+        if let (Some(counts), Some(module_id)) = (&mut *counts.borrow_mut(), function.module_id()) {
+            let key = (module_id.clone(), function.identifier().to_owned());
+            *counts.entry(key).or_default().entry(pc).or_insert(0) += 1;
+        }
+    });
+}
@@ -0,0 +1,232 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Native functions registered in addition to those the VM supports, so that networks can
+//! experiment with new natives without forking the VM.
+//!
+//! A custom native is declared in a Move module like any other native function, and registered
+//! with the address, module and name it's declared at, along with the gas it costs. The set of
+//! custom natives is validated as it's built, before the VM is constructed: a custom native can't
+//! replace a native of the VM, nor be free to call.
+//!
+//! Every node of a network must register the same custom natives, or they won't agree on the
+//! outcome of the transactions calling them.
+
+use crate::native_functions::NativeFunction;
+use anyhow::{bail, Result};
+use move_binary_format::errors::PartialVMResult;
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_schedule::{AbstractMemorySize, GasAlgebra, GasCarrier, GasCost, InternalGasUnits},
+    identifier::Identifier,
+};
+use move_vm_types::{
+    loaded_data::runtime_types::Type, natives::function::NativeResult, values::Value,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::Arc,
+};
+
+/// The implementation of a custom native function, called with the gas cost it was registered
+/// with, its type arguments and its arguments. Unlike the natives of the VM, it's given no
+/// `NativeContext`, so it can't emit events.
+pub type CustomNativeFunction = fn(
+    gas_cost: &GasCost,
+    ty_args: Vec<Type>,
+    arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult>;
+
+/// The gas a custom native costs for processing `size` units of data, as the natives of the VM are
+/// charged from the native table of the gas schedule.
+pub fn custom_native_gas(gas_cost: &GasCost, size: usize) -> InternalGasUnits<GasCarrier> {
+    let memory_size = AbstractMemorySize::new(std::cmp::max(1, size) as GasCarrier);
+    gas_cost.total().mul(memory_size)
+}
+
+pub(crate) struct CustomNative {
+    gas_cost: GasCost,
+    function: CustomNativeFunction,
+}
+
+impl CustomNative {
+    pub(crate) fn call(
+        &self,
+        ty_args: Vec<Type>,
+        arguments: VecDeque<Value>,
+    ) -> PartialVMResult<NativeResult> {
+        (self.function)(&self.gas_cost, ty_args, arguments)
+    }
+}
+
+impl fmt::Debug for CustomNative {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CustomNative")
+            .field("gas_cost", &self.gas_cost)
+            .finish()
+    }
+}
+
+/// A validated set of custom natives, by the address, module and name they're declared at.
+#[derive(Clone, Debug, Default)]
+pub struct CustomNatives {
+    natives: Arc<HashMap<(AccountAddress, Identifier, Identifier), Arc<CustomNative>>>,
+}
+
+impl CustomNatives {
+    pub fn builder() -> CustomNativesBuilder {
+        CustomNativesBuilder::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.natives.is_empty()
+    }
+
+    /// Resolves the native function declared at the given location, be it one of the VM or a
+    /// custom one.
+    pub(crate) fn resolve(
+        &self,
+        module_address: &AccountAddress,
+        module_name: &str,
+        function_name: &str,
+    ) -> Option<NativeFunction> {
+        NativeFunction::resolve(module_address, module_name, function_name).or_else(|| {
+            let native = self.natives.get(&(
+                *module_address,
+                Identifier::new(module_name).ok()?,
+                Identifier::new(function_name).ok()?,
+            ))?;
+            Some(NativeFunction::Custom(Arc::clone(native)))
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct CustomNativesBuilder {
+    natives: Vec<(
+        AccountAddress,
+        String,
+        String,
+        GasCost,
+        CustomNativeFunction,
+    )>,
+}
+
+impl CustomNativesBuilder {
+    /// Registers `function` as the native function `function_name` of the module
+    /// `module_address::module_name`, costing `gas_cost` to call.
+    pub fn add_native(
+        mut self,
+        module_address: AccountAddress,
+        module_name: &str,
+        function_name: &str,
+        gas_cost: GasCost,
+        function: CustomNativeFunction,
+    ) -> Self {
+        self.natives.push((
+            module_address,
+            module_name.to_string(),
+            function_name.to_string(),
+            gas_cost,
+            function,
+        ));
+        self
+    }
+
+    /// Validates the custom natives registered.
+    pub fn build(self) -> Result<CustomNatives> {
+        let mut natives = HashMap::new();
+        for (module_address, module_name, function_name, gas_cost, function) in self.natives {
+            let location = format!("{}::{}::{}", module_address, module_name, function_name);
+            if NativeFunction::resolve(&module_address, &module_name, &function_name).is_some() {
+                bail!("Custom native {} replaces a native of the VM", location);
+            }
+            if gas_cost.total().get() == 0 {
+                bail!("Custom native {} is free to call", location);
+            }
+            let key = (
+                module_address,
+                Identifier::new(module_name)?,
+                Identifier::new(function_name)?,
+            );
+            let native = CustomNative { gas_cost, function };
+            if natives.insert(key, Arc::new(native)).is_some() {
+                bail!("Custom native {} is registered twice", location);
+            }
+        }
+        Ok(CustomNatives {
+            natives: Arc::new(natives),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use move_core_types::language_storage::CORE_CODE_ADDRESS;
+
+    fn native_answer(
+        gas_cost: &GasCost,
+        _ty_args: Vec<Type>,
+        _arguments: VecDeque<Value>,
+    ) -> PartialVMResult<NativeResult> {
+        Ok(NativeResult::ok(
+            custom_native_gas(gas_cost, 1),
+            vec![Value::u64(42)].into(),
+        ))
+    }
+
+    #[test]
+    fn test_build() {
+        let address = AccountAddress::random();
+        let natives = CustomNatives::builder()
+            .add_native(address, "M", "answer", GasCost::new(10, 1), native_answer)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            natives.resolve(&address, "M", "answer"),
+            Some(NativeFunction::Custom(_))
+        ));
+        assert!(natives.resolve(&address, "M", "question").is_none());
+        assert!(matches!(
+            natives.resolve(&CORE_CODE_ADDRESS, "Hash", "sha3_256"),
+            Some(NativeFunction::HashSha3_256)
+        ));
+    }
+
+    #[test]
+    fn test_build_invalid() {
+        let address = AccountAddress::random();
+        let gas_cost = GasCost::new(10, 1);
+        assert!(CustomNatives::builder()
+            .add_native(address, "M", "answer", GasCost::new(0, 0), native_answer)
+            .build()
+            .is_err());
+        assert!(CustomNatives::builder()
+            .add_native(address, "M", "answer", gas_cost.clone(), native_answer)
+            .add_native(address, "M", "answer", gas_cost.clone(), native_answer)
+            .build()
+            .is_err());
+        assert!(CustomNatives::builder()
+            .add_native(
+                address,
+                "M",
+                "not an identifier",
+                gas_cost.clone(),
+                native_answer
+            )
+            .build()
+            .is_err());
+        assert!(CustomNatives::builder()
+            .add_native(
+                CORE_CODE_ADDRESS,
+                "Hash",
+                "sha3_256",
+                gas_cost,
+                native_answer
+            )
+            .build()
+            .is_err());
+    }
+}
@@ -5,6 +5,7 @@ use crate::{
     loader::{Function, Loader, Resolver},
     logging::LogContext,
     native_functions::FunctionContext,
+    observer::InterpreterState,
     trace,
 };
 use diem_logger::prelude::*;
@@ -82,7 +83,10 @@ impl<L: LogContext> Interpreter<L> {
         // We count the intrinsic cost of the transaction here, since that needs to also cover the
         // setup of the function.
         let mut interp = Self::new(log_context.clone());
-        interp.execute(loader, data_store, gas_status, function, ty_args, args)
+        let result = interp.execute(loader, data_store, gas_status, function, ty_args, args);
+        // Execution may have aborted in the middle of calls
+        gas_status.exit_all_functions();
+        result
     }
 
     /// Create a new instance of an `Interpreter` in the context of a transaction with a
@@ -134,7 +138,10 @@ impl<L: LogContext> Interpreter<L> {
                 .map_err(|e| self.set_location(e))?;
         }
 
+        gas_status.enter_function(|| function.pretty_string());
         let mut current_frame = Frame::new(function, ty_args, locals);
+        #[cfg(debug_assertions)]
+        self.frame_pushed(&current_frame, loader);
         loop {
             let resolver = current_frame.resolver(loader);
             let exit_code = current_frame //self
@@ -142,6 +149,7 @@ impl<L: LogContext> Interpreter<L> {
                 .map_err(|err| self.maybe_core_dump(err, &current_frame))?;
             match exit_code {
                 ExitCode::Return => {
+                    gas_status.exit_function();
                     if let Some(frame) = self.call_stack.pop() {
                         current_frame = frame;
                         current_frame.pc += 1; // advance past the Call instruction in the caller
@@ -173,7 +181,10 @@ impl<L: LogContext> Interpreter<L> {
                         let err = set_err_info!(frame, err);
                         self.maybe_core_dump(err, &frame)
                     })?;
+                    gas_status.enter_function(|| frame.function.pretty_string());
                     current_frame = frame;
+                    #[cfg(debug_assertions)]
+                    self.frame_pushed(&current_frame, loader);
                 }
                 ExitCode::CallGeneric(idx) => {
                     let arity = resolver.type_params_count(idx);
@@ -206,12 +217,21 @@ impl<L: LogContext> Interpreter<L> {
                         let err = set_err_info!(frame, err);
                         self.maybe_core_dump(err, &frame)
                     })?;
+                    gas_status.enter_function(|| frame.function.pretty_string());
                     current_frame = frame;
+                    #[cfg(debug_assertions)]
+                    self.frame_pushed(&current_frame, loader);
                 }
             }
         }
     }
 
+    /// Lets the observer of the current thread know that `frame` was pushed.
+    #[cfg(debug_assertions)]
+    fn frame_pushed(&self, frame: &Frame, loader: &Loader) {
+        crate::observer::frame_pushed(&frame.function, &frame.locals, loader, self);
+    }
+
     /// Returns a `Frame` if the call is to a Move function. Calls to native functions are
     /// "inlined" and this returns `None`.
     ///
@@ -272,7 +292,10 @@ impl<L: LogContext> Interpreter<L> {
         let mut native_context = FunctionContext::new(self, data_store, gas_status, resolver);
         let native_function = function.get_native()?;
         let result = native_function.dispatch(&mut native_context, ty_args, arguments)?;
-        gas_status.deduct_gas(result.cost)?;
+        gas_status.deduct_gas_for(
+            || format!("{} (native)", function.pretty_string()),
+            result.cost,
+        )?;
         let return_values = result
             .result
             .map_err(|code| PartialVMError::new(StatusCode::ABORTED).with_sub_status(code))?;
@@ -557,6 +580,16 @@ impl<L: LogContext> Interpreter<L> {
     }
 }
 
+impl<L: LogContext> InterpreterState for Interpreter<L> {
+    fn caller_count(&self) -> usize {
+        self.call_stack.0.len()
+    }
+
+    fn write_stack_trace(&self, buf: &mut String, loader: &Loader) -> PartialVMResult<()> {
+        self.debug_print_stack_trace(buf, loader)
+    }
+}
+
 // TODO Determine stack size limits based on gas limit
 const OPERAND_STACK_SIZE_LIMIT: usize = 1024;
 const CALL_STACK_SIZE_LIMIT: usize = 1024;
@@ -12,12 +12,15 @@
 #[macro_use]
 extern crate mirai_annotations;
 
+pub mod coverage;
+pub mod custom_natives;
 pub mod data_cache;
 mod interpreter;
 mod loader;
 pub mod logging;
 pub mod move_vm;
 mod native_functions;
+pub mod observer;
 mod runtime;
 pub mod session;
 #[macro_use]
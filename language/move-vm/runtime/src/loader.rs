@@ -2,10 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    custom_natives::CustomNatives,
     logging::{expect_no_verification_errors, LogContext},
     native_functions::NativeFunction,
 };
-use bytecode_verifier::{self, cyclic_dependencies, dependencies, script_signature};
+use bytecode_verifier::{
+    self, cyclic_dependencies, dependencies, script_signature, VerifierConfig,
+};
 use diem_crypto::HashValue;
 use diem_logger::prelude::*;
 use move_binary_format::{
@@ -147,6 +150,7 @@ impl ModuleCache {
         &mut self,
         id: ModuleId,
         module: CompiledModule,
+        natives: &CustomNatives,
         log_context: &impl LogContext,
     ) -> VMResult<Arc<Module>> {
         if let Some(module) = self.module_at(&id) {
@@ -155,7 +159,7 @@ impl ModuleCache {
 
         // we need this operation to be transactional, if an error occurs we must
         // leave a clean state
-        self.add_module(&module, log_context)?;
+        self.add_module(&module, natives, log_context)?;
         match Module::new(module, self) {
             Ok(module) => Ok(Arc::clone(self.modules.insert(id, module))),
             Err((err, module)) => {
@@ -173,6 +177,7 @@ impl ModuleCache {
     fn add_module(
         &mut self,
         module: &CompiledModule,
+        natives: &CustomNatives,
         log_context: &impl LogContext,
     ) -> VMResult<()> {
         let starting_idx = self.structs.len();
@@ -188,7 +193,7 @@ impl ModuleCache {
             })?;
         for (idx, func) in module.function_defs().iter().enumerate() {
             let findex = FunctionDefinitionIndex(idx as TableIndex);
-            let function = Function::new(findex, func, module);
+            let function = Function::new(findex, func, module, natives);
             self.functions.push(Arc::new(function));
         }
         Ok(())
@@ -419,14 +424,19 @@ pub(crate) struct Loader {
     scripts: RwLock<ScriptCache>,
     module_cache: RwLock<ModuleCache>,
     type_cache: RwLock<TypeCache>,
+    natives: CustomNatives,
+    // Bounds on the complexity of the modules published
+    verifier_config: VerifierConfig,
 }
 
 impl Loader {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(natives: CustomNatives, verifier_config: VerifierConfig) -> Self {
         Self {
             scripts: RwLock::new(ScriptCache::new()),
             module_cache: RwLock::new(ModuleCache::new()),
             type_cache: RwLock::new(TypeCache::new()),
+            natives,
+            verifier_config,
         }
     }
 
@@ -648,7 +658,7 @@ impl Loader {
         data_store: &mut impl DataStore,
         log_context: &impl LogContext,
     ) -> VMResult<()> {
-        self.verify_module(module, data_store, true, log_context)
+        self.verify_module(module, data_store, true, &self.verifier_config, log_context)
     }
 
     fn verify_module_expect_no_missing_dependencies(
@@ -657,7 +667,15 @@ impl Loader {
         data_store: &mut impl DataStore,
         log_context: &impl LogContext,
     ) -> VMResult<()> {
-        self.verify_module(module, data_store, false, log_context)
+        // Modules in storage fit within the bounds of the verifier config, if any, as they were
+        // checked against them when published
+        self.verify_module(
+            module,
+            data_store,
+            false,
+            &VerifierConfig::default(),
+            log_context,
+        )
     }
 
     fn verify_module(
@@ -665,10 +683,11 @@ impl Loader {
         module: &CompiledModule,
         data_store: &mut impl DataStore,
         verify_no_missing_modules: bool,
+        verifier_config: &VerifierConfig,
         log_context: &impl LogContext,
     ) -> VMResult<()> {
-        bytecode_verifier::verify_module(&module)?;
-        Self::check_natives(&module)?;
+        bytecode_verifier::verify_module_with_config(&module, verifier_config)?;
+        self.check_natives(&module)?;
 
         let deps = module.immediate_dependencies();
         let loaded_imm_deps = if verify_no_missing_modules {
@@ -713,8 +732,11 @@ impl Loader {
     }
 
     // All native functions must be known to the loader
-    fn check_natives(module: &CompiledModule) -> VMResult<()> {
-        fn check_natives_impl(module: &CompiledModule) -> PartialVMResult<()> {
+    fn check_natives(&self, module: &CompiledModule) -> VMResult<()> {
+        fn check_natives_impl(
+            module: &CompiledModule,
+            natives: &CustomNatives,
+        ) -> PartialVMResult<()> {
             for (idx, native_function) in module
                 .function_defs()
                 .iter()
@@ -723,18 +745,19 @@ impl Loader {
             {
                 let fh = module.function_handle_at(native_function.function);
                 let mh = module.module_handle_at(fh.module);
-                NativeFunction::resolve(
-                    module.address_identifier_at(mh.address),
-                    module.identifier_at(mh.name).as_str(),
-                    module.identifier_at(fh.name).as_str(),
-                )
-                .ok_or_else(|| {
-                    verification_error(
-                        StatusCode::MISSING_DEPENDENCY,
-                        IndexKind::FunctionHandle,
-                        idx as TableIndex,
+                natives
+                    .resolve(
+                        module.address_identifier_at(mh.address),
+                        module.identifier_at(mh.name).as_str(),
+                        module.identifier_at(fh.name).as_str(),
                     )
-                })?;
+                    .ok_or_else(|| {
+                        verification_error(
+                            StatusCode::MISSING_DEPENDENCY,
+                            IndexKind::FunctionHandle,
+                            idx as TableIndex,
+                        )
+                    })?;
             }
             // TODO: fix check and error code if we leave something around for native structs.
             // For now this generates the only error test cases care about...
@@ -749,7 +772,8 @@ impl Loader {
             }
             Ok(())
         }
-        check_natives_impl(module).map_err(|e| e.finish(Location::Module(module.self_id())))
+        check_natives_impl(module, &self.natives)
+            .map_err(|e| e.finish(Location::Module(module.self_id())))
     }
 
     //
@@ -852,10 +876,10 @@ impl Loader {
 
         let module = deserialize_and_verify_module(self, bytes, data_store, log_context)
             .map_err(|err| expect_no_verification_errors(err, log_context))?;
-        let module_ref = self
-            .module_cache
-            .write()
-            .insert(id.clone(), module, log_context)?;
+        let module_ref =
+            self.module_cache
+                .write()
+                .insert(id.clone(), module, &self.natives, log_context)?;
 
         // friendship is an upward edge in the dependencies DAG, so it has to be checked after the
         // module is put into cache, otherwise it is a chicken-and-egg problem.
@@ -1541,12 +1565,13 @@ impl Function {
         index: FunctionDefinitionIndex,
         def: &FunctionDefinition,
         module: &CompiledModule,
+        natives: &CustomNatives,
     ) -> Self {
         let handle = module.function_handle_at(def.function);
         let name = module.identifier_at(handle.name).to_owned();
         let module_id = module.self_id();
         let native = if def.is_native() {
-            NativeFunction::resolve(
+            natives.resolve(
                 module_id.address(),
                 module_id.name().as_str(),
                 name.as_str(),
@@ -1627,6 +1652,10 @@ impl Function {
         self.name.as_str()
     }
 
+    pub(crate) fn identifier(&self) -> &IdentStr {
+        &self.name
+    }
+
     pub(crate) fn code(&self) -> &[Bytecode] {
         &self.code
     }
@@ -1657,7 +1686,7 @@ impl Function {
     }
 
     pub(crate) fn get_native(&self) -> PartialVMResult<NativeFunction> {
-        self.native.ok_or_else(|| {
+        self.native.clone().ok_or_else(|| {
             PartialVMError::new(StatusCode::UNREACHABLE)
                 .with_message("Missing Native Function".to_string())
         })
@@ -1,7 +1,10 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{data_cache::MoveStorage, runtime::VMRuntime, session::Session};
+use crate::{
+    custom_natives::CustomNatives, data_cache::MoveStorage, runtime::VMRuntime, session::Session,
+};
+use bytecode_verifier::VerifierConfig;
 
 pub struct MoveVM {
     runtime: VMRuntime,
@@ -9,8 +12,19 @@ pub struct MoveVM {
 
 impl MoveVM {
     pub fn new() -> Self {
+        Self::new_with_natives(CustomNatives::default())
+    }
+
+    /// A VM supporting `natives` in addition to its own native functions.
+    pub fn new_with_natives(natives: CustomNatives) -> Self {
+        Self::new_with_config(natives, VerifierConfig::default())
+    }
+
+    /// A VM supporting `natives` in addition to its own native functions, and rejecting the
+    /// modules published more complex than the bounds of `verifier_config`.
+    pub fn new_with_config(natives: CustomNatives, verifier_config: VerifierConfig) -> Self {
         Self {
-            runtime: VMRuntime::new(),
+            runtime: VMRuntime::new(natives, verifier_config),
         }
     }
 
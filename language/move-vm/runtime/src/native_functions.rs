@@ -1,7 +1,9 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{interpreter::Interpreter, loader::Resolver, logging::LogContext};
+use crate::{
+    custom_natives::CustomNative, interpreter::Interpreter, loader::Resolver, logging::LogContext,
+};
 use move_binary_format::errors::PartialVMResult;
 use move_core_types::{
     account_address::AccountAddress, gas_schedule::CostTable, language_storage::CORE_CODE_ADDRESS,
@@ -15,7 +17,7 @@ use move_vm_types::{
     natives::function::{NativeContext, NativeResult},
     values::Value,
 };
-use std::{collections::VecDeque, fmt::Write};
+use std::{collections::VecDeque, fmt::Write, sync::Arc};
 
 // The set of native functions the VM supports.
 // The functions can line in any crate linked in but the VM declares them here.
@@ -23,7 +25,7 @@ use std::{collections::VecDeque, fmt::Write};
 // - `resolve` which given a function unique name ModuleAddress::ModuleName::FunctionName
 // returns a `NativeFunction`
 // - `dispatch` which given a `NativeFunction` invokes the native
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub(crate) enum NativeFunction {
     HashSha2_256,
     HashSha3_256,
@@ -51,6 +53,8 @@ pub(crate) enum NativeFunction {
     DecimalDemo,
     DecimalSingle,
     DecimalPair,
+    // registered by the network, see `custom_natives`
+    Custom(Arc<CustomNative>),
 }
 
 impl NativeFunction {
@@ -129,6 +133,7 @@ impl NativeFunction {
             Self::DecimalDemo => ol_decimal::native_decimal_demo(ctx, t, v),
             Self::DecimalSingle => ol_decimal::native_decimal_single(ctx, t, v),
             Self::DecimalPair => ol_decimal::native_decimal_pair(ctx, t, v),
+            Self::Custom(native) => native.call(t, v),
         };
         debug_assert!(match &result {
             Err(e) => e.major_status().status_type() == StatusType::InvariantViolation,
@@ -0,0 +1,156 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hooks into the execution of Move code on the current thread, for tools such as step debuggers.
+//!
+//! An [`ExecutionObserver`] is called before every instruction is executed and whenever a Move
+//! function is called, and can inspect the state of the execution: the function being executed,
+//! its locals, and the call and operand stacks.
+//!
+//! Observers are only called by debug builds of the VM, which trace every instruction they
+//! execute; release builds never call them.
+
+use crate::loader::{Function, Loader};
+use move_binary_format::{
+    errors::PartialVMResult,
+    file_format::{Bytecode, CodeOffset},
+};
+use move_core_types::language_storage::ModuleId;
+use move_vm_types::values::{self, Locals};
+use std::cell::RefCell;
+
+pub trait ExecutionObserver {
+    /// Called before each instruction is executed.
+    fn on_instruction(&mut self, state: &ExecutionState);
+
+    /// Called when a Move function is called, once its frame is pushed, before its first
+    /// instruction is executed. Calls to native functions push no frame.
+    fn on_frame_push(&mut self, _state: &ExecutionState) {}
+}
+
+/// What observers may inspect of the stacks of the interpreter.
+pub(crate) trait InterpreterState {
+    /// The number of frames in the call stack below the current one.
+    fn caller_count(&self) -> usize;
+
+    /// Writes the frames of the callers, with their code and locals, and the operand stack.
+    fn write_stack_trace(&self, buf: &mut String, loader: &Loader) -> PartialVMResult<()>;
+}
+
+/// The state of the execution an observer is called in.
+pub struct ExecutionState<'a> {
+    function: &'a Function,
+    locals: &'a Locals,
+    pc: CodeOffset,
+    loader: &'a Loader,
+    interpreter: &'a dyn InterpreterState,
+}
+
+impl<'a> ExecutionState<'a> {
+    /// The function being executed, such as `0x1::DiemAccount::pay_from`.
+    pub fn function(&self) -> String {
+        self.function.pretty_string()
+    }
+
+    /// The module of the function being executed, unless it's that of a script.
+    pub fn module_id(&self) -> Option<&ModuleId> {
+        self.function.module_id()
+    }
+
+    /// The name of the function being executed, without its module.
+    pub fn function_name(&self) -> &str {
+        self.function.name()
+    }
+
+    /// The offset of the instruction about to be executed.
+    pub fn pc(&self) -> CodeOffset {
+        self.pc
+    }
+
+    pub fn instruction(&self) -> &Bytecode {
+        &self.code()[self.pc as usize]
+    }
+
+    /// The code of the function being executed.
+    pub fn code(&self) -> &[Bytecode] {
+        self.function.code()
+    }
+
+    /// How deep the function being executed is in the call stack, the outermost one being at 0.
+    pub fn depth(&self) -> usize {
+        self.interpreter.caller_count()
+    }
+
+    /// The locals of the function being executed, one per line.
+    pub fn locals(&self) -> PartialVMResult<String> {
+        let mut buf = String::new();
+        if self.function.local_count() > 0 {
+            values::debug::print_locals(&mut buf, self.locals)?;
+        }
+        Ok(buf)
+    }
+
+    /// The frames of the callers of the function being executed, with their code and locals, and
+    /// the operand stack.
+    pub fn stack_trace(&self) -> PartialVMResult<String> {
+        let mut buf = String::new();
+        self.interpreter.write_stack_trace(&mut buf, self.loader)?;
+        Ok(buf)
+    }
+}
+
+thread_local! {
+    static OBSERVER: RefCell<Option<Box<dyn ExecutionObserver>>> = RefCell::new(None);
+}
+
+/// Starts calling `observer` on the execution of Move code on the current thread, in place of the
+/// observer called so far, if any.
+pub fn start_observing(observer: Box<dyn ExecutionObserver>) {
+    OBSERVER.with(|current| *current.borrow_mut() = Some(observer));
+}
+
+/// Stops calling the observer of the current thread, returning it if there was one.
+pub fn stop_observing() -> Option<Box<dyn ExecutionObserver>> {
+    OBSERVER.with(|current| current.borrow_mut().take())
+}
+
+#[cfg(debug_assertions)]
+pub(crate) fn instruction(
+    function: &Function,
+    locals: &Locals,
+    pc: CodeOffset,
+    loader: &Loader,
+    interpreter: &dyn InterpreterState,
+) {
+    OBSERVER.with(|current| {
+        if let Some(observer) = &mut *current.borrow_mut() {
+            observer.on_instruction(&ExecutionState {
+                function,
+                locals,
+                pc,
+                loader,
+                interpreter,
+            });
+        }
+    });
+}
+
+#[cfg(debug_assertions)]
+pub(crate) fn frame_pushed(
+    function: &Function,
+    locals: &Locals,
+    loader: &Loader,
+    interpreter: &dyn InterpreterState,
+) {
+    OBSERVER.with(|current| {
+        if let Some(observer) = &mut *current.borrow_mut() {
+            observer.on_frame_push(&ExecutionState {
+                function,
+                locals,
+                pc: 0,
+                loader,
+                interpreter,
+            });
+        }
+    });
+}
@@ -2,12 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    custom_natives::CustomNatives,
     data_cache::{MoveStorage, TransactionDataCache},
     interpreter::Interpreter,
     loader::Loader,
     logging::LogContext,
     session::Session,
 };
+use bytecode_verifier::VerifierConfig;
 use diem_logger::prelude::*;
 use move_binary_format::{
     access::ModuleAccess,
@@ -41,9 +43,9 @@ fn is_signer_reference(s: &Type) -> bool {
 }
 
 impl VMRuntime {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(natives: CustomNatives, verifier_config: VerifierConfig) -> Self {
         VMRuntime {
-            loader: Loader::new(),
+            loader: Loader::new(natives, verifier_config),
         }
     }
 
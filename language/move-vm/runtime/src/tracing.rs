@@ -21,9 +21,11 @@ use ::{
 
 #[cfg(debug_assertions)] //////// 0L ////////
 use crate::{
+    coverage,
     interpreter::Interpreter,
     loader::{Function, Loader},
     logging::LogContext,
+    observer,
 };
 
 #[cfg(debug_assertions)]
@@ -69,6 +71,8 @@ pub(crate) fn trace<L: LogContext>(
     loader: &Loader,
     interp: &Interpreter<L>,
 ) {
+    coverage::record(function_desc, pc);
+    observer::instruction(function_desc, locals, pc, loader, interp);
     if *TRACING_ENABLED {
         let f = &mut *LOGGING_FILE.lock().unwrap();
         writeln!(
@@ -0,0 +1,189 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Profiling of the gas charged while executing Move code.
+//!
+//! A `GasProfile` attributes every charge to the call stack it was made in, down to the
+//! instruction or native function charged for, so that Move developers can see where the gas
+//! of their script goes. Gas is recorded in internal gas units, the unit of the cost table.
+//!
+//! The profile can be rendered as folded stacks, one `function;function;item gas` line per call
+//! stack, the input format of flame graph tools such as `inferno-flamegraph` or `flamegraph.pl`.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+/// Item gas is attributed to when charged outside of any known instruction or native function.
+pub const UNATTRIBUTED: &str = "<unattributed>";
+
+/// Frame gas is attributed to when charged outside of any function.
+const TOP_LEVEL: &str = "<top level>";
+
+/// Number of items listed in the summary of a profile.
+const SUMMARY_ITEMS: usize = 20;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GasProfile {
+    /// The functions being executed, outermost first
+    call_stack: Vec<String>,
+    /// Gas charged by call stack and item, the item being last
+    charges: HashMap<Vec<String>, u64>,
+}
+
+impl GasProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `function` was called, and is charged for until it returns.
+    pub fn enter_function(&mut self, function: String) {
+        self.call_stack.push(function);
+    }
+
+    /// Records that the function called last returned.
+    pub fn exit_function(&mut self) {
+        self.call_stack.pop();
+    }
+
+    /// Records that execution stopped, whether the functions being executed returned or not.
+    pub fn exit_all_functions(&mut self) {
+        self.call_stack.clear();
+    }
+
+    /// Records `gas` charged for `item`, an instruction or native function, in the current call
+    /// stack.
+    pub fn record(&mut self, item: String, gas: u64) {
+        let mut stack = if self.call_stack.is_empty() {
+            vec![TOP_LEVEL.to_string()]
+        } else {
+            self.call_stack.clone()
+        };
+        stack.push(item);
+        *self.charges.entry(stack).or_insert(0) += gas;
+    }
+
+    /// The gas charged in total.
+    pub fn total(&self) -> u64 {
+        self.charges.values().sum()
+    }
+
+    /// The gas charged for each instruction and native function, wherever it was charged.
+    pub fn by_item(&self) -> BTreeMap<&str, u64> {
+        let mut by_item = BTreeMap::new();
+        for (stack, gas) in &self.charges {
+            let item = stack.last().map_or(UNATTRIBUTED, String::as_str);
+            *by_item.entry(item).or_insert(0) += gas;
+        }
+        by_item
+    }
+
+    /// The gas charged in each function, including that charged in the functions it called.
+    pub fn by_function(&self) -> BTreeMap<&str, u64> {
+        let mut by_function = BTreeMap::new();
+        for (stack, gas) in &self.charges {
+            let mut functions: Vec<_> = stack[..stack.len() - 1]
+                .iter()
+                .map(String::as_str)
+                .collect();
+            // Recursive functions are only counted once.
+            functions.sort_unstable();
+            functions.dedup();
+            for function in functions {
+                *by_function.entry(function).or_insert(0) += gas;
+            }
+        }
+        by_function
+    }
+
+    /// The profile as folded stacks, sorted, for flame graph tools.
+    pub fn folded_stacks(&self) -> String {
+        let mut lines: Vec<_> = self
+            .charges
+            .iter()
+            .map(|(stack, gas)| format!("{} {}", stack.join(";"), gas))
+            .collect();
+        lines.sort();
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+}
+
+/// A summary of the profile, with the items and functions which were charged the most.
+impl fmt::Display for GasProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        writeln!(f, "Total gas charged: {} internal gas units", total)?;
+        write_top(
+            f,
+            "By instruction and native function",
+            self.by_item(),
+            total,
+        )?;
+        write_top(
+            f,
+            "By function, including its callees",
+            self.by_function(),
+            total,
+        )
+    }
+}
+
+fn write_top(
+    f: &mut fmt::Formatter<'_>,
+    title: &str,
+    charges: BTreeMap<&str, u64>,
+    total: u64,
+) -> fmt::Result {
+    writeln!(f, "{}:", title)?;
+    let mut charges: Vec<_> = charges.into_iter().collect();
+    charges.sort_by(|(a_name, a_gas), (b_name, b_gas)| b_gas.cmp(a_gas).then(a_name.cmp(b_name)));
+    for (name, gas) in charges.into_iter().take(SUMMARY_ITEMS) {
+        let percent = if total == 0 {
+            0.0
+        } else {
+            gas as f64 * 100.0 / total as f64
+        };
+        writeln!(f, "  {:>12} {:>6.2}%  {}", gas, percent, name)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_profile() {
+        let mut profile = GasProfile::new();
+        profile.record("<intrinsic>".to_string(), 600);
+        profile.enter_function("0x1::M::f".to_string());
+        profile.record("LdU64".to_string(), 1);
+        profile.enter_function("0x1::M::f".to_string());
+        profile.record("LdU64".to_string(), 1);
+        profile.record("0x1::Hash::sha3_256 (native)".to_string(), 10);
+        profile.exit_function();
+        profile.record("Ret".to_string(), 2);
+        profile.exit_all_functions();
+        profile.record("global writes".to_string(), 100);
+
+        assert_eq!(profile.total(), 714);
+        assert_eq!(profile.by_item()["LdU64"], 2);
+        assert_eq!(profile.by_item()["<intrinsic>"], 600);
+        // The recursive call is only counted once.
+        assert_eq!(profile.by_function()["0x1::M::f"], 14);
+        assert_eq!(profile.by_function()[TOP_LEVEL], 700);
+        assert_eq!(
+            profile.folded_stacks(),
+            "0x1::M::f;0x1::M::f;0x1::Hash::sha3_256 (native) 10\n\
+             0x1::M::f;0x1::M::f;LdU64 1\n\
+             0x1::M::f;LdU64 1\n\
+             0x1::M::f;Ret 2\n\
+             <top level>;<intrinsic> 600\n\
+             <top level>;global writes 100\n"
+        );
+        assert!(profile
+            .to_string()
+            .starts_with("Total gas charged: 714 internal gas units\n"));
+    }
+}
@@ -6,6 +6,7 @@
 //! It is important to note that the cost schedule defined in this file does not track hashing
 //! operations or other native operations; the cost of each native operation will be returned by the
 //! native function itself.
+use crate::gas_profile::{GasProfile, UNATTRIBUTED};
 use mirai_annotations::*;
 use move_binary_format::{
     errors::{Location, PartialVMError, PartialVMResult, VMResult},
@@ -37,6 +38,7 @@ pub struct GasStatus<'a> {
     cost_table: &'a CostTable,
     gas_left: InternalGasUnits<GasCarrier>,
     charge: bool,
+    profile: Option<GasProfile>,
 }
 
 impl<'a> GasStatus<'a> {
@@ -49,6 +51,7 @@ impl<'a> GasStatus<'a> {
             gas_left: cost_table.gas_constants.to_internal_units(gas_left),
             cost_table,
             charge: true,
+            profile: None,
         }
     }
 
@@ -61,6 +64,7 @@ impl<'a> GasStatus<'a> {
             gas_left: InternalGasUnits::new(0),
             cost_table: &ZERO_COST_SCHEDULE,
             charge: false,
+            profile: None,
         }
     }
 
@@ -78,13 +82,28 @@ impl<'a> GasStatus<'a> {
 
     /// Charge a given amount of gas and fail if not enough gas units are left.
     pub fn deduct_gas(&mut self, amount: InternalGasUnits<GasCarrier>) -> PartialVMResult<()> {
+        self.deduct_gas_for(|| UNATTRIBUTED.to_string(), amount)
+    }
+
+    /// Charge a given amount of gas for `item`, such as a native function, which the gas profile
+    /// attributes it to, and fail if not enough gas units are left.
+    pub fn deduct_gas_for(
+        &mut self,
+        item: impl FnOnce() -> String,
+        amount: InternalGasUnits<GasCarrier>,
+    ) -> PartialVMResult<()> {
         if !self.charge {
             return Ok(());
         }
-        if self
+        let enough_gas = self
             .gas_left
-            .app(&amount, |curr_gas, gas_amt| curr_gas >= gas_amt)
-        {
+            .app(&amount, |curr_gas, gas_amt| curr_gas >= gas_amt);
+        if let Some(profile) = &mut self.profile {
+            // Only what's left is charged when running out of gas
+            let charged = if enough_gas { amount } else { self.gas_left };
+            profile.record(item(), charged.get());
+        }
+        if enough_gas {
             self.gas_left = self.gas_left.sub(amount);
             Ok(())
         } else {
@@ -103,7 +122,8 @@ impl<'a> GasStatus<'a> {
         // Make sure that the size is always non-zero
         let size = size.map(|x| std::cmp::max(1, x));
         debug_assert!(size.get() > 0);
-        self.deduct_gas(
+        self.deduct_gas_for(
+            || format!("{:?}", opcode),
             self.cost_table
                 .instruction_cost(opcode as u8)
                 .total()
@@ -113,7 +133,10 @@ impl<'a> GasStatus<'a> {
 
     /// Charge an instruction and fail if not enough gas units are left.
     pub fn charge_instr(&mut self, opcode: Opcodes) -> PartialVMResult<()> {
-        self.deduct_gas(self.cost_table.instruction_cost(opcode as u8).total())
+        self.deduct_gas_for(
+            || format!("{:?}", opcode),
+            self.cost_table.instruction_cost(opcode as u8).total(),
+        )
     }
 
     /// Charge gas related to the overall size of a transaction and fail if not enough
@@ -123,13 +146,45 @@ impl<'a> GasStatus<'a> {
         intrinsic_cost: AbstractMemorySize<GasCarrier>,
     ) -> VMResult<()> {
         let cost = calculate_intrinsic_gas(intrinsic_cost, &self.cost_table.gas_constants);
-        self.deduct_gas(cost)
+        self.deduct_gas_for(|| "<intrinsic>".to_string(), cost)
             .map_err(|e| e.finish(Location::Undefined))
     }
 
     pub fn set_metering(&mut self, enabled: bool) {
         self.charge = enabled
     }
+
+    /// Start recording the gas charged into a profile, by call stack and item charged for.
+    pub fn enable_profiling(&mut self) {
+        self.profile.get_or_insert_with(GasProfile::new);
+    }
+
+    /// Take the profile of the gas charged so far, if profiling is enabled. Profiling stops.
+    pub fn take_profile(&mut self) -> Option<GasProfile> {
+        self.profile.take()
+    }
+
+    /// Record that the profiled code called `function`, which gas is attributed to until it
+    /// returns.
+    pub fn enter_function(&mut self, function: impl FnOnce() -> String) {
+        if let Some(profile) = &mut self.profile {
+            profile.enter_function(function());
+        }
+    }
+
+    /// Record that the profiled code returned from the function it called last.
+    pub fn exit_function(&mut self) {
+        if let Some(profile) = &mut self.profile {
+            profile.exit_function();
+        }
+    }
+
+    /// Record that the profiled code stopped, whether its functions returned or aborted.
+    pub fn exit_all_functions(&mut self) {
+        if let Some(profile) = &mut self.profile {
+            profile.exit_all_functions();
+        }
+    }
 }
 
 pub fn new_from_instructions(
@@ -0,0 +1,116 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Coverage of the Move modules exercised by the tests run with a
+//! [`FakeExecutor`](crate::executor::FakeExecutor).
+//!
+//! When the `MOVE_COVERAGE_DIR` environment variable is set, every executor counts the bytecode
+//! instructions the VM executes for it, and once dropped writes them to that directory as a
+//! coverage map named after its test. The `coverage-summaries` tool of `move-coverage` merges the
+//! maps of a directory into per-module and per-function coverage reports, for example:
+//!
+//! ```text
+//! MOVE_COVERAGE_DIR=/tmp/coverage cargo test -p language-e2e-testsuite
+//! cargo run --bin coverage-summaries -- -t /tmp/coverage -s <compiled modules dir> -f
+//! ```
+//!
+//! As the VM only counts instructions in debug builds, tests only collect coverage in debug builds.
+
+use move_coverage::coverage_map::{output_map_to_file, CoverageMap, ExecCoverageMap};
+use move_vm_runtime::coverage;
+use std::{
+    collections::BTreeMap,
+    env, fs, mem,
+    path::PathBuf,
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+/// The environment variable naming the directory coverage maps are written to.
+pub const ENV_COVERAGE_DIR: &str = "MOVE_COVERAGE_DIR";
+
+/// The extension of coverage map files.
+pub const COVERAGE_MAP_EXTENSION: &str = "mvcov";
+
+/// Number of trackers created by this process, telling apart the maps of the executors of a test.
+static TRACKER_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts the instructions executed for an executor, and writes them as a coverage map once
+/// dropped.
+#[derive(Debug)]
+pub struct CoverageTracker {
+    map_path: PathBuf,
+    exec_map: Mutex<ExecCoverageMap>,
+}
+
+impl CoverageTracker {
+    /// A tracker of the test running on the current thread, if coverage was requested through the
+    /// environment.
+    pub fn from_env() -> Option<Self> {
+        let dir = PathBuf::from(env::var_os(ENV_COVERAGE_DIR)?);
+        fs::create_dir_all(&dir).expect("Failed to create the coverage directory");
+        // Test harnesses name the thread running a test after it.
+        let test_name = thread::current()
+            .name()
+            .unwrap_or("executor")
+            .replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "_");
+        let exec_id = format!(
+            "{}-{}-{}",
+            test_name,
+            process::id(),
+            TRACKER_COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        Some(Self {
+            map_path: dir.join(format!("{}.{}", exec_id, COVERAGE_MAP_EXTENSION)),
+            exec_map: Mutex::new(ExecCoverageMap::new(exec_id)),
+        })
+    }
+
+    /// Runs `f`, counting the instructions the VM executes on the current thread meanwhile.
+    pub fn track<T>(&self, f: impl FnOnce() -> T) -> T {
+        coverage::start_collecting();
+        let result = f();
+        let counts = coverage::stop_collecting().unwrap_or_default();
+
+        let mut exec_map = self.exec_map.lock().unwrap();
+        for ((module_id, function_name), pcs) in counts {
+            for (pc, count) in pcs {
+                exec_map.insert_multi(
+                    *module_id.address(),
+                    module_id.name().to_owned(),
+                    function_name.clone(),
+                    u64::from(pc),
+                    count,
+                );
+            }
+        }
+        result
+    }
+}
+
+impl Drop for CoverageTracker {
+    fn drop(&mut self) {
+        let exec_map = match self.exec_map.get_mut() {
+            Ok(exec_map) => mem::replace(exec_map, ExecCoverageMap::new(String::new())),
+            Err(_) => return,
+        };
+        // Executors which never ran the VM have nothing to report
+        if exec_map.module_maps.is_empty() {
+            return;
+        }
+        let mut exec_maps = BTreeMap::new();
+        exec_maps.insert(exec_map.exec_id.clone(), exec_map);
+        let result = output_map_to_file(&self.map_path, &CoverageMap { exec_maps });
+        if let Err(err) = result {
+            eprintln!(
+                "Failed to write the coverage map {}: {}",
+                self.map_path.display(),
+                err
+            );
+        }
+    }
+}
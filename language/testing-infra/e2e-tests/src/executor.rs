@@ -13,6 +13,7 @@ use std::{
 
 use crate::{
     account::{Account, AccountData},
+    coverage::CoverageTracker,
     data_store::{FakeDataStore, GENESIS_CHANGE_SET, GENESIS_CHANGE_SET_FRESH},
     golden_outputs::GoldenOutputs,
     keygen::KeyGen,
@@ -78,6 +79,7 @@ pub struct FakeExecutor {
     block_time: u64,
     executed_output: Option<GoldenOutputs>,
     trace_dir: Option<PathBuf>,
+    coverage: Option<CoverageTracker>,
     rng: KeyGen,
 }
 
@@ -89,6 +91,7 @@ impl FakeExecutor {
             block_time: 0,
             executed_output: None,
             trace_dir: None,
+            coverage: CoverageTracker::from_env(),
             rng: KeyGen::from_seed(RNG_SEED),
         };
         executor.apply_write_set(write_set);
@@ -137,6 +140,7 @@ impl FakeExecutor {
             block_time: 0,
             executed_output: None,
             trace_dir: None,
+            coverage: CoverageTracker::from_env(),
             rng: KeyGen::from_seed(RNG_SEED),
         }
     }
@@ -345,7 +349,7 @@ impl FakeExecutor {
             }
         }
 
-        let output = DiemVM::execute_block(txn_block, &self.data_store);
+        let output = self.track_coverage(|| DiemVM::execute_block(txn_block, &self.data_store));
         if let Some(logger) = &self.executed_output {
             logger.log(format!("{:?}\n", output).as_str());
         }
@@ -385,6 +389,15 @@ impl FakeExecutor {
             .expect("A block with one transaction should have one output")
     }
 
+    /// Runs `f`, counting the instructions the VM executes for the coverage of the test, if
+    /// tracked.
+    fn track_coverage<T>(&self, f: impl FnOnce() -> T) -> T {
+        match &self.coverage {
+            Some(coverage) => coverage.track(f),
+            None => f(),
+        }
+    }
+
     fn trace<P: AsRef<Path>, T: Serialize>(dir: P, item: &T) -> usize {
         let dir = dir.as_ref();
         let seq = fs::read_dir(dir).expect("Unable to read trace dir").count();
@@ -500,7 +513,7 @@ impl FakeExecutor {
         type_params: Vec<TypeTag>,
         args: Vec<Vec<u8>>,
     ) {
-        let write_set = {
+        let write_set = self.track_coverage(|| {
             let mut gas_status = GasStatus::new_unmetered();
             let vm = MoveVM::new();
             let remote_view = RemoteStorage::new(&self.data_store);
@@ -527,7 +540,7 @@ impl FakeExecutor {
             let (writeset, _events) = convert_changeset_and_events(changeset, events)
                 .expect("Failed to generate writeset");
             writeset
-        };
+        });
         self.data_store.add_write_set(&write_set);
     }
 
@@ -543,8 +556,8 @@ impl FakeExecutor {
         let remote_view = RemoteStorage::new(&self.data_store);
         let mut session = vm.new_session(&remote_view);
         let log_context = NoContextLog::new();
-        session
-            .execute_function(
+        self.track_coverage(|| {
+            session.execute_function(
                 &Self::module(module_name),
                 &Self::name(function_name),
                 type_params,
@@ -552,7 +565,8 @@ impl FakeExecutor {
                 &mut gas_status,
                 &log_context,
             )
-            .map_err(|e| e.into_vm_status())?;
+        })
+        .map_err(|e| e.into_vm_status())?;
         let (changeset, events) = session.finish().expect("Failed to generate txn effects");
         let (writeset, _events) =
             convert_changeset_and_events(changeset, events).expect("Failed to generate writeset");
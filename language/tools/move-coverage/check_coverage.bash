@@ -3,11 +3,12 @@
 # Copyright (c) The Diem Core Contributors
 # SPDX-License-Identifier: Apache-2.0
 
-TRACE_PATH=$HOME/trace
+# The test runners write the coverage map of each test to this directory.
+COVERAGE_DIR=$HOME/coverage
 
-[ ! -e  "$TRACE_PATH" ] || rm -f "$TRACE_PATH"
+[ ! -e  "$COVERAGE_DIR" ] || rm -rf "$COVERAGE_DIR"
 
-export MOVE_VM_TRACE=$TRACE_PATH
+export MOVE_COVERAGE_DIR=$COVERAGE_DIR
 
 echo "Rebuilding stdlib..."
 pushd ../../diem-framework || exit 1
@@ -45,24 +46,18 @@ rm -rf build
 cargo run --bin move-build -- ../diem-framework/modules -m
 popd || exit 1
 
-echo "---------------------------------------------------------------------------"
-echo "Converting trace file..."
-echo "---------------------------------------------------------------------------"
-cargo run --bin move-trace-conversion -- -f "$TRACE_PATH" -o trace.mvcov
-
 echo "---------------------------------------------------------------------------"
 echo "Producing coverage summaries..."
 echo "---------------------------------------------------------------------------"
-cargo run --bin coverage-summaries -- -t trace.mvcov -s ../../diem-framework/releases/artifacts/current/modules
+unset MOVE_COVERAGE_DIR
+cargo run --bin coverage-summaries -- -t "$COVERAGE_DIR" -s ../../diem-framework/releases/artifacts/current/modules
 
 echo "==========================================================================="
 echo "You can check source coverage for a module by running:"
-echo "> cargo run --bin source-coverage -- -t trace.mvcov -b ../../move-lang/build/modules/<LOOK_FOR_MODULE_HERE>.mv -s ../../diem-framework/modules/<SOURCE_MODULE>.move"
+echo "> cargo run --bin source-coverage -- -t $COVERAGE_DIR -b ../../move-lang/build/modules/<LOOK_FOR_MODULE_HERE>.mv -s ../../diem-framework/modules/<SOURCE_MODULE>.move"
 echo "---------------------------------------------------------------------------"
 echo "You can can also get a finer-grained coverage summary for each function by running:"
-echo "> cargo run --bin coverage-summaries -- -t trace.mvcov -s ../../diem-framework/releases/artifacts/current/stdlib.mv"
+echo "> cargo run --bin coverage-summaries -- -t $COVERAGE_DIR -s ../../diem-framework/releases/artifacts/current/modules -f"
 echo "==========================================================================="
 
-unset MOVE_VM_TRACE
-
 echo "DONE"
@@ -21,7 +21,7 @@ use structopt::StructOpt;
     about = "Creates a coverage summary from the trace data collected from the Move VM"
 )]
 struct Args {
-    /// The path to the coverage map or trace file
+    /// The path to the trace file, or to the coverage map or a directory of coverage maps
     #[structopt(long = "input-trace-path", short = "t")]
     pub input_trace_path: String,
     /// Whether the passed-in file is a raw trace file or a serialized coverage map
@@ -158,7 +158,7 @@ fn main() {
         let coverage_map = if args.is_raw_trace_file {
             CoverageMap::from_trace_file(&input_trace_path)
         } else {
-            CoverageMap::from_binary_file(&input_trace_path)
+            CoverageMap::from_binary_path(&input_trace_path)
         };
         let unified_exec_map = coverage_map.to_unified_exec_map();
         if !args.csv_output {
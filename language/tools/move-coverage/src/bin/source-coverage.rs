@@ -20,7 +20,7 @@ use structopt::StructOpt;
     about = "Annotate Move Source Code with Coverage Information"
 )]
 struct Args {
-    /// The path to the coverage map or trace file
+    /// The path to the trace file, or to the coverage map or a directory of coverage maps
     #[structopt(long = "input-trace-path", short = "t")]
     pub input_trace_path: String,
     /// Whether the passed-in file is a raw trace file or a serialized coverage map
@@ -43,7 +43,7 @@ fn main() {
     let coverage_map = if args.is_raw_trace_file {
         CoverageMap::from_trace_file(&args.input_trace_path)
     } else {
-        CoverageMap::from_binary_file(&args.input_trace_path)
+        CoverageMap::from_binary_path(&args.input_trace_path)
     };
 
     let bytecode_bytes = fs::read(&args.module_binary_path).expect("Unable to read bytecode file");
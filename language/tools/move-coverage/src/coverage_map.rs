@@ -12,7 +12,7 @@ use move_core_types::{
 use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
-    fs::File,
+    fs::{self, File},
     io::{BufRead, BufReader, Read, Write},
     path::Path,
 };
@@ -105,6 +105,32 @@ impl CoverageMap {
             .unwrap()
     }
 
+    /// Takes in a directory of files containing serialized coverage maps, such as those written
+    /// by the test runners, and returns them merged into a coverage map.
+    pub fn from_binary_dir<P: AsRef<Path>>(dirname: P) -> Self {
+        let mut coverage_map = CoverageMap {
+            exec_maps: BTreeMap::new(),
+        };
+        for entry in fs::read_dir(dirname).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_file() {
+                coverage_map
+                    .exec_maps
+                    .extend(Self::from_binary_file(path).exec_maps);
+            }
+        }
+        coverage_map
+    }
+
+    /// Takes in either a file containing a serialized coverage map, or a directory of them.
+    pub fn from_binary_path<P: AsRef<Path>>(path: P) -> Self {
+        if path.as_ref().is_dir() {
+            Self::from_binary_dir(path)
+        } else {
+            Self::from_binary_file(path)
+        }
+    }
+
     // add entries in a cascading manner
     pub fn insert(
         &mut self,
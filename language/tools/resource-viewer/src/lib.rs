@@ -199,7 +199,8 @@ impl<'a> MoveValueAnnotator<'a> {
                         .collect::<Result<_>>()?,
                 ),
                 _ => AnnotatedMoveValue::Vector(
-                    ty.type_tag().unwrap(),
+                    ty.type_tag()
+                        .map_err(|e| e.finish(Location::Undefined).into_vm_status())?,
                     a.iter()
                         .map(|v| self.annotate_value(v, ty.as_ref()))
                         .collect::<Result<_>>()?,
@@ -330,3 +331,151 @@ impl MoveStorage for NullStateView {
         Ok(None)
     }
 }
+
+impl AnnotatedMoveValue {
+    /// JSON rendering of the value. `u128` values are rendered as strings since they do not fit
+    /// in a JSON number, byte vectors as hex strings and addresses as `0x` prefixed hex.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value;
+        match self {
+            AnnotatedMoveValue::Bool(b) => Value::Bool(*b),
+            AnnotatedMoveValue::U8(v) => Value::from(*v),
+            AnnotatedMoveValue::U64(v) => Value::from(*v),
+            AnnotatedMoveValue::U128(v) => Value::String(v.to_string()),
+            AnnotatedMoveValue::Address(a) => {
+                Value::String(format!("0x{}", a.short_str_lossless()))
+            }
+            AnnotatedMoveValue::Vector(_, v) => {
+                Value::Array(v.iter().map(AnnotatedMoveValue::to_json).collect())
+            }
+            AnnotatedMoveValue::Bytes(v) => Value::String(hex::encode(&v)),
+            AnnotatedMoveValue::Struct(s) => s.to_json(),
+        }
+    }
+
+    /// JSON rendering of the value as `to_json`, with each struct rendered as an object holding
+    /// its type, type arguments included, and its fields, so that the values of generic and
+    /// nested structs can be told apart.
+    pub fn to_annotated_json(&self) -> serde_json::Value {
+        match self {
+            AnnotatedMoveValue::Vector(_, v) => serde_json::Value::Array(
+                v.iter()
+                    .map(AnnotatedMoveValue::to_annotated_json)
+                    .collect(),
+            ),
+            AnnotatedMoveValue::Struct(s) => s.to_annotated_json(),
+            _ => self.to_json(),
+        }
+    }
+}
+
+impl AnnotatedMoveStruct {
+    /// JSON object mapping each field name to its value.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::Value::Object(
+            self.value
+                .iter()
+                .map(|(field_name, v)| (field_name.to_string(), v.to_json()))
+                .collect(),
+        )
+    }
+
+    /// JSON object holding the type of the struct and its fields, each annotated in turn.
+    pub fn to_annotated_json(&self) -> serde_json::Value {
+        let fields = self
+            .value
+            .iter()
+            .map(|(field_name, v)| (field_name.to_string(), v.to_annotated_json()))
+            .collect();
+        serde_json::json!({
+            "type": self.type_.to_string(),
+            "fields": serde_json::Value::Object(fields),
+        })
+    }
+}
+
+impl AnnotatedAccountStateBlob {
+    /// JSON object mapping the type of each resource to its fields.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::Value::Object(
+            self.0
+                .iter()
+                .map(|(tag, resource)| (tag.to_string(), resource.to_json()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn struct_tag(name: &str, type_params: Vec<TypeTag>) -> StructTag {
+        StructTag {
+            address: AccountAddress::from_hex_literal("0x1").unwrap(),
+            module: Identifier::new("M").unwrap(),
+            name: Identifier::new(name).unwrap(),
+            type_params,
+        }
+    }
+
+    #[test]
+    fn test_annotated_json() {
+        let coin_tag = struct_tag("Coin", vec![TypeTag::U64]);
+        let coin = |value| {
+            AnnotatedMoveValue::Struct(AnnotatedMoveStruct {
+                abilities: AbilitySet::EMPTY | Ability::Store,
+                type_: coin_tag.clone(),
+                value: vec![(
+                    Identifier::new("value").unwrap(),
+                    AnnotatedMoveValue::U64(value),
+                )],
+            })
+        };
+        let event = AnnotatedMoveValue::Struct(AnnotatedMoveStruct {
+            abilities: AbilitySet::EMPTY | Ability::Drop | Ability::Store,
+            type_: struct_tag("Paid", vec![]),
+            value: vec![
+                (
+                    Identifier::new("coins").unwrap(),
+                    AnnotatedMoveValue::Vector(
+                        TypeTag::Struct(coin_tag.clone()),
+                        vec![coin(1), coin(2)],
+                    ),
+                ),
+                (
+                    Identifier::new("total").unwrap(),
+                    AnnotatedMoveValue::U128(3),
+                ),
+                (
+                    Identifier::new("metadata").unwrap(),
+                    AnnotatedMoveValue::Bytes(vec![0xca, 0xfe]),
+                ),
+            ],
+        });
+
+        assert_eq!(
+            event.to_json(),
+            json!({
+                "coins": [{"value": 1}, {"value": 2}],
+                "total": "3",
+                "metadata": "cafe",
+            })
+        );
+        assert_eq!(
+            event.to_annotated_json(),
+            json!({
+                "type": "0x1::M::Paid",
+                "fields": {
+                    "coins": [
+                        {"type": "0x1::M::Coin<U64>", "fields": {"value": 1}},
+                        {"type": "0x1::M::Coin<U64>", "fields": {"value": 2}},
+                    ],
+                    "total": "3",
+                    "metadata": "cafe",
+                },
+            })
+        );
+    }
+}
@@ -7,9 +7,11 @@
 //! cargo run -p transaction-builder-generator -- --help
 //! '''
 
+use diem_types::{account_address::AccountAddress, transaction::ScriptABI};
+use move_core_types::language_storage::ModuleId;
 use serde_generate as serdegen;
 use serde_reflection::Registry;
-use std::path::PathBuf;
+use std::{collections::HashSet, path::PathBuf};
 use structopt::{clap::arg_enum, StructOpt};
 use transaction_builder_generator as buildgen;
 
@@ -35,6 +37,20 @@ struct Options {
     /// Path to the directory containing ABI files in BCS encoding.
     abi_directories: Vec<PathBuf>,
 
+    /// Also generate builders for the script functions of the modules published on chain under
+    /// the accounts given with `--module-address`, as read from this JSON-RPC endpoint.
+    #[structopt(long, requires = "module-address")]
+    json_rpc_url: Option<String>,
+
+    /// Account under which modules are published on chain, such as `0x1` (see `--json-rpc-url`).
+    #[structopt(
+        long,
+        requires = "json-rpc-url",
+        number_of_values = 1,
+        parse(try_from_str = AccountAddress::from_hex_literal)
+    )]
+    module_address: Vec<AccountAddress>,
+
     /// Language for code generation.
     #[structopt(long, possible_values = &Language::variants(), case_insensitive = true, default_value = "Python3")]
     language: Language,
@@ -74,10 +90,31 @@ struct Options {
     with_custom_diem_code: Vec<PathBuf>,
 }
 
+/// What identifies a script function, or a transaction script.
+fn abi_key(abi: &ScriptABI) -> (Option<ModuleId>, String) {
+    match abi {
+        ScriptABI::ScriptFunction(abi) => (Some(abi.module_name().clone()), abi.name().to_string()),
+        ScriptABI::TransactionScript(abi) => (None, abi.name().to_string()),
+    }
+}
+
 fn main() {
     let options = Options::from_args();
-    let abis =
+    let mut abis =
         buildgen::read_abis(&options.abi_directories).expect("Failed to read ABI in directory");
+    if let Some(url) = &options.json_rpc_url {
+        let onchain_abis = buildgen::onchain::read_onchain_abis(url, &options.module_address)
+            .expect("Failed to read ABIs on chain");
+        // ABI files, which keep the names of arguments and doc comments, take precedence.
+        let known: HashSet<_> = abis.iter().map(abi_key).collect();
+        abis.extend(
+            onchain_abis
+                .into_iter()
+                .filter(|abi| !known.contains(&abi_key(abi))),
+        );
+        #[allow(clippy::unnecessary_sort_by)]
+        abis.sort_by(|a, b| a.name().cmp(b.name()));
+    }
 
     let install_dir = match options.target_source_dir {
         None => {
@@ -13,6 +13,8 @@ pub mod csharp;
 pub mod golang;
 /// Support for code-generation in Java 8.
 pub mod java;
+/// Reading the ABIs of script functions published on chain.
+pub mod onchain;
 /// Support for code-generation in Python 3.
 pub mod python3;
 /// Support for code-generation in Rust.
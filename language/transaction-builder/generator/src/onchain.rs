@@ -0,0 +1,204 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! ABIs of the script functions of modules published on chain, so that modules published by third
+//! parties get transaction builders without their ABI files being checked in.
+//!
+//! Compiled modules don't keep the names of parameters nor doc comments, so arguments are named
+//! `arg0`, `arg1`, ..., type arguments `t0`, `t1`, ..., and builders are documented with the
+//! function they call.
+
+use anyhow::{format_err, Result};
+use diem_client::BlockingClient;
+use diem_types::{
+    account_address::AccountAddress,
+    account_state::AccountState,
+    account_state_blob::AccountStateBlob,
+    transaction::{ArgumentABI, ScriptABI, ScriptFunctionABI, TypeArgumentABI},
+};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{FunctionDefinition, SignatureToken, Visibility},
+    CompiledModule,
+};
+use move_core_types::language_storage::TypeTag;
+use std::convert::TryFrom;
+
+/// Read the ABIs of the script functions of all the modules published under the given accounts,
+/// from the JSON-RPC endpoint at `url`.
+pub fn read_onchain_abis(url: &str, addresses: &[AccountAddress]) -> Result<Vec<ScriptABI>> {
+    let client = BlockingClient::new(url);
+    let mut abis = Vec::new();
+    for address in addresses {
+        for module in fetch_modules(&client, *address)? {
+            abis.extend(script_function_abis(&module));
+        }
+    }
+    // Sort scripts by alphabetical order, as `read_abis` does.
+    #[allow(clippy::unnecessary_sort_by)]
+    abis.sort_by(|a, b| a.name().cmp(b.name()));
+    Ok(abis)
+}
+
+fn fetch_modules(client: &BlockingClient, address: AccountAddress) -> Result<Vec<CompiledModule>> {
+    let account_state = client
+        .get_account_state_with_proof(address, None, None)?
+        .into_inner();
+    let blob = account_state
+        .blob
+        .ok_or_else(|| format_err!("No account at address {}", address))?;
+    let account_state_blob = AccountStateBlob::from(bcs::from_bytes::<Vec<u8>>(&blob)?);
+    let account_state = AccountState::try_from(&account_state_blob)?;
+    account_state
+        .get_modules()
+        .map(|bytes| {
+            CompiledModule::deserialize(bytes).map_err(|err| {
+                format_err!("Failed to deserialize module at {}: {:?}", address, err)
+            })
+        })
+        .collect()
+}
+
+/// The ABIs of the script functions of `module`. Functions taking arguments which can't be passed
+/// in a transaction, such as structs, are skipped.
+pub fn script_function_abis(module: &CompiledModule) -> Vec<ScriptABI> {
+    module
+        .function_defs()
+        .iter()
+        .filter(|def| def.visibility == Visibility::Script)
+        .filter_map(|def| script_function_abi(module, def))
+        .collect()
+}
+
+fn script_function_abi(module: &CompiledModule, def: &FunctionDefinition) -> Option<ScriptABI> {
+    let handle = module.function_handle_at(def.function);
+    let name = module.identifier_at(handle.name).to_string();
+    let module_id = module.self_id();
+    let doc = format!(
+        "Call the script function `0x{}::{}::{}` published on chain.",
+        module_id.address().short_str_lossless(),
+        module_id.name(),
+        name
+    );
+    let ty_args = (0..handle.type_parameters.len())
+        .map(|index| TypeArgumentABI::new(format!("t{}", index)))
+        .collect();
+    // Signers are passed by the transaction itself, ahead of the arguments.
+    let args = module
+        .signature_at(handle.parameters)
+        .0
+        .iter()
+        .skip_while(|token| is_signer(token))
+        .enumerate()
+        .map(|(index, token)| Some(ArgumentABI::new(format!("arg{}", index), type_tag(token)?)))
+        .collect::<Option<_>>()?;
+    Some(ScriptABI::ScriptFunction(ScriptFunctionABI::new(
+        name, module_id, doc, ty_args, args,
+    )))
+}
+
+fn is_signer(token: &SignatureToken) -> bool {
+    match token {
+        SignatureToken::Signer => true,
+        SignatureToken::Reference(token) => matches!(token.as_ref(), SignatureToken::Signer),
+        _ => false,
+    }
+}
+
+/// The type of a transaction argument of type `token`, if transaction builders can pass one.
+fn type_tag(token: &SignatureToken) -> Option<TypeTag> {
+    Some(match token {
+        SignatureToken::Bool => TypeTag::Bool,
+        SignatureToken::U8 => TypeTag::U8,
+        SignatureToken::U64 => TypeTag::U64,
+        SignatureToken::U128 => TypeTag::U128,
+        SignatureToken::Address => TypeTag::Address,
+        SignatureToken::Vector(token) if matches!(token.as_ref(), SignatureToken::U8) => {
+            TypeTag::Vector(Box::new(TypeTag::U8))
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use move_binary_format::file_format::{
+        basic_test_module, AbilitySet, CodeUnit, CompiledModuleMut, FunctionHandle,
+        FunctionHandleIndex, IdentifierIndex, ModuleHandleIndex, Signature, SignatureIndex,
+        StructHandleIndex,
+    };
+    use move_core_types::identifier::Identifier;
+
+    fn add_script_function(
+        module: &mut CompiledModuleMut,
+        name: &str,
+        parameters: Vec<SignatureToken>,
+        type_parameters: usize,
+    ) {
+        module.signatures.push(Signature(parameters));
+        module.identifiers.push(Identifier::new(name).unwrap());
+        module.function_handles.push(FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex(module.identifiers.len() as u16 - 1),
+            parameters: SignatureIndex(module.signatures.len() as u16 - 1),
+            return_: SignatureIndex(0),
+            type_parameters: vec![AbilitySet::EMPTY; type_parameters],
+        });
+        module.function_defs.push(FunctionDefinition {
+            function: FunctionHandleIndex(module.function_handles.len() as u16 - 1),
+            visibility: Visibility::Script,
+            acquires_global_resources: vec![],
+            code: Some(CodeUnit {
+                locals: SignatureIndex(0),
+                code: vec![],
+            }),
+        });
+    }
+
+    #[test]
+    fn test_script_function_abis() {
+        let mut module = basic_test_module();
+        add_script_function(
+            &mut module,
+            "pay",
+            vec![
+                SignatureToken::Signer,
+                SignatureToken::Address,
+                SignatureToken::U64,
+                SignatureToken::Vector(Box::new(SignatureToken::U8)),
+            ],
+            1,
+        );
+        // Structs can't be passed in transactions.
+        add_script_function(
+            &mut module,
+            "pay_bar",
+            vec![
+                SignatureToken::Signer,
+                SignatureToken::Struct(StructHandleIndex(0)),
+            ],
+            0,
+        );
+        let module = module.freeze().unwrap();
+
+        // The private function `foo` has no ABI either.
+        let abis = script_function_abis(&module);
+        assert_eq!(abis.len(), 1);
+        let abi = match &abis[0] {
+            ScriptABI::ScriptFunction(abi) => abi,
+            ScriptABI::TransactionScript(_) => panic!("Expected a script function ABI"),
+        };
+        assert_eq!(abi.name(), "pay");
+        assert_eq!(abi.module_name(), &module.self_id());
+        assert_eq!(abi.ty_args(), &[TypeArgumentABI::new("t0".to_string())]);
+        assert_eq!(
+            abi.args(),
+            &[
+                ArgumentABI::new("arg0".to_string(), TypeTag::Address),
+                ArgumentABI::new("arg1".to_string(), TypeTag::U64),
+                ArgumentABI::new("arg2".to_string(), TypeTag::Vector(Box::new(TypeTag::U8))),
+            ]
+        );
+    }
+}
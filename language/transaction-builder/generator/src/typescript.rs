@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::common;
-use diem_types::transaction::{ArgumentABI, ScriptABI, TransactionScriptABI, TypeArgumentABI};
-use move_core_types::language_storage::TypeTag;
+use diem_types::transaction::{
+    ArgumentABI, ScriptABI, ScriptFunctionABI, TransactionScriptABI, TypeArgumentABI,
+};
+use move_core_types::{account_address::AccountAddress, language_storage::TypeTag};
 use serde_generate::{
     indent::{IndentConfig, IndentedWriter},
     typescript, CodeGeneratorConfig,
@@ -17,11 +19,10 @@ use std::{
 };
 /// Output transaction builders and decoders in TypeScript for the given ABIs.
 pub fn output(out: &mut dyn Write, abis: &[ScriptABI]) -> Result<()> {
-    // TODO(#7876): implement typescript support for script functions
-    // TODO(#7973): encode/decode transaction args in script functions with BCS
-    let abis = common::transaction_script_abis(abis);
-    write_script_calls(out, &abis)?;
-    write_helpers(out, &abis)
+    let tx_script_abis = common::transaction_script_abis(abis);
+    let script_function_abis = common::script_function_abis(abis);
+    write_script_calls(out, &tx_script_abis, &script_function_abis)?;
+    write_helpers(out, &tx_script_abis, &script_function_abis)
 }
 
 fn write_stdlib_helper_interfaces(emitter: &mut TypeScriptEmitter<&mut dyn Write>) -> Result<()> {
@@ -69,11 +70,15 @@ export enum Types {{
 }
 
 /// Output transaction helper functions for the given ABIs.
-fn write_helpers(out: &mut dyn Write, abis: &[TransactionScriptABI]) -> Result<()> {
+fn write_helpers(
+    out: &mut dyn Write,
+    abis: &[TransactionScriptABI],
+    script_function_abis: &[ScriptFunctionABI],
+) -> Result<()> {
     let mut emitter = TypeScriptEmitter {
         out: IndentedWriter::new(out, IndentConfig::Space(2)),
     };
-    emitter.output_preamble()?;
+    emitter.output_preamble(!script_function_abis.is_empty())?;
     write_stdlib_helper_interfaces(&mut emitter)?;
     writeln!(emitter.out, "\nexport class Stdlib {{")?;
     emitter.out.indent();
@@ -82,9 +87,23 @@ fn write_helpers(out: &mut dyn Write, abis: &[TransactionScriptABI]) -> Result<(
     for abi in abis {
         emitter.output_script_encoder_function(abi)?;
     }
+    for abi in script_function_abis {
+        emitter.output_script_function_encoder_function(abi)?;
+    }
     for abi in abis {
         emitter.output_script_decoder_function(abi)?;
     }
+    for abi in script_function_abis {
+        emitter.output_script_function_decoder_function(abi)?;
+    }
+    let script_function_abis: Vec<_> = script_function_abis
+        .iter()
+        .cloned()
+        .map(ScriptABI::ScriptFunction)
+        .collect();
+    for type_tag in common::get_required_helper_types(&script_function_abis) {
+        emitter.output_argument_helpers(type_tag)?;
+    }
 
     for abi in abis {
         emitter.output_code_constant(abi)?;
@@ -120,9 +139,13 @@ fn write_helpers(out: &mut dyn Write, abis: &[TransactionScriptABI]) -> Result<(
     writeln!(emitter.out, "}};")
 }
 
-fn write_script_calls(out: &mut dyn Write, abis: &[TransactionScriptABI]) -> Result<()> {
+fn write_script_calls(
+    out: &mut dyn Write,
+    abis: &[TransactionScriptABI],
+    script_function_abis: &[ScriptFunctionABI],
+) -> Result<()> {
     let external_definitions = crate::common::get_external_definitions("diemTypes");
-    let script_registry: BTreeMap<_, _> = vec![(
+    let mut script_registry: BTreeMap<_, _> = vec![(
         "ScriptCall".to_string(),
         common::make_abi_enum_container(
             abis.iter()
@@ -145,6 +168,27 @@ fn write_script_calls(out: &mut dyn Write, abis: &[TransactionScriptABI]) -> Res
         vec!["ScriptCall".to_string()],
         "Structured representation of a call into a known Move script.".into(),
     );
+    if !script_function_abis.is_empty() {
+        script_registry.insert(
+            "ScriptFunctionCall".to_string(),
+            common::make_abi_enum_container(
+                script_function_abis
+                    .iter()
+                    .cloned()
+                    .map(ScriptABI::ScriptFunction)
+                    .collect::<Vec<_>>()
+                    .as_slice(),
+            ),
+        );
+        for abi in script_function_abis {
+            let paths = vec!["ScriptFunctionCall".to_string(), abi.name().to_camel_case()];
+            comments.insert(paths, crate::common::prepare_doc_string(abi.doc()));
+        }
+        comments.insert(
+            vec!["ScriptFunctionCall".to_string()],
+            "Structured representation of a call into a known Move script function.".into(),
+        );
+    }
 
     let config = CodeGeneratorConfig::new("StdLib".to_string())
         .with_comments(comments)
@@ -166,13 +210,18 @@ impl<T> TypeScriptEmitter<T>
 where
     T: Write,
 {
-    fn output_preamble(&mut self) -> Result<()> {
+    fn output_preamble(&mut self, with_deserializer: bool) -> Result<()> {
         writeln!(
             self.out,
             r#"
-import {{ BcsSerializer }} from '../bcs/bcsSerializer';
+import {{ BcsSerializer }} from '../bcs/bcsSerializer';{}
 
 "#,
+            if with_deserializer {
+                "\nimport { BcsDeserializer } from '../bcs/bcsDeserializer';"
+            } else {
+                ""
+            }
         )?;
         Ok(())
     }
@@ -205,6 +254,41 @@ return new DiemTypes.Script(code, tyArgs, args);"#,
         writeln!(self.out, "}}")
     }
 
+    fn output_script_function_encoder_function(&mut self, abi: &ScriptFunctionABI) -> Result<()> {
+        writeln!(
+            self.out,
+            "\n{}static encode{}ScriptFunction({}): DiemTypes.TransactionPayload {{",
+            Self::quote_doc(abi.doc()),
+            abi.name().to_camel_case(),
+            [
+                Self::quote_type_parameters(abi.ty_args()),
+                Self::quote_parameters(abi.args()),
+            ]
+            .concat()
+            .join(", ")
+        )?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            r#"const tyArgs: Seq<DiemTypes.TypeTag> = [{}];
+const args: Seq<bytes> = [{}];
+const script = new DiemTypes.ScriptFunction(
+  new DiemTypes.ModuleId({}, new DiemTypes.Identifier('{}')),
+  new DiemTypes.Identifier('{}'),
+  tyArgs,
+  args,
+);
+return new DiemTypes.TransactionPayloadVariantScriptFunction(script);"#,
+            Self::quote_type_arguments(abi.ty_args()),
+            Self::quote_serialized_arguments(abi.args()),
+            Self::quote_address(abi.module_name().address()),
+            abi.module_name().name(),
+            abi.name(),
+        )?;
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
     fn output_script_decoder_function(&mut self, abi: &TransactionScriptABI) -> Result<()> {
         writeln!(
             self.out,
@@ -253,6 +337,106 @@ return new DiemTypes.Script(code, tyArgs, args);"#,
         Ok(())
     }
 
+    fn output_script_function_decoder_function(&mut self, abi: &ScriptFunctionABI) -> Result<()> {
+        writeln!(
+            self.out,
+            "\nstatic decode{}ScriptFunction(payload: DiemTypes.TransactionPayload): ScriptFunctionCallVariant{0} {{",
+            abi.name().to_camel_case(),
+        )?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            r#"if (!(payload instanceof DiemTypes.TransactionPayloadVariantScriptFunction)) {{
+  throw new Error("Transaction payload not a script function payload");
+}}
+const script = payload.value;
+if (script.module.name.value !== '{}' || script.function.value !== '{}') {{
+  throw new Error("Script function payload not a call to {}::{}");
+}}"#,
+            abi.module_name().name(),
+            abi.name(),
+            abi.module_name().name(),
+            abi.name(),
+        )?;
+        let mut all_args: Vec<String> = Vec::new();
+        all_args.extend(
+            abi.ty_args()
+                .iter()
+                .enumerate()
+                .map(|(idx, _)| format!("script.ty_args[{}]", idx)),
+        );
+        all_args.extend(abi.args().iter().enumerate().map(|(idx, arg)| {
+            format!(
+                "Stdlib.decode{}Argument(script.args[{}])",
+                common::mangle_type(arg.type_tag()).to_camel_case(),
+                idx
+            )
+        }));
+        writeln!(
+            self.out,
+            "return new ScriptFunctionCallVariant{}(",
+            abi.name().to_camel_case()
+        )?;
+        self.out.indent();
+        writeln!(self.out, "{}", all_args.join(",\n"))?;
+        self.out.unindent();
+        writeln!(self.out, ");")?;
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    /// Helpers to BCS-encode and decode the arguments of script functions of type `type_tag`.
+    fn output_argument_helpers(&mut self, type_tag: &TypeTag) -> Result<()> {
+        use TypeTag::*;
+        let (serialize, deserialize) = match type_tag {
+            Bool => (
+                "serializer.serializeBool(value)",
+                "deserializer.deserializeBool()",
+            ),
+            U8 => (
+                "serializer.serializeU8(value)",
+                "deserializer.deserializeU8()",
+            ),
+            U64 => (
+                "serializer.serializeU64(value)",
+                "deserializer.deserializeU64()",
+            ),
+            U128 => (
+                "serializer.serializeU128(value)",
+                "deserializer.deserializeU128()",
+            ),
+            Address => (
+                "value.serialize(serializer)",
+                "DiemTypes.AccountAddress.deserialize(deserializer)",
+            ),
+            Vector(type_tag) => match type_tag.as_ref() {
+                U8 => (
+                    "serializer.serializeBytes(value)",
+                    "deserializer.deserializeBytes()",
+                ),
+                _ => common::type_not_allowed(type_tag),
+            },
+            Struct(_) | Signer => common::type_not_allowed(type_tag),
+        };
+        let name = common::mangle_type(type_tag).to_camel_case();
+        let ty = Self::quote_type(type_tag);
+        writeln!(
+            self.out,
+            r#"
+private static encode{0}Argument(value: {1}): bytes {{
+  const serializer = new BcsSerializer();
+  {2};
+  return serializer.getBytes();
+}}
+
+private static decode{0}Argument(arg: bytes): {1} {{
+  const deserializer = new BcsDeserializer(arg);
+  return {3};
+}}"#,
+            name, ty, serialize, deserialize
+        )
+    }
+
     fn output_code_constant(&mut self, abi: &TransactionScriptABI) -> Result<()> {
         writeln!(
             self.out,
@@ -383,6 +567,31 @@ return new DiemTypes.Script(code, tyArgs, args);"#,
             .join(", ")
     }
 
+    fn quote_serialized_arguments(args: &[ArgumentABI]) -> String {
+        args.iter()
+            .map(|arg| {
+                format!(
+                    "Stdlib.encode{}Argument({})",
+                    common::mangle_type(arg.type_tag()).to_camel_case(),
+                    arg.name()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn quote_address(address: &AccountAddress) -> String {
+        format!(
+            "new DiemTypes.AccountAddress([{}])",
+            address
+                .to_vec()
+                .iter()
+                .map(|x| format!("[{}]", x))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
     fn quote_type(type_tag: &TypeTag) -> String {
         use TypeTag::*;
         match type_tag {
@@ -0,0 +1,143 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Policies deciding which parked transaction to drop when Mempool is full.
+use crate::core_mempool::{
+    index::{AccountTransactions, ParkingLotIndex, TxnPointer},
+    transaction::MempoolTransaction,
+};
+use diem_config::config::{MempoolConfig, MempoolEvictionPolicy};
+use diem_types::account_address::AccountAddress;
+use std::collections::HashMap;
+
+/// Picks the transaction to evict from the ParkingLot.
+/// Implementations must return one of the candidates yielded by `ParkingLotIndex::poppable_iter`
+/// (or `ParkingLotIndex::get_poppable`), so that evicting it never strands later parked transactions.
+pub(crate) trait EvictionPolicy: Send + Sync {
+    fn select_victim(
+        &self,
+        parking_lot: &ParkingLotIndex,
+        transactions: &HashMap<AccountAddress, AccountTransactions>,
+    ) -> Option<TxnPointer>;
+}
+
+pub(crate) fn build_eviction_policy(config: &MempoolConfig) -> Box<dyn EvictionPolicy> {
+    match config.eviction_policy {
+        MempoolEvictionPolicy::Random => Box::new(RandomEviction),
+        MempoolEvictionPolicy::OldestFirst => Box::new(OldestFirstEviction),
+        MempoolEvictionPolicy::LowestGasPriceFirst => Box::new(LowestGasPriceFirstEviction),
+        MempoolEvictionPolicy::LargestAccountFootprintFirst => {
+            Box::new(LargestAccountFootprintFirstEviction)
+        }
+        MempoolEvictionPolicy::PerSenderCap => Box::new(PerSenderCapEviction {
+            parking_lot_capacity_per_user: config.parking_lot_capacity_per_user,
+        }),
+    }
+}
+
+/// Evicts the highest parked transaction of a randomly chosen account.
+pub struct RandomEviction;
+
+impl EvictionPolicy for RandomEviction {
+    fn select_victim(
+        &self,
+        parking_lot: &ParkingLotIndex,
+        _transactions: &HashMap<AccountAddress, AccountTransactions>,
+    ) -> Option<TxnPointer> {
+        parking_lot.get_poppable()
+    }
+}
+
+/// Evicts the candidate that has been sitting in Mempool the longest.
+pub struct OldestFirstEviction;
+
+impl EvictionPolicy for OldestFirstEviction {
+    fn select_victim(
+        &self,
+        parking_lot: &ParkingLotIndex,
+        transactions: &HashMap<AccountAddress, AccountTransactions>,
+    ) -> Option<TxnPointer> {
+        // system expiration time is insertion time plus a fixed TTL, so the smallest one is the oldest
+        candidates(parking_lot, transactions)
+            .min_by_key(|(pointer, txn)| (txn.expiration_time, *pointer))
+            .map(|(pointer, _)| pointer)
+    }
+}
+
+/// Evicts the candidate paying the lowest gas unit price. Ties are broken by age, oldest first.
+pub struct LowestGasPriceFirstEviction;
+
+impl EvictionPolicy for LowestGasPriceFirstEviction {
+    fn select_victim(
+        &self,
+        parking_lot: &ParkingLotIndex,
+        transactions: &HashMap<AccountAddress, AccountTransactions>,
+    ) -> Option<TxnPointer> {
+        candidates(parking_lot, transactions)
+            .min_by_key(|(pointer, txn)| (txn.get_gas_price(), txn.expiration_time, *pointer))
+            .map(|(pointer, _)| pointer)
+    }
+}
+
+/// Evicts from the account holding the most transactions in Mempool, ready or not.
+pub struct LargestAccountFootprintFirstEviction;
+
+impl EvictionPolicy for LargestAccountFootprintFirstEviction {
+    fn select_victim(
+        &self,
+        parking_lot: &ParkingLotIndex,
+        transactions: &HashMap<AccountAddress, AccountTransactions>,
+    ) -> Option<TxnPointer> {
+        parking_lot
+            .poppable_iter()
+            .filter_map(|pointer| {
+                transactions
+                    .get(&pointer.0)
+                    .map(|account_txns| (account_txns.len(), pointer))
+            })
+            // prefer the smaller address on ties so the choice is deterministic
+            .max_by(|(len_a, ptr_a), (len_b, ptr_b)| len_a.cmp(len_b).then(ptr_b.cmp(ptr_a)))
+            .map(|(_, pointer)| pointer)
+    }
+}
+
+/// Evicts from the account parking the most transactions, as long as it parks more than its cap,
+/// so that a single sender can't crowd others out of the ParkingLot.
+/// Once no account is over the cap, evicts the candidate that has been sitting in Mempool the longest.
+pub struct PerSenderCapEviction {
+    parking_lot_capacity_per_user: usize,
+}
+
+impl EvictionPolicy for PerSenderCapEviction {
+    fn select_victim(
+        &self,
+        parking_lot: &ParkingLotIndex,
+        transactions: &HashMap<AccountAddress, AccountTransactions>,
+    ) -> Option<TxnPointer> {
+        parking_lot
+            .poppable_iter()
+            .map(|pointer| (parking_lot.account_size(&pointer.0), pointer))
+            .filter(|(parked, _)| *parked > self.parking_lot_capacity_per_user)
+            // prefer the smaller address on ties so the choice is deterministic
+            .max_by(|(parked_a, ptr_a), (parked_b, ptr_b)| {
+                parked_a.cmp(parked_b).then(ptr_b.cmp(ptr_a))
+            })
+            .map(|(_, pointer)| pointer)
+            .or_else(|| OldestFirstEviction.select_victim(parking_lot, transactions))
+    }
+}
+
+/// Resolves every eviction candidate in the ParkingLot to its transaction.
+fn candidates<'a>(
+    parking_lot: &'a ParkingLotIndex,
+    transactions: &'a HashMap<AccountAddress, AccountTransactions>,
+) -> impl Iterator<Item = (TxnPointer, &'a MempoolTransaction)> + 'a {
+    parking_lot
+        .poppable_iter()
+        .filter_map(move |(address, sequence_number)| {
+            transactions
+                .get(&address)
+                .and_then(|txns| txns.get(&sequence_number))
+                .map(|txn| ((address, sequence_number), txn))
+        })
+}
@@ -7,12 +7,13 @@ use crate::{
     counters,
     logging::{LogEntry, LogSchema},
 };
+use diem_config::config::MempoolOrderingPolicy;
 use diem_logger::prelude::*;
 use diem_types::{account_address::AccountAddress, transaction::GovernanceRole};
 use rand::seq::SliceRandom;
 use std::{
     cmp::Ordering,
-    collections::{btree_set::Iter, BTreeMap, BTreeSet, HashMap},
+    collections::{btree_map, btree_set::Iter, BTreeMap, BTreeSet, HashMap},
     iter::Rev,
     ops::Bound,
     time::Duration,
@@ -23,19 +24,24 @@ pub type AccountTransactions = BTreeMap<u64, MempoolTransaction>;
 /// PriorityIndex represents the main Priority Queue in Mempool.
 /// It's used to form the transaction block for Consensus.
 /// Transactions are ordered by gas price. Second level ordering is done by expiration time.
+/// With `MempoolOrderingPolicy::Fifo`, gas price is ignored, so transactions are ordered by the
+/// time they entered Mempool, as their system expiration time is that plus a fixed TTL.
+/// Either way, transactions of the same account are pulled into a block in sequence number order.
 ///
 /// We don't store the full content of transactions in the index.
 /// Instead we use `OrderedQueueKey` - logical reference to the transaction in the main store.
 pub struct PriorityIndex {
     data: BTreeSet<OrderedQueueKey>,
+    ordering_policy: MempoolOrderingPolicy,
 }
 
 pub type PriorityQueueIter<'a> = Rev<Iter<'a, OrderedQueueKey>>;
 
 impl PriorityIndex {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(ordering_policy: MempoolOrderingPolicy) -> Self {
         Self {
             data: BTreeSet::new(),
+            ordering_policy,
         }
     }
 
@@ -53,7 +59,10 @@ impl PriorityIndex {
 
     fn make_key(&self, txn: &MempoolTransaction) -> OrderedQueueKey {
         OrderedQueueKey {
-            gas_ranking_score: txn.ranking_score,
+            gas_ranking_score: match self.ordering_policy {
+                MempoolOrderingPolicy::GasPrice => txn.ranking_score,
+                MempoolOrderingPolicy::Fifo => 0,
+            },
             expiration_time: txn.expiration_time,
             address: txn.get_sender(),
             sequence_number: txn.get_sequence_number(),
@@ -269,9 +278,12 @@ pub struct ParkingLotIndex {
     // DS invariants:
     // 1. for each entry (account, txns) in `data`, `txns` is never empty
     // 2. for all accounts, data.get(account_indices.get(`account`)) == (account, sequence numbers of account's txns)
-    data: Vec<(AccountAddress, BTreeSet<u64>)>,
+    // along with the time since the Unix epoch each txn was parked
+    data: Vec<(AccountAddress, BTreeMap<u64, Duration>)>,
     account_indices: HashMap<AccountAddress, usize>,
     size: usize,
+    // total size in bytes of the parked txns
+    size_bytes: usize,
 }
 
 impl ParkingLotIndex {
@@ -280,6 +292,7 @@ impl ParkingLotIndex {
             data: vec![],
             account_indices: HashMap::new(),
             size: 0,
+            size_bytes: 0,
         }
     }
 
@@ -289,7 +302,13 @@ impl ParkingLotIndex {
         let is_new_entry = match self.account_indices.get(sender) {
             Some(index) => {
                 if let Some((_account, seq_nums)) = self.data.get_mut(*index) {
-                    seq_nums.insert(sequence_number)
+                    match seq_nums.entry(sequence_number) {
+                        btree_map::Entry::Vacant(entry) => {
+                            entry.insert(diem_infallible::duration_since_epoch());
+                            true
+                        }
+                        btree_map::Entry::Occupied(_) => false,
+                    }
                 } else {
                     counters::CORE_MEMPOOL_INVARIANT_VIOLATION_COUNT.inc();
                     error!(
@@ -301,7 +320,8 @@ impl ParkingLotIndex {
                 }
             }
             None => {
-                let seq_nums = [sequence_number].iter().cloned().collect::<BTreeSet<_>>();
+                let mut seq_nums = BTreeMap::new();
+                seq_nums.insert(sequence_number, diem_infallible::duration_since_epoch());
                 self.data.push((*sender, seq_nums));
                 self.account_indices.insert(*sender, self.data.len() - 1);
                 true
@@ -309,6 +329,7 @@ impl ParkingLotIndex {
         };
         if is_new_entry {
             self.size += 1;
+            self.size_bytes += txn.bytes;
         }
     }
 
@@ -316,8 +337,15 @@ impl ParkingLotIndex {
         let sender = &txn.txn.sender();
         if let Some(index) = self.account_indices.get(sender).cloned() {
             if let Some((_account, txns)) = self.data.get_mut(index) {
-                if txns.remove(&txn.txn.sequence_number()) {
+                if let Some(parked_time) = txns.remove(&txn.txn.sequence_number()) {
                     self.size -= 1;
+                    self.size_bytes -= txn.bytes;
+                    if let Some(residency) =
+                        diem_infallible::duration_since_epoch().checked_sub(parked_time)
+                    {
+                        counters::CORE_MEMPOOL_PARKING_LOT_RESIDENCY
+                            .observe(residency.as_secs_f64());
+                    }
                 }
 
                 // maintain DS invariant
@@ -339,20 +367,40 @@ impl ParkingLotIndex {
         self.account_indices
             .get(&account)
             .and_then(|idx| self.data.get(*idx))
-            .map_or(false, |(_account, txns)| txns.contains(seq_num))
+            .map_or(false, |(_account, txns)| txns.contains_key(seq_num))
     }
 
     /// Returns a random "non-ready" transaction (with highest sequence number for that account).
-    pub(crate) fn get_poppable(&mut self) -> Option<TxnPointer> {
+    pub(crate) fn get_poppable(&self) -> Option<TxnPointer> {
         let mut rng = rand::thread_rng();
         self.data
             .choose(&mut rng)
-            .and_then(|(sender, txns)| txns.iter().rev().next().map(|seq_num| (*sender, *seq_num)))
+            .and_then(|(sender, txns)| txns.keys().rev().next().map(|seq_num| (*sender, *seq_num)))
+    }
+
+    /// Returns the highest parked transaction of every account in the parking lot.
+    /// Only these can be evicted without leaving a gap in an account's parked sequence numbers.
+    pub(crate) fn poppable_iter(&self) -> impl Iterator<Item = TxnPointer> + '_ {
+        self.data.iter().filter_map(|(sender, txns)| {
+            txns.keys().rev().next().map(|seq_num| (*sender, *seq_num))
+        })
+    }
+
+    /// Returns how many transactions of `account` are parked.
+    pub(crate) fn account_size(&self, account: &AccountAddress) -> usize {
+        self.account_indices
+            .get(account)
+            .and_then(|idx| self.data.get(*idx))
+            .map_or(0, |(_account, txns)| txns.len())
     }
 
     pub(crate) fn size(&self) -> usize {
         self.size
     }
+
+    pub(crate) fn size_bytes(&self) -> usize {
+        self.size_bytes
+    }
 }
 
 /// Logical pointer to `MempoolTransaction`.
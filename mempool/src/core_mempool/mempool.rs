@@ -12,6 +12,7 @@ use crate::{
     },
     counters,
     logging::{LogEntry, LogSchema, TxnsLog},
+    PendingTransaction, RemovedTransaction,
 };
 use diem_config::config::NodeConfig;
 use diem_logger::prelude::*;
@@ -237,6 +238,7 @@ impl Mempool {
     pub(crate) fn gc(&mut self) {
         let now = SystemTime::now();
         self.transactions.gc_by_system_ttl(&self.metrics_cache);
+        self.transactions.gc_removed_transactions(now);
         self.metrics_cache.gc(now);
         self.sequence_number_cache.gc(now);
     }
@@ -266,6 +268,34 @@ impl Mempool {
         self.transactions.gen_snapshot(&self.metrics_cache)
     }
 
+    /// Number of transactions in mempool, and the most it can hold.
+    pub(crate) fn get_size_and_capacity(&self) -> (usize, usize) {
+        self.transactions.get_size_and_capacity()
+    }
+
+    /// Transactions of `account` in mempool, by sequence number.
+    pub(crate) fn get_account_transactions(
+        &self,
+        account: &AccountAddress,
+    ) -> Vec<PendingTransaction> {
+        self.transactions.get_account_transactions(account)
+    }
+
+    /// Why the transaction of `account` with `sequence_number` was removed, if it was recently.
+    pub(crate) fn get_removed_transaction(
+        &self,
+        account: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<RemovedTransaction> {
+        self.transactions
+            .get_removed_transaction(account, sequence_number)
+    }
+
+    /// All transactions in mempool, by sender and sequence number.
+    pub(crate) fn get_all_transactions(&self) -> Vec<PendingTransaction> {
+        self.transactions.get_all_transactions()
+    }
+
     #[cfg(test)]
     pub fn get_parking_lot_size(&self) -> usize {
         self.transactions.get_parking_lot_size()
@@ -17,6 +17,10 @@ pub struct MempoolTransaction {
     pub ranking_score: u64,
     pub timeline_state: TimelineState,
     pub governance_role: GovernanceRole,
+    // Size of the raw transaction, counted against the capacity in bytes of Mempool.
+    pub bytes: usize,
+    // Time since the Unix epoch the transaction entered Mempool.
+    pub insertion_time: Duration,
 }
 
 impl MempoolTransaction {
@@ -28,6 +32,7 @@ impl MempoolTransaction {
         timeline_state: TimelineState,
         governance_role: GovernanceRole,
     ) -> Self {
+        let bytes = txn.raw_txn_bytes_len();
         Self {
             txn,
             expiration_time,
@@ -35,6 +40,8 @@ impl MempoolTransaction {
             ranking_score,
             timeline_state,
             governance_role,
+            bytes,
+            insertion_time: diem_infallible::duration_since_epoch(),
         }
     }
     pub(crate) fn get_sequence_number(&self) -> u64 {
@@ -3,6 +3,7 @@
 
 use crate::{
     core_mempool::{
+        eviction::{build_eviction_policy, EvictionPolicy},
         index::{
             AccountTransactions, ParkingLotIndex, PriorityIndex, PriorityQueueIter, TTLIndex,
             TimelineIndex,
@@ -12,12 +13,13 @@ use crate::{
     },
     counters,
     logging::{LogEntry, LogEvent, LogSchema, TxnsLog},
+    PendingTransaction, PendingTransactionStatus, RemovalReason, RemovedTransaction,
 };
 use diem_config::config::MempoolConfig;
 use diem_logger::prelude::*;
 use diem_types::{
     account_address::AccountAddress,
-    mempool_status::{MempoolStatus, MempoolStatusCode},
+    mempool_status::{MempoolCapacity, MempoolStatus, MempoolStatusCode},
     transaction::SignedTransaction,
 };
 use std::{
@@ -43,9 +45,21 @@ pub struct TransactionStore {
     // keeps track of "non-ready" txns (transactions that can't be included in next block)
     parking_lot_index: ParkingLotIndex,
 
+    // total size in bytes of the transactions in the store
+    size_bytes: usize,
+
+    // why recently removed transactions were removed
+    removed_transactions: TtlCache<(AccountAddress, u64), RemovedTransaction>,
+
     // configuration
     capacity: usize,
+    capacity_bytes: usize,
     capacity_per_user: usize,
+    backpressure_retry_after: Duration,
+    gas_price_bump_percent: u64,
+    min_time_in_mempool_before_gc: Duration,
+    // decides which parked txn to drop when Mempool is full
+    eviction_policy: Box<dyn EvictionPolicy>,
 }
 
 impl TransactionStore {
@@ -59,13 +73,27 @@ impl TransactionStore {
             expiration_time_index: TTLIndex::new(Box::new(|t: &MempoolTransaction| {
                 Duration::from_secs(t.txn.expiration_timestamp_secs())
             })),
-            priority_index: PriorityIndex::new(),
+            priority_index: PriorityIndex::new(config.ordering_policy),
             timeline_index: TimelineIndex::new(),
             parking_lot_index: ParkingLotIndex::new(),
 
+            size_bytes: 0,
+
+            removed_transactions: TtlCache::new(
+                config.removed_transactions_cache_capacity,
+                Duration::from_secs(config.removed_transactions_cache_ttl_secs),
+            ),
+
             // configuration
             capacity: config.capacity,
+            capacity_bytes: config.capacity_bytes,
             capacity_per_user: config.capacity_per_user,
+            backpressure_retry_after: Duration::from_millis(config.backpressure_retry_after_ms),
+            gas_price_bump_percent: config.gas_price_bump_percent,
+            min_time_in_mempool_before_gc: Duration::from_secs(
+                config.min_time_in_mempool_before_gc_secs,
+            ),
+            eviction_policy: build_eviction_policy(config),
         }
     }
 
@@ -96,36 +124,47 @@ impl TransactionStore {
 
         // check if transaction is already present in Mempool
         // e.g. given request is update
-        // we allow increase in gas price to speed up process.
+        // we allow a large enough increase in gas price to speed up process. The replacement gets
+        // a new position in the timeline, so that it's broadcast to peers like a new transaction.
         // ignores the case transaction hash is same for retrying submit transaction.
-        if let Some(txns) = self.transactions.get_mut(&address) {
-            if let Some(current_version) = txns.get_mut(&sequence_number) {
-                if current_version.txn == txn.txn {
-                    return MempoolStatus::new(MempoolStatusCode::Accepted);
-                }
-                if current_version.txn.max_gas_amount() == txn.txn.max_gas_amount()
-                    && current_version.txn.payload() == txn.txn.payload()
-                    && current_version.txn.expiration_timestamp_secs()
-                        == txn.txn.expiration_timestamp_secs()
-                    && current_version.get_gas_price() < txn.get_gas_price()
-                {
-                    if let Some(txn) = txns.remove(&txn.get_sequence_number()) {
-                        self.index_remove(&txn);
-                    }
-                } else {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        format!("Failed to update gas price to {}", txn.get_gas_price()),
-                    );
-                }
+        // The pending transaction is only removed once the replacement is certain to be inserted.
+        let mut replaced_bytes = None;
+        if let Some(current_version) = self
+            .transactions
+            .get(&address)
+            .and_then(|txns| txns.get(&sequence_number))
+        {
+            if current_version.txn == txn.txn {
+                return MempoolStatus::new(MempoolStatusCode::Accepted);
+            }
+            let min_gas_price =
+                bumped_gas_price(current_version.get_gas_price(), self.gas_price_bump_percent);
+            if current_version.txn.max_gas_amount() == txn.txn.max_gas_amount()
+                && current_version.txn.payload() == txn.txn.payload()
+                && current_version.txn.expiration_timestamp_secs()
+                    == txn.txn.expiration_timestamp_secs()
+                && txn.get_gas_price() >= min_gas_price
+            {
+                replaced_bytes = Some(current_version.bytes);
+            } else {
+                return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(format!(
+                    "Failed to update gas price to {}, at least {} is required",
+                    txn.get_gas_price(),
+                    min_gas_price
+                ));
             }
         }
 
-        if self.check_is_full_after_eviction(&txn, current_sequence_number) {
-            return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
-                "mempool size: {}, capacity: {}",
-                self.system_ttl_index.size(),
-                self.capacity,
-            ));
+        if self.check_is_full_after_eviction(&txn, replaced_bytes, current_sequence_number) {
+            return MempoolStatus::new(MempoolStatusCode::MempoolIsFull)
+                .with_message(format!(
+                    "mempool size: {}, capacity: {}, size in bytes: {}, capacity in bytes: {}",
+                    self.system_ttl_index.size(),
+                    self.capacity,
+                    self.size_bytes,
+                    self.capacity_bytes,
+                ))
+                .with_capacity(self.capacity_of(&address));
         }
 
         self.transactions
@@ -135,27 +174,50 @@ impl TransactionStore {
         self.clean_committed_transactions(&address, current_sequence_number);
 
         if let Some(txns) = self.transactions.get_mut(&address) {
-            // capacity check
-            if txns.len() >= self.capacity_per_user {
-                return MempoolStatus::new(MempoolStatusCode::TooManyTransactions).with_message(
-                    format!(
-                        "txns length: {} capacity per user: {}",
-                        txns.len(),
-                        self.capacity_per_user,
-                    ),
+            // capacity check, a replacement takes the slot of the txn it replaces
+            if replaced_bytes.is_none() && txns.len() >= self.capacity_per_user {
+                let message = format!(
+                    "txns length: {} capacity per user: {}",
+                    txns.len(),
+                    self.capacity_per_user,
                 );
+                return MempoolStatus::new(MempoolStatusCode::TooManyTransactions)
+                    .with_message(message)
+                    .with_capacity(self.capacity_of(&address));
+            }
+
+            if let Some(replaced) = txns.remove(&sequence_number) {
+                counters::CORE_MEMPOOL_REPLACED_TXNS.inc();
+                self.index_remove(&replaced, RemovalReason::Replaced);
             }
+        }
 
+        if let Some(txns) = self.transactions.get_mut(&address) {
             // insert into storage and other indexes
             self.system_ttl_index.insert(&txn);
             self.expiration_time_index.insert(&txn);
+            self.size_bytes += txn.bytes;
             txns.insert(sequence_number, txn);
+            counters::CORE_MEMPOOL_ACCOUNT_QUEUE_DEPTH.observe(txns.len() as f64);
             self.track_indices();
         }
         self.process_ready_transactions(&address, current_sequence_number);
         MempoolStatus::new(MempoolStatusCode::Accepted)
     }
 
+    /// How full mempool is, for a transaction of `address` rejected for lack of room.
+    fn capacity_of(&self, address: &AccountAddress) -> MempoolCapacity {
+        MempoolCapacity {
+            size: self.system_ttl_index.size() as u64,
+            capacity: self.capacity as u64,
+            size_bytes: self.size_bytes as u64,
+            capacity_bytes: self.capacity_bytes as u64,
+            sender_size: self.transactions.get(address).map_or(0, |txns| txns.len()) as u64,
+            capacity_per_sender: self.capacity_per_user as u64,
+            retry_after_ms: self.backpressure_retry_after.as_millis() as u64,
+        }
+    }
+
     fn track_indices(&self) {
         counters::core_mempool_index_size(
             counters::SYSTEM_TTL_INDEX_LABEL,
@@ -177,37 +239,80 @@ impl TransactionStore {
             counters::TIMELINE_INDEX_LABEL,
             self.timeline_index.size(),
         );
+        counters::CORE_MEMPOOL_SIZE_BYTES.set(self.size_bytes as i64);
+    }
+
+    /// Checks if Mempool has no room left for `txn`, either in number of transactions or in bytes.
+    /// `replaced_bytes` is the size of the txn `txn` replaces, if any, whose slot and bytes it takes.
+    fn is_full(&self, txn: &MempoolTransaction, replaced_bytes: Option<usize>) -> bool {
+        self.is_full_after_removing(txn, replaced_bytes, 0, 0)
+    }
+
+    /// Checks if Mempool would still have no room left for `txn` after evicting the whole ParkingLot.
+    fn is_full_without_parking_lot(
+        &self,
+        txn: &MempoolTransaction,
+        replaced_bytes: Option<usize>,
+    ) -> bool {
+        self.is_full_after_removing(
+            txn,
+            replaced_bytes,
+            self.parking_lot_index.size(),
+            self.parking_lot_index.size_bytes(),
+        )
+    }
+
+    fn is_full_after_removing(
+        &self,
+        txn: &MempoolTransaction,
+        replaced_bytes: Option<usize>,
+        removed_size: usize,
+        removed_bytes: usize,
+    ) -> bool {
+        let removed_size = removed_size + replaced_bytes.map_or(0, |_| 1);
+        let removed_bytes = removed_bytes + replaced_bytes.unwrap_or(0);
+        self.system_ttl_index.size() - removed_size >= self.capacity
+            || self.size_bytes - removed_bytes + txn.bytes > self.capacity_bytes
     }
 
     /// Checks if Mempool is full.
-    /// If it's full, tries to free some space by evicting transactions from the ParkingLot.
+    /// If it's full, tries to free some space by evicting transactions from the ParkingLot, as many
+    /// as needed for `txn` to fit in the capacity in bytes.
+    /// Nothing is evicted if even an empty ParkingLot wouldn't leave room for `txn`.
     /// We only evict on attempt to insert a transaction that would be ready for broadcast upon insertion.
     fn check_is_full_after_eviction(
         &mut self,
         txn: &MempoolTransaction,
+        replaced_bytes: Option<usize>,
         curr_sequence_number: u64,
     ) -> bool {
-        if self.system_ttl_index.size() >= self.capacity
+        if self.is_full(txn, replaced_bytes)
+            && !self.is_full_without_parking_lot(txn, replaced_bytes)
             && self.check_txn_ready(txn, curr_sequence_number)
         {
-            // try to free some space in Mempool from ParkingLot by evicting a non-ready txn
-            if let Some((address, sequence_number)) = self.parking_lot_index.get_poppable() {
-                if let Some(txn) = self
-                    .transactions
-                    .get_mut(&address)
-                    .and_then(|txns| txns.remove(&sequence_number))
-                {
-                    debug!(
-                        LogSchema::new(LogEntry::MempoolFullEvictedTxn).txns(TxnsLog::new_txn(
-                            txn.get_sender(),
-                            txn.get_sequence_number()
-                        ))
-                    );
-                    self.index_remove(&txn);
+            // try to free some space in Mempool from ParkingLot by evicting non-ready txns
+            while self.is_full(txn, replaced_bytes) {
+                let evicted = self
+                    .eviction_policy
+                    .select_victim(&self.parking_lot_index, &self.transactions)
+                    .and_then(|(address, sequence_number)| {
+                        self.transactions
+                            .get_mut(&address)
+                            .and_then(|txns| txns.remove(&sequence_number))
+                    });
+                match evicted {
+                    Some(txn) => {
+                        debug!(LogSchema::new(LogEntry::MempoolFullEvictedTxn).txns(
+                            TxnsLog::new_txn(txn.get_sender(), txn.get_sequence_number())
+                        ));
+                        counters::CORE_MEMPOOL_EVICTED_TXNS.inc();
+                        self.index_remove(&txn, RemovalReason::Evicted);
+                    }
+                    None => break,
                 }
             }
         }
-        self.system_ttl_index.size() >= self.capacity
+        self.is_full(txn, replaced_bytes)
     }
 
     /// Check if a transaction would be ready for broadcast in mempool upon insertion (without inserting it).
@@ -295,7 +400,7 @@ impl TransactionStore {
             let mut rm_txns = TxnsLog::new();
             for transaction in txns_for_removal.values() {
                 rm_txns.add(transaction.get_sender(), transaction.get_sequence_number());
-                self.index_remove(transaction);
+                self.index_remove(transaction, RemovalReason::Committed);
             }
             trace!(
                 LogSchema::new(LogEntry::CleanCommittedTxn).txns(rm_txns),
@@ -323,15 +428,29 @@ impl TransactionStore {
             let mut txns_log = TxnsLog::new();
             for transaction in txns.values() {
                 txns_log.add(transaction.get_sender(), transaction.get_sequence_number());
-                self.index_remove(&transaction);
+                self.index_remove(&transaction, RemovalReason::Rejected);
             }
             debug!(LogSchema::new(LogEntry::CleanRejectedTxn).txns(txns_log));
         }
     }
 
-    /// Removes transaction from all indexes.
-    fn index_remove(&mut self, txn: &MempoolTransaction) {
+    /// Removes transaction from all indexes, and remembers why.
+    fn index_remove(&mut self, txn: &MempoolTransaction, reason: RemovalReason) {
         counters::CORE_MEMPOOL_REMOVED_TXNS.inc();
+        let now = diem_infallible::duration_since_epoch();
+        if let Some(time_in_mempool) = now.checked_sub(txn.insertion_time) {
+            counters::CORE_MEMPOOL_TIME_IN_MEMPOOL
+                .with_label_values(&[reason.as_str()])
+                .observe(time_in_mempool.as_secs_f64());
+        }
+        self.removed_transactions.insert(
+            (txn.get_sender(), txn.get_sequence_number()),
+            RemovedTransaction {
+                reason,
+                removal_time: now,
+            },
+        );
+        self.size_bytes -= txn.bytes;
         self.system_ttl_index.remove(&txn);
         self.expiration_time_index.remove(&txn);
         self.priority_index.remove(&txn);
@@ -402,24 +521,46 @@ impl TransactionStore {
         by_system_ttl: bool,
         metrics_cache: &TtlCache<(AccountAddress, u64), SystemTime>,
     ) {
-        let (metric_label, index, log_event) = if by_system_ttl {
+        let (metric_label, index, log_event, reason) = if by_system_ttl {
             (
                 counters::GC_SYSTEM_TTL_LABEL,
                 &mut self.system_ttl_index,
                 LogEvent::SystemTTLExpiration,
+                RemovalReason::SystemTtlExpired,
             )
         } else {
             (
                 counters::GC_CLIENT_EXP_LABEL,
                 &mut self.expiration_time_index,
                 LogEvent::ClientExpiration,
+                RemovalReason::Expired,
             )
         };
         counters::CORE_MEMPOOL_GC_EVENT_COUNT
             .with_label_values(&[metric_label])
             .inc();
 
-        let mut gc_txns = index.gc(now);
+        // transactions that entered mempool too recently are kept in the index for a later gc
+        let last_insertion_time = diem_infallible::duration_since_epoch()
+            .checked_sub(self.min_time_in_mempool_before_gc)
+            .unwrap_or_default();
+        let transactions = &self.transactions;
+        let mut gc_txns: Vec<_> = index
+            .gc(now)
+            .into_iter()
+            .filter(|key| {
+                match transactions
+                    .get(&key.address)
+                    .and_then(|txns| txns.get(&key.sequence_number))
+                {
+                    Some(txn) if txn.insertion_time > last_insertion_time => {
+                        index.insert(txn);
+                        false
+                    }
+                    _ => true,
+                }
+            })
+            .collect();
         // sort the expired txns by order of sequence number per account
         gc_txns.sort_by_key(|key| (key.address, key.sequence_number));
         let mut gc_iter = gc_txns.iter().peekable();
@@ -459,7 +600,7 @@ impl TransactionStore {
                     }
 
                     // remove txn
-                    self.index_remove(&txn);
+                    self.index_remove(&txn, reason);
                 }
             }
         }
@@ -468,6 +609,22 @@ impl TransactionStore {
         self.track_indices();
     }
 
+    /// Why the transaction of `account` with `sequence_number` was removed, if it was recently.
+    pub(crate) fn get_removed_transaction(
+        &self,
+        account: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<RemovedTransaction> {
+        self.removed_transactions
+            .get(&(*account, sequence_number))
+            .copied()
+    }
+
+    /// Forgets the transactions removed longer ago than the TTL of the cache.
+    pub(crate) fn gc_removed_transactions(&mut self, now: SystemTime) {
+        self.removed_transactions.gc(now);
+    }
+
     pub(crate) fn iter_queue(&self) -> PriorityQueueIter {
         self.priority_index.iter()
     }
@@ -491,8 +648,62 @@ impl TransactionStore {
         txns_log
     }
 
+    /// Number of transactions in mempool, and the most it can hold.
+    pub(crate) fn get_size_and_capacity(&self) -> (usize, usize) {
+        (self.system_ttl_index.size(), self.capacity)
+    }
+
+    /// Transactions of `account`, by sequence number.
+    pub(crate) fn get_account_transactions(
+        &self,
+        account: &AccountAddress,
+    ) -> Vec<PendingTransaction> {
+        self.transactions
+            .get(account)
+            .map_or_else(Vec::new, |txns| self.pending_transactions(account, txns))
+    }
+
+    /// Transactions of all accounts, by sender and sequence number.
+    pub(crate) fn get_all_transactions(&self) -> Vec<PendingTransaction> {
+        let mut accounts: Vec<_> = self.transactions.iter().collect();
+        accounts.sort_by_key(|(account, _txns)| *account);
+        accounts
+            .into_iter()
+            .flat_map(|(account, txns)| self.pending_transactions(account, txns))
+            .collect()
+    }
+
+    fn pending_transactions(
+        &self,
+        account: &AccountAddress,
+        txns: &AccountTransactions,
+    ) -> Vec<PendingTransaction> {
+        txns.iter()
+            .map(|(seq_num, txn)| {
+                let status = if self.parking_lot_index.contains(account, seq_num) {
+                    PendingTransactionStatus::Parked
+                } else {
+                    PendingTransactionStatus::Ready
+                };
+                PendingTransaction {
+                    txn: txn.txn.clone(),
+                    status,
+                    insertion_time: txn.insertion_time,
+                }
+            })
+            .collect()
+    }
+
     #[cfg(test)]
     pub(crate) fn get_parking_lot_size(&self) -> usize {
         self.parking_lot_index.size()
     }
 }
+
+/// The minimum gas unit price of a transaction replacing one paying `gas_price`: `bump_percent`
+/// more, rounded up, and always strictly more.
+fn bumped_gas_price(gas_price: u64, bump_percent: u64) -> u64 {
+    let bump = (u128::from(gas_price) * u128::from(bump_percent) + 99) / 100;
+    let bumped = u128::from(gas_price) + std::cmp::max(bump, 1);
+    std::cmp::min(bumped, u128::from(u64::max_value())) as u64
+}
@@ -75,6 +75,11 @@ where
         }
     }
 
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.ttl_index.clear();
+    }
+
     pub fn gc(&mut self, gc_time: SystemTime) {
         // Remove the expired entries.
         let mut active = self.ttl_index.split_off(&gc_time);
@@ -4,8 +4,8 @@
 use diem_config::{config::PeerNetworkId, network_id::NetworkId};
 use diem_metrics::{
     register_histogram, register_histogram_vec, register_int_counter, register_int_counter_vec,
-    register_int_gauge, register_int_gauge_vec, DurationHistogram, HistogramTimer, HistogramVec,
-    IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    register_int_gauge, register_int_gauge_vec, DurationHistogram, Histogram, HistogramTimer,
+    HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use diem_types::PeerId;
 use once_cell::sync::Lazy;
@@ -92,6 +92,69 @@ pub fn core_mempool_index_size(label: &'static str, size: usize) {
         .set(size as i64)
 }
 
+/// Gauge tracking the total size in bytes of the txns in core mempool
+pub static CORE_MEMPOOL_SIZE_BYTES: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_core_mempool_size_bytes",
+        "Total size in bytes of the txns in core mempool"
+    )
+    .unwrap()
+});
+
+/// Counter tracking number of parked txns evicted from core mempool to make room for ready ones
+pub static CORE_MEMPOOL_EVICTED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_core_mempool_evicted_txns_count",
+        "Number of parked txns evicted from core mempool when full"
+    )
+    .unwrap()
+});
+
+/// Counter tracking number of txns replaced in core mempool by one paying a higher gas price
+pub static CORE_MEMPOOL_REPLACED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_core_mempool_replaced_txns_count",
+        "Number of txns replaced in core mempool by one paying a higher gas price"
+    )
+    .unwrap()
+});
+
+// Buckets, in seconds, of how long txns stay in core mempool, which can be minutes when it's busy
+const TIME_IN_MEMPOOL_BUCKETS: &[f64] = &[
+    0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0,
+];
+
+/// Histogram of the number of txns of a sender in core mempool, observed when one is inserted
+pub static CORE_MEMPOOL_ACCOUNT_QUEUE_DEPTH: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "diem_core_mempool_account_queue_depth",
+        "Number of txns of the sender in core mempool when one is inserted",
+        vec![1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0]
+    )
+    .unwrap()
+});
+
+/// Histogram of how long txns stayed in core mempool, by why they were removed
+pub static CORE_MEMPOOL_TIME_IN_MEMPOOL: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "diem_core_mempool_time_in_mempool",
+        "How long txns stayed in core mempool before they were removed",
+        &["reason"],
+        TIME_IN_MEMPOOL_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
+/// Histogram of how long txns stayed in the parking lot before they became ready or were removed
+pub static CORE_MEMPOOL_PARKING_LOT_RESIDENCY: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "diem_core_mempool_parking_lot_residency",
+        "How long txns stayed in the parking lot of core mempool",
+        TIME_IN_MEMPOOL_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
 /// Counter tracking number of txns removed from core mempool
 pub static CORE_MEMPOOL_REMOVED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -236,6 +299,23 @@ pub static SHARED_MEMPOOL_BROADCAST_RTT: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter tracking number of times upstream peers were demoted for failing broadcasts
+static SHARED_MEMPOOL_PEER_DEMOTIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_shared_mempool_peer_demotions",
+        "Number of times an upstream peer was demoted for failing broadcasts",
+        &["network", "recipient"]
+    )
+    .unwrap()
+});
+
+pub fn shared_mempool_peer_demotions(peer: &PeerNetworkId) -> IntCounter {
+    SHARED_MEMPOOL_PEER_DEMOTIONS.with_label_values(&[
+        peer.raw_network_id().as_str(),
+        peer.peer_id().short_str().as_str(),
+    ])
+}
+
 /// Counter tracking number of mempool broadcasts that have not been ACK'ed for
 static SHARED_MEMPOOL_PENDING_BROADCASTS_COUNT: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -334,6 +414,31 @@ pub static CORE_MEMPOOL_INVARIANT_VIOLATION_COUNT: Lazy<IntCounter> = Lazy::new(
     .unwrap()
 });
 
+/// Counter of incoming txns mempool did not accept, by the VM or mempool status of the rejection
+static SHARED_MEMPOOL_REJECTED_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_shared_mempool_rejected_txns_count",
+        "Number of incoming txns not accepted by mempool, by reason",
+        &["reason"]
+    )
+    .unwrap()
+});
+
+pub fn shared_mempool_rejected_txns_inc(reason: &str) {
+    SHARED_MEMPOOL_REJECTED_TXNS
+        .with_label_values(&[reason])
+        .inc();
+}
+
+/// Counter of incoming transactions whose VM validation result was found in the validation cache
+pub static VALIDATION_CACHE_HITS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_mempool_validation_cache_hits_count",
+        "Number of incoming txns not validated again thanks to the validation cache"
+    )
+    .unwrap()
+});
+
 pub static VM_RECONFIG_UPDATE_FAIL_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
         "diem_mempool_vm_reconfig_update_fail_count",
@@ -62,7 +62,8 @@ pub use shared_mempool::{
     types::{
         gen_mempool_reconfig_subscription, CommitNotification, CommitResponse,
         CommittedTransaction, ConsensusRequest, ConsensusResponse, MempoolClientSender,
-        SubmissionStatus, TransactionExclusion,
+        MempoolReader, PendingTransaction, PendingTransactionStatus, RemovalReason,
+        RemovedTransaction, SubmissionStatus, TransactionExclusion,
     },
 };
 #[cfg(any(test, feature = "fuzzing"))]
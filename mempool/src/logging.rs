@@ -147,6 +147,7 @@ pub enum LogEntry {
     ProcessReadyTxns,
     DBError,
     UpstreamNetwork,
+    PeerDemoted,
     UnexpectedNetworkMsg,
     MempoolSnapshot,
 }
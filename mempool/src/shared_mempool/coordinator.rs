@@ -151,6 +151,7 @@ async fn handle_mempool_reconfig_event<V>(
         .spawn(tasks::process_config_update(
             config_update,
             smp.validator.clone(),
+            smp.validation_cache.clone(),
         ))
         .await;
 }
@@ -269,6 +270,19 @@ pub(crate) async fn gc_coordinator(mempool: Arc<Mutex<CoreMempool>>, gc_interval
     ));
 }
 
+/// Garbage collect all transactions past their client-specified expiration time, by system time.
+/// Blocks pulled or committed also trigger such gc, by their timestamp, but a node may see few.
+pub(crate) async fn expiration_gc_coordinator(
+    mempool: Arc<Mutex<CoreMempool>>,
+    gc_interval_ms: u64,
+) {
+    let mut interval = IntervalStream::new(interval(Duration::from_millis(gc_interval_ms)));
+    while let Some(_interval) = interval.next().await {
+        let now = diem_infallible::duration_since_epoch();
+        mempool.lock().gc_by_expiration_time(now);
+    }
+}
+
 /// Periodically logs a snapshot of transactions in core mempool.
 /// In the future we may want an interactive way to directly query mempool's internal state.
 /// For now, we will rely on this periodic snapshot to observe the internal state.
@@ -28,6 +28,8 @@ use std::{
 use vm_validator::vm_validator::TransactionValidation;
 
 const PRIMARY_NETWORK_PREFERENCE: usize = 0;
+/// Weight of the latest broadcast outcome in the moving averages of `PeerScore`.
+const SCORE_SMOOTHING_FACTOR: f64 = 0.2;
 
 /// Peers that receive txns from this node.
 pub(crate) type PeerSyncStates = HashMap<PeerNetworkId, PeerSyncState>;
@@ -41,6 +43,7 @@ pub(crate) struct PeerSyncState {
     pub is_alive: bool,
     pub broadcast_info: BroadcastInfo,
     pub metadata: ConnectionMetadata,
+    pub score: PeerScore,
 }
 
 impl PeerSyncState {
@@ -50,6 +53,7 @@ impl PeerSyncState {
             is_alive: true,
             broadcast_info: BroadcastInfo::new(),
             metadata,
+            score: PeerScore::default(),
         }
     }
 }
@@ -100,6 +104,67 @@ impl BroadcastInfo {
     }
 }
 
+/// How responsive a remote peer is to broadcasts, for non-validators to prefer healthy upstream
+/// peers.
+#[derive(Clone, Debug, Default)]
+pub struct PeerScore {
+    // Moving average of the time between sending a broadcast and receiving its ACK.
+    pub ack_latency: Option<Duration>,
+    // Moving average of broadcast failures, from 0 when all were ACK'ed to 1 when all failed.
+    pub failure_rate: f64,
+    // Until when the peer is demoted for failing broadcasts.
+    pub demoted_until: Option<Instant>,
+}
+
+impl PeerScore {
+    fn record_ack(&mut self, rtt: Duration) {
+        self.ack_latency = Some(match self.ack_latency {
+            Some(latency) => {
+                latency.mul_f64(1.0 - SCORE_SMOOTHING_FACTOR) + rtt.mul_f64(SCORE_SMOOTHING_FACTOR)
+            }
+            None => rtt,
+        });
+        self.failure_rate *= 1.0 - SCORE_SMOOTHING_FACTOR;
+    }
+
+    /// Records a broadcast that timed out or failed to be sent.
+    /// Returns true if the peer is demoted for it.
+    fn record_failure(&mut self, config: &MempoolConfig, now: Instant) -> bool {
+        if self.demoted_until.map_or(false, |until| until <= now) {
+            // The demotion is over, the peer starts afresh
+            self.demoted_until = None;
+            self.failure_rate = 0.0;
+        }
+        self.failure_rate =
+            self.failure_rate * (1.0 - SCORE_SMOOTHING_FACTOR) + SCORE_SMOOTHING_FACTOR;
+        if self.is_demoted(now)
+            || self.failure_rate * 100.0 <= config.peer_demotion_failure_percent as f64
+        {
+            return false;
+        }
+        self.demoted_until = Some(now + Duration::from_millis(config.peer_demotion_duration_ms));
+        true
+    }
+
+    fn is_demoted(&self, now: Instant) -> bool {
+        self.demoted_until.map_or(false, |until| now < until)
+    }
+
+    fn health(&self, now: Instant) -> PeerHealth {
+        PeerHealth {
+            demoted: self.is_demoted(now),
+            ack_latency: self.ack_latency,
+        }
+    }
+}
+
+/// What the prioritization of upstream peers takes from their `PeerScore`
+#[derive(Clone, Copy, Debug, Default)]
+struct PeerHealth {
+    demoted: bool,
+    ack_latency: Option<Duration>,
+}
+
 impl PeerManager {
     pub fn new(role: RoleType, mempool_config: MempoolConfig) -> Self {
         // Primary network is always chosen at initialization.
@@ -174,6 +239,26 @@ impl PeerManager {
         smp: &mut SharedMempool<V>,
     ) where
         V: TransactionValidation,
+    {
+        if self.broadcast(peer.clone(), scheduled_backoff, smp) {
+            counters::shared_mempool_peer_demotions(&peer).inc();
+            warn!(
+                LogSchema::new(LogEntry::PeerDemoted).peer(&peer),
+                "upstream peer demoted for failing broadcasts"
+            );
+            self.update_prioritized_peers();
+        }
+    }
+
+    // Returns true if `peer` is demoted for failing broadcasts.
+    fn broadcast<V>(
+        &self,
+        peer: PeerNetworkId,
+        scheduled_backoff: bool,
+        smp: &mut SharedMempool<V>,
+    ) -> bool
+    where
+        V: TransactionValidation,
     {
         // Start timer for tracking broadcast latency.
         let start_time = Instant::now();
@@ -183,12 +268,12 @@ impl PeerManager {
             state
         } else {
             // If we don't have any info about the node, we shouldn't broadcast to it
-            return;
+            return false;
         };
 
         // Only broadcast to peers that are alive.
         if !state.is_alive {
-            return;
+            return false;
         }
 
         // When not a validator, only broadcast to `default_failovers`
@@ -200,7 +285,7 @@ impl PeerManager {
                 .find_position(|peer_network_id| *peer_network_id == &peer)
                 .map_or(usize::MAX, |(pos, _)| pos);
             if priority > self.mempool_config.default_failovers {
-                return;
+                return false;
             }
         }
 
@@ -208,12 +293,13 @@ impl PeerManager {
         // This is to ensure the backoff mode is actually honored (there is a chance a broadcast was scheduled
         // in non-backoff mode before backoff mode was turned on - ignore such scheduled broadcasts).
         if state.broadcast_info.backoff_mode && !scheduled_backoff {
-            return;
+            return false;
         }
 
         let batch_id: BatchId;
         let transactions: Vec<SignedTransaction>;
         let mut metric_label = None;
+        let mut demoted = false;
         {
             let mut mempool = smp.mempool.lock();
 
@@ -252,7 +338,7 @@ impl PeerManager {
                 // This helps rate-limit egress network bandwidth and not overload a remote peer or this
                 // node's Diem network sender.
                 if pending_broadcasts >= self.mempool_config.max_broadcasts_per_peer {
-                    return;
+                    return false;
                 }
             }
             let retry = state.broadcast_info.retry_batches.iter().rev().next();
@@ -260,6 +346,9 @@ impl PeerManager {
             let (new_batch_id, new_transactions) = match std::cmp::max(expired, retry) {
                 Some(id) => {
                     metric_label = if Some(id) == expired {
+                        demoted = state
+                            .score
+                            .record_failure(&self.mempool_config, Instant::now());
                         Some(counters::EXPIRED_BROADCAST_LABEL)
                     } else {
                         Some(counters::RETRY_BROADCAST_LABEL)
@@ -283,7 +372,7 @@ impl PeerManager {
         }
 
         if transactions.is_empty() {
-            return;
+            return demoted;
         }
 
         let mut network_sender = smp
@@ -301,12 +390,15 @@ impl PeerManager {
             },
         ) {
             counters::network_send_fail_inc(counters::BROADCAST_TXNS);
+            demoted |= state
+                .score
+                .record_failure(&self.mempool_config, Instant::now());
             error!(
                 LogSchema::event_log(LogEntry::BroadcastTransaction, LogEvent::NetworkSendFail)
                     .peer(&peer)
                     .error(&e.into())
             );
-            return;
+            return demoted;
         }
         // Update peer sync state with info from above broadcast.
         state.timeline_id = std::cmp::max(state.timeline_id, batch_id.1);
@@ -350,6 +442,7 @@ impl PeerManager {
                 ])
                 .inc();
         }
+        demoted
     }
 
     fn update_prioritized_peers(&self) {
@@ -359,23 +452,24 @@ impl PeerManager {
         }
 
         // Retrieve just what's needed for the peer ordering
+        let now = Instant::now();
         let peers: Vec<_> = {
             let peer_states = self.peer_states.lock();
             peer_states
                 .iter()
                 .filter(|(_, state)| state.is_alive)
-                .map(|(peer, state)| (peer.clone(), state.metadata.role))
+                .map(|(peer, state)| (peer.clone(), state.metadata.role, state.score.health(now)))
                 .collect()
         };
 
-        // Order peers by network and by type
+        // Order peers by health, by network, by type and by responsiveness
         // Origin doesn't matter at this point, only inserted ones into peer_states are upstream
         // Validators will always have the full set
         let mut prioritized_peers = self.prioritized_peers.lock();
         let peers: Vec<_> = peers
             .iter()
             .sorted_by(|peer_a, peer_b| compare_prioritized_peers(peer_a, peer_b))
-            .map(|(peer, _)| peer.clone())
+            .map(|(peer, _, _)| peer.clone())
             .collect();
         let _ = std::mem::replace(&mut *prioritized_peers, peers);
     }
@@ -414,6 +508,7 @@ impl PeerManager {
             counters::SHARED_MEMPOOL_BROADCAST_RTT
                 .with_label_values(&[network_id.as_str(), peer_id.as_str()])
                 .observe(rtt.as_secs_f64());
+            sync_state.score.record_ack(rtt);
 
             counters::shared_mempool_pending_broadcasts(&peer).dec();
         } else {
@@ -445,6 +540,10 @@ impl PeerManager {
         if backoff {
             sync_state.broadcast_info.backoff_mode = true;
         }
+        drop(peer_states);
+
+        // The ACK latency and failure rate of the peer changed
+        self.update_prioritized_peers();
     }
 
     // If the origin is provided, checks whether this peer is an upstream peer based on configured preferences and
@@ -473,9 +572,16 @@ impl PeerManager {
 
 /// Provides ordering for prioritized peers
 fn compare_prioritized_peers(
-    peer_a: &(PeerNetworkId, PeerRole),
-    peer_b: &(PeerNetworkId, PeerRole),
+    peer_a: &(PeerNetworkId, PeerRole, PeerHealth),
+    peer_b: &(PeerNetworkId, PeerRole, PeerHealth),
 ) -> Ordering {
+    // Demoted peers come last
+    let demoted_a = peer_a.2.demoted;
+    let demoted_b = peer_b.2.demoted;
+    if demoted_a != demoted_b {
+        return demoted_a.cmp(&demoted_b);
+    }
+
     let network_a = peer_a.0.raw_network_id();
     let network_b = peer_b.0.raw_network_id();
 
@@ -486,12 +592,16 @@ fn compare_prioritized_peers(
             let role_a = peer_a.1;
             let role_b = peer_b.1;
             match role_a.cmp(&role_b) {
-                // Then tiebreak by PeerId for stability
-                Ordering::Equal => {
-                    let peer_id_a = peer_a.0.peer_id();
-                    let peer_id_b = peer_b.0.peer_id();
-                    peer_id_a.cmp(&peer_id_b)
-                }
+                // Then sort by ACK latency, unknown first so that new peers get a chance
+                Ordering::Equal => match peer_a.2.ack_latency.cmp(&peer_b.2.ack_latency) {
+                    // Then tiebreak by PeerId for stability
+                    Ordering::Equal => {
+                        let peer_id_a = peer_a.0.peer_id();
+                        let peer_id_b = peer_b.0.peer_id();
+                        peer_id_a.cmp(&peer_id_b)
+                    }
+                    ordering => ordering,
+                },
                 ordering => ordering,
             }
         }
@@ -516,18 +626,22 @@ mod test {
         let val_1 = (
             peer_network_id(peer_id_1, NetworkId::vfn_network()),
             PeerRole::Validator,
+            PeerHealth::default(),
         );
         let val_2 = (
             peer_network_id(peer_id_2, NetworkId::vfn_network()),
             PeerRole::Validator,
+            PeerHealth::default(),
         );
         let vfn_1 = (
             peer_network_id(peer_id_1, NetworkId::Public),
             PeerRole::ValidatorFullNode,
+            PeerHealth::default(),
         );
         let preferred_1 = (
             peer_network_id(peer_id_1, NetworkId::Public),
             PeerRole::PreferredUpstream,
+            PeerHealth::default(),
         );
 
         // NetworkId ordering
@@ -551,4 +665,74 @@ mod test {
         // Same the only equal case
         assert_eq!(Ordering::Equal, compare_prioritized_peers(&val_1, &val_1));
     }
+
+    #[test]
+    fn check_peer_health_prioritization() {
+        let peer_id_1 = PeerId::from_hex_literal("0x1").unwrap();
+        let peer_id_2 = PeerId::from_hex_literal("0x2").unwrap();
+        let peer = |peer_id, demoted, ack_latency_ms: Option<u64>| {
+            (
+                peer_network_id(peer_id, NetworkId::Public),
+                PeerRole::PreferredUpstream,
+                PeerHealth {
+                    demoted,
+                    ack_latency: ack_latency_ms.map(Duration::from_millis),
+                },
+            )
+        };
+        let vfn = (
+            peer_network_id(peer_id_1, NetworkId::vfn_network()),
+            PeerRole::Validator,
+            PeerHealth {
+                demoted: true,
+                ack_latency: None,
+            },
+        );
+
+        // Demoted peers come last, whatever their network
+        assert_eq!(
+            Ordering::Less,
+            compare_prioritized_peers(&peer(peer_id_2, false, Some(500)), &vfn)
+        );
+        // Faster peers come first, and unknown ones before them
+        assert_eq!(
+            Ordering::Less,
+            compare_prioritized_peers(
+                &peer(peer_id_2, false, Some(10)),
+                &peer(peer_id_1, false, Some(500))
+            )
+        );
+        assert_eq!(
+            Ordering::Less,
+            compare_prioritized_peers(
+                &peer(peer_id_2, false, None),
+                &peer(peer_id_1, false, Some(10))
+            )
+        );
+    }
+
+    #[test]
+    fn check_peer_score() {
+        let config = MempoolConfig::default();
+        let mut score = PeerScore::default();
+        let now = Instant::now();
+
+        // A single failure doesn't demote a peer
+        assert!(!score.record_failure(&config, now));
+        score.record_ack(Duration::from_millis(100));
+        assert_eq!(score.ack_latency, Some(Duration::from_millis(100)));
+        assert!(!score.is_demoted(now));
+
+        // Repeated failures do, for a while
+        let demoted = (0..10).any(|_| score.record_failure(&config, now));
+        assert!(demoted);
+        assert!(score.is_demoted(now));
+        assert!(!score.record_failure(&config, now));
+        let later = now + Duration::from_millis(config.peer_demotion_duration_ms);
+        assert!(!score.is_demoted(later));
+
+        // After which the peer starts afresh
+        assert!(!score.record_failure(&config, later));
+        assert!(!score.is_demoted(later));
+    }
 }
@@ -5,11 +5,11 @@ use crate::{
     core_mempool::CoreMempool,
     network::{MempoolNetworkEvents, MempoolNetworkSender},
     shared_mempool::{
-        coordinator::{coordinator, gc_coordinator, snapshot_job},
+        coordinator::{coordinator, expiration_gc_coordinator, gc_coordinator, snapshot_job},
         peer_manager::PeerManager,
-        types::{SharedMempool, SharedMempoolNotification},
+        types::{validation_cache, SharedMempool, SharedMempoolNotification},
     },
-    CommitNotification, ConsensusRequest, SubmissionStatus,
+    CommitNotification, ConsensusRequest, MempoolReader, SubmissionStatus,
 };
 use anyhow::Result;
 use channel::diem_channel;
@@ -62,6 +62,7 @@ pub(crate) fn start_shared_mempool<V>(
         network_senders,
         db,
         validator,
+        validation_cache: validation_cache(&config.mempool),
         peer_manager,
         subscribers,
     };
@@ -81,12 +82,19 @@ pub(crate) fn start_shared_mempool<V>(
         config.mempool.system_transaction_gc_interval_ms,
     ));
 
+    executor.spawn(expiration_gc_coordinator(
+        mempool.clone(),
+        config.mempool.expiration_gc_interval_ms,
+    ));
+
     executor.spawn(snapshot_job(
         mempool,
         config.mempool.mempool_snapshot_interval_secs,
     ));
 }
 
+/// Starts shared mempool on its own runtime, which is returned along with read access to the
+/// transactions in mempool.
 pub fn bootstrap(
     config: &NodeConfig,
     db: Arc<dyn DbReader>,
@@ -97,7 +105,7 @@ pub fn bootstrap(
     consensus_requests: Receiver<ConsensusRequest>,
     state_sync_requests: Receiver<CommitNotification>,
     mempool_reconfig_events: diem_channel::Receiver<(), OnChainConfigPayload>,
-) -> Runtime {
+) -> (Runtime, Arc<dyn MempoolReader>) {
     let runtime = Builder::new_multi_thread()
         .thread_name("shared-mem")
         .enable_all()
@@ -108,7 +116,7 @@ pub fn bootstrap(
     start_shared_mempool(
         runtime.handle(),
         config,
-        mempool,
+        mempool.clone(),
         mempool_network_handles,
         client_events,
         consensus_requests,
@@ -118,5 +126,5 @@ pub fn bootstrap(
         vm_validator,
         vec![],
     );
-    runtime
+    (runtime, mempool)
 }
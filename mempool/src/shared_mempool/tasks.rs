@@ -10,20 +10,21 @@ use crate::{
     network::MempoolSyncMsg,
     shared_mempool::types::{
         notify_subscribers, ScheduledBroadcast, SharedMempool, SharedMempoolNotification,
-        SubmissionStatusBundle,
+        SubmissionStatusBundle, ValidationCache,
     },
     CommitNotification, CommitResponse, CommittedTransaction, ConsensusRequest, ConsensusResponse,
     SubmissionStatus,
 };
 use anyhow::Result;
 use diem_config::config::PeerNetworkId;
+use diem_crypto::hash::CryptoHash;
 use diem_infallible::{Mutex, RwLock};
 use diem_logger::prelude::*;
 use diem_metrics::HistogramTimer;
 use diem_types::{
     mempool_status::{MempoolStatus, MempoolStatusCode},
     on_chain_config::OnChainConfigPayload,
-    transaction::SignedTransaction,
+    transaction::{SignedTransaction, Transaction, VMValidatorResult},
     vm_status::DiscardedVMStatus,
 };
 use futures::{channel::oneshot, stream::FuturesUnordered};
@@ -33,7 +34,7 @@ use std::{
     cmp,
     collections::HashSet,
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 use tokio::runtime::Handle;
 use vm_validator::vm_validator::{get_account_sequence_number, TransactionValidation};
@@ -252,9 +253,10 @@ where
     let vm_validation_timer = counters::PROCESS_TXN_BREAKDOWN_LATENCY
         .with_label_values(&[counters::VM_VALIDATION_LABEL])
         .start_timer();
+    smp.validation_cache.lock().gc(SystemTime::now());
     let validation_results = transactions
         .par_iter()
-        .map(|t| smp.validator.read().validate_transaction(t.0.clone()))
+        .map(|(t, sequence_number)| validate_transaction(smp, t, *sequence_number))
         .collect::<Vec<_>>();
     vm_validation_timer.stop_and_record();
 
@@ -294,6 +296,35 @@ where
     statuses
 }
 
+/// Validates `transaction` with the VM, unless the same one passed validation recently while its
+/// sender was at `sequence_number`, as when a client submits it again. Failures aren't cached, as
+/// what failed, e.g. the balance of the sender, may be fixed by the time it's submitted again.
+fn validate_transaction<V>(
+    smp: &SharedMempool<V>,
+    transaction: &SignedTransaction,
+    sequence_number: u64,
+) -> Result<VMValidatorResult>
+where
+    V: TransactionValidation,
+{
+    let key = (
+        Transaction::UserTransaction(transaction.clone()).hash(),
+        sequence_number,
+    );
+    if let Some(result) = smp.validation_cache.lock().get(&key) {
+        counters::VALIDATION_CACHE_HITS.inc();
+        return Ok(result.clone());
+    }
+    let result = smp
+        .validator
+        .read()
+        .validate_transaction(transaction.clone())?;
+    if result.status().is_none() {
+        smp.validation_cache.lock().insert(key, result.clone());
+    }
+    Ok(result)
+}
+
 fn log_txn_process_results(results: &[SubmissionStatusBundle], sender: Option<PeerNetworkId>) {
     let (network, sender) = match sender {
         Some(peer) => (
@@ -318,6 +349,7 @@ fn log_txn_process_results(results: &[SubmissionStatusBundle], sender: Option<Pe
                 &network,
                 &sender,
             );
+            counters::shared_mempool_rejected_txns_inc(&format!("{:?}", vm_status));
             continue;
         }
         match mempool_status.code {
@@ -326,11 +358,14 @@ fn log_txn_process_results(results: &[SubmissionStatusBundle], sender: Option<Pe
                 &network,
                 &sender,
             ),
-            _ => counters::shared_mempool_transactions_processed_inc(
-                &mempool_status.code.to_string(),
-                &network,
-                &sender,
-            ),
+            _ => {
+                counters::shared_mempool_transactions_processed_inc(
+                    &mempool_status.code.to_string(),
+                    &network,
+                    &sender,
+                );
+                counters::shared_mempool_rejected_txns_inc(&mempool_status.code.to_string());
+            }
         }
     }
 }
@@ -448,6 +483,7 @@ async fn commit_txns(
 pub(crate) async fn process_config_update<V>(
     config_update: OnChainConfigPayload,
     validator: Arc<RwLock<V>>,
+    validation_cache: Arc<Mutex<ValidationCache>>,
 ) where
     V: TransactionValidation,
 {
@@ -460,4 +496,6 @@ pub(crate) async fn process_config_update<V>(
         counters::VM_RECONFIG_UPDATE_FAIL_COUNT.inc();
         error!(LogSchema::event_log(LogEntry::ReconfigUpdate, LogEvent::VMUpdateFail).error(&e));
     }
+    // validation results may differ under the new configuration
+    validation_cache.lock().clear();
 }
@@ -4,7 +4,7 @@
 //! Objects used by/related to shared mempool
 
 use crate::{
-    core_mempool::CoreMempool,
+    core_mempool::{CoreMempool, TtlCache},
     shared_mempool::{network::MempoolNetworkSender, peer_manager::PeerManager},
 };
 use anyhow::Result;
@@ -13,12 +13,13 @@ use diem_config::{
     config::{MempoolConfig, PeerNetworkId},
     network_id::NodeNetworkId,
 };
+use diem_crypto::HashValue;
 use diem_infallible::{Mutex, RwLock};
 use diem_types::{
     account_address::AccountAddress,
     mempool_status::MempoolStatus,
     on_chain_config::{ConfigID, DiemVersion, OnChainConfig, OnChainConfigPayload, VMConfig},
-    transaction::SignedTransaction,
+    transaction::{SignedTransaction, VMValidatorResult},
     vm_status::DiscardedVMStatus,
 };
 use futures::{
@@ -26,9 +27,16 @@ use futures::{
     future::Future,
     task::{Context, Poll},
 };
-use std::{collections::HashMap, fmt, pin::Pin, sync::Arc, task::Waker, time::Instant};
+use std::{
+    collections::HashMap,
+    fmt,
+    pin::Pin,
+    sync::Arc,
+    task::Waker,
+    time::{Duration, Instant},
+};
 use storage_interface::DbReader;
-use subscription_service::ReconfigSubscription;
+use subscription_service::ReconfigNotificationBus;
 use tokio::runtime::Handle;
 use vm_validator::vm_validator::TransactionValidation;
 
@@ -43,10 +51,22 @@ where
     pub network_senders: HashMap<NodeNetworkId, MempoolNetworkSender>,
     pub db: Arc<dyn DbReader>,
     pub validator: Arc<RwLock<V>>,
+    pub validation_cache: Arc<Mutex<ValidationCache>>,
     pub peer_manager: Arc<PeerManager>,
     pub subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
 }
 
+/// Recent successful VM validation results, keyed by the hash of the transaction and the sequence number of
+/// its sender when it was validated.
+pub(crate) type ValidationCache = TtlCache<(HashValue, u64), VMValidatorResult>;
+
+pub(crate) fn validation_cache(config: &MempoolConfig) -> Arc<Mutex<ValidationCache>> {
+    Arc::new(Mutex::new(TtlCache::new(
+        config.validation_cache_capacity,
+        Duration::from_secs(config.validation_cache_ttl_secs),
+    )))
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum SharedMempoolNotification {
     PeerStateChange,
@@ -242,9 +262,109 @@ pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 pub type MempoolClientSender =
     mpsc::Sender<(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>)>;
 
+/// Whether a transaction in mempool can be included in the next block
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PendingTransactionStatus {
+    /// The sequence numbers before the transaction's are committed or in mempool
+    Ready,
+    /// The transaction waits for an earlier sequence number of its sender
+    Parked,
+}
+
+/// A transaction in mempool, not committed yet
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingTransaction {
+    pub txn: SignedTransaction,
+    pub status: PendingTransactionStatus,
+    /// Time since the Unix epoch the transaction entered mempool
+    pub insertion_time: Duration,
+}
+
+/// Why a transaction is no longer in mempool
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RemovalReason {
+    /// A transaction of the sender with this or a later sequence number was committed
+    Committed,
+    /// A transaction of the sender was rejected, which removes all of the sender's transactions
+    Rejected,
+    /// The expiration time set by the client passed
+    Expired,
+    /// The transaction stayed in mempool for longer than the system TTL
+    SystemTtlExpired,
+    /// The transaction was parked and evicted to make room in a full mempool
+    Evicted,
+    /// A transaction with the same sequence number and a higher gas price replaced it
+    Replaced,
+}
+
+impl RemovalReason {
+    /// Label of the reason in metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RemovalReason::Committed => "committed",
+            RemovalReason::Rejected => "rejected",
+            RemovalReason::Expired => "expired",
+            RemovalReason::SystemTtlExpired => "system_ttl_expired",
+            RemovalReason::Evicted => "evicted",
+            RemovalReason::Replaced => "replaced",
+        }
+    }
+}
+
+/// A transaction recently removed from mempool
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RemovedTransaction {
+    pub reason: RemovalReason,
+    /// Time since the Unix epoch the transaction was removed
+    pub removal_time: Duration,
+}
+
+/// Read-only access to the transactions in mempool, for clients of mempool such as JSON-RPC
+pub trait MempoolReader: Send + Sync {
+    /// Transactions of `account`, by sequence number
+    fn get_account_transactions(&self, account: &AccountAddress) -> Vec<PendingTransaction>;
+
+    /// All transactions, by sender and sequence number
+    fn get_all_transactions(&self) -> Vec<PendingTransaction>;
+
+    /// Number of transactions, and the most mempool can hold: submissions fail once it is full
+    fn get_size_and_capacity(&self) -> (usize, usize);
+
+    /// Why the transaction of `account` with `sequence_number` was removed, if it was recently
+    fn get_removed_transaction(
+        &self,
+        account: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<RemovedTransaction>;
+}
+
+impl MempoolReader for Mutex<CoreMempool> {
+    fn get_account_transactions(&self, account: &AccountAddress) -> Vec<PendingTransaction> {
+        self.lock().get_account_transactions(account)
+    }
+
+    fn get_all_transactions(&self) -> Vec<PendingTransaction> {
+        self.lock().get_all_transactions()
+    }
+
+    fn get_size_and_capacity(&self) -> (usize, usize) {
+        self.lock().get_size_and_capacity()
+    }
+
+    fn get_removed_transaction(
+        &self,
+        account: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<RemovedTransaction> {
+        self.lock()
+            .get_removed_transaction(account, sequence_number)
+    }
+}
+
 const MEMPOOL_SUBSCRIBED_CONFIGS: &[ConfigID] = &[DiemVersion::CONFIG_ID, VMConfig::CONFIG_ID];
 
 pub fn gen_mempool_reconfig_subscription(
-) -> (ReconfigSubscription, Receiver<(), OnChainConfigPayload>) {
-    ReconfigSubscription::subscribe_all("mempool", MEMPOOL_SUBSCRIBED_CONFIGS.to_vec(), vec![])
+    reconfig_bus: &ReconfigNotificationBus,
+) -> Receiver<(), OnChainConfigPayload> {
+    reconfig_bus.subscribe("mempool", MEMPOOL_SUBSCRIBED_CONFIGS.to_vec(), vec![])
 }
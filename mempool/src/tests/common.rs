@@ -64,6 +64,13 @@ impl TestTransaction {
         self.make_signed_transaction_impl(max_gas_amount, u64::max_value())
     }
 
+    pub(crate) fn make_signed_transaction_with_code_size(
+        &self,
+        code_size: usize,
+    ) -> SignedTransaction {
+        self.make_signed_transaction_impl_with_code(vec![0; code_size], 100, u64::max_value())
+    }
+
     pub(crate) fn make_signed_transaction(&self) -> SignedTransaction {
         self.make_signed_transaction_impl(100, u64::max_value())
     }
@@ -72,11 +79,20 @@ impl TestTransaction {
         &self,
         max_gas_amount: u64,
         exp_timestamp_secs: u64,
+    ) -> SignedTransaction {
+        self.make_signed_transaction_impl_with_code(vec![], max_gas_amount, exp_timestamp_secs)
+    }
+
+    fn make_signed_transaction_impl_with_code(
+        &self,
+        code: Vec<u8>,
+        max_gas_amount: u64,
+        exp_timestamp_secs: u64,
     ) -> SignedTransaction {
         let raw_txn = RawTransaction::new_script(
             TestTransaction::get_address(self.address),
             self.sequence_number,
-            Script::new(vec![], vec![], vec![]),
+            Script::new(code, vec![], vec![]),
             max_gas_amount,
             self.gas_price,
             XUS_NAME.to_owned(),
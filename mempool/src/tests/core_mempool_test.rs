@@ -5,11 +5,15 @@ use crate::{
     core_mempool::{CoreMempool, TimelineState, TtlCache},
     tests::common::{
         add_signed_txn, add_txn, add_txns_to_mempool, exist_in_metrics_cache, setup_mempool,
-        TestTransaction,
+        ConsensusMock, TestTransaction,
     },
+    PendingTransaction, PendingTransactionStatus, RemovalReason,
+};
+use diem_config::config::{MempoolEvictionPolicy, MempoolOrderingPolicy, NodeConfig};
+use diem_types::{
+    mempool_status::MempoolStatusCode,
+    transaction::{GovernanceRole, SignedTransaction},
 };
-use diem_config::config::NodeConfig;
-use diem_types::transaction::{GovernanceRole, SignedTransaction};
 use std::{
     collections::HashSet,
     time::{Duration, SystemTime},
@@ -65,6 +69,41 @@ fn test_transaction_ordering() {
     }
 }
 
+#[test]
+fn test_fifo_transaction_ordering() {
+    let mut config = NodeConfig::random();
+    config.mempool.ordering_policy = MempoolOrderingPolicy::Fifo;
+    let mut mempool = CoreMempool::new(&config);
+    let mut consensus = ConsensusMock::new();
+
+    // First come, first served, whatever the gas price
+    let transactions = add_txns_to_mempool(
+        &mut mempool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(1, 0, 5),
+            TestTransaction::new(0, 1, 10),
+        ],
+    );
+    for transaction in &transactions {
+        assert_eq!(
+            consensus.get_block(&mut mempool, 1),
+            vec![transaction.clone()]
+        );
+    }
+
+    // Sequence numbers of the same account are still respected
+    let (mut mempool, mut consensus) = (CoreMempool::new(&config), ConsensusMock::new());
+    let transactions = add_txns_to_mempool(
+        &mut mempool,
+        vec![TestTransaction::new(0, 1, 1), TestTransaction::new(0, 0, 1)],
+    );
+    assert_eq!(
+        consensus.get_block(&mut mempool, 2),
+        vec![transactions[1].clone(), transactions[0].clone()]
+    );
+}
+
 #[test]
 fn test_ordering_of_governance_transactions() {
     let gov_roles = vec![
@@ -149,6 +188,68 @@ fn test_update_transaction_in_mempool() {
     assert_eq!(consensus.get_block(&mut mempool, 1), vec![txns[1].clone()]);
 }
 
+#[test]
+fn test_gas_price_bump() {
+    let mut config = NodeConfig::random();
+    config.mempool.gas_price_bump_percent = 20;
+    let mut mempool = CoreMempool::new(&config);
+    let mut consensus = ConsensusMock::new();
+    add_txn(&mut mempool, TestTransaction::new(0, 0, 10)).unwrap();
+
+    // Not enough of a bump
+    assert!(add_txn(&mut mempool, TestTransaction::new(0, 0, 11)).is_err());
+    add_txn(&mut mempool, TestTransaction::new(0, 0, 12)).unwrap();
+    assert_eq!(
+        consensus.get_block(&mut mempool, 1),
+        vec![TestTransaction::new(0, 0, 12).make_signed_transaction()]
+    );
+
+    // Even without a bump percentage, the gas price must increase
+    config.mempool.gas_price_bump_percent = 0;
+    let mut mempool = CoreMempool::new(&config);
+    add_txn(&mut mempool, TestTransaction::new(0, 0, 10)).unwrap();
+    assert!(add_txn(&mut mempool, TestTransaction::new(0, 0, 9)).is_err());
+    add_txn(&mut mempool, TestTransaction::new(0, 0, 11)).unwrap();
+}
+
+#[test]
+fn test_replacement_is_broadcast() {
+    let (mut mempool, _) = setup_mempool();
+    add_txn(&mut mempool, TestTransaction::new(0, 0, 1)).unwrap();
+    let (timeline, last_timeline_id) = mempool.read_timeline(0, 10);
+    assert_eq!(timeline.len(), 1);
+
+    // The replacement comes after the transactions already broadcast
+    add_txn(&mut mempool, TestTransaction::new(0, 0, 5)).unwrap();
+    let (timeline, _) = mempool.read_timeline(last_timeline_id, 10);
+    assert_eq!(
+        timeline,
+        vec![TestTransaction::new(0, 0, 5).make_signed_transaction()]
+    );
+}
+
+#[test]
+fn test_replacement_when_full() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 2;
+    config.mempool.capacity_per_user = 1;
+    let mut mempool = CoreMempool::new(&config);
+    let mut consensus = ConsensusMock::new();
+    add_txn(&mut mempool, TestTransaction::new(0, 0, 1)).unwrap();
+    add_txn(&mut mempool, TestTransaction::new(1, 0, 1)).unwrap();
+    assert!(add_txn(&mut mempool, TestTransaction::new(2, 0, 1)).is_err());
+
+    // The replacement takes the slot of the transaction it replaces, for Mempool and the sender.
+    add_txn(&mut mempool, TestTransaction::new(0, 0, 5)).unwrap();
+    assert_eq!(
+        consensus.get_block(&mut mempool, 2),
+        vec![
+            TestTransaction::new(0, 0, 5).make_signed_transaction(),
+            TestTransaction::new(1, 0, 1).make_signed_transaction(),
+        ]
+    );
+}
+
 #[test]
 fn test_ignore_same_transaction_submitted_to_mempool() {
     let (mut mempool, _) = setup_mempool();
@@ -318,6 +419,43 @@ fn test_timeline() {
     assert_eq!(0, pool.get_parking_lot_size());
 }
 
+#[test]
+fn test_get_pending_transactions() {
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(1, 3, 1),
+            TestTransaction::new(1, 0, 1),
+            TestTransaction::new(0, 0, 1),
+        ],
+    );
+    let view = |txns: Vec<PendingTransaction>| -> Vec<(u64, PendingTransactionStatus)> {
+        txns.iter()
+            .map(|pending| (pending.txn.sequence_number(), pending.status))
+            .collect()
+    };
+    assert_eq!(
+        view(pool.get_account_transactions(&TestTransaction::get_address(1))),
+        vec![
+            (0, PendingTransactionStatus::Ready),
+            (3, PendingTransactionStatus::Parked)
+        ]
+    );
+    assert!(pool
+        .get_account_transactions(&TestTransaction::get_address(2))
+        .is_empty());
+
+    // All transactions, grouped by sender.
+    let all = pool.get_all_transactions();
+    assert_eq!(all.len(), 3);
+    assert_eq!(pool.get_size_and_capacity().0, 3);
+    let senders: Vec<_> = all.iter().map(|pending| pending.txn.sender()).collect();
+    let mut sorted = senders.clone();
+    sorted.sort();
+    assert_eq!(senders, sorted);
+}
+
 #[test]
 fn test_capacity() {
     let mut config = NodeConfig::random();
@@ -339,6 +477,48 @@ fn test_capacity() {
     assert!(add_txn(&mut pool, TestTransaction::new(1, 2, 1)).is_ok());
 }
 
+#[test]
+fn test_capacity_in_status() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    config.mempool.capacity_per_user = 2;
+    config.mempool.backpressure_retry_after_ms = 500;
+    let mut pool = CoreMempool::new(&config);
+    let mut add = |transaction: TestTransaction| {
+        let txn = transaction.make_signed_transaction();
+        pool.add_txn(
+            txn.clone(),
+            0,
+            txn.gas_unit_price(),
+            0,
+            TimelineState::NotReady,
+            GovernanceRole::NonGovernanceRole,
+        )
+    };
+
+    let status = add(TestTransaction::new(0, 0, 1));
+    assert_eq!(status.code, MempoolStatusCode::Accepted);
+    assert!(status.capacity.is_none());
+    add(TestTransaction::new(0, 1, 1));
+
+    // The sender has too many transactions.
+    let status = add(TestTransaction::new(0, 2, 1));
+    assert_eq!(status.code, MempoolStatusCode::TooManyTransactions);
+    let capacity = status.capacity.unwrap();
+    assert_eq!((capacity.size, capacity.capacity), (2, 3));
+    assert_eq!((capacity.sender_size, capacity.capacity_per_sender), (2, 2));
+    assert_eq!(capacity.retry_after(), Duration::from_millis(500));
+
+    // Mempool is full, with no parked transaction to evict.
+    add(TestTransaction::new(1, 0, 1));
+    let status = add(TestTransaction::new(2, 0, 1));
+    assert_eq!(status.code, MempoolStatusCode::MempoolIsFull);
+    let capacity = status.capacity.unwrap();
+    assert_eq!((capacity.size, capacity.capacity), (3, 3));
+    assert_eq!(capacity.sender_size, 0);
+    assert!(capacity.size_bytes > 0);
+}
+
 #[test]
 fn test_parking_lot_eviction() {
     let mut config = NodeConfig::random();
@@ -365,6 +545,145 @@ fn test_parking_lot_eviction() {
     assert!(add_txn(&mut pool, TestTransaction::new(0, 2, 1)).is_err());
 }
 
+fn is_parked(pool: &CoreMempool, address: usize, sequence_number: u64) -> bool {
+    pool.gen_snapshot().to_string().contains(&format!(
+        "{}:{}:parked",
+        TestTransaction::get_address(address),
+        sequence_number
+    ))
+}
+
+#[test]
+fn test_parking_lot_eviction_oldest_first() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    config.mempool.eviction_policy = MempoolEvictionPolicy::OldestFirst;
+    let mut pool = CoreMempool::new(&config);
+    for address in &[2, 1, 3] {
+        add_txn(&mut pool, TestTransaction::new(*address, 5, 1)).unwrap();
+    }
+    // Mempool is full. A ready txn should push out the parked txn that arrived first.
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    assert!(!is_parked(&pool, 2, 5));
+    assert!(is_parked(&pool, 1, 5));
+    assert!(is_parked(&pool, 3, 5));
+}
+
+#[test]
+fn test_parking_lot_eviction_capacity_bytes() {
+    let txn_bytes = TestTransaction::new(0, 0, 1)
+        .make_signed_transaction()
+        .raw_txn_bytes_len();
+    let mut config = NodeConfig::random();
+    config.mempool.capacity_bytes = 3 * txn_bytes;
+    config.mempool.eviction_policy = MempoolEvictionPolicy::OldestFirst;
+    let mut pool = CoreMempool::new(&config);
+    for address in &[2, 1, 3] {
+        add_txn(&mut pool, TestTransaction::new(*address, 5, 1)).unwrap();
+    }
+    // Mempool is out of bytes. A ready txn should push out the parked txn that arrived first.
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    assert!(!is_parked(&pool, 2, 5));
+    assert!(is_parked(&pool, 1, 5));
+    assert!(is_parked(&pool, 3, 5));
+
+    // A parked txn doesn't evict others.
+    assert!(add_txn(&mut pool, TestTransaction::new(2, 5, 1)).is_err());
+}
+
+#[test]
+fn test_parking_lot_no_eviction_if_not_enough_room() {
+    let txn_bytes = TestTransaction::new(0, 0, 1)
+        .make_signed_transaction()
+        .raw_txn_bytes_len();
+    let mut config = NodeConfig::random();
+    config.mempool.capacity_bytes = 3 * txn_bytes;
+    let mut pool = CoreMempool::new(&config);
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(2, 5, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(3, 5, 1)).unwrap();
+
+    // Evicting both parked txns wouldn't make room for a txn larger than two, so none is evicted.
+    let large_txn =
+        TestTransaction::new(0, 0, 1).make_signed_transaction_with_code_size(2 * txn_bytes);
+    assert!(add_signed_txn(&mut pool, large_txn).is_err());
+    assert!(is_parked(&pool, 2, 5));
+    assert!(is_parked(&pool, 3, 5));
+
+    // A txn that fits once one of them is gone only evicts that one.
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    assert_eq!(
+        [is_parked(&pool, 2, 5), is_parked(&pool, 3, 5)]
+            .iter()
+            .filter(|parked| **parked)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_parking_lot_eviction_lowest_gas_price_first() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    config.mempool.eviction_policy = MempoolEvictionPolicy::LowestGasPriceFirst;
+    let mut pool = CoreMempool::new(&config);
+    for (address, gas_price) in &[(1, 3), (2, 1), (3, 2)] {
+        add_txn(&mut pool, TestTransaction::new(*address, 5, *gas_price)).unwrap();
+    }
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    assert!(is_parked(&pool, 1, 5));
+    assert!(!is_parked(&pool, 2, 5));
+    assert!(is_parked(&pool, 3, 5));
+
+    // Of the remaining candidates, account 3 pays less.
+    add_txn(&mut pool, TestTransaction::new(0, 1, 1)).unwrap();
+    assert!(is_parked(&pool, 1, 5));
+    assert!(!is_parked(&pool, 3, 5));
+}
+
+#[test]
+fn test_parking_lot_eviction_largest_account_footprint_first() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 5;
+    config.mempool.eviction_policy = MempoolEvictionPolicy::LargestAccountFootprintFirst;
+    let mut pool = CoreMempool::new(&config);
+    add_txn(&mut pool, TestTransaction::new(2, 3, 1)).unwrap();
+    // Account 1 holds three ready txns and one parked txn.
+    for seq in &[0, 1, 2, 9] {
+        add_txn(&mut pool, TestTransaction::new(1, *seq, 1)).unwrap();
+    }
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    assert!(!is_parked(&pool, 1, 9));
+    assert!(is_parked(&pool, 2, 3));
+}
+
+#[test]
+fn test_parking_lot_eviction_per_sender_cap() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 4;
+    config.mempool.eviction_policy = MempoolEvictionPolicy::PerSenderCap;
+    config.mempool.parking_lot_capacity_per_user = 1;
+    let mut pool = CoreMempool::new(&config);
+    add_txn(&mut pool, TestTransaction::new(2, 5, 1)).unwrap();
+    // Account 1 parks more txns than its cap.
+    for seq in &[5, 6, 7] {
+        add_txn(&mut pool, TestTransaction::new(1, *seq, 1)).unwrap();
+    }
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    assert!(!is_parked(&pool, 1, 7));
+    assert!(is_parked(&pool, 1, 6));
+    assert!(is_parked(&pool, 2, 5));
+
+    add_txn(&mut pool, TestTransaction::new(0, 1, 1)).unwrap();
+    assert!(!is_parked(&pool, 1, 6));
+    assert!(is_parked(&pool, 1, 5));
+
+    // No account is over its cap anymore, so the txn parked first goes.
+    add_txn(&mut pool, TestTransaction::new(0, 2, 1)).unwrap();
+    assert!(!is_parked(&pool, 2, 5));
+    assert!(is_parked(&pool, 1, 5));
+}
+
 #[test]
 fn test_parking_lot_evict_only_for_ready_txn_insertion() {
     let mut config = NodeConfig::random();
@@ -435,6 +754,53 @@ fn test_gc_ready_transaction() {
     assert_eq!(timeline[0].sequence_number(), 0);
 }
 
+#[test]
+fn test_removed_transactions() {
+    let mut pool = setup_mempool().0;
+    let removal_reason = |pool: &CoreMempool, address, sequence_number| {
+        pool.get_removed_transaction(&TestTransaction::get_address(address), sequence_number)
+            .map(|removed| removed.reason)
+    };
+
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(0, 0, 5)).unwrap();
+    assert_eq!(removal_reason(&pool, 0, 0), Some(RemovalReason::Replaced));
+
+    let txn = TestTransaction::new(1, 0, 1).make_signed_transaction_with_expiration_time(0);
+    add_signed_txn(&mut pool, txn).unwrap();
+    pool.gc_by_expiration_time(Duration::from_secs(1));
+    assert_eq!(removal_reason(&pool, 1, 0), Some(RemovalReason::Expired));
+
+    add_txn(&mut pool, TestTransaction::new(2, 0, 1)).unwrap();
+    pool.remove_transaction(&TestTransaction::get_address(2), 0, false);
+    assert_eq!(removal_reason(&pool, 2, 0), Some(RemovalReason::Committed));
+
+    add_txn(&mut pool, TestTransaction::new(3, 0, 1)).unwrap();
+    pool.remove_transaction(&TestTransaction::get_address(3), 0, true);
+    assert_eq!(removal_reason(&pool, 3, 0), Some(RemovalReason::Rejected));
+
+    assert_eq!(removal_reason(&pool, 3, 1), None);
+}
+
+#[test]
+fn test_min_time_in_mempool_before_gc() {
+    let mut config = NodeConfig::random();
+    config.mempool.system_transaction_timeout_secs = 0;
+    config.mempool.min_time_in_mempool_before_gc_secs = 100;
+    let mut pool = CoreMempool::new(&config);
+
+    let txn = TestTransaction::new(0, 0, 1).make_signed_transaction_with_expiration_time(0);
+    add_signed_txn(&mut pool, txn.clone()).unwrap();
+
+    // The transaction is both expired and past the system TTL, but it entered mempool too recently
+    pool.gc_by_expiration_time(Duration::from_secs(1));
+    pool.gc();
+    assert_eq!(pool.get_block(1, HashSet::new()), vec![txn]);
+    assert!(pool
+        .get_removed_transaction(&TestTransaction::get_address(0), 0)
+        .is_none());
+}
+
 #[test]
 fn test_clean_stuck_transactions() {
     let mut pool = setup_mempool().0;
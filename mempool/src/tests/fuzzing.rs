@@ -3,7 +3,11 @@
 
 use crate::{
     core_mempool::{CoreMempool, TimelineState},
-    shared_mempool::{peer_manager::PeerManager, tasks, types::SharedMempool},
+    shared_mempool::{
+        peer_manager::PeerManager,
+        tasks,
+        types::{validation_cache, SharedMempool},
+    },
 };
 use diem_config::config::NodeConfig;
 use diem_infallible::{Mutex, RwLock};
@@ -41,6 +45,7 @@ pub fn test_mempool_process_incoming_transactions_impl(
         network_senders: HashMap::new(),
         db: Arc::new(mock_db),
         validator: vm_validator,
+        validation_cache: validation_cache(&config.mempool),
         peer_manager: Arc::new(PeerManager::new(config.base.role, config.mempool)),
         subscribers: vec![],
     };
@@ -2,15 +2,61 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    core_mempool::{CoreMempool, TimelineState},
     mocks::MockSharedMempool,
+    shared_mempool::{
+        peer_manager::PeerManager,
+        tasks,
+        types::{validation_cache, SharedMempool},
+    },
     tests::common::{batch_add_signed_txn, TestTransaction},
     CommitNotification, CommittedTransaction, ConsensusRequest,
 };
+use anyhow::Result;
+use diem_config::config::NodeConfig;
+use diem_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+use diem_infallible::{Mutex, RwLock};
+use diem_types::{
+    account_address::AccountAddress,
+    mempool_status::MempoolStatusCode,
+    on_chain_config::OnChainConfigPayload,
+    test_helpers::transaction_test_helpers::get_test_signed_txn,
+    transaction::{SignedTransaction, VMValidatorResult},
+};
 use futures::{
     channel::{mpsc, oneshot},
     executor::block_on,
     sink::SinkExt,
 };
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use storage_interface::mock::MockDbReader;
+use vm_validator::{
+    mocks::mock_vm_validator::MockVMValidator, vm_validator::TransactionValidation,
+};
+
+/// Validates transactions like `MockVMValidator`, counting them
+#[derive(Clone, Default)]
+struct CountingVMValidator(Arc<AtomicUsize>);
+
+impl TransactionValidation for CountingVMValidator {
+    type ValidationInstance = MockVMValidator;
+
+    fn validate_transaction(&self, txn: SignedTransaction) -> Result<VMValidatorResult> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        TransactionValidation::validate_transaction(&MockVMValidator, txn)
+    }
+
+    fn restart(&mut self, _config: OnChainConfigPayload) -> Result<()> {
+        Ok(())
+    }
+}
 
 #[test]
 fn test_consensus_events_rejected_txns() {
@@ -96,3 +142,66 @@ fn test_state_sync_events_committed_txns() {
     assert_eq!(timeline.len(), 1);
     assert_eq!(timeline.get(0).unwrap(), &kept_txn);
 }
+
+#[test]
+fn test_validation_cache() {
+    let config = NodeConfig::random();
+    let validator = CountingVMValidator::default();
+    let validations = validator.0.clone();
+    let smp = SharedMempool {
+        mempool: Arc::new(Mutex::new(CoreMempool::new(&config))),
+        config: config.mempool.clone(),
+        network_senders: HashMap::new(),
+        db: Arc::new(MockDbReader),
+        validator: Arc::new(RwLock::new(validator)),
+        validation_cache: validation_cache(&config.mempool),
+        peer_manager: Arc::new(PeerManager::new(config.base.role, config.mempool.clone())),
+        subscribers: vec![],
+    };
+    let submit_with_status = |txn: &SignedTransaction, status: MempoolStatusCode| {
+        let statuses = block_on(tasks::process_incoming_transactions(
+            &smp,
+            vec![txn.clone()],
+            TimelineState::NotReady,
+        ));
+        assert_eq!((statuses[0].1).0.code, status);
+        validations.load(Ordering::SeqCst)
+    };
+    let submit = |txn: &SignedTransaction| submit_with_status(txn, MempoolStatusCode::Accepted);
+
+    let txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    assert_eq!(submit(&txn), 1);
+    // The same transaction submitted again isn't validated again.
+    assert_eq!(submit(&txn), 1);
+    assert_eq!(
+        submit(&TestTransaction::new(0, 1, 1).make_signed_transaction()),
+        2
+    );
+
+    // Results from before a reconfiguration are dropped.
+    block_on(tasks::process_config_update(
+        OnChainConfigPayload::new(1, Arc::new(HashMap::new())),
+        smp.validator.clone(),
+        smp.validation_cache.clone(),
+    ));
+    assert_eq!(submit(&txn), 3);
+
+    // Failures aren't cached, the transaction may pass once the sender has fixed what failed.
+    let private_key = Ed25519PrivateKey::generate(&mut StdRng::from_seed([0; 32]));
+    let failing_txn = get_test_signed_txn(
+        // Has an insufficient balance for `MockVMValidator`
+        AccountAddress::new([2; AccountAddress::LENGTH]),
+        0,
+        &private_key,
+        private_key.public_key(),
+        None,
+    );
+    assert_eq!(
+        submit_with_status(&failing_txn, MempoolStatusCode::VmError),
+        4
+    );
+    assert_eq!(
+        submit_with_status(&failing_txn, MempoolStatusCode::VmError),
+        5
+    );
+}
@@ -12,10 +12,12 @@
 use channel::{self, message_queues::QueueStyle};
 use diem_config::{
     config::{
-        DiscoveryMethod, NetworkConfig, Peer, PeerRole, PeerSet, RateLimitConfig, RoleType,
-        CONNECTION_BACKOFF_BASE, CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONCURRENT_NETWORK_REQS,
-        MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS,
-        MAX_INBOUND_CONNECTIONS, NETWORK_CHANNEL_SIZE,
+        CompressionConfig, DiscoveryMethod, DnsSeedConfig, InboundAdmissionConfig,
+        NatTraversalConfig, NetworkConfig, OutboundQosConfig, Peer, PeerReputationConfig, PeerRole,
+        PeerSet, RateLimitConfig, RoleType, CONNECTION_BACKOFF_BASE,
+        CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONCURRENT_NETWORK_REQS, MAX_CONNECTION_DELAY_MS,
+        MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS,
+        NETWORK_CHANNEL_SIZE,
     },
     network_id::NetworkContext,
 };
@@ -25,12 +27,19 @@ use diem_logger::prelude::*;
 use diem_metrics::IntCounterVec;
 use diem_network_address_encryption::Encryptor;
 use diem_time_service::TimeService;
-use diem_types::{chain_id::ChainId, network_address::NetworkAddress};
+use diem_types::{
+    chain_id::ChainId,
+    network_address::{IpPreference, NetworkAddress},
+};
 use network::{
-    connectivity_manager::{builder::ConnectivityManagerBuilder, ConnectivityRequest},
+    connectivity_manager::{
+        builder::ConnectivityManagerBuilder, dns_seeds::DnsSeedResolver, ConnectivityRequest,
+    },
     logging::NetworkSchema,
+    nat::{ExternalAddress, PortMapper},
     peer_manager::{
         builder::{AuthenticationMode, PeerManagerBuilder},
+        reputation::PeerReputation,
         ConnectionRequestSender,
     },
     protocols::{
@@ -42,7 +51,7 @@ use network::{
 use network_simple_onchain_discovery::{
     builder::ValidatorSetChangeListenerBuilder, gen_simple_discovery_reconfig_subscription,
 };
-use std::{clone::Clone, collections::HashMap, sync::Arc};
+use std::{clone::Clone, collections::HashMap, sync::Arc, time::Duration};
 use subscription_service::ReconfigSubscription;
 use tokio::runtime::Handle;
 
@@ -68,6 +77,10 @@ pub struct NetworkBuilder {
     connectivity_manager_builder: Option<ConnectivityManagerBuilder>,
     health_checker_builder: Option<HealthCheckerBuilder>,
     peer_manager_builder: PeerManagerBuilder,
+    // The network public key of the node and how to map its port, if it's behind a NAT.
+    nat_traversal: Option<(PublicKey, NatTraversalConfig)>,
+    external_address: ExternalAddress,
+    dns_seed_resolver: Option<DnsSeedResolver>,
 
     // (StateSync) ReconfigSubscriptions required by internal Network components.
     reconfig_subscriptions: Vec<ReconfigSubscription>,
@@ -90,6 +103,14 @@ impl NetworkBuilder {
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
+        peer_inbound_rate_limit_config: Option<RateLimitConfig>,
+        peer_outbound_rate_limit_config: Option<RateLimitConfig>,
+        protocol_rate_limit_configs: &HashMap<String, RateLimitConfig>,
+        peer_reputation_config: PeerReputationConfig,
+        outbound_qos_config: OutboundQosConfig,
+        compression_config: CompressionConfig,
+        ip_preference: IpPreference,
+        inbound_admission_config: InboundAdmissionConfig,
     ) -> Self {
         // A network cannot exist without a PeerManager
         // TODO:  construct this in create and pass it to new() as a parameter. The complication is manual construction of NetworkBuilder in various tests.
@@ -107,6 +128,14 @@ impl NetworkBuilder {
             inbound_connection_limit,
             inbound_rate_limit_config,
             outbound_rate_limit_config,
+            peer_inbound_rate_limit_config,
+            peer_outbound_rate_limit_config,
+            protocol_rate_limit_configs,
+            peer_reputation_config,
+            outbound_qos_config,
+            compression_config,
+            ip_preference,
+            inbound_admission_config,
         );
 
         NetworkBuilder {
@@ -118,6 +147,9 @@ impl NetworkBuilder {
             connectivity_manager_builder: None,
             health_checker_builder: None,
             peer_manager_builder,
+            nat_traversal: None,
+            external_address: Arc::new(RwLock::new(None)),
+            dns_seed_resolver: None,
             reconfig_subscriptions: vec![],
         }
     }
@@ -147,6 +179,14 @@ impl NetworkBuilder {
             MAX_INBOUND_CONNECTIONS,
             None,
             None,
+            None,
+            None,
+            &HashMap::new(),
+            PeerReputationConfig::default(),
+            OutboundQosConfig::default(),
+            CompressionConfig::default(),
+            IpPreference::default(),
+            InboundAdmissionConfig::default(),
         );
 
         builder.add_connectivity_manager(
@@ -158,6 +198,7 @@ impl NetworkBuilder {
             CONNECTIVITY_CHECK_INTERVAL_MS,
             NETWORK_CHANNEL_SIZE,
             mutual_authentication,
+            IpPreference::default(),
         );
 
         builder
@@ -202,6 +243,14 @@ impl NetworkBuilder {
             config.max_inbound_connections,
             config.inbound_rate_limit_config,
             config.outbound_rate_limit_config,
+            config.peer_inbound_rate_limit_config,
+            config.peer_outbound_rate_limit_config,
+            &config.protocol_rate_limit_configs,
+            config.peer_reputation_config,
+            config.outbound_qos_config,
+            config.compression_config.clone(),
+            config.ip_preference,
+            config.inbound_admission_config,
         );
 
         network_builder.add_connection_monitoring(
@@ -219,13 +268,15 @@ impl NetworkBuilder {
         //    trusted peers set.
         // 2) networks with a discovery protocol need connmgr to connect to newly
         //    discovered peers.
-        // 3) if we have seed peers, then we need connmgr to connect to them.
+        // 3) if we have seed peers, from the config or from DNS, then we need connmgr to
+        //    connect to them.
         // TODO(philiphayes): could probably use a better way to specify these cases
         // TODO:  Why not add ConnectivityManager always?
         if config.mutual_authentication
             || config.discovery_method != DiscoveryMethod::None
             || !config.seed_addrs.is_empty()
             || !config.seeds.is_empty()
+            || !config.dns_seed_config.names.is_empty()
         {
             let seeds = merge_seeds(config);
 
@@ -238,6 +289,7 @@ impl NetworkBuilder {
                 config.connectivity_check_interval_ms,
                 config.network_channel_size,
                 config.mutual_authentication,
+                config.ip_preference,
             );
         }
 
@@ -248,6 +300,14 @@ impl NetworkBuilder {
             DiscoveryMethod::None => {}
         }
 
+        if !config.dns_seed_config.names.is_empty() {
+            network_builder.add_dns_seeds(&config.dns_seed_config);
+        }
+
+        if config.nat_traversal_config.enabled {
+            network_builder.add_nat_traversal(pubkey, config.nat_traversal_config);
+        }
+
         network_builder
     }
 
@@ -296,6 +356,31 @@ impl NetworkBuilder {
                 "{} Started validator set listener", self.network_context
             );
         }
+
+        if let Some(dns_seed_resolver) = self.dns_seed_resolver.take() {
+            executor.spawn(dns_seed_resolver.start());
+            debug!(
+                NetworkSchema::new(&self.network_context),
+                "{} Started DNS seed resolver", self.network_context
+            );
+        }
+
+        if let Some((pubkey, config)) = self.nat_traversal.take() {
+            let port_mapper = PortMapper::new(
+                self.network_context.clone(),
+                self.time_service.clone(),
+                self.peer_manager_builder.listen_address(),
+                pubkey,
+                Duration::from_secs(u64::from(config.lease_duration_secs)),
+                Duration::from_millis(config.timeout_ms),
+                self.external_address.clone(),
+            );
+            executor.spawn(port_mapper.start());
+            debug!(
+                NetworkSchema::new(&self.network_context),
+                "{} Started port mapper", self.network_context
+            );
+        }
         self
     }
 
@@ -317,6 +402,16 @@ impl NetworkBuilder {
         self.peer_manager_builder.listen_address()
     }
 
+    /// The address peers outside of the NAT of the node can dial, once its port is mapped.
+    pub fn external_address(&self) -> ExternalAddress {
+        self.external_address.clone()
+    }
+
+    /// The scores of the peers of this network, for the peer monitoring endpoint.
+    pub fn peer_reputation(&self) -> Arc<RwLock<PeerReputation>> {
+        self.peer_manager_builder.peer_reputation()
+    }
+
     /// Add a [`ConnectivityManager`] to the network.
     ///
     /// [`ConnectivityManager`] is responsible for ensuring that we are connected
@@ -336,6 +431,7 @@ impl NetworkBuilder {
         connectivity_check_interval_ms: u64,
         channel_size: usize,
         mutual_authentication: bool,
+        ip_preference: IpPreference,
     ) -> &mut Self {
         let pm_conn_mgr_notifs_rx = self.peer_manager_builder.add_connection_event_listener();
         let outbound_connection_limit = if !self.network_context.network_id().is_validator_network()
@@ -358,6 +454,7 @@ impl NetworkBuilder {
             pm_conn_mgr_notifs_rx,
             outbound_connection_limit,
             mutual_authentication,
+            ip_preference,
         ));
         self
     }
@@ -381,6 +478,29 @@ impl NetworkBuilder {
         self
     }
 
+    /// Discover seed peers published in DNS, for the ConnectivityManager to connect to.
+    fn add_dns_seeds(&mut self, config: &DnsSeedConfig) -> &mut Self {
+        let conn_mgr_reqs_tx = self
+            .conn_mgr_reqs_tx()
+            .expect("ConnectivityManager must be installed for DNS seeds");
+        self.dns_seed_resolver = Some(DnsSeedResolver::new(
+            self.network_context(),
+            self.time_service.clone(),
+            config.names.clone(),
+            Duration::from_secs(config.refresh_interval_secs),
+            Duration::from_millis(config.timeout_ms),
+            conn_mgr_reqs_tx,
+        ));
+        self
+    }
+
+    /// Map the listen port on the router of the node, for it to accept inbound connections from
+    /// outside of its NAT.
+    fn add_nat_traversal(&mut self, pubkey: PublicKey, config: NatTraversalConfig) -> &mut Self {
+        self.nat_traversal = Some((pubkey, config));
+        self
+    }
+
     /// Add a HealthChecker to the network.
     fn add_connection_monitoring(
         &mut self,
@@ -4,7 +4,9 @@
 //! TCP Transport
 use crate::transport::Transport;
 use diem_types::{
-    network_address::{parse_dns_tcp, parse_ip_tcp, parse_tcp, IpFilter, NetworkAddress},
+    network_address::{
+        parse_dns_tcp, parse_ip_tcp, parse_tcp, IpFilter, IpPreference, NetworkAddress,
+    },
     PeerId,
 };
 use futures::{
@@ -14,6 +16,7 @@ use futures::{
     stream::Stream,
 };
 use proxy::Proxy;
+use socket2::{Domain, Socket, Type};
 use std::{
     convert::TryFrom,
     fmt::Debug,
@@ -36,6 +39,8 @@ pub struct TcpTransport {
     pub ttl: Option<u32>,
     /// `TCP_NODELAY` to set for opened sockets, or `None` to keep default.
     pub nodelay: Option<bool>,
+    /// Which IP family to dial first when a name resolves to both.
+    pub ip_preference: IpPreference,
 }
 
 impl TcpTransport {
@@ -69,7 +74,7 @@ impl Transport for TcpTransport {
             return Err(invalid_addr_error(&addr));
         }
 
-        let listener = ::std::net::TcpListener::bind((ipaddr, port))?;
+        let listener = bind_listener(SocketAddr::new(ipaddr, port))?;
         listener.set_nonblocking(true)?;
         let listener = TcpListener::try_from(listener)?;
         let listen_addr = NetworkAddress::from(listener.local_addr()?);
@@ -123,7 +128,7 @@ impl Transport for TcpTransport {
         let f: Pin<Box<dyn Future<Output = io::Result<TcpStream>> + Send + 'static>> =
             Box::pin(match proxy_addr {
                 Some(proxy_addr) => Either::Left(connect_via_proxy(proxy_addr, addr)),
-                None => Either::Right(resolve_and_connect(addr)),
+                None => Either::Right(resolve_and_connect(addr, self.ip_preference)),
             });
 
         Ok(TcpOutbound {
@@ -133,6 +138,27 @@ impl Transport for TcpTransport {
     }
 }
 
+/// Binds a listener on `addr`. A listener on the unspecified IPv6 address, `::`, also accepts
+/// connections over IPv4, whatever the default of the host is.
+fn bind_listener(addr: SocketAddr) -> io::Result<::std::net::TcpListener> {
+    match addr {
+        SocketAddr::V6(v6_addr) if v6_addr.ip().is_unspecified() => {
+            let socket = Socket::new(
+                Domain::ipv6(),
+                Type::stream(),
+                Some(socket2::Protocol::tcp()),
+            )?;
+            socket.set_only_v6(false)?;
+            // Like std's bind, so that the port can be bound again right after a restart.
+            socket.set_reuse_address(true)?;
+            socket.bind(&addr.into())?;
+            socket.listen(128)?;
+            Ok(socket.into_tcp_listener())
+        }
+        _ => ::std::net::TcpListener::bind(addr),
+    }
+}
+
 /// Try to lookup the dns name, then filter addrs according to the `IpFilter`.
 async fn resolve_with_filter(
     ip_filter: IpFilter,
@@ -146,7 +172,13 @@ async fn resolve_with_filter(
 
 /// Note: we need to take ownership of this `NetworkAddress` (instead of just
 /// borrowing the `&[Protocol]` slice) so this future can be `Send + 'static`.
-pub async fn resolve_and_connect(addr: NetworkAddress) -> io::Result<TcpStream> {
+///
+/// A name resolving to several addresses has them dialed in turn, those of the family of
+/// `ip_preference` first.
+pub async fn resolve_and_connect(
+    addr: NetworkAddress,
+    ip_preference: IpPreference,
+) -> io::Result<TcpStream> {
     let protos = addr.as_slice();
 
     if let Some(((ipaddr, port), _addr_suffix)) = parse_ip_tcp(protos) {
@@ -155,11 +187,14 @@ pub async fn resolve_and_connect(addr: NetworkAddress) -> io::Result<TcpStream>
         TcpStream::connect((ipaddr, port)).await
     } else if let Some(((ip_filter, dns_name, port), _addr_suffix)) = parse_dns_tcp(protos) {
         // resolve dns name and filter
-        let socketaddr_iter = resolve_with_filter(ip_filter, dns_name.as_ref(), port).await?;
+        let mut socketaddrs: Vec<_> = resolve_with_filter(ip_filter, dns_name.as_ref(), port)
+            .await?
+            .collect();
+        socketaddrs.sort_by_key(|socketaddr| ip_preference.rank_ip(socketaddr.ip()));
         let mut last_err = None;
 
         // try to connect until the first succeeds
-        for socketaddr in socketaddr_iter {
+        for socketaddr in socketaddrs {
             match TcpStream::connect(socketaddr).await {
                 Ok(stream) => return Ok(stream),
                 Err(err) => last_err = Some(err),
@@ -363,6 +398,35 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn dual_stack_listen() -> Result<(), ::std::io::Error> {
+        let t = TcpTransport::default();
+        let (listener, addr) = match t.listen_on("/ip6/::/tcp/0".parse().unwrap()) {
+            Ok(listening) => listening,
+            // The host has IPv6 disabled.
+            Err(err) if err.kind() == io::ErrorKind::AddrNotAvailable => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        let ((_, port), _) = parse_ip_tcp(addr.as_slice()).unwrap();
+
+        // Peers dialing over IPv4 are seen with their IPv4 address, not an IPv4-mapped one.
+        let dial = t.dial(
+            PeerId::random(),
+            format!("/ip4/127.0.0.1/tcp/{}", port).parse().unwrap(),
+        )?;
+        let listener = listener.into_future().map(|(maybe_result, _stream)| {
+            let (_incoming, addr) = maybe_result.unwrap().unwrap();
+            addr
+        });
+        let (outgoing, remote_addr) = join(dial, listener).await;
+        outgoing?;
+        assert_eq!(
+            remote_addr.find_ip_addr(),
+            Some("127.0.0.1".parse().unwrap())
+        );
+        Ok(())
+    }
+
     #[test]
     fn unsupported_multiaddrs() {
         let t = TcpTransport::default();
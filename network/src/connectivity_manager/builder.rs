@@ -9,6 +9,7 @@ use crate::{
 use diem_config::{config::PeerSet, network_id::NetworkContext};
 use diem_infallible::RwLock;
 use diem_time_service::TimeService;
+use diem_types::network_address::IpPreference;
 use std::{sync::Arc, time::Duration};
 use tokio::runtime::Handle;
 use tokio_retry::strategy::ExponentialBackoff;
@@ -34,6 +35,7 @@ impl ConnectivityManagerBuilder {
         connection_notifs_rx: conn_notifs_channel::Receiver,
         outbound_connection_limit: Option<usize>,
         mutual_authentication: bool,
+        ip_preference: IpPreference,
     ) -> Self {
         let (conn_mgr_reqs_tx, conn_mgr_reqs_rx) = channel::new(
             channel_size,
@@ -55,6 +57,7 @@ impl ConnectivityManagerBuilder {
                 Duration::from_millis(max_connection_delay_ms),
                 outbound_connection_limit,
                 mutual_authentication,
+                ip_preference,
             )),
         }
     }
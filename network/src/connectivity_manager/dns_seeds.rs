@@ -0,0 +1,283 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Discovery of seed peers published in DNS, so that operators can rotate their seeds without
+//! every downstream node editing its config.
+//!
+//! Seeds are published under each configured name in either of two ways:
+//!
+//! 1. TXT records, each holding the full network address of a seed, such as
+//!    `/dns/seed1.example.com/tcp/6180/ln-noise-ik/<pubkey>/ln-handshake/0`.
+//! 2. SRV records, each pointing at the host and port of a seed, whose TXT records hold its network
+//!    public key as `noise-ik=<pubkey>`.
+//!
+//! [`DnsSeedResolver`] resolves the names every `refresh_interval_secs`, and hands the seeds over
+//! to the [`ConnectivityManager`](crate::connectivity_manager::ConnectivityManager) as their own
+//! discovery source. A name which fails to resolve keeps the seeds it last resolved to.
+//!
+//! Nothing authenticates the answers of the nameservers, so the network public keys of the seeds
+//! only tell whom to dial: the config rejects DNS seeds on mutually authenticated networks, where
+//! they would be trusted peers, and the seeds are given the weakest upstream role.
+
+use crate::{
+    connectivity_manager::{ConnectivityRequest, DiscoverySource},
+    logging::NetworkSchema,
+};
+use anyhow::Result;
+use diem_config::{
+    config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION},
+    network_id::NetworkContext,
+};
+use diem_crypto::{x25519, ValidCryptoMaterialStringExt};
+use diem_logger::prelude::*;
+use diem_time_service::{TimeService, TimeServiceTrait};
+use diem_types::{
+    account_address::from_identity_public_key,
+    network_address::{DnsName, NetworkAddress, Protocol},
+};
+use futures::sink::SinkExt;
+use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
+use trust_dns_resolver::{error::ResolveErrorKind, system_conf, TokioAsyncResolver};
+
+/// Prefix of the TXT records holding the network public key of the target of an SRV record.
+const NOISE_KEY_PREFIX: &str = "noise-ik=";
+
+pub struct DnsSeedResolver {
+    network_context: Arc<NetworkContext>,
+    time_service: TimeService,
+    names: Vec<String>,
+    refresh_interval: Duration,
+    request_timeout: Duration,
+    conn_mgr_reqs_tx: channel::Sender<ConnectivityRequest>,
+    /// The addresses each name last resolved to
+    addresses: HashMap<String, Vec<NetworkAddress>>,
+}
+
+impl DnsSeedResolver {
+    pub fn new(
+        network_context: Arc<NetworkContext>,
+        time_service: TimeService,
+        names: Vec<String>,
+        refresh_interval: Duration,
+        request_timeout: Duration,
+        conn_mgr_reqs_tx: channel::Sender<ConnectivityRequest>,
+    ) -> Self {
+        Self {
+            network_context,
+            time_service,
+            names,
+            refresh_interval,
+            request_timeout,
+            conn_mgr_reqs_tx,
+            addresses: HashMap::new(),
+        }
+    }
+
+    pub async fn start(mut self) {
+        info!(
+            NetworkSchema::new(&self.network_context),
+            "{} Starting DNS seed discovery of {:?}", self.network_context, self.names
+        );
+        let resolver = match self.resolver() {
+            Ok(resolver) => resolver,
+            Err(err) => {
+                error!(
+                    NetworkSchema::new(&self.network_context),
+                    error = %err,
+                    "{} Failed to set up the DNS resolver, stopping DNS seed discovery: {}",
+                    self.network_context,
+                    err
+                );
+                return;
+            }
+        };
+        loop {
+            for name in self.names.clone() {
+                match self.resolve(&resolver, &name).await {
+                    Ok(addresses) => {
+                        self.addresses.insert(name, addresses);
+                    }
+                    Err(err) => {
+                        warn!(
+                            NetworkSchema::new(&self.network_context),
+                            error = %err,
+                            "{} Failed to resolve seeds of {}, keeping the last ones: {}",
+                            self.network_context,
+                            name,
+                            err
+                        );
+                    }
+                }
+            }
+
+            let seeds = seed_peers(self.addresses.values().flatten().cloned());
+            let request = ConnectivityRequest::UpdateDiscoveredPeers(DiscoverySource::Dns, seeds);
+            if let Err(err) = self.conn_mgr_reqs_tx.send(request).await {
+                warn!(
+                    NetworkSchema::new(&self.network_context),
+                    "{} ConnectivityManager is gone, stopping DNS seed discovery: {}",
+                    self.network_context,
+                    err
+                );
+                break;
+            }
+            self.time_service.sleep(self.refresh_interval).await;
+        }
+    }
+
+    /// A resolver querying the nameservers of the system, waiting `request_timeout` for each.
+    fn resolver(&self) -> Result<TokioAsyncResolver> {
+        let (config, mut options) = system_conf::read_system_conf()?;
+        options.timeout = self.request_timeout;
+        Ok(TokioAsyncResolver::tokio(config, options)?)
+    }
+
+    /// The addresses of the seeds published under `name`.
+    async fn resolve(
+        &self,
+        resolver: &TokioAsyncResolver,
+        name: &str,
+    ) -> Result<Vec<NetworkAddress>> {
+        let mut addresses: Vec<_> = txt_records(resolver, name)
+            .await?
+            .iter()
+            .filter_map(|record| self.parse_address(name, record))
+            .collect();
+
+        let services: Vec<_> = match resolver.srv_lookup(name).await {
+            Ok(lookup) => lookup
+                .iter()
+                .map(|srv| (srv.target().to_utf8(), srv.port()))
+                .collect(),
+            Err(err) if matches!(err.kind(), ResolveErrorKind::NoRecordsFound { .. }) => vec![],
+            Err(err) => return Err(err.into()),
+        };
+        for (target, port) in services {
+            for record in txt_records(resolver, &target).await? {
+                if let Some(address) = service_address(&target, port, &record) {
+                    addresses.push(address);
+                }
+            }
+        }
+        Ok(addresses)
+    }
+
+    fn parse_address(&self, name: &str, record: &str) -> Option<NetworkAddress> {
+        match NetworkAddress::from_str(record) {
+            Ok(address) if address.find_noise_proto().is_some() => Some(address),
+            _ => {
+                debug!(
+                    NetworkSchema::new(&self.network_context),
+                    "{} Skipping TXT record of {} which isn't a seed address: {}",
+                    self.network_context,
+                    name,
+                    record
+                );
+                None
+            }
+        }
+    }
+}
+
+/// The TXT records of `name`, each the concatenation of its strings.
+async fn txt_records(resolver: &TokioAsyncResolver, name: &str) -> Result<Vec<String>> {
+    match resolver.txt_lookup(name).await {
+        Ok(lookup) => Ok(lookup
+            .iter()
+            .map(|txt| {
+                txt.txt_data()
+                    .iter()
+                    .map(|text| String::from_utf8_lossy(text))
+                    .collect()
+            })
+            .collect()),
+        Err(err) if matches!(err.kind(), ResolveErrorKind::NoRecordsFound { .. }) => Ok(vec![]),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// The address of the seed served at `target` and `port`, if `record` holds its network public
+/// key.
+fn service_address(target: &str, port: u16, record: &str) -> Option<NetworkAddress> {
+    let pubkey = record.strip_prefix(NOISE_KEY_PREFIX)?;
+    let pubkey = x25519::PublicKey::from_encoded_string(pubkey.trim()).ok()?;
+    let host = DnsName::from_str(target.trim_end_matches('.')).ok()?;
+    let address = NetworkAddress::from(Protocol::Dns(host))
+        .push(Protocol::Tcp(port))
+        .append_prod_protos(pubkey, HANDSHAKE_VERSION);
+    Some(address)
+}
+
+/// Groups seed addresses by the peer their network public key belongs to.
+fn seed_peers(addresses: impl Iterator<Item = NetworkAddress>) -> PeerSet {
+    let mut addresses_by_peer: HashMap<_, Vec<_>> = HashMap::new();
+    for address in addresses {
+        if let Some(pubkey) = address.find_noise_proto() {
+            addresses_by_peer
+                .entry(from_identity_public_key(pubkey))
+                .or_default()
+                .push(address);
+        }
+    }
+    addresses_by_peer
+        .into_iter()
+        .map(|(peer_id, addresses)| (peer_id, Peer::from_addrs(PeerRole::Upstream, addresses)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use diem_crypto::{test_utils::TEST_SEED, Uniform};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_seed_peers() {
+        let mut rng = StdRng::from_seed(TEST_SEED);
+        let pubkey = x25519::PrivateKey::generate(&mut rng).public_key();
+        let other_pubkey = x25519::PrivateKey::generate(&mut rng).public_key();
+
+        let target = "seed1.example.com.";
+        let record = format!("{}{}", NOISE_KEY_PREFIX, pubkey);
+        let srv_address = service_address(target, 6180, &record).unwrap();
+        assert_eq!(
+            srv_address.to_string(),
+            format!(
+                "/dns/seed1.example.com/tcp/6180/ln-noise-ik/{}/ln-handshake/0",
+                pubkey
+            )
+        );
+        assert_eq!(service_address(target, 6180, "v=spf1 -all"), None);
+
+        let txt_address: NetworkAddress = format!(
+            "/ip4/203.0.113.7/tcp/6180/ln-noise-ik/{}/ln-handshake/0",
+            pubkey
+        )
+        .parse()
+        .unwrap();
+        let other_address: NetworkAddress = format!(
+            "/dns/seed2.example.com/tcp/6180/ln-noise-ik/{}/ln-handshake/0",
+            other_pubkey
+        )
+        .parse()
+        .unwrap();
+        let seeds = seed_peers(
+            vec![
+                srv_address.clone(),
+                txt_address.clone(),
+                other_address.clone(),
+            ]
+            .into_iter(),
+        );
+
+        assert_eq!(seeds.len(), 2);
+        let seed = &seeds[&from_identity_public_key(pubkey)];
+        assert_eq!(seed.addresses, vec![srv_address, txt_address]);
+        assert!(seed.keys.contains(&pubkey));
+        assert_eq!(seed.role, PeerRole::Upstream);
+        assert_eq!(
+            seeds[&from_identity_public_key(other_pubkey)].addresses,
+            vec![other_address]
+        );
+    }
+}
@@ -9,11 +9,12 @@
 //! Consensus actor informs the ConnectivityManager of eligible nodes.
 //!
 //! Different discovery sources notify the ConnectivityManager of updates to
-//! peers' addresses. Currently, there are 2 discovery sources (ordered by
+//! peers' addresses. Currently, there are 3 discovery sources (ordered by
 //! decreasing dial priority, i.e., first is highest priority):
 //!
 //! 1. Onchain discovery protocol
-//! 2. Seed peers from config
+//! 2. Seed peers published in DNS
+//! 3. Seed peers from config
 //!
 //! In other words, if a we have some addresses discovered via onchain discovery
 //! and some seed addresses from our local config, we will try the onchain
@@ -40,7 +41,10 @@ use diem_crypto::x25519;
 use diem_infallible::RwLock;
 use diem_logger::prelude::*;
 use diem_time_service::{TimeService, TimeServiceTrait};
-use diem_types::{network_address::NetworkAddress, PeerId};
+use diem_types::{
+    network_address::{IpPreference, NetworkAddress},
+    PeerId,
+};
 use futures::{
     channel::oneshot,
     future::{BoxFuture, FutureExt},
@@ -64,6 +68,7 @@ use std::{
 use tokio_retry::strategy::jitter;
 
 pub mod builder;
+pub mod dns_seeds;
 #[cfg(test)]
 mod test;
 
@@ -113,6 +118,8 @@ pub struct ConnectivityManager<TBackoff> {
     rng: SmallRng,
     /// Whether we are using mutual authentication or not
     mutual_authentication: bool,
+    /// Which IP family to dial first, when a peer has addresses of both
+    ip_preference: IpPreference,
 }
 
 /// Different sources for peer addresses, ordered by priority (Onchain=highest,
@@ -121,6 +128,7 @@ pub struct ConnectivityManager<TBackoff> {
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, NumVariants, Serialize)]
 pub enum DiscoverySource {
     OnChainValidatorSet,
+    Dns,
     Config,
 }
 
@@ -137,6 +145,7 @@ impl fmt::Display for DiscoverySource {
             "{}",
             match self {
                 DiscoverySource::OnChainValidatorSet => "OnChainValidatorSet",
+                DiscoverySource::Dns => "Dns",
                 DiscoverySource::Config => "Config",
             }
         )
@@ -261,6 +270,7 @@ where
         max_delay: Duration,
         outbound_connection_limit: Option<usize>,
         mutual_authentication: bool,
+        ip_preference: IpPreference,
     ) -> Self {
         assert!(
             eligible.read().is_empty(),
@@ -291,6 +301,7 @@ where
             outbound_connection_limit,
             rng: SmallRng::from_entropy(),
             mutual_authentication,
+            ip_preference,
         };
 
         // set the initial config addresses and pubkeys
@@ -505,7 +516,10 @@ where
         // Choose the next addr to dial for this peer. Currently, we just
         // round-robin the selection, i.e., try the sequence:
         // addr[0], .., addr[len-1], addr[0], ..
-        let addr = dial_state.next_addr(&peer.addrs).clone();
+        // with the addresses of each discovery source ordered by IP family preference.
+        let addr = dial_state
+            .next_addr(&peer.addrs, self.ip_preference)
+            .clone();
 
         // Using the DialState's backoff strategy, compute the delay until
         // the next dial attempt for this peer.
@@ -875,8 +889,17 @@ impl Addresses {
         self.update(src, Vec::new())
     }
 
-    fn get(&self, idx: usize) -> Option<&NetworkAddress> {
-        self.0.iter().flatten().nth(idx)
+    /// The address at `idx` in dialing order: by discovery source, then with those of the
+    /// preferred IP family first.
+    fn get(&self, idx: usize, ip_preference: IpPreference) -> Option<&NetworkAddress> {
+        self.0
+            .iter()
+            .flat_map(|addrs| {
+                let mut addrs: Vec<_> = addrs.iter().collect();
+                addrs.sort_by_key(|addr| ip_preference.rank_addr(addr));
+                addrs
+            })
+            .nth(idx)
     }
 
     /// The Union isn't stable, and order is completely disregarded
@@ -961,13 +984,17 @@ where
         }
     }
 
-    fn next_addr<'a>(&mut self, addrs: &'a Addresses) -> &'a NetworkAddress {
+    fn next_addr<'a>(
+        &mut self,
+        addrs: &'a Addresses,
+        ip_preference: IpPreference,
+    ) -> &'a NetworkAddress {
         assert!(!addrs.is_empty());
 
         let addr_idx = self.addr_idx;
         self.addr_idx = self.addr_idx.wrapping_add(1);
 
-        addrs.get(addr_idx % addrs.len()).unwrap()
+        addrs.get(addr_idx % addrs.len(), ip_preference).unwrap()
     }
 
     fn next_backoff_delay(&mut self, max_delay: Duration) -> Duration {
@@ -12,7 +12,7 @@ use diem_config::config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION};
 use diem_crypto::{test_utils::TEST_SEED, x25519, Uniform};
 use diem_logger::info;
 use diem_time_service::{MockTimeService, TimeService};
-use diem_types::network_address::NetworkAddress;
+use diem_types::network_address::{IpPreference, NetworkAddress};
 use futures::{executor::block_on, future, SinkExt};
 use maplit::{hashmap, hashset};
 use rand::rngs::StdRng;
@@ -103,6 +103,7 @@ impl TestHarness {
             MAX_CONNECTION_DELAY,
             Some(MAX_TEST_CONNECTIONS),
             true, /* mutual_authentication */
+            IpPreference::Any,
         );
         let mock = Self {
             trusted_peers,
@@ -669,6 +670,35 @@ fn multiple_addrs_wrapping() {
     block_on(future::join(conn_mgr.start(), test));
 }
 
+#[test]
+fn addrs_ip_preference() {
+    let ip4 = network_address("/ip4/127.0.0.1/tcp/9091");
+    let ip6 = network_address("/ip6/::1/tcp/9091");
+    let dns = network_address("/dns/example.com/tcp/9091");
+    let config_ip6 = network_address("/ip6/::1/tcp/9092");
+    let mut addrs = Addresses::default();
+    addrs.update(
+        DiscoverySource::OnChainValidatorSet,
+        vec![ip4.clone(), dns.clone(), ip6.clone()],
+    );
+    addrs.update(DiscoverySource::Config, vec![config_ip6.clone()]);
+    let dial_order = |ip_preference| {
+        (0..addrs.len())
+            .map(|idx| addrs.get(idx, ip_preference).unwrap().clone())
+            .collect::<Vec<_>>()
+    };
+
+    // Addresses of a higher priority source still come first.
+    assert_eq!(
+        dial_order(IpPreference::PreferIp6),
+        vec![ip6.clone(), dns.clone(), ip4.clone(), config_ip6.clone()]
+    );
+    assert_eq!(
+        dial_order(IpPreference::Any),
+        vec![ip4, dns, ip6, config_ip6]
+    );
+}
+
 // Test that connectivity manager will still work when dialing a peer with
 // multiple listen addrs and then that peer advertises a smaller number of addrs.
 #[test]
@@ -1,7 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::protocols::wire::handshake::v1::ProtocolId;
+use crate::protocols::wire::{compression::CompressionAlgorithm, handshake::v1::ProtocolId};
 use diem_config::network_id::NetworkContext;
 use diem_metrics::{
     register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
@@ -10,11 +10,16 @@ use diem_metrics::{
 use diem_types::PeerId;
 use netcore::transport::ConnectionOrigin;
 use once_cell::sync::Lazy;
+use serde::Serialize;
 use short_hex_str::AsShortHexStr;
+use std::collections::BTreeMap;
 
 // some type labels
 pub const REQUEST_LABEL: &str = "request";
 pub const RESPONSE_LABEL: &str = "response";
+pub const DIRECT_SEND_LABEL: &str = "direct_send";
+pub const COMPRESSED_LABEL: &str = "compressed";
+pub const UNCOMPRESSED_LABEL: &str = "uncompressed";
 
 // some state labels
 pub const CANCELED_LABEL: &str = "canceled";
@@ -63,6 +68,88 @@ pub fn connections_rejected(
     ])
 }
 
+pub static DIEM_NETWORK_PEER_MISBEHAVIORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_network_peer_misbehaviors",
+        "Number of misbehaviors reported of remote peers, by kind",
+        &["role_type", "network_id", "peer_id", "misbehavior"]
+    )
+    .unwrap()
+});
+
+pub fn peer_misbehaviors(network_context: &NetworkContext, misbehavior: &str) -> IntCounter {
+    DIEM_NETWORK_PEER_MISBEHAVIORS.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        misbehavior,
+    ])
+}
+
+pub static DIEM_NETWORK_INBOUND_CONNECTIONS_REFUSED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_network_inbound_connections_refused",
+        "Number of inbound connections refused before their handshake, by reason",
+        &["role_type", "network_id", "peer_id", "reason"]
+    )
+    .unwrap()
+});
+
+pub fn inbound_connections_refused(network_context: &NetworkContext, reason: &str) -> IntCounter {
+    DIEM_NETWORK_INBOUND_CONNECTIONS_REFUSED.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        reason,
+    ])
+}
+
+pub static DIEM_NETWORK_PEERS_BANNED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_network_peers_banned",
+        "Number of remote peers banned for scoring too low",
+        &["role_type", "network_id", "peer_id"]
+    )
+    .unwrap()
+});
+
+pub fn peers_banned(network_context: &NetworkContext) -> IntCounter {
+    DIEM_NETWORK_PEERS_BANNED.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+    ])
+}
+
+pub static DIEM_NETWORK_RATE_LIMITED_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_network_rate_limited_messages",
+        "Number of messages dropped for going over the byte rate limit of their protocol",
+        &[
+            "role_type",
+            "network_id",
+            "peer_id",
+            "protocol_id",
+            "direction"
+        ]
+    )
+    .unwrap()
+});
+
+pub fn rate_limited_messages(
+    network_context: &NetworkContext,
+    protocol_id: ProtocolId,
+    direction_label: &'static str,
+) -> IntCounter {
+    DIEM_NETWORK_RATE_LIMITED_MESSAGES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        protocol_id.as_str(),
+        direction_label,
+    ])
+}
+
 pub static DIEM_NETWORK_PEER_CONNECTED: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
         "diem_network_peer_connected",
@@ -261,6 +348,191 @@ pub fn inbound_rpc_handler_latency(
     ])
 }
 
+pub static DIEM_NETWORK_PROTOCOL_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_network_protocol_messages",
+        "Number of messages by protocol",
+        &[
+            "role_type",
+            "network_id",
+            "peer_id",
+            "protocol_id",
+            "type",
+            "direction"
+        ]
+    )
+    .unwrap()
+});
+
+pub fn protocol_messages(
+    network_context: &NetworkContext,
+    protocol_id: ProtocolId,
+    type_label: &'static str,
+    direction_label: &'static str,
+) -> IntCounter {
+    DIEM_NETWORK_PROTOCOL_MESSAGES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        protocol_id.as_str(),
+        type_label,
+        direction_label,
+    ])
+}
+
+pub static DIEM_NETWORK_PROTOCOL_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_network_protocol_bytes",
+        "Number of message bytes by protocol",
+        &[
+            "role_type",
+            "network_id",
+            "peer_id",
+            "protocol_id",
+            "type",
+            "direction"
+        ]
+    )
+    .unwrap()
+});
+
+pub fn protocol_bytes(
+    network_context: &NetworkContext,
+    protocol_id: ProtocolId,
+    type_label: &'static str,
+    direction_label: &'static str,
+) -> IntCounter {
+    DIEM_NETWORK_PROTOCOL_BYTES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        protocol_id.as_str(),
+        type_label,
+        direction_label,
+    ])
+}
+
+pub static DIEM_NETWORK_WRITE_QUEUE_WAIT: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "diem_network_write_queue_wait_seconds",
+        "Time messages wait in the write queue of their connection in seconds",
+        &["role_type", "network_id", "peer_id", "protocol_id"]
+    )
+    .unwrap()
+});
+
+pub fn write_queue_wait(network_context: &NetworkContext, protocol_id: ProtocolId) -> Histogram {
+    DIEM_NETWORK_WRITE_QUEUE_WAIT.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        protocol_id.as_str(),
+    ])
+}
+
+pub static DIEM_NETWORK_COMPRESSION_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_network_compression_bytes",
+        "Number of bytes of compressed messages, before and after compression",
+        &[
+            "role_type",
+            "network_id",
+            "peer_id",
+            "algorithm",
+            "type",
+            "direction"
+        ]
+    )
+    .unwrap()
+});
+
+pub fn compression_bytes(
+    network_context: &NetworkContext,
+    algorithm: CompressionAlgorithm,
+    type_label: &'static str,
+    direction_label: &'static str,
+) -> IntCounter {
+    DIEM_NETWORK_COMPRESSION_BYTES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        algorithm.as_str(),
+        type_label,
+        direction_label,
+    ])
+}
+
+/// The traffic of a protocol since the node started, as served by the network telemetry
+/// endpoint.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ProtocolTraffic {
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Average round trip time of the outbound rpcs which got a response
+    pub rpc_rtt_avg_secs: Option<f64>,
+    /// Average time messages waited in the write queue
+    pub write_queue_wait_avg_secs: Option<f64>,
+}
+
+/// The traffic of each protocol which had any on the network, keyed by protocol name.
+pub fn protocol_traffic(
+    network_context: &NetworkContext,
+) -> BTreeMap<&'static str, ProtocolTraffic> {
+    ProtocolId::all()
+        .iter()
+        .filter_map(|protocol_id| {
+            // Sums the counts of every message type of the protocol in the direction.
+            let total = |counters: &IntCounterVec, direction_label: &str| -> u64 {
+                [DIRECT_SEND_LABEL, REQUEST_LABEL, RESPONSE_LABEL]
+                    .iter()
+                    .map(|type_label| {
+                        counters
+                            .with_label_values(&[
+                                network_context.role().as_str(),
+                                network_context.network_id().as_str(),
+                                network_context.peer_id().short_str().as_str(),
+                                protocol_id.as_str(),
+                                *type_label,
+                                direction_label,
+                            ])
+                            .get()
+                    })
+                    .sum()
+            };
+            let traffic = ProtocolTraffic {
+                messages_sent: total(&DIEM_NETWORK_PROTOCOL_MESSAGES, SENT_LABEL),
+                messages_received: total(&DIEM_NETWORK_PROTOCOL_MESSAGES, RECEIVED_LABEL),
+                bytes_sent: total(&DIEM_NETWORK_PROTOCOL_BYTES, SENT_LABEL),
+                bytes_received: total(&DIEM_NETWORK_PROTOCOL_BYTES, RECEIVED_LABEL),
+                rpc_rtt_avg_secs: average(&outbound_rpc_request_latency(
+                    network_context,
+                    *protocol_id,
+                )),
+                write_queue_wait_avg_secs: average(&write_queue_wait(
+                    network_context,
+                    *protocol_id,
+                )),
+            };
+            if traffic == ProtocolTraffic::default() {
+                None
+            } else {
+                Some((protocol_id.as_str(), traffic))
+            }
+        })
+        .collect()
+}
+
+fn average(histogram: &Histogram) -> Option<f64> {
+    let count = histogram.get_sample_count();
+    if count == 0 {
+        None
+    } else {
+        Some(histogram.get_sample_sum() / count as f64)
+    }
+}
+
 pub static DIEM_NETWORK_DIRECT_SEND_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "diem_network_direct_send_messages",
@@ -474,3 +746,48 @@ pub static NETWORK_RATE_LIMIT_METRICS: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_protocol_traffic() {
+        let network_context = NetworkContext::mock();
+        assert!(protocol_traffic(&network_context).is_empty());
+
+        let protocol_id = ProtocolId::ConsensusRpc;
+        protocol_messages(&network_context, protocol_id, REQUEST_LABEL, SENT_LABEL).inc();
+        protocol_bytes(&network_context, protocol_id, REQUEST_LABEL, SENT_LABEL).inc_by(100);
+        protocol_messages(
+            &network_context,
+            protocol_id,
+            RESPONSE_LABEL,
+            RECEIVED_LABEL,
+        )
+        .inc();
+        protocol_bytes(
+            &network_context,
+            protocol_id,
+            RESPONSE_LABEL,
+            RECEIVED_LABEL,
+        )
+        .inc_by(50);
+        outbound_rpc_request_latency(&network_context, protocol_id).observe(0.5);
+        write_queue_wait(&network_context, protocol_id).observe(0.01);
+
+        let traffic = protocol_traffic(&network_context);
+        assert_eq!(traffic.len(), 1);
+        assert_eq!(
+            traffic[protocol_id.as_str()],
+            ProtocolTraffic {
+                messages_sent: 1,
+                messages_received: 1,
+                bytes_sent: 100,
+                bytes_received: 50,
+                rpc_rtt_avg_secs: Some(0.5),
+                write_queue_wait_avg_secs: Some(0.01),
+            }
+        );
+    }
+}
@@ -15,6 +15,7 @@ pub mod constants;
 pub mod counters;
 pub mod error;
 pub mod logging;
+pub mod nat;
 pub mod noise;
 pub mod peer;
 pub mod peer_manager;
@@ -0,0 +1,195 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! NAT traversal, so that nodes behind a home router can accept inbound connections.
+//!
+//! [`PortMapper`] asks the router of the node to forward the listen port to it, with NAT-PMP if
+//! the router speaks it and with UPnP otherwise, and queries the external address of the router.
+//! The mapping is renewed halfway through its lease, and the external address of the node is
+//! published for it to be advertised to peers.
+
+use crate::logging::NetworkSchema;
+use anyhow::{ensure, format_err, Result};
+use diem_config::{config::HANDSHAKE_VERSION, network_id::NetworkContext};
+use diem_crypto::x25519;
+use diem_infallible::RwLock;
+use diem_logger::prelude::*;
+use diem_time_service::{TimeService, TimeServiceTrait};
+use diem_types::network_address::{parse_ip_tcp, NetworkAddress, Protocol};
+use std::{fs, net::Ipv4Addr, sync::Arc, time::Duration};
+
+pub mod natpmp;
+pub mod upnp;
+
+/// The address peers can dial the node at from outside of its NAT, once mapped.
+pub type ExternalAddress = Arc<RwLock<Option<NetworkAddress>>>;
+
+/// Time to wait before trying again when mapping the port failed.
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct PortMapper {
+    network_context: Arc<NetworkContext>,
+    time_service: TimeService,
+    listen_address: NetworkAddress,
+    /// Network public key of the node, part of the external address
+    pubkey: x25519::PublicKey,
+    lease: Duration,
+    request_timeout: Duration,
+    external_address: ExternalAddress,
+}
+
+impl PortMapper {
+    pub fn new(
+        network_context: Arc<NetworkContext>,
+        time_service: TimeService,
+        listen_address: NetworkAddress,
+        pubkey: x25519::PublicKey,
+        lease: Duration,
+        request_timeout: Duration,
+        external_address: ExternalAddress,
+    ) -> Self {
+        Self {
+            network_context,
+            time_service,
+            listen_address,
+            pubkey,
+            lease,
+            request_timeout,
+            external_address,
+        }
+    }
+
+    pub async fn start(self) {
+        loop {
+            let renew_in = match self.map_port().await {
+                Ok((address, lease)) => {
+                    info!(
+                        NetworkSchema::new(&self.network_context),
+                        "{} Mapped listen address {} to external address {} for {:?}",
+                        self.network_context,
+                        self.listen_address,
+                        address,
+                        lease
+                    );
+                    *self.external_address.write() = Some(address);
+                    lease / 2
+                }
+                Err(err) => {
+                    // A mapping which failed to renew may still hold until the end of its lease,
+                    // so the external address is kept.
+                    warn!(
+                        NetworkSchema::new(&self.network_context),
+                        error = %err,
+                        "{} Failed to map listen address {}: {}",
+                        self.network_context,
+                        self.listen_address,
+                        err
+                    );
+                    RETRY_INTERVAL
+                }
+            };
+            self.time_service.sleep(renew_in.max(RETRY_INTERVAL)).await;
+        }
+    }
+
+    /// Maps the listen port on the router, returning the external address and its lease.
+    async fn map_port(&self) -> Result<(NetworkAddress, Duration)> {
+        let ((ip, port), _) = parse_ip_tcp(self.listen_address.as_slice())
+            .ok_or_else(|| format_err!("Only TCP listen addresses can be mapped"))?;
+        // A dual-stack listener on `::` also accepts connections over IPv4, which get mapped. IPv6
+        // addresses are globally routable, so specific ones have nothing to map.
+        ensure!(
+            ip.is_ipv4() || ip.is_unspecified(),
+            "Only IPv4 or unspecified IPv6 listen addresses can be mapped, {} doesn't need NAT",
+            ip
+        );
+
+        let (external_ip, external_port, lease) = match default_gateway() {
+            Some(gateway) => match self.map_port_with_natpmp(gateway, port).await {
+                Ok(mapping) => mapping,
+                Err(err) => {
+                    debug!(
+                        NetworkSchema::new(&self.network_context),
+                        error = %err,
+                        "{} NAT-PMP mapping failed, trying UPnP: {}",
+                        self.network_context,
+                        err
+                    );
+                    self.map_port_with_upnp(port).await?
+                }
+            },
+            None => self.map_port_with_upnp(port).await?,
+        };
+        ensure!(
+            !external_ip.is_private(),
+            "The external address of the router, {}, is private, it's behind another NAT",
+            external_ip
+        );
+
+        let address = NetworkAddress::from(Protocol::Ip4(external_ip))
+            .push(Protocol::Tcp(external_port))
+            .append_prod_protos(self.pubkey, HANDSHAKE_VERSION);
+        Ok((address, lease))
+    }
+
+    async fn map_port_with_natpmp(
+        &self,
+        gateway: Ipv4Addr,
+        port: u16,
+    ) -> Result<(Ipv4Addr, u16, Duration)> {
+        let external_ip = natpmp::external_address(gateway, self.request_timeout).await?;
+        let (external_port, lease) =
+            natpmp::map_tcp_port(gateway, port, self.lease, self.request_timeout).await?;
+        Ok((external_ip, external_port, lease))
+    }
+
+    async fn map_port_with_upnp(&self, port: u16) -> Result<(Ipv4Addr, u16, Duration)> {
+        let gateway = upnp::Gateway::search(self.request_timeout).await?;
+        gateway
+            .map_tcp_port(port, self.lease, self.request_timeout)
+            .await?;
+        let external_ip = gateway.external_address(self.request_timeout).await?;
+        Ok((external_ip, port, self.lease))
+    }
+}
+
+/// The IPv4 default gateway of the host, read from the routing table on Linux.
+fn default_gateway() -> Option<Ipv4Addr> {
+    let routes = fs::read_to_string("/proc/net/route").ok()?;
+    parse_default_gateway(&routes)
+}
+
+fn parse_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    // Columns are the interface, destination and gateway, then others. Addresses are in hex, in
+    // the byte order of the host.
+    routes.lines().skip(1).find_map(|route| {
+        let mut columns = route.split_whitespace().skip(1);
+        let destination = columns.next()?;
+        let gateway = u32::from_str_radix(columns.next()?, 16).ok()?;
+        if destination != "00000000" || gateway == 0 {
+            return None;
+        }
+        Some(Ipv4Addr::from(gateway.to_ne_bytes()))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_default_gateway() {
+        let header =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n";
+        let local_route = "eth0\t0001A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n";
+        let default_route = "eth0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0\n";
+
+        assert_eq!(parse_default_gateway(&[header, local_route].concat()), None);
+        if cfg!(target_endian = "little") {
+            assert_eq!(
+                parse_default_gateway(&[header, local_route, default_route].concat()),
+                Some(Ipv4Addr::new(192, 168, 1, 1))
+            );
+        }
+    }
+}
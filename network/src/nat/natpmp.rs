@@ -0,0 +1,173 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Port mapping with NAT-PMP ([RFC 6886](https://tools.ietf.org/html/rfc6886)).
+//!
+//! Requests are sent over UDP to port 5351 of the gateway, which answers with a fixed size
+//! response. Requests are retried until the timeout, as UDP datagrams may be lost.
+
+use anyhow::{bail, ensure, format_err, Result};
+use std::{
+    convert::TryInto,
+    net::{Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+use tokio::{net::UdpSocket, time::timeout};
+
+const NATPMP_PORT: u16 = 5351;
+const VERSION: u8 = 0;
+const OP_EXTERNAL_ADDRESS: u8 = 0;
+const OP_MAP_TCP: u8 = 2;
+/// Responses have the opcode of their request with the high bit set.
+const OP_RESPONSE: u8 = 128;
+const RETRY_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Asks `gateway` for its external address.
+pub async fn external_address(gateway: Ipv4Addr, request_timeout: Duration) -> Result<Ipv4Addr> {
+    let response = request(
+        gateway,
+        &[VERSION, OP_EXTERNAL_ADDRESS],
+        OP_EXTERNAL_ADDRESS,
+        request_timeout,
+    )
+    .await?;
+    parse_external_address(&response)
+}
+
+/// Maps the TCP `port` of this host to the same port of `gateway`, or whichever port the gateway
+/// picks, for `lease`. Returns the external port and the lease granted.
+pub async fn map_tcp_port(
+    gateway: Ipv4Addr,
+    port: u16,
+    lease: Duration,
+    request_timeout: Duration,
+) -> Result<(u16, Duration)> {
+    let response = request(
+        gateway,
+        &map_tcp_request(port, lease),
+        OP_MAP_TCP,
+        request_timeout,
+    )
+    .await?;
+    parse_map_response(&response, port)
+}
+
+async fn request(
+    gateway: Ipv4Addr,
+    request: &[u8],
+    op: u8,
+    request_timeout: Duration,
+) -> Result<Vec<u8>> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket
+        .connect(SocketAddr::from((gateway, NATPMP_PORT)))
+        .await?;
+
+    let exchange = async {
+        let mut buf = [0u8; 16];
+        loop {
+            socket.send(request).await?;
+            // Gateways which don't speak NAT-PMP don't answer at all, so resend until the timeout.
+            if let Ok(len) = timeout(RETRY_INTERVAL, socket.recv(&mut buf)).await {
+                let len = len?;
+                // Skip stray datagrams, such as the answers to earlier attempts.
+                if len >= 4 && buf[1] == OP_RESPONSE + op {
+                    check_result(&buf[..len])?;
+                    return Ok(buf[..len].to_vec());
+                }
+            }
+        }
+    };
+    timeout(request_timeout, exchange)
+        .await
+        .map_err(|_| format_err!("No NAT-PMP answer from gateway {}", gateway))?
+}
+
+fn map_tcp_request(port: u16, lease: Duration) -> [u8; 12] {
+    let mut request = [0u8; 12];
+    request[0] = VERSION;
+    request[1] = OP_MAP_TCP;
+    // Bytes 2 and 3 are reserved.
+    request[4..6].copy_from_slice(&port.to_be_bytes());
+    request[6..8].copy_from_slice(&port.to_be_bytes());
+    let lease_secs = lease.as_secs().min(u64::from(u32::MAX)) as u32;
+    request[8..12].copy_from_slice(&lease_secs.to_be_bytes());
+    request
+}
+
+fn check_result(response: &[u8]) -> Result<()> {
+    let result_code = u16::from_be_bytes([response[2], response[3]]);
+    match result_code {
+        0 => Ok(()),
+        1 => bail!("Unsupported NAT-PMP version"),
+        2 => bail!("Port mapping refused by the gateway"),
+        3 => bail!("Gateway has no external address"),
+        4 => bail!("Gateway is out of port mappings"),
+        code => bail!("NAT-PMP request failed with result code {}", code),
+    }
+}
+
+fn parse_external_address(response: &[u8]) -> Result<Ipv4Addr> {
+    ensure!(
+        response.len() >= 12,
+        "NAT-PMP external address response is too short: {} bytes",
+        response.len()
+    );
+    let octets: [u8; 4] = response[8..12].try_into()?;
+    Ok(Ipv4Addr::from(octets))
+}
+
+fn parse_map_response(response: &[u8], port: u16) -> Result<(u16, Duration)> {
+    ensure!(
+        response.len() >= 16,
+        "NAT-PMP mapping response is too short: {} bytes",
+        response.len()
+    );
+    let internal_port = u16::from_be_bytes([response[8], response[9]]);
+    ensure!(
+        internal_port == port,
+        "NAT-PMP mapping is for port {}, not {}",
+        internal_port,
+        port
+    );
+    let external_port = u16::from_be_bytes([response[10], response[11]]);
+    let lease_secs = u32::from_be_bytes(response[12..16].try_into()?);
+    Ok((external_port, Duration::from_secs(u64::from(lease_secs))))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_map_tcp_request() {
+        let request = map_tcp_request(6180, Duration::from_secs(3600));
+        assert_eq!(
+            request,
+            [0, 2, 0, 0, 0x18, 0x24, 0x18, 0x24, 0, 0, 0x0e, 0x10]
+        );
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        let response = [0, 128, 0, 0, 0, 0, 0, 1, 203, 0, 113, 7];
+        check_result(&response).unwrap();
+        assert_eq!(
+            parse_external_address(&response).unwrap(),
+            Ipv4Addr::new(203, 0, 113, 7)
+        );
+
+        let response = [
+            0, 130, 0, 0, 0, 0, 0, 1, 0x18, 0x24, 0x1f, 0x40, 0, 0, 0x07, 0x08,
+        ];
+        check_result(&response).unwrap();
+        assert_eq!(
+            parse_map_response(&response, 6180).unwrap(),
+            (8000, Duration::from_secs(1800))
+        );
+        assert!(parse_map_response(&response, 6181).is_err());
+
+        let refused = [0, 130, 0, 2, 0, 0, 0, 1, 0x18, 0x24, 0, 0, 0, 0, 0, 0];
+        assert!(check_result(&refused).is_err());
+    }
+}
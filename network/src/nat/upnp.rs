@@ -0,0 +1,325 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Port mapping with the UPnP Internet Gateway Device protocol.
+//!
+//! The gateway is found by multicasting an SSDP search, to which it answers with the location of
+//! its device description. The description gives the control URL of its WAN connection service,
+//! to which SOAP requests are posted to query the external address and add port mappings.
+//!
+//! Only what's needed of HTTP and XML is implemented: requests are sent as HTTP/1.0 so that the
+//! responses aren't chunked, and the few elements needed are picked out of the documents by name.
+
+use anyhow::{bail, format_err, Result};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    str,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpStream, UdpSocket},
+    time::timeout,
+};
+
+const SSDP_IP: Ipv4Addr = Ipv4Addr::new(239, 255, 255, 250);
+const SSDP_PORT: u16 = 1900;
+const SEARCH_TARGET: &str = "urn:schemas-upnp-org:device:InternetGatewayDevice:1";
+const WAN_SERVICE_TYPES: &[&str] = &[
+    "urn:schemas-upnp-org:service:WANIPConnection:1",
+    "urn:schemas-upnp-org:service:WANPPPConnection:1",
+];
+const PORT_MAPPING_DESCRIPTION: &str = "diem";
+
+/// The WAN connection service of a gateway.
+#[derive(Clone, Debug)]
+pub struct Gateway {
+    /// Address of the HTTP server of the gateway
+    addr: SocketAddr,
+    /// Path of the control URL of the service
+    control_path: String,
+    service_type: &'static str,
+}
+
+impl Gateway {
+    /// Searches the local network for a gateway.
+    pub async fn search(request_timeout: Duration) -> Result<Self> {
+        let location = timeout(request_timeout, search_location())
+            .await
+            .map_err(|_| format_err!("No UPnP gateway answered the search"))??;
+        let (addr, path) = parse_url(&location)?;
+        let description = timeout(request_timeout, http_get(addr, &path))
+            .await
+            .map_err(|_| format_err!("Timed out fetching the description of {}", location))??;
+        let (service_type, control_url) = find_wan_service(&description)
+            .ok_or_else(|| format_err!("UPnP gateway {} has no WAN connection service", addr))?;
+        let control_path = if control_url.starts_with("http://") {
+            parse_url(&control_url)?.1
+        } else if control_url.starts_with('/') {
+            control_url
+        } else {
+            format!("/{}", control_url)
+        };
+        Ok(Self {
+            addr,
+            control_path,
+            service_type,
+        })
+    }
+
+    pub async fn external_address(&self, request_timeout: Duration) -> Result<Ipv4Addr> {
+        let response = timeout(
+            request_timeout,
+            self.soap_request("GetExternalIPAddress", &[]),
+        )
+        .await
+        .map_err(|_| format_err!("Timed out querying the external address of {}", self.addr))??;
+        let address = find_element(&response, "NewExternalIPAddress")
+            .ok_or_else(|| format_err!("UPnP gateway {} sent no external address", self.addr))?;
+        Ok(address.trim().parse()?)
+    }
+
+    /// Maps the TCP `port` of this host to the same port of the gateway, for `lease`.
+    pub async fn map_tcp_port(
+        &self,
+        port: u16,
+        lease: Duration,
+        request_timeout: Duration,
+    ) -> Result<()> {
+        // The gateway only maps ports to the address it's talked to from, which is found once
+        // connected to it.
+        let local_ip = timeout(request_timeout, local_ip(self.addr))
+            .await
+            .map_err(|_| format_err!("Timed out connecting to {}", self.addr))??;
+        let port = port.to_string();
+        let local_ip = local_ip.to_string();
+        let lease_secs = lease.as_secs().to_string();
+        let args = [
+            ("NewRemoteHost", ""),
+            ("NewExternalPort", port.as_str()),
+            ("NewProtocol", "TCP"),
+            ("NewInternalPort", port.as_str()),
+            ("NewInternalClient", local_ip.as_str()),
+            ("NewEnabled", "1"),
+            ("NewPortMappingDescription", PORT_MAPPING_DESCRIPTION),
+            ("NewLeaseDuration", lease_secs.as_str()),
+        ];
+        timeout(request_timeout, self.soap_request("AddPortMapping", &args))
+            .await
+            .map_err(|_| format_err!("Timed out adding a port mapping on {}", self.addr))??;
+        Ok(())
+    }
+
+    /// Posts the `action` SOAP request, returning the body of the response.
+    async fn soap_request(&self, action: &str, args: &[(&str, &str)]) -> Result<String> {
+        let args: String = args
+            .iter()
+            .map(|(name, value)| format!("<{0}>{1}</{0}>", name, value))
+            .collect();
+        let body = format!(
+            "<?xml version=\"1.0\"?>\
+             <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+             s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">\
+             <s:Body><u:{0} xmlns:u=\"{1}\">{2}</u:{0}></s:Body></s:Envelope>",
+            action, self.service_type, args
+        );
+        let request = format!(
+            "POST {} HTTP/1.0\r\n\
+             Host: {}\r\n\
+             Content-Type: text/xml; charset=\"utf-8\"\r\n\
+             Content-Length: {}\r\n\
+             SOAPAction: \"{}#{}\"\r\n\r\n{}",
+            self.control_path,
+            self.addr,
+            body.len(),
+            self.service_type,
+            action,
+            body
+        );
+        http_request(self.addr, &request).await
+    }
+}
+
+/// Multicasts an SSDP search, returning the description location of the first gateway to answer.
+async fn search_location() -> Result<String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    let request = format!(
+        "M-SEARCH * HTTP/1.1\r\n\
+         HOST: {}:{}\r\n\
+         ST: {}\r\n\
+         MAN: \"ssdp:discover\"\r\n\
+         MX: 2\r\n\r\n",
+        SSDP_IP, SSDP_PORT, SEARCH_TARGET
+    );
+    socket
+        .send_to(request.as_bytes(), (SSDP_IP, SSDP_PORT))
+        .await?;
+
+    let mut buf = [0u8; 2048];
+    loop {
+        let (len, _) = socket.recv_from(&mut buf).await?;
+        // Skip answers which aren't from gateways, or can't be read.
+        if let Ok(response) = str::from_utf8(&buf[..len]) {
+            if let Some(location) = parse_search_response(response) {
+                return Ok(location);
+            }
+        }
+    }
+}
+
+fn parse_search_response(response: &str) -> Option<String> {
+    if !response.starts_with("HTTP/1.1 200") {
+        return None;
+    }
+    response.lines().find_map(|line| {
+        let (name, value) = line.split_at(line.find(':')?);
+        if name.trim().eq_ignore_ascii_case("location") {
+            Some(value[1..].trim().to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// Splits a URL of the form `http://<ip>:<port>/<path>` into its address and path.
+fn parse_url(url: &str) -> Result<(SocketAddr, String)> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format_err!("Unsupported URL: {}", url))?;
+    let (host, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    let addr = if host.contains(':') {
+        host.parse()?
+    } else {
+        SocketAddr::new(host.parse()?, 80)
+    };
+    Ok((addr, path.to_string()))
+}
+
+/// Finds the type and control URL of the first WAN connection service of a device description.
+fn find_wan_service(description: &str) -> Option<(&'static str, String)> {
+    description.split("<service>").skip(1).find_map(|service| {
+        let service_type = find_element(service, "serviceType")?;
+        let service_type = WAN_SERVICE_TYPES
+            .iter()
+            .find(|wan_service_type| **wan_service_type == service_type.trim())?;
+        let control_url = find_element(service, "controlURL")?;
+        Some((*service_type, control_url.trim().to_string()))
+    })
+}
+
+/// The text of the first `name` element of `document`, which must have no child elements,
+/// ignoring namespace prefixes.
+fn find_element<'a>(document: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("{}>", name);
+    let start = document
+        .match_indices(&open)
+        .find(|(index, _)| {
+            // The name is either right after the `<`, or after a namespace prefix.
+            let before = &document[..*index];
+            before.rfind('<').map_or(false, |tag_start| {
+                let prefix = &before[tag_start + 1..];
+                prefix.is_empty() || (!prefix.starts_with('/') && prefix.ends_with(':'))
+            })
+        })
+        .map(|(index, _)| index + open.len())?;
+    let end = start + document[start..].find("</")?;
+    Some(&document[start..end])
+}
+
+async fn http_get(addr: SocketAddr, path: &str) -> Result<String> {
+    let request = format!("GET {} HTTP/1.0\r\nHost: {}\r\n\r\n", path, addr);
+    http_request(addr, &request).await
+}
+
+/// Sends `request`, returning the body of the response.
+async fn http_request(addr: SocketAddr, request: &str) -> Result<String> {
+    let mut stream = TcpStream::connect(addr).await?;
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+    let response = String::from_utf8(response)?;
+
+    let (head, body) = match response.find("\r\n\r\n") {
+        Some(index) => (&response[..index], &response[index + 4..]),
+        None => bail!("Malformed HTTP response from {}", addr),
+    };
+    let status = head.lines().next().unwrap_or_default();
+    if !status.contains(" 200 ") && !status.ends_with(" 200") {
+        bail!("HTTP request to {} failed: {}", addr, status);
+    }
+    Ok(body.to_string())
+}
+
+/// The address of this host on the network of `addr`.
+async fn local_ip(addr: SocketAddr) -> Result<IpAddr> {
+    Ok(TcpStream::connect(addr).await?.local_addr()?.ip())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_search_response() {
+        let response = "HTTP/1.1 200 OK\r\n\
+                        CACHE-CONTROL: max-age=120\r\n\
+                        Location: http://192.168.1.1:5000/rootDesc.xml\r\n\
+                        ST: urn:schemas-upnp-org:device:InternetGatewayDevice:1\r\n\r\n";
+        assert_eq!(
+            parse_search_response(response).unwrap(),
+            "http://192.168.1.1:5000/rootDesc.xml"
+        );
+        assert_eq!(parse_search_response("NOTIFY * HTTP/1.1\r\n\r\n"), None);
+    }
+
+    #[test]
+    fn test_parse_url() {
+        let (addr, path) = parse_url("http://192.168.1.1:5000/rootDesc.xml").unwrap();
+        assert_eq!(addr, "192.168.1.1:5000".parse().unwrap());
+        assert_eq!(path, "/rootDesc.xml");
+        let (addr, path) = parse_url("http://10.0.0.1").unwrap();
+        assert_eq!(addr, "10.0.0.1:80".parse().unwrap());
+        assert_eq!(path, "/");
+        assert!(parse_url("https://10.0.0.1/").is_err());
+    }
+
+    #[test]
+    fn test_find_wan_service() {
+        let description = "<root><device><serviceList>\
+            <service><serviceType>urn:schemas-upnp-org:service:Layer3Forwarding:1</serviceType>\
+            <controlURL>/ctl/L3F</controlURL></service>\
+            <service><serviceType>urn:schemas-upnp-org:service:WANIPConnection:1</serviceType>\
+            <controlURL>/ctl/IPConn</controlURL></service>\
+            </serviceList></device></root>";
+        assert_eq!(
+            find_wan_service(description).unwrap(),
+            (
+                "urn:schemas-upnp-org:service:WANIPConnection:1",
+                "/ctl/IPConn".to_string()
+            )
+        );
+        assert_eq!(find_wan_service("<root></root>"), None);
+    }
+
+    #[test]
+    fn test_find_element() {
+        let response = "<s:Envelope><s:Body><u:GetExternalIPAddressResponse>\
+            <NewExternalIPAddress>203.0.113.7</NewExternalIPAddress>\
+            </u:GetExternalIPAddressResponse></s:Body></s:Envelope>";
+        assert_eq!(
+            find_element(response, "NewExternalIPAddress"),
+            Some("203.0.113.7")
+        );
+        assert_eq!(
+            find_element(
+                "<a:NewExternalIPAddress>1.2.3.4</a:NewExternalIPAddress>",
+                "NewExternalIPAddress"
+            ),
+            Some("1.2.3.4")
+        );
+        assert_eq!(find_element(response, "NewPortMappingDescription"), None);
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compression of the messages exchanged with a peer.
+//!
+//! Messages to the peer whose payload is at least `threshold_bytes` are compressed with the first
+//! of the algorithms of the node which the peer also supports, as negotiated in the handshake.
+//! Messages which don't get any smaller, such as those already compressed by their application,
+//! are sent as they are.
+
+use crate::{
+    counters::{self, COMPRESSED_LABEL, RECEIVED_LABEL, SENT_LABEL, UNCOMPRESSED_LABEL},
+    logging::NetworkSchema,
+    protocols::wire::{
+        compression::CompressionAlgorithm,
+        handshake::v1::SupportedProtocols,
+        messaging::v1::{CompressedMsg, NetworkMessage},
+    },
+};
+use anyhow::{ensure, Result};
+use diem_config::{config::CompressionConfig, network_id::NetworkContext};
+use diem_logger::prelude::*;
+use std::{convert::TryFrom, sync::Arc};
+
+#[derive(Clone)]
+pub struct MessageCompressor {
+    network_context: Arc<NetworkContext>,
+    /// The algorithms negotiated with the peer, in order of preference
+    algorithms: Vec<CompressionAlgorithm>,
+    threshold_bytes: usize,
+    /// Compressed messages can't decompress to more than a frame
+    max_frame_size: usize,
+}
+
+impl MessageCompressor {
+    pub fn new(
+        network_context: Arc<NetworkContext>,
+        config: &CompressionConfig,
+        application_protocols: &SupportedProtocols,
+        max_frame_size: usize,
+    ) -> Self {
+        let algorithms = config
+            .algorithms
+            .iter()
+            .map(|algorithm| CompressionAlgorithm::from(*algorithm))
+            .filter(|algorithm| application_protocols.supports_compression(*algorithm))
+            .collect();
+        Self {
+            network_context,
+            algorithms,
+            threshold_bytes: config.threshold_bytes,
+            max_frame_size,
+        }
+    }
+
+    /// Compresses `message` if compression was negotiated and its payload is large enough.
+    pub fn compress(&self, message: NetworkMessage) -> NetworkMessage {
+        let algorithm = match self.algorithms.first() {
+            Some(algorithm) if payload_len(&message) >= self.threshold_bytes => *algorithm,
+            _ => return message,
+        };
+        match self.try_compress(algorithm, &message) {
+            Ok(Some(compressed)) => compressed,
+            Ok(None) => message,
+            Err(err) => {
+                warn!(
+                    NetworkSchema::new(&self.network_context),
+                    error = %err,
+                    "{} Failed to compress message with {}, sending it uncompressed: {}",
+                    self.network_context,
+                    algorithm,
+                    err
+                );
+                message
+            }
+        }
+    }
+
+    /// The compressed message, unless compression doesn't make it smaller.
+    fn try_compress(
+        &self,
+        algorithm: CompressionAlgorithm,
+        message: &NetworkMessage,
+    ) -> Result<Option<NetworkMessage>> {
+        let serialized = bcs::to_bytes(message)?;
+        let uncompressed_len = u32::try_from(serialized.len())?;
+        let compressed = algorithm.compress(&serialized)?;
+        if compressed.len() >= serialized.len() {
+            return Ok(None);
+        }
+        self.observe(algorithm, serialized.len(), compressed.len(), SENT_LABEL);
+        Ok(Some(NetworkMessage::CompressedMsg(CompressedMsg {
+            algorithm,
+            uncompressed_len,
+            raw_msg: compressed,
+        })))
+    }
+
+    /// Decompresses a message of the peer, which is never itself compressed.
+    pub fn decompress(&self, message: CompressedMsg) -> Result<NetworkMessage> {
+        let CompressedMsg {
+            algorithm,
+            uncompressed_len,
+            raw_msg,
+        } = message;
+        ensure!(
+            self.algorithms.contains(&algorithm),
+            "Message compressed with {}, which wasn't negotiated",
+            algorithm
+        );
+        let uncompressed_len = uncompressed_len as usize;
+        ensure!(
+            uncompressed_len <= self.max_frame_size,
+            "Message decompresses to {} bytes, over the maximum frame size of {}",
+            uncompressed_len,
+            self.max_frame_size
+        );
+
+        let serialized = algorithm.decompress(&raw_msg, uncompressed_len)?;
+        let message: NetworkMessage = bcs::from_bytes(&serialized)?;
+        ensure!(
+            !matches!(message, NetworkMessage::CompressedMsg(_)),
+            "Compressed message nested in another"
+        );
+        self.observe(algorithm, uncompressed_len, raw_msg.len(), RECEIVED_LABEL);
+        Ok(message)
+    }
+
+    fn observe(
+        &self,
+        algorithm: CompressionAlgorithm,
+        uncompressed_len: usize,
+        compressed_len: usize,
+        direction_label: &'static str,
+    ) {
+        counters::compression_bytes(
+            &self.network_context,
+            algorithm,
+            UNCOMPRESSED_LABEL,
+            direction_label,
+        )
+        .inc_by(uncompressed_len as u64);
+        counters::compression_bytes(
+            &self.network_context,
+            algorithm,
+            COMPRESSED_LABEL,
+            direction_label,
+        )
+        .inc_by(compressed_len as u64);
+    }
+}
+
+fn payload_len(message: &NetworkMessage) -> usize {
+    match message {
+        NetworkMessage::DirectSendMsg(message) => message.raw_msg.len(),
+        NetworkMessage::RpcRequest(request) => request.raw_request.len(),
+        NetworkMessage::RpcResponse(response) => response.raw_response.len(),
+        NetworkMessage::Error(_) | NetworkMessage::CompressedMsg(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocols::wire::{handshake::v1::ProtocolId, messaging::v1::DirectSendMsg};
+    use diem_config::config;
+    use diem_types::PeerId;
+
+    fn compressor(
+        algorithms: Vec<config::CompressionAlgorithm>,
+        negotiated: &[CompressionAlgorithm],
+    ) -> MessageCompressor {
+        let config = CompressionConfig {
+            algorithms,
+            threshold_bytes: 1024,
+        };
+        let protocols: SupportedProtocols = ProtocolId::all().iter().into();
+        MessageCompressor::new(
+            NetworkContext::mock_with_peer_id(PeerId::random()),
+            &config,
+            &protocols.with_compression(negotiated),
+            64 * 1024,
+        )
+    }
+
+    fn direct_send(len: usize) -> NetworkMessage {
+        NetworkMessage::DirectSendMsg(DirectSendMsg {
+            protocol_id: ProtocolId::StateSyncDirectSend,
+            priority: 0,
+            raw_msg: vec![7; len],
+        })
+    }
+
+    #[test]
+    fn test_compress() {
+        let compressor = compressor(
+            vec![
+                config::CompressionAlgorithm::Lz4,
+                config::CompressionAlgorithm::Zstd,
+            ],
+            &[CompressionAlgorithm::Zstd],
+        );
+
+        // Small messages aren't compressed.
+        assert_eq!(compressor.compress(direct_send(100)), direct_send(100));
+
+        // Large ones are, with the algorithm the peer supports.
+        let compressed = match compressor.compress(direct_send(10_000)) {
+            NetworkMessage::CompressedMsg(compressed) => compressed,
+            message => panic!("Message wasn't compressed: {:?}", message),
+        };
+        assert_eq!(compressed.algorithm, CompressionAlgorithm::Zstd);
+        assert!(compressed.raw_msg.len() < 1000);
+        assert_eq!(
+            compressor.decompress(compressed).unwrap(),
+            direct_send(10_000)
+        );
+    }
+
+    #[test]
+    fn test_not_negotiated() {
+        // The peer doesn't support our algorithm, so nothing is compressed.
+        let compressor = compressor(
+            vec![config::CompressionAlgorithm::Lz4],
+            &[CompressionAlgorithm::Zstd],
+        );
+        assert_eq!(
+            compressor.compress(direct_send(10_000)),
+            direct_send(10_000)
+        );
+
+        // Nor are messages compressed with an algorithm that wasn't negotiated accepted.
+        let serialized = bcs::to_bytes(&direct_send(10_000)).unwrap();
+        let message = CompressedMsg {
+            algorithm: CompressionAlgorithm::Zstd,
+            uncompressed_len: serialized.len() as u32,
+            raw_msg: CompressionAlgorithm::Zstd.compress(&serialized).unwrap(),
+        };
+        compressor.decompress(message).unwrap_err();
+    }
+
+    #[test]
+    fn test_decompress_rejects() {
+        let compressor = compressor(
+            vec![config::CompressionAlgorithm::Lz4],
+            &[CompressionAlgorithm::Lz4],
+        );
+        let compress = |message: &NetworkMessage| {
+            let serialized = bcs::to_bytes(message).unwrap();
+            CompressedMsg {
+                algorithm: CompressionAlgorithm::Lz4,
+                uncompressed_len: serialized.len() as u32,
+                raw_msg: CompressionAlgorithm::Lz4.compress(&serialized).unwrap(),
+            }
+        };
+
+        // Messages decompressing to more than a frame.
+        let message = compress(&direct_send(100 * 1024));
+        compressor.decompress(message).unwrap_err();
+
+        // Compressed messages nested in others.
+        let nested = NetworkMessage::CompressedMsg(compress(&direct_send(10_000)));
+        compressor.decompress(compress(&nested)).unwrap_err();
+    }
+}
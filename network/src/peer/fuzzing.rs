@@ -4,6 +4,7 @@
 use crate::{
     constants,
     peer::Peer,
+    peer_manager::bandwidth::PeerBandwidthBuckets,
     protocols::wire::{
         handshake::v1::{MessagingProtocolVersion, SupportedProtocols},
         messaging::v1::{NetworkMessage, NetworkMessageSink},
@@ -13,7 +14,10 @@ use crate::{
     ProtocolId,
 };
 use channel::{diem_channel, message_queues::QueueStyle};
-use diem_config::{config::PeerRole, network_id::NetworkContext};
+use diem_config::{
+    config::{CompressionConfig, OutboundQosConfig, PeerRole},
+    network_id::NetworkContext,
+};
 use diem_proptest_helpers::ValueGenerator;
 use diem_time_service::TimeService;
 use diem_types::{network_address::NetworkAddress, PeerId};
@@ -109,6 +113,9 @@ pub fn fuzz(data: &[u8]) {
         constants::MAX_FRAME_SIZE,
         None,
         None,
+        PeerBandwidthBuckets::default(),
+        OutboundQosConfig::default(),
+        CompressionConfig::default(),
     );
     executor.spawn(peer.start());
 
@@ -15,12 +15,15 @@
 //! [`PeerManager`]: crate::peer_manager::PeerManager
 
 use crate::{
-    counters::{self, RECEIVED_LABEL, SENT_LABEL},
+    counters::{self, DIRECT_SEND_LABEL, RECEIVED_LABEL, SENT_LABEL},
     logging::NetworkSchema,
-    peer_manager::{PeerManagerError, TransportNotification},
+    peer_manager::{
+        bandwidth::PeerBandwidthBuckets, reputation::Misbehavior, PeerManagerError,
+        TransportNotification,
+    },
     protocols::{
         direct_send::Message,
-        rpc::{InboundRpcRequest, InboundRpcs, OutboundRpcRequest, OutboundRpcs},
+        rpc::{error::RpcError, InboundRpcRequest, InboundRpcs, OutboundRpcRequest, OutboundRpcs},
         wire::messaging::v1::{
             DirectSendMsg, ErrorCode, NetworkMessage, NetworkMessageSink, NetworkMessageStream,
             Priority, ReadError, WriteError,
@@ -31,9 +34,12 @@ use crate::{
 };
 use bytes::Bytes;
 use channel::diem_channel;
-use diem_config::network_id::NetworkContext;
+use diem_config::{
+    config::{CompressionConfig, OutboundQosConfig},
+    network_id::NetworkContext,
+};
 use diem_logger::prelude::*;
-use diem_rate_limiter::rate_limit::SharedBucket;
+use diem_rate_limiter::{async_lib::AsyncRateLimiter, rate_limit::SharedBucket};
 use diem_time_service::{TimeService, TimeServiceTrait};
 use diem_types::PeerId;
 use futures::{
@@ -45,18 +51,26 @@ use futures::{
 };
 use serde::Serialize;
 use short_hex_str::AsShortHexStr;
-use std::{fmt, panic, sync::Arc, time::Duration};
+use std::{
+    fmt, panic,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::runtime::Handle;
 use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
 };
 
+pub mod compression;
+pub mod qos;
 #[cfg(test)]
 mod test;
 
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod fuzzing;
 
+use self::{compression::MessageCompressor, qos::WriteQueueSender};
+
 /// Requests [`Peer`] receives from the [`PeerManager`](crate::peer_manager::PeerManager).
 #[derive(Debug)]
 pub enum PeerRequest {
@@ -75,6 +89,18 @@ pub enum PeerNotification {
     RecvMessage(Message),
 }
 
+/// A message for the writer task of a [`Peer`] to write to the connection.
+#[derive(Debug)]
+pub struct WriteRequest {
+    pub message: NetworkMessage,
+    /// The protocol of the message, if it has one, for metrics
+    pub protocol_id: Option<ProtocolId>,
+    /// When the message was queued, to measure how long it waited to be written
+    pub queued_at: Instant,
+    /// Notified once the message is written
+    pub ack_tx: oneshot::Sender<Result<(), PeerManagerError>>,
+}
+
 /// The reason for closing a connection.
 ///
 /// For example, if the remote peer closed the connection or the connection was
@@ -134,6 +160,12 @@ pub struct Peer<TSocket> {
     inbound_rate_limiter: Option<SharedBucket>,
     /// Optional outbound rate limiter
     outbound_rate_limiter: Option<SharedBucket>,
+    /// Rate limiters of the remote peer, shared by all of its connections
+    bandwidth_buckets: PeerBandwidthBuckets,
+    /// Weights of the QoS classes of outbound messages
+    qos_config: OutboundQosConfig,
+    /// Compresses large outbound messages and decompresses inbound ones
+    compressor: MessageCompressor,
 }
 
 impl<TSocket> Peer<TSocket>
@@ -154,12 +186,21 @@ where
         max_frame_size: usize,
         inbound_rate_limiter: Option<SharedBucket>,
         outbound_rate_limiter: Option<SharedBucket>,
+        bandwidth_buckets: PeerBandwidthBuckets,
+        qos_config: OutboundQosConfig,
+        compression_config: CompressionConfig,
     ) -> Self {
         let Connection {
             metadata: connection_metadata,
             socket,
         } = connection;
         let remote_peer_id = connection_metadata.remote_peer_id;
+        let compressor = MessageCompressor::new(
+            network_context.clone(),
+            &compression_config,
+            &connection_metadata.application_protocols,
+            max_frame_size,
+        );
         Self {
             network_context: network_context.clone(),
             executor,
@@ -186,6 +227,9 @@ where
             max_frame_size,
             inbound_rate_limiter,
             outbound_rate_limiter,
+            bandwidth_buckets,
+            qos_config,
+            compressor,
         }
     }
 
@@ -207,14 +251,22 @@ where
         let (read_socket, write_socket) =
             tokio::io::split(self.connection.take().unwrap().compat());
 
+        // Limit the bytes of the peer, on top of those of its IP.
+        let read_socket =
+            AsyncRateLimiter::new(read_socket.compat(), self.bandwidth_buckets.inbound.clone());
+        let write_socket = AsyncRateLimiter::new(
+            write_socket.compat_write(),
+            self.bandwidth_buckets.outbound.clone(),
+        );
+
         let mut reader = NetworkMessageStream::new(
-            read_socket.compat(),
+            read_socket,
             self.max_frame_size,
             self.inbound_rate_limiter.clone(),
         )
         .fuse();
         let writer = NetworkMessageSink::new(
-            write_socket.compat_write(),
+            write_socket,
             self.max_frame_size,
             self.outbound_rate_limiter.clone(),
         );
@@ -228,6 +280,8 @@ where
             self.time_service.clone(),
             self.connection_metadata.clone(),
             self.network_context.clone(),
+            self.qos_config,
+            self.compressor.clone(),
             writer,
         );
 
@@ -295,7 +349,9 @@ where
     // Start a new task on the given executor which is responsible for writing outbound messages on
     // the wire. The function returns two channels which can be used to send intructions to the
     // task:
-    // 1. The first channel is used to send outbound NetworkMessages to the task
+    // 1. The first channel is used to send outbound NetworkMessages to the task, queued by QoS
+    //    class so that consensus messages go first when the connection backs up. Large messages
+    //    are compressed as they are written
     // 2. The second channel is used to instruct the task to close the connection and terminate.
     // If outbound messages are queued when the task receives a close instruction, it discards
     // them and immediately closes the connection.
@@ -304,31 +360,28 @@ where
         time_service: TimeService,
         connection_metadata: ConnectionMetadata,
         network_context: Arc<NetworkContext>,
+        qos_config: OutboundQosConfig,
+        compressor: MessageCompressor,
         mut writer: NetworkMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
-    ) -> (
-        channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
-        oneshot::Sender<()>,
-    ) {
+    ) -> (WriteQueueSender, oneshot::Sender<()>) {
         let remote_peer_id = connection_metadata.remote_peer_id;
-        let (write_reqs_tx, mut write_reqs_rx): (
-            channel::Sender<(
-                NetworkMessage,
-                oneshot::Sender<Result<(), PeerManagerError>>,
-            )>,
-            _,
-        ) = channel::new(1024, &counters::PENDING_WIRE_MESSAGES);
+        let (write_reqs_tx, mut write_reqs_rx) = qos::write_queues(&qos_config);
         let (close_tx, close_rx) = oneshot::channel();
         let writer_task = async move {
             let mut close_rx = close_rx.into_stream();
             loop {
                 futures::select! {
-                    (message, ack_ch) = write_reqs_rx.select_next_some() => {
+                    request = write_reqs_rx.select_next_some() => {
+                        let WriteRequest { message, protocol_id, queued_at, ack_tx } = request;
+                        if let Some(protocol_id) = protocol_id {
+                            let queue_wait = time_service.now().saturating_duration_since(queued_at);
+                            counters::write_queue_wait(&network_context, protocol_id)
+                                .observe(queue_wait.as_secs_f64());
+                        }
+                        let message = compressor.compress(message);
                         if let Err(err) = writer
                             .send(&message)
-                            .map_ok(|_| ack_ch.send(Ok(())))
+                            .map_ok(|_| ack_tx.send(Ok(())))
                             .await
                         {
                             warn!(
@@ -401,10 +454,7 @@ where
     async fn handle_inbound_message(
         &mut self,
         message: Result<NetworkMessage, ReadError>,
-        write_reqs_tx: &mut channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
+        write_reqs_tx: &mut WriteQueueSender,
     ) -> Result<(), PeerManagerError> {
         trace!(
             NetworkSchema::new(&self.network_context)
@@ -420,14 +470,28 @@ where
                 ReadError::DeserializeError(_, _, ref frame_prefix) => {
                     // DeserializeError's are recoverable so we'll let the other
                     // peer know about the error and log the issue, but we won't
-                    // close the connection.
+                    // close the connection. PeerManager still scores the peer for
+                    // it, and may ban the peer if it keeps doing so.
                     let message_type = frame_prefix.as_ref().get(0).unwrap_or(&0);
                     let protocol_id = frame_prefix.as_ref().get(1).unwrap_or(&0);
                     let error_code = ErrorCode::parsing_error(*message_type, *protocol_id);
                     let message = NetworkMessage::Error(error_code);
 
                     let (ack_tx, _) = oneshot::channel();
-                    write_reqs_tx.send((message, ack_tx)).await?;
+                    write_reqs_tx
+                        .send(WriteRequest {
+                            message,
+                            protocol_id: None,
+                            queued_at: self.time_service.now(),
+                            ack_tx,
+                        })
+                        .await?;
+                    self.connection_notifs_tx
+                        .send(TransportNotification::Misbehaved(
+                            self.connection_metadata.clone(),
+                            Misbehavior::ProtocolViolation,
+                        ))
+                        .await?;
                     return Err(err.into());
                 }
                 ReadError::IoError(_) => {
@@ -438,6 +502,51 @@ where
             },
         };
 
+        let message = match message {
+            NetworkMessage::CompressedMsg(message) => match self.compressor.decompress(message) {
+                Ok(message) => message,
+                Err(err) => {
+                    // Unlike messages we can't parse, those we can't decompress aren't answered,
+                    // as they are the result of a bug or malice rather than of a version mismatch.
+                    self.connection_notifs_tx
+                        .send(TransportNotification::Misbehaved(
+                            self.connection_metadata.clone(),
+                            Misbehavior::ProtocolViolation,
+                        ))
+                        .await?;
+                    return Err(err.into());
+                }
+            },
+            message => message,
+        };
+
+        // Drop messages over the rate limit of their protocol.
+        let over_rate_limit = match &message {
+            NetworkMessage::DirectSendMsg(message) => {
+                Some((message.protocol_id, message.raw_msg.len()))
+            }
+            NetworkMessage::RpcRequest(request) => {
+                Some((request.protocol_id, request.raw_request.len()))
+            }
+            _ => None,
+        }
+        .filter(|(protocol_id, len)| !self.bandwidth_buckets.acquire_inbound(*protocol_id, *len));
+        if let Some((protocol_id, len)) = over_rate_limit {
+            counters::rate_limited_messages(&self.network_context, protocol_id, RECEIVED_LABEL)
+                .inc();
+            warn!(
+                NetworkSchema::new(&self.network_context)
+                    .connection_metadata(&self.connection_metadata),
+                protocol_id = protocol_id,
+                "{} Dropping inbound message of {} bytes from peer {} over the rate limit of protocol {}",
+                self.network_context,
+                len,
+                self.remote_peer_id().short_str(),
+                protocol_id,
+            );
+            return Ok(());
+        }
+
         match message {
             NetworkMessage::DirectSendMsg(message) => self.handle_inbound_direct_send(message),
             NetworkMessage::Error(error_msg) => {
@@ -469,6 +578,9 @@ where
             NetworkMessage::RpcResponse(response) => {
                 self.outbound_rpcs.handle_inbound_response(response)
             }
+            NetworkMessage::CompressedMsg(_) => {
+                unreachable!("Compressed messages are decompressed above, and never nested")
+            }
         };
         Ok(())
     }
@@ -493,6 +605,20 @@ where
         counters::direct_send_messages(&self.network_context, RECEIVED_LABEL).inc();
         counters::direct_send_bytes(&self.network_context, RECEIVED_LABEL)
             .inc_by(data.len() as u64);
+        counters::protocol_messages(
+            &self.network_context,
+            protocol_id,
+            DIRECT_SEND_LABEL,
+            RECEIVED_LABEL,
+        )
+        .inc();
+        counters::protocol_bytes(
+            &self.network_context,
+            protocol_id,
+            DIRECT_SEND_LABEL,
+            RECEIVED_LABEL,
+        )
+        .inc_by(data.len() as u64);
 
         let notif = PeerNotification::RecvMessage(Message {
             protocol_id,
@@ -513,10 +639,7 @@ where
     async fn handle_outbound_request(
         &mut self,
         request: PeerRequest,
-        write_reqs_tx: &mut channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
+        write_reqs_tx: &mut WriteQueueSender,
     ) {
         trace!(
             "Peer {} PeerRequest::{:?}",
@@ -529,18 +652,46 @@ where
             PeerRequest::SendDirectSend(message) => {
                 let message_len = message.mdata.len();
                 let protocol_id = message.protocol_id;
+                if !self
+                    .bandwidth_buckets
+                    .acquire_outbound(protocol_id, message_len)
+                {
+                    counters::rate_limited_messages(&self.network_context, protocol_id, SENT_LABEL)
+                        .inc();
+                    return;
+                }
                 let message = NetworkMessage::DirectSendMsg(DirectSendMsg {
                     protocol_id,
                     priority: Priority::default(),
                     raw_msg: Vec::from(message.mdata.as_ref()),
                 });
                 let (ack_tx, _ack_rx) = oneshot::channel();
-
-                match write_reqs_tx.send((message, ack_tx)).await {
+                let request = WriteRequest {
+                    message,
+                    protocol_id: Some(protocol_id),
+                    queued_at: self.time_service.now(),
+                    ack_tx,
+                };
+
+                match write_reqs_tx.send(request).await {
                     Ok(_) => {
                         counters::direct_send_messages(&self.network_context, SENT_LABEL).inc();
                         counters::direct_send_bytes(&self.network_context, SENT_LABEL)
                             .inc_by(message_len as u64);
+                        counters::protocol_messages(
+                            &self.network_context,
+                            protocol_id,
+                            DIRECT_SEND_LABEL,
+                            SENT_LABEL,
+                        )
+                        .inc();
+                        counters::protocol_bytes(
+                            &self.network_context,
+                            protocol_id,
+                            DIRECT_SEND_LABEL,
+                            SENT_LABEL,
+                        )
+                        .inc_by(message_len as u64);
                     }
                     Err(e) => {
                         warn!(
@@ -557,6 +708,16 @@ where
             }
             PeerRequest::SendRpc(request) => {
                 let protocol_id = request.protocol_id;
+                if !self
+                    .bandwidth_buckets
+                    .acquire_outbound(protocol_id, request.data.len())
+                {
+                    counters::rate_limited_messages(&self.network_context, protocol_id, SENT_LABEL)
+                        .inc();
+                    // Notify the application that its request was dropped.
+                    let _ = request.res_tx.send(Err(RpcError::RateLimited(protocol_id)));
+                    return;
+                }
                 if let Err(e) = self
                     .outbound_rpcs
                     .handle_outbound_request(request, write_reqs_tx)
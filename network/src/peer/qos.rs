@@ -0,0 +1,238 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Quality of service classes of the messages written to a peer.
+//!
+//! Each [`QosClass`] has a write queue of its own, and the writer task of the peer takes messages
+//! from them by weighted round robin: in each round, a class may have as many messages written as
+//! its weight, and a new round starts as soon as every class with some left has an empty queue.
+//! When the connection backs up, consensus messages then preempt mempool broadcasts and state sync
+//! chunks, while no class is starved. When it doesn't, every message is written right away.
+
+use crate::{counters, peer::WriteRequest, ProtocolId};
+use diem_config::config::OutboundQosConfig;
+use futures::{
+    channel::mpsc,
+    sink::SinkExt,
+    stream::{FusedStream, Stream, StreamExt},
+    task::{Context, Poll},
+};
+use std::pin::Pin;
+
+/// Capacity of the write queue of each class.
+const WRITE_QUEUE_SIZE: usize = 1024;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QosClass {
+    /// Consensus messages, which the liveness of the chain depends on.
+    Consensus,
+    /// Small messages keeping connections healthy, such as health checks, discovery and errors.
+    Control,
+    /// Bulk traffic, such as mempool broadcasts and state sync chunks.
+    Bulk,
+}
+
+impl QosClass {
+    /// Every class, in order of priority.
+    const ALL: [QosClass; 3] = [QosClass::Consensus, QosClass::Control, QosClass::Bulk];
+
+    /// The class of the messages of `protocol_id`, or of those without protocol.
+    pub fn of(protocol_id: Option<ProtocolId>) -> Self {
+        match protocol_id {
+            Some(ProtocolId::ConsensusRpc) | Some(ProtocolId::ConsensusDirectSend) => {
+                QosClass::Consensus
+            }
+            Some(ProtocolId::MempoolDirectSend) | Some(ProtocolId::StateSyncDirectSend) => {
+                QosClass::Bulk
+            }
+            Some(ProtocolId::DiscoveryDirectSend) | Some(ProtocolId::HealthCheckerRpc) | None => {
+                QosClass::Control
+            }
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            QosClass::Consensus => 0,
+            QosClass::Control => 1,
+            QosClass::Bulk => 2,
+        }
+    }
+
+    /// The weight of the class in `config`. A class weighs at least 1, so it can't be starved.
+    fn weight(self, config: &OutboundQosConfig) -> u32 {
+        let weight = match self {
+            QosClass::Consensus => config.consensus_weight,
+            QosClass::Control => config.control_weight,
+            QosClass::Bulk => config.bulk_weight,
+        };
+        weight.max(1)
+    }
+}
+
+/// Creates the write queues of a peer, weighted by `config`.
+pub fn write_queues(config: &OutboundQosConfig) -> (WriteQueueSender, WriteQueueReceiver) {
+    let (senders, queues) = QosClass::ALL
+        .iter()
+        .map(|class| {
+            let (sender, receiver) =
+                channel::new(WRITE_QUEUE_SIZE, &counters::PENDING_WIRE_MESSAGES);
+            let weight = class.weight(config);
+            let queue = ClassQueue {
+                weight,
+                credits: weight,
+                receiver,
+            };
+            (sender, queue)
+        })
+        .unzip();
+    (WriteQueueSender { senders }, WriteQueueReceiver { queues })
+}
+
+/// Queues messages to be written in the queue of their class.
+#[derive(Clone)]
+pub struct WriteQueueSender {
+    senders: Vec<channel::Sender<WriteRequest>>,
+}
+
+impl WriteQueueSender {
+    /// Queues `request`, waiting for room in the queue of its class.
+    pub async fn send(&mut self, request: WriteRequest) -> Result<(), mpsc::SendError> {
+        let class = QosClass::of(request.protocol_id);
+        self.senders[class.index()].send(request).await
+    }
+}
+
+struct ClassQueue {
+    weight: u32,
+    /// Messages the class may still have written in the current round
+    credits: u32,
+    receiver: channel::Receiver<WriteRequest>,
+}
+
+/// The messages to write, from the queues of every class by weighted round robin.
+pub struct WriteQueueReceiver {
+    /// The queues of every class, in order of priority
+    queues: Vec<ClassQueue>,
+}
+
+impl Stream for WriteQueueReceiver {
+    type Item = WriteRequest;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        // The second pass is for a new round, in case the classes with credits left had nothing.
+        for _ in 0..2 {
+            for queue in this
+                .queues
+                .iter_mut()
+                .filter(|queue| queue.credits > 0 && !queue.receiver.is_terminated())
+            {
+                if let Poll::Ready(Some(request)) = queue.receiver.poll_next_unpin(cx) {
+                    queue.credits -= 1;
+                    return Poll::Ready(Some(request));
+                }
+            }
+            for queue in this.queues.iter_mut() {
+                queue.credits = queue.weight;
+            }
+        }
+        if this.is_terminated() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl FusedStream for WriteQueueReceiver {
+    fn is_terminated(&self) -> bool {
+        self.queues
+            .iter()
+            .all(|queue| queue.receiver.is_terminated())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocols::wire::messaging::v1::{ErrorCode, NetworkMessage};
+    use futures::{channel::oneshot, executor::block_on};
+    use std::time::Instant;
+
+    fn write_request(protocol_id: ProtocolId) -> WriteRequest {
+        let (ack_tx, _) = oneshot::channel();
+        WriteRequest {
+            message: NetworkMessage::Error(ErrorCode::parsing_error(0, 0)),
+            protocol_id: Some(protocol_id),
+            queued_at: Instant::now(),
+            ack_tx,
+        }
+    }
+
+    #[test]
+    fn test_weighted_round_robin() {
+        let config = OutboundQosConfig {
+            consensus_weight: 3,
+            control_weight: 2,
+            bulk_weight: 1,
+        };
+        let (mut sender, mut receiver) = write_queues(&config);
+        block_on(async {
+            for _ in 0..4 {
+                sender
+                    .send(write_request(ProtocolId::MempoolDirectSend))
+                    .await
+                    .unwrap();
+                sender
+                    .send(write_request(ProtocolId::HealthCheckerRpc))
+                    .await
+                    .unwrap();
+                sender
+                    .send(write_request(ProtocolId::ConsensusDirectSend))
+                    .await
+                    .unwrap();
+            }
+            drop(sender);
+
+            let mut protocols = Vec::new();
+            while let Some(request) = receiver.next().await {
+                protocols.push(request.protocol_id.unwrap());
+            }
+            use ProtocolId::*;
+            assert_eq!(
+                protocols,
+                vec![
+                    // A full round.
+                    ConsensusDirectSend,
+                    ConsensusDirectSend,
+                    ConsensusDirectSend,
+                    HealthCheckerRpc,
+                    HealthCheckerRpc,
+                    MempoolDirectSend,
+                    // Consensus runs out of messages, so the other classes share the rest.
+                    ConsensusDirectSend,
+                    HealthCheckerRpc,
+                    HealthCheckerRpc,
+                    MempoolDirectSend,
+                    MempoolDirectSend,
+                    MempoolDirectSend,
+                ]
+            );
+            assert!(receiver.is_terminated());
+        });
+    }
+
+    #[test]
+    fn test_classes() {
+        assert_eq!(
+            QosClass::of(Some(ProtocolId::ConsensusRpc)),
+            QosClass::Consensus
+        );
+        assert_eq!(
+            QosClass::of(Some(ProtocolId::StateSyncDirectSend)),
+            QosClass::Bulk
+        );
+        assert_eq!(QosClass::of(None), QosClass::Control);
+    }
+}
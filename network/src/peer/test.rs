@@ -7,7 +7,7 @@ use crate::{
         MAX_FRAME_SIZE, NETWORK_CHANNEL_SIZE,
     },
     peer::{DisconnectReason, Peer, PeerNotification, PeerRequest},
-    peer_manager::TransportNotification,
+    peer_manager::{bandwidth::PeerBandwidthBuckets, TransportNotification},
     protocols::{
         direct_send::Message,
         rpc::{error::RpcError, InboundRpcRequest, OutboundRpcRequest},
@@ -24,7 +24,10 @@ use crate::{
 };
 use bytes::Bytes;
 use channel::{self, diem_channel, message_queues::QueueStyle};
-use diem_config::{config::PeerRole, network_id::NetworkContext};
+use diem_config::{
+    config::{CompressionConfig, OutboundQosConfig, PeerRole},
+    network_id::NetworkContext,
+};
 use diem_time_service::{MockTimeService, TimeService};
 use diem_types::{network_address::NetworkAddress, PeerId};
 use futures::{
@@ -90,6 +93,9 @@ fn build_test_peer(
         MAX_FRAME_SIZE,
         None,
         None,
+        PeerBandwidthBuckets::default(),
+        OutboundQosConfig::default(),
+        CompressionConfig::default(),
     );
     let peer_handle = PeerHandle(peer_reqs_tx);
 
@@ -0,0 +1,212 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Admission control of inbound connections, so that a flood of connections can't exhaust a node.
+//!
+//! Inbound connections are admitted or rejected as soon as they're accepted, before their Noise
+//! handshake, whose key exchange is the costly part of setting up a connection. A connection is
+//! rejected when there are already `max_connections` inbound connections, when its IP already has
+//! `max_connections_per_ip` of them, or when handshakes start faster than `max_handshakes_per_sec`
+//! for longer than `handshake_burst` allows. Admitted connections count until they're closed.
+
+use crate::transport::ConnectionId;
+use diem_config::config::InboundAdmissionConfig;
+use diem_infallible::Mutex;
+use diem_time_service::{TimeService, TimeServiceTrait};
+use std::{collections::HashMap, fmt, net::IpAddr, time::Instant};
+
+/// Why an inbound connection was rejected.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rejection {
+    /// The node has `max_connections` inbound connections already.
+    ConnectionLimit,
+    /// The IP of the connection has `max_connections_per_ip` of them already.
+    IpConnectionLimit,
+    /// Handshakes start too fast.
+    HandshakeRate,
+}
+
+impl Rejection {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Rejection::ConnectionLimit => "connection_limit",
+            Rejection::IpConnectionLimit => "ip_connection_limit",
+            Rejection::HandshakeRate => "handshake_rate",
+        }
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+struct AdmissionState {
+    connections: usize,
+    connections_per_ip: HashMap<IpAddr, usize>,
+    /// The IPs of the established connections, for them to be released once closed
+    established: HashMap<ConnectionId, IpAddr>,
+    /// Handshakes which may still start right away
+    handshake_tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Admission of inbound connections, shared by PeerManager and the listener of its transport.
+pub struct InboundAdmission {
+    config: InboundAdmissionConfig,
+    time_service: TimeService,
+    state: Mutex<AdmissionState>,
+}
+
+impl InboundAdmission {
+    pub fn new(config: InboundAdmissionConfig, time_service: TimeService) -> Self {
+        let state = AdmissionState {
+            connections: 0,
+            connections_per_ip: HashMap::new(),
+            established: HashMap::new(),
+            handshake_tokens: f64::from(config.handshake_burst),
+            refilled_at: time_service.now(),
+        };
+        Self {
+            config,
+            time_service,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Admits a connection just accepted from `ip`, which counts until it's released.
+    pub fn admit(&self, ip: IpAddr) -> Result<(), Rejection> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        let mut state = self.state.lock();
+        if state.connections >= self.config.max_connections {
+            return Err(Rejection::ConnectionLimit);
+        }
+        let ip_connections = state.connections_per_ip.get(&ip).copied().unwrap_or(0);
+        if ip_connections >= self.config.max_connections_per_ip {
+            return Err(Rejection::IpConnectionLimit);
+        }
+
+        let now = self.time_service.now();
+        let elapsed = now.saturating_duration_since(state.refilled_at);
+        state.handshake_tokens = (state.handshake_tokens
+            + elapsed.as_secs_f64() * f64::from(self.config.max_handshakes_per_sec))
+        .min(f64::from(self.config.handshake_burst.max(1)));
+        state.refilled_at = now;
+        if state.handshake_tokens < 1.0 {
+            return Err(Rejection::HandshakeRate);
+        }
+        state.handshake_tokens -= 1.0;
+
+        state.connections += 1;
+        state.connections_per_ip.insert(ip, ip_connections + 1);
+        Ok(())
+    }
+
+    /// Records that the connection admitted from `ip` completed its handshake, for it to be
+    /// released by `connection_id` once closed.
+    pub fn established(&self, connection_id: ConnectionId, ip: IpAddr) {
+        if self.config.enabled {
+            self.state.lock().established.insert(connection_id, ip);
+        }
+    }
+
+    /// Releases a connection admitted from `ip` which failed its handshake.
+    pub fn release(&self, ip: IpAddr) {
+        self.state.lock().release(ip);
+    }
+
+    /// Releases the established connection `connection_id`, if it was admitted. Connections are
+    /// only released once, however many times they're closed.
+    pub fn close(&self, connection_id: ConnectionId) {
+        let mut state = self.state.lock();
+        if let Some(ip) = state.established.remove(&connection_id) {
+            state.release(ip);
+        }
+    }
+}
+
+impl AdmissionState {
+    fn release(&mut self, ip: IpAddr) {
+        if let Some(ip_connections) = self.connections_per_ip.get_mut(&ip) {
+            *ip_connections -= 1;
+            if *ip_connections == 0 {
+                self.connections_per_ip.remove(&ip);
+            }
+            self.connections -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn admission() -> (InboundAdmission, diem_time_service::MockTimeService) {
+        let time_service = TimeService::mock();
+        let config = InboundAdmissionConfig {
+            max_connections: 3,
+            max_connections_per_ip: 2,
+            max_handshakes_per_sec: 1,
+            handshake_burst: 10,
+            enabled: true,
+        };
+        (
+            InboundAdmission::new(config, time_service.clone()),
+            time_service.into_mock(),
+        )
+    }
+
+    #[test]
+    fn test_connection_limits() {
+        let (admission, _) = admission();
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let other_ip: IpAddr = "2001:db8::1".parse().unwrap();
+
+        admission.admit(ip).unwrap();
+        admission.admit(ip).unwrap();
+        assert_eq!(admission.admit(ip), Err(Rejection::IpConnectionLimit));
+        admission.admit(other_ip).unwrap();
+        assert_eq!(admission.admit(other_ip), Err(Rejection::ConnectionLimit));
+
+        // A failed handshake makes room for another connection.
+        admission.release(ip);
+        admission.admit(other_ip).unwrap();
+
+        // So does closing an established connection, only once.
+        let connection_id = ConnectionId::from(1);
+        admission.established(connection_id, ip);
+        admission.close(connection_id);
+        admission.close(connection_id);
+        admission.admit(ip).unwrap();
+        assert_eq!(admission.admit(ip), Err(Rejection::ConnectionLimit));
+    }
+
+    #[test]
+    fn test_handshake_rate() {
+        let (admission, mock_time) = admission();
+        for i in 0..10u8 {
+            let ip = IpAddr::from([10, 0, 0, i]);
+            admission.admit(ip).unwrap();
+            admission.release(ip);
+        }
+        let ip: IpAddr = "10.0.1.1".parse().unwrap();
+        assert_eq!(admission.admit(ip), Err(Rejection::HandshakeRate));
+
+        mock_time.advance_secs(1);
+        admission.admit(ip).unwrap();
+        assert_eq!(admission.admit(ip), Err(Rejection::HandshakeRate));
+    }
+
+    #[test]
+    fn test_disabled() {
+        let (mut admission, _) = admission();
+        admission.config.enabled = false;
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        for _ in 0..20 {
+            admission.admit(ip).unwrap();
+        }
+    }
+}
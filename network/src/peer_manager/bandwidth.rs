@@ -0,0 +1,178 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Byte rate limits of each peer, in total and by protocol, on top of those of each IP, so that a
+//! single chatty peer can't take up all of the bandwidth of a node.
+//!
+//! The total bytes read from and written to a peer are limited on its connection, which waits for
+//! tokens, pushing back on the remote peer or on the local senders. The bytes of the messages of a
+//! protocol are limited message by message instead, and messages over the limit are dropped, as
+//! waiting would hold up the messages of every other protocol. The bucket size of a protocol must
+//! then be at least the size of its largest message.
+
+use crate::ProtocolId;
+use diem_config::config::RateLimitConfig;
+use diem_rate_limiter::rate_limit::{SharedBucket, TokenBucketRateLimiter};
+use diem_types::PeerId;
+use std::collections::HashMap;
+
+pub type PeerIdTokenBucketLimiter = TokenBucketRateLimiter<PeerId>;
+
+/// The rate limiters of all peers.
+pub struct BandwidthLimiters {
+    inbound: PeerIdTokenBucketLimiter,
+    outbound: PeerIdTokenBucketLimiter,
+    inbound_protocols: HashMap<ProtocolId, PeerIdTokenBucketLimiter>,
+    outbound_protocols: HashMap<ProtocolId, PeerIdTokenBucketLimiter>,
+}
+
+impl BandwidthLimiters {
+    pub fn new(
+        inbound: PeerIdTokenBucketLimiter,
+        outbound: PeerIdTokenBucketLimiter,
+        inbound_protocols: HashMap<ProtocolId, PeerIdTokenBucketLimiter>,
+        outbound_protocols: HashMap<ProtocolId, PeerIdTokenBucketLimiter>,
+    ) -> Self {
+        Self {
+            inbound,
+            outbound,
+            inbound_protocols,
+            outbound_protocols,
+        }
+    }
+
+    /// No rate limiting at all
+    pub fn open() -> Self {
+        Self::new(
+            TokenBucketRateLimiter::open("peer_inbound"),
+            TokenBucketRateLimiter::open("peer_outbound"),
+            HashMap::new(),
+            HashMap::new(),
+        )
+    }
+
+    /// The buckets of `peer_id`, created on first use
+    pub fn buckets(&self, peer_id: PeerId) -> PeerBandwidthBuckets {
+        let protocol_buckets = |limiters: &HashMap<ProtocolId, PeerIdTokenBucketLimiter>| {
+            limiters
+                .iter()
+                .map(|(protocol_id, limiter)| (*protocol_id, limiter.bucket(peer_id)))
+                .collect()
+        };
+        PeerBandwidthBuckets {
+            inbound: Some(self.inbound.bucket(peer_id)),
+            outbound: Some(self.outbound.bucket(peer_id)),
+            inbound_protocols: protocol_buckets(&self.inbound_protocols),
+            outbound_protocols: protocol_buckets(&self.outbound_protocols),
+        }
+    }
+
+    /// Garbage collects the buckets of `peer_id`, if no connection uses them anymore
+    pub fn try_garbage_collect_peer(&self, peer_id: &PeerId) {
+        self.inbound.try_garbage_collect_key(peer_id);
+        self.outbound.try_garbage_collect_key(peer_id);
+        for limiter in self
+            .inbound_protocols
+            .values()
+            .chain(self.outbound_protocols.values())
+        {
+            limiter.try_garbage_collect_key(peer_id);
+        }
+    }
+}
+
+/// Resolves the protocol names of `configs` into their `ProtocolId`.
+pub fn protocol_rate_limit_configs(
+    configs: &HashMap<String, RateLimitConfig>,
+) -> HashMap<ProtocolId, RateLimitConfig> {
+    configs
+        .iter()
+        .map(|(name, config)| {
+            let protocol_id = ProtocolId::all()
+                .iter()
+                .find(|protocol_id| protocol_id.as_str() == name)
+                .unwrap_or_else(|| panic!("Unknown protocol in rate limit configs: '{}'", name));
+            (*protocol_id, *config)
+        })
+        .collect()
+}
+
+/// The rate limiting buckets of a single peer, used by its `Peer` actor.
+#[derive(Clone, Default)]
+pub struct PeerBandwidthBuckets {
+    pub inbound: Option<SharedBucket>,
+    pub outbound: Option<SharedBucket>,
+    pub inbound_protocols: HashMap<ProtocolId, SharedBucket>,
+    pub outbound_protocols: HashMap<ProtocolId, SharedBucket>,
+}
+
+impl PeerBandwidthBuckets {
+    /// Takes the tokens for an inbound message of `len` bytes of `protocol_id`. Returns false if
+    /// there aren't enough of them, in which case the message should be dropped.
+    pub fn acquire_inbound(&self, protocol_id: ProtocolId, len: usize) -> bool {
+        acquire(&self.inbound_protocols, protocol_id, len)
+    }
+
+    /// Takes the tokens for an outbound message of `len` bytes of `protocol_id`. Returns false if
+    /// there aren't enough of them, in which case the message should be dropped.
+    pub fn acquire_outbound(&self, protocol_id: ProtocolId, len: usize) -> bool {
+        acquire(&self.outbound_protocols, protocol_id, len)
+    }
+}
+
+fn acquire(
+    buckets: &HashMap<ProtocolId, SharedBucket>,
+    protocol_id: ProtocolId,
+    len: usize,
+) -> bool {
+    buckets
+        .get(&protocol_id)
+        .map_or(true, |bucket| bucket.lock().acquire_all_tokens(len).is_ok())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_protocol_limits() {
+        let limiter = || TokenBucketRateLimiter::test(100, 100);
+        let limiters = BandwidthLimiters::new(
+            TokenBucketRateLimiter::open("peer_inbound"),
+            TokenBucketRateLimiter::open("peer_outbound"),
+            vec![(ProtocolId::MempoolDirectSend, limiter())]
+                .into_iter()
+                .collect(),
+            vec![(ProtocolId::StateSyncDirectSend, limiter())]
+                .into_iter()
+                .collect(),
+        );
+        let peer_a = PeerId::random();
+        let buckets = limiters.buckets(peer_a);
+
+        assert!(buckets.acquire_inbound(ProtocolId::MempoolDirectSend, 60));
+        assert!(!buckets.acquire_inbound(ProtocolId::MempoolDirectSend, 60));
+        // Protocols and directions without limits aren't rate limited.
+        assert!(buckets.acquire_inbound(ProtocolId::StateSyncDirectSend, 1000));
+        assert!(buckets.acquire_outbound(ProtocolId::MempoolDirectSend, 1000));
+        assert!(buckets.acquire_outbound(ProtocolId::StateSyncDirectSend, 100));
+        assert!(!buckets.acquire_outbound(ProtocolId::StateSyncDirectSend, 1));
+
+        // Every peer has buckets of its own, shared by its connections.
+        assert!(limiters
+            .buckets(PeerId::random())
+            .acquire_inbound(ProtocolId::MempoolDirectSend, 60));
+        assert!(!limiters
+            .buckets(peer_a)
+            .acquire_inbound(ProtocolId::MempoolDirectSend, 60));
+    }
+
+    #[test]
+    fn test_protocol_rate_limit_configs() {
+        let mut configs = HashMap::new();
+        configs.insert("MempoolDirectSend".to_string(), RateLimitConfig::default());
+        let configs = protocol_rate_limit_configs(&configs);
+        assert_eq!(configs.len(), 1);
+        assert!(configs.contains_key(&ProtocolId::MempoolDirectSend));
+    }
+}
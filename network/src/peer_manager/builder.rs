@@ -6,16 +6,23 @@ use crate::{
     counters::NETWORK_RATE_LIMIT_METRICS,
     noise::{stream::NoiseStream, HandshakeAuthMode},
     peer_manager::{
-        conn_notifs_channel, ConnectionRequest, ConnectionRequestSender, PeerManager,
-        PeerManagerNotification, PeerManagerRequest, PeerManagerRequestSender,
+        admission::InboundAdmission,
+        bandwidth::{protocol_rate_limit_configs, BandwidthLimiters, PeerIdTokenBucketLimiter},
+        conn_notifs_channel,
+        reputation::PeerReputation,
+        ConnectionRequest, ConnectionRequestSender, PeerManager, PeerManagerNotification,
+        PeerManagerRequest, PeerManagerRequestSender,
     },
-    protocols::wire::handshake::v1::SupportedProtocols,
+    protocols::wire::{compression::CompressionAlgorithm, handshake::v1::SupportedProtocols},
     transport::{self, Connection, DiemNetTransport, DIEM_TCP_TRANSPORT},
     ProtocolId,
 };
 use channel::{self, diem_channel, message_queues::QueueStyle};
 use diem_config::{
-    config::{PeerSet, RateLimitConfig, HANDSHAKE_VERSION},
+    config::{
+        CompressionConfig, InboundAdmissionConfig, OutboundQosConfig, PeerReputationConfig,
+        PeerSet, RateLimitConfig, HANDSHAKE_VERSION,
+    },
     network_id::NetworkContext,
 };
 use diem_crypto::x25519;
@@ -24,14 +31,18 @@ use diem_logger::prelude::*;
 use diem_metrics::IntCounterVec;
 use diem_rate_limiter::rate_limit::TokenBucketRateLimiter;
 use diem_time_service::TimeService;
-use diem_types::{chain_id::ChainId, network_address::NetworkAddress, PeerId};
+use diem_types::{
+    chain_id::ChainId,
+    network_address::{IpPreference, NetworkAddress},
+    PeerId,
+};
 #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
 use netcore::transport::memory::MemoryTransport;
 use netcore::transport::{
     tcp::{TcpSocket, TcpTransport},
     Transport,
 };
-use std::{clone::Clone, collections::HashMap, fmt::Debug, net::IpAddr, sync::Arc};
+use std::{clone::Clone, collections::HashMap, fmt::Debug, hash::Hash, sync::Arc};
 use tokio::runtime::Handle;
 
 /// Inbound and Outbound connections are always secured with NoiseIK.  The dialer
@@ -55,6 +66,8 @@ struct TransportContext {
     authentication_mode: AuthenticationMode,
     trusted_peers: Arc<RwLock<PeerSet>>,
     enable_proxy_protocol: bool,
+    compression_algorithms: Vec<CompressionAlgorithm>,
+    ip_preference: IpPreference,
 }
 
 impl TransportContext {
@@ -65,6 +78,8 @@ impl TransportContext {
         authentication_mode: AuthenticationMode,
         trusted_peers: Arc<RwLock<PeerSet>>,
         enable_proxy_protocol: bool,
+        compression_algorithms: Vec<CompressionAlgorithm>,
+        ip_preference: IpPreference,
     ) -> Self {
         Self {
             chain_id,
@@ -73,14 +88,14 @@ impl TransportContext {
             authentication_mode,
             trusted_peers,
             enable_proxy_protocol,
+            compression_algorithms,
+            ip_preference,
         }
     }
 
     fn supported_protocols(&self) -> SupportedProtocols {
-        self.direct_send_protocols
-            .iter()
-            .chain(&self.rpc_protocols)
-            .into()
+        SupportedProtocols::from(self.direct_send_protocols.iter().chain(&self.rpc_protocols))
+            .with_compression(&self.compression_algorithms)
     }
 
     fn augment_direct_send_protocols(
@@ -105,6 +120,7 @@ struct PeerManagerContext {
     connection_reqs_rx: diem_channel::Receiver<PeerId, ConnectionRequest>,
 
     trusted_peers: Arc<RwLock<PeerSet>>,
+    peer_reputation: Arc<RwLock<PeerReputation>>,
     upstream_handlers:
         HashMap<ProtocolId, diem_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>>,
     connection_event_handlers: Vec<conn_notifs_channel::Sender>,
@@ -115,6 +131,12 @@ struct PeerManagerContext {
     inbound_connection_limit: usize,
     inbound_rate_limit_config: Option<RateLimitConfig>,
     outbound_rate_limit_config: Option<RateLimitConfig>,
+    peer_inbound_rate_limit_config: Option<RateLimitConfig>,
+    peer_outbound_rate_limit_config: Option<RateLimitConfig>,
+    protocol_rate_limit_configs: HashMap<ProtocolId, RateLimitConfig>,
+    outbound_qos_config: OutboundQosConfig,
+    compression_config: CompressionConfig,
+    inbound_admission: Arc<InboundAdmission>,
 }
 
 impl PeerManagerContext {
@@ -125,6 +147,7 @@ impl PeerManagerContext {
         connection_reqs_rx: diem_channel::Receiver<PeerId, ConnectionRequest>,
 
         trusted_peers: Arc<RwLock<PeerSet>>,
+        peer_reputation: Arc<RwLock<PeerReputation>>,
         upstream_handlers: HashMap<
             ProtocolId,
             diem_channel::Sender<(PeerId, ProtocolId), PeerManagerNotification>,
@@ -137,6 +160,12 @@ impl PeerManagerContext {
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
+        peer_inbound_rate_limit_config: Option<RateLimitConfig>,
+        peer_outbound_rate_limit_config: Option<RateLimitConfig>,
+        protocol_rate_limit_configs: HashMap<ProtocolId, RateLimitConfig>,
+        outbound_qos_config: OutboundQosConfig,
+        compression_config: CompressionConfig,
+        inbound_admission: Arc<InboundAdmission>,
     ) -> Self {
         Self {
             pm_reqs_tx,
@@ -145,6 +174,7 @@ impl PeerManagerContext {
             connection_reqs_rx,
 
             trusted_peers,
+            peer_reputation,
             upstream_handlers,
             connection_event_handlers,
 
@@ -154,6 +184,12 @@ impl PeerManagerContext {
             inbound_connection_limit,
             inbound_rate_limit_config,
             outbound_rate_limit_config,
+            peer_inbound_rate_limit_config,
+            peer_outbound_rate_limit_config,
+            protocol_rate_limit_configs,
+            outbound_qos_config,
+            compression_config,
+            inbound_admission,
         }
     }
 
@@ -193,6 +229,7 @@ pub struct PeerManagerBuilder {
     peer_manager: Option<TransportPeerManager>,
     // ListenAddress will be updated when the PeerManager is built
     listen_address: NetworkAddress,
+    peer_reputation: Arc<RwLock<PeerReputation>>,
 }
 
 impl PeerManagerBuilder {
@@ -211,6 +248,14 @@ impl PeerManagerBuilder {
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
+        peer_inbound_rate_limit_config: Option<RateLimitConfig>,
+        peer_outbound_rate_limit_config: Option<RateLimitConfig>,
+        protocol_rate_limit_configs: &HashMap<String, RateLimitConfig>,
+        peer_reputation_config: PeerReputationConfig,
+        outbound_qos_config: OutboundQosConfig,
+        compression_config: CompressionConfig,
+        ip_preference: IpPreference,
+        inbound_admission_config: InboundAdmissionConfig,
     ) -> Self {
         // Setup channel to send requests to peer manager.
         let (pm_reqs_tx, pm_reqs_rx) = diem_channel::new(
@@ -221,6 +266,14 @@ impl PeerManagerBuilder {
         // Setup channel to send connection requests to peer manager.
         let (connection_reqs_tx, connection_reqs_rx) =
             diem_channel::new(QueueStyle::FIFO, channel_size, None);
+        let peer_reputation = Arc::new(RwLock::new(PeerReputation::new(
+            peer_reputation_config,
+            time_service.clone(),
+        )));
+        let inbound_admission = Arc::new(InboundAdmission::new(
+            inbound_admission_config,
+            time_service.clone(),
+        ));
 
         Self {
             network_context,
@@ -232,6 +285,12 @@ impl PeerManagerBuilder {
                 authentication_mode,
                 trusted_peers.clone(),
                 enable_proxy_protocol,
+                compression_config
+                    .algorithms
+                    .iter()
+                    .map(|algorithm| CompressionAlgorithm::from(*algorithm))
+                    .collect(),
+                ip_preference,
             )),
             peer_manager_context: Some(PeerManagerContext::new(
                 pm_reqs_tx,
@@ -239,6 +298,7 @@ impl PeerManagerBuilder {
                 connection_reqs_tx,
                 connection_reqs_rx,
                 trusted_peers,
+                peer_reputation.clone(),
                 HashMap::new(),
                 Vec::new(),
                 max_concurrent_network_reqs,
@@ -247,9 +307,16 @@ impl PeerManagerBuilder {
                 inbound_connection_limit,
                 inbound_rate_limit_config,
                 outbound_rate_limit_config,
+                peer_inbound_rate_limit_config,
+                peer_outbound_rate_limit_config,
+                protocol_rate_limit_configs(protocol_rate_limit_configs),
+                outbound_qos_config,
+                compression_config,
+                inbound_admission,
             )),
             peer_manager: None,
             listen_address,
+            peer_reputation,
         }
     }
 
@@ -257,6 +324,11 @@ impl PeerManagerBuilder {
         self.listen_address.clone()
     }
 
+    /// The scores of peers, which PeerManager keeps up to date once started.
+    pub fn peer_reputation(&self) -> Arc<RwLock<PeerReputation>> {
+        self.peer_reputation.clone()
+    }
+
     pub fn connection_reqs_tx(&self) -> diem_channel::Sender<PeerId, ConnectionRequest> {
         self.peer_manager_context
             .as_ref()
@@ -278,6 +350,10 @@ impl PeerManagerBuilder {
         let protos = transport_context.supported_protocols();
         let chain_id = transport_context.chain_id;
         let enable_proxy_protocol = transport_context.enable_proxy_protocol;
+        let tcp_transport = TcpTransport {
+            ip_preference: transport_context.ip_preference,
+            ..DIEM_TCP_TRANSPORT
+        };
 
         let (key, auth_mode) = match transport_context.authentication_mode {
             AuthenticationMode::MaybeMutual(key) => (
@@ -294,7 +370,7 @@ impl PeerManagerBuilder {
             [Ip4(_), Tcp(_)] | [Ip6(_), Tcp(_)] => {
                 Some(TransportPeerManager::Tcp(self.build_with_transport(
                     DiemNetTransport::new(
-                        DIEM_TCP_TRANSPORT.clone(),
+                        tcp_transport,
                         self.network_context.clone(),
                         self.time_service.clone(),
                         key,
@@ -357,6 +433,28 @@ impl PeerManagerBuilder {
             "outbound",
             pm_context.outbound_rate_limit_config,
         );
+        let bandwidth_limiters = BandwidthLimiters::new(
+            token_bucket_rate_limiter(
+                &self.network_context,
+                "peer_inbound",
+                pm_context.peer_inbound_rate_limit_config,
+            ),
+            token_bucket_rate_limiter(
+                &self.network_context,
+                "peer_outbound",
+                pm_context.peer_outbound_rate_limit_config,
+            ),
+            protocol_rate_limiters(
+                &self.network_context,
+                "inbound",
+                &pm_context.protocol_rate_limit_configs,
+            ),
+            protocol_rate_limiters(
+                &self.network_context,
+                "outbound",
+                &pm_context.protocol_rate_limit_configs,
+            ),
+        );
         let peer_mgr = PeerManager::new(
             executor.clone(),
             self.time_service.clone(),
@@ -366,6 +464,7 @@ impl PeerManagerBuilder {
             // (which could be empty, like in client use case)
             self.listen_address.clone(),
             pm_context.trusted_peers,
+            pm_context.peer_reputation,
             pm_context.pm_reqs_rx,
             pm_context.connection_reqs_rx,
             pm_context.upstream_handlers,
@@ -376,6 +475,10 @@ impl PeerManagerBuilder {
             pm_context.inbound_connection_limit,
             inbound_rate_limiters,
             outbound_rate_limiters,
+            bandwidth_limiters,
+            pm_context.outbound_qos_config,
+            pm_context.compression_config,
+            pm_context.inbound_admission,
         );
 
         // PeerManager constructor appends a public key to the listen_address.
@@ -463,11 +566,11 @@ impl PeerManagerBuilder {
 }
 
 /// Builds a token bucket rate limiter with attached metrics
-fn token_bucket_rate_limiter(
+fn token_bucket_rate_limiter<Key: Eq + Hash + Clone + Debug>(
     network_context: &Arc<NetworkContext>,
     label: &'static str,
     input: Option<RateLimitConfig>,
-) -> TokenBucketRateLimiter<IpAddr> {
+) -> TokenBucketRateLimiter<Key> {
     if let Some(config) = input {
         if config.enabled {
             return TokenBucketRateLimiter::new(
@@ -482,3 +585,27 @@ fn token_bucket_rate_limiter(
     }
     TokenBucketRateLimiter::open(label)
 }
+
+/// Builds a token bucket rate limiter for each protocol with a config, without metrics, as messages
+/// over the limit are counted when dropped
+fn protocol_rate_limiters(
+    network_context: &Arc<NetworkContext>,
+    label: &'static str,
+    configs: &HashMap<ProtocolId, RateLimitConfig>,
+) -> HashMap<ProtocolId, PeerIdTokenBucketLimiter> {
+    configs
+        .iter()
+        .filter(|(_, config)| config.enabled)
+        .map(|(protocol_id, config)| {
+            let limiter = TokenBucketRateLimiter::new(
+                label,
+                format!("{}-{}", network_context, protocol_id),
+                config.initial_bucket_fill_percentage,
+                config.ip_byte_bucket_size,
+                config.ip_byte_bucket_rate,
+                None,
+            );
+            (*protocol_id, limiter)
+        })
+        .collect()
+}
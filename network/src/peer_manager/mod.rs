@@ -51,14 +51,22 @@ use std::{
 };
 use tokio::runtime::Handle;
 
+pub mod admission;
+pub mod bandwidth;
 pub mod builder;
 pub mod conn_notifs_channel;
 mod error;
+pub mod reputation;
 #[cfg(test)]
 mod tests;
 
 pub use self::error::PeerManagerError;
-use diem_config::config::{PeerRole, PeerSet};
+use self::{
+    admission::InboundAdmission,
+    bandwidth::BandwidthLimiters,
+    reputation::{Misbehavior, PeerReputation},
+};
+use diem_config::config::{CompressionConfig, OutboundQosConfig, PeerRole, PeerSet};
 use diem_infallible::RwLock;
 
 /// Request received by PeerManager from upstream actors.
@@ -90,6 +98,8 @@ pub enum ConnectionRequest {
         PeerId,
         #[serde(skip)] oneshot::Sender<Result<(), PeerManagerError>>,
     ),
+    /// Lowers the reputation of a peer for a misbehavior.
+    ReportPeer(PeerId, Misbehavior),
 }
 
 #[derive(Clone, PartialEq, Serialize)]
@@ -234,6 +244,18 @@ impl ConnectionRequestSender {
             .push(peer, ConnectionRequest::DisconnectPeer(peer, oneshot_tx))?;
         oneshot_rx.await?
     }
+
+    /// Reports a misbehavior of `peer`. The function returns when the report has been enqueued,
+    /// PeerManager may then ban and disconnect the peer.
+    pub fn report_peer(
+        &mut self,
+        peer: PeerId,
+        misbehavior: Misbehavior,
+    ) -> Result<(), PeerManagerError> {
+        self.inner
+            .push(peer, ConnectionRequest::ReportPeer(peer, misbehavior))?;
+        Ok(())
+    }
 }
 
 pub type IpAddrTokenBucketLimiter = TokenBucketRateLimiter<IpAddr>;
@@ -263,6 +285,8 @@ where
     >,
     /// Known trusted peers from discovery
     trusted_peers: Arc<RwLock<PeerSet>>,
+    /// Scores of peers by their misbehaviors, shared with the peer monitoring endpoint
+    peer_reputation: Arc<RwLock<PeerReputation>>,
     /// Channel to receive requests from other actors.
     requests_rx: diem_channel::Receiver<(PeerId, ProtocolId), PeerManagerRequest>,
     /// Upstream handlers for RPC and DirectSend protocols. The handlers are promised fair delivery
@@ -296,6 +320,14 @@ where
     inbound_rate_limiters: IpAddrTokenBucketLimiter,
     /// Keyed storage of all outbound rate limiters
     outbound_rate_limiters: IpAddrTokenBucketLimiter,
+    /// Rate limiters of each peer, in total and by protocol
+    bandwidth_limiters: BandwidthLimiters,
+    /// Weights of the QoS classes of the messages written to each peer
+    outbound_qos_config: OutboundQosConfig,
+    /// Compression of the messages exchanged with each peer
+    compression_config: CompressionConfig,
+    /// Admission of inbound connections, shared with the TransportHandler
+    inbound_admission: Arc<InboundAdmission>,
 }
 
 impl<TTransport, TSocket> PeerManager<TTransport, TSocket>
@@ -312,6 +344,7 @@ where
         network_context: Arc<NetworkContext>,
         listen_addr: NetworkAddress,
         trusted_peers: Arc<RwLock<PeerSet>>,
+        peer_reputation: Arc<RwLock<PeerReputation>>,
         requests_rx: diem_channel::Receiver<(PeerId, ProtocolId), PeerManagerRequest>,
         connection_reqs_rx: diem_channel::Receiver<PeerId, ConnectionRequest>,
         upstream_handlers: HashMap<
@@ -325,6 +358,10 @@ where
         inbound_connection_limit: usize,
         inbound_rate_limiters: IpAddrTokenBucketLimiter,
         outbound_rate_limiters: IpAddrTokenBucketLimiter,
+        bandwidth_limiters: BandwidthLimiters,
+        outbound_qos_config: OutboundQosConfig,
+        compression_config: CompressionConfig,
+        inbound_admission: Arc<InboundAdmission>,
     ) -> Self {
         let (transport_notifs_tx, transport_notifs_rx) = channel::new(
             channel_size,
@@ -343,6 +380,7 @@ where
             listen_addr,
             transport_reqs_rx,
             transport_notifs_tx_clone,
+            inbound_admission.clone(),
         );
 
         Self {
@@ -353,6 +391,7 @@ where
             transport_handler: Some(transport_handler),
             active_peers: HashMap::new(),
             trusted_peers,
+            peer_reputation,
             requests_rx,
             connection_reqs_rx,
             transport_reqs_tx,
@@ -368,6 +407,10 @@ where
             inbound_connection_limit,
             inbound_rate_limiters,
             outbound_rate_limiters,
+            bandwidth_limiters,
+            outbound_qos_config,
+            compression_config,
+            inbound_admission,
         }
     }
 
@@ -454,6 +497,25 @@ where
         self.sample_connected_peers();
         match event {
             TransportNotification::NewConnection(mut conn) => {
+                // Reject connections with banned peers, whichever side dialed
+                if self
+                    .peer_reputation
+                    .read()
+                    .is_banned(&conn.metadata.remote_peer_id)
+                {
+                    info!(
+                        NetworkSchema::new(&self.network_context)
+                            .connection_metadata_with_address(&conn.metadata),
+                        "{} Connection rejected since peer is banned: {}",
+                        self.network_context,
+                        conn.metadata
+                    );
+                    counters::connections_rejected(&self.network_context, conn.metadata.origin)
+                        .inc();
+                    self.disconnect(conn);
+                    return;
+                }
+
                 match conn.metadata.origin {
                     ConnectionOrigin::Outbound => {
                         // TODO: This is right now a hack around having to feed trusted peers deeper in the outbound path.  Inbound ones are assigned at Noise handshake time.
@@ -550,6 +612,8 @@ where
                     }
                 }
                 self.update_connected_peers_metrics();
+                self.inbound_admission
+                    .close(lost_conn_metadata.connection_id);
 
                 // If the connection was explicitly closed by an upstream client, send an ACK.
                 if let Some(oneshot_tx) = self
@@ -589,10 +653,39 @@ where
                 self.inbound_rate_limiters.try_garbage_collect_key(&ip_addr);
                 self.outbound_rate_limiters
                     .try_garbage_collect_key(&ip_addr);
+                self.bandwidth_limiters.try_garbage_collect_peer(&peer_id);
+            }
+            TransportNotification::Misbehaved(conn_metadata, misbehavior) => {
+                self.report_peer(conn_metadata.remote_peer_id, misbehavior);
             }
         }
     }
 
+    /// Lowers the score of `peer_id` for `misbehavior`. Once it scores too low, the peer is
+    /// banned and disconnected, unless it's a trusted peer.
+    fn report_peer(&mut self, peer_id: PeerId, misbehavior: Misbehavior) {
+        counters::peer_misbehaviors(&self.network_context, misbehavior.as_str()).inc();
+        let should_ban = self.peer_reputation.write().report(peer_id, misbehavior);
+        if !should_ban || self.trusted_peers.read().contains_key(&peer_id) {
+            return;
+        }
+
+        self.peer_reputation.write().ban(peer_id);
+        counters::peers_banned(&self.network_context).inc();
+        warn!(
+            NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+            misbehavior = %misbehavior,
+            "{} Banning peer {} after misbehavior: {}",
+            self.network_context,
+            peer_id.short_str(),
+            misbehavior
+        );
+        // Dropping the send end of the PeerRequest channel triggers a disconnect.
+        if let Some((_, sender)) = self.active_peers.remove(&peer_id) {
+            drop(sender);
+        }
+    }
+
     async fn handle_connection_request(&mut self, request: ConnectionRequest) {
         trace!(
             NetworkSchema::new(&self.network_context),
@@ -604,6 +697,30 @@ where
         self.sample_connected_peers();
         match request {
             ConnectionRequest::DialPeer(requested_peer_id, addr, response_tx) => {
+                // Don't dial banned peers
+                if self.peer_reputation.read().is_banned(&requested_peer_id) {
+                    debug!(
+                        NetworkSchema::new(&self.network_context).remote_peer(&requested_peer_id),
+                        "{} Peer {} is banned. Not dialing address {}",
+                        self.network_context,
+                        requested_peer_id.short_str(),
+                        addr
+                    );
+                    if let Err(send_err) =
+                        response_tx.send(Err(PeerManagerError::Banned(requested_peer_id)))
+                    {
+                        info!(
+                            NetworkSchema::new(&self.network_context)
+                                .remote_peer(&requested_peer_id),
+                            "{} Failed to notify that peer is banned for Peer {}: {:?}",
+                            self.network_context,
+                            requested_peer_id.short_str(),
+                            send_err
+                        );
+                    }
+                    return;
+                }
+
                 // Only dial peers which we aren't already connected with
                 if let Some((curr_connection, _)) = self.active_peers.get(&requested_peer_id) {
                     let error = PeerManagerError::AlreadyConnected(curr_connection.addr.clone());
@@ -659,6 +776,9 @@ where
                     }
                 }
             }
+            ConnectionRequest::ReportPeer(peer_id, misbehavior) => {
+                self.report_peer(peer_id, misbehavior);
+            }
         }
     }
 
@@ -755,6 +875,8 @@ where
     }
 
     fn disconnect(&mut self, connection: Connection<TSocket>) {
+        self.inbound_admission
+            .close(connection.metadata.connection_id);
         let network_context = self.network_context.clone();
         let time_service = self.time_service.clone();
 
@@ -837,6 +959,7 @@ where
             .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
         let inbound_rate_limiter = self.inbound_rate_limiters.bucket(ip_addr);
         let outbound_rate_limiter = self.outbound_rate_limiters.bucket(ip_addr);
+        let bandwidth_buckets = self.bandwidth_limiters.buckets(peer_id);
 
         // TODO: Add label for peer.
         let (peer_reqs_tx, peer_reqs_rx) = diem_channel::new(
@@ -866,6 +989,9 @@ where
             self.max_frame_size,
             Some(inbound_rate_limiter),
             Some(outbound_rate_limiter),
+            bandwidth_buckets,
+            self.outbound_qos_config,
+            self.compression_config.clone(),
         );
         self.executor.spawn(peer.start());
 
@@ -999,6 +1125,7 @@ enum TransportRequest {
 pub enum TransportNotification<TSocket> {
     NewConnection(#[serde(skip)] Connection<TSocket>),
     Disconnected(ConnectionMetadata, DisconnectReason),
+    Misbehaved(ConnectionMetadata, Misbehavior),
 }
 
 /// Responsible for listening for new incoming connections
@@ -1014,6 +1141,8 @@ where
     listener: Fuse<TTransport::Listener>,
     transport_reqs_rx: channel::Receiver<TransportRequest>,
     transport_notifs_tx: channel::Sender<TransportNotification<TSocket>>,
+    /// Admission of inbound connections, checked before upgrading them
+    inbound_admission: Arc<InboundAdmission>,
 }
 
 impl<TTransport, TSocket> TransportHandler<TTransport, TSocket>
@@ -1031,6 +1160,7 @@ where
         listen_addr: NetworkAddress,
         transport_reqs_rx: channel::Receiver<TransportRequest>,
         transport_notifs_tx: channel::Sender<TransportNotification<TSocket>>,
+        inbound_admission: Arc<InboundAdmission>,
     ) -> (Self, NetworkAddress) {
         let (listener, listen_addr) = transport
             .listen_on(listen_addr)
@@ -1050,6 +1180,7 @@ where
                 listener: listener.fuse(),
                 transport_reqs_rx,
                 transport_notifs_tx,
+                inbound_admission,
             },
             listen_addr,
         )
@@ -1082,6 +1213,28 @@ where
                                 addr
                             );
 
+                            // Reject the connection before its handshake if it's over the limits,
+                            // dropping it closes the socket
+                            let ip_addr = addr.find_ip_addr();
+                            let admission = ip_addr
+                                .map(|ip_addr| self.inbound_admission.admit(ip_addr));
+                            if let Some(Err(rejection)) = admission {
+                                debug!(
+                                    NetworkSchema::new(&self.network_context)
+                                        .network_address(&addr),
+                                    "{} Inbound connection from {} refused: {}",
+                                    self.network_context,
+                                    addr,
+                                    rejection
+                                );
+                                counters::inbound_connections_refused(
+                                    &self.network_context,
+                                    rejection.as_str(),
+                                )
+                                .inc();
+                                continue;
+                            }
+
                             counters::pending_connection_upgrades(
                                 &self.network_context,
                                 ConnectionOrigin::Inbound,
@@ -1089,7 +1242,7 @@ where
                             .inc();
 
                             let start_time = self.time_service.now();
-                            pending_inbound_connections.push(upgrade.map(move |out| (out, addr, start_time)));
+                            pending_inbound_connections.push(upgrade.map(move |out| (out, addr, ip_addr, start_time)));
                         }
                         Err(e) => {
                             info!(
@@ -1105,8 +1258,8 @@ where
                 (upgrade, addr, peer_id, start_time, response_tx) = pending_outbound_connections.select_next_some() => {
                     self.handle_completed_outbound_upgrade(upgrade, addr, peer_id, start_time, response_tx).await;
                 },
-                (upgrade, addr, start_time) = pending_inbound_connections.select_next_some() => {
-                    self.handle_completed_inbound_upgrade(upgrade, addr, start_time).await;
+                (upgrade, addr, ip_addr, start_time) = pending_inbound_connections.select_next_some() => {
+                    self.handle_completed_inbound_upgrade(upgrade, addr, ip_addr, start_time).await;
                 },
                 complete => break,
             }
@@ -1262,6 +1415,7 @@ where
         &mut self,
         upgrade: Result<Connection<TSocket>, TTransport::Error>,
         addr: NetworkAddress,
+        ip_addr: Option<IpAddr>,
         start_time: Instant,
     ) {
         counters::pending_connection_upgrades(&self.network_context, ConnectionOrigin::Inbound)
@@ -1287,11 +1441,18 @@ where
                 )
                 .observe(elapsed_time);
 
-                // Send the new connection to PeerManager
+                // Send the new connection to PeerManager, which releases it once closed
+                if let Some(ip_addr) = ip_addr {
+                    self.inbound_admission
+                        .established(connection.metadata.connection_id, ip_addr);
+                }
                 let event = TransportNotification::NewConnection(connection);
                 self.transport_notifs_tx.send(event).await.unwrap();
             }
             Err(err) => {
+                if let Some(ip_addr) = ip_addr {
+                    self.inbound_admission.release(ip_addr);
+                }
                 warn!(
                     NetworkSchema::new(&self.network_context)
                         .network_address(&addr),
@@ -0,0 +1,243 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scoring of peers by their misbehaviors.
+//!
+//! Every peer starts with a score of [`MAX_SCORE`], which each misbehavior reported lowers by its
+//! penalty. Lost points come back over time, half of them every `score_half_life_secs`, so that
+//! occasional misbehaviors are forgiven while repeated ones add up. Once the score of a peer falls
+//! under `ban_score`, it may be banned for `ban_duration_secs`, during which PeerManager neither
+//! accepts nor dials connections with it.
+
+use diem_config::config::PeerReputationConfig;
+use diem_time_service::{TimeService, TimeServiceTrait};
+use diem_types::PeerId;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// The score of a peer which hasn't misbehaved lately.
+pub const MAX_SCORE: f64 = 100.0;
+
+/// Peers whose score is at least this close to [`MAX_SCORE`] are forgotten.
+const FORGOTTEN_DEFICIT: f64 = 0.5;
+
+/// Ways a peer can misbehave.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Misbehavior {
+    /// The peer sent something that couldn't be read as a network message.
+    ProtocolViolation,
+    /// The peer sent a message an application failed to verify.
+    InvalidMessage,
+    /// The peer failed too many health checks in a row.
+    HealthCheckFailure,
+}
+
+impl Misbehavior {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Misbehavior::ProtocolViolation => "protocol_violation",
+            Misbehavior::InvalidMessage => "invalid_message",
+            Misbehavior::HealthCheckFailure => "health_check_failure",
+        }
+    }
+
+    /// The points a peer loses for the misbehavior.
+    fn penalty(self) -> f64 {
+        match self {
+            Misbehavior::ProtocolViolation => 20.0,
+            Misbehavior::InvalidMessage => 10.0,
+            Misbehavior::HealthCheckFailure => 5.0,
+        }
+    }
+}
+
+impl fmt::Display for Misbehavior {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+struct PeerScore {
+    score: f64,
+    updated_at: Instant,
+    banned_until: Option<Instant>,
+    misbehaviors: BTreeMap<Misbehavior, u64>,
+}
+
+/// What's known of a peer, as served by the peer monitoring endpoint.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PeerScoreView {
+    pub score: f64,
+    /// Seconds left before the ban of the peer ends, if it's banned.
+    pub ban_remaining_secs: Option<u64>,
+    pub misbehaviors: BTreeMap<Misbehavior, u64>,
+}
+
+pub struct PeerReputation {
+    config: PeerReputationConfig,
+    time_service: TimeService,
+    scores: HashMap<PeerId, PeerScore>,
+}
+
+impl PeerReputation {
+    pub fn new(config: PeerReputationConfig, time_service: TimeService) -> Self {
+        Self {
+            config,
+            time_service,
+            scores: HashMap::new(),
+        }
+    }
+
+    /// Lowers the score of `peer_id` for `misbehavior`. Returns whether the peer should be
+    /// banned, which is when it scores under `ban_score` and isn't banned already.
+    pub fn report(&mut self, peer_id: PeerId, misbehavior: Misbehavior) -> bool {
+        let now = self.time_service.now();
+        let half_life = Duration::from_secs(self.config.score_half_life_secs);
+        self.scores.retain(|_, peer| {
+            peer.is_banned(now) || peer.deficit(now, half_life) > FORGOTTEN_DEFICIT
+        });
+
+        let peer = self.scores.entry(peer_id).or_insert_with(|| PeerScore {
+            score: MAX_SCORE,
+            updated_at: now,
+            banned_until: None,
+            misbehaviors: BTreeMap::new(),
+        });
+        peer.score = (MAX_SCORE - peer.deficit(now, half_life) - misbehavior.penalty()).max(0.0);
+        peer.updated_at = now;
+        *peer.misbehaviors.entry(misbehavior).or_insert(0) += 1;
+
+        self.config.enabled && peer.score < f64::from(self.config.ban_score) && !peer.is_banned(now)
+    }
+
+    /// Bans `peer_id` for `ban_duration_secs` from now.
+    pub fn ban(&mut self, peer_id: PeerId) {
+        let banned_until =
+            self.time_service.now() + Duration::from_secs(self.config.ban_duration_secs);
+        if let Some(peer) = self.scores.get_mut(&peer_id) {
+            peer.banned_until = Some(banned_until);
+        }
+    }
+
+    pub fn is_banned(&self, peer_id: &PeerId) -> bool {
+        let now = self.time_service.now();
+        self.scores
+            .get(peer_id)
+            .map_or(false, |peer| peer.is_banned(now))
+    }
+
+    /// The peers which misbehaved lately.
+    pub fn peers(&self) -> HashMap<PeerId, PeerScoreView> {
+        let now = self.time_service.now();
+        let half_life = Duration::from_secs(self.config.score_half_life_secs);
+        self.scores
+            .iter()
+            .map(|(peer_id, peer)| {
+                let view = PeerScoreView {
+                    score: MAX_SCORE - peer.deficit(now, half_life),
+                    ban_remaining_secs: peer
+                        .banned_until
+                        .filter(|banned_until| *banned_until > now)
+                        .map(|banned_until| (banned_until - now).as_secs()),
+                    misbehaviors: peer.misbehaviors.clone(),
+                };
+                (*peer_id, view)
+            })
+            .collect()
+    }
+}
+
+impl PeerScore {
+    /// The points the peer is missing at `now`, having recovered some since its last update.
+    fn deficit(&self, now: Instant, half_life: Duration) -> f64 {
+        let deficit = MAX_SCORE - self.score;
+        if half_life == Duration::from_secs(0) {
+            return 0.0;
+        }
+        let elapsed = now.saturating_duration_since(self.updated_at);
+        deficit * 0.5f64.powf(elapsed.as_secs_f64() / half_life.as_secs_f64())
+    }
+
+    fn is_banned(&self, now: Instant) -> bool {
+        self.banned_until
+            .map_or(false, |banned_until| banned_until > now)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn reputation() -> (PeerReputation, diem_time_service::MockTimeService) {
+        let time_service = TimeService::mock();
+        let config = PeerReputationConfig {
+            ban_score: 50,
+            ban_duration_secs: 60,
+            score_half_life_secs: 10,
+            enabled: true,
+        };
+        (
+            PeerReputation::new(config, time_service.clone()),
+            time_service.into_mock(),
+        )
+    }
+
+    #[test]
+    fn test_report_and_ban() {
+        let (mut reputation, mock_time) = reputation();
+        let peer_id = PeerId::random();
+
+        assert!(!reputation.report(peer_id, Misbehavior::ProtocolViolation));
+        assert!(!reputation.report(peer_id, Misbehavior::ProtocolViolation));
+        assert_eq!(reputation.peers()[&peer_id].score, 60.0);
+        assert!(!reputation.report(peer_id, Misbehavior::HealthCheckFailure));
+        // The third violation brings the peer under the ban score.
+        assert!(reputation.report(peer_id, Misbehavior::ProtocolViolation));
+        reputation.ban(peer_id);
+        assert!(reputation.is_banned(&peer_id));
+        // A banned peer isn't banned again.
+        assert!(!reputation.report(peer_id, Misbehavior::InvalidMessage));
+        assert_eq!(reputation.peers()[&peer_id].ban_remaining_secs, Some(60));
+        assert!(!reputation.is_banned(&PeerId::random()));
+
+        mock_time.advance_secs(60);
+        assert!(!reputation.is_banned(&peer_id));
+        let view = &reputation.peers()[&peer_id];
+        assert_eq!(view.ban_remaining_secs, None);
+        assert_eq!(view.misbehaviors[&Misbehavior::ProtocolViolation], 3);
+        assert_eq!(view.misbehaviors[&Misbehavior::InvalidMessage], 1);
+    }
+
+    #[test]
+    fn test_score_recovery() {
+        let (mut reputation, mock_time) = reputation();
+        let peer_id = PeerId::random();
+
+        reputation.report(peer_id, Misbehavior::ProtocolViolation);
+        mock_time.advance_secs(10);
+        assert_eq!(reputation.peers()[&peer_id].score, 90.0);
+        mock_time.advance_secs(10);
+        assert_eq!(reputation.peers()[&peer_id].score, 95.0);
+
+        // Peers which recovered are forgotten once another peer is reported.
+        mock_time.advance_secs(100);
+        reputation.report(PeerId::random(), Misbehavior::InvalidMessage);
+        assert!(!reputation.peers().contains_key(&peer_id));
+    }
+
+    #[test]
+    fn test_bans_disabled() {
+        let (mut reputation, _) = reputation();
+        reputation.config.enabled = false;
+        let peer_id = PeerId::random();
+
+        for _ in 0..5 {
+            assert!(!reputation.report(peer_id, Misbehavior::ProtocolViolation));
+        }
+        assert_eq!(reputation.peers()[&peer_id].score, 0.0);
+    }
+}
@@ -5,8 +5,13 @@ use crate::{
     constants,
     peer::DisconnectReason,
     peer_manager::{
-        conn_notifs_channel, error::PeerManagerError, ConnectionNotification, ConnectionRequest,
-        PeerManager, PeerManagerNotification, PeerManagerRequest, TransportNotification,
+        admission::InboundAdmission,
+        bandwidth::BandwidthLimiters,
+        conn_notifs_channel,
+        error::PeerManagerError,
+        reputation::{Misbehavior, PeerReputation},
+        ConnectionNotification, ConnectionRequest, PeerManager, PeerManagerNotification,
+        PeerManagerRequest, TransportNotification,
     },
     protocols::wire::{
         handshake::v1::MessagingProtocolVersion,
@@ -20,7 +25,10 @@ use anyhow::anyhow;
 use bytes::Bytes;
 use channel::{diem_channel, message_queues::QueueStyle};
 use diem_config::{
-    config::{PeerRole, MAX_INBOUND_CONNECTIONS},
+    config::{
+        CompressionConfig, InboundAdmissionConfig, OutboundQosConfig, Peer, PeerReputationConfig,
+        PeerRole, MAX_INBOUND_CONNECTIONS,
+    },
     network_id::NetworkContext,
 };
 use diem_infallible::RwLock;
@@ -104,6 +112,10 @@ fn build_test_peer_manager(
         NetworkContext::mock_with_peer_id(peer_id),
         "/memory/0".parse().unwrap(),
         Arc::new(RwLock::new(HashMap::new())),
+        Arc::new(RwLock::new(PeerReputation::new(
+            PeerReputationConfig::default(),
+            TimeService::mock(),
+        ))),
         peer_manager_request_rx,
         connection_reqs_rx,
         [(TEST_PROTOCOL, hello_tx)].iter().cloned().collect(),
@@ -114,6 +126,13 @@ fn build_test_peer_manager(
         MAX_INBOUND_CONNECTIONS,
         TokenBucketRateLimiter::open("inbound"),
         TokenBucketRateLimiter::open("outbound"),
+        BandwidthLimiters::open(),
+        OutboundQosConfig::default(),
+        CompressionConfig::default(),
+        Arc::new(InboundAdmission::new(
+            InboundAdmissionConfig::default(),
+            TimeService::mock(),
+        )),
     );
 
     (
@@ -155,7 +174,12 @@ async fn assert_peer_disconnected_event(
         MemorySocket,
     >,
 ) {
-    let connection_event = peer_manager.transport_notifs_rx.select_next_some().await;
+    let mut connection_event = peer_manager.transport_notifs_rx.select_next_some().await;
+    // Skip the misbehaviors reported for the garbage frames `ping_pong` sends.
+    while let TransportNotification::Misbehaved(_, _) = connection_event {
+        peer_manager.handle_connection_event(connection_event);
+        connection_event = peer_manager.transport_notifs_rx.select_next_some().await;
+    }
     match &connection_event {
         TransportNotification::Disconnected(ref actual_metadata, ref actual_reason) => {
             assert_eq!(actual_metadata.remote_peer_id, peer_id);
@@ -638,3 +662,92 @@ fn test_dial_disconnect() {
 
     runtime.block_on(test);
 }
+
+#[test]
+fn test_ban_misbehaving_peer() {
+    ::diem_logger::Logger::init_for_testing();
+    let runtime = ::tokio::runtime::Runtime::new().unwrap();
+
+    let ids = ordered_peer_ids(3);
+    let (mut peer_manager, _request_tx, _connection_reqs_tx, _hello_rx, mut conn_status_rx) =
+        build_test_peer_manager(runtime.handle().clone(), ids[2]);
+    let trusted_peer = Peer::new(vec![], Default::default(), PeerRole::Validator);
+    peer_manager
+        .trusted_peers
+        .write()
+        .insert(ids[1], trusted_peer);
+
+    let test = async move {
+        let (outbound, _inbound) = build_test_connection();
+        peer_manager.add_peer(create_connection(
+            outbound,
+            ids[0],
+            NetworkAddress::mock(),
+            ConnectionOrigin::Outbound,
+            ConnectionId::from(0),
+        ));
+        let conn_notif = conn_status_rx.next().await.unwrap();
+        assert!(matches!(conn_notif, ConnectionNotification::NewPeer(_, _)));
+
+        // The peer is banned and disconnected once it scores under the ban score.
+        for _ in 0..4 {
+            peer_manager
+                .handle_connection_request(ConnectionRequest::ReportPeer(
+                    ids[0],
+                    Misbehavior::ProtocolViolation,
+                ))
+                .await;
+            assert!(peer_manager.active_peers.contains_key(&ids[0]));
+        }
+        peer_manager
+            .handle_connection_request(ConnectionRequest::ReportPeer(
+                ids[0],
+                Misbehavior::ProtocolViolation,
+            ))
+            .await;
+        assert!(!peer_manager.active_peers.contains_key(&ids[0]));
+        assert!(peer_manager.peer_reputation.read().is_banned(&ids[0]));
+
+        // Connections with the banned peer are rejected.
+        let (dial_resp_tx, dial_resp_rx) = oneshot::channel();
+        peer_manager
+            .handle_connection_request(ConnectionRequest::DialPeer(
+                ids[0],
+                NetworkAddress::mock(),
+                dial_resp_tx,
+            ))
+            .await;
+        assert!(matches!(
+            dial_resp_rx.await.unwrap(),
+            Err(PeerManagerError::Banned(_))
+        ));
+        let (inbound, _outbound) = build_test_connection();
+        peer_manager.handle_connection_event(TransportNotification::NewConnection(
+            create_connection(
+                inbound,
+                ids[0],
+                NetworkAddress::mock(),
+                ConnectionOrigin::Inbound,
+                ConnectionId::from(1),
+            ),
+        ));
+        assert!(!peer_manager.active_peers.contains_key(&ids[0]));
+
+        // Trusted peers are scored, but never banned.
+        for _ in 0..5 {
+            peer_manager
+                .handle_connection_request(ConnectionRequest::ReportPeer(
+                    ids[1],
+                    Misbehavior::ProtocolViolation,
+                ))
+                .await;
+        }
+        assert!(!peer_manager.peer_reputation.read().is_banned(&ids[1]));
+        assert_eq!(
+            peer_manager.peer_reputation.read().peers()[&ids[1]].score,
+            0.0
+        );
+    };
+
+    runtime.block_on(test);
+}
@@ -8,8 +8,8 @@
 //! healthy peer is expected to respond with a corresponding Pong message.
 //!
 //! If a certain number of successive liveness probes for a peer fail, the HealthChecker initiates a
-//! disconnect from the peer. It relies on ConnectivityManager or the remote peer to re-establish
-//! the connection.
+//! disconnect from the peer, and reports it to PeerManager, which may ban the peer if it keeps
+//! failing. It relies on ConnectivityManager or the remote peer to re-establish the connection.
 //!
 //! Future Work
 //! -----------
@@ -22,7 +22,7 @@ use crate::{
     counters,
     error::NetworkError,
     logging::NetworkSchema,
-    peer_manager::{ConnectionRequestSender, PeerManagerRequestSender},
+    peer_manager::{reputation::Misbehavior, ConnectionRequestSender, PeerManagerRequestSender},
     protocols::{
         network::{Event, NetworkEvents, NetworkSender, NewNetworkSender},
         rpc::error::RpcError,
@@ -119,6 +119,14 @@ impl HealthCheckerNetworkSender {
     pub async fn disconnect_peer(&mut self, peer_id: PeerId) -> Result<(), NetworkError> {
         self.inner.disconnect_peer(peer_id).await
     }
+
+    pub fn report_peer(
+        &mut self,
+        peer_id: PeerId,
+        misbehavior: Misbehavior,
+    ) -> Result<(), NetworkError> {
+        self.inner.report_peer(peer_id, misbehavior)
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -378,7 +386,8 @@ impl HealthChecker {
                             return;
                         }
                         // Increment num of failures. If the ping failures are now more than
-                        // `self.ping_failures_tolerated`, we disconnect from the node.
+                        // `self.ping_failures_tolerated`, we disconnect from the node, and
+                        // report it to lower its reputation.
                         // The HealthChecker only performs the disconnect. It relies on
                         // ConnectivityManager or the remote peer to re-establish the connection.
                         *failures += 1;
@@ -400,6 +409,20 @@ impl HealthChecker {
                                     err
                                 );
                             }
+                            if let Err(err) = self
+                                .network_tx
+                                .report_peer(peer_id, Misbehavior::HealthCheckFailure)
+                            {
+                                warn!(
+                                    NetworkSchema::new(&self.network_context)
+                                        .remote_peer(&peer_id),
+                                    error = ?err,
+                                    "{} Failed to report peer: {} with error: {:?}",
+                                    self.network_context,
+                                    peer_id.short_str(),
+                                    err
+                                );
+                            }
                         }
                     }
                 }
@@ -141,6 +141,15 @@ impl TestHarness {
         };
         assert_eq!(peer_id, expected_peer_id);
         res_tx.send(Ok(())).unwrap();
+
+        // The peer is reported once disconnected.
+        let req = self.connection_reqs_rx.next().await.unwrap();
+        match req {
+            ConnectionRequest::ReportPeer(peer_id, Misbehavior::HealthCheckFailure) => {
+                assert_eq!(peer_id, expected_peer_id)
+            }
+            _ => panic!("Unexpected ConnectionRequest: {:?}", req),
+        }
     }
 
     async fn send_new_peer_notification(&mut self, peer_id: PeerId) {
@@ -7,8 +7,8 @@ pub use crate::protocols::rpc::error::RpcError;
 use crate::{
     error::NetworkError,
     peer_manager::{
-        ConnectionNotification, ConnectionRequestSender, PeerManagerNotification,
-        PeerManagerRequestSender,
+        reputation::Misbehavior, ConnectionNotification, ConnectionRequestSender,
+        PeerManagerNotification, PeerManagerRequestSender,
     },
     transport::ConnectionMetadata,
     ProtocolId,
@@ -244,6 +244,17 @@ impl<TMessage> NetworkSender<TMessage> {
         self.connection_reqs_tx.disconnect_peer(peer).await?;
         Ok(())
     }
+
+    /// Report a misbehavior of a given Peer, lowering its reputation. Returns once the report
+    /// is enqueued, without waiting for it to be handled.
+    pub fn report_peer(
+        &mut self,
+        peer: PeerId,
+        misbehavior: Misbehavior,
+    ) -> Result<(), NetworkError> {
+        self.connection_reqs_tx.report_peer(peer, misbehavior)?;
+        Ok(())
+    }
 }
 
 impl<TMessage: Message> NetworkSender<TMessage> {
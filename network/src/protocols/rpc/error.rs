@@ -3,7 +3,7 @@
 
 //! Rpc protocol errors
 
-use crate::peer_manager::PeerManagerError;
+use crate::{peer_manager::PeerManagerError, ProtocolId};
 use anyhow::anyhow;
 use diem_types::PeerId;
 use futures::channel::{mpsc, oneshot};
@@ -47,6 +47,9 @@ pub enum RpcError {
 
     #[error("Rpc timed out")]
     TimedOut,
+
+    #[error("Rate limited on protocol {0}")]
+    RateLimited(ProtocolId),
 }
 
 impl From<PeerManagerError> for RpcError {
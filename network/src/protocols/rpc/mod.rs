@@ -49,8 +49,7 @@ use crate::{
         RESPONSE_LABEL, SENT_LABEL,
     },
     logging::NetworkSchema,
-    peer::PeerNotification,
-    peer_manager::PeerManagerError,
+    peer::{qos::WriteQueueSender, PeerNotification, WriteRequest},
     protocols::wire::messaging::v1::{
         NetworkMessage, Priority, RequestId, RpcRequest, RpcResponse,
     },
@@ -67,7 +66,6 @@ use error::RpcError;
 use futures::{
     channel::oneshot,
     future::{BoxFuture, FusedFuture, Future, FutureExt},
-    sink::SinkExt,
     stream::{FuturesUnordered, StreamExt},
 };
 use serde::Serialize;
@@ -170,7 +168,8 @@ pub struct InboundRpcs {
     remote_peer_id: PeerId,
     /// The core async queue of pending inbound rpc tasks. The tasks are driven
     /// to completion by the `InboundRpcs::next_completed_response()` method.
-    inbound_rpc_tasks: FuturesUnordered<BoxFuture<'static, Result<RpcResponse, RpcError>>>,
+    inbound_rpc_tasks:
+        FuturesUnordered<BoxFuture<'static, Result<(ProtocolId, RpcResponse), RpcError>>>,
     /// A blanket timeout on all inbound rpc requests. If the application handler
     /// doesn't respond to the request before this timeout, the request will be
     /// dropped.
@@ -230,6 +229,10 @@ impl InboundRpcs {
         // Collect counters for received request.
         counters::rpc_messages(network_context, REQUEST_LABEL, RECEIVED_LABEL).inc();
         counters::rpc_bytes(network_context, REQUEST_LABEL, RECEIVED_LABEL).inc_by(req_len);
+        counters::protocol_messages(network_context, protocol_id, REQUEST_LABEL, RECEIVED_LABEL)
+            .inc();
+        counters::protocol_bytes(network_context, protocol_id, REQUEST_LABEL, RECEIVED_LABEL)
+            .inc_by(req_len);
         let timer =
             counters::inbound_rpc_handler_latency(network_context, protocol_id).start_timer();
 
@@ -252,11 +255,14 @@ impl InboundRpcs {
             .map(move |result| {
                 // Flatten the errors
                 let maybe_response = match result {
-                    Ok(Ok(Ok(response_bytes))) => Ok(RpcResponse {
-                        request_id,
-                        priority,
-                        raw_response: Vec::from(response_bytes.as_ref()),
-                    }),
+                    Ok(Ok(Ok(response_bytes))) => {
+                        let response = RpcResponse {
+                            request_id,
+                            priority,
+                            raw_response: Vec::from(response_bytes.as_ref()),
+                        };
+                        Ok((protocol_id, response))
+                    }
                     Ok(Ok(Err(err))) => Err(err),
                     Ok(Err(oneshot::Canceled)) => Err(RpcError::UnexpectedResponseChannelCancel),
                     Err(timeout::Elapsed) => Err(RpcError::TimedOut),
@@ -282,7 +288,7 @@ impl InboundRpcs {
     /// `futures::select!`.
     pub fn next_completed_response(
         &mut self,
-    ) -> impl Future<Output = Result<RpcResponse, RpcError>> + FusedFuture + '_ {
+    ) -> impl Future<Output = Result<(ProtocolId, RpcResponse), RpcError>> + FusedFuture + '_ {
         self.inbound_rpc_tasks.select_next_some()
     }
 
@@ -291,14 +297,11 @@ impl InboundRpcs {
     /// the outbound write queue.
     pub async fn send_outbound_response(
         &mut self,
-        write_reqs_tx: &mut channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
-        maybe_response: Result<RpcResponse, RpcError>,
+        write_reqs_tx: &mut WriteQueueSender,
+        maybe_response: Result<(ProtocolId, RpcResponse), RpcError>,
     ) -> Result<(), RpcError> {
         let network_context = &self.network_context;
-        let response = match maybe_response {
+        let (protocol_id, response) = match maybe_response {
             Ok(response) => response,
             Err(err) => {
                 counters::rpc_messages(network_context, RESPONSE_LABEL, FAILED_LABEL).inc();
@@ -317,11 +320,21 @@ impl InboundRpcs {
         );
         let message = NetworkMessage::RpcResponse(response);
         let (ack_tx, _) = oneshot::channel();
-        write_reqs_tx.send((message, ack_tx)).await?;
+        write_reqs_tx
+            .send(WriteRequest {
+                message,
+                protocol_id: Some(protocol_id),
+                queued_at: self.time_service.now(),
+                ack_tx,
+            })
+            .await?;
 
         // Collect counters for sent response.
         counters::rpc_messages(network_context, RESPONSE_LABEL, SENT_LABEL).inc();
         counters::rpc_bytes(network_context, RESPONSE_LABEL, SENT_LABEL).inc_by(res_len);
+        counters::protocol_messages(network_context, protocol_id, RESPONSE_LABEL, SENT_LABEL).inc();
+        counters::protocol_bytes(network_context, protocol_id, RESPONSE_LABEL, SENT_LABEL)
+            .inc_by(res_len);
         Ok(())
     }
 }
@@ -378,10 +391,7 @@ impl OutboundRpcs {
     pub async fn handle_outbound_request(
         &mut self,
         request: OutboundRpcRequest,
-        write_reqs_tx: &mut channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
+        write_reqs_tx: &mut WriteQueueSender,
     ) -> Result<(), RpcError> {
         let network_context = &self.network_context;
         let peer_id = &self.remote_peer_id;
@@ -433,11 +443,21 @@ impl OutboundRpcs {
             raw_request: Vec::from(request_data.as_ref()),
         });
         let (ack_tx, _) = oneshot::channel();
-        write_reqs_tx.send((message, ack_tx)).await?;
+        write_reqs_tx
+            .send(WriteRequest {
+                message,
+                protocol_id: Some(protocol_id),
+                queued_at: self.time_service.now(),
+                ack_tx,
+            })
+            .await?;
 
         // Collect counters for requests sent.
         counters::rpc_messages(network_context, REQUEST_LABEL, SENT_LABEL).inc();
         counters::rpc_bytes(network_context, REQUEST_LABEL, SENT_LABEL).inc_by(req_len);
+        counters::protocol_messages(network_context, protocol_id, REQUEST_LABEL, SENT_LABEL).inc();
+        counters::protocol_bytes(network_context, protocol_id, REQUEST_LABEL, SENT_LABEL)
+            .inc_by(req_len);
 
         // Create channel over which response is delivered to outbound_rpc_task.
         let (response_tx, response_rx) = oneshot::channel::<RpcResponse>();
@@ -485,12 +505,27 @@ impl OutboundRpcs {
             }
         };
 
+        let network_context = network_context.clone();
         let outbound_rpc_task = async move {
             // Always return the request_id so we can garbage collect the
             // pending_outbound_rpcs map.
             match notify_application.await {
                 Ok(response_len) => {
                     let latency = timer.stop_and_record();
+                    counters::protocol_messages(
+                        &network_context,
+                        protocol_id,
+                        RESPONSE_LABEL,
+                        RECEIVED_LABEL,
+                    )
+                    .inc();
+                    counters::protocol_bytes(
+                        &network_context,
+                        protocol_id,
+                        RESPONSE_LABEL,
+                        RECEIVED_LABEL,
+                    )
+                    .inc_by(response_len);
                     (request_id, Ok((latency, response_len)))
                 }
                 Err(err) => {
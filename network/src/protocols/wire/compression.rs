@@ -0,0 +1,108 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The algorithms messages can be compressed with over-the-wire.
+//!
+//! A node advertises the algorithms it has enabled as capability bits of its
+//! [`SupportedProtocols`](crate::protocols::wire::handshake::v1::SupportedProtocols), past those of
+//! the application protocols. Nodes which don't know about compression drop these bits when
+//! intersecting their protocols with ours, so the handshake with them is unchanged, and they are
+//! never sent a [`CompressedMsg`](crate::protocols::wire::messaging::v1::CompressedMsg).
+
+use anyhow::{ensure, Result};
+use diem_config::config;
+#[cfg(any(test, feature = "fuzzing"))]
+use proptest_derive::Arbitrary;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Compression level of zstd, its default, which compresses about as fast as the network sends.
+const ZSTD_LEVEL: i32 = 3;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub enum CompressionAlgorithm {
+    Lz4 = 0,
+    Zstd = 1,
+}
+
+impl CompressionAlgorithm {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CompressionAlgorithm::Lz4 => "lz4",
+            CompressionAlgorithm::Zstd => "zstd",
+        }
+    }
+
+    pub fn all() -> &'static [CompressionAlgorithm] {
+        &[CompressionAlgorithm::Lz4, CompressionAlgorithm::Zstd]
+    }
+
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(match self {
+            CompressionAlgorithm::Lz4 => lz4_flex::block::compress(data),
+            CompressionAlgorithm::Zstd => zstd::bulk::compress(data, ZSTD_LEVEL)?,
+        })
+    }
+
+    /// Decompresses `data`, which must decompress to exactly `len` bytes. No more than `len` bytes
+    /// are ever allocated, so that a peer can't exhaust our memory with a small message.
+    pub fn decompress(self, data: &[u8], len: usize) -> Result<Vec<u8>> {
+        let decompressed = match self {
+            CompressionAlgorithm::Lz4 => lz4_flex::block::decompress(data, len)?,
+            CompressionAlgorithm::Zstd => zstd::bulk::decompress(data, len)?,
+        };
+        ensure!(
+            decompressed.len() == len,
+            "{} data decompressed to {} bytes, expected {}",
+            self,
+            decompressed.len(),
+            len
+        );
+        Ok(decompressed)
+    }
+}
+
+impl From<config::CompressionAlgorithm> for CompressionAlgorithm {
+    fn from(algorithm: config::CompressionAlgorithm) -> Self {
+        match algorithm {
+            config::CompressionAlgorithm::Lz4 => CompressionAlgorithm::Lz4,
+            config::CompressionAlgorithm::Zstd => CompressionAlgorithm::Zstd,
+        }
+    }
+}
+
+impl fmt::Debug for CompressionAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl fmt::Display for CompressionAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let data = b"state sync chunk ".repeat(1000);
+        for algorithm in CompressionAlgorithm::all() {
+            let compressed = algorithm.compress(&data).unwrap();
+            assert!(compressed.len() < data.len() / 10);
+            assert_eq!(algorithm.decompress(&compressed, data.len()).unwrap(), data);
+            // Data decompressing to more or less than announced is rejected.
+            algorithm
+                .decompress(&compressed, data.len() - 1)
+                .unwrap_err();
+            algorithm
+                .decompress(&compressed, data.len() + 1)
+                .unwrap_err();
+        }
+    }
+}
@@ -12,6 +12,7 @@
 //!
 //! [DiemNet Handshake v1 Specification]: https://github.com/diem/diem/blob/main/specifications/network/handshake-v1.md
 
+use crate::protocols::wire::compression::CompressionAlgorithm;
 use diem_config::network_id::NetworkId;
 use diem_types::chain_id::ChainId;
 use serde::{Deserialize, Serialize};
@@ -82,7 +83,12 @@ impl fmt::Display for ProtocolId {
 // SupportedProtocols
 //
 
-/// A bit vector of supported [`ProtocolId`]s.
+/// Position of the first capability bit of the [`CompressionAlgorithm`]s in [`SupportedProtocols`],
+/// far enough from those of the [`ProtocolId`]s for them to never meet.
+const COMPRESSION_BITS_START: u8 = 248;
+
+/// A bit vector of supported [`ProtocolId`]s, followed by capability bits of the supported
+/// [`CompressionAlgorithm`]s.
 #[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
 pub struct SupportedProtocols(bitvec::BitVec);
@@ -93,7 +99,7 @@ impl TryInto<Vec<ProtocolId>> for SupportedProtocols {
     fn try_into(self) -> bcs::Result<Vec<ProtocolId>> {
         let mut protocols = Vec::with_capacity(self.0.count_ones() as usize);
         if let Some(last_bit) = self.0.last_set_bit() {
-            for i in 0..=last_bit {
+            for i in 0..=last_bit.min(COMPRESSION_BITS_START - 1) {
                 if self.0.is_set(i) {
                     let protocol: ProtocolId = bcs::from_bytes(&[i])?;
                     protocols.push(protocol);
@@ -113,6 +119,18 @@ impl<'a, T: Iterator<Item = &'a ProtocolId>> From<T> for SupportedProtocols {
 }
 
 impl SupportedProtocols {
+    /// Advertises support for compressing messages with each of `algorithms`.
+    pub fn with_compression(mut self, algorithms: &[CompressionAlgorithm]) -> Self {
+        for algorithm in algorithms {
+            self.0.set(COMPRESSION_BITS_START + *algorithm as u8);
+        }
+        self
+    }
+
+    pub fn supports_compression(&self, algorithm: CompressionAlgorithm) -> bool {
+        self.0.is_set(COMPRESSION_BITS_START + algorithm as u8)
+    }
+
     /// Returns a new SupportedProtocols struct that is an intersection.
     fn intersection(self, other: SupportedProtocols) -> SupportedProtocols {
         SupportedProtocols(self.0 & other.0)
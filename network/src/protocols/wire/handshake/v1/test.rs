@@ -115,3 +115,49 @@ fn common_protocols() {
         h1.perform_handshake(&h2).unwrap()
     );
 }
+
+#[test]
+fn compression_capabilities() {
+    let protocols: SupportedProtocols = [ProtocolId::ConsensusRpc, ProtocolId::StateSyncDirectSend]
+        .iter()
+        .into();
+    let handshake_msg = |protocols: SupportedProtocols| {
+        let mut supported_protocols = BTreeMap::new();
+        supported_protocols.insert(MessagingProtocolVersion::V1, protocols);
+        HandshakeMsg {
+            supported_protocols,
+            chain_id: ChainId::default(),
+            network_id: NetworkId::default(),
+        }
+    };
+    let h1 = handshake_msg(
+        protocols
+            .clone()
+            .with_compression(&[CompressionAlgorithm::Zstd, CompressionAlgorithm::Lz4]),
+    );
+    let h2 = handshake_msg(
+        protocols
+            .clone()
+            .with_compression(&[CompressionAlgorithm::Zstd]),
+    );
+    let h3 = handshake_msg(protocols);
+
+    // Only the algorithms both ends support are negotiated.
+    let (_, common) = h1.perform_handshake(&h2).unwrap();
+    assert!(common.supports_compression(CompressionAlgorithm::Zstd));
+    assert!(!common.supports_compression(CompressionAlgorithm::Lz4));
+
+    // Capability bits aren't mistaken for protocols, and are dropped by an end without them.
+    let (_, common) = h1.perform_handshake(&h3).unwrap();
+    assert!(!common.supports_compression(CompressionAlgorithm::Zstd));
+    let (_, common) = h3.perform_handshake(&h1).unwrap();
+    assert_eq!(
+        (common.try_into() as Result<Vec<ProtocolId>, _>).unwrap(),
+        vec![ProtocolId::ConsensusRpc, ProtocolId::StateSyncDirectSend]
+    );
+    let (_, common) = h1.perform_handshake(&h1).unwrap();
+    assert_eq!(
+        (common.try_into() as Result<Vec<ProtocolId>, _>).unwrap(),
+        vec![ProtocolId::ConsensusRpc, ProtocolId::StateSyncDirectSend]
+    );
+}
@@ -9,7 +9,7 @@
 //! describes in greater detail how these messages are sent and received
 //! over-the-wire.
 
-use crate::protocols::wire::handshake::v1::ProtocolId;
+use crate::protocols::wire::{compression::CompressionAlgorithm, handshake::v1::ProtocolId};
 use bytes::Bytes;
 use diem_rate_limiter::{async_lib::AsyncRateLimiter, rate_limit::SharedBucket};
 use futures::{
@@ -43,6 +43,8 @@ pub enum NetworkMessage {
     RpcRequest(RpcRequest),
     RpcResponse(RpcResponse),
     DirectSendMsg(DirectSendMsg),
+    /// Only sent to peers which negotiated its algorithm in the handshake.
+    CompressedMsg(CompressedMsg),
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
@@ -123,6 +125,19 @@ pub struct DirectSendMsg {
     pub raw_msg: Vec<u8>,
 }
 
+/// Another [`NetworkMessage`], serialized then compressed.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub struct CompressedMsg {
+    /// The algorithm the message is compressed with.
+    pub algorithm: CompressionAlgorithm,
+    /// Length of the serialized message before compression.
+    pub uncompressed_len: u32,
+    /// Compressed message.
+    #[serde(with = "serde_bytes")]
+    pub raw_msg: Vec<u8>,
+}
+
 /// Errors from reading and deserializing network messages off the wire.
 #[derive(Debug, Error)]
 pub enum ReadError {
@@ -18,7 +18,7 @@ use diem_logger::prelude::*;
 use diem_time_service::{timeout, TimeService, TimeServiceTrait};
 use diem_types::{
     chain_id::ChainId,
-    network_address::{parse_dns_tcp, parse_ip_tcp, parse_memory, NetworkAddress},
+    network_address::{parse_dns_tcp, parse_ip_tcp, parse_memory, IpPreference, NetworkAddress},
     PeerId,
 };
 use futures::{
@@ -60,6 +60,8 @@ pub const DIEM_TCP_TRANSPORT: tcp::TcpTransport = tcp::TcpTransport {
     ttl: None,
     // Use TCP_NODELAY for diem tcp connections.
     nodelay: Some(true),
+    // Dial addresses in the order they resolve to, unless configured otherwise.
+    ip_preference: IpPreference::Any,
 };
 
 /// A trait alias for "socket-like" things.
@@ -11,8 +11,9 @@ use crate::{
     error::WaitForTransactionError,
     move_deserialize::{self, Event},
     views::{
-        AccountStateWithProofView, AccountView, CurrencyInfoView, EventView, 
-        EventWithProofView, MetadataView, StateProofView, TransactionView, 
+        AccountEntryView, AccountStateWithProofView, AccountView, ApiVersionsView, BlockMetadataView,
+        CurrencyInfoView, EventView, EventWithProofView, MetadataView, MoveResourceView,
+        PendingTransactionView, RemovedTransactionView, StateProofView, TransactionView,
         TransactionsWithProofsView, TowerStateResourceView, OracleUpgradeStateView,
     },
     Error, Result, Retry, State,
@@ -134,6 +135,18 @@ impl BlockingClient {
         self.send_without_retry(&request, true)
     }
 
+    /// Submits `txn` and waits for it to commit, for up to `timeout` if given and at most as long
+    /// as the server allows. Returns `None` if the transaction is still pending by then.
+    pub fn submit_and_wait(
+        &self,
+        txn: &SignedTransaction,
+        timeout: Option<Duration>,
+    ) -> Result<Response<Option<TransactionView>>> {
+        let timeout_ms = timeout.map(|timeout| timeout.as_millis() as u64);
+        let request = MethodRequest::submit_and_wait(txn, timeout_ms).map_err(Error::request)?;
+        self.send_without_retry(&JsonRpcRequest::new(request), true)
+    }
+
     pub fn get_metadata_by_version(&self, version: u64) -> Result<Response<MetadataView>> {
         self.send(MethodRequest::get_metadata_by_version(version))
     }
@@ -154,6 +167,40 @@ impl BlockingClient {
         self.send(MethodRequest::get_account_by_version(address, version))
     }
 
+    pub fn get_accounts(
+        &self,
+        addresses: Vec<AccountAddress>,
+    ) -> Result<Response<Vec<AccountEntryView>>> {
+        self.send(MethodRequest::get_accounts(addresses))
+    }
+
+    pub fn get_accounts_by_version(
+        &self,
+        addresses: Vec<AccountAddress>,
+        version: u64,
+    ) -> Result<Response<Vec<AccountEntryView>>> {
+        self.send(MethodRequest::get_accounts_by_version(addresses, version))
+    }
+
+    pub fn get_resource(
+        &self,
+        address: AccountAddress,
+        struct_tag: &str,
+    ) -> Result<Response<Option<MoveResourceView>>> {
+        self.send(MethodRequest::get_resource(address, struct_tag))
+    }
+
+    pub fn get_resource_by_version(
+        &self,
+        address: AccountAddress,
+        struct_tag: &str,
+        version: u64,
+    ) -> Result<Response<Option<MoveResourceView>>> {
+        self.send(MethodRequest::get_resource_by_version(
+            address, struct_tag, version,
+        ))
+    }
+
     /////// 0L /////////
     pub fn get_miner_state(&self, address: AccountAddress) 
     -> Result<Response<Option<TowerStateResourceView>>> {
@@ -222,6 +269,49 @@ impl BlockingClient {
         self.send(MethodRequest::get_events(key, start_seq, limit))
     }
 
+    pub fn get_events_by_type(
+        &self,
+        type_tag: &str,
+        start_version: u64,
+        limit: u64,
+    ) -> Result<Response<Vec<EventView>>> {
+        self.send(MethodRequest::get_events_by_type(
+            type_tag,
+            start_version,
+            limit,
+        ))
+    }
+
+    pub fn get_events_by_account(
+        &self,
+        account: AccountAddress,
+        start_version: u64,
+        limit: u64,
+    ) -> Result<Response<Vec<EventView>>> {
+        self.send(MethodRequest::get_events_by_account(
+            account,
+            start_version,
+            limit,
+        ))
+    }
+
+    pub fn get_events_by_currency(
+        &self,
+        currency: &str,
+        start_version: u64,
+        limit: u64,
+    ) -> Result<Response<Vec<EventView>>> {
+        self.send(MethodRequest::get_events_by_currency(
+            currency,
+            start_version,
+            limit,
+        ))
+    }
+
+    pub fn get_block_metadata(&self, version: u64) -> Result<Response<Option<BlockMetadataView>>> {
+        self.send(MethodRequest::get_block_metadata(version))
+    }
+
     pub fn get_currencies(&self) -> Result<Response<Vec<CurrencyInfoView>>> {
         self.send(MethodRequest::get_currencies())
     }
@@ -230,6 +320,28 @@ impl BlockingClient {
         self.send(MethodRequest::get_network_status())
     }
 
+    pub fn get_pending_transactions(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<Vec<PendingTransactionView>>> {
+        self.send(MethodRequest::get_pending_transactions(address))
+    }
+
+    pub fn get_removed_transaction(
+        &self,
+        address: AccountAddress,
+        sequence_number: u64,
+    ) -> Result<Response<Option<RemovedTransactionView>>> {
+        self.send(MethodRequest::get_removed_transaction(
+            address,
+            sequence_number,
+        ))
+    }
+
+    pub fn get_api_versions(&self) -> Result<Response<ApiVersionsView>> {
+        self.send(MethodRequest::get_api_versions())
+    }
+
     //
     // Experimental APIs
     //
@@ -264,6 +376,21 @@ impl BlockingClient {
         ))
     }
 
+    pub fn get_transactions_with_proofs_by_version(
+        &self,
+        start_version: u64,
+        limit: u64,
+        include_events: bool,
+        ledger_version: u64,
+    ) -> Result<Response<Option<TransactionsWithProofsView>>> {
+        self.send(MethodRequest::get_transactions_with_proofs_by_version(
+            start_version,
+            limit,
+            include_events,
+            ledger_version,
+        ))
+    }
+
     pub fn get_events_with_proofs(
         &self,
         key: EventKey,
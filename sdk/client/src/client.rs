@@ -11,8 +11,10 @@ use crate::{
     error::WaitForTransactionError,
     move_deserialize::{self, Event},
     views::{
-        AccountStateWithProofView, AccountView, CurrencyInfoView, EventView, EventWithProofView,
-        MetadataView, StateProofView, TransactionView, TransactionsWithProofsView,
+        AccountEntryView, AccountStateWithProofView, AccountView, ApiVersionsView,
+        BlockMetadataView, CurrencyInfoView, EventView, EventWithProofView, MetadataView,
+        MoveResourceView, PendingTransactionView, RemovedTransactionView, StateProofView,
+        TransactionView, TransactionsWithProofsView,
     },
     Error, Result, Retry, State,
 };
@@ -164,6 +166,43 @@ impl Client {
             .await
     }
 
+    pub async fn get_accounts(
+        &self,
+        addresses: Vec<AccountAddress>,
+    ) -> Result<Response<Vec<AccountEntryView>>> {
+        self.send(MethodRequest::get_accounts(addresses)).await
+    }
+
+    pub async fn get_accounts_by_version(
+        &self,
+        addresses: Vec<AccountAddress>,
+        version: u64,
+    ) -> Result<Response<Vec<AccountEntryView>>> {
+        self.send(MethodRequest::get_accounts_by_version(addresses, version))
+            .await
+    }
+
+    pub async fn get_resource(
+        &self,
+        address: AccountAddress,
+        struct_tag: &str,
+    ) -> Result<Response<Option<MoveResourceView>>> {
+        self.send(MethodRequest::get_resource(address, struct_tag))
+            .await
+    }
+
+    pub async fn get_resource_by_version(
+        &self,
+        address: AccountAddress,
+        struct_tag: &str,
+        version: u64,
+    ) -> Result<Response<Option<MoveResourceView>>> {
+        self.send(MethodRequest::get_resource_by_version(
+            address, struct_tag, version,
+        ))
+        .await
+    }
+
     pub async fn get_transactions(
         &self,
         start_seq: u64,
@@ -218,6 +257,55 @@ impl Client {
             .await
     }
 
+    pub async fn get_events_by_type(
+        &self,
+        type_tag: &str,
+        start_version: u64,
+        limit: u64,
+    ) -> Result<Response<Vec<EventView>>> {
+        self.send(MethodRequest::get_events_by_type(
+            type_tag,
+            start_version,
+            limit,
+        ))
+        .await
+    }
+
+    pub async fn get_events_by_account(
+        &self,
+        account: AccountAddress,
+        start_version: u64,
+        limit: u64,
+    ) -> Result<Response<Vec<EventView>>> {
+        self.send(MethodRequest::get_events_by_account(
+            account,
+            start_version,
+            limit,
+        ))
+        .await
+    }
+
+    pub async fn get_events_by_currency(
+        &self,
+        currency: &str,
+        start_version: u64,
+        limit: u64,
+    ) -> Result<Response<Vec<EventView>>> {
+        self.send(MethodRequest::get_events_by_currency(
+            currency,
+            start_version,
+            limit,
+        ))
+        .await
+    }
+
+    pub async fn get_block_metadata(
+        &self,
+        version: u64,
+    ) -> Result<Response<Option<BlockMetadataView>>> {
+        self.send(MethodRequest::get_block_metadata(version)).await
+    }
+
     pub async fn get_currencies(&self) -> Result<Response<Vec<CurrencyInfoView>>> {
         self.send(MethodRequest::get_currencies()).await
     }
@@ -226,6 +314,30 @@ impl Client {
         self.send(MethodRequest::get_network_status()).await
     }
 
+    pub async fn get_pending_transactions(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<Vec<PendingTransactionView>>> {
+        self.send(MethodRequest::get_pending_transactions(address))
+            .await
+    }
+
+    pub async fn get_removed_transaction(
+        &self,
+        address: AccountAddress,
+        sequence_number: u64,
+    ) -> Result<Response<Option<RemovedTransactionView>>> {
+        self.send(MethodRequest::get_removed_transaction(
+            address,
+            sequence_number,
+        ))
+        .await
+    }
+
+    pub async fn get_api_versions(&self) -> Result<Response<ApiVersionsView>> {
+        self.send(MethodRequest::get_api_versions()).await
+    }
+
     //
     // Experimental APIs
     //
@@ -263,6 +375,22 @@ impl Client {
         .await
     }
 
+    pub async fn get_transactions_with_proofs_by_version(
+        &self,
+        start_version: u64,
+        limit: u64,
+        include_events: bool,
+        ledger_version: u64,
+    ) -> Result<Response<Option<TransactionsWithProofsView>>> {
+        self.send(MethodRequest::get_transactions_with_proofs_by_version(
+            start_version,
+            limit,
+            include_events,
+            ledger_version,
+        ))
+        .await
+    }
+
     pub async fn get_events_with_proofs(
         &self,
         key: EventKey,
@@ -6,6 +6,7 @@
 #![allow(dead_code)]
 
 use diem_json_rpc_types::errors::JsonRpcError;
+use std::time::Duration;
 
 pub type Result<T, E = Error> = ::std::result::Result<T, E>;
 
@@ -48,9 +49,12 @@ impl Error {
             // internal server errors are retriable
             Kind::HttpStatus(status) => (500..=599).contains(&status),
             Kind::Timeout | Kind::StaleResponse => true,
+            // per the error code registry, e.g. rate limited calls
+            Kind::JsonRpcError => self
+                .json_rpc_error()
+                .map_or(false, JsonRpcError::is_retriable),
             Kind::RpcResponse
             | Kind::Request
-            | Kind::JsonRpcError
             | Kind::ChainId
             | Kind::Batch
             | Kind::Decode
@@ -59,6 +63,11 @@ impl Error {
         }
     }
 
+    /// How long the server suggests to wait before retrying, e.g. when its mempool is full
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.json_rpc_error().and_then(JsonRpcError::retry_after)
+    }
+
     //
     // Private Constructors
     //
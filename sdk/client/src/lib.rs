@@ -10,6 +10,9 @@ pub use error::{Error, Result, WaitForTransactionError};
 cfg_blocking! {
     mod blocking;
     pub use blocking::BlockingClient;
+
+    mod light_client;
+    pub use light_client::LightClient;
 }
 
 cfg_async! {
@@ -21,7 +24,12 @@ cfg_async! {
     // to use it until it stabilizes further
     // TODO(philiphayes): make this pub once verifying_client is stable.
     #[doc(hidden)]
-    pub use verifying_client::{Storage, InMemoryStorage, VerifyingClient};
+    pub use verifying_client::VerifyingClient;
+}
+
+cfg_async_or_blocking! {
+    mod trusted_state_store;
+    pub use trusted_state_store::{FileStorage, InMemoryStorage, Storage};
 }
 
 cfg_faucet! {
@@ -65,14 +73,24 @@ enum JsonRpcVersion {
 #[serde(rename_all = "snake_case")]
 pub enum Method {
     Submit,
+    SubmitAndWait,
     GetMetadata,
     GetAccount,
+    GetAccounts,
+    GetResource,
     GetTransactions,
     GetAccountTransaction,
     GetAccountTransactions,
     GetEvents,
+    GetEventsByType,
+    GetEventsByAccount,
+    GetEventsByCurrency,
+    GetBlockMetadata,
     GetCurrencies,
     GetNetworkStatus,
+    GetPendingTransactions,
+    GetRemovedTransaction,
+    GetApiVersions,
 
 
     //
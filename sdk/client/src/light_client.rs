@@ -0,0 +1,271 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A light client syncs only the ledger infos and the epoch changes of the chain from a full node,
+//! starting from a waypoint, and verifies the proofs of the account states and transactions it
+//! requests against the latest ledger info it verified. It executes no transactions and keeps no
+//! state besides its trusted state, so it suits mobile and edge devices verifying payments.
+
+use crate::{
+    blocking::BlockingClient,
+    error::{Error, Result},
+    trusted_state_store::{Storage, TrustedStateStore},
+};
+use diem_json_rpc_types::views::{AccountView, TransactionListView, TransactionView};
+use diem_types::{
+    account_address::AccountAddress,
+    account_state::AccountState,
+    account_state_blob::AccountStateWithProof,
+    epoch_change::EpochChangeProof,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    proof::AccumulatorConsistencyProof,
+    transaction::{Transaction, TransactionListWithProof, Version},
+    trusted_state::TrustedState,
+    waypoint::Waypoint,
+};
+use std::convert::TryFrom;
+
+type StateProof = (
+    LedgerInfoWithSignatures,
+    EpochChangeProof,
+    AccumulatorConsistencyProof,
+);
+
+#[derive(Debug)]
+pub struct LightClient<S> {
+    inner: BlockingClient,
+    trusted_state_store: TrustedStateStore<S>,
+    // The latest ledger info verified, which the proofs of the requests are verified against.
+    // Only the trusted state is stored, so it's unknown until the client syncs.
+    latest_li: Option<LedgerInfoWithSignatures>,
+}
+
+impl<S: Storage> LightClient<S> {
+    /// Creates a light client carrying on from the trusted state in `storage`.
+    pub fn new(inner: BlockingClient, storage: S) -> Result<Self> {
+        Ok(Self {
+            inner,
+            trusted_state_store: TrustedStateStore::new(storage)?,
+            latest_li: None,
+        })
+    }
+
+    /// Creates a light client trusting `waypoint`, or the trusted state in `storage` if it's
+    /// ahead of it.
+    pub fn new_with_waypoint(inner: BlockingClient, waypoint: Waypoint, storage: S) -> Self {
+        Self {
+            inner,
+            trusted_state_store: TrustedStateStore::new_with_state(
+                TrustedState::from(waypoint),
+                storage,
+            ),
+            latest_li: None,
+        }
+    }
+
+    /// Get our current trusted ledger [`Version`].
+    pub fn version(&self) -> Version {
+        self.trusted_state_store.version()
+    }
+
+    /// Get our current trusted [`Waypoint`].
+    pub fn waypoint(&self) -> Waypoint {
+        self.trusted_state_store.waypoint()
+    }
+
+    /// Get our current [`TrustedState`].
+    pub fn trusted_state(&self) -> &TrustedState {
+        self.trusted_state_store.trusted_state()
+    }
+
+    /// The latest ledger info verified, if the client synced since it was created.
+    pub fn latest_ledger_info(&self) -> Option<&LedgerInfoWithSignatures> {
+        self.latest_li.as_ref()
+    }
+
+    /// Issues `get_state_proof` requests until the client is synced to the latest ledger info of
+    /// the full node, verifying the epoch changes on the way.
+    pub fn sync(&mut self) -> Result<()> {
+        loop {
+            let (state_proof_view, state) =
+                self.inner.get_state_proof(self.version())?.into_parts();
+            let (latest_li, epoch_change_proof, _) =
+                StateProof::try_from(&state_proof_view).map_err(Error::decode)?;
+            if latest_li.ledger_info().version() != state.version {
+                return Err(Error::invalid_proof(format!(
+                    "latest LedgerInfo version ({}) doesn't match response version ({})",
+                    latest_li.ledger_info().version(),
+                    state.version,
+                )));
+            }
+
+            let change = self
+                .trusted_state()
+                .verify_and_ratchet(&latest_li, &epoch_change_proof)
+                .map_err(Error::invalid_proof)?;
+            if let Some(new_state) = change.new_state() {
+                self.trusted_state_store.ratchet(new_state)?;
+            }
+
+            // Unless more epoch changes are to come, the latest ledger info is verified
+            if !epoch_change_proof.more {
+                self.latest_li = Some(latest_li);
+                return Ok(());
+            }
+        }
+    }
+
+    /// Returns the account at the latest verified version.
+    pub fn get_account(&mut self, address: AccountAddress) -> Result<Option<AccountView>> {
+        let ledger_info = self.synced_ledger_info()?;
+        self.get_account_state(&ledger_info, address)?
+            .map(|account_state| {
+                AccountView::try_from_account_state(address, account_state, ledger_info.version())
+                    .map_err(Error::decode)
+            })
+            .transpose()
+    }
+
+    /// Returns the transactions from `start_version`, up to `limit` of them and up to the latest
+    /// verified version.
+    pub fn get_transactions(
+        &mut self,
+        start_version: Version,
+        limit: u64,
+        include_events: bool,
+    ) -> Result<Vec<TransactionView>> {
+        let ledger_info = self.synced_ledger_info()?;
+        let txn_list_with_proof =
+            self.get_txn_list_with_proof(&ledger_info, start_version, limit, include_events)?;
+        let txn_list_view =
+            TransactionListView::try_from(txn_list_with_proof).map_err(Error::decode)?;
+        Ok(txn_list_view.0)
+    }
+
+    /// Returns the transaction sent by `sender` with `sequence_number` if it's committed at the
+    /// latest verified version, with its events, or `None` if it isn't. Whether it's a payment
+    /// that succeeded is for the caller to check, from its `vm_status` and its events.
+    pub fn verify_payment(
+        &mut self,
+        sender: AccountAddress,
+        sequence_number: u64,
+    ) -> Result<Option<TransactionView>> {
+        let ledger_info = self.synced_ledger_info()?;
+
+        // The sequence number of the account tells whether the transaction is committed
+        let account_sequence_number = match self.get_account_state(&ledger_info, sender)? {
+            Some(account_state) => account_state
+                .get_account_resource()
+                .map_err(Error::decode)?
+                .map_or(0, |account| account.sequence_number()),
+            None => 0,
+        };
+        if sequence_number >= account_sequence_number {
+            return Ok(None);
+        }
+
+        // The version of the transaction is looked up unverified, the transaction at that version
+        // is verified to be the one asked for
+        let version = self
+            .inner
+            .get_account_transaction(sender, sequence_number, false)?
+            .into_inner()
+            .ok_or_else(|| {
+                Error::rpc_response(format!(
+                    "committed transaction not found, sender: {}, sequence number: {}",
+                    sender, sequence_number,
+                ))
+            })?
+            .version;
+        let txn_list_with_proof = self.get_txn_list_with_proof(&ledger_info, version, 1, true)?;
+        match txn_list_with_proof.transactions.first() {
+            Some(Transaction::UserTransaction(txn))
+                if txn.sender() == sender && txn.sequence_number() == sequence_number => {}
+            _ => {
+                return Err(Error::invalid_proof(format!(
+                    "transaction at version {} is not sent by {} with sequence number {}",
+                    version, sender, sequence_number,
+                )))
+            }
+        }
+        let mut txn_list_view =
+            TransactionListView::try_from(txn_list_with_proof).map_err(Error::decode)?;
+        Ok(txn_list_view.0.pop())
+    }
+
+    /// The latest ledger info verified, syncing first if there's none yet.
+    fn synced_ledger_info(&mut self) -> Result<LedgerInfo> {
+        if self.latest_li.is_none() {
+            self.sync()?;
+        }
+        self.latest_li
+            .as_ref()
+            .map(|latest_li| latest_li.ledger_info().clone())
+            .ok_or_else(|| Error::unknown("no ledger info verified"))
+    }
+
+    /// Returns the state of `address` at the version of `ledger_info`, verified against it.
+    fn get_account_state(
+        &self,
+        ledger_info: &LedgerInfo,
+        address: AccountAddress,
+    ) -> Result<Option<AccountState>> {
+        let version = ledger_info.version();
+        let account_state_with_proof_view = self
+            .inner
+            .get_account_state_with_proof(address, Some(version), Some(version))?
+            .into_inner();
+        let account_state_with_proof =
+            AccountStateWithProof::try_from(&account_state_with_proof_view)
+                .map_err(Error::decode)?;
+        account_state_with_proof
+            .verify(ledger_info, version, address)
+            .map_err(Error::invalid_proof)?;
+
+        account_state_with_proof
+            .blob
+            .map(|blob| AccountState::try_from(&blob).map_err(Error::decode))
+            .transpose()
+    }
+
+    /// Returns the transactions from `start_version`, with their proofs verified against
+    /// `ledger_info`.
+    fn get_txn_list_with_proof(
+        &self,
+        ledger_info: &LedgerInfo,
+        start_version: Version,
+        limit: u64,
+        include_events: bool,
+    ) -> Result<TransactionListWithProof> {
+        let txs_with_proofs_view = match self
+            .inner
+            .get_transactions_with_proofs_by_version(
+                start_version,
+                limit,
+                include_events,
+                ledger_info.version(),
+            )?
+            .into_inner()
+        {
+            Some(txs_with_proofs_view) => txs_with_proofs_view,
+            None => return Ok(TransactionListWithProof::new_empty()),
+        };
+
+        // Check that the presence of events in the response matches our expectation.
+        let has_events = txs_with_proofs_view.serialized_events.is_some();
+        if include_events != has_events {
+            return Err(Error::rpc_response(format!(
+                "expected events: {}, received events: {}",
+                include_events, has_events
+            )));
+        }
+
+        let txn_list_with_proof = txs_with_proofs_view
+            .try_into_txn_list_with_proof(start_version)
+            .map_err(Error::decode)?;
+        txn_list_with_proof
+            .verify(ledger_info, Some(start_version))
+            .map_err(Error::invalid_proof)?;
+        Ok(txn_list_with_proof)
+    }
+}
@@ -5,7 +5,7 @@ use super::{JsonRpcVersion, Method};
 use diem_types::{
     account_address::AccountAddress, event::EventKey, transaction::SignedTransaction,
 };
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::sync::atomic::AtomicU64;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -13,14 +13,24 @@ use std::sync::atomic::AtomicU64;
 #[serde(tag = "method", content = "params")]
 pub enum MethodRequest {
     Submit((String,)),
+    SubmitAndWait(String, Option<u64>),
     GetMetadata((Option<u64>,)),
     GetAccount(AccountAddress, Option<u64>),
+    GetAccounts(Vec<AccountAddress>, Option<u64>),
+    GetResource(AccountAddress, String, Option<u64>),
     GetTransactions(u64, u64, bool),
     GetAccountTransaction(AccountAddress, u64, bool),
     GetAccountTransactions(AccountAddress, u64, u64, bool),
     GetEvents(EventKey, u64, u64),
+    GetEventsByType(String, u64, u64),
+    GetEventsByAccount(AccountAddress, u64, u64),
+    GetEventsByCurrency(String, u64, u64),
+    GetBlockMetadata((u64,)),
     GetCurrencies([(); 0]),
     GetNetworkStatus([(); 0]),
+    GetPendingTransactions((AccountAddress,)),
+    GetRemovedTransaction((AccountAddress, u64)),
+    GetApiVersions([(); 0]),
 
 
     //
@@ -28,7 +38,12 @@ pub enum MethodRequest {
     //
     GetStateProof((u64,)),
     GetAccountStateWithProof(AccountAddress, Option<u64>, Option<u64>),
-    GetTransactionsWithProofs(u64, u64, bool),
+    // The ledger version is left out of the params when unset, for servers that don't know it.
+    #[serde(
+        serialize_with = "serialize_transactions_with_proofs",
+        deserialize_with = "deserialize_transactions_with_proofs"
+    )]
+    GetTransactionsWithProofs(u64, u64, bool, Option<u64>),
     GetEventsWithProofs(EventKey, u64, u64),
 
     //////// 0L ////////
@@ -43,6 +58,14 @@ impl MethodRequest {
         Ok(Self::Submit((txn_payload,)))
     }
 
+    pub fn submit_and_wait(
+        txn: &SignedTransaction,
+        timeout_ms: Option<u64>,
+    ) -> Result<Self, bcs::Error> {
+        let txn_payload = hex::encode(bcs::to_bytes(txn)?);
+        Ok(Self::SubmitAndWait(txn_payload, timeout_ms))
+    }
+
     pub fn get_metadata_by_version(version: u64) -> Self {
         Self::GetMetadata((Some(version),))
     }
@@ -59,6 +82,26 @@ impl MethodRequest {
         Self::GetAccount(address, None)
     }
 
+    pub fn get_accounts_by_version(addresses: Vec<AccountAddress>, version: u64) -> Self {
+        Self::GetAccounts(addresses, Some(version))
+    }
+
+    pub fn get_accounts(addresses: Vec<AccountAddress>) -> Self {
+        Self::GetAccounts(addresses, None)
+    }
+
+    pub fn get_resource_by_version(
+        address: AccountAddress,
+        struct_tag: &str,
+        version: u64,
+    ) -> Self {
+        Self::GetResource(address, struct_tag.to_string(), Some(version))
+    }
+
+    pub fn get_resource(address: AccountAddress, struct_tag: &str) -> Self {
+        Self::GetResource(address, struct_tag.to_string(), None)
+    }
+
     /////// 0L /////////
     pub fn get_miner_state(address: AccountAddress) -> Self {
         Self::GetTowerStateView((address,))
@@ -100,6 +143,22 @@ impl MethodRequest {
         Self::GetEvents(key, start_seq, limit)
     }
 
+    pub fn get_events_by_type(type_tag: &str, start_version: u64, limit: u64) -> Self {
+        Self::GetEventsByType(type_tag.to_string(), start_version, limit)
+    }
+
+    pub fn get_events_by_account(account: AccountAddress, start_version: u64, limit: u64) -> Self {
+        Self::GetEventsByAccount(account, start_version, limit)
+    }
+
+    pub fn get_events_by_currency(currency: &str, start_version: u64, limit: u64) -> Self {
+        Self::GetEventsByCurrency(currency.to_string(), start_version, limit)
+    }
+
+    pub fn get_block_metadata(version: u64) -> Self {
+        Self::GetBlockMetadata((version,))
+    }
+
     pub fn get_currencies() -> Self {
         Self::GetCurrencies([])
     }
@@ -108,6 +167,18 @@ impl MethodRequest {
         Self::GetNetworkStatus([])
     }
 
+    pub fn get_api_versions() -> Self {
+        Self::GetApiVersions([])
+    }
+
+    pub fn get_pending_transactions(address: AccountAddress) -> Self {
+        Self::GetPendingTransactions((address,))
+    }
+
+    pub fn get_removed_transaction(address: AccountAddress, sequence_number: u64) -> Self {
+        Self::GetRemovedTransaction((address, sequence_number))
+    }
+
     //
     // Experimental APIs
     //
@@ -128,7 +199,16 @@ impl MethodRequest {
         limit: u64,
         include_events: bool,
     ) -> Self {
-        Self::GetTransactionsWithProofs(start_version, limit, include_events)
+        Self::GetTransactionsWithProofs(start_version, limit, include_events, None)
+    }
+
+    pub fn get_transactions_with_proofs_by_version(
+        start_version: u64,
+        limit: u64,
+        include_events: bool,
+        ledger_version: u64,
+    ) -> Self {
+        Self::GetTransactionsWithProofs(start_version, limit, include_events, Some(ledger_version))
     }
 
     pub fn get_events_with_proofs(key: EventKey, start_seq: u64, limit: u64) -> Self {
@@ -138,17 +218,29 @@ impl MethodRequest {
     pub fn method(&self) -> Method {
         match self {
             MethodRequest::Submit(_) => Method::Submit,
+            MethodRequest::SubmitAndWait(_, _) => Method::SubmitAndWait,
             MethodRequest::GetMetadata(_) => Method::GetMetadata,
             MethodRequest::GetAccount(_, _) => Method::GetAccount,
+            MethodRequest::GetAccounts(_, _) => Method::GetAccounts,
+            MethodRequest::GetResource(_, _, _) => Method::GetResource,
             MethodRequest::GetTransactions(_, _, _) => Method::GetTransactions,
             MethodRequest::GetAccountTransaction(_, _, _) => Method::GetAccountTransaction,
             MethodRequest::GetAccountTransactions(_, _, _, _) => Method::GetAccountTransactions,
             MethodRequest::GetEvents(_, _, _) => Method::GetEvents,
+            MethodRequest::GetEventsByType(_, _, _) => Method::GetEventsByType,
+            MethodRequest::GetEventsByAccount(_, _, _) => Method::GetEventsByAccount,
+            MethodRequest::GetEventsByCurrency(_, _, _) => Method::GetEventsByCurrency,
+            MethodRequest::GetBlockMetadata(_) => Method::GetBlockMetadata,
             MethodRequest::GetCurrencies(_) => Method::GetCurrencies,
             MethodRequest::GetNetworkStatus(_) => Method::GetNetworkStatus,
+            MethodRequest::GetPendingTransactions(_) => Method::GetPendingTransactions,
+            MethodRequest::GetRemovedTransaction(_) => Method::GetRemovedTransaction,
+            MethodRequest::GetApiVersions(_) => Method::GetApiVersions,
             MethodRequest::GetStateProof(_) => Method::GetStateProof,
             MethodRequest::GetAccountStateWithProof(_, _, _) => Method::GetAccountStateWithProof,
-            MethodRequest::GetTransactionsWithProofs(_, _, _) => Method::GetTransactionsWithProofs,
+            MethodRequest::GetTransactionsWithProofs(_, _, _, _) => {
+                Method::GetTransactionsWithProofs
+            }
             MethodRequest::GetEventsWithProofs(_, _, _) => Method::GetEventsWithProofs,
             /////// 0L /////////
             MethodRequest::GetTowerStateView(_) => Method::GetTowerStateView,
@@ -159,6 +251,41 @@ impl MethodRequest {
     }
 }
 
+fn serialize_transactions_with_proofs<S: Serializer>(
+    start_version: &u64,
+    limit: &u64,
+    include_events: &bool,
+    ledger_version: &Option<u64>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match ledger_version {
+        Some(ledger_version) => {
+            (start_version, limit, include_events, ledger_version).serialize(serializer)
+        }
+        None => (start_version, limit, include_events).serialize(serializer),
+    }
+}
+
+fn deserialize_transactions_with_proofs<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<(u64, u64, bool, Option<u64>), D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Params {
+        WithLedgerVersion(u64, u64, bool, Option<u64>),
+        Latest(u64, u64, bool),
+    }
+
+    Ok(match Params::deserialize(deserializer)? {
+        Params::WithLedgerVersion(start_version, limit, include_events, ledger_version) => {
+            (start_version, limit, include_events, ledger_version)
+        }
+        Params::Latest(start_version, limit, include_events) => {
+            (start_version, limit, include_events, None)
+        }
+    })
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct JsonRpcRequest {
     jsonrpc: JsonRpcVersion,
@@ -4,13 +4,16 @@
 use super::Method;
 use crate::{
     views::{
-        AccountStateWithProofView, AccountView, CurrencyInfoView, EventView, 
-        MetadataView, TowerStateResourceView, OracleUpgradeStateView,
+        AccountEntryView, AccountStateWithProofView, AccountView, CurrencyInfoView, EventView, 
+        MetadataView, PendingTransactionView, TowerStateResourceView, OracleUpgradeStateView,
         StateProofView, TransactionView,
     },
     Error, State,
 };
-use diem_json_rpc_types::views::{EventWithProofView, TransactionsWithProofsView, WaypointView};
+use diem_json_rpc_types::views::{
+    ApiVersionsView, BlockMetadataView, EventWithProofView, MoveResourceView,
+    RemovedTransactionView, TransactionsWithProofsView, WaypointView,
+};
 use serde_json::Value;
 
 #[derive(Debug)]
@@ -57,14 +60,24 @@ impl<T> Response<T> {
 #[derive(Clone, PartialEq, Debug)]
 pub enum MethodResponse {
     Submit,
+    SubmitAndWait(Option<TransactionView>),
     GetMetadata(MetadataView),
     GetAccount(Option<AccountView>),
+    GetAccounts(Vec<AccountEntryView>),
+    GetResource(Option<MoveResourceView>),
     GetTransactions(Vec<TransactionView>),
     GetAccountTransaction(Option<TransactionView>),
     GetAccountTransactions(Vec<TransactionView>),
     GetEvents(Vec<EventView>),
+    GetEventsByType(Vec<EventView>),
+    GetEventsByAccount(Vec<EventView>),
+    GetEventsByCurrency(Vec<EventView>),
+    GetBlockMetadata(Option<BlockMetadataView>),
     GetCurrencies(Vec<CurrencyInfoView>),
     GetNetworkStatus(u64),    
+    GetPendingTransactions(Vec<PendingTransactionView>),
+    GetRemovedTransaction(Option<RemovedTransactionView>),
+    GetApiVersions(ApiVersionsView),
     GetStateProof(StateProofView),
     GetAccountStateWithProof(AccountStateWithProofView),
     GetTransactionsWithProofs(Option<TransactionsWithProofsView>),
@@ -80,8 +93,11 @@ impl MethodResponse {
     pub fn from_json(method: Method, json: Value) -> Result<Self, Error> {
         let response = match method {
             Method::Submit => MethodResponse::Submit,
+            Method::SubmitAndWait => MethodResponse::SubmitAndWait(serde_json::from_value(json)?),
             Method::GetMetadata => MethodResponse::GetMetadata(serde_json::from_value(json)?),
             Method::GetAccount => MethodResponse::GetAccount(serde_json::from_value(json)?),
+            Method::GetAccounts => MethodResponse::GetAccounts(serde_json::from_value(json)?),
+            Method::GetResource => MethodResponse::GetResource(serde_json::from_value(json)?),
             Method::GetTransactions => {
                 MethodResponse::GetTransactions(serde_json::from_value(json)?)
             }
@@ -92,10 +108,29 @@ impl MethodResponse {
                 MethodResponse::GetAccountTransactions(serde_json::from_value(json)?)
             }
             Method::GetEvents => MethodResponse::GetEvents(serde_json::from_value(json)?),
+            Method::GetEventsByType => {
+                MethodResponse::GetEventsByType(serde_json::from_value(json)?)
+            }
+            Method::GetEventsByAccount => {
+                MethodResponse::GetEventsByAccount(serde_json::from_value(json)?)
+            }
+            Method::GetEventsByCurrency => {
+                MethodResponse::GetEventsByCurrency(serde_json::from_value(json)?)
+            }
+            Method::GetBlockMetadata => {
+                MethodResponse::GetBlockMetadata(serde_json::from_value(json)?)
+            }
             Method::GetCurrencies => MethodResponse::GetCurrencies(serde_json::from_value(json)?),
             Method::GetNetworkStatus => {
                 MethodResponse::GetNetworkStatus(serde_json::from_value(json)?)
             }
+            Method::GetPendingTransactions => {
+                MethodResponse::GetPendingTransactions(serde_json::from_value(json)?)
+            }
+            Method::GetRemovedTransaction => {
+                MethodResponse::GetRemovedTransaction(serde_json::from_value(json)?)
+            }
+            Method::GetApiVersions => MethodResponse::GetApiVersions(serde_json::from_value(json)?),
             //////// 0L ////////
             Method::GetTowerStateView => {
                 MethodResponse::GetTowerStateView(serde_json::from_value(json)?)
@@ -125,14 +160,24 @@ impl MethodResponse {
     pub fn method(&self) -> Method {
         match self {
             MethodResponse::Submit => Method::Submit,
+            MethodResponse::SubmitAndWait(_) => Method::SubmitAndWait,
             MethodResponse::GetMetadata(_) => Method::GetMetadata,
             MethodResponse::GetAccount(_) => Method::GetAccount,
+            MethodResponse::GetAccounts(_) => Method::GetAccounts,
+            MethodResponse::GetResource(_) => Method::GetResource,
             MethodResponse::GetTransactions(_) => Method::GetTransactions,
             MethodResponse::GetAccountTransaction(_) => Method::GetAccountTransaction,
             MethodResponse::GetAccountTransactions(_) => Method::GetAccountTransactions,
             MethodResponse::GetEvents(_) => Method::GetEvents,
+            MethodResponse::GetEventsByType(_) => Method::GetEventsByType,
+            MethodResponse::GetEventsByAccount(_) => Method::GetEventsByAccount,
+            MethodResponse::GetEventsByCurrency(_) => Method::GetEventsByCurrency,
+            MethodResponse::GetBlockMetadata(_) => Method::GetBlockMetadata,
             MethodResponse::GetCurrencies(_) => Method::GetCurrencies,
             MethodResponse::GetNetworkStatus(_) => Method::GetNetworkStatus,  
+            MethodResponse::GetPendingTransactions(_) => Method::GetPendingTransactions,
+            MethodResponse::GetRemovedTransaction(_) => Method::GetRemovedTransaction,
+            MethodResponse::GetApiVersions(_) => Method::GetApiVersions,
             MethodResponse::GetStateProof(_) => Method::GetStateProof,
             MethodResponse::GetAccountStateWithProof(_) => Method::GetAccountStateWithProof,
             MethodResponse::GetTransactionsWithProofs(_) => Method::GetTransactionsWithProofs,
@@ -159,6 +204,16 @@ impl MethodResponse {
         }
     }
 
+    pub fn try_into_submit_and_wait(self) -> Result<Option<TransactionView>, Error> {
+        match self {
+            MethodResponse::SubmitAndWait(txn) => Ok(txn),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::SubmitAndWait found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
     pub fn try_into_get_state_proof(self) -> Result<StateProofView, Error> {
         match self {
             MethodResponse::GetStateProof(state_proof) => Ok(state_proof),
@@ -179,6 +234,26 @@ impl MethodResponse {
         }
     }
 
+    pub fn try_into_get_accounts(self) -> Result<Vec<AccountEntryView>, Error> {
+        match self {
+            MethodResponse::GetAccounts(accounts) => Ok(accounts),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetAccounts found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
+    pub fn try_into_get_resource(self) -> Result<Option<MoveResourceView>, Error> {
+        match self {
+            MethodResponse::GetResource(resource) => Ok(resource),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetResource found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
     pub fn try_into_get_transactions(self) -> Result<Vec<TransactionView>, Error> {
         match self {
             MethodResponse::GetTransactions(txs) => Ok(txs),
@@ -199,6 +274,46 @@ impl MethodResponse {
         }
     }
 
+    pub fn try_into_get_events_by_type(self) -> Result<Vec<EventView>, Error> {
+        match self {
+            MethodResponse::GetEventsByType(events) => Ok(events),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetEventsByType found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
+    pub fn try_into_get_events_by_account(self) -> Result<Vec<EventView>, Error> {
+        match self {
+            MethodResponse::GetEventsByAccount(events) => Ok(events),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetEventsByAccount found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
+    pub fn try_into_get_events_by_currency(self) -> Result<Vec<EventView>, Error> {
+        match self {
+            MethodResponse::GetEventsByCurrency(events) => Ok(events),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetEventsByCurrency found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
+    pub fn try_into_get_block_metadata(self) -> Result<Option<BlockMetadataView>, Error> {
+        match self {
+            MethodResponse::GetBlockMetadata(block_metadata) => Ok(block_metadata),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetBlockMetadata found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
     pub fn try_into_get_currencies(self) -> Result<Vec<CurrencyInfoView>, Error> {
         match self {
             MethodResponse::GetCurrencies(currencies) => Ok(currencies),
@@ -218,4 +333,34 @@ impl MethodResponse {
             ))),
         }
     }
+
+    pub fn try_into_get_pending_transactions(self) -> Result<Vec<PendingTransactionView>, Error> {
+        match self {
+            MethodResponse::GetPendingTransactions(txns) => Ok(txns),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetPendingTransactions found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
+    pub fn try_into_get_removed_transaction(self) -> Result<Option<RemovedTransactionView>, Error> {
+        match self {
+            MethodResponse::GetRemovedTransaction(removed) => Ok(removed),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetRemovedTransaction found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
+    pub fn try_into_get_api_versions(self) -> Result<ApiVersionsView, Error> {
+        match self {
+            MethodResponse::GetApiVersions(versions) => Ok(versions),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetApiVersions found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
 }
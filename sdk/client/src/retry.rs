@@ -4,7 +4,7 @@
 use std::time::Duration;
 
 cfg_async_or_blocking! {
-    use crate::Result;
+    use crate::{Error, Result};
 }
 
 #[derive(Clone, Debug)]
@@ -33,8 +33,10 @@ impl Retry {
     }
 
     cfg_async_or_blocking! {
-        fn next_delay(&self, remaining_attempts: u32) -> Duration {
-            self.delay * self.max_retries.saturating_sub(remaining_attempts)
+        /// The backoff delay, or the wait the server suggests if it's longer
+        fn next_delay(&self, remaining_attempts: u32, error: &Error) -> Duration {
+            let delay = self.delay * self.max_retries.saturating_sub(remaining_attempts);
+            error.retry_after().map_or(delay, |retry_after| retry_after.max(delay))
         }
     }
 
@@ -49,7 +51,7 @@ impl Retry {
                     Ok(r) => return Ok(r),
                     Err(error) if error.is_retriable() && remaining_attempts > 0 => {
                         remaining_attempts = remaining_attempts.saturating_sub(1);
-                        std::thread::sleep(self.next_delay(remaining_attempts));
+                        std::thread::sleep(self.next_delay(remaining_attempts, &error));
                     }
                     Err(error) => return Err(error),
                 }
@@ -69,7 +71,7 @@ impl Retry {
                     Ok(r) => return Ok(r),
                     Err(error) if error.is_retriable() && remaining_attempts > 0 => {
                         remaining_attempts = remaining_attempts.saturating_sub(1);
-                        tokio::time::sleep(self.next_delay(remaining_attempts)).await;
+                        tokio::time::sleep(self.next_delay(remaining_attempts, &error)).await;
                     }
                     Err(error) => return Err(error),
                 }
@@ -7,6 +7,7 @@ use crate::{
     request::MethodRequest,
     response::{MethodResponse, Response},
     state::State,
+    trusted_state_store::{Storage, TrustedStateStore},
 };
 use diem_json_rpc_types::views::{
     AccountView, CurrencyInfoView, EventView, TransactionListView, TransactionView,
@@ -26,9 +27,7 @@ use diem_types::{
     waypoint::Waypoint,
 };
 use std::{
-    collections::HashMap,
     convert::TryFrom,
-    fmt::Debug,
     sync::{Arc, RwLock},
 };
 
@@ -512,7 +511,7 @@ fn verifying_get_transactions(
     include_events: bool,
 ) -> VerifyingRequest {
     let request = MethodRequest::GetTransactions(start_version, limit, include_events);
-    let subrequests = vec![MethodRequest::GetTransactionsWithProofs(
+    let subrequests = vec![MethodRequest::get_transactions_with_proofs(
         start_version,
         limit,
         include_events,
@@ -704,106 +703,3 @@ fn verifying_get_network_status() -> VerifyingRequest {
     };
     VerifyingRequest::new(request, subrequests, callback)
 }
-
-mod private {
-    pub trait Sealed {}
-
-    impl Sealed for super::InMemoryStorage {}
-}
-
-// TODO(philiphayes): unseal `Storage` trait once verifying client stabilizes.
-pub trait Storage: private::Sealed + Debug {
-    fn get(&self, key: &str) -> Result<Vec<u8>>;
-    fn set(&mut self, key: &str, value: Vec<u8>) -> Result<()>;
-}
-
-#[derive(Debug, Default)]
-pub struct InMemoryStorage {
-    data: HashMap<String, Vec<u8>>,
-}
-
-impl InMemoryStorage {
-    pub fn new() -> Self {
-        Self {
-            data: HashMap::new(),
-        }
-    }
-}
-
-impl Storage for InMemoryStorage {
-    fn get(&self, key: &str) -> Result<Vec<u8>> {
-        self.data
-            .get(key)
-            .map(Clone::clone)
-            .ok_or_else(|| Error::unknown("key not set"))
-    }
-
-    fn set(&mut self, key: &str, value: Vec<u8>) -> Result<()> {
-        self.data.insert(key.to_owned(), value);
-        Ok(())
-    }
-}
-
-pub const TRUSTED_STATE_KEY: &str = "trusted_state";
-
-#[derive(Debug)]
-struct TrustedStateStore<S> {
-    trusted_state: TrustedState,
-    storage: S,
-}
-
-impl<S: Storage> TrustedStateStore<S> {
-    fn new(storage: S) -> Result<Self> {
-        let trusted_state = storage
-            .get(TRUSTED_STATE_KEY)
-            .and_then(|bytes| bcs::from_bytes(&bytes).map_err(Error::decode))?;
-
-        Ok(Self {
-            trusted_state,
-            storage,
-        })
-    }
-
-    fn new_with_state(trusted_state: TrustedState, storage: S) -> Self {
-        let maybe_stored_state: Result<TrustedState> = storage
-            .get(TRUSTED_STATE_KEY)
-            .and_then(|bytes| bcs::from_bytes(&bytes).map_err(Error::decode));
-
-        let trusted_state = if let Ok(stored_state) = maybe_stored_state {
-            if trusted_state.version() > stored_state.version() {
-                trusted_state
-            } else {
-                stored_state
-            }
-        } else {
-            trusted_state
-        };
-
-        Self {
-            trusted_state,
-            storage,
-        }
-    }
-
-    fn version(&self) -> Version {
-        self.trusted_state.version()
-    }
-
-    fn waypoint(&self) -> Waypoint {
-        self.trusted_state.waypoint()
-    }
-
-    fn trusted_state(&self) -> &TrustedState {
-        &self.trusted_state
-    }
-
-    fn ratchet(&mut self, new_state: TrustedState) -> Result<()> {
-        if new_state.version() > self.trusted_state.version() {
-            self.trusted_state = new_state;
-            let trusted_state_bytes = bcs::to_bytes(&self.trusted_state).map_err(Error::decode)?;
-            self.storage.set(TRUSTED_STATE_KEY, trusted_state_bytes)?;
-        }
-
-        Ok(())
-    }
-}
@@ -14,7 +14,15 @@
 //!
 //! Internally both the client and server leverage a NetworkStream that communications in blocks
 //! where a block is a length prefixed array of bytes.
+//!
+//! Optionally, the client and server authenticate each other with the Noise IK handshake upon
+//! connecting, each knowing the static public key of the other, and then encrypt the blocks
+//! exchanged. Blocks are split into frames of at most `MAX_SIZE_NOISE_MSG` bytes for encryption.
 
+use diem_crypto::{
+    noise::{self, NoiseConfig, NoiseError, NoiseSession},
+    x25519,
+};
 use diem_logger::{info, trace, warn, Schema};
 use diem_secure_push_metrics::{register_int_counter_vec, IntCounterVec};
 use once_cell::sync::Lazy;
@@ -142,8 +150,27 @@ pub enum Error {
     NetworkError(#[from] std::io::Error),
     #[error("No active stream")]
     NoActiveStream,
+    #[error("Noise handshake or session failed: {0}")]
+    NoiseError(#[from] NoiseError),
     #[error("Remote stream cleanly closed")]
     RemoteStreamClosed,
+    #[error("Remote authenticated with an unexpected public key: {0}")]
+    UnexpectedRemoteKey(x25519::PublicKey),
+}
+
+/// The keys used to authenticate a connection with the Noise IK handshake.
+struct NoiseKeys {
+    config: NoiseConfig,
+    remote_public_key: x25519::PublicKey,
+}
+
+impl NoiseKeys {
+    fn new(private_key: x25519::PrivateKey, remote_public_key: x25519::PublicKey) -> Self {
+        Self {
+            config: NoiseConfig::new(private_key),
+            remote_public_key,
+        }
+    }
 }
 
 pub struct NetworkClient {
@@ -152,6 +179,7 @@ pub struct NetworkClient {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    noise: Option<NoiseKeys>,
 }
 
 impl NetworkClient {
@@ -161,9 +189,21 @@ impl NetworkClient {
             server,
             stream: None,
             timeout_ms,
+            noise: None,
         }
     }
 
+    /// Upon connecting, authenticate as `private_key` to a server that must authenticate as
+    /// `server_public_key`, and encrypt all the messages exchanged with it.
+    pub fn with_noise(
+        mut self,
+        private_key: x25519::PrivateKey,
+        server_public_key: x25519::PublicKey,
+    ) -> Self {
+        self.noise = Some(NoiseKeys::new(private_key, server_public_key));
+        self
+    }
+
     fn increment_counter(&self, method: Method, result: MethodResult) {
         increment_counter(self.service, NetworkMode::Client, method, result)
     }
@@ -255,7 +295,21 @@ impl NetworkClient {
 
             let stream = stream?;
             stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, self.server, self.timeout_ms));
+            let mut stream = NetworkStream::new(stream, self.server, self.timeout_ms);
+            if let Some(noise) = &self.noise {
+                if let Err(err) = stream.handshake_as_client(self.service.as_bytes(), noise) {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    warn!(SecureNetLogSchema::new(
+                        self.service,
+                        NetworkMode::Client,
+                        LogEvent::ConnectionFailed,
+                    )
+                    .error(&err)
+                    .remote_peer(&self.server));
+                    return Err(err);
+                }
+            }
+            self.stream = Some(stream);
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 self.service,
@@ -275,6 +329,7 @@ pub struct NetworkServer {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    noise: Option<NoiseKeys>,
 }
 
 impl NetworkServer {
@@ -285,9 +340,21 @@ impl NetworkServer {
             listener: Some(listener.unwrap()),
             stream: None,
             timeout_ms,
+            noise: None,
         }
     }
 
+    /// Only accept clients authenticating as `client_public_key`, authenticate to them as
+    /// `private_key`, and encrypt all the messages exchanged with them.
+    pub fn with_noise(
+        mut self,
+        private_key: x25519::PrivateKey,
+        client_public_key: x25519::PublicKey,
+    ) -> Self {
+        self.noise = Some(NoiseKeys::new(private_key, client_public_key));
+        self
+    }
+
     fn increment_counter(&self, method: Method, result: MethodResult) {
         increment_counter(self.service, NetworkMode::Server, method, result)
     }
@@ -388,6 +455,22 @@ impl NetworkServer {
                 }
             };
 
+            stream.set_nodelay(true)?;
+            let mut stream = NetworkStream::new(stream, stream_addr, self.timeout_ms);
+            if let Some(noise) = &self.noise {
+                if let Err(err) = stream.handshake_as_server(self.service.as_bytes(), noise) {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    warn!(SecureNetLogSchema::new(
+                        self.service,
+                        NetworkMode::Server,
+                        LogEvent::ConnectionFailed,
+                    )
+                    .error(&err)
+                    .remote_peer(&stream_addr));
+                    return Err(err);
+                }
+            }
+
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 self.service,
@@ -396,8 +479,7 @@ impl NetworkServer {
             )
             .remote_peer(&stream_addr));
 
-            stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, stream_addr, self.timeout_ms));
+            self.stream = Some(stream);
         }
 
         self.stream.as_mut().ok_or(Error::NoActiveStream)
@@ -409,6 +491,10 @@ struct NetworkStream {
     remote: SocketAddr,
     buffer: Vec<u8>,
     temp_buffer: [u8; 1024],
+    /// Set once the Noise handshake is done, to encrypt and decrypt the messages.
+    session: Option<NoiseSession>,
+    /// Encrypted frames read but not yet decrypted into `buffer`.
+    encrypted_buffer: Vec<u8>,
 }
 
 impl NetworkStream {
@@ -423,7 +509,56 @@ impl NetworkStream {
             remote,
             buffer: Vec::new(),
             temp_buffer: [0; 1024],
+            session: None,
+            encrypted_buffer: Vec::new(),
+        }
+    }
+
+    /// Authenticate the server and this client with the Noise IK handshake, after which all
+    /// messages are encrypted.
+    fn handshake_as_client(&mut self, prologue: &[u8], noise: &NoiseKeys) -> Result<(), Error> {
+        let mut rng = rand::rngs::OsRng;
+        let mut init_message = vec![0; noise::handshake_init_msg_len(0)];
+        let handshake_state = noise.config.initiate_connection(
+            &mut rng,
+            prologue,
+            noise.remote_public_key,
+            None,
+            &mut init_message,
+        )?;
+        self.write(&init_message)?;
+        let response = self.read()?;
+        let (_, session) = noise
+            .config
+            .finalize_connection(handshake_state, &response)?;
+        self.start_session(session)
+    }
+
+    /// Authenticate the client and this server with the Noise IK handshake, after which all
+    /// messages are encrypted. Clients with another key than the expected one are rejected.
+    fn handshake_as_server(&mut self, prologue: &[u8], noise: &NoiseKeys) -> Result<(), Error> {
+        let mut rng = rand::rngs::OsRng;
+        let init_message = self.read()?;
+        let (remote_public_key, handshake_state, _) = noise
+            .config
+            .parse_client_init_message(prologue, &init_message)?;
+        if remote_public_key != noise.remote_public_key {
+            return Err(Error::UnexpectedRemoteKey(remote_public_key));
         }
+        let mut response = vec![0; noise::handshake_resp_msg_len(0)];
+        let session =
+            noise
+                .config
+                .respond_to_client(&mut rng, handshake_state, None, &mut response)?;
+        self.write(&response)?;
+        self.start_session(session)
+    }
+
+    /// Anything read past the handshake was already encrypted.
+    fn start_session(&mut self, session: NoiseSession) -> Result<(), Error> {
+        let data = std::mem::take(&mut self.buffer);
+        self.session = Some(session);
+        self.receive(data)
     }
 
     /// Blocking read until able to successfully read an entire message
@@ -440,7 +575,7 @@ impl NetworkStream {
             if read == 0 {
                 return Err(Error::RemoteStreamClosed);
             }
-            self.buffer.extend(self.temp_buffer[..read].to_vec());
+            self.receive(self.temp_buffer[..read].to_vec())?;
             let result = self.read_buffer();
             if !result.is_empty() {
                 trace!("Found a message in the stream");
@@ -462,10 +597,19 @@ impl NetworkStream {
             return Err(Error::DataTooLarge(data.len()));
         }
         let data_len = data.len() as u32;
-        trace!("Attempting to write length, {},  to the stream", data_len);
-        self.write_all(&data_len.to_le_bytes())?;
-        trace!("Attempting to write data, {},  to the stream", data_len);
-        self.write_all(data)?;
+        if let Some(session) = &mut self.session {
+            trace!(
+                "Attempting to write encrypted data, {},  to the stream",
+                data_len
+            );
+            let frames = encrypt(session, data)?;
+            self.write_all(&frames)?;
+        } else {
+            trace!("Attempting to write length, {},  to the stream", data_len);
+            self.write_all(&data_len.to_le_bytes())?;
+            trace!("Attempting to write data, {},  to the stream", data_len);
+            self.write_all(data)?;
+        }
         trace!(
             "Successfully wrote length, {}, and data to the stream",
             data_len
@@ -473,6 +617,33 @@ impl NetworkStream {
         Ok(())
     }
 
+    /// Append the data read from the TCP socket to the buffer, once decrypted if the stream is
+    /// encrypted. Encrypted frames are only decrypted once fully received.
+    fn receive(&mut self, data: Vec<u8>) -> Result<(), Error> {
+        let session = match &mut self.session {
+            Some(session) => session,
+            None => {
+                self.buffer.extend(data);
+                return Ok(());
+            }
+        };
+
+        self.encrypted_buffer.extend(data);
+        while self.encrypted_buffer.len() >= 2 {
+            let mut u16_bytes = [0; 2];
+            u16_bytes.copy_from_slice(&self.encrypted_buffer[..2]);
+            let frame_size = u16::from_le_bytes(u16_bytes) as usize;
+            if self.encrypted_buffer.len() < 2 + frame_size {
+                break;
+            }
+
+            let mut frame: Vec<u8> = self.encrypted_buffer.drain(..2 + frame_size).collect();
+            let plaintext = session.read_message_in_place(&mut frame[2..])?;
+            self.buffer.extend_from_slice(plaintext);
+        }
+        Ok(())
+    }
+
     /// Data sent on a TCP socket may not necessarily be delivered at the exact time. So a read may
     /// only include a subset of what was sent. This wraps around the TCP read buffer to ensure
     /// that only full messages are received.
@@ -510,10 +681,28 @@ impl NetworkStream {
     }
 }
 
+/// Encrypt a length prefixed message into frames that fit in a Noise message, each prefixed by its
+/// length.
+fn encrypt(session: &mut NoiseSession, data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut message = (data.len() as u32).to_le_bytes().to_vec();
+    message.extend_from_slice(data);
+
+    let mut frames = Vec::new();
+    for chunk in message.chunks(noise::MAX_SIZE_NOISE_MSG - noise::AES_GCM_TAGLEN) {
+        let mut frame = chunk.to_vec();
+        let authentication_tag = session.write_message_in_place(&mut frame)?;
+        frame.extend(authentication_tag);
+        frames.extend_from_slice(&(frame.len() as u16).to_le_bytes());
+        frames.extend(frame);
+    }
+    Ok(frames)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use diem_config::utils;
+    use diem_crypto::Uniform;
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
     /// Read, Write, Connect timeout in milliseconds.
@@ -600,6 +789,61 @@ mod test {
         assert_eq!(data2, result2);
     }
 
+    fn noise_keys() -> (x25519::PrivateKey, x25519::PrivateKey) {
+        let mut rng = rand::rngs::OsRng;
+        (
+            x25519::PrivateKey::generate(&mut rng),
+            x25519::PrivateKey::generate(&mut rng),
+        )
+    }
+
+    #[test]
+    fn test_noise_ping() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let (server_key, client_key) = noise_keys();
+        let (server_public_key, client_public_key) =
+            (server_key.public_key(), client_key.public_key());
+        let mut server = NetworkServer::new("test", server_addr, TIMEOUT)
+            .with_noise(server_key, client_public_key);
+
+        // The handshake blocks the client until the server accepts it.
+        let client = thread::spawn(move || {
+            let mut client = NetworkClient::new("test", server_addr, TIMEOUT)
+                .with_noise(client_key, server_public_key);
+            client.write(&[0, 1, 2, 3]).unwrap();
+            client.read().unwrap()
+        });
+
+        let result = server.read().unwrap();
+        assert_eq!(vec![0, 1, 2, 3], result);
+
+        // Larger than a single Noise message
+        let data = vec![7; 3 * noise::MAX_SIZE_NOISE_MSG];
+        server.write(&data).unwrap();
+        assert_eq!(data, client.join().unwrap());
+    }
+
+    #[test]
+    fn test_noise_unexpected_client() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let (server_key, client_key) = noise_keys();
+        let (_, other_key) = noise_keys();
+        let server_public_key = server_key.public_key();
+        let mut server = NetworkServer::new("test", server_addr, TIMEOUT)
+            .with_noise(server_key, other_key.public_key());
+
+        let client = thread::spawn(move || {
+            let mut client = NetworkClient::new("test", server_addr, TIMEOUT)
+                .with_noise(client_key, server_public_key);
+            client.write(&[0, 1, 2, 3])
+        });
+
+        assert!(matches!(server.read(), Err(Error::UnexpectedRemoteKey(_))));
+        client.join().unwrap().unwrap_err();
+    }
+
     #[test]
     fn test_server_timeout() {
         let server_port = utils::get_available_port();
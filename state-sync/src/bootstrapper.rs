@@ -5,14 +5,15 @@ use crate::{
     coordinator::StateSyncCoordinator,
     executor_proxy::{ExecutorProxy, ExecutorProxyTrait},
     network::{StateSyncEvents, StateSyncSender},
+    sync_status::SyncStatusReader,
 };
 use diem_config::{config::NodeConfig, network_id::NodeNetworkId};
 use diem_types::waypoint::Waypoint;
 use executor_types::ChunkExecutor;
 use futures::channel::mpsc;
 use std::{boxed::Box, collections::HashMap, sync::Arc};
-use storage_interface::DbReader;
-use subscription_service::ReconfigSubscription;
+use storage_interface::DbReaderWriter;
+use subscription_service::ReconfigNotificationBus;
 use tokio::runtime::{Builder, Runtime};
 
 /// Creates and bootstraps new state syncs and creates clients for
@@ -20,17 +21,18 @@ use tokio::runtime::{Builder, Runtime};
 pub struct StateSyncBootstrapper {
     _runtime: Runtime,
     coordinator_sender: mpsc::UnboundedSender<CoordinatorMessage>,
+    sync_status_reader: Arc<dyn SyncStatusReader>,
 }
 
 impl StateSyncBootstrapper {
     pub fn bootstrap(
         network: Vec<(NodeNetworkId, StateSyncSender, StateSyncEvents)>,
         state_sync_to_mempool_sender: mpsc::Sender<diem_mempool::CommitNotification>,
-        storage: Arc<dyn DbReader>,
+        storage: DbReaderWriter,
         executor: Box<dyn ChunkExecutor>,
         node_config: &NodeConfig,
         waypoint: Waypoint,
-        reconfig_event_subscriptions: Vec<ReconfigSubscription>,
+        reconfig_bus: ReconfigNotificationBus,
     ) -> Self {
         let runtime = Builder::new_multi_thread()
             .thread_name("state-sync")
@@ -38,7 +40,7 @@ impl StateSyncBootstrapper {
             .build()
             .expect("[State Sync] Failed to create runtime!");
 
-        let executor_proxy = ExecutorProxy::new(storage, executor, reconfig_event_subscriptions);
+        let executor_proxy = ExecutorProxy::new(storage, executor, reconfig_bus);
         Self::bootstrap_with_executor_proxy(
             runtime,
             network,
@@ -76,15 +78,22 @@ impl StateSyncBootstrapper {
             initial_state,
         )
         .expect("[State Sync] Unable to create state sync coordinator!");
+        let sync_status_reader = coordinator.sync_status_reader();
         runtime.spawn(coordinator.start(network));
 
         Self {
             _runtime: runtime,
             coordinator_sender,
+            sync_status_reader,
         }
     }
 
     pub fn create_client(&self, commit_timeout_secs: u64) -> StateSyncClient {
         StateSyncClient::new(self.coordinator_sender.clone(), commit_timeout_secs)
     }
+
+    /// Returns a reader of the progress of state sync, e.g., to serve it to operators.
+    pub fn sync_status_reader(&self) -> Arc<dyn SyncStatusReader> {
+        self.sync_status_reader.clone()
+    }
 }
@@ -7,16 +7,24 @@ use crate::{
     client::{CoordinatorMessage, SyncRequest},
     counters,
     error::Error,
+    execution_throttle::ExecutionThrottle,
     executor_proxy::ExecutorProxyTrait,
+    ledger_info_verifier::LedgerInfoVerifier,
     logging::{LogEntry, LogEvent, LogSchema},
     network::{StateSyncEvents, StateSyncMessage, StateSyncSender},
+    parallel_fetch::ParallelChunkFetcher,
     request_manager::RequestManager,
     shared_components::SyncState,
+    state_snapshot::{
+        GetStateSnapshotChunkRequest, GetStateSnapshotChunkResponse, StateSnapshotDownload,
+    },
+    sync_status::{SyncProgress, SyncStatusReader},
 };
 use diem_config::{
-    config::{NodeConfig, PeerNetworkId, RoleType, StateSyncConfig},
+    config::{BootstrappingMode, NodeConfig, PeerNetworkId, RoleType, StateSyncConfig},
     network_id::NodeNetworkId,
 };
+use diem_infallible::RwLock;
 use diem_logger::prelude::*;
 use diem_mempool::{CommitResponse, CommittedTransaction};
 use diem_types::{
@@ -34,9 +42,10 @@ use futures::{
 };
 use network::{protocols::network::Event, transport::ConnectionMetadata};
 use std::{
-    cmp,
+    cmp::{self, Ordering},
     collections::HashMap,
-    time::{Duration, SystemTime},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
 };
 use tokio::time::{interval, timeout};
 use tokio_stream::wrappers::IntervalStream;
@@ -89,6 +98,21 @@ pub(crate) struct StateSyncCoordinator<T> {
     // queue of incoming long polling requests
     // peer will be notified about new chunk of transactions if it's available before expiry time
     subscriptions: HashMap<PeerNetworkId, PendingRequestInfo>,
+    // If we're downloading the state snapshot at the waypoint version instead of executing the
+    // transactions up to it, the progress of the download.
+    state_snapshot_download: Option<StateSnapshotDownload>,
+    // The chunk requests sent in parallel for the chunks after the next one, and their responses
+    // waiting to be applied.
+    parallel_chunk_fetcher: ParallelChunkFetcher,
+    // How long to wait for a state snapshot chunk or a parallel chunk before requesting it again
+    request_timeout: Duration,
+    // The progress of the sync, as reported to operators
+    sync_progress: Arc<RwLock<SyncProgress>>,
+    // Holds back chunk requests while the chunks executed recently exceed the configured caps
+    execution_throttle: ExecutionThrottle,
+    // Verifies the ledger infos of the chunks, skipping the verifications made redundant by the
+    // waypoint in batch mode
+    ledger_info_verifier: LedgerInfoVerifier,
     executor_proxy: T,
 }
 
@@ -120,9 +144,51 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
         let request_manager = RequestManager::new(
             Duration::from_millis(retry_timeout_val),
             Duration::from_millis(node_config.state_sync.multicast_timeout_ms),
+            &node_config.state_sync.preferred_peers,
             network_senders,
         );
 
+        // Download the state snapshot at the waypoint version if configured to, unless we're
+        // already there.
+        let state_snapshot_download = if node_config.state_sync.bootstrapping_mode
+            == BootstrappingMode::DownloadStateSnapshot
+            && initial_state.committed_version() < waypoint.version()
+        {
+            info!(
+                LogSchema::event_log(LogEntry::StateSnapshot, LogEvent::Initialize)
+                    .version(waypoint.version())
+            );
+            Some(StateSnapshotDownload::new(waypoint.version()))
+        } else {
+            None
+        };
+
+        // The epoch endings committed behind the waypoint before a restart may not have been
+        // verified, so they're verified again before the node reports it's initialized
+        let mut ledger_info_verifier =
+            LedgerInfoVerifier::new(node_config.state_sync.epoch_change_verification)
+                .persisted_in(node_config.data_dir());
+        if ledger_info_verifier.defers_epoch_endings() {
+            let epoch_endings = Self::unverified_epoch_endings(
+                &executor_proxy,
+                waypoint,
+                &initial_state,
+                ledger_info_verifier.verified_epoch_ending_version(),
+            );
+            ledger_info_verifier.defer_committed_epoch_endings(&epoch_endings);
+        }
+
+        // Until anything is synced, the waypoint is the highest version known
+        let mut sync_progress = SyncProgress::default();
+        sync_progress.update(
+            Instant::now(),
+            waypoint.version(),
+            initial_state.synced_version(),
+            initial_state.committed_version(),
+            waypoint.version() <= initial_state.committed_version()
+                && !ledger_info_verifier.has_deferred_epoch_endings(),
+        );
+
         Ok(Self {
             client_events,
             state_sync_to_mempool_sender,
@@ -135,10 +201,63 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
             sync_request: None,
             target_ledger_info: None,
             initialization_listener: None,
+            state_snapshot_download,
+            parallel_chunk_fetcher: ParallelChunkFetcher::new(
+                node_config.state_sync.chunk_limit,
+                node_config.state_sync.max_chunk_limit,
+            ),
+            request_timeout: Duration::from_millis(retry_timeout_val),
+            sync_progress: Arc::new(RwLock::new(sync_progress)),
+            execution_throttle: ExecutionThrottle::new(
+                &node_config.state_sync.execution_throttle,
+                Instant::now(),
+            ),
+            ledger_info_verifier,
             executor_proxy,
         })
     }
 
+    /// Returns the epoch ending ledger infos committed up to the waypoint since the last one
+    /// verified, in order of their epochs, preceded by the one naming their validators. They start
+    /// with the first one stored if none was verified, e.g., with the waypoint itself when the
+    /// node was bootstrapped from a state snapshot.
+    fn unverified_epoch_endings(
+        executor_proxy: &T,
+        waypoint: Waypoint,
+        initial_state: &SyncState,
+        verified_version: Option<Version>,
+    ) -> Vec<LedgerInfoWithSignatures> {
+        let last_epoch = if initial_state.committed_version() < waypoint.version() {
+            initial_state.trusted_epoch().saturating_sub(1)
+        } else {
+            match executor_proxy.get_epoch_ending_ledger_info(waypoint.version()) {
+                Ok(waypoint_li) => waypoint_li.ledger_info().epoch(),
+                Err(_) => return vec![],
+            }
+        };
+
+        let mut epoch_endings = vec![];
+        for epoch in (0..=last_epoch).rev() {
+            let epoch_ending = match executor_proxy.get_epoch_change_ledger_info(epoch) {
+                Ok(epoch_ending) => epoch_ending,
+                // Not stored, the node starting from a later epoch
+                Err(_) => break,
+            };
+            let version = epoch_ending.ledger_info().version();
+            epoch_endings.push(epoch_ending);
+            if verified_version.map_or(false, |verified_version| version <= verified_version) {
+                break;
+            }
+        }
+        epoch_endings.reverse();
+        epoch_endings
+    }
+
+    /// Returns a reader of the progress of the sync, which is updated as the coordinator runs.
+    pub fn sync_status_reader(&self) -> Arc<dyn SyncStatusReader> {
+        self.sync_progress.clone()
+    }
+
     /// main routine. starts sync coordinator that listens for CoordinatorMsg
     pub async fn start(
         mut self,
@@ -298,8 +417,44 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
                     ])
                     .start_timer();
 
-                // Process chunk response
-                self.process_chunk_response(&peer, *response).await
+                // Process chunk response, then the buffered responses it's followed by
+                let process_result = self.process_chunk_response(&peer, *response).await;
+                if process_result.is_ok() {
+                    self.process_buffered_chunk_responses().await;
+                    self.send_parallel_chunk_requests();
+                }
+                process_result
+            }
+            StateSyncMessage::GetStateSnapshotChunkRequest(request) => {
+                let _timer = counters::PROCESS_MSG_LATENCY
+                    .with_label_values(&[
+                        &peer.raw_network_id().to_string(),
+                        &peer.peer_id().to_string(),
+                        counters::STATE_SNAPSHOT_CHUNK_REQUEST_MSG_LABEL,
+                    ])
+                    .start_timer();
+
+                let process_result = self.process_state_snapshot_chunk_request(&peer, *request);
+                if let Err(ref error) = process_result {
+                    error!(LogSchema::event_log(
+                        LogEntry::ProcessStateSnapshotChunkRequest,
+                        LogEvent::Fail
+                    )
+                    .peer(&peer)
+                    .error(error));
+                }
+                process_result
+            }
+            StateSyncMessage::GetStateSnapshotChunkResponse(response) => {
+                let _timer = counters::PROCESS_MSG_LATENCY
+                    .with_label_values(&[
+                        &peer.raw_network_id().to_string(),
+                        &peer.peer_id().to_string(),
+                        counters::STATE_SNAPSHOT_CHUNK_RESPONSE_MSG_LABEL,
+                    ])
+                    .start_timer();
+
+                self.process_state_snapshot_chunk_response(&peer, *response)
             }
         }
     }
@@ -330,7 +485,8 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
         cb_sender: oneshot::Sender<Result<(), Error>>,
     ) -> Result<(), Error> {
         if self.is_initialized() {
-            Self::send_initialization_callback(cb_sender)?;
+            let result = self.verify_deferred_epoch_endings();
+            Self::send_initialization_callback(cb_sender, result)?;
         } else {
             self.initialization_listener = Some(cb_sender);
         }
@@ -498,7 +654,8 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
                     .local_li_version(committed_version)
                     .local_synced_version(synced_version)
                     .local_epoch(local_epoch));
-                Self::send_initialization_callback(initialization_listener)?;
+                let result = self.verify_deferred_epoch_endings();
+                Self::send_initialization_callback(initialization_listener, result)?;
             }
         }
 
@@ -598,6 +755,30 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
         counters::set_version(counters::VersionType::Committed, committed_version);
         counters::EPOCH.set(local_epoch as i64);
 
+        // Update the sync rate and the estimated time to catch up
+        let highest_known_version = cmp::max(
+            counters::get_version(counters::VersionType::Highest),
+            synced_version,
+        );
+        let is_initialized =
+            self.is_initialized() && !self.ledger_info_verifier.has_deferred_epoch_endings();
+        let mut sync_progress = self.sync_progress.write();
+        sync_progress.update(
+            Instant::now(),
+            highest_known_version,
+            synced_version,
+            committed_version,
+            is_initialized,
+        );
+        let sync_status = sync_progress.status(Instant::now());
+        drop(sync_progress);
+        counters::SYNC_RATE.set(sync_status.sync_rate as i64);
+        counters::ESTIMATED_CATCH_UP_TIME.set(
+            sync_status
+                .estimated_catch_up_time
+                .map_or(-1, |duration| duration.as_secs() as i64),
+        );
+
         // Update timestamps
         counters::set_timestamp(
             counters::TimestampType::Synced,
@@ -973,6 +1154,10 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
             Error::ProcessInvalidChunk(error.to_string())
         })?;
 
+        let chunk_bytes = bcs::serialized_size(&response.txn_list_with_proof).unwrap_or(0) as u64;
+        self.execution_throttle
+            .chunk_executed(Instant::now(), chunk_size, chunk_bytes);
+
         // Update counters and logs with processed chunk information
         counters::STATE_SYNC_CHUNK_SIZE
             .with_label_values(&[
@@ -1028,6 +1213,25 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
             return Err(error);
         }
 
+        // Responses to parallel requests for chunks after the next one are buffered until the
+        // chunks before them are applied.
+        if let Some(known_version) = self.complete_parallel_chunk_request(peer, &response) {
+            let synced_version = self.local_state.synced_version();
+            match known_version.cmp(&synced_version) {
+                Ordering::Greater => {
+                    return self.buffer_parallel_chunk_response(peer, known_version, response)
+                }
+                Ordering::Less => {
+                    return Err(Error::ReceivedChunkForOutdatedRequest(
+                        peer.to_string(),
+                        synced_version.to_string(),
+                        (known_version + 1).to_string(),
+                    ))
+                }
+                Ordering::Equal => {}
+            }
+        }
+
         // Verify the chunk response is well formed before trying to process it.
         self.verify_chunk_response_is_valid(&peer, &response)?;
 
@@ -1079,6 +1283,99 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
         })
     }
 
+    /// Completes the parallel request the given response answers, if any, and returns the
+    /// request's known version.
+    fn complete_parallel_chunk_request(
+        &mut self,
+        peer: &PeerNetworkId,
+        response: &GetChunkResponse,
+    ) -> Option<Version> {
+        let first_version = response.txn_list_with_proof.first_transaction_version?;
+        let known_version = first_version.checked_sub(1)?;
+        let chunk_size = response.txn_list_with_proof.len() as u64;
+        if self
+            .parallel_chunk_fetcher
+            .complete_request(peer, known_version, chunk_size)
+        {
+            Some(known_version)
+        } else {
+            None
+        }
+    }
+
+    /// Verifies the response to a parallel request for a chunk after the next one, and buffers
+    /// it until the chunks before it are applied.
+    fn buffer_parallel_chunk_response(
+        &mut self,
+        peer: &PeerNetworkId,
+        known_version: Version,
+        response: GetChunkResponse,
+    ) -> Result<(), Error> {
+        if let Err(error) = self.verify_parallel_chunk_response(known_version, &response) {
+            self.request_manager.process_invalid_chunk(peer);
+            self.parallel_chunk_fetcher.chunk_failed(peer);
+            return Err(error);
+        }
+
+        debug!(
+            LogSchema::event_log(LogEntry::ProcessParallelChunkResponse, LogEvent::Received)
+                .chunk_response(response.clone())
+                .peer(peer)
+        );
+        self.request_manager.process_success_response(peer);
+        self.parallel_chunk_fetcher
+            .add_response(known_version, peer.clone(), response);
+        Ok(())
+    }
+
+    /// Verifies the transactions of a parallel chunk response against the target they were
+    /// requested relative to. The target is trusted, so the chunk can be verified before the
+    /// chunks before it are applied.
+    fn verify_parallel_chunk_response(
+        &self,
+        known_version: Version,
+        response: &GetChunkResponse,
+    ) -> Result<(), Error> {
+        let target = self.parallel_chunk_fetcher.target().ok_or_else(|| {
+            Error::UnexpectedError("No target for the parallel chunk requests!".into())
+        })?;
+        match &response.response_li {
+            ResponseLedgerInfo::ProgressiveLedgerInfo { target_li, .. } if target_li == target => {}
+            _ => {
+                return Err(Error::ProcessInvalidChunk(
+                    "Parallel chunk response is not relative to the requested target!".into(),
+                ))
+            }
+        }
+
+        let first_version = known_version
+            .checked_add(1)
+            .ok_or_else(|| Error::IntegerOverflow("First version has overflown!".into()))?;
+        response
+            .txn_list_with_proof
+            .verify(target.ledger_info(), Some(first_version))
+            .map_err(|error| Error::ProcessInvalidChunk(error.to_string()))
+    }
+
+    /// Applies the buffered parallel chunk responses that follow the synced version, in order.
+    async fn process_buffered_chunk_responses(&mut self) {
+        while let Some((peer, response)) = self
+            .parallel_chunk_fetcher
+            .take_next_response(self.local_state.synced_version())
+        {
+            if let Err(error) = self.process_chunk_response(&peer, response).await {
+                error!(LogSchema::event_log(
+                    LogEntry::ProcessParallelChunkResponse,
+                    LogEvent::ApplyChunkFail
+                )
+                .peer(&peer)
+                .error(&error));
+                self.parallel_chunk_fetcher.chunk_failed(&peer);
+                return;
+            }
+        }
+    }
+
     fn verify_chunk_response_is_valid(
         &mut self,
         peer: &PeerNetworkId,
@@ -1332,10 +1629,12 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
         }
 
         // Validate chunk ledger infos
-        self.local_state.verify_ledger_info(&response_li)?;
+        self.ledger_info_verifier
+            .verify(self.local_state.trusted_epoch_state(), &response_li)?;
         if let Some(new_highest_li) = new_highest_li.clone() {
             if new_highest_li != response_li {
-                self.local_state.verify_ledger_info(&new_highest_li)?;
+                self.ledger_info_verifier
+                    .verify(self.local_state.trusted_epoch_state(), &new_highest_li)?;
             }
         }
 
@@ -1396,8 +1695,14 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
         // The executor expects that when it is passed an end_of_epoch_li to commit,
         // it is going to execute/commit transactions leading up to that li, so we
         // also verify that the end_of_epoch_li actually ends the epoch.
+        // When epoch endings are verified in batch, the signatures are verified once the waypoint
+        // is reached: until then, the executor checks the end_of_epoch_li it commits against the
+        // transactions proven by the waypoint ledger info.
         let end_of_epoch_li_to_commit = if let Some(end_of_epoch_li) = end_of_epoch_li {
-            self.local_state.verify_ledger_info(&end_of_epoch_li)?;
+            if !self.ledger_info_verifier.defers_epoch_endings() {
+                self.ledger_info_verifier
+                    .verify(self.local_state.trusted_epoch_state(), &end_of_epoch_li)?;
+            }
 
             let ledger_info = end_of_epoch_li.ledger_info();
             if !ledger_info.ends_epoch() {
@@ -1423,7 +1728,18 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
                 Error::UnexpectedError(format!("Waypoint verification failed: {}", error))
             })?;
 
-        self.validate_and_store_chunk(txn_list_with_proof, waypoint_li, end_of_epoch_li_to_commit)?;
+        let trusted_epoch_state = self.local_state.trusted_epoch_state().clone();
+        self.validate_and_store_chunk(
+            txn_list_with_proof,
+            waypoint_li,
+            end_of_epoch_li_to_commit.clone(),
+        )?;
+        if let Some(end_of_epoch_li) = end_of_epoch_li_to_commit {
+            if self.ledger_info_verifier.defers_epoch_endings() {
+                self.ledger_info_verifier
+                    .defer_epoch_ending(trusted_epoch_state, end_of_epoch_li);
+            }
+        }
         self.log_highest_seen_version(None);
 
         Ok(())
@@ -1506,12 +1822,45 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
             }
         }
 
+        // While downloading the state snapshot, request its next chunk if we didn't get it in
+        // time. Transactions are only requested once the download completes.
+        if let Some(download) = self.state_snapshot_download.as_ref() {
+            if download.should_request(self.request_timeout) {
+                return self.send_state_snapshot_chunk_request();
+            }
+            return Ok(());
+        }
+
+        // Give up on the parallel chunk requests that timed out. The one for the next chunk is
+        // handled below like any request for the synced version, so it's not penalized twice.
+        let known_version = self.local_state.synced_version();
+        for (version, peer) in self
+            .parallel_chunk_fetcher
+            .remove_timed_out_requests(self.request_timeout)
+        {
+            if version != known_version {
+                self.request_manager.process_parallel_request_timeout(&peer);
+            }
+        }
+
+        // Hold back the chunk requests while the chunks executed recently exceed the throttle
+        if self.is_execution_throttled() {
+            counters::EXECUTION_THROTTLED.inc();
+            return Ok(());
+        }
+
         // If the coordinator didn't make progress by the expected time or did not
         // send a request for the current local synced version, issue a new request.
-        let known_version = self.local_state.synced_version();
-        if self.request_manager.has_request_timed_out(known_version)? {
-            counters::TIMEOUT.inc();
-            warn!(LogSchema::new(LogEntry::Timeout).version(known_version));
+        let progress_result = if self.request_manager.has_request_timed_out(known_version)? {
+            // A request never sent (e.g., held back by the throttle) didn't time out
+            if self
+                .request_manager
+                .get_last_request_time(known_version)
+                .is_some()
+            {
+                counters::TIMEOUT.inc();
+                warn!(LogSchema::new(LogEntry::Timeout).version(known_version));
+            }
 
             let trusted_epoch = self.local_state.trusted_epoch();
             let chunk_target = if !self.is_initialized() {
@@ -1529,7 +1878,11 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
             )
         } else {
             Ok(())
-        }
+        };
+
+        // Keep the chunks after the next one requested in parallel
+        self.send_parallel_chunk_requests();
+        progress_result
     }
 
     /// Sends a chunk request with a given `known_version`, `known_epoch` and `chunk_target`.
@@ -1563,6 +1916,12 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
         known_epoch: u64,
         target: TargetType,
     ) -> Result<(), Error> {
+        // The chunk may already be requested in parallel, or received and waiting to be applied.
+        // If the execution is throttled, the chunk is requested once the throttle allows it.
+        if self.parallel_chunk_fetcher.is_fetching(known_version) || self.is_execution_throttled() {
+            return Ok(());
+        }
+
         if self.request_manager.no_available_peers() {
             warn!(LogSchema::event_log(
                 LogEntry::SendChunkRequest,
@@ -1582,6 +1941,262 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
         self.request_manager.send_chunk_request(req)
     }
 
+    /// Verifies the signatures of the epoch ending ledger infos committed behind the waypoint
+    /// without being verified, as the node reaches the waypoint.
+    fn verify_deferred_epoch_endings(&mut self) -> Result<(), Error> {
+        let result = self.ledger_info_verifier.verify_deferred_epoch_endings();
+        if let Err(error) = &result {
+            counters::EPOCH_ENDING_VERIFICATION_FAIL.inc();
+            error!(LogSchema::event_log(LogEntry::Waypoint, LogEvent::Fail).error(error));
+        }
+        result
+    }
+
+    /// Returns true if chunks should not be requested for now, the chunks executed recently
+    /// exceeding the caps of the execution throttle.
+    fn is_execution_throttled(&mut self) -> bool {
+        let initialized = self.is_initialized();
+        self.execution_throttle
+            .is_throttled(Instant::now(), initialized)
+    }
+
+    /// Returns the ledger info to request the chunks after the next one relative to, if parallel
+    /// chunk requests are enabled: the target of the sync request, or the target ledger info of
+    /// a full node. It must be in the trusted epoch, so the chunks can be verified as they're
+    /// received.
+    fn parallel_chunk_target(&mut self) -> Option<LedgerInfoWithSignatures> {
+        if self.config.max_parallel_chunk_requests <= 1
+            || !self.is_initialized()
+            || self.is_consensus_executing()
+        {
+            return None;
+        }
+
+        let target = match &self.sync_request {
+            Some(sync_request) => sync_request.target.clone(),
+            None => self.target_ledger_info.clone()?,
+        };
+        let ledger_info = target.ledger_info();
+        if ledger_info.epoch() == self.local_state.trusted_epoch()
+            && ledger_info.version() > self.local_state.synced_version()
+        {
+            Some(target)
+        } else {
+            None
+        }
+    }
+
+    /// Requests the chunks after the next one, up to the parallel chunk target, from peers
+    /// without a parallel request in flight. Each peer is asked for a chunk of its own size.
+    /// Errors are logged.
+    fn send_parallel_chunk_requests(&mut self) {
+        let target = self.parallel_chunk_target();
+        self.parallel_chunk_fetcher.set_target(target.clone());
+        let target = match target {
+            Some(target) => target,
+            None => return,
+        };
+        if self.is_execution_throttled() {
+            return;
+        }
+
+        // Start after the next chunk (unless it's already requested in parallel) and the chunks
+        // already requested.
+        let synced_version = self.local_state.synced_version();
+        let next_chunk_end = if self.parallel_chunk_fetcher.is_fetching(synced_version) {
+            synced_version
+        } else {
+            synced_version.saturating_add(self.config.chunk_limit)
+        };
+        let mut known_version = cmp::max(
+            next_chunk_end,
+            self.parallel_chunk_fetcher.frontier().unwrap_or(0),
+        );
+
+        // One request in flight is the one for the next chunk
+        let max_pending = (self.config.max_parallel_chunk_requests as usize).saturating_sub(1);
+        let num_requests = max_pending.saturating_sub(self.parallel_chunk_fetcher.num_pending());
+        let busy_peers = self.parallel_chunk_fetcher.busy_peers();
+        let peers = self
+            .request_manager
+            .pick_parallel_peers(num_requests, &busy_peers);
+
+        let target_version = target.ledger_info().version();
+        let trusted_epoch = self.local_state.trusted_epoch();
+        for peer in peers {
+            if known_version >= target_version {
+                break;
+            }
+            let limit = cmp::min(
+                self.parallel_chunk_fetcher.chunk_limit(&peer),
+                target_version - known_version,
+            );
+            let request = GetChunkRequest::new(
+                known_version,
+                trusted_epoch,
+                limit,
+                self.create_highest_available_chunk_target(Some(target.clone())),
+            );
+            // Send errors are logged by the request manager
+            if self
+                .request_manager
+                .send_parallel_chunk_request(&peer, request)
+                .is_ok()
+            {
+                self.request_manager
+                    .add_request(known_version, vec![peer.clone()]);
+                self.parallel_chunk_fetcher
+                    .add_request(known_version, peer, limit);
+                known_version += limit;
+            }
+        }
+    }
+
+    /// Sends the request for the next chunk of the state snapshot being downloaded.
+    fn send_state_snapshot_chunk_request(&mut self) -> Result<(), Error> {
+        let download = match self.state_snapshot_download.as_mut() {
+            Some(download) => download,
+            None => return Ok(()),
+        };
+        let request = download.next_request(self.config.snapshot_chunk_limit);
+        download.mark_requested();
+        self.request_manager
+            .send_state_snapshot_chunk_request(request)
+            .map_err(|error| {
+                error!(LogSchema::event_log(
+                    LogEntry::StateSnapshot,
+                    LogEvent::SendChunkRequestFail
+                )
+                .error(&error));
+                error
+            })
+    }
+
+    /// Serves a chunk of the state snapshot at a version we've committed.
+    fn process_state_snapshot_chunk_request(
+        &mut self,
+        peer: &PeerNetworkId,
+        request: GetStateSnapshotChunkRequest,
+    ) -> Result<(), Error> {
+        if request.limit == 0 || request.limit > self.config.max_chunk_limit {
+            self.request_manager.process_invalid_chunk_request(peer);
+            return Err(Error::InvalidChunkRequest(format!(
+                "State snapshot chunk limit {} is not in [1, {}]",
+                request.limit, self.config.max_chunk_limit
+            )));
+        }
+        if request.version > self.local_state.committed_version() {
+            return Err(Error::InvalidChunkRequest(format!(
+                "State snapshot version {} is beyond our committed version {}",
+                request.version,
+                self.local_state.committed_version()
+            )));
+        }
+
+        let snapshot_info = match request.start_after {
+            None => Some(
+                self.executor_proxy
+                    .get_state_snapshot_info(request.version)?,
+            ),
+            Some(_) => None,
+        };
+        let chunk = self.executor_proxy.get_state_snapshot_chunk(
+            request.version,
+            request.start_after,
+            request.limit,
+        )?;
+        let response = GetStateSnapshotChunkResponse {
+            version: request.version,
+            start_after: request.start_after,
+            snapshot_info,
+            chunk,
+        };
+        self.request_manager.send_chunk_response(
+            peer,
+            StateSyncMessage::GetStateSnapshotChunkResponse(Box::new(response)),
+        )
+    }
+
+    /// Restores the chunk of the state snapshot in the given response, if it's the next one, and
+    /// once the last chunk is restored, continues by syncing the transactions after the snapshot.
+    fn process_state_snapshot_chunk_response(
+        &mut self,
+        peer: &PeerNetworkId,
+        response: GetStateSnapshotChunkResponse,
+    ) -> Result<(), Error> {
+        let download = self.state_snapshot_download.as_ref().ok_or_else(|| {
+            Error::ReceivedWrongChunkType("Not downloading a state snapshot".into())
+        })?;
+        if !download.is_next_chunk(&response) {
+            return Err(Error::ReceivedChunkForOutdatedRequest(
+                peer.to_string(),
+                format!("{:?}", download.last_key()),
+                format!("{:?}", response.start_after),
+            ));
+        }
+
+        let is_last = match self.apply_state_snapshot_chunk(response) {
+            Ok(is_last) => is_last,
+            Err(error) => {
+                // The chunk may have left the restoration half way through, so start over.
+                self.request_manager.process_invalid_chunk(peer);
+                if let Some(download) = self.state_snapshot_download.as_mut() {
+                    download.restart();
+                }
+                return Err(error);
+            }
+        };
+        self.request_manager.process_success_response(peer);
+
+        if !is_last {
+            return self.send_state_snapshot_chunk_request();
+        }
+        if let Some(download) = self.state_snapshot_download.take() {
+            info!(
+                LogSchema::event_log(LogEntry::StateSnapshot, LogEvent::Complete)
+                    .version(download.version())
+                    .count(download.num_accounts())
+            );
+        }
+        self.sync_state_with_local_storage()?;
+        self.update_sync_state_metrics_and_logs()?;
+        self.check_initialized_or_sync_request_completed(self.local_state.synced_version())
+    }
+
+    /// Verifies and restores the chunk of the state snapshot in the given response, returning
+    /// whether it's the last one.
+    fn apply_state_snapshot_chunk(
+        &mut self,
+        response: GetStateSnapshotChunkResponse,
+    ) -> Result<bool, Error> {
+        let download = self
+            .state_snapshot_download
+            .as_mut()
+            .ok_or_else(|| Error::UnexpectedError("Not downloading a state snapshot".into()))?;
+        if download.snapshot_info().is_none() {
+            let snapshot_info = response.snapshot_info.ok_or_else(|| {
+                Error::ProcessInvalidChunk("First state snapshot chunk without snapshot".into())
+            })?;
+            snapshot_info
+                .verify(&self.waypoint)
+                .map_err(|error| Error::ProcessInvalidChunk(error.to_string()))?;
+            self.executor_proxy.start_state_snapshot(&snapshot_info)?;
+            download.start(snapshot_info);
+        }
+
+        let is_last = response.chunk.is_last;
+        download.chunk_added(&response.chunk);
+        self.executor_proxy
+            .add_state_snapshot_chunk(response.chunk)?;
+        if is_last {
+            let snapshot_info = download
+                .snapshot_info()
+                .expect("Snapshot is verified with the first chunk");
+            self.executor_proxy.finish_state_snapshot(snapshot_info)?;
+        }
+        Ok(is_last)
+    }
+
     fn deliver_subscription(
         &mut self,
         peer: PeerNetworkId,
@@ -1667,8 +2282,9 @@ impl<T: ExecutorProxyTrait> StateSyncCoordinator<T> {
 
     fn send_initialization_callback(
         callback: oneshot::Sender<Result<(), Error>>,
+        result: Result<(), Error>,
     ) -> Result<(), Error> {
-        match callback.send(Ok(())) {
+        match callback.send(result) {
             Err(error) => {
                 counters::FAILED_CHANNEL_SEND
                     .with_label_values(&[counters::WAYPOINT_INIT_CALLBACK])
@@ -1694,13 +2310,15 @@ mod tests {
         executor_proxy::ExecutorProxy,
         network::StateSyncMessage,
         shared_components::{test_utils, test_utils::create_coordinator_with_config_and_waypoint},
+        state_snapshot::{GetStateSnapshotChunkRequest, GetStateSnapshotChunkResponse},
     };
     use diem_config::{
-        config::{NodeConfig, PeerNetworkId, PeerRole, RoleType},
+        config::{BootstrappingMode, NodeConfig, PeerNetworkId, PeerRole, RoleType},
         network_id::{NetworkId, NodeNetworkId},
     };
     use diem_crypto::{
         ed25519::{Ed25519PrivateKey, Ed25519Signature},
+        hash::SPARSE_MERKLE_PLACEHOLDER_HASH,
         HashValue, PrivateKey, Uniform,
     };
     use diem_mempool::CommitResponse;
@@ -1709,18 +2327,26 @@ mod tests {
         block_info::BlockInfo,
         chain_id::ChainId,
         ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
-        proof::TransactionListProof,
+        proof::{
+            SparseMerkleRangeProof, TransactionAccumulatorProof, TransactionInfoWithProof,
+            TransactionListProof,
+        },
         transaction::{
-            RawTransaction, Script, SignedTransaction, Transaction, TransactionListWithProof,
-            TransactionPayload, Version,
+            RawTransaction, Script, SignedTransaction, Transaction, TransactionInfo,
+            TransactionListWithProof, TransactionPayload, Version,
         },
+        vm_status::KeptVMStatus,
         waypoint::Waypoint,
         PeerId,
     };
     use futures::{channel::oneshot, executor::block_on};
     use netcore::transport::ConnectionOrigin;
     use network::transport::ConnectionMetadata;
-    use std::{collections::BTreeMap, time::SystemTime};
+    use std::{
+        collections::BTreeMap,
+        time::{Duration, SystemTime},
+    };
+    use storage_interface::{StateSnapshotChunk, StateSnapshotInfo};
 
     #[test]
     fn test_process_sync_request() {
@@ -1802,6 +2428,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sync_status() {
+        // Create a coordinator for a node behind its waypoint
+        let waypoint_ledger_info = create_ledger_info_at_version(10);
+        let waypoint = Waypoint::new_any(&waypoint_ledger_info.ledger_info());
+        let coordinator =
+            create_coordinator_with_config_and_waypoint(NodeConfig::default(), waypoint);
+
+        // Verify the status reports the waypoint as the highest known version
+        let sync_status = coordinator.sync_status_reader().get_sync_status();
+        assert_eq!(sync_status.highest_known_version, 10);
+        assert_eq!(sync_status.synced_version, 0);
+        assert_eq!(sync_status.committed_version, 0);
+        assert!(!sync_status.initialized);
+        assert_eq!(sync_status.estimated_catch_up_time, None);
+
+        // Verify a node at its waypoint is initialized and caught up
+        let validator_coordinator = test_utils::create_validator_coordinator();
+        let sync_status = validator_coordinator.sync_status_reader().get_sync_status();
+        assert!(sync_status.initialized);
+        assert_eq!(
+            sync_status.estimated_catch_up_time,
+            Some(Duration::from_secs(0))
+        );
+    }
+
     #[test]
     fn test_wait_for_initialization() {
         // Create a coordinator for a validator node
@@ -2275,6 +2927,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_process_state_snapshot_chunk_request() {
+        // Create a coordinator for a validator node
+        let mut validator_coordinator = test_utils::create_validator_coordinator();
+        let peer_network_id = PeerNetworkId::random();
+
+        // Verify requests with an invalid limit or beyond the committed version are rejected
+        let requests = vec![(0, 0), (0, 100_000), (100, 250)]
+            .into_iter()
+            .map(|(version, limit)| {
+                StateSyncMessage::GetStateSnapshotChunkRequest(Box::new(
+                    GetStateSnapshotChunkRequest {
+                        version,
+                        start_after: None,
+                        limit,
+                    },
+                ))
+            })
+            .collect::<Vec<_>>();
+        verify_all_chunk_requests_are_invalid(
+            &mut validator_coordinator,
+            &peer_network_id,
+            &requests,
+        );
+
+        // Verify state snapshot chunks are unexpected when not downloading a snapshot
+        let response = create_state_snapshot_chunk_response(create_ledger_info_at_version(0));
+        verify_all_chunk_responses_are_the_wrong_type(
+            &mut validator_coordinator,
+            &peer_network_id,
+            &[response],
+        );
+    }
+
+    #[test]
+    fn test_process_state_snapshot_chunk_response() {
+        // Create a coordinator for a validator node downloading the state at version 10
+        let waypoint_ledger_info = create_ledger_info_at_version(10);
+        let waypoint = Waypoint::new_any(&waypoint_ledger_info.ledger_info());
+        let mut node_config = NodeConfig::default();
+        node_config.state_sync.bootstrapping_mode = BootstrappingMode::DownloadStateSnapshot;
+        let mut validator_coordinator =
+            create_coordinator_with_config_and_waypoint(node_config, waypoint);
+        let peer_network_id = PeerNetworkId::random();
+
+        // Verify chunks of a snapshot at another version are outdated
+        let response = create_state_snapshot_chunk_response(create_ledger_info_at_version(5));
+        let result = block_on(validator_coordinator.process_chunk_message(
+            peer_network_id.network_id(),
+            peer_network_id.peer_id(),
+            response,
+        ));
+        if !matches!(result, Err(Error::ReceivedChunkForOutdatedRequest(..))) {
+            panic!("Expected an outdated chunk error, but got: {:?}", result);
+        }
+
+        // Verify snapshots that don't match the waypoint, or don't end an epoch, are rejected
+        let responses = vec![
+            create_state_snapshot_chunk_response(create_ledger_info_at_version(10)),
+            create_state_snapshot_chunk_response(waypoint_ledger_info),
+        ];
+        verify_all_chunk_responses_are_invalid(
+            &mut validator_coordinator,
+            &peer_network_id,
+            &responses,
+        );
+    }
+
     fn create_test_transaction() -> Transaction {
         let private_key = Ed25519PrivateKey::generate_for_testing();
         let public_key = private_key.public_key();
@@ -2306,6 +3026,37 @@ mod tests {
         LedgerInfoWithSignatures::new(ledger_info, BTreeMap::new())
     }
 
+    /// Creates the response for the first chunk of a state snapshot with the given ledger info.
+    fn create_state_snapshot_chunk_response(
+        ledger_info: LedgerInfoWithSignatures,
+    ) -> StateSyncMessage {
+        let txn_info = TransactionInfo::new(
+            HashValue::zero(),
+            *SPARSE_MERKLE_PLACEHOLDER_HASH,
+            HashValue::zero(),
+            0,
+            KeptVMStatus::Executed,
+        );
+        let response = GetStateSnapshotChunkResponse {
+            version: ledger_info.ledger_info().version(),
+            start_after: None,
+            snapshot_info: Some(StateSnapshotInfo {
+                ledger_info,
+                txn_info_with_proof: TransactionInfoWithProof::new(
+                    TransactionAccumulatorProof::new(vec![]),
+                    txn_info,
+                ),
+                frozen_subtrees: vec![],
+            }),
+            chunk: StateSnapshotChunk {
+                accounts: vec![],
+                proof: SparseMerkleRangeProof::new(vec![]),
+                is_last: true,
+            },
+        };
+        StateSyncMessage::GetStateSnapshotChunkResponse(Box::new(response))
+    }
+
     fn create_sync_request_at_version(
         version: Version,
     ) -> (SyncRequest, oneshot::Receiver<Result<(), Error>>) {
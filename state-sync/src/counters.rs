@@ -17,6 +17,8 @@ pub const SYNC_MSG_LABEL: &str = "sync";
 pub const COMMIT_MSG_LABEL: &str = "commit";
 pub const CHUNK_REQUEST_MSG_LABEL: &str = "chunk_request";
 pub const CHUNK_RESPONSE_MSG_LABEL: &str = "chunk_response";
+pub const STATE_SNAPSHOT_CHUNK_REQUEST_MSG_LABEL: &str = "state_snapshot_chunk_request";
+pub const STATE_SNAPSHOT_CHUNK_RESPONSE_MSG_LABEL: &str = "state_snapshot_chunk_response";
 
 pub fn set_timestamp(timestamp_type: TimestampType, time_as_usecs: u64) {
     TIMESTAMP
@@ -187,6 +189,17 @@ pub static MULTICAST_LEVEL: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Whether state sync sends chunk requests to the connected preferred peers only. It is 0 if no
+/// preferred peers are configured, or once they made no progress and the node fails over to the
+/// other peers.
+pub static PREFERRED_PEERS_IN_USE: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_state_sync_preferred_peers_in_use",
+        "Whether state sync is sending chunk requests to its preferred peers only"
+    )
+    .unwrap()
+});
+
 pub static TIMESTAMP: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
         "diem_state_sync_timestamp",
@@ -198,6 +211,7 @@ pub static TIMESTAMP: Lazy<IntGaugeVec> = Lazy::new(|| {
 
 /// Notice: this metric is used in CT full node health check
 /// ~/diem/testsuite/cluster-test/health/fullnode_check.rs
+/// and in the JSON-RPC readiness check ~/diem/json-rpc/src/runtime.rs
 /// please make corresponding changes if this field is updated
 pub static VERSION: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -208,6 +222,25 @@ pub static VERSION: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Number of transactions synced per second, over the last minute
+pub static SYNC_RATE: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_state_sync_sync_rate",
+        "Number of transactions synced per second, over the last minute"
+    )
+    .unwrap()
+});
+
+/// Estimated time to sync to the highest known version at the current sync rate, or -1 if the
+/// node isn't syncing
+pub static ESTIMATED_CATCH_UP_TIME: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_state_sync_estimated_catch_up_time_s",
+        "Estimated number of seconds to sync to the highest known version (-1 if not syncing)"
+    )
+    .unwrap()
+});
+
 pub static EPOCH: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!("diem_state_sync_epoch", "Current epoch in local state").unwrap()
 });
@@ -334,6 +367,22 @@ pub static NETWORK_ERROR_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static EXECUTION_THROTTLED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_state_sync_execution_throttled",
+        "Number of progress checks holding back chunk requests, over the execution throttle caps"
+    )
+    .unwrap()
+});
+
+pub static EPOCH_ENDING_VERIFICATION_FAIL: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_state_sync_epoch_ending_verification_fail",
+        "Number of failures to verify the epoch ending ledger infos committed behind the waypoint"
+    )
+    .unwrap()
+});
+
 /// Duration of each run of the event loop.
 pub static MAIN_LOOP: Lazy<DurationHistogram> = Lazy::new(|| {
     DurationHistogram::new(
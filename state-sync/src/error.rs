@@ -19,6 +19,8 @@ pub enum Error {
     IntegerOverflow(String),
     #[error("Received an invalid chunk request: {0}")]
     InvalidChunkRequest(String),
+    #[error("Failed to verify an epoch ending ledger info: {0}")]
+    InvalidEpochEnding(String),
     #[error(
         "Unable to add peer as they are not a valid state sync peer: {0}. Connection origin: {1}"
     )]
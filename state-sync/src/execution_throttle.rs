@@ -0,0 +1,148 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Throttling of the transactions executed by state sync, so that a node catching up doesn't
+//! starve the services on the same machine (e.g., JSON-RPC) of IO.
+
+use diem_config::config::ExecutionThrottleConfig;
+use std::time::Instant;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// A budget refilled at a fixed rate, up to one second worth of it. Executing a chunk may take it
+/// below zero, as the size of a chunk is only known once it's received: no chunk is requested
+/// until the budget is refilled above zero, so the rate holds on average.
+#[derive(Debug)]
+struct Budget {
+    rate: f64,
+    available: f64,
+    last_refill: Instant,
+}
+
+impl Budget {
+    fn new(rate: u64, now: Instant) -> Self {
+        Self {
+            rate: rate as f64,
+            available: rate as f64,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.available = (self.available + elapsed * self.rate).min(self.rate);
+        self.last_refill = now;
+    }
+
+    fn is_exhausted(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.available <= 0.0
+    }
+
+    fn spend(&mut self, now: Instant, amount: u64) {
+        self.refill(now);
+        self.available -= amount as f64;
+    }
+}
+
+/// Caps the number of transactions and bytes executed per second.
+#[derive(Debug)]
+pub(crate) struct ExecutionThrottle {
+    versions: Option<Budget>,
+    bytes: Option<Budget>,
+    burst_mode: bool,
+}
+
+impl ExecutionThrottle {
+    pub fn new(config: &ExecutionThrottleConfig, now: Instant) -> Self {
+        Self {
+            versions: config
+                .max_versions_per_sec
+                .map(|rate| Budget::new(rate, now)),
+            bytes: config
+                .max_write_mb_per_sec
+                .map(|rate| Budget::new(rate.saturating_mul(BYTES_PER_MB), now)),
+            burst_mode: config.burst_mode,
+        }
+    }
+
+    /// Whether chunks should not be requested for now, the chunks executed recently exceeding
+    /// a cap. In burst mode, nothing is throttled until the node is `initialized`.
+    pub fn is_throttled(&mut self, now: Instant, initialized: bool) -> bool {
+        if self.burst_mode && !initialized {
+            return false;
+        }
+        let versions_exhausted = self
+            .versions
+            .as_mut()
+            .map_or(false, |budget| budget.is_exhausted(now));
+        let bytes_exhausted = self
+            .bytes
+            .as_mut()
+            .map_or(false, |budget| budget.is_exhausted(now));
+        versions_exhausted || bytes_exhausted
+    }
+
+    /// Records that a chunk of `num_versions` transactions and `num_bytes` bytes was executed.
+    pub fn chunk_executed(&mut self, now: Instant, num_versions: u64, num_bytes: u64) {
+        if let Some(budget) = self.versions.as_mut() {
+            budget.spend(now, num_versions);
+        }
+        if let Some(budget) = self.bytes.as_mut() {
+            budget.spend(now, num_bytes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_versions_per_sec() {
+        let start = Instant::now();
+        let config = ExecutionThrottleConfig {
+            max_versions_per_sec: Some(100),
+            ..ExecutionThrottleConfig::default()
+        };
+        let mut throttle = ExecutionThrottle::new(&config, start);
+        assert!(!throttle.is_throttled(start, true));
+
+        // A chunk over the budget holds back the next ones until the excess is made up for
+        throttle.chunk_executed(start, 250, 0);
+        assert!(throttle.is_throttled(start, true));
+        assert!(throttle.is_throttled(start + Duration::from_millis(1500), true));
+        assert!(!throttle.is_throttled(start + Duration::from_millis(1600), true));
+
+        // The budget doesn't build up beyond one second worth of it
+        let later = start + Duration::from_secs(60);
+        assert!(!throttle.is_throttled(later, true));
+        throttle.chunk_executed(later, 101, 0);
+        assert!(throttle.is_throttled(later, true));
+    }
+
+    #[test]
+    fn test_write_mb_per_sec_and_burst_mode() {
+        let start = Instant::now();
+        let config = ExecutionThrottleConfig {
+            max_write_mb_per_sec: Some(2),
+            burst_mode: true,
+            ..ExecutionThrottleConfig::default()
+        };
+        let mut throttle = ExecutionThrottle::new(&config, start);
+        throttle.chunk_executed(start, 1000, 3 * BYTES_PER_MB);
+
+        // Only the node initialized is throttled in burst mode
+        assert!(!throttle.is_throttled(start, false));
+        assert!(throttle.is_throttled(start, true));
+        assert!(!throttle.is_throttled(start + Duration::from_millis(600), true));
+
+        // No caps, no throttling
+        let mut throttle = ExecutionThrottle::new(&ExecutionThrottleConfig::default(), start);
+        throttle.chunk_executed(start, u64::MAX, u64::MAX);
+        assert!(!throttle.is_throttled(start, true));
+    }
+}
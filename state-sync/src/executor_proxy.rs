@@ -7,20 +7,30 @@ use crate::{
     logging::{LogEntry, LogEvent, LogSchema},
     shared_components::SyncState,
 };
+use diem_crypto::HashValue;
 use diem_logger::prelude::*;
 use diem_types::{
     account_state::AccountState,
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     move_resource::MoveStorage,
-    on_chain_config::{config_address, OnChainConfigPayload, ON_CHAIN_CONFIG_REGISTRY},
-    transaction::TransactionListWithProof,
+    on_chain_config::{
+        config_address, OnChainConfigPayload, ON_CHAIN_CONFIG_REGISTRY, OPTIONAL_ON_CHAIN_CONFIGS,
+    },
+    transaction::{TransactionListWithProof, Version},
 };
 use executor_types::{ChunkExecutor, ExecutedTrees};
 use itertools::Itertools;
-use std::{collections::HashSet, convert::TryFrom, sync::Arc};
-use storage_interface::DbReader;
-use subscription_service::ReconfigSubscription;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    sync::Arc,
+};
+use storage_interface::{
+    DbReader, DbReaderWriter, DbWriter, StateSnapshotChunk, StateSnapshotInfo,
+    StateSnapshotReceiver,
+};
+use subscription_service::ReconfigNotificationBus;
 
 /// Proxies interactions with execution and storage for state synchronization
 pub trait ExecutorProxyTrait: Send {
@@ -55,33 +65,57 @@ pub trait ExecutorProxyTrait: Send {
 
     /// publishes on-chain config updates to subscribed components
     fn publish_on_chain_config_updates(&mut self, events: Vec<ContractEvent>) -> Result<(), Error>;
+
+    /// Gets the ledger info, transaction info and accumulator of the state snapshot at an epoch
+    /// ending version.
+    fn get_state_snapshot_info(&self, version: Version) -> Result<StateSnapshotInfo, Error>;
+
+    /// Gets at most `limit` accounts of the state at `version`, starting after `start_after`.
+    fn get_state_snapshot_chunk(
+        &self,
+        version: Version,
+        start_after: Option<HashValue>,
+        limit: u64,
+    ) -> Result<StateSnapshotChunk, Error>;
+
+    /// Starts restoring the state of the verified snapshot, from its first chunk.
+    fn start_state_snapshot(&mut self, snapshot_info: &StateSnapshotInfo) -> Result<(), Error>;
+
+    /// Restores the next chunk of the state snapshot being restored.
+    fn add_state_snapshot_chunk(&mut self, chunk: StateSnapshotChunk) -> Result<(), Error>;
+
+    /// Finishes restoring the state snapshot once its last chunk is added, making storage start
+    /// from it, and publishes the on-chain configs at its version.
+    fn finish_state_snapshot(&mut self, snapshot_info: &StateSnapshotInfo) -> Result<(), Error>;
 }
 
 pub(crate) struct ExecutorProxy {
     storage: Arc<dyn DbReader>,
+    storage_writer: Arc<dyn DbWriter>,
     executor: Box<dyn ChunkExecutor>,
-    reconfig_subscriptions: Vec<ReconfigSubscription>,
+    reconfig_bus: ReconfigNotificationBus,
     on_chain_configs: OnChainConfigPayload,
+    state_snapshot_receiver: Option<Box<dyn StateSnapshotReceiver>>,
 }
 
 impl ExecutorProxy {
     pub(crate) fn new(
-        storage: Arc<dyn DbReader>,
+        storage: DbReaderWriter,
         executor: Box<dyn ChunkExecutor>,
-        mut reconfig_subscriptions: Vec<ReconfigSubscription>,
+        reconfig_bus: ReconfigNotificationBus,
     ) -> Self {
-        let on_chain_configs = Self::fetch_all_configs(&*storage)
+        let on_chain_configs = Self::fetch_all_configs(&*storage.reader)
             .expect("[state sync] Failed initial read of on-chain configs");
-        for subscription in reconfig_subscriptions.iter_mut() {
-            subscription
-                .publish(on_chain_configs.clone())
-                .expect("[state sync] Failed to publish initial on-chain config");
-        }
+        reconfig_bus
+            .publish_to_all(on_chain_configs.clone())
+            .expect("[state sync] Failed to publish initial on-chain config");
         Self {
-            storage,
+            storage: storage.reader,
+            storage_writer: storage.writer,
             executor,
-            reconfig_subscriptions,
+            reconfig_bus,
             on_chain_configs,
+            state_snapshot_receiver: None,
         }
     }
 
@@ -108,32 +142,31 @@ impl ExecutorProxy {
                 ))
             })?
             .0;
-        let epoch = account_state_blob
-            .map(|blob| {
-                AccountState::try_from(&blob).and_then(|state| {
-                    Ok(state
-                        .get_configuration_resource()?
-                        .ok_or_else(|| {
-                            Error::UnexpectedError("Configuration resource does not exist".into())
-                        })?
-                        .epoch())
-                })
-            })
+        let account_state = account_state_blob
+            .map(|blob| AccountState::try_from(&blob))
             .ok_or_else(|| Error::UnexpectedError("Missing account state blob".into()))?
             .map_err(|error| {
                 Error::UnexpectedError(format!("Failed to fetch configuration resource: {}", error))
             })?;
+        let epoch = account_state
+            .get_configuration_resource()
+            .map_err(|error| {
+                Error::UnexpectedError(format!("Failed to fetch configuration resource: {}", error))
+            })?
+            .ok_or_else(|| Error::UnexpectedError("Configuration resource does not exist".into()))?
+            .epoch();
 
-        Ok(OnChainConfigPayload::new(
-            epoch,
-            Arc::new(
-                ON_CHAIN_CONFIG_REGISTRY
-                    .iter()
-                    .cloned()
-                    .zip_eq(configs)
-                    .collect(),
-            ),
-        ))
+        let mut configs: HashMap<_, _> = ON_CHAIN_CONFIG_REGISTRY
+            .iter()
+            .cloned()
+            .zip_eq(configs)
+            .collect();
+        for config_id in OPTIONAL_ON_CHAIN_CONFIGS {
+            if let Some(config) = account_state.get(&config_id.access_path().path) {
+                configs.insert(*config_id, config.clone());
+            }
+        }
+        Ok(OnChainConfigPayload::new(epoch, Arc::new(configs)))
     }
 }
 
@@ -262,40 +295,37 @@ impl ExecutorProxyTrait for ExecutorProxy {
         let changed_configs = new_configs
             .configs()
             .iter()
-            .filter(|(id, cfg)| {
-                &self.on_chain_configs.configs().get(id).unwrap_or_else(|| {
-                    panic!("Missing on-chain config value in local copy: {}", id)
-                }) != cfg
+            .filter(|(id, cfg)| match self.on_chain_configs.configs().get(id) {
+                Some(local_cfg) => local_cfg != *cfg,
+                // An optional config was just published
+                None if OPTIONAL_ON_CHAIN_CONFIGS.contains(id) => true,
+                None => panic!("Missing on-chain config value in local copy: {}", id),
             })
             .map(|(id, _)| *id)
             .collect::<HashSet<_>>();
 
         // notify subscribers
+        // publish updates to the subscribers of *any* of the changed configs
+        // or emitted events
         let mut publish_success = true;
-        for subscription in self.reconfig_subscriptions.iter_mut() {
-            // publish updates if *any* of the subscribed configs changed
-            // or any of the subscribed events were emitted
-            let subscribed_items = subscription.subscribed_items();
-            if !changed_configs.is_disjoint(&subscribed_items.configs)
-                || !event_keys.is_disjoint(&subscribed_items.events)
-            {
-                if let Err(e) = subscription.publish(new_configs.clone()) {
-                    publish_success = false;
-                    error!(
-                        LogSchema::event_log(LogEntry::Reconfig, LogEvent::PublishError)
-                            .subscription_name(subscription.name.clone())
-                            .error(&Error::UnexpectedError(e.to_string())),
-                        "Failed to publish reconfig notification to subscription {}",
-                        subscription.name
-                    );
-                } else {
-                    info!(
-                        LogSchema::event_log(LogEntry::Reconfig, LogEvent::Success)
-                            .subscription_name(subscription.name.clone()),
-                        "Successfully published reconfig notification to subscription {}",
-                        subscription.name
-                    );
-                }
+        let outcomes =
+            self.reconfig_bus
+                .publish(new_configs.clone(), &changed_configs, &event_keys);
+        for (name, outcome) in outcomes {
+            if let Err(e) = outcome {
+                publish_success = false;
+                error!(
+                    LogSchema::event_log(LogEntry::Reconfig, LogEvent::PublishError)
+                        .subscription_name(name.clone())
+                        .error(&Error::UnexpectedError(e.to_string())),
+                    "Failed to publish reconfig notification to subscription {}", name
+                );
+            } else {
+                info!(
+                    LogSchema::event_log(LogEntry::Reconfig, LogEvent::Success)
+                        .subscription_name(name.clone()),
+                    "Successfully published reconfig notification to subscription {}", name
+                );
             }
         }
 
@@ -311,13 +341,81 @@ impl ExecutorProxyTrait for ExecutorProxy {
             ))
         }
     }
+
+    fn get_state_snapshot_info(&self, version: Version) -> Result<StateSnapshotInfo, Error> {
+        self.storage
+            .get_state_snapshot_info(version)
+            .map_err(|error| Error::UnexpectedError(error.to_string()))
+    }
+
+    fn get_state_snapshot_chunk(
+        &self,
+        version: Version,
+        start_after: Option<HashValue>,
+        limit: u64,
+    ) -> Result<StateSnapshotChunk, Error> {
+        self.storage
+            .get_state_snapshot_chunk(version, start_after, limit as usize)
+            .map_err(|error| Error::UnexpectedError(error.to_string()))
+    }
+
+    fn start_state_snapshot(&mut self, snapshot_info: &StateSnapshotInfo) -> Result<(), Error> {
+        let receiver = self
+            .storage_writer
+            .get_state_snapshot_receiver(snapshot_info)
+            .map_err(|error| {
+                Error::UnexpectedError(format!(
+                    "Failed to start restoring the state snapshot: {}",
+                    error
+                ))
+            })?;
+        self.state_snapshot_receiver = Some(receiver);
+        Ok(())
+    }
+
+    fn add_state_snapshot_chunk(&mut self, chunk: StateSnapshotChunk) -> Result<(), Error> {
+        self.state_snapshot_receiver
+            .as_mut()
+            .ok_or_else(|| Error::UnexpectedError("No state snapshot is being restored".into()))?
+            .add_chunk(chunk)
+            .map_err(|error| Error::ProcessInvalidChunk(error.to_string()))
+    }
+
+    fn finish_state_snapshot(&mut self, snapshot_info: &StateSnapshotInfo) -> Result<(), Error> {
+        self.state_snapshot_receiver
+            .take()
+            .ok_or_else(|| Error::UnexpectedError("No state snapshot is being restored".into()))?
+            .finish()
+            .map_err(|error| Error::ProcessInvalidChunk(error.to_string()))?;
+        self.storage_writer
+            .finalize_state_snapshot(snapshot_info)
+            .map_err(|error| {
+                Error::UnexpectedError(format!("Failed to finalize the state snapshot: {}", error))
+            })?;
+
+        // All the on-chain configs may have changed since the ones published at startup.
+        let new_configs = Self::fetch_all_configs(&*self.storage)?;
+        self.reconfig_bus
+            .publish_to_all(new_configs.clone())
+            .map_err(|error| {
+                Error::UnexpectedError(format!(
+                    "Failed to publish the on-chain configs of the state snapshot: {}",
+                    error
+                ))
+            })?;
+        self.on_chain_configs = new_configs;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::shared_components::test_utils::create_coordinator_with_executor_proxy;
     use channel::diem_channel::Receiver;
+    use diem_config::config::{EpochChangeVerification, NodeConfig};
     use diem_crypto::{ed25519::*, PrivateKey, Uniform};
+    use diem_temppath::TempPath;
     use diem_transaction_builder::stdlib::{
         encode_peer_to_peer_with_metadata_script,
         encode_set_validator_config_and_reconfigure_script, encode_update_diem_version_script,
@@ -332,9 +430,10 @@ mod tests {
             DiemVersion, OnChainConfig, OnChainConfigPayload, VMConfig, ValidatorSet,
         },
         transaction::{Transaction, WriteSetPayload},
+        waypoint::Waypoint,
     };
     use diem_vm::DiemVM;
-    use diemdb::DiemDB;
+    use diemdb::{DiemDB, PrunerOptions};
     use executor::Executor;
     use executor_test_helpers::{
         bootstrap_genesis, gen_block_id, gen_ledger_info_with_sigs, get_test_signed_transaction,
@@ -342,7 +441,7 @@ mod tests {
     use executor_types::BlockExecutor;
     use futures::{future::FutureExt, stream::StreamExt};
     use storage_interface::DbReaderWriter;
-    use subscription_service::ReconfigSubscription;
+    use subscription_service::{ReconfigNotificationBus, ReconfigSubscription};
     use vm_genesis::Validator;
 
     // TODO(joshlind): add unit tests for general executor proxy behaviour!
@@ -617,6 +716,89 @@ mod tests {
         assert_eq!(sync_state.synced_version(), 5); // 5 transactions have synced
     }
 
+    #[test]
+    fn test_restart_after_state_snapshot() {
+        // Commit two epochs on a first node
+        let (genesis, validators) = vm_genesis::test_genesis_change_set_and_validators(Some(1));
+        let (source_db, source_db_rw) =
+            DbReaderWriter::wrap(DiemDB::new_in_memory(PrunerOptions::default()));
+        let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
+        bootstrap_genesis::<DiemVM>(&source_db_rw, &genesis_txn).unwrap();
+        let mut block_executor = Box::new(Executor::<DiemVM>::new(source_db_rw));
+
+        let validator_account = validators[0].owner_address;
+        let block = vec![
+            create_dummy_transaction(1, validator_account),
+            create_new_update_diem_version_transaction(1),
+        ];
+        let _ = execute_and_commit_block(&mut block_executor, block, 1);
+        let block = vec![
+            create_transfer_to_validator_transaction(validator_account, 2),
+            create_dummy_transaction(2, validator_account),
+            create_consensus_key_rotation_transaction(&validators[0], 0),
+        ];
+        let (_, ledger_info_epoch_2) = execute_and_commit_block(&mut block_executor, block, 2);
+
+        // Bootstrap a second node from the state snapshot at the end of epoch 2, which leaves it
+        // without the earlier epoch endings
+        let version = ledger_info_epoch_2.ledger_info().version();
+        let snapshot_info = source_db.get_state_snapshot_info(version).unwrap();
+        let (_, db_rw) = DbReaderWriter::wrap(DiemDB::new_in_memory(PrunerOptions::default()));
+        let mut receiver = db_rw
+            .writer
+            .get_state_snapshot_receiver(&snapshot_info)
+            .unwrap();
+        let mut start_after = None;
+        loop {
+            let chunk = source_db
+                .get_state_snapshot_chunk(version, start_after, 100)
+                .unwrap();
+            start_after = chunk.accounts.last().map(|(key, _)| *key);
+            let is_last = chunk.is_last;
+            receiver.add_chunk(chunk).unwrap();
+            if is_last {
+                break;
+            }
+        }
+        receiver.finish().unwrap();
+        db_rw
+            .writer
+            .finalize_state_snapshot(&snapshot_info)
+            .unwrap();
+        assert!(db_rw.reader.get_epoch_ending_ledger_infos(1, 2).is_err());
+
+        // Restart the second node, which has no epoch ending to verify again
+        let waypoint = Waypoint::new_epoch_boundary(ledger_info_epoch_2.ledger_info()).unwrap();
+        let data_dir = TempPath::new();
+        data_dir.create_as_dir().unwrap();
+        let mut node_config = NodeConfig::default();
+        node_config.set_data_dir(data_dir.path().to_path_buf());
+        assert_eq!(
+            node_config.state_sync.epoch_change_verification,
+            EpochChangeVerification::Batch
+        );
+        for _ in 0..2 {
+            let chunk_executor = Box::new(Executor::<DiemVM>::new(db_rw.clone()));
+            let executor_proxy = ExecutorProxy::new(
+                db_rw.clone(),
+                chunk_executor,
+                ReconfigNotificationBus::new(),
+            );
+            let coordinator = create_coordinator_with_executor_proxy(
+                node_config.clone(),
+                waypoint,
+                executor_proxy,
+            )
+            .unwrap();
+            assert!(
+                coordinator
+                    .sync_status_reader()
+                    .get_sync_status()
+                    .initialized
+            );
+        }
+    }
+
     /// Executes a genesis transaction, creates the executor proxy and sets the given reconfig
     /// subscription.
     fn bootstrap_genesis_and_set_subscription(
@@ -627,9 +809,7 @@ mod tests {
         let (genesis, validators) = vm_genesis::test_genesis_change_set_and_validators(Some(1));
 
         // Create test diem database
-        let db_path = diem_temppath::TempPath::new();
-        db_path.create_as_dir().unwrap();
-        let (db, db_rw) = DbReaderWriter::wrap(DiemDB::new_for_test(db_path.path()));
+        let (_, db_rw) = DbReaderWriter::wrap(DiemDB::new_in_memory(PrunerOptions::default()));
 
         // Boostrap the genesis transaction
         let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
@@ -637,8 +817,10 @@ mod tests {
 
         // Create executor proxy with given subscription
         let block_executor = Box::new(Executor::<DiemVM>::new(db_rw.clone()));
-        let chunk_executor = Box::new(Executor::<DiemVM>::new(db_rw));
-        let executor_proxy = ExecutorProxy::new(db, chunk_executor, vec![subscription]);
+        let chunk_executor = Box::new(Executor::<DiemVM>::new(db_rw.clone()));
+        let reconfig_bus = ReconfigNotificationBus::new();
+        reconfig_bus.register(subscription).unwrap();
+        let executor_proxy = ExecutorProxy::new(db_rw, chunk_executor, reconfig_bus);
 
         // Verify initial reconfiguration notification is sent
         assert!(
@@ -0,0 +1,347 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verification of the ledger infos carried by chunks. A node catching up receives the same
+//! ledger info with many chunks (e.g., the target of a sync request), and one epoch ending ledger
+//! info per epoch behind the waypoint: in batch mode, a ledger info is verified only once, and
+//! the signatures of the epoch ending ledger infos behind the waypoint are verified all at once
+//! when the waypoint is reached. The version of the last epoch ending ledger info verified that
+//! way is persisted, so that after a restart only the ones committed since are verified again.
+
+use crate::{
+    error::Error,
+    logging::{LogEntry, LogSchema},
+};
+use diem_config::config::EpochChangeVerification;
+use diem_logger::prelude::*;
+use diem_types::{
+    epoch_change::Verifier, epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures,
+    transaction::Version,
+};
+use std::{
+    collections::VecDeque,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The number of ledger infos verified recently which aren't verified again.
+const VERIFIED_CACHE_SIZE: usize = 16;
+
+/// The file of the data directory holding the version of the last deferred epoch ending ledger
+/// info verified.
+const VERIFIED_EPOCH_ENDING_FILE: &str = "state_sync_verified_epoch_ending";
+
+#[derive(Debug)]
+pub(crate) struct LedgerInfoVerifier {
+    mode: EpochChangeVerification,
+    // The ledger infos verified recently, oldest first
+    verified: VecDeque<LedgerInfoWithSignatures>,
+    // The epoch ending ledger infos committed without verifying their signatures, with the epoch
+    // state of the validators which should have signed them
+    deferred_epoch_endings: Vec<(EpochState, LedgerInfoWithSignatures)>,
+    // Where the version of the last deferred epoch ending ledger info verified is persisted
+    verified_epoch_ending_file: Option<PathBuf>,
+}
+
+impl LedgerInfoVerifier {
+    pub fn new(mode: EpochChangeVerification) -> Self {
+        Self {
+            mode,
+            verified: VecDeque::with_capacity(VERIFIED_CACHE_SIZE),
+            deferred_epoch_endings: vec![],
+            verified_epoch_ending_file: None,
+        }
+    }
+
+    /// Persists the version of the last deferred epoch ending ledger info verified in
+    /// `data_dir`, for `verified_epoch_ending_version` to read it after a restart.
+    pub fn persisted_in(mut self, data_dir: &Path) -> Self {
+        self.verified_epoch_ending_file = Some(data_dir.join(VERIFIED_EPOCH_ENDING_FILE));
+        self
+    }
+
+    /// Returns the version of the last deferred epoch ending ledger info verified, if persisted:
+    /// the epoch ending ledger infos up to it don't need to be verified again.
+    pub fn verified_epoch_ending_version(&self) -> Option<Version> {
+        let file = self.verified_epoch_ending_file.as_ref()?;
+        fs::read_to_string(file).ok()?.trim().parse().ok()
+    }
+
+    /// Verifies that `ledger_info` is signed by the validators of `epoch_state`. In batch mode,
+    /// a ledger info verified recently in the same epoch isn't verified again.
+    pub fn verify(
+        &mut self,
+        epoch_state: &EpochState,
+        ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<(), Error> {
+        if self.mode == EpochChangeVerification::Batch
+            && ledger_info.ledger_info().epoch() == epoch_state.epoch
+            && self.verified.contains(ledger_info)
+        {
+            return Ok(());
+        }
+
+        epoch_state
+            .verify(ledger_info)
+            .map_err(|error| Error::UnexpectedError(error.to_string()))?;
+        if self.mode == EpochChangeVerification::Batch {
+            if self.verified.len() == VERIFIED_CACHE_SIZE {
+                self.verified.pop_front();
+            }
+            self.verified.push_back(ledger_info.clone());
+        }
+        Ok(())
+    }
+
+    /// Returns true if the signatures of the epoch ending ledger infos committed behind the
+    /// waypoint are verified in a batch, instead of with each chunk.
+    pub fn defers_epoch_endings(&self) -> bool {
+        self.mode == EpochChangeVerification::Batch
+    }
+
+    /// Records an epoch ending ledger info committed without verifying its signatures, to be
+    /// verified with the validators of `epoch_state` by `verify_deferred_epoch_endings`.
+    pub fn defer_epoch_ending(
+        &mut self,
+        epoch_state: EpochState,
+        ledger_info: LedgerInfoWithSignatures,
+    ) {
+        let epoch = ledger_info.ledger_info().epoch();
+        if epoch != epoch_state.epoch
+            || self
+                .deferred_epoch_endings
+                .iter()
+                .any(|(deferred_state, _)| deferred_state.epoch == epoch)
+        {
+            return;
+        }
+        self.deferred_epoch_endings.push((epoch_state, ledger_info));
+    }
+
+    /// Records the epoch ending ledger infos committed before a restart, in order of their epochs,
+    /// to be verified again by `verify_deferred_epoch_endings`. Each one is verified with the
+    /// validators named by the one before it, so the first one isn't.
+    pub fn defer_committed_epoch_endings(&mut self, epoch_endings: &[LedgerInfoWithSignatures]) {
+        for pair in epoch_endings.windows(2) {
+            if let Some(epoch_state) = pair[0].ledger_info().next_epoch_state() {
+                self.defer_epoch_ending(epoch_state.clone(), pair[1].clone());
+            }
+        }
+    }
+
+    /// Returns true if some epoch ending ledger infos are waiting for their signatures to be
+    /// verified, or failed the verification.
+    pub fn has_deferred_epoch_endings(&self) -> bool {
+        !self.deferred_epoch_endings.is_empty()
+    }
+
+    /// Verifies the signatures of the deferred epoch ending ledger infos. They're forgotten once
+    /// verified, the version of the last one being persisted, and kept otherwise, so every later
+    /// call fails too.
+    pub fn verify_deferred_epoch_endings(&mut self) -> Result<(), Error> {
+        for (epoch_state, ledger_info) in &self.deferred_epoch_endings {
+            epoch_state.verify(ledger_info).map_err(|error| {
+                Error::InvalidEpochEnding(format!(
+                    "Epoch: {}, version: {}, error: {}",
+                    epoch_state.epoch,
+                    ledger_info.ledger_info().version(),
+                    error
+                ))
+            })?;
+        }
+        let last_version = self
+            .deferred_epoch_endings
+            .iter()
+            .map(|(_, ledger_info)| ledger_info.ledger_info().version())
+            .max();
+        if let Some(version) = last_version {
+            self.persist_verified_epoch_ending(version);
+        }
+        self.deferred_epoch_endings.clear();
+        Ok(())
+    }
+
+    fn persist_verified_epoch_ending(&self, version: Version) {
+        if let Some(file) = &self.verified_epoch_ending_file {
+            // Failing only means the epoch endings are verified again after a restart
+            if let Err(error) = fs::write(file, version.to_string()) {
+                let error = Error::UnexpectedError(format!(
+                    "Failed to persist the verified epoch ending version {} in {:?}: {}",
+                    version, file, error
+                ));
+                warn!(LogSchema::new(LogEntry::Waypoint).error(&error));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diem_crypto::HashValue;
+    use diem_temppath::TempPath;
+    use diem_types::{
+        block_info::BlockInfo, ledger_info::LedgerInfo, validator_signer::ValidatorSigner,
+        validator_verifier::random_validator_verifier,
+    };
+    use std::collections::BTreeMap;
+
+    fn create_epoch_state(epoch: u64) -> (Vec<ValidatorSigner>, EpochState) {
+        let (signers, verifier) = random_validator_verifier(4, None, false);
+        (signers, EpochState { epoch, verifier })
+    }
+
+    fn create_ledger_info(
+        signers: &[ValidatorSigner],
+        epoch: u64,
+        version: u64,
+        next_epoch_state: Option<EpochState>,
+    ) -> LedgerInfoWithSignatures {
+        let block_info = BlockInfo::new(
+            epoch,
+            0,
+            HashValue::zero(),
+            HashValue::random(),
+            version,
+            0,
+            next_epoch_state,
+        );
+        let ledger_info = LedgerInfo::new(block_info, HashValue::zero());
+        let signatures = signers
+            .iter()
+            .map(|signer| (signer.author(), signer.sign(&ledger_info)))
+            .collect();
+        LedgerInfoWithSignatures::new(ledger_info, signatures)
+    }
+
+    #[test]
+    fn test_verify() {
+        let (signers, epoch_state) = create_epoch_state(1);
+        let ledger_info = create_ledger_info(&signers, 1, 100, None);
+        let unsigned_ledger_info =
+            LedgerInfoWithSignatures::new(ledger_info.ledger_info().clone(), BTreeMap::new());
+
+        for mode in &[
+            EpochChangeVerification::Strict,
+            EpochChangeVerification::Batch,
+        ] {
+            let mut verifier = LedgerInfoVerifier::new(*mode);
+            verifier.verify(&epoch_state, &ledger_info).unwrap();
+            verifier.verify(&epoch_state, &ledger_info).unwrap();
+            assert!(verifier
+                .verify(&epoch_state, &unsigned_ledger_info)
+                .is_err());
+
+            // A ledger info verified in a previous epoch must be verified again
+            let next_epoch_state = EpochState {
+                epoch: 2,
+                verifier: epoch_state.verifier.clone(),
+            };
+            assert!(verifier.verify(&next_epoch_state, &ledger_info).is_err());
+        }
+    }
+
+    #[test]
+    fn test_verify_deferred_epoch_endings() {
+        let (signers, epoch_state) = create_epoch_state(1);
+        let (_, next_epoch_state) = create_epoch_state(2);
+        let mut verifier = LedgerInfoVerifier::new(EpochChangeVerification::Batch);
+        assert!(verifier.defers_epoch_endings());
+        assert!(!LedgerInfoVerifier::new(EpochChangeVerification::Strict).defers_epoch_endings());
+
+        // Epoch endings are verified once, with the validators of the epoch they end
+        let epoch_ending = create_ledger_info(&signers, 1, 100, Some(next_epoch_state.clone()));
+        verifier.defer_epoch_ending(epoch_state.clone(), epoch_ending.clone());
+        verifier.defer_epoch_ending(epoch_state.clone(), epoch_ending);
+        verifier.defer_epoch_ending(
+            next_epoch_state.clone(),
+            create_ledger_info(&[], 1, 100, None),
+        );
+        assert_eq!(verifier.deferred_epoch_endings.len(), 1);
+        verifier.verify_deferred_epoch_endings().unwrap();
+        assert!(verifier.deferred_epoch_endings.is_empty());
+
+        // An epoch ending signed by other validators keeps failing the verification
+        let forged_epoch_ending = create_ledger_info(&signers, 2, 200, None);
+        verifier.defer_epoch_ending(next_epoch_state, forged_epoch_ending);
+        assert!(matches!(
+            verifier.verify_deferred_epoch_endings(),
+            Err(Error::InvalidEpochEnding(_))
+        ));
+        assert!(verifier.verify_deferred_epoch_endings().is_err());
+    }
+
+    #[test]
+    fn test_defer_committed_epoch_endings() {
+        let (signers, epoch_state) = create_epoch_state(1);
+        let (next_signers, next_epoch_state) = create_epoch_state(2);
+        let genesis = create_ledger_info(&[], 0, 0, Some(epoch_state.clone()));
+        let epoch_ending = create_ledger_info(&signers, 1, 100, Some(next_epoch_state.clone()));
+        let next_epoch_ending = create_ledger_info(&next_signers, 2, 200, None);
+
+        // Genesis isn't signed, and every later epoch ending is verified again
+        let mut verifier = LedgerInfoVerifier::new(EpochChangeVerification::Batch);
+        verifier.defer_committed_epoch_endings(&[
+            genesis.clone(),
+            epoch_ending.clone(),
+            next_epoch_ending,
+        ]);
+        assert!(verifier.has_deferred_epoch_endings());
+        assert_eq!(verifier.deferred_epoch_endings.len(), 2);
+        verifier.verify_deferred_epoch_endings().unwrap();
+        assert!(!verifier.has_deferred_epoch_endings());
+
+        // An epoch ending forged before the restart still fails the verification
+        let forged_epoch_ending = create_ledger_info(&signers, 2, 200, None);
+        verifier.defer_committed_epoch_endings(&[genesis, epoch_ending, forged_epoch_ending]);
+        assert!(matches!(
+            verifier.verify_deferred_epoch_endings(),
+            Err(Error::InvalidEpochEnding(_))
+        ));
+        assert!(verifier.has_deferred_epoch_endings());
+    }
+
+    #[test]
+    fn test_persist_verified_epoch_ending() {
+        let data_dir = TempPath::new();
+        data_dir.create_as_dir().unwrap();
+        let (signers, epoch_state) = create_epoch_state(1);
+        let (next_signers, next_epoch_state) = create_epoch_state(2);
+        let genesis = create_ledger_info(&[], 0, 0, Some(epoch_state));
+        let epoch_ending = create_ledger_info(&signers, 1, 100, Some(next_epoch_state));
+        let next_epoch_ending = create_ledger_info(&next_signers, 2, 200, None);
+
+        let mut verifier =
+            LedgerInfoVerifier::new(EpochChangeVerification::Batch).persisted_in(data_dir.path());
+        assert_eq!(verifier.verified_epoch_ending_version(), None);
+        verifier.defer_committed_epoch_endings(&[genesis, epoch_ending.clone()]);
+        verifier.verify_deferred_epoch_endings().unwrap();
+        assert_eq!(verifier.verified_epoch_ending_version(), Some(100));
+
+        // The version survives a restart, and only moves once the next ones are verified
+        let mut verifier =
+            LedgerInfoVerifier::new(EpochChangeVerification::Batch).persisted_in(data_dir.path());
+        assert_eq!(verifier.verified_epoch_ending_version(), Some(100));
+        let forged_epoch_ending = create_ledger_info(&signers, 2, 200, None);
+        verifier.defer_committed_epoch_endings(&[epoch_ending.clone(), forged_epoch_ending]);
+        assert!(verifier.verify_deferred_epoch_endings().is_err());
+        assert_eq!(verifier.verified_epoch_ending_version(), Some(100));
+
+        let mut verifier =
+            LedgerInfoVerifier::new(EpochChangeVerification::Batch).persisted_in(data_dir.path());
+        verifier.defer_committed_epoch_endings(&[epoch_ending, next_epoch_ending]);
+        verifier.verify_deferred_epoch_endings().unwrap();
+        assert_eq!(verifier.verified_epoch_ending_version(), Some(200));
+    }
+
+    #[test]
+    fn test_verified_cache_is_bounded() {
+        let (signers, epoch_state) = create_epoch_state(1);
+        let mut verifier = LedgerInfoVerifier::new(EpochChangeVerification::Batch);
+        for version in 0..(2 * VERIFIED_CACHE_SIZE as u64) {
+            let ledger_info = create_ledger_info(&signers, 1, version, None);
+            verifier.verify(&epoch_state, &ledger_info).unwrap();
+        }
+        assert_eq!(verifier.verified.len(), VERIFIED_CACHE_SIZE);
+    }
+}
@@ -14,11 +14,16 @@ pub mod client;
 pub mod coordinator;
 mod counters;
 pub mod error;
+mod execution_throttle;
 pub mod executor_proxy;
+mod ledger_info_verifier;
 mod logging;
 pub mod network;
+mod parallel_fetch;
 mod request_manager;
 pub mod shared_components;
+pub mod state_snapshot;
+pub mod sync_status;
 
 #[cfg(any(feature = "fuzzing", test))]
 pub mod fuzzing;
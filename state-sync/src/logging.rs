@@ -99,15 +99,21 @@ pub enum LogEntry {
     Timeout,
     LocalState,
     SendChunkRequest,
+    SendParallelChunkRequest,
     ProcessChunkRequest,
     ProcessChunkResponse,
+    ProcessParallelChunkResponse,
     ProcessChunkMessage,
     NetworkError,
     EpochChange,
     CommitFlow,
     Multicast,
+    PreferredPeers,
     SubscriptionDeliveryFail,
     ProgressCheck,
+    StateSnapshot,
+    ProcessStateSnapshotChunkRequest,
+    ProcessStateSnapshotChunkResponse,
 }
 
 #[derive(Clone, Copy, Serialize)]
@@ -140,7 +146,7 @@ pub enum LogEvent {
     PastEpochRequested,
     DeliverChunk,
 
-    // Multicast network events
+    // Multicast network and preferred peers events
     Failover,
     Recover,
 }
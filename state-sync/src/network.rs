@@ -4,7 +4,11 @@
 //! Interface between State Sync and Network layers.
 
 use crate::{
-    chunk_request::GetChunkRequest, chunk_response::GetChunkResponse, counters, error::Error,
+    chunk_request::GetChunkRequest,
+    chunk_response::GetChunkResponse,
+    counters,
+    error::Error,
+    state_snapshot::{GetStateSnapshotChunkRequest, GetStateSnapshotChunkResponse},
 };
 use channel::message_queues::QueueStyle;
 use diem_metrics::IntCounterVec;
@@ -23,6 +27,8 @@ const STATE_SYNC_MAX_BUFFER_SIZE: usize = 1;
 pub enum StateSyncMessage {
     GetChunkRequest(Box<GetChunkRequest>),
     GetChunkResponse(Box<GetChunkResponse>),
+    GetStateSnapshotChunkRequest(Box<GetStateSnapshotChunkRequest>),
+    GetStateSnapshotChunkResponse(Box<GetStateSnapshotChunkResponse>),
 }
 
 /// The interface from Network to StateSync layer.
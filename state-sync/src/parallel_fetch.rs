@@ -0,0 +1,324 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parallel fetching of the chunks ahead of the next one: while the next chunk is requested as
+//! usual, the version ranges after it (up to the sync target) are requested from other peers,
+//! and their responses are buffered until the chunks before them are applied.
+
+use crate::chunk_response::GetChunkResponse;
+use diem_config::config::PeerNetworkId;
+use diem_types::{ledger_info::LedgerInfoWithSignatures, transaction::Version};
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, SystemTime},
+};
+
+/// The smallest chunk size requested from a peer, however slow.
+const MIN_PARALLEL_CHUNK_LIMIT: u64 = 10;
+
+/// A chunk request sent ahead of the synced version.
+#[derive(Clone, Debug)]
+struct ParallelChunkRequest {
+    peer: PeerNetworkId,
+    limit: u64,
+    request_time: SystemTime,
+}
+
+/// Tracks the chunk requests sent ahead of the synced version, the responses waiting for the
+/// chunks before them, and the chunk size requested from each peer.
+pub(crate) struct ParallelChunkFetcher {
+    // The ledger info all the chunks are requested relative to. It's trusted, i.e., it's the
+    // target of a sync request or a ledger info verified by the local validator set.
+    target: Option<LedgerInfoWithSignatures>,
+    // Outstanding requests, by known version (i.e., the version before the requested chunk)
+    requests: BTreeMap<Version, ParallelChunkRequest>,
+    // Verified responses, by known version, waiting to be applied in order
+    responses: BTreeMap<Version, (PeerNetworkId, GetChunkResponse)>,
+    // The chunk size to request from each peer, adapted to how the peer keeps up
+    peer_chunk_limits: HashMap<PeerNetworkId, u64>,
+    default_chunk_limit: u64,
+    max_chunk_limit: u64,
+}
+
+impl ParallelChunkFetcher {
+    pub fn new(default_chunk_limit: u64, max_chunk_limit: u64) -> Self {
+        Self {
+            target: None,
+            requests: BTreeMap::new(),
+            responses: BTreeMap::new(),
+            peer_chunk_limits: HashMap::new(),
+            default_chunk_limit,
+            max_chunk_limit,
+        }
+    }
+
+    pub fn target(&self) -> Option<&LedgerInfoWithSignatures> {
+        self.target.as_ref()
+    }
+
+    /// Sets the ledger info to request chunks relative to. If it changes, the requests and
+    /// responses for the previous target are dropped.
+    pub fn set_target(&mut self, target: Option<LedgerInfoWithSignatures>) {
+        if self.target != target {
+            self.requests.clear();
+            self.responses.clear();
+            self.target = target;
+        }
+    }
+
+    /// The number of requests in flight plus the number of responses waiting to be applied.
+    pub fn num_pending(&self) -> usize {
+        self.requests.len() + self.responses.len()
+    }
+
+    /// The peers with a request in flight.
+    pub fn busy_peers(&self) -> Vec<PeerNetworkId> {
+        self.requests
+            .values()
+            .map(|request| request.peer.clone())
+            .collect()
+    }
+
+    /// Whether the chunk after `known_version` is requested or waiting to be applied.
+    pub fn is_fetching(&self, known_version: Version) -> bool {
+        self.requests.contains_key(&known_version) || self.responses.contains_key(&known_version)
+    }
+
+    /// The first version not covered by the requests or responses, if any.
+    pub fn frontier(&self) -> Option<Version> {
+        let request_end = self
+            .requests
+            .iter()
+            .map(|(known_version, request)| known_version.saturating_add(request.limit))
+            .max();
+        let response_end = self
+            .responses
+            .iter()
+            .map(|(known_version, (_, response))| {
+                known_version.saturating_add(response.txn_list_with_proof.len() as u64)
+            })
+            .max();
+        request_end.max(response_end)
+    }
+
+    /// The chunk size to request from `peer`.
+    pub fn chunk_limit(&self, peer: &PeerNetworkId) -> u64 {
+        *self
+            .peer_chunk_limits
+            .get(peer)
+            .unwrap_or(&self.default_chunk_limit)
+    }
+
+    pub fn add_request(&mut self, known_version: Version, peer: PeerNetworkId, limit: u64) {
+        self.requests.insert(
+            known_version,
+            ParallelChunkRequest {
+                peer,
+                limit,
+                request_time: SystemTime::now(),
+            },
+        );
+    }
+
+    /// Removes the request for the chunk after `known_version` if it was sent to `peer`, and
+    /// adapts the chunk size of the peer to the `chunk_size` it responded with: a full chunk
+    /// doubles it, a partial chunk (e.g., the peer has a lower maximum) sets it to the chunk size.
+    /// Returns whether there was such a request.
+    pub fn complete_request(
+        &mut self,
+        peer: &PeerNetworkId,
+        known_version: Version,
+        chunk_size: u64,
+    ) -> bool {
+        match self.requests.get(&known_version) {
+            Some(request) if request.peer == *peer => {}
+            _ => return false,
+        }
+        if let Some(request) = self.requests.remove(&known_version) {
+            let new_limit = if chunk_size >= request.limit {
+                request.limit.saturating_mul(2)
+            } else {
+                chunk_size
+            };
+            self.set_chunk_limit(peer, new_limit);
+        }
+        true
+    }
+
+    /// Halves the chunk size of `peer`, after a timeout or a bad chunk.
+    pub fn chunk_failed(&mut self, peer: &PeerNetworkId) {
+        let new_limit = self.chunk_limit(peer) / 2;
+        self.set_chunk_limit(peer, new_limit);
+    }
+
+    /// Removes the requests older than `timeout`, halving the chunk size of their peers. Returns
+    /// the known versions and peers of those requests.
+    pub fn remove_timed_out_requests(
+        &mut self,
+        timeout: Duration,
+    ) -> Vec<(Version, PeerNetworkId)> {
+        let now = SystemTime::now();
+        let timed_out_versions = self
+            .requests
+            .iter()
+            .filter(|(_, request)| {
+                now.duration_since(request.request_time)
+                    .map_or(false, |elapsed| elapsed >= timeout)
+            })
+            .map(|(known_version, _)| *known_version)
+            .collect::<Vec<_>>();
+
+        let mut timed_out_requests = vec![];
+        for known_version in timed_out_versions {
+            if let Some(request) = self.requests.remove(&known_version) {
+                self.chunk_failed(&request.peer);
+                timed_out_requests.push((known_version, request.peer));
+            }
+        }
+        timed_out_requests
+    }
+
+    pub fn add_response(
+        &mut self,
+        known_version: Version,
+        peer: PeerNetworkId,
+        response: GetChunkResponse,
+    ) {
+        self.responses.insert(known_version, (peer, response));
+    }
+
+    /// Takes the response for the chunk after `synced_version`, if it was received. Responses
+    /// for earlier chunks, which overlap the synced transactions, are dropped.
+    pub fn take_next_response(
+        &mut self,
+        synced_version: Version,
+    ) -> Option<(PeerNetworkId, GetChunkResponse)> {
+        self.responses = self.responses.split_off(&synced_version);
+        self.requests = self.requests.split_off(&synced_version);
+        self.responses.remove(&synced_version)
+    }
+
+    fn set_chunk_limit(&mut self, peer: &PeerNetworkId, limit: u64) {
+        let limit = limit
+            .max(MIN_PARALLEL_CHUNK_LIMIT)
+            .min(self.max_chunk_limit);
+        self.peer_chunk_limits.insert(peer.clone(), limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_response::ResponseLedgerInfo;
+    use diem_crypto::HashValue;
+    use diem_types::{
+        account_address::AccountAddress,
+        block_info::BlockInfo,
+        block_metadata::BlockMetadata,
+        ledger_info::LedgerInfo,
+        proof::TransactionListProof,
+        transaction::{Transaction, TransactionListWithProof},
+    };
+
+    fn create_ledger_info_at_version(version: Version) -> LedgerInfoWithSignatures {
+        let block_info =
+            BlockInfo::new(0, 0, HashValue::zero(), HashValue::zero(), version, 0, None);
+        let ledger_info = LedgerInfo::new(block_info, HashValue::zero());
+        LedgerInfoWithSignatures::new(ledger_info, BTreeMap::new())
+    }
+
+    fn create_response(known_version: Version, chunk_size: u64) -> GetChunkResponse {
+        let transactions = (0..chunk_size)
+            .map(|round| {
+                let block_metadata =
+                    BlockMetadata::new(HashValue::zero(), round, 0, vec![], AccountAddress::ZERO);
+                Transaction::BlockMetadata(block_metadata)
+            })
+            .collect();
+        let txn_list_with_proof = TransactionListWithProof::new(
+            transactions,
+            None,
+            Some(known_version + 1),
+            TransactionListProof::new_empty(),
+        );
+        GetChunkResponse::new(
+            ResponseLedgerInfo::ProgressiveLedgerInfo {
+                target_li: create_ledger_info_at_version(100),
+                highest_li: None,
+            },
+            txn_list_with_proof,
+        )
+    }
+
+    #[test]
+    fn test_requests_and_responses() {
+        let mut fetcher = ParallelChunkFetcher::new(20, 40);
+        fetcher.set_target(Some(create_ledger_info_at_version(100)));
+        let (peer_0, peer_1) = (PeerNetworkId::random(), PeerNetworkId::random());
+
+        fetcher.add_request(20, peer_0.clone(), 20);
+        fetcher.add_request(40, peer_1.clone(), 20);
+        assert!(fetcher.is_fetching(20));
+        assert!(!fetcher.is_fetching(30));
+        assert_eq!(fetcher.frontier(), Some(60));
+        assert_eq!(fetcher.num_pending(), 2);
+
+        // Only the peer a request was sent to completes it
+        assert!(!fetcher.complete_request(&peer_1, 20, 20));
+        assert!(fetcher.complete_request(&peer_0, 20, 20));
+        fetcher.add_response(20, peer_0.clone(), create_response(20, 20));
+        assert_eq!(fetcher.busy_peers(), vec![peer_1.clone()]);
+        assert!(fetcher.is_fetching(20));
+
+        // Responses are taken in order, once the chunks before them are synced
+        assert!(fetcher.take_next_response(10).is_none());
+        let (peer, response) = fetcher.take_next_response(20).unwrap();
+        assert_eq!(peer, peer_0);
+        assert_eq!(
+            response.txn_list_with_proof.first_transaction_version,
+            Some(21)
+        );
+        assert!(fetcher.take_next_response(40).is_none());
+
+        // Requests for synced chunks are dropped
+        fetcher.take_next_response(50);
+        assert_eq!(fetcher.num_pending(), 0);
+
+        // A new target drops everything pending
+        fetcher.add_request(60, peer_0, 20);
+        fetcher.set_target(Some(create_ledger_info_at_version(200)));
+        assert_eq!(fetcher.num_pending(), 0);
+        assert_eq!(fetcher.frontier(), None);
+    }
+
+    #[test]
+    fn test_adaptive_chunk_limits() {
+        let mut fetcher = ParallelChunkFetcher::new(20, 40);
+        let peer = PeerNetworkId::random();
+        assert_eq!(fetcher.chunk_limit(&peer), 20);
+
+        // Full chunks grow the chunk size, up to the max
+        for known_version in 0..3 {
+            let limit = fetcher.chunk_limit(&peer);
+            fetcher.add_request(known_version, peer.clone(), limit);
+            assert!(fetcher.complete_request(&peer, known_version, limit));
+        }
+        assert_eq!(fetcher.chunk_limit(&peer), 40);
+
+        // Partial chunks set the chunk size to what the peer sent
+        fetcher.add_request(10, peer.clone(), 40);
+        assert!(fetcher.complete_request(&peer, 10, 30));
+        assert_eq!(fetcher.chunk_limit(&peer), 30);
+
+        // Failures halve the chunk size, down to the min
+        fetcher.chunk_failed(&peer);
+        assert_eq!(fetcher.chunk_limit(&peer), 15);
+        fetcher.add_request(20, peer.clone(), 15);
+        assert_eq!(
+            fetcher.remove_timed_out_requests(Duration::from_secs(0)),
+            vec![(20, peer.clone())]
+        );
+        assert_eq!(fetcher.chunk_limit(&peer), MIN_PARALLEL_CHUNK_LIMIT);
+        assert_eq!(fetcher.num_pending(), 0);
+    }
+}
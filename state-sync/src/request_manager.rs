@@ -7,12 +7,14 @@ use crate::{
     error::Error,
     logging::{LogEntry, LogEvent, LogSchema},
     network::{StateSyncMessage, StateSyncSender},
+    state_snapshot::GetStateSnapshotChunkRequest,
 };
 use diem_config::{
-    config::{PeerNetworkId, PeerRole},
+    config::{PeerNetworkId, PeerRole, PreferredPeerConfig},
     network_id::{NetworkId, NodeNetworkId},
 };
 use diem_logger::prelude::*;
+use diem_types::PeerId;
 use itertools::Itertools;
 use netcore::transport::ConnectionOrigin;
 use network::transport::ConnectionMetadata;
@@ -84,6 +86,11 @@ pub struct RequestManager {
     // the more networks this node will send multicast requests to. Network ordering is defined by
     // NetworkId.
     multicast_network_level: NetworkId,
+    // The configured preferred peers, which chunk requests are sent to before the other peers
+    preferred_peers: HashMap<PeerId, PreferredPeerConfig>,
+    // Whether chunk requests are sent to the connected preferred peers only. Unset once they
+    // made no progress for the multicast timeout, until one of them sends a chunk again.
+    use_preferred_peers: bool,
     network_senders: HashMap<NodeNetworkId, StateSyncSender>,
 }
 
@@ -91,10 +98,13 @@ impl RequestManager {
     pub fn new(
         request_timeout: Duration,
         multicast_timeout: Duration,
+        preferred_peers: &[PreferredPeerConfig],
         network_senders: HashMap<NodeNetworkId, StateSyncSender>,
     ) -> Self {
         let multicast_network_level = NetworkId::Validator;
         update_multicast_network_counter(multicast_network_level.clone());
+        let use_preferred_peers = !preferred_peers.is_empty();
+        counters::PREFERRED_PEERS_IN_USE.set(use_preferred_peers as i64);
 
         Self {
             peer_scores: HashMap::new(),
@@ -102,6 +112,11 @@ impl RequestManager {
             request_timeout,
             multicast_timeout,
             multicast_network_level,
+            preferred_peers: preferred_peers
+                .iter()
+                .map(|preferred_peer| (preferred_peer.peer_id, preferred_peer.clone()))
+                .collect(),
+            use_preferred_peers,
             network_senders,
         }
     }
@@ -130,7 +145,11 @@ impl RequestManager {
                 warn!(LogSchema::new(LogEntry::NewPeerAlreadyExists).peer(occupied_entry.key()));
             }
             Vacant(vacant_entry) => {
-                let peer_score = if metadata.role == PeerRole::PreferredUpstream {
+                let peer_score = if metadata.role == PeerRole::PreferredUpstream
+                    || self
+                        .preferred_peers
+                        .contains_key(&vacant_entry.key().peer_id())
+                {
                     STARTING_SCORE_PREFERRED
                 } else {
                     STARTING_SCORE
@@ -185,6 +204,55 @@ impl RequestManager {
         }
     }
 
+    // Returns the weight of the peer when sampling peers: its score, scaled by its configured
+    // weight if it's a preferred peer.
+    fn peer_weight(&self, peer: &PeerNetworkId, peer_score: f64) -> f64 {
+        self.preferred_peers
+            .get(&peer.peer_id())
+            .map_or(peer_score, |preferred_peer| {
+                peer_score * preferred_peer.weight
+            })
+    }
+
+    fn is_preferred_peer(&self, peer: &PeerNetworkId) -> bool {
+        self.preferred_peers.contains_key(&peer.peer_id())
+    }
+
+    // Returns the connected preferred peers chunk requests are sent to, if any, with their weights.
+    fn eligible_preferred_peers(&self) -> Vec<(PeerNetworkId, f64)> {
+        if !self.use_preferred_peers {
+            return vec![];
+        }
+        self.peer_scores
+            .iter()
+            .filter(|(peer, _)| self.is_preferred_peer(peer))
+            .map(|(peer, peer_score)| (peer.clone(), self.peer_weight(peer, *peer_score)))
+            .collect()
+    }
+
+    // Picks one of the connected preferred peers of the highest priority, sampled by weight.
+    fn pick_preferred_peer(&self) -> Option<PeerNetworkId> {
+        let preferred_peers = self.eligible_preferred_peers();
+        let highest_priority = preferred_peers
+            .iter()
+            .map(|(peer, _)| self.preferred_peers[&peer.peer_id()].priority)
+            .min()?;
+        let (peers, weights): (Vec<_>, Vec<_>) = preferred_peers
+            .into_iter()
+            .filter(|(peer, _)| self.preferred_peers[&peer.peer_id()].priority == highest_priority)
+            .unzip();
+        let weighted_index = WeightedIndex::new(weights)
+            .map_err(|error| {
+                error!(
+                    "Failed to compute weighted index for preferred peers: {:?}",
+                    error
+                );
+                error
+            })
+            .ok();
+        pick_peer(&peers, &weighted_index)
+    }
+
     // Calculates a weighted index for each peer per network. This is used to probabilistically
     // select a peer (per network) to send a chunk request to.
     fn calculate_weighted_peers_per_network(
@@ -206,7 +274,7 @@ impl RequestManager {
                     .iter()
                     .map(|(peer, peer_score)| {
                         eligible_peers.push((*peer).clone());
-                        *peer_score
+                        self.peer_weight(peer, **peer_score)
                     })
                     .collect();
                 let weighted_index = WeightedIndex::new(weights)
@@ -223,12 +291,18 @@ impl RequestManager {
             .collect()
     }
 
-    /// Picks a set of peers to send chunk requests to. Here, we attempt to pick one peer
+    /// Picks a set of peers to send chunk requests to. If a preferred peer is connected (and the
+    /// preferred peers haven't been failed over from), only one of the preferred peers of the
+    /// highest priority is picked. Otherwise, we attempt to pick one peer
     /// per network, in order of network level preference. The set of networks selected is
     /// determined by the multicast network level. All networks with preference
     /// level <= multicast level are sampled. If there are no live peers in these networks,
     /// the multicast level is updated to the preference level of the first chosen network.
     fn pick_peers(&mut self) -> Vec<PeerNetworkId> {
+        if let Some(peer) = self.pick_preferred_peer() {
+            return vec![peer];
+        }
+
         // Calculate a weighted peer selection map per network level
         let weighted_peers_per_network = self.calculate_weighted_peers_per_network();
 
@@ -307,6 +381,114 @@ impl RequestManager {
         }
     }
 
+    /// Sends a request for a chunk of a state snapshot, to the peers picked as for chunk requests.
+    pub fn send_state_snapshot_chunk_request(
+        &mut self,
+        req: GetStateSnapshotChunkRequest,
+    ) -> Result<(), Error> {
+        let log = LogSchema::new(LogEntry::StateSnapshot);
+
+        let peers = self.pick_peers();
+        if peers.is_empty() {
+            warn!(log.event(LogEvent::MissingPeers));
+            return Err(Error::NoAvailablePeers(
+                "No peers to send state snapshot chunk request to".into(),
+            ));
+        }
+
+        let msg = StateSyncMessage::GetStateSnapshotChunkRequest(Box::new(req));
+        let mut failed_peer_sends = vec![];
+        for peer in peers {
+            let mut sender = self.get_network_sender(&peer);
+            if let Err(e) = sender.send_to(peer.peer_id(), msg.clone()) {
+                error!(log
+                    .clone()
+                    .event(LogEvent::NetworkSendError)
+                    .peer(&peer)
+                    .error(&e));
+                failed_peer_sends.push(peer);
+            }
+        }
+
+        if failed_peer_sends.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::UnexpectedError(format!(
+                "Failed to send state snapshot chunk request to: {:?}",
+                failed_peer_sends
+            )))
+        }
+    }
+
+    /// Picks up to `count` distinct peers, other than `excluded_peers`, to send parallel chunk
+    /// requests to. Peers are sampled by weight, from the connected preferred peers of any
+    /// priority if there are any, or else from the networks up to the multicast level.
+    pub fn pick_parallel_peers(
+        &mut self,
+        count: usize,
+        excluded_peers: &[PeerNetworkId],
+    ) -> Vec<PeerNetworkId> {
+        let preferred_peers = self.eligible_preferred_peers();
+        let mut eligible_peers: Vec<_> = if preferred_peers.is_empty() {
+            self.peer_scores
+                .iter()
+                .filter(|(peer, _)| peer.raw_network_id() <= self.multicast_network_level)
+                .map(|(peer, peer_score)| (peer.clone(), self.peer_weight(peer, *peer_score)))
+                .collect()
+        } else {
+            preferred_peers
+        };
+        eligible_peers.retain(|(peer, _)| !excluded_peers.contains(peer));
+
+        let mut rng = thread_rng();
+        let mut chosen_peers = vec![];
+        while chosen_peers.len() < count && !eligible_peers.is_empty() {
+            let weights = eligible_peers.iter().map(|(_, peer_weight)| *peer_weight);
+            let weighted_index = match WeightedIndex::new(weights) {
+                Ok(weighted_index) => weighted_index,
+                Err(error) => {
+                    error!(
+                        "Failed to compute weighted index for parallel peers: {:?}",
+                        error
+                    );
+                    break;
+                }
+            };
+            let (peer, _) = eligible_peers.swap_remove(weighted_index.sample(&mut rng));
+            chosen_peers.push(peer);
+        }
+        chosen_peers
+    }
+
+    /// Sends a request for a chunk ahead of the next one to the given peer.
+    pub fn send_parallel_chunk_request(
+        &mut self,
+        peer: &PeerNetworkId,
+        req: GetChunkRequest,
+    ) -> Result<(), Error> {
+        let log = LogSchema::new(LogEntry::SendParallelChunkRequest)
+            .chunk_request(req.clone())
+            .peer(peer);
+
+        let msg = StateSyncMessage::GetChunkRequest(Box::new(req));
+        let send_result = self.get_network_sender(peer).send_to(peer.peer_id(), msg);
+        let result_label = if let Err(e) = &send_result {
+            error!(log.event(LogEvent::NetworkSendError).error(e));
+            counters::SEND_FAIL_LABEL
+        } else {
+            debug!(log.event(LogEvent::Success));
+            counters::SEND_SUCCESS_LABEL
+        };
+        counters::REQUESTS_SENT
+            .with_label_values(&[
+                &peer.raw_network_id().to_string(),
+                &peer.peer_id().to_string(),
+                result_label,
+            ])
+            .inc();
+        send_result
+    }
+
     fn get_network_sender(&mut self, peer: &PeerNetworkId) -> StateSyncSender {
         self.network_senders
             .get_mut(&peer.network_id())
@@ -363,7 +545,16 @@ impl RequestManager {
         self.update_score(peer, PeerScoreUpdateType::InvalidChunkRequest);
     }
 
+    pub fn process_parallel_request_timeout(&mut self, peer: &PeerNetworkId) {
+        self.update_score(peer, PeerScoreUpdateType::TimeOut);
+    }
+
     pub fn process_success_response(&mut self, peer: &PeerNetworkId) {
+        // Send chunk requests to the preferred peers again, if they were failed over from
+        if !self.use_preferred_peers && self.is_preferred_peer(peer) {
+            self.update_use_preferred_peers(true, None);
+        }
+
         // Update the multicast level if appropriate
         let peer_network_level = peer.raw_network_id();
         if peer_network_level < self.multicast_network_level {
@@ -471,9 +662,23 @@ impl RequestManager {
             self.update_score(peer, PeerScoreUpdateType::TimeOut);
         }
 
-        // Increase the multicast network level if this request has also hit a multicast timeout
+        // Increase the multicast network level if this request has also hit a multicast timeout.
+        // If the request was sent to the preferred peers, fail over from them first.
         let multicast_start_time = self.get_multicast_start_time(version).unwrap_or(UNIX_EPOCH);
-        if is_timeout(multicast_start_time, self.multicast_timeout) {
+        if !is_timeout(multicast_start_time, self.multicast_timeout) {
+            return Ok(timeout);
+        }
+        let sent_to_preferred_peers = self.use_preferred_peers
+            && peers_to_penalize
+                .iter()
+                .all(|peer| self.is_preferred_peer(peer));
+        if sent_to_preferred_peers {
+            self.update_use_preferred_peers(false, Some(version));
+            // Restart the multicast timer, for the other peers to get a multicast timeout too
+            if let Some(prev_request) = self.requests.get_mut(&version) {
+                prev_request.multicast_start_time = SystemTime::now();
+            }
+        } else {
             // Move to the next multicast network level
             let new_multicast_network_level = match self.multicast_network_level {
                 NetworkId::Validator => NetworkId::vfn_network(),
@@ -498,6 +703,27 @@ impl RequestManager {
         self.peer_scores.contains_key(peer)
     }
 
+    fn update_use_preferred_peers(
+        &mut self,
+        use_preferred_peers: bool,
+        request_version: Option<u64>,
+    ) {
+        self.use_preferred_peers = use_preferred_peers;
+
+        // Update the counters and logs
+        counters::PREFERRED_PEERS_IN_USE.set(use_preferred_peers as i64);
+        let log_event = if use_preferred_peers {
+            LogEvent::Recover
+        } else {
+            LogEvent::Failover
+        };
+        let mut log_event = LogSchema::event_log(LogEntry::PreferredPeers, log_event);
+        if let Some(version) = request_version {
+            log_event = log_event.request_version(version);
+        }
+        info!(log_event);
+    }
+
     fn update_multicast_network_level(
         &mut self,
         new_level: NetworkId,
@@ -567,6 +793,7 @@ fn update_multicast_network_counter(multicast_network_level: NetworkId) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
 
     const NUM_CHUNKS_TO_PROCESS: u64 = 50;
     const NUM_PICKS_TO_MAKE: u64 = 1000;
@@ -632,6 +859,56 @@ mod tests {
         verify_validator_picked_least_often(&mut request_manager, &validators, 0);
     }
 
+    #[test]
+    fn test_score_parallel_request_timeout() {
+        let (mut request_manager, validators) = generate_request_manager_and_validators(0, 4);
+
+        // Process multiple parallel request timeouts from validator 0
+        for _ in 0..NUM_CHUNKS_TO_PROCESS {
+            request_manager.process_parallel_request_timeout(&validators[0]);
+        }
+
+        // Verify validator 0 is chosen less often than the other validators
+        verify_validator_picked_least_often(&mut request_manager, &validators, 0);
+    }
+
+    #[test]
+    fn test_pick_parallel_peers() {
+        let (mut request_manager, validators) = generate_request_manager_and_validators(0, 4);
+
+        // Verify distinct peers are picked, up to the number of eligible peers
+        for count in 0..6 {
+            let picked_peers = request_manager.pick_parallel_peers(count, &[]);
+            assert_eq!(picked_peers.len(), count.min(validators.len()));
+            let unique_peers: HashSet<_> = picked_peers.iter().collect();
+            assert_eq!(unique_peers.len(), count.min(validators.len()));
+        }
+
+        // Verify excluded peers are never picked
+        let excluded_peers = vec![validators[0].clone(), validators[1].clone()];
+        for _ in 0..NUM_PICKS_TO_MAKE {
+            let picked_peers = request_manager.pick_parallel_peers(2, &excluded_peers);
+            assert_eq!(picked_peers.len(), 2);
+            assert!(picked_peers
+                .iter()
+                .all(|peer| !excluded_peers.contains(peer)));
+        }
+
+        // Verify peers on networks above the multicast level are not picked
+        let public_peer = PeerNetworkId::random();
+        let connection_metadata = ConnectionMetadata::mock_with_role_and_origin(
+            public_peer.peer_id(),
+            PeerRole::Unknown,
+            ConnectionOrigin::Outbound,
+        );
+        request_manager
+            .enable_peer(public_peer.clone(), connection_metadata)
+            .unwrap();
+        let picked_peers = request_manager.pick_parallel_peers(10, &[]);
+        assert_eq!(picked_peers.len(), validators.len());
+        assert!(!picked_peers.contains(&public_peer));
+    }
+
     #[test]
     fn test_score_chunk_version_mismatch() {
         let (mut request_manager, validators) = generate_request_manager_and_validators(0, 4);
@@ -753,6 +1030,82 @@ mod tests {
         verify_validator_picked_most_often(&mut request_manager, &validators, 0);
     }
 
+    #[test]
+    fn test_pick_preferred_peers() {
+        let validators: Vec<_> = (0..4).map(|_| PeerNetworkId::random_validator()).collect();
+        let mut request_manager = generate_request_manager_with_preferred_peers(&[
+            (&validators[1], 0, 1.0),
+            (&validators[2], 1, 1.0),
+        ]);
+        for validator in &validators {
+            add_validator_to_request_manager(&mut request_manager, validator, PeerRole::Validator);
+        }
+
+        // Verify only the connected preferred peer of the highest priority is picked
+        let pick_counts = calculate_pick_counts_for_validators(&mut request_manager, 100);
+        assert_eq!(pick_counts.get(&validators[1]), Some(&100));
+        request_manager.disable_peer(&validators[1]).unwrap();
+        let pick_counts = calculate_pick_counts_for_validators(&mut request_manager, 100);
+        assert_eq!(pick_counts.get(&validators[2]), Some(&100));
+
+        // Verify parallel peers are picked from the preferred peers only
+        let picked_peers = request_manager.pick_parallel_peers(3, &[]);
+        assert_eq!(picked_peers, vec![validators[2].clone()]);
+
+        // Verify the other peers are picked once no preferred peer is connected
+        request_manager.disable_peer(&validators[2]).unwrap();
+        let pick_counts = calculate_pick_counts_for_validators(&mut request_manager, 100);
+        assert!(pick_counts.contains_key(&validators[0]));
+        assert!(pick_counts.contains_key(&validators[3]));
+        assert_eq!(request_manager.pick_parallel_peers(3, &[]).len(), 2);
+    }
+
+    #[test]
+    fn test_preferred_peer_weights() {
+        let validators: Vec<_> = (0..3).map(|_| PeerNetworkId::random_validator()).collect();
+        let mut request_manager = generate_request_manager_with_preferred_peers(&[
+            (&validators[0], 0, 10.0),
+            (&validators[1], 0, 1.0),
+        ]);
+        for validator in &validators {
+            add_validator_to_request_manager(&mut request_manager, validator, PeerRole::Validator);
+        }
+
+        // Verify the peers are picked by weight among the preferred peers
+        let pick_counts =
+            calculate_pick_counts_for_validators(&mut request_manager, NUM_PICKS_TO_MAKE);
+        assert!(pick_counts.get(&validators[0]) > pick_counts.get(&validators[1]));
+        assert!(!pick_counts.contains_key(&validators[2]));
+    }
+
+    #[test]
+    fn test_preferred_peers_failover() {
+        let validators: Vec<_> = (0..2).map(|_| PeerNetworkId::random_validator()).collect();
+        let mut request_manager =
+            generate_request_manager_with_preferred_peers(&[(&validators[0], 0, 1.0)]);
+        for validator in &validators {
+            add_validator_to_request_manager(&mut request_manager, validator, PeerRole::Validator);
+        }
+        assert_eq!(request_manager.pick_peers(), vec![validators[0].clone()]);
+
+        // Verify the other peers are picked once the preferred peer times out
+        request_manager.add_request(1, vec![validators[0].clone()]);
+        assert!(request_manager.has_request_timed_out(1).unwrap());
+        assert!(!request_manager.use_preferred_peers);
+        assert_eq!(
+            request_manager.multicast_network_level,
+            NetworkId::Validator
+        );
+        let pick_counts =
+            calculate_pick_counts_for_validators(&mut request_manager, NUM_PICKS_TO_MAKE);
+        assert!(pick_counts.contains_key(&validators[1]));
+
+        // Verify the preferred peer is picked again once it sends a chunk
+        request_manager.process_success_response(&validators[0]);
+        assert!(request_manager.use_preferred_peers);
+        assert_eq!(request_manager.pick_peers(), vec![validators[0].clone()]);
+    }
+
     #[test]
     fn test_remove_requests() {
         let (mut request_manager, validators) = generate_request_manager_and_validators(0, 2);
@@ -866,6 +1219,28 @@ mod tests {
         RequestManager::new(
             Duration::from_secs(request_timeout),
             Duration::from_secs(30),
+            &[],
+            HashMap::new(),
+        )
+    }
+
+    /// Generates a new request manager with the given preferred peers, which times out
+    /// requests (and multicasts them) immediately.
+    fn generate_request_manager_with_preferred_peers(
+        preferred_peers: &[(&PeerNetworkId, u32, f64)],
+    ) -> RequestManager {
+        let preferred_peers: Vec<_> = preferred_peers
+            .iter()
+            .map(|(peer, priority, weight)| PreferredPeerConfig {
+                peer_id: peer.peer_id(),
+                priority: *priority,
+                weight: *weight,
+            })
+            .collect();
+        RequestManager::new(
+            Duration::from_secs(0),
+            Duration::from_secs(0),
+            &preferred_peers,
             HashMap::new(),
         )
     }
@@ -68,6 +68,10 @@ impl SyncState {
         self.trusted_epoch_state.epoch
     }
 
+    pub fn trusted_epoch_state(&self) -> &EpochState {
+        &self.trusted_epoch_state
+    }
+
     pub fn verify_ledger_info(&self, ledger_info: &LedgerInfoWithSignatures) -> Result<(), Error> {
         self.trusted_epoch_state
             .verify(ledger_info)
@@ -79,6 +83,7 @@ impl SyncState {
 pub(crate) mod test_utils {
     use crate::{
         coordinator::StateSyncCoordinator,
+        error::Error,
         executor_proxy::{ExecutorProxy, ExecutorProxyTrait},
         network::StateSyncSender,
     };
@@ -91,7 +96,7 @@ pub(crate) mod test_utils {
     };
     use diem_types::transaction::{Transaction, WriteSetPayload};
     use diem_vm::DiemVM;
-    use diemdb::DiemDB;
+    use diemdb::{DiemDB, PrunerOptions};
     use executor::Executor;
     use executor_test_helpers::bootstrap_genesis;
     use futures::channel::mpsc;
@@ -101,6 +106,7 @@ pub(crate) mod test_utils {
     };
     use std::collections::HashMap;
     use storage_interface::DbReaderWriter;
+    use subscription_service::ReconfigNotificationBus;
 
     #[cfg(test)]
     pub(crate) fn create_coordinator_with_config_and_waypoint(
@@ -133,18 +139,26 @@ pub(crate) mod test_utils {
         let (genesis, _) = vm_genesis::test_genesis_change_set_and_validators(Some(1));
 
         // Create test diem database
-        let db_path = diem_temppath::TempPath::new();
-        db_path.create_as_dir().unwrap();
-        let (db, db_rw) = DbReaderWriter::wrap(DiemDB::new_for_test(db_path.path()));
+        let (_, db_rw) = DbReaderWriter::wrap(DiemDB::new_in_memory(PrunerOptions::default()));
 
         // Bootstrap the genesis transaction
         let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
         bootstrap_genesis::<DiemVM>(&db_rw, &genesis_txn).unwrap();
 
         // Create executor proxy
-        let chunk_executor = Box::new(Executor::<DiemVM>::new(db_rw));
-        let executor_proxy = ExecutorProxy::new(db, chunk_executor, vec![]);
+        let chunk_executor = Box::new(Executor::<DiemVM>::new(db_rw.clone()));
+        let executor_proxy =
+            ExecutorProxy::new(db_rw, chunk_executor, ReconfigNotificationBus::new());
+
+        create_coordinator_with_executor_proxy(node_config, waypoint, executor_proxy).unwrap()
+    }
 
+    /// Creates a coordinator starting from the storage of `executor_proxy`, as a node restarting.
+    pub(crate) fn create_coordinator_with_executor_proxy(
+        node_config: NodeConfig,
+        waypoint: Waypoint,
+        executor_proxy: ExecutorProxy,
+    ) -> Result<StateSyncCoordinator<ExecutorProxy>, Error> {
         // Get initial state
         let initial_state = executor_proxy.get_local_storage_state().unwrap();
 
@@ -174,6 +188,5 @@ pub(crate) mod test_utils {
             executor_proxy,
             initial_state,
         )
-        .unwrap()
     }
 }
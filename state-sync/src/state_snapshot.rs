@@ -0,0 +1,263 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fast sync of a node behind its waypoint: instead of executing all the transactions up to the
+//! waypoint version, the node downloads the state at that version from its peers, chunk by chunk,
+//! verifies it against the waypoint, and only executes the transactions after it.
+
+use diem_crypto::HashValue;
+use diem_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+use storage_interface::{StateSnapshotChunk, StateSnapshotInfo};
+
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GetStateSnapshotChunkRequest {
+    /// The version of the snapshot, which must end an epoch.
+    pub version: Version,
+    /// The response should start with the first account after this key, or with the very first
+    /// account if it's `None`.
+    pub start_after: Option<HashValue>,
+    /// Max number of accounts in the response.
+    pub limit: u64,
+}
+
+impl fmt::Debug for GetStateSnapshotChunkRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl fmt::Display for GetStateSnapshotChunkRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[StateSnapshotChunkRequest: version: {}, start after: {:?}, limit: {}]",
+            self.version, self.start_after, self.limit,
+        )
+    }
+}
+
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GetStateSnapshotChunkResponse {
+    pub version: Version,
+    pub start_after: Option<HashValue>,
+    /// The ledger info, transaction info and accumulator of the snapshot, only carried by the
+    /// response for the first chunk.
+    pub snapshot_info: Option<StateSnapshotInfo>,
+    pub chunk: StateSnapshotChunk,
+}
+
+impl fmt::Debug for GetStateSnapshotChunkResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl fmt::Display for GetStateSnapshotChunkResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[StateSnapshotChunkResponse: version: {}, start after: {:?}, accounts: {}, last: {}]",
+            self.version,
+            self.start_after,
+            self.chunk.accounts.len(),
+            self.chunk.is_last,
+        )
+    }
+}
+
+/// Progress of the download of the state snapshot at the waypoint version.
+pub(crate) struct StateSnapshotDownload {
+    version: Version,
+    /// The snapshot, once verified with the first chunk.
+    snapshot_info: Option<StateSnapshotInfo>,
+    /// The key of the last account restored.
+    last_key: Option<HashValue>,
+    num_accounts: usize,
+    last_request_time: Option<SystemTime>,
+}
+
+impl StateSnapshotDownload {
+    pub fn new(version: Version) -> Self {
+        Self {
+            version,
+            snapshot_info: None,
+            last_key: None,
+            num_accounts: 0,
+            last_request_time: None,
+        }
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn snapshot_info(&self) -> Option<&StateSnapshotInfo> {
+        self.snapshot_info.as_ref()
+    }
+
+    pub fn last_key(&self) -> Option<HashValue> {
+        self.last_key
+    }
+
+    pub fn num_accounts(&self) -> usize {
+        self.num_accounts
+    }
+
+    /// The request for the next chunk to restore.
+    pub fn next_request(&self, limit: u64) -> GetStateSnapshotChunkRequest {
+        GetStateSnapshotChunkRequest {
+            version: self.version,
+            start_after: self.last_key,
+            limit,
+        }
+    }
+
+    /// Whether the request for the next chunk should be sent, none having been sent within
+    /// `timeout`.
+    pub fn should_request(&self, timeout: Duration) -> bool {
+        match self.last_request_time {
+            None => true,
+            Some(last_request_time) => SystemTime::now()
+                .duration_since(last_request_time)
+                .map_or(false, |elapsed| elapsed >= timeout),
+        }
+    }
+
+    pub fn mark_requested(&mut self) {
+        self.last_request_time = Some(SystemTime::now());
+    }
+
+    /// Whether `response` carries the next chunk to restore. Others answer earlier requests, sent
+    /// again on timeouts or to several peers.
+    pub fn is_next_chunk(&self, response: &GetStateSnapshotChunkResponse) -> bool {
+        response.version == self.version
+            && response.start_after == self.last_key
+            && (self.snapshot_info.is_some() || response.snapshot_info.is_some())
+    }
+
+    /// Starts restoring the verified snapshot.
+    pub fn start(&mut self, snapshot_info: StateSnapshotInfo) {
+        self.snapshot_info = Some(snapshot_info);
+    }
+
+    /// Records that `chunk` was restored, so the next chunk starts after it.
+    pub fn chunk_added(&mut self, chunk: &StateSnapshotChunk) {
+        if let Some((key, _)) = chunk.accounts.last() {
+            self.last_key = Some(*key);
+        }
+        self.num_accounts += chunk.accounts.len();
+        self.last_request_time = None;
+    }
+
+    /// Starts the download over, from the first chunk, as a bad chunk may have left the
+    /// restoration half way through.
+    pub fn restart(&mut self) {
+        *self = Self::new(self.version);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diem_crypto::hash::SPARSE_MERKLE_PLACEHOLDER_HASH;
+    use diem_types::{
+        account_state_blob::AccountStateBlob,
+        block_info::BlockInfo,
+        ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+        proof::{SparseMerkleRangeProof, TransactionAccumulatorProof, TransactionInfoWithProof},
+        transaction::TransactionInfo,
+        vm_status::KeptVMStatus,
+    };
+    use std::collections::BTreeMap;
+
+    fn create_snapshot_info(version: Version) -> StateSnapshotInfo {
+        let block_info =
+            BlockInfo::new(0, 0, HashValue::zero(), HashValue::zero(), version, 0, None);
+        let ledger_info = LedgerInfo::new(block_info, HashValue::zero());
+        let txn_info = TransactionInfo::new(
+            HashValue::zero(),
+            *SPARSE_MERKLE_PLACEHOLDER_HASH,
+            HashValue::zero(),
+            0,
+            KeptVMStatus::Executed,
+        );
+        StateSnapshotInfo {
+            ledger_info: LedgerInfoWithSignatures::new(ledger_info, BTreeMap::new()),
+            txn_info_with_proof: TransactionInfoWithProof::new(
+                TransactionAccumulatorProof::new(vec![]),
+                txn_info,
+            ),
+            frozen_subtrees: vec![],
+        }
+    }
+
+    fn create_response(
+        version: Version,
+        start_after: Option<HashValue>,
+        keys: &[HashValue],
+        with_info: bool,
+    ) -> GetStateSnapshotChunkResponse {
+        GetStateSnapshotChunkResponse {
+            version,
+            start_after,
+            snapshot_info: if with_info {
+                Some(create_snapshot_info(version))
+            } else {
+                None
+            },
+            chunk: StateSnapshotChunk {
+                accounts: keys
+                    .iter()
+                    .map(|key| (*key, AccountStateBlob::from(vec![0])))
+                    .collect(),
+                proof: SparseMerkleRangeProof::new(vec![]),
+                is_last: false,
+            },
+        }
+    }
+
+    #[test]
+    fn test_download_progress() {
+        let version = 10;
+        let mut download = StateSnapshotDownload::new(version);
+        assert!(download.should_request(Duration::from_secs(60)));
+        assert_eq!(download.next_request(5).start_after, None);
+        download.mark_requested();
+        assert!(!download.should_request(Duration::from_secs(60)));
+        assert!(download.should_request(Duration::from_secs(0)));
+
+        // The first chunk must carry the snapshot, for the version of the download.
+        let keys = [HashValue::random(), HashValue::random()];
+        assert!(!download.is_next_chunk(&create_response(version, None, &keys, false)));
+        assert!(!download.is_next_chunk(&create_response(version + 1, None, &keys, true)));
+        let first = create_response(version, None, &keys, true);
+        assert!(download.is_next_chunk(&first));
+        download.start(first.snapshot_info.clone().unwrap());
+        download.chunk_added(&first.chunk);
+        assert_eq!(download.last_key(), Some(keys[1]));
+        assert_eq!(download.num_accounts(), 2);
+        assert!(download.should_request(Duration::from_secs(60)));
+        assert_eq!(download.next_request(5).start_after, Some(keys[1]));
+
+        // Later chunks start after the last key restored, and don't need to carry the snapshot.
+        let next_keys = [HashValue::random()];
+        assert!(!download.is_next_chunk(&first));
+        assert!(download.is_next_chunk(&create_response(
+            version,
+            Some(keys[1]),
+            &next_keys,
+            false
+        )));
+
+        download.restart();
+        assert!(download.snapshot_info().is_none());
+        assert_eq!(download.last_key(), None);
+        assert_eq!(download.num_accounts(), 0);
+        assert!(download.is_next_chunk(&first));
+    }
+}
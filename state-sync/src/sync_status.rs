@@ -0,0 +1,189 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The progress of state sync as reported to operators (e.g., by the JSON-RPC admin routes): how
+//! far the node is behind, how fast it's syncing and when it should catch up.
+
+use diem_infallible::RwLock;
+use diem_types::transaction::Version;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// The sync rate is measured over this window.
+const SYNC_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// A snapshot of the progress of state sync.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyncStatus {
+    /// The highest version known to be committed, by the waypoint, the sync target or the ledger
+    /// infos received from peers.
+    pub highest_known_version: Version,
+    /// The highest version in local storage, even if it's not covered by a ledger info yet.
+    pub synced_version: Version,
+    /// The version of the latest ledger info in local storage.
+    pub committed_version: Version,
+    /// Whether the node synced to its waypoint.
+    pub initialized: bool,
+    /// Transactions synced per second, over the last minute.
+    pub sync_rate: f64,
+    /// How long it should take to sync to the highest known version at the current rate, if the
+    /// node is syncing at all.
+    pub estimated_catch_up_time: Option<Duration>,
+}
+
+/// Read-only access to the progress of state sync, for clients such as JSON-RPC.
+pub trait SyncStatusReader: Send + Sync {
+    fn get_sync_status(&self) -> SyncStatus;
+}
+
+/// The progress of state sync, updated by the coordinator as it commits.
+#[derive(Debug, Default)]
+pub(crate) struct SyncProgress {
+    highest_known_version: Version,
+    synced_version: Version,
+    committed_version: Version,
+    initialized: bool,
+    // The synced versions over the sync rate window and the last one before it, oldest first
+    samples: VecDeque<(Instant, Version)>,
+}
+
+impl SyncProgress {
+    pub fn update(
+        &mut self,
+        now: Instant,
+        highest_known_version: Version,
+        synced_version: Version,
+        committed_version: Version,
+        initialized: bool,
+    ) {
+        self.highest_known_version = highest_known_version;
+        self.synced_version = synced_version;
+        self.committed_version = committed_version;
+        self.initialized = initialized;
+
+        // Only the last sample at the start of the window or before it is needed
+        self.samples.push_back((now, synced_version));
+        while self
+            .samples
+            .get(1)
+            .map_or(false, |(time, _)| is_outside_window(now, *time))
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn status(&self, now: Instant) -> SyncStatus {
+        let sync_rate = self.sync_rate(now);
+        let highest_known_version = self.highest_known_version.max(self.synced_version);
+        let remaining = highest_known_version - self.synced_version;
+        let estimated_catch_up_time = if remaining == 0 {
+            Some(Duration::from_secs(0))
+        } else if sync_rate > 0.0 {
+            Some(Duration::from_secs_f64(remaining as f64 / sync_rate))
+        } else {
+            None
+        };
+
+        SyncStatus {
+            highest_known_version,
+            synced_version: self.synced_version,
+            committed_version: self.committed_version,
+            initialized: self.initialized,
+            sync_rate,
+            estimated_catch_up_time,
+        }
+    }
+
+    /// The transactions synced per second since the last sample at the start of the window or
+    /// before it, or since the first sample if there's no such sample yet.
+    fn sync_rate(&self, now: Instant) -> f64 {
+        let (start_time, start_version) = match self
+            .samples
+            .iter()
+            .rev()
+            .find(|(time, _)| is_outside_window(now, *time))
+            .or_else(|| self.samples.front())
+        {
+            Some(sample) => *sample,
+            None => return 0.0,
+        };
+        let elapsed = now.saturating_duration_since(start_time).as_secs_f64();
+        if elapsed > 0.0 {
+            self.synced_version.saturating_sub(start_version) as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+}
+
+fn is_outside_window(now: Instant, time: Instant) -> bool {
+    now.saturating_duration_since(time) >= SYNC_RATE_WINDOW
+}
+
+impl SyncStatusReader for RwLock<SyncProgress> {
+    fn get_sync_status(&self) -> SyncStatus {
+        self.read().status(Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_rate_and_catch_up_time() {
+        let start = Instant::now();
+        let mut progress = SyncProgress::default();
+
+        // Nothing synced yet: no rate, no estimate
+        progress.update(start, 1000, 0, 0, false);
+        let status = progress.status(start);
+        assert_eq!(status.sync_rate, 0.0);
+        assert_eq!(status.estimated_catch_up_time, None);
+        assert!(!status.initialized);
+
+        // 100 transactions per second
+        progress.update(start + Duration::from_secs(2), 1000, 200, 150, false);
+        let status = progress.status(start + Duration::from_secs(2));
+        assert!((status.sync_rate - 100.0).abs() < 1e-9);
+        assert_eq!(status.estimated_catch_up_time, Some(Duration::from_secs(8)));
+        assert_eq!(status.synced_version, 200);
+        assert_eq!(status.committed_version, 150);
+
+        // The rate drops while no progress is made
+        let status = progress.status(start + Duration::from_secs(4));
+        assert!((status.sync_rate - 50.0).abs() < 1e-9);
+
+        // Caught up
+        progress.update(start + Duration::from_secs(5), 1000, 1000, 1000, true);
+        let status = progress.status(start + Duration::from_secs(5));
+        assert_eq!(status.estimated_catch_up_time, Some(Duration::from_secs(0)));
+        assert!(status.initialized);
+    }
+
+    #[test]
+    fn test_sync_rate_window() {
+        let start = Instant::now();
+        let mut progress = SyncProgress::default();
+        progress.update(start, 10_000, 0, 0, true);
+        progress.update(start + Duration::from_secs(10), 10_000, 5000, 5000, true);
+
+        // Only the samples within the window and the last one before it are kept
+        let later = start + SYNC_RATE_WINDOW + Duration::from_secs(20);
+        progress.update(later, 10_000, 6000, 6000, true);
+        assert_eq!(progress.samples.len(), 2);
+
+        // The rate is measured since the last sample before the window
+        let status = progress.status(later);
+        assert!((status.sync_rate - 1000.0 / 70.0).abs() < 1e-9);
+        let status = progress.status(later + Duration::from_secs(10));
+        assert!((status.sync_rate - 12.5).abs() < 1e-9);
+
+        // Once the node hasn't synced anything for the whole window, the rate is 0
+        let status = progress.status(later + SYNC_RATE_WINDOW);
+        assert_eq!(status.sync_rate, 0.0);
+        assert_eq!(status.estimated_catch_up_time, None);
+    }
+}
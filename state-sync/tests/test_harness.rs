@@ -30,6 +30,7 @@ use diem_types::{
     test_helpers::transaction_test_helpers::get_test_signed_txn,
     transaction::{
         authenticator::AuthenticationKey, SignedTransaction, Transaction, TransactionListWithProof,
+        Version,
     },
     validator_config::ValidatorConfig,
     validator_info::ValidatorInfo,
@@ -72,6 +73,7 @@ use std::{
     ops::DerefMut,
     sync::Arc,
 };
+use storage_interface::{StateSnapshotChunk, StateSnapshotInfo};
 use tokio::runtime::Runtime;
 use vm_genesis::GENESIS_KEYPAIR;
 
@@ -884,4 +886,39 @@ impl ExecutorProxyTrait for MockExecutorProxy {
     ) -> Result<(), Error> {
         Ok(())
     }
+
+    fn get_state_snapshot_info(&self, _version: Version) -> Result<StateSnapshotInfo, Error> {
+        Err(Error::UnexpectedError(
+            "State snapshots are not supported by the mock storage".into(),
+        ))
+    }
+
+    fn get_state_snapshot_chunk(
+        &self,
+        _version: Version,
+        _start_after: Option<HashValue>,
+        _limit: u64,
+    ) -> Result<StateSnapshotChunk, Error> {
+        Err(Error::UnexpectedError(
+            "State snapshots are not supported by the mock storage".into(),
+        ))
+    }
+
+    fn start_state_snapshot(&mut self, _snapshot_info: &StateSnapshotInfo) -> Result<(), Error> {
+        Err(Error::UnexpectedError(
+            "State snapshots are not supported by the mock storage".into(),
+        ))
+    }
+
+    fn add_state_snapshot_chunk(&mut self, _chunk: StateSnapshotChunk) -> Result<(), Error> {
+        Err(Error::UnexpectedError(
+            "State snapshots are not supported by the mock storage".into(),
+        ))
+    }
+
+    fn finish_state_snapshot(&mut self, _snapshot_info: &StateSnapshotInfo) -> Result<(), Error> {
+        Err(Error::UnexpectedError(
+            "State snapshots are not supported by the mock storage".into(),
+        ))
+    }
 }
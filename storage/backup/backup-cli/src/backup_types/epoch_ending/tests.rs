@@ -83,6 +83,7 @@ fn end_to_end() {
                 trusted_waypoints: TrustedWaypointOpt::default(),
                 rocksdb_opt: RocksdbOpt::default(),
                 concurernt_downloads: ConcurrentDownloadsOpt::default(),
+                concurrency: None,
             }
             .try_into()
             .unwrap(),
@@ -220,6 +221,7 @@ async fn test_trusted_waypoints_impl(
             trusted_waypoints: TrustedWaypointOpt::default(),
             rocksdb_opt: RocksdbOpt::default(),
             concurernt_downloads: ConcurrentDownloadsOpt::default(),
+            concurrency: None,
         }
         .try_into()
         .unwrap(),
@@ -240,6 +242,7 @@ async fn test_trusted_waypoints_impl(
             },
             rocksdb_opt: RocksdbOpt::default(),
             concurernt_downloads: ConcurrentDownloadsOpt::default(),
+            concurrency: None,
         }
         .try_into()
         .unwrap(),
@@ -4,6 +4,7 @@
 use crate::{
     backup_types::state_snapshot::manifest::{StateSnapshotBackup, StateSnapshotChunk},
     metadata::Metadata,
+    metrics::backup::{STATE_SNAPSHOT_CHUNKS_REUSED, STATE_SNAPSHOT_CHUNKS_WRITTEN},
     storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
     utils::{
         backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
@@ -19,7 +20,7 @@ use diem_types::{
     proof::TransactionInfoWithProof, transaction::Version,
 };
 use once_cell::sync::Lazy;
-use std::{convert::TryInto, str::FromStr, sync::Arc};
+use std::{collections::HashMap, convert::TryInto, str::FromStr, sync::Arc};
 use structopt::StructOpt;
 use tokio::io::AsyncWriteExt;
 
@@ -30,10 +31,17 @@ pub struct StateSnapshotBackupOpt {
         help = "Version at which a state snapshot to be taken."
     )]
     pub version: Version,
+    #[structopt(
+        long = "base-manifest",
+        help = "Manifest of an earlier state snapshot in the same storage. Chunks identical to one \
+        of its chunks are not uploaded again but refer to it."
+    )]
+    pub base_manifest: Option<FileHandle>,
 }
 
 pub struct StateSnapshotBackupController {
     version: Version,
+    base_manifest: Option<FileHandle>,
     max_chunk_size: usize,
     client: Arc<BackupServiceClient>,
     storage: Arc<dyn BackupStorage>,
@@ -48,6 +56,7 @@ impl StateSnapshotBackupController {
     ) -> Self {
         Self {
             version: opt.version,
+            base_manifest: opt.base_manifest,
             max_chunk_size: global_opt.max_chunk_size,
             client,
             storage,
@@ -72,6 +81,7 @@ impl StateSnapshotBackupController {
             .storage
             .create_backup_with_random_suffix(&self.backup_name())
             .await?;
+        let base_chunks = self.load_base_chunks().await?;
 
         let mut chunks = vec![];
 
@@ -83,42 +93,48 @@ impl StateSnapshotBackupController {
         let mut chunk_bytes = (prev_record_bytes.len() as u32).to_be_bytes().to_vec();
         chunk_bytes.extend(&prev_record_bytes);
         let mut chunk_first_key = Self::parse_key(&prev_record_bytes)?;
+        let mut prev_key = chunk_first_key;
         let mut current_idx: usize = 0;
         let mut chunk_first_idx: usize = 0;
 
         while let Some(record_bytes) = state_snapshot_file.read_record_bytes().await? {
-            if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
+            let key = Self::parse_key(&record_bytes)?;
+            if Self::is_chunk_boundary(&prev_key)
+                || should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size)
+            {
                 let chunk = self
                     .write_chunk(
                         &backup_handle,
+                        &base_chunks,
                         &chunk_bytes,
                         chunk_first_idx,
                         current_idx,
                         chunk_first_key,
-                        Self::parse_key(&prev_record_bytes)?,
+                        prev_key,
                     )
                     .await?;
                 chunks.push(chunk);
                 chunk_bytes = vec![];
                 chunk_first_idx = current_idx + 1;
-                chunk_first_key = Self::parse_key(&record_bytes)?;
+                chunk_first_key = key;
             }
 
             current_idx += 1;
             chunk_bytes.extend(&(record_bytes.len() as u32).to_be_bytes());
             chunk_bytes.extend(&record_bytes);
-            prev_record_bytes = record_bytes;
+            prev_key = key;
         }
 
         assert!(!chunk_bytes.is_empty());
         let chunk = self
             .write_chunk(
                 &backup_handle,
+                &base_chunks,
                 &chunk_bytes,
                 chunk_first_idx,
                 current_idx,
                 chunk_first_key,
-                Self::parse_key(&prev_record_bytes)?,
+                prev_key,
             )
             .await?;
         chunks.push(chunk);
@@ -159,21 +175,62 @@ impl StateSnapshotBackupController {
         Ok(key)
     }
 
+    /// Whether a chunk ends with the account of `key`, regardless of the chunk size.
+    ///
+    /// Cutting chunks at accounts picked by their keys, rather than by the sizes of the accounts
+    /// before them, means a changed account only changes the chunk it's in, so consecutive
+    /// snapshots share most of their chunks. One in 4096 accounts ends a chunk.
+    fn is_chunk_boundary(key: &HashValue) -> bool {
+        const BOUNDARY_BITS: u32 = 12;
+        u16::from_be_bytes([key[0], key[1]]).leading_zeros() >= BOUNDARY_BITS
+    }
+
+    /// Loads the chunks of the base manifest, if any, by the hashes of their content. Chunks
+    /// backed up before content hashes were recorded can't be reused.
+    async fn load_base_chunks(&self) -> Result<HashMap<HashValue, FileHandle>> {
+        Ok(match &self.base_manifest {
+            Some(base_manifest) => {
+                let manifest: StateSnapshotBackup =
+                    self.storage.load_json_file(base_manifest).await?;
+                manifest
+                    .chunks
+                    .into_iter()
+                    .filter_map(|chunk| chunk.blobs_hash.map(|hash| (hash, chunk.blobs)))
+                    .collect()
+            }
+            None => HashMap::new(),
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn write_chunk(
         &self,
         backup_handle: &BackupHandleRef,
+        base_chunks: &HashMap<HashValue, FileHandle>,
         chunk_bytes: &[u8],
         first_idx: usize,
         last_idx: usize,
         first_key: HashValue,
         last_key: HashValue,
     ) -> Result<StateSnapshotChunk> {
-        let (chunk_handle, mut chunk_file) = self
-            .storage
-            .create_for_write(backup_handle, &Self::chunk_name(first_idx))
-            .await?;
-        chunk_file.write_all(&chunk_bytes).await?;
-        chunk_file.shutdown().await?;
+        let blobs_hash = HashValue::sha3_256_of(chunk_bytes);
+        let chunk_handle = match base_chunks.get(&blobs_hash) {
+            Some(chunk_handle) => {
+                STATE_SNAPSHOT_CHUNKS_REUSED.inc();
+                chunk_handle.clone()
+            }
+            None => {
+                let (chunk_handle, mut chunk_file) = self
+                    .storage
+                    .create_for_write(backup_handle, &Self::chunk_name(first_idx))
+                    .await?;
+                chunk_file.write_all(&chunk_bytes).await?;
+                chunk_file.shutdown().await?;
+                STATE_SNAPSHOT_CHUNKS_WRITTEN.inc();
+                chunk_handle
+            }
+        };
+        // The proof is against the root hash of this snapshot, so it's never reused.
         let (proof_handle, mut proof_file) = self
             .storage
             .create_for_write(backup_handle, &Self::chunk_proof_name(first_idx, last_idx))
@@ -194,6 +251,7 @@ impl StateSnapshotBackupController {
             first_key,
             last_key,
             blobs: chunk_handle,
+            blobs_hash: Some(blobs_hash),
             proof: proof_handle,
         })
     }
@@ -19,8 +19,13 @@ pub struct StateSnapshotChunk {
     /// key of the last account in this chunk.
     pub last_key: HashValue,
     /// Repeated `len(record) + record` where `record` is BCS serialized tuple
-    /// `(key, account_state_blob)`
+    /// `(key, account_state_blob)`. The file might belong to an earlier backup which happens to
+    /// have a chunk of the same content.
     pub blobs: FileHandle,
+    /// SHA3-256 hash of the content of `blobs`. Absent from backups taken by earlier versions of
+    /// the tool.
+    #[serde(default)]
+    pub blobs_hash: Option<HashValue>,
     /// BCS serialized `SparseMerkleRangeProof` that proves this chunk adds up to the root hash
     /// indicated in the backup (`StateSnapshotBackup::root_hash`).
     pub proof: FileHandle,
@@ -16,17 +16,20 @@ use crate::{
     },
     storage::{BackupStorage, FileHandle},
     utils::{
-        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOptions,
-        RestoreRunMode,
+        progress::ProgressReporter, read_record_bytes::ReadRecordBytes,
+        storage_ext::BackupStorageExt, stream::StreamX, GlobalRestoreOptions, RestoreRunMode,
     },
 };
 use anyhow::{anyhow, ensure, Result};
 use diem_crypto::HashValue;
 use diem_logger::prelude::*;
 use diem_types::{
-    account_state_blob::AccountStateBlob, ledger_info::LedgerInfoWithSignatures,
-    proof::TransactionInfoWithProof, transaction::Version,
+    account_state_blob::AccountStateBlob,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{SparseMerkleRangeProof, TransactionInfoWithProof},
+    transaction::Version,
 };
+use futures::StreamExt;
 use std::sync::Arc;
 use structopt::StructOpt;
 
@@ -48,6 +51,8 @@ pub struct StateSnapshotRestoreController {
     /// nothing will be done, otherwise, this has no effect.
     target_version: Version,
     epoch_history: Option<Arc<EpochHistory>>,
+    /// Number of chunks being downloaded and deserialized ahead of the one being restored.
+    concurrency: usize,
 }
 
 impl StateSnapshotRestoreController {
@@ -64,6 +69,7 @@ impl StateSnapshotRestoreController {
             manifest_handle: opt.manifest_handle,
             target_version: global_opt.target_version,
             epoch_history,
+            concurrency: global_opt.concurrency,
         }
     }
 
@@ -129,30 +135,64 @@ impl StateSnapshotRestoreController {
         // FIXME update counters
         ver_gauge.set(self.version as i64);
         tgt_leaf_idx.set(manifest.chunks.last().map_or(0, |c| c.last_idx as i64));
-        for chunk in manifest.chunks {
-            let blobs = self.read_account_state_chunk(chunk.blobs).await?;
-            let proof = self.storage.load_bcs_file(&chunk.proof).await?;
-
+        let num_leaves = manifest.chunks.last().map_or(0, |c| c.last_idx as u64 + 1);
+        let mut progress = ProgressReporter::new(self.name(), "accounts", num_leaves);
+
+        // Chunks are downloaded and deserialized concurrently, but have to be fed into the
+        // receiver in order.
+        let futs_iter = manifest.chunks.into_iter().map(|chunk| {
+            let storage = Arc::clone(&self.storage);
+            async move {
+                // Use `spawn()` so the hashing and deserialization happen off the main thread.
+                tokio::spawn(async move {
+                    let blobs =
+                        read_account_state_chunk(&storage, chunk.blobs, chunk.blobs_hash).await?;
+                    let proof: SparseMerkleRangeProof = storage.load_bcs_file(&chunk.proof).await?;
+                    Result::<_>::Ok((chunk.last_idx, blobs, proof))
+                })
+                .await
+                .expect("Failed to spawn task.")
+            }
+        });
+        let mut futs_stream = futures::stream::iter(futs_iter).buffered_x(
+            self.concurrency * 2, /* buffer size */
+            self.concurrency,     /* concurrency */
+        );
+        while let Some(loaded_chunk) = futs_stream.next().await {
+            let (last_idx, blobs, proof) = loaded_chunk?;
             receiver.add_chunk(blobs, proof)?;
-            leaf_idx.set(chunk.last_idx as i64);
+            leaf_idx.set(last_idx as i64);
+            progress.update(last_idx as u64 + 1);
         }
 
         receiver.finish()?;
         Ok(())
     }
+}
 
-    async fn read_account_state_chunk(
-        &self,
-        file_handle: FileHandle,
-    ) -> Result<Vec<(HashValue, AccountStateBlob)>> {
-        let mut file = self.storage.open_for_read(&file_handle).await?;
-
-        let mut chunk = vec![];
+async fn read_account_state_chunk(
+    storage: &Arc<dyn BackupStorage>,
+    file_handle: FileHandle,
+    expected_hash: Option<HashValue>,
+) -> Result<Vec<(HashValue, AccountStateBlob)>> {
+    let bytes = storage.read_all(&file_handle).await?;
+    if let Some(expected_hash) = expected_hash {
+        let hash = HashValue::sha3_256_of(&bytes);
+        ensure!(
+            hash == expected_hash,
+            "Chunk hash mismatch. file: {}, hash: {}, expected: {}",
+            file_handle,
+            hash,
+            expected_hash,
+        );
+    }
+    let mut file = bytes.as_slice();
 
-        while let Some(record_bytes) = file.read_record_bytes().await? {
-            chunk.push(bcs::from_bytes(&record_bytes)?);
-        }
+    let mut chunk = vec![];
 
-        Ok(chunk)
+    while let Some(record_bytes) = file.read_record_bytes().await? {
+        chunk.push(bcs::from_bytes(&record_bytes)?);
     }
+
+    Ok(chunk)
 }
@@ -4,11 +4,13 @@
 use crate::{
     backup_types::state_snapshot::{
         backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        manifest::StateSnapshotBackup,
         restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
     },
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
         backup_service_client::BackupServiceClient,
+        storage_ext::BackupStorageExt,
         test_utils::{start_local_backup_service, tmp_db_with_random_content},
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt,
     },
@@ -40,19 +42,39 @@ fn end_to_end() {
         port
     )));
 
-    let manifest_handle = rt
-        .block_on(
+    let backup = |base_manifest| {
+        rt.block_on(
             StateSnapshotBackupController::new(
-                StateSnapshotBackupOpt { version },
+                StateSnapshotBackupOpt {
+                    version,
+                    base_manifest,
+                },
                 GlobalBackupOpt {
                     max_chunk_size: 500,
                 },
-                client,
+                Arc::clone(&client),
                 Arc::clone(&store),
             )
             .run(),
         )
-        .unwrap();
+        .unwrap()
+    };
+    let load_manifest = |manifest_handle| {
+        rt.block_on(store.load_json_file::<StateSnapshotBackup>(manifest_handle))
+            .unwrap()
+    };
+    let base_manifest_handle = backup(None);
+    // Backing up the same state again on top of the first backup uploads no chunk.
+    let manifest_handle = backup(Some(base_manifest_handle.clone()));
+    let base_manifest = load_manifest(&base_manifest_handle);
+    let manifest = load_manifest(&manifest_handle);
+    assert_eq!(manifest.chunks.len(), base_manifest.chunks.len());
+    for (chunk, base_chunk) in manifest.chunks.iter().zip(base_manifest.chunks.iter()) {
+        assert!(chunk.blobs_hash.is_some());
+        assert_eq!(chunk.blobs_hash, base_chunk.blobs_hash);
+        assert_eq!(chunk.blobs, base_chunk.blobs);
+        assert_ne!(chunk.proof, base_chunk.proof);
+    }
 
     rt.block_on(
         StateSnapshotRestoreController::new(
@@ -67,6 +89,7 @@ fn end_to_end() {
                 trusted_waypoints: TrustedWaypointOpt::default(),
                 rocksdb_opt: RocksdbOpt::default(),
                 concurernt_downloads: ConcurrentDownloadsOpt::default(),
+                concurrency: None,
             }
             .try_into()
             .unwrap(),
@@ -82,7 +82,10 @@ fn test_end_to_end_impl(d: TestData) {
     let state_snapshot_manifest = d.state_snapshot_ver.map(|version| {
         rt.block_on(
             StateSnapshotBackupController::new(
-                StateSnapshotBackupOpt { version },
+                StateSnapshotBackupOpt {
+                    version,
+                    base_manifest: None,
+                },
                 global_backup_opt.clone(),
                 Arc::clone(&client),
                 Arc::clone(&store),
@@ -114,6 +117,7 @@ fn test_end_to_end_impl(d: TestData) {
         trusted_waypoints: TrustedWaypointOpt::default(),
         rocksdb_opt: RocksdbOpt::default(),
         concurernt_downloads: ConcurrentDownloadsOpt::default(),
+        concurrency: None,
     }
     .try_into()
     .unwrap();
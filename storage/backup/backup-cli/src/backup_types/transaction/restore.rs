@@ -12,8 +12,8 @@ use crate::{
     },
     storage::{BackupStorage, FileHandle},
     utils::{
-        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, stream::StreamX,
-        GlobalRestoreOptions, RestoreRunMode,
+        progress::ProgressReporter, read_record_bytes::ReadRecordBytes,
+        storage_ext::BackupStorageExt, stream::StreamX, GlobalRestoreOptions, RestoreRunMode,
     },
 };
 use anyhow::{anyhow, bail, ensure, Result};
@@ -27,7 +27,7 @@ use diem_types::{
 use diem_vm::DiemVM;
 use executor::Executor;
 use executor_types::TransactionReplayer;
-use futures::StreamExt;
+use futures::{StreamExt, TryStreamExt};
 use std::{
     cmp::{max, min},
     sync::Arc,
@@ -62,6 +62,8 @@ pub struct TransactionRestoreController {
     target_version: Version,
     replay_from_version: Version,
     epoch_history: Option<Arc<EpochHistory>>,
+    /// Number of chunks in the manifest being loaded and verified concurrently.
+    concurrency: usize,
     state: State,
 }
 
@@ -147,6 +149,7 @@ impl TransactionRestoreController {
             manifest_handle: opt.manifest_handle,
             target_version: global_opt.target_version,
             epoch_history,
+            concurrency: global_opt.concurrency,
             state: State::default(),
         }
     }
@@ -173,21 +176,31 @@ impl TransactionRestoreController {
             self.storage.load_json_file(&self.manifest_handle).await?;
         manifest.verify()?;
 
-        let mut loaded_chunks = Vec::new();
-        for chunk_manifest in &manifest.chunks {
-            if chunk_manifest.first_version > self.target_version {
-                break;
-            }
-
-            loaded_chunks.push(
-                LoadedChunk::load(
-                    chunk_manifest.clone(),
-                    &self.storage,
-                    self.epoch_history.as_ref(),
-                )
-                .await?,
+        let futs_iter = manifest
+            .chunks
+            .iter()
+            .take_while(|chunk_manifest| chunk_manifest.first_version <= self.target_version)
+            .cloned()
+            .map(|chunk_manifest| {
+                let storage = Arc::clone(&self.storage);
+                let epoch_history = self.epoch_history.clone();
+                async move {
+                    // Loading a chunk involves deserializing and verifying the transaction list,
+                    // so spawn it off the main thread.
+                    tokio::spawn(async move {
+                        LoadedChunk::load(chunk_manifest, &storage, epoch_history.as_ref()).await
+                    })
+                    .await
+                    .expect("Failed to spawn task.")
+                }
+            });
+        let loaded_chunks = futures::stream::iter(futs_iter)
+            .buffered_x(
+                self.concurrency * 2, /* buffer size */
+                self.concurrency,     /* concurrency */
             )
-        }
+            .try_collect::<Vec<_>>()
+            .await?;
 
         Ok(TransactionRestorePreheatData {
             manifest,
@@ -394,6 +407,11 @@ impl TransactionRestoreBatchController {
 
     pub async fn run(self) -> Result<()> {
         let timer = std::time::Instant::now();
+        let mut progress = ProgressReporter::new(
+            format!("transaction {}", self.global_opt.run_mode.name()),
+            "manifests",
+            self.manifest_handles.len() as u64,
+        );
 
         let futs_iter = self.manifest_handles.clone().into_iter().map(|hdl| {
             let _global_opt = self.global_opt.clone();
@@ -427,9 +445,12 @@ impl TransactionRestoreBatchController {
             self.global_opt.concurrent_downloads * 3,
             self.global_opt.concurrent_downloads, /* concurrency */
         );
+        let mut num_done = 0;
         while let Some(preheated_txn_restore) = futs_stream.next().await {
             let v = preheated_txn_restore.get_last_version();
             preheated_txn_restore.run().await?;
+            num_done += 1;
+            progress.update(num_done);
             debug!(
                 "Accumulative TPS: {:.0}",
                 (v? + 1) as f64 / timer.elapsed().as_secs_f64()
@@ -3,27 +3,40 @@
 
 use crate::{
     backup_types::{
-        epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
-        state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        epoch_ending::{
+            backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+            manifest::EpochEndingBackup,
+        },
+        state_snapshot::{
+            backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+            manifest::StateSnapshotBackup,
+        },
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
     metadata,
-    metadata::cache::MetadataCacheOpt,
+    metadata::{cache::MetadataCacheOpt, Metadata},
     metrics::backup::{
-        EPOCH_ENDING_EPOCH, HEARTBEAT_TS, STATE_SNAPSHOT_VERSION, TRANSACTION_VERSION,
+        BACKUP_AGE, BACKUP_TIMESTAMP, EPOCH_ENDING_EPOCH, HEARTBEAT_TS, STATE_SNAPSHOTS_EXPIRED,
+        STATE_SNAPSHOT_VERSION, TRANSACTION_VERSION,
     },
-    storage::BackupStorage,
+    storage::{BackupStorage, FileHandle},
     utils::{
-        backup_service_client::BackupServiceClient, unix_timestamp_sec, ConcurrentDownloadsOpt,
-        GlobalBackupOpt,
+        backup_service_client::BackupServiceClient, storage_ext::BackupStorageExt,
+        unix_timestamp_sec, ConcurrentDownloadsOpt, GlobalBackupOpt,
     },
 };
 use anyhow::{anyhow, ensure, Result};
+use diem_infallible::Mutex;
 use diem_logger::prelude::*;
 use diem_types::transaction::Version;
 use diemdb::backup::backup_handler::DbState;
 use futures::{stream, Future, StreamExt};
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::Debug,
+    sync::Arc,
+    time::Instant,
+};
 use structopt::StructOpt;
 use tokio::{
     sync::watch,
@@ -31,6 +44,11 @@ use tokio::{
 };
 use tokio_stream::wrappers::IntervalStream;
 
+// Backup types, as labels of the backup age metrics.
+const EPOCH_ENDING: &str = "epoch_ending";
+const STATE_SNAPSHOT: &str = "state_snapshot";
+const TRANSACTION: &str = "transaction";
+
 #[derive(StructOpt)]
 pub struct BackupCoordinatorOpt {
     #[structopt(flatten)]
@@ -46,10 +64,58 @@ pub struct BackupCoordinatorOpt {
     // slower than expected.
     #[structopt(long, default_value = "100000")]
     pub transaction_batch_size: usize,
+    // By default a state snapshot only uploads the chunks not found in the previous one, which
+    // it refers to for the rest, since most accounts don't change between snapshots.
+    #[structopt(
+        long,
+        help = "Upload every chunk of each state snapshot, not reusing those of the previous one."
+    )]
+    pub full_state_snapshots: bool,
+    #[structopt(
+        long,
+        help = "Also take a state snapshot at the last version of each epoch, once the epoch \
+        ending is backed up."
+    )]
+    pub epoch_ending_state_snapshots: bool,
+    #[structopt(
+        long,
+        help = "Also take a state snapshot at the latest committed version when no snapshot has \
+        been taken for this many seconds, for slow chains not reaching the next interval soon."
+    )]
+    pub state_snapshot_period_secs: Option<u64>,
+    #[structopt(flatten)]
+    pub retention_opt: BackupRetentionOpt,
     #[structopt(flatten)]
     pub concurernt_downloads: ConcurrentDownloadsOpt,
 }
 
+/// Which state snapshots to keep in the backup storage. A state snapshot is full if it uploaded
+/// all its chunks, incremental if it refers to chunks of earlier snapshots, see
+/// `BackupCoordinatorOpt::full_state_snapshots`. The latest snapshot is always kept. Files of an
+/// expired snapshot still referred to by a kept one are kept as well.
+#[derive(StructOpt)]
+pub struct BackupRetentionOpt {
+    #[structopt(
+        long,
+        help = "Number of the latest full state snapshots to keep, older ones are expired and \
+        their files deleted. All are kept if not set."
+    )]
+    pub retain_full_state_snapshots: Option<usize>,
+    #[structopt(
+        long,
+        help = "Number of the latest incremental state snapshots to keep, older ones are expired \
+        and their files deleted. All are kept if not set."
+    )]
+    pub retain_incremental_state_snapshots: Option<usize>,
+}
+
+impl BackupRetentionOpt {
+    fn enabled(&self) -> bool {
+        self.retain_full_state_snapshots.is_some()
+            || self.retain_incremental_state_snapshots.is_some()
+    }
+}
+
 impl BackupCoordinatorOpt {
     fn validate(&self) -> Result<()> {
         ensure!(
@@ -63,6 +129,10 @@ impl BackupCoordinatorOpt {
              that's not yet in a transaction backup, resulting in replaying all transactions \
              at restore time."
         );
+        ensure!(
+            self.state_snapshot_period_secs != Some(0),
+            "State snapshot period must be greater than 0."
+        );
         Ok(())
     }
 }
@@ -74,7 +144,17 @@ pub struct BackupCoordinator {
     metadata_cache_opt: MetadataCacheOpt,
     state_snapshot_interval: usize,
     transaction_batch_size: usize,
+    full_state_snapshots: bool,
+    epoch_ending_state_snapshots: bool,
+    state_snapshot_period: Option<Duration>,
+    retention_opt: BackupRetentionOpt,
     concurrent_downloads: usize,
+    /// Manifest of the latest state snapshot in the backup storage, to reuse chunks of.
+    last_state_snapshot_manifest: Mutex<Option<FileHandle>>,
+    /// When the latest state snapshot was taken, or the coordinator started.
+    last_state_snapshot_time: Mutex<Instant>,
+    /// Last version of the latest epoch backed up by the coordinator, to take a state snapshot at.
+    last_epoch_ending_version: Mutex<Option<Version>>,
 }
 
 impl BackupCoordinator {
@@ -92,18 +172,36 @@ impl BackupCoordinator {
             metadata_cache_opt: opt.metadata_cache_opt,
             state_snapshot_interval: opt.state_snapshot_interval,
             transaction_batch_size: opt.transaction_batch_size,
+            full_state_snapshots: opt.full_state_snapshots,
+            epoch_ending_state_snapshots: opt.epoch_ending_state_snapshots,
+            state_snapshot_period: opt.state_snapshot_period_secs.map(Duration::from_secs),
+            retention_opt: opt.retention_opt,
             concurrent_downloads: opt.concurernt_downloads.get(),
+            last_state_snapshot_manifest: Mutex::new(None),
+            last_state_snapshot_time: Mutex::new(Instant::now()),
+            last_epoch_ending_version: Mutex::new(None),
         }
     }
+
     pub async fn run(&self) -> Result<()> {
         // Connect to both the local Diem node and the backup storage.
-        let backup_state = metadata::cache::sync_and_load(
+        let metadata_view = metadata::cache::sync_and_load(
             &self.metadata_cache_opt,
             Arc::clone(&self.storage),
             self.concurrent_downloads,
         )
-        .await?
-        .get_storage_state();
+        .await?;
+        let backup_state = metadata_view.get_storage_state();
+        *self.last_state_snapshot_manifest.lock() = metadata_view
+            .select_state_snapshot(Version::max_value())?
+            .map(|snapshot| snapshot.manifest);
+        // Backups are only known to be caught up after the first round, but a coordinator failing
+        // from the start should be alerted on as well.
+        for backup_type in &[EPOCH_ENDING, STATE_SNAPSHOT, TRANSACTION] {
+            BACKUP_TIMESTAMP
+                .with_label_values(&[*backup_type])
+                .set(unix_timestamp_sec());
+        }
 
         // On new DbState retrieved:
         // `watch_db_state` informs `backup_epoch_endings` via channel 1,
@@ -163,7 +261,14 @@ impl BackupCoordinator {
     async fn try_refresh_db_state(&self, db_state_broadcast: &watch::Sender<Option<DbState>>) {
         match self.client.get_db_state().await {
             Ok(s) => {
-                HEARTBEAT_TS.set(unix_timestamp_sec());
+                let now = unix_timestamp_sec();
+                HEARTBEAT_TS.set(now);
+                for backup_type in &[EPOCH_ENDING, STATE_SNAPSHOT, TRANSACTION] {
+                    let last = BACKUP_TIMESTAMP.with_label_values(&[*backup_type]).get();
+                    BACKUP_AGE
+                        .with_label_values(&[*backup_type])
+                        .set(now - last);
+                }
                 if s.is_none() {
                     warn!("DB not bootstrapped.");
                 } else {
@@ -197,7 +302,7 @@ impl BackupCoordinator {
                 break;
             }
 
-            EpochEndingBackupController::new(
+            let manifest = EpochEndingBackupController::new(
                 EpochEndingBackupOpt {
                     start_epoch: first,
                     end_epoch: last + 1,
@@ -208,8 +313,16 @@ impl BackupCoordinator {
             )
             .run()
             .await?;
+            if self.epoch_ending_state_snapshots {
+                let manifest: EpochEndingBackup = self.storage.load_json_file(&manifest).await?;
+                *self.last_epoch_ending_version.lock() =
+                    manifest.waypoints.last().map(|waypoint| waypoint.version());
+            }
             last_epoch_ending_epoch_in_backup = Some(last)
         }
+        BACKUP_TIMESTAMP
+            .with_label_values(&[EPOCH_ENDING])
+            .set(unix_timestamp_sec());
 
         downstream_db_state_broadcaster
             .send(Some(db_state))
@@ -226,20 +339,34 @@ impl BackupCoordinator {
         if let Some(version) = last_snapshot_version_in_backup {
             STATE_SNAPSHOT_VERSION.set(version as i64);
         }
-        let next_snapshot_version = get_next_snapshot(
+        let mut next_snapshot_version = get_next_snapshot(
             last_snapshot_version_in_backup,
             db_state,
             self.state_snapshot_interval,
         );
 
         if db_state.committed_version < next_snapshot_version {
-            // wait for the next db_state update
-            return Ok(last_snapshot_version_in_backup);
+            match self.get_unscheduled_snapshot(last_snapshot_version_in_backup, db_state) {
+                Some(version) => next_snapshot_version = version,
+                None => {
+                    // wait for the next db_state update
+                    BACKUP_TIMESTAMP
+                        .with_label_values(&[STATE_SNAPSHOT])
+                        .set(unix_timestamp_sec());
+                    return Ok(last_snapshot_version_in_backup);
+                }
+            }
         }
 
-        StateSnapshotBackupController::new(
+        let base_manifest = if self.full_state_snapshots {
+            None
+        } else {
+            self.last_state_snapshot_manifest.lock().clone()
+        };
+        let manifest = StateSnapshotBackupController::new(
             StateSnapshotBackupOpt {
                 version: next_snapshot_version,
+                base_manifest,
             },
             self.global_opt.clone(),
             Arc::clone(&self.client),
@@ -247,10 +374,103 @@ impl BackupCoordinator {
         )
         .run()
         .await?;
+        *self.last_state_snapshot_manifest.lock() = Some(manifest);
+        *self.last_state_snapshot_time.lock() = Instant::now();
+
+        if self.retention_opt.enabled() {
+            // A failure doesn't fail the snapshot, expiration is retried after the next one.
+            if let Err(e) = self.expire_state_snapshots().await {
+                warn!("Failed expiring state snapshots: {}. Will retry.", e);
+            }
+        }
 
         Ok(Some(next_snapshot_version))
     }
 
+    /// A snapshot to take besides those every `state_snapshot_interval` versions: at the latest
+    /// epoch ending, or at the latest version if no snapshot has been taken for a period.
+    fn get_unscheduled_snapshot(
+        &self,
+        last_snapshot_version_in_backup: Option<Version>,
+        db_state: DbState,
+    ) -> Option<Version> {
+        let is_new = |version: Version| {
+            last_snapshot_version_in_backup.map_or(true, |last| version > last)
+                && version <= db_state.committed_version
+        };
+        if self.epoch_ending_state_snapshots {
+            if let Some(version) = *self.last_epoch_ending_version.lock() {
+                if is_new(version) {
+                    return Some(version);
+                }
+            }
+        }
+        if let Some(period) = self.state_snapshot_period {
+            if self.last_state_snapshot_time.lock().elapsed() >= period
+                && is_new(db_state.committed_version)
+            {
+                return Some(db_state.committed_version);
+            }
+        }
+        None
+    }
+
+    /// Applies the retention policy to the state snapshots in the backup storage. Expired
+    /// snapshots are first marked so in the metadata, so nothing selects them for restoring, then
+    /// the files no kept snapshot refers to are deleted.
+    async fn expire_state_snapshots(&self) -> Result<()> {
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+        let snapshots = metadata_view.all_state_snapshots();
+        let mut snapshot_files = Vec::with_capacity(snapshots.len());
+        for snapshot in &snapshots {
+            let manifest: StateSnapshotBackup =
+                self.storage.load_json_file(&snapshot.manifest).await?;
+            let mut files: HashSet<FileHandle> = manifest
+                .chunks
+                .into_iter()
+                .flat_map(|chunk| vec![chunk.blobs, chunk.proof])
+                .collect();
+            files.insert(manifest.proof);
+            files.insert(snapshot.manifest.clone());
+            snapshot_files.push(files);
+        }
+
+        let (expired, files_to_delete) = select_expired_snapshots(
+            &snapshot_files,
+            self.retention_opt.retain_full_state_snapshots,
+            self.retention_opt.retain_incremental_state_snapshots,
+        );
+        for idx in &expired {
+            let metadata = Metadata::new_expired_state_snapshot_backup(snapshots[*idx].clone());
+            self.storage
+                .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
+                .await?;
+            STATE_SNAPSHOTS_EXPIRED.inc();
+        }
+        for file_handle in &files_to_delete {
+            self.storage.delete_file(file_handle).await?;
+        }
+        if !expired.is_empty() {
+            info!(
+                expired_versions = format!(
+                    "{:?}",
+                    expired
+                        .iter()
+                        .map(|idx| snapshots[*idx].version)
+                        .collect::<Vec<_>>()
+                ),
+                num_files_deleted = files_to_delete.len(),
+                "State snapshots expired."
+            );
+        }
+        Ok(())
+    }
+
     async fn backup_transactions(
         &self,
         mut last_transaction_version_in_backup: Option<Version>,
@@ -267,6 +487,9 @@ impl BackupCoordinator {
 
             if db_state.committed_version < last {
                 // wait for the next db_state update
+                BACKUP_TIMESTAMP
+                    .with_label_values(&[TRANSACTION])
+                    .set(unix_timestamp_sec());
                 return Ok(last_transaction_version_in_backup);
             }
 
@@ -357,10 +580,63 @@ fn get_next_snapshot(last_in_backup: Option<u64>, db_state: DbState, interval: u
     std::cmp::max(next_for_storage, last_for_db)
 }
 
+/// Given the files each state snapshot refers to, in version order, returns the indices of the
+/// snapshots to expire under the retention policy, and the files only those refer to. A snapshot
+/// is incremental if it shares files with an earlier one.
+fn select_expired_snapshots(
+    snapshot_files: &[HashSet<FileHandle>],
+    retain_full: Option<usize>,
+    retain_incremental: Option<usize>,
+) -> (Vec<usize>, BTreeSet<FileHandle>) {
+    let mut earlier_files = HashSet::new();
+    let is_full: Vec<bool> = snapshot_files
+        .iter()
+        .map(|files| {
+            let is_full = files.is_disjoint(&earlier_files);
+            earlier_files.extend(files.iter().cloned());
+            is_full
+        })
+        .collect();
+
+    let mut num_full = 0;
+    let mut num_incremental = 0;
+    let mut expired = Vec::new();
+    for idx in (0..snapshot_files.len()).rev() {
+        let (num_kept, retain) = if is_full[idx] {
+            (&mut num_full, retain_full)
+        } else {
+            (&mut num_incremental, retain_incremental)
+        };
+        // The latest snapshot is always kept, the next one will refer to its chunks.
+        let is_latest = idx + 1 == snapshot_files.len();
+        if !is_latest && retain.map_or(false, |retain| *num_kept >= retain) {
+            expired.push(idx);
+        } else {
+            *num_kept += 1;
+        }
+    }
+    expired.reverse();
+
+    let kept_files: HashSet<&FileHandle> = (0..snapshot_files.len())
+        .filter(|idx| !expired.contains(idx))
+        .flat_map(|idx| snapshot_files[idx].iter())
+        .collect();
+    let files_to_delete = expired
+        .iter()
+        .flat_map(|idx| snapshot_files[*idx].iter())
+        .filter(|file| !kept_files.contains(file))
+        .cloned()
+        .collect();
+    (expired, files_to_delete)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::coordinators::backup::{get_batch_range, get_next_snapshot};
+    use crate::coordinators::backup::{
+        get_batch_range, get_next_snapshot, select_expired_snapshots,
+    };
     use diemdb::backup::backup_handler::DbState;
+    use std::collections::{BTreeSet, HashSet};
 
     #[test]
     fn test_get_batch_range() {
@@ -386,4 +662,46 @@ mod tests {
         assert_eq!(get_next_snapshot(Some(0), _state(250), 100), 200);
         assert_eq!(get_next_snapshot(Some(200), _state(250), 100), 300);
     }
+
+    #[test]
+    fn test_select_expired_snapshots() {
+        let files = |names: &[&str]| -> HashSet<String> {
+            names.iter().map(|name| name.to_string()).collect()
+        };
+        let sorted = |names: &[&str]| -> BTreeSet<String> {
+            names.iter().map(|name| name.to_string()).collect()
+        };
+        let snapshot_files = vec![
+            files(&["m0", "a", "b"]), // full
+            files(&["m1", "a", "c"]), // incremental
+            files(&["m2", "a", "d"]), // incremental
+            files(&["m3", "e", "f"]), // full
+            files(&["m4", "e", "g"]), // incremental
+        ];
+
+        assert_eq!(
+            select_expired_snapshots(&snapshot_files, None, None),
+            (vec![], sorted(&[]))
+        );
+        assert_eq!(
+            select_expired_snapshots(&snapshot_files, Some(2), Some(3)),
+            (vec![], sorted(&[]))
+        );
+        assert_eq!(
+            select_expired_snapshots(&snapshot_files, Some(1), Some(1)),
+            (
+                vec![0, 1, 2],
+                sorted(&["a", "b", "c", "d", "m0", "m1", "m2"])
+            )
+        );
+        // files still referred to are kept, as is the latest snapshot
+        assert_eq!(
+            select_expired_snapshots(&snapshot_files, Some(0), None),
+            (vec![0, 3], sorted(&["b", "f", "m0", "m3"]))
+        );
+        assert_eq!(
+            select_expired_snapshots(&snapshot_files, None, Some(0)),
+            (vec![1, 2], sorted(&["c", "d", "m1", "m2"]))
+        );
+    }
 }
@@ -16,6 +16,9 @@ pub(crate) enum Metadata {
     EpochEndingBackup(EpochEndingBackupMeta),
     StateSnapshotBackup(StateSnapshotBackupMeta),
     TransactionBackup(TransactionBackupMeta),
+    /// Marks a state snapshot backup expired by the retention policy, some of its files might be
+    /// deleted already.
+    ExpiredStateSnapshotBackup(StateSnapshotBackupMeta),
 }
 
 impl Metadata {
@@ -39,6 +42,10 @@ impl Metadata {
         Self::StateSnapshotBackup(StateSnapshotBackupMeta { version, manifest })
     }
 
+    pub fn new_expired_state_snapshot_backup(backup: StateSnapshotBackupMeta) -> Self {
+        Self::ExpiredStateSnapshotBackup(backup)
+    }
+
     pub fn new_transaction_backup(
         first_version: Version,
         last_version: Version,
@@ -57,6 +64,9 @@ impl Metadata {
                 format!("epoch_ending_{}-{}.meta", e.first_epoch, e.last_epoch)
             }
             Self::StateSnapshotBackup(s) => format!("state_snapshot_ver_{}.meta", s.version),
+            Self::ExpiredStateSnapshotBackup(s) => {
+                format!("state_snapshot_ver_{}.expired.meta", s.version)
+            }
             Self::TransactionBackup(t) => {
                 format!("transaction_{}-{}.meta", t.first_version, t.last_version,)
             }
@@ -7,7 +7,7 @@ use crate::metadata::{
 use anyhow::{anyhow, ensure, Result};
 use diem_types::transaction::Version;
 use itertools::Itertools;
-use std::{fmt, str::FromStr};
+use std::{collections::BTreeSet, fmt, str::FromStr};
 
 pub struct MetadataView {
     epoch_ending_backups: Vec<EpochEndingBackupMeta>,
@@ -34,6 +34,15 @@ impl MetadataView {
         }
     }
 
+    /// All state snapshot backups not expired, in version order.
+    pub fn all_state_snapshots(&self) -> Vec<StateSnapshotBackupMeta> {
+        self.state_snapshot_backups
+            .iter()
+            .sorted()
+            .cloned()
+            .collect()
+    }
+
     pub fn select_state_snapshot(
         &self,
         target_version: Version,
@@ -107,14 +116,19 @@ impl From<Vec<Metadata>> for MetadataView {
         let mut epoch_ending_backups = Vec::new();
         let mut state_snapshot_backups = Vec::new();
         let mut transaction_backups = Vec::new();
+        let mut expired_state_snapshot_backups = BTreeSet::new();
 
         for meta in metadata_vec {
             match meta {
                 Metadata::EpochEndingBackup(e) => epoch_ending_backups.push(e),
                 Metadata::StateSnapshotBackup(s) => state_snapshot_backups.push(s),
                 Metadata::TransactionBackup(t) => transaction_backups.push(t),
+                Metadata::ExpiredStateSnapshotBackup(s) => {
+                    expired_state_snapshot_backups.insert(s);
+                }
             }
         }
+        state_snapshot_backups.retain(|s| !expired_state_snapshot_backups.contains(s));
 
         Self {
             epoch_ending_backups,
@@ -1,7 +1,10 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use diem_secure_push_metrics::{register_int_gauge, IntGauge};
+use diem_secure_push_metrics::{
+    register_int_counter, register_int_gauge, register_int_gauge_vec, IntCounter, IntGauge,
+    IntGaugeVec,
+};
 use once_cell::sync::Lazy;
 
 pub static HEARTBEAT_TS: Lazy<IntGauge> = Lazy::new(|| {
@@ -28,6 +31,22 @@ pub static STATE_SNAPSHOT_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static STATE_SNAPSHOT_CHUNKS_WRITTEN: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_db_backup_state_snapshot_chunks_written",
+        "Number of state snapshot chunks uploaded."
+    )
+    .unwrap()
+});
+
+pub static STATE_SNAPSHOT_CHUNKS_REUSED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_db_backup_state_snapshot_chunks_reused",
+        "Number of state snapshot chunks referring to an identical chunk in an earlier backup."
+    )
+    .unwrap()
+});
+
 pub static TRANSACTION_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
         "diem_db_backup_coordinator_transaction_version",
@@ -35,3 +54,29 @@ pub static TRANSACTION_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static STATE_SNAPSHOTS_EXPIRED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_db_backup_coordinator_state_snapshots_expired",
+        "Number of state snapshots expired by the retention policy."
+    )
+    .unwrap()
+});
+
+pub static BACKUP_TIMESTAMP: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "diem_db_backup_coordinator_backup_timestamp_s",
+        "Timestamp when a backup type was last found caught up with the local Diem node.",
+        &["backup_type"]
+    )
+    .unwrap()
+});
+
+pub static BACKUP_AGE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "diem_db_backup_coordinator_backup_age_s",
+        "Seconds since a backup type was last found caught up with the local Diem node.",
+        &["backup_type"]
+    )
+    .unwrap()
+});
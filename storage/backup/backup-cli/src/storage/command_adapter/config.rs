@@ -67,6 +67,12 @@ pub struct Commands {
     /// Command line to list all existing metadata file handles.
     /// expected stdout to stream out lines of file handles.
     pub list_metadata_files: String,
+    /// Command line to delete a file, when the backup it belongs to expires. Optional, backups
+    /// can't be expired without it.
+    /// input env vars:
+    ///     $FILE_HANDLE
+    #[serde(default)]
+    pub delete_file: Option<String>,
 }
 
 #[derive(Clone, Default, Deserialize)]
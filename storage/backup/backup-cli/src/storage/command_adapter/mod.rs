@@ -18,7 +18,7 @@ use crate::{
     },
     utils::error_notes::ErrorNotes,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use std::path::PathBuf;
 use structopt::StructOpt;
@@ -143,4 +143,17 @@ impl BackupStorage for CommandAdapter {
             .err_notes((file!(), line!(), &buf))?;
         Ok(buf.lines().map(str::to_string).collect())
     }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let cmd_str = self
+            .config
+            .commands
+            .delete_file
+            .as_ref()
+            .ok_or_else(|| anyhow!("No delete_file command configured."))?;
+        let child = self
+            .cmd(cmd_str, vec![EnvVar::file_handle(file_handle.to_string())])
+            .spawn()?;
+        child.join().await
+    }
 }
@@ -29,6 +29,7 @@ fn get_store(tmpdir: &TempPath) -> Box<dyn BackupStorage> {
                 open_for_read = 'cat "$FOLDER/$FILE_HANDLE"'
                 save_metadata_line= 'cd "$FOLDER" && mkdir -p metadata && cd metadata && cat > $FILE_NAME'
                 list_metadata_files = 'cd "$FOLDER" && (test -d metadata && cd metadata && ls -1 || exec) | while read f; do echo metadata/$f; done'
+                delete_file = 'rm "$FOLDER/$FILE_HANDLE"'
             "#, tmpdir.path().to_str().unwrap()),
     ).unwrap();
 
@@ -67,6 +68,7 @@ fn dummy_store(cmd: &str) -> CommandAdapter {
             open_for_read: cmd.to_string(),
             save_metadata_line: cmd.to_string(),
             list_metadata_files: cmd.to_string(),
+            delete_file: Some(cmd.to_string()),
         },
         env_vars: Vec::new(),
     })
@@ -113,6 +115,9 @@ async fn assert_commands_error(cmd: &str) {
 
     // list_metadata_files
     assert!(store.list_metadata_files().await.is_err());
+
+    // delete_file
+    assert!(store.delete_file(&handle).await.is_err());
 }
 
 async fn assert_commands_okay(cmd: &str) {
@@ -148,7 +153,10 @@ async fn assert_commands_okay(cmd: &str) {
         .unwrap();
 
     // list_metadata_files
-    assert_eq!(store.list_metadata_files().await.unwrap(), vec!["okay"])
+    assert_eq!(store.list_metadata_files().await.unwrap(), vec!["okay"]);
+
+    // delete_file
+    store.delete_file(&handle).await.unwrap();
 }
 
 #[test]
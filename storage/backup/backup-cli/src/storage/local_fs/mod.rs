@@ -15,7 +15,7 @@ use async_trait::async_trait;
 use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 use tokio::{
-    fs::{create_dir, create_dir_all, read_dir, OpenOptions},
+    fs::{create_dir, create_dir_all, read_dir, remove_dir, remove_file, OpenOptions},
     io::{AsyncRead, AsyncWrite, AsyncWriteExt},
 };
 
@@ -122,4 +122,14 @@ impl BackupStorage for LocalFs {
         }
         Ok(res)
     }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let path = self.dir.join(file_handle);
+        remove_file(&path).await.err_notes(&path)?;
+        // Remove the backup dir along with its last file, failing harmlessly if it's not empty.
+        if let Some(backup_dir) = path.parent() {
+            remove_dir(backup_dir).await.ok();
+        }
+        Ok(())
+    }
 }
@@ -167,6 +167,9 @@ pub trait BackupStorage: Send + Sync {
     ///   2. But the cache does expect the content stays the same for a file handle, so when
     /// reorganising metadata files, give them new unique names.
     async fn list_metadata_files(&self) -> Result<Vec<FileHandle>>;
+    /// Deletes a file returned by `create_for_write`, when the backup it belongs to expires.
+    /// Metadata files are never deleted, expiration is recorded by saving more metadata lines.
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()>;
 }
 
 #[derive(StructOpt)]
@@ -3,6 +3,7 @@
 
 pub mod backup_service_client;
 pub(crate) mod error_notes;
+pub(crate) mod progress;
 pub mod read_record_bytes;
 pub mod storage_ext;
 pub(crate) mod stream;
@@ -47,6 +48,9 @@ pub struct RocksdbOpt {
     // using the same default with a node (1GB).
     #[structopt(long, default_value = "1073741824")]
     max_total_wal_size: u64,
+    // must match the layout of the target DB, see `RocksdbConfig::state_shards`.
+    #[structopt(long, default_value = "1")]
+    state_shards: usize,
 }
 
 impl From<RocksdbOpt> for RocksdbConfig {
@@ -54,6 +58,8 @@ impl From<RocksdbOpt> for RocksdbConfig {
         Self {
             max_open_files: opt.max_open_files,
             max_total_wal_size: opt.max_total_wal_size,
+            state_shards: opt.state_shards,
+            ..Default::default()
         }
     }
 }
@@ -92,6 +98,13 @@ pub struct GlobalRestoreOpt {
 
     #[structopt(flatten)]
     pub concurernt_downloads: ConcurrentDownloadsOpt,
+
+    #[structopt(
+        long,
+        help = "[Defaults to number of CPUs] number of backup chunks downloaded, deserialized and \
+        verified concurrently while restoring. Verified chunks are still written to the DB in order."
+    )]
+    pub concurrency: Option<usize>,
 }
 
 pub enum RestoreRunMode {
@@ -146,6 +159,7 @@ pub struct GlobalRestoreOptions {
     pub trusted_waypoints: Arc<HashMap<Version, Waypoint>>,
     pub run_mode: Arc<RestoreRunMode>,
     pub concurrent_downloads: usize,
+    pub concurrency: usize,
 }
 
 impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
@@ -154,6 +168,7 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
     fn try_from(opt: GlobalRestoreOpt) -> Result<Self> {
         let target_version = opt.target_version.unwrap_or(Version::max_value());
         let concurrent_downloads = opt.concurernt_downloads.get();
+        let concurrency = opt.concurrency.unwrap_or_else(num_cpus::get);
         let run_mode = if let Some(db_dir) = &opt.db_dir {
             let restore_handler = Arc::new(DiemDB::open(
                 db_dir,
@@ -171,6 +186,7 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
             trusted_waypoints: Arc::new(opt.trusted_waypoints.verify()?),
             run_mode: Arc::new(run_mode),
             concurrent_downloads,
+            concurrency,
         })
     }
 }
@@ -0,0 +1,65 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use diem_logger::prelude::*;
+use std::time::{Duration, Instant};
+
+const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Logs the progress of a long running restore step, throttled to once every `REPORT_INTERVAL`
+/// except for the final update.
+pub(crate) struct ProgressReporter {
+    name: String,
+    unit: &'static str,
+    total: u64,
+    start: Instant,
+    last_report: Option<Instant>,
+}
+
+impl ProgressReporter {
+    pub fn new(name: String, unit: &'static str, total: u64) -> Self {
+        Self {
+            name,
+            unit,
+            total,
+            start: Instant::now(),
+            last_report: None,
+        }
+    }
+
+    pub fn update(&mut self, done: u64) {
+        let now = Instant::now();
+        if done < self.total
+            && self
+                .last_report
+                .map_or(false, |last| now.duration_since(last) < REPORT_INTERVAL)
+        {
+            return;
+        }
+        self.last_report = Some(now);
+
+        let elapsed = now.duration_since(self.start).as_secs_f64();
+        let rate = if elapsed > 0.0 {
+            done as f64 / elapsed
+        } else {
+            0.0
+        };
+        let eta_secs = if rate > 0.0 {
+            self.total.saturating_sub(done) as f64 / rate
+        } else {
+            0.0
+        };
+        info!(
+            "{} progress: {}/{} {} ({:.1}%), {:.0} {}/s, elapsed {:.0}s, ETA {:.0}s.",
+            self.name,
+            done,
+            self.total,
+            self.unit,
+            done as f64 * 100.0 / self.total.max(1) as f64,
+            rate,
+            self.unit,
+            elapsed,
+            eta_secs,
+        );
+    }
+}
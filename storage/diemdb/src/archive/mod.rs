@@ -0,0 +1,476 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module moves transactions and events far behind the latest version out of the DB into
+//! archive files, and reads them back when those versions are queried, so that nodes keeping the
+//! full history don't need to keep all of it on local disks.
+//!
+//! Versions are archived in chunks of consecutive versions, each in a file holding the BCS
+//! serialized transactions and events, indexed by `ArchivedChunkSchema`. Transaction infos, the
+//! accumulators and the indices by account and event key stay in the DB, so proofs are served as
+//! usual.
+
+use crate::{
+    errors::DiemDbError,
+    metrics::DIEM_STORAGE_ARCHIVED_VERSION,
+    schema::{
+        archived_chunk::ArchivedChunkSchema, event::EventSchema, transaction::TransactionSchema,
+    },
+};
+use anyhow::{ensure, format_err, Result};
+use diem_config::config::ArchiveStorageConfig;
+use diem_infallible::{Mutex, RwLock};
+use diem_logger::prelude::*;
+use diem_types::{
+    contract_event::ContractEvent,
+    transaction::{Transaction, Version},
+};
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+use schemadb::{ReadOptions, SchemaBatch, DB};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Debug, Formatter},
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command as Process, Stdio},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+/// What `ArchivedChunkSchema` records about an archived chunk, keyed by its first version.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub(crate) struct ArchivedChunkInfo {
+    pub last_version: Version,
+    pub file_name: String,
+}
+
+/// The content of an archive file.
+#[derive(Debug, Deserialize, Serialize)]
+struct ArchivedChunk {
+    first_version: Version,
+    transactions: Vec<Transaction>,
+    /// Events emitted by each of `transactions`.
+    events: Vec<Vec<ContractEvent>>,
+}
+
+impl ArchivedChunk {
+    fn contains(&self, version: Version) -> bool {
+        version >= self.first_version
+            && version - self.first_version < self.transactions.len() as u64
+    }
+
+    fn index_of(&self, version: Version) -> Option<usize> {
+        if self.contains(version) {
+            Some((version - self.first_version) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+/// Where archive files are kept.
+pub trait ArchiveStorage: Send + Sync {
+    /// Writes a file, overwriting any existing one with the same name.
+    fn put(&self, file_name: &str, data: &[u8]) -> Result<()>;
+
+    /// Reads a whole file.
+    fn get(&self, file_name: &str) -> Result<Vec<u8>>;
+}
+
+/// Creates the `ArchiveStorage` described by `config`.
+pub fn new_archive_storage(config: &ArchiveStorageConfig) -> Result<Arc<dyn ArchiveStorage>> {
+    Ok(match config {
+        ArchiveStorageConfig::Local { dir } => Arc::new(LocalArchiveStorage::new(dir.clone())?),
+        ArchiveStorageConfig::Command { put, get } => {
+            Arc::new(CommandArchiveStorage::new(put.clone(), get.clone()))
+        }
+    })
+}
+
+/// Keeps archive files in a local directory.
+pub struct LocalArchiveStorage {
+    dir: PathBuf,
+}
+
+impl LocalArchiveStorage {
+    pub fn new(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+}
+
+impl ArchiveStorage for LocalArchiveStorage {
+    fn put(&self, file_name: &str, data: &[u8]) -> Result<()> {
+        // Write to a temporary file and rename, so a partially written file is never seen.
+        let tmp_path = self.dir.join(format!("{}.tmp", file_name));
+        fs::write(&tmp_path, data)?;
+        fs::rename(&tmp_path, self.dir.join(file_name))?;
+        Ok(())
+    }
+
+    fn get(&self, file_name: &str) -> Result<Vec<u8>> {
+        fs::read(self.dir.join(file_name))
+            .map_err(|e| format_err!("Failed reading archive file {}: {}", file_name, e))
+    }
+}
+
+/// Keeps archive files wherever a pair of shell commands put them, e.g. in a cloud bucket.
+///
+/// Both commands see the file name in `$FILE_NAME`. The `put` command reads the file content from
+/// stdin, the `get` command writes it to stdout.
+pub struct CommandArchiveStorage {
+    put_cmd: String,
+    get_cmd: String,
+}
+
+impl CommandArchiveStorage {
+    pub fn new(put_cmd: String, get_cmd: String) -> Self {
+        Self { put_cmd, get_cmd }
+    }
+
+    fn command(cmd: &str, file_name: &str) -> Process {
+        let mut process = Process::new("bash");
+        process
+            .args(&[
+                "-c",
+                &format!("set -o nounset -o errexit -o pipefail; {}", cmd),
+            ])
+            .env("FILE_NAME", file_name)
+            .stderr(Stdio::piped());
+        process
+    }
+}
+
+impl ArchiveStorage for CommandArchiveStorage {
+    fn put(&self, file_name: &str, data: &[u8]) -> Result<()> {
+        let mut child = Self::command(&self.put_cmd, file_name)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()?;
+        child
+            .stdin
+            .take()
+            .expect("Stdin is piped.")
+            .write_all(data)?;
+        let output = child.wait_with_output()?;
+        ensure!(
+            output.status.success(),
+            "Archive put command failed for {}: {}. stderr: {}",
+            file_name,
+            output.status,
+            String::from_utf8_lossy(&output.stderr),
+        );
+        Ok(())
+    }
+
+    fn get(&self, file_name: &str) -> Result<Vec<u8>> {
+        let output = Self::command(&self.get_cmd, file_name)
+            .stdin(Stdio::null())
+            .output()?;
+        ensure!(
+            output.status.success(),
+            "Archive get command failed for {}: {}. stderr: {}",
+            file_name,
+            output.status,
+            String::from_utf8_lossy(&output.stderr),
+        );
+        Ok(output.stdout)
+    }
+}
+
+/// Moves chunks of transactions and events into archive files and reads them back.
+pub(crate) struct ArchiveStore {
+    db: Arc<DB>,
+    storage: RwLock<Option<Arc<dyn ArchiveStorage>>>,
+    /// Versions before this are archived, if archived at all (versions before the first chunk,
+    /// e.g. pruned ones, are simply not there).
+    archived_end: AtomicU64,
+    /// The chunk read last, since queries tend to hit the same chunk repeatedly.
+    cache: Mutex<Option<Arc<ArchivedChunk>>>,
+}
+
+impl Debug for ArchiveStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArchiveStore")
+            .field("archived_end", &self.archived_end())
+            .field("has_storage", &self.storage.read().is_some())
+            .finish()
+    }
+}
+
+impl ArchiveStore {
+    pub fn new(db: Arc<DB>) -> Result<Self> {
+        let mut iter = db.iter::<ArchivedChunkSchema>(ReadOptions::default())?;
+        iter.seek_to_last();
+        let archived_end = match iter.next().transpose()? {
+            Some((_first_version, info)) => info.last_version + 1,
+            None => 0,
+        };
+        DIEM_STORAGE_ARCHIVED_VERSION.set(archived_end as i64);
+
+        Ok(Self {
+            db,
+            storage: RwLock::new(None),
+            archived_end: AtomicU64::new(archived_end),
+            cache: Mutex::new(None),
+        })
+    }
+
+    pub fn set_storage(&self, storage: Arc<dyn ArchiveStorage>) {
+        *self.storage.write() = Some(storage);
+        *self.cache.lock() = None;
+    }
+
+    pub fn archived_end(&self) -> Version {
+        self.archived_end.load(Ordering::Relaxed)
+    }
+
+    /// Gets the transaction at `version` if it's archived.
+    pub fn get_transaction(&self, version: Version) -> Result<Option<Transaction>> {
+        Ok(self.get_chunk(version)?.and_then(|chunk| {
+            chunk
+                .index_of(version)
+                .map(|idx| chunk.transactions[idx].clone())
+        }))
+    }
+
+    /// Gets the events emitted at `version` if it's archived.
+    pub fn get_events(&self, version: Version) -> Result<Option<Vec<ContractEvent>>> {
+        Ok(self
+            .get_chunk(version)?
+            .and_then(|chunk| chunk.index_of(version).map(|idx| chunk.events[idx].clone())))
+    }
+
+    fn get_chunk(&self, version: Version) -> Result<Option<Arc<ArchivedChunk>>> {
+        if version >= self.archived_end() {
+            return Ok(None);
+        }
+        if let Some(chunk) = self.cache.lock().as_ref() {
+            if chunk.contains(version) {
+                return Ok(Some(Arc::clone(chunk)));
+            }
+        }
+
+        let mut iter = self.db.iter::<ArchivedChunkSchema>(ReadOptions::default())?;
+        iter.seek_for_prev(&version)?;
+        let (first_version, info) = match iter.next().transpose()? {
+            Some((first_version, info)) if info.last_version >= version => (first_version, info),
+            _ => return Ok(None),
+        };
+
+        let storage = self.storage.read().clone().ok_or_else(|| {
+            format_err!(
+                "Version {} is archived, but no archive storage is configured.",
+                version
+            )
+        })?;
+        let chunk: ArchivedChunk = bcs::from_bytes(&storage.get(&info.file_name)?)?;
+        ensure!(
+            chunk.first_version == first_version
+                && chunk.transactions.len() as u64 == info.last_version - first_version + 1
+                && chunk.events.len() == chunk.transactions.len(),
+            "Archive file {} doesn't hold versions [{}, {}].",
+            info.file_name,
+            first_version,
+            info.last_version,
+        );
+
+        let chunk = Arc::new(chunk);
+        *self.cache.lock() = Some(Arc::clone(&chunk));
+        Ok(Some(chunk))
+    }
+
+    /// The first version of the next chunk to archive, `None` if there are no transactions.
+    fn next_version_to_archive(&self) -> Result<Option<Version>> {
+        let mut iter = self.db.iter::<ArchivedChunkSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        if iter.next().transpose()?.is_some() {
+            return Ok(Some(self.archived_end()));
+        }
+
+        let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        Ok(iter.next().transpose()?.map(|(version, _)| version))
+    }
+
+    /// Moves transactions and events with versions in `[begin, end)` into an archive file.
+    pub fn archive(&self, begin: Version, end: Version) -> Result<()> {
+        ensure!(begin < end, "Nothing to archive in [{}, {}).", begin, end);
+        let storage = self
+            .storage
+            .read()
+            .clone()
+            .ok_or_else(|| format_err!("No archive storage is configured."))?;
+
+        let mut batch = SchemaBatch::new();
+        let mut transactions = Vec::with_capacity((end - begin) as usize);
+        let mut events = vec![Vec::new(); (end - begin) as usize];
+        for version in begin..end {
+            let txn = self
+                .db
+                .get::<TransactionSchema>(&version)?
+                .ok_or_else(|| DiemDbError::NotFound(format!("Txn {}", version)))?;
+            transactions.push(txn);
+            batch.delete::<TransactionSchema>(&version)?;
+        }
+        let mut iter = self.db.iter::<EventSchema>(ReadOptions::default())?;
+        iter.seek(&begin)?;
+        for res in iter {
+            let ((version, index), event) = res?;
+            if version >= end {
+                break;
+            }
+            events[(version - begin) as usize].push(event);
+            batch.delete::<EventSchema>(&(version, index))?;
+        }
+
+        let chunk = ArchivedChunk {
+            first_version: begin,
+            transactions,
+            events,
+        };
+        let info = ArchivedChunkInfo {
+            last_version: end - 1,
+            file_name: format!("transactions_{}-{}.bcs", begin, end - 1),
+        };
+        storage.put(&info.file_name, &bcs::to_bytes(&chunk)?)?;
+
+        // Index the chunk before deleting anything, so every version stays readable throughout.
+        self.db.put::<ArchivedChunkSchema>(&begin, &info)?;
+        self.archived_end.store(end, Ordering::Relaxed);
+        self.db.write_schemas(batch)?;
+        DIEM_STORAGE_ARCHIVED_VERSION.set(end as i64);
+
+        Ok(())
+    }
+}
+
+/// The `Archiver` is meant to be part of a `DiemDB` instance and runs in the background, archiving
+/// old transactions and events as new ones are committed.
+///
+/// Like the `Pruner`, it creates a worker thread on construction and joins it on destruction.
+#[derive(Debug)]
+pub(crate) struct Archiver {
+    /// It only becomes `None` after joined in `drop()`.
+    worker_thread: Option<JoinHandle<()>>,
+    command_sender: Mutex<Sender<Command>>,
+}
+
+impl Archiver {
+    pub fn new(archive: Arc<ArchiveStore>, keep_versions: u64, chunk_size: u64) -> Self {
+        let (command_sender, command_receiver) = channel();
+        let worker_thread = std::thread::Builder::new()
+            .name("diemdb_archiver".into())
+            .spawn(move || {
+                Worker {
+                    archive,
+                    command_receiver,
+                    keep_versions,
+                    chunk_size: chunk_size.max(1),
+                }
+                .work()
+            })
+            .expect("Creating archiver thread should succeed.");
+
+        Self {
+            worker_thread: Some(worker_thread),
+            command_sender: Mutex::new(command_sender),
+        }
+    }
+
+    /// Sends archiving command to the worker thread, which archives all complete chunks more than
+    /// `keep_versions` behind `latest_version`.
+    pub fn wake(&self, latest_version: Version) {
+        self.command_sender
+            .lock()
+            .send(Command::Archive { latest_version })
+            .expect("Receiver should not destruct prematurely.");
+    }
+}
+
+impl Drop for Archiver {
+    fn drop(&mut self) {
+        self.command_sender
+            .lock()
+            .send(Command::Quit)
+            .expect("Receiver should not destruct.");
+        self.worker_thread
+            .take()
+            .expect("Worker thread must exist.")
+            .join()
+            .expect("Worker thread should join peacefully.");
+    }
+}
+
+enum Command {
+    Quit,
+    Archive { latest_version: Version },
+}
+
+struct Worker {
+    archive: Arc<ArchiveStore>,
+    command_receiver: Receiver<Command>,
+    keep_versions: u64,
+    chunk_size: u64,
+}
+
+impl Worker {
+    fn work(self) {
+        while let Ok(Command::Archive { mut latest_version }) = self.command_receiver.recv() {
+            // Only the latest of the pending commands matters.
+            for command in self.command_receiver.try_iter() {
+                match command {
+                    Command::Quit => return,
+                    Command::Archive {
+                        latest_version: version,
+                    } => latest_version = version,
+                }
+            }
+
+            let target_end = latest_version.saturating_sub(self.keep_versions);
+            loop {
+                match self.archive_one_chunk(target_end) {
+                    Ok(true) => (),
+                    Ok(false) => break,
+                    Err(e) => {
+                        error!(error = ?e, "Error archiving old data, will retry on next commit.");
+                        break;
+                    }
+                }
+                // Archiving a chunk can take a while, check for Quit in between.
+                if let Ok(Command::Quit) = self.command_receiver.try_recv() {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Archives the next chunk if it ends no later than `target_end`. Returns whether it did.
+    fn archive_one_chunk(&self, target_end: Version) -> Result<bool> {
+        let begin = match self.archive.next_version_to_archive()? {
+            Some(begin) => begin,
+            None => return Ok(false),
+        };
+        let end = begin.saturating_add(self.chunk_size);
+        if end > target_end {
+            return Ok(false);
+        }
+        self.archive.archive(begin, end)?;
+        info!(
+            first_version = begin,
+            last_version = end - 1,
+            "Archived transactions and events."
+        );
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,138 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{test_helper::arb_blocks_to_commit, DiemDB};
+use diem_config::config::{ArchiveConfig, RocksdbConfig};
+use diem_temppath::TempPath;
+use diem_types::{ledger_info::LedgerInfoWithSignatures, transaction::TransactionToCommit};
+use proptest::{prelude::*, sample::Index};
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+use storage_interface::{DbReader, DbWriter};
+
+fn save_blocks(
+    db: &DiemDB,
+    blocks: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)],
+) -> Version {
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in blocks {
+        db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as Version;
+    }
+    cur_ver - 1
+}
+
+fn verify_all_readable(
+    db: &DiemDB,
+    blocks: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)],
+    latest_version: Version,
+) {
+    let txns_to_commit: Vec<_> = blocks.iter().flat_map(|(txns, _)| txns).collect();
+    let txn_list = db
+        .get_transactions(0, latest_version + 1, latest_version, true)
+        .unwrap();
+    let events = txn_list.events.as_ref().unwrap();
+    assert_eq!(txn_list.transactions.len(), txns_to_commit.len());
+    for (idx, txn_to_commit) in txns_to_commit.iter().enumerate() {
+        assert_eq!(&txn_list.transactions[idx], txn_to_commit.transaction());
+        assert_eq!(events[idx].as_slice(), txn_to_commit.events());
+    }
+
+    let txns_from_iter = db
+        .transaction_store
+        .get_transaction_iter(0, latest_version as usize + 1)
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(txns_from_iter, txn_list.transactions);
+    let events_from_iter = db
+        .event_store
+        .get_events_by_version_iter(0, latest_version as usize + 1)
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(&events_from_iter, events);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_read_archived(input in arb_blocks_to_commit(), index in any::<Index>()) {
+        let tmp_dir = TempPath::new();
+        let archive_dir = TempPath::new();
+        let latest_version = {
+            let db = DiemDB::new_for_test(&tmp_dir);
+            let latest_version = save_blocks(&db, &input);
+            db.archive.set_storage(Arc::new(
+                LocalArchiveStorage::new(archive_dir.path().to_path_buf()).unwrap(),
+            ));
+
+            let end = index.index(latest_version as usize + 1) as Version + 1;
+            db.archive.archive(0, end).unwrap();
+            prop_assert_eq!(db.archive.archived_end(), end);
+            // Moved out of the DB.
+            prop_assert!(db.db.get::<TransactionSchema>(&0).unwrap().is_none());
+
+            verify_all_readable(&db, &input, latest_version);
+            latest_version
+        };
+
+        // The archive index is loaded on reopening, and the archive storage is needed to read.
+        let db = DiemDB::new_for_test(&tmp_dir);
+        prop_assert!(db.get_transactions(0, 1, latest_version, false).is_err());
+        let db = db.with_archive_storage(Arc::new(
+            LocalArchiveStorage::new(archive_dir.path().to_path_buf()).unwrap(),
+        ));
+        verify_all_readable(&db, &input, latest_version);
+    }
+
+    #[test]
+    fn test_archiver(input in arb_blocks_to_commit()) {
+        const KEEP_VERSIONS: u64 = 1;
+        const CHUNK_SIZE: u64 = 2;
+
+        let tmp_dir = TempPath::new();
+        let archive_dir = TempPath::new();
+        let db = DiemDB::open(&tmp_dir, false, None, RocksdbConfig::default())
+            .unwrap()
+            .with_archive(&ArchiveConfig {
+                keep_versions: KEEP_VERSIONS,
+                chunk_size: CHUNK_SIZE,
+                storage: ArchiveStorageConfig::Local {
+                    dir: archive_dir.path().to_path_buf(),
+                },
+            })
+            .unwrap();
+        let latest_version = save_blocks(&db, &input);
+
+        // Only complete chunks ending `KEEP_VERSIONS` behind the latest version are archived.
+        let expected_end = latest_version.saturating_sub(KEEP_VERSIONS) / CHUNK_SIZE * CHUNK_SIZE;
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while db.archive.archived_end() < expected_end {
+            prop_assert!(Instant::now() < deadline, "Timeout waiting for archiver.");
+            sleep(Duration::from_millis(1));
+        }
+        prop_assert_eq!(db.archive.archived_end(), expected_end);
+
+        verify_all_readable(&db, &input, latest_version);
+    }
+}
+
+#[test]
+fn test_command_archive_storage() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let storage = CommandArchiveStorage::new(
+        format!("cat > {}/$FILE_NAME", dir.path().display()),
+        format!("cat {}/$FILE_NAME", dir.path().display()),
+    );
+
+    storage.put("a.bcs", b"hello").unwrap();
+    assert_eq!(storage.get("a.bcs").unwrap(), b"hello".to_vec());
+    assert!(storage.get("missing.bcs").is_err());
+}
@@ -7,9 +7,13 @@ use crate::{
     transaction_store::TransactionStore, DiemDB,
 };
 use anyhow::{ensure, Result};
-use diem_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
+use diem_crypto::{
+    hash::{CryptoHash, SPARSE_MERKLE_PLACEHOLDER_HASH},
+    HashValue,
+};
 use diem_jellyfish_merkle::restore::JellyfishMerkleRestore;
 use diem_types::{
+    account_address::AccountAddress,
     account_state_blob::AccountStateBlob,
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
@@ -62,6 +66,48 @@ impl RestoreHandler {
         )
     }
 
+    /// Starts a bulk import of trusted account states into the state tree at `version`, which
+    /// takes chunks of accounts sorted by hashed address without proofs, and tells the root hash
+    /// when finished.
+    pub fn get_state_bulk_import_receiver(
+        &self,
+        version: Version,
+    ) -> Result<JellyfishMerkleRestore<AccountStateBlob>> {
+        JellyfishMerkleRestore::new_bulk_import(Arc::clone(&self.state_store), version)
+    }
+
+    /// Builds the state tree at `version` from `account_states` bottom-up, writing `chunk_size`
+    /// accounts at a time, and returns its root hash. For genesis generation and chain migration
+    /// tools, which can import the migrated state at `PRE_GENESIS_VERSION` and then apply a
+    /// genesis transaction on top of it.
+    pub fn import_account_states(
+        &self,
+        version: Version,
+        account_states: Vec<(AccountAddress, AccountStateBlob)>,
+        chunk_size: usize,
+    ) -> Result<HashValue> {
+        ensure!(chunk_size > 0, "Chunk size must be positive.");
+        let mut account_states = account_states
+            .into_iter()
+            .map(|(address, blob)| (address.hash(), blob))
+            .collect::<Vec<_>>();
+        account_states.sort_unstable_by_key(|(key, _)| *key);
+
+        let mut receiver = self.get_state_bulk_import_receiver(version)?;
+        let mut chunk = Vec::with_capacity(chunk_size);
+        for account_state in account_states {
+            chunk.push(account_state);
+            if chunk.len() == chunk_size {
+                receiver.add_sorted_chunk(chunk)?;
+                chunk = Vec::with_capacity(chunk_size);
+            }
+        }
+        if !chunk.is_empty() {
+            receiver.add_sorted_chunk(chunk)?;
+        }
+        receiver.finish_with_root_hash()
+    }
+
     pub fn save_ledger_infos(&self, ledger_infos: &[LedgerInfoWithSignatures]) -> Result<()> {
         ensure!(!ledger_infos.is_empty(), "No LedgerInfos to save.");
 
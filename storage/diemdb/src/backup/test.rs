@@ -1,11 +1,12 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{test_helper::arb_blocks_to_commit, DiemDB};
+use crate::{test_helper::arb_blocks_to_commit, DiemDB, GetRestoreHandler};
 use anyhow::Result;
 use diem_temppath::TempPath;
 use proptest::prelude::*;
-use storage_interface::DbWriter;
+use std::{collections::HashMap, sync::Arc};
+use storage_interface::{DbReader, DbWriter};
 
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
@@ -41,4 +42,39 @@ proptest! {
             .unwrap();
         prop_assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_import_account_states(input in arb_blocks_to_commit(), chunk_size in 1usize..10) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+        let mut cur_ver = 0;
+        let mut account_states = HashMap::new();
+        for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+            db.save_transactions(&txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                .unwrap();
+            cur_ver += txns_to_commit.len() as u64;
+            for txn_to_commit in txns_to_commit {
+                account_states.extend(txn_to_commit.account_states().clone());
+            }
+        }
+        let expected_root_hash = db.state_store.get_root_hash(cur_ver - 1).unwrap();
+
+        let tmp_dir = TempPath::new();
+        let import_db = Arc::new(DiemDB::new_for_test(&tmp_dir));
+        let root_hash = import_db
+            .get_restore_handler()
+            .import_account_states(
+                cur_ver - 1,
+                account_states.clone().into_iter().collect(),
+                chunk_size,
+            )
+            .unwrap();
+        prop_assert_eq!(root_hash, expected_root_hash);
+        for (address, blob) in account_states {
+            let (account_state, _) = import_db
+                .get_account_state_with_proof_by_version(address, cur_ver - 1)
+                .unwrap();
+            prop_assert_eq!(account_state, Some(blob));
+        }
+    }
 }
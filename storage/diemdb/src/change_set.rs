@@ -4,7 +4,7 @@
 use crate::ledger_counters::LedgerCounterBumps;
 use diem_types::transaction::Version;
 use schemadb::SchemaBatch;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Structure that collects changes to be made to the DB in one transaction.
 ///
@@ -14,6 +14,8 @@ use std::collections::HashMap;
 pub(crate) struct ChangeSet {
     /// A batch of db alternations.
     pub batch: SchemaBatch,
+    /// Alternations of the state shards, by shard, written before `batch`. See `StateShards`.
+    pub shard_batches: BTreeMap<usize, SchemaBatch>,
     /// Counter bumps to be made on commit.
     counter_bumps: HashMap<Version, LedgerCounterBumps>,
 }
@@ -23,10 +25,17 @@ impl ChangeSet {
     pub fn new() -> Self {
         Self {
             batch: SchemaBatch::new(),
+            shard_batches: BTreeMap::new(),
             counter_bumps: HashMap::new(),
         }
     }
 
+    pub fn shard_batch(&mut self, shard: usize) -> &mut SchemaBatch {
+        self.shard_batches
+            .entry(shard)
+            .or_insert_with(SchemaBatch::new)
+    }
+
     pub fn counter_bumps(&mut self, version: Version) -> &mut LedgerCounterBumps {
         self.counter_bumps
             .entry(version)
@@ -37,6 +46,7 @@ impl ChangeSet {
     pub fn new_with_bumps(counter_bumps: HashMap<Version, LedgerCounterBumps>) -> Self {
         Self {
             batch: SchemaBatch::new(),
+            shard_batches: BTreeMap::new(),
             counter_bumps,
         }
     }
@@ -48,4 +58,6 @@ impl ChangeSet {
 pub(crate) struct SealedChangeSet {
     /// A batch of db alternations.
     pub batch: SchemaBatch,
+    /// Alternations of the state shards, by shard, written before `batch`.
+    pub shard_batches: BTreeMap<usize, SchemaBatch>,
 }
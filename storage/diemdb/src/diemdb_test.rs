@@ -79,7 +79,13 @@ fn verify_epochs(db: &DiemDB, ledger_infos_with_sigs: &[LedgerInfoWithSignatures
 pub fn test_save_blocks_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>) {
     let tmp_dir = TempPath::new();
     let db = DiemDB::new_for_test(&tmp_dir);
+    save_and_verify_blocks(&db, input);
+}
 
+fn save_and_verify_blocks(
+    db: &DiemDB,
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
     let num_batches = input.len();
     let mut cur_ver = 0;
     for (batch_idx, (txns_to_commit, ledger_info_with_sigs)) in input.iter().enumerate() {
@@ -95,7 +101,7 @@ pub fn test_save_blocks_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWit
             *ledger_info_with_sigs
         );
         verify_committed_transactions(
-            &db,
+            db,
             &txns_to_commit,
             cur_ver,
             ledger_info_with_sigs,
@@ -110,7 +116,7 @@ pub fn test_save_blocks_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWit
     let latest_ledger_info = input.last().unwrap().1.clone();
     // Verify an old batch with the latest LedgerInfo.
     verify_committed_transactions(
-        &db,
+        db,
         &first_batch,
         0,
         &latest_ledger_info,
@@ -118,14 +124,14 @@ pub fn test_save_blocks_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWit
     );
     // Verify an old batch with an old LedgerInfo.
     verify_committed_transactions(
-        &db,
+        db,
         &first_batch,
         0,
         &first_batch_ledger_info,
         true, /* is_latest */
     );
     let (_, ledger_infos_with_sigs): (Vec<_>, Vec<_>) = input.iter().cloned().unzip();
-    verify_epochs(&db, &ledger_infos_with_sigs);
+    verify_epochs(db, &ledger_infos_with_sigs);
 }
 
 fn test_sync_transactions_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>) {
@@ -397,10 +403,71 @@ proptest! {
         test_save_blocks_impl(input);
     }
 
+    #[test]
+    fn test_save_blocks_in_memory(input in arb_blocks_to_commit()) {
+        save_and_verify_blocks(&DiemDB::new_in_memory(PrunerOptions::default()), input);
+    }
+
+    #[test]
+    fn test_prune_in_memory(input in arb_blocks_to_commit()) {
+        let db = DiemDB::new_in_memory(PrunerOptions {
+            historical_versions_to_keep: Some(0),
+            prune_ledger: true,
+            ..Default::default()
+        });
+        let mut cur_ver = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in &input {
+            db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                .unwrap();
+            cur_ver += txns_to_commit.len() as u64;
+        }
+        let latest_version = cur_ver - 1;
+        db.pruner.as_ref().unwrap().wake_and_wait(latest_version).unwrap();
+
+        prop_assert_eq!(db.get_earliest_available_version().unwrap(), latest_version);
+        prop_assert!(db.get_transactions(latest_version, 1, latest_version, true).is_ok());
+        if latest_version > 0 {
+            prop_assert!(db.get_transactions(0, 1, latest_version, false).is_err());
+        }
+    }
+
     #[test]
     fn test_sync_transactions(input in arb_blocks_to_commit()) {
         test_sync_transactions_impl(input);
     }
+
+    #[test]
+    fn test_open_as_secondary(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let secondary_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+
+        let mut cur_ver = 0;
+        let (txns_to_commit, ledger_info_with_sigs) = &input[0];
+        db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+
+        let secondary = DiemDB::open_as_secondary(
+            tmp_dir.path(),
+            secondary_dir.path(),
+            RocksdbConfig::default(),
+        )
+        .unwrap();
+        prop_assert_eq!(&secondary.get_latest_ledger_info().unwrap(), ledger_info_with_sigs);
+
+        for (txns_to_commit, ledger_info_with_sigs) in input.iter().skip(1) {
+            db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                .unwrap();
+            cur_ver += txns_to_commit.len() as u64;
+        }
+        secondary.try_catch_up_with_primary().unwrap();
+        prop_assert_eq!(
+            &secondary.get_latest_ledger_info().unwrap(),
+            &input.last().unwrap().1
+        );
+        prop_assert_eq!(secondary.get_latest_version().unwrap(), cur_ver - 1);
+    }
 }
 
 #[test]
@@ -444,6 +511,46 @@ fn test_too_many_requested() {
     assert!(db.get_transactions(0, 1001 /* limit */, 0, true).is_err());
 }
 
+#[test]
+fn test_get_rocksdb_stats() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+
+    let stats = db.get_rocksdb_stats().unwrap();
+    let mut cf_names = DiemDB::column_families();
+    cf_names.sort_unstable();
+    assert_eq!(
+        stats
+            .column_families
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>(),
+        cf_names,
+    );
+    for properties in stats.column_families.values() {
+        assert_eq!(properties.len(), ROCKSDB_PROPERTY_MAP.len());
+    }
+    // Statistics are enabled by default.
+    assert!(stats.tickers.contains_key("rocksdb.block.cache.hit"));
+    assert!(stats.tickers.contains_key("rocksdb.block.cache.miss"));
+}
+
+#[test]
+fn test_parse_rocksdb_tickers() {
+    let statistics = "rocksdb.block.cache.miss COUNT : 10\n\
+                      rocksdb.block.cache.hit COUNT : 30\n\
+                      rocksdb.bytes.written COUNT : 100\n\
+                      rocksdb.db.get.micros P50 : 1.000000 P95 : 2.000000 COUNT : 3 SUM : 4\n";
+    let tickers = parse_rocksdb_tickers(statistics);
+    assert_eq!(
+        tickers.into_iter().collect::<Vec<_>>(),
+        vec![
+            ("rocksdb.block.cache.hit".to_string(), 30),
+            ("rocksdb.block.cache.miss".to_string(), 10),
+        ]
+    );
+}
+
 #[test]
 fn test_get_latest_tree_state() {
     let tmp_dir = TempPath::new();
@@ -7,12 +7,15 @@
 
 use super::DiemDB;
 use crate::{
+    archive::ArchiveStore,
     change_set::ChangeSet,
     errors::DiemDbError,
+    indexer::{collect_page, currency_involved, Indexer},
     ledger_counters::{LedgerCounter, LedgerCounterBumps},
     schema::{
         event::EventSchema, event_accumulator::EventAccumulatorSchema,
-        event_by_key::EventByKeySchema, event_by_version::EventByVersionSchema,
+        event_by_key::EventByKeySchema, event_by_type::EventByTypeSchema,
+        event_by_version::EventByVersionSchema,
     },
 };
 use accumulator::{HashReader, MerkleAccumulator};
@@ -30,7 +33,8 @@ use diem_types::{
     proof::{position::Position, EventAccumulatorProof, EventProof},
     transaction::Version,
 };
-use schemadb::{schema::ValueCodec, ReadOptions, SchemaIterator, DB};
+use move_core_types::language_storage::TypeTag;
+use schemadb::{schema::ValueCodec, ReadOptions, SchemaBatch, SchemaIterator, DB};
 use std::{
     convert::{TryFrom, TryInto},
     iter::Peekable,
@@ -40,17 +44,60 @@ use std::{
 #[derive(Debug)]
 pub(crate) struct EventStore {
     db: Arc<DB>,
+    /// Whether events are indexed by type in `EventByTypeSchema`.
+    index_by_type: bool,
+    /// Where events moved out of the DB are read back from.
+    archive: Option<Arc<ArchiveStore>>,
+    /// Indexes events by the accounts and currencies they involve, if enabled.
+    indexer: Option<Indexer>,
 }
 
 impl EventStore {
-    pub fn new(db: Arc<DB>) -> Self {
-        Self { db }
+    pub fn new(db: Arc<DB>, index_by_type: bool) -> Self {
+        Self {
+            db,
+            index_by_type,
+            archive: None,
+            indexer: None,
+        }
+    }
+
+    pub fn with_archive(mut self, archive: Arc<ArchiveStore>) -> Self {
+        self.archive = Some(archive);
+        self
+    }
+
+    pub fn with_indexer(mut self, enabled: bool) -> Self {
+        self.indexer = if enabled {
+            Some(Indexer::new(Arc::clone(&self.db)))
+        } else {
+            None
+        };
+        self
+    }
+
+    pub fn index_by_type(&self) -> bool {
+        self.index_by_type
+    }
+
+    pub fn has_indexer(&self) -> bool {
+        self.indexer.is_some()
+    }
+
+    fn get_archived_events(&self, version: Version) -> Result<Option<Vec<ContractEvent>>> {
+        match &self.archive {
+            Some(archive) => archive.get_events(version),
+            None => Ok(None),
+        }
     }
 
     /// Get all of the events given a transaction version.
     /// We don't need a proof for this because it's only used to get all events
     /// for a version which can be proved from the root hash of the event tree.
     pub fn get_events_by_version(&self, version: Version) -> Result<Vec<ContractEvent>> {
+        if let Some(events) = self.get_archived_events(version)? {
+            return Ok(events);
+        }
         let mut events = vec![];
 
         let mut iter = self.db.iter::<EventSchema>(ReadOptions::default())?;
@@ -76,6 +123,7 @@ impl EventStore {
 
         Ok(EventsByVersionIter {
             inner: iter.peekable(),
+            archive: self.archive.as_deref(),
             expected_next_version: start_version,
             end_version: start_version
                 .checked_add(num_versions as u64)
@@ -88,11 +136,15 @@ impl EventStore {
         version: Version,
         index: u64,
     ) -> Result<ContractEvent> {
-        self.db
-            .get::<EventSchema>(&(version, index))?
-            .ok_or_else(|| {
-                DiemDbError::NotFound(format!("Event {} of Txn {}", index, version)).into()
-            })
+        if let Some(event) = self.db.get::<EventSchema>(&(version, index))? {
+            return Ok(event);
+        }
+        if let Some(mut events) = self.get_archived_events(version)? {
+            if index < events.len() as u64 {
+                return Ok(events.swap_remove(index as usize));
+            }
+        }
+        Err(DiemDbError::NotFound(format!("Event {} of Txn {}", index, version)).into())
     }
 
     /// Get the event raw data given transaction version and the index of the event queried.
@@ -105,11 +157,16 @@ impl EventStore {
         let event = self.get_event_by_version_and_index(version, index)?;
 
         // Get the number of events in total for the transaction at `version`.
-        let mut iter = self.db.iter::<EventSchema>(ReadOptions::default())?;
-        iter.seek_for_prev(&(version + 1))?;
-        let num_events = match iter.next().transpose()? {
-            Some(((ver, index), _)) if ver == version => (index + 1),
-            _ => unreachable!(), // since we've already got at least one event above
+        let num_events = match self.get_archived_events(version)? {
+            Some(events) => events.len() as u64,
+            None => {
+                let mut iter = self.db.iter::<EventSchema>(ReadOptions::default())?;
+                iter.seek_for_prev(&(version + 1))?;
+                match iter.next().transpose()? {
+                    Some(((ver, index), _)) if ver == version => (index + 1),
+                    _ => unreachable!(), // since we've already got at least one event above
+                }
+            }
         };
 
         // Get proof.
@@ -168,6 +225,23 @@ impl EventStore {
             })
     }
 
+    /// Get the sequence number of the first event on `event_key` emitted at or after `version`.
+    /// Returns `None` if no such event exists.
+    pub fn get_first_sequence_number_at_or_after_version(
+        &self,
+        event_key: &EventKey,
+        version: Version,
+    ) -> Result<Option<u64>> {
+        let mut iter = self
+            .db
+            .iter::<EventByVersionSchema>(ReadOptions::default())?;
+        iter.seek(&(*event_key, version, 0))?;
+
+        Ok(iter.next().transpose()?.and_then(
+            |((key, _version, seq), _idx)| if &key == event_key { Some(seq) } else { None },
+        ))
+    }
+
     /// Given `event_key` and `start_seq_num`, returns events identified by transaction version and
     /// index among all events emitted by the same transaction. Result won't contain records with a
     /// transaction version > `ledger_version` and is in ascending order.
@@ -207,6 +281,91 @@ impl EventStore {
         Ok(result)
     }
 
+    /// Returns up to `limit` events of type `type_tag` emitted by transactions with versions from
+    /// `start_version` to `ledger_version`, in ascending order, along with the versions of those
+    /// transactions. Unless a page holds the events of a single transaction, it ends with the last
+    /// event of a transaction, so that the next page starts at the version after it.
+    pub fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, ContractEvent)>> {
+        ensure!(self.index_by_type, "Events are not indexed by type.");
+        let type_tag_hash = hash_type_tag(type_tag)?;
+
+        let mut iter = self.db.iter::<EventByTypeSchema>(ReadOptions::default())?;
+        iter.seek(&(type_tag_hash, start_version, 0))?;
+        let indices = collect_page(
+            iter.take_while(|res| {
+                res.as_ref()
+                    .map_or(true, |((hash, _, _), ())| *hash == type_tag_hash)
+            })
+            .map(|res| res.map(|((_, ver, idx), ())| (ver, idx))),
+            limit,
+            ledger_version,
+        )?;
+
+        indices
+            .into_iter()
+            .map(|(ver, idx)| Ok((ver, self.get_event_by_version_and_index(ver, idx)?)))
+            // Guard against type tags with colliding hashes.
+            .filter(|res| {
+                res.as_ref()
+                    .map_or(true, |(_, event)| event.type_tag() == type_tag)
+            })
+            .collect()
+    }
+
+    /// Like `get_events_by_type`, returns the events involving `address`: the events on the event
+    /// streams of `address`, and the payments from or to it. Requires the indexer.
+    pub fn get_events_by_account(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, ContractEvent)>> {
+        let indexer = self
+            .indexer
+            .as_ref()
+            .ok_or_else(|| format_err!("Events are not indexed by account."))?;
+
+        indexer
+            .lookup_events_by_account(address, start_version, limit, ledger_version)?
+            .into_iter()
+            .map(|(ver, idx)| Ok((ver, self.get_event_by_version_and_index(ver, idx)?)))
+            .collect()
+    }
+
+    /// Like `get_events_by_type`, returns the payment, mint, burn and exchange rate update events
+    /// of the currency `currency_code`. Requires the indexer.
+    pub fn get_events_by_currency(
+        &self,
+        currency_code: &str,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, ContractEvent)>> {
+        let indexer = self
+            .indexer
+            .as_ref()
+            .ok_or_else(|| format_err!("Events are not indexed by currency."))?;
+
+        indexer
+            .lookup_events_by_currency(currency_code, start_version, limit, ledger_version)?
+            .into_iter()
+            .map(|(ver, idx)| Ok((ver, self.get_event_by_version_and_index(ver, idx)?)))
+            // Guard against currency codes with colliding hashes.
+            .filter(|res| {
+                res.as_ref().map_or(true, |(_, event)| {
+                    currency_involved(event).map_or(false, |code| code.as_str() == currency_code)
+                })
+            })
+            .collect()
+    }
+
     fn lookup_event_by_key(
         &self,
         event_key: &EventKey,
@@ -251,6 +410,15 @@ impl EventStore {
                     &(*event.key(), version, event.sequence_number()),
                     &(idx as u64),
                 )?;
+                if self.index_by_type {
+                    cs.batch.put::<EventByTypeSchema>(
+                        &(hash_type_tag(event.type_tag())?, version, idx as u64),
+                        &(),
+                    )?;
+                }
+                if let Some(indexer) = &self.indexer {
+                    indexer.index_event(version, idx as u64, event, &mut cs.batch)?;
+                }
                 Ok(())
             })?;
 
@@ -283,6 +451,59 @@ impl EventStore {
             .collect::<Result<Vec<_>>>()
     }
 
+    /// Deletes the events emitted by transactions with versions in `[begin, end)`, their indices
+    /// and their event accumulators.
+    pub(crate) fn prune_events(
+        &self,
+        begin: Version,
+        end: Version,
+        batch: &mut SchemaBatch,
+    ) -> Result<()> {
+        let mut iter = self.db.iter::<EventSchema>(ReadOptions::default())?;
+        iter.seek(&begin)?;
+        for res in iter {
+            let ((version, idx), event) = res?;
+            if version >= end {
+                break;
+            }
+            batch.delete::<EventSchema>(&(version, idx))?;
+            batch.delete::<EventByKeySchema>(&(*event.key(), event.sequence_number()))?;
+            batch.delete::<EventByVersionSchema>(&(
+                *event.key(),
+                version,
+                event.sequence_number(),
+            ))?;
+            // Deleting an entry never indexed is harmless.
+            batch.delete::<EventByTypeSchema>(&(hash_type_tag(event.type_tag())?, version, idx))?;
+            Indexer::unindex_event(version, idx, &event, batch)?;
+        }
+
+        let mut iter = self
+            .db
+            .iter::<EventAccumulatorSchema>(ReadOptions::default())?;
+        iter.seek(&(begin, Position::from_inorder_index(0)))?;
+        for res in iter {
+            let ((version, position), _hash) = res?;
+            if version >= end {
+                break;
+            }
+            batch.delete::<EventAccumulatorSchema>(&(version, position))?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the sequence number of the first event on `event_key` that hasn't been pruned.
+    fn get_first_available_sequence_number(&self, event_key: &EventKey) -> Result<Option<u64>> {
+        let mut iter = self.db.iter::<EventByKeySchema>(ReadOptions::default())?;
+        iter.seek(&(*event_key, 0))?;
+
+        Ok(iter
+            .next()
+            .transpose()?
+            .and_then(|((key, seq), _)| if &key == event_key { Some(seq) } else { None }))
+    }
+
     /// Finds the first event sequence number in a specified stream on which `comp` returns false.
     /// (assuming the whole stream is partitioned by `comp`)
     fn search_for_event_lower_bound<C>(
@@ -294,7 +515,10 @@ impl EventStore {
     where
         C: FnMut(&ContractEvent) -> Result<bool>,
     {
-        let mut begin = 0u64;
+        // Events before the first available one might have been pruned.
+        let mut begin = self
+            .get_first_available_sequence_number(event_key)?
+            .unwrap_or(0);
         let mut end = match self.get_latest_sequence_number(ledger_version, event_key)? {
             Some(s) => s
                 .checked_add(1)
@@ -348,8 +572,11 @@ impl EventStore {
         ))?;
 
         ensure!(
-            seq_at_or_after_ts > 0,
-            "First block started at or after timestamp {}.",
+            seq_at_or_after_ts
+                > self
+                    .get_first_available_sequence_number(&event_key)?
+                    .unwrap_or(0),
+            "First available block started at or after timestamp {}.",
             timestamp,
         );
 
@@ -362,6 +589,11 @@ impl EventStore {
     }
 }
 
+/// Identifies `type_tag` in `EventByTypeSchema`.
+fn hash_type_tag(type_tag: &TypeTag) -> Result<HashValue> {
+    Ok(HashValue::sha3_256_of(&bcs::to_bytes(type_tag)?))
+}
+
 type Accumulator<'a> = MerkleAccumulator<EventHashReader<'a>, EventAccumulatorHasher>;
 
 struct EventHashReader<'a> {
@@ -397,6 +629,7 @@ impl HashReader for EmptyReader {
 
 pub struct EventsByVersionIter<'a> {
     inner: Peekable<SchemaIterator<'a, EventSchema>>,
+    archive: Option<&'a ArchiveStore>,
     expected_next_version: Version,
     end_version: Version,
 }
@@ -407,7 +640,12 @@ impl<'a> EventsByVersionIter<'a> {
             return Ok(None);
         }
 
-        let mut ret = Vec::new();
+        // Archived versions are all before the ones left in the DB, which `inner` starts from.
+        let archived = match self.archive {
+            Some(archive) => archive.get_events(self.expected_next_version)?,
+            None => None,
+        };
+        let mut ret = archived.unwrap_or_default();
         while let Some(res) = self.inner.peek() {
             let ((version, _index), _event) = res
                 .as_ref()
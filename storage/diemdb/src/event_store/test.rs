@@ -236,6 +236,22 @@ fn test_index_get_impl(event_batches: Vec<Vec<ContractEvent>>) {
                         "next_seq equals this since last seq bump.",
                     );
                 }
+                if prev_ver < mid || seq == 0 {
+                    assert_eq!(
+                        store
+                            .get_first_sequence_number_at_or_after_version(&path, mid)
+                            .unwrap(),
+                        Some(seq as u64),
+                        "first seq at or after a version in the gap is this one.",
+                    );
+                }
+                assert_eq!(
+                    store
+                        .get_first_sequence_number_at_or_after_version(&path, *ver)
+                        .unwrap(),
+                    Some(seq as u64),
+                    "first seq at or after its version is this one.",
+                );
                 // possible multiple emits of the event in the same version
                 let mut last_seq_in_same_version = seq;
                 while let Some((next_seq, (_, next_ver))) = iter.peek() {
@@ -255,6 +271,14 @@ fn test_index_get_impl(event_batches: Vec<Vec<ContractEvent>>) {
                 prev_ver = *ver;
             }
 
+            assert_eq!(
+                store
+                    .get_first_sequence_number_at_or_after_version(&path, ledger_version_plus_one)
+                    .unwrap(),
+                None,
+                "no event beyond the last version.",
+            );
+
             // Fetch by key
             let events = events_and_versions
                 .into_iter()
@@ -367,3 +391,139 @@ proptest! {
         test_get_last_version_before_timestamp_impl(new_block_events)
     }
 }
+
+#[test]
+fn test_get_events_by_type() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    let store = EventStore::new(Arc::clone(&db.db), true);
+
+    let key = EventKey::random();
+    let event = |seq_num, type_tag| ContractEvent::new(key, seq_num, type_tag, vec![]);
+    let a = || TypeTag::U64;
+    let b = || TypeTag::Bool;
+    save(&store, 0, &[event(0, a()), event(1, b()), event(2, a())]);
+    save(&store, 1, &[event(3, b())]);
+    save(&store, 2, &[event(4, a()), event(5, a())]);
+    save(&store, 3, &[event(6, a())]);
+
+    let versions = |start_version, limit, ledger_version| {
+        store
+            .get_events_by_type(&a(), start_version, limit, ledger_version)
+            .unwrap()
+            .into_iter()
+            .map(|(version, event)| {
+                assert_eq!(event.type_tag(), &a());
+                (version, event.sequence_number())
+            })
+            .collect::<Vec<_>>()
+    };
+    // the events of version 2 do not fit in the first page and are left to the next one
+    assert_eq!(versions(0, 3, 3), vec![(0, 0), (0, 2)]);
+    assert_eq!(versions(1, 3, 3), vec![(2, 4), (2, 5), (3, 6)]);
+    // a page holding a single transaction is cut by the limit
+    assert_eq!(versions(0, 1, 3), vec![(0, 0)]);
+    assert_eq!(versions(1, 10, 2), vec![(2, 4), (2, 5)]);
+    assert!(versions(4, 10, 3).is_empty());
+
+    // without the index, nothing is indexed and queries fail
+    assert!(db.event_store.get_events_by_type(&a(), 0, 10, 3).is_err());
+}
+
+#[test]
+fn test_prune_events() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    let store = EventStore::new(Arc::clone(&db.db), true);
+
+    let key = EventKey::random();
+    let event = |seq_num| ContractEvent::new(key, seq_num, TypeTag::U64, vec![]);
+    save(&store, 0, &[event(0), event(1)]);
+    save(&store, 1, &[event(2)]);
+    save(&store, 2, &[event(3)]);
+
+    let mut batch = SchemaBatch::new();
+    store.prune_events(0, 2, &mut batch).unwrap();
+    store.db.write_schemas(batch).unwrap();
+
+    assert!(store.get_events_by_version(0).unwrap().is_empty());
+    assert!(store.get_events_by_version(1).unwrap().is_empty());
+    assert_eq!(store.get_events_by_version(2).unwrap(), vec![event(3)]);
+    assert!(store
+        .get_event_with_proof_by_version_and_index(1, 0)
+        .is_err());
+    assert!(store
+        .get_event_with_proof_by_version_and_index(2, 0)
+        .is_ok());
+    assert!(store
+        .lookup_events_by_key(&key, 0, 10, 2)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        store.lookup_events_by_key(&key, 3, 10, 2).unwrap(),
+        vec![(3, 2, 0)]
+    );
+    assert_eq!(store.get_latest_sequence_number(2, &key).unwrap(), Some(3));
+    assert_eq!(
+        store
+            .get_first_sequence_number_at_or_after_version(&key, 0)
+            .unwrap(),
+        Some(3)
+    );
+    assert_eq!(
+        store
+            .get_events_by_type(&TypeTag::U64, 0, 10, 2)
+            .unwrap()
+            .into_iter()
+            .map(|(version, _)| version)
+            .collect::<Vec<_>>(),
+        vec![2]
+    );
+}
+
+#[test]
+fn test_get_last_version_before_timestamp_after_pruning() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    let store = &db.event_store;
+
+    let new_block_event = |seq, timestamp| {
+        ContractEvent::new(
+            new_block_event_key(),
+            seq,
+            TypeTag::Struct(NewBlockEvent::struct_tag()),
+            bcs::to_bytes(&NewBlockEvent::new(
+                seq,
+                AccountAddress::ZERO,
+                Vec::new(),
+                timestamp,
+            ))
+            .unwrap(),
+        )
+    };
+    // Blocks start at versions 1, 11, 21 and 31, at timestamps 100, 200, 300 and 400.
+    for seq in 0..4 {
+        save(
+            store,
+            seq * 10 + 1,
+            &[new_block_event(seq, (seq + 1) * 100)],
+        );
+    }
+    assert_eq!(
+        store.get_last_version_before_timestamp(250, 31).unwrap(),
+        20
+    );
+
+    let mut batch = SchemaBatch::new();
+    store.prune_events(0, 15, &mut batch).unwrap();
+    store.db.write_schemas(batch).unwrap();
+
+    // The search only goes through the blocks left.
+    assert_eq!(
+        store.get_last_version_before_timestamp(350, 31).unwrap(),
+        30
+    );
+    // What happened before the first block left is unknown.
+    assert!(store.get_last_version_before_timestamp(250, 31).is_err());
+    assert!(store.get_last_version_before_timestamp(150, 31).is_err());
+}
@@ -0,0 +1,226 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `GroupCommitter`, which writes the change sets of `save_transactions`
+//! without waiting for the RocksDB WAL to be synced to disk, and syncs the writes of many saves at
+//! once instead, so that catching up through state sync, which saves chunk after chunk, isn't
+//! held back by one fsync per chunk on slow disks.
+//!
+//! The WAL is synced once `max_bytes` are written unsynced, or by a worker thread once the oldest
+//! unsynced write is `max_latency` old, whichever comes first. Saves carrying a ledger info are
+//! always synced, along with everything written before them. A crash can lose at most the saves
+//! since the last sync, RocksDB recovering the ones before some point, which state sync then
+//! fetches again.
+
+#[cfg(test)]
+mod test;
+
+use crate::metrics::{DIEM_STORAGE_GROUP_COMMIT_SYNCS, DIEM_STORAGE_OTHER_TIMERS_SECONDS};
+use anyhow::Result;
+use diem_infallible::Mutex;
+use diem_logger::prelude::*;
+use schemadb::{SchemaBatch, DB};
+use std::{
+    sync::{
+        mpsc::{channel, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread::{sleep, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// The `GroupCommitter` is meant to be part of a `DiemDB` instance, which writes the main batch of
+/// every save through it.
+///
+/// It creates a worker thread on construction and joins it on destruction, syncing whatever is
+/// still unsynced then.
+#[derive(Debug)]
+pub(crate) struct GroupCommitter {
+    inner: Arc<Inner>,
+    /// The worker thread handle, created upon construction and joined upon destruction. It only
+    /// becomes `None` after joined in `drop()`.
+    worker_thread: Option<JoinHandle<()>>,
+    /// The sender side of the channel talking to the worker thread.
+    command_sender: Mutex<Sender<Command>>,
+}
+
+impl GroupCommitter {
+    pub fn new(db: Arc<DB>, max_bytes: usize, max_latency: Duration) -> Self {
+        let (command_sender, command_receiver) = channel();
+        let inner = Arc::new(Inner {
+            db,
+            max_bytes,
+            max_latency,
+            unsynced: Mutex::new(Unsynced::default()),
+        });
+        let worker = Worker {
+            inner: Arc::clone(&inner),
+            command_receiver,
+        };
+        let worker_thread = std::thread::Builder::new()
+            .name("diemdb_group_commit".into())
+            .spawn(move || worker.work())
+            .expect("Creating group commit thread should succeed.");
+
+        Self {
+            inner,
+            worker_thread: Some(worker_thread),
+            command_sender: Mutex::new(command_sender),
+        }
+    }
+
+    /// Writes `batch`, waiting for it and all the writes before it to be synced if `sync` is set.
+    /// Otherwise, it's synced along with the writes around it.
+    pub fn write(&self, batch: SchemaBatch, sync: bool) -> Result<()> {
+        let mut unsynced = self.inner.unsynced.lock();
+        if sync {
+            self.inner.db.write_schemas(batch)?;
+            if unsynced.bytes > 0 {
+                DIEM_STORAGE_GROUP_COMMIT_SYNCS
+                    .with_label_values(&["synced_write"])
+                    .inc();
+            }
+            *unsynced = Unsynced::default();
+            return Ok(());
+        }
+
+        unsynced.bytes += self.inner.db.write_schemas_unsynced(batch)?;
+        if unsynced.bytes >= self.inner.max_bytes {
+            return self.inner.sync(&mut unsynced, "max_bytes");
+        }
+        if unsynced.since.is_none() {
+            unsynced.since = Some(Instant::now());
+            // Have the worker sync in `max_latency` unless something else does first.
+            self.command_sender
+                .lock()
+                .send(Command::Wake)
+                .expect("Receiver should not destruct prematurely.");
+        }
+        Ok(())
+    }
+
+    /// Syncs all the writes so far.
+    pub fn sync(&self) -> Result<()> {
+        self.inner.sync(&mut self.inner.unsynced.lock(), "explicit")
+    }
+
+    /// (For tests) The number of bytes written but not synced yet.
+    #[cfg(test)]
+    fn unsynced_bytes(&self) -> usize {
+        self.inner.unsynced.lock().bytes
+    }
+}
+
+impl Drop for GroupCommitter {
+    fn drop(&mut self) {
+        self.command_sender
+            .lock()
+            .send(Command::Quit)
+            .expect("Receiver should not destruct.");
+        self.worker_thread
+            .take()
+            .expect("Worker thread must exist.")
+            .join()
+            .expect("Worker thread should join peacefully.");
+        if let Err(e) = self.sync() {
+            error!(error = ?e, "Failed to sync the writes left unsynced.");
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    db: Arc<DB>,
+    max_bytes: usize,
+    max_latency: Duration,
+    unsynced: Mutex<Unsynced>,
+}
+
+/// The writes since the last sync.
+#[derive(Debug, Default)]
+struct Unsynced {
+    bytes: usize,
+    /// When the oldest of them was written.
+    since: Option<Instant>,
+}
+
+impl Inner {
+    fn sync(&self, unsynced: &mut Unsynced, trigger: &str) -> Result<()> {
+        if unsynced.bytes == 0 {
+            return Ok(());
+        }
+        let _timer = DIEM_STORAGE_OTHER_TIMERS_SECONDS
+            .with_label_values(&["group_commit_sync"])
+            .start_timer();
+        self.db.sync_wal()?;
+        DIEM_STORAGE_GROUP_COMMIT_SYNCS
+            .with_label_values(&[trigger])
+            .inc();
+        *unsynced = Unsynced::default();
+        Ok(())
+    }
+
+    /// When the oldest unsynced write is due to be synced, if there's any.
+    fn deadline(&self) -> Option<Instant> {
+        self.unsynced
+            .lock()
+            .since
+            .map(|since| since + self.max_latency)
+    }
+}
+
+#[derive(Debug)]
+enum Command {
+    Quit,
+    /// Something was written since the last sync.
+    Wake,
+}
+
+struct Worker {
+    inner: Arc<Inner>,
+    command_receiver: Receiver<Command>,
+}
+
+impl Worker {
+    fn work(self) {
+        loop {
+            let command = match self.inner.deadline() {
+                None => self
+                    .command_receiver
+                    .recv()
+                    .expect("Sender should not destruct prematurely."),
+                Some(deadline) => match self
+                    .command_receiver
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                {
+                    Ok(command) => command,
+                    Err(RecvTimeoutError::Timeout) => {
+                        self.sync_if_due();
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => {
+                        panic!("Sender should not destruct prematurely.")
+                    }
+                },
+            };
+            match command {
+                Command::Quit => break,
+                Command::Wake => (),
+            }
+        }
+    }
+
+    fn sync_if_due(&self) {
+        let mut unsynced = self.inner.unsynced.lock();
+        // A sync since the deadline was computed may have reset it.
+        match unsynced.since {
+            Some(since) if since.elapsed() >= self.inner.max_latency => (),
+            _ => return,
+        }
+        if let Err(e) = self.inner.sync(&mut unsynced, "max_latency") {
+            warn!(error = ?e, "Failed to sync the WAL. Retrying later.");
+            drop(unsynced);
+            sleep(self.inner.max_latency.max(Duration::from_millis(100)));
+        }
+    }
+}
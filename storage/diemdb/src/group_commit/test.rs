@@ -0,0 +1,74 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{schema::transaction::TransactionSchema, DiemDB};
+use diem_crypto::HashValue;
+use diem_temppath::TempPath;
+use diem_types::{
+    account_address::AccountAddress,
+    block_metadata::BlockMetadata,
+    transaction::{Transaction, Version},
+};
+
+fn batch_of(version: Version) -> SchemaBatch {
+    let txn = Transaction::BlockMetadata(BlockMetadata::new(
+        HashValue::random(),
+        version, /* round */
+        version, /* timestamp_usecs */
+        vec![],
+        AccountAddress::ZERO,
+    ));
+    let mut batch = SchemaBatch::new();
+    batch.put::<TransactionSchema>(&version, &txn).unwrap();
+    batch
+}
+
+#[test]
+fn test_sync_on_max_bytes() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    let batch_bytes = db.db.write_schemas_unsynced(batch_of(0)).unwrap();
+    db.db.sync_wal().unwrap();
+    let group_committer = GroupCommitter::new(
+        Arc::clone(&db.db),
+        batch_bytes * 3,
+        Duration::from_secs(3600),
+    );
+
+    group_committer.write(batch_of(1), false).unwrap();
+    group_committer.write(batch_of(2), false).unwrap();
+    assert_eq!(group_committer.unsynced_bytes(), batch_bytes * 2);
+    // Unsynced writes are readable right away.
+    assert!(db.db.get::<TransactionSchema>(&2).unwrap().is_some());
+
+    group_committer.write(batch_of(3), false).unwrap();
+    assert_eq!(group_committer.unsynced_bytes(), 0);
+
+    group_committer.write(batch_of(4), false).unwrap();
+    assert_eq!(group_committer.unsynced_bytes(), batch_bytes);
+    // A synced write syncs those before it too.
+    group_committer.write(batch_of(5), true).unwrap();
+    assert_eq!(group_committer.unsynced_bytes(), 0);
+}
+
+#[test]
+fn test_sync_on_max_latency() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    let group_committer =
+        GroupCommitter::new(Arc::clone(&db.db), usize::MAX, Duration::from_millis(10));
+
+    for version in 0..3 {
+        group_committer.write(batch_of(version), false).unwrap();
+        assert!(group_committer.unsynced_bytes() > 0);
+        let start = Instant::now();
+        while group_committer.unsynced_bytes() > 0 {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "The worker should sync."
+            );
+            sleep(Duration::from_millis(1));
+        }
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `Indexer`, an optional component which indexes events by the accounts and
+//! the currencies they involve as transactions commit, so that explorer style queries, like all
+//! the payments received by an account or all the mints of a currency, are served by the node
+//! itself.
+//!
+//! An event involves the account owning its event stream and, for a payment, the other party of
+//! the payment. It involves a currency if it's a payment, mint, burn or exchange rate update of
+//! that currency.
+
+use crate::schema::{
+    event_by_account::EventByAccountSchema, event_by_currency::EventByCurrencySchema,
+};
+use anyhow::Result;
+use diem_crypto::HashValue;
+use diem_types::{
+    account_address::AccountAddress,
+    account_config::{
+        BurnEvent, CancelBurnEvent, MintEvent, PreburnEvent, ReceivedMintEvent,
+        ReceivedPaymentEvent, SentPaymentEvent, ToXDXExchangeRateUpdateEvent,
+    },
+    contract_event::ContractEvent,
+    transaction::Version,
+};
+use move_core_types::identifier::Identifier;
+use schemadb::{ReadOptions, SchemaBatch, DB};
+use std::{convert::TryFrom, sync::Arc};
+
+#[derive(Debug)]
+pub(crate) struct Indexer {
+    db: Arc<DB>,
+}
+
+impl Indexer {
+    pub fn new(db: Arc<DB>) -> Self {
+        Self { db }
+    }
+
+    /// Indexes the event at index `idx` among the events emitted by the transaction at `version`.
+    pub fn index_event(
+        &self,
+        version: Version,
+        idx: u64,
+        event: &ContractEvent,
+        batch: &mut SchemaBatch,
+    ) -> Result<()> {
+        for address in accounts_involved(event) {
+            batch.put::<EventByAccountSchema>(&(address, version, idx), &())?;
+        }
+        if let Some(currency_code) = currency_involved(event) {
+            batch.put::<EventByCurrencySchema>(
+                &(hash_currency_code(currency_code.as_str()), version, idx),
+                &(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the index entries of an event. Deleting entries never written is harmless, so this
+    /// can be called whether the indexer is enabled or not.
+    pub fn unindex_event(
+        version: Version,
+        idx: u64,
+        event: &ContractEvent,
+        batch: &mut SchemaBatch,
+    ) -> Result<()> {
+        for address in accounts_involved(event) {
+            batch.delete::<EventByAccountSchema>(&(address, version, idx))?;
+        }
+        if let Some(currency_code) = currency_involved(event) {
+            batch.delete::<EventByCurrencySchema>(&(
+                hash_currency_code(currency_code.as_str()),
+                version,
+                idx,
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// Looks up a page of events involving `address` in transactions from `start_version` to
+    /// `ledger_version`, as `(version, index)` pairs. See `collect_page` for how pages end.
+    pub fn lookup_events_by_account(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, u64)>> {
+        let mut iter = self
+            .db
+            .iter::<EventByAccountSchema>(ReadOptions::default())?;
+        iter.seek(&(address, start_version, 0))?;
+        collect_page(
+            iter.take_while(|res| res.as_ref().map_or(true, |((a, _, _), ())| *a == address))
+                .map(|res| res.map(|((_, version, idx), ())| (version, idx))),
+            limit,
+            ledger_version,
+        )
+    }
+
+    /// Looks up a page of events involving `currency_code` in transactions from `start_version`
+    /// to `ledger_version`, as `(version, index)` pairs. The events found must be checked with
+    /// `currency_involved`, since currency codes are identified by hashes in the index.
+    pub fn lookup_events_by_currency(
+        &self,
+        currency_code: &str,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, u64)>> {
+        let currency_code_hash = hash_currency_code(currency_code);
+        let mut iter = self
+            .db
+            .iter::<EventByCurrencySchema>(ReadOptions::default())?;
+        iter.seek(&(currency_code_hash, start_version, 0))?;
+        collect_page(
+            iter.take_while(|res| {
+                res.as_ref()
+                    .map_or(true, |((hash, _, _), ())| *hash == currency_code_hash)
+            })
+            .map(|res| res.map(|((_, version, idx), ())| (version, idx))),
+            limit,
+            ledger_version,
+        )
+    }
+}
+
+/// Collects up to `limit` `(version, index)` pairs in ascending order, stopping after
+/// `ledger_version`. Unless a page holds the events of a single transaction, it ends with the last
+/// event of a transaction, so that the next page starts at the version after it.
+pub(crate) fn collect_page(
+    iter: impl Iterator<Item = Result<(Version, u64)>>,
+    limit: u64,
+    ledger_version: Version,
+) -> Result<Vec<(Version, u64)>> {
+    let mut indices: Vec<(Version, u64)> = Vec::new();
+    let mut next_version = None;
+    for res in iter {
+        let (version, idx) = res?;
+        if version > ledger_version {
+            break;
+        }
+        if indices.len() as u64 == limit {
+            next_version = Some(version);
+            break;
+        }
+        indices.push((version, idx));
+    }
+    // Drop the events of a transaction cut by the limit.
+    if let (Some(next_version), Some(&(first_version, _))) = (next_version, indices.first()) {
+        if next_version != first_version {
+            indices.retain(|(version, _)| *version != next_version);
+        }
+    }
+    Ok(indices)
+}
+
+/// The accounts `event` involves: the owner of its event stream and, for payments, the other
+/// party.
+pub(crate) fn accounts_involved(event: &ContractEvent) -> Vec<AccountAddress> {
+    let owner = event.key().get_creator_address();
+    let other_party = if let Ok(sent) = SentPaymentEvent::try_from(event) {
+        Some(sent.receiver())
+    } else if let Ok(received) = ReceivedPaymentEvent::try_from(event) {
+        Some(received.sender())
+    } else {
+        None
+    };
+    match other_party {
+        Some(address) if address != owner => vec![owner, address],
+        _ => vec![owner],
+    }
+}
+
+/// The currency `event` involves, if any.
+pub(crate) fn currency_involved(event: &ContractEvent) -> Option<Identifier> {
+    if let Ok(e) = SentPaymentEvent::try_from(event) {
+        Some(e.currency_code().to_owned())
+    } else if let Ok(e) = ReceivedPaymentEvent::try_from(event) {
+        Some(e.currency_code().to_owned())
+    } else if let Ok(e) = MintEvent::try_from(event) {
+        Some(e.currency_code().to_owned())
+    } else if let Ok(e) = ReceivedMintEvent::try_from(event) {
+        Some(e.currency_code().to_owned())
+    } else if let Ok(e) = BurnEvent::try_from(event) {
+        Some(e.currency_code().to_owned())
+    } else if let Ok(e) = PreburnEvent::try_from(event) {
+        Some(e.currency_code().to_owned())
+    } else if let Ok(e) = CancelBurnEvent::try_from(event) {
+        Some(e.currency_code().to_owned())
+    } else if let Ok(e) = ToXDXExchangeRateUpdateEvent::try_from(event) {
+        Some(e.currency_code().to_owned())
+    } else {
+        None
+    }
+}
+
+/// Identifies `currency_code` in `EventByCurrencySchema`.
+fn hash_currency_code(currency_code: &str) -> HashValue {
+    HashValue::sha3_256_of(currency_code.as_bytes())
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,142 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{change_set::ChangeSet, event_store::EventStore, DiemDB};
+use diem_temppath::TempPath;
+use diem_types::event::EventKey;
+use move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
+
+fn payment<T: MoveStructType>(
+    owner: AccountAddress,
+    seq_num: u64,
+    currency_code: &str,
+    other_party: AccountAddress,
+) -> ContractEvent {
+    // Sent and received payment events share the same layout.
+    let data = (
+        100u64,
+        Identifier::new(currency_code).unwrap(),
+        other_party,
+        Vec::<u8>::new(),
+    );
+    ContractEvent::new(
+        EventKey::new_from_address(&owner, 0),
+        seq_num,
+        TypeTag::Struct(T::struct_tag()),
+        bcs::to_bytes(&data).unwrap(),
+    )
+}
+
+fn save(db: &DiemDB, store: &EventStore, version: Version, events: &[ContractEvent]) {
+    let mut cs = ChangeSet::new();
+    store.put_events(version, events, &mut cs).unwrap();
+    db.db.write_schemas(cs.batch).unwrap();
+}
+
+#[test]
+fn test_get_events_by_account_and_currency() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    let store = EventStore::new(Arc::clone(&db.db), false).with_indexer(true);
+
+    let a = AccountAddress::random();
+    let b = AccountAddress::random();
+    let c = AccountAddress::random();
+    save(
+        &db,
+        &store,
+        0,
+        &[
+            payment::<SentPaymentEvent>(a, 0, "XUS", b),
+            payment::<ReceivedPaymentEvent>(b, 0, "XUS", a),
+        ],
+    );
+    save(
+        &db,
+        &store,
+        1,
+        &[
+            payment::<SentPaymentEvent>(c, 0, "XDX", a),
+            payment::<ReceivedPaymentEvent>(a, 1, "XDX", c),
+        ],
+    );
+    save(
+        &db,
+        &store,
+        2,
+        &[ContractEvent::new(
+            EventKey::new_from_address(&c, 1),
+            0,
+            TypeTag::U64,
+            vec![],
+        )],
+    );
+
+    let positions = |events: Vec<(Version, ContractEvent)>| {
+        events
+            .into_iter()
+            .map(|(version, event)| (version, event.sequence_number()))
+            .collect::<Vec<_>>()
+    };
+    let by_account = |address, start_version, ledger_version| {
+        positions(
+            store
+                .get_events_by_account(address, start_version, 10, ledger_version)
+                .unwrap(),
+        )
+    };
+    let by_currency = |currency_code, start_version, ledger_version| {
+        positions(
+            store
+                .get_events_by_currency(currency_code, start_version, 10, ledger_version)
+                .unwrap(),
+        )
+    };
+
+    // both parties of a payment are involved in both of its events
+    assert_eq!(by_account(a, 0, 2), vec![(0, 0), (0, 0), (1, 0), (1, 1)]);
+    assert_eq!(by_account(b, 0, 2), vec![(0, 0), (0, 0)]);
+    assert_eq!(by_account(c, 0, 2), vec![(1, 0), (1, 1), (2, 0)]);
+    assert_eq!(by_account(a, 1, 2), vec![(1, 0), (1, 1)]);
+    assert_eq!(by_account(a, 0, 0), vec![(0, 0), (0, 0)]);
+    assert!(by_account(AccountAddress::random(), 0, 2).is_empty());
+
+    assert_eq!(by_currency("XUS", 0, 2), vec![(0, 0), (0, 0)]);
+    assert_eq!(by_currency("XDX", 0, 2), vec![(1, 0), (1, 1)]);
+    assert!(by_currency("XDX", 0, 0).is_empty());
+    assert!(by_currency("ABC", 0, 2).is_empty());
+
+    // pruned events are removed from the indices
+    let mut batch = SchemaBatch::new();
+    store.prune_events(0, 1, &mut batch).unwrap();
+    db.db.write_schemas(batch).unwrap();
+    assert!(by_account(b, 0, 2).is_empty());
+    assert_eq!(by_account(a, 0, 2), vec![(1, 0), (1, 1)]);
+    assert!(by_currency("XUS", 0, 2).is_empty());
+
+    // without the indexer, queries fail
+    assert!(db.event_store.get_events_by_account(a, 0, 10, 2).is_err());
+    assert!(db
+        .event_store
+        .get_events_by_currency("XUS", 0, 10, 2)
+        .is_err());
+}
+
+#[test]
+fn test_accounts_and_currency_involved() {
+    let a = AccountAddress::random();
+    let b = AccountAddress::random();
+
+    let sent = payment::<SentPaymentEvent>(a, 0, "XUS", b);
+    assert_eq!(accounts_involved(&sent), vec![a, b]);
+    assert_eq!(currency_involved(&sent).unwrap().as_str(), "XUS");
+
+    // paying oneself involves a single account
+    let sent_to_self = payment::<SentPaymentEvent>(a, 0, "XUS", a);
+    assert_eq!(accounts_involved(&sent_to_self), vec![a]);
+
+    let other = ContractEvent::new(EventKey::new_from_address(&b, 0), 0, TypeTag::U64, vec![]);
+    assert_eq!(accounts_involved(&other), vec![b]);
+    assert!(currency_involved(&other).is_none());
+}
@@ -92,6 +92,38 @@ proptest! {
         prop_assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_get_epoch_ending_ledger_infos_cached(
+        ledger_infos_with_sigs in arb_ledger_infos_with_sigs()
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = set_up(&tmp_dir, &ledger_infos_with_sigs);
+        let store = &db.ledger_store;
+        let end_epoch = get_last_epoch(&ledger_infos_with_sigs) + 1;
+        let expected = store
+            .get_epoch_ending_ledger_info_iter(0, end_epoch)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        store.set_proof_cache_capacity(100);
+        // the first call fills the cache, the second is served by it
+        prop_assert_eq!(&store.get_epoch_ending_ledger_infos(0, end_epoch).unwrap(), &expected);
+        prop_assert_eq!(&store.get_epoch_ending_ledger_infos(0, end_epoch).unwrap(), &expected);
+        for li in &expected {
+            let version = li.ledger_info().version();
+            prop_assert_eq!(&store.get_epoch_ending_ledger_info(version).unwrap(), li);
+        }
+        // the open epoch is never cached
+        let latest = ledger_infos_with_sigs.last().unwrap().ledger_info();
+        if !latest.ends_epoch() {
+            prop_assert!(store
+                .proof_cache
+                .get_epoch_ending_ledger_info(latest.epoch())
+                .is_none());
+        }
+    }
+
     #[test]
     fn test_get_epoch(
         (ledger_infos_with_sigs, version) in arb_ledger_infos_with_sigs()
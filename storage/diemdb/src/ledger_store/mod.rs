@@ -4,9 +4,12 @@
 //! This file defines ledger store APIs that are related to the main ledger accumulator, from the
 //! root(LedgerInfo) to leaf(TransactionInfo).
 
+mod proof_cache;
+
 use crate::{
     change_set::ChangeSet,
     errors::DiemDbError,
+    ledger_store::proof_cache::ProofCache,
     schema::{
         epoch_by_version::EpochByVersionSchema, ledger_info::LedgerInfoSchema,
         transaction_accumulator::TransactionAccumulatorSchema,
@@ -42,26 +45,44 @@ pub(crate) struct LedgerStore {
     /// cache it in memory in order to avoid reading DB and deserializing the object frequently. It
     /// should be updated every time new ledger info and signatures are persisted.
     latest_ledger_info: ArcSwap<Option<LedgerInfoWithSignatures>>,
+
+    /// Range proofs and epoch ending ledger infos recently served, disabled until a capacity is
+    /// set.
+    proof_cache: ProofCache,
 }
 
 impl LedgerStore {
     pub fn new(db: Arc<DB>) -> Self {
         // Upon restart, read the latest ledger info and signatures and cache them in memory.
-        let ledger_info = {
-            let mut iter = db
-                .iter::<LedgerInfoSchema>(ReadOptions::default())
-                .expect("Constructing iterator should work.");
-            iter.seek_to_last();
-            iter.next()
-                .transpose()
-                .expect("Reading latest ledger info from DB should work.")
-                .map(|kv| kv.1)
-        };
+        let ledger_info = Self::read_latest_ledger_info(&db)
+            .expect("Reading latest ledger info from DB should work.");
 
         Self {
             db,
             latest_ledger_info: ArcSwap::from(Arc::new(ledger_info)),
+            proof_cache: ProofCache::new(0),
+        }
+    }
+
+    /// Caches up to `capacity` range proofs and as many epoch ending ledger infos. Zero disables
+    /// the cache.
+    pub fn set_proof_cache_capacity(&self, capacity: usize) {
+        self.proof_cache.set_capacity(capacity);
+    }
+
+    fn read_latest_ledger_info(db: &DB) -> Result<Option<LedgerInfoWithSignatures>> {
+        let mut iter = db.iter::<LedgerInfoSchema>(ReadOptions::default())?;
+        iter.seek_to_last();
+        Ok(iter.next().transpose()?.map(|kv| kv.1))
+    }
+
+    /// Re-reads the latest ledger info from the DB into the cache, for when the DB is written by
+    /// another process, i.e. when this is a secondary instance.
+    pub fn reload_latest_ledger_info(&self) -> Result<()> {
+        if let Some(ledger_info_with_sigs) = Self::read_latest_ledger_info(&self.db)? {
+            self.set_latest_ledger_info(ledger_info_with_sigs);
         }
+        Ok(())
     }
 
     pub fn get_epoch(&self, version: Version) -> Result<u64> {
@@ -101,6 +122,11 @@ impl LedgerStore {
         version: Version,
     ) -> Result<LedgerInfoWithSignatures> {
         let epoch = self.get_epoch(version)?;
+        if let Some(li) = self.proof_cache.get_epoch_ending_ledger_info(epoch) {
+            if li.ledger_info().version() == version {
+                return Ok(li);
+            }
+        }
         let li = self
             .db
             .get::<LedgerInfoSchema>(&epoch)?
@@ -114,10 +140,34 @@ impl LedgerStore {
         li.ledger_info()
             .next_epoch_state()
             .ok_or_else(|| format_err!("Not an epoch change at version {}", version))?;
+        self.proof_cache.put_epoch_ending_ledger_info(&li);
 
         Ok(li)
     }
 
+    /// Gets the epoch ending ledger infos of epochs in [`start_epoch`, `end_epoch`), stopping
+    /// early at the open epoch. Served from the cache only if all of them are cached.
+    pub fn get_epoch_ending_ledger_infos(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> Result<Vec<LedgerInfoWithSignatures>> {
+        let cached = (start_epoch..end_epoch)
+            .map(|epoch| self.proof_cache.get_epoch_ending_ledger_info(epoch))
+            .collect::<Option<Vec<_>>>();
+        if let Some(lis) = cached {
+            return Ok(lis);
+        }
+
+        let lis = self
+            .get_epoch_ending_ledger_info_iter(start_epoch, end_epoch)?
+            .collect::<Result<Vec<_>>>()?;
+        for li in &lis {
+            self.proof_cache.put_epoch_ending_ledger_info(li);
+        }
+        Ok(lis)
+    }
+
     pub fn get_latest_ledger_info_option(&self) -> Option<LedgerInfoWithSignatures> {
         let ledger_info_ptr = self.latest_ledger_info.load();
         let ledger_info: &Option<_> = ledger_info_ptr.deref();
@@ -298,12 +348,21 @@ impl LedgerStore {
         num_txns: u64,
         ledger_version: Version,
     ) -> Result<TransactionAccumulatorRangeProof> {
-        Accumulator::get_range_proof(
+        if let Some(proof) =
+            self.proof_cache
+                .get_range_proof(start_version, num_txns, ledger_version)
+        {
+            return Ok(proof);
+        }
+        let proof = Accumulator::get_range_proof(
             self,
             ledger_version + 1, /* num_leaves */
             start_version,
             num_txns,
-        )
+        )?;
+        self.proof_cache
+            .put_range_proof(start_version, num_txns, ledger_version, &proof);
+        Ok(proof)
     }
 
     /// Gets proof that shows the ledger at `ledger_version` is consistent with the ledger at
@@ -0,0 +1,118 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This file defines `ProofCache`, an LRU cache of transaction accumulator range proofs and epoch
+//! ending ledger infos. Light clients and state sync requests keep asking for the same recent
+//! ranges and epoch changes, and both never change once they can be served, so caching them saves
+//! reading the accumulator nodes and rebuilding the proofs each time.
+
+use crate::metrics::DIEM_STORAGE_PROOF_CACHE_REQUESTS;
+use diem_infallible::Mutex;
+use diem_types::{
+    ledger_info::LedgerInfoWithSignatures, proof::TransactionAccumulatorRangeProof,
+    transaction::Version,
+};
+use lru::LruCache;
+use std::{fmt, hash::Hash};
+
+/// (start_version, num_txns, ledger_version) of a range proof request.
+type RangeProofKey = (Option<Version>, u64, Version);
+
+pub(crate) struct ProofCache {
+    range_proofs: Mutex<Option<LruCache<RangeProofKey, TransactionAccumulatorRangeProof>>>,
+    /// Keyed by epoch.
+    epoch_ending_ledger_infos: Mutex<Option<LruCache<u64, LedgerInfoWithSignatures>>>,
+}
+
+impl ProofCache {
+    /// Creates a cache holding up to `capacity` range proofs and as many ledger infos. A zero
+    /// capacity disables caching.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            range_proofs: Mutex::new(new_lru(capacity)),
+            epoch_ending_ledger_infos: Mutex::new(new_lru(capacity)),
+        }
+    }
+
+    /// Changes the capacity, dropping whatever is cached.
+    pub fn set_capacity(&self, capacity: usize) {
+        *self.range_proofs.lock() = new_lru(capacity);
+        *self.epoch_ending_ledger_infos.lock() = new_lru(capacity);
+    }
+
+    pub fn get_range_proof(
+        &self,
+        start_version: Option<Version>,
+        num_txns: u64,
+        ledger_version: Version,
+    ) -> Option<TransactionAccumulatorRangeProof> {
+        get(
+            &self.range_proofs,
+            &(start_version, num_txns, ledger_version),
+            "range_proof",
+        )
+    }
+
+    pub fn put_range_proof(
+        &self,
+        start_version: Option<Version>,
+        num_txns: u64,
+        ledger_version: Version,
+        proof: &TransactionAccumulatorRangeProof,
+    ) {
+        if let Some(cache) = self.range_proofs.lock().as_mut() {
+            cache.put((start_version, num_txns, ledger_version), proof.clone());
+        }
+    }
+
+    pub fn get_epoch_ending_ledger_info(&self, epoch: u64) -> Option<LedgerInfoWithSignatures> {
+        get(
+            &self.epoch_ending_ledger_infos,
+            &epoch,
+            "epoch_ending_ledger_info",
+        )
+    }
+
+    /// Caches `ledger_info_with_sigs` if it ends its epoch. The ledger info of the open epoch is
+    /// replaced by each commit, so it's never cached.
+    pub fn put_epoch_ending_ledger_info(&self, ledger_info_with_sigs: &LedgerInfoWithSignatures) {
+        let ledger_info = ledger_info_with_sigs.ledger_info();
+        if !ledger_info.ends_epoch() {
+            return;
+        }
+        if let Some(cache) = self.epoch_ending_ledger_infos.lock().as_mut() {
+            cache.put(ledger_info.epoch(), ledger_info_with_sigs.clone());
+        }
+    }
+}
+
+impl fmt::Debug for ProofCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let capacity = self.range_proofs.lock().as_ref().map_or(0, LruCache::cap);
+        f.debug_struct("ProofCache")
+            .field("capacity", &capacity)
+            .finish()
+    }
+}
+
+fn new_lru<K: Hash + Eq, V>(capacity: usize) -> Option<LruCache<K, V>> {
+    if capacity == 0 {
+        None
+    } else {
+        Some(LruCache::new(capacity))
+    }
+}
+
+fn get<K: Hash + Eq, V: Clone>(
+    cache: &Mutex<Option<LruCache<K, V>>>,
+    key: &K,
+    cache_name: &str,
+) -> Option<V> {
+    let mut cache = cache.lock();
+    let value = cache.as_mut()?.get(key).cloned();
+    let result = if value.is_some() { "hit" } else { "miss" };
+    DIEM_STORAGE_PROOF_CACHE_REQUESTS
+        .with_label_values(&[cache_name, result])
+        .inc();
+    value
+}
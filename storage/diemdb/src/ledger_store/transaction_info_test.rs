@@ -70,6 +70,29 @@ proptest! {
         verify(store, &batch1, 0, ledger_version1, root_hash1);
     }
 
+    #[test]
+    fn test_transaction_range_proof_cached(batch in vec(any::<TransactionInfo>(), 1..100)) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+        let store = &db.ledger_store;
+        let root_hash = save(store, 0, &batch);
+        let ledger_version = batch.len() as u64 - 1;
+        let txn_info_hashes: Vec<_> = batch.iter().map(CryptoHash::hash).collect();
+
+        store.set_proof_cache_capacity(2);
+        for (idx, start_version) in (0..=ledger_version).enumerate() {
+            let num_txns = ledger_version - start_version + 1;
+            // the second call is served by the cache
+            for _ in 0..2 {
+                store
+                    .get_transaction_range_proof(Some(start_version), num_txns, ledger_version)
+                    .unwrap()
+                    .verify(root_hash, Some(start_version), &txn_info_hashes[idx..])
+                    .unwrap();
+            }
+        }
+    }
+
     #[test]
     fn test_transaction_info_get_iterator(
         (infos, start_version, num_transaction_infos) in
@@ -19,12 +19,18 @@ pub mod backup;
 pub mod errors;
 pub mod metrics;
 pub mod schema;
+pub mod snapshot;
+pub mod verifier;
 
+mod archive;
 mod change_set;
 mod event_store;
+mod group_commit;
+mod indexer;
 mod ledger_counters;
 mod ledger_store;
 mod pruner;
+mod state_shards;
 mod state_store;
 mod system_store;
 mod transaction_store;
@@ -36,32 +42,43 @@ mod diemdb_test;
 #[cfg(feature = "fuzzing")]
 pub use diemdb_test::test_save_blocks_impl;
 
+pub use archive::{
+    new_archive_storage, ArchiveStorage, CommandArchiveStorage, LocalArchiveStorage,
+};
+pub use pruner::PrunerOptions;
+
 use crate::{
+    archive::{ArchiveStore, Archiver},
     backup::{backup_handler::BackupHandler, restore_handler::RestoreHandler},
     change_set::{ChangeSet, SealedChangeSet},
     errors::DiemDbError,
     event_store::EventStore,
+    group_commit::GroupCommitter,
     ledger_counters::LedgerCounters,
     ledger_store::LedgerStore,
     metrics::{
         DIEM_STORAGE_API_LATENCY_SECONDS, DIEM_STORAGE_COMMITTED_TXNS,
         DIEM_STORAGE_LATEST_TXN_VERSION, DIEM_STORAGE_LEDGER_VERSION,
         DIEM_STORAGE_NEXT_BLOCK_EPOCH, DIEM_STORAGE_OTHER_TIMERS_SECONDS,
-        DIEM_STORAGE_ROCKSDB_PROPERTIES,
+        DIEM_STORAGE_ROCKSDB_PROPERTIES, DIEM_STORAGE_ROCKSDB_TICKERS,
     },
     pruner::Pruner,
     schema::*,
+    snapshot::SnapshotHandler,
+    state_shards::StateShards,
     state_store::StateStore,
     system_store::SystemStore,
     transaction_store::TransactionStore,
+    verifier::DbVerifier,
 };
 use anyhow::{ensure, Result};
-use diem_config::config::RocksdbConfig;
+use diem_config::config::{ArchiveConfig, RocksdbCompactionStyle, RocksdbConfig};
 use diem_crypto::hash::{CryptoHash, HashValue, SPARSE_MERKLE_PLACEHOLDER_HASH};
 use diem_logger::prelude::*;
 use diem_types::{
     account_address::AccountAddress,
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
+    block_metadata::BlockMetadata,
     contract_event::{ContractEvent, EventWithProof},
     epoch_change::EpochChangeProof,
     event::EventKey,
@@ -76,17 +93,24 @@ use diem_types::{
     },
 };
 use itertools::{izip, zip_eq};
+use move_core_types::language_storage::TypeTag;
 use once_cell::sync::Lazy;
-use schemadb::{ColumnFamilyName, Options, DB, DEFAULT_CF_NAME};
+use schemadb::{
+    BlockBasedOptions, ColumnFamilyName, DBCompactionStyle, Options, DB, DEFAULT_CF_NAME,
+};
+use serde::Serialize;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     iter::Iterator,
     path::Path,
     sync::{mpsc, Arc, Mutex},
     thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
-use storage_interface::{DbReader, DbWriter, Order, StartupInfo, TreeState};
+use storage_interface::{
+    DbReader, DbWriter, Order, StartupInfo, StateSnapshotChunk, StateSnapshotInfo,
+    StateSnapshotReceiver, TreeState,
+};
 
 const MAX_LIMIT: u64 = 1000;
 
@@ -120,12 +144,67 @@ static ROCKSDB_PROPERTY_MAP: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
             "diem_rocksdb_cf_size_bytes",
             "rocksdb.estimate-live-data-size",
         ),
+        (
+            "diem_rocksdb_total_sst_files_size_bytes",
+            "rocksdb.total-sst-files-size",
+        ),
+        (
+            "diem_rocksdb_estimate_num_keys",
+            "rocksdb.estimate-num-keys",
+        ),
+        (
+            "diem_rocksdb_pending_compaction_bytes",
+            "rocksdb.estimate-pending-compaction-bytes",
+        ),
     ]
     .iter()
     .cloned()
     .collect()
 });
 
+/// Statistics tickers exported. Column families share the block cache, so RocksDB counts them for
+/// the whole DB.
+const ROCKSDB_TICKERS: &[&str] = &[
+    "rocksdb.block.cache.hit",
+    "rocksdb.block.cache.miss",
+    "rocksdb.block.cache.data.hit",
+    "rocksdb.block.cache.data.miss",
+    "rocksdb.block.cache.index.hit",
+    "rocksdb.block.cache.index.miss",
+    "rocksdb.block.cache.filter.hit",
+    "rocksdb.block.cache.filter.miss",
+];
+
+/// A snapshot of the RocksDB properties and statistics exported as metrics.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct RocksdbStats {
+    /// Integer properties of each column family, keyed by metric name.
+    pub column_families: BTreeMap<String, BTreeMap<String, u64>>,
+    /// Statistics tickers, empty if statistics are not enabled.
+    pub tickers: BTreeMap<String, u64>,
+    /// Block cache hits over lookups, None if there was no lookup.
+    pub block_cache_hit_rate: Option<f64>,
+}
+
+/// Picks `ROCKSDB_TICKERS` out of a RocksDB statistics dump, where tickers are lines like
+/// `rocksdb.block.cache.hit COUNT : 42`.
+fn parse_rocksdb_tickers(statistics: &str) -> BTreeMap<String, u64> {
+    statistics
+        .lines()
+        .filter_map(|line| {
+            let mut tokens = line.split_whitespace();
+            match (tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
+                (Some(name), Some("COUNT"), Some(":"), Some(count))
+                    if ROCKSDB_TICKERS.contains(&name) =>
+                {
+                    Some((name.to_string(), count.parse().ok()?))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
 fn error_if_too_many_requested(num_requested: u64, max_allowed: u64) -> Result<()> {
     if num_requested > max_allowed {
         Err(DiemDbError::TooManyRequested(num_requested, max_allowed).into())
@@ -138,20 +217,101 @@ fn gen_rocksdb_options(config: &RocksdbConfig) -> Options {
     let mut db_opts = Options::default();
     db_opts.set_max_open_files(config.max_open_files);
     db_opts.set_max_total_wal_size(config.max_total_wal_size);
+    if config.enable_statistics {
+        db_opts.enable_statistics();
+    }
     db_opts
 }
 
+fn gen_cf_options(config: &RocksdbConfig) -> Result<Vec<(ColumnFamilyName, Options)>> {
+    gen_cf_options_for(config, DiemDB::column_families())
+}
+
+/// Like `gen_cf_options`, for an instance with only `column_families`, e.g. a state shard.
+fn gen_cf_options_for(
+    config: &RocksdbConfig,
+    column_families: Vec<ColumnFamilyName>,
+) -> Result<Vec<(ColumnFamilyName, Options)>> {
+    let all_column_families = DiemDB::column_families();
+    for cf_name in config.column_families.keys() {
+        ensure!(
+            all_column_families.contains(&cf_name.as_str()),
+            "Unknown column family in rocksdb config: {}",
+            cf_name,
+        );
+    }
+
+    // The same table options on all column families make them share the block cache.
+    let mut table_opts = BlockBasedOptions::default();
+    if config.block_cache_size > 0 {
+        table_opts.set_lru_cache(config.block_cache_size as usize);
+    }
+    Ok(column_families
+        .into_iter()
+        .map(|cf_name| {
+            let cf_config = config.cf_config(cf_name);
+            let mut cf_opts = Options::default();
+            cf_opts.set_write_buffer_size(cf_config.write_buffer_size as usize);
+            cf_opts.set_max_write_buffer_number(cf_config.max_write_buffer_number);
+            cf_opts.set_compaction_style(match cf_config.compaction_style {
+                RocksdbCompactionStyle::Level => DBCompactionStyle::Level,
+                RocksdbCompactionStyle::Universal => DBCompactionStyle::Universal,
+            });
+            cf_opts.set_block_based_table_factory(&table_opts);
+            (cf_name, cf_opts)
+        })
+        .collect())
+}
+
+fn get_rocksdb_stats(db: &DB) -> Result<RocksdbStats> {
+    let mut column_families = BTreeMap::new();
+    for cf_name in DiemDB::column_families() {
+        let mut properties = BTreeMap::new();
+        for (property_name, rocksdb_property_argument) in &*ROCKSDB_PROPERTY_MAP {
+            properties.insert(
+                property_name.to_string(),
+                db.get_property(cf_name, rocksdb_property_argument)?,
+            );
+        }
+        column_families.insert(cf_name.to_string(), properties);
+    }
+
+    let tickers = db
+        .get_statistics()?
+        .map(|statistics| parse_rocksdb_tickers(&statistics))
+        .unwrap_or_default();
+    let hits = tickers.get("rocksdb.block.cache.hit").copied().unwrap_or(0);
+    let misses = tickers.get("rocksdb.block.cache.miss").copied().unwrap_or(0);
+    let block_cache_hit_rate = if hits + misses > 0 {
+        Some(hits as f64 / (hits + misses) as f64)
+    } else {
+        None
+    };
+
+    Ok(RocksdbStats {
+        column_families,
+        tickers,
+        block_cache_hit_rate,
+    })
+}
+
 fn update_rocksdb_properties(db: &DB) -> Result<()> {
     let _timer = DIEM_STORAGE_OTHER_TIMERS_SECONDS
         .with_label_values(&["update_rocksdb_properties"])
         .start_timer();
-    for cf_name in DiemDB::column_families() {
-        for (property_name, rocksdb_property_argument) in &*ROCKSDB_PROPERTY_MAP {
+    let stats = get_rocksdb_stats(db)?;
+    for (cf_name, properties) in &stats.column_families {
+        for (property_name, value) in properties {
             DIEM_STORAGE_ROCKSDB_PROPERTIES
                 .with_label_values(&[cf_name, property_name])
-                .set(db.get_property(cf_name, rocksdb_property_argument)? as i64);
+                .set(*value as i64);
         }
     }
+    for (ticker_name, count) in &stats.tickers {
+        DIEM_STORAGE_ROCKSDB_TICKERS
+            .with_label_values(&[ticker_name])
+            .set(*count as i64);
+    }
     Ok(())
 }
 
@@ -209,15 +369,23 @@ pub struct DiemDB {
     system_store: SystemStore,
     rocksdb_property_reporter: RocksdbPropertyReporter,
     pruner: Option<Pruner>,
+    archive: Arc<ArchiveStore>,
+    archiver: Option<Archiver>,
+    group_committer: Option<GroupCommitter>,
 }
 
 impl DiemDB {
     fn column_families() -> Vec<ColumnFamilyName> {
         vec![
             /* LedgerInfo CF = */ DEFAULT_CF_NAME,
+            ARCHIVED_CHUNK_CF_NAME,
+            BLOCK_BY_VERSION_CF_NAME,
             EPOCH_BY_VERSION_CF_NAME,
             EVENT_ACCUMULATOR_CF_NAME,
+            EVENT_BY_ACCOUNT_CF_NAME,
+            EVENT_BY_CURRENCY_CF_NAME,
             EVENT_BY_KEY_CF_NAME,
+            EVENT_BY_TYPE_CF_NAME,
             EVENT_BY_VERSION_CF_NAME,
             EVENT_CF_NAME,
             JELLYFISH_MERKLE_NODE_CF_NAME,
@@ -230,18 +398,34 @@ impl DiemDB {
         ]
     }
 
-    fn new_with_db(db: DB, prune_window: Option<u64>) -> Self {
+    fn new_with_db(db: DB, state_shards: StateShards, pruner_options: PrunerOptions) -> Self {
         let db = Arc::new(db);
+        let archive = Arc::new(
+            ArchiveStore::new(Arc::clone(&db)).expect("Reading the archive index should succeed."),
+        );
 
         DiemDB {
             db: Arc::clone(&db),
-            event_store: Arc::new(EventStore::new(Arc::clone(&db))),
+            event_store: Arc::new(
+                EventStore::new(Arc::clone(&db), false).with_archive(Arc::clone(&archive)),
+            ),
             ledger_store: Arc::new(LedgerStore::new(Arc::clone(&db))),
-            state_store: Arc::new(StateStore::new(Arc::clone(&db))),
-            transaction_store: Arc::new(TransactionStore::new(Arc::clone(&db))),
+            state_store: Arc::new(
+                StateStore::new(Arc::clone(&db)).with_shards(state_shards.clone()),
+            ),
+            transaction_store: Arc::new(
+                TransactionStore::new(Arc::clone(&db)).with_archive(Arc::clone(&archive)),
+            ),
             system_store: SystemStore::new(Arc::clone(&db)),
             rocksdb_property_reporter: RocksdbPropertyReporter::new(Arc::clone(&db)),
-            pruner: prune_window.map(|n| Pruner::new(Arc::clone(&db), n)),
+            pruner: if pruner_options.is_enabled() {
+                Some(Pruner::new(Arc::clone(&db), state_shards, pruner_options))
+            } else {
+                None
+            },
+            archive,
+            archiver: None,
+            group_committer: None,
         }
     }
 
@@ -250,10 +434,29 @@ impl DiemDB {
         readonly: bool,
         prune_window: Option<u64>,
         rocksdb_config: RocksdbConfig,
+    ) -> Result<Self> {
+        Self::open_with_pruner_options(
+            db_root_path,
+            readonly,
+            PrunerOptions {
+                historical_versions_to_keep: prune_window,
+                ..Default::default()
+            },
+            rocksdb_config,
+        )
+    }
+
+    /// Like `open`, but with the full range of pruning options, e.g. a wall-clock window or pruning
+    /// transactions and events too.
+    pub fn open_with_pruner_options<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+        readonly: bool,
+        pruner_options: PrunerOptions,
+        rocksdb_config: RocksdbConfig,
     ) -> Result<Self> {
         ensure!(
-            prune_window.is_none() || !readonly,
-            "Do not set prune_window when opening readonly.",
+            !pruner_options.is_enabled() || !readonly,
+            "Do not enable pruning when opening readonly.",
         );
 
         let path = db_root_path.as_ref().join("diemdb");
@@ -271,15 +474,23 @@ impl DiemDB {
         } else {
             rocksdb_opts.create_if_missing(true);
             rocksdb_opts.create_missing_column_families(true);
-            DB::open(
+            DB::open_with_cf_options(
                 path.clone(),
                 "diemdb",
-                Self::column_families(),
+                gen_cf_options(&rocksdb_config)?,
                 &rocksdb_opts,
             )?
         };
 
-        let ret = Self::new_with_db(db, prune_window);
+        let state_shards =
+            StateShards::open(db_root_path.as_ref(), &db, readonly, &rocksdb_config)?;
+        let mut ret = Self::new_with_db(db, state_shards, pruner_options);
+        if !readonly {
+            ret = ret.with_group_commit(
+                rocksdb_config.group_commit_max_bytes,
+                Duration::from_millis(rocksdb_config.group_commit_max_latency_ms),
+            );
+        }
         info!(
             path = path,
             time_ms = %instant.elapsed().as_millis(),
@@ -302,15 +513,121 @@ impl DiemDB {
         Ok(Self::new_with_db(
             DB::open_as_secondary(
                 primary_path,
-                secondary_path,
+                secondary_path.clone(),
                 "diemdb_sec",
                 Self::column_families(),
                 &rocksdb_opts,
             )?,
-            None, // prune_window
+            StateShards::open_as_secondary(
+                db_root_path.as_ref(),
+                &secondary_path,
+                &rocksdb_config,
+            )?,
+            PrunerOptions::default(), // no pruning
         ))
     }
 
+    /// Makes a DB opened by `open_as_secondary` catch up with the primary instance, i.e. the node
+    /// writing to the DB. Needs to be called periodically for the secondary to see new data.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        let _timer = DIEM_STORAGE_OTHER_TIMERS_SECONDS
+            .with_label_values(&["try_catch_up_with_primary"])
+            .start_timer();
+        self.db.try_catch_up_with_primary()?;
+        self.state_store.shards().try_catch_up_with_primary()?;
+        self.ledger_store.reload_latest_ledger_info()
+    }
+
+    /// Enables or disables the index of events by Move type, which serves `get_events_by_type`.
+    /// Only events committed while the index is enabled are indexed.
+    pub fn with_event_type_index(mut self, enabled: bool) -> Self {
+        self.event_store = Arc::new(
+            EventStore::new(Arc::clone(&self.db), enabled)
+                .with_archive(Arc::clone(&self.archive))
+                .with_indexer(self.event_store.has_indexer()),
+        );
+        self
+    }
+
+    /// Enables or disables the indexer of events by the accounts and currencies they involve,
+    /// which serves `get_events_by_account` and `get_events_by_currency`. Only events committed
+    /// while the indexer is enabled are indexed.
+    pub fn with_indexer(mut self, enabled: bool) -> Self {
+        self.event_store = Arc::new(
+            EventStore::new(Arc::clone(&self.db), self.event_store.index_by_type())
+                .with_archive(Arc::clone(&self.archive))
+                .with_indexer(enabled),
+        );
+        self
+    }
+
+    /// Caches up to `capacity` transaction accumulator range proofs and as many epoch ending
+    /// ledger infos, for nodes serving many clients asking for the same recent ranges. Zero
+    /// disables the cache.
+    pub fn with_proof_cache(self, capacity: usize) -> Self {
+        self.ledger_store.set_proof_cache_capacity(capacity);
+        self
+    }
+
+    /// Lets saves without a ledger info return before their writes are synced to disk, syncing
+    /// them together once `max_bytes` are unsynced or the oldest is `max_latency` old. Zero
+    /// `max_bytes` syncs every save. See `RocksdbConfig::group_commit_max_bytes`.
+    pub fn with_group_commit(mut self, max_bytes: u64, max_latency: Duration) -> Self {
+        // Sync what's pending before the committer it's pending in goes.
+        self.group_committer = None;
+        if max_bytes > 0 {
+            self.group_committer = Some(GroupCommitter::new(
+                Arc::clone(&self.db),
+                max_bytes as usize,
+                max_latency,
+            ));
+        }
+        self
+    }
+
+    /// Reads transactions and events already moved to archive files from `storage`, without
+    /// archiving anything, e.g. for a readonly or secondary instance.
+    pub fn with_archive_storage(self, storage: Arc<dyn ArchiveStorage>) -> Self {
+        self.archive.set_storage(storage);
+        self
+    }
+
+    /// Moves transactions and events more than `config.keep_versions` behind the latest version
+    /// to archive files in the background, reading them back from there when queried.
+    pub fn with_archive(mut self, config: &ArchiveConfig) -> Result<Self> {
+        self.archive
+            .set_storage(new_archive_storage(&config.storage)?);
+        self.archiver = Some(Archiver::new(
+            Arc::clone(&self.archive),
+            config.keep_versions,
+            config.chunk_size,
+        ));
+        Ok(self)
+    }
+
+    /// Moves the state of the DB at `db_root_path`, which must not be open, into
+    /// `num_shards` RocksDB instances, or back into the main instance if `num_shards` is 1. The DB
+    /// can then be opened with `rocksdb_config.state_shards` set to `num_shards`. See
+    /// `RocksdbConfig::state_shards`.
+    pub fn migrate_state_shards<P: AsRef<Path>>(
+        db_root_path: P,
+        num_shards: usize,
+        rocksdb_config: RocksdbConfig,
+    ) -> Result<()> {
+        let db_root_path = db_root_path.as_ref();
+        state_shards::recover_interrupted_migration(db_root_path)?;
+        let current_rocksdb_config = RocksdbConfig {
+            state_shards: state_shards::num_shards_on_disk(db_root_path)?,
+            ..rocksdb_config.clone()
+        };
+        let diem_db = Self::open(db_root_path, false, None, current_rocksdb_config)?;
+        let db = Arc::clone(&diem_db.db);
+        let shards = diem_db.state_store.shards().clone();
+        // Nothing else may hold the shards while they are being replaced.
+        drop(diem_db);
+        state_shards::migrate(db_root_path, &db, shards, num_shards, &rocksdb_config)
+    }
+
     /// This opens db in non-readonly mode, without the pruner.
     #[cfg(any(test, feature = "fuzzing"))]
     pub fn new_for_test<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
@@ -323,11 +640,31 @@ impl DiemDB {
         .expect("Unable to open DiemDB")
     }
 
+    /// Creates a DB kept entirely in memory, for tests that want the real `DbReader` and
+    /// `DbWriter` behavior, proofs and pruning included, without touching the disk.
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn new_in_memory(pruner_options: PrunerOptions) -> Self {
+        let rocksdb_config = RocksdbConfig::default();
+        let db = DB::open_in_memory(
+            "diemdb_in_memory",
+            gen_cf_options(&rocksdb_config).expect("Default rocksdb config should be valid."),
+            &gen_rocksdb_options(&rocksdb_config),
+        )
+        .expect("Unable to open in-memory DiemDB");
+        Self::new_with_db(db, StateShards::default(), pruner_options)
+    }
+
     /// This force the db to update rocksdb properties immediately.
     pub fn update_rocksdb_properties(&self) -> Result<()> {
         update_rocksdb_properties(&self.db)
     }
 
+    /// Reads the RocksDB properties of each column family and the statistics tickers, the same
+    /// ones exported as metrics.
+    pub fn get_rocksdb_stats(&self) -> Result<RocksdbStats> {
+        get_rocksdb_stats(&self.db)
+    }
+
     /// Returns ledger infos reflecting epoch bumps starting with the given epoch. If there are no
     /// more than `MAX_NUM_EPOCH_ENDING_LEDGER_INFO` results, this function returns all of them,
     /// otherwise the first `MAX_NUM_EPOCH_ENDING_LEDGER_INFO` results are returned and a flag
@@ -378,8 +715,7 @@ impl DiemDB {
 
         let lis = self
             .ledger_store
-            .get_epoch_ending_ledger_info_iter(start_epoch, paging_epoch)?
-            .collect::<Result<Vec<_>>>()?;
+            .get_epoch_ending_ledger_infos(start_epoch, paging_epoch)?;
         ensure!(
             lis.len() == (paging_epoch - start_epoch) as usize,
             "DB corruption: missing epoch ending ledger info for epoch {}",
@@ -428,6 +764,29 @@ impl DiemDB {
         )
     }
 
+    // ================================== Verifier APIs =================================
+
+    /// Gets an instance of `DbVerifier` for checking the integrity of the data in the DB.
+    pub fn get_verifier(&self) -> DbVerifier {
+        DbVerifier::new(
+            Arc::clone(&self.ledger_store),
+            Arc::clone(&self.transaction_store),
+            Arc::clone(&self.state_store),
+            Arc::clone(&self.event_store),
+        )
+    }
+
+    // ================================== Snapshot APIs =================================
+
+    /// Gets an instance of `SnapshotHandler` for exporting and importing state snapshots.
+    pub fn get_snapshot_handler(&self) -> SnapshotHandler {
+        SnapshotHandler::new(
+            Arc::clone(&self.db),
+            Arc::clone(&self.ledger_store),
+            Arc::clone(&self.state_store),
+        )
+    }
+
     // ================================== Private APIs ==================================
     fn get_events_with_proof_by_event_key(
         &self,
@@ -522,7 +881,13 @@ impl DiemDB {
             None
         };
 
-        Ok((SealedChangeSet { batch: cs.batch }, counters))
+        Ok((
+            SealedChangeSet {
+                batch: cs.batch,
+                shard_batches: cs.shard_batches,
+            },
+            counters,
+        ))
     }
 
     fn save_transactions_impl(
@@ -581,17 +946,28 @@ impl DiemDB {
 
     /// Write the whole schema batch including all data necessary to mutate the ledger
     /// state of some transaction by leveraging rocksdb atomicity support. Also committed are the
-    /// LedgerCounters.
-    fn commit(&self, sealed_cs: SealedChangeSet) -> Result<()> {
-        self.db.write_schemas(sealed_cs.batch)?;
+    /// LedgerCounters. With group commit, the main batch is only synced to disk right away if
+    /// `sync` is set.
+    fn commit(&self, sealed_cs: SealedChangeSet, sync: bool) -> Result<()> {
+        // State nodes in the shards first, so they are all in once the roots are.
+        self.state_store
+            .shards()
+            .write_shard_batches(sealed_cs.shard_batches)?;
+        match self.group_committer.as_ref() {
+            Some(group_committer) => group_committer.write(sealed_cs.batch, sync)?,
+            None => self.db.write_schemas(sealed_cs.batch)?,
+        }
 
         Ok(())
     }
 
-    fn wake_pruner(&self, latest_version: Version) {
+    fn wake_pruner_and_archiver(&self, latest_version: Version) {
         if let Some(pruner) = self.pruner.as_ref() {
             pruner.wake(latest_version)
         }
+        if let Some(archiver) = self.archiver.as_ref() {
+            archiver.wake(latest_version)
+        }
     }
 }
 
@@ -647,6 +1023,34 @@ impl DbReader for DiemDB {
         })
     }
 
+    fn get_account_transactions(
+        &self,
+        address: AccountAddress,
+        start_seq_num: u64,
+        order: Order,
+        limit: u64,
+        ledger_version: Version,
+        fetch_events: bool,
+    ) -> Result<Vec<TransactionWithProof>> {
+        gauged_api("get_account_transactions", || {
+            error_if_too_many_requested(limit, MAX_LIMIT)?;
+
+            self.transaction_store
+                .get_account_transaction_version_iter(
+                    address,
+                    start_seq_num,
+                    order,
+                    ledger_version,
+                )?
+                .take(limit as usize)
+                .map(|res| {
+                    let (_seq_num, version) = res?;
+                    self.get_transaction_with_proof(version, ledger_version, fetch_events)
+                })
+                .collect()
+        })
+    }
+
     // ======================= State Synchronizer Internal APIs ===================================
     /// Gets a batch of transactions for the purpose of synchronizing state to another node.
     ///
@@ -718,6 +1122,51 @@ impl DbReader for DiemDB {
         })
     }
 
+    fn get_events_by_type(
+        &self,
+        type_tag: &TypeTag,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        gauged_api("get_events_by_type", || {
+            error_if_too_many_requested(limit, MAX_LIMIT)?;
+
+            self.event_store
+                .get_events_by_type(type_tag, start_version, limit, ledger_version)
+        })
+    }
+
+    fn get_events_by_account(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        gauged_api("get_events_by_account", || {
+            error_if_too_many_requested(limit, MAX_LIMIT)?;
+
+            self.event_store
+                .get_events_by_account(address, start_version, limit, ledger_version)
+        })
+    }
+
+    fn get_events_by_currency(
+        &self,
+        currency_code: &str,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        gauged_api("get_events_by_currency", || {
+            error_if_too_many_requested(limit, MAX_LIMIT)?;
+
+            self.event_store
+                .get_events_by_currency(currency_code, start_version, limit, ledger_version)
+        })
+    }
+
     fn get_events_with_proofs(
         &self,
         event_key: &EventKey,
@@ -743,6 +1192,17 @@ impl DbReader for DiemDB {
         })
     }
 
+    fn get_first_event_sequence_number_at_or_after_version(
+        &self,
+        event_key: &EventKey,
+        version: Version,
+    ) -> Result<Option<u64>> {
+        gauged_api("get_first_event_sequence_number_at_or_after_version", || {
+            self.event_store
+                .get_first_sequence_number_at_or_after_version(event_key, version)
+        })
+    }
+
     /// Gets ledger info at specified version and ensures it's an epoch ending.
     fn get_epoch_ending_ledger_info(&self, version: u64) -> Result<LedgerInfoWithSignatures> {
         gauged_api("get_epoch_ending_ledger_info", || {
@@ -898,6 +1358,12 @@ impl DbReader for DiemDB {
         })
     }
 
+    fn get_block_metadata(&self, version: Version) -> Result<Option<(Version, BlockMetadata)>> {
+        gauged_api("get_block_metadata", || {
+            self.transaction_store.get_block_metadata(version)
+        })
+    }
+
     fn get_last_version_before_timestamp(
         &self,
         timestamp: u64,
@@ -909,6 +1375,18 @@ impl DbReader for DiemDB {
         })
     }
 
+    fn get_earliest_available_version(&self) -> Result<Version> {
+        gauged_api("get_earliest_available_version", || {
+            Ok(match self.pruner.as_ref() {
+                Some(pruner) => pruner.least_readable_version(),
+                None => std::cmp::max(
+                    pruner::get_least_readable_state_version(&self.db)?,
+                    pruner::get_least_readable_ledger_version(&self.db)?,
+                ),
+            })
+        })
+    }
+
     fn get_latest_transaction_info_option(&self) -> Result<Option<(Version, TransactionInfo)>> {
         gauged_api("get_latest_transaction_info_option", || {
             self.ledger_store.get_latest_transaction_info_option()
@@ -920,6 +1398,25 @@ impl DbReader for DiemDB {
             self.ledger_store.get_root_hash(version)
         })
     }
+
+    fn get_state_snapshot_info(&self, version: Version) -> Result<StateSnapshotInfo> {
+        gauged_api("get_state_snapshot_info", || {
+            self.get_snapshot_handler().get_info(version)
+        })
+    }
+
+    fn get_state_snapshot_chunk(
+        &self,
+        version: Version,
+        start_after: Option<HashValue>,
+        limit: usize,
+    ) -> Result<StateSnapshotChunk> {
+        gauged_api("get_state_snapshot_chunk", || {
+            error_if_too_many_requested(limit as u64, MAX_LIMIT)?;
+            self.get_snapshot_handler()
+                .get_chunk(version, start_after, limit)
+        })
+    }
 }
 
 impl DbWriter for DiemDB {
@@ -979,7 +1476,8 @@ impl DbWriter for DiemDB {
                 let _timer = DIEM_STORAGE_OTHER_TIMERS_SECONDS
                     .with_label_values(&["save_transactions_commit"])
                     .start_timer();
-                self.commit(sealed_cs)?;
+                // What's saved with a ledger info is reported committed, so it's synced.
+                self.commit(sealed_cs, ledger_info_with_sigs.is_some())?;
             }
 
             // Once everything is successfully persisted, update the latest in-memory ledger info.
@@ -991,7 +1489,7 @@ impl DbWriter for DiemDB {
             }
 
             // Only increment counter if commit succeeds and there are at least one transaction written
-            // to the storage. That's also when we'd inform the pruner thread to work.
+            // to the storage. That's also when we'd inform the pruner and archiver threads to work.
             if num_txns > 0 {
                 let last_version = first_version + num_txns - 1;
                 DIEM_STORAGE_COMMITTED_TXNS.inc_by(num_txns);
@@ -1000,12 +1498,32 @@ impl DbWriter for DiemDB {
                     .expect("Counters should be bumped with transactions being saved.")
                     .bump_op_counters();
 
-                self.wake_pruner(last_version);
+                self.wake_pruner_and_archiver(last_version);
             }
 
             Ok(())
         })
     }
+
+    fn get_state_snapshot_receiver(
+        &self,
+        info: &StateSnapshotInfo,
+    ) -> Result<Box<dyn StateSnapshotReceiver>> {
+        gauged_api("get_state_snapshot_receiver", || {
+            Ok(Box::new(self.get_snapshot_handler().get_receiver(info)?)
+                as Box<dyn StateSnapshotReceiver>)
+        })
+    }
+
+    fn finalize_state_snapshot(&self, info: &StateSnapshotInfo) -> Result<()> {
+        gauged_api("finalize_state_snapshot", || {
+            self.get_snapshot_handler().finalize(info)?;
+            let version = info.version();
+            DIEM_STORAGE_LEDGER_VERSION.set(version as i64);
+            DIEM_STORAGE_LATEST_TXN_VERSION.set(version as i64);
+            Ok(())
+        })
+    }
 }
 
 // Convert requested range and order to a range in ascending order.
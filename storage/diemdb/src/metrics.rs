@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use diem_metrics::{
-    register_histogram_vec, register_int_counter, register_int_gauge, register_int_gauge_vec,
-    HistogramVec, IntCounter, IntGauge, IntGaugeVec,
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -55,6 +55,22 @@ pub static DIEM_STORAGE_PRUNE_WINDOW: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!("diem_storage_prune_window", "Diem storage prune window").unwrap()
 });
 
+pub static DIEM_STORAGE_PRUNE_WINDOW_SECS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_storage_prune_window_secs",
+        "Diem storage prune window in seconds"
+    )
+    .unwrap()
+});
+
+pub static DIEM_STORAGE_PRUNER_TARGET_LEAST_READABLE_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_storage_pruner_target_least_readable_version",
+        "Diem storage pruner target least readable version"
+    )
+    .unwrap()
+});
+
 pub static DIEM_STORAGE_PRUNER_LEAST_READABLE_STATE_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
         "diem_storage_pruner_least_readable_state_version",
@@ -63,6 +79,22 @@ pub static DIEM_STORAGE_PRUNER_LEAST_READABLE_STATE_VERSION: Lazy<IntGauge> = La
     .unwrap()
 });
 
+pub static DIEM_STORAGE_PRUNER_LEAST_READABLE_LEDGER_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_storage_pruner_least_readable_ledger_version",
+        "Diem storage pruner least readable transaction and event version"
+    )
+    .unwrap()
+});
+
+pub static DIEM_STORAGE_ARCHIVED_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_storage_archived_version",
+        "Transactions and events before this version are moved to archive files"
+    )
+    .unwrap()
+});
+
 pub static DIEM_STORAGE_API_LATENCY_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         // metric name
@@ -87,6 +119,18 @@ pub static DIEM_STORAGE_OTHER_TIMERS_SECONDS: Lazy<HistogramVec> = Lazy::new(||
     .unwrap()
 });
 
+pub static DIEM_STORAGE_GROUP_COMMIT_SYNCS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "diem_storage_group_commit_syncs",
+        // metric description
+        "Syncs to disk of the writes grouped by group commit, by what triggered them",
+        // metric labels (dimensions)
+        &["trigger"]
+    )
+    .unwrap()
+});
+
 /// Rocksdb metrics
 pub static DIEM_STORAGE_ROCKSDB_PROPERTIES: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -100,6 +144,19 @@ pub static DIEM_STORAGE_ROCKSDB_PROPERTIES: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Rocksdb statistics tickers, counted since the DB was opened.
+pub static DIEM_STORAGE_ROCKSDB_TICKERS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        // metric name
+        "diem_rocksdb_tickers",
+        // metric description
+        "rocksdb statistics tickers",
+        // metric labels (dimensions)
+        &["ticker_name"]
+    )
+    .unwrap()
+});
+
 // Backup progress gauges:
 
 pub(crate) static BACKUP_EPOCH_ENDING_EPOCH: Lazy<IntGauge> = Lazy::new(|| {
@@ -133,3 +190,15 @@ pub(crate) static BACKUP_STATE_SNAPSHOT_LEAF_IDX: Lazy<IntGauge> = Lazy::new(||
     )
     .unwrap()
 });
+
+pub static DIEM_STORAGE_PROOF_CACHE_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "diem_storage_proof_cache_requests",
+        // metric description
+        "Lookups in the cache of range proofs and epoch ending ledger infos, by result",
+        // metric labels (dimensions)
+        &["cache", "result"]
+    )
+    .unwrap()
+});
@@ -3,15 +3,23 @@
 
 //! This module provides `Pruner` which manages a thread pruning old data in the background and is
 //! meant to be triggered by other threads as they commit new data to the DB.
+//!
+//! Old versions of the state tree are always pruned. Transactions and events are only pruned if
+//! `PrunerOptions::prune_ledger` is set. Transaction infos, the transaction accumulator and ledger
+//! infos are never pruned, so proofs at the versions still readable can always be served.
 
 use crate::{
+    change_set::ChangeSet,
+    event_store::EventStore,
     metrics::{
-        DIEM_STORAGE_OTHER_TIMERS_SECONDS, DIEM_STORAGE_PRUNER_LEAST_READABLE_STATE_VERSION,
-        DIEM_STORAGE_PRUNE_WINDOW,
-    },
-    schema::{
-        jellyfish_merkle_node::JellyfishMerkleNodeSchema, stale_node_index::StaleNodeIndexSchema,
+        DIEM_STORAGE_OTHER_TIMERS_SECONDS, DIEM_STORAGE_PRUNER_LEAST_READABLE_LEDGER_VERSION,
+        DIEM_STORAGE_PRUNER_LEAST_READABLE_STATE_VERSION,
+        DIEM_STORAGE_PRUNER_TARGET_LEAST_READABLE_VERSION, DIEM_STORAGE_PRUNE_WINDOW,
+        DIEM_STORAGE_PRUNE_WINDOW_SECS,
     },
+    schema::{stale_node_index::StaleNodeIndexSchema, transaction::TransactionSchema},
+    state_shards::StateShards,
+    transaction_store::TransactionStore,
 };
 use anyhow::Result;
 use diem_infallible::Mutex;
@@ -20,6 +28,7 @@ use diem_logger::prelude::*;
 use diem_types::transaction::Version;
 use schemadb::{ReadOptions, SchemaBatch, SchemaIterator, DB};
 use std::{
+    cmp::{max, min},
     iter::Peekable,
     sync::{
         atomic::{AtomicU64, Ordering},
@@ -30,6 +39,29 @@ use std::{
     time::{Duration, Instant},
 };
 
+/// Configures how much history the `Pruner` keeps.
+///
+/// A version is pruned once it's outside all the configured windows, so with both windows set the
+/// larger one wins. With neither set, nothing is pruned.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PrunerOptions {
+    /// Other than the latest version, how many historical versions to keep being readable. For
+    /// example, this being `Some(0)` means keep only the latest version.
+    pub historical_versions_to_keep: Option<u64>,
+    /// Keeps readable all versions committed within this many seconds (in block time) of the
+    /// latest version.
+    pub window_secs: Option<u64>,
+    /// Whether to prune transactions and events as well, not only the state tree.
+    pub prune_ledger: bool,
+}
+
+impl PrunerOptions {
+    /// Whether any window is configured, i.e. whether there's anything to prune at all.
+    pub fn is_enabled(&self) -> bool {
+        self.historical_versions_to_keep.is_some() || self.window_secs.is_some()
+    }
+}
+
 /// The `Pruner` is meant to be part of a `DiemDB` instance and runs in the background to prune old
 /// data.
 ///
@@ -37,69 +69,101 @@ use std::{
 /// quits the worker thread eagerly without waiting for all pending work to be done.
 #[derive(Debug)]
 pub(crate) struct Pruner {
-    /// Other than the latest version, how many historical versions to keep being readable. For
-    /// example, this being 0 means keep only the latest version.
-    historical_versions_to_keep: u64,
+    /// (For tests) What the worker thread is configured with.
+    #[allow(dead_code)]
+    options: PrunerOptions,
     /// The worker thread handle, created upon Pruner instance construction and joined upon its
     /// destruction. It only becomes `None` after joined in `drop()`.
     worker_thread: Option<JoinHandle<()>>,
     /// The sender side of the channel talking to the worker thread.
     command_sender: Mutex<Sender<Command>>,
-    /// (For tests) A way for the worker thread to inform the `Pruner` the pruning progress. If it
-    /// sets this atomic value to `V`, all versions before `V` can no longer be accessed.
-    #[allow(dead_code)]
-    worker_progress: Arc<AtomicU64>,
+    /// A way for the worker thread to inform the `Pruner` the pruning progress. If it sets this
+    /// atomic value to `V`, the state of all versions before `V` can no longer be accessed.
+    state_progress: Arc<AtomicU64>,
+    /// Like `state_progress`, for transactions and events.
+    ledger_progress: Arc<AtomicU64>,
 }
 
 impl Pruner {
-    /// Creates a worker thread that waits on a channel for pruning commands.
-    pub fn new(db: Arc<DB>, historical_versions_to_keep: u64) -> Self {
+    /// Creates a worker thread that waits on a channel for pruning commands. State nodes below
+    /// the root are deleted from `state_shards` if the state is sharded.
+    pub fn new(db: Arc<DB>, state_shards: StateShards, options: PrunerOptions) -> Self {
         let (command_sender, command_receiver) = channel();
 
-        let worker_progress = Arc::new(AtomicU64::new(0));
-        let worker_progress_clone = Arc::clone(&worker_progress);
+        // Until the worker initializes, nothing is known to be pruned.
+        let state_progress = Arc::new(AtomicU64::new(0));
+        let ledger_progress = Arc::new(AtomicU64::new(0));
+        let state_progress_clone = Arc::clone(&state_progress);
+        let ledger_progress_clone = Arc::clone(&ledger_progress);
 
-        DIEM_STORAGE_PRUNE_WINDOW.set(historical_versions_to_keep as i64);
+        if let Some(historical_versions_to_keep) = options.historical_versions_to_keep {
+            DIEM_STORAGE_PRUNE_WINDOW.set(historical_versions_to_keep as i64);
+        }
+        if let Some(window_secs) = options.window_secs {
+            DIEM_STORAGE_PRUNE_WINDOW_SECS.set(window_secs as i64);
+        }
         let worker_thread = std::thread::Builder::new()
             .name("diemdb_pruner".into())
-            .spawn(move || Worker::new(db, command_receiver, worker_progress_clone).work())
+            .spawn(move || {
+                Worker::new(
+                    db,
+                    state_shards,
+                    command_receiver,
+                    options,
+                    state_progress_clone,
+                    ledger_progress_clone,
+                )
+                .work()
+            })
             .expect("Creating pruner thread should succeed.");
 
         Self {
-            historical_versions_to_keep,
+            options,
             worker_thread: Some(worker_thread),
             command_sender: Mutex::new(command_sender),
-            worker_progress,
+            state_progress,
+            ledger_progress,
         }
     }
 
-    /// Sends pruning command to the worker thread when necessary.
+    /// Sends pruning command to the worker thread, which works out what's to be pruned as of
+    /// `latest_version`.
     pub fn wake(&self, latest_version: Version) {
-        if latest_version > self.historical_versions_to_keep {
-            let least_readable_version = latest_version - self.historical_versions_to_keep;
-            self.command_sender
-                .lock()
-                .send(Command::Prune {
-                    least_readable_version,
-                })
-                .expect("Receiver should not destruct prematurely.");
-        }
+        self.command_sender
+            .lock()
+            .send(Command::Prune { latest_version })
+            .expect("Receiver should not destruct prematurely.");
+    }
+
+    /// The least version whose state, transaction and events are all readable.
+    pub fn least_readable_version(&self) -> Version {
+        max(
+            self.state_progress.load(Ordering::Relaxed),
+            self.ledger_progress.load(Ordering::Relaxed),
+        )
     }
 
     /// (For tests only.) Notifies the worker thread and waits for it to finish its job by polling
-    /// an internal counter.
+    /// internal counters. Only the version count window is taken into account.
     #[cfg(test)]
     pub fn wake_and_wait(&self, latest_version: Version) -> Result<()> {
         self.wake(latest_version);
 
-        if latest_version > self.historical_versions_to_keep {
-            let least_readable_version = latest_version - self.historical_versions_to_keep;
+        let historical_versions_to_keep = self
+            .options
+            .historical_versions_to_keep
+            .expect("Tests wait on the version count window.");
+        if latest_version > historical_versions_to_keep {
+            let least_readable_version = latest_version - historical_versions_to_keep;
             // Assuming no big pruning chunks will be issued by a test.
             const TIMEOUT: Duration = Duration::from_secs(10);
             let end = Instant::now() + TIMEOUT;
 
             while Instant::now() < end {
-                if self.worker_progress.load(Ordering::Relaxed) >= least_readable_version {
+                if self.state_progress.load(Ordering::Relaxed) >= least_readable_version
+                    && (!self.options.prune_ledger
+                        || self.ledger_progress.load(Ordering::Relaxed) >= least_readable_version)
+                {
                     return Ok(());
                 }
                 sleep(Duration::from_millis(1));
@@ -126,17 +190,23 @@ impl Drop for Pruner {
 
 enum Command {
     Quit,
-    Prune { least_readable_version: Version },
+    Prune { latest_version: Version },
 }
 
 struct Worker {
     db: Arc<DB>,
+    state_shards: StateShards,
+    transaction_store: TransactionStore,
+    event_store: EventStore,
     command_receiver: Receiver<Command>,
+    options: PrunerOptions,
     target_least_readable_version: Version,
     /// Keeps a record of the pruning progress. If this equals to version `V`, we know versions
     /// smaller than `V` are no longer readable.
-    /// This being an atomic value is to communicate the info with the Pruner thread (for tests).
+    /// This being an atomic value is to communicate the info with the Pruner thread.
     least_readable_version: Arc<AtomicU64>,
+    /// Like `least_readable_version`, for transactions and events.
+    least_readable_ledger_version: Arc<AtomicU64>,
     /// Indicates if there's NOT any pending work to do currently, to hint
     /// `Self::receive_commands()` to `recv()` blocking-ly.
     blocking_recv: bool,
@@ -149,13 +219,22 @@ impl Worker {
 
     fn new(
         db: Arc<DB>,
+        state_shards: StateShards,
         command_receiver: Receiver<Command>,
+        options: PrunerOptions,
         least_readable_version: Arc<AtomicU64>,
+        least_readable_ledger_version: Arc<AtomicU64>,
     ) -> Self {
         Self {
+            transaction_store: TransactionStore::new(Arc::clone(&db)),
+            // The index flag only matters for writes.
+            event_store: EventStore::new(Arc::clone(&db), false),
             db,
+            state_shards,
             command_receiver,
+            options,
             least_readable_version,
+            least_readable_ledger_version,
             target_least_readable_version: 0,
             blocking_recv: true,
             index_min_nonpurged_version: 0,
@@ -169,19 +248,10 @@ impl Worker {
         while self.receive_commands() {
             // Process a reasonably small batch of work before trying to receive commands again,
             // in case `Command::Quit` is received (that's when we should quit.)
-            let least_readable_version = self.least_readable_version.load(Ordering::Relaxed);
-            match prune_state(
-                Arc::clone(&self.db),
-                least_readable_version,
-                self.target_least_readable_version,
-                Self::MAX_VERSIONS_TO_PRUNE_PER_BATCH,
-            ) {
-                Ok(new_least_readable_version) => {
-                    self.record_progress(new_least_readable_version);
-
+            match self.prune_batch() {
+                Ok(done) => {
                     // Make next recv() blocking if nothing left to do.
-                    self.blocking_recv = new_least_readable_version == least_readable_version // did nothing
-                        || new_least_readable_version == self.target_least_readable_version; // did all
+                    self.blocking_recv = done;
 
                     // Try to purge the log.
                     if let Err(e) = self.maybe_purge_index() {
@@ -194,7 +264,7 @@ impl Worker {
                 Err(e) => {
                     error!(
                         error = ?e,
-                        "Error pruning stale state nodes.",
+                        "Error pruning old data.",
                     );
                     // On error, stop retrying vigorously by making next recv() blocking.
                     self.blocking_recv = true;
@@ -203,26 +273,130 @@ impl Worker {
         }
     }
 
-    /// Find out the first undeleted item in the stale node index.
+    /// Prunes up to `MAX_VERSIONS_TO_PRUNE_PER_BATCH` versions of the state and, if configured,
+    /// of transactions and events. Returns whether there's nothing left to do.
+    fn prune_batch(&mut self) -> Result<bool> {
+        let least_readable_version = self.least_readable_version.load(Ordering::Relaxed);
+        let new_least_readable_version = prune_state(
+            Arc::clone(&self.db),
+            &self.state_shards,
+            least_readable_version,
+            self.target_least_readable_version,
+            Self::MAX_VERSIONS_TO_PRUNE_PER_BATCH,
+        )?;
+        self.record_progress(new_least_readable_version);
+        let mut done = new_least_readable_version == least_readable_version // did nothing
+            || new_least_readable_version == self.target_least_readable_version; // did all
+
+        if self.options.prune_ledger {
+            let least_readable_ledger_version =
+                self.least_readable_ledger_version.load(Ordering::Relaxed);
+            let new_least_readable_ledger_version = self.prune_ledger(
+                least_readable_ledger_version,
+                self.target_least_readable_version,
+                Self::MAX_VERSIONS_TO_PRUNE_PER_BATCH,
+            )?;
+            self.record_ledger_progress(new_least_readable_ledger_version);
+            done &= new_least_readable_ledger_version >= self.target_least_readable_version;
+        }
+
+        Ok(done)
+    }
+
+    /// Deletes transactions and events with versions from `least_readable_version` (inclusive),
+    /// going through at most `max_versions` versions but not reaching
+    /// `target_least_readable_version`. Returns the new least readable version.
+    fn prune_ledger(
+        &self,
+        least_readable_version: Version,
+        target_least_readable_version: Version,
+        max_versions: usize,
+    ) -> Result<Version> {
+        let end = min(
+            target_least_readable_version,
+            least_readable_version.saturating_add(max_versions as u64),
+        );
+        if end <= least_readable_version {
+            return Ok(least_readable_version);
+        }
+
+        let _timer = DIEM_STORAGE_OTHER_TIMERS_SECONDS
+            .with_label_values(&["pruner_ledger_commit"])
+            .start_timer();
+        let mut batch = SchemaBatch::new();
+        self.transaction_store
+            .prune_transactions(least_readable_version, end, &mut batch)?;
+        self.event_store
+            .prune_events(least_readable_version, end, &mut batch)?;
+        self.db.write_schemas(batch)?;
+        Ok(end)
+    }
+
+    /// The least version to keep readable as of `latest_version`, according to the configured
+    /// windows.
+    fn get_target_least_readable_version(&self, latest_version: Version) -> Version {
+        let by_versions = self
+            .options
+            .historical_versions_to_keep
+            .map(|n| latest_version.saturating_sub(n));
+        let by_time = self.options.window_secs.map(|window_secs| {
+            self.get_least_readable_version_by_time(latest_version, window_secs)
+                .unwrap_or_else(|e| {
+                    // E.g. the chain (or what's left of it) is younger than the window.
+                    debug!(
+                        error = ?e,
+                        "[pruner worker] Nothing to prune by time.",
+                    );
+                    0
+                })
+        });
+
+        by_versions.into_iter().chain(by_time).min().unwrap_or(0)
+    }
+
+    /// The last version committed before the window of `window_secs` ending at the block of
+    /// `latest_version`, i.e. the state at the start of the window.
+    fn get_least_readable_version_by_time(
+        &self,
+        latest_version: Version,
+        window_secs: u64,
+    ) -> Result<Version> {
+        let latest_timestamp = self
+            .transaction_store
+            .get_block_metadata(latest_version)?
+            .map_or(0, |(_v, block_meta)| block_meta.into_inner().1);
+        match latest_timestamp.checked_sub(window_secs.saturating_mul(1_000_000)) {
+            Some(timestamp) if timestamp > 0 => self
+                .event_store
+                .get_last_version_before_timestamp(timestamp, latest_version),
+            _ => Ok(0),
+        }
+    }
+
+    /// Find out the first undeleted item in the stale node index and the first transaction.
     ///
     /// Seeking from the beginning (version 0) is potentially costly, we do it once upon worker
     /// thread start, record the progress and seek from that position afterwards.
     fn initialize(&mut self) {
         loop {
-            match self.get_least_readable_version() {
-                Ok(least_readable_version) => {
+            match get_least_readable_state_version(&self.db)
+                .and_then(|v| Ok((v, get_least_readable_ledger_version(&self.db)?)))
+            {
+                Ok((least_readable_version, least_readable_ledger_version)) => {
                     info!(
                         least_readable_version = least_readable_version,
-                        "[state pruner worker] initialized."
+                        least_readable_ledger_version = least_readable_ledger_version,
+                        "[pruner worker] initialized."
                     );
                     self.target_least_readable_version = least_readable_version;
                     self.record_progress(least_readable_version);
+                    self.record_ledger_progress(least_readable_ledger_version);
                     return;
                 }
                 Err(e) => {
                     error!(
                         error = ?e,
-                        "[state pruner worker] Error on first seek. Retrying in 1 second.",
+                        "[pruner worker] Error on first seek. Retrying in 1 second.",
                     );
                     sleep(Duration::from_secs(1));
                 }
@@ -230,19 +404,6 @@ impl Worker {
         }
     }
 
-    fn get_least_readable_version(&self) -> Result<Version> {
-        let mut iter = self
-            .db
-            .iter::<StaleNodeIndexSchema>(ReadOptions::default())?;
-        iter.seek_to_first();
-        Ok(iter.next().transpose()?.map_or(0, |(index, _)| {
-            index
-                .stale_since_version
-                .checked_sub(1)
-                .expect("Nothing is stale since version 0.")
-        }))
-    }
-
     /// Log the progress.
     fn record_progress(&mut self, least_readable_version: Version) {
         self.least_readable_version
@@ -250,6 +411,13 @@ impl Worker {
         DIEM_STORAGE_PRUNER_LEAST_READABLE_STATE_VERSION.set(least_readable_version as i64);
     }
 
+    /// Log the progress of pruning transactions and events.
+    fn record_ledger_progress(&mut self, least_readable_ledger_version: Version) {
+        self.least_readable_ledger_version
+            .store(least_readable_ledger_version, Ordering::Relaxed);
+        DIEM_STORAGE_PRUNER_LEAST_READABLE_LEDGER_VERSION.set(least_readable_ledger_version as i64);
+    }
+
     /// Tries to receive all pending commands, blocking waits for the next command if no work needs
     /// to be done, otherwise quits with `true` to allow the outer loop to do some work before
     /// getting back here.
@@ -275,11 +443,13 @@ impl Worker {
             match command {
                 // On `Command::Quit` inform the outer loop to quit by returning `false`.
                 Command::Quit => return false,
-                Command::Prune {
-                    least_readable_version,
-                } => {
+                Command::Prune { latest_version } => {
+                    let least_readable_version =
+                        self.get_target_least_readable_version(latest_version);
                     if least_readable_version > self.target_least_readable_version {
                         self.target_least_readable_version = least_readable_version;
+                        DIEM_STORAGE_PRUNER_TARGET_LEAST_READABLE_VERSION
+                            .set(least_readable_version as i64);
                         // Switch to non-blocking to allow some work to be done after the
                         // channel has drained.
                         self.blocking_recv = false;
@@ -320,6 +490,26 @@ impl Worker {
     }
 }
 
+/// The least version whose state is readable according to the stale node index. The index is purged
+/// lazily, so this may be smaller than the least version actually readable.
+pub(crate) fn get_least_readable_state_version(db: &DB) -> Result<Version> {
+    let mut iter = db.iter::<StaleNodeIndexSchema>(ReadOptions::default())?;
+    iter.seek_to_first();
+    Ok(iter.next().transpose()?.map_or(0, |(index, _)| {
+        index
+            .stale_since_version
+            .checked_sub(1)
+            .expect("Nothing is stale since version 0.")
+    }))
+}
+
+/// The version of the first transaction in the DB, or 0 if there's none.
+pub(crate) fn get_least_readable_ledger_version(db: &DB) -> Result<Version> {
+    let mut iter = db.iter::<TransactionSchema>(ReadOptions::default())?;
+    iter.seek_to_first();
+    Ok(iter.next().transpose()?.map_or(0, |(version, _)| version))
+}
+
 struct StaleNodeIndicesByVersionIterator<'a> {
     inner: Peekable<SchemaIterator<'a, StaleNodeIndexSchema>>,
     target_least_readable_version: Version,
@@ -377,6 +567,7 @@ impl<'a> Iterator for StaleNodeIndicesByVersionIterator<'a> {
 
 pub fn prune_state(
     db: Arc<DB>,
+    state_shards: &StateShards,
     least_readable_version: Version,
     target_least_readable_version: Version,
     max_versions: usize,
@@ -399,11 +590,12 @@ pub fn prune_state(
             .with_label_values(&["pruner_commit"])
             .start_timer();
         let new_least_readable_version = indices.last().expect("Should exist.").stale_since_version;
-        let mut batch = SchemaBatch::new();
+        let mut cs = ChangeSet::new();
         indices
             .into_iter()
-            .try_for_each(|index| batch.delete::<JellyfishMerkleNodeSchema>(&index.node_key))?;
-        db.write_schemas(batch)?;
+            .try_for_each(|index| state_shards.delete_node(&index.node_key, &mut cs))?;
+        state_shards.write_shard_batches(cs.shard_batches)?;
+        db.write_schemas(cs.batch)?;
         Ok(new_least_readable_version)
     }
 }
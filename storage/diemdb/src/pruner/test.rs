@@ -5,7 +5,15 @@ use super::*;
 use crate::{change_set::ChangeSet, state_store::StateStore, DiemDB};
 use diem_crypto::HashValue;
 use diem_temppath::TempPath;
-use diem_types::{account_address::AccountAddress, account_state_blob::AccountStateBlob};
+use diem_types::{
+    account_address::AccountAddress,
+    account_config::NewBlockEvent,
+    account_state_blob::AccountStateBlob,
+    block_metadata::{new_block_event_key, BlockMetadata},
+    contract_event::ContractEvent,
+    transaction::Transaction,
+};
+use move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
 use std::collections::HashMap;
 
 fn put_account_state_set(
@@ -49,7 +57,14 @@ fn test_pruner() {
     let tmp_dir = TempPath::new();
     let db = DiemDB::new_for_test(&tmp_dir).db;
     let state_store = &StateStore::new(Arc::clone(&db));
-    let pruner = Pruner::new(Arc::clone(&db), 0 /* historical_versions_to_keep */);
+    let pruner = Pruner::new(
+        Arc::clone(&db),
+        StateShards::default(),
+        PrunerOptions {
+            historical_versions_to_keep: Some(0),
+            ..Default::default()
+        },
+    );
 
     let _root0 = put_account_state_set(
         &db,
@@ -134,18 +149,20 @@ fn test_worker_quit_eagerly() {
         let (command_sender, command_receiver) = channel();
         let worker = Worker::new(
             Arc::clone(&db),
+            StateShards::default(),
             command_receiver,
+            PrunerOptions {
+                historical_versions_to_keep: Some(0),
+                ..Default::default()
+            },
             Arc::new(AtomicU64::new(0)), /* progress */
+            Arc::new(AtomicU64::new(0)), /* ledger progress */
         );
         command_sender
-            .send(Command::Prune {
-                least_readable_version: 1,
-            })
+            .send(Command::Prune { latest_version: 1 })
             .unwrap();
         command_sender
-            .send(Command::Prune {
-                least_readable_version: 2,
-            })
+            .send(Command::Prune { latest_version: 2 })
             .unwrap();
         command_sender.send(Command::Quit).unwrap();
         // Worker quits immediately although `Command::Quit` is not the first command sent.
@@ -155,3 +172,156 @@ fn test_worker_quit_eagerly() {
         verify_state_in_store(state_store, address, Some(&value2), 2);
     }
 }
+
+/// Saves the block metadata transaction at `version`, which emits the new block event with `seq`.
+/// The other transactions of the block are left out.
+fn put_block(
+    db: &DB,
+    transaction_store: &TransactionStore,
+    event_store: &EventStore,
+    version: Version,
+    seq: u64,
+    timestamp_usecs: u64,
+) {
+    let mut cs = ChangeSet::new();
+    let block_metadata = BlockMetadata::new(
+        HashValue::random(),
+        seq, /* round */
+        timestamp_usecs,
+        vec![],
+        AccountAddress::ZERO,
+    );
+    let new_block_event = ContractEvent::new(
+        new_block_event_key(),
+        seq,
+        TypeTag::Struct(NewBlockEvent::struct_tag()),
+        bcs::to_bytes(&diem_types::block_metadata::NewBlockEvent::new(
+            seq,
+            AccountAddress::ZERO,
+            vec![],
+            timestamp_usecs,
+        ))
+        .unwrap(),
+    );
+    transaction_store
+        .put_transaction(
+            version,
+            &Transaction::BlockMetadata(block_metadata),
+            &mut cs,
+        )
+        .unwrap();
+    event_store
+        .put_events(version, &[new_block_event], &mut cs)
+        .unwrap();
+    db.write_schemas(cs.batch).unwrap();
+}
+
+#[test]
+fn test_ledger_pruner() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir).db;
+    let state_store = &StateStore::new(Arc::clone(&db));
+    let transaction_store = &TransactionStore::new(Arc::clone(&db));
+    let event_store = &EventStore::new(Arc::clone(&db), false);
+    let address = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+    for version in 0..30 {
+        put_account_state_set(
+            &db,
+            state_store,
+            vec![(address, AccountStateBlob::from(vec![version as u8]))],
+            version,
+        );
+    }
+    // Blocks of 10 transactions each, starting at versions 0, 10 and 20.
+    for seq in 0..3 {
+        put_block(
+            &db,
+            transaction_store,
+            event_store,
+            seq * 10,
+            seq,
+            (seq + 1) * 1_000_000,
+        );
+    }
+
+    let pruner = Pruner::new(
+        Arc::clone(&db),
+        StateShards::default(),
+        PrunerOptions {
+            historical_versions_to_keep: Some(15),
+            prune_ledger: true,
+            ..Default::default()
+        },
+    );
+    pruner.wake_and_wait(29 /* latest_version */).unwrap();
+    assert_eq!(pruner.least_readable_version(), 14);
+
+    assert!(transaction_store.get_transaction(10).is_err());
+    assert!(transaction_store.get_transaction(20).is_ok());
+    assert!(event_store.get_events_by_version(10).unwrap().is_empty());
+    assert_eq!(event_store.get_events_by_version(20).unwrap().len(), 1);
+    // The first transaction left.
+    assert_eq!(get_least_readable_ledger_version(&db).unwrap(), 20);
+    assert!(state_store
+        .get_account_state_with_proof_by_version(address, 13)
+        .is_err());
+    verify_state_in_store(
+        state_store,
+        address,
+        Some(&AccountStateBlob::from(vec![14])),
+        14,
+    );
+}
+
+#[test]
+fn test_target_by_time() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir).db;
+    let transaction_store = &TransactionStore::new(Arc::clone(&db));
+    let event_store = &EventStore::new(Arc::clone(&db), false);
+    // Blocks of 10 transactions each, starting at versions 0, 10, 20 and 30, one second apart.
+    for seq in 0..4 {
+        put_block(
+            &db,
+            transaction_store,
+            event_store,
+            seq * 10,
+            seq,
+            (seq + 1) * 1_000_000,
+        );
+    }
+
+    let worker = |historical_versions_to_keep, window_secs| {
+        Worker::new(
+            Arc::clone(&db),
+            StateShards::default(),
+            channel().1,
+            PrunerOptions {
+                historical_versions_to_keep,
+                window_secs,
+                prune_ledger: true,
+            },
+            Arc::new(AtomicU64::new(0)), /* progress */
+            Arc::new(AtomicU64::new(0)), /* ledger progress */
+        )
+    };
+    // The window starts with the block at version 20.
+    assert_eq!(
+        worker(None, Some(1)).get_target_least_readable_version(39),
+        19
+    );
+    // The window is longer than the chain.
+    assert_eq!(
+        worker(None, Some(10)).get_target_least_readable_version(39),
+        0
+    );
+    // The larger window wins.
+    assert_eq!(
+        worker(Some(5), Some(1)).get_target_least_readable_version(39),
+        19
+    );
+    assert_eq!(
+        worker(Some(30), Some(1)).get_target_least_readable_version(39),
+        9
+    );
+}
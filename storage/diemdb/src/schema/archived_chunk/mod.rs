@@ -0,0 +1,57 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the index of the chunks of transactions and
+//! events moved out of the DB into archive files.
+//!
+//! ```text
+//! |<-----key----->|<-------value------->|
+//! | first_version | last_version, file  |
+//! ```
+//!
+//! `first_version` is serialized in big endian so that records in RocksDB will be in order of their
+//! numeric value, making it possible to find the chunk containing a version with `seek_for_prev`.
+
+use crate::{
+    archive::ArchivedChunkInfo,
+    schema::{ensure_slice_len_eq, ARCHIVED_CHUNK_CF_NAME},
+};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt};
+use diem_types::transaction::Version;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(
+    ArchivedChunkSchema,
+    Version,
+    ArchivedChunkInfo,
+    ARCHIVED_CHUNK_CF_NAME
+);
+
+impl KeyCodec<ArchivedChunkSchema> for Version {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Version>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+impl ValueCodec<ArchivedChunkSchema> for ArchivedChunkInfo {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,16 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::prelude::*;
+use schemadb::schema::assert_encode_decode;
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        first_version in any::<Version>(),
+        info in any::<ArchivedChunkInfo>(),
+    ) {
+        assert_encode_decode::<ArchivedChunkSchema>(&first_version, &info);
+    }
+}
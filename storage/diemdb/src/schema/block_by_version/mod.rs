@@ -0,0 +1,56 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an index to help us find out which block a
+//! transaction is in, by storing the block metadata carried on each block metadata transaction
+//! by its version: a transaction belongs to the block of the closest entry at or before its
+//! version.
+//!
+//! ```text
+//! |<--key-->|<-----value---->|
+//! | version | block metadata |
+//! ```
+//!
+//! `version` is serialized in big endian so that records in RocksDB will be in order of their
+//! numeric value.
+
+use crate::schema::{ensure_slice_len_eq, BLOCK_BY_VERSION_CF_NAME};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt};
+use diem_types::{block_metadata::BlockMetadata, transaction::Version};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(
+    BlockByVersionSchema,
+    Version,
+    BlockMetadata,
+    BLOCK_BY_VERSION_CF_NAME
+);
+
+impl KeyCodec<BlockByVersionSchema> for Version {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+impl ValueCodec<BlockByVersionSchema> for BlockMetadata {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
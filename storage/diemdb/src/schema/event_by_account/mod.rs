@@ -0,0 +1,64 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an optional event index via which the
+//! ContractEvents involving an account (each represented by a <txn_version, event_idx> tuple so
+//! that it can be fetched from `EventSchema`) can be found in the order they were emitted.
+//!
+//! ```text
+//! |<----------key---------->|
+//! | address | txn_ver | idx |
+//! ```
+
+use crate::schema::{ensure_slice_len_eq, EVENT_BY_ACCOUNT_CF_NAME};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use diem_types::{account_address::AccountAddress, transaction::Version};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::{convert::TryFrom, mem::size_of};
+
+define_schema!(EventByAccountSchema, Key, (), EVENT_BY_ACCOUNT_CF_NAME);
+
+type Index = u64;
+type Key = (AccountAddress, Version, Index);
+
+impl KeyCodec<EventByAccountSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref address, version, index) = *self;
+
+        let mut encoded = address.to_vec();
+        encoded.write_u64::<BigEndian>(version)?;
+        encoded.write_u64::<BigEndian>(index)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+
+        const ADDRESS_LEN: usize = AccountAddress::LENGTH;
+        const VERSION_SIZE: usize = size_of::<Version>();
+        let address = AccountAddress::try_from(&data[..ADDRESS_LEN])?;
+        let version = (&data[ADDRESS_LEN..ADDRESS_LEN + VERSION_SIZE]).read_u64::<BigEndian>()?;
+        let index = (&data[ADDRESS_LEN + VERSION_SIZE..]).read_u64::<BigEndian>()?;
+
+        Ok((address, version, index))
+    }
+}
+
+impl ValueCodec<EventByAccountSchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 0)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,68 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an optional event index via which the
+//! ContractEvents involving a currency (each represented by a <txn_version, event_idx> tuple so
+//! that it can be fetched from `EventSchema`) can be found in the order they were emitted.
+//!
+//! The currency is identified by the SHA3-256 hash of its currency code, which keeps keys fixed
+//! size. A reader must check the currency of the events it fetches through this index.
+//!
+//! ```text
+//! |<----------------key--------------->|
+//! | currency_code_hash | txn_ver | idx |
+//! ```
+
+use crate::schema::{ensure_slice_len_eq, EVENT_BY_CURRENCY_CF_NAME};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use diem_crypto::HashValue;
+use diem_types::transaction::Version;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(EventByCurrencySchema, Key, (), EVENT_BY_CURRENCY_CF_NAME);
+
+type Index = u64;
+type Key = (HashValue, Version, Index);
+
+impl KeyCodec<EventByCurrencySchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref currency_code_hash, version, index) = *self;
+
+        let mut encoded = currency_code_hash.to_vec();
+        encoded.write_u64::<BigEndian>(version)?;
+        encoded.write_u64::<BigEndian>(index)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+
+        const HASH_LEN: usize = HashValue::LENGTH;
+        const VERSION_SIZE: usize = size_of::<Version>();
+        let currency_code_hash = HashValue::from_slice(&data[..HASH_LEN])?;
+        let version = (&data[HASH_LEN..HASH_LEN + VERSION_SIZE]).read_u64::<BigEndian>()?;
+        let index = (&data[HASH_LEN + VERSION_SIZE..]).read_u64::<BigEndian>()?;
+
+        Ok((currency_code_hash, version, index))
+    }
+}
+
+impl ValueCodec<EventByCurrencySchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 0)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
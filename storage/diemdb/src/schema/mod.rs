@@ -6,10 +6,15 @@
 //!
 //! All schemas are `pub(crate)` so not shown in rustdoc, refer to the source code to see details.
 
+pub(crate) mod archived_chunk;
+pub(crate) mod block_by_version;
 pub(crate) mod epoch_by_version;
 pub(crate) mod event;
 pub(crate) mod event_accumulator;
+pub(crate) mod event_by_account;
+pub(crate) mod event_by_currency;
 pub(crate) mod event_by_key;
+pub(crate) mod event_by_type;
 pub(crate) mod event_by_version;
 pub(crate) mod jellyfish_merkle_node;
 pub(crate) mod ledger_counters;
@@ -23,9 +28,14 @@ pub(crate) mod transaction_info;
 use anyhow::{ensure, Result};
 use schemadb::ColumnFamilyName;
 
+pub const ARCHIVED_CHUNK_CF_NAME: ColumnFamilyName = "archived_chunk";
+pub const BLOCK_BY_VERSION_CF_NAME: ColumnFamilyName = "block_by_version";
 pub const EPOCH_BY_VERSION_CF_NAME: ColumnFamilyName = "epoch_by_version";
 pub const EVENT_ACCUMULATOR_CF_NAME: ColumnFamilyName = "event_accumulator";
+pub const EVENT_BY_ACCOUNT_CF_NAME: ColumnFamilyName = "event_by_account";
+pub const EVENT_BY_CURRENCY_CF_NAME: ColumnFamilyName = "event_by_currency";
 pub const EVENT_BY_KEY_CF_NAME: ColumnFamilyName = "event_by_key";
+pub const EVENT_BY_TYPE_CF_NAME: ColumnFamilyName = "event_by_type";
 pub const EVENT_BY_VERSION_CF_NAME: ColumnFamilyName = "event_by_version";
 pub const EVENT_CF_NAME: ColumnFamilyName = "event";
 pub const JELLYFISH_MERKLE_NODE_CF_NAME: ColumnFamilyName = "jellyfish_merkle_node";
@@ -70,10 +80,15 @@ pub mod fuzzing {
     pub fn fuzz_decode(data: &[u8]) {
         #[allow(unused_must_use)]
         {
+            decode_key_value!(super::archived_chunk::ArchivedChunkSchema, data);
+            decode_key_value!(super::block_by_version::BlockByVersionSchema, data);
             decode_key_value!(super::epoch_by_version::EpochByVersionSchema, data);
             decode_key_value!(super::event::EventSchema, data);
             decode_key_value!(super::event_accumulator::EventAccumulatorSchema, data);
+            decode_key_value!(super::event_by_account::EventByAccountSchema, data);
+            decode_key_value!(super::event_by_currency::EventByCurrencySchema, data);
             decode_key_value!(super::event_by_key::EventByKeySchema, data);
+            decode_key_value!(super::event_by_type::EventByTypeSchema, data);
             decode_key_value!(super::event_by_version::EventByVersionSchema, data);
             decode_key_value!(
                 super::jellyfish_merkle_node::JellyfishMerkleNodeSchema,
@@ -0,0 +1,391 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `SnapshotHandler`, which exports the state at an epoch ending version
+//! into a self-contained, verifiable snapshot, and imports such a snapshot into an empty DB so a
+//! new node can start from that version without replaying the chain. It also serves and restores
+//! snapshots chunk by chunk, for nodes downloading them from their peers.
+//!
+//! A snapshot is a directory holding a `manifest.bcs` and a number of chunk files. The manifest
+//! carries the epoch ending ledger info, the transaction info at the snapshot version with its
+//! proof against the ledger info, and the frozen subtree roots of the transaction accumulator
+//! before that version. Each chunk file holds consecutive leaves of the state tree with a range
+//! proof against the state root hash in the transaction info.
+
+use crate::{
+    change_set::ChangeSet, ledger_store::LedgerStore,
+    schema::transaction_accumulator::TransactionAccumulatorSchema, state_store::StateStore,
+};
+use anyhow::{bail, ensure, Result};
+use diem_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
+use diem_jellyfish_merkle::{iterator::JellyfishMerkleIterator, restore::JellyfishMerkleRestore};
+use diem_logger::prelude::*;
+use diem_types::{
+    account_state_blob::AccountStateBlob,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{position::FrozenSubTreeIterator, SparseMerkleRangeProof, TransactionInfoWithProof},
+    transaction::Version,
+    waypoint::Waypoint,
+};
+use schemadb::DB;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path, sync::Arc};
+use storage_interface::{StateSnapshotChunk, StateSnapshotInfo, StateSnapshotReceiver};
+
+const MANIFEST_FILE_NAME: &str = "manifest.bcs";
+
+/// Describes a state snapshot, stored as `manifest.bcs` in the snapshot directory.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SnapshotManifest {
+    pub version: Version,
+    /// The epoch ending ledger info at `version`.
+    pub ledger_info: LedgerInfoWithSignatures,
+    /// The transaction info at `version`, proven against `ledger_info`.
+    pub txn_info_with_proof: TransactionInfoWithProof,
+    /// Roots of the frozen subtrees of the transaction accumulator with `version` leaves, from
+    /// left to right.
+    pub frozen_subtrees: Vec<HashValue>,
+    pub chunks: Vec<SnapshotChunkInfo>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SnapshotChunkInfo {
+    pub first_key: HashValue,
+    pub last_key: HashValue,
+    pub num_accounts: usize,
+    pub file_name: String,
+}
+
+/// The content of a chunk file.
+#[derive(Deserialize, Serialize)]
+struct SnapshotChunk {
+    accounts: Vec<(HashValue, AccountStateBlob)>,
+    proof: SparseMerkleRangeProof,
+}
+
+/// Exports and imports state snapshots.
+#[derive(Clone)]
+pub struct SnapshotHandler {
+    db: Arc<DB>,
+    ledger_store: Arc<LedgerStore>,
+    state_store: Arc<StateStore>,
+}
+
+impl SnapshotHandler {
+    pub(crate) fn new(
+        db: Arc<DB>,
+        ledger_store: Arc<LedgerStore>,
+        state_store: Arc<StateStore>,
+    ) -> Self {
+        Self {
+            db,
+            ledger_store,
+            state_store,
+        }
+    }
+
+    /// Writes a snapshot of the state at `version` into `dir`, with at most `chunk_size` accounts
+    /// in each chunk file. `version` must end an epoch, and defaults to the latest such version.
+    pub fn export(
+        &self,
+        version: Option<Version>,
+        dir: &Path,
+        chunk_size: usize,
+    ) -> Result<SnapshotManifest> {
+        ensure!(chunk_size > 0, "Chunk size must be positive.");
+        let ledger_info = match version {
+            Some(version) => self.ledger_store.get_epoch_ending_ledger_info(version)?,
+            None => {
+                let latest = self.ledger_store.get_latest_ledger_info()?;
+                if latest.ledger_info().ends_epoch() {
+                    latest
+                } else {
+                    let epoch = latest.ledger_info().epoch();
+                    ensure!(epoch > 0, "No epoch has ended yet.");
+                    self.ledger_store.get_latest_ledger_info_in_epoch(epoch - 1)?
+                }
+            }
+        };
+        let StateSnapshotInfo {
+            ledger_info,
+            txn_info_with_proof,
+            frozen_subtrees,
+        } = self.snapshot_info(ledger_info)?;
+        let version = ledger_info.ledger_info().version();
+
+        fs::create_dir_all(dir)?;
+        let mut chunks = Vec::new();
+        let mut accounts = Vec::with_capacity(chunk_size);
+        let iter =
+            JellyfishMerkleIterator::new(Arc::clone(&self.state_store), version, HashValue::zero())?;
+        for res in iter {
+            accounts.push(res?);
+            if accounts.len() == chunk_size {
+                chunks.push(self.write_chunk(dir, chunks.len(), accounts, version)?);
+                accounts = Vec::with_capacity(chunk_size);
+            }
+        }
+        if !accounts.is_empty() {
+            chunks.push(self.write_chunk(dir, chunks.len(), accounts, version)?);
+        }
+
+        let manifest = SnapshotManifest {
+            version,
+            ledger_info,
+            txn_info_with_proof,
+            frozen_subtrees,
+            chunks,
+        };
+        fs::write(dir.join(MANIFEST_FILE_NAME), bcs::to_bytes(&manifest)?)?;
+        info!(
+            version = version,
+            num_chunks = manifest.chunks.len(),
+            "State snapshot exported."
+        );
+        Ok(manifest)
+    }
+
+    fn write_chunk(
+        &self,
+        dir: &Path,
+        idx: usize,
+        accounts: Vec<(HashValue, AccountStateBlob)>,
+        version: Version,
+    ) -> Result<SnapshotChunkInfo> {
+        let first_key = accounts.first().expect("Chunk is not empty.").0;
+        let last_key = accounts.last().expect("Chunk is not empty.").0;
+        let proof = self
+            .state_store
+            .get_account_state_range_proof(last_key, version)?;
+        let info = SnapshotChunkInfo {
+            first_key,
+            last_key,
+            num_accounts: accounts.len(),
+            file_name: format!("chunk_{}.bcs", idx),
+        };
+        fs::write(
+            dir.join(&info.file_name),
+            bcs::to_bytes(&SnapshotChunk { accounts, proof })?,
+        )?;
+        Ok(info)
+    }
+
+    /// Verifies the snapshot in `dir` against `waypoint`, which must be the one of the snapshot's
+    /// ledger info, and seeds the DB, which must be empty, with it.
+    pub fn import(&self, dir: &Path, waypoint: Waypoint) -> Result<SnapshotManifest> {
+        ensure!(
+            self.ledger_store.get_latest_ledger_info_option().is_none()
+                && self
+                    .ledger_store
+                    .get_latest_transaction_info_option()?
+                    .is_none(),
+            "State snapshots can only be imported into an empty DB.",
+        );
+
+        let manifest: SnapshotManifest = bcs::from_bytes(&fs::read(dir.join(MANIFEST_FILE_NAME))?)?;
+        let version = manifest.version;
+        let info = StateSnapshotInfo {
+            ledger_info: manifest.ledger_info.clone(),
+            txn_info_with_proof: manifest.txn_info_with_proof.clone(),
+            frozen_subtrees: manifest.frozen_subtrees.clone(),
+        };
+        info.verify(&waypoint)?;
+        ensure!(
+            info.version() == version,
+            "Ledger info in the snapshot is not at version {}.",
+            version,
+        );
+
+        // The state tree, each chunk verified against the state root hash while being added.
+        let state_root_hash = info.state_root_hash();
+        if manifest.chunks.is_empty() {
+            ensure!(
+                state_root_hash == *SPARSE_MERKLE_PLACEHOLDER_HASH,
+                "No accounts in the snapshot, but the state root is not empty.",
+            );
+        } else {
+            let mut receiver = JellyfishMerkleRestore::new_overwrite(
+                Arc::clone(&self.state_store),
+                version,
+                state_root_hash,
+            )?;
+            for info in &manifest.chunks {
+                let chunk: SnapshotChunk = bcs::from_bytes(&fs::read(dir.join(&info.file_name))?)?;
+                ensure!(
+                    chunk.accounts.len() == info.num_accounts
+                        && chunk.accounts.first().map(|(key, _)| *key) == Some(info.first_key)
+                        && chunk.accounts.last().map(|(key, _)| *key) == Some(info.last_key),
+                    "Chunk file {} doesn't match the manifest.",
+                    info.file_name,
+                );
+                receiver.add_chunk(chunk.accounts, chunk.proof)?;
+            }
+            receiver.finish()?;
+        }
+        self.write_ledger(&info)?;
+
+        info!(version = version, "State snapshot imported.");
+        Ok(manifest)
+    }
+
+    /// Gets the ledger info, transaction info and accumulator of the snapshot at `version`,
+    /// which must end an epoch.
+    pub fn get_info(&self, version: Version) -> Result<StateSnapshotInfo> {
+        let ledger_info = self.ledger_store.get_epoch_ending_ledger_info(version)?;
+        ensure!(
+            ledger_info.ledger_info().version() == version,
+            "Version {} doesn't end an epoch.",
+            version,
+        );
+        self.snapshot_info(ledger_info)
+    }
+
+    fn snapshot_info(&self, ledger_info: LedgerInfoWithSignatures) -> Result<StateSnapshotInfo> {
+        let version = ledger_info.ledger_info().version();
+        let txn_info_with_proof = self
+            .ledger_store
+            .get_transaction_info_with_proof(version, version)?;
+        let frozen_subtrees = self.ledger_store.get_frozen_subtree_hashes(version)?;
+        Ok(StateSnapshotInfo {
+            ledger_info,
+            txn_info_with_proof,
+            frozen_subtrees,
+        })
+    }
+
+    /// Gets at most `limit` accounts of the state at `version`, starting with the first one after
+    /// `start_after`, or with the very first one if it's `None`.
+    pub fn get_chunk(
+        &self,
+        version: Version,
+        start_after: Option<HashValue>,
+        limit: usize,
+    ) -> Result<StateSnapshotChunk> {
+        ensure!(limit > 0, "Chunk limit must be positive.");
+        let mut iter = JellyfishMerkleIterator::new(
+            Arc::clone(&self.state_store),
+            version,
+            start_after.unwrap_or_else(HashValue::zero),
+        )?
+        .peekable();
+        // The iterator starts at the first key at least `start_after`.
+        if let (Some(start_after), Some(Ok((key, _)))) = (start_after, iter.peek()) {
+            if *key == start_after {
+                iter.next();
+            }
+        }
+        let accounts = iter.by_ref().take(limit).collect::<Result<Vec<_>>>()?;
+        let last_key = match accounts.last() {
+            Some((key, _)) => *key,
+            None => bail!(
+                "No accounts after {:?} at version {}.",
+                start_after,
+                version
+            ),
+        };
+        let proof = self
+            .state_store
+            .get_account_state_range_proof(last_key, version)?;
+        Ok(StateSnapshotChunk {
+            accounts,
+            proof,
+            is_last: iter.next().is_none(),
+        })
+    }
+
+    /// Gets a receiver restoring the state of the verified snapshot `info` into the DB, which must
+    /// be behind the version of the snapshot.
+    pub fn get_receiver(&self, info: &StateSnapshotInfo) -> Result<StateSnapshotRestore> {
+        self.ensure_behind(info.version())?;
+        Ok(StateSnapshotRestore {
+            restore: JellyfishMerkleRestore::new_overwrite(
+                Arc::clone(&self.state_store),
+                info.version(),
+                info.state_root_hash(),
+            )?,
+            expected_root_hash: info.state_root_hash(),
+        })
+    }
+
+    /// Makes the DB, which must be behind the version of the verified snapshot `info`, start from
+    /// it once its state is fully restored.
+    pub fn finalize(&self, info: &StateSnapshotInfo) -> Result<()> {
+        self.ensure_behind(info.version())?;
+        ensure!(
+            self.state_store.get_root_hash(info.version())? == info.state_root_hash(),
+            "State of the snapshot at version {} is not fully restored.",
+            info.version(),
+        );
+        self.write_ledger(info)?;
+        info!(version = info.version(), "State snapshot finalized.");
+        Ok(())
+    }
+
+    fn ensure_behind(&self, version: Version) -> Result<()> {
+        if let Some((latest_version, _)) = self.ledger_store.get_latest_transaction_info_option()? {
+            ensure!(
+                latest_version < version,
+                "DB is already at version {}, not behind the snapshot at version {}.",
+                latest_version,
+                version,
+            );
+        }
+        Ok(())
+    }
+
+    /// Writes the transaction accumulator, transaction info and ledger info of the snapshot,
+    /// only once the state is fully in place, since they make the DB look bootstrapped.
+    fn write_ledger(&self, info: &StateSnapshotInfo) -> Result<()> {
+        let version = info.version();
+        let ledger_info = info.ledger_info.ledger_info();
+        let mut cs = ChangeSet::new();
+        for (position, hash) in FrozenSubTreeIterator::new(version).zip(info.frozen_subtrees.iter())
+        {
+            cs.batch
+                .put::<TransactionAccumulatorSchema>(&position, hash)?;
+        }
+        self.db.write_schemas(cs.batch)?;
+        let mut cs = ChangeSet::new();
+        let root_hash = self.ledger_store.put_transaction_infos(
+            version,
+            &[info.txn_info_with_proof.transaction_info().clone()],
+            &mut cs,
+        )?;
+        ensure!(
+            root_hash == ledger_info.transaction_accumulator_hash(),
+            "Transaction accumulator root hash mismatch after import.",
+        );
+        self.ledger_store
+            .put_ledger_info(&info.ledger_info, &mut cs)?;
+        self.db.write_schemas(cs.batch)?;
+        self.ledger_store
+            .set_latest_ledger_info(info.ledger_info.clone());
+        Ok(())
+    }
+}
+
+/// Restores the state of a snapshot received chunk by chunk.
+pub struct StateSnapshotRestore {
+    restore: JellyfishMerkleRestore<AccountStateBlob>,
+    expected_root_hash: HashValue,
+}
+
+impl StateSnapshotReceiver for StateSnapshotRestore {
+    fn add_chunk(&mut self, chunk: StateSnapshotChunk) -> Result<()> {
+        self.restore.add_chunk(chunk.accounts, chunk.proof)
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        let root_hash = self.restore.finish_with_root_hash()?;
+        ensure!(
+            root_hash == self.expected_root_hash,
+            "Restored state has root hash {}, expected {}.",
+            root_hash,
+            self.expected_root_hash,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
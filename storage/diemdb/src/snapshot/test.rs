@@ -0,0 +1,162 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{test_helper::arb_blocks_to_commit, DiemDB};
+use diem_temppath::TempPath;
+use diem_types::transaction::TransactionToCommit;
+use proptest::prelude::*;
+use storage_interface::{DbReader, DbWriter};
+
+fn save_blocks(db: &DiemDB, blocks: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)]) {
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in blocks {
+        db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as Version;
+    }
+}
+
+fn all_accounts(db: &DiemDB, version: Version) -> Vec<(HashValue, AccountStateBlob)> {
+    db.get_backup_handler()
+        .get_account_iter(version)
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_export_import(input in arb_blocks_to_commit(), chunk_size in 1usize..4) {
+        let src_dir = TempPath::new();
+        let src_db = DiemDB::new_for_test(&src_dir);
+        save_blocks(&src_db, &input);
+
+        // The genesis ledger info always ends an epoch, so there's always one to export.
+        let expected_ledger_info = input
+            .iter()
+            .map(|(_, li)| li)
+            .filter(|li| li.ledger_info().ends_epoch())
+            .last()
+            .unwrap();
+        let snapshot_dir = TempPath::new();
+        let manifest = src_db
+            .get_snapshot_handler()
+            .export(None, snapshot_dir.path(), chunk_size)
+            .unwrap();
+        let version = manifest.version;
+        prop_assert_eq!(&manifest.ledger_info, expected_ledger_info);
+
+        let waypoint = Waypoint::new_epoch_boundary(manifest.ledger_info.ledger_info()).unwrap();
+        let dst_dir = TempPath::new();
+        let dst_db = DiemDB::new_for_test(&dst_dir);
+        dst_db
+            .get_snapshot_handler()
+            .import(snapshot_dir.path(), waypoint)
+            .unwrap();
+
+        let startup_info = dst_db.get_startup_info().unwrap().unwrap();
+        prop_assert_eq!(&startup_info.latest_ledger_info, expected_ledger_info);
+        prop_assert_eq!(startup_info.committed_tree_state.num_transactions, version + 1);
+        prop_assert_eq!(
+            startup_info.committed_tree_state.ledger_frozen_subtree_hashes,
+            src_db.ledger_store.get_frozen_subtree_hashes(version + 1).unwrap()
+        );
+        prop_assert_eq!(all_accounts(&dst_db, version), all_accounts(&src_db, version));
+
+        // Only into an empty DB.
+        prop_assert!(dst_db
+            .get_snapshot_handler()
+            .import(snapshot_dir.path(), waypoint)
+            .is_err());
+    }
+
+    #[test]
+    fn test_import_rejects_bad_snapshot(input in arb_blocks_to_commit()) {
+        let src_dir = TempPath::new();
+        let src_db = DiemDB::new_for_test(&src_dir);
+        save_blocks(&src_db, &input);
+        let snapshot_dir = TempPath::new();
+        let manifest = src_db
+            .get_snapshot_handler()
+            .export(None, snapshot_dir.path(), 1)
+            .unwrap();
+        let waypoint = Waypoint::new_epoch_boundary(manifest.ledger_info.ledger_info()).unwrap();
+
+        // Waypoint of another ledger info.
+        let genesis_waypoint = Waypoint::new_epoch_boundary(input[0].1.ledger_info()).unwrap();
+        if genesis_waypoint != waypoint {
+            let dst_dir = TempPath::new();
+            let dst_db = DiemDB::new_for_test(&dst_dir);
+            prop_assert!(dst_db
+                .get_snapshot_handler()
+                .import(snapshot_dir.path(), genesis_waypoint)
+                .is_err());
+        }
+
+        // Tampered account state.
+        let chunk_path = snapshot_dir.path().join(&manifest.chunks[0].file_name);
+        let mut chunk: SnapshotChunk = bcs::from_bytes(&fs::read(&chunk_path).unwrap()).unwrap();
+        chunk.accounts[0].1 = AccountStateBlob::from(b"tampered".to_vec());
+        fs::write(&chunk_path, bcs::to_bytes(&chunk).unwrap()).unwrap();
+        let dst_dir = TempPath::new();
+        let dst_db = DiemDB::new_for_test(&dst_dir);
+        prop_assert!(dst_db
+            .get_snapshot_handler()
+            .import(snapshot_dir.path(), waypoint)
+            .is_err());
+        // Nothing makes the DB look bootstrapped.
+        prop_assert!(dst_db.get_startup_info().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_restore_chunk_by_chunk(input in arb_blocks_to_commit(), limit in 1usize..4) {
+        let src_dir = TempPath::new();
+        let src_db = DiemDB::new_for_test(&src_dir);
+        save_blocks(&src_db, &input);
+        let ledger_info = input
+            .iter()
+            .map(|(_, li)| li)
+            .filter(|li| li.ledger_info().ends_epoch())
+            .last()
+            .unwrap();
+        let version = ledger_info.ledger_info().version();
+        let info = src_db.get_state_snapshot_info(version).unwrap();
+        let waypoint = Waypoint::new_epoch_boundary(ledger_info.ledger_info()).unwrap();
+        info.verify(&waypoint).unwrap();
+
+        // A DB with the first block only, if it's behind the snapshot.
+        let dst_dir = TempPath::new();
+        let dst_db = DiemDB::new_for_test(&dst_dir);
+        if (input[0].0.len() as Version) <= version {
+            save_blocks(&dst_db, &input[..1]);
+        }
+        let mut receiver = dst_db.get_state_snapshot_receiver(&info).unwrap();
+        let mut start_after = None;
+        loop {
+            let chunk = src_db
+                .get_state_snapshot_chunk(version, start_after, limit)
+                .unwrap();
+            prop_assert!(chunk.accounts.len() <= limit);
+            start_after = chunk.accounts.last().map(|(key, _)| *key);
+            let is_last = chunk.is_last;
+            receiver.add_chunk(chunk).unwrap();
+            if is_last {
+                break;
+            }
+        }
+        receiver.finish().unwrap();
+        dst_db.finalize_state_snapshot(&info).unwrap();
+
+        let startup_info = dst_db.get_startup_info().unwrap().unwrap();
+        prop_assert_eq!(&startup_info.latest_ledger_info, ledger_info);
+        prop_assert_eq!(startup_info.committed_tree_state.num_transactions, version + 1);
+        prop_assert_eq!(all_accounts(&dst_db, version), all_accounts(&src_db, version));
+
+        // Only into a DB behind the snapshot.
+        prop_assert!(dst_db.get_state_snapshot_receiver(&info).is_err());
+        prop_assert!(dst_db.finalize_state_snapshot(&info).is_err());
+    }
+}
@@ -0,0 +1,391 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `StateShards`, the RocksDB instances the nodes of the state Merkle tree
+//! are spread across when `RocksdbConfig::state_shards` is more than 1, so that writing them, and
+//! compacting them, which is most of the work of the DB, is not all done by a single instance.
+//!
+//! A node is kept in the shard picked by the first nibble of its path, so each subtree below the
+//! root is entirely in one shard. Roots, whose path is empty, stay in the main instance with the
+//! stale node index. A commit writes the shards first, in parallel, and then the main instance,
+//! so a version is only readable once all its nodes are in. Nodes written to the shards by a
+//! commit failing afterwards can't be reached from any root, and are overwritten if their version
+//! is committed again.
+//!
+//! The shards are in `state_shards/shard_<i>` under the DB root. `migrate` moves the state between
+//! layouts with different numbers of shards.
+
+use crate::{
+    change_set::ChangeSet,
+    gen_cf_options_for, gen_rocksdb_options,
+    schema::{jellyfish_merkle_node::JellyfishMerkleNodeSchema, JELLYFISH_MERKLE_NODE_CF_NAME},
+};
+use anyhow::{ensure, Result};
+use diem_config::config::{RocksdbConfig, MAX_STATE_SHARDS};
+use diem_jellyfish_merkle::node_type::NodeKey;
+use diem_logger::prelude::*;
+use diem_types::account_state_blob::AccountStateBlob;
+use rayon::prelude::*;
+use schemadb::{ColumnFamilyName, SchemaBatch, DB, DEFAULT_CF_NAME};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+type Node = diem_jellyfish_merkle::node_type::Node<AccountStateBlob>;
+
+const STATE_SHARDS_DIR_NAME: &str = "state_shards";
+/// Where `migrate` builds the new shards before swapping them in.
+const MIGRATING_DIR_NAME: &str = "state_shards.migrating";
+/// Where `migrate` moves the old shards before removing them.
+const RETIRED_DIR_NAME: &str = "state_shards.retired";
+/// Number of nodes moved at a time by `migrate`.
+const MIGRATION_BATCH_SIZE: usize = 10_000;
+
+/// The shards of the state Merkle tree. Empty if the state is not sharded, in which case all the
+/// nodes are in the main instance.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StateShards {
+    shards: Vec<Arc<DB>>,
+}
+
+impl StateShards {
+    /// Opens the shards of the DB at `db_root_path`, whose main instance is `db`. They must be
+    /// laid out as `rocksdb_config.state_shards` says, unless there's no state at all yet, in
+    /// which case they are created.
+    pub fn open(
+        db_root_path: &Path,
+        db: &DB,
+        readonly: bool,
+        rocksdb_config: &RocksdbConfig,
+    ) -> Result<Self> {
+        let num_shards = rocksdb_config.state_shards;
+        let num_shards_on_disk = num_shards_on_disk(db_root_path)?;
+        if num_shards_on_disk != num_shards {
+            ensure!(
+                num_shards_on_disk == 1 && !readonly && !has_state_nodes(db)?,
+                "The state is in {} RocksDB instance(s), but state_shards is {}. Migrate it first.",
+                num_shards_on_disk,
+                num_shards,
+            );
+        }
+        if num_shards == 1 {
+            return Ok(Self::default());
+        }
+
+        let rocksdb_opts = gen_rocksdb_options(rocksdb_config);
+        let shards = (0..num_shards)
+            .map(|shard| {
+                let path = shard_path(&db_root_path.join(STATE_SHARDS_DIR_NAME), shard);
+                Ok(Arc::new(if readonly {
+                    DB::open_readonly(
+                        path,
+                        "state_shard_ro",
+                        shard_column_families(),
+                        &rocksdb_opts,
+                    )?
+                } else {
+                    open_shard(path, rocksdb_config)?
+                }))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { shards })
+    }
+
+    /// Like `open`, for a DB opened with `DB::open_as_secondary`, with the secondary instance of
+    /// each shard under `secondary_path`.
+    pub fn open_as_secondary(
+        db_root_path: &Path,
+        secondary_path: &Path,
+        rocksdb_config: &RocksdbConfig,
+    ) -> Result<Self> {
+        let num_shards = rocksdb_config.state_shards;
+        let num_shards_on_disk = num_shards_on_disk(db_root_path)?;
+        ensure!(
+            num_shards_on_disk == num_shards,
+            "The state is in {} RocksDB instance(s), but state_shards is {}.",
+            num_shards_on_disk,
+            num_shards,
+        );
+        if num_shards == 1 {
+            return Ok(Self::default());
+        }
+
+        let rocksdb_opts = gen_rocksdb_options(rocksdb_config);
+        let shards = (0..num_shards)
+            .map(|shard| {
+                Ok(Arc::new(DB::open_as_secondary(
+                    shard_path(&db_root_path.join(STATE_SHARDS_DIR_NAME), shard),
+                    shard_path(&secondary_path.join(STATE_SHARDS_DIR_NAME), shard),
+                    "state_shard_sec",
+                    shard_column_families(),
+                    &rocksdb_opts,
+                )?))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { shards })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DB> {
+        self.shards.iter().map(Arc::as_ref)
+    }
+
+    /// The shard keeping the node at `node_key`, or None if it's kept in the main instance.
+    pub fn shard_of(&self, node_key: &NodeKey) -> Option<usize> {
+        let nibble_path = node_key.nibble_path();
+        if self.shards.is_empty() || nibble_path.num_nibbles() == 0 {
+            None
+        } else {
+            let first_nibble = (nibble_path.bytes()[0] >> 4) as usize;
+            Some(first_nibble * self.shards.len() / 16)
+        }
+    }
+
+    /// The instance keeping the node at `node_key`, `db` being the main one.
+    pub fn db_for<'a>(&'a self, db: &'a DB, node_key: &NodeKey) -> &'a DB {
+        match self.shard_of(node_key) {
+            Some(shard) => &self.shards[shard],
+            None => db,
+        }
+    }
+
+    /// Puts the node into the batch of the instance keeping it.
+    pub fn put_node(&self, node_key: &NodeKey, node: &Node, cs: &mut ChangeSet) -> Result<()> {
+        match self.shard_of(node_key) {
+            Some(shard) => cs
+                .shard_batch(shard)
+                .put::<JellyfishMerkleNodeSchema>(node_key, node),
+            None => cs.batch.put::<JellyfishMerkleNodeSchema>(node_key, node),
+        }
+    }
+
+    /// Deletes the node from the instance keeping it.
+    pub fn delete_node(&self, node_key: &NodeKey, cs: &mut ChangeSet) -> Result<()> {
+        match self.shard_of(node_key) {
+            Some(shard) => cs
+                .shard_batch(shard)
+                .delete::<JellyfishMerkleNodeSchema>(node_key),
+            None => cs.batch.delete::<JellyfishMerkleNodeSchema>(node_key),
+        }
+    }
+
+    /// Writes the batches of the shards in parallel. Must be done before writing the batch of the
+    /// main instance of the same change set.
+    pub fn write_shard_batches(&self, shard_batches: BTreeMap<usize, SchemaBatch>) -> Result<()> {
+        shard_batches
+            .into_iter()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .try_for_each(|(shard, batch)| self.shards[shard].write_schemas(batch))
+    }
+
+    /// Makes the secondary instances of the shards catch up with the primary ones.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.shards
+            .iter()
+            .try_for_each(|shard| shard.try_catch_up_with_primary())
+    }
+}
+
+/// Moves the nodes of the state Merkle tree of the DB at `db_root_path` into `num_shards` shards,
+/// or back into the main instance if `num_shards` is 1. `db` is the main instance and `shards`
+/// the current shards, which are closed and removed on success. The DB must not be in use by
+/// anything else.
+///
+/// The new shards are built aside and swapped in once complete, and nodes are deleted from the
+/// main instance last, so the migration can be run again if interrupted, after
+/// `recover_interrupted_migration`.
+pub(crate) fn migrate(
+    db_root_path: &Path,
+    db: &DB,
+    shards: StateShards,
+    num_shards: usize,
+    rocksdb_config: &RocksdbConfig,
+) -> Result<()> {
+    ensure!(
+        (1..=MAX_STATE_SHARDS).contains(&num_shards),
+        "Number of state shards must be between 1 and {}, got {}.",
+        MAX_STATE_SHARDS,
+        num_shards,
+    );
+    let shards_dir = db_root_path.join(STATE_SHARDS_DIR_NAME);
+    let migrating_dir = db_root_path.join(MIGRATING_DIR_NAME);
+    let retired_dir = db_root_path.join(RETIRED_DIR_NAME);
+    if migrating_dir.exists() {
+        fs::remove_dir_all(&migrating_dir)?;
+    }
+
+    if num_shards == 1 {
+        // Back into the main instance, before the shards are removed.
+        for shard in shards.iter() {
+            copy_nodes(shard, |_| Some(db), MIGRATION_BATCH_SIZE)?;
+        }
+        drop(shards);
+        if shards_dir.exists() {
+            fs::rename(&shards_dir, &retired_dir)?;
+        }
+    } else if shards.shards.len() != num_shards {
+        let new_shards = StateShards {
+            shards: (0..num_shards)
+                .map(|shard| {
+                    Ok(Arc::new(open_shard(
+                        shard_path(&migrating_dir, shard),
+                        rocksdb_config,
+                    )?))
+                })
+                .collect::<Result<_>>()?,
+        };
+        for source in std::iter::once(db).chain(shards.iter()) {
+            copy_nodes(
+                source,
+                |node_key| {
+                    new_shards
+                        .shard_of(node_key)
+                        .map(|shard| new_shards.shards[shard].as_ref())
+                },
+                MIGRATION_BATCH_SIZE,
+            )?;
+        }
+        drop(new_shards);
+        drop(shards);
+        if shards_dir.exists() {
+            fs::rename(&shards_dir, &retired_dir)?;
+        }
+        fs::rename(&migrating_dir, &shards_dir)?;
+    }
+    if retired_dir.exists() {
+        fs::remove_dir_all(&retired_dir)?;
+    }
+
+    if num_shards > 1 {
+        // What's left in the main instance below the roots is now in the shards.
+        let mut batch = SchemaBatch::new();
+        let mut batch_size = 0;
+        let mut iter = db.iter::<JellyfishMerkleNodeSchema>(Default::default())?;
+        iter.seek_to_first();
+        for res in iter {
+            let (node_key, _node) = res?;
+            if node_key.nibble_path().num_nibbles() > 0 {
+                batch.delete::<JellyfishMerkleNodeSchema>(&node_key)?;
+                batch_size += 1;
+                if batch_size == MIGRATION_BATCH_SIZE {
+                    db.write_schemas(std::mem::replace(&mut batch, SchemaBatch::new()))?;
+                    batch_size = 0;
+                }
+            }
+        }
+        db.write_schemas(batch)?;
+    }
+
+    info!(num_shards = num_shards, "State shards migrated.");
+    Ok(())
+}
+
+/// Puts back the shards a migration interrupted while swapping them moved aside, so that the DB
+/// opens in the layout it had before.
+pub(crate) fn recover_interrupted_migration(db_root_path: &Path) -> Result<()> {
+    let shards_dir = db_root_path.join(STATE_SHARDS_DIR_NAME);
+    let retired_dir = db_root_path.join(RETIRED_DIR_NAME);
+    if retired_dir.exists() {
+        if shards_dir.exists() {
+            // The new shards are in place, the old ones were being removed.
+            fs::remove_dir_all(&retired_dir)?;
+        } else {
+            fs::rename(&retired_dir, &shards_dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copies the nodes in `source` to the instances `target_of` picks, skipping those it picks none
+/// for, `batch_size` nodes at a time.
+fn copy_nodes<'a>(
+    source: &DB,
+    target_of: impl Fn(&NodeKey) -> Option<&'a DB>,
+    batch_size: usize,
+) -> Result<()> {
+    let mut batches: Vec<(&DB, SchemaBatch)> = Vec::new();
+    let mut num_nodes = 0;
+    let mut iter = source.iter::<JellyfishMerkleNodeSchema>(Default::default())?;
+    iter.seek_to_first();
+    for res in iter {
+        let (node_key, node) = res?;
+        if let Some(target) = target_of(&node_key) {
+            let idx = match batches.iter().position(|(db, _)| std::ptr::eq(*db, target)) {
+                Some(idx) => idx,
+                None => {
+                    batches.push((target, SchemaBatch::new()));
+                    batches.len() - 1
+                }
+            };
+            batches[idx]
+                .1
+                .put::<JellyfishMerkleNodeSchema>(&node_key, &node)?;
+            num_nodes += 1;
+            if num_nodes == batch_size {
+                write_batches(std::mem::take(&mut batches))?;
+                num_nodes = 0;
+            }
+        }
+    }
+    write_batches(batches)
+}
+
+fn write_batches(batches: Vec<(&DB, SchemaBatch)>) -> Result<()> {
+    batches
+        .into_par_iter()
+        .try_for_each(|(db, batch)| db.write_schemas(batch))
+}
+
+/// Number of instances the state is in: 1 if it's not sharded, the number of shards otherwise.
+pub(crate) fn num_shards_on_disk(db_root_path: &Path) -> Result<usize> {
+    let shards_dir = db_root_path.join(STATE_SHARDS_DIR_NAME);
+    if !shards_dir.exists() {
+        return Ok(1);
+    }
+    let num_shards = fs::read_dir(&shards_dir)?.count();
+    for shard in 0..num_shards {
+        ensure!(
+            shard_path(&shards_dir, shard).is_dir(),
+            "{} is not a complete set of state shards.",
+            shards_dir.display(),
+        );
+    }
+    Ok(num_shards)
+}
+
+/// Whether there's any node of the state Merkle tree in `db`.
+fn has_state_nodes(db: &DB) -> Result<bool> {
+    let mut iter = db.iter::<JellyfishMerkleNodeSchema>(Default::default())?;
+    iter.seek_to_first();
+    Ok(iter.next().transpose()?.is_some())
+}
+
+fn shard_path(shards_dir: &Path, shard: usize) -> PathBuf {
+    shards_dir.join(format!("shard_{}", shard))
+}
+
+fn shard_column_families() -> Vec<ColumnFamilyName> {
+    vec![DEFAULT_CF_NAME, JELLYFISH_MERKLE_NODE_CF_NAME]
+}
+
+fn open_shard(path: PathBuf, rocksdb_config: &RocksdbConfig) -> Result<DB> {
+    let mut rocksdb_opts = gen_rocksdb_options(rocksdb_config);
+    rocksdb_opts.create_if_missing(true);
+    rocksdb_opts.create_missing_column_families(true);
+    DB::open_with_cf_options(
+        path,
+        "state_shard",
+        gen_cf_options_for(rocksdb_config, shard_column_families())?,
+        &rocksdb_opts,
+    )
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,122 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{test_helper::arb_blocks_to_commit, DiemDB};
+use diem_crypto::HashValue;
+use diem_temppath::TempPath;
+use diem_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{TransactionToCommit, Version},
+};
+use proptest::prelude::*;
+use storage_interface::{DbReader, DbWriter};
+
+fn open_db(path: &Path, state_shards: usize) -> Result<DiemDB> {
+    DiemDB::open(
+        path,
+        false, /* readonly */
+        None,  /* pruner */
+        RocksdbConfig {
+            state_shards,
+            ..Default::default()
+        },
+    )
+}
+
+fn save_blocks(db: &DiemDB, blocks: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)]) {
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in blocks {
+        db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as Version;
+    }
+}
+
+fn all_accounts(db: &DiemDB) -> Vec<(HashValue, AccountStateBlob)> {
+    let (version, _root_hash) = db.get_latest_state_root().unwrap();
+    db.get_backup_handler()
+        .get_account_iter(version)
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap()
+}
+
+fn num_nodes(db: &DB) -> usize {
+    let mut iter = db
+        .iter::<JellyfishMerkleNodeSchema>(Default::default())
+        .unwrap();
+    iter.seek_to_first();
+    iter.count()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_sharded_state(input in arb_blocks_to_commit()) {
+        let unsharded_dir = TempPath::new();
+        let unsharded_db = open_db(unsharded_dir.path(), 1).unwrap();
+        save_blocks(&unsharded_db, &input);
+
+        let tmp_dir = TempPath::new();
+        let db = open_db(tmp_dir.path(), 4).unwrap();
+        save_blocks(&db, &input);
+        prop_assert_eq!(
+            db.get_latest_state_root().unwrap(),
+            unsharded_db.get_latest_state_root().unwrap()
+        );
+        let expected_accounts = all_accounts(&unsharded_db);
+        prop_assert_eq!(&all_accounts(&db), &expected_accounts);
+        // Only the roots are left in the main instance.
+        let num_nodes_in_shards = |db: &DiemDB| {
+            db.state_store.shards().iter().map(num_nodes).sum::<usize>()
+        };
+        prop_assert_eq!(
+            num_nodes(&db.db),
+            num_nodes(&unsharded_db.db) - num_nodes_in_shards(&db)
+        );
+        drop(db);
+
+        // The layout on disk must match the config.
+        prop_assert!(open_db(tmp_dir.path(), 1).is_err());
+        prop_assert!(open_db(tmp_dir.path(), 2).is_err());
+
+        for num_shards in [2, 1, 3].iter().copied() {
+            DiemDB::migrate_state_shards(
+                tmp_dir.path(),
+                num_shards,
+                RocksdbConfig::default(),
+            )
+            .unwrap();
+            prop_assert_eq!(num_shards_on_disk(tmp_dir.path()).unwrap(), num_shards);
+            let db = open_db(tmp_dir.path(), num_shards).unwrap();
+            prop_assert_eq!(&all_accounts(&db), &expected_accounts);
+            prop_assert_eq!(
+                num_nodes(&db.db) + num_nodes_in_shards(&db),
+                num_nodes(&unsharded_db.db)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_shards_created_on_empty_db() {
+    let tmp_dir = TempPath::new();
+    drop(open_db(tmp_dir.path(), 1).unwrap());
+    // No state yet, so the layout can still change.
+    let db = open_db(tmp_dir.path(), 4).unwrap();
+    assert_eq!(db.state_store.shards().iter().count(), 4);
+    drop(db);
+    assert_eq!(num_shards_on_disk(tmp_dir.path()).unwrap(), 4);
+    assert!(open_db(tmp_dir.path(), 1).is_err());
+
+    // A migration interrupted after moving the old shards aside opens as before.
+    fs::rename(
+        tmp_dir.path().join(STATE_SHARDS_DIR_NAME),
+        tmp_dir.path().join(RETIRED_DIR_NAME),
+    )
+    .unwrap();
+    recover_interrupted_migration(tmp_dir.path()).unwrap();
+    assert!(open_db(tmp_dir.path(), 4).is_ok());
+}
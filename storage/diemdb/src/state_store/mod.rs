@@ -12,6 +12,7 @@ use crate::{
     schema::{
         jellyfish_merkle_node::JellyfishMerkleNodeSchema, stale_node_index::StaleNodeIndexSchema,
     },
+    state_shards::StateShards,
 };
 use anyhow::Result;
 use diem_crypto::HashValue;
@@ -24,7 +25,7 @@ use diem_types::{
     proof::{SparseMerkleProof, SparseMerkleRangeProof},
     transaction::Version,
 };
-use schemadb::{SchemaBatch, DB};
+use schemadb::DB;
 use std::{collections::HashMap, sync::Arc};
 
 type LeafNode = diem_jellyfish_merkle::node_type::LeafNode<AccountStateBlob>;
@@ -34,11 +35,25 @@ type NodeBatch = diem_jellyfish_merkle::NodeBatch<AccountStateBlob>;
 #[derive(Debug)]
 pub(crate) struct StateStore {
     db: Arc<DB>,
+    shards: StateShards,
 }
 
 impl StateStore {
     pub fn new(db: Arc<DB>) -> Self {
-        Self { db }
+        Self {
+            db,
+            shards: StateShards::default(),
+        }
+    }
+
+    /// Keeps the nodes below the root in `shards`.
+    pub fn with_shards(mut self, shards: StateShards) -> Self {
+        self.shards = shards;
+        self
+    }
+
+    pub fn shards(&self) -> &StateShards {
+        &self.shards
     }
 
     /// Get the account state blob given account address and root hash of state Merkle tree
@@ -97,7 +112,7 @@ impl StateStore {
                 counter_bumps.bump(LedgerCounter::StaleStateNodes, stats.stale_nodes);
                 counter_bumps.bump(LedgerCounter::StaleStateLeaves, stats.stale_leaves);
             });
-        add_node_batch(&mut cs.batch, &tree_update_batch.node_batch)?;
+        self.add_node_batch(cs, &tree_update_batch.node_batch)?;
 
         tree_update_batch
             .stale_node_index_batch
@@ -121,18 +136,18 @@ impl StateStore {
     pub fn get_rightmost_leaf_naive(&self) -> Result<Option<(NodeKey, LeafNode)>> {
         let mut ret = None;
 
-        let mut iter = self
-            .db
-            .iter::<JellyfishMerkleNodeSchema>(Default::default())?;
-        iter.seek_to_first();
+        for db in self.dbs() {
+            let mut iter = db.iter::<JellyfishMerkleNodeSchema>(Default::default())?;
+            iter.seek_to_first();
 
-        while let Some((node_key, node)) = iter.next().transpose()? {
-            if let Node::Leaf(leaf_node) = node {
-                match ret {
-                    None => ret = Some((node_key, leaf_node)),
-                    Some(ref other) => {
-                        if leaf_node.account_key() > other.1.account_key() {
-                            ret = Some((node_key, leaf_node));
+            while let Some((node_key, node)) = iter.next().transpose()? {
+                if let Node::Leaf(leaf_node) = node {
+                    match ret {
+                        None => ret = Some((node_key, leaf_node)),
+                        Some(ref other) => {
+                            if leaf_node.account_key() > other.1.account_key() {
+                                ret = Some((node_key, leaf_node));
+                            }
                         }
                     }
                 }
@@ -141,19 +156,26 @@ impl StateStore {
 
         Ok(ret)
     }
-}
 
-impl TreeReader<AccountStateBlob> for StateStore {
-    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>> {
-        self.db.get::<JellyfishMerkleNodeSchema>(node_key)
+    /// The main instance followed by the shards, if any.
+    fn dbs(&self) -> impl Iterator<Item = &DB> {
+        std::iter::once(self.db.as_ref()).chain(self.shards.iter())
     }
 
-    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>> {
+    fn add_node_batch(&self, cs: &mut ChangeSet, node_batch: &NodeBatch) -> Result<()> {
+        node_batch
+            .iter()
+            .map(|(node_key, node)| self.shards.put_node(node_key, node, cs))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(())
+    }
+
+    /// Like `TreeReader::get_rightmost_leaf`, among the nodes in `db`. Since each subtree below
+    /// the root is entirely in one instance, the reasoning holds for each instance on its own.
+    fn get_rightmost_leaf_in(db: &DB) -> Result<Option<(NodeKey, LeafNode)>> {
         // Since everything has the same version during restore, we seek to the first node and get
         // its version.
-        let mut iter = self
-            .db
-            .iter::<JellyfishMerkleNodeSchema>(Default::default())?;
+        let mut iter = db.iter::<JellyfishMerkleNodeSchema>(Default::default())?;
         iter.seek_to_first();
         let version = match iter.next().transpose()? {
             Some((node_key, _node)) => node_key.version(),
@@ -180,9 +202,7 @@ impl TreeReader<AccountStateBlob> for StateStore {
         let mut ret = None;
 
         for num_nibbles in 1..=ROOT_NIBBLE_HEIGHT + 1 {
-            let mut iter = self
-                .db
-                .iter::<JellyfishMerkleNodeSchema>(Default::default())?;
+            let mut iter = db.iter::<JellyfishMerkleNodeSchema>(Default::default())?;
             // nibble_path is always non-empty except for the root, so if we use an empty nibble
             // path as the seek key, the iterator will end up pointing to the end of the previous
             // range.
@@ -210,18 +230,33 @@ impl TreeReader<AccountStateBlob> for StateStore {
     }
 }
 
-impl TreeWriter<AccountStateBlob> for StateStore {
-    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<()> {
-        let mut batch = SchemaBatch::new();
-        add_node_batch(&mut batch, node_batch)?;
-        self.db.write_schemas(batch)
+impl TreeReader<AccountStateBlob> for StateStore {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>> {
+        self.shards
+            .db_for(&self.db, node_key)
+            .get::<JellyfishMerkleNodeSchema>(node_key)
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>> {
+        let mut ret: Option<(NodeKey, LeafNode)> = None;
+        for db in self.dbs() {
+            if let Some((node_key, leaf_node)) = Self::get_rightmost_leaf_in(db)? {
+                if ret.as_ref().map_or(true, |other| {
+                    leaf_node.account_key() > other.1.account_key()
+                }) {
+                    ret = Some((node_key, leaf_node));
+                }
+            }
+        }
+        Ok(ret)
     }
 }
 
-fn add_node_batch(batch: &mut SchemaBatch, node_batch: &NodeBatch) -> Result<()> {
-    node_batch
-        .iter()
-        .map(|(node_key, node)| batch.put::<JellyfishMerkleNodeSchema>(node_key, node))
-        .collect::<Result<Vec<_>>>()?;
-    Ok(())
+impl TreeWriter<AccountStateBlob> for StateStore {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<()> {
+        let mut cs = ChangeSet::new();
+        self.add_node_batch(&mut cs, node_batch)?;
+        self.shards.write_shard_batches(cs.shard_batches)?;
+        self.db.write_schemas(cs.batch)
+    }
 }
@@ -55,6 +55,7 @@ fn prune_stale_indices(
 ) {
     pruner::prune_state(
         Arc::clone(&store.db),
+        store.shards(),
         least_readable_version,
         target_least_readable_version,
         limit,
@@ -4,9 +4,13 @@
 //! This file defines transaction store APIs that are related to committed signed transactions.
 
 use crate::{
+    archive::ArchiveStore,
     change_set::ChangeSet,
     errors::DiemDbError,
-    schema::{transaction::TransactionSchema, transaction_by_account::TransactionByAccountSchema},
+    schema::{
+        block_by_version::BlockByVersionSchema, transaction::TransactionSchema,
+        transaction_by_account::TransactionByAccountSchema,
+    },
 };
 use anyhow::{ensure, format_err, Result};
 use diem_types::{
@@ -14,17 +18,25 @@ use diem_types::{
     block_metadata::BlockMetadata,
     transaction::{Transaction, Version},
 };
-use schemadb::{SchemaIterator, DB};
+use schemadb::{SchemaBatch, SchemaIterator, DB};
 use std::sync::Arc;
+use storage_interface::Order;
 
 #[derive(Debug)]
 pub(crate) struct TransactionStore {
     db: Arc<DB>,
+    /// Where transactions moved out of the DB are read back from.
+    archive: Option<Arc<ArchiveStore>>,
 }
 
 impl TransactionStore {
     pub fn new(db: Arc<DB>) -> Self {
-        Self { db }
+        Self { db, archive: None }
+    }
+
+    pub fn with_archive(mut self, archive: Arc<ArchiveStore>) -> Self {
+        self.archive = Some(archive);
+        self
     }
 
     /// Gets the version of a transaction by the sender `address` and `sequence_number`.
@@ -46,11 +58,49 @@ impl TransactionStore {
         Ok(None)
     }
 
+    /// Gets an iterator that yields the sequence numbers and versions of the transactions sent by
+    /// `address` and committed at or before `ledger_version`, from `start_seq_num` on in `order`.
+    /// In descending order, a `start_seq_num` beyond the latest one starts from the latest one.
+    pub fn get_account_transaction_version_iter(
+        &self,
+        address: AccountAddress,
+        start_seq_num: u64,
+        order: Order,
+        ledger_version: Version,
+    ) -> Result<AccountTransactionVersionIter> {
+        let inner = match order {
+            Order::Ascending => {
+                let mut iter = self.db.iter::<TransactionByAccountSchema>(Default::default())?;
+                iter.seek(&(address, start_seq_num))?;
+                iter
+            }
+            Order::Descending => {
+                let mut iter = self
+                    .db
+                    .rev_iter::<TransactionByAccountSchema>(Default::default())?;
+                iter.seek_for_prev(&(address, start_seq_num))?;
+                iter
+            }
+        };
+        Ok(AccountTransactionVersionIter {
+            inner,
+            address,
+            order,
+            ledger_version,
+        })
+    }
+
     /// Get signed transaction given `version`
     pub fn get_transaction(&self, version: Version) -> Result<Transaction> {
-        self.db
-            .get::<TransactionSchema>(&version)?
-            .ok_or_else(|| DiemDbError::NotFound(format!("Txn {}", version)).into())
+        if let Some(txn) = self.db.get::<TransactionSchema>(&version)? {
+            return Ok(txn);
+        }
+        if let Some(archive) = &self.archive {
+            if let Some(txn) = archive.get_transaction(version)? {
+                return Ok(txn);
+            }
+        }
+        Err(DiemDbError::NotFound(format!("Txn {}", version)).into())
     }
 
     /// Gets an iterator that yields `num_transactions` transactions starting from `start_version`.
@@ -63,6 +113,7 @@ impl TransactionStore {
         iter.seek(&start_version)?;
         Ok(TransactionIter {
             inner: iter,
+            archive: self.archive.as_deref(),
             expected_next_version: start_version,
             end_version: start_version
                 .checked_add(num_transactions as u64)
@@ -75,6 +126,17 @@ impl TransactionStore {
     /// Returns None if there's no such transaction at or preceding `version` (it's likely the genesis
     /// version 0).
     pub fn get_block_metadata(&self, version: Version) -> Result<Option<(Version, BlockMetadata)>> {
+        // Every block metadata transaction committed since `BlockByVersionSchema` was introduced
+        // is in it, so an entry found at or before `version` is the closest block metadata
+        // transaction. Otherwise the block started before that, and we search the transactions.
+        let mut iter = self
+            .db
+            .iter::<BlockByVersionSchema>(Default::default())?;
+        iter.seek_for_prev(&version)?;
+        if let Some(res) = iter.next() {
+            return res.map(Some);
+        }
+
         // Maximum TPS from benchmark is around 1000.
         const MAX_VERSIONS_TO_SEARCH: usize = 1000 * 3;
 
@@ -102,20 +164,49 @@ impl TransactionStore {
         transaction: &Transaction,
         cs: &mut ChangeSet,
     ) -> Result<()> {
-        if let Transaction::UserTransaction(txn) = transaction {
-            cs.batch.put::<TransactionByAccountSchema>(
+        match transaction {
+            Transaction::UserTransaction(txn) => cs.batch.put::<TransactionByAccountSchema>(
                 &(txn.sender(), txn.sequence_number()),
                 &version,
-            )?;
+            )?,
+            Transaction::BlockMetadata(block_meta) => {
+                cs.batch.put::<BlockByVersionSchema>(&version, block_meta)?
+            }
+            _ => (),
         }
         cs.batch.put::<TransactionSchema>(&version, &transaction)?;
 
         Ok(())
     }
+
+    /// Deletes the transactions with versions in `[begin, end)` and their index entries.
+    pub(crate) fn prune_transactions(
+        &self,
+        begin: Version,
+        end: Version,
+        batch: &mut SchemaBatch,
+    ) -> Result<()> {
+        for version in begin..end {
+            match self.db.get::<TransactionSchema>(&version)? {
+                Some(Transaction::UserTransaction(txn)) => batch
+                    .delete::<TransactionByAccountSchema>(&(txn.sender(), txn.sequence_number()))?,
+                Some(Transaction::BlockMetadata(_)) => {
+                    batch.delete::<BlockByVersionSchema>(&version)?
+                }
+                Some(_) => (),
+                // Not in the DB, e.g. restored from a state snapshot after it.
+                None => continue,
+            }
+            batch.delete::<TransactionSchema>(&version)?;
+        }
+
+        Ok(())
+    }
 }
 
 pub struct TransactionIter<'a> {
     inner: SchemaIterator<'a, TransactionSchema>,
+    archive: Option<&'a ArchiveStore>,
     expected_next_version: Version,
     end_version: Version,
 }
@@ -126,6 +217,14 @@ impl<'a> TransactionIter<'a> {
             return Ok(None);
         }
 
+        // Archived versions are all before the ones left in the DB, which `inner` starts from.
+        if let Some(archive) = self.archive {
+            if let Some(txn) = archive.get_transaction(self.expected_next_version)? {
+                self.expected_next_version += 1;
+                return Ok(Some(txn));
+            }
+        }
+
         let ret = match self.inner.next().transpose()? {
             Some((version, transaction)) => {
                 ensure!(
@@ -150,5 +249,39 @@ impl<'a> Iterator for TransactionIter<'a> {
     }
 }
 
+pub struct AccountTransactionVersionIter<'a> {
+    inner: SchemaIterator<'a, TransactionByAccountSchema>,
+    address: AccountAddress,
+    order: Order,
+    ledger_version: Version,
+}
+
+impl<'a> AccountTransactionVersionIter<'a> {
+    fn next_impl(&mut self) -> Result<Option<(u64, Version)>> {
+        while let Some(((address, seq_num), version)) = self.inner.next().transpose()? {
+            if address != self.address {
+                return Ok(None);
+            }
+            if version <= self.ledger_version {
+                return Ok(Some((seq_num, version)));
+            }
+            // Versions grow with sequence numbers: the transactions after this one are not
+            // committed at `ledger_version` either, the ones before it may be.
+            if self.order == Order::Ascending {
+                return Ok(None);
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for AccountTransactionVersionIter<'a> {
+    type Item = Result<(u64, Version)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_impl().transpose()
+    }
+}
+
 #[cfg(test)]
 mod test;
@@ -103,6 +103,73 @@ proptest! {
         prop_assert!(store.get_transaction_iter(10, usize::max_value()).is_err());
     }
 
+    #[test]
+    fn test_get_account_transaction_version_iter(
+        universe in any_with::<AccountInfoUniverse>(3),
+        gens in vec(
+            (any::<Index>(), any::<SignatureCheckedTransactionGen>()),
+            1..10
+        ),
+        ledger_version in 0..10u64,
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+        let store = &db.transaction_store;
+        let txns = init_store(universe, gens, &store);
+
+        let mut senders: Vec<_> = txns
+            .iter()
+            .map(|txn| txn.as_signed_user_txn().unwrap().sender())
+            .collect();
+        senders.sort();
+        senders.dedup();
+        for sender in senders {
+            let mut expected: Vec<_> = txns
+                .iter()
+                .enumerate()
+                .filter(|(ver, _)| *ver as Version <= ledger_version)
+                .map(|(ver, txn)| (txn.as_signed_user_txn().unwrap(), ver as Version))
+                .filter(|(txn, _)| txn.sender() == sender)
+                .map(|(txn, ver)| (txn.sequence_number(), ver))
+                .collect();
+            expected.sort_unstable();
+
+            let actual = store
+                .get_account_transaction_version_iter(sender, 0, Order::Ascending, ledger_version)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            prop_assert_eq!(&actual, &expected);
+
+            expected.reverse();
+            let actual = store
+                .get_account_transaction_version_iter(
+                    sender,
+                    u64::max_value(),
+                    Order::Descending,
+                    ledger_version,
+                )
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            prop_assert_eq!(&actual, &expected);
+
+            if let Some(&(seq_num, _)) = expected.get(1) {
+                let actual = store
+                    .get_account_transaction_version_iter(
+                        sender,
+                        seq_num,
+                        Order::Descending,
+                        ledger_version,
+                    )
+                    .unwrap()
+                    .collect::<Result<Vec<_>>>()
+                    .unwrap();
+                prop_assert_eq!(&actual, &expected[1..].to_vec());
+            }
+        }
+    }
+
     #[test]
     fn test_get_block_metadata(
         txns in vec(
@@ -111,7 +178,8 @@ proptest! {
                 any::<SignedTransaction>().prop_map(Transaction::UserTransaction),
             ],
             1..100,
-        )
+        ),
+        num_unindexed in any::<Index>(),
     ) {
         let tmp_dir = TempPath::new();
         let db = DiemDB::new_for_test(&tmp_dir);
@@ -125,6 +193,14 @@ proptest! {
         }
         store.db.write_schemas(cs.batch).unwrap();
 
+        // Blocks committed before the index was introduced are found by searching transactions.
+        let num_unindexed = num_unindexed.index(txns.len() + 1) as Version;
+        let mut batch = SchemaBatch::new();
+        for ver in 0..num_unindexed {
+            batch.delete::<BlockByVersionSchema>(&ver).unwrap();
+        }
+        store.db.write_schemas(batch).unwrap();
+
         let mut timestamp = 0;
         let mut block_meta_ver = 0;
         let mut seen_any_block = false;
@@ -176,3 +252,49 @@ fn init_store(
 
     txns
 }
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_prune_transactions(
+        universe in any_with::<AccountInfoUniverse>(3),
+        gens in vec(
+            (any::<Index>(), any::<SignatureCheckedTransactionGen>()),
+            1..10
+        ),
+        num_to_prune in any::<Index>(),
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+        let store = &db.transaction_store;
+        let txns = init_store(universe, gens, &store);
+        let ledger_version = txns.len() as Version - 1;
+        let end = num_to_prune.index(txns.len() + 1) as Version;
+
+        let mut cs = ChangeSet::new();
+        store.prune_transactions(0, end, &mut cs.batch).unwrap();
+        store.db.write_schemas(cs.batch).unwrap();
+
+        for (ver, txn) in txns.iter().enumerate() {
+            let ver = ver as Version;
+            let user_txn = txn
+                .as_signed_user_txn()
+                .expect("All should be user transactions here.");
+            let by_account = store
+                .lookup_transaction_by_account(
+                    user_txn.sender(),
+                    user_txn.sequence_number(),
+                    ledger_version,
+                )
+                .unwrap();
+            if ver < end {
+                prop_assert!(store.get_transaction(ver).is_err());
+                prop_assert_eq!(by_account, None);
+            } else {
+                prop_assert_eq!(store.get_transaction(ver).unwrap(), txn.clone());
+                prop_assert_eq!(by_account, Some(ver));
+            }
+        }
+    }
+}
@@ -0,0 +1,452 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `DbVerifier` which checks the integrity of the data in DiemDB by
+//! recomputing the hashes that bind it together: transaction and event hashes against the
+//! transaction infos, the transaction accumulator against the transaction infos, the state root
+//! hashes against the Jellyfish Merkle tree, and the ledger infos against the accumulator and the
+//! validator sets of their epochs.
+//!
+//! It only reads from the DB, so it can run on a secondary instance while the node is running.
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    event_store::EventStore, ledger_store::LedgerStore, state_store::StateStore,
+    transaction_store::TransactionStore,
+};
+use anyhow::{ensure, Result};
+use diem_crypto::hash::{CryptoHash, EventAccumulatorHasher, TransactionAccumulatorHasher};
+use diem_jellyfish_merkle::JellyfishMerkleTree;
+use diem_logger::prelude::*;
+use diem_types::{
+    epoch_change::Verifier, epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures,
+    proof::accumulator::InMemoryAccumulator, transaction::Version,
+};
+use std::{fmt, sync::Arc};
+
+const PROGRESS_LOG_INTERVAL: Version = 100_000;
+
+/// The kinds of inconsistency `DbVerifier` detects.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CorruptionKind {
+    /// Data expected to exist at the version can't be read.
+    MissingData,
+    /// The hash of the stored transaction doesn't match the transaction info.
+    TransactionHash,
+    /// The root hash of the stored events doesn't match the transaction info.
+    EventRootHash,
+    /// The stored transaction accumulator doesn't match the one recomputed from the transaction
+    /// infos.
+    TransactionAccumulator,
+    /// The root hash of the state tree doesn't match the transaction info.
+    StateRootHash,
+    /// A node of the state tree doesn't hash to what's recorded for it in its parent.
+    StateTree,
+    /// A ledger info doesn't match the transaction accumulator or isn't signed by the validator
+    /// set of its epoch.
+    LedgerInfo,
+}
+
+/// An inconsistency found in a contiguous range of versions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Corruption {
+    pub kind: CorruptionKind,
+    pub first_version: Version,
+    pub last_version: Version,
+    /// Details of the first occurrence in the range.
+    pub detail: String,
+}
+
+impl fmt::Display for Corruption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} at versions [{}, {}]: {}",
+            self.kind, self.first_version, self.last_version, self.detail,
+        )
+    }
+}
+
+/// The result of a `DbVerifier::verify()` run.
+#[derive(Clone, Debug)]
+pub struct VerifyReport {
+    pub start_version: Version,
+    pub end_version: Version,
+    pub corruptions: Vec<Corruption>,
+}
+
+impl VerifyReport {
+    fn new(start_version: Version, end_version: Version) -> Self {
+        Self {
+            start_version,
+            end_version,
+            corruptions: Vec::new(),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.corruptions.is_empty()
+    }
+
+    /// Records an inconsistency at `version`, extending the last one of the same kind if it ends
+    /// right before `version`.
+    fn add(&mut self, kind: CorruptionKind, version: Version, detail: String) {
+        if let Some(corruption) = self
+            .corruptions
+            .iter_mut()
+            .rev()
+            .find(|corruption| corruption.kind == kind)
+        {
+            if corruption.last_version + 1 == version {
+                corruption.last_version = version;
+                return;
+            }
+        }
+        error!("{:?} at version {}: {}", kind, version, detail);
+        self.corruptions.push(Corruption {
+            kind,
+            first_version: version,
+            last_version: version,
+            detail,
+        });
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Verified versions [{}, {}], {} corruption(s) found.",
+            self.start_version,
+            self.end_version,
+            self.corruptions.len(),
+        )?;
+        for corruption in &self.corruptions {
+            write!(f, "\n  {}", corruption)?;
+        }
+        Ok(())
+    }
+}
+
+/// `DbVerifier` checks the integrity of the data in DiemDB.
+#[derive(Clone)]
+pub struct DbVerifier {
+    ledger_store: Arc<LedgerStore>,
+    transaction_store: Arc<TransactionStore>,
+    state_store: Arc<StateStore>,
+    event_store: Arc<EventStore>,
+}
+
+impl DbVerifier {
+    pub(crate) fn new(
+        ledger_store: Arc<LedgerStore>,
+        transaction_store: Arc<TransactionStore>,
+        state_store: Arc<StateStore>,
+        event_store: Arc<EventStore>,
+    ) -> Self {
+        Self {
+            ledger_store,
+            transaction_store,
+            state_store,
+            event_store,
+        }
+    }
+
+    /// Checks the transactions, events, transaction accumulator and state root hashes at versions
+    /// in `[start_version, end_version]`, as well as all the epoch ending ledger infos and the
+    /// latest ledger info. If `check_state_tree` is set, the state tree at `end_version` is walked
+    /// in full, recomputing the hash of every node.
+    pub fn verify(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        check_state_tree: bool,
+    ) -> Result<VerifyReport> {
+        let latest_ledger_info = self.ledger_store.get_latest_ledger_info()?;
+        let latest_version = latest_ledger_info.ledger_info().version();
+        ensure!(
+            start_version <= end_version && end_version <= latest_version,
+            "Invalid version range [{}, {}], latest committed version: {}.",
+            start_version,
+            end_version,
+            latest_version,
+        );
+
+        let mut report = VerifyReport::new(start_version, end_version);
+        self.verify_transactions(start_version, end_version, &mut report)?;
+        self.verify_ledger_infos(&latest_ledger_info, &mut report)?;
+        if check_state_tree {
+            self.verify_state_tree(end_version, &mut report);
+        }
+
+        info!("{}", report);
+        Ok(report)
+    }
+
+    fn verify_transactions(
+        &self,
+        start_version: Version,
+        end_version: Version,
+        report: &mut VerifyReport,
+    ) -> Result<()> {
+        let mut accumulator = self.load_accumulator(start_version)?;
+
+        for version in start_version..=end_version {
+            if (version - start_version) % PROGRESS_LOG_INTERVAL == 0 {
+                info!(
+                    "Verifying version {}, target: {}, {} corruption(s) found so far.",
+                    version,
+                    end_version,
+                    report.corruptions.len(),
+                );
+            }
+
+            let txn_info = match self.ledger_store.get_transaction_info(version) {
+                Ok(txn_info) => txn_info,
+                Err(e) => {
+                    report.add(
+                        CorruptionKind::MissingData,
+                        version,
+                        format!("Failed to read transaction info: {}", e),
+                    );
+                    accumulator = self.load_accumulator(version + 1)?;
+                    continue;
+                }
+            };
+
+            match self.transaction_store.get_transaction(version) {
+                Ok(txn) => {
+                    let txn_hash = txn.hash();
+                    if txn_hash != txn_info.transaction_hash() {
+                        report.add(
+                            CorruptionKind::TransactionHash,
+                            version,
+                            format!(
+                                "Transaction hash: {}, in transaction info: {}",
+                                txn_hash,
+                                txn_info.transaction_hash(),
+                            ),
+                        );
+                    }
+                }
+                Err(e) => report.add(
+                    CorruptionKind::MissingData,
+                    version,
+                    format!("Failed to read transaction: {}", e),
+                ),
+            }
+
+            match self.event_store.get_events_by_version(version) {
+                Ok(events) => {
+                    let event_hashes: Vec<_> = events.iter().map(CryptoHash::hash).collect();
+                    let event_root_hash =
+                        InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&event_hashes)
+                            .root_hash();
+                    if event_root_hash != txn_info.event_root_hash() {
+                        report.add(
+                            CorruptionKind::EventRootHash,
+                            version,
+                            format!(
+                                "Event root hash: {}, in transaction info: {}",
+                                event_root_hash,
+                                txn_info.event_root_hash(),
+                            ),
+                        );
+                    }
+                }
+                Err(e) => report.add(
+                    CorruptionKind::MissingData,
+                    version,
+                    format!("Failed to read events: {}", e),
+                ),
+            }
+
+            // The state tree at versions already pruned is simply not there.
+            match self.state_store.get_root_hash_option(version) {
+                Ok(Some(state_root_hash)) => {
+                    if state_root_hash != txn_info.state_root_hash() {
+                        report.add(
+                            CorruptionKind::StateRootHash,
+                            version,
+                            format!(
+                                "State root hash: {}, in transaction info: {}",
+                                state_root_hash,
+                                txn_info.state_root_hash(),
+                            ),
+                        );
+                    }
+                }
+                Ok(None) => (),
+                Err(e) => report.add(
+                    CorruptionKind::MissingData,
+                    version,
+                    format!("Failed to read state root: {}", e),
+                ),
+            }
+
+            accumulator = accumulator.append(&[txn_info.hash()]);
+            match self.ledger_store.get_root_hash(version) {
+                Ok(stored_root_hash) => {
+                    if stored_root_hash != accumulator.root_hash() {
+                        report.add(
+                            CorruptionKind::TransactionAccumulator,
+                            version,
+                            format!(
+                                "Stored root hash: {}, computed: {}",
+                                stored_root_hash,
+                                accumulator.root_hash(),
+                            ),
+                        );
+                        // Continue from what's stored so a single bad transaction info doesn't
+                        // fail all the versions after it.
+                        accumulator = self.load_accumulator(version + 1)?;
+                    }
+                }
+                Err(e) => {
+                    report.add(
+                        CorruptionKind::MissingData,
+                        version,
+                        format!("Failed to read transaction accumulator: {}", e),
+                    );
+                    accumulator = self.load_accumulator(version + 1)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn verify_ledger_infos(
+        &self,
+        latest_ledger_info: &LedgerInfoWithSignatures,
+        report: &mut VerifyReport,
+    ) -> Result<()> {
+        let latest_version = latest_ledger_info.ledger_info().version();
+        // The first ledger info is trusted as is, being the genesis or where the DB was restored
+        // from.
+        let mut epoch_state: Option<EpochState> = None;
+        let mut next_version = 0;
+
+        for res in self.ledger_store.get_epoch_ending_ledger_info_iter(
+            0,
+            latest_ledger_info.ledger_info().next_block_epoch(),
+        )? {
+            match res {
+                Ok(li) => {
+                    self.verify_ledger_info(&li, epoch_state.as_ref(), report);
+                    epoch_state = li.ledger_info().next_epoch_state().cloned();
+                    next_version = li.ledger_info().version() + 1;
+                }
+                Err(e) => {
+                    for version in next_version..=latest_version {
+                        report.add(
+                            CorruptionKind::LedgerInfo,
+                            version,
+                            format!("Failed to read epoch ending ledger infos: {}", e),
+                        );
+                    }
+                    return Ok(());
+                }
+            }
+        }
+
+        if !latest_ledger_info.ledger_info().ends_epoch() {
+            self.verify_ledger_info(latest_ledger_info, epoch_state.as_ref(), report);
+        }
+        Ok(())
+    }
+
+    fn verify_ledger_info(
+        &self,
+        li: &LedgerInfoWithSignatures,
+        epoch_state: Option<&EpochState>,
+        report: &mut VerifyReport,
+    ) {
+        let version = li.ledger_info().version();
+        match self.ledger_store.get_root_hash(version) {
+            Ok(root_hash) => {
+                if root_hash != li.ledger_info().transaction_accumulator_hash() {
+                    report.add(
+                        CorruptionKind::LedgerInfo,
+                        version,
+                        format!(
+                            "Accumulator root hash in epoch {} ledger info: {}, stored: {}",
+                            li.ledger_info().epoch(),
+                            li.ledger_info().transaction_accumulator_hash(),
+                            root_hash,
+                        ),
+                    );
+                }
+            }
+            Err(e) => report.add(
+                CorruptionKind::MissingData,
+                version,
+                format!("Failed to read transaction accumulator: {}", e),
+            ),
+        }
+
+        if let Some(epoch_state) = epoch_state {
+            if let Err(e) = epoch_state.verify(li) {
+                report.add(
+                    CorruptionKind::LedgerInfo,
+                    version,
+                    format!(
+                        "Epoch {} ledger info failed verification: {}",
+                        li.ledger_info().epoch(),
+                        e,
+                    ),
+                );
+            }
+        }
+    }
+
+    fn verify_state_tree(&self, version: Version, report: &mut VerifyReport) {
+        let expected_root_hash = match self.ledger_store.get_transaction_info(version) {
+            Ok(txn_info) => txn_info.state_root_hash(),
+            Err(e) => {
+                report.add(
+                    CorruptionKind::MissingData,
+                    version,
+                    format!("Failed to read transaction info: {}", e),
+                );
+                return;
+            }
+        };
+
+        info!("Walking the state tree at version {}.", version);
+        match JellyfishMerkleTree::new(&*self.state_store).verify_tree(version) {
+            Ok((root_hash, num_leaves)) => {
+                if root_hash == expected_root_hash {
+                    info!(
+                        "State tree at version {} verified, {} accounts.",
+                        version, num_leaves,
+                    );
+                } else {
+                    report.add(
+                        CorruptionKind::StateTree,
+                        version,
+                        format!(
+                            "Recomputed state root hash: {}, in transaction info: {}",
+                            root_hash, expected_root_hash,
+                        ),
+                    );
+                }
+            }
+            Err(e) => report.add(CorruptionKind::StateTree, version, e.to_string()),
+        }
+    }
+
+    /// Rebuilds the accumulator of the first `num_leaves` transaction infos from the frozen
+    /// subtrees in the DB.
+    fn load_accumulator(
+        &self,
+        num_leaves: Version,
+    ) -> Result<InMemoryAccumulator<TransactionAccumulatorHasher>> {
+        InMemoryAccumulator::new(
+            self.ledger_store.get_frozen_subtree_hashes(num_leaves)?,
+            num_leaves,
+        )
+    }
+}
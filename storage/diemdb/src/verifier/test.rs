@@ -0,0 +1,99 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    schema::transaction_info::TransactionInfoSchema, test_helper::arb_blocks_to_commit, DiemDB,
+};
+use diem_temppath::TempPath;
+use diem_types::transaction::{TransactionInfo, TransactionToCommit};
+use proptest::{prelude::*, sample::Index};
+use storage_interface::DbWriter;
+
+fn save_blocks(
+    db: &DiemDB,
+    blocks: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)],
+) -> Version {
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in blocks {
+        db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as Version;
+    }
+    cur_ver - 1
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_verify_intact_db(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+        let latest_version = save_blocks(&db, &input);
+
+        let report = db.get_verifier().verify(0, latest_version, true).unwrap();
+        prop_assert!(report.is_ok(), "{}", report);
+
+        // Out of range.
+        prop_assert!(db.get_verifier().verify(0, latest_version + 1, false).is_err());
+    }
+
+    #[test]
+    fn test_verify_tampered_transaction_info(input in arb_blocks_to_commit(), index in any::<Index>()) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+        let latest_version = save_blocks(&db, &input);
+
+        let version = index.index(latest_version as usize + 1) as Version;
+        let txn_info = db.ledger_store.get_transaction_info(version).unwrap();
+        let tampered = TransactionInfo::new(
+            txn_info.transaction_hash(),
+            txn_info.state_root_hash(),
+            txn_info.event_root_hash(),
+            txn_info.gas_used() + 1,
+            txn_info.status().clone(),
+        );
+        db.db.put::<TransactionInfoSchema>(&version, &tampered).unwrap();
+
+        let report = db.get_verifier().verify(0, latest_version, false).unwrap();
+        prop_assert_eq!(report.corruptions.len(), 1, "{}", report);
+        let corruption = &report.corruptions[0];
+        prop_assert_eq!(corruption.kind, CorruptionKind::TransactionAccumulator);
+        prop_assert_eq!(corruption.first_version, version);
+        prop_assert_eq!(corruption.last_version, version);
+    }
+}
+
+#[test]
+fn test_report_merges_adjacent_versions() {
+    let mut report = VerifyReport::new(0, 10);
+    report.add(CorruptionKind::TransactionHash, 3, "first".to_string());
+    report.add(CorruptionKind::EventRootHash, 3, "events".to_string());
+    report.add(CorruptionKind::TransactionHash, 4, "second".to_string());
+    report.add(CorruptionKind::TransactionHash, 7, "third".to_string());
+
+    assert_eq!(
+        report.corruptions,
+        vec![
+            Corruption {
+                kind: CorruptionKind::TransactionHash,
+                first_version: 3,
+                last_version: 4,
+                detail: "first".to_string(),
+            },
+            Corruption {
+                kind: CorruptionKind::EventRootHash,
+                first_version: 3,
+                last_version: 3,
+                detail: "events".to_string(),
+            },
+            Corruption {
+                kind: CorruptionKind::TransactionHash,
+                first_version: 7,
+                last_version: 7,
+                detail: "third".to_string(),
+            },
+        ]
+    );
+}
@@ -13,6 +13,7 @@ use storage_interface::DbReader;
 
 use diem_types::{
     account_address::AccountAddress, account_config::AccountResource, account_state::AccountState,
+    waypoint::Waypoint,
 };
 use std::convert::TryFrom;
 use structopt::StructOpt;
@@ -22,6 +23,10 @@ struct Opt {
     #[structopt(long, parse(from_os_str))]
     db: PathBuf,
 
+    /// Number of RocksDB instances the state of the DB is in. See `RocksdbConfig::state_shards`.
+    #[structopt(long, default_value = "1")]
+    state_shards: usize,
+
     #[structopt(subcommand)] // Note that we mark a field as a subcommand
     cmd: Option<Command>,
 }
@@ -39,6 +44,54 @@ enum Command {
     },
     #[structopt(name = "list-accounts")]
     ListAccounts,
+    /// Checks the integrity of the DB, reporting the version ranges found corrupted.
+    #[structopt(name = "verify")]
+    Verify {
+        /// Defaults to the earliest version whose transactions are still in the DB.
+        #[structopt(long)]
+        start_version: Option<u64>,
+        /// Defaults to the latest committed version.
+        #[structopt(long)]
+        end_version: Option<u64>,
+        /// Don't walk the whole state tree at the end version, which can take long on a big DB.
+        #[structopt(long)]
+        skip_state_tree: bool,
+    },
+    /// Exports or imports a verifiable state snapshot, for bootstrapping a new node.
+    #[structopt(name = "snapshot")]
+    Snapshot(SnapshotCommand),
+    /// Moves the state of the DB into a number of RocksDB instances, 1 being the main one only.
+    /// The node must be stopped.
+    #[structopt(name = "migrate-state-shards")]
+    MigrateStateShards {
+        #[structopt(long)]
+        to: usize,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+enum SnapshotCommand {
+    /// Writes the state at an epoch ending version with proofs and the ledger info to a directory.
+    #[structopt(name = "export")]
+    Export {
+        /// Must end an epoch. Defaults to the latest epoch ending version.
+        #[structopt(long)]
+        version: Option<u64>,
+        #[structopt(long, parse(from_os_str))]
+        output_dir: PathBuf,
+        /// Number of accounts in each chunk file.
+        #[structopt(long, default_value = "100000")]
+        chunk_size: usize,
+    },
+    /// Verifies a snapshot and seeds an empty DB with it.
+    #[structopt(name = "import")]
+    Import {
+        #[structopt(long, parse(from_os_str))]
+        input_dir: PathBuf,
+        /// The waypoint of the snapshot's ledger info, obtained from a trusted source.
+        #[structopt(long)]
+        waypoint: Waypoint,
+    },
 }
 
 /// Print out latest information stored in the DB.
@@ -152,6 +205,54 @@ fn list_accounts(db: &DiemDB) {
     info!("Total Accounts: {}", num_account);
 }
 
+fn verify(
+    db: &DiemDB,
+    start_version: Option<u64>,
+    end_version: Option<u64>,
+    skip_state_tree: bool,
+) -> Result<bool> {
+    let start_version = match start_version {
+        Some(v) => v,
+        None => db.get_earliest_available_version()?,
+    };
+    let end_version = match end_version {
+        Some(v) => v,
+        None => db.get_latest_version()?,
+    };
+    let report = db
+        .get_verifier()
+        .verify(start_version, end_version, !skip_state_tree)?;
+    println!("{}", report);
+    Ok(report.is_ok())
+}
+
+fn snapshot(db: &DiemDB, cmd: SnapshotCommand) -> Result<()> {
+    let handler = db.get_snapshot_handler();
+    match cmd {
+        SnapshotCommand::Export {
+            version,
+            output_dir,
+            chunk_size,
+        } => {
+            let manifest = handler.export(version, &output_dir, chunk_size)?;
+            println!(
+                "Exported state snapshot at version {} in {} chunks, waypoint: {}",
+                manifest.version,
+                manifest.chunks.len(),
+                Waypoint::new_epoch_boundary(manifest.ledger_info.ledger_info())?,
+            );
+        }
+        SnapshotCommand::Import {
+            input_dir,
+            waypoint,
+        } => {
+            let manifest = handler.import(&input_dir, waypoint)?;
+            println!("Imported state snapshot at version {}.", manifest.version);
+        }
+    }
+    Ok(())
+}
+
 fn main() {
     ::diem_logger::DiemLogger::builder().build();
 
@@ -159,19 +260,35 @@ fn main() {
 
     let p = opt.db.as_path();
 
-    if !p.is_dir() {
+    // Importing a snapshot is the only command writing to the DB, and it needs the DB directory to
+    // exist.
+    let readonly = !matches!(
+        opt.cmd,
+        Some(Command::Snapshot(SnapshotCommand::Import { .. }))
+    );
+    if readonly && !p.is_dir() {
         info!("Invalid Directory {:?}!", p);
         std::process::exit(-1);
     }
 
+    if let Some(Command::MigrateStateShards { to }) = opt.cmd {
+        DiemDB::migrate_state_shards(p, to, RocksdbConfig::default())
+            .expect("Unable to migrate state shards");
+        info!("State migrated into {} RocksDB instance(s).", to);
+        return;
+    }
+
     let log_dir = tempfile::tempdir().expect("Unable to get temp dir");
     info!("Opening DB at: {:?}, log at {:?}", p, log_dir.path());
 
     let db = DiemDB::open(
         p,
-        true, /* readonly */
+        readonly,
         None, /* pruner */
-        RocksdbConfig::default(),
+        RocksdbConfig {
+            state_shards: opt.state_shards,
+            ..Default::default()
+        },
     )
     .expect("Unable to open DiemDB");
     info!("DB opened successfully.");
@@ -190,6 +307,21 @@ fn main() {
             Command::ListAccounts => {
                 list_accounts(&db);
             }
+            Command::Verify {
+                start_version,
+                end_version,
+                skip_state_tree,
+            } => {
+                if !verify(&db, start_version, end_version, skip_state_tree)
+                    .expect("Unable to verify DB")
+                {
+                    std::process::exit(1);
+                }
+            }
+            Command::Snapshot(cmd) => {
+                snapshot(&db, cmd).expect("Snapshot command failed");
+            }
+            Command::MigrateStateShards { .. } => unreachable!("Handled before opening the DB."),
         }
     } else {
         print_head(&db).expect("Unable to read information from DB");
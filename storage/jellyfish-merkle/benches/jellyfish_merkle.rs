@@ -0,0 +1,108 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use diem_crypto::HashValue;
+use diem_jellyfish_merkle::{
+    node_type::{LeafNode, Node, NodeKey},
+    JellyfishMerkleTree, TreeReader,
+};
+use diem_types::account_state_blob::AccountStateBlob;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::HashMap;
+
+const NUM_EXISTING_ACCOUNTS: usize = 10_000;
+const ACCOUNTS_PER_TXN: usize = 2;
+const BLOB_SIZE: usize = 100;
+
+#[derive(Default)]
+struct InMemoryTreeStore(HashMap<NodeKey, Node<AccountStateBlob>>);
+
+impl TreeReader<AccountStateBlob> for InMemoryTreeStore {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node<AccountStateBlob>>> {
+        Ok(self.0.get(node_key).cloned())
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode<AccountStateBlob>)>> {
+        unimplemented!()
+    }
+}
+
+fn random_blob(rng: &mut StdRng) -> AccountStateBlob {
+    AccountStateBlob::from((0..BLOB_SIZE).map(|_| rng.gen()).collect::<Vec<u8>>())
+}
+
+fn put_value_sets(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let accounts = (0..NUM_EXISTING_ACCOUNTS)
+        .map(|_| HashValue::random_with_rng(&mut rng))
+        .collect::<Vec<_>>();
+    let mut db = InMemoryTreeStore::default();
+    let genesis = accounts
+        .iter()
+        .map(|key| (*key, random_blob(&mut rng)))
+        .collect();
+    let (_, batch) = JellyfishMerkleTree::new(&db)
+        .put_value_sets(vec![genesis], 0 /* first_version */)
+        .unwrap();
+    db.0.extend(batch.node_batch);
+    let tree = JellyfishMerkleTree::new(&db);
+
+    let mut group = c.benchmark_group("put_value_sets");
+    for block_size in &[100, 1000] {
+        // Each transaction touches a few accounts, half of them new.
+        let value_sets = (0..*block_size)
+            .map(|_| {
+                (0..ACCOUNTS_PER_TXN)
+                    .map(|_| {
+                        let key = if rng.gen() {
+                            accounts[rng.gen_range(0..accounts.len())]
+                        } else {
+                            HashValue::random_with_rng(&mut rng)
+                        };
+                        (key, random_blob(&mut rng))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let one_large_set = vec![value_sets.iter().flatten().cloned().collect::<Vec<_>>()];
+
+        group.throughput(Throughput::Elements(*block_size as u64));
+        group.bench_function(BenchmarkId::new("serial", block_size), |b| {
+            b.iter_batched(
+                || value_sets.clone(),
+                |value_sets| tree.put_value_sets_serial(value_sets, 1).unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("parallel", block_size), |b| {
+            b.iter_batched(
+                || value_sets.clone(),
+                |value_sets| tree.put_value_sets(value_sets, 1).unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("serial__one_large_set", block_size), |b| {
+            b.iter_batched(
+                || one_large_set.clone(),
+                |value_sets| tree.put_value_sets_serial(value_sets, 1).unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(
+            BenchmarkId::new("parallel__one_large_set", block_size),
+            |b| {
+                b.iter_batched(
+                    || one_large_set.clone(),
+                    |value_sets| tree.put_value_sets(value_sets, 1).unwrap(),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, put_value_sets);
+criterion_main!(benches);
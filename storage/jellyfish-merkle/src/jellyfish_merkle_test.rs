@@ -11,7 +11,7 @@ use diem_crypto::HashValue;
 use diem_nibble::Nibble;
 use diem_types::transaction::PRE_GENESIS_VERSION;
 use mock_tree_store::MockTreeStore;
-use proptest::prelude::*;
+use proptest::{collection::vec, prelude::*, sample::Index};
 use rand::{rngs::StdRng, Rng, SeedableRng};
 use std::collections::HashMap;
 
@@ -539,6 +539,44 @@ fn test_put_value_sets() {
     }
 }
 
+fn put_value_sets_same_as_serial(
+    keys: Vec<HashValue>,
+    updates: Vec<Vec<(Index, ValueBlob)>>,
+    num_committed_sets: usize,
+) {
+    // Keys differing only in the last nibble make the deepest subtrees possible.
+    let keys = keys
+        .iter()
+        .flat_map(|key| vec![*key, update_nibble(key, ROOT_NIBBLE_HEIGHT - 1, 0)])
+        .collect::<Vec<_>>();
+    let mut value_sets = updates
+        .into_iter()
+        .map(|value_set| {
+            value_set
+                .into_iter()
+                .map(|(idx, value)| (*idx.get(&keys), value))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // Some sets go into the db first, for the rest to update an existing tree.
+    let db = MockTreeStore::default();
+    let tree = JellyfishMerkleTree::new(&db);
+    let num_committed_sets = std::cmp::min(num_committed_sets, value_sets.len() - 1);
+    let rest = value_sets.split_off(num_committed_sets);
+    if !value_sets.is_empty() {
+        let (_, batch) = tree.put_value_sets_serial(value_sets, 0).unwrap();
+        db.write_tree_update_batch(batch).unwrap();
+    }
+
+    let first_version = num_committed_sets as Version;
+    let (root_hashes, batch) = tree.put_value_sets(rest.clone(), first_version).unwrap();
+    let (root_hashes_serial, batch_serial) =
+        tree.put_value_sets_serial(rest, first_version).unwrap();
+    assert_eq!(root_hashes, root_hashes_serial);
+    assert_eq!(batch, batch_serial);
+}
+
 fn many_keys_get_proof_and_verify_tree_root(seed: &[u8], num_keys: usize) {
     assert!(seed.len() < 32);
     let mut actual_seed = [0u8; 32];
@@ -629,6 +667,45 @@ fn test_1000_versions() {
     many_versions_get_proof_and_verify_tree_root(seed, 1000);
 }
 
+#[test]
+fn test_verify_tree() {
+    let mut rng: StdRng = StdRng::from_seed([0u8; 32]);
+    let db = MockTreeStore::new(true /* allow_overwrite */);
+    let tree = JellyfishMerkleTree::new(&db);
+
+    let mut roots = vec![];
+    for version in 0..2 {
+        let kvs = (0..50)
+            .map(|_| {
+                (
+                    HashValue::random_with_rng(&mut rng),
+                    ValueBlob::from(HashValue::random_with_rng(&mut rng).to_vec()),
+                )
+            })
+            .collect();
+        let (root, batch) = tree.put_value_set(kvs, version).unwrap();
+        db.write_tree_update_batch(batch).unwrap();
+        roots.push(root);
+    }
+
+    assert_eq!(tree.verify_tree(0).unwrap(), (roots[0], 50));
+    assert_eq!(tree.verify_tree(1).unwrap(), (roots[1], 100));
+    assert!(tree.verify_tree(2).is_err());
+
+    // Tamper with the value of a leaf, which is still referenced by the tree at version 1.
+    let (leaf_node_key, leaf_node) = db.get_rightmost_leaf().unwrap().unwrap();
+    db.write_node_batch(
+        &vec![(
+            leaf_node_key,
+            Node::new_leaf(leaf_node.account_key(), ValueBlob::from(vec![1, 2, 3])),
+        )]
+        .into_iter()
+        .collect(),
+    )
+    .unwrap();
+    assert!(tree.verify_tree(1).is_err());
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
 
@@ -646,4 +723,13 @@ proptest! {
     fn proptest_get_range_proof((btree, n) in arb_tree_with_index::<ValueBlob>(1000)) {
         test_get_range_proof((btree, n))
     }
+
+    #[test]
+    fn proptest_put_value_sets_same_as_serial(
+        keys in vec(any::<HashValue>(), 1..100),
+        updates in vec(vec((any::<Index>(), any::<ValueBlob>()), 1..50), 1..10),
+        num_committed_sets in 0usize..5,
+    ) {
+        put_value_sets_same_as_serial(keys, updates, num_committed_sets)
+    }
 }
@@ -94,6 +94,7 @@ use node_type::{Child, Children, InternalNode, LeafNode, Node, NodeKey};
 use proptest::arbitrary::Arbitrary;
 #[cfg(any(test, feature = "fuzzing"))]
 use proptest_derive::Arbitrary;
+use rayon::prelude::*;
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
     collections::{BTreeMap, BTreeSet},
@@ -111,6 +112,10 @@ pub struct MissingRootError {
 /// The hardcoded maximum height of a [`JellyfishMerkleTree`] in nibbles.
 pub const ROOT_NIBBLE_HEIGHT: usize = HashValue::LENGTH * 2;
 
+/// Children of nodes less than this many nibbles deep are updated in parallel by batched
+/// insertions, which makes up to 256 subtrees to hash concurrently.
+const MAX_PARALLELIZABLE_DEPTH: usize = 2;
+
 /// `TreeReader` defines the interface between
 /// [`JellyfishMerkleTree`](struct.JellyfishMerkleTree.html)
 /// and underlying storage holding nodes.
@@ -129,13 +134,13 @@ pub trait TreeReader<V> {
     fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode<V>)>>;
 }
 
-pub trait TreeWriter<V> {
+pub trait TreeWriter<V>: Send + Sync {
     /// Writes a node batch into storage.
     fn write_node_batch(&self, node_batch: &NodeBatch<V>) -> Result<()>;
 }
 
 /// `Value` defines the types of data that can be stored in a Jellyfish Merkle tree.
-pub trait Value: Clone + CryptoHash + Serialize + DeserializeOwned {}
+pub trait Value: Clone + CryptoHash + Serialize + DeserializeOwned + Send + Sync {}
 
 /// `TestValue` defines the types of data that can be stored in a Jellyfish Merkle tree and used in
 /// tests.
@@ -188,6 +193,34 @@ pub struct TreeUpdateBatch<V> {
     pub node_stats: Vec<NodeStats>,
 }
 
+/// Gets the nibble of `key` at `depth`, i.e. the index of the child of a node `depth` nibbles deep
+/// it falls into.
+fn nibble_at(key: HashValue, depth: usize) -> Nibble {
+    let byte = key[depth / 2];
+    Nibble::from(if depth % 2 == 0 { byte >> 4 } else { byte & 0x0f })
+}
+
+/// The nodes created and made stale by a batched insertion into a subtree, to be applied to the
+/// [`TreeCache`] once all the subtrees updated in parallel are done.
+struct SubtreeUpdate<V> {
+    new_nodes: Vec<(NodeKey, Node<V>)>,
+    stale_nodes: Vec<(NodeKey, bool /* is_leaf */)>,
+}
+
+impl<V> SubtreeUpdate<V> {
+    fn new() -> Self {
+        Self {
+            new_nodes: Vec::new(),
+            stale_nodes: Vec::new(),
+        }
+    }
+
+    fn merge(&mut self, other: Self) {
+        self.new_nodes.extend(other.new_nodes);
+        self.stale_nodes.extend(other.stale_nodes);
+    }
+}
+
 /// The Jellyfish Merkle tree data structure. See [`crate`] for description.
 pub struct JellyfishMerkleTree<'a, R, V> {
     reader: &'a R,
@@ -266,10 +299,39 @@ where
     /// the returned batch, the state `S_{i+1}` is ready to be read from the tree by calling
     /// [`get_with_proof`](struct.JellyfishMerkleTree.html#method.get_with_proof). Anything inside
     /// the batch is not reachable from public interfaces before being committed.
+    ///
+    /// All the keys of a `value_set` are inserted in one pass, each new node being hashed once,
+    /// and the subtrees near the root are updated in parallel.
     pub fn put_value_sets(
         &self,
         value_sets: Vec<Vec<(HashValue, V)>>,
         first_version: Version,
+    ) -> Result<(Vec<HashValue>, TreeUpdateBatch<V>)>
+    where
+        R: Sync,
+    {
+        let mut tree_cache = TreeCache::new(self.reader, first_version)?;
+        for (idx, value_set) in value_sets.into_iter().enumerate() {
+            assert!(
+                !value_set.is_empty(),
+                "Transactions that output empty write set should not be included.",
+            );
+            let version = first_version + idx as u64;
+            Self::batch_put(value_set, version, &mut tree_cache)?;
+            // Freezes the current cache to make all contents in the current cache immutable.
+            tree_cache.freeze();
+        }
+
+        Ok(tree_cache.into())
+    }
+
+    /// Same as [`put_value_sets`](struct.JellyfishMerkleTree.html#method.put_value_sets), but
+    /// inserts the keys one at a time on the calling thread. Produces exactly the same results,
+    /// and serves as the reference for tests and benchmarks.
+    pub fn put_value_sets_serial(
+        &self,
+        value_sets: Vec<Vec<(HashValue, V)>>,
+        first_version: Version,
     ) -> Result<(Vec<HashValue>, TreeUpdateBatch<V>)> {
         let mut tree_cache = TreeCache::new(self.reader, first_version)?;
         for (idx, value_set) in value_sets.into_iter().enumerate() {
@@ -288,6 +350,214 @@ where
         Ok(tree_cache.into())
     }
 
+    fn batch_put(
+        value_set: Vec<(HashValue, V)>,
+        version: Version,
+        tree_cache: &mut TreeCache<R, V>,
+    ) -> Result<()>
+    where
+        R: Sync,
+    {
+        // Later values of a key overwrite earlier ones, like consecutive `put`s do. Leaves are
+        // sorted by key, so that the ones under a node are next to each other.
+        let value_set = value_set.into_iter().collect::<BTreeMap<_, _>>();
+        let leaves = value_set
+            .into_iter()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(key, value)| LeafNode::new(key, value))
+            .collect::<Vec<_>>();
+
+        let root_node_key = tree_cache.get_root_node_key().clone();
+        let mut update = SubtreeUpdate::new();
+        let (new_root_node_key, _) =
+            Self::batch_insert_at(root_node_key, version, &leaves, tree_cache, &mut update)?;
+
+        // Nodes made stale are older than `version`, except for the null root of an empty tree,
+        // so they never clash with the new ones.
+        for (node_key, is_leaf) in update.stale_nodes {
+            tree_cache.delete_node(&node_key, is_leaf);
+        }
+        for (node_key, node) in update.new_nodes {
+            tree_cache.put_node(node_key, node)?;
+        }
+        tree_cache.set_root_node_key(new_root_node_key);
+        Ok(())
+    }
+
+    /// Helper function for batched insertion of `leaves` into the subtree that starts from the
+    /// node at `node_key`. Returns the new root node of the subtree with its
+    /// [`NodeKey`](node_type/struct.NodeKey.html). All the `leaves` must be under `node_key`.
+    fn batch_insert_at(
+        node_key: NodeKey,
+        version: Version,
+        leaves: &[LeafNode<V>],
+        tree_cache: &TreeCache<R, V>,
+        update: &mut SubtreeUpdate<V>,
+    ) -> Result<(NodeKey, Node<V>)>
+    where
+        R: Sync,
+    {
+        let node = tree_cache.get_node(&node_key)?;
+        match node {
+            Node::Internal(internal_node) => {
+                update.stale_nodes.push((node_key.clone(), false /* is_leaf */));
+
+                let children = Self::batch_update_children(
+                    node_key.nibble_path(),
+                    leaves,
+                    update,
+                    |child_index, child_leaves, child_update| match internal_node.child(child_index)
+                    {
+                        Some(child) => Self::batch_insert_at(
+                            node_key.gen_child_node_key(child.version, child_index),
+                            version,
+                            child_leaves,
+                            tree_cache,
+                            child_update,
+                        ),
+                        None => Self::batch_create_subtree(
+                            node_key.gen_child_node_key(version, child_index),
+                            child_leaves,
+                            child_update,
+                        ),
+                    },
+                )?;
+
+                let mut new_children: Children = internal_node.into();
+                new_children.extend(children);
+                let new_internal_node: Node<V> = InternalNode::new(new_children).into();
+                let mut new_node_key = node_key;
+                new_node_key.set_version(version);
+                update
+                    .new_nodes
+                    .push((new_node_key.clone(), new_internal_node.clone()));
+                Ok((new_node_key, new_internal_node))
+            }
+            Node::Leaf(existing_leaf_node) => {
+                update.stale_nodes.push((node_key.clone(), true /* is_leaf */));
+
+                // The existing leaf moves into the new subtree, unless its value is overwritten.
+                let mut new_node_key = node_key;
+                new_node_key.set_version(version);
+                match leaves
+                    .binary_search_by_key(&existing_leaf_node.account_key(), |leaf| {
+                        leaf.account_key()
+                    }) {
+                    Ok(_) => Self::batch_create_subtree(new_node_key, leaves, update),
+                    Err(pos) => {
+                        let mut all_leaves = Vec::with_capacity(leaves.len() + 1);
+                        all_leaves.extend_from_slice(&leaves[..pos]);
+                        all_leaves.push(existing_leaf_node);
+                        all_leaves.extend_from_slice(&leaves[pos..]);
+                        Self::batch_create_subtree(new_node_key, &all_leaves, update)
+                    }
+                }
+            }
+            Node::Null => {
+                if node_key.nibble_path().num_nibbles() != 0 {
+                    bail!(
+                        "Null node exists for non-root node with node_key {:?}",
+                        node_key
+                    );
+                }
+                // delete the old null node if the at the same version.
+                if node_key.version() == version {
+                    update.stale_nodes.push((node_key, false /* is_leaf */));
+                }
+                Self::batch_create_subtree(NodeKey::new_empty_path(version), leaves, update)
+            }
+        }
+    }
+
+    /// Helper function for creating a subtree at `node_key` from scratch, holding the sorted
+    /// `leaves`. Returns the root node of the subtree.
+    fn batch_create_subtree(
+        node_key: NodeKey,
+        leaves: &[LeafNode<V>],
+        update: &mut SubtreeUpdate<V>,
+    ) -> Result<(NodeKey, Node<V>)> {
+        let version = node_key.version();
+        let new_node: Node<V> = if let [leaf] = leaves {
+            leaf.clone().into()
+        } else {
+            let children = Self::batch_update_children(
+                node_key.nibble_path(),
+                leaves,
+                update,
+                |child_index, child_leaves, child_update| {
+                    Self::batch_create_subtree(
+                        node_key.gen_child_node_key(version, child_index),
+                        child_leaves,
+                        child_update,
+                    )
+                },
+            )?;
+            InternalNode::new(children).into()
+        };
+        update.new_nodes.push((node_key.clone(), new_node.clone()));
+        Ok((node_key, new_node))
+    }
+
+    /// Splits the sorted `leaves` under the node at `nibble_path` by the child they fall into,
+    /// and calls `update_child` for each child with its leaves, in parallel if the node is close
+    /// enough to the root. Returns the new children, the new nodes being hashed by the tasks
+    /// which created them.
+    fn batch_update_children<F>(
+        nibble_path: &NibblePath,
+        leaves: &[LeafNode<V>],
+        update: &mut SubtreeUpdate<V>,
+        update_child: F,
+    ) -> Result<Children>
+    where
+        F: Fn(Nibble, &[LeafNode<V>], &mut SubtreeUpdate<V>) -> Result<(NodeKey, Node<V>)>
+            + Send
+            + Sync,
+    {
+        let depth = nibble_path.num_nibbles();
+        let mut groups: Vec<(Nibble, &[LeafNode<V>])> = Vec::new();
+        let mut start = 0;
+        while start < leaves.len() {
+            let child_index = nibble_at(leaves[start].account_key(), depth);
+            let len = leaves[start..]
+                .iter()
+                .take_while(|leaf| nibble_at(leaf.account_key(), depth) == child_index)
+                .count();
+            groups.push((child_index, &leaves[start..start + len]));
+            start += len;
+        }
+
+        let update_group = |(child_index, child_leaves): (Nibble, &[LeafNode<V>])| -> Result<_> {
+            let mut child_update = SubtreeUpdate::new();
+            let (new_child_node_key, new_child_node) =
+                update_child(child_index, child_leaves, &mut child_update)?;
+            let child = Child::new(
+                new_child_node.hash(),
+                new_child_node_key.version(),
+                new_child_node.is_leaf(),
+            );
+            Ok((child_index, child, child_update))
+        };
+        let results = if depth < MAX_PARALLELIZABLE_DEPTH {
+            groups
+                .into_par_iter()
+                .map(update_group)
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            groups
+                .into_iter()
+                .map(update_group)
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        let mut children = Children::new();
+        for (child_index, child, child_update) in results {
+            children.insert(child_index, child);
+            update.merge(child_update);
+        }
+        Ok(children)
+    }
+
     fn put(
         key: HashValue,
         value: V,
@@ -650,6 +920,55 @@ where
             .get_node_option(&root_node_key)?
             .map(|root_node| root_node.hash()))
     }
+
+    /// Walks all nodes of the tree at `version`, checking that the hash of every node read from
+    /// storage matches the hash recorded for it in its parent. Since leaf hashes are computed from
+    /// the values, a successful walk means the root hash is fully derived from the stored values.
+    /// Returns the root hash and the number of leaves.
+    pub fn verify_tree(&self, version: Version) -> Result<(HashValue, usize)> {
+        let root_node_key = NodeKey::new_empty_path(version);
+        let root_node = self
+            .reader
+            .get_node_option(&root_node_key)?
+            .ok_or(MissingRootError { version })?;
+        let root_hash = root_node.hash();
+
+        let mut num_leaves = 0;
+        let mut stack = vec![(root_node_key, root_node)];
+        while let Some((node_key, node)) = stack.pop() {
+            match node {
+                Node::Internal(internal_node) => {
+                    for i in 0..16u8 {
+                        let nibble = Nibble::from(i);
+                        if let Some(child) = internal_node.child(nibble) {
+                            let child_node_key = node_key.gen_child_node_key(child.version, nibble);
+                            let child_node = self.reader.get_node(&child_node_key)?;
+                            ensure!(
+                                child_node.is_leaf() == child.is_leaf,
+                                "Node type mismatch at {:?}, is_leaf: {}, recorded in parent: {}.",
+                                child_node_key,
+                                child_node.is_leaf(),
+                                child.is_leaf,
+                            );
+                            let child_hash = child_node.hash();
+                            ensure!(
+                                child_hash == child.hash,
+                                "Hash mismatch at {:?}, computed: {}, recorded in parent: {}.",
+                                child_node_key,
+                                child_hash,
+                                child.hash,
+                            );
+                            stack.push((child_node_key, child_node));
+                        }
+                    }
+                }
+                Node::Leaf(_) => num_leaves += 1,
+                Node::Null => (),
+            }
+        }
+
+        Ok((root_hash, num_leaves))
+    }
 }
 
 trait NibbleExt {
@@ -3,6 +3,11 @@
 
 //! This module implements the functionality to restore a `JellyfishMerkleTree` from small chunks
 //! of accounts.
+//!
+//! Since the tree is built bottom-up from accounts in increasing key order, each node being
+//! written once, the same machinery also serves bulk imports of trusted accounts, e.g. by genesis
+//! generation and chain migration tools, where chunks come without proofs and the root hash is
+//! only known in the end.
 
 #[cfg(test)]
 mod restore_test;
@@ -152,8 +157,9 @@ pub struct JellyfishMerkleRestore<V> {
     /// The number of keys we have received since the most recent restart.
     num_keys_received: u64,
 
-    /// When the restoration process finishes, we expect the tree to have this root hash.
-    expected_root_hash: HashValue,
+    /// When the restoration process finishes, we expect the tree to have this root hash. `None`
+    /// for bulk imports, whose chunks come without proofs.
+    expected_root_hash: Option<HashValue>,
 }
 
 impl<V> JellyfishMerkleRestore<V>
@@ -189,7 +195,7 @@ where
             frozen_nodes: NodeBatch::new(),
             previous_leaf,
             num_keys_received: 0,
-            expected_root_hash,
+            expected_root_hash: Some(expected_root_hash),
         })
     }
 
@@ -205,7 +211,24 @@ where
             frozen_nodes: NodeBatch::new(),
             previous_leaf: None,
             num_keys_received: 0,
-            expected_root_hash,
+            expected_root_hash: Some(expected_root_hash),
+        })
+    }
+
+    /// Starts a bulk import of accounts into the tree at `version`, overwriting whatever is
+    /// there. Accounts are added with `add_sorted_chunk` and trusted as they are.
+    pub fn new_bulk_import<D: 'static + TreeWriter<V>>(
+        store: Arc<D>,
+        version: Version,
+    ) -> Result<Self> {
+        Ok(Self {
+            store,
+            version,
+            partial_nodes: vec![InternalInfo::new_empty(NodeKey::new_empty_path(version))],
+            frozen_nodes: NodeBatch::new(),
+            previous_leaf: None,
+            num_keys_received: 0,
+            expected_root_hash: None,
         })
     }
 
@@ -285,7 +308,37 @@ where
         proof: SparseMerkleRangeProof,
     ) -> Result<()> {
         ensure!(!chunk.is_empty(), "Should not add empty chunks.");
+        ensure!(
+            self.expected_root_hash.is_some(),
+            "Chunks of a bulk import come without proofs.",
+        );
+
+        self.add_leaves(chunk)?;
+
+        // Verify what we have added so far is all correct.
+        self.verify(proof)?;
+
+        // Write the frozen nodes to storage.
+        self.store.write_node_batch(&self.frozen_nodes)?;
+        self.frozen_nodes.clear();
+
+        Ok(())
+    }
+
+    /// Adds a chunk of accounts to a bulk import. Nothing is verified except that the keys come
+    /// in increasing order, and the nodes that will not change anymore are written to storage.
+    pub fn add_sorted_chunk(&mut self, chunk: Vec<(HashValue, V)>) -> Result<()> {
+        ensure!(!chunk.is_empty(), "Should not add empty chunks.");
+
+        self.add_leaves(chunk)?;
 
+        self.store.write_node_batch(&self.frozen_nodes)?;
+        self.frozen_nodes.clear();
+
+        Ok(())
+    }
+
+    fn add_leaves(&mut self, chunk: Vec<(HashValue, V)>) -> Result<()> {
         for (key, value) in chunk {
             if let Some(ref prev_leaf) = self.previous_leaf {
                 ensure!(
@@ -297,14 +350,6 @@ where
             self.previous_leaf.replace(LeafNode::new(key, value));
             self.num_keys_received += 1;
         }
-
-        // Verify what we have added so far is all correct.
-        self.verify(proof)?;
-
-        // Write the frozen nodes to storage.
-        self.store.write_node_batch(&self.frozen_nodes)?;
-        self.frozen_nodes.clear();
-
         Ok(())
     }
 
@@ -592,11 +637,14 @@ where
             current_hash = SparseMerkleInternalNode::new(left_hash, right_hash).hash();
         }
 
+        let expected_root_hash = self
+            .expected_root_hash
+            .expect("Only chunks with proofs are verified.");
         ensure!(
-            current_hash == self.expected_root_hash,
+            current_hash == expected_root_hash,
             "Root hashes do not match. Actual root hash: {:x}. Expected root hash: {:x}.",
             current_hash,
-            self.expected_root_hash,
+            expected_root_hash,
         );
 
         Ok(())
@@ -646,7 +694,15 @@ where
 
     /// Finishes the restoration process. This tells the code that there is no more account,
     /// otherwise we can not freeze the rightmost leaf and its ancestors.
-    pub fn finish(mut self) -> Result<()> {
+    pub fn finish(self) -> Result<()> {
+        self.finish_with_root_hash().map(|_| ())
+    }
+
+    /// Same as `finish`, also returning the root hash of the tree, which is how a bulk import
+    /// learns it.
+    pub fn finish_with_root_hash(mut self) -> Result<HashValue> {
+        ensure!(self.previous_leaf.is_some(), "No accounts were added.");
+
         // Deal with the special case when the entire tree has a single leaf.
         if self.partial_nodes.len() == 1 {
             let mut num_children = 0;
@@ -663,15 +719,22 @@ where
             if num_children == 1 {
                 if let Some(node) = leaf {
                     let node_key = NodeKey::new_empty_path(self.version);
+                    let root_hash = node.hash();
                     assert!(self.frozen_nodes.is_empty());
                     self.frozen_nodes.insert(node_key, node.into());
                     self.store.write_node_batch(&self.frozen_nodes)?;
-                    return Ok(());
+                    return Ok(root_hash);
                 }
             }
         }
 
         self.freeze(0);
-        self.store.write_node_batch(&self.frozen_nodes)
+        let root_hash = self
+            .frozen_nodes
+            .get(&NodeKey::new_empty_path(self.version))
+            .expect("Root node must be frozen in the end.")
+            .hash();
+        self.store.write_node_batch(&self.frozen_nodes)?;
+        Ok(root_hash)
     }
 }
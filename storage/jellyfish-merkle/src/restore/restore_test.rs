@@ -89,6 +89,53 @@ proptest! {
         // overwrite, an entirely different tree
         restore_without_interruption(&btree2, target_version, &restore_db, false);
     }
+
+    #[test]
+    fn test_bulk_import(
+        btree in btree_map(any::<HashValue>(), any::<ValueBlob>(), 1..1000),
+        chunk_size in 1usize..100,
+        target_version in 0u64..2000,
+    ) {
+        let (db, source_version) = init_mock_db(&btree.clone().into_iter().collect());
+        let expected_root_hash = JellyfishMerkleTree::new(&db)
+            .get_root_hash(source_version)
+            .unwrap();
+
+        let target_db = Arc::new(MockTreeStore::default());
+        let mut import =
+            JellyfishMerkleRestore::new_bulk_import(Arc::clone(&target_db), target_version)
+                .unwrap();
+        let kvs: Vec<_> = btree.clone().into_iter().collect();
+        for chunk in kvs.chunks(chunk_size) {
+            import.add_sorted_chunk(chunk.to_vec()).unwrap();
+        }
+        prop_assert_eq!(import.finish_with_root_hash().unwrap(), expected_root_hash);
+
+        assert_success(&target_db, expected_root_hash, &btree, target_version);
+    }
+}
+
+#[test]
+fn test_bulk_import_rejects_unsorted_keys() {
+    let key1 = HashValue::zero();
+    let key2 = HashValue::new([1; HashValue::LENGTH]);
+    let value = ValueBlob::from(vec![1]);
+
+    let mut import =
+        JellyfishMerkleRestore::new_bulk_import(Arc::new(MockTreeStore::default()), 0).unwrap();
+    import
+        .add_sorted_chunk(vec![(key2, value.clone())])
+        .unwrap();
+    assert!(import
+        .add_sorted_chunk(vec![(key1, value.clone())])
+        .is_err());
+    assert!(import.add_sorted_chunk(vec![(key2, value)]).is_err());
+
+    // Nothing to import.
+    let import =
+        JellyfishMerkleRestore::<ValueBlob>::new_bulk_import(Arc::new(MockTreeStore::default()), 0)
+            .unwrap();
+    assert!(import.finish_with_root_hash().is_err());
 }
 
 fn assert_success<V>(
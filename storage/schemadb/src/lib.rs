@@ -40,6 +40,12 @@ pub type ReadOptions = rocksdb::ReadOptions;
 /// Type alias to `rocksdb::Options`.
 pub type Options = rocksdb::Options;
 
+/// Type alias to `rocksdb::BlockBasedOptions`.
+pub type BlockBasedOptions = rocksdb::BlockBasedOptions;
+
+/// Type alias to `rocksdb::DBCompactionStyle`.
+pub type DBCompactionStyle = rocksdb::DBCompactionStyle;
+
 /// Type alias to improve readability.
 pub type ColumnFamilyName = &'static str;
 
@@ -195,6 +201,17 @@ pub struct DB {
     name: &'static str, // for logging
     inner: rocksdb::DB,
     column_families: Vec<ColumnFamilyName>,
+    /// The in-memory file system of a DB opened by `open_in_memory`. Declared after `inner` so
+    /// that it's dropped after the DB is closed.
+    mem_env: Option<MemEnv>,
+}
+
+struct MemEnv(rocksdb::Env);
+
+impl std::fmt::Debug for MemEnv {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MemEnv")
+    }
 }
 
 impl DB {
@@ -205,20 +222,58 @@ impl DB {
         name: &'static str,
         column_families: Vec<ColumnFamilyName>,
         db_opts: &rocksdb::Options,
+    ) -> Result<Self> {
+        Self::open_with_cf_options(
+            path,
+            name,
+            column_families
+                .into_iter()
+                .map(|cf_name| (cf_name, rocksdb::Options::default()))
+                .collect(),
+            db_opts,
+        )
+    }
+
+    /// Like `open`, but each column family is opened with its own options, e.g. tuned to how
+    /// heavily it's written. LZ4 compression is enabled on top of the options given.
+    pub fn open_with_cf_options(
+        path: impl AsRef<Path>,
+        name: &'static str,
+        cf_opts: Vec<(ColumnFamilyName, rocksdb::Options)>,
+        db_opts: &rocksdb::Options,
     ) -> Result<Self> {
         {
-            let cfs_set: HashSet<_> = column_families.iter().collect();
+            let cfs_set: HashSet<_> = cf_opts.iter().map(|(cf_name, _)| cf_name).collect();
             ensure!(
                 cfs_set.contains(&DEFAULT_CF_NAME),
                 "No \"default\" column family name is provided.",
             );
             ensure!(
-                cfs_set.len() == column_families.len(),
+                cfs_set.len() == cf_opts.len(),
                 "Duplicate column family name found.",
             );
         }
 
-        let db = DB::open_cf(db_opts, path, name, column_families)?;
+        let db = DB::open_cf(db_opts, path, name, cf_opts)?;
+        Ok(db)
+    }
+
+    /// Creates a DB whose files are all kept in memory, so nothing touches the disk. Meant for
+    /// tests. Everything is lost once the DB is dropped.
+    pub fn open_in_memory(
+        name: &'static str,
+        cf_opts: Vec<(ColumnFamilyName, rocksdb::Options)>,
+        db_opts: &rocksdb::Options,
+    ) -> Result<Self> {
+        let env = rocksdb::Env::mem_env()?;
+        let mut db_opts = db_opts.clone();
+        db_opts.set_env(&env);
+        db_opts.create_if_missing(true);
+        db_opts.create_missing_column_families(true);
+
+        // The path is only seen by the in-memory file system.
+        let mut db = Self::open_with_cf_options("/in-memory-db", name, cf_opts, &db_opts)?;
+        db.mem_env = Some(MemEnv(env));
         Ok(db)
     }
 
@@ -248,19 +303,26 @@ impl DB {
         DB::open_cf_as_secondary(db_opts, primary_path, secondary_path, name, column_families)
     }
 
+    /// Makes a secondary instance catch up with the primary, so that writes done by the primary
+    /// since the secondary was opened or last caught up become visible.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.inner.try_catch_up_with_primary()?;
+        Ok(())
+    }
+
     fn open_cf(
         db_opts: &rocksdb::Options,
         path: impl AsRef<Path>,
         name: &'static str,
-        column_families: Vec<ColumnFamilyName>,
+        cf_opts: Vec<(ColumnFamilyName, rocksdb::Options)>,
     ) -> Result<DB> {
+        let column_families = cf_opts.iter().map(|(cf_name, _)| *cf_name).collect();
         let inner = rocksdb::DB::open_cf_descriptors(
             db_opts,
             path,
-            column_families.iter().map(|cf_name| {
-                let mut cf_opts = rocksdb::Options::default();
+            cf_opts.into_iter().map(|(cf_name, mut cf_opts)| {
                 cf_opts.set_compression_type(rocksdb::DBCompressionType::Lz4);
-                rocksdb::ColumnFamilyDescriptor::new((*cf_name).to_string(), cf_opts)
+                rocksdb::ColumnFamilyDescriptor::new(cf_name.to_string(), cf_opts)
             }),
         )?;
         Ok(Self::log_construct(name, column_families, inner))
@@ -310,6 +372,7 @@ impl DB {
             name,
             inner,
             column_families,
+            mem_env: None,
         }
     }
 
@@ -383,6 +446,26 @@ impl DB {
 
     /// Writes a group of records wrapped in a [`SchemaBatch`].
     pub fn write_schemas(&self, batch: SchemaBatch) -> Result<()> {
+        self.write_schemas_opt(batch, &default_write_options())
+            .map(|_| ())
+    }
+
+    /// Writes a group of records wrapped in a [`SchemaBatch`] without waiting for the WAL to be
+    /// synced to disk, returning the size of the write. The write is durable once
+    /// [`DB::sync_wal`] returns, or a synced write does.
+    pub fn write_schemas_unsynced(&self, batch: SchemaBatch) -> Result<usize> {
+        self.write_schemas_opt(batch, &rocksdb::WriteOptions::default())
+    }
+
+    /// Syncs the WAL to disk, making all the writes so far durable.
+    pub fn sync_wal(&self) -> Result<()> {
+        // A synced write syncs everything written to the WAL before it, an empty one included.
+        self.inner
+            .write_opt(rocksdb::WriteBatch::default(), &default_write_options())?;
+        Ok(())
+    }
+
+    fn write_schemas_opt(&self, batch: SchemaBatch, opts: &rocksdb::WriteOptions) -> Result<usize> {
         let _timer = DIEM_SCHEMADB_BATCH_COMMIT_LATENCY_SECONDS
             .with_label_values(&[self.name])
             .start_timer();
@@ -399,7 +482,7 @@ impl DB {
         }
         let serialized_size = db_batch.size_in_bytes();
 
-        self.inner.write_opt(db_batch, &default_write_options())?;
+        self.inner.write_opt(db_batch, opts)?;
 
         // Bump counters only after DB write succeeds.
         for (cf_name, rows) in &batch.rows {
@@ -420,7 +503,7 @@ impl DB {
             .with_label_values(&[self.name])
             .observe(serialized_size as f64);
 
-        Ok(())
+        Ok(serialized_size)
     }
 
     fn get_cf_handle(&self, cf_name: &str) -> Result<&rocksdb::ColumnFamily> {
@@ -453,6 +536,12 @@ impl DB {
                 )
             })
     }
+
+    /// Returns the dump of RocksDB statistics, None if they are not enabled in the options the
+    /// DB was opened with.
+    pub fn get_statistics(&self) -> Result<Option<String>> {
+        Ok(self.inner.property_value("rocksdb.options-statistics")?)
+    }
 }
 
 /// For now we always use synchronous writes. This makes sure that once the operation returns
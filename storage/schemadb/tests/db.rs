@@ -319,6 +319,62 @@ fn test_reopen() {
     }
 }
 
+#[test]
+fn test_open_with_cf_options() {
+    let tmpdir = diem_temppath::TempPath::new();
+    let mut db_opts = rocksdb::Options::default();
+    db_opts.create_if_missing(true);
+    db_opts.create_missing_column_families(true);
+    let cf_opts = || {
+        get_column_families()
+            .into_iter()
+            .map(|cf_name| {
+                let mut opts = rocksdb::Options::default();
+                if cf_name == TestSchema1::COLUMN_FAMILY_NAME {
+                    opts.set_compaction_style(rocksdb::DBCompactionStyle::Universal);
+                    opts.set_write_buffer_size(1 << 20);
+                }
+                (cf_name, opts)
+            })
+            .collect::<Vec<_>>()
+    };
+    {
+        let db = DB::open_with_cf_options(&tmpdir.path(), "test", cf_opts(), &db_opts).unwrap();
+        db.put::<TestSchema1>(&TestField(0), &TestField(0)).unwrap();
+    }
+    {
+        let db = DB::open_with_cf_options(&tmpdir.path(), "test", cf_opts(), &db_opts).unwrap();
+        assert_eq!(
+            db.get::<TestSchema1>(&TestField(0)).unwrap(),
+            Some(TestField(0)),
+        );
+    }
+
+    let mut duplicated = cf_opts();
+    duplicated.push((TestSchema1::COLUMN_FAMILY_NAME, rocksdb::Options::default()));
+    assert!(DB::open_with_cf_options(&tmpdir.path(), "test", duplicated, &db_opts).is_err());
+}
+
+#[test]
+fn test_open_in_memory() {
+    let cf_opts = || {
+        get_column_families()
+            .into_iter()
+            .map(|cf_name| (cf_name, rocksdb::Options::default()))
+            .collect::<Vec<_>>()
+    };
+    let db = DB::open_in_memory("test", cf_opts(), &rocksdb::Options::default()).unwrap();
+    db.put::<TestSchema1>(&TestField(0), &TestField(0)).unwrap();
+    assert_eq!(
+        db.get::<TestSchema1>(&TestField(0)).unwrap(),
+        Some(TestField(0)),
+    );
+
+    // Each in-memory DB is on its own.
+    let db2 = DB::open_in_memory("test", cf_opts(), &rocksdb::Options::default()).unwrap();
+    assert_eq!(db2.get::<TestSchema1>(&TestField(0)).unwrap(), None);
+}
+
 #[test]
 fn test_open_read_only() {
     let tmpdir = diem_temppath::TempPath::new();
@@ -349,6 +405,20 @@ fn test_open_as_secondary() {
         db_sec.get::<TestSchema1>(&TestField(0)).unwrap(),
         Some(TestField(0)),
     );
+
+    // Writes after opening are not visible until catching up.
+    db.put::<TestSchema1>(&TestField(1), &TestField(1)).unwrap();
+    assert_eq!(db_sec.get::<TestSchema1>(&TestField(1)).unwrap(), None);
+    db_sec.try_catch_up_with_primary().unwrap();
+    assert_eq!(
+        db_sec.get::<TestSchema1>(&TestField(1)).unwrap(),
+        Some(TestField(1)),
+    );
+
+    // Secondary is read only.
+    assert!(db_sec
+        .put::<TestSchema1>(&TestField(2), &TestField(2))
+        .is_err());
 }
 
 #[test]
@@ -1,26 +1,35 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{format_err, Result};
-use diem_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
+use anyhow::{ensure, format_err, Result};
+use diem_crypto::{
+    hash::{CryptoHash, TransactionAccumulatorHasher, SPARSE_MERKLE_PLACEHOLDER_HASH},
+    HashValue,
+};
 use diem_types::{
     access_path::AccessPath,
     account_address::AccountAddress,
     account_state::AccountState,
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
+    block_metadata::BlockMetadata,
     contract_event::{ContractEvent, EventWithProof},
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
     move_resource::MoveStorage,
-    proof::{definition::LeafCount, AccumulatorConsistencyProof, SparseMerkleProof},
+    proof::{
+        accumulator::InMemoryAccumulator, definition::LeafCount, AccumulatorConsistencyProof,
+        SparseMerkleProof, SparseMerkleRangeProof, TransactionInfoWithProof,
+    },
     transaction::{
         TransactionInfo, TransactionListWithProof, TransactionToCommit, TransactionWithProof,
         Version,
     },
+    waypoint::Waypoint,
 };
 use itertools::Itertools;
+use move_core_types::language_storage::TypeTag;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
@@ -124,6 +133,75 @@ impl TreeState {
     }
 }
 
+/// What a node needs, besides the accounts, to start from the state at an epoch ending version
+/// instead of replaying the transactions up to that version.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateSnapshotInfo {
+    /// The epoch ending ledger info at the version of the snapshot.
+    pub ledger_info: LedgerInfoWithSignatures,
+    /// The transaction info at the version of the snapshot, proven against `ledger_info`.
+    pub txn_info_with_proof: TransactionInfoWithProof,
+    /// Roots of the frozen subtrees of the transaction accumulator before the version of the
+    /// snapshot, from left to right.
+    pub frozen_subtrees: Vec<HashValue>,
+}
+
+impl StateSnapshotInfo {
+    pub fn version(&self) -> Version {
+        self.ledger_info.ledger_info().version()
+    }
+
+    /// The root hash of the state tree the accounts of the snapshot are proven against.
+    pub fn state_root_hash(&self) -> HashValue {
+        self.txn_info_with_proof
+            .transaction_info()
+            .state_root_hash()
+    }
+
+    /// Verifies the snapshot against `waypoint`, which must be the one of its ledger info.
+    pub fn verify(&self, waypoint: &Waypoint) -> Result<()> {
+        let ledger_info = self.ledger_info.ledger_info();
+        let version = ledger_info.version();
+        waypoint.verify(ledger_info)?;
+        ensure!(
+            ledger_info.ends_epoch(),
+            "Ledger info of the snapshot at version {} doesn't end an epoch.",
+            version,
+        );
+        self.txn_info_with_proof.verify(ledger_info, version)?;
+        let accumulator = InMemoryAccumulator::<TransactionAccumulatorHasher>::new(
+            self.frozen_subtrees.clone(),
+            version,
+        )?
+        .append(&[self.txn_info_with_proof.transaction_info().hash()]);
+        ensure!(
+            accumulator.root_hash() == ledger_info.transaction_accumulator_hash(),
+            "Frozen subtrees of the snapshot don't match its ledger info.",
+        );
+        Ok(())
+    }
+}
+
+/// Consecutive accounts of the state at the version of a snapshot, in the order of their keys,
+/// with a range proof against the state root hash.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateSnapshotChunk {
+    pub accounts: Vec<(HashValue, AccountStateBlob)>,
+    pub proof: SparseMerkleRangeProof,
+    /// Whether the chunk holds the last account of the state.
+    pub is_last: bool,
+}
+
+/// Restores the state of a snapshot chunk by chunk, each verified against the state root hash
+/// before it's written.
+pub trait StateSnapshotReceiver: Send {
+    fn add_chunk(&mut self, chunk: StateSnapshotChunk) -> Result<()>;
+
+    /// Finishes the restoration once the last chunk is added, verifying that the state restored
+    /// has the expected root hash.
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
 #[derive(Debug, Deserialize, Error, PartialEq, Serialize)]
 pub enum Error {
     #[error("Service error: {:?}", error)]
@@ -194,6 +272,43 @@ pub trait DbReader: Send + Sync {
         limit: u64,
     ) -> Result<Vec<(u64, ContractEvent)>>;
 
+    /// Returns up to `limit` events of type `type_tag` emitted by transactions with versions from
+    /// `start_version` to `ledger_version`, in ascending order, along with the versions of those
+    /// transactions. Requires the event type index to be enabled.
+    fn get_events_by_type(
+        &self,
+        _type_tag: &TypeTag,
+        _start_version: Version,
+        _limit: u64,
+        _ledger_version: Version,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        unimplemented!()
+    }
+
+    /// Like `get_events_by_type`, returns the events involving `address`: the events on its event
+    /// streams and the payments from or to it. Requires the event indexer to be enabled.
+    fn get_events_by_account(
+        &self,
+        _address: AccountAddress,
+        _start_version: Version,
+        _limit: u64,
+        _ledger_version: Version,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        unimplemented!()
+    }
+
+    /// Like `get_events_by_type`, returns the payment, mint, burn and exchange rate update events
+    /// of the currency `currency_code`. Requires the event indexer to be enabled.
+    fn get_events_by_currency(
+        &self,
+        _currency_code: &str,
+        _start_version: Version,
+        _limit: u64,
+        _ledger_version: Version,
+    ) -> Result<Vec<(u64, ContractEvent)>> {
+        unimplemented!()
+    }
+
     /// Returns events by given event key
     fn get_events_with_proofs(
         &self,
@@ -204,12 +319,29 @@ pub trait DbReader: Send + Sync {
         known_version: Option<u64>,
     ) -> Result<Vec<EventWithProof>>;
 
+    /// Returns the sequence number of the first event on `event_key` emitted at or after
+    /// `version`, or `None` if there is no such event.
+    fn get_first_event_sequence_number_at_or_after_version(
+        &self,
+        _event_key: &EventKey,
+        _version: Version,
+    ) -> Result<Option<u64>> {
+        unimplemented!()
+    }
+
     /// See [`DiemDB::get_block_timestamp`].
     ///
     /// [`DiemDB::get_block_timestamp`]:
     /// ../diemdb/struct.DiemDB.html#method.get_block_timestamp
     fn get_block_timestamp(&self, version: u64) -> Result<u64>;
 
+    /// Returns the metadata of the block the transaction at `version` is in, together with the
+    /// version of the block metadata transaction starting the block. Returns `None` for
+    /// transactions before the first block, like genesis.
+    fn get_block_metadata(&self, _version: Version) -> Result<Option<(Version, BlockMetadata)>> {
+        unimplemented!()
+    }
+
     /// Gets the version of the last transaction committed before timestamp,
     /// a commited block at or after the required timestamp must exist (otherwise it's possible
     /// the next block committed as a timestamp smaller than the one in the request).
@@ -221,6 +353,12 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Gets the earliest version whose transaction, events and account states are all still
+    /// available, i.e. everything before it might have been pruned.
+    fn get_earliest_available_version(&self) -> Result<Version> {
+        unimplemented!()
+    }
+
     /// See [`DiemDB::get_latest_account_state`].
     ///
     /// [`DiemDB::get_latest_account_state`]:
@@ -258,6 +396,21 @@ pub trait DbReader: Send + Sync {
         fetch_events: bool,
     ) -> Result<Option<TransactionWithProof>>;
 
+    /// Returns up to `limit` transactions sent by `address` and committed at or before
+    /// `ledger_version`, from sequence number `start_seq_num` on in `order`. In descending order, a
+    /// `start_seq_num` beyond the latest sequence number starts from the latest transaction.
+    fn get_account_transactions(
+        &self,
+        _address: AccountAddress,
+        _start_seq_num: u64,
+        _order: Order,
+        _limit: u64,
+        _ledger_version: Version,
+        _fetch_events: bool,
+    ) -> Result<Vec<TransactionWithProof>> {
+        unimplemented!()
+    }
+
     /// Returns proof of new state for a given ledger info with signatures relative to version known
     /// to client
     fn get_state_proof_with_ledger_info(
@@ -327,6 +480,23 @@ pub trait DbReader: Send + Sync {
     fn get_accumulator_root_hash(&self, _version: Version) -> Result<HashValue> {
         unimplemented!()
     }
+
+    /// Gets what a node needs, besides the accounts, to start from the state at `version`, which
+    /// must end an epoch.
+    fn get_state_snapshot_info(&self, _version: Version) -> Result<StateSnapshotInfo> {
+        unimplemented!()
+    }
+
+    /// Gets at most `limit` accounts of the state at `version`, starting with the first one after
+    /// `start_after`, or with the very first one if it's `None`.
+    fn get_state_snapshot_chunk(
+        &self,
+        _version: Version,
+        _start_after: Option<HashValue>,
+        _limit: usize,
+    ) -> Result<StateSnapshotChunk> {
+        unimplemented!()
+    }
 }
 
 impl MoveStorage for &dyn DbReader {
@@ -404,6 +574,21 @@ pub trait DbWriter: Send + Sync {
         first_version: Version,
         ledger_info_with_sigs: Option<&LedgerInfoWithSignatures>,
     ) -> Result<()>;
+
+    /// Gets a receiver restoring the state of the verified snapshot `info`. The DB must be behind
+    /// the version of the snapshot.
+    fn get_state_snapshot_receiver(
+        &self,
+        _info: &StateSnapshotInfo,
+    ) -> Result<Box<dyn StateSnapshotReceiver>> {
+        unimplemented!()
+    }
+
+    /// Makes the DB start from the verified snapshot `info`, once its state is fully restored:
+    /// the transaction at its version becomes the latest one, and its ledger info the latest one.
+    fn finalize_state_snapshot(&self, _info: &StateSnapshotInfo) -> Result<()> {
+        unimplemented!()
+    }
 }
 
 #[derive(Clone)]
@@ -120,6 +120,19 @@ impl Mnemonic {
         Ok(Mnemonic(mnemonic))
     }
 
+    /// Words of the word list starting with `prefix`. A complete word only matches itself, even
+    /// if it is the prefix of other words.
+    pub fn complete_word(prefix: &str) -> Vec<&'static str> {
+        if let Ok(idx) = WORDS.binary_search(&prefix) {
+            return vec![WORDS[idx]];
+        }
+        WORDS
+            .iter()
+            .copied()
+            .filter(|word| word.starts_with(prefix))
+            .collect()
+    }
+
     /// Write mnemonic to output_file_path.
     pub fn write(&self, output_file_path: &Path) -> Result<()> {
         if output_file_path.exists() && !output_file_path.is_file() {
@@ -468,6 +481,14 @@ fn test_roundtrip_mnemonic() {
     assert_eq!(mnemonic.to_string(), other_mnemonic.to_string());
 }
 
+#[test]
+fn test_complete_word() {
+    assert_eq!(Mnemonic::complete_word("zo"), vec!["zone", "zoo"]);
+    assert_eq!(Mnemonic::complete_word("zeb"), vec!["zebra"]);
+    assert_eq!(Mnemonic::complete_word("act"), vec!["act"]);
+    assert!(Mnemonic::complete_word("qx").is_empty());
+}
+
 #[test]
 fn test_deterministic_mnemonic() {
     let zeros_entropy: [u8; 32] = [0; 32];
@@ -4,7 +4,9 @@
 use crate::{
     client_proxy::ClientProxy,
     commands::{blocking_cmd, report_error, subcommand_execute, Command},
+    tr,
 };
+use anyhow::{bail, ensure, Context, Result};
 
 /// Major command for account related operations.
 pub struct AccountCommand {}
@@ -14,15 +16,16 @@ impl Command for AccountCommand {
         vec!["account", "a"]
     }
     fn get_description(&self) -> &'static str {
-        "Account operations"
+        tr!("account.description")
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        let commands: Vec<Box<dyn Command>> = vec![
+    fn get_subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![
             Box::new(AccountCommandCreateLocal {}),
             Box::new(AccountCommandListAccounts {}),
             Box::new(AccountCommandRecoverWallet {}),
             Box::new(AccountCommandWriteRecovery {}),
             Box::new(AccountCommandMint {}),
+            Box::new(AccountCommandMintBatch {}),
             Box::new(AccountCommandAddCurrency {}),
             //////// 0L ////////
             Box::new(AccountCommandCreateUser {}),
@@ -32,9 +35,10 @@ impl Command for AccountCommand {
             Box::new(AccountCommandAutopayBatch {}),
             Box::new(AccountCommandUpdateValConfig {}),
             Box::new(AccountCommandSetOperator {}),
-        ];
-
-        subcommand_execute(&params[0], commands, client, &params[1..]);
+        ]
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        subcommand_execute(&params[0], self.get_subcommands(), client, &params[1..])
     }
 }
 
@@ -46,22 +50,27 @@ impl Command for AccountCommandCreateLocal {
         vec!["create", "c"]
     }
     fn get_description(&self) -> &'static str {
-        "Create a local account--no on-chain effect. Returns reference ID to use in other operations"
-    }
-    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) {
-        println!(">> Creating/retrieving next local account from wallet");
-        match client.create_next_account(true) {
-            Ok(account_data) => println!(
-                "Created/retrieved local account #{} address {}",
+        tr!("account.create.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) -> Result<()> {
+        println!("{}", tr!("account.create.progress"));
+        let account_data = client
+            .create_next_account(true)
+            .context(tr!("account.create.error"))?;
+        println!(
+            "{}",
+            tr!(
+                "account.create.done",
                 account_data.index,
                 hex::encode(account_data.address)
-            ),
-            Err(e) => report_error("Error creating local account", e),
-        }
+            )
+        );
+        Ok(())
     }
 }
 
-/// Sub command to recover wallet from the file specified.
+/// Sub command to recover wallet from the file specified, or from a mnemonic typed at a hidden
+/// prompt.
 pub struct AccountCommandRecoverWallet {}
 
 impl Command for AccountCommandRecoverWallet {
@@ -69,25 +78,32 @@ impl Command for AccountCommandRecoverWallet {
         vec!["recover", "r"]
     }
     fn get_params_help(&self) -> &'static str {
-        "<file_path>"
+        "<file_path> | --interactive [--words <12|15|18|21|24>] [output_file_path]"
     }
     fn get_description(&self) -> &'static str {
-        "Recover Diem wallet from the file path"
-    }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        println!(">> Recovering Wallet");
-        match client.recover_wallet_accounts(&params) {
-            Ok(account_data) => {
-                println!(
-                    "Wallet recovered and the first {} child accounts were derived",
-                    account_data.len()
-                );
-                for data in account_data {
-                    println!("#{} address {}", data.index, hex::encode(data.address));
-                }
-            }
-            Err(e) => report_error("Error recovering Diem wallet", e),
+        tr!("account.recover.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        println!("{}", tr!("account.recover.progress"));
+        let result = if params.get(1) == Some(&"--interactive") {
+            println!("{}", tr!("account.recover.interactive_hint"));
+            client.recover_wallet_accounts_interactive(&params)
+        } else {
+            client.recover_wallet_accounts(&params)
+        };
+        let account_data = result.context(tr!("main.error_recovering_wallet"))?;
+        println!("{}", tr!("main.wallet_recovered", account_data.len()));
+        for data in account_data {
+            println!(
+                "{}",
+                tr!(
+                    "account.wallet_entry",
+                    data.index,
+                    hex::encode(data.address)
+                )
+            );
         }
+        Ok(())
     }
 }
 
@@ -102,14 +118,15 @@ impl Command for AccountCommandWriteRecovery {
         "<file_path>"
     }
     fn get_description(&self) -> &'static str {
-        "Save Diem wallet mnemonic recovery seed to disk"
+        tr!("account.write.description")
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        println!(">> Saving Diem wallet mnemonic recovery seed to disk");
-        match client.write_recovery(&params) {
-            Ok(_) => println!("Saved mnemonic seed to disk"),
-            Err(e) => report_error("Error writing mnemonic recovery seed to file", e),
-        }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        println!("{}", tr!("account.write.progress"));
+        client
+            .write_recovery(&params)
+            .context(tr!("account.write.error"))?;
+        println!("{}", tr!("account.write.done"));
+        Ok(())
     }
 }
 
@@ -121,10 +138,11 @@ impl Command for AccountCommandListAccounts {
         vec!["list", "la"]
     }
     fn get_description(&self) -> &'static str {
-        "Print all accounts that were created or loaded"
+        tr!("account.list.description")
     }
-    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) -> Result<()> {
         client.print_all_accounts();
+        Ok(())
     }
 }
 
@@ -139,19 +157,52 @@ impl Command for AccountCommandMint {
         "<receiver_account_ref_id>|<receiver_account_address> <number_of_coins> <currency_code> [use_base_units (default=false)]"
     }
     fn get_description(&self) -> &'static str {
-        "Send currency of the given type from the faucet address to the given recipient address. Creates an account at the recipient address if one does not already exist."
+        tr!("account.mint.description")
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         if params.len() < 4 || params.len() > 5 {
-            println!("Invalid number of arguments for mint");
-            return;
+            bail!(tr!("account.mint.invalid_args"));
         }
-        match client.mint_coins(&params, true) {
-            Ok(_) => {
-                println!("Finished sending coins from faucet!");
+        client
+            .mint_coins(&params, true)
+            .context(tr!("account.mint.error"))?;
+        println!("{}", tr!("account.mint.done"));
+        Ok(())
+    }
+}
+
+/// Sub command to fund every account listed in a file through the faucet service.
+pub struct AccountCommandMintBatch {}
+
+impl Command for AccountCommandMintBatch {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["mint_batch", "mbatch"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<accounts_file> <number_of_coins> <currency_code> [concurrency]"
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("account.mint_batch.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.len() < 4 || params.len() > 5 {
+            bail!(tr!("account.mint_batch.invalid_args"));
+        }
+        let results = client
+            .mint_coins_batch(params)
+            .context(tr!("account.mint.error"))?;
+        let total = results.len();
+        let mut funded = 0;
+        for (receiver, result) in results {
+            match result {
+                Ok(()) => funded += 1,
+                Err(e) => report_error(&tr!("account.mint_batch.failed", receiver), e),
             }
-            Err(e) => report_error("Error transferring coins from faucet", e),
         }
+        let done = tr!("account.mint_batch.done", funded, total);
+        ensure!(funded == total, done);
+        println!("{}", done);
+        Ok(())
     }
 }
 
@@ -166,25 +217,23 @@ impl Command for AccountCommandAddCurrency {
         "<account_address> <currency_code>"
     }
     fn get_description(&self) -> &'static str {
-        "Add specified currency to the account. Suffix 'b' is for blocking"
+        tr!("account.add_currency.description")
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         if params.len() < 3 {
-            println!("Invalid number of arguments for adding currency to account");
-            return;
+            bail!(tr!("account.add_currency.invalid_args"));
         }
-        println!(">> Adding zero balance in currency to account");
+        println!("{}", tr!("account.add_currency.progress"));
         let is_blocking = blocking_cmd(params[0]);
-        match client.add_currency(&params, is_blocking) {
-            Ok(_) => {
-                if is_blocking {
-                    println!("Finished adding currency to account!");
-                } else {
-                    println!("Currency addition request submitted");
-                }
-            }
-            Err(e) => report_error("Error adding zero balance in currency to account", e),
+        client
+            .add_currency(&params, is_blocking)
+            .context(tr!("account.add_currency.error"))?;
+        if is_blocking {
+            println!("{}", tr!("account.add_currency.done"));
+        } else {
+            println!("{}", tr!("account.add_currency.submitted"));
         }
+        Ok(())
     }
 }
 
@@ -197,16 +246,17 @@ impl Command for AccountCommandCreateUser {
         vec!["create_user", "cu"]
     }
     fn get_description(&self) -> &'static str {
-        "Create on-chain user account from proof"
+        tr!("account.create_user.description")
     }
     fn get_params_help(&self) -> &'static str {
         "<sending_account> <path_to_proof_file>"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        match client.create_user(params, true) {
-            Ok(()) => println!("Created account"),
-            Err(e) => report_error("Error creating user account", e),
-        }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        client
+            .create_user(params, true)
+            .context(tr!("account.create_user.error"))?;
+        println!("{}", tr!("account.create_user.done"));
+        Ok(())
     }
 }
 
@@ -220,16 +270,17 @@ impl Command for AccountCommandCreateVal {
         vec!["create_val", "cv"]
     }
     fn get_description(&self) -> &'static str {
-        "Create on-chain user account and configure validator"
+        tr!("account.create_val.description")
     }
     fn get_params_help(&self) -> &'static str {
         "<sending_account> <path_to_proof_file>"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        match client.create_val(params, true) {
-            Ok(()) => println!("Created account"),
-            Err(e) => report_error("Error creating user account", e),
-        }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        client
+            .create_val(params, true)
+            .context(tr!("account.create_user.error"))?;
+        println!("{}", tr!("account.create_user.done"));
+        Ok(())
     }
 }
 
@@ -242,16 +293,17 @@ impl Command for AccountCommandUpdateValConfig {
         vec!["update_val_config", "uvc"]
     }
     fn get_description(&self) -> &'static str {
-        "Operator updates a val config"
+        tr!("account.update_val_config.description")
     }
     fn get_params_help(&self) -> &'static str {
         "<sending_account> <path_to_account_file>"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        match client.update_val_configs(params, true) {
-            Ok(()) => println!("Val configs updated"),
-            Err(e) => report_error("Error updating val configs", e),
-        }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        client
+            .update_val_configs(params, true)
+            .context(tr!("account.update_val_config.error"))?;
+        println!("{}", tr!("account.update_val_config.done"));
+        Ok(())
     }
 }
 
@@ -264,16 +316,17 @@ impl Command for AccountCommandSetOperator {
         vec!["set_operator", "so"]
     }
     fn get_description(&self) -> &'static str {
-        "Validator picks a new operator"
+        tr!("account.set_operator.description")
     }
     fn get_params_help(&self) -> &'static str {
         "<sending_account> <path_to_account_file>"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        match client.set_operator(params, true) {
-            Ok(()) => println!("Operator updated"),
-            Err(e) => report_error("Error updating operator", e),
-        }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        client
+            .set_operator(params, true)
+            .context(tr!("account.set_operator.error"))?;
+        println!("{}", tr!("account.set_operator.done"));
+        Ok(())
     }
 }
 
@@ -286,21 +339,22 @@ impl Command for AccountCommandAutopayEnable {
         vec!["autopay_enable", "ae"]
     }
     fn get_description(&self) -> &'static str {
-        "Enables Autopay functionality on an account"
+        tr!("account.autopay_enable.description")
     }
     fn get_params_help(&self) -> &'static str {
         "<sending_account>"
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         assert!(
             params.len() == 2,
             "Invalid number of arguments to enable autopay. Did you pass your account address?"
         );
 
-        match client.autopay_enable(params[1]) {
-            Ok(()) => println!("Enabled Autopay"),
-            Err(e) => report_error("Error creating local account", e),
-        }
+        client
+            .autopay_enable(params[1])
+            .context(tr!("account.autopay_enable.error"))?;
+        println!("{}", tr!("account.autopay_enable.done"));
+        Ok(())
     }
 }
 
@@ -313,17 +367,18 @@ impl Command for AccountCommandAutopayCreate {
         vec!["autopay_instruction", "ai"]
     }
     fn get_description(&self) -> &'static str {
-        "Creates Autopay instruction"
+        tr!("account.autopay_create.description")
     }
     fn get_params_help(&self) -> &'static str {
         "<sending_account> <instruction_id> <payee_account> <end_epoch> <percent_integer>"
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        match client.autopay_create(params, true) {
-            Ok(()) => println!("Created autopay instruction"),
-            Err(e) => report_error("Error on autopay instruction tx", e),
-        }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        client
+            .autopay_create(params, true)
+            .context(tr!("account.autopay_create.error"))?;
+        println!("{}", tr!("account.autopay_create.done"));
+        Ok(())
     }
 }
 
@@ -336,13 +391,13 @@ impl Command for AccountCommandAutopayBatch {
         vec!["autopay_batch", "ab"]
     }
     fn get_description(&self) -> &'static str {
-        "Batches Autopay instructions from file."
+        tr!("account.autopay_batch.description")
     }
     fn get_params_help(&self) -> &'static str {
         "<file path>"
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         // do loop in here
         let file = std::fs::File::open(params[1])
             .expect("file should open read only");
@@ -370,11 +425,12 @@ impl Command for AccountCommandAutopayBatch {
             }
         }).collect();
 
-        match client.autopay_enable("0") {
-            Ok(()) => println!("Autopay enabled"),
-            Err(e) => report_error("error creating local account", e),
-        }
+        client
+            .autopay_enable("0")
+            .context(tr!("account.autopay_enable.error"))?;
+        println!("{}", tr!("account.autopay_enable.done"));
 
+        let mut failed = 0;
         for inst in list {
             let in_type_u8 = match inst.in_type {
                 0 => 0,
@@ -392,10 +448,15 @@ impl Command for AccountCommandAutopayBatch {
                 inst.end_epoch,
                 inst.percent
             ){
-                Ok(()) => println!("Submitted autopay batch instruction, uid: {}", inst.uid),
-                Err(e) => report_error("Error submitting batch autopay", e),
+                Ok(()) => println!("{}", tr!("account.autopay_batch.submitted", inst.uid)),
+                Err(e) => {
+                    report_error(tr!("account.autopay_batch.error"), e);
+                    failed += 1;
+                }
             }
         }
+        ensure!(failed == 0, tr!("account.autopay_batch.failed", failed));
+        Ok(())
     }
 }
 
@@ -0,0 +1,206 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, ensure, format_err, Result};
+use diem_crypto::HashValue;
+use diem_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// File name of the address book inside the CLI config directory.
+pub const ADDRESS_BOOK_FILE: &str = "addressbook.json";
+/// Prefix marking an alias wherever the CLI expects an account.
+pub const ALIAS_PREFIX: char = '@';
+
+/// Number of hash bytes kept in an address checksum.
+const CHECKSUM_LENGTH: usize = 4;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct AddressBookEntry {
+    address: AccountAddress,
+    checksum: String,
+}
+
+/// Named aliases for account addresses, persisted as JSON.
+///
+/// Every entry carries a checksum of its address so that a mistyped or corrupted address in a
+/// hand-edited file is rejected instead of silently receiving funds.
+#[derive(Debug, Default)]
+pub struct AddressBook {
+    /// Where the book is saved, `None` keeps it in memory only.
+    path: Option<PathBuf>,
+    entries: BTreeMap<String, AddressBookEntry>,
+}
+
+impl AddressBook {
+    /// Load the address book at `path`, starting an empty one if the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        let entries: BTreeMap<String, AddressBookEntry> = if path.exists() {
+            serde_json::from_slice(&fs::read(path)?)
+                .map_err(|e| format_err!("Malformed address book {:?}: {}", path, e))?
+        } else {
+            BTreeMap::new()
+        };
+        for (alias, entry) in &entries {
+            validate_alias(alias)?;
+            verify_checksum(&entry.address, &entry.checksum)
+                .map_err(|e| format_err!("Address book entry {:?} in {:?}: {}", alias, path, e))?;
+        }
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            entries,
+        })
+    }
+
+    /// Add or replace `alias`. If `checksum` is given it must match the address.
+    pub fn add(
+        &mut self,
+        alias: &str,
+        address: AccountAddress,
+        checksum: Option<&str>,
+    ) -> Result<()> {
+        validate_alias(alias)?;
+        if let Some(checksum) = checksum {
+            verify_checksum(&address, checksum)?;
+        }
+        self.entries.insert(
+            alias.to_string(),
+            AddressBookEntry {
+                address,
+                checksum: address_checksum(&address),
+            },
+        );
+        self.save()
+    }
+
+    /// Remove `alias`, failing if it is unknown.
+    pub fn remove(&mut self, alias: &str) -> Result<()> {
+        self.entries
+            .remove(alias)
+            .ok_or_else(|| format_err!("Unknown alias: {}{}", ALIAS_PREFIX, alias))?;
+        self.save()
+    }
+
+    /// Look up the address stored under `alias`.
+    pub fn resolve(&self, alias: &str) -> Result<AccountAddress> {
+        self.entries
+            .get(alias)
+            .map(|entry| entry.address)
+            .ok_or_else(|| {
+                format_err!(
+                    "Unknown alias: {}{}, to see all aliases, run: 'addressbook list'",
+                    ALIAS_PREFIX,
+                    alias
+                )
+            })
+    }
+
+    /// All entries as (alias, address, checksum), sorted by alias.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &AccountAddress, &str)> {
+        self.entries
+            .iter()
+            .map(|(alias, entry)| (alias.as_str(), &entry.address, entry.checksum.as_str()))
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, serde_json::to_vec_pretty(&self.entries)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Short checksum of `address`: the hex encoded leading bytes of its SHA3-256 hash.
+pub fn address_checksum(address: &AccountAddress) -> String {
+    hex::encode(&HashValue::sha3_256_of(address.as_ref()).as_ref()[..CHECKSUM_LENGTH])
+}
+
+fn verify_checksum(address: &AccountAddress, checksum: &str) -> Result<()> {
+    let expected = address_checksum(address);
+    ensure!(
+        checksum.eq_ignore_ascii_case(&expected),
+        "Checksum mismatch for address {}: expected {}, got {}",
+        address,
+        expected,
+        checksum
+    );
+    Ok(())
+}
+
+fn validate_alias(alias: &str) -> Result<()> {
+    if alias.is_empty()
+        || !alias
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        bail!(
+            "Invalid alias: {:?}, only letters, digits, '_' and '-' are allowed",
+            alias
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diem_temppath::TempPath;
+
+    fn address(byte: u8) -> AccountAddress {
+        AccountAddress::new([byte; AccountAddress::LENGTH])
+    }
+
+    #[test]
+    fn test_add_resolve_remove() {
+        let mut book = AddressBook::default();
+        book.add("alice", address(1), None).unwrap();
+        assert_eq!(book.resolve("alice").unwrap(), address(1));
+        assert!(book.resolve("bob").is_err());
+
+        book.remove("alice").unwrap();
+        assert!(book.resolve("alice").is_err());
+        assert!(book.remove("alice").is_err());
+    }
+
+    #[test]
+    fn test_checksum_and_alias_validation() {
+        let mut book = AddressBook::default();
+        let checksum = address_checksum(&address(1));
+        book.add("alice", address(1), Some(&checksum.to_uppercase()))
+            .unwrap();
+        assert!(book.add("bob", address(2), Some(&checksum)).is_err());
+        assert!(book.add("not an alias", address(2), None).is_err());
+        assert!(book.add("", address(2), None).is_err());
+    }
+
+    #[test]
+    fn test_persistence() {
+        let file = TempPath::new();
+        let mut book = AddressBook::load(file.path()).unwrap();
+        book.add("alice", address(1), None).unwrap();
+
+        let book = AddressBook::load(file.path()).unwrap();
+        assert_eq!(book.resolve("alice").unwrap(), address(1));
+        assert_eq!(book.entries().count(), 1);
+    }
+
+    #[test]
+    fn test_load_rejects_bad_checksum() {
+        let file = TempPath::new();
+        let mut book = AddressBook::load(file.path()).unwrap();
+        book.add("alice", address(1), None).unwrap();
+
+        let tampered = fs::read_to_string(file.path())
+            .unwrap()
+            .replace(&address(1).to_hex(), &address(2).to_hex());
+        fs::write(file.path(), tampered).unwrap();
+        assert!(AddressBook::load(file.path()).is_err());
+    }
+}
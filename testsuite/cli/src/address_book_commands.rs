@@ -0,0 +1,125 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    address_book::ALIAS_PREFIX,
+    client_proxy::ClientProxy,
+    commands::{subcommand_execute, Command},
+    tr,
+};
+use anyhow::{bail, Context, Result};
+
+/// Major command for managing named aliases of account addresses.
+pub struct AddressBookCommand {}
+
+impl Command for AddressBookCommand {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["addressbook", "ab"]
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("addressbook.description")
+    }
+    fn get_subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![
+            Box::new(AddressBookCommandAdd {}),
+            Box::new(AddressBookCommandRemove {}),
+            Box::new(AddressBookCommandList {}),
+        ]
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        subcommand_execute(&params[0], self.get_subcommands(), client, &params[1..])
+    }
+}
+
+/// Sub command to add or replace an alias.
+pub struct AddressBookCommandAdd {}
+
+impl Command for AddressBookCommandAdd {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["add", "a"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<alias> <account_address>|<account_ref_id> [checksum]"
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("addressbook.add.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.len() < 3 || params.len() > 4 {
+            bail!(tr!("addressbook.add.invalid_args"));
+        }
+        let alias = params[1].trim_start_matches(ALIAS_PREFIX);
+        let address = client
+            .get_account_address_from_parameter(params[2])
+            .and_then(|(address, _)| {
+                client
+                    .address_book
+                    .add(alias, address, params.get(3).copied())
+                    .map(|_| address)
+            })
+            .context(tr!("addressbook.add.error"))?;
+        println!(
+            "{}",
+            tr!("addressbook.add.success", ALIAS_PREFIX, alias, address)
+        );
+        Ok(())
+    }
+}
+
+/// Sub command to remove an alias.
+pub struct AddressBookCommandRemove {}
+
+impl Command for AddressBookCommandRemove {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["remove", "rm"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<alias>"
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("addressbook.remove.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.len() != 2 {
+            bail!(tr!("addressbook.remove.invalid_args"));
+        }
+        let alias = params[1].trim_start_matches(ALIAS_PREFIX);
+        client
+            .address_book
+            .remove(alias)
+            .context(tr!("addressbook.remove.error"))?;
+        println!("{}", tr!("addressbook.remove.success", ALIAS_PREFIX, alias));
+        Ok(())
+    }
+}
+
+/// Sub command to list all aliases.
+pub struct AddressBookCommandList {}
+
+impl Command for AddressBookCommandList {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["list", "l"]
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("addressbook.list.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) -> Result<()> {
+        let mut entries = client.address_book.entries().peekable();
+        if entries.peek().is_none() {
+            println!("{}", tr!("addressbook.list.empty"));
+        }
+        for (alias, address, checksum) in entries {
+            println!(
+                "{}",
+                tr!(
+                    "addressbook.list.entry",
+                    ALIAS_PREFIX,
+                    alias,
+                    address,
+                    checksum
+                )
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    client_proxy::ClientProxy,
+    commands::{get_commands, parse_cmd, report_error},
+    tr,
+};
+use anyhow::{bail, format_err, Result};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+};
+
+/// Variable holding the address of the most recently created or recovered account.
+pub const LAST_ACCOUNT_VAR: &str = "LAST_ACCOUNT";
+/// Variable holding the ref id of the most recently created or recovered account.
+pub const LAST_ACCOUNT_INDEX_VAR: &str = "LAST_ACCOUNT_INDEX";
+
+/// Outcome of running a batch of commands.
+#[derive(Debug, Default, PartialEq)]
+pub struct BatchSummary {
+    /// Number of commands that were dispatched.
+    pub executed: usize,
+    /// Number of commands that reported an error.
+    pub failed: usize,
+    /// Whether the batch stopped early because of `--abort-on-error`.
+    pub aborted: bool,
+}
+
+/// Run the commands in the script at `path`, or read them from stdin when `path` is `-`.
+pub fn run_batch_file(
+    client: &mut ClientProxy,
+    path: &str,
+    abort_on_error: bool,
+) -> Result<BatchSummary> {
+    if path == "-" {
+        let stdin = io::stdin();
+        run_batch(client, stdin.lock(), abort_on_error)
+    } else {
+        let file = File::open(path)
+            .map_err(|e| format_err!("{}: {}", tr!("batch.open_failed", path), e))?;
+        run_batch(client, BufReader::new(file), abort_on_error)
+    }
+}
+
+/// Run every command read from `reader` as if it had been typed at the prompt.
+///
+/// Blank lines and lines starting with `#` are skipped, `quit` ends the batch, and `$NAME` or
+/// `${NAME}` is replaced by `$LAST_ACCOUNT`, `$LAST_ACCOUNT_INDEX` or the environment variable
+/// `NAME`. A command fails when it returns an error; with `abort_on_error` the first failure stops
+/// the batch.
+pub fn run_batch<R: BufRead>(
+    client: &mut ClientProxy,
+    reader: R,
+    abort_on_error: bool,
+) -> Result<BatchSummary> {
+    let (_, alias_to_cmd) = get_commands(true);
+    let mut summary = BatchSummary::default();
+
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = match substitute_variables(line, |name| lookup_variable(client, name)) {
+            Ok(line) => line,
+            Err(e) => {
+                report_error(&tr!("batch.line_error", line_number + 1), e);
+                summary.failed += 1;
+                if abort_on_error {
+                    summary.aborted = true;
+                    break;
+                }
+                continue;
+            }
+        };
+        let params = parse_cmd(&line);
+        if params.is_empty() {
+            continue;
+        }
+        if params[0] == "quit" || params[0] == "q!" {
+            break;
+        }
+        println!("diem% {}", line);
+
+        summary.executed += 1;
+        let result = match alias_to_cmd.get(&params[0]) {
+            Some(cmd) => cmd.execute(client, &params),
+            None => Err(format_err!(tr!(
+                "main.unknown_command",
+                format!("{:?}", params[0])
+            ))),
+        };
+        if let Err(e) = result {
+            report_error(&tr!("batch.line_error", line_number + 1), e);
+            summary.failed += 1;
+            if abort_on_error {
+                summary.aborted = true;
+                break;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+fn lookup_variable(client: &ClientProxy, name: &str) -> Option<String> {
+    match name {
+        LAST_ACCOUNT_VAR => client
+            .accounts
+            .last()
+            .map(|account| hex::encode(account.address)),
+        LAST_ACCOUNT_INDEX_VAR => client
+            .accounts
+            .len()
+            .checked_sub(1)
+            .map(|index| index.to_string()),
+        _ => std::env::var(name).ok(),
+    }
+}
+
+/// Replace `$NAME` and `${NAME}` in `line` using `lookup`. A `$` not followed by a name is kept.
+fn substitute_variables(line: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(pos) = rest.find('$') {
+        result.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        let (name, remaining) = if let Some(braced) = rest.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => bail!(tr!("batch.unterminated_variable")),
+            }
+        } else {
+            let end = rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len());
+            (&rest[..end], &rest[end..])
+        };
+        if name.is_empty() {
+            result.push('$');
+            continue;
+        }
+        match lookup(name) {
+            Some(value) => result.push_str(&value),
+            None => bail!(tr!("batch.undefined_variable", name)),
+        }
+        rest = remaining;
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            LAST_ACCOUNT_VAR => Some("cafe".to_string()),
+            "AMOUNT" => Some("10".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_substitute_variables() {
+        assert_eq!(
+            substitute_variables("transfer 0 $LAST_ACCOUNT ${AMOUNT}GAS", lookup).unwrap(),
+            "transfer 0 cafe 10GAS"
+        );
+        assert_eq!(
+            substitute_variables("query balance 0", lookup).unwrap(),
+            "query balance 0"
+        );
+        assert_eq!(
+            substitute_variables("cost $ 5", lookup).unwrap(),
+            "cost $ 5"
+        );
+        assert!(substitute_variables("query balance $UNKNOWN", lookup).is_err());
+        assert!(substitute_variables("query balance ${AMOUNT", lookup).is_err());
+    }
+}
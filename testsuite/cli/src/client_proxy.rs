@@ -2,18 +2,32 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    address_book::{AddressBook, ALIAS_PREFIX},
     commands::{is_address, is_authentication_key},
     diem_client::DiemClient,
-    AccountData, AccountStatus,
+    faucet_client::{FaucetClient, MintRequest, DEFAULT_BATCH_CONCURRENCY},
+    mnemonic_prompt::{read_mnemonic, DEFAULT_MNEMONIC_WORDS},
+    move_debugger::StepDebugger,
+    multi_agent::{load_json, save_json, PartialMultiAgentTransaction, PartialSignature},
+    script_args::{
+        check_arguments, parse_typed_argument, script_function_parameters, script_parameters,
+        split_typed_argument,
+    },
+    sequence_manager::{is_sequence_number_error, SequenceNumberManager},
+    tr, AccountData, AccountStatus,
 };
 use anyhow::{bail, ensure, format_err, Error, Result};
 use compiler::Compiler;
 use diem_client::{WaitForTransactionError, views::{self, WaypointView}};
 use diem_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    hash::CryptoHash,
     test_utils::KeyPair,
+    HashValue, SigningKey,
+};
+use diem_json_rpc_client::views::{
+    OracleUpgradeStateView, SyncStatusView, TowerStateResourceView,
 };
-use diem_json_rpc_client::views::{OracleUpgradeStateView, TowerStateResourceView};
 use diem_logger::prelude::{error, info};
 use diem_temppath::TempPath;
 use diem_transaction_builder::stdlib as transaction_builder;
@@ -31,22 +45,38 @@ use diem_types::{
         XUS_NAME,
     },
     account_state::AccountState,
+    account_state_blob::AccountStateBlob,
     chain_id::ChainId,
+    contract_event::ContractEvent,
+    event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
     transaction::{
-        authenticator::AuthenticationKey,
+        authenticator::{AccountAuthenticator, AuthenticationKey},
         helpers::{create_unsigned_txn, create_user_txn, TransactionSigner},
-        parse_transaction_argument, ChangeSet, Module, RawTransaction, Script, SignedTransaction,
-        TransactionArgument, TransactionPayload, Version, WriteSetPayload,
+        parse_transaction_argument, ChangeSet, Module, RawTransaction, Script, ScriptFunction,
+        SignedTransaction, Transaction, TransactionArgument, TransactionInfo,
+        TransactionListWithProof, TransactionOutput, TransactionPayload, TransactionStatus,
+        Version, WriteSetPayload,
     },
+    vm_status::VMStatus,
     waypoint::Waypoint,
-    write_set::{WriteOp, WriteSetMut},
+    write_set::{WriteOp, WriteSet, WriteSetMut},
+};
+use diem_validator_interface::{
+    DebuggerStateView, DiemValidatorInterface, JsonRpcDebuggerInterface,
 };
+use diem_vm::{DiemVM, VMExecutor};
 use diem_wallet::{io_utils, Mnemonic, WalletLibrary};
+use move_core_types::{
+    identifier::Identifier, language_storage::ModuleId, transaction_argument::convert_txn_args,
+};
+use move_vm_runtime::observer;
+use move_vm_types::gas_profile::GasProfile;
 use num_traits::cast::{FromPrimitive, ToPrimitive};
 use reqwest::Url;
 use resource_viewer::{AnnotatedAccountStateBlob, MoveValueAnnotator, NullStateView};
 use rust_decimal::Decimal;
+use serde::Serialize;
 use std::{
     collections::HashMap,
     convert::TryFrom,
@@ -66,6 +96,11 @@ const GAS_UNIT_PRICE: u64 = 0;
 const MAX_GAS_AMOUNT: u64 = 1_000_000;
 const TX_EXPIRATION: i64 = 100;
 const DEFAULT_WAIT_TIMEOUT: time::Duration = time::Duration::from_secs(60);
+const FOLLOW_EVENTS_PAGE_SIZE: u64 = 100;
+const FOLLOW_EVENTS_POLL_INTERVAL: time::Duration = time::Duration::from_secs(1);
+const MAX_RESUBMISSIONS: usize = 3;
+const TXN_DETAIL_PAGE_SIZE: u64 = 100;
+const DEFAULT_TXN_HASH_SEARCH_DEPTH: u64 = 1000;
 
 /// Enum used for error formatting.
 #[derive(Debug)]
@@ -92,6 +127,16 @@ pub enum AccountEntry {
     Address(AccountAddress),
 }
 
+/// Outcome of running the Move Prover on a module
+pub struct ProverOutcome {
+    /// Whether all the specs were verified.
+    pub verified: bool,
+    /// The errors and warnings reported by the prover, as it formats them.
+    pub diagnostics: String,
+    /// The number of errors reported.
+    pub error_count: usize,
+}
+
 /// Used to return the sequence and sender account index submitted for a transfer
 pub struct IndexAndSequence {
     /// Index/key of the account in TestClient::accounts vector.
@@ -100,18 +145,45 @@ pub struct IndexAndSequence {
     pub sequence_number: u64,
 }
 
+/// A committed transaction whose inclusion, events and execution status were verified against
+/// the trusted state.
+pub struct TransactionDetail {
+    /// Decoded transaction, events and execution status.
+    pub view: views::TransactionView,
+    /// Write set recovered by replaying the transaction, which JSON-RPC does not serve. The
+    /// replay is only accepted if it reproduces the verified status, gas usage and events.
+    pub write_set: Result<WriteSet>,
+}
+
+/// Arguments shared by the transfer and simulated transfer commands.
+struct TransferParams {
+    sender: AccountAddress,
+    receiver: AccountAddress,
+    num_coins: u64,
+    currency: String,
+    gas_unit_price: Option<u64>,
+    max_gas_amount: Option<u64>,
+    gas_currency: String,
+}
+
 /// Proxy handling CLI commands/inputs.
 pub struct ClientProxy {
     /// chain ID of the Diem network this client is interacting with
     pub chain_id: ChainId,
     /// client for admission control interface.
     pub client: DiemClient,
+    /// JSON-RPC endpoint of the node, used to fetch state for local simulation.
+    url: Url,
     /// Created accounts.
     pub accounts: Vec<AccountData>,
     /// Address to account_ref_id map.
     address_to_ref_id: HashMap<AccountAddress, usize>,
-    /// Host that operates a faucet service
-    faucet_url: Url,
+    /// Named aliases accepted as `@alias` wherever an account is expected.
+    pub address_book: AddressBook,
+    /// Client of the faucet service used for minting when no designated dealer key is loaded.
+    pub faucet: FaucetClient,
+    /// Transactions submitted without waiting for them to commit.
+    sequence_numbers: SequenceNumberManager,
     /// Account used for Diem Root operations (e.g., adding a new transaction script)
     pub diem_root_account: Option<AccountData>,
     /// Account used for Treasury Compliance operations
@@ -215,9 +287,12 @@ impl ClientProxy {
         Ok(ClientProxy {
             chain_id,
             client,
+            url,
             accounts,
             address_to_ref_id,
-            faucet_url,
+            address_book: AddressBook::default(),
+            faucet: FaucetClient::new(faucet_url),
+            sequence_numbers: SequenceNumberManager::default(),
             diem_root_account,
             tc_account,
             testnet_designated_dealer_account: dd_account,
@@ -293,7 +368,7 @@ impl ClientProxy {
 
     /// Returns the ledger info corresonding to the latest epoch change
     /// (could further be used for e.g., generating a waypoint)
-    pub fn latest_epoch_change_li(&mut self) -> Option<&LedgerInfoWithSignatures> {
+    pub fn latest_epoch_change_li(&mut self) -> Option<LedgerInfoWithSignatures> {
         self.client.update_and_verify_state_proof().unwrap();
         self.client.latest_epoch_change_li()
     }
@@ -301,43 +376,55 @@ impl ClientProxy {
     /// Print index and address of all accounts.
     pub fn print_all_accounts(&self) {
         if self.accounts.is_empty() {
-            println!("No user accounts");
+            println!("{}", tr!("account.list.none"));
         } else {
             for (ref index, ref account) in self.accounts.iter().enumerate() {
                 //////// 0L ////////
                 println!(
-                    "User account index: {}, address: {}, sequence number: {}, status: {:?}",
-                    index,
-                    hex::encode(&account.address),
-                    // hex::encode(&self.wallet.get_private_key(&account.address).unwrap().to_bytes()),
-                    account.sequence_number,
-                    account.status,
+                    "{}",
+                    tr!(
+                        "account.list.user",
+                        index,
+                        hex::encode(&account.address),
+                        // hex::encode(&self.wallet.get_private_key(&account.address).unwrap().to_bytes()),
+                        account.sequence_number,
+                        format!("{:?}", account.status),
+                    )
                 );
             }
         }
 
         if let Some(diem_root_account) = &self.diem_root_account {
             println!(
-                "AssocRoot account address: {}, sequence_number: {}, status: {:?}",
-                hex::encode(&diem_root_account.address),
-                diem_root_account.sequence_number,
-                diem_root_account.status,
+                "{}",
+                tr!(
+                    "account.list.root",
+                    hex::encode(&diem_root_account.address),
+                    diem_root_account.sequence_number,
+                    format!("{:?}", diem_root_account.status),
+                )
             );
         }
         if let Some(tc_account) = &self.tc_account {
             println!(
-                "TC account address: {}, sequence_number: {}, status: {:?}",
-                hex::encode(&tc_account.address),
-                tc_account.sequence_number,
-                tc_account.status,
+                "{}",
+                tr!(
+                    "account.list.tc",
+                    hex::encode(&tc_account.address),
+                    tc_account.sequence_number,
+                    format!("{:?}", tc_account.status),
+                )
             );
         }
         if let Some(testnet_dd_account) = &self.testnet_designated_dealer_account {
             println!(
-                "Testnet DD account address: {}, sequence_number: {}, status: {:?}",
-                hex::encode(&testnet_dd_account.address),
-                testnet_dd_account.sequence_number,
-                testnet_dd_account.status,
+                "{}",
+                tr!(
+                    "account.list.testnet_dd",
+                    hex::encode(&testnet_dd_account.address),
+                    testnet_dd_account.sequence_number,
+                    format!("{:?}", testnet_dd_account.status),
+                )
             );
         }
     }
@@ -362,7 +449,7 @@ impl ClientProxy {
     /// Get balance from validator for the account specified.
     pub fn get_balances(&mut self, space_delim_strings: &[&str]) -> Result<Vec<String>> {
         ensure!(
-            space_delim_strings.len() == 2,
+            space_delim_strings.len() == 2 || space_delim_strings.len() == 3,
             "Invalid number of arguments for getting balances"
         );
         let (address, _) = self.get_account_address_from_parameter(space_delim_strings[1])?;
@@ -372,7 +459,12 @@ impl ClientProxy {
             .into_iter()
             .map(|view| (view.code.clone(), view))
             .collect();
-        let account = self.get_account_resource_and_update(&address)?;
+        let account = match space_delim_strings.get(2) {
+            Some(version) => self
+                .get_account_by_version(&address, version)?
+                .ok_or_else(|| format_err!("No account exists at {:?}", address))?,
+            None => self.get_account_resource_and_update(&address)?,
+        };
         account
             .balances
             .iter()
@@ -842,6 +934,17 @@ impl ClientProxy {
         self.client.get_waypoint_state()
     }
 
+    /// Get the progress of state sync from the admin address of a node.
+    pub fn get_sync_status(&self, space_delim_strings: &[&str]) -> Result<SyncStatusView> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for getting sync status"
+        );
+        let url = Url::parse(space_delim_strings[1])?.join("-/state-sync")?;
+        let response = reqwest::blocking::get(url)?.error_for_status()?;
+        Ok(response.json()?)
+    }
+
 
     /// Get the latest sequence number from validator for the account specified.
     pub fn get_sequence_number(&mut self, space_delim_strings: &[&str]) -> Result<u64> {
@@ -867,6 +970,7 @@ impl ClientProxy {
             false
         };
         if reset_sequence_number {
+            self.sequence_numbers.forget(&address);
             self.update_account_seq(&address, sequence_number);
         }
 
@@ -989,7 +1093,7 @@ impl ClientProxy {
             {
                 let status = &self.accounts.get(pos).unwrap().status;
                 if &AccountStatus::Local == status {
-                    println!(">> Creating recipient account before minting from faucet");
+                    println!("{}", tr!("account.mint.creating_recipient"));
                     // This needs to be blocking since the mint can't happen until it completes
                     self.association_transaction_with_local_tc_account(payload, true)?;
                     self.accounts.get_mut(pos).unwrap().status = AccountStatus::Persisted;
@@ -1001,7 +1105,7 @@ impl ClientProxy {
             } // else, the account has already been created -- do nothing
         }
 
-        println!(">> Sending coins from faucet");
+        println!("{}", tr!("account.mint.progress"));
         match self.testnet_designated_dealer_account {
             Some(_) => {
                 let payload =
@@ -1022,6 +1126,68 @@ impl ClientProxy {
         }
     }
 
+    /// Fund every account listed in a file through the faucet service, sending the requests
+    /// concurrently. The file holds one account (ref id, address or authentication key) per
+    /// line; blank lines and lines starting with `#` are skipped. Returns the outcome for each
+    /// listed account.
+    pub fn mint_coins_batch(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<Vec<(String, Result<()>)>> {
+        ensure!(
+            space_delim_strings.len() >= 4 && space_delim_strings.len() <= 5,
+            "Invalid number of arguments for batch mint"
+        );
+        let currency = space_delim_strings[3];
+        let amount = self.convert_to_on_chain_representation(space_delim_strings[2], currency)?;
+        ensure!(
+            amount > 0,
+            "Invalid number of coins to transfer from faucet."
+        );
+        let concurrency = match space_delim_strings.get(4) {
+            Some(concurrency) => concurrency.parse()?,
+            None => DEFAULT_BATCH_CONCURRENCY,
+        };
+
+        let contents = fs::read_to_string(space_delim_strings[1])?;
+        let receivers: Vec<_> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        let mut results: Vec<_> = receivers.iter().map(|_| Ok(())).collect();
+        let mut requests = vec![];
+        let mut request_indices = vec![];
+        for (index, receiver) in receivers.iter().enumerate() {
+            match self.get_account_address_from_parameter(receiver) {
+                Ok((_, Some(auth_key))) => {
+                    requests.push(MintRequest {
+                        auth_key,
+                        amount,
+                        currency: currency.to_owned(),
+                    });
+                    request_indices.push(index);
+                }
+                Ok((_, None)) => {
+                    results[index] = Err(format_err!(
+                        "Need authentication key to create new account via minting from faucet"
+                    ))
+                }
+                Err(e) => results[index] = Err(e),
+            }
+        }
+
+        let minted = self.faucet.mint_batch(requests, concurrency)?;
+        for (index, txns) in request_indices.into_iter().zip(minted) {
+            results[index] = txns.and_then(|txns| self.wait_for_minting_transactions(&txns));
+        }
+        Ok(receivers
+            .into_iter()
+            .map(str::to_owned)
+            .zip(results)
+            .collect())
+    }
+
     /// Allow executing arbitrary script in the network.
     pub fn enable_custom_script(
         &mut self,
@@ -1155,21 +1321,128 @@ impl ClientProxy {
         )
     }
 
-    /// Submit transaction and waits for the transaction executed
-    pub fn submit_and_wait(&mut self, txn: &SignedTransaction, is_blocking: bool) -> Result<()> {
-        self.client.submit_transaction(&txn)?;
-        if is_blocking {
-            self.wait_for_signed_transaction(txn)?;
+    /// Submit transaction and waits for the transaction executed. Without blocking, the
+    /// transaction is tracked so that further transactions from the same account can be submitted
+    /// right away. Returns the transaction accepted by the node, which was renumbered if the
+    /// sequence number of `txn` turned out to be stale.
+    pub fn submit_and_wait(
+        &mut self,
+        txn: &SignedTransaction,
+        is_blocking: bool,
+    ) -> Result<SignedTransaction> {
+        let (txn, committed) = self.submit_with_resync(txn, is_blocking)?;
+        if let Some(committed) = committed {
+            if let Err(err) = self.get_account_and_update(&txn.sender()) {
+                println!("{}", tr!("account.update_failed", err));
+            }
+            ensure!(
+                committed.vm_status.is_executed(),
+                "Transaction failed to execute; status: {:?}!",
+                committed.vm_status
+            );
+        } else if is_blocking {
+            self.wait_for_signed_transaction(&txn)?;
         } else {
             let seq = txn
                 .sequence_number()
                 .checked_add(1)
                 .ok_or_else(|| format_err!("seqnum can't reach u64::max"))?;
+            self.sequence_numbers.submitted(txn.clone());
             self.update_account_seq(&txn.sender(), seq);
         }
+        Ok(txn)
+    }
+
+    /// Submit `txn`, and if `wait`, have the node wait for it to commit: the committed transaction
+    /// is returned along with the one submitted when it did. If the node rejects its sequence
+    /// number, resync the sender with the chain and submit it again renumbered.
+    fn submit_with_resync(
+        &mut self,
+        txn: &SignedTransaction,
+        wait: bool,
+    ) -> Result<(SignedTransaction, Option<views::TransactionView>)> {
+        let mut txn = txn.clone();
+        let mut resubmissions = 0;
+        loop {
+            let submitted = if wait {
+                self.client.submit_and_wait(&txn, DEFAULT_WAIT_TIMEOUT)
+            } else {
+                self.client.submit_transaction(&txn).map(|()| None)
+            };
+            match submitted {
+                Ok(committed) => return Ok((txn, committed)),
+                Err(e) if resubmissions < MAX_RESUBMISSIONS && is_sequence_number_error(&e) => {
+                    resubmissions += 1;
+                    let sender = txn.sender();
+                    self.get_account_resource_and_update(&sender)?;
+                    let sequence_number = self.get_account_data(&sender)?.sequence_number;
+                    info!(
+                        "Resubmitting transaction from {} with sequence number {}: {}",
+                        sender, sequence_number, e
+                    );
+                    txn = self.renumber_txn(&txn, sequence_number).map_err(|_| e)?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Reconcile the tracked sequence numbers of `address` with its committed sequence number,
+    /// renumbering and resubmitting in-flight transactions that can no longer commit.
+    fn sync_sequence_number(&mut self, address: &AccountAddress, committed: u64) -> Result<()> {
+        let mut next = committed;
+        if self.sequence_numbers.pending_count(address) > 0 {
+            let ledger_timestamp_secs = self.client.get_metadata()?.timestamp / 1_000_000;
+            let stranded = self
+                .sequence_numbers
+                .sync(address, committed, ledger_timestamp_secs);
+            next = self
+                .sequence_numbers
+                .next_sequence_number(address)
+                .unwrap_or(committed);
+            for txn in stranded {
+                let txn = self.renumber_txn(&txn, next)?;
+                self.client.submit_transaction(&txn)?;
+                info!(
+                    "Resubmitted transaction from {} with sequence number {}",
+                    address, next
+                );
+                self.sequence_numbers.submitted(txn);
+                next += 1;
+            }
+        }
+        self.update_account_seq(address, next);
         Ok(())
     }
 
+    /// Sign the payload of `txn` again with `sequence_number` and a fresh expiration time.
+    fn renumber_txn(
+        &self,
+        txn: &SignedTransaction,
+        sequence_number: u64,
+    ) -> Result<SignedTransaction> {
+        ensure!(
+            txn.authenticator().secondary_signer_addreses().is_empty(),
+            "Multi-agent transactions cannot be renumbered"
+        );
+        let sender = self.get_account_data(&txn.sender())?;
+        let signer: Box<&dyn TransactionSigner> = match &sender.key_pair {
+            Some(key_pair) => Box::new(key_pair),
+            None => Box::new(&self.wallet),
+        };
+        create_user_txn(
+            *signer,
+            txn.payload().clone(),
+            sender.address,
+            sequence_number,
+            txn.max_gas_amount(),
+            txn.gas_unit_price(),
+            txn.gas_currency_code().to_owned(),
+            TX_EXPIRATION,
+            self.chain_id,
+        )
+    }
+
     /// Waits for the transaction
     pub fn wait_for_signed_transaction(
         &mut self,
@@ -1196,7 +1469,7 @@ impl ClientProxy {
         }
 
         if let Err(err) = ac_update {
-            println!("account update failed: {}", err);
+            println!("{}", tr!("account.update_failed", err));
         }
         match ret {
             Ok(t) => Ok(t),
@@ -1240,7 +1513,7 @@ impl ClientProxy {
             gas_unit_price,    /* gas_unit_price */
             gas_currency_code, /* gas_currency_code */
         )?;
-        self.submit_and_wait(&txn, is_blocking)?;
+        let txn = self.submit_and_wait(&txn, is_blocking)?;
 
         Ok(IndexAndSequence {
             account_index: AccountEntry::Index(sender_account_ref_id),
@@ -1288,6 +1561,47 @@ impl ClientProxy {
         space_delim_strings: &[&str],
         is_blocking: bool,
     ) -> Result<IndexAndSequence> {
+        let params = self.parse_transfer_params(space_delim_strings)?;
+        self.transfer_coins_int(
+            &params.sender,
+            &params.receiver,
+            params.num_coins,
+            params.currency,
+            params.gas_unit_price,
+            Some(params.gas_currency),
+            params.max_gas_amount,
+            is_blocking,
+        )
+    }
+
+    /// Build a transfer from the same arguments as `transfer_coins` and execute it locally
+    /// against the latest committed state, without submitting it.
+    pub fn simulate_transfer_coins(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<TransactionOutput> {
+        let params = self.parse_transfer_params(space_delim_strings)?;
+        let currency_code = from_currency_code_string(&params.currency)
+            .map_err(|_| format_err!("Invalid currency code {} specified", params.currency))?;
+        let sender = self.get_account_data(&params.sender)?;
+        let program = transaction_builder::encode_peer_to_peer_with_metadata_script_function(
+            type_tag_for_currency_code(currency_code),
+            params.receiver,
+            params.num_coins,
+            vec![],
+            vec![],
+        );
+        let txn = self.create_txn_to_submit(
+            program,
+            sender,
+            params.max_gas_amount,
+            params.gas_unit_price,
+            Some(params.gas_currency),
+        )?;
+        self.simulate_transaction(txn)
+    }
+
+    fn parse_transfer_params(&mut self, space_delim_strings: &[&str]) -> Result<TransferParams> {
         ensure!(
             space_delim_strings.len() >= 5 && space_delim_strings.len() <= 7,
             "Invalid number of arguments for transfer"
@@ -1334,16 +1648,29 @@ impl ClientProxy {
             transfer_currency.to_owned()
         };
 
-        self.transfer_coins_int(
-            &sender_account_address,
-            &receiver_address,
+        Ok(TransferParams {
+            sender: sender_account_address,
+            receiver: receiver_address,
             num_coins,
-            transfer_currency.to_owned(),
+            currency: transfer_currency.to_owned(),
             gas_unit_price,
-            Some(gas_currency),
             max_gas_amount,
-            is_blocking,
-        )
+            gas_currency,
+        })
+    }
+
+    /// Execute a signed transaction with the local VM against the latest state committed on the
+    /// node. Nothing is broadcast; the returned output is what the transaction would produce if
+    /// it were the next one committed.
+    pub fn simulate_transaction(&self, txn: SignedTransaction) -> Result<TransactionOutput> {
+        let remote = JsonRpcDebuggerInterface::new(self.url.as_str())?;
+        let latest_version = remote.get_latest_version()?;
+        // The debugger view reads state as of `version - 1`.
+        let state_view = DebuggerStateView::new(&remote, latest_version + 1);
+        DiemVM::execute_block(vec![Transaction::UserTransaction(txn)], &state_view)
+            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?
+            .pop()
+            .ok_or_else(|| format_err!("VM returned no output for simulated transaction"))
     }
 
     /// Compile Move program
@@ -1399,6 +1726,58 @@ impl ClientProxy {
         Ok(output_files)
     }
 
+    /// Run the Move Prover on the specs of the module at `space_delim_strings[1]`, with the Diem
+    /// framework and the other source files given as dependencies, which aren't verified. Only
+    /// `function` is verified if there's one.
+    pub fn prove_module(
+        &self,
+        space_delim_strings: &[&str],
+        function: Option<&str>,
+    ) -> Result<ProverOutcome> {
+        ensure!(
+            space_delim_strings[0] == "prove" || space_delim_strings[0] == "pr",
+            "inconsistent command '{}' for prove_module",
+            space_delim_strings[0]
+        );
+        ensure!(
+            space_delim_strings.len() >= 2,
+            "Invalid number of arguments to prove module"
+        );
+        let file_path = space_delim_strings[1];
+        // Only the prover's warnings and errors are printed, not the logs of cargo nor its own.
+        let mut args = vec!["run", "-q", "-p", "move-prover", "--"];
+        args.extend(&["-v", "warn", file_path]);
+        let framework_files = diem_framework::diem_stdlib_files();
+        for dep in framework_files
+            .iter()
+            .map(String::as_str)
+            .chain(space_delim_strings[2..].iter().copied())
+        {
+            args.push("-d");
+            args.push(dep);
+        }
+        if let Some(function) = function {
+            args.push("--verify-only");
+            args.push(function);
+        }
+
+        let output = Command::new("cargo").args(&args).output()?;
+        let diagnostics = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let error_count = diagnostics
+            .lines()
+            .filter(|line| line.starts_with("error"))
+            .count();
+        Ok(ProverOutcome {
+            verified: output.status.success(),
+            diagnostics,
+            error_count,
+        })
+    }
+
     /// Submit a transaction to the network given the unsigned raw transaction, sender public key
     /// and signature
     pub fn submit_signed_transaction(
@@ -1447,16 +1826,215 @@ impl ClientProxy {
             "inconsistent command '{}' for execute_script",
             space_delim_strings[0]
         );
-        let script_bytes = fs::read(space_delim_strings[2])?;
-        let arguments: Vec<_> = space_delim_strings[3..]
-            .iter()
-            .filter_map(|arg| parse_transaction_argument_for_client(arg).ok())
-            .collect();
-        // TODO: support type arguments in the client.
-        self.submit_program(
-            space_delim_strings,
-            TransactionPayload::Script(Script::new(script_bytes, vec![], arguments)),
-        )
+        let program = self.script_payload_from_params(space_delim_strings)?;
+        self.submit_program(space_delim_strings, program)
+    }
+
+    /// Sign a custom script with the sender's key and execute it locally against the latest
+    /// committed state, without submitting it.
+    pub fn simulate_script(&mut self, space_delim_strings: &[&str]) -> Result<TransactionOutput> {
+        ensure!(
+            space_delim_strings.len() >= 3,
+            "Invalid number of arguments to simulate script"
+        );
+        let program = self.script_payload_from_params(space_delim_strings)?;
+        let (sender_address, _) =
+            self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let sender = self.get_account_data(&sender_address)?;
+        let txn = self.create_txn_to_submit(program, &sender, None, None, None)?;
+        self.simulate_transaction(txn)
+    }
+
+    /// Sign a custom script with the sender's key and execute it locally against the latest
+    /// committed state, without submitting it, stepping through it with the Move debugger from its
+    /// first instruction. Only debug builds of the VM call debuggers, so only those of the client
+    /// can debug scripts.
+    pub fn debug_script(&mut self, space_delim_strings: &[&str]) -> Result<TransactionOutput> {
+        ensure!(
+            cfg!(debug_assertions),
+            "Scripts can only be debugged by debug builds of the client"
+        );
+        ensure!(
+            space_delim_strings.len() >= 3,
+            "Invalid number of arguments to debug script"
+        );
+        let entry = parse_script_function_id(space_delim_strings[2])?;
+        observer::start_observing(Box::new(StepDebugger::new(entry)));
+        let output = self.simulate_script(space_delim_strings);
+        observer::stop_observing();
+        output
+    }
+
+    /// Sign a custom script with the sender's key and execute it locally against the latest
+    /// committed state, without submitting it, profiling the gas it's charged. There's no profile
+    /// when the transaction is discarded before it's charged for.
+    pub fn profile_script_gas(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<(VMStatus, TransactionOutput, Option<GasProfile>)> {
+        ensure!(
+            space_delim_strings.len() >= 3,
+            "Invalid number of arguments to profile script"
+        );
+        let program = self.script_payload_from_params(space_delim_strings)?;
+        let (sender_address, _) =
+            self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let sender = self.get_account_data(&sender_address)?;
+        let txn = self.create_txn_to_submit(program, &sender, None, None, None)?;
+
+        let remote = JsonRpcDebuggerInterface::new(self.url.as_str())?;
+        let latest_version = remote.get_latest_version()?;
+        // The debugger view reads state as of `version - 1`.
+        let state_view = DebuggerStateView::new(&remote, latest_version + 1);
+        DiemVM::execute_user_transaction_with_gas_profile(txn, &state_view)
+            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))
+    }
+
+    /// Build a multi-agent script transaction and save it to a file for the secondary signers.
+    /// The sender's signature is added right away when the sender is a local account.
+    pub fn build_multi_agent_script(
+        &self,
+        space_delim_strings: &[&str],
+    ) -> Result<PartialMultiAgentTransaction> {
+        ensure!(
+            space_delim_strings.len() >= 4,
+            "Invalid number of arguments to build a multi-agent transaction"
+        );
+        let mut secondary_signers = vec![];
+        let mut script_params = space_delim_strings[..3].to_vec();
+        let mut rest = space_delim_strings[4..].iter();
+        while let Some(param) = rest.next() {
+            if *param == "--secondary-signer" {
+                let signer = rest
+                    .next()
+                    .ok_or_else(|| format_err!("Missing address after --secondary-signer"))?;
+                secondary_signers.push(self.get_account_address_from_parameter(signer)?.0);
+            } else {
+                script_params.push(param);
+            }
+        }
+        ensure!(
+            !secondary_signers.is_empty(),
+            "A multi-agent transaction needs at least one --secondary-signer"
+        );
+
+        let program = self.script_payload_from_params(&script_params)?;
+        let (sender_address, _) =
+            self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let sender = self.get_account_data(&sender_address).ok();
+        let sequence_number = match sender {
+            Some(sender) => sender.sequence_number,
+            None => {
+                self.client
+                    .get_account(&sender_address)?
+                    .ok_or_else(|| {
+                        format_err!("No account found for address {:?}", sender_address)
+                    })?
+                    .sequence_number
+            }
+        };
+        let raw_txn = create_unsigned_txn(
+            program,
+            sender_address,
+            sequence_number,
+            MAX_GAS_AMOUNT,
+            GAS_UNIT_PRICE,
+            XUS_NAME.to_owned(),
+            TX_EXPIRATION,
+            self.chain_id,
+        );
+        let mut txn = PartialMultiAgentTransaction::new(raw_txn, secondary_signers)?;
+        if let Some(sender) = sender {
+            let authenticator = self.sign_with_account(sender, &txn.signing_message())?;
+            txn.add_signature(PartialSignature {
+                signer: sender_address,
+                authenticator,
+            })?;
+        }
+        save_json(Path::new(space_delim_strings[3]), &txn)?;
+        Ok(txn)
+    }
+
+    /// Sign a multi-agent transaction file with a local account and write the signature to a
+    /// file that can be merged with `add_multi_agent_signatures`.
+    pub fn sign_multi_agent(&self, space_delim_strings: &[&str]) -> Result<AccountAddress> {
+        ensure!(
+            space_delim_strings.len() == 4,
+            "Invalid number of arguments to sign a multi-agent transaction"
+        );
+        let (signer, _) = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let txn: PartialMultiAgentTransaction = load_json(Path::new(space_delim_strings[2]))?;
+        ensure!(
+            txn.signers().any(|address| address == signer),
+            "{} is not a signer of this transaction",
+            signer
+        );
+        let authenticator =
+            self.sign_with_account(self.get_account_data(&signer)?, &txn.signing_message())?;
+        save_json(
+            Path::new(space_delim_strings[3]),
+            &PartialSignature {
+                signer,
+                authenticator,
+            },
+        )?;
+        Ok(signer)
+    }
+
+    /// Merge signature files into a multi-agent transaction file.
+    pub fn add_multi_agent_signatures(
+        &self,
+        space_delim_strings: &[&str],
+    ) -> Result<PartialMultiAgentTransaction> {
+        ensure!(
+            space_delim_strings.len() >= 3,
+            "Invalid number of arguments to add signatures"
+        );
+        let txn_path = Path::new(space_delim_strings[1]);
+        let mut txn: PartialMultiAgentTransaction = load_json(txn_path)?;
+        for signature_file in &space_delim_strings[2..] {
+            txn.add_signature(load_json(Path::new(signature_file))?)?;
+        }
+        save_json(txn_path, &txn)?;
+        Ok(txn)
+    }
+
+    /// Assemble a fully signed multi-agent transaction, write it BCS encoded to a file and
+    /// optionally submit it.
+    pub fn assemble_multi_agent(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<SignedTransaction> {
+        let submit = match space_delim_strings.len() {
+            3 => false,
+            4 if space_delim_strings[3] == "--submit" => true,
+            _ => bail!("Invalid arguments to assemble a multi-agent transaction"),
+        };
+        let txn: PartialMultiAgentTransaction = load_json(Path::new(space_delim_strings[1]))?;
+        let signed_txn = txn.into_signed_transaction()?;
+        fs::write(space_delim_strings[2], bcs::to_bytes(&signed_txn)?)?;
+        if submit {
+            self.submit_and_wait(&signed_txn, true)?;
+        }
+        Ok(signed_txn)
+    }
+
+    /// Sign an arbitrary message with the key of a local account.
+    fn sign_with_account<T: CryptoHash + Serialize>(
+        &self,
+        account: &AccountData,
+        message: &T,
+    ) -> Result<AccountAuthenticator> {
+        Ok(match &account.key_pair {
+            Some(key_pair) => AccountAuthenticator::ed25519(
+                key_pair.public_key.clone(),
+                key_pair.private_key.sign(message),
+            ),
+            None => {
+                let private_key = self.wallet.get_private_key(&account.address)?;
+                AccountAuthenticator::ed25519((&private_key).into(), private_key.sign(message))
+            }
+        })
     }
 
     /// Submit a writeset transaction signed by local diem root account.
@@ -1470,17 +2048,55 @@ impl ClientProxy {
         self.association_transaction_with_local_diem_root_account(payload, true)
     }
 
-    /// Get the latest account information from validator.
+    /// Get the latest account information from validator, or the account as of the version
+    /// following the account parameter.
     pub fn get_latest_account(
         &mut self,
         space_delim_strings: &[&str],
     ) -> Result<Option<views::AccountView>> {
         ensure!(
-            space_delim_strings.len() == 2,
+            space_delim_strings.len() == 2 || space_delim_strings.len() == 3,
             "Invalid number of arguments to get latest account"
         );
         let (account, _) = self.get_account_address_from_parameter(space_delim_strings[1])?;
-        self.get_account_and_update(&account)
+        match space_delim_strings.get(2) {
+            Some(version) => self.get_account_by_version(&account, version),
+            None => self.get_account_and_update(&account),
+        }
+    }
+
+    /// Get the account at `address` as it was at `version`. Historical state says nothing about
+    /// the account's current sequence number, so the local account data is left untouched.
+    fn get_account_by_version(
+        &self,
+        address: &AccountAddress,
+        version: &str,
+    ) -> Result<Option<views::AccountView>> {
+        let version = version.parse::<Version>().map_err(|error| {
+            format_parse_data_error("version", InputType::UnsignedInt, version, error)
+        })?;
+        self.client.get_account_by_version(address, version)
+    }
+
+    /// Trust the waypoint in `space_delim_strings[1]` from now on and verify the server's
+    /// state proof against it. Moving to an older waypoint requires `--force`.
+    pub fn set_waypoint(&mut self, space_delim_strings: &[&str]) -> Result<Waypoint> {
+        let force = match space_delim_strings.len() {
+            2 => false,
+            3 if space_delim_strings[2] == "--force" => true,
+            _ => bail!("Invalid arguments to set a waypoint"),
+        };
+        let waypoint = Waypoint::from_str(space_delim_strings[1])
+            .map_err(|e| format_err!("Invalid waypoint {}: {}", space_delim_strings[1], e))?;
+        self.client.set_waypoint(waypoint, force)?;
+        self.client.update_and_verify_state_proof().map_err(|e| {
+            format_err!(
+                "Server state could not be verified against waypoint {}: {}",
+                waypoint,
+                e
+            )
+        })?;
+        Ok(waypoint)
     }
 
     /// Get the latest version
@@ -1502,6 +2118,69 @@ impl ClientProxy {
         self.get_annotate_account_blob(account)
     }
 
+    /// Build a script or script function payload from
+    /// `<cmd> <sender> <compiled_script_path>|<address>::<module>::<function> [parameters]`,
+    /// checking the arguments against the parameters the script or function declares.
+    fn script_payload_from_params(
+        &self,
+        space_delim_strings: &[&str],
+    ) -> Result<TransactionPayload> {
+        let arguments = space_delim_strings[3..]
+            .iter()
+            .map(|arg| parse_script_argument(arg))
+            .collect::<Result<Vec<_>>>()?;
+        // TODO: support type arguments in the client.
+        if let Some((module_id, function)) = parse_script_function_id(space_delim_strings[2])? {
+            let parameters =
+                script_function_parameters(&self.get_module_bytes(&module_id)?, function.as_str())?;
+            check_arguments(&parameters, &arguments)?;
+            Ok(TransactionPayload::ScriptFunction(ScriptFunction::new(
+                module_id,
+                function,
+                vec![],
+                convert_txn_args(&arguments),
+            )))
+        } else {
+            let script_bytes = fs::read(space_delim_strings[2])?;
+            check_arguments(&script_parameters(&script_bytes)?, &arguments)?;
+            Ok(TransactionPayload::Script(Script::new(
+                script_bytes,
+                vec![],
+                arguments,
+            )))
+        }
+    }
+
+    /// Fetch the bytecode of a published module.
+    fn get_module_bytes(&self, module_id: &ModuleId) -> Result<Vec<u8>> {
+        let (blob, _) = self.client.get_verified_account_state_blob(module_id.address())?;
+        let blob =
+            blob.ok_or_else(|| format_err!("No account found at {}", module_id.address()))?;
+        AccountState::try_from(&blob)?
+            .get(&AccessPath::code_access_path(module_id.clone()).path)
+            .cloned()
+            .ok_or_else(|| format_err!("Module {} is not published", module_id))
+    }
+
+    /// Get every Move resource of an account at the latest verified version as annotated JSON.
+    /// The account state is checked against its proof before being decoded.
+    pub fn get_account_resources_json(
+        &self,
+        space_delim_strings: &[&str],
+    ) -> Result<(AccountAddress, Option<serde_json::Value>, Version)> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments to get account resources"
+        );
+        let (account, _) = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let (blob, version) = self.client.get_verified_account_state_blob(&account)?;
+        let resources = match blob {
+            Some(blob) => Some(annotate_account_blob(&blob)?.to_json()),
+            None => None,
+        };
+        Ok((account, resources, version))
+    }
+
     /// Get committed txn by account and sequence number.
     pub fn get_committed_txn_by_acc_seq(
         &mut self,
@@ -1534,7 +2213,7 @@ impl ClientProxy {
             .get_txn_by_acc_seq(&account, sequence_number, fetch_events)
     }
 
-    /// Get committed txn by account and sequence number
+    /// Get committed txns by version range, verified against the trusted state.
     pub fn get_committed_txn_by_range(
         &mut self,
         space_delim_strings: &[&str],
@@ -1568,8 +2247,120 @@ impl ClientProxy {
             )
         })?;
 
-        self.client
-            .get_txn_by_range(start_version, limit, fetch_events)
+        verified_txns(self.client.get_verified_transactions(start_version, limit)?)?
+            .into_iter()
+            .map(|(version, txn, info, events)| {
+                let events = if fetch_events { events } else { vec![] };
+                views::TransactionView::try_from_tx_and_events(version, txn, info, events)
+            })
+            .collect()
+    }
+
+    /// Get a committed transaction by version or hash, verified against the trusted state. A hash
+    /// is searched for among the latest `search_depth` transactions.
+    pub fn get_txn_detail(&mut self, space_delim_strings: &[&str]) -> Result<TransactionDetail> {
+        ensure!(
+            space_delim_strings.len() == 2 || space_delim_strings.len() == 3,
+            "Invalid number of arguments to get transaction detail"
+        );
+        let (version, txn, info, events) = match space_delim_strings[1].parse::<Version>() {
+            Ok(version) => verified_txns(self.client.get_verified_transactions(version, 1)?)?
+                .pop()
+                .ok_or_else(|| format_err!("No transaction at version {}", version))?,
+            Err(_) => {
+                let hash = HashValue::from_hex(space_delim_strings[1].trim_start_matches("0x"))
+                    .map_err(|_| {
+                        format_err!(
+                            "Expected a version or a transaction hash, got {}",
+                            space_delim_strings[1]
+                        )
+                    })?;
+                let search_depth = match space_delim_strings.get(2) {
+                    Some(depth) => depth.parse::<u64>().map_err(|error| {
+                        format_parse_data_error(
+                            "search_depth",
+                            InputType::UnsignedInt,
+                            depth,
+                            error,
+                        )
+                    })?,
+                    None => DEFAULT_TXN_HASH_SEARCH_DEPTH,
+                };
+                self.find_verified_txn_by_hash(hash, search_depth)?
+            }
+        };
+        let write_set = self.replay_write_set(version, &txn, &info, &events);
+        Ok(TransactionDetail {
+            view: views::TransactionView::try_from_tx_and_events(version, txn, info, events)?,
+            write_set,
+        })
+    }
+
+    /// Scan the latest `search_depth` transactions backwards, a page at a time, for `hash`.
+    fn find_verified_txn_by_hash(
+        &self,
+        hash: HashValue,
+        search_depth: u64,
+    ) -> Result<(Version, Transaction, TransactionInfo, Vec<ContractEvent>)> {
+        self.client.update_and_verify_state_proof()?;
+        let latest_version = self.client.trusted_state().version();
+        let lowest_version = (latest_version + 1).saturating_sub(search_depth);
+        let mut end_version = latest_version + 1;
+        while end_version > lowest_version {
+            let start_version = end_version
+                .saturating_sub(TXN_DETAIL_PAGE_SIZE)
+                .max(lowest_version);
+            let page = self
+                .client
+                .get_verified_transactions(start_version, end_version - start_version)?;
+            if let Some(found) = verified_txns(page)?
+                .into_iter()
+                .find(|(_, txn, _, _)| txn.hash() == hash)
+            {
+                return Ok(found);
+            }
+            end_version = start_version;
+        }
+        bail!(
+            "Transaction {} not found in the latest {} transactions",
+            hash,
+            search_depth
+        )
+    }
+
+    /// Re-execute the transaction at `version` on the state right before it and check the output
+    /// against the verified transaction info and events.
+    fn replay_write_set(
+        &self,
+        version: Version,
+        txn: &Transaction,
+        info: &TransactionInfo,
+        events: &[ContractEvent],
+    ) -> Result<WriteSet> {
+        let remote = JsonRpcDebuggerInterface::new(self.url.as_str())?;
+        // The debugger view reads state as of `version - 1`.
+        let state_view = DebuggerStateView::new(&remote, version);
+        let output = DiemVM::execute_block(vec![txn.clone()], &state_view)
+            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?
+            .pop()
+            .ok_or_else(|| format_err!("VM returned no output for replayed transaction"))?;
+        ensure!(
+            output.status() == &TransactionStatus::Keep(info.status().clone()),
+            "Replayed status {:?} does not match the committed status {:?}",
+            output.status(),
+            info.status()
+        );
+        ensure!(
+            output.gas_used() == info.gas_used(),
+            "Replayed gas usage {} does not match the committed gas usage {}",
+            output.gas_used(),
+            info.gas_used()
+        );
+        ensure!(
+            output.events() == events,
+            "Replayed events do not match the committed events"
+        );
+        Ok(output.write_set().clone())
     }
 
     /// Get account address and (if applicable) authentication key from parameter. If the parameter
@@ -1579,7 +2370,9 @@ impl ClientProxy {
         &self,
         para: &str,
     ) -> Result<(AccountAddress, Option<AuthenticationKey>)> {
-        if para.starts_with("0x") {
+        if let Some(alias) = para.strip_prefix(ALIAS_PREFIX) {
+            Ok((self.address_book.resolve(alias)?, None))
+        } else if para.starts_with("0x") {
             //////// 0L ////////
             let (_, addr_hex) = para.split_at(2);
             let mut padding_prefix = String::from("00000000000000000000000000000000");
@@ -1635,14 +2428,7 @@ impl ClientProxy {
             Some(account) => account,
         };
 
-        let path = match space_delim_strings[2] {
-            "sent" => account_view.sent_events_key,
-            "received" => account_view.received_events_key,
-            _ => bail!(
-                "Unknown event type: {:?}, only sent and received are supported",
-                space_delim_strings[2]
-            ),
-        };
+        let path = event_key_by_type(&account_view, space_delim_strings[2])?;
         let start_seq_number = space_delim_strings[3].parse::<u64>().map_err(|error| {
             format_parse_data_error(
                 "start_seq_number",
@@ -1665,6 +2451,60 @@ impl ClientProxy {
         ))
     }
 
+    /// Poll for events by account and event type, handing each newly committed event to
+    /// `on_event` in order. Starts after the latest event unless `--from-seq` is given, and only
+    /// returns on error.
+    pub fn follow_events(
+        &mut self,
+        space_delim_strings: &[&str],
+        mut on_event: impl FnMut(&views::EventView),
+    ) -> Result<()> {
+        ensure!(
+            space_delim_strings.len() == 4 || space_delim_strings.len() == 6,
+            "Invalid number of arguments, required 4 or 6, given {}",
+            space_delim_strings.len()
+        );
+        let (account, _) = self.get_account_address_from_parameter(space_delim_strings[2])?;
+        let account_view = match self.client.get_account(&account)? {
+            None => bail!("No account found for address {:?}", account),
+            Some(account) => account,
+        };
+        let event_type = space_delim_strings[3];
+        let event_key = event_key_by_type(&account_view, event_type)?;
+
+        let mut next_seq = match &space_delim_strings[4..] {
+            [] => self.get_event_count(&account, event_type)?,
+            ["--from-seq", seq_number] => seq_number.parse::<u64>().map_err(|error| {
+                format_parse_data_error("from_seq", InputType::UnsignedInt, seq_number, error)
+            })?,
+            [option, ..] => bail!("Unknown option: {:?}, expected --from-seq", option),
+        };
+        loop {
+            let events = self
+                .client
+                .get_events(event_key, next_seq, FOLLOW_EVENTS_PAGE_SIZE)?;
+            match events.last() {
+                Some(last) => next_seq = last.sequence_number + 1,
+                None => std::thread::sleep(FOLLOW_EVENTS_POLL_INTERVAL),
+            }
+            events.iter().for_each(&mut on_event);
+        }
+    }
+
+    /// Number of events emitted so far on the sent or received event handle of `account`.
+    fn get_event_count(&self, account: &AccountAddress, event_type: &str) -> Result<u64> {
+        let (blob, _) = self.client.get_verified_account_state_blob(account)?;
+        let account_resource = match blob {
+            Some(blob) => AccountState::try_from(&blob)?.get_account_resource()?,
+            None => None,
+        }
+        .ok_or_else(|| format_err!("No account resource found for address {:?}", account))?;
+        Ok(match event_type {
+            "sent" => account_resource.sent_events().count(),
+            _ => account_resource.received_events().count(),
+        })
+    }
+
     /// Write mnemonic recover to the file specified.
     pub fn write_recovery(&self, space_delim_strings: &[&str]) -> Result<()> {
         ensure!(
@@ -1691,10 +2531,50 @@ impl ClientProxy {
         self.recover_accounts_in_wallet()
     }
 
+    /// Recover wallet accounts from a mnemonic typed word by word with terminal echo disabled,
+    /// from command 'recover --interactive [--words <count>] [file]'. The phrase is only kept in
+    /// memory, and is written to `file` only after its checksum has been verified.
+    pub fn recover_wallet_accounts_interactive(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<Vec<AddressAndIndex>> {
+        ensure!(
+            space_delim_strings.get(1) == Some(&"--interactive"),
+            "inconsistent command '{}' for recover_wallet_accounts_interactive",
+            space_delim_strings[0]
+        );
+        let mut args = &space_delim_strings[2..];
+        let mut word_count = DEFAULT_MNEMONIC_WORDS;
+        if args.first() == Some(&"--words") {
+            let count = args
+                .get(1)
+                .ok_or_else(|| format_err!("Missing word count after --words"))?;
+            word_count = count.parse::<usize>().map_err(|error| {
+                format_parse_data_error("word_count", InputType::Usize, count, error)
+            })?;
+            args = &args[2..];
+        }
+        ensure!(
+            args.len() <= 1,
+            "Invalid number of arguments for recovering wallets"
+        );
+
+        let mnemonic = read_mnemonic(word_count, |prompt| {
+            rpassword::read_password_from_tty(Some(prompt))
+        })?;
+        let mut wallet = WalletLibrary::new_from_mnemonic(mnemonic);
+        wallet.generate_addresses(6)?; //////// 0L ////////
+        if let Some(path) = args.first() {
+            wallet.write_recovery(&Path::new(path))?;
+        }
+        self.set_wallet(wallet);
+        self.recover_accounts_in_wallet()
+    }
+
     /// Recover accounts in wallets and sync state if sync_on_wallet_recovery is true.
     pub fn recover_accounts_in_wallet(&mut self) -> Result<Vec<AddressAndIndex>> {
         let wallet_addresses = self.wallet.get_addresses()?;
-        println!("length: {}", wallet_addresses.len()); //////// 0L ////////
+        println!("{}", tr!("account.recover.count", wallet_addresses.len())); //////// 0L ////////
         let mut account_data = Vec::new();
         for address in wallet_addresses {
             let auth_key = self.wallet.get_authentication_key(&address)?;
@@ -1740,13 +2620,9 @@ impl ClientProxy {
         &mut self,
         address: AccountAddress,
     ) -> Result<(Option<AnnotatedAccountStateBlob>, Version)> {
-        let (blob, ver) = self.client.get_account_state_blob(&address)?;
+        let (blob, ver) = self.client.get_verified_account_state_blob(&address)?;
         if let Some(account_blob) = blob {
-            let state_view = NullStateView::default();
-            let annotator = MoveValueAnnotator::new(&state_view);
-            let annotate_blob =
-                annotator.view_account_state(&AccountState::try_from(&account_blob)?)?;
-            Ok((Some(annotate_blob), ver))
+            Ok((Some(annotate_account_blob(&account_blob)?), ver))
         } else {
             Ok((None, ver))
         }
@@ -1765,7 +2641,7 @@ impl ClientProxy {
         // self.client.update_and_verify_state_proof()?;
 
         if let Some(ref ac) = account.as_ref() {
-            self.update_account_seq(address, ac.sequence_number)
+            self.sync_sequence_number(address, ac.sequence_number)?;
         }
         Ok(account)
     }
@@ -1966,37 +2842,21 @@ impl ClientProxy {
         num_coins: u64,
         coin_currency: String,
     ) -> Result<()> {
-        let client = reqwest::blocking::Client::new();
-
-        let url = Url::parse_with_params(
-            self.faucet_url.as_str(),
-            &[
-                ("amount", num_coins.to_string().as_str()),
-                ("auth_key", &hex::encode(receiver)),
-                ("currency_code", coin_currency.as_str()),
-                ("return_txns", "true"),
-            ],
-        )?;
+        let txns = self.faucet.mint(&MintRequest {
+            auth_key: receiver,
+            amount: num_coins,
+            currency: coin_currency,
+        })?;
+        self.wait_for_minting_transactions(&txns)
+    }
 
-        let response = client.post(url).send()?;
-        let status_code = response.status();
-        let body = response.text()?;
-        if !status_code.is_success() {
-            return Err(format_err!(
-                "Failed to query remote faucet server[status={}]: {:?}",
-                status_code.as_str(),
-                body,
-            ));
-        }
-        let bytes = hex::decode(body)?;
-        let txns: Vec<SignedTransaction> = bcs::from_bytes(&bytes).unwrap();
-        for txn in &txns {
+    fn wait_for_minting_transactions(&mut self, txns: &[SignedTransaction]) -> Result<()> {
+        for txn in txns {
             self.wait_for_signed_transaction(txn).map_err(|e| {
                 info!("minting transaction error: {}", e);
                 format_err!("transaction execution failed, please retry")
             })?;
         }
-
         Ok(())
     }
 
@@ -2117,6 +2977,58 @@ fn encode_stdlib_upgrade_transaction() -> ChangeSet {
     )
 }
 
+/// Split a verified transaction list into (version, transaction, info, events) entries.
+fn verified_txns(
+    txns: TransactionListWithProof,
+) -> Result<Vec<(Version, Transaction, TransactionInfo, Vec<ContractEvent>)>> {
+    let first_version = match txns.first_transaction_version {
+        Some(version) => version,
+        None => return Ok(vec![]),
+    };
+    let events = txns
+        .events
+        .ok_or_else(|| format_err!("Transactions were returned without their events"))?;
+    Ok(txns
+        .transactions
+        .into_iter()
+        .zip(txns.proof.transaction_infos().iter().cloned())
+        .zip(events)
+        .enumerate()
+        .map(|(index, ((txn, info), events))| (first_version + index as u64, txn, info, events))
+        .collect())
+}
+
+/// Parse a script argument given either as a typed literal like `u64:100` or, for backwards
+/// compatibility, as an untyped literal like `100` or `0x1`.
+fn parse_script_argument(s: &str) -> Result<TransactionArgument> {
+    if split_typed_argument(s).is_some() {
+        parse_typed_argument(s, |address| {
+            if address.starts_with("0x") {
+                Ok(AccountAddress::from_hex_literal(address)?)
+            } else {
+                ClientProxy::address_from_strings(address)
+            }
+        })
+    } else {
+        parse_transaction_argument_for_client(s)
+    }
+}
+
+/// Parse `<address>::<module>::<function>`, returning `None` for anything else, e.g. a path to
+/// a compiled script.
+fn parse_script_function_id(s: &str) -> Result<Option<(ModuleId, Identifier)>> {
+    let parts: Vec<_> = s.split("::").collect();
+    if parts.len() != 3 {
+        return Ok(None);
+    }
+    let address = AccountAddress::from_hex_literal(parts[0])
+        .or_else(|_| ClientProxy::address_from_strings(parts[0]))?;
+    Ok(Some((
+        ModuleId::new(address, Identifier::new(parts[1])?),
+        Identifier::new(parts[2])?,
+    )))
+}
+
 fn parse_transaction_argument_for_client(s: &str) -> Result<TransactionArgument> {
     if is_address(s) {
         let account_address = ClientProxy::address_from_strings(s)?;
@@ -2125,6 +3037,23 @@ fn parse_transaction_argument_for_client(s: &str) -> Result<TransactionArgument>
     parse_transaction_argument(s)
 }
 
+fn annotate_account_blob(blob: &AccountStateBlob) -> Result<AnnotatedAccountStateBlob> {
+    let state_view = NullStateView::default();
+    let annotator = MoveValueAnnotator::new(&state_view);
+    annotator.view_account_state(&AccountState::try_from(blob)?)
+}
+
+fn event_key_by_type(account_view: &views::AccountView, event_type: &str) -> Result<EventKey> {
+    match event_type {
+        "sent" => Ok(account_view.sent_events_key),
+        "received" => Ok(account_view.received_events_key),
+        _ => bail!(
+            "Unknown event type: {:?}, only sent and received are supported",
+            event_type
+        ),
+    }
+}
+
 fn format_parse_data_error<T: std::fmt::Debug>(
     field: &str,
     input_type: InputType,
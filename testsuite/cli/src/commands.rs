@@ -2,20 +2,28 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    account_commands::AccountCommand, client_proxy::ClientProxy, counters::COUNTER_CLIENT_ERRORS,
-    dev_commands::DevCommand, info_commands::InfoCommand, query_commands::QueryCommand,
-    transfer_commands::TransferCommand,
+    account_commands::AccountCommand, address_book_commands::AddressBookCommand,
+    client_proxy::ClientProxy, counters::COUNTER_CLIENT_ERRORS, dev_commands::DevCommand,
+    info_commands::InfoCommand, query_commands::QueryCommand, tr,
+    transaction_commands::TransactionCommand, transfer_commands::TransferCommand,
+    trusted_state_commands::TrustedStateCommand,
 };
 use crate::ol_node_commands::NodeCommand;
 use crate::ol_oracle_commands::OracleCommand;
 
-use anyhow::Error;
+use anyhow::{bail, Error, Result};
 use diem_types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey};
 use std::{collections::HashMap, sync::Arc};
 
 /// Print the error and bump up error counter.
 pub fn report_error(msg: &str, e: Error) {
-    println!("[ERROR] {}: {}", msg, e);
+    println!("{} {}: {:#}", tr!("commands.error_prefix"), msg, e);
+    COUNTER_CLIENT_ERRORS.inc();
+}
+
+/// Print the error a command failed with and bump up error counter.
+pub fn report_command_error(e: Error) {
+    println!("{} {:#}", tr!("commands.error_prefix"), e);
     COUNTER_CLIENT_ERRORS.inc();
 }
 
@@ -51,6 +59,9 @@ pub fn get_commands(
         Arc::new(AccountCommand {}),
         Arc::new(QueryCommand {}),
         Arc::new(TransferCommand {}),
+        Arc::new(TransactionCommand {}),
+        Arc::new(AddressBookCommand {}),
+        Arc::new(TrustedStateCommand {}),
         Arc::new(InfoCommand {}),
         ///////// 0L ////////
         Arc::new(NodeCommand {}),
@@ -75,10 +86,7 @@ pub fn parse_cmd(cmd_str: &str) -> Vec<&str> {
 
 /// Print the help message for all sub commands.
 pub fn print_subcommand_help(parent_command: &str, commands: &[Box<dyn Command>]) {
-    println!(
-        "usage: {} <arg>\n\nUse the following args for this command:\n",
-        parent_command
-    );
+    println!("{}", tr!("commands.subcommand_usage", parent_command));
     for cmd in commands {
         println!(
             "{} {}\n\t{}",
@@ -97,7 +105,7 @@ pub fn subcommand_execute(
     commands: Vec<Box<dyn Command>>,
     client: &mut ClientProxy,
     params: &[&str],
-) {
+) -> Result<()> {
     let mut commands_map = HashMap::new();
     for (i, cmd) in commands.iter().enumerate() {
         for alias in cmd.get_aliases() {
@@ -109,12 +117,15 @@ pub fn subcommand_execute(
 
     if params.is_empty() {
         print_subcommand_help(parent_command_name, &commands);
-        return;
+        return Ok(());
     }
 
     match commands_map.get(&params[0]) {
         Some(&idx) => commands[idx].execute(client, &params),
-        _ => print_subcommand_help(parent_command_name, &commands),
+        _ => {
+            print_subcommand_help(parent_command_name, &commands);
+            bail!(tr!("main.unknown_command", format!("{:?}", params[0])))
+        }
     }
 }
 
@@ -128,6 +139,10 @@ pub trait Command {
     }
     /// string that describes what the command does.
     fn get_description(&self) -> &'static str;
-    /// code to execute.
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]);
+    /// sub commands dispatched by this command, empty for leaf commands.
+    fn get_subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![]
+    }
+    /// code to execute, failing if the command didn't complete.
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()>;
 }
@@ -0,0 +1,165 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{address_book::ALIAS_PREFIX, commands::Command, AccountData};
+use rustyline::{
+    completion::Completer, highlight::Highlighter, hint::Hinter, validate::Validator, Context,
+    Helper,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// Built-in commands handled by the prompt itself rather than by a `Command`.
+const PROMPT_COMMANDS: &[&str] = &["help", "h", "quit", "q!"];
+
+/// Rustyline helper completing command names, sub command names, account ref ids, the
+/// addresses of accounts known to the client and address book aliases.
+pub struct CliHelper {
+    commands: Vec<&'static str>,
+    subcommands: HashMap<&'static str, Vec<&'static str>>,
+    accounts: Vec<String>,
+    aliases: Vec<String>,
+}
+
+impl CliHelper {
+    pub fn new(commands: &[Arc<dyn Command>]) -> Self {
+        let mut aliases = PROMPT_COMMANDS.to_vec();
+        let mut subcommands = HashMap::new();
+        for command in commands {
+            let subcommand_aliases: Vec<_> = command
+                .get_subcommands()
+                .iter()
+                .flat_map(|subcommand| subcommand.get_aliases())
+                .collect();
+            for alias in command.get_aliases() {
+                aliases.push(alias);
+                subcommands.insert(alias, subcommand_aliases.clone());
+            }
+        }
+        Self {
+            commands: aliases,
+            subcommands,
+            accounts: vec![],
+            aliases: vec![],
+        }
+    }
+
+    /// Refresh the account ref ids and addresses offered as arguments.
+    pub fn set_accounts(&mut self, accounts: &[AccountData]) {
+        self.accounts = (0..accounts.len())
+            .map(|index| index.to_string())
+            .chain(accounts.iter().map(|account| hex::encode(account.address)))
+            .collect();
+    }
+
+    /// Refresh the address book aliases offered as arguments.
+    pub fn set_aliases<'a>(&mut self, aliases: impl Iterator<Item = &'a str>) {
+        self.aliases = aliases
+            .map(|alias| format!("{}{}", ALIAS_PREFIX, alias))
+            .collect();
+    }
+
+    fn has_subcommands(&self, command: &str) -> bool {
+        self.subcommands
+            .get(command)
+            .map_or(false, |subcommands| !subcommands.is_empty())
+    }
+
+    /// Returns the start of the word under the cursor and the candidates completing it.
+    fn candidates(&self, line: &str) -> (usize, Vec<String>) {
+        let start = line.rfind(char::is_whitespace).map_or(0, |index| index + 1);
+        let prefix = &line[start..];
+        let previous: Vec<&str> = line[..start].split_whitespace().collect();
+        let pool: Vec<&str> = match previous.as_slice() {
+            [] => self.commands.clone(),
+            [command] if self.has_subcommands(command) => self.subcommands[command].clone(),
+            _ => self
+                .accounts
+                .iter()
+                .chain(&self.aliases)
+                .map(String::as_str)
+                .collect(),
+        };
+        let mut matches: Vec<String> = pool
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(prefix))
+            .map(str::to_string)
+            .collect();
+        matches.sort();
+        matches.dedup();
+        (start, matches)
+    }
+}
+
+impl Completer for CliHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.candidates(&line[..pos]))
+    }
+}
+
+impl Hinter for CliHelper {
+    type Hint = String;
+}
+
+impl Highlighter for CliHelper {}
+
+impl Validator for CliHelper {}
+
+impl Helper for CliHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{commands::get_commands, AccountStatus};
+    use diem_types::account_address::AccountAddress;
+
+    fn helper() -> CliHelper {
+        let (commands, _) = get_commands(true);
+        let mut helper = CliHelper::new(&commands);
+        helper.set_accounts(&[AccountData {
+            address: AccountAddress::new([0xab; AccountAddress::LENGTH]),
+            authentication_key: None,
+            key_pair: None,
+            sequence_number: 0,
+            status: AccountStatus::Local,
+        }]);
+        helper.set_aliases(vec!["alice"].into_iter());
+        helper
+    }
+
+    #[test]
+    fn test_complete_command() {
+        let (start, candidates) = helper().candidates("acc");
+        assert_eq!(start, 0);
+        assert_eq!(candidates, vec!["account"]);
+    }
+
+    #[test]
+    fn test_complete_subcommand() {
+        let (start, candidates) = helper().candidates("account li");
+        assert_eq!(start, 8);
+        assert_eq!(candidates, vec!["list"]);
+    }
+
+    #[test]
+    fn test_complete_account() {
+        let helper = helper();
+        assert_eq!(helper.candidates("query balance ").1.len(), 3);
+        let (start, candidates) = helper.candidates("query balance ab");
+        assert_eq!(start, 14);
+        assert_eq!(candidates, vec!["ab".repeat(AccountAddress::LENGTH)]);
+    }
+
+    #[test]
+    fn test_complete_alias() {
+        let (start, candidates) = helper().candidates("transfer 0 @a");
+        assert_eq!(start, 11);
+        assert_eq!(candidates, vec!["@alice"]);
+    }
+}
@@ -2,12 +2,36 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    batch::run_batch_file,
     client_proxy::ClientProxy,
     commands::{subcommand_execute, Command},
+    tr,
 };
+use anyhow::{bail, ensure, format_err, Context, Result};
 use chrono::{DateTime, Utc};
 use diem_types::waypoint::Waypoint;
-use std::time::{Duration, UNIX_EPOCH};
+use move_binary_format::{
+    access::ModuleAccess,
+    compatibility::{Compatibility, Incompatibility},
+    normalized, CompiledModule,
+};
+use move_core_types::language_storage::ModuleId;
+use std::{
+    fs,
+    time::{Duration, UNIX_EPOCH},
+};
+
+/// Flag of `dev execute` profiling the gas of the script instead of submitting it.
+const PROFILE_GAS_FLAG: &str = "--profile-gas";
+
+/// Flag of `dev execute` stepping through the script in the debugger instead of submitting it.
+const DEBUG_FLAG: &str = "--debug";
+
+/// Option of `dev prove` verifying a single function.
+const VERIFY_ONLY_OPTION: &str = "--verify-only";
+
+/// File the gas profile is written to as folded stacks.
+const FOLDED_STACKS_FILE: &str = "gas_profile.folded";
 
 /// Major command for account related operations.
 pub struct DevCommand {}
@@ -17,21 +41,26 @@ impl Command for DevCommand {
         vec!["dev", "d"]
     }
     fn get_description(&self) -> &'static str {
-        "Local Move development"
+        tr!("dev.description")
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        let commands: Vec<Box<dyn Command>> = vec![
+    fn get_subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![
             Box::new(DevCommandCompile {}),
             Box::new(DevCommandPublish {}),
+            Box::new(DevCommandCheckUpgrade {}),
+            Box::new(DevCommandProve {}),
             Box::new(DevCommandExecute {}),
             Box::new(DevCommandUpgradeStdlib {}),
             Box::new(DevCommandGenWaypoint {}),
             Box::new(DevCommandChangeDiemVersion {}),
             Box::new(DevCommandEnableCustomScript {}),
             Box::new(DevSubmitWriteSet {}),
+            Box::new(DevCommandBatch {}),
             Box::new(DevCommandNoop {}), //////// 0L ////////            
-        ];
-        subcommand_execute(&params[0], commands, client, &params[1..]);
+        ]
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        subcommand_execute(&params[0], self.get_subcommands(), client, &params[1..])
     }
 }
 
@@ -46,23 +75,19 @@ impl Command for DevCommandCompile {
         "<sender_account_address>|<sender_account_ref_id> <file_path> <dependency_source_files...>"
     }
     fn get_description(&self) -> &'static str {
-        "Compile Move program"
+        tr!("dev.compile.description")
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         if params.len() < 3 {
-            println!("Invalid number of arguments for compilation");
-            return;
+            bail!(tr!("dev.compile.invalid_args"));
         }
-        println!(">> Compiling program");
-        match client.compile_program(params) {
-            Ok(paths) => {
-                println!("Successfully compiled a program at:");
-                for p in paths {
-                    println!("  {}", p);
-                }
-            }
-            Err(e) => println!("{}", e),
+        println!("{}", tr!("dev.compile.progress"));
+        let paths = client.compile_program(params)?;
+        println!("{}", tr!("dev.compile.done"));
+        for p in paths {
+            println!("  {}", p);
         }
+        Ok(())
     }
 }
 
@@ -79,21 +104,123 @@ impl Command for DevCommandPublish {
     }
 
     fn get_description(&self) -> &'static str {
-        "Publish Move module on-chain"
+        tr!("dev.publish.description")
+    }
+
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.len() != 3 {
+            bail!(tr!("dev.publish.invalid_args"));
+        }
+        client.publish_module(params)?;
+        println!("{}", tr!("dev.publish.done"));
+        Ok(())
+    }
+}
+
+/// Sub command to verify the specs of a Move module with the Move Prover
+pub struct DevCommandProve {}
+
+impl Command for DevCommandProve {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["prove", "pr"]
+    }
+
+    fn get_params_help(&self) -> &'static str {
+        "<module_source_path> <dependency_source_files...> [--verify-only <function>]"
+    }
+
+    fn get_description(&self) -> &'static str {
+        tr!("dev.prove.description")
+    }
+
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        let (params, function) = match params.iter().position(|p| *p == VERIFY_ONLY_OPTION) {
+            Some(index) if index + 2 == params.len() => (&params[..index], Some(params[index + 1])),
+            Some(_) => bail!(tr!("dev.prove.invalid_args")),
+            None => (params, None),
+        };
+        if params.len() < 2 {
+            bail!(tr!("dev.prove.invalid_args"));
+        }
+        println!("{}", tr!("dev.prove.progress", params[1]));
+        let outcome = client
+            .prove_module(params, function)
+            .context(tr!("dev.prove.error"))?;
+        print!("{}", outcome.diagnostics);
+        ensure!(
+            outcome.verified,
+            tr!("dev.prove.failed", params[1], outcome.error_count)
+        );
+        println!("{}", tr!("dev.prove.verified", params[1]));
+        Ok(())
+    }
+}
+
+/// Sub command to check that a compiled module can be upgraded to a new version
+pub struct DevCommandCheckUpgrade {}
+
+impl Command for DevCommandCheckUpgrade {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["check-upgrade", "cu"]
+    }
+
+    fn get_params_help(&self) -> &'static str {
+        "<old_compiled_module_path> <new_compiled_module_path>"
+    }
+
+    fn get_description(&self) -> &'static str {
+        tr!("dev.check_upgrade.description")
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, _client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         if params.len() != 3 {
-            println!("Invalid number of arguments to publish module");
-            return;
+            bail!(tr!("dev.check_upgrade.invalid_args"));
+        }
+        let (module_id, incompatibilities) =
+            check_upgrade(params[1], params[2]).context(tr!("dev.check_upgrade.error"))?;
+        if incompatibilities.is_empty() {
+            println!("{}", tr!("dev.check_upgrade.compatible", module_id));
+            return Ok(());
+        }
+        let (layout, linking): (Vec<_>, Vec<_>) = incompatibilities
+            .iter()
+            .partition(|incompatibility| incompatibility.breaks_layout());
+        if !linking.is_empty() {
+            println!("{}", tr!("dev.check_upgrade.linking", module_id));
+            for incompatibility in linking {
+                println!("  - {}", incompatibility);
+            }
         }
-        match client.publish_module(params) {
-            Ok(_) => println!("Successfully published module"),
-            Err(e) => println!("{}", e),
+        if !layout.is_empty() {
+            println!("{}", tr!("dev.check_upgrade.layout", module_id));
+            for incompatibility in layout {
+                println!("  - {}", incompatibility);
+            }
         }
+        bail!(tr!("dev.check_upgrade.incompatible", module_id))
     }
 }
 
+/// The changes of the compiled module at `new_path` breaking its compatibility with its previous
+/// version, at `old_path`, which publishing it on chain would be rejected for.
+fn check_upgrade(old_path: &str, new_path: &str) -> Result<(ModuleId, Vec<Incompatibility>)> {
+    let old_module = read_module(old_path)?;
+    let new_module = read_module(new_path)?;
+    Ok((
+        new_module.self_id(),
+        Compatibility::incompatibilities(
+            &normalized::Module::new(&old_module),
+            &normalized::Module::new(&new_module),
+        ),
+    ))
+}
+
+fn read_module(path: &str) -> Result<CompiledModule> {
+    let bytes = fs::read(path).map_err(|e| format_err!("Failed to read {}: {}", path, e))?;
+    CompiledModule::deserialize(&bytes)
+        .map_err(|e| format_err!("Failed to deserialize the module at {}: {:?}", path, e))
+}
+
 /// Sub command to execute a custom Move script
 pub struct DevCommandExecute {}
 
@@ -103,25 +230,78 @@ impl Command for DevCommandExecute {
     }
 
     fn get_params_help(&self) -> &'static str {
-        "<sender_account_address>|<sender_account_ref_id> <compiled_module_path> [parameters]"
+        "<sender_account_address>|<sender_account_ref_id> \
+         <compiled_script_path>|<address>::<module>::<function> [<type>:<value>...] \
+         [--profile-gas|--debug]"
     }
 
     fn get_description(&self) -> &'static str {
-        "Execute custom Move script"
+        tr!("dev.execute.description")
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        let profile_gas = params.contains(&PROFILE_GAS_FLAG);
+        let debug = params.contains(&DEBUG_FLAG);
+        let params: Vec<_> = params
+            .iter()
+            .copied()
+            .filter(|param| *param != PROFILE_GAS_FLAG && *param != DEBUG_FLAG)
+            .collect();
         if params.len() < 3 {
-            println!("Invalid number of arguments to execute script");
-            return;
+            bail!(tr!("dev.execute.invalid_args"));
         }
-        match client.execute_script(params) {
-            Ok(_) => println!("Successfully finished execution"),
-            Err(e) => println!("{}", e),
+        if profile_gas {
+            return profile_script_gas(client, &params);
         }
+        if debug {
+            return debug_script(client, &params);
+        }
+        client.execute_script(&params)?;
+        println!("{}", tr!("commands.execution_done"));
+        Ok(())
     }
 }
 
+/// Executes a script locally and reports where its gas goes, also writing the profile as folded
+/// stacks for flame graph tools.
+fn profile_script_gas(client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+    println!("{}", tr!("dev.profile_gas.progress"));
+    let (vm_status, output, profile) = client
+        .profile_script_gas(params)
+        .context(tr!("dev.profile_gas.error"))?;
+    println!(
+        "{}",
+        tr!("dev.profile_gas.status", format!("{:?}", vm_status))
+    );
+    println!("{}", tr!("dev.profile_gas.gas_used", output.gas_used()));
+    let profile = match profile {
+        Some(profile) => profile,
+        None => {
+            println!("{}", tr!("dev.profile_gas.discarded"));
+            return Ok(());
+        }
+    };
+    print!("{}", profile);
+    fs::write(FOLDED_STACKS_FILE, profile.folded_stacks())
+        .context(tr!("dev.profile_gas.folded_error", FOLDED_STACKS_FILE))?;
+    println!("{}", tr!("dev.profile_gas.folded", FOLDED_STACKS_FILE));
+    Ok(())
+}
+
+/// Executes a script locally, stepping through it in the debugger.
+fn debug_script(client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+    println!("{}", tr!("dev.debug.progress"));
+    let output = client
+        .debug_script(params)
+        .context(tr!("dev.debug.error"))?;
+    println!(
+        "{}",
+        tr!("dev.debug.status", format!("{:?}", output.status()))
+    );
+    println!("{}", tr!("dev.debug.gas_used", output.gas_used()));
+    Ok(())
+}
+
 pub struct DevCommandEnableCustomScript {}
 
 impl Command for DevCommandEnableCustomScript {
@@ -134,18 +314,16 @@ impl Command for DevCommandEnableCustomScript {
     }
 
     fn get_description(&self) -> &'static str {
-        "Allow executing arbitrary script in the network. This disables script hash verification."
+        tr!("dev.enable_custom_script.description")
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         if params.len() != 1 {
-            println!("Invalid number of arguments");
-            return;
-        }
-        match client.enable_custom_script(params, false, true) {
-            Ok(_) => println!("Successfully finished execution"),
-            Err(e) => println!("{}", e),
+            bail!(tr!("commands.invalid_args"));
         }
+        client.enable_custom_script(params, false, true)?;
+        println!("{}", tr!("commands.execution_done"));
+        Ok(())
     }
 }
 
@@ -161,18 +339,16 @@ impl Command for DevCommandChangeDiemVersion {
     }
 
     fn get_description(&self) -> &'static str {
-        "Change the diem_version stored on chain"
+        tr!("dev.change_diem_version.description")
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         if params.len() != 2 {
-            println!("Invalid number of arguments");
-            return;
-        }
-        match client.change_diem_version(params, true) {
-            Ok(_) => println!("Successfully finished execution"),
-            Err(e) => println!("{}", e),
+            bail!(tr!("commands.invalid_args"));
         }
+        client.change_diem_version(params, true)?;
+        println!("{}", tr!("commands.execution_done"));
+        Ok(())
     }
 }
 
@@ -189,15 +365,13 @@ impl Command for DevCommandNoop {
     }
 
     fn get_description(&self) -> &'static str {
-        "Calls demo_e2e tx script, for testing purposes"
+        tr!("dev.noop.description")
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-
-        match client.noop_demo(params, true) {
-            Ok(_) => println!("Successfully finished execution"),
-            Err(e) => println!("{}", e),
-        }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        client.noop_demo(params, true)?;
+        println!("{}", tr!("commands.execution_done"));
+        Ok(())
     }
 }
 
@@ -213,18 +387,16 @@ impl Command for DevCommandUpgradeStdlib {
     }
 
     fn get_description(&self) -> &'static str {
-        "Upgrade the move stdlib used for the blockchain"
+        tr!("dev.upgrade_stdlib.description")
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         if params.len() != 1 {
-            println!("Invalid number of arguments");
-            return;
-        }
-        match client.upgrade_stdlib(params, true) {
-            Ok(_) => println!("Successfully finished execution"),
-            Err(e) => println!("{}", e),
+            bail!(tr!("commands.invalid_args"));
         }
+        client.upgrade_stdlib(params, true)?;
+        println!("{}", tr!("commands.execution_done"));
+        Ok(())
     }
 }
 
@@ -240,40 +412,38 @@ impl Command for DevCommandGenWaypoint {
     }
 
     fn get_description(&self) -> &'static str {
-        "Generate a waypoint for the latest epoch change LedgerInfo"
+        tr!("gen_waypoint.description")
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         if params.len() != 1 {
-            println!("No parameters required for waypoint generation");
-            return;
+            bail!(tr!("gen_waypoint.invalid_args"));
         }
-        println!("Retrieving the uptodate ledger info...");
+        println!("{}", tr!("gen_waypoint.progress"));
         if let Err(e) = client.test_validator_connection() {
-            println!("Failed to get uptodate ledger info connection: {}", e);
-            return;
+            bail!(tr!("gen_waypoint.connection_error", e));
         }
 
         let latest_epoch_change_li = match client.latest_epoch_change_li() {
             Some(li) => li,
-            None => {
-                println!("No epoch change LedgerInfo found");
-                return;
-            }
+            None => bail!(tr!("gen_waypoint.no_epoch_change")),
         };
         let li_time_str = DateTime::<Utc>::from(
             UNIX_EPOCH
                 + Duration::from_micros(latest_epoch_change_li.ledger_info().timestamp_usecs()),
         );
-        match Waypoint::new_epoch_boundary(latest_epoch_change_li.ledger_info()) {
-            Err(e) => println!("Failed to generate a waypoint: {}", e),
-            Ok(waypoint) => println!(
-                "Waypoint (end of epoch {}, time {}): {}",
+        let waypoint = Waypoint::new_epoch_boundary(latest_epoch_change_li.ledger_info())
+            .map_err(|e| format_err!(tr!("gen_waypoint.error", e)))?;
+        println!(
+            "{}",
+            tr!(
+                "gen_waypoint.done",
                 latest_epoch_change_li.ledger_info().epoch(),
                 li_time_str,
                 waypoint
-            ),
-        }
+            )
+        );
+        Ok(())
     }
 }
 
@@ -290,17 +460,47 @@ impl Command for DevSubmitWriteSet {
     }
 
     fn get_description(&self) -> &'static str {
-        "Submit a WriteSet with local diem root account. Path should be a bcs serialized TransactionPayload."
+        tr!("dev.submit_writeset.description")
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         if params.len() < 2 {
-            println!("Invalid number of arguments to execute script");
-            return;
+            bail!(tr!("dev.execute.invalid_args"));
         }
-        match client.submit_writeset(params) {
-            Ok(_) => println!("Successfully finished execution"),
-            Err(e) => println!("{}", e),
+        client.submit_writeset(params)?;
+        println!("{}", tr!("commands.execution_done"));
+        Ok(())
+    }
+}
+
+/// Sub command to run CLI commands from a script file or stdin
+pub struct DevCommandBatch {}
+
+impl Command for DevCommandBatch {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["batch", "b"]
+    }
+
+    fn get_params_help(&self) -> &'static str {
+        "[--abort-on-error] <script_file_path>|-"
+    }
+
+    fn get_description(&self) -> &'static str {
+        tr!("batch.description")
+    }
+
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        let (abort_on_error, path) = match params {
+            [_, path] => (false, path),
+            [_, "--abort-on-error", path] => (true, path),
+            _ => bail!(tr!("batch.invalid_args")),
+        };
+        let summary = run_batch_file(client, path, abort_on_error).context(tr!("batch.error"))?;
+        if summary.aborted {
+            println!("{}", tr!("batch.aborted"));
         }
+        println!("{}", tr!("batch.summary", summary.executed, summary.failed));
+        ensure!(summary.failed == 0, tr!("batch.failed", summary.failed));
+        Ok(())
     }
 }
@@ -1,21 +1,21 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{/*bail,*/ ensure, Error, Result};
-use diem_client::{BlockingClient, Response, WaitForTransactionError, views, views::{OracleUpgradeStateView, TowerStateResourceView, TransactionView, WaypointView}};
+use anyhow::{bail, ensure, format_err, Error, Result};
+use diem_client::{errors::InvalidRequestCode, BlockingClient, MethodRequest, MethodResponse, Response, WaitForTransactionError, views, views::{OracleUpgradeStateView, TowerStateResourceView, TransactionView, WaypointView}};
 use diem_logger::prelude::info;
 use diem_types::{
     account_address::AccountAddress,
-    account_state_blob::AccountStateBlob,
+    account_state_blob::{AccountStateBlob, AccountStateWithProof},
     epoch_change::EpochChangeProof,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
-    transaction::{SignedTransaction, Version},
+    transaction::{SignedTransaction, TransactionListWithProof, Version},
     trusted_state::{TrustedState, TrustedStateChange},
     waypoint::Waypoint,
 };
 use reqwest::Url;
-use std::time::Duration;
+use std::{convert::TryFrom, sync::Mutex, time::Duration};
 
 /// A client connection to an AdmissionControl (AC) service. `DiemClient` also
 /// handles verifying the server's responses, retrying on non-fatal failures, and
@@ -33,12 +33,21 @@ use std::time::Duration;
 ///    AC will be behind us and we will reject their response as stale.
 pub struct DiemClient {
     client: BlockingClient,
+    /// The verified chain state, ratcheted forward whenever a response is
+    /// ahead of it.
+    verified_state: Mutex<VerifiedState>,
+}
+
+struct VerifiedState {
     /// The latest verified chain state.
     trusted_state: TrustedState,
     /// The most recent epoch change ledger info. This is `None` if we only know
     /// about our local [`Waypoint`] and have not yet ratcheted to the remote's
     /// latest state.
     latest_epoch_change_li: Option<LedgerInfoWithSignatures>,
+    /// The ledger info our trusted state was last ratcheted to, used to verify
+    /// proofs of individual responses.
+    latest_li: Option<LedgerInfoWithSignatures>,
 }
 
 impl DiemClient {
@@ -49,8 +58,11 @@ impl DiemClient {
 
         Ok(DiemClient {
             client,
-            trusted_state: initial_trusted_state,
-            latest_epoch_change_li: None,
+            verified_state: Mutex::new(VerifiedState {
+                trusted_state: initial_trusted_state,
+                latest_epoch_change_li: None,
+                latest_li: None,
+            }),
         })
     }
 
@@ -63,13 +75,46 @@ impl DiemClient {
             .map(Response::into_inner)
     }
 
+    /// Submits a transaction and waits for it to commit, for up to `timeout`. Returns `None` if it
+    /// is still pending by then, or if the node is too old to wait for it.
+    pub fn submit_and_wait(
+        &self,
+        transaction: &SignedTransaction,
+        timeout: Duration,
+    ) -> Result<Option<views::TransactionView>> {
+        match self.client.submit_and_wait(transaction, Some(timeout)) {
+            Ok(response) => Ok(response.into_inner()),
+            Err(e)
+                if e.json_rpc_error().map_or(false, |error| {
+                    error.code == InvalidRequestCode::MethodNotFound as i16
+                }) =>
+            {
+                self.submit_transaction(transaction)?;
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
     /// Retrieves account information
     /// - If `with_state_proof`, will also retrieve state proof from node and update trusted_state accordingly
     pub fn get_account(&self, account: &AccountAddress) -> Result<Option<views::AccountView>> {
         self.client
             .get_account(*account)
             .map_err(Into::into)
-            .map(Response::into_inner)
+            .and_then(|response| self.check_response_version(response))
+    }
+
+    /// Retrieves account information as of `version`
+    pub fn get_account_by_version(
+        &self,
+        account: &AccountAddress,
+        version: Version,
+    ) -> Result<Option<views::AccountView>> {
+        self.client
+            .get_account_by_version(*account, version)
+            .map_err(Into::into)
+            .and_then(|response| self.check_response_version(response))
     }
 
     ///////// 0L ////////
@@ -81,7 +126,7 @@ impl DiemClient {
         self.client
             .get_miner_state(*account)
             .map_err(Into::into)
-            .map(Response::into_inner)
+            .and_then(|response| self.check_response_version(response))
     }
 
     ///////// 0L ////////
@@ -90,7 +135,7 @@ impl DiemClient {
         self.client
             .get_oracle_upgrade_state()
             .map_err(Into::into)
-            .map(Response::into_inner)
+            .and_then(|response| self.check_response_version(response))
     }
 
       ///////// 0L ////////
@@ -99,22 +144,97 @@ impl DiemClient {
         self.client
             .get_waypoint()
             .map_err(Into::into)
-            .map(Response::into_inner)
+            .and_then(|response| self.check_response_version(response))
     }
 
-    pub fn get_account_state_blob(
+    /// Retrieves the account state blob at the latest verified version and checks its proof
+    /// against the verified ledger info.
+    pub fn get_verified_account_state_blob(
         &self,
         account: &AccountAddress,
     ) -> Result<(Option<AccountStateBlob>, Version)> {
-        let ret = self
+        self.update_and_verify_state_proof()?;
+        let latest_li = self
+            .verified_state
+            .lock()
+            .unwrap()
+            .latest_li
+            .clone()
+            .ok_or_else(|| format_err!("No verified ledger info to check the proof against"))?;
+        let version = latest_li.ledger_info().version();
+        let view = self
             .client
-            .get_account_state_with_proof(*account, None, None)
+            .get_account_state_with_proof(*account, Some(version), Some(version))
             .map(Response::into_inner)?;
-        if let Some(blob) = ret.blob {
-            Ok((Some(bcs::from_bytes(&blob)?), ret.version))
-        } else {
-            Ok((None, ret.version))
+        let account_state_with_proof = AccountStateWithProof::try_from(&view)?;
+        account_state_with_proof
+            .verify(latest_li.ledger_info(), version, *account)
+            .map_err(|e| {
+                format_err!(
+                    "Invalid proof for account {} at version {}: {}",
+                    account,
+                    version,
+                    e
+                )
+            })?;
+        Ok((account_state_with_proof.blob, version))
+    }
+
+    /// Retrieves up to `limit` transactions starting at `start_version` together with their
+    /// events and transaction infos. The state proof is requested in the same batch so that both
+    /// are served at the same ledger version, and the list is verified against that ledger info.
+    pub fn get_verified_transactions(
+        &self,
+        start_version: Version,
+        limit: u64,
+    ) -> Result<TransactionListWithProof> {
+        let mut responses = self
+            .client
+            .batch(vec![
+                MethodRequest::get_state_proof(self.trusted_state().version()),
+                MethodRequest::get_transactions_with_proofs(start_version, limit, true),
+            ])?
+            .into_iter();
+        let mut next_response = || -> Result<MethodResponse> {
+            responses
+                .next()
+                .ok_or_else(|| format_err!("Missing response in batch"))?
+                .map(Response::into_inner)
+                .map_err(Error::from)
+        };
+        let state_proof = next_response()?.try_into_get_state_proof()?;
+        let txns_view = match next_response()? {
+            MethodResponse::GetTransactionsWithProofs(view) => view,
+            other => bail!(
+                "Expected transactions with proofs, got {:?}",
+                other.method()
+            ),
+        };
+        self.verify_state_proof(state_proof)?;
+
+        let txns = match txns_view {
+            Some(view) => view.try_into_txn_list_with_proof(start_version)?,
+            None => return Ok(TransactionListWithProof::new_empty()),
+        };
+        if txns.is_empty() {
+            return Ok(txns);
         }
+        let latest_li = self
+            .verified_state
+            .lock()
+            .unwrap()
+            .latest_li
+            .clone()
+            .ok_or_else(|| format_err!("No verified ledger info to check the proof against"))?;
+        txns.verify(latest_li.ledger_info(), Some(start_version))
+            .map_err(|e| {
+                format_err!(
+                    "Invalid proof for transactions starting at version {}: {}",
+                    start_version,
+                    e
+                )
+            })?;
+        Ok(txns)
     }
 
     pub fn get_events(
@@ -126,7 +246,7 @@ impl DiemClient {
         self.client
             .get_events(event_key, start, limit)
             .map_err(Into::into)
-            .map(Response::into_inner)
+            .and_then(|response| self.check_response_version(response))
     }
 
     pub fn wait_for_transaction(
@@ -144,7 +264,7 @@ impl DiemClient {
         self.client
             .get_metadata()
             .map_err(Into::into)
-            .map(Response::into_inner)
+            .and_then(|response| self.check_response_version(response))
     }
 
     /// Gets the currency info stored on-chain
@@ -152,11 +272,11 @@ impl DiemClient {
         self.client
             .get_currencies()
             .map_err(Into::into)
-            .map(Response::into_inner)
+            .and_then(|response| self.check_response_version(response))
     }
 
     /// Retrieves and checks the state proof
-    pub fn update_and_verify_state_proof(&mut self) -> Result<()> {
+    pub fn update_and_verify_state_proof(&self) -> Result<()> {
         let state_proof = self
             .client
             .get_state_proof(self.trusted_state().version())
@@ -165,14 +285,44 @@ impl DiemClient {
         self.verify_state_proof(state_proof)
     }
 
-    fn verify_state_proof(&mut self, state_proof: views::StateProofView) -> Result<()> {
-        let state = self.trusted_state();
+    /// Unwraps a response after making sure the ledger version it was served at is
+    /// covered by our trusted state, ratcheting the trusted state forward through the
+    /// server's epoch change proofs first if the response is ahead of it.
+    ///
+    /// This only rejects responses from a server on another chain or ahead of what it can
+    /// prove; the contents aren't proven. Queries needing that go through the
+    /// `get_verified_*` calls, which check the proofs against the latest verified ledger info.
+    fn check_response_version<T>(&self, response: Response<T>) -> Result<T> {
+        let (inner, state) = response.into_parts();
+        if state.version > self.trusted_state().version() {
+            self.update_and_verify_state_proof().map_err(|e| {
+                format_err!(
+                    "Unable to verify response at version {} against trusted waypoint {}: {}",
+                    state.version,
+                    self.trusted_state().waypoint(),
+                    e
+                )
+            })?;
+            let trusted_version = self.trusted_state().version();
+            ensure!(
+                state.version <= trusted_version,
+                "Response at version {} is ahead of the latest verifiable version {}",
+                state.version,
+                trusted_version,
+            );
+        }
+        Ok(inner)
+    }
 
+    fn verify_state_proof(&self, state_proof: views::StateProofView) -> Result<()> {
         let li: LedgerInfoWithSignatures =
             bcs::from_bytes(&state_proof.ledger_info_with_signatures)?;
         let epoch_change_proof: EpochChangeProof =
             bcs::from_bytes(&state_proof.epoch_change_proof)?;
 
+        let mut verified_state = self.verified_state.lock().unwrap();
+        let state = verified_state.trusted_state.clone();
+
         // check ledger info version
         ensure!(
             li.ledger_info().version() >= state.version(),
@@ -195,17 +345,36 @@ impl DiemClient {
                         .expect("no validator set in epoch change ledger info"),
                 );
                 // Update client state
-                self.update_trusted_state(new_state);
-                self.update_latest_epoch_change_li(latest_epoch_change_li.clone());
+                verified_state.latest_epoch_change_li = Some(latest_epoch_change_li.clone());
+                verified_state.trusted_state = new_state;
             }
             TrustedStateChange::Version { new_state } => {
                 if state.version() < new_state.version() {
                     info!("Verified version change to: {}", new_state.version());
                 }
-                self.update_trusted_state(new_state);
+                verified_state.trusted_state = new_state;
             }
             TrustedStateChange::NoChange => (),
         }
+        verified_state.latest_li = Some(li);
+        Ok(())
+    }
+
+    /// Replace the trusted state with one starting from `waypoint`. Unless `force` is set, the
+    /// waypoint must not be behind the currently trusted version.
+    pub fn set_waypoint(&self, waypoint: Waypoint, force: bool) -> Result<()> {
+        let mut verified_state = self.verified_state.lock().unwrap();
+        let trusted_version = verified_state.trusted_state.version();
+        if !force && waypoint.version() < trusted_version {
+            bail!(
+                "Waypoint version {} is behind the trusted version {}, use --force to roll back",
+                waypoint.version(),
+                trusted_version
+            );
+        }
+        verified_state.trusted_state = TrustedState::from(waypoint);
+        verified_state.latest_epoch_change_li = None;
+        verified_state.latest_li = None;
         Ok(())
     }
 
@@ -221,21 +390,17 @@ impl DiemClient {
     }
 
     /// LedgerInfo corresponding to the latest epoch change.
-    pub(crate) fn latest_epoch_change_li(&self) -> Option<&LedgerInfoWithSignatures> {
-        self.latest_epoch_change_li.as_ref()
+    pub(crate) fn latest_epoch_change_li(&self) -> Option<LedgerInfoWithSignatures> {
+        self.verified_state
+            .lock()
+            .unwrap()
+            .latest_epoch_change_li
+            .clone()
     }
 
     /// Latest trusted state
     pub(crate) fn trusted_state(&self) -> TrustedState {
-        self.trusted_state.clone()
-    }
-
-    fn update_latest_epoch_change_li(&mut self, ledger: LedgerInfoWithSignatures) {
-        self.latest_epoch_change_li = Some(ledger);
-    }
-
-    fn update_trusted_state(&mut self, state: TrustedState) {
-        self.trusted_state = state
+        self.verified_state.lock().unwrap().trusted_state.clone()
     }
 
     /// Get transaction from validator by account and sequence number.
@@ -248,7 +413,7 @@ impl DiemClient {
         self.client
             .get_account_transaction(*account, sequence_number, fetch_events)
             .map_err(Into::into)
-            .map(Response::into_inner)
+            .and_then(|response| self.check_response_version(response))
     }
 
     // 0L todo: Not sure if it is possible to implement this fn with diem 1.3.0 code
@@ -265,7 +430,7 @@ impl DiemClient {
       self.client
             .get_account_transactions(account, start_height, num_txs_limit, fetch_events)
             .map_err(Into::into)
-            .map(Response::into_inner)
+            .and_then(|response| self.check_response_version(response))
     }
 
     /// Get transactions in range (start_version..start_version + limit - 1) from validator.
@@ -278,6 +443,6 @@ impl DiemClient {
         self.client
             .get_transactions(start_version, limit, fetch_events)
             .map_err(Into::into)
-            .map(Response::into_inner)
+            .and_then(|response| self.check_response_version(response))
     }
 }
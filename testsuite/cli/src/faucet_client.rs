@@ -0,0 +1,235 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{format_err, Result};
+use diem_types::transaction::{authenticator::AuthenticationKey, SignedTransaction};
+use reqwest::{StatusCode, Url};
+use std::{sync::Arc, thread, time::Duration};
+use tokio::sync::Semaphore;
+
+/// Default number of times a request answered with 429 or a 5xx status is retried.
+pub const DEFAULT_MAX_RETRIES: u32 = 5;
+/// Default number of requests kept in flight by `FaucetClient::mint_batch`.
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 16;
+
+/// Delay before the first retry, doubled on every following one.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Upper bound of the delay between two retries.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Client of a testnet faucet service.
+///
+/// Requests carry an optional bearer token so that faucets behind an authenticating proxy can be
+/// used, and are retried with exponential backoff while the faucet is rate limiting (429) or
+/// failing (5xx).
+#[derive(Clone, Debug)]
+pub struct FaucetClient {
+    url: Url,
+    auth_token: Option<String>,
+    max_retries: u32,
+}
+
+/// Coins requested from the faucet for one account.
+#[derive(Clone, Debug)]
+pub struct MintRequest {
+    pub auth_key: AuthenticationKey,
+    pub amount: u64,
+    pub currency: String,
+}
+
+enum Outcome {
+    Minted(Vec<SignedTransaction>),
+    Retry(Duration),
+}
+
+impl FaucetClient {
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            auth_token: None,
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Token sent as `Authorization: Bearer <token>` with every request.
+    pub fn set_auth_token(&mut self, auth_token: Option<String>) {
+        self.auth_token = auth_token;
+    }
+
+    pub fn set_max_retries(&mut self, max_retries: u32) {
+        self.max_retries = max_retries;
+    }
+
+    /// Request coins and return the transactions the faucet submitted for them.
+    pub fn mint(&self, request: &MintRequest) -> Result<Vec<SignedTransaction>> {
+        let client = reqwest::blocking::Client::new();
+        let mut attempt = 0;
+        loop {
+            let mut builder = client.post(self.mint_url(request)?);
+            if let Some(auth_token) = &self.auth_token {
+                builder = builder.bearer_auth(auth_token);
+            }
+            let response = builder.send()?;
+            let status = response.status();
+            match self.outcome(status, response.text()?, attempt)? {
+                Outcome::Minted(txns) => return Ok(txns),
+                Outcome::Retry(delay) => thread::sleep(delay),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Fund many accounts concurrently, keeping at most `concurrency` requests in flight.
+    /// Results are returned in the order of `requests`.
+    pub fn mint_batch(
+        &self,
+        requests: Vec<MintRequest>,
+        concurrency: usize,
+    ) -> Result<Vec<Result<Vec<SignedTransaction>>>> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        Ok(runtime.block_on(async {
+            let client = reqwest::Client::new();
+            let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+            let handles: Vec<_> = requests
+                .into_iter()
+                .map(|request| {
+                    let faucet = self.clone();
+                    let client = client.clone();
+                    let semaphore = Arc::clone(&semaphore);
+                    tokio::spawn(async move {
+                        let _permit = semaphore.acquire().await?;
+                        faucet.mint_async(&client, &request).await
+                    })
+                })
+                .collect();
+            let mut results = Vec::with_capacity(handles.len());
+            for handle in handles {
+                results.push(handle.await.map_err(Into::into).and_then(|result| result));
+            }
+            results
+        }))
+    }
+
+    async fn mint_async(
+        &self,
+        client: &reqwest::Client,
+        request: &MintRequest,
+    ) -> Result<Vec<SignedTransaction>> {
+        let mut attempt = 0;
+        loop {
+            let mut builder = client.post(self.mint_url(request)?);
+            if let Some(auth_token) = &self.auth_token {
+                builder = builder.bearer_auth(auth_token);
+            }
+            let response = builder.send().await?;
+            let status = response.status();
+            match self.outcome(status, response.text().await?, attempt)? {
+                Outcome::Minted(txns) => return Ok(txns),
+                Outcome::Retry(delay) => tokio::time::sleep(delay).await,
+            }
+            attempt += 1;
+        }
+    }
+
+    fn mint_url(&self, request: &MintRequest) -> Result<Url> {
+        Ok(Url::parse_with_params(
+            self.url.as_str(),
+            &[
+                ("amount", request.amount.to_string().as_str()),
+                ("auth_key", &hex::encode(request.auth_key)),
+                ("currency_code", request.currency.as_str()),
+                ("return_txns", "true"),
+            ],
+        )?)
+    }
+
+    fn outcome(&self, status: StatusCode, body: String, attempt: u32) -> Result<Outcome> {
+        if status.is_success() {
+            let txns = bcs::from_bytes(&hex::decode(body)?)?;
+            return Ok(Outcome::Minted(txns));
+        }
+        if is_retryable(status) && attempt < self.max_retries {
+            return Ok(Outcome::Retry(backoff(attempt)));
+        }
+        Err(format_err!(
+            "Failed to query remote faucet server[status={}]: {:?}",
+            status.as_str(),
+            body,
+        ))
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn backoff(attempt: u32) -> Duration {
+    (INITIAL_BACKOFF * 2u32.saturating_pow(attempt)).min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn faucet() -> FaucetClient {
+        FaucetClient::new(Url::parse("http://localhost:8000/mint").unwrap())
+    }
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(0), INITIAL_BACKOFF);
+        assert_eq!(backoff(1), INITIAL_BACKOFF * 2);
+        assert_eq!(backoff(3), INITIAL_BACKOFF * 8);
+        assert_eq!(backoff(100), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_outcome() {
+        let faucet = faucet();
+        let empty = hex::encode(bcs::to_bytes(&Vec::<SignedTransaction>::new()).unwrap());
+        assert!(matches!(
+            faucet.outcome(StatusCode::OK, empty, 0),
+            Ok(Outcome::Minted(txns)) if txns.is_empty()
+        ));
+        assert!(matches!(
+            faucet.outcome(StatusCode::TOO_MANY_REQUESTS, String::new(), 0),
+            Ok(Outcome::Retry(_))
+        ));
+        assert!(matches!(
+            faucet.outcome(StatusCode::BAD_GATEWAY, String::new(), 1),
+            Ok(Outcome::Retry(_))
+        ));
+        assert!(faucet
+            .outcome(
+                StatusCode::SERVICE_UNAVAILABLE,
+                String::new(),
+                DEFAULT_MAX_RETRIES
+            )
+            .is_err());
+        assert!(faucet
+            .outcome(StatusCode::BAD_REQUEST, String::new(), 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_mint_url() {
+        let request = MintRequest {
+            auth_key: AuthenticationKey::new([1; AuthenticationKey::LENGTH]),
+            amount: 10,
+            currency: "XUS".to_string(),
+        };
+        let url = faucet().mint_url(&request).unwrap();
+        assert_eq!(url.path(), "/mint");
+        let query: Vec<_> = url.query_pairs().map(|(key, _)| key.into_owned()).collect();
+        assert_eq!(
+            query,
+            vec!["amount", "auth_key", "currency_code", "return_txns"]
+        );
+    }
+}
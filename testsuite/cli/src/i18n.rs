@@ -0,0 +1,901 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Message catalog for user-facing CLI output.
+//!
+//! Every string printed by the command layer is looked up by a stable key in the catalog of the
+//! active [`Locale`]. Keys missing from a non-English catalog fall back to English, so a partially
+//! translated catalog never hides output from the operator. Positional `{}` placeholders in a
+//! message are filled in order by the [`tr!`] macro.
+
+use anyhow::{format_err, Error, Result};
+use once_cell::sync::OnceCell;
+use std::{collections::HashMap, fmt, str::FromStr};
+
+/// Environment variable consulted when no `--locale` flag is passed.
+pub const LOCALE_ENV_VAR: &str = "DIEM_CLI_LOCALE";
+
+static ACTIVE_LOCALE: OnceCell<Locale> = OnceCell::new();
+
+/// Languages the CLI has a message catalog for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Locale {
+    /// English, the reference catalog every key must exist in.
+    English,
+    /// Spanish, the pilot translation.
+    Spanish,
+}
+
+impl Locale {
+    /// All supported locales.
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::Spanish];
+
+    /// Short language tag used on the command line and in the environment.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::Spanish => "es",
+        }
+    }
+
+    /// Pick the locale from an explicit flag, then `DIEM_CLI_LOCALE`, then `LANG`, defaulting to
+    /// English when none of them name a supported language.
+    pub fn detect(flag: Option<&str>) -> Result<Self> {
+        if let Some(tag) = flag {
+            return tag.parse();
+        }
+        if let Ok(tag) = std::env::var(LOCALE_ENV_VAR) {
+            return tag.parse();
+        }
+        Ok(std::env::var("LANG")
+            .ok()
+            .and_then(|lang| lang.parse().ok())
+            .unwrap_or(Locale::English))
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::English => EN,
+            Locale::Spanish => ES,
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = Error;
+
+    /// Accepts bare tags (`es`) as well as POSIX locale names (`es_MX.UTF-8`).
+    fn from_str(s: &str) -> Result<Self> {
+        let language = s
+            .split(|c: char| c == '_' || c == '-' || c == '.')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        Locale::ALL
+            .iter()
+            .copied()
+            .find(|locale| locale.tag() == language)
+            .ok_or_else(|| format_err!("Unsupported locale: {:?}", s))
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.tag())
+    }
+}
+
+/// Set the locale used for the rest of the process. Only the first call has an effect.
+pub fn set_locale(locale: Locale) {
+    let _ = ACTIVE_LOCALE.set(locale);
+}
+
+/// The active locale, English if none has been set.
+pub fn locale() -> Locale {
+    ACTIVE_LOCALE.get().copied().unwrap_or(Locale::English)
+}
+
+/// Look up `key` in the active catalog, falling back to English and then to the key itself.
+pub fn lookup(key: &'static str) -> &'static str {
+    lookup_in(locale(), key)
+}
+
+/// Look up `key` in the catalog of `locale`, falling back to English and then to the key itself.
+pub fn lookup_in(locale: Locale, key: &'static str) -> &'static str {
+    static INDEX: OnceCell<HashMap<Locale, HashMap<&'static str, &'static str>>> = OnceCell::new();
+    let index = INDEX.get_or_init(|| {
+        Locale::ALL
+            .iter()
+            .map(|locale| (*locale, locale.catalog().iter().copied().collect()))
+            .collect()
+    });
+    index[&locale]
+        .get(key)
+        .or_else(|| index[&Locale::English].get(key))
+        .copied()
+        .unwrap_or(key)
+}
+
+/// Replace each `{}` in `template` with the next argument, in order.
+pub fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(pos) = rest.find("{}") {
+        out.push_str(&rest[..pos]);
+        match args.next() {
+            Some(arg) => out.push_str(&arg.to_string()),
+            None => out.push_str("{}"),
+        }
+        rest = &rest[pos + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Translate a catalog key, optionally filling `{}` placeholders with the given arguments.
+///
+/// `tr!("key")` yields a `&'static str`; `tr!("key", a, b)` yields a `String`.
+#[macro_export]
+macro_rules! tr {
+    ($key:literal) => {
+        $crate::i18n::lookup($key)
+    };
+    ($key:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::lookup($key), &[$(&$arg),+])
+    };
+}
+
+#[rustfmt::skip]
+const EN: &[(&str, &str)] = &[
+    // main
+    ("main.connected", "Connected to validator at: {}, {}"),
+    ("main.ledger_info", "latest version = {}, timestamp = {}"),
+    ("main.input_prompt", "Please, input commands: \n"),
+    ("main.unknown_command", "Unknown command: {}"),
+    ("main.history_save_failed", "Failed to save history to {}: {}"),
+    ("main.usage", "usage: <command> <args>\n\nUse the following commands:\n"),
+    ("main.help_description", "Prints this help"),
+    ("main.quit_description", "Exit this client"),
+    ("main.error_recovering_wallet", "Error recovering Diem wallet"),
+    ("main.wallet_recovered", "Wallet recovered and the first {} child accounts were derived"),
+    ("main.error_loading_address_book", "Error loading address book"),
+    ("main.unsupported_locale", "{}, falling back to English"),
+    ("main.readline_error", "Readline error: {}"),
+    // commands
+    ("commands.error_prefix", "[ERROR]"),
+    ("commands.subcommand_usage", "usage: {} <arg>\n\nUse the following args for this command:\n"),
+    ("commands.invalid_args", "Invalid number of arguments"),
+    ("commands.execution_done", "Successfully finished execution"),
+    // account
+    ("account.description", "Account operations"),
+    ("account.create.description", "Create a local account--no on-chain effect. Returns reference ID to use in other operations"),
+    ("account.create.progress", ">> Creating/retrieving next local account from wallet"),
+    ("account.create.done", "Created/retrieved local account #{} address {}"),
+    ("account.create.error", "Error creating local account"),
+    ("account.recover.description", "Recover Diem wallet from the file path, or with --interactive from a mnemonic typed word by word at a hidden prompt, optionally saving it to a file once its checksum is verified"),
+    ("account.recover.progress", ">> Recovering Wallet"),
+    ("account.write.description", "Save Diem wallet mnemonic recovery seed to disk"),
+    ("account.write.progress", ">> Saving Diem wallet mnemonic recovery seed to disk"),
+    ("account.write.done", "Saved mnemonic seed to disk"),
+    ("account.write.error", "Error writing mnemonic recovery seed to file"),
+    ("account.list.description", "Print all accounts that were created or loaded"),
+    ("account.mint.description", "Send currency of the given type from the faucet address to the given recipient address. Creates an account at the recipient address if one does not already exist."),
+    ("account.mint.invalid_args", "Invalid number of arguments for mint"),
+    ("account.mint.done", "Finished sending coins from faucet!"),
+    ("account.mint.error", "Error transferring coins from faucet"),
+    ("account.add_currency.description", "Add specified currency to the account. Suffix 'b' is for blocking"),
+    ("account.add_currency.invalid_args", "Invalid number of arguments for adding currency to account"),
+    ("account.add_currency.progress", ">> Adding zero balance in currency to account"),
+    ("account.add_currency.done", "Finished adding currency to account!"),
+    ("account.add_currency.submitted", "Currency addition request submitted"),
+    ("account.add_currency.error", "Error adding zero balance in currency to account"),
+    ("account.wallet_entry", "#{} address {}"),
+    ("account.mint_batch.description", "Fund every account listed in a file (one ref id, address or authentication key per line) through the faucet service, sending the requests concurrently"),
+    ("account.mint_batch.invalid_args", "Invalid number of arguments for batch mint"),
+    ("account.mint_batch.failed", "Failed to fund {}"),
+    ("account.mint_batch.done", "Funded {} of {} accounts"),
+    ("account.recover.interactive_hint", "Type each word of the mnemonic, the input is not shown. A unique prefix of a word is enough."),
+    ("account.recover.word_prompt", "Word {} of {}: "),
+    ("account.recover.unknown_word", "No word of the word list starts with that, try again"),
+    ("account.recover.ambiguous_word", "{} words start with that, type more letters"),
+    ("account.recover.invalid_word_count", "A mnemonic has 12, 15, 18, 21 or 24 words, not {}"),
+    ("account.list.none", "No user accounts"),
+    ("account.list.user", "User account index: {}, address: {}, sequence number: {}, status: {}"),
+    ("account.list.root", "AssocRoot account address: {}, sequence_number: {}, status: {}"),
+    ("account.list.tc", "TC account address: {}, sequence_number: {}, status: {}"),
+    ("account.list.testnet_dd", "Testnet DD account address: {}, sequence_number: {}, status: {}"),
+    ("account.mint.creating_recipient", ">> Creating recipient account before minting from faucet"),
+    ("account.mint.progress", ">> Sending coins from faucet"),
+    ("account.update_failed", "account update failed: {}"),
+    ("account.recover.count", "Accounts in wallet: {}"),
+    ("account.create_user.description", "Create on-chain user account from proof"),
+    ("account.create_user.done", "Created account"),
+    ("account.create_user.error", "Error creating user account"),
+    ("account.create_val.description", "Create on-chain user account and configure validator"),
+    ("account.update_val_config.description", "Operator updates a val config"),
+    ("account.update_val_config.done", "Val configs updated"),
+    ("account.update_val_config.error", "Error updating val configs"),
+    ("account.set_operator.description", "Validator picks a new operator"),
+    ("account.set_operator.done", "Operator updated"),
+    ("account.set_operator.error", "Error updating operator"),
+    ("account.autopay_enable.description", "Enables Autopay functionality on an account"),
+    ("account.autopay_enable.done", "Enabled Autopay"),
+    ("account.autopay_enable.error", "Error enabling Autopay"),
+    ("account.autopay_create.description", "Creates Autopay instruction"),
+    ("account.autopay_create.done", "Created autopay instruction"),
+    ("account.autopay_create.error", "Error on autopay instruction tx"),
+    ("account.autopay_batch.description", "Batches Autopay instructions from file."),
+    ("account.autopay_batch.submitted", "Submitted autopay batch instruction, uid: {}"),
+    ("account.autopay_batch.error", "Error submitting batch autopay"),
+    ("account.autopay_batch.failed", "{} autopay instructions failed"),
+    // query
+    ("query.description", "Query operations"),
+    ("query.balance.description", "Get the balances of an account, currently or as of a past version"),
+    ("query.balance.invalid_args", "Invalid number of arguments for balance query"),
+    ("query.balance.result", "Balance is: {}"),
+    ("query.balance.error", "Failed to get balances"),
+    ("query.sequence.description", "Get the current sequence number for an account, and reset current sequence number in CLI (optional, default is false)"),
+    ("query.sequence.progress", ">> Getting current sequence number"),
+    ("query.sequence.result", "Sequence number is: {}"),
+    ("query.sequence.error", "Error getting sequence number"),
+    ("query.account_state.description", "Get the latest state for an account, or its state as of a past version"),
+    ("query.account_state.progress", ">> Getting latest account state"),
+    ("query.account_state.error", "Error getting latest account state"),
+    ("query.account_resources.description", "Print every Move resource of an account as annotated JSON, verified against the latest trusted state"),
+    ("query.account_resources.header", "Resources of account {} at version {}:"),
+    ("query.account_resources.not_found", "No Account found for {}"),
+    ("query.txn_acc_seq.description", "Get the committed transaction by account and sequence number.  Optionally also fetch events emitted by this transaction."),
+    ("query.txn_acc_seq.progress", ">> Getting committed transaction by account and sequence number"),
+    ("query.txn_acc_seq.not_available", "Transaction not available"),
+    ("query.txn_acc_seq.error", "Error getting committed transaction by account and sequence number"),
+    ("query.txn_range.description", "Get the committed transactions by version range. Optionally also fetch events emitted by these transactions."),
+    ("query.txn_range.progress", ">> Getting committed transaction by range"),
+    ("query.txn_range.error", "Error getting committed transactions by range"),
+    ("query.event.description", "Get events by account and event type (sent|received)."),
+    ("query.event.progress", ">> Getting events by account and event type."),
+    ("query.event.none", "No events returned"),
+    ("query.event.error", "Error getting events by access path"),
+    ("query.event.follow_progress", ">> Following new events, press Ctrl-C to exit."),
+    ("query.waypoint.description", "query latest waypoint"),
+    ("query.waypoint.none", "Nothing found"),
+    ("query.txn_detail.description", "Show the decoded payload, signature scheme, gas usage, VM status, events and write set of a committed transaction, verified against the trusted state"),
+    ("query.txn_detail.progress", ">> Getting verified transaction details"),
+    ("query.txn_detail.error", "Error getting transaction details"),
+    ("query.txn_detail.header", "Transaction {} at version {}"),
+    ("query.txn_detail.vm_status", "VM status: {}"),
+    ("query.txn_detail.gas_used", "Gas used: {}"),
+    ("query.txn_detail.sender", "Sender: {} (sequence number {})"),
+    ("query.txn_detail.signature_scheme", "Signature scheme: {}"),
+    ("query.txn_detail.payload", "Payload:"),
+    ("query.txn_detail.events", "Events ({}):"),
+    ("query.txn_detail.write_set", "Write set, from replay ({} changes):"),
+    ("query.txn_detail.write_set_unavailable", "Write set unavailable: {}"),
+    ("query.account_state.historical", "Historical account state is: \n Account: {}\n State: {}\n Blockchain Version: {}\n"),
+    ("query.account_state.latest", "Latest account state is: \n Account: {}\n State: {}\n Blockchain Version: {}\n"),
+    ("query.txn_acc_seq.done", "Committed transaction: {}"),
+    ("query.txn_range.entry", "Transaction at version {}: {}"),
+    ("query.event.last_state", "Last event state: {}"),
+    ("query.txn_detail.event", "  {} seq={} {}"),
+    // transfer
+    ("transfer.description", "Transfer coins from one account to another. Without the 'b' suffix the client does not wait for the commit, so several transfers from the same account can be submitted back-to-back."),
+    ("transfer.invalid_args", "Invalid number of arguments for transfer"),
+    ("transfer.progress", ">> Transferring"),
+    ("transfer.done", "Finished transaction!"),
+    ("transfer.submitted", "Transaction submitted to validator"),
+    ("transfer.query_hint", "To query for transaction status, run: query txn_acc_seq {} {} <fetch_events=true|false>"),
+    ("transfer.error", "Failed to perform transaction"),
+    // info
+    ("info.description", "Print cli config and client internal information"),
+    ("info.chain_id", "ChainID: {}"),
+    ("info.trusted_state", "Trusted State: {}"),
+    ("info.ledger_info", "LedgerInfo: {}"),
+    // transaction
+    ("transaction.description", "Build and dry-run transactions without committing them"),
+    ("transaction.simulate.description", "Execute a transfer through the VM against the latest state and report the result without broadcasting it"),
+    ("transaction.simulate_script.description", "Execute a compiled Move script through the VM against the latest state and report the result without broadcasting it"),
+    ("transaction.simulate.invalid_args", "Invalid number of arguments for simulation"),
+    ("transaction.simulate.progress", ">> Simulating transaction (nothing will be submitted)"),
+    ("transaction.simulate.error", "Failed to simulate transaction"),
+    ("transaction.simulate.status", "VM status: {}"),
+    ("transaction.simulate.gas_used", "Gas used: {}"),
+    ("transaction.simulate.write_set", "Write set ({} entries):"),
+    ("transaction.simulate.events", "Events ({}):"),
+    ("transaction.build.description", "Build a script transaction with secondary signers and save it for signing; a local sender signs it right away"),
+    ("transaction.build.invalid_args", "Invalid arguments for building a multi-agent transaction"),
+    ("transaction.build.success", "Transaction written to {}"),
+    ("transaction.build.error", "Failed to build multi-agent transaction"),
+    ("transaction.sign.description", "Sign a multi-agent transaction file with a local account"),
+    ("transaction.sign.invalid_args", "Invalid number of arguments for signing"),
+    ("transaction.sign.success", "Signature of {} written to {}"),
+    ("transaction.sign.error", "Failed to sign multi-agent transaction"),
+    ("transaction.add_signature.description", "Merge signature files into a multi-agent transaction file"),
+    ("transaction.add_signature.invalid_args", "Invalid number of arguments for adding signatures"),
+    ("transaction.add_signature.success", "Added {} signature(s)"),
+    ("transaction.add_signature.error", "Failed to add signatures"),
+    ("transaction.assemble.description", "Assemble a fully signed multi-agent transaction and optionally submit it"),
+    ("transaction.assemble.invalid_args", "Invalid arguments for assembling a multi-agent transaction"),
+    ("transaction.assemble.success", "Signed transaction written to {}"),
+    ("transaction.assemble.error", "Failed to assemble multi-agent transaction"),
+    ("transaction.multi_agent.complete", "All signatures collected, ready to assemble"),
+    ("transaction.multi_agent.missing", "Waiting for signatures from: {}"),
+    ("transaction.simulate.event", "  {} seq={} type={} data={}"),
+    ("transaction.write_op.write", "  write  {} ({} bytes)"),
+    ("transaction.write_op.delete", "  delete {}"),
+    // batch
+    ("batch.description", "Run CLI commands from a script file, or from stdin with \"-\""),
+    ("batch.invalid_args", "Invalid arguments: expected [--abort-on-error] <script_file_path> or \"-\""),
+    ("batch.summary", "Batch finished: {} commands executed, {} failed"),
+    ("batch.aborted", "Batch aborted after the first failing command"),
+    ("batch.error", "Failed to run batch"),
+    ("batch.failed", "{} commands of the batch failed"),
+    ("batch.open_failed", "Failed to open script {}"),
+    ("batch.line_error", "Line {}"),
+    ("batch.unterminated_variable", "Unterminated ${ in variable reference"),
+    ("batch.undefined_variable", "Undefined variable ${}"),
+    // dev
+    ("dev.prove.description", "Verify the specs of a Move module with the Move Prover, with the Diem framework as a dependency, optionally of a single function. Requires the prover tools installed by scripts/dev_setup.sh -y"),
+    ("dev.prove.invalid_args", "Invalid arguments: expected <module_source_path> <dependency_source_files...> [--verify-only <function>]"),
+    ("dev.prove.progress", ">> Proving {}"),
+    ("dev.prove.error", "Failed to run the Move Prover"),
+    ("dev.prove.verified", "All the specs of {} were verified"),
+    ("dev.prove.failed", "Verification of {} failed with {} errors"),
+    ("dev.check_upgrade.description", "Check that a compiled module can be upgraded to a new version, reporting the changes breaking its dependent modules or its data published on chain before the upgrade is submitted"),
+    ("dev.check_upgrade.invalid_args", "Invalid number of arguments to check an upgrade"),
+    ("dev.check_upgrade.error", "Failed to check the upgrade"),
+    ("dev.check_upgrade.compatible", "The new version of {} is fully compatible with the old one"),
+    ("dev.check_upgrade.linking", "Modules depending on {} may not link with the new version, and need to be fixed and published again:"),
+    ("dev.check_upgrade.layout", "Structs of {} published on chain can't be read by the new version, and need a data migration:"),
+    ("dev.check_upgrade.incompatible", "The new version of {} isn't compatible with the old one"),
+    ("dev.profile_gas.progress", ">> Profiling the gas of the script (nothing will be submitted)"),
+    ("dev.profile_gas.error", "Failed to profile the gas of the script"),
+    ("dev.profile_gas.status", "VM status: {}"),
+    ("dev.profile_gas.gas_used", "Gas used: {}"),
+    ("dev.profile_gas.discarded", "The transaction was discarded before being charged for, so there is no gas profile"),
+    ("dev.profile_gas.folded", "Folded stacks for flame graph tools written to {}"),
+    ("dev.profile_gas.folded_error", "Failed to write the folded stacks to {}"),
+    ("dev.debug.progress", ">> Debugging the script (nothing will be submitted), type help for the commands"),
+    ("dev.debug.error", "Failed to debug the script"),
+    ("dev.debug.status", "Transaction status: {}"),
+    ("dev.debug.gas_used", "Gas used: {}"),
+    ("dev.description", "Local Move development"),
+    ("dev.compile.description", "Compile Move program"),
+    ("dev.compile.invalid_args", "Invalid number of arguments for compilation"),
+    ("dev.compile.progress", ">> Compiling program"),
+    ("dev.compile.done", "Successfully compiled a program at:"),
+    ("dev.publish.description", "Publish Move module on-chain"),
+    ("dev.publish.invalid_args", "Invalid number of arguments to publish module"),
+    ("dev.publish.done", "Successfully published module"),
+    ("dev.execute.description", "Execute custom Move script or script function, arguments are typed literals such as u64:100, address:0x1, vector<u8>:0xdead or bool:true and are checked against its parameters. With --profile-gas, it's executed locally without being submitted, and the gas charged per instruction and native function is reported. With --debug, it's executed locally in a step debugger, prompting for commands at each instruction"),
+    ("dev.execute.invalid_args", "Invalid number of arguments to execute script"),
+    ("dev.enable_custom_script.description", "Allow executing arbitrary script in the network. This disables script hash verification."),
+    ("dev.change_diem_version.description", "Change the diem_version stored on chain"),
+    ("dev.noop.description", "Calls demo_e2e tx script, for testing purposes"),
+    ("dev.upgrade_stdlib.description", "Upgrade the move stdlib used for the blockchain"),
+    ("dev.submit_writeset.description", "Submit a WriteSet with local diem root account. Path should be a bcs serialized TransactionPayload."),
+    // debugger
+    ("debugger.help", "Commands:\n  step (s), or an empty line   execute the next instruction\n  next (n)                     execute the next instruction, stepping over calls\n  continue (c)                 run until a breakpoint or the end of the script\n  break (b) <function>         stop when <function> is called, such as 0x1::DiemAccount::pay_from or DiemAccount::pay_from\n  delete (d) <function>        delete the breakpoint on <function>\n  breakpoints (bl)             list the breakpoints\n  locals (l)                   print the locals of the current function\n  stack (bt)                   print the call stack and the operand stack\n  code                         print the code of the current function\n  help (h)                     print this help"),
+    ("debugger.unknown_command", "Unknown command: {}, type help for the commands"),
+    ("debugger.end_of_input", "End of input, running the script to its end"),
+    ("debugger.breakpoint_set", "Breakpoint set on {}"),
+    ("debugger.breakpoint_deleted", "Breakpoint on {} deleted"),
+    ("debugger.breakpoint_hit", "Breakpoint hit: {}"),
+    ("debugger.no_breakpoint", "No breakpoint on {}"),
+    ("debugger.no_breakpoints", "No breakpoints"),
+    ("debugger.no_locals", "(no locals)"),
+    ("debugger.current_frame", "Current frame: {}"),
+    ("debugger.inspect_error", "Failed to inspect the execution: {}"),
+    // addressbook
+    ("addressbook.description", "Manage named aliases usable as @alias in place of an account"),
+    ("addressbook.add.description", "Add or replace an alias, verifying the address checksum if given"),
+    ("addressbook.add.invalid_args", "Invalid number of arguments for adding an alias"),
+    ("addressbook.add.success", "Added {}{} -> {}"),
+    ("addressbook.add.error", "Error adding alias"),
+    ("addressbook.remove.description", "Remove an alias"),
+    ("addressbook.remove.invalid_args", "Invalid number of arguments for removing an alias"),
+    ("addressbook.remove.success", "Removed {}{}"),
+    ("addressbook.remove.error", "Error removing alias"),
+    ("addressbook.list.description", "List all aliases"),
+    ("addressbook.list.empty", "No aliases defined"),
+    ("addressbook.list.entry", "{}{}: {} (checksum {})"),
+    // trusted_state
+    ("trusted_state.description", "Inspect and reset the verified chain state used to check server responses"),
+    ("trusted_state.show.description", "Show the trusted waypoint, version and latest verified epoch change"),
+    ("trusted_state.show.waypoint", "Trusted waypoint: {}"),
+    ("trusted_state.show.version", "Trusted version: {}"),
+    ("trusted_state.show.epoch_change", "Latest verified epoch change: epoch {} at version {}"),
+    ("trusted_state.show.no_epoch_change", "No epoch change verified yet"),
+    ("trusted_state.set_waypoint.description", "Trust a new waypoint and verify the server's state against it"),
+    ("trusted_state.set_waypoint.invalid_args", "Invalid arguments for setting a waypoint"),
+    ("trusted_state.set_waypoint.success", "Now trusting waypoint {}, verified up to version {}"),
+    ("trusted_state.set_waypoint.error", "Failed to set waypoint"),
+    // gen_waypoint
+    ("gen_waypoint.description", "Generate a waypoint for the latest epoch change LedgerInfo"),
+    ("gen_waypoint.invalid_args", "No parameters required for waypoint generation"),
+    ("gen_waypoint.progress", "Retrieving the uptodate ledger info..."),
+    ("gen_waypoint.connection_error", "Failed to get uptodate ledger info connection: {}"),
+    ("gen_waypoint.no_epoch_change", "No epoch change LedgerInfo found"),
+    ("gen_waypoint.error", "Failed to generate a waypoint: {}"),
+    ("gen_waypoint.done", "Waypoint (end of epoch {}, time {}): {}"),
+    // node
+    ("node.description", "Get state of validators, miners."),
+    ("node.miner_state.description", "Get miner state for a address"),
+    ("node.miner_state.done", " Account: {}\n {}"),
+    ("node.miner_state.error", "Didn't find miner state for this address: {}"),
+    ("node.sync_status.description", "Get the progress of state sync from the JSON-RPC admin address of a node"),
+    ("node.sync_status.highest_known_version", "Highest known version: {}"),
+    ("node.sync_status.synced_version", "Synced version: {}"),
+    ("node.sync_status.committed_version", "Committed version: {}"),
+    ("node.sync_status.initialized", "Initialized: {}"),
+    ("node.sync_status.sync_rate", "Sync rate: {} txns/s"),
+    ("node.sync_status.caught_up", "Caught up"),
+    ("node.sync_status.catch_up_time", "Estimated catch up time: {}s"),
+    ("node.sync_status.catch_up_unknown", "Estimated catch up time: unknown, the node isn't syncing"),
+    ("node.sync_status.error", "Failed to get sync status: {}"),
+    // oracle
+    ("oracle.description", "Oracle related commands"),
+    ("oracle.upgrade.description", "On-chain upgrade of stdlib"),
+    ("oracle.query.description", "Query the on-chain upgrade"),
+    ("oracle.query.none", "Nothing found"),
+];
+
+#[rustfmt::skip]
+const ES: &[(&str, &str)] = &[
+    // main
+    ("main.connected", "Conectado al validador en: {}, {}"),
+    ("main.ledger_info", "última versión = {}, marca de tiempo = {}"),
+    ("main.input_prompt", "Por favor, introduzca comandos: \n"),
+    ("main.unknown_command", "Comando desconocido: {}"),
+    ("main.history_save_failed", "No se pudo guardar el historial en {}: {}"),
+    ("main.usage", "uso: <comando> <argumentos>\n\nUtilice los siguientes comandos:\n"),
+    ("main.help_description", "Muestra esta ayuda"),
+    ("main.quit_description", "Salir de este cliente"),
+    ("main.error_recovering_wallet", "Error al recuperar la billetera Diem"),
+    ("main.wallet_recovered", "Billetera recuperada y se derivaron las primeras {} cuentas hijas"),
+    ("main.error_loading_address_book", "Error al cargar la libreta de direcciones"),
+    ("main.unsupported_locale", "{}, se usará el inglés"),
+    ("main.readline_error", "Error de lectura de la línea: {}"),
+    // commands
+    ("commands.error_prefix", "[ERROR]"),
+    ("commands.subcommand_usage", "uso: {} <argumento>\n\nUtilice los siguientes argumentos para este comando:\n"),
+    ("commands.invalid_args", "Número de argumentos no válido"),
+    ("commands.execution_done", "Ejecución terminada con éxito"),
+    // account
+    ("account.description", "Operaciones de cuenta"),
+    ("account.create.description", "Crea una cuenta local, sin efecto en la cadena. Devuelve el ID de referencia para usar en otras operaciones"),
+    ("account.create.progress", ">> Creando/obteniendo la siguiente cuenta local de la billetera"),
+    ("account.create.done", "Cuenta local #{} creada/obtenida, dirección {}"),
+    ("account.create.error", "Error al crear la cuenta local"),
+    ("account.recover.description", "Recupera la billetera Diem desde la ruta del archivo, o con --interactive desde una frase mnemotécnica escrita palabra por palabra sin mostrarla, guardándola opcionalmente en un archivo una vez verificada su suma de control"),
+    ("account.recover.progress", ">> Recuperando la billetera"),
+    ("account.write.description", "Guarda en disco la semilla mnemónica de recuperación de la billetera Diem"),
+    ("account.write.progress", ">> Guardando en disco la semilla mnemónica de recuperación de la billetera Diem"),
+    ("account.write.done", "Semilla mnemónica guardada en disco"),
+    ("account.write.error", "Error al escribir la semilla mnemónica de recuperación en el archivo"),
+    ("account.list.description", "Muestra todas las cuentas creadas o cargadas"),
+    ("account.mint.description", "Envía moneda del tipo indicado desde la dirección del faucet a la dirección del destinatario. Crea una cuenta en la dirección del destinatario si aún no existe."),
+    ("account.mint.invalid_args", "Número de argumentos no válido para mint"),
+    ("account.mint.done", "¡Envío de monedas desde el faucet terminado!"),
+    ("account.mint.error", "Error al transferir monedas desde el faucet"),
+    ("account.add_currency.description", "Agrega la moneda indicada a la cuenta. El sufijo 'b' es para modo bloqueante"),
+    ("account.add_currency.invalid_args", "Número de argumentos no válido para agregar moneda a la cuenta"),
+    ("account.add_currency.progress", ">> Agregando saldo cero en la moneda a la cuenta"),
+    ("account.add_currency.done", "¡Moneda agregada a la cuenta!"),
+    ("account.add_currency.submitted", "Solicitud para agregar moneda enviada"),
+    ("account.add_currency.error", "Error al agregar saldo cero en la moneda a la cuenta"),
+    ("account.wallet_entry", "#{} dirección {}"),
+    ("account.mint_batch.description", "Financia cada cuenta listada en un archivo (un id de referencia, dirección o clave de autenticación por línea) a través del servicio faucet, enviando las solicitudes de forma concurrente"),
+    ("account.mint_batch.invalid_args", "Número de argumentos no válido para mint por lotes"),
+    ("account.mint_batch.failed", "No se pudo financiar {}"),
+    ("account.mint_batch.done", "Se financiaron {} de {} cuentas"),
+    ("account.recover.interactive_hint", "Escriba cada palabra de la frase mnemotécnica, la entrada no se muestra. Basta con un prefijo único de la palabra."),
+    ("account.recover.word_prompt", "Palabra {} de {}: "),
+    ("account.recover.unknown_word", "Ninguna palabra de la lista empieza así, inténtelo de nuevo"),
+    ("account.recover.ambiguous_word", "{} palabras empiezan así, escriba más letras"),
+    ("account.recover.invalid_word_count", "Una frase mnemotécnica tiene 12, 15, 18, 21 o 24 palabras, no {}"),
+    ("account.list.none", "No hay cuentas de usuario"),
+    ("account.list.user", "Índice de cuenta de usuario: {}, dirección: {}, número de secuencia: {}, estado: {}"),
+    ("account.list.root", "Dirección de la cuenta AssocRoot: {}, número de secuencia: {}, estado: {}"),
+    ("account.list.tc", "Dirección de la cuenta TC: {}, número de secuencia: {}, estado: {}"),
+    ("account.list.testnet_dd", "Dirección de la cuenta DD de testnet: {}, número de secuencia: {}, estado: {}"),
+    ("account.mint.creating_recipient", ">> Creando la cuenta del destinatario antes de acuñar desde el faucet"),
+    ("account.mint.progress", ">> Enviando monedas desde el faucet"),
+    ("account.update_failed", "error al actualizar la cuenta: {}"),
+    ("account.recover.count", "Cuentas en el monedero: {}"),
+    ("account.create_user.description", "Crear una cuenta de usuario en la cadena a partir de una prueba"),
+    ("account.create_user.done", "Cuenta creada"),
+    ("account.create_user.error", "Error al crear la cuenta de usuario"),
+    ("account.create_val.description", "Crear una cuenta de usuario en la cadena y configurar el validador"),
+    ("account.update_val_config.description", "El operador actualiza la configuración de un validador"),
+    ("account.update_val_config.done", "Configuración del validador actualizada"),
+    ("account.update_val_config.error", "Error al actualizar la configuración del validador"),
+    ("account.set_operator.description", "El validador elige un nuevo operador"),
+    ("account.set_operator.done", "Operador actualizado"),
+    ("account.set_operator.error", "Error al actualizar el operador"),
+    ("account.autopay_enable.description", "Activa Autopay en una cuenta"),
+    ("account.autopay_enable.done", "Autopay activado"),
+    ("account.autopay_enable.error", "Error al activar Autopay"),
+    ("account.autopay_create.description", "Crea una instrucción de Autopay"),
+    ("account.autopay_create.done", "Instrucción de Autopay creada"),
+    ("account.autopay_create.error", "Error en la transacción de la instrucción de Autopay"),
+    ("account.autopay_batch.description", "Envía en lote las instrucciones de Autopay de un archivo."),
+    ("account.autopay_batch.submitted", "Instrucción de Autopay del lote enviada, uid: {}"),
+    ("account.autopay_batch.error", "Error al enviar el lote de Autopay"),
+    ("account.autopay_batch.failed", "Fallaron {} instrucciones de Autopay"),
+    // query
+    ("query.description", "Operaciones de consulta"),
+    ("query.balance.description", "Obtiene los saldos de una cuenta, actuales o en una versión anterior"),
+    ("query.balance.invalid_args", "Número de argumentos no válido para consultar el saldo"),
+    ("query.balance.result", "El saldo es: {}"),
+    ("query.balance.error", "No se pudieron obtener los saldos"),
+    ("query.sequence.description", "Obtiene el número de secuencia actual de una cuenta y, opcionalmente, lo restablece en el CLI (por defecto, false)"),
+    ("query.sequence.progress", ">> Obteniendo el número de secuencia actual"),
+    ("query.sequence.result", "El número de secuencia es: {}"),
+    ("query.sequence.error", "Error al obtener el número de secuencia"),
+    ("query.account_state.description", "Obtiene el estado más reciente de una cuenta, o su estado en una versión anterior"),
+    ("query.account_state.progress", ">> Obteniendo el estado más reciente de la cuenta"),
+    ("query.account_state.error", "Error al obtener el estado más reciente de la cuenta"),
+    ("query.account_resources.description", "Imprime cada recurso Move de una cuenta como JSON anotado, verificado contra el último estado de confianza"),
+    ("query.account_resources.header", "Recursos de la cuenta {} en la versión {}:"),
+    ("query.account_resources.not_found", "No se encontró ninguna cuenta para {}"),
+    ("query.txn_acc_seq.description", "Obtiene la transacción confirmada por cuenta y número de secuencia. Opcionalmente también obtiene los eventos emitidos por esta transacción."),
+    ("query.txn_acc_seq.progress", ">> Obteniendo la transacción confirmada por cuenta y número de secuencia"),
+    ("query.txn_acc_seq.not_available", "Transacción no disponible"),
+    ("query.txn_acc_seq.error", "Error al obtener la transacción confirmada por cuenta y número de secuencia"),
+    ("query.txn_range.description", "Obtiene las transacciones confirmadas por rango de versiones. Opcionalmente también obtiene los eventos emitidos por estas transacciones."),
+    ("query.txn_range.progress", ">> Obteniendo transacciones confirmadas por rango"),
+    ("query.txn_range.error", "Error al obtener transacciones confirmadas por rango"),
+    ("query.event.description", "Obtiene eventos por cuenta y tipo de evento (sent|received)."),
+    ("query.event.progress", ">> Obteniendo eventos por cuenta y tipo de evento."),
+    ("query.event.none", "No se devolvieron eventos"),
+    ("query.event.error", "Error al obtener eventos por ruta de acceso"),
+    ("query.event.follow_progress", ">> Siguiendo nuevos eventos, pulse Ctrl-C para salir."),
+    ("query.waypoint.description", "consulta el waypoint más reciente"),
+    ("query.waypoint.none", "No se encontró nada"),
+    ("query.txn_detail.description", "Muestra la carga decodificada, el esquema de firma, el gas usado, el estado de la VM, los eventos y el conjunto de escrituras de una transacción confirmada, verificados contra el estado de confianza"),
+    ("query.txn_detail.progress", ">> Obteniendo los detalles verificados de la transacción"),
+    ("query.txn_detail.error", "Error al obtener los detalles de la transacción"),
+    ("query.txn_detail.header", "Transacción {} en la versión {}"),
+    ("query.txn_detail.vm_status", "Estado de la VM: {}"),
+    ("query.txn_detail.gas_used", "Gas usado: {}"),
+    ("query.txn_detail.sender", "Remitente: {} (número de secuencia {})"),
+    ("query.txn_detail.signature_scheme", "Esquema de firma: {}"),
+    ("query.txn_detail.payload", "Carga:"),
+    ("query.txn_detail.events", "Eventos ({}):"),
+    ("query.txn_detail.write_set", "Conjunto de escrituras, por reejecución ({} cambios):"),
+    ("query.txn_detail.write_set_unavailable", "Conjunto de escrituras no disponible: {}"),
+    ("query.account_state.historical", "El estado histórico de la cuenta es: \n Cuenta: {}\n Estado: {}\n Versión de la cadena: {}\n"),
+    ("query.account_state.latest", "El último estado de la cuenta es: \n Cuenta: {}\n Estado: {}\n Versión de la cadena: {}\n"),
+    ("query.txn_acc_seq.done", "Transacción confirmada: {}"),
+    ("query.txn_range.entry", "Transacción en la versión {}: {}"),
+    ("query.event.last_state", "Último estado de eventos: {}"),
+    ("query.txn_detail.event", "  {} sec={} {}"),
+    // transfer
+    ("transfer.description", "Transfiere monedas de una cuenta a otra. Sin el sufijo 'b' el cliente no espera la confirmación, así que se pueden enviar varias transferencias seguidas desde la misma cuenta."),
+    ("transfer.invalid_args", "Número de argumentos no válido para la transferencia"),
+    ("transfer.progress", ">> Transfiriendo"),
+    ("transfer.done", "¡Transacción terminada!"),
+    ("transfer.submitted", "Transacción enviada al validador"),
+    ("transfer.query_hint", "Para consultar el estado de la transacción, ejecute: query txn_acc_seq {} {} <fetch_events=true|false>"),
+    ("transfer.error", "No se pudo realizar la transacción"),
+    // info
+    ("info.description", "Muestra la configuración del cli y la información interna del cliente"),
+    ("info.chain_id", "ID de la cadena: {}"),
+    ("info.trusted_state", "Estado de confianza: {}"),
+    ("info.ledger_info", "LedgerInfo: {}"),
+    // transaction
+    ("transaction.description", "Construye y simula transacciones sin confirmarlas"),
+    ("transaction.simulate.description", "Ejecuta una transferencia en la VM contra el estado más reciente e informa el resultado sin difundirla"),
+    ("transaction.simulate_script.description", "Ejecuta un script Move compilado en la VM contra el estado más reciente e informa el resultado sin difundirlo"),
+    ("transaction.simulate.invalid_args", "Número de argumentos no válido para la simulación"),
+    ("transaction.simulate.progress", ">> Simulando la transacción (no se enviará nada)"),
+    ("transaction.simulate.error", "No se pudo simular la transacción"),
+    ("transaction.simulate.status", "Estado de la VM: {}"),
+    ("transaction.simulate.gas_used", "Gas utilizado: {}"),
+    ("transaction.simulate.write_set", "Conjunto de escritura ({} entradas):"),
+    ("transaction.simulate.events", "Eventos ({}):"),
+    ("transaction.build.description", "Construir una transacción de script con firmantes secundarios y guardarla para firmar; un remitente local la firma de inmediato"),
+    ("transaction.build.invalid_args", "Argumentos inválidos para construir una transacción multiagente"),
+    ("transaction.build.success", "Transacción escrita en {}"),
+    ("transaction.build.error", "Error al construir la transacción multiagente"),
+    ("transaction.sign.description", "Firmar un archivo de transacción multiagente con una cuenta local"),
+    ("transaction.sign.invalid_args", "Número de argumentos inválido para firmar"),
+    ("transaction.sign.success", "Firma de {} escrita en {}"),
+    ("transaction.sign.error", "Error al firmar la transacción multiagente"),
+    ("transaction.add_signature.description", "Combinar archivos de firma en un archivo de transacción multiagente"),
+    ("transaction.add_signature.invalid_args", "Número de argumentos inválido para añadir firmas"),
+    ("transaction.add_signature.success", "Se añadieron {} firma(s)"),
+    ("transaction.add_signature.error", "Error al añadir firmas"),
+    ("transaction.assemble.description", "Ensamblar una transacción multiagente completamente firmada y opcionalmente enviarla"),
+    ("transaction.assemble.invalid_args", "Argumentos inválidos para ensamblar una transacción multiagente"),
+    ("transaction.assemble.success", "Transacción firmada escrita en {}"),
+    ("transaction.assemble.error", "Error al ensamblar la transacción multiagente"),
+    ("transaction.multi_agent.complete", "Todas las firmas recogidas, lista para ensamblar"),
+    ("transaction.multi_agent.missing", "Esperando firmas de: {}"),
+    ("transaction.simulate.event", "  {} sec={} tipo={} datos={}"),
+    ("transaction.write_op.write", "  escritura  {} ({} bytes)"),
+    ("transaction.write_op.delete", "  borrado {}"),
+    // batch
+    ("batch.description", "Ejecutar comandos de la CLI desde un archivo de script, o desde stdin con \"-\""),
+    ("batch.invalid_args", "Argumentos inválidos: se esperaba [--abort-on-error] <ruta_del_script> o \"-\""),
+    ("batch.summary", "Lote terminado: {} comandos ejecutados, {} fallidos"),
+    ("batch.aborted", "Lote abortado tras el primer comando fallido"),
+    ("batch.error", "Error al ejecutar el lote"),
+    ("batch.failed", "Fallaron {} comandos del lote"),
+    ("batch.open_failed", "No se pudo abrir el script {}"),
+    ("batch.line_error", "Línea {}"),
+    ("batch.unterminated_variable", "${ sin cerrar en referencia a variable"),
+    ("batch.undefined_variable", "Variable no definida ${}"),
+    // dev
+    ("dev.prove.description", "Verificar las especificaciones de un módulo Move con el Move Prover, con el framework de Diem como dependencia, opcionalmente de una sola función. Requiere las herramientas del prover instaladas por scripts/dev_setup.sh -y"),
+    ("dev.prove.invalid_args", "Argumentos inválidos: se esperaba <ruta_del_módulo> <archivos_de_dependencias...> [--verify-only <función>]"),
+    ("dev.prove.progress", ">> Verificando {}"),
+    ("dev.prove.error", "No se pudo ejecutar el Move Prover"),
+    ("dev.prove.verified", "Todas las especificaciones de {} fueron verificadas"),
+    ("dev.prove.failed", "La verificación de {} falló con {} errores"),
+    ("dev.check_upgrade.description", "Comprobar que un módulo compilado puede actualizarse a una nueva versión, informando de los cambios que rompen sus módulos dependientes o sus datos publicados en la cadena antes de enviar la actualización"),
+    ("dev.check_upgrade.invalid_args", "Número de argumentos no válido para comprobar una actualización"),
+    ("dev.check_upgrade.error", "No se pudo comprobar la actualización"),
+    ("dev.check_upgrade.compatible", "La nueva versión de {} es totalmente compatible con la anterior"),
+    ("dev.check_upgrade.linking", "Los módulos que dependen de {} pueden no enlazar con la nueva versión, y deben corregirse y publicarse de nuevo:"),
+    ("dev.check_upgrade.layout", "Las estructuras de {} publicadas en la cadena no pueden leerse con la nueva versión, y necesitan una migración de datos:"),
+    ("dev.check_upgrade.incompatible", "La nueva versión de {} no es compatible con la anterior"),
+    ("dev.profile_gas.progress", ">> Perfilando el gas del script (no se enviará nada)"),
+    ("dev.profile_gas.error", "No se pudo perfilar el gas del script"),
+    ("dev.profile_gas.status", "Estado de la VM: {}"),
+    ("dev.profile_gas.gas_used", "Gas utilizado: {}"),
+    ("dev.profile_gas.discarded", "La transacción se descartó antes de cobrarse, así que no hay perfil de gas"),
+    ("dev.profile_gas.folded", "Pilas plegadas para herramientas de flame graph escritas en {}"),
+    ("dev.profile_gas.folded_error", "No se pudieron escribir las pilas plegadas en {}"),
+    ("dev.debug.progress", ">> Depurando el script (no se enviará nada), escriba help para ver los comandos"),
+    ("dev.debug.error", "No se pudo depurar el script"),
+    ("dev.debug.status", "Estado de la transacción: {}"),
+    ("dev.debug.gas_used", "Gas utilizado: {}"),
+    ("dev.description", "Desarrollo local de Move"),
+    ("dev.compile.description", "Compilar un programa Move"),
+    ("dev.compile.invalid_args", "Número de argumentos no válido para la compilación"),
+    ("dev.compile.progress", ">> Compilando el programa"),
+    ("dev.compile.done", "Programa compilado con éxito en:"),
+    ("dev.publish.description", "Publicar un módulo Move en la cadena"),
+    ("dev.publish.invalid_args", "Número de argumentos no válido para publicar el módulo"),
+    ("dev.publish.done", "Módulo publicado con éxito"),
+    ("dev.execute.description", "Ejecutar un script o una función de script Move personalizados; los argumentos son literales con tipo como u64:100, address:0x1, vector<u8>:0xdead o bool:true y se comprueban contra sus parámetros. Con --profile-gas se ejecuta localmente sin enviarse y se informa del gas cobrado por instrucción y función nativa. Con --debug se ejecuta localmente en un depurador paso a paso que pide comandos en cada instrucción"),
+    ("dev.execute.invalid_args", "Número de argumentos no válido para ejecutar el script"),
+    ("dev.enable_custom_script.description", "Permitir ejecutar scripts arbitrarios en la red. Esto desactiva la verificación del hash de los scripts."),
+    ("dev.change_diem_version.description", "Cambiar la diem_version almacenada en la cadena"),
+    ("dev.noop.description", "Llama al script de transacción demo_e2e, para pruebas"),
+    ("dev.upgrade_stdlib.description", "Actualizar la biblioteca estándar de Move usada por la cadena"),
+    ("dev.submit_writeset.description", "Enviar un WriteSet con la cuenta diem root local. La ruta debe ser un TransactionPayload serializado en bcs."),
+    // debugger
+    ("debugger.help", "Comandos:\n  step (s), o una línea vacía  ejecuta la siguiente instrucción\n  next (n)                     ejecuta la siguiente instrucción, sin entrar en las llamadas\n  continue (c)                 ejecuta hasta un punto de interrupción o el final del script\n  break (b) <función>          se detiene al llamarse <función>, como 0x1::DiemAccount::pay_from o DiemAccount::pay_from\n  delete (d) <función>         elimina el punto de interrupción en <función>\n  breakpoints (bl)             lista los puntos de interrupción\n  locals (l)                   muestra las variables locales de la función actual\n  stack (bt)                   muestra la pila de llamadas y la pila de operandos\n  code                         muestra el código de la función actual\n  help (h)                     muestra esta ayuda"),
+    ("debugger.unknown_command", "Comando desconocido: {}, escriba help para ver los comandos"),
+    ("debugger.end_of_input", "Fin de la entrada, ejecutando el script hasta el final"),
+    ("debugger.breakpoint_set", "Punto de interrupción en {}"),
+    ("debugger.breakpoint_deleted", "Punto de interrupción en {} eliminado"),
+    ("debugger.breakpoint_hit", "Punto de interrupción alcanzado: {}"),
+    ("debugger.no_breakpoint", "No hay punto de interrupción en {}"),
+    ("debugger.no_breakpoints", "No hay puntos de interrupción"),
+    ("debugger.no_locals", "(sin variables locales)"),
+    ("debugger.current_frame", "Marco actual: {}"),
+    ("debugger.inspect_error", "No se pudo inspeccionar la ejecución: {}"),
+    // addressbook
+    ("addressbook.description", "Gestionar alias con nombre utilizables como @alias en lugar de una cuenta"),
+    ("addressbook.add.description", "Añadir o reemplazar un alias, verificando la suma de control de la dirección si se indica"),
+    ("addressbook.add.invalid_args", "Número de argumentos inválido para añadir un alias"),
+    ("addressbook.add.success", "Añadido {}{} -> {}"),
+    ("addressbook.add.error", "Error al añadir el alias"),
+    ("addressbook.remove.description", "Eliminar un alias"),
+    ("addressbook.remove.invalid_args", "Número de argumentos inválido para eliminar un alias"),
+    ("addressbook.remove.success", "Eliminado {}{}"),
+    ("addressbook.remove.error", "Error al eliminar el alias"),
+    ("addressbook.list.description", "Listar todos los alias"),
+    ("addressbook.list.empty", "No hay alias definidos"),
+    ("addressbook.list.entry", "{}{}: {} (suma de verificación {})"),
+    // trusted_state
+    ("trusted_state.description", "Inspeccionar y restablecer el estado de la cadena verificado usado para comprobar las respuestas del servidor"),
+    ("trusted_state.show.description", "Mostrar el waypoint de confianza, la versión y el último cambio de época verificado"),
+    ("trusted_state.show.waypoint", "Waypoint de confianza: {}"),
+    ("trusted_state.show.version", "Versión de confianza: {}"),
+    ("trusted_state.show.epoch_change", "Último cambio de época verificado: época {} en la versión {}"),
+    ("trusted_state.show.no_epoch_change", "Aún no se ha verificado ningún cambio de época"),
+    ("trusted_state.set_waypoint.description", "Confiar en un nuevo waypoint y verificar el estado del servidor con él"),
+    ("trusted_state.set_waypoint.invalid_args", "Argumentos inválidos para establecer un waypoint"),
+    ("trusted_state.set_waypoint.success", "Ahora se confía en el waypoint {}, verificado hasta la versión {}"),
+    ("trusted_state.set_waypoint.error", "Error al establecer el waypoint"),
+    // gen_waypoint
+    ("gen_waypoint.description", "Generar un waypoint para el LedgerInfo del último cambio de época"),
+    ("gen_waypoint.invalid_args", "La generación del waypoint no necesita parámetros"),
+    ("gen_waypoint.progress", "Obteniendo el ledger info actualizado..."),
+    ("gen_waypoint.connection_error", "Error al obtener el ledger info actualizado: {}"),
+    ("gen_waypoint.no_epoch_change", "No se encontró ningún LedgerInfo de cambio de época"),
+    ("gen_waypoint.error", "Error al generar el waypoint: {}"),
+    ("gen_waypoint.done", "Waypoint (fin de la época {}, hora {}): {}"),
+    // node
+    ("node.description", "Obtener el estado de los validadores y mineros."),
+    ("node.miner_state.description", "Obtener el estado de minería de una dirección"),
+    ("node.miner_state.done", " Cuenta: {}\n {}"),
+    ("node.miner_state.error", "No se encontró el estado de minería de esta dirección: {}"),
+    ("node.sync_status.description", "Obtener el progreso de la sincronización de estado desde la dirección de administración JSON-RPC de un nodo"),
+    ("node.sync_status.highest_known_version", "Versión más alta conocida: {}"),
+    ("node.sync_status.synced_version", "Versión sincronizada: {}"),
+    ("node.sync_status.committed_version", "Versión confirmada: {}"),
+    ("node.sync_status.initialized", "Inicializado: {}"),
+    ("node.sync_status.sync_rate", "Velocidad de sincronización: {} txns/s"),
+    ("node.sync_status.caught_up", "Al día"),
+    ("node.sync_status.catch_up_time", "Tiempo estimado para ponerse al día: {}s"),
+    ("node.sync_status.catch_up_unknown", "Tiempo estimado para ponerse al día: desconocido, el nodo no se está sincronizando"),
+    ("node.sync_status.error", "Error al obtener el estado de sincronización: {}"),
+    // oracle
+    ("oracle.description", "Comandos del oráculo"),
+    ("oracle.upgrade.description", "Actualización en la cadena de la biblioteca estándar"),
+    ("oracle.query.description", "Consultar la actualización en la cadena"),
+    ("oracle.query.none", "No se encontró nada"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::BTreeSet, fs, path::Path};
+
+    /// Output that is command syntax rather than prose, and so isn't translated.
+    const UNTRANSLATED_LITERALS: &[&str] = &[
+        "help | h \\n\\t{}",
+        "quit | q! \\n\\t{}",
+        "diem% {}",
+        "CTRL-C",
+        "CTRL-D",
+    ];
+
+    /// The contents of every crate source file except this one, whose docs mention placeholder
+    /// keys.
+    fn sources() -> Vec<(String, String)> {
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        walkdir::WalkDir::new(src)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().map_or(false, |ext| ext == "rs"))
+            .filter(|e| e.file_name() != "i18n.rs")
+            .map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                (name, fs::read_to_string(e.path()).unwrap())
+            })
+            .collect()
+    }
+
+    /// The raw contents of the string literal starting `chunk`, if it starts with one.
+    fn leading_literal(chunk: &str) -> Option<&str> {
+        let rest = chunk.trim_start().strip_prefix('"')?;
+        let mut escaped = false;
+        for (i, c) in rest.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => return Some(&rest[..i]),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Whether a format string has words left once placeholders and escapes are removed.
+    fn has_prose(literal: &str) -> bool {
+        let mut depth = 0;
+        let mut chars = literal.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                '\\' => {
+                    chars.next();
+                }
+                c if depth == 0 && c.is_ascii_alphabetic() => return true,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Collect every literal key passed to `tr!` in the crate sources.
+    fn extract_keys() -> BTreeSet<String> {
+        let mut keys = BTreeSet::new();
+        for (_, contents) in sources() {
+            for chunk in contents.split("tr!(").skip(1) {
+                let chunk = chunk.trim_start();
+                if let Some(rest) = chunk.strip_prefix('"') {
+                    if let Some(end) = rest.find('"') {
+                        keys.insert(rest[..end].to_string());
+                    }
+                }
+            }
+        }
+        keys
+    }
+
+    #[test]
+    fn test_every_used_key_is_translated() {
+        let keys = extract_keys();
+        assert!(!keys.is_empty());
+        for locale in Locale::ALL.iter() {
+            let catalog: BTreeSet<_> = locale.catalog().iter().map(|(k, _)| *k).collect();
+            let missing: Vec<_> = keys
+                .iter()
+                .filter(|key| !catalog.contains(key.as_str()))
+                .collect();
+            assert!(
+                missing.is_empty(),
+                "untranslated keys for locale {}: {:?}",
+                locale,
+                missing
+            );
+        }
+    }
+
+    #[test]
+    fn test_user_facing_literals_are_in_the_catalog() {
+        let sinks = [
+            "println!(",
+            "print!(",
+            "eprintln!(",
+            "eprint!(",
+            "report_error(",
+            "fn get_description(&self) -> &'static str {",
+        ];
+        let mut untranslated = vec![];
+        for (name, contents) in sources() {
+            for sink in sinks.iter() {
+                for chunk in contents.split(sink).skip(1) {
+                    if let Some(literal) = leading_literal(chunk) {
+                        if has_prose(literal) && !UNTRANSLATED_LITERALS.contains(&literal) {
+                            untranslated.push(format!("{}: {:?}", name, literal));
+                        }
+                    }
+                }
+            }
+        }
+        assert!(
+            untranslated.is_empty(),
+            "literals printed without tr!: {:?}",
+            untranslated
+        );
+    }
+
+    #[test]
+    fn test_catalogs_have_no_duplicate_or_stale_keys() {
+        let english: BTreeSet<_> = EN.iter().map(|(k, _)| *k).collect();
+        assert_eq!(english.len(), EN.len());
+        for locale in Locale::ALL.iter() {
+            let catalog: BTreeSet<_> = locale.catalog().iter().map(|(k, _)| *k).collect();
+            assert_eq!(catalog.len(), locale.catalog().len());
+            assert_eq!(catalog, english, "catalog {} out of sync with en", locale);
+        }
+    }
+
+    #[test]
+    fn test_placeholders_match_english() {
+        for (key, english) in EN {
+            let expected = english.matches("{}").count();
+            assert_eq!(
+                lookup_in(Locale::Spanish, key).matches("{}").count(),
+                expected,
+                "placeholder count mismatch for {}",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!("en".parse::<Locale>().unwrap(), Locale::English);
+        assert_eq!("ES".parse::<Locale>().unwrap(), Locale::Spanish);
+        assert_eq!("es_MX.UTF-8".parse::<Locale>().unwrap(), Locale::Spanish);
+        assert!("fr".parse::<Locale>().is_err());
+        assert!("".parse::<Locale>().is_err());
+    }
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(fill("a {} b {}", &[&1, &"x"]), "a 1 b x");
+        assert_eq!(fill("a {}", &[]), "a {}");
+        assert_eq!(lookup_in(Locale::Spanish, "missing.key"), "missing.key");
+    }
+}
@@ -1,7 +1,8 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{client_proxy::ClientProxy, commands::Command};
+use crate::{client_proxy::ClientProxy, commands::Command, tr};
+use anyhow::Result;
 
 /// Major command for account related operations.
 pub struct InfoCommand {}
@@ -11,11 +12,24 @@ impl Command for InfoCommand {
         vec!["info", "i"]
     }
     fn get_description(&self) -> &'static str {
-        "Print cli config and client internal information"
+        tr!("info.description")
     }
-    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) {
-        println!("ChainID: {}", client.chain_id);
-        println!("Trusted State: {:#?}", client.client.trusted_state());
-        println!("LedgerInfo: {:#?}", client.client.latest_epoch_change_li());
+    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) -> Result<()> {
+        println!("{}", tr!("info.chain_id", client.chain_id));
+        println!(
+            "{}",
+            tr!(
+                "info.trusted_state",
+                format!("{:#?}", client.client.trusted_state())
+            )
+        );
+        println!(
+            "{}",
+            tr!(
+                "info.ledger_info",
+                format!("{:#?}", client.client.latest_epoch_change_li())
+            )
+        );
+        Ok(())
     }
 }
@@ -19,17 +19,40 @@ use diem_types::account_address::AccountAddress;
 use serde::{Deserialize, Serialize};
 
 mod account_commands;
+/// Named aliases for account addresses.
+pub mod address_book;
+mod address_book_commands;
+/// Non-interactive execution of CLI commands from a script.
+pub mod batch;
 /// Main instance of client holding corresponding information, e.g. account address.
 pub mod client_proxy;
 /// Command struct to interact with client.
 pub mod commands;
+/// Tab completion for the interactive prompt.
+pub mod completion;
 mod counters;
 mod dev_commands;
 /// Client wrapper to connect to validator.
 pub mod diem_client; //////// 0L ////////
+/// Client of the testnet faucet service.
+pub mod faucet_client;
+/// Message catalog for localized CLI output.
+pub mod i18n;
 mod info_commands;
+/// Reading a mnemonic from the terminal without echoing it.
+pub mod mnemonic_prompt;
+/// Step debugger of Move scripts executed locally.
+pub mod move_debugger;
+/// Collecting signatures for multi-agent transactions.
+pub mod multi_agent;
 mod query_commands;
+/// Typed Move script arguments and their validation against the script ABI.
+pub mod script_args;
+/// Sequence numbers of transactions in flight.
+pub mod sequence_manager;
+mod transaction_commands;
 mod transfer_commands;
+mod trusted_state_commands;
 //////// 0L ////////
 mod ol_node_commands;
 mod ol_oracle_commands;
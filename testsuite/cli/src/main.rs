@@ -8,17 +8,28 @@ use chrono::{
     DateTime,
 };
 use cli::{
+    address_book::{AddressBook, ADDRESS_BOOK_FILE},
+    batch::run_batch_file,
     client_proxy::ClientProxy,
-    commands::{get_commands, parse_cmd, report_error, Command},
+    commands::{get_commands, parse_cmd, report_command_error, report_error, Command},
+    completion::CliHelper,
+    i18n::{self, Locale},
+    tr,
 };
 use diem_types::{chain_id::ChainId, waypoint::Waypoint};
 use rustyline::{config::CompletionType, error::ReadlineError, Config, Editor};
 use ol_keys::wallet;
 use std::{
+    io,
+    path::PathBuf,
     str::FromStr,
     time::{Duration, UNIX_EPOCH},
 };
-use structopt::StructOpt;
+use structopt::{clap::Shell, StructOpt};
+
+/// Environment variable a batch run takes the mnemonic from, as there is no one to type it at the
+/// prompt.
+const MNEMONIC_ENV_VAR: &str = "DIEM_CLI_MNEMONIC";
 
 #[derive(Debug, StructOpt)]
 #[structopt(
@@ -53,6 +64,14 @@ struct Args {
     /// If not passed, will be derived from host parameter
     #[structopt(short = "f", long)]
     pub faucet_url: Option<String>,
+    /// Token sent as a bearer token with every faucet request, for faucets behind an
+    /// authenticating proxy.
+    #[structopt(long)]
+    pub faucet_auth_token: Option<String>,
+    /// Number of times a faucet request answered with 429 or a 5xx status is retried, with
+    /// exponential backoff.
+    #[structopt(long, default_value = "5")]
+    pub faucet_max_retries: u32,
     /// File location from which to load mnemonic word for user account address/key generation.
     /// If not passed, a new mnemonic file will be generated by diem-wallet in the current
     /// directory.
@@ -80,13 +99,46 @@ struct Args {
     /// Verbose output.
     #[structopt(short = "v", long = "verbose")]
     pub verbose: bool,
+    /// Language of the CLI output, e.g. "en" or "es". Defaults to $DIEM_CLI_LOCALE, then $LANG.
+    #[structopt(long)]
+    pub locale: Option<String>,
+    /// Run the commands in this script file ("-" for stdin) instead of starting the prompt.
+    /// The process exits with a non-zero status if any command fails. The mnemonic isn't prompted
+    /// for; it's read from $DIEM_CLI_MNEMONIC, or from --mnemonic-file.
+    #[structopt(long)]
+    pub batch: Option<String>,
+    /// Stop a batch run at the first failing command.
+    #[structopt(long, requires = "batch")]
+    pub abort_on_error: bool,
+    /// Directory holding the address book and the prompt history. Defaults to $HOME/.diem_cli.
+    #[structopt(long, parse(from_os_str))]
+    pub config_dir: Option<PathBuf>,
+    /// File the interactive prompt history is kept in. Defaults to <config-dir>/history.
+    #[structopt(long, parse(from_os_str))]
+    pub history_file: Option<PathBuf>,
+    /// Print a completion script for the given shell and exit.
+    #[structopt(long, possible_values = &Shell::variants())]
+    pub completions: Option<Shell>,
 }
 
 fn main() {
+    // Handled before regular parsing, which would insist on connection arguments.
+    if let Some(shell) = completions_shell() {
+        Args::clap().gen_completions_to(env!("CARGO_PKG_NAME"), shell, &mut io::stdout());
+        return;
+    }
     let args = Args::from_args();
+    i18n::set_locale(Locale::detect(args.locale.as_deref()).unwrap_or_else(|e| {
+        eprintln!("{}", tr!("main.unsupported_locale", e));
+        Locale::English
+    }));
 
     //////// 0L ////////
-    let mnemonic_str = wallet::get_account_from_prompt().2.mnemonic();
+    let mnemonic_str = if args.batch.is_some() {
+        std::env::var(MNEMONIC_ENV_VAR).unwrap_or_default()
+    } else {
+        wallet::get_account_from_prompt().2.mnemonic()
+    };
     let entered_mnem = if mnemonic_str.is_empty() { false } else { true };
 
     let mut logger = ::diem_logger::Logger::new();
@@ -128,11 +180,15 @@ fn main() {
         true, //////// 0L ////////
         args.faucet_url.clone(),
         mnemonic_file,
-        Some(mnemonic_str.trim().to_string()), //////// 0L ////////        
+        Some(mnemonic_str.trim().to_string()).filter(|_| entered_mnem), //////// 0L ////////        
         waypoint,
         false,
     )
     .expect("Failed to construct client.");
+    client_proxy
+        .faucet
+        .set_auth_token(args.faucet_auth_token.clone());
+    client_proxy.faucet.set_max_retries(args.faucet_max_retries);
 
     // Test connection to validator
     let block_metadata = client_proxy
@@ -143,39 +199,84 @@ fn main() {
                 args.url, e,
             )
         });
-    let ledger_info_str = format!(
-        "latest version = {}, timestamp = {}",
+    let ledger_info_str = tr!(
+        "main.ledger_info",
         block_metadata.version,
         DateTime::<Utc>::from(UNIX_EPOCH + Duration::from_micros(block_metadata.timestamp))
     );
-    let cli_info = format!(
-        "Connected to validator at: {}, {}",
-        args.url, ledger_info_str
-    );
+    let cli_info = tr!("main.connected", args.url, ledger_info_str);
+    let config_dir = args
+        .config_dir
+        .clone()
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".diem_cli")));
+    if let Some(dir) = &config_dir {
+        match AddressBook::load(&dir.join(ADDRESS_BOOK_FILE)) {
+            Ok(address_book) => client_proxy.address_book = address_book,
+            Err(e) => report_error(tr!("main.error_loading_address_book"), e),
+        }
+    }
     if entered_mnem || args.mnemonic_file.is_some() { //////// 0L ////////
         match client_proxy.recover_accounts_in_wallet() {
             Ok(account_data) => {
-                println!(
-                    "Wallet recovered and the first {} child accounts were derived",
-                    account_data.len()
-                );
+                println!("{}", tr!("main.wallet_recovered", account_data.len()));
                 for data in account_data {
-                    println!("#{} address {}", data.index, hex::encode(data.address));
+                    println!(
+                        "{}",
+                        tr!(
+                            "account.wallet_entry",
+                            data.index,
+                            hex::encode(data.address)
+                        )
+                    );
                 }
             }
-            Err(e) => report_error("Error recovering Diem wallet", e),
+            Err(e) => report_error(tr!("main.error_recovering_wallet"), e),
+        }
+    }
+    if let Some(path) = &args.batch {
+        println!("{}", cli_info);
+        match run_batch_file(&mut client_proxy, path, args.abort_on_error) {
+            Ok(summary) => {
+                if summary.aborted {
+                    println!("{}", tr!("batch.aborted"));
+                }
+                println!("{}", tr!("batch.summary", summary.executed, summary.failed));
+                std::process::exit(if summary.failed == 0 { 0 } else { 1 });
+            }
+            Err(e) => {
+                report_error(tr!("batch.error"), e);
+                std::process::exit(1);
+            }
         }
     }
     print_help(&cli_info, &commands);
-    println!("Please, input commands: \n");
+    println!("{}", tr!("main.input_prompt"));
 
     let config = Config::builder()
         .history_ignore_space(true)
         .completion_type(CompletionType::List)
         .auto_add_history(true)
         .build();
-    let mut rl = Editor::<()>::with_config(config);
+    let mut rl = Editor::<CliHelper>::with_config(config);
+    rl.set_helper(Some(CliHelper::new(&commands)));
+    let history_file = args
+        .history_file
+        .clone()
+        .or_else(|| config_dir.map(|dir| dir.join("history")));
+    if let Some(path) = &history_file {
+        // A missing history file is expected on first use.
+        let _ = rl.load_history(path);
+    }
     loop {
+        if let Some(helper) = rl.helper_mut() {
+            helper.set_accounts(&client_proxy.accounts);
+            helper.set_aliases(
+                client_proxy
+                    .address_book
+                    .entries()
+                    .map(|(alias, _, _)| alias),
+            );
+        }
         let readline = rl.readline("diem% ");
         match readline {
             Ok(line) => {
@@ -188,13 +289,15 @@ fn main() {
                         if args.verbose {
                             println!("{}", Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true));
                         }
-                        cmd.execute(&mut client_proxy, &params);
+                        if let Err(e) = cmd.execute(&mut client_proxy, &params) {
+                            report_command_error(e);
+                        }
                     }
                     None => match params[0] {
                         "quit" | "q!" => break,
                         "help" | "h" => print_help(&cli_info, &commands),
                         "" => continue,
-                        x => println!("Unknown command: {:?}", x),
+                        x => println!("{}", tr!("main.unknown_command", format!("{:?}", x))),
                     },
                 }
             }
@@ -207,17 +310,40 @@ fn main() {
                 break;
             }
             Err(err) => {
-                println!("Error: {:?}", err);
+                println!("{}", tr!("main.readline_error", format!("{:?}", err)));
                 break;
             }
         }
     }
+    if let Some(path) = &history_file {
+        if let Some(dir) = path.parent() {
+            // Failures surface through save_history below.
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Err(e) = rl.save_history(path) {
+            println!("{}", tr!("main.history_save_failed", path.display(), e));
+        }
+    }
+}
+
+/// Returns the shell passed to `--completions`, if any, without parsing the other arguments.
+fn completions_shell() -> Option<Shell> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if let Some(shell) = arg.strip_prefix("--completions=") {
+            return shell.parse().ok();
+        }
+        if arg == "--completions" {
+            return args.next().and_then(|shell| shell.parse().ok());
+        }
+    }
+    None
 }
 
 /// Print the help message for the client and underlying command.
 fn print_help(client_info: &str, commands: &[std::sync::Arc<dyn Command>]) {
     println!("{}", client_info);
-    println!("usage: <command> <args>\n\nUse the following commands:\n");
+    println!("{}", tr!("main.usage"));
     for cmd in commands {
         println!(
             "{} {}\n\t{}",
@@ -227,8 +353,8 @@ fn print_help(client_info: &str, commands: &[std::sync::Arc<dyn Command>]) {
         );
     }
 
-    println!("help | h \n\tPrints this help");
-    println!("quit | q! \n\tExit this client");
+    println!("help | h \n\t{}", tr!("main.help_description"));
+    println!("quit | q! \n\t{}", tr!("main.quit_description"));
     println!("\n");
 }
 
@@ -0,0 +1,89 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tr;
+use anyhow::{ensure, Result};
+use diem_wallet::Mnemonic;
+use std::io;
+
+/// Word count of the mnemonics generated by the wallet.
+pub const DEFAULT_MNEMONIC_WORDS: usize = 24;
+
+/// Read a mnemonic one word at a time through `read_hidden`, which is given the prompt to show
+/// and must read a line without echoing it.
+///
+/// Each answer may be a prefix of a word, as long as it is a word or only one word starts with it.
+/// The checksum of the assembled phrase is verified before it is returned.
+pub fn read_mnemonic(
+    word_count: usize,
+    mut read_hidden: impl FnMut(&str) -> io::Result<String>,
+) -> Result<Mnemonic> {
+    ensure!(
+        (12..=24).contains(&word_count) && word_count % 3 == 0,
+        "{}",
+        tr!("account.recover.invalid_word_count", word_count)
+    );
+    let mut words = Vec::with_capacity(word_count);
+    while words.len() < word_count {
+        let prompt = tr!("account.recover.word_prompt", words.len() + 1, word_count);
+        let input = read_hidden(&prompt)?;
+        let prefix = input.trim().to_lowercase();
+        if prefix.is_empty() {
+            continue;
+        }
+        match Mnemonic::complete_word(&prefix).as_slice() {
+            [] => println!("{}", tr!("account.recover.unknown_word")),
+            [word] => words.push(*word),
+            candidates => println!(
+                "{}",
+                tr!("account.recover.ambiguous_word", candidates.len())
+            ),
+        }
+    }
+    Mnemonic::from(&words.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scripted(answers: &[&'static str]) -> impl FnMut(&str) -> io::Result<String> {
+        let mut answers = answers.to_vec().into_iter();
+        move |_prompt| {
+            answers
+                .next()
+                .map(str::to_string)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "no more input"))
+        }
+    }
+
+    #[test]
+    fn test_read_mnemonic_with_prefixes() {
+        let phrase = "legal winner thank year wave sausage worth useful legal winner thank yellow";
+        let answers = [
+            "lega", "winne", "than", "year", "wave", "sausa", "worth", "usefu", "LEGAL", "",
+            "winner", "thank", "yell",
+        ];
+        let mnemonic = read_mnemonic(12, scripted(&answers)).unwrap();
+        assert_eq!(mnemonic.to_string(), phrase);
+    }
+
+    #[test]
+    fn test_read_mnemonic_retries_unknown_and_ambiguous_words() {
+        let answers = [
+            "qx", "le", "legal", "winner", "thank", "year", "wave", "sausage", "worth", "useful",
+            "legal", "winner", "thank", "yellow",
+        ];
+        assert!(read_mnemonic(12, scripted(&answers)).is_ok());
+    }
+
+    #[test]
+    fn test_read_mnemonic_rejects_bad_checksum() {
+        let answers = [
+            "legal", "winner", "thank", "year", "wave", "sausage", "worth", "useful", "legal",
+            "winner", "thank", "year",
+        ];
+        assert!(read_mnemonic(12, scripted(&answers)).is_err());
+        assert!(read_mnemonic(13, scripted(&answers)).is_err());
+    }
+}
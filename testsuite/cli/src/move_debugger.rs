@@ -0,0 +1,247 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tr;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use move_vm_runtime::observer::{ExecutionObserver, ExecutionState};
+use std::{
+    collections::BTreeSet,
+    io::{self, Write},
+};
+
+/// Prompt of the debugger, telling its commands apart from those of the client.
+const PROMPT: &str = "(move debug) ";
+
+#[derive(Debug, Eq, PartialEq)]
+enum DebugCommand {
+    Step,
+    Next,
+    Continue,
+    Break(String),
+    Delete(String),
+    Breakpoints,
+    Locals,
+    Stack,
+    Code,
+    Help,
+}
+
+impl DebugCommand {
+    /// Parses a line of input, an empty one stepping to the next instruction.
+    fn parse(line: &str) -> Option<Self> {
+        let words: Vec<_> = line.split_whitespace().collect();
+        Some(match words.as_slice() {
+            [] | ["step"] | ["s"] => DebugCommand::Step,
+            ["next"] | ["n"] => DebugCommand::Next,
+            ["continue"] | ["c"] => DebugCommand::Continue,
+            ["break", function] | ["b", function] => DebugCommand::Break(function.to_string()),
+            ["delete", function] | ["d", function] => DebugCommand::Delete(function.to_string()),
+            ["breakpoints"] | ["bl"] => DebugCommand::Breakpoints,
+            ["locals"] | ["l"] => DebugCommand::Locals,
+            ["stack"] | ["bt"] => DebugCommand::Stack,
+            ["code"] => DebugCommand::Code,
+            ["help"] | ["h"] => DebugCommand::Help,
+            _ => return None,
+        })
+    }
+}
+
+/// When the debugger stops.
+enum Mode {
+    /// At the first instruction of the script, once the prologue ran
+    Entry,
+    /// At every instruction
+    Step,
+    /// At the next instruction of the current function or of its callers, at this depth at most
+    Next(usize),
+    /// At breakpoints only
+    Continue,
+    /// Never again, once the script returned and the epilogue runs
+    Done,
+}
+
+/// Step debugger of the Move script executed by `dev execute --debug`, prompting for commands on
+/// stdin at each instruction stepped through.
+pub struct StepDebugger {
+    /// The script function executed, or none for a script
+    entry: Option<(ModuleId, Identifier)>,
+    mode: Mode,
+    /// Functions to stop at when they're called, by name
+    breakpoints: BTreeSet<String>,
+}
+
+impl StepDebugger {
+    /// A debugger stopping at the first instruction of the script function `entry`, or of the
+    /// script if there's none.
+    pub fn new(entry: Option<(ModuleId, Identifier)>) -> Self {
+        Self {
+            entry,
+            mode: Mode::Entry,
+            breakpoints: BTreeSet::new(),
+        }
+    }
+
+    fn is_entry(&self, state: &ExecutionState) -> bool {
+        if state.depth() > 0 {
+            return false;
+        }
+        match (&self.entry, state.module_id()) {
+            (None, None) => true,
+            (Some((module_id, function)), Some(state_module_id)) => {
+                module_id == state_module_id && function.as_str() == state.function_name()
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether a breakpoint is set on `function`, by its full name such as
+    /// `0x1::DiemAccount::pay_from`, or without its address or module.
+    fn is_breakpoint(&self, function: &str) -> bool {
+        self.breakpoints.iter().any(|breakpoint| {
+            function == breakpoint || function.ends_with(&format!("::{}", breakpoint))
+        })
+    }
+
+    /// Prompts for commands until one resumes execution.
+    fn prompt(&mut self, state: &ExecutionState) {
+        println!(
+            "{} [{}] {:?}",
+            state.function(),
+            state.pc(),
+            state.instruction()
+        );
+        loop {
+            print!("{}", PROMPT);
+            io::stdout().flush().ok();
+            let mut line = String::new();
+            if !matches!(io::stdin().read_line(&mut line), Ok(read) if read > 0) {
+                println!("{}", tr!("debugger.end_of_input"));
+                self.breakpoints.clear();
+                self.mode = Mode::Continue;
+                return;
+            }
+            let command = match DebugCommand::parse(&line) {
+                Some(command) => command,
+                None => {
+                    println!("{}", tr!("debugger.unknown_command", line.trim()));
+                    continue;
+                }
+            };
+            match command {
+                DebugCommand::Step => {
+                    self.mode = Mode::Step;
+                    return;
+                }
+                DebugCommand::Next => {
+                    self.mode = Mode::Next(state.depth());
+                    return;
+                }
+                DebugCommand::Continue => {
+                    self.mode = Mode::Continue;
+                    return;
+                }
+                DebugCommand::Break(function) => {
+                    println!("{}", tr!("debugger.breakpoint_set", function));
+                    self.breakpoints.insert(function);
+                }
+                DebugCommand::Delete(function) => {
+                    if self.breakpoints.remove(&function) {
+                        println!("{}", tr!("debugger.breakpoint_deleted", function));
+                    } else {
+                        println!("{}", tr!("debugger.no_breakpoint", function));
+                    }
+                }
+                DebugCommand::Breakpoints => {
+                    if self.breakpoints.is_empty() {
+                        println!("{}", tr!("debugger.no_breakpoints"));
+                    }
+                    for breakpoint in &self.breakpoints {
+                        println!("  {}", breakpoint);
+                    }
+                }
+                DebugCommand::Locals => match state.locals() {
+                    Ok(locals) if locals.is_empty() => println!("{}", tr!("debugger.no_locals")),
+                    Ok(locals) => println!("{}", locals),
+                    Err(e) => println!("{}", tr!("debugger.inspect_error", format!("{:?}", e))),
+                },
+                DebugCommand::Stack => match state.stack_trace() {
+                    Ok(stack_trace) => {
+                        print!("{}", stack_trace);
+                        println!("{}", tr!("debugger.current_frame", state.function()));
+                    }
+                    Err(e) => println!("{}", tr!("debugger.inspect_error", format!("{:?}", e))),
+                },
+                DebugCommand::Code => {
+                    for (pc, instruction) in state.code().iter().enumerate() {
+                        let marker = if pc == state.pc() as usize { ">" } else { " " };
+                        println!("{} [{}] {:?}", marker, pc, instruction);
+                    }
+                }
+                DebugCommand::Help => println!("{}", tr!("debugger.help")),
+            }
+        }
+    }
+}
+
+impl ExecutionObserver for StepDebugger {
+    fn on_instruction(&mut self, state: &ExecutionState) {
+        let stop = match self.mode {
+            Mode::Entry | Mode::Continue | Mode::Done => false,
+            Mode::Step => true,
+            Mode::Next(depth) => state.depth() <= depth,
+        };
+        if stop {
+            self.prompt(state);
+        }
+    }
+
+    fn on_frame_push(&mut self, state: &ExecutionState) {
+        match self.mode {
+            Mode::Entry => {
+                if self.is_entry(state) {
+                    self.mode = Mode::Step;
+                }
+            }
+            Mode::Done => (),
+            _ if state.depth() == 0 => self.mode = Mode::Done,
+            _ => {
+                let function = state.function();
+                if self.is_breakpoint(&function) {
+                    println!("{}", tr!("debugger.breakpoint_hit", function));
+                    self.mode = Mode::Step;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(DebugCommand::parse("\n"), Some(DebugCommand::Step));
+        assert_eq!(DebugCommand::parse("n"), Some(DebugCommand::Next));
+        assert_eq!(
+            DebugCommand::parse("  break DiemAccount::pay_from \n"),
+            Some(DebugCommand::Break("DiemAccount::pay_from".to_string()))
+        );
+        assert_eq!(DebugCommand::parse("break"), None);
+        assert_eq!(DebugCommand::parse("step twice"), None);
+        assert_eq!(DebugCommand::parse("jump"), None);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut debugger = StepDebugger::new(None);
+        debugger
+            .breakpoints
+            .insert("DiemAccount::pay_from".to_string());
+        debugger.breakpoints.insert("withdraw".to_string());
+        assert!(debugger.is_breakpoint("0x1::DiemAccount::pay_from"));
+        assert!(debugger.is_breakpoint("0x1::DiemAccount::withdraw"));
+        assert!(!debugger.is_breakpoint("0x1::DiemAccount::pay_from_with_metadata"));
+        assert!(!debugger.is_breakpoint("0x1::DiemAccount::do_withdraw"));
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, ensure, format_err, Result};
+use diem_types::{
+    account_address::AccountAddress,
+    transaction::{
+        authenticator::AccountAuthenticator, RawTransaction, RawTransactionWithData,
+        SignedTransaction,
+    },
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// A multi-agent transaction being passed around for signatures.
+///
+/// The sender and every secondary signer sign the same `RawTransactionWithData`; once all of
+/// their signatures are collected the transaction can be assembled into a `SignedTransaction`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PartialMultiAgentTransaction {
+    pub raw_txn: RawTransaction,
+    pub secondary_signer_addresses: Vec<AccountAddress>,
+    /// Signatures collected so far, keyed by signer.
+    pub signatures: BTreeMap<AccountAddress, AccountAuthenticator>,
+}
+
+/// One signer's signature over a `PartialMultiAgentTransaction`, exchanged as a file.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PartialSignature {
+    pub signer: AccountAddress,
+    pub authenticator: AccountAuthenticator,
+}
+
+impl PartialMultiAgentTransaction {
+    pub fn new(
+        raw_txn: RawTransaction,
+        secondary_signer_addresses: Vec<AccountAddress>,
+    ) -> Result<Self> {
+        let mut signers = secondary_signer_addresses.clone();
+        signers.push(raw_txn.sender());
+        signers.sort();
+        signers.dedup();
+        ensure!(
+            signers.len() == secondary_signer_addresses.len() + 1,
+            "Secondary signers must be distinct from each other and from the sender"
+        );
+        Ok(Self {
+            raw_txn,
+            secondary_signer_addresses,
+            signatures: BTreeMap::new(),
+        })
+    }
+
+    /// The message every signer signs.
+    pub fn signing_message(&self) -> RawTransactionWithData {
+        RawTransactionWithData::new_multi_agent(
+            self.raw_txn.clone(),
+            self.secondary_signer_addresses.clone(),
+        )
+    }
+
+    /// The sender followed by the secondary signers.
+    pub fn signers(&self) -> impl Iterator<Item = AccountAddress> + '_ {
+        std::iter::once(self.raw_txn.sender())
+            .chain(self.secondary_signer_addresses.iter().copied())
+    }
+
+    /// Signers whose signature is still missing.
+    pub fn missing_signers(&self) -> Vec<AccountAddress> {
+        self.signers()
+            .filter(|signer| !self.signatures.contains_key(signer))
+            .collect()
+    }
+
+    /// Record `signature` after checking it comes from a signer of this transaction and
+    /// verifies against the signing message.
+    pub fn add_signature(&mut self, signature: PartialSignature) -> Result<()> {
+        ensure!(
+            self.signers().any(|signer| signer == signature.signer),
+            "{} is not a signer of this transaction",
+            signature.signer
+        );
+        signature
+            .authenticator
+            .verify(&self.signing_message())
+            .map_err(|e| format_err!("Invalid signature from {}: {}", signature.signer, e))?;
+        self.signatures
+            .insert(signature.signer, signature.authenticator);
+        Ok(())
+    }
+
+    /// Assemble the final transaction, failing if any signature is missing.
+    pub fn into_signed_transaction(mut self) -> Result<SignedTransaction> {
+        let missing = self.missing_signers();
+        if !missing.is_empty() {
+            bail!("Missing signatures from {:?}", missing);
+        }
+        let sender = self
+            .signatures
+            .remove(&self.raw_txn.sender())
+            .expect("checked above");
+        let secondary_signers = self
+            .secondary_signer_addresses
+            .iter()
+            .map(|signer| self.signatures[signer].clone())
+            .collect();
+        Ok(SignedTransaction::new_multi_agent(
+            self.raw_txn,
+            sender,
+            self.secondary_signer_addresses,
+            secondary_signers,
+        ))
+    }
+}
+
+/// Read a JSON file written by `save_json`.
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    serde_json::from_slice(&fs::read(path)?)
+        .map_err(|e| format_err!("Unable to parse {:?}: {}", path, e))
+}
+
+/// Write `value` to `path` as pretty-printed JSON.
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    fs::write(path, serde_json::to_vec_pretty(value)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diem_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, SigningKey, Uniform};
+    use diem_types::{
+        chain_id::ChainId,
+        transaction::{Script, TransactionPayload},
+    };
+
+    fn raw_txn(sender: AccountAddress) -> RawTransaction {
+        RawTransaction::new(
+            sender,
+            0,
+            TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+            1_000_000,
+            0,
+            "XUS".to_string(),
+            u64::MAX,
+            ChainId::test(),
+        )
+    }
+
+    fn sign(
+        txn: &PartialMultiAgentTransaction,
+        signer: AccountAddress,
+        key: &Ed25519PrivateKey,
+    ) -> PartialSignature {
+        PartialSignature {
+            signer,
+            authenticator: AccountAuthenticator::ed25519(
+                key.public_key(),
+                key.sign(&txn.signing_message()),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_collect_and_assemble() {
+        let sender = AccountAddress::random();
+        let secondary = AccountAddress::random();
+        let sender_key = Ed25519PrivateKey::generate_for_testing();
+        let secondary_key = Ed25519PrivateKey::generate_for_testing();
+        let mut txn = PartialMultiAgentTransaction::new(raw_txn(sender), vec![secondary]).unwrap();
+
+        txn.add_signature(sign(&txn, sender, &sender_key)).unwrap();
+        assert_eq!(txn.missing_signers(), vec![secondary]);
+        assert!(txn.clone().into_signed_transaction().is_err());
+
+        txn.add_signature(sign(&txn, secondary, &secondary_key))
+            .unwrap();
+        let signed = txn.into_signed_transaction().unwrap();
+        assert!(signed.check_signature().is_ok());
+        assert_eq!(
+            signed.authenticator().secondary_signer_addreses(),
+            vec![secondary]
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_signatures() {
+        let sender = AccountAddress::random();
+        let secondary = AccountAddress::random();
+        let key = Ed25519PrivateKey::generate_for_testing();
+        let mut txn = PartialMultiAgentTransaction::new(raw_txn(sender), vec![secondary]).unwrap();
+
+        // Not a signer of this transaction.
+        let outsider = sign(&txn, AccountAddress::random(), &key);
+        assert!(txn.add_signature(outsider).is_err());
+
+        // Signed over a different message.
+        let other = PartialMultiAgentTransaction::new(raw_txn(sender), vec![]).unwrap();
+        assert!(txn.add_signature(sign(&other, sender, &key)).is_err());
+
+        assert!(PartialMultiAgentTransaction::new(raw_txn(sender), vec![sender]).is_err());
+    }
+}
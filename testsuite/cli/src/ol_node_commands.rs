@@ -1,8 +1,10 @@
 use crate::{
     client_proxy::ClientProxy,
     commands::{subcommand_execute, Command},
+    tr,
 };
 
+use anyhow::{bail, format_err, Result};
 use chrono::{DateTime, Utc};
 use diem_types::waypoint::Waypoint;
 use std::time::{Duration, UNIX_EPOCH};
@@ -17,15 +19,17 @@ impl Command for NodeCommand {
         vec!["node", "n"]
     }
     fn get_description(&self) -> &'static str {
-        "Get state of validators, miners."
+        tr!("node.description")
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        let commands: Vec<Box<dyn Command>> = vec![
+    fn get_subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![
             Box::new(CommandQueryTowerState {}),
             Box::new(CommandGenWaypoint {}),
-        ];
-
-        subcommand_execute(&params[0], commands, client, &params[1..]);
+            Box::new(CommandSyncStatus {}),
+        ]
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        subcommand_execute(&params[0], self.get_subcommands(), client, &params[1..])
     }
 }
 
@@ -40,14 +44,18 @@ impl Command for CommandQueryTowerState {
         "<account_address>"
     }
     fn get_description(&self) -> &'static str {
-        "Get miner state for a address"
+        tr!("node.miner_state.description")
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         match client.get_miner_state(&params) {
-            Ok( Some(msv)) => println!(" Account: {:?}\n {:?}", &params[1], msv ),
-            Err(e) => println!("Didn't find miner state for this address: {:?}", e),
+            Ok( Some(msv)) => println!(
+                "{}",
+                tr!("node.miner_state.done", format!("{:?}", &params[1]), format!("{:?}", msv))
+            ),
+            Err(e) => bail!(tr!("node.miner_state.error", format!("{:?}", e))),
             _ => {}
         }
+        Ok(())
     }
 }
 
@@ -63,39 +71,82 @@ impl Command for CommandGenWaypoint {
     }
 
     fn get_description(&self) -> &'static str {
-        "Generate a waypoint for the latest epoch change LedgerInfo"
+        tr!("gen_waypoint.description")
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         if params.len() != 1 {
-            println!("No parameters required for waypoint generation");
-            return;
+            bail!(tr!("gen_waypoint.invalid_args"));
         }
-        println!("Retrieving the uptodate ledger info...");
+        println!("{}", tr!("gen_waypoint.progress"));
         if let Err(e) = client.test_validator_connection() {
-            println!("Failed to get uptodate ledger info connection: {}", e);
-            return;
+            bail!(tr!("gen_waypoint.connection_error", e));
         }
 
         let latest_epoch_change_li = match client.latest_epoch_change_li() {
             Some(li) => li,
-            None => {
-                println!("No epoch change LedgerInfo found");
-                return;
-            }
+            None => bail!(tr!("gen_waypoint.no_epoch_change")),
         };
         let li_time_str = DateTime::<Utc>::from(
             UNIX_EPOCH
                 + Duration::from_micros(latest_epoch_change_li.ledger_info().timestamp_usecs()),
         );
-        match Waypoint::new_epoch_boundary(latest_epoch_change_li.ledger_info()) {
-            Err(e) => println!("Failed to generate a waypoint: {}", e),
-            Ok(waypoint) => println!(
-                "Waypoint (end of epoch {}, time {}): {}",
+        let waypoint = Waypoint::new_epoch_boundary(latest_epoch_change_li.ledger_info())
+            .map_err(|e| format_err!(tr!("gen_waypoint.error", e)))?;
+        println!(
+            "{}",
+            tr!(
+                "gen_waypoint.done",
                 latest_epoch_change_li.ledger_info().epoch(),
                 li_time_str,
                 waypoint
-            ),
+            )
+        );
+        Ok(())
+    }
+}
+
+/// Sub command to report the progress of state sync of a node.
+pub struct CommandSyncStatus {}
+
+impl Command for CommandSyncStatus {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["sync-status", "sync_status"]
+    }
+
+    fn get_params_help(&self) -> &'static str {
+        "<admin_url>"
+    }
+
+    fn get_description(&self) -> &'static str {
+        tr!("node.sync_status.description")
+    }
+
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        match client.get_sync_status(params) {
+            Ok(status) => {
+                println!(
+                    "{}",
+                    tr!("node.sync_status.highest_known_version", status.highest_known_version)
+                );
+                println!("{}", tr!("node.sync_status.synced_version", status.synced_version));
+                println!(
+                    "{}",
+                    tr!("node.sync_status.committed_version", status.committed_version)
+                );
+                println!("{}", tr!("node.sync_status.initialized", status.initialized));
+                println!(
+                    "{}",
+                    tr!("node.sync_status.sync_rate", format!("{:.1}", status.sync_rate))
+                );
+                match status.estimated_catch_up_secs {
+                    Some(0) => println!("{}", tr!("node.sync_status.caught_up")),
+                    Some(secs) => println!("{}", tr!("node.sync_status.catch_up_time", secs)),
+                    None => println!("{}", tr!("node.sync_status.catch_up_unknown")),
+                }
+            }
+            Err(e) => bail!(tr!("node.sync_status.error", e)),
         }
+        Ok(())
     }
 }
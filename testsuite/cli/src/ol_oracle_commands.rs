@@ -1,7 +1,9 @@
 use crate::{
     client_proxy::ClientProxy,
     commands::{subcommand_execute, Command},
+    tr,
 };
+use anyhow::Result;
 /// Major command for query operations.
 pub struct OracleCommand {}
 
@@ -10,14 +12,13 @@ impl Command for OracleCommand {
         vec!["oracle", "o"]
     }
     fn get_description(&self) -> &'static str {
-        "Oracle related commands"
+        tr!("oracle.description")
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        let commands: Vec<Box<dyn Command>> = vec![
-            Box::new(OracleCommandUpdate {}),
-        ];
-
-        subcommand_execute(&params[0], commands, client, &params[1..]);
+    fn get_subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![Box::new(OracleCommandUpdate {})]
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        subcommand_execute(&params[0], self.get_subcommands(), client, &params[1..])
     }
 }
 
@@ -33,15 +34,13 @@ impl Command for OracleCommandUpdate {
     }
 
     fn get_description(&self) -> &'static str {
-        "On-chain upgrade of stdlib"
+        tr!("oracle.upgrade.description")
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-
-        match client.oracle_upgrade_stdlib(params, true) {
-            Ok(_) => println!("Successfully finished execution"),
-            Err(e) => println!("{}", e),
-        }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        client.oracle_upgrade_stdlib(params, true)?;
+        println!("{}", tr!("commands.execution_done"));
+        Ok(())
     }
 }
 
@@ -57,19 +56,15 @@ impl Command for OracleQueryCommandUpdate {
     }
 
     fn get_description(&self) -> &'static str {
-        "query on-chain upgrade "
+        tr!("oracle.query.description")
     }
 
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        match client.query_oracle_upgrade(params) {
-            Ok(view) => {
-                match view {
-                    Some(o)=>println!("{:?}", o),
-                    None=> println!("Nothing found")
-                }
-            },
-            Err(e) => println!("{}", e),
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        match client.query_oracle_upgrade(params)? {
+            Some(o)=>println!("{:?}", o),
+            None=> println!("{}", tr!("oracle.query.none"))
         }
+        Ok(())
     }
 }
 
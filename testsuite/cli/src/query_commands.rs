@@ -2,9 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    client_proxy::ClientProxy,
-    commands::{report_error, subcommand_execute, Command},
+    client_proxy::{ClientProxy, TransactionDetail},
+    commands::{subcommand_execute, Command},
+    tr,
 };
+use anyhow::{bail, Context, Result};
+use diem_client::views::TransactionDataView;
+use diem_types::write_set::WriteOp;
 
 /// Major command for query operations.
 pub struct QueryCommand {}
@@ -14,21 +18,23 @@ impl Command for QueryCommand {
         vec!["query", "q"]
     }
     fn get_description(&self) -> &'static str {
-        "Query operations"
+        tr!("query.description")
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        let commands: Vec<Box<dyn Command>> = vec![
+    fn get_subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![
             Box::new(QueryCommandGetBalance {}),
             Box::new(QueryCommandGetSeqNum {}),
             Box::new(QueryCommandGetLatestAccountState {}),
             Box::new(QueryCommandGetTxnByAccountSeq {}),
             Box::new(QueryCommandGetTxnByRange {}),
+            Box::new(QueryCommandGetTxnDetail {}),
             Box::new(QueryCommandGetEvent {}),
             Box::new(QueryCommandGetLatestAccountResources {}),
             Box::new(QueryWaypoint {}), ///////// 0L /////////
-        ];
-
-        subcommand_execute(&params[0], commands, client, &params[1..]);
+        ]
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        subcommand_execute(&params[0], self.get_subcommands(), client, &params[1..])
     }
 }
 
@@ -40,22 +46,22 @@ impl Command for QueryCommandGetBalance {
         vec!["balance", "b"]
     }
     fn get_params_help(&self) -> &'static str {
-        "<account_ref_id>|<account_address>"
+        "<account_ref_id>|<account_address> [version]"
     }
     fn get_description(&self) -> &'static str {
-        "Get the current balances of an account"
+        tr!("query.balance.description")
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        if params.len() != 2 {
-            println!("Invalid number of arguments for balance query");
-            return;
-        }
-        match client.get_balances(&params) {
-            Ok(balances) => balances
-                .iter()
-                .for_each(|balance| println!("Balance is: {}", balance)),
-            Err(e) => report_error("Failed to get balances", e),
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.len() != 2 && params.len() != 3 {
+            bail!(tr!("query.balance.invalid_args"));
         }
+        let balances = client
+            .get_balances(&params)
+            .context(tr!("query.balance.error"))?;
+        balances
+            .iter()
+            .for_each(|balance| println!("{}", tr!("query.balance.result", balance)));
+        Ok(())
     }
 }
 
@@ -70,15 +76,15 @@ impl Command for QueryCommandGetSeqNum {
         "<account_ref_id>|<account_address> [reset_sequence_number=true|false]"
     }
     fn get_description(&self) -> &'static str {
-        "Get the current sequence number for an account, \
-         and reset current sequence number in CLI (optional, default is false)"
-    }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        println!(">> Getting current sequence number");
-        match client.get_sequence_number(&params) {
-            Ok(sn) => println!("Sequence number is: {}", sn),
-            Err(e) => report_error("Error getting sequence number", e),
-        }
+        tr!("query.sequence.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        println!("{}", tr!("query.sequence.progress"));
+        let sn = client
+            .get_sequence_number(&params)
+            .context(tr!("query.sequence.error"))?;
+        println!("{}", tr!("query.sequence.result", sn));
+        Ok(())
     }
 }
 
@@ -90,65 +96,71 @@ impl Command for QueryCommandGetLatestAccountState {
         vec!["account_state", "as"]
     }
     fn get_params_help(&self) -> &'static str {
-        "<account_ref_id>|<account_address>"
+        "<account_ref_id>|<account_address> [version]"
     }
     fn get_description(&self) -> &'static str {
-        "Get the latest state for an account"
-    }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        println!(">> Getting latest account state");
-        match client.get_latest_account(&params) {
-            Ok(acc) => println!(
-                "Latest account state is: \n \
-                 Account: {:#?}\n \
-                 State: {:#?}\n \
-                 Blockchain Version: {}\n",
-                client
-                    .get_account_address_from_parameter(params[1])
-                    .expect("Unable to parse account parameter"),
-                acc,
-                client.get_latest_version(),
+        tr!("query.account_state.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        println!("{}", tr!("query.account_state.progress"));
+        let acc = client
+            .get_latest_account(&params)
+            .context(tr!("query.account_state.error"))?;
+        let address = client
+            .get_account_address_from_parameter(params[1])
+            .expect("Unable to parse account parameter");
+        let state = match params.get(2) {
+            Some(version) => tr!(
+                "query.account_state.historical",
+                format!("{:#?}", address),
+                format!("{:#?}", acc),
+                version
             ),
-            Err(e) => report_error("Error getting latest account state", e),
-        }
+            None => tr!(
+                "query.account_state.latest",
+                format!("{:#?}", address),
+                format!("{:#?}", acc),
+                client.get_latest_version()
+            ),
+        };
+        println!("{}", state);
+        Ok(())
     }
 }
 
-/// Command to query latest account state from validator.
+/// Command to dump every Move resource of an account as annotated JSON.
 pub struct QueryCommandGetLatestAccountResources {}
 
 impl Command for QueryCommandGetLatestAccountResources {
     fn get_aliases(&self) -> Vec<&'static str> {
-        vec!["account_resources", "ar"]
+        vec!["account-resources", "account_resources", "ar"]
     }
     fn get_params_help(&self) -> &'static str {
         "<account_ref_id>|<account_address>"
     }
     fn get_description(&self) -> &'static str {
-        "Get the latest annotated resources in an account"
-    }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        println!(">> Getting latest account state");
-        match client.get_latest_account_resources(&params) {
-            Ok((Some(acc), version)) => println!(
-                "Latest account state is: \n \
-                 Account: {:#?}\n \
-                 State: {}\n \
-                 Blockchain Version: {}\n",
-                client
-                    .get_account_address_from_parameter(params[1])
-                    .expect("Unable to parse account parameter"),
-                acc,
-                version,
-            ),
-            Ok((None, _version)) => println!(
-                "No Account found for {:#?}",
-                client
-                    .get_account_address_from_parameter(params[1])
-                    .expect("Unable to parse account parameter")
-            ),
-            Err(e) => report_error("Error getting latest account state", e),
+        tr!("query.account_resources.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        println!("{}", tr!("query.account_state.progress"));
+        match client
+            .get_account_resources_json(&params)
+            .context(tr!("query.account_state.error"))?
+        {
+            (account, Some(resources), version) => {
+                println!(
+                    "{}",
+                    tr!("query.account_resources.header", account, version)
+                );
+                let json = serde_json::to_string_pretty(&resources)
+                    .context(tr!("query.account_state.error"))?;
+                println!("{}", json);
+            }
+            (account, None, _version) => {
+                println!("{}", tr!("query.account_resources.not_found", account))
+            }
         }
+        Ok(())
     }
 }
 
@@ -163,25 +175,20 @@ impl Command for QueryCommandGetTxnByAccountSeq {
         "<account_ref_id>|<account_address> <sequence_number> <fetch_events=true|false>"
     }
     fn get_description(&self) -> &'static str {
-        "Get the committed transaction by account and sequence number.  \
-         Optionally also fetch events emitted by this transaction."
-    }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        println!(">> Getting committed transaction by account and sequence number");
-        match client.get_committed_txn_by_acc_seq(&params) {
-            Ok(txn_view) => {
-                match txn_view {
-                    Some(txn_view) => {
-                        println!("Committed transaction: {:#?}", txn_view);
-                    }
-                    None => println!("Transaction not available"),
-                };
+        tr!("query.txn_acc_seq.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        println!("{}", tr!("query.txn_acc_seq.progress"));
+        let txn_view = client
+            .get_committed_txn_by_acc_seq(&params)
+            .context(tr!("query.txn_acc_seq.error"))?;
+        match txn_view {
+            Some(txn_view) => {
+                println!("{}", tr!("query.txn_acc_seq.done", format!("{:#?}", txn_view)));
             }
-            Err(e) => report_error(
-                "Error getting committed transaction by account and sequence number",
-                e,
-            ),
-        }
+            None => println!("{}", tr!("query.txn_acc_seq.not_available")),
+        };
+        Ok(())
     }
 }
 
@@ -196,23 +203,119 @@ impl Command for QueryCommandGetTxnByRange {
         "<start_version> <limit> <fetch_events=true|false>"
     }
     fn get_description(&self) -> &'static str {
-        "Get the committed transactions by version range. \
-         Optionally also fetch events emitted by these transactions."
-    }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        println!(">> Getting committed transaction by range");
-        match client.get_committed_txn_by_range(&params) {
-            Ok(comm_txns_and_events) => {
-                // Note that this should never panic because we shouldn't return items
-                // if the version wasn't able to be parsed in the first place
-                let mut cur_version = params[1].parse::<u64>().expect("Unable to parse version");
-                for txn_view in comm_txns_and_events {
-                    println!("Transaction at version {}: {:#?}", cur_version, txn_view,);
-                    cur_version += 1;
+        tr!("query.txn_range.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        println!("{}", tr!("query.txn_range.progress"));
+        let comm_txns_and_events = client
+            .get_committed_txn_by_range(&params)
+            .context(tr!("query.txn_range.error"))?;
+        // Note that this should never panic because we shouldn't return items
+        // if the version wasn't able to be parsed in the first place
+        let mut cur_version = params[1].parse::<u64>().expect("Unable to parse version");
+        for txn_view in comm_txns_and_events {
+            println!(
+                "{}",
+                tr!("query.txn_range.entry", cur_version, format!("{:#?}", txn_view))
+            );
+            cur_version += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Sub command to show everything about one committed transaction, verified against the
+/// trusted state.
+pub struct QueryCommandGetTxnDetail {}
+
+impl Command for QueryCommandGetTxnDetail {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["txn-detail", "txn_detail", "td"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<version>|<transaction_hash> [search_depth]"
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("query.txn_detail.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        println!("{}", tr!("query.txn_detail.progress"));
+        let detail = client
+            .get_txn_detail(&params)
+            .context(tr!("query.txn_detail.error"))?;
+        print_txn_detail(&detail);
+        Ok(())
+    }
+}
+
+fn print_txn_detail(detail: &TransactionDetail) {
+    let view = &detail.view;
+    println!(
+        "{}",
+        tr!("query.txn_detail.header", view.hash, view.version)
+    );
+    println!("{}", tr!("query.txn_detail.vm_status", &view.vm_status));
+    println!("{}", tr!("query.txn_detail.gas_used", view.gas_used));
+    if let TransactionDataView::UserTransaction {
+        sender,
+        sequence_number,
+        signature_scheme,
+        secondary_signers,
+        secondary_signature_schemes,
+        script,
+        ..
+    } = &view.transaction
+    {
+        println!(
+            "{}",
+            tr!("query.txn_detail.sender", sender, sequence_number)
+        );
+        println!(
+            "{}",
+            tr!("query.txn_detail.signature_scheme", signature_scheme)
+        );
+        if let (Some(signers), Some(schemes)) = (secondary_signers, secondary_signature_schemes) {
+            for (signer, scheme) in signers.iter().zip(schemes) {
+                println!("  {}: {}", signer, scheme);
+            }
+        }
+        println!("{}", tr!("query.txn_detail.payload"));
+        println!("{:#?}", script);
+    } else {
+        println!("{}", tr!("query.txn_detail.payload"));
+        println!("{:#?}", view.transaction);
+    }
+    println!("{}", tr!("query.txn_detail.events", view.events.len()));
+    for event in &view.events {
+        println!(
+            "{}",
+            tr!(
+                "query.txn_detail.event",
+                event.key,
+                event.sequence_number,
+                format!("{:?}", event.data)
+            )
+        );
+    }
+    match &detail.write_set {
+        Ok(write_set) => {
+            println!(
+                "{}",
+                tr!("query.txn_detail.write_set", write_set.iter().count())
+            );
+            for (access_path, op) in write_set {
+                match op {
+                    WriteOp::Value(bytes) => println!(
+                        "{}",
+                        tr!("transaction.write_op.write", access_path, bytes.len())
+                    ),
+                    WriteOp::Deletion => {
+                        println!("{}", tr!("transaction.write_op.delete", access_path))
+                    }
                 }
             }
-            Err(e) => report_error("Error getting committed transactions by range", e),
         }
+        Err(e) => println!("{}", tr!("query.txn_detail.write_set_unavailable", e)),
     }
 }
 
@@ -221,29 +324,38 @@ pub struct QueryCommandGetEvent {}
 
 impl Command for QueryCommandGetEvent {
     fn get_aliases(&self) -> Vec<&'static str> {
-        vec!["event", "ev"]
+        vec!["event", "events", "ev"]
     }
     fn get_params_help(&self) -> &'static str {
-        "<account_ref_id>|<account_address> <sent|received> <start_sequence_number> <limit>"
+        "<account_ref_id>|<account_address> <sent|received> <start_sequence_number> <limit> \
+         | --follow <account_ref_id>|<account_address> <sent|received> [--from-seq <sequence_number>]"
     }
     fn get_description(&self) -> &'static str {
-        "Get events by account and event type (sent|received)."
-    }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        println!(">> Getting events by account and event type.");
-        match client.get_events_by_account_and_type(&params) {
-            Ok((events, last_event_state)) => {
-                if events.is_empty() {
-                    println!("No events returned");
-                } else {
-                    for event in events {
-                        println!("{:?}", event);
-                    }
-                }
-                println!("Last event state: {:#?}", last_event_state);
+        tr!("query.event.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.get(1) == Some(&"--follow") {
+            println!("{}", tr!("query.event.follow_progress"));
+            return client
+                .follow_events(&params, |event| println!("{:?}", event))
+                .context(tr!("query.event.error"));
+        }
+        println!("{}", tr!("query.event.progress"));
+        let (events, last_event_state) = client
+            .get_events_by_account_and_type(&params)
+            .context(tr!("query.event.error"))?;
+        if events.is_empty() {
+            println!("{}", tr!("query.event.none"));
+        } else {
+            for event in events {
+                println!("{:?}", event);
             }
-            Err(e) => report_error("Error getting events by access path", e),
         }
+        println!(
+            "{}",
+            tr!("query.event.last_state", format!("{:#?}", last_event_state))
+        );
+        Ok(())
     }
 }
 
@@ -262,18 +374,14 @@ impl Command for QueryWaypoint {
     }
 
     fn get_description(&self) -> &'static str {
-        "query latest waypoint"
+        tr!("query.waypoint.description")
     }
 
-    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) {
-        match client.query_waypoint() {
-            Ok(view) => {
-                match view {
-                    Some(o)=>println!("{:?}", o),
-                    None=> println!("Nothing found")
-                }
-            },
-            Err(e) => println!("{}", e),
+    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) -> Result<()> {
+        match client.query_waypoint()? {
+            Some(o)=>println!("{:?}", o),
+            None=> println!("{}", tr!("query.waypoint.none"))
         }
+        Ok(())
     }
 }
@@ -0,0 +1,238 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, ensure, format_err, Result};
+use diem_types::{account_address::AccountAddress, transaction::TransactionArgument};
+use move_binary_format::{
+    access::{ModuleAccess, ScriptAccess},
+    file_format::{CompiledModule, CompiledScript, SignatureToken, Visibility},
+};
+
+/// Separator between the type and the value of a typed argument, e.g. `u64:100`.
+const TYPE_SEPARATOR: char = ':';
+
+/// Parse a typed argument literal: `bool:true`, `u8:1`, `u64:100`, `u128:100`, `address:0x1`,
+/// `vector<u8>:0xdead` or `vector<address>:0x1,0x2`. Addresses are resolved with
+/// `parse_address` so callers can accept whatever account notation they support.
+pub fn parse_typed_argument(
+    literal: &str,
+    parse_address: impl Fn(&str) -> Result<AccountAddress>,
+) -> Result<TransactionArgument> {
+    let (ty, value) = split_typed_argument(literal)
+        .ok_or_else(|| format_err!("Expected <type>:<value>, got {:?}", literal))?;
+    let invalid =
+        |e: &dyn std::fmt::Display| format_err!("Invalid {} value {:?}: {}", ty, value, e);
+    Ok(match ty {
+        "bool" => TransactionArgument::Bool(value.parse().map_err(|e| invalid(&e))?),
+        "u8" => TransactionArgument::U8(value.parse().map_err(|e| invalid(&e))?),
+        "u64" => TransactionArgument::U64(value.parse().map_err(|e| invalid(&e))?),
+        "u128" => TransactionArgument::U128(value.parse().map_err(|e| invalid(&e))?),
+        "address" => TransactionArgument::Address(parse_address(value)?),
+        "vector<u8>" => TransactionArgument::U8Vector(
+            hex::decode(value.trim_start_matches("0x")).map_err(|e| invalid(&e))?,
+        ),
+        //////// 0L ////////
+        "vector<address>" => TransactionArgument::AddressVector(
+            value
+                .split(',')
+                .filter(|address| !address.is_empty())
+                .map(&parse_address)
+                .collect::<Result<_>>()?,
+        ),
+        _ => bail!(
+            "Unsupported argument type {:?}, expected one of bool, u8, u64, u128, address, \
+             vector<u8>, vector<address>",
+            ty
+        ),
+    })
+}
+
+/// Returns the type and value of `literal` if it is written as a typed argument.
+pub fn split_typed_argument(literal: &str) -> Option<(&str, &str)> {
+    let index = literal.find(TYPE_SEPARATOR)?;
+    let ty = &literal[..index];
+    if ty.is_empty() || ty.starts_with("0x") || ty.starts_with('@') {
+        return None;
+    }
+    Some((ty, &literal[index + 1..]))
+}
+
+/// The parameters of a compiled script a transaction has to supply, i.e. without the leading
+/// signers.
+pub fn script_parameters(script_bytes: &[u8]) -> Result<Vec<SignatureToken>> {
+    let script = CompiledScript::deserialize(script_bytes)
+        .map_err(|e| format_err!("Unable to deserialize script: {}", e))?;
+    Ok(without_signers(
+        &script.signature_at(script.as_inner().parameters).0,
+    ))
+}
+
+/// The parameters of the script function `name` in a compiled module, without the leading
+/// signers.
+pub fn script_function_parameters(module_bytes: &[u8], name: &str) -> Result<Vec<SignatureToken>> {
+    let module = CompiledModule::deserialize(module_bytes)
+        .map_err(|e| format_err!("Unable to deserialize module: {}", e))?;
+    let definition = module
+        .function_defs()
+        .iter()
+        .find(|definition| {
+            module
+                .identifier_at(module.function_handle_at(definition.function).name)
+                .as_str()
+                == name
+        })
+        .ok_or_else(|| format_err!("Function {} not found in module {}", name, module.self_id()))?;
+    ensure!(
+        definition.visibility == Visibility::Script,
+        "Function {} in module {} is not a script function",
+        name,
+        module.self_id()
+    );
+    let handle = module.function_handle_at(definition.function);
+    Ok(without_signers(&module.signature_at(handle.parameters).0))
+}
+
+/// Check that `arguments` match `parameters` in number and type.
+pub fn check_arguments(
+    parameters: &[SignatureToken],
+    arguments: &[TransactionArgument],
+) -> Result<()> {
+    ensure!(
+        parameters.len() == arguments.len(),
+        "Expected {} argument(s), got {}",
+        parameters.len(),
+        arguments.len()
+    );
+    for (index, (parameter, argument)) in parameters.iter().zip(arguments).enumerate() {
+        let matches = match (parameter, argument) {
+            (SignatureToken::Bool, TransactionArgument::Bool(_))
+            | (SignatureToken::U8, TransactionArgument::U8(_))
+            | (SignatureToken::U64, TransactionArgument::U64(_))
+            | (SignatureToken::U128, TransactionArgument::U128(_))
+            | (SignatureToken::Address, TransactionArgument::Address(_)) => true,
+            (SignatureToken::Vector(inner), TransactionArgument::U8Vector(_)) => {
+                **inner == SignatureToken::U8
+            }
+            (SignatureToken::Vector(inner), TransactionArgument::AddressVector(_)) => {
+                **inner == SignatureToken::Address
+            }
+            _ => false,
+        };
+        ensure!(
+            matches,
+            "Argument {} has type {}, expected {}",
+            index + 1,
+            argument_type(argument),
+            parameter_type(parameter)
+        );
+    }
+    Ok(())
+}
+
+fn without_signers(parameters: &[SignatureToken]) -> Vec<SignatureToken> {
+    parameters
+        .iter()
+        .skip_while(|parameter| match parameter {
+            SignatureToken::Signer => true,
+            SignatureToken::Reference(inner) => **inner == SignatureToken::Signer,
+            _ => false,
+        })
+        .cloned()
+        .collect()
+}
+
+fn argument_type(argument: &TransactionArgument) -> &'static str {
+    match argument {
+        TransactionArgument::Bool(_) => "bool",
+        TransactionArgument::U8(_) => "u8",
+        TransactionArgument::U64(_) => "u64",
+        TransactionArgument::U128(_) => "u128",
+        TransactionArgument::Address(_) => "address",
+        TransactionArgument::U8Vector(_) => "vector<u8>",
+        TransactionArgument::AddressVector(_) => "vector<address>",
+    }
+}
+
+fn parameter_type(parameter: &SignatureToken) -> String {
+    match parameter {
+        SignatureToken::Bool => "bool".to_string(),
+        SignatureToken::U8 => "u8".to_string(),
+        SignatureToken::U64 => "u64".to_string(),
+        SignatureToken::U128 => "u128".to_string(),
+        SignatureToken::Address => "address".to_string(),
+        SignatureToken::Signer => "signer".to_string(),
+        SignatureToken::Vector(inner) => format!("vector<{}>", parameter_type(inner)),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(literal: &str) -> Result<TransactionArgument> {
+        parse_typed_argument(literal, |address| {
+            AccountAddress::from_hex_literal(address).map_err(Into::into)
+        })
+    }
+
+    #[test]
+    fn test_parse_typed_argument() {
+        assert_eq!(parse("bool:true").unwrap(), TransactionArgument::Bool(true));
+        assert_eq!(parse("u8:7").unwrap(), TransactionArgument::U8(7));
+        assert_eq!(parse("u64:100").unwrap(), TransactionArgument::U64(100));
+        assert_eq!(parse("u128:100").unwrap(), TransactionArgument::U128(100));
+        assert_eq!(
+            parse("address:0x1").unwrap(),
+            TransactionArgument::Address(AccountAddress::from_hex_literal("0x1").unwrap())
+        );
+        assert_eq!(
+            parse("vector<u8>:0xdead").unwrap(),
+            TransactionArgument::U8Vector(vec![0xde, 0xad])
+        );
+        assert_eq!(
+            parse("vector<address>:0x1,0x2").unwrap(),
+            TransactionArgument::AddressVector(vec![
+                AccountAddress::from_hex_literal("0x1").unwrap(),
+                AccountAddress::from_hex_literal("0x2").unwrap(),
+            ])
+        );
+
+        assert!(parse("u8:256").is_err());
+        assert!(parse("bool:yes").is_err());
+        assert!(parse("vector<u8>:0xzz").is_err());
+        assert!(parse("string:hello").is_err());
+        assert!(parse("100").is_err());
+        assert!(split_typed_argument("0x1").is_none());
+    }
+
+    #[test]
+    fn test_check_arguments() {
+        let parameters = vec![
+            SignatureToken::Address,
+            SignatureToken::U64,
+            SignatureToken::Vector(Box::new(SignatureToken::U8)),
+        ];
+        let arguments = vec![
+            TransactionArgument::Address(AccountAddress::ZERO),
+            TransactionArgument::U64(1),
+            TransactionArgument::U8Vector(vec![]),
+        ];
+        check_arguments(&parameters, &arguments).unwrap();
+        assert!(check_arguments(&parameters, &arguments[..2]).is_err());
+
+        let mut wrong_type = arguments;
+        wrong_type[1] = TransactionArgument::U8(1);
+        assert!(check_arguments(&parameters, &wrong_type).is_err());
+    }
+
+    #[test]
+    fn test_without_signers() {
+        let parameters = vec![
+            SignatureToken::Reference(Box::new(SignatureToken::Signer)),
+            SignatureToken::Signer,
+            SignatureToken::U64,
+        ];
+        assert_eq!(without_signers(&parameters), vec![SignatureToken::U64]);
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Error;
+use diem_client::errors::ServerCode;
+use diem_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use move_core_types::vm_status::StatusCode;
+use std::collections::{BTreeMap, HashMap};
+
+/// Tracks transactions submitted without waiting for them to commit, so that several of them can
+/// be in flight for the same account.
+///
+/// Each account's in-flight transactions are kept by sequence number until the committed sequence
+/// number of the account moves past them. If one of them can no longer commit, because it expired
+/// or because the sequence number before it was never submitted, the ones after it cannot commit
+/// either; `sync` hands those back so they can be renumbered and resubmitted. This assumes the
+/// client is the only one submitting transactions for the account.
+#[derive(Debug, Default)]
+pub struct SequenceNumberManager {
+    pending: HashMap<AccountAddress, BTreeMap<u64, SignedTransaction>>,
+}
+
+impl SequenceNumberManager {
+    /// Record a transaction accepted by the node.
+    pub fn submitted(&mut self, txn: SignedTransaction) {
+        self.pending
+            .entry(txn.sender())
+            .or_default()
+            .insert(txn.sequence_number(), txn);
+    }
+
+    /// The sequence number following the in-flight transactions of `address`, if it has any.
+    pub fn next_sequence_number(&self, address: &AccountAddress) -> Option<u64> {
+        self.pending
+            .get(address)
+            .and_then(|pending| pending.keys().next_back())
+            .map(|sequence_number| sequence_number + 1)
+    }
+
+    /// Number of in-flight transactions of `address`.
+    pub fn pending_count(&self, address: &AccountAddress) -> usize {
+        self.pending.get(address).map_or(0, BTreeMap::len)
+    }
+
+    /// Reconcile `address` with its committed sequence number and the latest ledger timestamp:
+    /// forget the transactions that committed, and remove and return, in order, those stranded
+    /// behind a missing sequence number or an expired transaction.
+    pub fn sync(
+        &mut self,
+        address: &AccountAddress,
+        committed: u64,
+        ledger_timestamp_secs: u64,
+    ) -> Vec<SignedTransaction> {
+        let pending = match self.pending.get_mut(address) {
+            Some(pending) => pending,
+            None => return vec![],
+        };
+        *pending = pending.split_off(&committed);
+        let live = pending
+            .iter()
+            .zip(committed..)
+            .take_while(|((sequence_number, txn), expected)| {
+                **sequence_number == *expected
+                    && txn.expiration_timestamp_secs() > ledger_timestamp_secs
+            })
+            .count();
+        let stranded = match pending.keys().nth(live) {
+            Some(&first_stranded) => pending
+                .split_off(&first_stranded)
+                .into_iter()
+                .map(|(_, txn)| txn)
+                .collect(),
+            None => vec![],
+        };
+        if pending.is_empty() {
+            self.pending.remove(address);
+        }
+        stranded
+    }
+
+    /// Stop tracking the in-flight transactions of `address`.
+    pub fn forget(&mut self, address: &AccountAddress) {
+        self.pending.remove(address);
+    }
+}
+
+/// Whether a submission was rejected because its sequence number is no longer usable.
+pub fn is_sequence_number_error(error: &Error) -> bool {
+    error
+        .downcast_ref::<diem_client::Error>()
+        .and_then(diem_client::Error::json_rpc_error)
+        .map_or(false, |error| {
+            error.code == ServerCode::MempoolInvalidSeqNumber as i16
+                || error.as_status_code() == Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diem_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+    use diem_types::{
+        chain_id::ChainId,
+        transaction::{RawTransaction, Script, TransactionPayload},
+    };
+
+    fn txn(sender: AccountAddress, sequence_number: u64) -> SignedTransaction {
+        expiring_txn(sender, sequence_number, u64::MAX)
+    }
+
+    fn expiring_txn(
+        sender: AccountAddress,
+        sequence_number: u64,
+        expiration_timestamp_secs: u64,
+    ) -> SignedTransaction {
+        let key = Ed25519PrivateKey::generate_for_testing();
+        RawTransaction::new(
+            sender,
+            sequence_number,
+            TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+            1_000_000,
+            0,
+            "XUS".to_string(),
+            expiration_timestamp_secs,
+            ChainId::test(),
+        )
+        .sign(&key, key.public_key())
+        .unwrap()
+        .into_inner()
+    }
+
+    fn sequence_numbers(txns: &[SignedTransaction]) -> Vec<u64> {
+        txns.iter()
+            .map(SignedTransaction::sequence_number)
+            .collect()
+    }
+
+    #[test]
+    fn test_committed_transactions_are_forgotten() {
+        let sender = AccountAddress::random();
+        let mut manager = SequenceNumberManager::default();
+        for sequence_number in 3..6 {
+            manager.submitted(txn(sender, sequence_number));
+        }
+        assert_eq!(manager.next_sequence_number(&sender), Some(6));
+
+        assert!(manager.sync(&sender, 5, 0).is_empty());
+        assert_eq!(manager.pending_count(&sender), 1);
+        assert_eq!(manager.next_sequence_number(&sender), Some(6));
+
+        assert!(manager.sync(&sender, 6, 0).is_empty());
+        assert_eq!(manager.next_sequence_number(&sender), None);
+    }
+
+    #[test]
+    fn test_stranded_transactions_are_returned() {
+        let sender = AccountAddress::random();
+        let mut manager = SequenceNumberManager::default();
+        for sequence_number in &[3, 4, 6, 7] {
+            manager.submitted(txn(sender, *sequence_number));
+        }
+
+        // 5 never made it, so 6 and 7 are stuck behind it.
+        assert_eq!(sequence_numbers(&manager.sync(&sender, 3, 0)), vec![6, 7]);
+        assert_eq!(manager.next_sequence_number(&sender), Some(5));
+
+        // 2 never made it either: everything still pending is stuck.
+        manager.submitted(txn(sender, 5));
+        assert_eq!(
+            sequence_numbers(&manager.sync(&sender, 2, 0)),
+            vec![3, 4, 5]
+        );
+        assert_eq!(manager.pending_count(&sender), 0);
+    }
+
+    #[test]
+    fn test_expired_transactions_strand_the_rest() {
+        let sender = AccountAddress::random();
+        let mut manager = SequenceNumberManager::default();
+        manager.submitted(txn(sender, 0));
+        manager.submitted(expiring_txn(sender, 1, 100));
+        manager.submitted(txn(sender, 2));
+
+        assert!(manager.sync(&sender, 0, 99).is_empty());
+        assert_eq!(sequence_numbers(&manager.sync(&sender, 0, 100)), vec![1, 2]);
+        assert_eq!(manager.next_sequence_number(&sender), Some(1));
+    }
+
+    #[test]
+    fn test_accounts_are_independent() {
+        let alice = AccountAddress::random();
+        let bob = AccountAddress::random();
+        let mut manager = SequenceNumberManager::default();
+        manager.submitted(txn(alice, 0));
+        manager.submitted(txn(bob, 7));
+
+        manager.forget(&alice);
+        assert_eq!(manager.next_sequence_number(&alice), None);
+        assert_eq!(manager.next_sequence_number(&bob), Some(8));
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    client_proxy::ClientProxy,
+    commands::{subcommand_execute, Command},
+    tr,
+};
+use anyhow::{bail, Context, Result};
+use diem_types::{
+    account_address::AccountAddress, transaction::TransactionOutput, write_set::WriteOp,
+};
+
+/// Major command for building and inspecting transactions without committing them.
+pub struct TransactionCommand {}
+
+impl Command for TransactionCommand {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["transaction", "txn"]
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("transaction.description")
+    }
+    fn get_subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![
+            Box::new(TransactionCommandSimulateTransfer {}),
+            Box::new(TransactionCommandSimulateScript {}),
+            Box::new(TransactionCommandBuild {}),
+            Box::new(TransactionCommandSign {}),
+            Box::new(TransactionCommandAddSignature {}),
+            Box::new(TransactionCommandAssemble {}),
+        ]
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        subcommand_execute(&params[0], self.get_subcommands(), client, &params[1..])
+    }
+}
+
+/// Sub command to dry-run a transfer through the VM.
+pub struct TransactionCommandSimulateTransfer {}
+
+impl Command for TransactionCommandSimulateTransfer {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["simulate", "sim"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<sender_account_address>|<sender_account_ref_id> \
+         <receiver_account_address>|<receiver_account_ref_id> <number_of_coins> <currency_code> \
+         [gas_unit_price_in_micro_diems] [max_gas_amount_in_micro_diems]"
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("transaction.simulate.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.len() < 5 || params.len() > 7 {
+            bail!(tr!("transaction.simulate.invalid_args"));
+        }
+        println!("{}", tr!("transaction.simulate.progress"));
+        let output = client
+            .simulate_transfer_coins(params)
+            .context(tr!("transaction.simulate.error"))?;
+        print_simulated_output(&output);
+        Ok(())
+    }
+}
+
+/// Sub command to dry-run a compiled Move script through the VM.
+pub struct TransactionCommandSimulateScript {}
+
+impl Command for TransactionCommandSimulateScript {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["simulate_script", "ss"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<sender_account_address>|<sender_account_ref_id> <compiled_script_path> [parameters]"
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("transaction.simulate_script.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.len() < 3 {
+            bail!(tr!("transaction.simulate.invalid_args"));
+        }
+        println!("{}", tr!("transaction.simulate.progress"));
+        let output = client
+            .simulate_script(params)
+            .context(tr!("transaction.simulate.error"))?;
+        print_simulated_output(&output);
+        Ok(())
+    }
+}
+
+/// Sub command to build a script transaction with secondary signers.
+pub struct TransactionCommandBuild {}
+
+impl Command for TransactionCommandBuild {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["build", "b"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<sender_account_address>|<sender_account_ref_id> <compiled_script_path> <output_file> \
+         --secondary-signer <account_address> [--secondary-signer <account_address>...] \
+         [parameters]"
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("transaction.build.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.len() < 6 {
+            bail!(tr!("transaction.build.invalid_args"));
+        }
+        let txn = client
+            .build_multi_agent_script(params)
+            .context(tr!("transaction.build.error"))?;
+        println!("{}", tr!("transaction.build.success", params[3]));
+        print_missing_signers(&txn.missing_signers());
+        Ok(())
+    }
+}
+
+/// Sub command to sign a multi-agent transaction file with a local account.
+pub struct TransactionCommandSign {}
+
+impl Command for TransactionCommandSign {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["sign"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_address>|<account_ref_id> <transaction_file> <signature_output_file>"
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("transaction.sign.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.len() != 4 {
+            bail!(tr!("transaction.sign.invalid_args"));
+        }
+        let signer = client
+            .sign_multi_agent(params)
+            .context(tr!("transaction.sign.error"))?;
+        println!("{}", tr!("transaction.sign.success", signer, params[3]));
+        Ok(())
+    }
+}
+
+/// Sub command to merge signature files into a multi-agent transaction file.
+pub struct TransactionCommandAddSignature {}
+
+impl Command for TransactionCommandAddSignature {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["add-signature", "add_signature"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<transaction_file> <signature_file> [signature_file...]"
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("transaction.add_signature.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.len() < 3 {
+            bail!(tr!("transaction.add_signature.invalid_args"));
+        }
+        let txn = client
+            .add_multi_agent_signatures(params)
+            .context(tr!("transaction.add_signature.error"))?;
+        println!(
+            "{}",
+            tr!("transaction.add_signature.success", params.len() - 2)
+        );
+        print_missing_signers(&txn.missing_signers());
+        Ok(())
+    }
+}
+
+/// Sub command to assemble a fully signed multi-agent transaction.
+pub struct TransactionCommandAssemble {}
+
+impl Command for TransactionCommandAssemble {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["assemble"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<transaction_file> <signed_output_file> [--submit]"
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("transaction.assemble.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.len() < 3 || params.len() > 4 {
+            bail!(tr!("transaction.assemble.invalid_args"));
+        }
+        client
+            .assemble_multi_agent(params)
+            .context(tr!("transaction.assemble.error"))?;
+        println!("{}", tr!("transaction.assemble.success", params[2]));
+        Ok(())
+    }
+}
+
+fn print_missing_signers(missing: &[AccountAddress]) {
+    if missing.is_empty() {
+        println!("{}", tr!("transaction.multi_agent.complete"));
+    } else {
+        let missing: Vec<_> = missing.iter().map(ToString::to_string).collect();
+        println!(
+            "{}",
+            tr!("transaction.multi_agent.missing", missing.join(", "))
+        );
+    }
+}
+
+fn print_simulated_output(output: &TransactionOutput) {
+    println!(
+        "{}",
+        tr!(
+            "transaction.simulate.status",
+            format!("{:?}", output.status())
+        )
+    );
+    println!(
+        "{}",
+        tr!("transaction.simulate.gas_used", output.gas_used())
+    );
+    println!(
+        "{}",
+        tr!(
+            "transaction.simulate.write_set",
+            output.write_set().iter().count()
+        )
+    );
+    for (access_path, op) in output.write_set() {
+        match op {
+            WriteOp::Value(bytes) => println!(
+                "{}",
+                tr!("transaction.write_op.write", access_path, bytes.len())
+            ),
+            WriteOp::Deletion => println!("{}", tr!("transaction.write_op.delete", access_path)),
+        }
+    }
+    println!(
+        "{}",
+        tr!("transaction.simulate.events", output.events().len())
+    );
+    for event in output.events() {
+        println!(
+            "{}",
+            tr!(
+                "transaction.simulate.event",
+                event.key(),
+                event.sequence_number(),
+                event.type_tag(),
+                hex::encode(event.event_data())
+            )
+        );
+    }
+}
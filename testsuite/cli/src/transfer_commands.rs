@@ -3,8 +3,10 @@
 
 use crate::{
     client_proxy::ClientProxy,
-    commands::{blocking_cmd, report_error, Command},
+    commands::{blocking_cmd, Command},
+    tr,
 };
+use anyhow::{bail, Context, Result};
 
 /// Command to transfer coins between two accounts.
 pub struct TransferCommand {}
@@ -20,35 +22,36 @@ impl Command for TransferCommand {
          Suffix 'b' is for blocking. "
     }
     fn get_description(&self) -> &'static str {
-        "Transfer coins from one account to another."
+        tr!("transfer.description")
     }
-    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
         if params.len() < 5 || params.len() > 7 {
-            println!("Invalid number of arguments for transfer");
             println!(
                 "{} {}",
                 self.get_aliases().join(" | "),
                 self.get_params_help()
             );
-            return;
+            bail!(tr!("transfer.invalid_args"));
         }
 
-        println!(">> Transferring");
+        println!("{}", tr!("transfer.progress"));
         let is_blocking = blocking_cmd(&params[0]);
-        match client.transfer_coins(&params, is_blocking) {
-            Ok(index_and_seq) => {
-                if is_blocking {
-                    println!("Finished transaction!");
-                } else {
-                    println!("Transaction submitted to validator");
-                }
-                println!(
-                    "To query for transaction status, run: query txn_acc_seq {} {} \
-                     <fetch_events=true|false>",
-                    index_and_seq.account_index, index_and_seq.sequence_number
-                );
-            }
-            Err(e) => report_error("Failed to perform transaction", e),
+        let index_and_seq = client
+            .transfer_coins(&params, is_blocking)
+            .context(tr!("transfer.error"))?;
+        if is_blocking {
+            println!("{}", tr!("transfer.done"));
+        } else {
+            println!("{}", tr!("transfer.submitted"));
         }
+        println!(
+            "{}",
+            tr!(
+                "transfer.query_hint",
+                index_and_seq.account_index,
+                index_and_seq.sequence_number
+            )
+        );
+        Ok(())
     }
 }
@@ -0,0 +1,97 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    client_proxy::ClientProxy,
+    commands::{subcommand_execute, Command},
+    tr,
+};
+use anyhow::{bail, Context, Result};
+
+/// Major command for inspecting and resetting the verified chain state of the client.
+pub struct TrustedStateCommand {}
+
+impl Command for TrustedStateCommand {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["trusted-state", "ts"]
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("trusted_state.description")
+    }
+    fn get_subcommands(&self) -> Vec<Box<dyn Command>> {
+        vec![
+            Box::new(TrustedStateCommandShow {}),
+            Box::new(TrustedStateCommandSetWaypoint {}),
+        ]
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        subcommand_execute(&params[0], self.get_subcommands(), client, &params[1..])
+    }
+}
+
+/// Sub command to print the trusted waypoint, version and latest verified epoch change.
+pub struct TrustedStateCommandShow {}
+
+impl Command for TrustedStateCommandShow {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["show", "s"]
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("trusted_state.show.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) -> Result<()> {
+        let trusted_state = client.client.trusted_state();
+        println!(
+            "{}",
+            tr!("trusted_state.show.waypoint", trusted_state.waypoint())
+        );
+        println!(
+            "{}",
+            tr!("trusted_state.show.version", trusted_state.version())
+        );
+        match client.client.latest_epoch_change_li() {
+            Some(li) => println!(
+                "{}",
+                tr!(
+                    "trusted_state.show.epoch_change",
+                    li.ledger_info().epoch(),
+                    li.ledger_info().version()
+                )
+            ),
+            None => println!("{}", tr!("trusted_state.show.no_epoch_change")),
+        }
+        Ok(())
+    }
+}
+
+/// Sub command to start trusting a new waypoint.
+pub struct TrustedStateCommandSetWaypoint {}
+
+impl Command for TrustedStateCommandSetWaypoint {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["set-waypoint", "set_waypoint", "w"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<waypoint> [--force]"
+    }
+    fn get_description(&self) -> &'static str {
+        tr!("trusted_state.set_waypoint.description")
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) -> Result<()> {
+        if params.len() < 2 || params.len() > 3 {
+            bail!(tr!("trusted_state.set_waypoint.invalid_args"));
+        }
+        let waypoint = client
+            .set_waypoint(params)
+            .context(tr!("trusted_state.set_waypoint.error"))?;
+        println!(
+            "{}",
+            tr!(
+                "trusted_state.set_waypoint.success",
+                waypoint,
+                client.client.trusted_state().version()
+            )
+        );
+        Ok(())
+    }
+}
@@ -19,6 +19,9 @@ mod genesis;
 #[cfg(test)]
 mod key_manager;
 
+#[cfg(test)]
+mod light_client;
+
 #[cfg(test)]
 mod operational_tooling;
 
@@ -0,0 +1,50 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::test_utils::{diem_swarm_utils::get_json_rpc_url, setup_swarm_and_client_proxy};
+use diem_client::{BlockingClient, FileStorage, LightClient};
+use diem_temppath::TempPath;
+
+#[test]
+fn test_light_client_verify_payment() {
+    let (env, mut client_proxy) = setup_swarm_and_client_proxy(1, 0);
+    let url = get_json_rpc_url(&env.validator_swarm, 0);
+    let waypoint = env.validator_swarm.config.waypoint;
+
+    let sender = client_proxy.create_next_account(false).unwrap().address;
+    let receiver = client_proxy.create_next_account(false).unwrap().address;
+    client_proxy
+        .mint_coins(&["mb", "0", "100", "XUS"], true)
+        .unwrap();
+    client_proxy
+        .mint_coins(&["mb", "1", "10", "XUS"], true)
+        .unwrap();
+    client_proxy
+        .transfer_coins(&["tb", "0", "1", "10", "XUS"], true)
+        .unwrap();
+
+    // Sync from the genesis waypoint and verify the payment
+    let data_dir = TempPath::new();
+    let mut light_client = LightClient::new_with_waypoint(
+        BlockingClient::new(url.clone()),
+        waypoint,
+        FileStorage::new(data_dir.path()).unwrap(),
+    );
+    light_client.sync().unwrap();
+    assert!(light_client.version() > waypoint.version());
+    let payment = light_client.verify_payment(sender, 0).unwrap().unwrap();
+    assert!(payment.vm_status.is_executed());
+    assert!(light_client.verify_payment(sender, 1).unwrap().is_none());
+    let account = light_client.get_account(sender).unwrap().unwrap();
+    assert_eq!(account.sequence_number, 1);
+    assert!(light_client.get_account(receiver).unwrap().is_some());
+
+    // A light client restarting carries on from the trusted state kept in its data dir
+    let version = light_client.version();
+    let light_client = LightClient::new(
+        BlockingClient::new(url),
+        FileStorage::new(data_dir.path()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(light_client.version(), version);
+}
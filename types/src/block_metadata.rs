@@ -144,4 +144,9 @@ impl NewBlockEvent {
     pub fn votes(&self) -> Vec<AccountAddress> {
         self.votes.clone()
     }
+
+    /// The timestamp of the block, in microseconds.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
 }
@@ -8,7 +8,8 @@ use anyhow::Result;
 use proptest::prelude::*;
 #[cfg(any(test, feature = "fuzzing"))]
 use proptest_derive::Arbitrary;
-use std::{convert::TryFrom, fmt};
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt, time::Duration};
 
 /// A `MempoolStatus` is represented as a required status code that is semantic coupled with an optional sub status and message.
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
@@ -19,6 +20,8 @@ pub struct MempoolStatus {
     pub code: MempoolStatusCode,
     /// optional message
     pub message: String,
+    /// how full Mempool is, set when it rejects a transaction for lack of room
+    pub capacity: Option<MempoolCapacity>,
 }
 
 impl MempoolStatus {
@@ -26,6 +29,7 @@ impl MempoolStatus {
         Self {
             code,
             message: "".to_string(),
+            capacity: None,
         }
     }
 
@@ -34,6 +38,36 @@ impl MempoolStatus {
         self.message = message;
         self
     }
+
+    /// Adds how full Mempool is to the Mempool status.
+    pub fn with_capacity(mut self, capacity: MempoolCapacity) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+}
+
+/// How full Mempool is when it rejects a transaction for lack of room, either globally or for the
+/// sender, so that clients can back off instead of submitting again right away.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub struct MempoolCapacity {
+    /// number of transactions in Mempool, and the most it holds
+    pub size: u64,
+    pub capacity: u64,
+    /// size in bytes of the transactions in Mempool, and the most it holds
+    pub size_bytes: u64,
+    pub capacity_bytes: u64,
+    /// number of transactions of the sender in Mempool, and the most it holds per sender
+    pub sender_size: u64,
+    pub capacity_per_sender: u64,
+    /// suggested wait before submitting again
+    pub retry_after_ms: u64,
+}
+
+impl MempoolCapacity {
+    pub fn retry_after(&self) -> Duration {
+        Duration::from_millis(self.retry_after_ms)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
@@ -568,13 +568,27 @@ impl Protocol {
 
 impl From<IpAddr> for Protocol {
     fn from(addr: IpAddr) -> Protocol {
-        match addr {
+        match canonical_ip(addr) {
             IpAddr::V4(addr) => Protocol::Ip4(addr),
             IpAddr::V6(addr) => Protocol::Ip6(addr),
         }
     }
 }
 
+/// Unmaps IPv4-mapped IPv6 addresses, such as `::ffff:1.2.3.4`, which is how sockets listening on
+/// both families see peers dialing over IPv4.
+pub fn canonical_ip(addr: IpAddr) -> IpAddr {
+    match addr {
+        IpAddr::V6(v6) => match v6.segments() {
+            [0, 0, 0, 0, 0, 0xffff, high, low] => {
+                IpAddr::V4(Ipv4Addr::from((u32::from(high) << 16) | u32::from(low)))
+            }
+            _ => addr,
+        },
+        IpAddr::V4(_) => addr,
+    }
+}
+
 /////////////
 // DnsName //
 /////////////
@@ -715,6 +729,50 @@ impl IpFilter {
     }
 }
 
+/// Which IP family to dial first, when a peer has addresses of both or its name resolves to both.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpPreference {
+    /// Dial addresses in the order they come in.
+    Any,
+    PreferIp4,
+    PreferIp6,
+}
+
+impl Default for IpPreference {
+    fn default() -> Self {
+        IpPreference::Any
+    }
+}
+
+impl IpPreference {
+    /// Rank of `ipaddr`, lower ranks being dialed first.
+    pub fn rank_ip(self, ipaddr: IpAddr) -> u8 {
+        self.rank(Some(ipaddr.is_ipv6()))
+    }
+
+    /// Rank of `addr`, lower ranks being dialed first. Names which may resolve to either family
+    /// rank in between.
+    pub fn rank_addr(self, addr: &NetworkAddress) -> u8 {
+        use Protocol::*;
+
+        let is_ipv6 = match addr.as_slice().first() {
+            Some(Ip4(_)) | Some(Dns4(_)) => Some(false),
+            Some(Ip6(_)) | Some(Dns6(_)) => Some(true),
+            _ => None,
+        };
+        self.rank(is_ipv6)
+    }
+
+    fn rank(self, is_ipv6: Option<bool>) -> u8 {
+        match (self, is_ipv6) {
+            (IpPreference::Any, _) | (_, None) => 1,
+            (IpPreference::PreferIp4, Some(false)) | (IpPreference::PreferIp6, Some(true)) => 0,
+            _ => 2,
+        }
+    }
+}
+
 /// parse the `&[Protocol]` into the `"/dns/<domain>/tcp/<port>"`,
 /// `"/dns4/<domain>/tcp/<port>"`, or `"/dns6/<domain>/tcp/<port>"` prefix and
 /// unparsed `&[Protocol]` suffix.
@@ -995,6 +1053,47 @@ mod test {
         assert_eq!(None, parse_dns_tcp(addr.as_slice()));
     }
 
+    #[test]
+    fn test_canonical_ip() {
+        let mapped = SocketAddr::from_str("[::ffff:1.2.3.4]:6180").unwrap();
+        assert_eq!(
+            NetworkAddress::from(mapped).to_string(),
+            "/ip4/1.2.3.4/tcp/6180"
+        );
+        let v6 = SocketAddr::from_str("[2001:db8::1]:6180").unwrap();
+        assert_eq!(
+            NetworkAddress::from(v6).to_string(),
+            "/ip6/2001:db8::1/tcp/6180"
+        );
+    }
+
+    #[test]
+    fn test_ip_preference() {
+        let ip4 = NetworkAddress::from_str("/ip4/1.2.3.4/tcp/6180").unwrap();
+        let ip6 = NetworkAddress::from_str("/ip6/2001:db8::1/tcp/6180").unwrap();
+        let dns = NetworkAddress::from_str("/dns/example.com/tcp/6180").unwrap();
+        let dns6 = NetworkAddress::from_str("/dns6/example.com/tcp/6180").unwrap();
+
+        let mut addrs = vec![ip4.clone(), dns.clone(), dns6.clone(), ip6.clone()];
+        addrs.sort_by_key(|addr| IpPreference::PreferIp6.rank_addr(addr));
+        assert_eq!(
+            addrs,
+            vec![dns6.clone(), ip6.clone(), dns.clone(), ip4.clone()]
+        );
+
+        addrs.sort_by_key(|addr| IpPreference::PreferIp4.rank_addr(addr));
+        assert_eq!(
+            addrs,
+            vec![ip4.clone(), dns.clone(), dns6.clone(), ip6.clone()]
+        );
+
+        // Without preference, the order is kept.
+        let unsorted = vec![ip6, dns, ip4, dns6];
+        let mut addrs = unsorted.clone();
+        addrs.sort_by_key(|addr| IpPreference::Any.rank_addr(addr));
+        assert_eq!(addrs, unsorted);
+    }
+
     #[test]
     fn test_parse_noise_ik() {
         let pubkey_str = "080e287879c918794170e258bfaddd75acac5b3e350419044655e4983a487120";
@@ -0,0 +1,76 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::on_chain_config::OnChainConfig;
+use anyhow::{format_err, Result};
+use serde::{Deserialize, Serialize};
+
+/// The consensus parameters every validator of an epoch must agree on. Diem root publishes them in
+/// the `DiemConsensusConfig` resource, which holds them BCS serialized and is empty until first set.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum OnChainConsensusConfig {
+    V1(ConsensusConfigV1),
+}
+
+impl OnChainConsensusConfig {
+    /// The leader reputation parameters, which take precedence over the local ones when set.
+    pub fn leader_reputation(&self) -> Option<&LeaderReputationParams> {
+        match self {
+            OnChainConsensusConfig::V1(config) => config.leader_reputation.as_ref(),
+        }
+    }
+}
+
+impl Default for OnChainConsensusConfig {
+    fn default() -> Self {
+        OnChainConsensusConfig::V1(ConsensusConfigV1::default())
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ConsensusConfigV1 {
+    pub leader_reputation: Option<LeaderReputationParams>,
+}
+
+/// Mirrors `LeaderReputationConfig` of the node config.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LeaderReputationParams {
+    pub active_weights: u64,
+    pub inactive_weights: u64,
+    pub window_size: u64,
+    pub round_gap: u64,
+    pub heuristic: ReputationHeuristicParams,
+}
+
+/// Mirrors `ReputationHeuristicConfig` of the node config.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ReputationHeuristicParams {
+    ActiveInactive,
+    ProposerAndVoter {
+        failed_weights: u64,
+        failure_threshold_percent: u64,
+        slow_proposal_threshold_ms: u64,
+    },
+}
+
+impl OnChainConfig for OnChainConsensusConfig {
+    const IDENTIFIER: &'static str = "DiemConsensusConfig";
+
+    fn deserialize_into_config(bytes: &[u8]) -> Result<Self> {
+        let raw_bytes: Vec<u8> = bcs::from_bytes(&bytes).map_err(|e| {
+            format_err!(
+                "Failed first round of deserialization for OnChainConsensusConfig: {}",
+                e
+            )
+        })?;
+        if raw_bytes.is_empty() {
+            return Ok(Self::default());
+        }
+        bcs::from_bytes(&raw_bytes).map_err(|e| {
+            format_err!(
+                "Failed second round of deserialization for OnChainConsensusConfig: {}",
+                e
+            )
+        })
+    }
+}
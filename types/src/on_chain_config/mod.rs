@@ -5,6 +5,7 @@ use crate::{
     access_path::AccessPath,
     account_address::AccountAddress,
     account_config::CORE_CODE_ADDRESS,
+    epoch_state::EpochState,
     event::{EventHandle, EventKey},
 };
 use anyhow::{format_err, Result};
@@ -17,6 +18,7 @@ use move_core_types::{
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{collections::HashMap, fmt, sync::Arc};
 
+mod consensus_config;
 mod diem_version;
 mod registered_currencies;
 mod validator_set;
@@ -24,6 +26,10 @@ mod vm_config;
 mod vm_publishing_option;
 
 pub use self::{
+    consensus_config::{
+        ConsensusConfigV1, LeaderReputationParams, OnChainConsensusConfig,
+        ReputationHeuristicParams,
+    },
     diem_version::{DiemVersion, DIEM_MAX_KNOWN_VERSION, DIEM_VERSION_2, DIEM_VERSION_3},
     registered_currencies::RegisteredCurrencies,
     validator_set::ValidatorSet,
@@ -72,6 +78,10 @@ pub const ON_CHAIN_CONFIG_REGISTRY: &[ConfigID] = &[
     RegisteredCurrencies::CONFIG_ID,
 ];
 
+/// Configs that are only published once a network sets them, and that are left out of the
+/// payload until then
+pub const OPTIONAL_ON_CHAIN_CONFIGS: &[ConfigID] = &[OnChainConsensusConfig::CONFIG_ID];
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct OnChainConfigPayload {
     epoch: u64,
@@ -98,6 +108,15 @@ impl OnChainConfigPayload {
     pub fn configs(&self) -> &HashMap<ConfigID, Vec<u8>> {
         &self.configs
     }
+
+    /// The state of the epoch the configs are for, with the validator set in them.
+    pub fn epoch_state(&self) -> Result<EpochState> {
+        let validator_set: ValidatorSet = self.get()?;
+        Ok(EpochState {
+            epoch: self.epoch,
+            verifier: (&validator_set).into(),
+        })
+    }
 }
 
 impl fmt::Display for OnChainConfigPayload {
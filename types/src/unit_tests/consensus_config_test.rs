@@ -0,0 +1,42 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::on_chain_config::{
+    ConsensusConfigV1, LeaderReputationParams, OnChainConfig, OnChainConsensusConfig,
+    ReputationHeuristicParams,
+};
+
+#[test]
+fn test_unset_consensus_config_is_default() {
+    let bytes = bcs::to_bytes(&Vec::<u8>::new()).unwrap();
+    assert_eq!(
+        OnChainConsensusConfig::deserialize_into_config(&bytes).unwrap(),
+        OnChainConsensusConfig::default()
+    );
+    assert!(OnChainConsensusConfig::default()
+        .leader_reputation()
+        .is_none());
+}
+
+#[test]
+fn test_consensus_config_deserialization() {
+    let params = LeaderReputationParams {
+        active_weights: 99,
+        inactive_weights: 1,
+        window_size: 100,
+        round_gap: 4,
+        heuristic: ReputationHeuristicParams::ProposerAndVoter {
+            failed_weights: 10,
+            failure_threshold_percent: 30,
+            slow_proposal_threshold_ms: 2000,
+        },
+    };
+    let config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
+        leader_reputation: Some(params),
+    });
+    // As `DiemConsensusConfig` holds it
+    let bytes = bcs::to_bytes(&bcs::to_bytes(&config).unwrap()).unwrap();
+    let deserialized = OnChainConsensusConfig::deserialize_into_config(&bytes).unwrap();
+    assert_eq!(deserialized, config);
+    assert_eq!(deserialized.leader_reputation(), Some(&params));
+}
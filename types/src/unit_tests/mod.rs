@@ -5,6 +5,7 @@ mod access_path_test;
 mod block_metadata_test;
 mod canonical_serialization_examples;
 mod code_debug_fmt_test;
+mod consensus_config_test;
 mod contract_event_test;
 mod currency_code_test;
 mod transaction_test;